@@ -0,0 +1,216 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Middle-click autoscrolling, where the page scrolls continuously towards
+//! the cursor, faster the further it is from where the middle button was
+//! pressed.
+
+use self::AutoscrollState::*;
+use crate::windowing::MouseWindowEvent;
+use euclid::{Point2D, Vector2D};
+use script_traits::MouseButton;
+use std::mem;
+use style_traits::DevicePixel;
+
+/// How far the cursor has to move away from the origin, in device pixels,
+/// before the page starts scrolling.
+const AUTOSCROLL_DEAD_ZONE: f32 = 10.0;
+
+/// How many device pixels per second the page scrolls by for every device
+/// pixel the cursor is outside of the dead zone.
+const AUTOSCROLL_SPEED: f32 = 8.0;
+
+/// The states of the autoscroll state machine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AutoscrollState {
+    /// Not autoscrolling.
+    Nothing,
+    /// The middle button is held down and the cursor hasn't left the dead
+    /// zone yet. Releasing the button now keeps autoscrolling until the next
+    /// click.
+    Pressed,
+    /// Autoscrolling until the next click.
+    Sticky,
+    /// The cursor left the dead zone while the middle button was held down.
+    /// Releasing the button ends autoscrolling.
+    Dragging,
+}
+
+pub struct AutoscrollHandler {
+    pub state: AutoscrollState,
+    /// Where the middle button was pressed.
+    origin: Point2D<f32, DevicePixel>,
+    cursor: Point2D<f32, DevicePixel>,
+    /// When the page last scrolled, in seconds.
+    last_tick: f64,
+    /// Whether the release of the button that ended autoscrolling still has
+    /// to be kept from content.
+    ignore_release: bool,
+}
+
+impl AutoscrollHandler {
+    pub fn new() -> Self {
+        AutoscrollHandler {
+            state: Nothing,
+            origin: Point2D::zero(),
+            cursor: Point2D::zero(),
+            last_tick: 0.,
+            ignore_release: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state != Nothing
+    }
+
+    /// Where the middle button was pressed, which is where the scroll
+    /// container that autoscrolls is found.
+    pub fn origin(&self) -> Point2D<f32, DevicePixel> {
+        self.origin
+    }
+
+    /// Handles a mouse button event at `now`, in seconds, and returns whether
+    /// it was consumed. The middle button is always consumed, as are the
+    /// presses and releases of other buttons that end autoscrolling.
+    pub fn on_mouse_window_event(&mut self, event: MouseWindowEvent, now: f64) -> bool {
+        match event {
+            MouseWindowEvent::MouseDown(MouseButton::Middle, point) => {
+                self.ignore_release = false;
+                if self.state == Nothing {
+                    self.state = Pressed;
+                    self.origin = point;
+                    self.cursor = point;
+                    self.last_tick = now;
+                } else {
+                    self.state = Nothing;
+                }
+                true
+            },
+            MouseWindowEvent::MouseUp(MouseButton::Middle, _) => {
+                self.state = match self.state {
+                    Pressed => Sticky,
+                    Dragging => Nothing,
+                    state => state,
+                };
+                true
+            },
+            MouseWindowEvent::Click(MouseButton::Middle, _) => true,
+            MouseWindowEvent::MouseDown(..) => {
+                self.ignore_release = self.is_active();
+                self.state = Nothing;
+                self.ignore_release
+            },
+            MouseWindowEvent::MouseUp(..) => self.ignore_release,
+            MouseWindowEvent::Click(..) => mem::replace(&mut self.ignore_release, false),
+        }
+    }
+
+    pub fn on_mouse_move(&mut self, point: Point2D<f32, DevicePixel>) {
+        self.cursor = point;
+        if self.state == Pressed && (point - self.origin).length() > AUTOSCROLL_DEAD_ZONE {
+            self.state = Dragging;
+        }
+    }
+
+    /// How far the page scrolls by at `now`, in seconds, since it last did,
+    /// in device pixels.
+    pub fn on_tick(&mut self, now: f64) -> Vector2D<f32, DevicePixel> {
+        if !self.is_active() {
+            return Vector2D::zero();
+        }
+        let elapsed = (now - mem::replace(&mut self.last_tick, now)) as f32;
+        let offset = self.cursor - self.origin;
+        let distance = offset.length();
+        if distance <= AUTOSCROLL_DEAD_ZONE {
+            return Vector2D::zero();
+        }
+        offset * ((distance - AUTOSCROLL_DEAD_ZONE) / distance * AUTOSCROLL_SPEED * elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AutoscrollHandler, AutoscrollState};
+    use crate::windowing::MouseWindowEvent;
+    use euclid::{Point2D, Vector2D};
+    use script_traits::MouseButton;
+
+    fn down(button: MouseButton, x: f32, y: f32) -> MouseWindowEvent {
+        MouseWindowEvent::MouseDown(button, Point2D::new(x, y))
+    }
+
+    fn up(button: MouseButton, x: f32, y: f32) -> MouseWindowEvent {
+        MouseWindowEvent::MouseUp(button, Point2D::new(x, y))
+    }
+
+    fn click(button: MouseButton, x: f32, y: f32) -> MouseWindowEvent {
+        MouseWindowEvent::Click(button, Point2D::new(x, y))
+    }
+
+    #[test]
+    fn quick_middle_click_keeps_autoscrolling() {
+        let mut handler = AutoscrollHandler::new();
+        assert!(handler.on_mouse_window_event(down(MouseButton::Middle, 100., 100.), 0.));
+        assert_eq!(handler.state, AutoscrollState::Pressed);
+        assert_eq!(handler.origin(), Point2D::new(100., 100.));
+
+        // Moving within the dead zone doesn't start dragging.
+        handler.on_mouse_move(Point2D::new(105., 105.));
+        assert_eq!(handler.state, AutoscrollState::Pressed);
+
+        assert!(handler.on_mouse_window_event(up(MouseButton::Middle, 105., 105.), 0.));
+        assert!(handler.on_mouse_window_event(click(MouseButton::Middle, 105., 105.), 0.));
+        assert_eq!(handler.state, AutoscrollState::Sticky);
+
+        // The next middle click ends it.
+        assert!(handler.on_mouse_window_event(down(MouseButton::Middle, 105., 105.), 0.));
+        assert!(!handler.is_active());
+    }
+
+    #[test]
+    fn releasing_after_dragging_ends_autoscrolling() {
+        let mut handler = AutoscrollHandler::new();
+        handler.on_mouse_window_event(down(MouseButton::Middle, 100., 100.), 0.);
+        handler.on_mouse_move(Point2D::new(100., 120.));
+        assert_eq!(handler.state, AutoscrollState::Dragging);
+        assert!(handler.on_mouse_window_event(up(MouseButton::Middle, 100., 120.), 0.));
+        assert!(!handler.is_active());
+    }
+
+    #[test]
+    fn other_buttons_end_autoscrolling_without_reaching_content() {
+        let mut handler = AutoscrollHandler::new();
+        handler.on_mouse_window_event(down(MouseButton::Middle, 100., 100.), 0.);
+        handler.on_mouse_window_event(up(MouseButton::Middle, 100., 100.), 0.);
+        assert!(handler.is_active());
+
+        assert!(handler.on_mouse_window_event(down(MouseButton::Left, 10., 10.), 0.));
+        assert!(!handler.is_active());
+        assert!(handler.on_mouse_window_event(up(MouseButton::Left, 10., 10.), 0.));
+        assert!(handler.on_mouse_window_event(click(MouseButton::Left, 10., 10.), 0.));
+
+        // Later clicks reach content again.
+        assert!(!handler.on_mouse_window_event(down(MouseButton::Left, 10., 10.), 0.));
+        assert!(!handler.on_mouse_window_event(up(MouseButton::Left, 10., 10.), 0.));
+        assert!(!handler.on_mouse_window_event(click(MouseButton::Left, 10., 10.), 0.));
+    }
+
+    #[test]
+    fn scrolls_faster_further_from_the_origin() {
+        let mut handler = AutoscrollHandler::new();
+        assert_eq!(handler.on_tick(1.), Vector2D::zero());
+
+        handler.on_mouse_window_event(down(MouseButton::Middle, 100., 100.), 1.);
+        handler.on_mouse_move(Point2D::new(100., 105.));
+        assert_eq!(handler.on_tick(2.), Vector2D::zero());
+
+        // 20 pixels past the dead zone scroll by 20 * 8 pixels per second.
+        handler.on_mouse_move(Point2D::new(100., 130.));
+        let delta = handler.on_tick(2.5);
+        assert!((delta - Vector2D::new(0., 80.)).length() < 0.001);
+        handler.on_mouse_move(Point2D::new(70., 100.));
+        let delta = handler.on_tick(3.);
+        assert!((delta - Vector2D::new(-80., 0.)).length() < 0.001);
+    }
+}
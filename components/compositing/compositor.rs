@@ -2,23 +2,28 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::autoscroll::AutoscrollHandler;
 use crate::compositor_thread::CompositorReceiver;
-use crate::compositor_thread::{InitialCompositorState, Msg};
+use crate::compositor_thread::{InitialCompositorState, Msg, ScreenshotClip};
 #[cfg(feature = "gl")]
 use crate::gl;
+use crate::keyboard_scroll::{KeyboardScroll, KEYBOARD_SCROLL_LINE_DISTANCE};
+use crate::scrollbars::ScrollbarHandler;
+use crate::smooth_scroll::SmoothScrollHandler;
 use crate::touch::{TouchAction, TouchHandler};
 use crate::windowing::{
     self, EmbedderCoordinates, MouseWindowEvent, WebRenderDebugOption, WindowMethods,
 };
 use crate::{CompositionPipeline, ConstellationMsg, SendableFrameTree};
 use crossbeam_channel::Sender;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, FrameCaptureOptions, Screenshot, ScreenshotError, ScreenshotFormat};
 use euclid::{Point2D, Rect, Scale, Vector2D};
 use gfx_traits::Epoch;
-#[cfg(feature = "gl")]
-use image::{DynamicImage, ImageFormat};
+use image::{DynamicImage, ImageFormat, RgbImage};
 use ipc_channel::ipc;
+use keyboard_types::KeyboardEvent;
 use libc::c_void;
+use msg::constellation_msg::TopLevelBrowsingContextId;
 use msg::constellation_msg::{PipelineId, PipelineIndex, PipelineNamespaceId};
 use net_traits::image::base::Image;
 use net_traits::image_cache::CorsStatus;
@@ -26,14 +31,18 @@ use num_traits::FromPrimitive;
 #[cfg(feature = "gl")]
 use pixels::PixelFormat;
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, TouchEvent, WheelEvent};
-use script_traits::{AnimationState, AnimationTickType, LayoutControlMsg};
+use script_traits::CompositorEvent::{
+    DoubleTapEvent, DragEvent, MouseButtonEvent, MouseMoveEvent, PointerLockedMoveEvent,
+    TouchEvent, WheelEvent,
+};
+use script_traits::{AnimationState, AnimationTickType, DragData, DragEventType, LayoutControlMsg};
 use script_traits::{
     MouseButton, MouseEventType, ScrollState, TouchEventType, TouchId, WheelDelta,
 };
 use script_traits::{UntrustedNodeAddress, WindowSizeData, WindowSizeType};
+use servo_config::pref;
 use servo_geometry::{DeviceIndependentPixel, FramebufferUintLength};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::fs::{create_dir_all, File};
 use std::io::Write;
@@ -41,11 +50,15 @@ use std::num::NonZeroU32;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use style_traits::viewport::ViewportConstraints;
+use style_traits::viewport::{UserZoom, ViewportConstraints};
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
 use time::{now, precise_time_ns, precise_time_s};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize, DevicePoint, LayoutVector2D};
-use webrender_api::{self, HitTestFlags, HitTestResult, ScrollLocation};
+use webrender_api::units::{
+    DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePoint, DeviceVector2D, LayoutPoint,
+    LayoutVector2D,
+};
+use webrender_api::ScrollLocation;
+use webrender_api::{self, ExternalScrollId, HitTestFlags, HitTestResult, ScrollClamping};
 use webvr_traits::WebVRMainThreadHeartbeat;
 
 #[derive(Debug, PartialEq)]
@@ -64,6 +77,19 @@ enum NotReadyToPaint {
 const MAX_ZOOM: f32 = 8.0;
 const MIN_ZOOM: f32 = 0.1;
 
+/// The space left on either side of a block that a double-tap zooms to, in
+/// `DeviceIndependentPixel`s.
+const DOUBLE_TAP_ZOOM_MARGIN: f32 = 8.0;
+
+/// The largest width or height of a screenshot of a whole page, in device pixels, beyond which
+/// it is cut.
+const MAX_FULL_PAGE_SIZE: i32 = 16384;
+
+/// How often the dedicated workers that wait for animation frames get one
+/// when nothing is composited, in nanoseconds. When something is, they get
+/// one after each composite.
+const WORKER_ANIMATION_FRAME_INTERVAL: u64 = 16_666_667;
+
 trait ConvertPipelineIdFromWebRender {
     fn from_webrender(&self) -> PipelineId;
 }
@@ -123,9 +149,20 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     viewport_zoom: PinchZoomFactor,
 
     /// Viewport zoom constraints provided by @viewport.
+    initial_viewport_zoom: PinchZoomFactor,
     min_viewport_zoom: Option<PinchZoomFactor>,
     max_viewport_zoom: Option<PinchZoomFactor>,
 
+    /// Whether @viewport lets the user zoom.
+    user_scalable: bool,
+
+    /// How far the visual viewport, which is what the window shows of the pinch-zoomed layout
+    /// viewport, is offset from the origin of the layout viewport.
+    visual_viewport_offset: Vector2D<f32, DevicePixel>,
+
+    /// Where the last double-tap was, while script finds the block it zooms to.
+    double_tap_point: Option<DevicePoint>,
+
     /// "Desktop-style" zoom that resizes the viewport to fit the window.
     page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
 
@@ -142,6 +179,9 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Tracks the last composite time.
     last_composite_time: u64,
 
+    /// When the dedicated workers that wait for animation frames last got one.
+    last_worker_animation_tick_time: u64,
+
     /// Tracks whether the zoom action has happened recently.
     zoom_action: bool,
 
@@ -201,6 +241,18 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Current cursor position.
     cursor_pos: DevicePoint,
 
+    /// The pipeline under the drag from outside of the window, if any.
+    drag_pipeline: Option<PipelineId>,
+
+    /// Tracks middle-click autoscrolling.
+    autoscroll_handler: AutoscrollHandler,
+
+    /// Animates the smooth scrolls requested by content.
+    smooth_scroll_handler: SmoothScrollHandler,
+
+    /// Moves and fades the scrollbar thumbs that layout painted.
+    scrollbar_handler: ScrollbarHandler,
+
     output_file: Option<String>,
 
     is_running_problem_test: bool,
@@ -215,6 +267,51 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// taken before the render is complete will not reflect the
     /// most up to date rendering.
     waiting_on_pending_frame: Arc<AtomicBool>,
+
+    /// The screenshots that the embedder requested, which are taken in order once the output
+    /// is stable.
+    pending_screenshots: VecDeque<PendingScreenshot>,
+
+    /// The viewport enlarged to the size of the page, while a screenshot of it is taken.
+    full_page_viewport: Option<FullPageViewport>,
+
+    /// The browsers whose frames are sent to the embedder as they are composited.
+    frame_captures: Vec<FrameCapture>,
+}
+
+struct FrameCapture {
+    top_level_browsing_context_id: TopLevelBrowsingContextId,
+    id: u32,
+    options: FrameCaptureOptions,
+    /// When the last frame was sent, in nanoseconds.
+    last_frame_time: Option<u64>,
+}
+
+impl FrameCapture {
+    fn is_due(&self, now: u64) -> bool {
+        match (self.last_frame_time, self.options.max_frame_rate) {
+            (Some(last_frame_time), rate) if rate > 0 => {
+                now - last_frame_time >= 1_000_000_000 / rate as u64
+            },
+            _ => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PendingScreenshot {
+    top_level_browsing_context_id: TopLevelBrowsingContextId,
+    id: u32,
+    clip: ScreenshotClip,
+    format: ScreenshotFormat,
+}
+
+#[derive(Clone, Copy)]
+struct FullPageViewport {
+    size: DeviceIntSize,
+    /// The epoch that the root pipeline was painted at before the viewport was enlarged, since
+    /// the page has to be laid out in it again before the screenshot is taken.
+    epoch: Option<Epoch>,
 }
 
 #[derive(Clone, Copy)]
@@ -252,6 +349,9 @@ struct PipelineDetails {
     /// Whether there are animation callbacks
     animation_callbacks_running: bool,
 
+    /// Whether dedicated workers of the pipeline have animation callbacks
+    worker_animation_callbacks_running: bool,
+
     /// Whether this pipeline is visible
     visible: bool,
 }
@@ -262,6 +362,7 @@ impl PipelineDetails {
             pipeline: None,
             animations_running: false,
             animation_callbacks_running: false,
+            worker_animation_callbacks_running: false,
             visible: true,
         }
     }
@@ -275,6 +376,10 @@ enum CompositeTarget {
     /// Compose as normal, but also return a PNG of the composed output
     WindowAndPng,
 
+    /// Compose as normal, but also return the composed output without waiting for it to be
+    /// stable, for the frame captures
+    WindowAndFrame,
+
     /// Compose to a PNG, write it to disk, and then exit the browser (used for reftests)
     PngFile,
 }
@@ -308,14 +413,19 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             shutdown_state: ShutdownState::NotShuttingDown,
             page_zoom: Scale::new(1.0),
             viewport_zoom: PinchZoomFactor::new(1.0),
+            initial_viewport_zoom: PinchZoomFactor::new(1.0),
             min_viewport_zoom: None,
             max_viewport_zoom: None,
+            user_scalable: true,
+            visual_viewport_offset: Vector2D::zero(),
+            double_tap_point: None,
             zoom_action: false,
             zoom_time: 0f64,
             frame_tree_id: FrameTreeId(0),
             constellation_chan: state.constellation_chan,
             time_profiler_chan: state.time_profiler_chan,
             last_composite_time: 0,
+            last_worker_animation_tick_time: 0,
             ready_to_save_state: ReadyState::Unknown,
             webrender: state.webrender,
             webrender_document: state.webrender_document,
@@ -325,11 +435,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             pending_paint_metrics: HashMap::new(),
             cursor: Cursor::None,
             cursor_pos: DevicePoint::new(0.0, 0.0),
+            drag_pipeline: None,
+            autoscroll_handler: AutoscrollHandler::new(),
+            smooth_scroll_handler: SmoothScrollHandler::new(),
+            scrollbar_handler: ScrollbarHandler::new(),
             output_file,
             is_running_problem_test,
             exit_after_load,
             convert_mouse_to_touch,
             waiting_on_pending_frame: state.pending_wr_frame,
+            pending_screenshots: VecDeque::new(),
+            full_page_viewport: None,
+            frame_captures: vec![],
         }
     }
 
@@ -364,13 +481,17 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     pub fn update_cursor(&mut self, hit_test_results: HitTestResult) {
         if let Some(item) = hit_test_results.items.first() {
             if let Some(cursor) = Cursor::from_u8(item.tag.1 as _) {
-                if cursor != self.cursor {
-                    self.cursor = cursor;
-                    let msg = ConstellationMsg::SetCursor(cursor);
-                    if let Err(e) = self.constellation_chan.send(msg) {
-                        warn!("Sending event to constellation failed ({:?}).", e);
-                    }
-                }
+                self.set_cursor(cursor);
+            }
+        }
+    }
+
+    fn set_cursor(&mut self, cursor: Cursor) {
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            let msg = ConstellationMsg::SetCursor(cursor);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending event to constellation failed ({:?}).", e);
             }
         }
     }
@@ -441,6 +562,18 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.touch_handler.on_event_processed(result);
             },
 
+            (Msg::DoubleTapZoomRect(pipeline_id, rect), ShutdownState::NotShuttingDown) => {
+                self.zoom_to_rect(pipeline_id, rect);
+            },
+
+            (Msg::ScrollSmoothly(scroll_id, position), ShutdownState::NotShuttingDown) => {
+                self.scroll_smoothly(scroll_id, position);
+            },
+
+            (Msg::AbortSmoothScroll(scroll_id), ShutdownState::NotShuttingDown) => {
+                self.smooth_scroll_handler.abort(scroll_id);
+            },
+
             (Msg::CreatePng(rect, reply), ShutdownState::NotShuttingDown) => {
                 let res = self.composite_specific_target(CompositeTarget::WindowAndPng, rect);
                 if let Err(ref e) = res {
@@ -452,6 +585,19 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (
+                Msg::TakeScreenshot(top_level_browsing_context_id, id, clip, format),
+                ShutdownState::NotShuttingDown,
+            ) => {
+                self.pending_screenshots.push_back(PendingScreenshot {
+                    top_level_browsing_context_id,
+                    id,
+                    clip,
+                    format,
+                });
+                self.composite_if_necessary(CompositingReason::Screenshot);
+            },
+
             (
                 Msg::ViewportConstrained(pipeline_id, constraints),
                 ShutdownState::NotShuttingDown,
@@ -459,6 +605,15 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.constrain_viewport(pipeline_id, constraints);
             },
 
+            (Msg::ScrollbarThumbs(pipeline_id, thumbs), ShutdownState::NotShuttingDown) => {
+                self.scrollbar_handler.set_thumbs(pipeline_id, thumbs);
+                self.update_scrollbars();
+            },
+
+            (Msg::ScrolledFromScript(scroll_state), ShutdownState::NotShuttingDown) => {
+                self.scrollbars_scrolled(vec![scroll_state]);
+            },
+
             (Msg::IsReadyToSaveImageReply(is_ready), ShutdownState::NotShuttingDown) => {
                 assert_eq!(
                     self.ready_to_save_state,
@@ -540,20 +695,22 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.pending_paint_metrics.insert(pipeline_id, epoch);
             },
 
+            // The position of the window and the screen it is on can change
+            // without a resize, so these are queried from the embedder.
             (Msg::GetClientWindow(req), ShutdownState::NotShuttingDown) => {
-                if let Err(e) = req.send(self.embedder_coordinates.window) {
+                if let Err(e) = req.send(self.window.get_coordinates().window) {
                     warn!("Sending response to get client window failed ({:?}).", e);
                 }
             },
 
             (Msg::GetScreenSize(req), ShutdownState::NotShuttingDown) => {
-                if let Err(e) = req.send(self.embedder_coordinates.screen) {
+                if let Err(e) = req.send(self.window.get_coordinates().screen) {
                     warn!("Sending response to get screen size failed ({:?}).", e);
                 }
             },
 
             (Msg::GetScreenAvailSize(req), ShutdownState::NotShuttingDown) => {
-                if let Err(e) = req.send(self.embedder_coordinates.screen_avail) {
+                if let Err(e) = req.send(self.window.get_coordinates().screen_avail) {
                     warn!(
                         "Sending response to get screen avail size failed ({:?}).",
                         e
@@ -561,6 +718,15 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (Msg::GetScreenOrientationAngle(req), ShutdownState::NotShuttingDown) => {
+                if let Err(e) = req.send(self.embedder_coordinates.screen_orientation_angle) {
+                    warn!(
+                        "Sending response to get screen orientation angle failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
             // When we are shutting_down, we need to avoid performing operations
             // such as Paint that may crash because we have begun tearing down
             // the rest of our resources.
@@ -600,6 +766,22 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 self.pipeline_details(pipeline_id)
                     .animation_callbacks_running = false;
             },
+            AnimationState::WorkerAnimationCallbacksPresent => {
+                let visible = self.pipeline_details(pipeline_id).visible;
+                self.pipeline_details(pipeline_id)
+                    .worker_animation_callbacks_running = true;
+                // The window keeps calling into the compositor while the
+                // workers wait, even if nothing is composited.
+                self.update_animation_state();
+                if visible {
+                    self.tick_animations_for_pipeline(pipeline_id);
+                }
+            },
+            AnimationState::NoWorkerAnimationCallbacksPresent => {
+                self.pipeline_details(pipeline_id)
+                    .worker_animation_callbacks_running = false;
+                self.update_animation_state();
+            },
         }
     }
 
@@ -656,18 +838,32 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
     fn remove_pipeline_root_layer(&mut self, pipeline_id: PipelineId) {
         self.pipeline_details.remove(&pipeline_id);
+        self.scrollbar_handler.remove_pipeline(pipeline_id);
+    }
+
+    /// The coordinates that the document is rendered with, which are the ones of the window
+    /// unless the viewport is enlarged to take a screenshot of the whole page.
+    fn rendering_coordinates(&self) -> EmbedderCoordinates {
+        let mut coordinates = self.embedder_coordinates;
+        if let Some(full_page_viewport) = self.full_page_viewport {
+            coordinates.framebuffer = full_page_viewport.size;
+            coordinates.viewport =
+                DeviceIntRect::new(DeviceIntPoint::zero(), full_page_viewport.size);
+        }
+        coordinates
     }
 
     fn send_window_size(&self, size_type: WindowSizeType) {
         let dppx = self.page_zoom * self.embedder_coordinates.hidpi_factor;
+        let coordinates = self.rendering_coordinates();
 
         self.webrender_api.set_document_view(
             self.webrender_document,
-            self.embedder_coordinates.get_flipped_viewport(),
-            self.embedder_coordinates.hidpi_factor.get(),
+            coordinates.get_flipped_viewport(),
+            coordinates.hidpi_factor.get(),
         );
 
-        let initial_viewport = self.embedder_coordinates.viewport.size.to_f32() / dppx;
+        let initial_viewport = coordinates.viewport.size.to_f32() / dppx;
 
         let data = WindowSizeData {
             device_pixel_ratio: dppx,
@@ -702,6 +898,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
 
         self.send_window_size(WindowSizeType::Resize);
+        self.set_visual_viewport_offset(self.visual_viewport_offset);
+        self.update_visual_viewport_for_webrender();
         self.composite_if_necessary(CompositingReason::Resize);
     }
 
@@ -715,9 +913,43 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             return;
         }
 
+        if pref!(browser.autoscroll.enabled) {
+            let was_autoscrolling = self.autoscroll_handler.is_active();
+            let consumed = self
+                .autoscroll_handler
+                .on_mouse_window_event(mouse_window_event.clone(), precise_time_s());
+            if self.autoscroll_handler.is_active() != was_autoscrolling {
+                if was_autoscrolling {
+                    self.update_cursor(self.hit_test_at_point(self.cursor_pos));
+                } else {
+                    self.set_cursor(Cursor::AllScroll);
+                }
+                self.process_animations();
+            }
+            if consumed {
+                return;
+            }
+        }
+
         self.dispatch_mouse_window_event_class(mouse_window_event);
     }
 
+    /// Scrolls under the cursor as the default action of a key that content
+    /// left alone, and returns whether the key is one that scrolls.
+    pub fn on_unhandled_key_event(&mut self, key_event: &KeyboardEvent) -> bool {
+        let scroll = match KeyboardScroll::from_event(key_event) {
+            Some(scroll) => scroll,
+            None => return false,
+        };
+        let line_length = KEYBOARD_SCROLL_LINE_DISTANCE * self.device_pixels_per_page_px().get();
+        let viewport_height = self.embedder_coordinates.viewport.size.height as f32;
+        self.on_scroll_window_event(
+            scroll.scroll_location(line_length, viewport_height),
+            self.cursor_pos.to_i32(),
+        );
+        true
+    }
+
     fn dispatch_mouse_window_event_class(&mut self, mouse_window_event: MouseWindowEvent) {
         let point = match mouse_window_event {
             MouseWindowEvent::Click(_, p) => p,
@@ -754,8 +986,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn hit_test_at_point(&self, point: DevicePoint) -> HitTestResult {
-        let dppx = self.page_zoom * self.hidpi_factor();
-        let scaled_point = (point / dppx).to_untyped();
+        let scaled_point = (point / self.device_pixels_per_world_px()).to_untyped();
 
         let world_cursor = webrender_api::units::WorldPoint::from_untyped(scaled_point);
         self.webrender_api.hit_test(
@@ -767,6 +998,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn on_mouse_window_move_event_class(&mut self, cursor: DevicePoint) {
+        self.cursor_pos = cursor;
+        self.autoscroll_handler.on_mouse_move(cursor);
+
         if self.convert_mouse_to_touch {
             self.on_touch_move(TouchId(0), cursor);
             return;
@@ -775,6 +1009,22 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.dispatch_mouse_window_move_event_class(cursor);
     }
 
+    /// Sends the motion of the mouse while the pointer is locked to the root
+    /// pipeline, which the constellation redirects to the document that locked
+    /// it.
+    pub fn on_pointer_locked_mouse_move(&mut self, delta: DeviceVector2D) {
+        let root_pipeline_id = match self.get_root_pipeline_id() {
+            Some(root_pipeline_id) => root_pipeline_id,
+            None => return,
+        };
+        let delta = delta / self.device_pixels_per_page_px();
+        let event = PointerLockedMoveEvent(delta.to_untyped(), 0);
+        let msg = ConstellationMsg::ForwardEvent(root_pipeline_id, event);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending event to constellation failed ({:?}).", e);
+        }
+    }
+
     fn dispatch_mouse_window_move_event_class(&mut self, cursor: DevicePoint) {
         let root_pipeline_id = match self.get_root_pipeline_id() {
             Some(root_pipeline_id) => root_pipeline_id,
@@ -793,7 +1043,60 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             if let Err(e) = self.constellation_chan.send(msg) {
                 warn!("Sending event to constellation failed ({:?}).", e);
             }
-            self.update_cursor(results);
+            if !self.autoscroll_handler.is_active() {
+                self.update_cursor(results);
+            }
+        }
+    }
+
+    /// Sends an event of a drag from outside of the window to the document
+    /// under `point`. The documents that the drag moves out of get a `Leave`
+    /// event, and the ones it moves into an `Enter` event.
+    pub fn on_drag_event(&mut self, event_type: DragEventType, point: DevicePoint, data: DragData) {
+        let item = match event_type {
+            DragEventType::Leave => None,
+            _ => self.hit_test_at_point(point).items.into_iter().next(),
+        };
+        let pipeline_id = item
+            .as_ref()
+            .map(|item| PipelineId::from_webrender(item.pipeline));
+        let previous_pipeline_id = self.drag_pipeline.take();
+        if let Some(previous_pipeline_id) = previous_pipeline_id {
+            if Some(previous_pipeline_id) != pipeline_id {
+                let event = DragEvent(
+                    DragEventType::Leave,
+                    Point2D::zero(),
+                    None,
+                    DragData::default(),
+                );
+                let msg = ConstellationMsg::ForwardEvent(previous_pipeline_id, event);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending event to constellation failed ({:?}).", e);
+                }
+            }
+        }
+        let (item, pipeline_id) = match (item, pipeline_id) {
+            (Some(item), Some(pipeline_id)) => (item, pipeline_id),
+            _ => return,
+        };
+        let event_type = match event_type {
+            DragEventType::Over if previous_pipeline_id != Some(pipeline_id) => {
+                DragEventType::Enter
+            },
+            _ => event_type,
+        };
+        if event_type != DragEventType::Drop {
+            self.drag_pipeline = Some(pipeline_id);
+        }
+        let event = DragEvent(
+            event_type,
+            item.point_in_viewport.to_untyped(),
+            Some(UntrustedNodeAddress(item.tag.0 as *const c_void)),
+            data,
+        );
+        let msg = ConstellationMsg::ForwardEvent(pipeline_id, event);
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending event to constellation failed ({:?}).", e);
         }
     }
 
@@ -860,8 +1163,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 ScrollLocation::Delta(LayoutVector2D::from_untyped(delta.to_untyped())),
                 point.cast(),
             ),
-            TouchAction::Zoom(magnification, scroll_delta) => {
-                let cursor = Point2D::new(-1, -1); // Make sure this hits the base layer.
+            TouchAction::Zoom(magnification, center, scroll_delta) => {
+                let cursor = center.to_i32();
                 self.pending_scroll_zoom_events.push(ScrollZoomEvent {
                     magnification: magnification,
                     scroll_location: ScrollLocation::Delta(LayoutVector2D::from_untyped(
@@ -881,9 +1184,15 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     fn on_touch_up(&mut self, identifier: TouchId, point: DevicePoint) {
         self.send_touch_event(TouchEventType::Up, identifier, point);
 
-        if let TouchAction::Click = self.touch_handler.on_touch_up(identifier, point) {
-            self.simulate_mouse_click(point);
+        match self
+            .touch_handler
+            .on_touch_up(identifier, point, precise_time_s())
+        {
+            TouchAction::Click(point) => self.simulate_mouse_click(point),
+            TouchAction::DoubleTap => self.on_double_tap(point),
+            _ => {},
         }
+        self.update_animation_state();
     }
 
     fn on_touch_cancel(&mut self, identifier: TouchId, point: DevicePoint) {
@@ -892,6 +1201,68 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.send_touch_event(TouchEventType::Cancel, identifier, point);
     }
 
+    /// Zooms out if the page is zoomed in, and otherwise asks script for the block at `point` to
+    /// zoom to.
+    fn on_double_tap(&mut self, point: DevicePoint) {
+        if !self.user_scalable {
+            return;
+        }
+        if self.pinch_zoom_level() > self.initial_viewport_zoom.get() {
+            let magnification = self.initial_viewport_zoom.get() / self.pinch_zoom_level();
+            self.zoom_visual_viewport(magnification, point);
+            self.update_visual_viewport_for_webrender();
+            return;
+        }
+
+        let results = self.hit_test_at_point(point);
+        if let Some(item) = results.items.first() {
+            self.double_tap_point = Some(point);
+            let event = DoubleTapEvent(Some(UntrustedNodeAddress(item.tag.0 as *const c_void)));
+            let pipeline_id = PipelineId::from_webrender(item.pipeline);
+            let msg = ConstellationMsg::ForwardEvent(pipeline_id, event);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending event to constellation failed ({:?}).", e);
+            }
+        }
+    }
+
+    /// Zooms in so that `rect`, which script found in the layout viewport of `pipeline_id` for
+    /// the last double-tap, fills the width of the window. The double-tapped point stays at the
+    /// same height.
+    fn zoom_to_rect(&mut self, pipeline_id: PipelineId, rect: Option<Rect<f32, CSSPixel>>) {
+        let point = match self.double_tap_point.take() {
+            Some(point) => point,
+            None => return,
+        };
+        let is_root = self
+            .root_pipeline
+            .as_ref()
+            .map_or(false, |root_pipeline| root_pipeline.id == pipeline_id);
+        let rect = match rect {
+            Some(rect) if is_root && rect.size.width > 0. => rect,
+            _ => return,
+        };
+
+        // Where the block and the double-tapped point are in the unzoomed layout viewport.
+        let rect = rect * self.device_pixels_per_page_px();
+        let old_zoom = self.pinch_zoom_level();
+        let point_y = (point.y + self.visual_viewport_offset.y) / old_zoom;
+
+        let margin = DOUBLE_TAP_ZOOM_MARGIN * self.hidpi_factor().get();
+        let viewport_width = self.embedder_coordinates.viewport.size.width as f32;
+        self.set_pinch_zoom_level(viewport_width / (rect.size.width + 2. * margin));
+        let zoom = self.pinch_zoom_level();
+        if zoom <= old_zoom {
+            self.set_pinch_zoom_level(old_zoom);
+            return;
+        }
+        self.set_visual_viewport_offset(Vector2D::new(
+            (rect.origin.x - margin) * zoom,
+            point_y * zoom - point.y,
+        ));
+        self.update_visual_viewport_for_webrender();
+    }
+
     /// <http://w3c.github.io/touch-events/#mouse-events>
     fn simulate_mouse_click(&mut self, p: DevicePoint) {
         let button = MouseButton::Left;
@@ -923,6 +1294,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     fn on_scroll_window_event(&mut self, scroll_location: ScrollLocation, cursor: DeviceIntPoint) {
+        // Scrolling by the user stops the smooth scrolls of content.
+        self.smooth_scroll_handler.abort_all();
         self.pending_scroll_zoom_events.push(ScrollZoomEvent {
             magnification: 1.0,
             scroll_location: scroll_location,
@@ -979,25 +1352,30 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
 
         if let Some(combined_event) = last_combined_event {
+            if combined_event.magnification != 1.0 && self.user_scalable {
+                self.zoom_visual_viewport(
+                    combined_event.magnification,
+                    combined_event.cursor.to_f32(),
+                );
+            }
+            let scale = self.device_pixels_per_world_px();
             let scroll_location = match combined_event.scroll_location {
                 ScrollLocation::Delta(delta) => {
-                    let scaled_delta =
-                        (Vector2D::from_untyped(delta.to_untyped()) / self.scale).to_untyped();
-                    let calculated_delta = LayoutVector2D::from_untyped(scaled_delta);
+                    // Panning the visual viewport comes before scrolling the page.
+                    let delta =
+                        self.pan_visual_viewport(Vector2D::from_untyped(delta.to_untyped()));
+                    let calculated_delta =
+                        LayoutVector2D::from_untyped((delta / scale).to_untyped());
                     ScrollLocation::Delta(calculated_delta)
                 },
                 // Leave ScrollLocation unchanged if it is Start or End location.
                 sl @ ScrollLocation::Start | sl @ ScrollLocation::End => sl,
             };
-            let cursor = (combined_event.cursor.to_f32() / self.scale).to_untyped();
+            let cursor = (combined_event.cursor.to_f32() / scale).to_untyped();
             let cursor = webrender_api::units::WorldPoint::from_untyped(cursor);
             let mut txn = webrender_api::Transaction::new();
             txn.scroll(scroll_location, cursor);
-            if combined_event.magnification != 1.0 {
-                let old_zoom = self.pinch_zoom_level();
-                self.set_pinch_zoom_level(old_zoom * combined_event.magnification);
-                txn.set_pinch_zoom(webrender_api::ZoomFactor::new(self.pinch_zoom_level()));
-            }
+            self.set_visual_viewport(&mut txn);
             txn.generate_frame();
             self.webrender_api
                 .send_transaction(self.webrender_document, txn);
@@ -1009,17 +1387,61 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Starts scrolling a scroll node smoothly from where it currently is.
+    fn scroll_smoothly(&mut self, scroll_id: ExternalScrollId, position: LayoutPoint) {
+        let start = self
+            .webrender_api
+            .get_scroll_node_state(self.webrender_document)
+            .into_iter()
+            .find(|state| state.id == scroll_id)
+            .map_or(LayoutPoint::zero(), |state| {
+                LayoutPoint::zero() - state.scroll_offset
+            });
+        self.smooth_scroll_handler
+            .start(scroll_id, start, position, precise_time_s());
+        self.process_animations();
+    }
+
+    /// Scrolls the scroll nodes that scroll smoothly to where they are now.
+    fn tick_smooth_scrolls(&mut self) {
+        let positions = self.smooth_scroll_handler.on_tick(precise_time_s());
+        let mut txn = webrender_api::Transaction::new();
+        for (scroll_id, position) in positions {
+            txn.scroll_node_with_id(position, scroll_id, ScrollClamping::ToContentBounds);
+        }
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        self.waiting_for_results_of_scroll = true;
+        self.send_viewport_rects();
+    }
+
     /// If there are any animations running, dispatches appropriate messages to the constellation.
     fn process_animations(&mut self) {
+        let pipeline_ids = self.update_animation_state();
+        for pipeline_id in &pipeline_ids {
+            self.tick_animations_for_pipeline(*pipeline_id)
+        }
+    }
+
+    /// Tells the window whether anything animates, and returns the visible
+    /// pipelines that have animations running.
+    fn update_animation_state(&mut self) -> Vec<PipelineId> {
         let mut pipeline_ids = vec![];
         for (pipeline_id, pipeline_details) in &self.pipeline_details {
-            if (pipeline_details.animations_running || pipeline_details.animation_callbacks_running) &&
+            if (pipeline_details.animations_running ||
+                pipeline_details.animation_callbacks_running ||
+                pipeline_details.worker_animation_callbacks_running) &&
                 pipeline_details.visible
             {
                 pipeline_ids.push(*pipeline_id);
             }
         }
         let animation_state = if pipeline_ids.is_empty() &&
+            !self.autoscroll_handler.is_active() &&
+            !self.smooth_scroll_handler.is_active() &&
+            !self.scrollbar_handler.is_fading() &&
+            !self.touch_handler.is_holding_tap() &&
             !self.webvr_heartbeats_racing() &&
             !self.webxr_main_thread.running()
         {
@@ -1028,8 +1450,27 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             windowing::AnimationState::Animating
         };
         self.window.set_animation_state(animation_state);
-        for pipeline_id in &pipeline_ids {
-            self.tick_animations_for_pipeline(*pipeline_id)
+        pipeline_ids
+    }
+
+    /// Gives an animation frame to the dedicated workers of the visible
+    /// pipelines that wait for one.
+    fn tick_worker_animation_frames(&mut self) {
+        let pipeline_ids: Vec<_> = self
+            .pipeline_details
+            .iter()
+            .filter(|(_, details)| details.worker_animation_callbacks_running && details.visible)
+            .map(|(pipeline_id, _)| *pipeline_id)
+            .collect();
+        if pipeline_ids.is_empty() {
+            return;
+        }
+        self.last_worker_animation_tick_time = precise_time_ns();
+        for pipeline_id in pipeline_ids {
+            let msg = ConstellationMsg::TickAnimation(pipeline_id, AnimationTickType::Worker);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending tick to constellation failed ({:?}).", e);
+            }
         }
     }
 
@@ -1048,6 +1489,17 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             }
         }
 
+        let worker_animation_callbacks_running = self
+            .pipeline_details(pipeline_id)
+            .worker_animation_callbacks_running;
+        if worker_animation_callbacks_running {
+            self.last_worker_animation_tick_time = precise_time_ns();
+            let msg = ConstellationMsg::TickAnimation(pipeline_id, AnimationTickType::Worker);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending tick to constellation failed ({:?}).", e);
+            }
+        }
+
         // We may need to tick animations in layout. (See #12749.)
         let animations_running = self.pipeline_details(pipeline_id).animations_running;
         if animations_running {
@@ -1066,9 +1518,13 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         if is_root {
             self.viewport_zoom = constraints.initial_zoom;
+            self.initial_viewport_zoom = constraints.initial_zoom;
             self.min_viewport_zoom = constraints.min_zoom;
             self.max_viewport_zoom = constraints.max_zoom;
+            self.user_scalable = constraints.user_zoom == UserZoom::Zoom;
+            self.visual_viewport_offset = Vector2D::zero();
             self.update_zoom_transform();
+            self.update_visual_viewport_for_webrender();
         }
     }
 
@@ -1083,6 +1539,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.page_zoom * self.hidpi_factor()
     }
 
+    /// The scale of the scene, including the pinch zoom, which WebRender's world coordinates
+    /// are in before it is applied.
+    fn device_pixels_per_world_px(&self) -> Scale<f32, LayerPixel, DevicePixel> {
+        Scale::new(self.scale.get() * self.pinch_zoom_level())
+    }
+
     fn update_zoom_transform(&mut self) {
         let scale = self.device_pixels_per_page_px();
         self.scale = Scale::new(scale.get());
@@ -1119,14 +1581,16 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     pub fn on_pinch_zoom_window_event(&mut self, magnification: f32) {
         self.pending_scroll_zoom_events.push(ScrollZoomEvent {
             magnification: magnification,
-            scroll_location: ScrollLocation::Delta(Vector2D::zero()), // TODO: Scroll to keep the center in view?
-            cursor: Point2D::new(-1, -1), // Make sure this hits the base layer.
+            scroll_location: ScrollLocation::Delta(Vector2D::zero()),
+            // Zoom around the cursor.
+            cursor: self.cursor_pos.to_i32(),
             event_count: 1,
         });
     }
 
-    fn send_viewport_rects(&self) {
+    fn send_viewport_rects(&mut self) {
         let mut scroll_states_per_pipeline = HashMap::new();
+        let mut scroll_states = vec![];
         for scroll_layer_state in self
             .webrender_api
             .get_scroll_node_state(self.webrender_document)
@@ -1135,12 +1599,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 scroll_id: scroll_layer_state.id,
                 scroll_offset: scroll_layer_state.scroll_offset,
             };
+            scroll_states.push(scroll_state);
 
             scroll_states_per_pipeline
                 .entry(scroll_layer_state.id.pipeline_id())
                 .or_insert(vec![])
                 .push(scroll_state);
         }
+        self.scrollbars_scrolled(scroll_states);
 
         for (pipeline_id, scroll_states) in scroll_states_per_pipeline {
             if let Some(pipeline) = self.pipeline(pipeline_id.from_webrender()) {
@@ -1150,6 +1616,33 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Moves the scrollbar thumbs of the scroll nodes that scrolled, and shows
+    /// their overlay scrollbars.
+    fn scrollbars_scrolled(&mut self, scroll_states: Vec<ScrollState>) {
+        let now = precise_time_s();
+        let mut moved = false;
+        for scroll_state in scroll_states {
+            moved |= self.scrollbar_handler.on_scroll(
+                scroll_state.scroll_id,
+                scroll_state.scroll_offset,
+                now,
+            );
+        }
+        if moved {
+            self.update_scrollbars();
+            self.update_animation_state();
+        }
+    }
+
+    /// Tells WebRender where the scrollbar thumbs are and how opaque they are.
+    fn update_scrollbars(&mut self) {
+        let mut txn = webrender_api::Transaction::new();
+        txn.update_dynamic_properties(self.scrollbar_handler.dynamic_properties(precise_time_s()));
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+    }
+
     // Check if any pipelines currently have active animations or animation callbacks.
     fn animations_active(&self) -> bool {
         for (_, details) in &self.pipeline_details {
@@ -1220,9 +1713,21 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn composite(&mut self) {
-        let target = self.composite_target;
+        if !self.pending_screenshots.is_empty() {
+            return self.composite_pending_screenshot();
+        }
+
+        let target = match self.composite_target {
+            CompositeTarget::Window if self.is_frame_capture_due() => {
+                CompositeTarget::WindowAndFrame
+            },
+            target => target,
+        };
         match self.composite_specific_target(target, None) {
-            Ok(_) => {
+            Ok(frame) => {
+                if let Some(frame) = frame.filter(|_| target == CompositeTarget::WindowAndFrame) {
+                    self.send_captured_frame(frame);
+                }
                 if self.output_file.is_some() || self.exit_after_load {
                     println!("Shutting down the Constellation after generating an output file or exit flag specified");
                     self.start_shutting_down();
@@ -1240,6 +1745,176 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
+    /// Takes the first pending screenshot once the output is stable. The viewport is enlarged to
+    /// the size of the page first for the screenshots of the whole page, and restored after.
+    fn composite_pending_screenshot(&mut self) {
+        let screenshot = match self.pending_screenshots.front() {
+            Some(screenshot) => *screenshot,
+            None => return,
+        };
+
+        // Only the root browser is painted.
+        let is_painted = self.root_pipeline.as_ref().map_or(false, |pipeline| {
+            pipeline.top_level_browsing_context_id == screenshot.top_level_browsing_context_id
+        });
+        if !is_painted {
+            return self.finish_pending_screenshot(Err(ScreenshotError::Failed));
+        }
+
+        if let ScreenshotClip::Page(size) = screenshot.clip {
+            let size = (size * self.device_pixels_per_page_px()).ceil().to_i32();
+            let size = DeviceIntSize::new(
+                size.width.min(MAX_FULL_PAGE_SIZE),
+                size.height.min(MAX_FULL_PAGE_SIZE),
+            );
+            if size.is_empty_or_negative() {
+                return self.finish_pending_screenshot(Err(ScreenshotError::EmptyArea));
+            }
+            let is_enlarged = self
+                .full_page_viewport
+                .map_or(false, |viewport| viewport.size == size);
+            if !is_enlarged && size != self.embedder_coordinates.viewport.size {
+                self.full_page_viewport = Some(FullPageViewport {
+                    size,
+                    epoch: self.root_painted_epoch(),
+                });
+                // Don't use an answer about the stability of the output from before the
+                // viewport was enlarged.
+                if self.ready_to_save_state == ReadyState::ReadyToSaveImage {
+                    self.ready_to_save_state = ReadyState::Unknown;
+                }
+                return self.send_window_size(WindowSizeType::Resize);
+            }
+        }
+
+        // Wait for the page to be painted in the enlarged viewport.
+        if let Some(viewport) = self.full_page_viewport {
+            if self.root_painted_epoch() == viewport.epoch {
+                return;
+            }
+        }
+
+        let rect = match screenshot.clip {
+            ScreenshotClip::Rect(rect) => {
+                let viewport = Rect::new(
+                    Point2D::zero(),
+                    self.embedder_coordinates.viewport.size.to_f32() /
+                        self.device_pixels_per_page_px(),
+                );
+                match rect.intersection(&viewport) {
+                    Some(rect) if !rect.is_empty_or_negative() => Some(rect),
+                    _ => return self.finish_pending_screenshot(Err(ScreenshotError::EmptyArea)),
+                }
+            },
+            ScreenshotClip::Viewport | ScreenshotClip::Page(_) => None,
+        };
+
+        let result = match self.composite_specific_target(CompositeTarget::WindowAndPng, rect) {
+            Ok(Some(image)) => encode_screenshot(&image, screenshot.format),
+            Ok(None) => Err(ScreenshotError::Failed),
+            Err(e) => {
+                if self.is_running_problem_test {
+                    println!("not ready to take screenshot: {:?}", e);
+                }
+                return;
+            },
+        };
+        self.finish_pending_screenshot(result);
+    }
+
+    /// Start sending the frames of a browser to the embedder as they are composited, while it
+    /// is the painted one.
+    pub fn start_frame_capture(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        id: u32,
+        options: FrameCaptureOptions,
+    ) {
+        self.stop_frame_capture(id);
+        self.frame_captures.push(FrameCapture {
+            top_level_browsing_context_id,
+            id,
+            options,
+            last_frame_time: None,
+        });
+        // Send the current frame right away, instead of waiting for the next change.
+        self.composite_if_necessary(CompositingReason::FrameCapture);
+    }
+
+    pub fn stop_frame_capture(&mut self, id: u32) {
+        self.frame_captures.retain(|capture| capture.id != id);
+    }
+
+    /// Whether the next composite has to be read back for a capture of the painted browser.
+    fn is_frame_capture_due(&self) -> bool {
+        let top_level_browsing_context_id = match self.root_pipeline {
+            Some(ref pipeline) => pipeline.top_level_browsing_context_id,
+            None => return false,
+        };
+        let now = precise_time_ns();
+        self.frame_captures.iter().any(|capture| {
+            capture.top_level_browsing_context_id == top_level_browsing_context_id &&
+                capture.is_due(now)
+        })
+    }
+
+    /// Sends a composited frame of the painted browser to the captures of it that are due.
+    fn send_captured_frame(&mut self, frame: Image) {
+        let top_level_browsing_context_id = match self.root_pipeline {
+            Some(ref pipeline) => pipeline.top_level_browsing_context_id,
+            None => return,
+        };
+        let now = precise_time_ns();
+        for capture in self.frame_captures.iter_mut() {
+            if capture.top_level_browsing_context_id != top_level_browsing_context_id ||
+                !capture.is_due(now)
+            {
+                continue;
+            }
+            capture.last_frame_time = Some(now);
+            let frame = match encode_screenshot(&frame, capture.options.format) {
+                Ok(frame) => frame,
+                Err(_) => continue,
+            };
+            let msg =
+                ConstellationMsg::FrameCaptured(top_level_browsing_context_id, capture.id, frame);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending captured frame to constellation failed ({:?}).", e);
+            }
+        }
+    }
+
+    /// Sends the result of the first pending screenshot, restoring the viewport if it was
+    /// enlarged for it, and schedules the next one.
+    fn finish_pending_screenshot(&mut self, result: Result<Screenshot, ScreenshotError>) {
+        let screenshot = match self.pending_screenshots.pop_front() {
+            Some(screenshot) => screenshot,
+            None => return,
+        };
+        if self.full_page_viewport.take().is_some() {
+            self.send_window_size(WindowSizeType::Resize);
+        }
+        let msg = ConstellationMsg::ScreenshotTaken(
+            screenshot.top_level_browsing_context_id,
+            screenshot.id,
+            result,
+        );
+        if let Err(e) = self.constellation_chan.send(msg) {
+            warn!("Sending screenshot to constellation failed ({:?}).", e);
+        }
+        self.composite_if_necessary(CompositingReason::Screenshot);
+    }
+
+    /// The epoch that WebRender last painted the root pipeline at.
+    fn root_painted_epoch(&mut self) -> Option<Epoch> {
+        let pipeline_id = self.get_root_pipeline_id()?;
+        self.window.make_gl_context_current();
+        self.webrender.update();
+        self.webrender
+            .current_epoch(self.webrender_document, pipeline_id.to_webrender())
+            .map(|webrender_api::Epoch(epoch)| Epoch(epoch))
+    }
+
     /// Composite either to the screen or to a png image or both.
     /// Returns Ok if composition was performed or Err if it was not possible to composite
     /// for some reason. If CompositeTarget is Window or Png no image data is returned;
@@ -1250,14 +1925,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         target: CompositeTarget,
         rect: Option<Rect<f32, CSSPixel>>,
     ) -> Result<Option<Image>, UnableToComposite> {
-        let size = self.embedder_coordinates.framebuffer.to_u32();
+        let size = self.rendering_coordinates().framebuffer.to_u32();
 
         self.window.make_gl_context_current();
         self.webrender.update();
 
         let wait_for_stable_image = match target {
             CompositeTarget::WindowAndPng | CompositeTarget::PngFile => true,
-            CompositeTarget::Window => self.exit_after_load,
+            CompositeTarget::Window | CompositeTarget::WindowAndFrame => self.exit_after_load,
         };
 
         if wait_for_stable_image {
@@ -1277,7 +1952,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
         let rt_info = match target {
             #[cfg(feature = "gl")]
-            CompositeTarget::Window => gl::RenderTargetInfo::default(),
+            CompositeTarget::Window | CompositeTarget::WindowAndFrame => {
+                gl::RenderTargetInfo::default()
+            },
             #[cfg(feature = "gl")]
             CompositeTarget::WindowAndPng | CompositeTarget::PngFile => gl::initialize_png(
                 &*self.window.gl(),
@@ -1295,8 +1972,9 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             || {
                 debug!("compositor: compositing");
 
-                let size =
-                    DeviceIntSize::from_untyped(self.embedder_coordinates.framebuffer.to_untyped());
+                let size = DeviceIntSize::from_untyped(
+                    self.rendering_coordinates().framebuffer.to_untyped(),
+                );
 
                 // Paint the scene.
                 // TODO(gw): Take notice of any errors the renderer returns!
@@ -1319,16 +1997,17 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                     .webrender
                     .current_epoch(self.webrender_document, id.to_webrender())
                 {
-                    // and check if it is the one the layout thread is expecting,
+                    // and check if it is the one the layout thread is expecting, or a
+                    // later one that replaced it,
                     let epoch = Epoch(epoch);
-                    if *pending_epoch != epoch {
+                    if epoch < *pending_epoch {
                         continue;
                     }
                     // in which case, we remove it from the list of pending metrics,
                     to_remove.push(id.clone());
                     if let Some(pipeline) = self.pipeline(*id) {
                         // and inform the layout thread with the measured paint time.
-                        let msg = LayoutControlMsg::PaintMetric(epoch, paint_time);
+                        let msg = LayoutControlMsg::PaintMetric(*pending_epoch, paint_time);
                         if let Err(e) = pipeline.layout_chan.send(msg) {
                             warn!("Sending PaintMetric message to layout failed ({:?}).", e);
                         }
@@ -1359,7 +2038,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         let rv = match target {
             CompositeTarget::Window => None,
             #[cfg(feature = "gl")]
-            CompositeTarget::WindowAndPng => {
+            CompositeTarget::WindowAndPng | CompositeTarget::WindowAndFrame => {
                 let img = gl::draw_img(
                     &*self.window.gl(),
                     rt_info,
@@ -1447,7 +2126,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         gl.clear(gleam::gl::COLOR_BUFFER_BIT);
 
         // Make the viewport white.
-        let viewport = self.embedder_coordinates.get_flipped_viewport();
+        let viewport = self.rendering_coordinates().get_flipped_viewport();
         gl.scissor(
             viewport.origin.x,
             viewport.origin.y,
@@ -1509,9 +2188,47 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         // Run the WebXR main thread
         self.webxr_main_thread.run_one_frame();
 
+        // Autoscroll only once the previous scroll was rendered, so that its
+        // delta isn't averaged away with the pending ones.
+        if self.autoscroll_handler.is_active() && !self.waiting_for_results_of_scroll {
+            let delta = self.autoscroll_handler.on_tick(precise_time_s());
+            if delta != Vector2D::zero() {
+                let origin = self.autoscroll_handler.origin().to_i32();
+                self.on_scroll_window_event(
+                    ScrollLocation::Delta(LayoutVector2D::from_untyped(-delta.to_untyped())),
+                    origin,
+                );
+            }
+        }
+
+        if self.smooth_scroll_handler.is_active() && !self.waiting_for_results_of_scroll {
+            self.tick_smooth_scrolls();
+        }
+
+        // Click for a tap once it's too late for it to start a double-tap.
+        if let Some(point) = self.touch_handler.on_tick(precise_time_s()) {
+            self.simulate_mouse_click(point);
+            self.update_animation_state();
+        }
+
+        if self.scrollbar_handler.is_fading() {
+            self.update_scrollbars();
+            if !self.scrollbar_handler.is_fading() {
+                self.update_animation_state();
+            }
+        }
+
         if !self.pending_scroll_zoom_events.is_empty() && !self.waiting_for_results_of_scroll {
             self.process_pending_scroll_events()
         }
+
+        // The workers that draw to offscreen canvases may not cause composites,
+        // they still need their animation frames.
+        if precise_time_ns() - self.last_worker_animation_tick_time >=
+            WORKER_ANIMATION_FRAME_INTERVAL
+        {
+            self.tick_worker_animation_frames();
+        }
         self.shutdown_state != ShutdownState::FinishedShuttingDown
     }
 
@@ -1541,6 +2258,51 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.viewport_zoom.get()
     }
 
+    /// Zooms by `magnification` while keeping the content at `focus` in place.
+    fn zoom_visual_viewport(&mut self, magnification: f32, focus: DevicePoint) {
+        let old_zoom = self.pinch_zoom_level();
+        self.set_pinch_zoom_level(old_zoom * magnification);
+        let ratio = self.pinch_zoom_level() / old_zoom;
+        let focus = focus.to_vector();
+        self.set_visual_viewport_offset((self.visual_viewport_offset + focus) * ratio - focus);
+    }
+
+    /// Moves the visual viewport to `offset`, keeping it within the pinch-zoomed layout viewport.
+    fn set_visual_viewport_offset(&mut self, offset: Vector2D<f32, DevicePixel>) {
+        let max_offset = self.embedder_coordinates.viewport.size.to_f32() *
+            (self.pinch_zoom_level() - 1.).max(0.);
+        self.visual_viewport_offset = Vector2D::new(
+            offset.x.max(0.).min(max_offset.width),
+            offset.y.max(0.).min(max_offset.height),
+        );
+    }
+
+    /// Pans the visual viewport by the scroll `delta`, and returns what is left of it for the
+    /// page to scroll by once the visual viewport reaches the edge of the layout viewport.
+    fn pan_visual_viewport(
+        &mut self,
+        delta: Vector2D<f32, DevicePixel>,
+    ) -> Vector2D<f32, DevicePixel> {
+        let old_offset = self.visual_viewport_offset;
+        // Scroll deltas are positive towards the start of the page.
+        self.set_visual_viewport_offset(old_offset - delta);
+        delta - (old_offset - self.visual_viewport_offset)
+    }
+
+    fn set_visual_viewport(&self, txn: &mut webrender_api::Transaction) {
+        txn.set_pinch_zoom(webrender_api::ZoomFactor::new(self.pinch_zoom_level()));
+        let offset = self.visual_viewport_offset.round().to_i32();
+        txn.set_pan(DeviceIntPoint::new(-offset.x, -offset.y));
+    }
+
+    fn update_visual_viewport_for_webrender(&self) {
+        let mut txn = webrender_api::Transaction::new();
+        self.set_visual_viewport(&mut txn);
+        txn.generate_frame();
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+    }
+
     fn set_pinch_zoom_level(&mut self, mut zoom: f32) {
         if let Some(min) = self.min_viewport_zoom {
             zoom = f32::max(min.get(), zoom);
@@ -1642,4 +2404,34 @@ pub enum CompositingReason {
     NewWebRenderScrollFrame,
     /// The window has been resized and will need to be synchronously repainted.
     Resize,
+    /// The embedder has requested a screenshot.
+    Screenshot,
+    /// The embedder has started capturing the frames of a browser.
+    FrameCapture,
+}
+
+/// Encodes the pixels of a screenshot in the requested format.
+fn encode_screenshot(
+    image: &Image,
+    format: ScreenshotFormat,
+) -> Result<Screenshot, ScreenshotError> {
+    let data = match format {
+        ScreenshotFormat::Raw => image.bytes.to_vec(),
+        ScreenshotFormat::Png => {
+            let pixels = RgbImage::from_raw(image.width, image.height, image.bytes.to_vec())
+                .ok_or(ScreenshotError::Failed)?;
+            let mut data = vec![];
+            if let Err(e) = DynamicImage::ImageRgb8(pixels).write_to(&mut data, ImageFormat::PNG) {
+                warn!("Failed to encode screenshot ({}).", e);
+                return Err(ScreenshotError::Failed);
+            }
+            data
+        },
+    };
+    Ok(Screenshot {
+        width: image.width,
+        height: image.height,
+        format,
+        data,
+    })
 }
@@ -7,8 +7,8 @@
 use crate::compositor::CompositingReason;
 use crate::{ConstellationMsg, SendableFrameTree};
 use crossbeam_channel::{Receiver, Sender};
-use embedder_traits::EventLoopWaker;
-use euclid::Rect;
+use embedder_traits::{EventLoopWaker, ScreenshotFormat};
+use euclid::{Rect, Size2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
@@ -16,13 +16,15 @@ use net_traits::image::base::Image;
 use profile_traits::mem;
 use profile_traits::time;
 use script_traits::{AnimationState, EventResult, MouseButton, MouseEventType};
+use script_traits::{ScrollState, ScrollbarThumb};
 use std::fmt::{Debug, Error, Formatter};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webrender_api;
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPoint};
+use webrender_api::ExternalScrollId;
 use webvr_traits::WebVRMainThreadHeartbeat;
 
 /// Sends messages to the compositor.
@@ -83,10 +85,29 @@ pub enum Msg {
     Recomposite(CompositingReason),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Script has found the block that a double-tap in the pipeline zooms to, relative to its
+    /// viewport.
+    DoubleTapZoomRect(PipelineId, Option<Rect<f32, CSSPixel>>),
+    /// Script wants a scroll node to scroll smoothly to a position.
+    ScrollSmoothly(ExternalScrollId, LayoutPoint),
+    /// Script wants the smooth scroll of a scroll node to stop.
+    AbortSmoothScroll(ExternalScrollId),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(Option<Rect<f32, CSSPixel>>, IpcSender<Option<Image>>),
+    /// Take a screenshot of a part of the browser once its output is stable, for the request of
+    /// the embedder with the given id.
+    TakeScreenshot(
+        TopLevelBrowsingContextId,
+        u32,
+        ScreenshotClip,
+        ScreenshotFormat,
+    ),
     /// Alerts the compositor that the viewport has been constrained in some manner
     ViewportConstrained(PipelineId, ViewportConstraints),
+    /// Layout painted new scrollbar thumbs for the pipeline.
+    ScrollbarThumbs(PipelineId, Vec<ScrollbarThumb>),
+    /// Script scrolled a scroll node.
+    ScrolledFromScript(ScrollState),
     /// A reply to the compositor asking if the output image is stable.
     IsReadyToSaveImageReply(bool),
     /// Pipeline visibility changed
@@ -121,6 +142,8 @@ pub enum Msg {
     GetScreenSize(IpcSender<DeviceIntSize>),
     /// Get screen available size.
     GetScreenAvailSize(IpcSender<DeviceIntSize>),
+    /// Get the angle the screen is rotated by.
+    GetScreenOrientationAngle(IpcSender<u16>),
 }
 
 impl Debug for Msg {
@@ -133,8 +156,14 @@ impl Debug for Msg {
             Msg::SetFrameTree(..) => write!(f, "SetFrameTree"),
             Msg::Recomposite(..) => write!(f, "Recomposite"),
             Msg::TouchEventProcessed(..) => write!(f, "TouchEventProcessed"),
+            Msg::DoubleTapZoomRect(..) => write!(f, "DoubleTapZoomRect"),
+            Msg::ScrollSmoothly(..) => write!(f, "ScrollSmoothly"),
+            Msg::AbortSmoothScroll(..) => write!(f, "AbortSmoothScroll"),
             Msg::CreatePng(..) => write!(f, "CreatePng"),
+            Msg::TakeScreenshot(..) => write!(f, "TakeScreenshot"),
             Msg::ViewportConstrained(..) => write!(f, "ViewportConstrained"),
+            Msg::ScrollbarThumbs(..) => write!(f, "ScrollbarThumbs"),
+            Msg::ScrolledFromScript(..) => write!(f, "ScrolledFromScript"),
             Msg::IsReadyToSaveImageReply(..) => write!(f, "IsReadyToSaveImageReply"),
             Msg::PipelineVisibilityChanged(..) => write!(f, "PipelineVisibilityChanged"),
             Msg::PipelineExited(..) => write!(f, "PipelineExited"),
@@ -147,10 +176,23 @@ impl Debug for Msg {
             Msg::GetClientWindow(..) => write!(f, "GetClientWindow"),
             Msg::GetScreenSize(..) => write!(f, "GetScreenSize"),
             Msg::GetScreenAvailSize(..) => write!(f, "GetScreenAvailSize"),
+            Msg::GetScreenOrientationAngle(..) => write!(f, "GetScreenOrientationAngle"),
         }
     }
 }
 
+/// The part of the browser that a screenshot shows.
+#[derive(Clone, Copy, Debug)]
+pub enum ScreenshotClip {
+    /// What the viewport shows.
+    Viewport,
+    /// A rectangle, relative to the viewport and clipped to it.
+    Rect(Rect<f32, CSSPixel>),
+    /// The whole page, which has the given size and is laid out in a viewport as large for the
+    /// time of the screenshot.
+    Page(Size2D<f32, CSSPixel>),
+}
+
 /// Data used to construct a compositor.
 pub struct InitialCompositorState {
     /// A channel to the compositor.
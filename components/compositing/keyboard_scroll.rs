@@ -0,0 +1,166 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Scrolling with the keyboard, which is the default action of the keys that
+//! content leaves alone. The scroll node under the cursor scrolls, or its
+//! nearest ancestor that still can that way.
+
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+use script_traits::SCROLLBAR_PAGE_RATIO;
+use webrender_api::units::LayoutVector2D;
+use webrender_api::ScrollLocation;
+
+/// How far the arrow keys scroll, in CSS pixels.
+pub const KEYBOARD_SCROLL_LINE_DISTANCE: f32 = 40.;
+
+/// How far a key scrolls.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyboardScroll {
+    /// By a number of lines horizontally and vertically, backwards if
+    /// negative.
+    Lines(f32, f32),
+    /// By a number of pages vertically, backwards if negative.
+    Pages(f32),
+    Start,
+    End,
+}
+
+impl KeyboardScroll {
+    /// How the key scrolls, if it does. Keys pressed along with a modifier
+    /// are left to the embedder.
+    pub fn from_event(event: &KeyboardEvent) -> Option<Self> {
+        if event.state != KeyState::Down ||
+            event
+                .modifiers
+                .intersects(Modifiers::ALT | Modifiers::CONTROL | Modifiers::META)
+        {
+            return None;
+        }
+        let shift = event.modifiers.contains(Modifiers::SHIFT);
+        if event.code == Code::Space {
+            return Some(KeyboardScroll::Pages(if shift { -1. } else { 1. }));
+        }
+        if shift {
+            return None;
+        }
+        Some(match event.key {
+            Key::ArrowUp => KeyboardScroll::Lines(0., -1.),
+            Key::ArrowDown => KeyboardScroll::Lines(0., 1.),
+            Key::ArrowLeft => KeyboardScroll::Lines(-1., 0.),
+            Key::ArrowRight => KeyboardScroll::Lines(1., 0.),
+            Key::PageUp => KeyboardScroll::Pages(-1.),
+            Key::PageDown => KeyboardScroll::Pages(1.),
+            Key::Home => KeyboardScroll::Start,
+            Key::End => KeyboardScroll::End,
+            _ => return None,
+        })
+    }
+
+    /// Where this scrolls to, given how long lines and the viewport are in
+    /// device pixels.
+    pub fn scroll_location(&self, line_length: f32, viewport_height: f32) -> ScrollLocation {
+        // Scroll deltas are negative towards the end of the scroll range.
+        match *self {
+            KeyboardScroll::Lines(x, y) => {
+                ScrollLocation::Delta(LayoutVector2D::new(x, y) * -line_length)
+            },
+            KeyboardScroll::Pages(pages) => ScrollLocation::Delta(LayoutVector2D::new(
+                0.,
+                -pages * viewport_height * SCROLLBAR_PAGE_RATIO,
+            )),
+            KeyboardScroll::Start => ScrollLocation::Start,
+            KeyboardScroll::End => ScrollLocation::End,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyboardScroll;
+    use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+    use webrender_api::units::LayoutVector2D;
+    use webrender_api::ScrollLocation;
+
+    fn key_down(key: Key, code: Code, modifiers: Modifiers) -> KeyboardEvent {
+        KeyboardEvent {
+            state: KeyState::Down,
+            key,
+            code,
+            modifiers,
+            ..KeyboardEvent::default()
+        }
+    }
+
+    fn delta(location: ScrollLocation) -> LayoutVector2D {
+        match location {
+            ScrollLocation::Delta(delta) => delta,
+            _ => panic!("expected a scroll delta"),
+        }
+    }
+
+    #[test]
+    fn keys_that_scroll() {
+        let scroll =
+            |key, code, modifiers| KeyboardScroll::from_event(&key_down(key, code, modifiers));
+        assert_eq!(
+            scroll(Key::ArrowDown, Code::ArrowDown, Modifiers::empty()),
+            Some(KeyboardScroll::Lines(0., 1.))
+        );
+        assert_eq!(
+            scroll(Key::ArrowLeft, Code::ArrowLeft, Modifiers::empty()),
+            Some(KeyboardScroll::Lines(-1., 0.))
+        );
+        assert_eq!(
+            scroll(
+                Key::Character(" ".to_owned()),
+                Code::Space,
+                Modifiers::SHIFT
+            ),
+            Some(KeyboardScroll::Pages(-1.))
+        );
+        assert_eq!(
+            scroll(Key::End, Code::End, Modifiers::empty()),
+            Some(KeyboardScroll::End)
+        );
+
+        // Modified keys are left to the embedder.
+        assert_eq!(
+            scroll(Key::ArrowDown, Code::ArrowDown, Modifiers::SHIFT),
+            None
+        );
+        assert_eq!(scroll(Key::Home, Code::Home, Modifiers::CONTROL), None);
+        assert_eq!(
+            scroll(
+                Key::Character("a".to_owned()),
+                Code::KeyA,
+                Modifiers::empty()
+            ),
+            None
+        );
+
+        let mut key_up = key_down(Key::PageDown, Code::PageDown, Modifiers::empty());
+        key_up.state = KeyState::Up;
+        assert_eq!(KeyboardScroll::from_event(&key_up), None);
+    }
+
+    #[test]
+    fn keys_scroll_by_lines_and_pages() {
+        assert_eq!(
+            delta(KeyboardScroll::Lines(0., 1.).scroll_location(80., 1000.)),
+            LayoutVector2D::new(0., -80.)
+        );
+        assert_eq!(
+            delta(KeyboardScroll::Lines(-1., 0.).scroll_location(80., 1000.)),
+            LayoutVector2D::new(80., 0.)
+        );
+        assert_eq!(
+            delta(KeyboardScroll::Pages(-1.).scroll_location(80., 1000.)),
+            LayoutVector2D::new(0., 875.)
+        );
+        match KeyboardScroll::Start.scroll_location(80., 1000.) {
+            ScrollLocation::Start => {},
+            _ => panic!("Home should scroll to the start"),
+        }
+    }
+}
@@ -11,29 +11,38 @@ pub use crate::compositor::CompositingReason;
 pub use crate::compositor::IOCompositor;
 pub use crate::compositor::ShutdownState;
 pub use crate::compositor_thread::CompositorProxy;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, Screenshot, ScreenshotError, ScreenshotFormat};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::PipelineId;
 use msg::constellation_msg::TopLevelBrowsingContextId;
 use msg::constellation_msg::{BrowsingContextId, TraversalDirection};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::NetworkConditions;
 use script_traits::{
-    AnimationTickType, LogEntry, WebDriverCommandMsg, WindowSizeData, WindowSizeType,
+    AnimationTickType, DeviceEmulation, LogEntry, WebDriverCommandMsg, WindowSizeData,
+    WindowSizeType,
 };
 use script_traits::{
-    CompositorEvent, ConstellationControlMsg, LayoutControlMsg, MediaSessionActionType,
+    CompositorEvent, ConstellationControlMsg, DeviceSensorReading, FrameThrottlingPolicy,
+    LayoutControlMsg, MediaSessionActionType, NetworkStatus, ScreenshotArea,
 };
+use servo_config::prefs::PrefValue;
 use servo_url::ServoUrl;
 use std::collections::HashMap;
 use std::fmt;
 use std::time::Duration;
 use webvr_traits::WebVREvent;
 
+mod autoscroll;
 mod compositor;
 pub mod compositor_thread;
 #[cfg(feature = "gl")]
 mod gl;
+mod keyboard_scroll;
+mod scrollbars;
+mod smooth_scroll;
 mod touch;
 pub mod windowing;
 
@@ -72,6 +81,8 @@ pub enum ConstellationMsg {
     AllowNavigationResponse(PipelineId, bool),
     /// Request to load a page.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
+    /// Request to prefetch a page that the current document of a browser links to.
+    Prefetch(TopLevelBrowsingContextId, ServoUrl),
     /// Request to traverse the joint session history of the provided browsing context.
     TraverseHistory(TopLevelBrowsingContextId, TraversalDirection),
     /// Inform the constellation of a window being resized.
@@ -90,8 +101,8 @@ pub enum ConstellationMsg {
     LogEntry(Option<TopLevelBrowsingContextId>, Option<String>, LogEntry),
     /// Dispatch WebVR events to the subscribed script threads.
     WebVREvents(Vec<PipelineId>, Vec<WebVREvent>),
-    /// Create a new top level browsing context.
-    NewBrowser(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context, in the named profile if any.
+    NewBrowser(ServoUrl, TopLevelBrowsingContextId, Option<String>),
     /// Close a top level browsing context.
     CloseBrowser(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
@@ -112,6 +123,41 @@ pub enum ConstellationMsg {
     MediaSessionAction(MediaSessionActionType),
     /// Toggle browser visibility.
     ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
+    /// A preference changed, and has to be changed in all script threads too.
+    PreferenceChanged(String, PrefValue),
+    /// The network connection changed, which all script threads are told about.
+    NetworkStatusChanged(NetworkStatus),
+    /// A reading of the sensors of the device, which the documents of the
+    /// active browser are told about.
+    DeviceSensorReading(DeviceSensorReading),
+    /// Turn the content blocker on or off for the requests of a browser.
+    SetContentBlocking(TopLevelBrowsingContextId, bool),
+    /// Make a browser present itself as another user agent, or as the default
+    /// one again.
+    SetUserAgentOverride(TopLevelBrowsingContextId, Option<UserAgentOverride>),
+    /// Make a browser emulate a device, or stop emulating one.
+    SetDeviceEmulation(TopLevelBrowsingContextId, Option<DeviceEmulation>),
+    /// Make the requests of a browser under emulated network conditions, or
+    /// under the real ones again.
+    SetNetworkConditions(TopLevelBrowsingContextId, Option<NetworkConditions>),
+    /// Throttle the frames of a browser that are far from the viewport with the
+    /// given policy, or stop throttling them.
+    SetFrameThrottlingPolicy(TopLevelBrowsingContextId, Option<FrameThrottlingPolicy>),
+    /// Take a screenshot of an area of a browser, for the request with the given id.
+    TakeScreenshot(
+        TopLevelBrowsingContextId,
+        u32,
+        ScreenshotArea,
+        ScreenshotFormat,
+    ),
+    /// A screenshot was taken, or couldn't be.
+    ScreenshotTaken(
+        TopLevelBrowsingContextId,
+        u32,
+        Result<Screenshot, ScreenshotError>,
+    ),
+    /// A frame of a browser was captured, for the frame capture with the given id.
+    FrameCaptured(TopLevelBrowsingContextId, u32, Screenshot),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -126,6 +172,7 @@ impl fmt::Debug for ConstellationMsg {
             Keyboard(..) => "Keyboard",
             AllowNavigationResponse(..) => "AllowNavigationResponse",
             LoadUrl(..) => "LoadUrl",
+            Prefetch(..) => "Prefetch",
             TraverseHistory(..) => "TraverseHistory",
             WindowSize(..) => "WindowSize",
             TickAnimation(..) => "TickAnimation",
@@ -144,6 +191,17 @@ impl fmt::Debug for ConstellationMsg {
             ExitFullScreen(..) => "ExitFullScreen",
             MediaSessionAction(..) => "MediaSessionAction",
             ChangeBrowserVisibility(..) => "ChangeBrowserVisibility",
+            PreferenceChanged(..) => "PreferenceChanged",
+            NetworkStatusChanged(..) => "NetworkStatusChanged",
+            DeviceSensorReading(..) => "DeviceSensorReading",
+            SetContentBlocking(..) => "SetContentBlocking",
+            SetUserAgentOverride(..) => "SetUserAgentOverride",
+            SetDeviceEmulation(..) => "SetDeviceEmulation",
+            SetNetworkConditions(..) => "SetNetworkConditions",
+            SetFrameThrottlingPolicy(..) => "SetFrameThrottlingPolicy",
+            TakeScreenshot(..) => "TakeScreenshot",
+            ScreenshotTaken(..) => "ScreenshotTaken",
+            FrameCaptured(..) => "FrameCaptured",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
@@ -0,0 +1,199 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The scrollbar thumbs that layout paints, which the compositor moves along
+//! their tracks and fades out through WebRender property bindings, so that
+//! they follow asynchronous scrolling without a display list rebuild.
+
+use msg::constellation_msg::PipelineId;
+use script_traits::{overlay_scrollbar_opacity, ScrollbarThumb};
+use script_traits::{OVERLAY_SCROLLBAR_FADE_DURATION, OVERLAY_SCROLLBAR_VISIBLE_DURATION};
+use std::collections::HashMap;
+use webrender_api::units::{LayoutTransform, LayoutVector2D};
+use webrender_api::{DynamicProperties, ExternalScrollId, PropertyBindingKey, PropertyValue};
+
+pub struct ScrollbarHandler {
+    thumbs: HashMap<PipelineId, Vec<ScrollbarThumb>>,
+    /// The last known scroll offsets of the scroll nodes.
+    scroll_offsets: HashMap<ExternalScrollId, LayoutVector2D>,
+    /// When the scroll nodes with overlay scrollbars that are still visible
+    /// last scrolled, in seconds.
+    last_scrolled: HashMap<ExternalScrollId, f64>,
+}
+
+impl ScrollbarHandler {
+    pub fn new() -> Self {
+        ScrollbarHandler {
+            thumbs: HashMap::new(),
+            scroll_offsets: HashMap::new(),
+            last_scrolled: HashMap::new(),
+        }
+    }
+
+    /// Replaces the thumbs that layout painted for a pipeline.
+    pub fn set_thumbs(&mut self, pipeline_id: PipelineId, thumbs: Vec<ScrollbarThumb>) {
+        if thumbs.is_empty() {
+            self.thumbs.remove(&pipeline_id);
+        } else {
+            self.thumbs.insert(pipeline_id, thumbs);
+        }
+    }
+
+    pub fn remove_pipeline(&mut self, pipeline_id: PipelineId) {
+        self.thumbs.remove(&pipeline_id);
+        let pipeline_id = pipeline_id.to_webrender();
+        self.scroll_offsets
+            .retain(|scroll_id, _| scroll_id.pipeline_id() != pipeline_id);
+        self.last_scrolled
+            .retain(|scroll_id, _| scroll_id.pipeline_id() != pipeline_id);
+    }
+
+    /// Records the scroll offset of a scroll node at `now`, in seconds, and
+    /// returns whether any thumb has to move or to show up.
+    pub fn on_scroll(
+        &mut self,
+        scroll_id: ExternalScrollId,
+        scroll_offset: LayoutVector2D,
+        now: f64,
+    ) -> bool {
+        let previous = self.scroll_offsets.insert(scroll_id, scroll_offset);
+        if previous == Some(scroll_offset) {
+            return false;
+        }
+        let mut thumbs = self
+            .thumbs
+            .values()
+            .flatten()
+            .filter(|thumb| thumb.scroll_id == scroll_id)
+            .peekable();
+        if thumbs.peek().is_none() {
+            return false;
+        }
+        // The first offset we learn of is where the node starts out, rather
+        // than a scroll that shows overlay scrollbars.
+        if previous.is_some() && thumbs.any(|thumb| thumb.opacity_binding.is_some()) {
+            self.last_scrolled.insert(scroll_id, now);
+        }
+        true
+    }
+
+    /// Whether overlay scrollbars are visible, and so have yet to fade out,
+    /// which takes animation frames.
+    pub fn is_fading(&self) -> bool {
+        !self.last_scrolled.is_empty()
+    }
+
+    /// The transforms and opacities of the thumbs at `now`, in seconds. The
+    /// overlay scrollbars that finished fading out are forgotten.
+    pub fn dynamic_properties(&mut self, now: f64) -> DynamicProperties {
+        let mut transforms = vec![];
+        let mut floats = vec![];
+        for thumb in self.thumbs.values().flatten() {
+            let offset = self
+                .scroll_offsets
+                .get(&thumb.scroll_id)
+                .map_or(0., |scroll_offset| thumb.offset(*scroll_offset));
+            let (x, y) = if thumb.vertical {
+                (0., offset)
+            } else {
+                (offset, 0.)
+            };
+            transforms.push(PropertyValue {
+                key: PropertyBindingKey::new(thumb.transform_binding),
+                value: LayoutTransform::create_translation(x, y, 0.),
+            });
+            if let Some(opacity_binding) = thumb.opacity_binding {
+                let elapsed = self
+                    .last_scrolled
+                    .get(&thumb.scroll_id)
+                    .map(|last_scrolled| now - last_scrolled);
+                floats.push(PropertyValue {
+                    key: PropertyBindingKey::new(opacity_binding),
+                    value: overlay_scrollbar_opacity(elapsed),
+                });
+            }
+        }
+        self.last_scrolled.retain(|_, last_scrolled| {
+            now - *last_scrolled <
+                OVERLAY_SCROLLBAR_VISIBLE_DURATION + OVERLAY_SCROLLBAR_FADE_DURATION
+        });
+        DynamicProperties { transforms, floats }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScrollbarHandler;
+    use msg::constellation_msg::{PipelineId, TEST_PIPELINE_ID};
+    use script_traits::ScrollbarThumb;
+    use webrender_api::units::LayoutVector2D;
+    use webrender_api::ExternalScrollId;
+
+    fn thumb(pipeline_id: PipelineId, opacity_binding: Option<u64>) -> ScrollbarThumb {
+        ScrollbarThumb {
+            scroll_id: ExternalScrollId(1, pipeline_id.to_webrender()),
+            vertical: true,
+            transform_binding: 1,
+            opacity_binding,
+            thumb_offset_per_scroll: 0.2,
+            max_thumb_offset: 80.,
+        }
+    }
+
+    #[test]
+    fn thumbs_follow_their_scroll_node() {
+        let pipeline_id = TEST_PIPELINE_ID;
+        let thumb = thumb(pipeline_id, None);
+        let mut handler = ScrollbarHandler::new();
+        handler.set_thumbs(pipeline_id, vec![thumb]);
+
+        assert!(handler.on_scroll(thumb.scroll_id, LayoutVector2D::new(0., -100.), 0.));
+        assert!(!handler.on_scroll(thumb.scroll_id, LayoutVector2D::new(0., -100.), 0.));
+        let properties = handler.dynamic_properties(0.);
+        assert_eq!(properties.transforms.len(), 1);
+        assert_eq!(properties.transforms[0].value.m42, 20.);
+        assert!(properties.floats.is_empty());
+        assert!(!handler.is_fading());
+
+        // Thumbs stay on their track.
+        handler.on_scroll(thumb.scroll_id, LayoutVector2D::new(0., -1000.), 0.);
+        assert_eq!(handler.dynamic_properties(0.).transforms[0].value.m42, 80.);
+
+        let other = ExternalScrollId(2, pipeline_id.to_webrender());
+        assert!(!handler.on_scroll(other, LayoutVector2D::new(0., -10.), 0.));
+    }
+
+    #[test]
+    fn overlay_thumbs_fade_out_after_scrolling() {
+        let pipeline_id = TEST_PIPELINE_ID;
+        let thumb = thumb(pipeline_id, Some(2));
+        let mut handler = ScrollbarHandler::new();
+        handler.set_thumbs(pipeline_id, vec![thumb]);
+
+        // Where the node starts out doesn't show the thumb.
+        handler.on_scroll(thumb.scroll_id, LayoutVector2D::zero(), 0.);
+        assert!(!handler.is_fading());
+        assert_eq!(handler.dynamic_properties(0.).floats[0].value, 0.);
+
+        handler.on_scroll(thumb.scroll_id, LayoutVector2D::new(0., -10.), 10.);
+        assert!(handler.is_fading());
+        assert_eq!(handler.dynamic_properties(10.).floats[0].value, 1.);
+        assert!((handler.dynamic_properties(11.25).floats[0].value - 0.5).abs() < 0.001);
+        assert_eq!(handler.dynamic_properties(12.).floats[0].value, 0.);
+        assert!(!handler.is_fading());
+    }
+
+    #[test]
+    fn thumbs_go_away_with_their_pipeline() {
+        let pipeline_id = TEST_PIPELINE_ID;
+        let thumb = thumb(pipeline_id, Some(2));
+        let mut handler = ScrollbarHandler::new();
+        handler.set_thumbs(pipeline_id, vec![thumb]);
+        handler.on_scroll(thumb.scroll_id, LayoutVector2D::zero(), 0.);
+        handler.on_scroll(thumb.scroll_id, LayoutVector2D::new(0., -10.), 0.);
+        handler.remove_pipeline(pipeline_id);
+        assert!(!handler.is_fading());
+        assert!(handler.dynamic_properties(0.).transforms.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Smooth scrolls requested by content, which the compositor animates so
+//! that they keep up with the frames even if the script thread is busy.
+//! <https://drafts.csswg.org/cssom-view/#concept-smooth-scroll>
+
+use webrender_api::units::LayoutPoint;
+use webrender_api::ExternalScrollId;
+
+/// How long a smooth scroll lasts, in seconds.
+const SMOOTH_SCROLL_DURATION: f64 = 0.3;
+
+struct SmoothScroll {
+    scroll_id: ExternalScrollId,
+    start: LayoutPoint,
+    end: LayoutPoint,
+    /// When the scroll started, in seconds.
+    start_time: f64,
+}
+
+pub struct SmoothScrollHandler {
+    scrolls: Vec<SmoothScroll>,
+}
+
+impl SmoothScrollHandler {
+    pub fn new() -> Self {
+        SmoothScrollHandler { scrolls: vec![] }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.scrolls.is_empty()
+    }
+
+    /// Starts scrolling a scroll node from `start` to `end` at `now`, in
+    /// seconds, replacing the ongoing smooth scroll of the node if any.
+    pub fn start(
+        &mut self,
+        scroll_id: ExternalScrollId,
+        start: LayoutPoint,
+        end: LayoutPoint,
+        now: f64,
+    ) {
+        self.abort(scroll_id);
+        self.scrolls.push(SmoothScroll {
+            scroll_id,
+            start,
+            end,
+            start_time: now,
+        });
+    }
+
+    pub fn abort(&mut self, scroll_id: ExternalScrollId) {
+        self.scrolls.retain(|scroll| scroll.scroll_id != scroll_id);
+    }
+
+    /// Stops all the smooth scrolls, which happens when the user scrolls.
+    pub fn abort_all(&mut self) {
+        self.scrolls.clear();
+    }
+
+    /// Where the scroll nodes that scroll smoothly are at `now`, in seconds.
+    /// The scrolls that reach their end are done.
+    pub fn on_tick(&mut self, now: f64) -> Vec<(ExternalScrollId, LayoutPoint)> {
+        let positions = self
+            .scrolls
+            .iter()
+            .map(|scroll| {
+                let progress = ((now - scroll.start_time) / SMOOTH_SCROLL_DURATION).min(1.);
+                let position = scroll.start.lerp(scroll.end, ease_in_out(progress) as f32);
+                (scroll.scroll_id, position)
+            })
+            .collect();
+        self.scrolls
+            .retain(|scroll| now - scroll.start_time < SMOOTH_SCROLL_DURATION);
+        positions
+    }
+}
+
+/// A cubic easing that starts and ends slowly, for a progress between 0 and 1.
+fn ease_in_out(progress: f64) -> f64 {
+    if progress < 0.5 {
+        4. * progress * progress * progress
+    } else {
+        let remaining = 2. - 2. * progress;
+        1. - remaining * remaining * remaining / 2.
+    }
+}
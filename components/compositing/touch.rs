@@ -3,16 +3,25 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use self::TouchState::*;
-use euclid::{Point2D, Scale, Vector2D};
+use euclid::{Point2D, Vector2D};
 use script_traits::{EventResult, TouchId};
 use style_traits::DevicePixel;
 
 /// Minimum number of `DeviceIndependentPixel` to begin touch scrolling.
 const TOUCH_PAN_MIN_SCREEN_PX: f32 = 20.0;
 
+/// Maximum time between two taps of a double-tap, in seconds.
+const DOUBLE_TAP_MAX_INTERVAL: f64 = 0.3;
+
+/// Maximum distance between two taps of a double-tap, in `DevicePixel`s.
+const DOUBLE_TAP_MAX_DISTANCE: f32 = 20.0;
+
 pub struct TouchHandler {
     pub state: TouchState,
     pub active_touch_points: Vec<TouchPoint>,
+    /// When and where the last tap ended, if its click is held back until it
+    /// is clear that the tap doesn't start a double-tap.
+    held_tap: Option<(f64, Point2D<f32, DevicePixel>)>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -56,12 +65,14 @@ pub enum TouchState {
 /// The action to take in response to a touch event
 #[derive(Clone, Copy, Debug)]
 pub enum TouchAction {
-    /// Simulate a mouse click.
-    Click,
+    /// Simulate a mouse click at a point.
+    Click(Point2D<f32, DevicePixel>),
     /// Scroll by the provided offset.
     Scroll(Vector2D<f32, DevicePixel>),
-    /// Zoom by a magnification factor and scroll by the provided offset.
-    Zoom(f32, Vector2D<f32, DevicePixel>),
+    /// Zoom by a magnification factor around a point and scroll by the provided offset.
+    Zoom(f32, Point2D<f32, DevicePixel>, Vector2D<f32, DevicePixel>),
+    /// Zoom to the block that was double-tapped.
+    DoubleTap,
     /// Send a JavaScript event to content.
     DispatchEvent,
     /// Don't do anything.
@@ -73,6 +84,7 @@ impl TouchHandler {
         TouchHandler {
             state: Nothing,
             active_touch_points: Vec::new(),
+            held_tap: None,
         }
     }
 
@@ -122,10 +134,7 @@ impl TouchHandler {
                 self.active_touch_points[idx].point = point;
                 let (d1, c1) = self.pinch_distance_and_center();
 
-                let magnification = d1 / d0;
-                let scroll_delta = c1 - c0 * Scale::new(magnification);
-
-                TouchAction::Zoom(magnification, scroll_delta)
+                TouchAction::Zoom(d1 / d0, c0, c1 - c0)
             },
             WaitingForScript => TouchAction::NoAction,
             MultiTouch => TouchAction::NoAction,
@@ -140,7 +149,13 @@ impl TouchHandler {
         action
     }
 
-    pub fn on_touch_up(&mut self, id: TouchId, _point: Point2D<f32, DevicePixel>) -> TouchAction {
+    /// Handles a touch point being lifted at `now`, in seconds.
+    pub fn on_touch_up(
+        &mut self,
+        id: TouchId,
+        point: Point2D<f32, DevicePixel>,
+        now: f64,
+    ) -> TouchAction {
         match self.active_touch_points.iter().position(|t| t.id == id) {
             Some(i) => {
                 self.active_touch_points.swap_remove(i);
@@ -154,7 +169,23 @@ impl TouchHandler {
                 // FIXME: If the duration exceeds some threshold, send a contextmenu event instead.
                 // FIXME: Don't send a click if preventDefault is called on the touchend event.
                 self.state = Nothing;
-                TouchAction::Click
+                match self.held_tap.take() {
+                    Some((time, last_point))
+                        if now - time <= DOUBLE_TAP_MAX_INTERVAL &&
+                            (point - last_point).length() <= DOUBLE_TAP_MAX_DISTANCE =>
+                    {
+                        TouchAction::DoubleTap
+                    },
+                    held_tap => {
+                        // Hold this click back in turn. A tap that can't be
+                        // the first of a double-tap with this one clicks now.
+                        self.held_tap = Some((now, point));
+                        match held_tap {
+                            Some((_, last_point)) => TouchAction::Click(last_point),
+                            None => TouchAction::NoAction,
+                        }
+                    },
+                }
             },
             Nothing | Panning => {
                 self.state = Nothing;
@@ -199,6 +230,23 @@ impl TouchHandler {
         }
     }
 
+    /// Whether the click of a tap is held back, which is released by ticking.
+    pub fn is_holding_tap(&self) -> bool {
+        self.held_tap.is_some()
+    }
+
+    /// Where the tap whose click is held back ended, once it's too late at
+    /// `now`, in seconds, for a second tap to make a double-tap.
+    pub fn on_tick(&mut self, now: f64) -> Option<Point2D<f32, DevicePixel>> {
+        match self.held_tap {
+            Some((time, point)) if now - time > DOUBLE_TAP_MAX_INTERVAL => {
+                self.held_tap = None;
+                Some(point)
+            },
+            _ => None,
+        }
+    }
+
     pub fn on_event_processed(&mut self, result: EventResult) {
         if let WaitingForScript = self.state {
             self.state = match result {
@@ -226,3 +274,107 @@ impl TouchHandler {
         (distance, center)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TouchAction, TouchHandler, TouchState};
+    use euclid::{Point2D, Vector2D};
+    use script_traits::{EventResult, TouchId};
+
+    fn tap(handler: &mut TouchHandler, x: f32, y: f32, now: f64) -> TouchAction {
+        handler.on_touch_down(TouchId(0), Point2D::new(x, y));
+        handler.on_event_processed(EventResult::DefaultAllowed);
+        handler.on_touch_up(TouchId(0), Point2D::new(x, y), now)
+    }
+
+    #[test]
+    fn double_tap_holds_back_the_first_click() {
+        let mut handler = TouchHandler::new();
+        match tap(&mut handler, 10., 10., 1.) {
+            TouchAction::NoAction => {},
+            action => panic!("the first tap shouldn't click yet: {:?}", action),
+        }
+        assert!(handler.is_holding_tap());
+        assert_eq!(handler.on_tick(1.1), None);
+
+        match tap(&mut handler, 15., 12., 1.2) {
+            TouchAction::DoubleTap => {},
+            action => panic!("expected a double-tap: {:?}", action),
+        }
+        // Neither tap of the double-tap clicks.
+        assert!(!handler.is_holding_tap());
+        assert_eq!(handler.on_tick(2.), None);
+    }
+
+    #[test]
+    fn single_tap_clicks_once_no_double_tap_can_follow() {
+        let mut handler = TouchHandler::new();
+        tap(&mut handler, 10., 10., 1.);
+        assert_eq!(handler.on_tick(1.2), None);
+        assert_eq!(handler.on_tick(1.4), Some(Point2D::new(10., 10.)));
+        assert!(!handler.is_holding_tap());
+
+        // A tap too far from the held one clicks the held one right away.
+        tap(&mut handler, 10., 10., 2.);
+        match tap(&mut handler, 100., 10., 2.1) {
+            TouchAction::Click(point) => assert_eq!(point, Point2D::new(10., 10.)),
+            action => panic!("expected the first tap to click: {:?}", action),
+        }
+        assert_eq!(handler.on_tick(2.5), Some(Point2D::new(100., 10.)));
+    }
+
+    #[test]
+    fn pan_scrolls_past_the_threshold() {
+        let mut handler = TouchHandler::new();
+        handler.on_touch_down(TouchId(0), Point2D::new(100., 100.));
+        handler.on_event_processed(EventResult::DefaultAllowed);
+        assert_eq!(handler.state, TouchState::Touching);
+
+        match handler.on_touch_move(TouchId(0), Point2D::new(100., 110.)) {
+            TouchAction::NoAction => {},
+            action => panic!("small moves shouldn't scroll: {:?}", action),
+        }
+        match handler.on_touch_move(TouchId(0), Point2D::new(100., 130.)) {
+            TouchAction::Scroll(delta) => assert_eq!(delta, Vector2D::new(0., 30.)),
+            action => panic!("expected a scroll: {:?}", action),
+        }
+        assert_eq!(handler.state, TouchState::Panning);
+        match handler.on_touch_move(TouchId(0), Point2D::new(90., 135.)) {
+            TouchAction::Scroll(delta) => assert_eq!(delta, Vector2D::new(-10., 5.)),
+            action => panic!("expected a scroll: {:?}", action),
+        }
+
+        // Panning doesn't click.
+        match handler.on_touch_up(TouchId(0), Point2D::new(90., 135.), 1.) {
+            TouchAction::NoAction => {},
+            action => panic!("a pan shouldn't click: {:?}", action),
+        }
+        assert_eq!(handler.state, TouchState::Nothing);
+        assert!(!handler.is_holding_tap());
+    }
+
+    #[test]
+    fn pinch_zooms_around_the_center() {
+        let mut handler = TouchHandler::new();
+        handler.on_touch_down(TouchId(0), Point2D::new(100., 100.));
+        handler.on_event_processed(EventResult::DefaultAllowed);
+        handler.on_touch_down(TouchId(1), Point2D::new(200., 100.));
+        assert_eq!(handler.state, TouchState::Pinching);
+
+        match handler.on_touch_move(TouchId(1), Point2D::new(300., 100.)) {
+            TouchAction::Zoom(magnification, center, scroll_delta) => {
+                assert_eq!(magnification, 2.);
+                assert_eq!(center, Point2D::new(150., 100.));
+                assert_eq!(scroll_delta, Vector2D::new(50., 0.));
+            },
+            action => panic!("expected a zoom: {:?}", action),
+        }
+
+        // Lifting a finger pans with the other one.
+        handler.on_touch_up(TouchId(1), Point2D::new(300., 100.), 1.);
+        assert_eq!(handler.state, TouchState::Panning);
+        handler.on_touch_up(TouchId(0), Point2D::new(100., 100.), 1.);
+        assert_eq!(handler.state, TouchState::Nothing);
+        assert!(!handler.is_holding_tap());
+    }
+}
@@ -4,13 +4,18 @@
 
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
-use embedder_traits::EventLoopWaker;
+use embedder_traits::{EventLoopWaker, FrameCaptureOptions, ScreenshotFormat};
 use euclid::Scale;
 #[cfg(feature = "gl")]
 use gleam::gl;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
-use script_traits::{MediaSessionActionType, MouseButton, TouchEventType, TouchId, WheelDelta};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::NetworkConditions;
+use script_traits::FrameThrottlingPolicy;
+use script_traits::{DeviceEmulation, DeviceSensorReading, DragData, DragEventType};
+use script_traits::{MediaSessionActionType, MouseButton, NetworkStatus, ScreenshotArea};
+use script_traits::{TouchEventType, TouchId, WheelDelta};
 use servo_geometry::DeviceIndependentPixel;
 use servo_media::player::context::{GlApi, GlContext, NativeDisplay};
 use servo_url::ServoUrl;
@@ -21,8 +26,8 @@ use std::time::Duration;
 use style_traits::DevicePixel;
 
 use rust_webvr::VRServiceManager;
-use webrender_api::units::DevicePoint;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+use webrender_api::units::{DevicePoint, DeviceVector2D};
 use webrender_api::ScrollLocation;
 use webvr_traits::WebVRMainThreadHeartbeat;
 
@@ -60,10 +65,19 @@ pub enum WindowEvent {
     AllowNavigationResponse(PipelineId, bool),
     /// Sent when a new URL is to be loaded.
     LoadUrl(TopLevelBrowsingContextId, ServoUrl),
+    /// Sent when a same-origin link of a browser is likely to be followed, e.g. when the
+    /// user hovers it, to fetch its document into the HTTP cache ahead of the navigation.
+    Prefetch(TopLevelBrowsingContextId, ServoUrl),
     /// Sent when a mouse hit test is to be performed.
     MouseWindowEventClass(MouseWindowEvent),
     /// Sent when a mouse move.
     MouseWindowMoveEventClass(DevicePoint),
+    /// Sent when a drag from outside of the window moves over a point in it,
+    /// leaves it, or is dropped.
+    Drag(DragEventType, DevicePoint, DragData),
+    /// Sent with the motion of the mouse while the pointer is locked, in
+    /// which case the cursor doesn't move.
+    PointerLockedMouseMove(DeviceVector2D),
     /// Touch event: type, identifier, point
     Touch(TouchEventType, TouchId, DevicePoint),
     /// Sent when user moves the mouse wheel.
@@ -87,8 +101,9 @@ pub enum WindowEvent {
     Keyboard(KeyboardEvent),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload(TopLevelBrowsingContextId),
-    /// Create a new top level browsing context
-    NewBrowser(ServoUrl, TopLevelBrowsingContextId),
+    /// Create a new top level browsing context, in the named profile if any,
+    /// or else in the profile of the instance.
+    NewBrowser(ServoUrl, TopLevelBrowsingContextId, Option<String>),
     /// Close a top level browsing context
     CloseBrowser(TopLevelBrowsingContextId),
     /// Panic a top level browsing context.
@@ -107,6 +122,39 @@ pub enum WindowEvent {
     MediaSessionAction(MediaSessionActionType),
     /// Set browser visibility. A hidden browser will not tick the animations.
     ChangeBrowserVisibility(TopLevelBrowsingContextId, bool),
+    /// Sent when the network connection is lost or regained, or its quality changes.
+    NetworkStatusChanged(NetworkStatus),
+    /// Sent when the sensors of the device report its orientation or motion.
+    DeviceSensorReading(DeviceSensorReading),
+    /// Turn the content blocker on or off for the requests of a browser.
+    SetContentBlocking(TopLevelBrowsingContextId, bool),
+    /// Make a browser present itself as another user agent, for compatibility
+    /// testing or for the sites that need it, or as the default one again.
+    SetUserAgentOverride(TopLevelBrowsingContextId, Option<UserAgentOverride>),
+    /// Make a browser emulate a device, like a phone, without resizing the
+    /// window, or stop emulating one.
+    SetDeviceEmulation(TopLevelBrowsingContextId, Option<DeviceEmulation>),
+    /// Make the requests of a browser fail as if it were offline, or slow them
+    /// down with a latency and a throughput cap, for performance testing. Or
+    /// make them under the real network conditions again.
+    SetNetworkConditions(TopLevelBrowsingContextId, Option<NetworkConditions>),
+    /// Throttle the frames of a browser that are far from the viewport with the
+    /// given policy, or stop throttling them. The embedder is told which frames
+    /// are throttled with `EmbedderMsg::FrameProximityChanged`.
+    SetFrameThrottlingPolicy(TopLevelBrowsingContextId, Option<FrameThrottlingPolicy>),
+    /// Take a screenshot of an area of a browser once it is done loading and painting. The
+    /// result is sent back with `EmbedderMsg::ScreenshotTaken` and the given id.
+    TakeScreenshot(
+        TopLevelBrowsingContextId,
+        u32,
+        ScreenshotArea,
+        ScreenshotFormat,
+    ),
+    /// Start sending the frames of a browser to the embedder as they are composited, with
+    /// `EmbedderMsg::FrameCaptured` and the given id, e.g. to record or cast it.
+    StartFrameCapture(TopLevelBrowsingContextId, u32, FrameCaptureOptions),
+    /// Stop the frame capture with the given id.
+    StopFrameCapture(u32),
 }
 
 impl Debug for WindowEvent {
@@ -118,8 +166,11 @@ impl Debug for WindowEvent {
             WindowEvent::Keyboard(..) => write!(f, "Keyboard"),
             WindowEvent::AllowNavigationResponse(..) => write!(f, "AllowNavigationResponse"),
             WindowEvent::LoadUrl(..) => write!(f, "LoadUrl"),
+            WindowEvent::Prefetch(..) => write!(f, "Prefetch"),
             WindowEvent::MouseWindowEventClass(..) => write!(f, "Mouse"),
             WindowEvent::MouseWindowMoveEventClass(..) => write!(f, "MouseMove"),
+            WindowEvent::Drag(..) => write!(f, "Drag"),
+            WindowEvent::PointerLockedMouseMove(..) => write!(f, "PointerLockedMouseMove"),
             WindowEvent::Touch(..) => write!(f, "Touch"),
             WindowEvent::Wheel(..) => write!(f, "Wheel"),
             WindowEvent::Scroll(..) => write!(f, "Scroll"),
@@ -139,6 +190,16 @@ impl Debug for WindowEvent {
             WindowEvent::ExitFullScreen(..) => write!(f, "ExitFullScreen"),
             WindowEvent::MediaSessionAction(..) => write!(f, "MediaSessionAction"),
             WindowEvent::ChangeBrowserVisibility(..) => write!(f, "ChangeBrowserVisibility"),
+            WindowEvent::NetworkStatusChanged(..) => write!(f, "NetworkStatusChanged"),
+            WindowEvent::DeviceSensorReading(..) => write!(f, "DeviceSensorReading"),
+            WindowEvent::SetContentBlocking(..) => write!(f, "SetContentBlocking"),
+            WindowEvent::SetUserAgentOverride(..) => write!(f, "SetUserAgentOverride"),
+            WindowEvent::SetDeviceEmulation(..) => write!(f, "SetDeviceEmulation"),
+            WindowEvent::SetNetworkConditions(..) => write!(f, "SetNetworkConditions"),
+            WindowEvent::SetFrameThrottlingPolicy(..) => write!(f, "SetFrameThrottlingPolicy"),
+            WindowEvent::TakeScreenshot(..) => write!(f, "TakeScreenshot"),
+            WindowEvent::StartFrameCapture(..) => write!(f, "StartFrameCapture"),
+            WindowEvent::StopFrameCapture(..) => write!(f, "StopFrameCapture"),
         }
     }
 }
@@ -195,6 +256,9 @@ pub struct EmbedderCoordinates {
     pub screen: DeviceIntSize,
     /// Size of the available screen space (screen without toolbars and docks).
     pub screen_avail: DeviceIntSize,
+    /// The angle the screen is rotated by from its natural orientation, in
+    /// degrees: 0, 90, 180 or 270.
+    pub screen_orientation_angle: u16,
     /// Size of the native window.
     pub window: (DeviceIntSize, DeviceIntPoint),
     /// Size of the GL buffer in the window.
@@ -6,7 +6,8 @@
 //! For linux based platforms, it uses the XDG base directory spec but provides
 //! similar abstractions for non-linux platforms.
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 #[cfg(all(
     unix,
@@ -46,3 +47,26 @@ pub fn default_config_dir() -> Option<PathBuf> {
 pub fn default_config_dir() -> Option<PathBuf> {
     None
 }
+
+/// Returns the directory of a profile, which keeps its cookies, storage and
+/// prefs apart from the ones of the other profiles, creating it if needed.
+/// Profile names are made of ASCII alphanumerics, `-` and `_`.
+pub fn profile_dir(config_dir: &Path, profile: &str) -> Option<PathBuf> {
+    let is_valid = !profile.is_empty() &&
+        profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !is_valid {
+        warn!("Invalid profile name {:?}.", profile);
+        return None;
+    }
+    let profile_dir = config_dir.join("profiles").join(profile);
+    if let Err(e) = fs::create_dir_all(&profile_dir) {
+        warn!(
+            "Failed to create the profile directory {:?} ({}).",
+            profile_dir, e
+        );
+        return None;
+    }
+    Some(profile_dir)
+}
@@ -200,6 +200,10 @@ pub struct Opts {
     /// Directory for a default config directory
     pub config_dir: Option<PathBuf>,
 
+    /// The profile whose config directory, inside the `profiles` directory
+    /// of the config directory, is used by default.
+    pub user_profile: Option<String>,
+
     // don't skip any backtraces on panic
     pub full_backtraces: bool,
 
@@ -566,6 +570,7 @@ pub fn default_opts() -> Opts {
         exit_after_load: false,
         webrender_stats: false,
         config_dir: None,
+        user_profile: None,
         full_backtraces: false,
         is_printing_version: false,
         webrender_record: false,
@@ -733,6 +738,12 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         "config directory following xdg spec on linux platform",
         "",
     );
+    opts.optopt(
+        "",
+        "user-profile",
+        "Use the profile with this name in the config directory",
+        "work",
+    );
     opts.optflag("v", "version", "Display servo version information");
     opts.optflag("", "unminify-js", "Unminify Javascript");
     opts.optopt("", "profiler-db-user", "Profiler database user", "");
@@ -991,6 +1002,7 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         exit_after_load: opt_match.opt_present("x"),
         webrender_stats: debug_options.webrender_stats,
         config_dir: opt_match.opt_str("config-dir").map(Into::into),
+        user_profile: opt_match.opt_str("user-profile"),
         full_backtraces: debug_options.full_backtraces,
         is_printing_version: is_printing_version,
         webrender_record: debug_options.webrender_record,
@@ -188,10 +188,15 @@ impl<P, V> Accessor<P, V> {
     }
 }
 
+/// A callback that is notified with the key and new value of preferences
+/// whose value changed.
+pub type PrefObserver = Box<dyn Fn(&str, &PrefValue) + Send + Sync>;
+
 pub struct Preferences<'m, P> {
     user_prefs: Arc<RwLock<P>>,
     default_prefs: P,
     accessors: &'m HashMap<String, Accessor<P, PrefValue>>,
+    observers: RwLock<Vec<PrefObserver>>,
 }
 
 impl<'m, P: Clone> Preferences<'m, P> {
@@ -202,6 +207,7 @@ impl<'m, P: Clone> Preferences<'m, P> {
             user_prefs: Arc::new(RwLock::new(default_prefs.clone())),
             default_prefs,
             accessors,
+            observers: RwLock::new(Vec::new()),
         }
     }
 
@@ -220,6 +226,15 @@ impl<'m, P: Clone> Preferences<'m, P> {
         }
     }
 
+    /// Retrieve the default value of a preference using its key
+    pub fn get_default(&self, key: &str) -> PrefValue {
+        if let Some(accessor) = self.accessors.get(key) {
+            (accessor.getter)(&self.default_prefs)
+        } else {
+            PrefValue::Missing
+        }
+    }
+
     /// Creates an iterator over all keys and values
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = (String, PrefValue)> + 'a {
         let prefs = self.user_prefs.read().unwrap();
@@ -233,12 +248,48 @@ impl<'m, P: Clone> Preferences<'m, P> {
         self.accessors.keys().map(String::as_str)
     }
 
-    fn set_inner<V>(&self, key: &str, mut prefs: &mut P, val: V) -> Result<(), PrefError>
+    /// Register a callback that is notified whenever the value of a preference changes,
+    /// whether by `set`, `set_all`, `reset` or `reset_all`. Setting a preference to the
+    /// value it already has doesn't notify observers.
+    pub fn add_observer<F>(&self, observer: F)
+    where
+        F: Fn(&str, &PrefValue) + Send + Sync + 'static,
+    {
+        self.observers.write().unwrap().push(Box::new(observer));
+    }
+
+    /// Notifies the observers of `changes`. This must not be called while holding the lock
+    /// on the values, so that observers can read preferences.
+    fn notify(&self, changes: Vec<(String, PrefValue)>) {
+        if changes.is_empty() {
+            return;
+        }
+        let observers = self.observers.read().unwrap();
+        for (key, value) in &changes {
+            for observer in observers.iter() {
+                observer(key, value);
+            }
+        }
+    }
+
+    fn set_inner<V>(
+        &self,
+        key: &str,
+        mut prefs: &mut P,
+        val: V,
+        changes: &mut Vec<(String, PrefValue)>,
+    ) -> Result<(), PrefError>
     where
         V: Into<PrefValue>,
     {
         if let Some(accessor) = self.accessors.get(key) {
-            Ok((accessor.setter)(&mut prefs, val.into()))
+            let old_value = (accessor.getter)(&prefs);
+            (accessor.setter)(&mut prefs, val.into());
+            let new_value = (accessor.getter)(&prefs);
+            if new_value != old_value {
+                changes.push((String::from(key), new_value));
+            }
+            Ok(())
         } else {
             Err(PrefError::NoSuchPref(String::from(key)))
         }
@@ -249,27 +300,42 @@ impl<'m, P: Clone> Preferences<'m, P> {
     where
         V: Into<PrefValue>,
     {
-        let mut prefs = self.user_prefs.write().unwrap();
-        self.set_inner(key, &mut prefs, val)
+        let mut changes = vec![];
+        let result = {
+            let mut prefs = self.user_prefs.write().unwrap();
+            self.set_inner(key, &mut prefs, val, &mut changes)
+        };
+        self.notify(changes);
+        result
     }
 
     pub fn set_all<M>(&self, values: M) -> Result<(), PrefError>
     where
         M: IntoIterator<Item = (String, PrefValue)>,
     {
-        let mut prefs = self.user_prefs.write().unwrap();
-        for (k, v) in values.into_iter() {
-            self.set_inner(&k, &mut prefs, v)?;
-        }
-        Ok(())
+        let mut changes = vec![];
+        let result = {
+            let mut prefs = self.user_prefs.write().unwrap();
+            values
+                .into_iter()
+                .try_for_each(|(k, v)| self.set_inner(&k, &mut prefs, v, &mut changes))
+        };
+        self.notify(changes);
+        result
     }
 
     pub fn reset(&self, key: &str) -> Result<PrefValue, PrefError> {
         if let Some(accessor) = self.accessors.get(key) {
-            let mut prefs = self.user_prefs.write().unwrap();
-            let old_pref = (accessor.getter)(&prefs);
-            let default_pref = (accessor.getter)(&self.default_prefs);
-            (accessor.setter)(&mut prefs, default_pref);
+            let (old_pref, default_pref) = {
+                let mut prefs = self.user_prefs.write().unwrap();
+                let old_pref = (accessor.getter)(&prefs);
+                let default_pref = (accessor.getter)(&self.default_prefs);
+                (accessor.setter)(&mut prefs, default_pref.clone());
+                (old_pref, default_pref)
+            };
+            if old_pref != default_pref {
+                self.notify(vec![(String::from(key), default_pref)]);
+            }
             Ok(old_pref)
         } else {
             Err(PrefError::NoSuchPref(String::from(key)))
@@ -277,6 +343,23 @@ impl<'m, P: Clone> Preferences<'m, P> {
     }
 
     pub fn reset_all(&self) {
-        *self.user_prefs.write().unwrap() = self.default_prefs.clone();
+        let changes = {
+            let mut prefs = self.user_prefs.write().unwrap();
+            let changes = self
+                .accessors
+                .iter()
+                .filter_map(|(key, accessor)| {
+                    let default_pref = (accessor.getter)(&self.default_prefs);
+                    if (accessor.getter)(&prefs) != default_pref {
+                        Some((key.clone(), default_pref))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            *prefs = self.default_prefs.clone();
+            changes
+        };
+        self.notify(changes);
     }
 }
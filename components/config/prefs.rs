@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::basedir::default_config_dir;
+use crate::basedir::{default_config_dir, profile_dir};
 use crate::opts;
 use embedder_traits::resources::{self, Resource};
 use serde_json::{self, Value};
@@ -65,10 +65,14 @@ pub(crate) fn add_user_prefs() {
 }
 
 fn user_prefs_path() -> Option<PathBuf> {
-    opts::get()
-        .config_dir
+    let opts = opts::get();
+    opts.config_dir
         .clone()
         .or_else(|| default_config_dir())
+        .and_then(|path| match opts.user_profile {
+            Some(ref profile) => profile_dir(&path, profile),
+            None => Some(path),
+        })
         .map(|path| path.join("prefs.json"))
         .filter(|path| path.exists())
 }
@@ -143,6 +147,9 @@ mod gen {
         // tree of structs to generate
         gen_types = Prefs {
             browser: {
+                autoscroll: {
+                    enabled: bool,
+                },
                 display: {
                     #[serde(default = "white")]
                     background_color: i64,
@@ -162,6 +169,9 @@ mod gen {
                 webgpu: {
                     enabled: bool,
                 },
+                bfcache: {
+                    enabled: bool,
+                },
                 bluetooth: {
                     enabled: bool,
                     testing: {
@@ -176,28 +186,62 @@ mod gen {
                     #[serde(rename = "dom.compositionevent.enabled")]
                     enabled: bool,
                 },
+                constructable_stylesheets: {
+                    enabled: bool,
+                },
                 custom_elements: {
                     #[serde(rename = "dom.customelements.enabled")]
                     enabled: bool,
                 },
+                deviceorientation: {
+                    enabled: bool,
+                },
                 document: {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                document_all: {
+                    enabled: bool,
+                },
+                fontfaceset: {
+                    enabled: bool,
+                },
                 forcetouch: {
                     enabled: bool,
                 },
+                frame_throttling: {
+                    enabled: bool,
+                },
                 fullscreen: {
                     test: bool,
                 },
                 gamepad: {
                     enabled: bool,
                 },
+                geolocation: {
+                    enabled: bool,
+                },
+                geometry_utils: {
+                    enabled: bool,
+                },
+                import_maps: {
+                    #[serde(rename = "dom.importmaps.enabled")]
+                    enabled: bool,
+                },
+                layout_instability: {
+                    enabled: bool,
+                },
+                lazy_loading: {
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
                     }
                 },
+                midi: {
+                    enabled: bool,
+                },
                 mouse_event: {
                     which: {
                         #[serde(rename = "dom.mouseevent.which.enabled")]
@@ -210,12 +254,22 @@ mod gen {
                 offscreen_canvas: {
                     enabled: bool,
                 },
+                origin_trials: {
+                    allowlist: String,
+                    tokens: String,
+                },
                 permissions: {
                     enabled: bool,
                     testing: {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                reporting: {
+                    enabled: bool,
+                },
+                sanitizer: {
+                    enabled: bool,
+                },
                 serviceworker: {
                     enabled: bool,
                     timeout_seconds: i64,
@@ -228,6 +282,21 @@ mod gen {
                 shadowdom: {
                     enabled: bool,
                 },
+                speculation_rules: {
+                    enabled: bool,
+                },
+                speech_synthesis: {
+                    enabled: bool,
+                },
+                storage: {
+                    quota: i64,
+                },
+                storage_access: {
+                    enabled: bool,
+                },
+                subtle_crypto: {
+                    enabled: bool,
+                },
                 svg: {
                     enabled: bool,
                 },
@@ -277,6 +346,15 @@ mod gen {
                     #[serde(default)]
                     enabled: bool,
                 },
+                typed_om: {
+                    enabled: bool,
+                },
+                webauthn: {
+                    enabled: bool,
+                    testing: {
+                        enabled: bool,
+                    }
+                },
                 webgl: {
                     dom_to_texture: {
                         enabled: bool,
@@ -302,6 +380,11 @@ mod gen {
                     #[serde(default)]
                     glwindow: bool,
                 },
+                workers: {
+                    animation_frames: {
+                        enabled: bool,
+                    },
+                },
                 worklet: {
                     blockingsleep: {
                         #[serde(default)]
@@ -315,6 +398,14 @@ mod gen {
                     },
                     timeout_ms: i64,
                 },
+                xhr: {
+                    sync_deprecation_warning: {
+                        enabled: bool,
+                    },
+                },
+                xslt: {
+                    enabled: bool,
+                },
             },
             gfx: {
                 subpixel_text_antialiasing: {
@@ -413,7 +504,18 @@ mod gen {
                     enabled: bool,
                 },
                 timers: {
+                    background: {
+                        alignment: i64,
+                        budget: {
+                            enabled: bool,
+                            max: i64,
+                            regeneration_rate: f64,
+                        },
+                        max_delay: i64,
+                    },
                     minimum_duration: i64,
+                    nested_minimum_duration: i64,
+                    nesting_level: i64,
                 },
                 wasm: {
                     baseline: {
@@ -437,6 +539,18 @@ mod gen {
                 columns: {
                     enabled: bool,
                 },
+                css: {
+                    properties_and_values: {
+                        enabled: bool,
+                    }
+                },
+                grid: {
+                    enabled: bool,
+                },
+                scrollbars: {
+                    enabled: bool,
+                    overlay: bool,
+                },
                 #[serde(default = "default_layout_threads")]
                 threads: i64,
                 viewport: {
@@ -456,6 +570,23 @@ mod gen {
                 }
             },
             network: {
+                client_hints: {
+                    architecture: String,
+                    bitness: String,
+                    brand: String,
+                    enabled: bool,
+                    full_version: String,
+                    mobile: bool,
+                    model: String,
+                    platform: String,
+                    platform_version: String,
+                },
+                content_blocking: {
+                    enabled: bool,
+                },
+                cookie: {
+                    block_third_party: bool,
+                },
                 enforce_tls: {
                     enabled: bool,
                     localhost: bool,
@@ -465,6 +596,9 @@ mod gen {
                     #[serde(rename = "network.http-cache.disabled")]
                     disabled: bool,
                 },
+                keepalive: {
+                    shutdown_timeout_ms: i64,
+                },
                 mime: {
                     sniff: bool,
                 }
@@ -12,6 +12,7 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fs::{self, File};
 use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
 
 #[test]
 fn test_create_prefs_map() {
@@ -116,6 +117,22 @@ fn test_reset_pref() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_get_default_pref() -> Result<(), Box<dyn Error>> {
+    let mut def_prefs = gen::TestPrefs::default();
+    def_prefs.group.nested.nested_i64 = 999;
+    let prefs = Preferences::new(def_prefs, &gen::TEST_PREF_ACCESSORS);
+
+    prefs.set("group.nested.nested_i64", 1)?;
+    assert_eq!(Some(1), prefs.get("group.nested.nested_i64").as_i64());
+    assert_eq!(
+        Some(999),
+        prefs.get_default("group.nested.nested_i64").as_i64()
+    );
+    assert!(prefs.get_default("unknown_pref").is_missing());
+    Ok(())
+}
+
 #[test]
 fn test_default_values() -> Result<(), Box<dyn Error>> {
     let def_prefs: gen::TestPrefs = serde_json::from_str(DEF_JSON_STR)?;
@@ -207,6 +224,53 @@ fn test_reset_all() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_observers_notified_of_changes() -> Result<(), Box<dyn Error>> {
+    let def_prefs: gen::TestPrefs = serde_json::from_str(DEF_JSON_STR)?;
+    let prefs = Preferences::new(def_prefs, &gen::TEST_PREF_ACCESSORS);
+    let changes = Arc::new(Mutex::new(vec![]));
+    let observed = changes.clone();
+    prefs.add_observer(move |key, value| {
+        observed
+            .lock()
+            .unwrap()
+            .push((key.to_owned(), value.clone()))
+    });
+
+    prefs.set("pref_i64", 24)?;
+    // Setting the value a preference already has isn't a change.
+    prefs.set("pref_i64", 24)?;
+    prefs.set("pref_bool", true)?;
+    prefs.reset("pref_i64")?;
+    assert_eq!(
+        vec![
+            (String::from("pref_i64"), PrefValue::from(24_i64)),
+            (String::from("pref_i64"), PrefValue::from(23_i64)),
+        ],
+        *changes.lock().unwrap()
+    );
+
+    changes.lock().unwrap().clear();
+    prefs.set_all(read_prefs_map(USER_JSON_STR)?)?;
+    assert!(changes
+        .lock()
+        .unwrap()
+        .contains(&(String::from("pref_string"), PrefValue::from("bye"))));
+
+    changes.lock().unwrap().clear();
+    prefs.reset_all();
+    assert!(changes
+        .lock()
+        .unwrap()
+        .contains(&(String::from("pref_string"), PrefValue::from("hello"))));
+    assert!(!changes
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(key, _)| key == "default_value"));
+    Ok(())
+}
+
 #[test]
 fn test_set_all_from_map() -> Result<(), Box<dyn Error>> {
     let def_prefs: gen::TestPrefs = serde_json::from_str(DEF_JSON_STR)?;
@@ -55,6 +55,14 @@ pub struct BrowsingContext {
     /// purposes of scheduling and resource management.
     pub is_visible: bool,
 
+    /// The distance in CSS pixels between the frame of this browsing context
+    /// and the viewport of its parent, as last reported by script.
+    pub viewport_distance: f32,
+
+    /// Whether the documents of this browsing context are throttled because
+    /// the frame is too far from the viewport.
+    pub is_throttled: bool,
+
     /// The pipeline for the current session history entry.
     pub pipeline_id: PipelineId,
 
@@ -89,6 +97,8 @@ impl BrowsingContext {
             size,
             is_private,
             is_visible,
+            viewport_distance: 0.,
+            is_throttled: false,
             pipeline_id,
             parent_pipeline_id,
             pipelines,
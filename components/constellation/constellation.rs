@@ -106,14 +106,15 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use canvas_traits::webgl::WebGLThreads;
 use canvas_traits::ConstellationCanvasMsg;
-use compositing::compositor_thread::CompositorProxy;
 use compositing::compositor_thread::Msg as ToCompositorMsg;
+use compositing::compositor_thread::{CompositorProxy, ScreenshotClip};
 use compositing::{ConstellationMsg as FromCompositorMsg, SendableFrameTree};
 use crossbeam_channel::{after, never, unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
-use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy, EventLoopWaker};
+use embedder_traits::{Cursor, EmbedderMsg, EmbedderProxy, EventLoopWaker, FrameProximity};
 use embedder_traits::{MediaSessionEvent, MediaSessionPlaybackState};
-use euclid::{default::Size2D as UntypedSize2D, Size2D};
+use embedder_traits::{Screenshot, ScreenshotError, ScreenshotFormat};
+use euclid::{default::Size2D as UntypedSize2D, Rect, Size2D};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
@@ -133,29 +134,44 @@ use msg::constellation_msg::{
     BrowsingContextGroupId, BrowsingContextId, HistoryStateId, PipelineId,
     TopLevelBrowsingContextId,
 };
+use net::resource_thread::new_resource_threads;
+use net_traits::blob_url_store::get_blob_origin;
+use net_traits::filemanager_thread::FileManagerThreadMsg;
 use net_traits::pub_domains::reg_host;
 use net_traits::request::RequestBuilder;
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::{self, CoreResourceMsg, FetchResponseMsg, IpcSend};
+use net_traits::{NetworkConditions, ResourceThreads};
 use profile_traits::mem;
 use profile_traits::time;
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
+use script_traits::CompositorEvent::{
+    DragEvent, MouseButtonEvent, MouseMoveEvent, PointerLockedMoveEvent,
+};
 use script_traits::{webdriver_msg, LogEntry, ScriptToConstellationChan, ServiceWorkerMsg};
 use script_traits::{
     AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg,
     CompositorEvent,
 };
-use script_traits::{ConstellationControlMsg, DiscardBrowsingContext};
-use script_traits::{DocumentActivity, DocumentState, LayoutControlMsg, LoadData, LoadOrigin};
+use script_traits::{ConstellationControlMsg, CrashReport, DiscardBrowsingContext};
+use script_traits::{DeviceEmulation, DeviceSensorReading, DocumentActivity, DocumentState};
+use script_traits::{
+    DragData, DragEventType, DraggedFile, FrameThrottlingPolicy, LayoutControlMsg,
+};
 use script_traits::{HistoryEntryReplacement, IFrameSizeMsg, WindowSizeData, WindowSizeType};
 use script_traits::{
     IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState, TimerSchedulerMsg,
 };
 use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory};
-use script_traits::{MediaSessionActionType, MouseEventType};
-use script_traits::{MessagePortMsg, PortMessageTask, StructuredSerializedData};
+use script_traits::{LoadData, LoadOrigin, ScreenshotArea};
+use script_traits::{MediaSessionActionType, MouseEventType, NavigationType, NetworkStatus};
+use script_traits::{
+    MessagePortMsg, PersistedUserState, PortMessageTask, StructuredSerializedData,
+};
 use script_traits::{SWManagerMsg, ScopeThings, UpdatePipelineIdReason, WebDriverCommandMsg};
 use serde::{Deserialize, Serialize};
+use servo_config::basedir::profile_dir;
+use servo_config::prefs::PrefValue;
 use servo_config::{opts, pref};
 use servo_rand::{random, Rng, ServoRng, SliceRandom};
 use servo_remutex::ReentrantMutex;
@@ -170,6 +186,7 @@ use std::rc::{Rc, Weak};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webgpu::{WebGPU, WebGPURequest};
@@ -223,6 +240,28 @@ struct Browser {
 
     /// The joint session history for this browser.
     session_history: JointSessionHistory,
+
+    /// Whether the content blocker filters the requests of this browser.
+    content_blocking: bool,
+
+    /// The user agent that this browser presents itself as, if the embedder
+    /// overrides it.
+    user_agent_override: Option<UserAgentOverride>,
+
+    /// The device that this browser emulates, if the embedder set one.
+    device_emulation: Option<DeviceEmulation>,
+
+    /// The profile that this browser was created in, if it isn't the profile
+    /// of the instance.
+    profile: Option<String>,
+
+    /// The network conditions that this browser emulates, if the embedder set
+    /// some.
+    network_conditions: Option<NetworkConditions>,
+
+    /// How the frames of this browser that are far from the viewport are
+    /// throttled, if the embedder set a policy.
+    frame_throttling_policy: Option<FrameThrottlingPolicy>,
 }
 
 /// A browsing context group.
@@ -332,6 +371,11 @@ pub struct Constellation<Message, LTF, STF> {
     /// browsing.
     private_resource_threads: ResourceThreads,
 
+    /// Channels to the public and private resource-related threads of the
+    /// profiles that browsers were created in, other than the profile of the
+    /// instance. Each profile has its own cookies, cache and storage.
+    profile_resource_threads: HashMap<String, (ResourceThreads, ResourceThreads)>,
+
     /// A channel for the constellation to send messages to the font
     /// cache thread.
     font_cache_thread: FontCacheThread,
@@ -448,6 +492,13 @@ pub struct Constellation<Message, LTF, STF> {
     /// The buffer contains `(thread_name, reason)` entries.
     handled_warnings: VecDeque<(Option<String>, String)>,
 
+    /// The most recent panics that the constellation recovered from, as listed by
+    /// about:crashes.
+    crash_reports: VecDeque<CrashReport>,
+
+    /// The network connection, as last reported by the embedder.
+    network_status: NetworkStatus,
+
     /// The random number generator and probability for closing pipelines.
     /// This is for testing the hardening of the constellation.
     random_pipeline_closure: Option<(ServoRng, f32)>,
@@ -476,6 +527,10 @@ pub struct Constellation<Message, LTF, STF> {
     /// currently being pressed.
     pressed_mouse_buttons: u16,
 
+    /// The pipeline whose document the pointer is locked to, which gets the
+    /// mouse events while it is.
+    pointer_lock_pipeline: Option<PipelineId>,
+
     is_running_problem_test: bool,
 
     /// If True, exits on thread failure instead of displaying about:failure
@@ -707,6 +762,9 @@ fn log_entry(record: &Record) -> Option<LogEntry> {
 /// The number of warnings to include in each crash report.
 const WARNINGS_BUFFER_SIZE: usize = 32;
 
+/// The number of panics that are kept for about:crashes.
+const CRASH_REPORTS_BUFFER_SIZE: usize = 32;
+
 /// Route an ipc receiver to an mpsc receiver, preserving any errors.
 /// This is the same as `route_ipc_receiver_to_new_mpsc_receiver`,
 /// but does not panic on deserializtion errors.
@@ -960,6 +1018,7 @@ where
                     bluetooth_thread: state.bluetooth_thread,
                     public_resource_threads: state.public_resource_threads,
                     private_resource_threads: state.private_resource_threads,
+                    profile_resource_threads: HashMap::new(),
                     font_cache_thread: state.font_cache_thread,
                     swmanager_chan: None,
                     swmanager_receiver: swmanager_receiver,
@@ -995,6 +1054,8 @@ where
                     ),
                     shutting_down: false,
                     handled_warnings: VecDeque::new(),
+                    crash_reports: VecDeque::new(),
+                    network_status: NetworkStatus::default(),
                     random_pipeline_closure: random_pipeline_closure_probability.map(|prob| {
                         let seed = random_pipeline_closure_seed.unwrap_or_else(random);
                         let rng = ServoRng::new_manually_reseeded(seed as u64);
@@ -1009,6 +1070,7 @@ where
                     ipc_canvas_chan,
                     pending_approval_navigations: HashMap::new(),
                     pressed_mouse_buttons: 0,
+                    pointer_lock_pipeline: None,
                     is_running_problem_test,
                     hard_fail,
                     enable_canvas_antialiasing,
@@ -1213,11 +1275,11 @@ where
             },
         };
 
-        let resource_threads = if is_private {
-            self.private_resource_threads.clone()
-        } else {
-            self.public_resource_threads.clone()
-        };
+        let is_new_event_loop = event_loop.is_none();
+
+        let resource_threads = self
+            .resource_threads(top_level_browsing_context_id, is_private)
+            .clone();
 
         let result = Pipeline::spawn::<Message, LTF, STF>(InitialPipelineState {
             id: pipeline_id,
@@ -1247,7 +1309,9 @@ where
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             window_size: WindowSizeData {
                 initial_viewport: initial_window_size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                device_pixel_ratio: self
+                    .browser_window_size(top_level_browsing_context_id)
+                    .device_pixel_ratio,
             },
             event_loop,
             load_data,
@@ -1287,8 +1351,25 @@ where
             );
         }
 
+        // New script threads start out online, and have to be told if the network is not.
+        let event_loop = pipeline.pipeline.event_loop.clone();
         assert!(!self.pipelines.contains_key(&pipeline_id));
         self.pipelines.insert(pipeline_id, pipeline.pipeline);
+        self.update_content_blocking(pipeline_id, top_level_browsing_context_id, is_private);
+        self.update_user_agent_override(pipeline_id, top_level_browsing_context_id, is_private);
+        self.update_network_conditions(pipeline_id, top_level_browsing_context_id, is_private);
+        self.update_device_emulation(pipeline_id, top_level_browsing_context_id);
+        self.update_frame_throttling(
+            pipeline_id,
+            browsing_context_id,
+            top_level_browsing_context_id,
+        );
+        if is_new_event_loop && self.network_status != NetworkStatus::default() {
+            let msg = ConstellationControlMsg::SetNetworkStatus(self.network_status);
+            if let Err(e) = event_loop.send(msg) {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
     }
 
     /// Get an iterator for the fully active browsing contexts in a subtree.
@@ -1637,6 +1718,9 @@ where
                     HistoryEntryReplacement::Disabled,
                 );
             },
+            FromCompositorMsg::Prefetch(top_level_browsing_context_id, url) => {
+                self.handle_prefetch_msg(top_level_browsing_context_id, url);
+            },
             FromCompositorMsg::IsReadyToSaveImage(pipeline_states) => {
                 let is_ready = self.handle_is_ready_to_save_image(pipeline_states);
                 debug!("Ready to save image {:?}.", is_ready);
@@ -1652,8 +1736,12 @@ where
             },
             // Create a new top level browsing context. Will use response_chan to return
             // the browsing context id.
-            FromCompositorMsg::NewBrowser(url, top_level_browsing_context_id) => {
-                self.handle_new_top_level_browsing_context(url, top_level_browsing_context_id);
+            FromCompositorMsg::NewBrowser(url, top_level_browsing_context_id, profile) => {
+                self.handle_new_top_level_browsing_context(
+                    url,
+                    top_level_browsing_context_id,
+                    profile,
+                );
             },
             // Close a top level browsing context.
             FromCompositorMsg::CloseBrowser(top_level_browsing_context_id) => {
@@ -1721,6 +1809,42 @@ where
             FromCompositorMsg::ChangeBrowserVisibility(top_level_browsing_context_id, visible) => {
                 self.handle_change_browser_visibility(top_level_browsing_context_id, visible);
             },
+            FromCompositorMsg::PreferenceChanged(name, value) => {
+                self.handle_preference_changed(name, value);
+            },
+            FromCompositorMsg::NetworkStatusChanged(status) => {
+                self.handle_network_status_changed(status);
+            },
+            FromCompositorMsg::DeviceSensorReading(reading) => {
+                self.handle_device_sensor_reading(reading);
+            },
+            FromCompositorMsg::SetContentBlocking(top_level_browsing_context_id, enabled) => {
+                self.handle_set_content_blocking(top_level_browsing_context_id, enabled);
+            },
+            FromCompositorMsg::SetUserAgentOverride(top_level_browsing_context_id, ua_override) => {
+                self.handle_set_user_agent_override(top_level_browsing_context_id, ua_override);
+            },
+            FromCompositorMsg::SetDeviceEmulation(top_level_browsing_context_id, emulation) => {
+                self.handle_set_device_emulation(top_level_browsing_context_id, emulation);
+            },
+            FromCompositorMsg::SetNetworkConditions(top_level_browsing_context_id, conditions) => {
+                self.handle_set_network_conditions(top_level_browsing_context_id, conditions);
+            },
+            FromCompositorMsg::SetFrameThrottlingPolicy(top_level_browsing_context_id, policy) => {
+                self.handle_set_frame_throttling_policy(top_level_browsing_context_id, policy);
+            },
+            FromCompositorMsg::TakeScreenshot(top_level_browsing_context_id, id, area, format) => {
+                self.handle_take_screenshot(top_level_browsing_context_id, id, area, format);
+            },
+            FromCompositorMsg::ScreenshotTaken(top_level_browsing_context_id, id, result) => {
+                self.handle_screenshot_taken(top_level_browsing_context_id, id, result);
+            },
+            FromCompositorMsg::FrameCaptured(top_level_browsing_context_id, id, frame) => {
+                self.embedder_proxy.send((
+                    Some(top_level_browsing_context_id),
+                    EmbedderMsg::FrameCaptured(id, frame),
+                ));
+            },
         }
     }
 
@@ -1806,15 +1930,24 @@ where
             FromScriptMsg::PipelineExited => {
                 self.handle_pipeline_exited(source_pipeline_id);
             },
-            FromScriptMsg::DiscardDocument => {
-                self.handle_discard_document(source_top_ctx_id, source_pipeline_id);
+            FromScriptMsg::DocumentSalvageable(salvageable) => {
+                self.handle_document_salvageable(
+                    source_top_ctx_id,
+                    source_pipeline_id,
+                    salvageable,
+                );
             },
             FromScriptMsg::DiscardTopLevelBrowsingContext => {
                 self.handle_close_top_level_browsing_context(source_top_ctx_id);
             },
 
             FromScriptMsg::InitiateNavigateRequest(req_init, cancel_chan) => {
-                self.handle_navigate_request(source_pipeline_id, req_init, cancel_chan);
+                self.handle_navigate_request(
+                    source_top_ctx_id,
+                    source_pipeline_id,
+                    req_init,
+                    cancel_chan,
+                );
             },
             FromScriptMsg::ScriptLoadedURLInIFrame(load_info) => {
                 self.handle_script_loaded_url_in_iframe_msg(load_info);
@@ -1854,6 +1987,15 @@ where
             FromScriptMsg::ReplaceHistoryState(history_state_id, url) => {
                 self.handle_replace_history_state_msg(source_pipeline_id, history_state_id, url);
             },
+            FromScriptMsg::PersistUserState(user_state) => {
+                match self.pipelines.get_mut(&source_pipeline_id) {
+                    Some(pipeline) => pipeline.persisted_user_state = user_state,
+                    None => warn!(
+                        "Persisted user state for closed pipeline {}",
+                        source_pipeline_id
+                    ),
+                }
+            },
             // Handle a joint session history length request.
             FromScriptMsg::JointSessionHistoryLength(sender) => {
                 self.handle_joint_session_history_length(source_top_ctx_id, sender);
@@ -1862,6 +2004,10 @@ where
             FromScriptMsg::ActivateDocument => {
                 self.handle_activate_document_msg(source_pipeline_id);
             },
+            // Timing of the unload event of a document that was navigated away from
+            FromScriptMsg::DocumentUnloaded(start, end) => {
+                self.handle_document_unloaded_msg(source_pipeline_id, start, end);
+            },
             // Update pipeline url after redirections
             FromScriptMsg::SetFinalUrl(final_url) => {
                 // The script may have finished loading after we already started shutting down.
@@ -1892,6 +2038,9 @@ where
             FromScriptMsg::VisibilityChangeComplete(is_visible) => {
                 self.handle_visibility_change_complete(source_pipeline_id, is_visible);
             },
+            FromScriptMsg::IFrameViewportDistances(distances) => {
+                self.handle_iframe_viewport_distances(source_top_ctx_id, distances);
+            },
             FromScriptMsg::RemoveIFrame(browsing_context_id, sender) => {
                 let removed_pipeline_ids = self.handle_remove_iframe_msg(browsing_context_id);
                 if let Err(e) = sender.send(removed_pipeline_ids) {
@@ -1916,12 +2065,38 @@ where
                 self.compositor_proxy
                     .send(ToCompositorMsg::GetScreenAvailSize(send));
             },
+            FromScriptMsg::GetScreenOrientationAngle(send) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::GetScreenOrientationAngle(send));
+            },
             FromScriptMsg::LogEntry(thread_name, entry) => {
                 self.handle_log_entry(Some(source_top_ctx_id), thread_name, entry);
             },
+            FromScriptMsg::GetCrashReports(sender) => {
+                let reports = self.crash_reports.iter().cloned().collect();
+                if let Err(e) = sender.send(reports) {
+                    warn!("Failed to send crash reports ({}).", e);
+                }
+            },
             FromScriptMsg::TouchEventProcessed(result) => self
                 .compositor_proxy
                 .send(ToCompositorMsg::TouchEventProcessed(result)),
+            FromScriptMsg::DoubleTapZoomRect(rect) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::DoubleTapZoomRect(source_pipeline_id, rect)),
+            FromScriptMsg::ScrollSmoothly(scroll_id, position) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::ScrollSmoothly(scroll_id, position)),
+            FromScriptMsg::AbortSmoothScroll(scroll_id) => self
+                .compositor_proxy
+                .send(ToCompositorMsg::AbortSmoothScroll(scroll_id)),
+            FromScriptMsg::PointerLockChanged(locked) => {
+                if locked {
+                    self.pointer_lock_pipeline = Some(source_pipeline_id);
+                } else if self.pointer_lock_pipeline == Some(source_pipeline_id) {
+                    self.pointer_lock_pipeline = None;
+                }
+            },
             FromScriptMsg::GetBrowsingContextInfo(pipeline_id, sender) => {
                 let result = self
                     .pipelines
@@ -2250,6 +2425,14 @@ where
             FromLayoutMsg::ViewportConstrained(pipeline_id, constraints) => {
                 self.handle_viewport_constrained_msg(pipeline_id, constraints);
             },
+            FromLayoutMsg::ScrollbarThumbs(pipeline_id, thumbs) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::ScrollbarThumbs(pipeline_id, thumbs));
+            },
+            FromLayoutMsg::ScrolledFromScript(scroll_state) => {
+                self.compositor_proxy
+                    .send(ToCompositorMsg::ScrolledFromScript(scroll_state));
+            },
         }
     }
 
@@ -2729,14 +2912,42 @@ where
         let (storage_sender, storage_receiver) =
             ipc::channel().expect("Failed to create IPC channel!");
 
+        // The keepalive fetches in flight, like beacons, are given a bounded time to complete,
+        // and the cookies are saved after them.
         debug!("Exiting core resource threads.");
+        let keepalive_timeout =
+            Duration::from_millis(pref!(network.keepalive.shutdown_timeout_ms).max(0) as u64);
         if let Err(e) = self
             .public_resource_threads
-            .send(net_traits::CoreResourceMsg::Exit(core_sender))
+            .send(net_traits::CoreResourceMsg::Exit(
+                keepalive_timeout,
+                core_sender,
+            ))
         {
             warn!("Exit resource thread failed ({})", e);
         }
 
+        // The resource threads of the other profiles save their data too.
+        debug!("Exiting the resource threads of the profiles.");
+        let profile_receivers: Vec<_> = self
+            .profile_resource_threads
+            .values()
+            .map(|(resource_threads, _)| {
+                let (core_sender, core_receiver) =
+                    ipc::channel().expect("Failed to create IPC channel!");
+                let (storage_sender, storage_receiver) =
+                    ipc::channel().expect("Failed to create IPC channel!");
+                let msg = CoreResourceMsg::Exit(keepalive_timeout, core_sender);
+                if let Err(e) = resource_threads.send(msg) {
+                    warn!("Exit resource thread of a profile failed ({})", e);
+                }
+                if let Err(e) = resource_threads.send(StorageThreadMsg::Exit(storage_sender)) {
+                    warn!("Exit storage thread of a profile failed ({})", e);
+                }
+                (core_receiver, storage_receiver)
+            })
+            .collect();
+
         if let Some(ref chan) = self.debugger_chan {
             debugger::shutdown_server(chan);
         }
@@ -2829,6 +3040,14 @@ where
         if let Err(e) = storage_receiver.recv() {
             warn!("Exit storage thread failed ({:?})", e);
         }
+        for (core_receiver, storage_receiver) in profile_receivers {
+            if let Err(e) = core_receiver.recv() {
+                warn!("Exit resource thread of a profile failed ({:?})", e);
+            }
+            if let Err(e) = storage_receiver.recv() {
+                warn!("Exit storage thread of a profile failed ({:?})", e);
+            }
+        }
 
         debug!("Asking compositor to complete shutdown.");
         self.compositor_proxy
@@ -2838,6 +3057,36 @@ where
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("Pipeline {:?} exited.", pipeline_id);
         self.pipelines.remove(&pipeline_id);
+        for resource_threads in self.all_resource_threads() {
+            let msg = CoreResourceMsg::SetContentBlocking(pipeline_id, None);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!(
+                    "Failed to stop the content blocking of a pipeline ({:?}).",
+                    e
+                );
+            }
+            let msg = CoreResourceMsg::SetUserAgentOverride(pipeline_id, None);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!(
+                    "Failed to remove the user agent override of a pipeline ({:?}).",
+                    e
+                );
+            }
+            let msg = CoreResourceMsg::SetNetworkConditions(pipeline_id, None);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!(
+                    "Failed to remove the network conditions of a pipeline ({:?}).",
+                    e
+                );
+            }
+            let msg = CoreResourceMsg::StopObservingCookieChanges(pipeline_id);
+            if let Err(e) = resource_threads.send(msg) {
+                warn!(
+                    "Failed to stop observing the cookie changes of a pipeline ({:?}).",
+                    e
+                );
+            }
+        }
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
@@ -2873,6 +3122,13 @@ where
 
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
 
+        let url = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id))
+            .map(|pipeline| pipeline.url.clone());
+        self.record_crash(reason.clone(), backtrace.clone(), url);
+
         self.embedder_proxy.send((
             Some(top_level_browsing_context_id),
             EmbedderMsg::Panic(reason, backtrace),
@@ -2944,18 +3200,39 @@ where
             (LogEntry::Panic(reason, backtrace), Some(top_level_browsing_context_id)) => {
                 self.handle_panic(top_level_browsing_context_id, reason, Some(backtrace));
             },
-            (LogEntry::Panic(reason, _), _) |
-            (LogEntry::Error(reason), _) |
-            (LogEntry::Warn(reason), _) => {
-                // VecDeque::truncate is unstable
-                if WARNINGS_BUFFER_SIZE <= self.handled_warnings.len() {
-                    self.handled_warnings.pop_front();
-                }
-                self.handled_warnings.push_back((thread_name, reason));
+            (LogEntry::Panic(reason, backtrace), None) => {
+                self.record_crash(reason.clone(), Some(backtrace), None);
+                self.handle_warning(thread_name, reason);
+            },
+            (LogEntry::Error(reason), _) | (LogEntry::Warn(reason), _) => {
+                self.handle_warning(thread_name, reason)
             },
         }
     }
 
+    fn handle_warning(&mut self, thread_name: Option<String>, reason: String) {
+        // VecDeque::truncate is unstable
+        if WARNINGS_BUFFER_SIZE <= self.handled_warnings.len() {
+            self.handled_warnings.pop_front();
+        }
+        self.handled_warnings.push_back((thread_name, reason));
+    }
+
+    fn record_crash(&mut self, reason: String, backtrace: Option<String>, url: Option<ServoUrl>) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64);
+        if CRASH_REPORTS_BUFFER_SIZE <= self.crash_reports.len() {
+            self.crash_reports.pop_front();
+        }
+        self.crash_reports.push_back(CrashReport {
+            reason,
+            backtrace,
+            url,
+            time,
+        });
+    }
+
     fn handle_webvr_events(&mut self, ids: Vec<PipelineId>, events: Vec<WebVREvent>) {
         for id in ids {
             match self.pipelines.get_mut(&id) {
@@ -2996,9 +3273,25 @@ where
             MouseMoveEvent(point, node_address, _) => {
                 MouseMoveEvent(point, node_address, self.pressed_mouse_buttons)
             },
+            PointerLockedMoveEvent(delta, _) => {
+                PointerLockedMoveEvent(delta, self.pressed_mouse_buttons)
+            },
+            DragEvent(event_type, point, node_address, data) => {
+                let data = self.drag_data_for_pipeline(destination_pipeline_id, event_type, data);
+                DragEvent(event_type, point, node_address, data)
+            },
             _ => event,
         };
 
+        // While the pointer is locked, the document that locked it gets the
+        // mouse events wherever the cursor was.
+        let destination_pipeline_id = match event {
+            MouseButtonEvent(..) | PointerLockedMoveEvent(..) => self
+                .pointer_lock_pipeline
+                .unwrap_or(destination_pipeline_id),
+            _ => destination_pipeline_id,
+        };
+
         if let MouseButtonEvent(MouseEventType::Click, ..) = event {
             self.pressed_mouse_buttons = 0;
         }
@@ -3019,11 +3312,135 @@ where
         }
     }
 
+    /// The paths of the files dragged from outside the window are only given
+    /// to the document once they are dropped on it, as files registered with
+    /// the file manager. Until then it only learns about their names.
+    /// <https://html.spec.whatwg.org/multipage/#concept-dnd-p>
+    fn drag_data_for_pipeline(
+        &self,
+        pipeline_id: PipelineId,
+        event_type: DragEventType,
+        data: DragData,
+    ) -> DragData {
+        let DragData {
+            strings,
+            files: dragged,
+        } = data;
+        let mut files = vec![];
+        let mut paths = vec![];
+        for file in dragged {
+            match file {
+                DraggedFile::Path(path) => paths.push(path),
+                file => files.push(file),
+            }
+        }
+        if event_type != DragEventType::Drop {
+            files.extend(paths.into_iter().map(|path| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                DraggedFile::Hidden(name)
+            }));
+            return DragData { strings, files };
+        }
+
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return DragData { strings, files },
+        };
+        let is_private = self
+            .browsing_contexts
+            .get(&pipeline.browsing_context_id)
+            .map_or(false, |ctx| ctx.is_private);
+        let resource_threads =
+            self.resource_threads(pipeline.top_level_browsing_context_id, is_private);
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let msg =
+            FileManagerThreadMsg::AddDroppedFiles(paths, sender, get_blob_origin(&pipeline.url));
+        if let Err(e) = resource_threads.send(CoreResourceMsg::ToFileManager(msg)) {
+            warn!(
+                "Failed to send dropped files to the file manager ({:?}).",
+                e
+            );
+            return DragData { strings, files };
+        }
+        match receiver.recv() {
+            Ok(selected) => files.extend(selected.into_iter().map(DraggedFile::Dropped)),
+            Err(e) => warn!("Failed to register dropped files ({:?}).", e),
+        }
+        DragData { strings, files }
+    }
+
+    /// Starts the resource threads of the profile of a new browser, if no
+    /// browser was created in it yet. Returns the profile that the browser
+    /// is created in, which is none for the profile of the instance.
+    fn start_profile(&mut self, profile: Option<String>) -> Option<String> {
+        let opts = opts::get();
+        let profile = profile.filter(|profile| opts.user_profile.as_ref() != Some(profile))?;
+        if !self.profile_resource_threads.contains_key(&profile) {
+            // Without a config directory, the data of the profile is only kept
+            // in memory.
+            let config_dir = opts
+                .config_dir
+                .as_ref()
+                .and_then(|config_dir| profile_dir(config_dir, &profile));
+            let resource_threads = new_resource_threads(
+                opts.user_agent.clone(),
+                self.devtools_chan.clone(),
+                self.time_profiler_chan.clone(),
+                self.mem_profiler_chan.clone(),
+                self.embedder_proxy.clone(),
+                config_dir,
+                opts.certificate_path.clone(),
+            );
+            self.profile_resource_threads
+                .insert(profile.clone(), resource_threads);
+        }
+        Some(profile)
+    }
+
+    /// The resource threads of the profile of a browser.
+    fn resource_threads(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+    ) -> &ResourceThreads {
+        let profile_resource_threads = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.profile.as_ref())
+            .and_then(|profile| self.profile_resource_threads.get(profile));
+        match (profile_resource_threads, is_private) {
+            (Some((_, private_resource_threads)), true) => private_resource_threads,
+            (Some((public_resource_threads, _)), false) => public_resource_threads,
+            (None, true) => &self.private_resource_threads,
+            (None, false) => &self.public_resource_threads,
+        }
+    }
+
+    /// The resource threads of all the profiles.
+    fn all_resource_threads(&self) -> Vec<&ResourceThreads> {
+        let mut all_resource_threads = vec![
+            &self.public_resource_threads,
+            &self.private_resource_threads,
+        ];
+        for (public_resource_threads, private_resource_threads) in
+            self.profile_resource_threads.values()
+        {
+            all_resource_threads.push(public_resource_threads);
+            all_resource_threads.push(private_resource_threads);
+        }
+        all_resource_threads
+    }
+
     fn handle_new_top_level_browsing_context(
         &mut self,
         url: ServoUrl,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
+        profile: Option<String>,
     ) {
+        let profile = self.start_profile(profile);
         let window_size = self.window_size.initial_viewport;
         let pipeline_id = PipelineId::new();
         let msg = (
@@ -3044,6 +3461,12 @@ where
             Browser {
                 focused_browsing_context_id: browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                content_blocking: true,
+                user_agent_override: None,
+                device_emulation: None,
+                profile,
+                network_conditions: None,
+                frame_throttling_policy: None,
             },
         );
 
@@ -3106,9 +3529,16 @@ where
 
     fn handle_iframe_size_msg(&mut self, iframe_sizes: Vec<IFrameSizeMsg>) {
         for IFrameSizeMsg { data, type_ } in iframe_sizes {
+            let device_pixel_ratio = match self.browsing_contexts.get(&data.id) {
+                Some(browsing_context) => {
+                    self.browser_window_size(browsing_context.top_level_id)
+                        .device_pixel_ratio
+                },
+                None => self.window_size.device_pixel_ratio,
+            };
             let window_size = WindowSizeData {
                 initial_viewport: data.size,
-                device_pixel_ratio: self.window_size.device_pixel_ratio,
+                device_pixel_ratio,
             };
 
             self.resize_browsing_context(window_size, type_, data.id);
@@ -3157,6 +3587,7 @@ where
 
     fn handle_navigate_request(
         &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
         id: PipelineId,
         request_builder: RequestBuilder,
         cancel_chan: IpcReceiver<()>,
@@ -3164,7 +3595,8 @@ where
         let listener = NetworkListener::new(
             request_builder,
             id,
-            self.public_resource_threads.clone(),
+            self.resource_threads(top_level_browsing_context_id, false)
+                .clone(),
             self.network_listener_sender.clone(),
         );
 
@@ -3323,6 +3755,15 @@ where
 
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
+        self.update_content_blocking(new_pipeline_id, top_level_browsing_context_id, is_private);
+        self.update_user_agent_override(new_pipeline_id, top_level_browsing_context_id, is_private);
+        self.update_network_conditions(new_pipeline_id, top_level_browsing_context_id, is_private);
+        self.update_device_emulation(new_pipeline_id, top_level_browsing_context_id);
+        self.update_frame_throttling(
+            new_pipeline_id,
+            browsing_context_id,
+            top_level_browsing_context_id,
+        );
         self.add_pending_change(SessionHistoryChange {
             top_level_browsing_context_id: top_level_browsing_context_id,
             browsing_context_id: browsing_context_id,
@@ -3351,9 +3792,13 @@ where
             new_pipeline_id,
         } = load_info;
 
-        let (script_sender, opener_browsing_context_id) =
+        let (script_sender, opener_browsing_context_id, opener_top_level_browsing_context_id) =
             match self.pipelines.get(&opener_pipeline_id) {
-                Some(pipeline) => (pipeline.event_loop.clone(), pipeline.browsing_context_id),
+                Some(pipeline) => (
+                    pipeline.event_loop.clone(),
+                    pipeline.browsing_context_id,
+                    pipeline.top_level_browsing_context_id,
+                ),
                 None => {
                     return warn!(
                         "Auxiliary loaded url in closed iframe {}.",
@@ -3383,6 +3828,19 @@ where
             load_data,
         );
 
+        // Auxiliaries present themselves as the same user agent as their
+        // opener, share its profile, and emulate the same network conditions.
+        let (user_agent_override, profile, network_conditions) = self
+            .browsers
+            .get(&opener_top_level_browsing_context_id)
+            .map_or((None, None, None), |browser| {
+                (
+                    browser.user_agent_override.clone(),
+                    browser.profile.clone(),
+                    browser.network_conditions,
+                )
+            });
+
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
         self.browsers.insert(
@@ -3390,8 +3848,29 @@ where
             Browser {
                 focused_browsing_context_id: new_browsing_context_id,
                 session_history: JointSessionHistory::new(),
+                content_blocking: true,
+                user_agent_override,
+                device_emulation: None,
+                profile,
+                network_conditions,
+                frame_throttling_policy: None,
             },
         );
+        self.update_content_blocking(
+            new_pipeline_id,
+            new_top_level_browsing_context_id,
+            is_opener_private,
+        );
+        self.update_user_agent_override(
+            new_pipeline_id,
+            new_top_level_browsing_context_id,
+            is_opener_private,
+        );
+        self.update_network_conditions(
+            new_pipeline_id,
+            new_top_level_browsing_context_id,
+            is_opener_private,
+        );
 
         // https://html.spec.whatwg.org/multipage/#bcg-append
         let opener = match self.browsing_contexts.get(&opener_browsing_context_id) {
@@ -3465,6 +3944,15 @@ where
                     },
                 }
             },
+            AnimationTickType::Worker => {
+                let msg = ConstellationControlMsg::TickWorkerAnimationFrames(pipeline_id);
+                match self.pipelines.get(&pipeline_id) {
+                    Some(pipeline) => pipeline.event_loop.send(msg),
+                    None => {
+                        return warn!("Pipeline {:?} got worker tick after closure.", pipeline_id);
+                    },
+                }
+            },
             AnimationTickType::Layout => match self.pipelines.get(&pipeline_id) {
                 Some(pipeline) => {
                     let msg = LayoutControlMsg::TickAnimations(pipeline.load_data.url.origin());
@@ -3726,25 +4214,35 @@ where
         new_url: ServoUrl,
         replacement_enabled: HistoryEntryReplacement,
     ) {
-        let (top_level_browsing_context_id, old_url) = match self.pipelines.get_mut(&pipeline_id) {
-            Some(pipeline) => {
-                let old_url = replace(&mut pipeline.url, new_url.clone());
-                (pipeline.top_level_browsing_context_id, old_url)
-            },
-            None => {
-                return warn!(
-                    "Pipeline {} navigated to fragment after closure",
-                    pipeline_id
-                );
-            },
-        };
+        let (top_level_browsing_context_id, old_url, user_state) =
+            match self.pipelines.get_mut(&pipeline_id) {
+                Some(pipeline) => {
+                    let old_url = replace(&mut pipeline.url, new_url.clone());
+                    (
+                        pipeline.top_level_browsing_context_id,
+                        old_url,
+                        pipeline.persisted_user_state.clone(),
+                    )
+                },
+                None => {
+                    return warn!(
+                        "Pipeline {} navigated to fragment after closure",
+                        pipeline_id
+                    );
+                },
+            };
 
         match replacement_enabled {
             HistoryEntryReplacement::Disabled => {
+                // The new entry starts out with the scroll restoration mode of
+                // the old one, the rest of its state is persisted when the
+                // document leaves it.
                 let diff = SessionHistoryDiff::HashDiff {
                     pipeline_reloader: NeedsToReload::No(pipeline_id),
                     new_url,
                     old_url,
+                    new_user_state: user_state.clone(),
+                    old_user_state: user_state,
                 };
                 self.get_joint_session_history(top_level_browsing_context_id)
                     .push_diff(diff);
@@ -3760,8 +4258,20 @@ where
         direction: TraversalDirection,
     ) {
         let mut browsing_context_changes = HashMap::<BrowsingContextId, NeedsToReload>::new();
-        let mut pipeline_changes = HashMap::<PipelineId, (Option<HistoryStateId>, ServoUrl)>::new();
-        let mut url_to_load = HashMap::<PipelineId, ServoUrl>::new();
+        let mut pipeline_changes =
+            HashMap::<PipelineId, (Option<HistoryStateId>, ServoUrl, PersistedUserState)>::new();
+        let mut url_to_load = HashMap::<PipelineId, (ServoUrl, PersistedUserState)>::new();
+
+        // The state of the entries that the documents leave, which is persisted
+        // in the first diff of each document that is traversed.
+        let mut user_states_to_persist: HashMap<PipelineId, PersistedUserState> = self
+            .pipelines
+            .iter()
+            .filter(|(_, pipeline)| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|(id, pipeline)| (*id, pipeline.persisted_user_state.clone()))
+            .collect();
         {
             let session_history = self.get_joint_session_history(top_level_browsing_context_id);
             match direction {
@@ -3772,7 +4282,7 @@ where
                         return warn!("Cannot traverse that far into the future.");
                     }
 
-                    for diff in session_history
+                    for mut diff in session_history
                         .future
                         .drain(future_length - forward..)
                         .rev()
@@ -3790,31 +4300,58 @@ where
                                 ref pipeline_reloader,
                                 new_history_state_id,
                                 ref new_url,
+                                ref mut old_user_state,
+                                ref new_user_state,
                                 ..
                             } => match *pipeline_reloader {
                                 NeedsToReload::No(pipeline_id) => {
+                                    if let Some(user_state) =
+                                        user_states_to_persist.remove(&pipeline_id)
+                                    {
+                                        *old_user_state = user_state;
+                                    }
                                     pipeline_changes.insert(
                                         pipeline_id,
-                                        (Some(new_history_state_id), new_url.clone()),
+                                        (
+                                            Some(new_history_state_id),
+                                            new_url.clone(),
+                                            new_user_state.clone(),
+                                        ),
                                     );
                                 },
                                 NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, new_url.clone());
+                                    url_to_load.insert(
+                                        pipeline_id,
+                                        (new_url.clone(), new_user_state.clone()),
+                                    );
                                 },
                             },
                             SessionHistoryDiff::HashDiff {
                                 ref pipeline_reloader,
                                 ref new_url,
+                                ref mut old_user_state,
+                                ref new_user_state,
                                 ..
                             } => match *pipeline_reloader {
                                 NeedsToReload::No(pipeline_id) => {
+                                    if let Some(user_state) =
+                                        user_states_to_persist.remove(&pipeline_id)
+                                    {
+                                        *old_user_state = user_state;
+                                    }
                                     let state = pipeline_changes
                                         .get(&pipeline_id)
                                         .and_then(|change| change.0);
-                                    pipeline_changes.insert(pipeline_id, (state, new_url.clone()));
+                                    pipeline_changes.insert(
+                                        pipeline_id,
+                                        (state, new_url.clone(), new_user_state.clone()),
+                                    );
                                 },
                                 NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, new_url.clone());
+                                    url_to_load.insert(
+                                        pipeline_id,
+                                        (new_url.clone(), new_user_state.clone()),
+                                    );
                                 },
                             },
                         }
@@ -3828,7 +4365,7 @@ where
                         return warn!("Cannot traverse that far into the past.");
                     }
 
-                    for diff in session_history.past.drain(past_length - back..).rev() {
+                    for mut diff in session_history.past.drain(past_length - back..).rev() {
                         match diff {
                             SessionHistoryDiff::BrowsingContextDiff {
                                 browsing_context_id,
@@ -3842,31 +4379,58 @@ where
                                 ref pipeline_reloader,
                                 old_history_state_id,
                                 ref old_url,
+                                ref old_user_state,
+                                ref mut new_user_state,
                                 ..
                             } => match *pipeline_reloader {
                                 NeedsToReload::No(pipeline_id) => {
+                                    if let Some(user_state) =
+                                        user_states_to_persist.remove(&pipeline_id)
+                                    {
+                                        *new_user_state = user_state;
+                                    }
                                     pipeline_changes.insert(
                                         pipeline_id,
-                                        (old_history_state_id, old_url.clone()),
+                                        (
+                                            old_history_state_id,
+                                            old_url.clone(),
+                                            old_user_state.clone(),
+                                        ),
                                     );
                                 },
                                 NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, old_url.clone());
+                                    url_to_load.insert(
+                                        pipeline_id,
+                                        (old_url.clone(), old_user_state.clone()),
+                                    );
                                 },
                             },
                             SessionHistoryDiff::HashDiff {
                                 ref pipeline_reloader,
                                 ref old_url,
+                                ref old_user_state,
+                                ref mut new_user_state,
                                 ..
                             } => match *pipeline_reloader {
                                 NeedsToReload::No(pipeline_id) => {
+                                    if let Some(user_state) =
+                                        user_states_to_persist.remove(&pipeline_id)
+                                    {
+                                        *new_user_state = user_state;
+                                    }
                                     let state = pipeline_changes
                                         .get(&pipeline_id)
                                         .and_then(|change| change.0);
-                                    pipeline_changes.insert(pipeline_id, (state, old_url.clone()));
+                                    pipeline_changes.insert(
+                                        pipeline_id,
+                                        (state, old_url.clone(), old_user_state.clone()),
+                                    );
                                 },
                                 NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, old_url.clone());
+                                    url_to_load.insert(
+                                        pipeline_id,
+                                        (old_url.clone(), old_user_state.clone()),
+                                    );
                                 },
                             },
                         }
@@ -3877,16 +4441,26 @@ where
         }
 
         for (browsing_context_id, mut pipeline_reloader) in browsing_context_changes.drain() {
+            if let NeedsToReload::No(pipeline_id) = pipeline_reloader {
+                pipeline_reloader = self.restore_document(pipeline_id);
+                if let NeedsToReload::Yes(..) = pipeline_reloader {
+                    if let Some((_, url, user_state)) = pipeline_changes.remove(&pipeline_id) {
+                        url_to_load.insert(pipeline_id, (url, user_state));
+                    }
+                }
+            }
             if let NeedsToReload::Yes(pipeline_id, ref mut load_data) = pipeline_reloader {
-                if let Some(url) = url_to_load.get(&pipeline_id) {
-                    load_data.url = url.clone();
+                if let Some((url, user_state)) = url_to_load.remove(&pipeline_id) {
+                    load_data.url = url;
+                    load_data.persisted_user_state = Some(user_state);
                 }
+                load_data.navigation_type = NavigationType::BackForward;
             }
             self.update_browsing_context(browsing_context_id, pipeline_reloader);
         }
 
-        for (pipeline_id, (history_state_id, url)) in pipeline_changes.drain() {
-            self.update_pipeline(pipeline_id, history_state_id, url);
+        for (pipeline_id, (history_state_id, url, user_state)) in pipeline_changes.drain() {
+            self.update_pipeline(pipeline_id, history_state_id, url, user_state);
         }
 
         self.notify_history_changed(top_level_browsing_context_id);
@@ -4015,6 +4589,7 @@ where
         pipeline_id: PipelineId,
         history_state_id: Option<HistoryStateId>,
         url: ServoUrl,
+        user_state: PersistedUserState,
     ) {
         let result = match self.pipelines.get_mut(&pipeline_id) {
             None => {
@@ -4028,9 +4603,11 @@ where
                     pipeline_id,
                     history_state_id,
                     url.clone(),
+                    user_state.clone(),
                 );
                 pipeline.history_state_id = history_state_id;
                 pipeline.url = url;
+                pipeline.persisted_user_state = user_state;
                 pipeline.event_loop.send(msg)
             },
         };
@@ -4058,7 +4635,7 @@ where
         history_state_id: HistoryStateId,
         url: ServoUrl,
     ) {
-        let (top_level_browsing_context_id, old_state_id, old_url) =
+        let (top_level_browsing_context_id, old_state_id, old_url, user_state) =
             match self.pipelines.get_mut(&pipeline_id) {
                 Some(pipeline) => {
                     let old_history_state_id = pipeline.history_state_id;
@@ -4069,6 +4646,7 @@ where
                         pipeline.top_level_browsing_context_id,
                         old_history_state_id,
                         old_url,
+                        pipeline.persisted_user_state.clone(),
                     )
                 },
                 None => {
@@ -4085,6 +4663,8 @@ where
             new_url: url,
             old_history_state_id: old_state_id,
             old_url: old_url,
+            new_user_state: user_state.clone(),
+            old_user_state: user_state,
         };
         self.get_joint_session_history(top_level_browsing_context_id)
             .push_diff(diff);
@@ -4895,6 +5475,7 @@ where
                 Some(pipeline) => {
                     let mut load_data = pipeline.load_data.clone();
                     load_data.url = pipeline.url.clone();
+                    load_data.persisted_user_state = Some(pipeline.persisted_user_state.clone());
                     load_data
                 },
                 None => continue,
@@ -4959,6 +5540,39 @@ where
         }
     }
 
+    /// The unload event timing of a document is reported to the document that replaced it in
+    /// its browsing context, if both have the same origin.
+    /// <https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventstart>
+    fn handle_document_unloaded_msg(&mut self, pipeline_id: PipelineId, start: u64, end: u64) {
+        let (browsing_context_id, origin) = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => (pipeline.browsing_context_id, pipeline.url.origin()),
+            None => return warn!("Unloaded document {} after closure.", pipeline_id),
+        };
+        let new_pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context.pipeline_id,
+            None => {
+                return warn!(
+                    "Unloaded document {} after browsing context {} closure.",
+                    pipeline_id, browsing_context_id
+                );
+            },
+        };
+        if new_pipeline_id == pipeline_id {
+            return;
+        }
+        let result = match self.pipelines.get(&new_pipeline_id) {
+            Some(pipeline) if pipeline.url.origin() == origin => {
+                let msg =
+                    ConstellationControlMsg::SetUnloadEventTiming(new_pipeline_id, start, end);
+                pipeline.event_loop.send(msg)
+            },
+            _ => return,
+        };
+        if let Err(e) = result {
+            self.handle_send_error(new_pipeline_id, e);
+        }
+    }
+
     /// Called when the window is resized.
     fn handle_window_size_msg(
         &mut self,
@@ -4971,16 +5585,33 @@ where
             new_size.initial_viewport.to_untyped()
         );
 
+        self.window_size = new_size;
+
         if let Some(top_level_browsing_context_id) = top_level_browsing_context_id {
             let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-            self.resize_browsing_context(new_size, size_type, browsing_context_id);
+            let browser_size = self.browser_window_size(top_level_browsing_context_id);
+            self.resize_browsing_context(browser_size, size_type, browsing_context_id);
         }
 
         if let Some(resize_channel) = self.webdriver.resize_channel.take() {
             let _ = resize_channel.send(new_size);
         }
+    }
 
-        self.window_size = new_size;
+    /// The size of the window as the pipelines of a browser see it, which is
+    /// the one of the device it emulates, if any.
+    fn browser_window_size(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> WindowSizeData {
+        match self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.device_emulation.as_ref())
+        {
+            Some(emulation) => emulation.window_size(self.window_size),
+            None => self.window_size,
+        }
     }
 
     /// Called when the window exits from fullscreen mode
@@ -5228,18 +5859,44 @@ where
         }
     }
 
-    // Handle switching from fullscreen mode
-    fn switch_fullscreen_mode(&mut self, browsing_context_id: BrowsingContextId) {
-        if let Some(browsing_context) = self.browsing_contexts.get(&browsing_context_id) {
-            let pipeline_id = browsing_context.pipeline_id;
-            let pipeline = match self.pipelines.get(&pipeline_id) {
-                None => {
-                    return warn!(
-                        "Pipeline {:?} switched from fullscreen mode after closing.",
-                        pipeline_id
-                    )
-                },
-                Some(pipeline) => pipeline,
+    fn handle_prefetch_msg(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        url: ServoUrl,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id));
+        let pipeline = match pipeline {
+            Some(pipeline) => pipeline,
+            None => {
+                return warn!(
+                    "Prefetch for unknown browsing context: {:?}",
+                    top_level_browsing_context_id
+                )
+            },
+        };
+        let pipeline_id = pipeline.id;
+        let msg = ConstellationControlMsg::Prefetch(pipeline_id, url);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    // Handle switching from fullscreen mode
+    fn switch_fullscreen_mode(&mut self, browsing_context_id: BrowsingContextId) {
+        if let Some(browsing_context) = self.browsing_contexts.get(&browsing_context_id) {
+            let pipeline_id = browsing_context.pipeline_id;
+            let pipeline = match self.pipelines.get(&pipeline_id) {
+                None => {
+                    return warn!(
+                        "Pipeline {:?} switched from fullscreen mode after closing.",
+                        pipeline_id
+                    )
+                },
+                Some(pipeline) => pipeline,
             };
             let _ = pipeline
                 .event_loop
@@ -5315,28 +5972,56 @@ where
         debug!("Closed browsing context children {}.", browsing_context_id);
     }
 
+    // Keep an unloaded document in the back-forward cache if it is salvageable,
+    // discard it otherwise.
+    fn handle_document_salvageable(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_id: PipelineId,
+        salvageable: bool,
+    ) {
+        if !salvageable {
+            self.handle_discard_document(top_level_browsing_context_id, pipeline_id);
+            return;
+        }
+        if let Some(pipeline) = self.pipelines.get_mut(&pipeline_id) {
+            pipeline.awaiting_unload = false;
+        }
+    }
+
     // Discard the pipeline for a given document, udpdate the joint session history.
+    // Returns the data to load the document again with, if it wasn't closed already.
     fn handle_discard_document(
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
         pipeline_id: PipelineId,
-    ) {
-        match self.browsers.get_mut(&top_level_browsing_context_id) {
+    ) -> Option<LoadData> {
+        let load_data = match self.browsers.get_mut(&top_level_browsing_context_id) {
             Some(browser) => {
                 let load_data = match self.pipelines.get(&pipeline_id) {
-                    Some(pipeline) => pipeline.load_data.clone(),
-                    None => return warn!("Discarding closed pipeline {}", pipeline_id),
+                    Some(pipeline) => {
+                        let mut load_data = pipeline.load_data.clone();
+                        load_data.persisted_user_state =
+                            Some(pipeline.persisted_user_state.clone());
+                        load_data
+                    },
+                    None => {
+                        warn!("Discarding closed pipeline {}", pipeline_id);
+                        return None;
+                    },
                 };
                 browser.session_history.replace_reloader(
                     NeedsToReload::No(pipeline_id),
-                    NeedsToReload::Yes(pipeline_id, load_data),
+                    NeedsToReload::Yes(pipeline_id, load_data.clone()),
                 );
+                load_data
             },
             None => {
-                return warn!(
+                warn!(
                     "Discarding pipeline {} after browser {} closure",
                     pipeline_id, top_level_browsing_context_id,
                 );
+                return None;
             },
         };
         self.close_pipeline(
@@ -5344,11 +6029,31 @@ where
             DiscardBrowsingContext::No,
             ExitPipelineMode::Normal,
         );
+        Some(load_data)
+    }
+
+    // Take a document out of the back-forward cache to show it again. If script
+    // hasn't reported yet that the document is salvageable once unloaded, it
+    // is discarded and has to be loaded again instead.
+    fn restore_document(&mut self, pipeline_id: PipelineId) -> NeedsToReload {
+        let top_level_browsing_context_id = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) if pipeline.awaiting_unload => pipeline.top_level_browsing_context_id,
+            _ => return NeedsToReload::No(pipeline_id),
+        };
+        debug!(
+            "Discarding document {} not known to be salvageable.",
+            pipeline_id
+        );
+        match self.handle_discard_document(top_level_browsing_context_id, pipeline_id) {
+            Some(load_data) => NeedsToReload::Yes(pipeline_id, load_data),
+            None => NeedsToReload::No(pipeline_id),
+        }
     }
 
     // Send a message to script requesting the document associated with this pipeline runs the 'unload' algorithm.
-    fn unload_document(&self, pipeline_id: PipelineId) {
-        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+    fn unload_document(&mut self, pipeline_id: PipelineId) {
+        if let Some(pipeline) = self.pipelines.get_mut(&pipeline_id) {
+            pipeline.awaiting_unload = true;
             let msg = ConstellationControlMsg::UnloadDocument(pipeline_id);
             let _ = pipeline.event_loop.send(msg);
         }
@@ -5363,6 +6068,16 @@ where
     ) {
         debug!("Closing pipeline {:?}.", pipeline_id);
 
+        if self.pointer_lock_pipeline == Some(pipeline_id) {
+            self.pointer_lock_pipeline = None;
+            let top_level_browsing_context_id = self
+                .pipelines
+                .get(&pipeline_id)
+                .map(|pipeline| pipeline.top_level_browsing_context_id);
+            self.embedder_proxy
+                .send((top_level_browsing_context_id, EmbedderMsg::UnlockPointer));
+        }
+
         // Sever connection to browsing context
         let browsing_context_id = self
             .pipelines
@@ -5468,6 +6183,12 @@ where
             .or_insert_with(|| Browser {
                 focused_browsing_context_id: BrowsingContextId::from(top_level_id),
                 session_history: JointSessionHistory::new(),
+                content_blocking: true,
+                user_agent_override: None,
+                device_emulation: None,
+                profile: None,
+                network_conditions: None,
+                frame_throttling_policy: None,
             })
             .session_history
     }
@@ -5556,4 +6277,612 @@ where
             error!("Got a media session action but no active media session is registered");
         }
     }
+
+    /// The event loops of all pipelines, each with one of the pipelines that run in it.
+    /// Script threads are shared between pipelines, so this is how each of them is told
+    /// about something once.
+    fn event_loops(&self) -> Vec<(PipelineId, Rc<EventLoop>)> {
+        let mut event_loops: Vec<(PipelineId, Rc<EventLoop>)> = vec![];
+        for (pipeline_id, pipeline) in &self.pipelines {
+            if !event_loops
+                .iter()
+                .any(|(_, event_loop)| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push((*pipeline_id, pipeline.event_loop.clone()));
+            }
+        }
+        event_loops
+    }
+
+    fn handle_preference_changed(&mut self, name: String, value: PrefValue) {
+        for (pipeline_id, event_loop) in self.event_loops() {
+            let msg = ConstellationControlMsg::SetPreference(name.clone(), value.clone());
+            if let Err(e) = event_loop.send(msg) {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    fn handle_network_status_changed(&mut self, status: NetworkStatus) {
+        if status == self.network_status {
+            return;
+        }
+        self.network_status = status;
+        for (pipeline_id, event_loop) in self.event_loops() {
+            let msg = ConstellationControlMsg::SetNetworkStatus(status);
+            if let Err(e) = event_loop.send(msg) {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Sends a reading of the sensors of the device to the event loops of the
+    /// active browser, since only its documents can use the sensors.
+    fn handle_device_sensor_reading(&mut self, reading: DeviceSensorReading) {
+        let top_level_browsing_context_id = match self.active_browser_id {
+            Some(top_level_browsing_context_id) => top_level_browsing_context_id,
+            None => return,
+        };
+        let mut event_loops: Vec<(PipelineId, Rc<EventLoop>)> = vec![];
+        for (pipeline_id, pipeline) in &self.pipelines {
+            if pipeline.top_level_browsing_context_id != top_level_browsing_context_id {
+                continue;
+            }
+            if !event_loops
+                .iter()
+                .any(|(_, event_loop)| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                event_loops.push((*pipeline_id, pipeline.event_loop.clone()));
+            }
+        }
+        for (pipeline_id, event_loop) in event_loops {
+            let msg = ConstellationControlMsg::DeviceSensorReading(
+                top_level_browsing_context_id,
+                reading,
+            );
+            if let Err(e) = event_loop.send(msg) {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    /// Tells the resource threads whether the content blocker filters the
+    /// requests of a pipeline, which depends on the setting of its browser.
+    fn update_content_blocking(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+    ) {
+        let enabled = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .map_or(true, |browser| browser.content_blocking);
+        let resource_threads = self.resource_threads(top_level_browsing_context_id, is_private);
+        let msg = CoreResourceMsg::SetContentBlocking(
+            pipeline_id,
+            Some(top_level_browsing_context_id).filter(|_| enabled),
+        );
+        if let Err(e) = resource_threads.send(msg) {
+            warn!(
+                "Failed to update the content blocking of a pipeline ({:?}).",
+                e
+            );
+        }
+    }
+
+    fn handle_set_content_blocking(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        enabled: bool,
+    ) {
+        match self.browsers.get_mut(&top_level_browsing_context_id) {
+            Some(browser) if browser.content_blocking != enabled => {
+                browser.content_blocking = enabled;
+            },
+            Some(_) => return,
+            None => {
+                return warn!(
+                    "Browser {} got content blocking change after closure.",
+                    top_level_browsing_context_id
+                );
+            },
+        }
+        let pipelines: Vec<_> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| {
+                let is_private = self
+                    .browsing_contexts
+                    .get(&pipeline.browsing_context_id)
+                    .map_or(false, |ctx| ctx.is_private);
+                (pipeline.id, is_private)
+            })
+            .collect();
+        for (pipeline_id, is_private) in pipelines {
+            self.update_content_blocking(pipeline_id, top_level_browsing_context_id, is_private);
+        }
+    }
+
+    /// Tells the resource threads and the script thread of a new pipeline
+    /// which user agent it presents itself as, if its browser overrides it.
+    fn update_user_agent_override(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+    ) {
+        let ua_override = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.user_agent_override.clone());
+        if ua_override.is_some() {
+            self.send_user_agent_override(
+                pipeline_id,
+                top_level_browsing_context_id,
+                is_private,
+                ua_override,
+            );
+        }
+    }
+
+    fn send_user_agent_override(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+        ua_override: Option<UserAgentOverride>,
+    ) {
+        let resource_threads = self.resource_threads(top_level_browsing_context_id, is_private);
+        let msg = CoreResourceMsg::SetUserAgentOverride(pipeline_id, ua_override.clone());
+        if let Err(e) = resource_threads.send(msg) {
+            warn!(
+                "Failed to update the user agent override of a pipeline ({:?}).",
+                e
+            );
+        }
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            let msg = ConstellationControlMsg::SetUserAgentOverride(pipeline_id, ua_override);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "Failed to update the user agent override of a pipeline ({:?}).",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Tells the resource threads of a new pipeline under which network
+    /// conditions its requests are made, if its browser emulates some.
+    fn update_network_conditions(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+    ) {
+        let conditions = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.network_conditions);
+        if conditions.is_some() {
+            self.send_network_conditions(
+                pipeline_id,
+                top_level_browsing_context_id,
+                is_private,
+                conditions,
+            );
+        }
+    }
+
+    fn send_network_conditions(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_private: bool,
+        conditions: Option<NetworkConditions>,
+    ) {
+        let resource_threads = self.resource_threads(top_level_browsing_context_id, is_private);
+        let msg = CoreResourceMsg::SetNetworkConditions(pipeline_id, conditions);
+        if let Err(e) = resource_threads.send(msg) {
+            warn!(
+                "Failed to update the network conditions of a pipeline ({:?}).",
+                e
+            );
+        }
+    }
+
+    /// Tells the script thread of a new pipeline which device it emulates, if
+    /// its browser emulates one.
+    fn update_device_emulation(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let emulation = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.device_emulation.clone());
+        if emulation.is_some() {
+            self.send_device_emulation(pipeline_id, emulation);
+        }
+    }
+
+    fn send_device_emulation(&self, pipeline_id: PipelineId, emulation: Option<DeviceEmulation>) {
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            let msg = ConstellationControlMsg::SetDeviceEmulation(pipeline_id, emulation);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "Failed to update the device emulation of a pipeline ({:?}).",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Makes a browser emulate a device, or stop emulating one, which resizes
+    /// its top-level browsing context but not the window.
+    fn handle_set_device_emulation(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        emulation: Option<DeviceEmulation>,
+    ) {
+        match self.browsers.get_mut(&top_level_browsing_context_id) {
+            Some(browser) => browser.device_emulation = emulation.clone(),
+            None => {
+                return warn!(
+                    "Browser {} got device emulation after closure.",
+                    top_level_browsing_context_id
+                );
+            },
+        }
+        let pipeline_ids: Vec<_> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in pipeline_ids {
+            self.send_device_emulation(pipeline_id, emulation.clone());
+        }
+        let browser_size = self.browser_window_size(top_level_browsing_context_id);
+        self.resize_browsing_context(
+            browser_size,
+            WindowSizeType::Resize,
+            BrowsingContextId::from(top_level_browsing_context_id),
+        );
+    }
+
+    /// Tells the script thread of a new pipeline how it is throttled, if its
+    /// browsing context is throttled.
+    fn update_frame_throttling(
+        &self,
+        pipeline_id: PipelineId,
+        browsing_context_id: BrowsingContextId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let is_throttled = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .map_or(false, |ctx| ctx.is_throttled);
+        if !is_throttled {
+            return;
+        }
+        let policy = self
+            .browsers
+            .get(&top_level_browsing_context_id)
+            .and_then(|browser| browser.frame_throttling_policy);
+        if policy.is_some() {
+            self.send_frame_throttling(pipeline_id, policy);
+        }
+    }
+
+    fn send_frame_throttling(
+        &self,
+        pipeline_id: PipelineId,
+        policy: Option<FrameThrottlingPolicy>,
+    ) {
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            let msg = ConstellationControlMsg::SetFrameThrottling(pipeline_id, policy);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!("Failed to update the throttling of a pipeline ({:?}).", e);
+            }
+        }
+    }
+
+    fn handle_set_frame_throttling_policy(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        policy: Option<FrameThrottlingPolicy>,
+    ) {
+        let policy_changed = match self.browsers.get_mut(&top_level_browsing_context_id) {
+            Some(browser) => {
+                let old_policy = replace(&mut browser.frame_throttling_policy, policy);
+                old_policy != policy
+            },
+            None => {
+                return warn!(
+                    "Browser {} got frame throttling policy after closure.",
+                    top_level_browsing_context_id
+                );
+            },
+        };
+        self.throttle_frames(top_level_browsing_context_id, policy_changed);
+    }
+
+    fn handle_iframe_viewport_distances(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        distances: Vec<(BrowsingContextId, f32)>,
+    ) {
+        for (browsing_context_id, distance) in distances {
+            match self.browsing_contexts.get_mut(&browsing_context_id) {
+                Some(browsing_context) => browsing_context.viewport_distance = distance,
+                None => warn!(
+                    "Viewport distance of closed browsing context {}.",
+                    browsing_context_id
+                ),
+            }
+        }
+        self.throttle_frames(top_level_browsing_context_id, false);
+    }
+
+    /// Throttles the frames of a browser that are too far from the viewport
+    /// of their parent, or nested in such a frame, and unthrottles the others,
+    /// then tells the embedder where the frames of the browser are. If the
+    /// policy of the browser changed, the frames that stay throttled are told
+    /// about the new one.
+    fn throttle_frames(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        policy_changed: bool,
+    ) {
+        let policy = match self.browsers.get(&top_level_browsing_context_id) {
+            Some(browser) => browser.frame_throttling_policy,
+            None => return,
+        };
+
+        // The iterator is depth-first, so the parent of a browsing context
+        // is always visited before it.
+        let mut throttled = HashSet::new();
+        let mut frames = vec![];
+        for browsing_context in
+            self.fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+        {
+            let parent_id = match browsing_context.parent_pipeline_id {
+                Some(parent_pipeline_id) => self
+                    .pipelines
+                    .get(&parent_pipeline_id)
+                    .map(|pipeline| pipeline.browsing_context_id),
+                None => continue,
+            };
+            let is_throttled = policy.map_or(false, |policy| {
+                browsing_context.viewport_distance > policy.viewport_margin ||
+                    parent_id.map_or(false, |parent_id| throttled.contains(&parent_id))
+            });
+            if is_throttled {
+                throttled.insert(browsing_context.id);
+            }
+            frames.push(FrameProximity {
+                browsing_context_id: browsing_context.id,
+                pipeline_id: browsing_context.pipeline_id,
+                distance: browsing_context.viewport_distance,
+                throttled: is_throttled,
+            });
+        }
+
+        let mut pipeline_ids = HashSet::new();
+        for frame in &frames {
+            let browsing_context = match self.browsing_contexts.get_mut(&frame.browsing_context_id)
+            {
+                Some(browsing_context) => browsing_context,
+                None => continue,
+            };
+            let changed = browsing_context.is_throttled != frame.throttled;
+            browsing_context.is_throttled = frame.throttled;
+            if changed || (frame.throttled && policy_changed) {
+                pipeline_ids.extend(browsing_context.pipelines.iter().cloned());
+            }
+        }
+        for pipeline_id in pipeline_ids {
+            let is_throttled = self
+                .pipelines
+                .get(&pipeline_id)
+                .and_then(|pipeline| self.browsing_contexts.get(&pipeline.browsing_context_id))
+                .map_or(false, |ctx| ctx.is_throttled);
+            self.send_frame_throttling(pipeline_id, policy.filter(|_| is_throttled));
+        }
+
+        let msg = EmbedderMsg::FrameProximityChanged(frames);
+        self.embedder_proxy
+            .send((Some(top_level_browsing_context_id), msg));
+    }
+
+    fn handle_set_user_agent_override(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        ua_override: Option<UserAgentOverride>,
+    ) {
+        match self.browsers.get_mut(&top_level_browsing_context_id) {
+            Some(browser) => browser.user_agent_override = ua_override.clone(),
+            None => {
+                return warn!(
+                    "Browser {} got user agent override after closure.",
+                    top_level_browsing_context_id
+                );
+            },
+        }
+        let pipelines: Vec<_> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| {
+                let is_private = self
+                    .browsing_contexts
+                    .get(&pipeline.browsing_context_id)
+                    .map_or(false, |ctx| ctx.is_private);
+                (pipeline.id, is_private)
+            })
+            .collect();
+        for (pipeline_id, is_private) in pipelines {
+            self.send_user_agent_override(
+                pipeline_id,
+                top_level_browsing_context_id,
+                is_private,
+                ua_override.clone(),
+            );
+        }
+    }
+
+    fn handle_set_network_conditions(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        conditions: Option<NetworkConditions>,
+    ) {
+        match self.browsers.get_mut(&top_level_browsing_context_id) {
+            Some(browser) => browser.network_conditions = conditions,
+            None => {
+                return warn!(
+                    "Browser {} got network conditions after closure.",
+                    top_level_browsing_context_id
+                );
+            },
+        }
+        let pipelines: Vec<_> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| {
+                let is_private = self
+                    .browsing_contexts
+                    .get(&pipeline.browsing_context_id)
+                    .map_or(false, |ctx| ctx.is_private);
+                (pipeline.id, is_private)
+            })
+            .collect();
+        for (pipeline_id, is_private) in pipelines {
+            self.send_network_conditions(
+                pipeline_id,
+                top_level_browsing_context_id,
+                is_private,
+                conditions,
+            );
+        }
+    }
+
+    /// Takes a screenshot of an area of a browser. The compositor takes the viewport and the
+    /// rectangles itself, while the script thread of the current document finds the elements
+    /// and the size of the page.
+    fn handle_take_screenshot(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        id: u32,
+        area: ScreenshotArea,
+        format: ScreenshotFormat,
+    ) {
+        let clip = match area {
+            ScreenshotArea::Viewport => ScreenshotClip::Viewport,
+            ScreenshotArea::Rect(rect) => ScreenshotClip::Rect(rect),
+            ScreenshotArea::FullPage | ScreenshotArea::Element(_) => {
+                return self.request_screenshot_rect(
+                    top_level_browsing_context_id,
+                    id,
+                    area,
+                    format,
+                );
+            },
+        };
+        self.compositor_proxy.send(ToCompositorMsg::TakeScreenshot(
+            top_level_browsing_context_id,
+            id,
+            clip,
+            format,
+        ));
+    }
+
+    fn request_screenshot_rect(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        id: u32,
+        area: ScreenshotArea,
+        format: ScreenshotFormat,
+    ) {
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id));
+        let pipeline = match pipeline {
+            Some(pipeline) => pipeline,
+            None => {
+                warn!(
+                    "Screenshot of closed browser {}.",
+                    top_level_browsing_context_id
+                );
+                return self.handle_screenshot_taken(
+                    top_level_browsing_context_id,
+                    id,
+                    Err(ScreenshotError::Failed),
+                );
+            },
+        };
+
+        let full_page = area == ScreenshotArea::FullPage;
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let compositor_proxy = self.compositor_proxy.clone();
+        let embedder_proxy = self.embedder_proxy.clone();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let rect: Option<Rect<f32, CSSPixel>> = message.to().unwrap_or(None);
+                let clip = match rect {
+                    Some(rect) if full_page => ScreenshotClip::Page(rect.size),
+                    Some(rect) => ScreenshotClip::Rect(rect),
+                    None => {
+                        let error = if full_page {
+                            ScreenshotError::Failed
+                        } else {
+                            ScreenshotError::NoSuchElement
+                        };
+                        return embedder_proxy.send((
+                            Some(top_level_browsing_context_id),
+                            EmbedderMsg::ScreenshotTaken(id, Err(error)),
+                        ));
+                    },
+                };
+                compositor_proxy.send(ToCompositorMsg::TakeScreenshot(
+                    top_level_browsing_context_id,
+                    id,
+                    clip,
+                    format,
+                ));
+            }),
+        );
+
+        let pipeline_id = pipeline.id;
+        let msg = ConstellationControlMsg::GetScreenshotRect(pipeline_id, area, sender);
+        if let Err(e) = pipeline.event_loop.send(msg) {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_screenshot_taken(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        id: u32,
+        result: Result<Screenshot, ScreenshotError>,
+    ) {
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::ScreenshotTaken(id, result),
+        ));
+    }
 }
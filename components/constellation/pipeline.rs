@@ -35,7 +35,7 @@ use script_traits::{
 };
 use script_traits::{DocumentActivity, InitialScriptState};
 use script_traits::{LayoutControlMsg, LayoutMsg, LoadData};
-use script_traits::{NewLayoutInfo, SWManagerMsg, SWManagerSenders};
+use script_traits::{NewLayoutInfo, PersistedUserState, SWManagerMsg, SWManagerSenders};
 use script_traits::{ScriptThreadFactory, TimerSchedulerMsg, WindowSizeData};
 use servo_config::opts::{self, Opts};
 use servo_config::{prefs, prefs::PrefValue};
@@ -96,8 +96,16 @@ pub struct Pipeline {
     /// The history states owned by this pipeline.
     pub history_states: HashSet<HistoryStateId>,
 
+    /// The state to persist in the current session history entry of this
+    /// pipeline when the document leaves it, as last reported by script.
+    pub persisted_user_state: PersistedUserState,
+
     /// Has this pipeline received a notification that it is completely loaded?
     pub completely_loaded: bool,
+
+    /// Whether script was asked to unload the document of this pipeline and
+    /// hasn't reported yet whether it can be kept in the back-forward cache.
+    pub awaiting_unload: bool,
 }
 
 /// Initial setup data needed to construct a pipeline.
@@ -373,6 +381,7 @@ impl Pipeline {
         is_visible: bool,
         load_data: LoadData,
     ) -> Pipeline {
+        let persisted_user_state = load_data.persisted_user_state.clone().unwrap_or_default();
         let pipeline = Pipeline {
             id: id,
             browsing_context_id: browsing_context_id,
@@ -387,7 +396,9 @@ impl Pipeline {
             load_data: load_data,
             history_state_id: None,
             history_states: HashSet::new(),
+            persisted_user_state,
             completely_loaded: false,
+            awaiting_unload: false,
         };
 
         pipeline.notify_visibility(is_visible);
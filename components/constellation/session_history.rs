@@ -7,7 +7,7 @@ use euclid::Size2D;
 use msg::constellation_msg::{
     BrowsingContextId, HistoryStateId, PipelineId, TopLevelBrowsingContextId,
 };
-use script_traits::LoadData;
+use script_traits::{LoadData, PersistedUserState};
 use servo_url::ServoUrl;
 use std::cmp::PartialEq;
 use std::{fmt, mem};
@@ -197,11 +197,17 @@ pub enum SessionHistoryDiff {
         new_history_state_id: HistoryStateId,
         /// The new url
         new_url: ServoUrl,
+        /// The persisted state of the old entry.
+        old_user_state: PersistedUserState,
+        /// The persisted state of the new entry.
+        new_user_state: PersistedUserState,
     },
     HashDiff {
         pipeline_reloader: NeedsToReload,
         old_url: ServoUrl,
         new_url: ServoUrl,
+        old_user_state: PersistedUserState,
+        new_user_state: PersistedUserState,
     },
 }
 
@@ -140,6 +140,14 @@ impl ActorRegistry {
         actors.push(actor);
     }
 
+    /// Add the actors of `register_later` to the registry of known actors.
+    pub fn register_pending_actors(&mut self) {
+        let new_actors = replace(&mut *self.new_actors.borrow_mut(), vec![]);
+        for actor in new_actors.into_iter() {
+            self.actors.insert(actor.name().to_owned(), actor);
+        }
+    }
+
     /// Find an actor by registered name
     pub fn find<'a, T: Any>(&'a self, name: &str) -> &'a T {
         let actor = self.actors.get(name).unwrap();
@@ -175,10 +183,7 @@ impl ActorRegistry {
                 }
             },
         }
-        let new_actors = replace(&mut *self.new_actors.borrow_mut(), vec![]);
-        for actor in new_actors.into_iter() {
-            self.actors.insert(actor.name().to_owned(), actor);
-        }
+        self.register_pending_actors();
 
         let old_actors = replace(&mut *self.old_actors.borrow_mut(), vec![]);
         for name in old_actors {
@@ -8,18 +8,16 @@
 //! inspection, JS evaluation, autocompletion) in Servo.
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
-use crate::actors::object::ObjectActor;
+use crate::actors::object::grip;
 use crate::protocol::JsonPacketStream;
 use crate::{ConsoleAPICall, ConsoleMessage, ConsoleMsg, PageErrorMsg};
 use devtools_traits::CachedConsoleMessage;
-use devtools_traits::EvaluateJSReply::{ActorValue, BooleanValue, StringValue};
-use devtools_traits::EvaluateJSReply::{NullValue, NumberValue, VoidValue};
 use devtools_traits::{
     CachedConsoleMessageTypes, ConsoleAPI, DevtoolScriptControlMsg, LogLevel, PageError,
 };
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
-use serde_json::{self, Map, Number, Value};
+use serde_json::{self, Map, Value};
 use std::cell::RefCell;
 use std::net::TcpStream;
 use time::precise_time_ns;
@@ -128,55 +126,12 @@ impl ConsoleActor {
             ))
             .unwrap();
 
-        //TODO: extract conversion into protocol module or some other useful place
-        let result = match port.recv().map_err(|_| ())? {
-            VoidValue => {
-                let mut m = Map::new();
-                m.insert("type".to_owned(), Value::String("undefined".to_owned()));
-                Value::Object(m)
-            },
-            NullValue => {
-                let mut m = Map::new();
-                m.insert("type".to_owned(), Value::String("null".to_owned()));
-                Value::Object(m)
-            },
-            BooleanValue(val) => Value::Bool(val),
-            NumberValue(val) => {
-                if val.is_nan() {
-                    let mut m = Map::new();
-                    m.insert("type".to_owned(), Value::String("NaN".to_owned()));
-                    Value::Object(m)
-                } else if val.is_infinite() {
-                    let mut m = Map::new();
-                    if val < 0. {
-                        m.insert("type".to_owned(), Value::String("-Infinity".to_owned()));
-                    } else {
-                        m.insert("type".to_owned(), Value::String("Infinity".to_owned()));
-                    }
-                    Value::Object(m)
-                } else if val == 0. && val.is_sign_negative() {
-                    let mut m = Map::new();
-                    m.insert("type".to_owned(), Value::String("-0".to_owned()));
-                    Value::Object(m)
-                } else {
-                    Value::Number(Number::from_f64(val).unwrap())
-                }
-            },
-            StringValue(s) => Value::String(s),
-            ActorValue { class, uuid } => {
-                //TODO: make initial ActorValue message include these properties?
-                let mut m = Map::new();
-                let actor = ObjectActor::new(registry, uuid);
-
-                m.insert("type".to_owned(), Value::String("object".to_owned()));
-                m.insert("class".to_owned(), Value::String(class));
-                m.insert("actor".to_owned(), Value::String(actor));
-                m.insert("extensible".to_owned(), Value::Bool(true));
-                m.insert("frozen".to_owned(), Value::Bool(false));
-                m.insert("sealed".to_owned(), Value::Bool(false));
-                Value::Object(m)
-            },
-        };
+        let result = grip(
+            registry,
+            port.recv().map_err(|_| ())?,
+            self.pipeline,
+            &self.script_chan,
+        );
 
         //TODO: catch and return exception values from JS evaluation
         let reply = EvaluateJSReply {
@@ -205,13 +160,26 @@ impl ConsoleActor {
         }
     }
 
-    pub(crate) fn handle_console_api(&self, console_message: ConsoleMessage) {
+    pub(crate) fn handle_console_api(
+        &self,
+        registry: &ActorRegistry,
+        console_message: ConsoleMessage,
+    ) {
         let level = match console_message.logLevel {
+            LogLevel::Log => "log",
             LogLevel::Debug => "debug",
             LogLevel::Info => "info",
             LogLevel::Warn => "warn",
             LogLevel::Error => "error",
-            _ => "log",
+            LogLevel::Dir => "dir",
+            LogLevel::Table => "table",
+            LogLevel::Count => "count",
+            LogLevel::Group => "group",
+            LogLevel::GroupCollapsed => "groupCollapsed",
+            LogLevel::GroupEnd => "groupEnd",
+            LogLevel::Time => "time",
+            LogLevel::TimeLog => "timeLog",
+            LogLevel::TimeEnd => "timeEnd",
         }
         .to_owned();
         self.cached_events
@@ -221,21 +189,37 @@ impl ConsoleActor {
                 level: level.clone(),
                 filename: console_message.filename.clone(),
                 lineNumber: console_message.lineNumber as u32,
-                functionName: "".to_string(), //TODO
+                functionName: console_message
+                    .stacktrace
+                    .as_ref()
+                    .and_then(|frames| frames.first())
+                    .map_or(String::new(), |frame| frame.functionName.clone()),
                 timeStamp: precise_time_ns(),
                 private: false,
                 arguments: vec![console_message.message.clone()],
             }));
+        // Messages without values, like the ones of the console API methods
+        // that only take a label, are shown as their text.
+        let arguments = if console_message.arguments.is_empty() {
+            vec![Value::String(console_message.message)]
+        } else {
+            console_message
+                .arguments
+                .into_iter()
+                .map(|argument| grip(registry, argument, self.pipeline, &self.script_chan))
+                .collect()
+        };
         let msg = ConsoleAPICall {
             from: self.name(),
             type_: "consoleAPICall".to_owned(),
             message: ConsoleMsg {
                 level: level,
                 timeStamp: precise_time_ns(),
-                arguments: vec![console_message.message],
+                arguments,
                 filename: console_message.filename,
                 lineNumber: console_message.lineNumber,
                 columnNumber: console_message.columnNumber,
+                stacktrace: console_message.stacktrace,
             },
         };
         for stream in &mut *self.streams.borrow_mut() {
@@ -3,12 +3,36 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
-use serde_json::{Map, Value};
+use crate::protocol::JsonPacketStream;
+use devtools_traits::EvaluateJSReply::{ActorValue, BooleanValue, StringValue};
+use devtools_traits::EvaluateJSReply::{NullValue, NumberValue, VoidValue};
+use devtools_traits::{DevtoolScriptControlMsg, EvaluateJSReply};
+use ipc_channel::ipc::{self, IpcSender};
+use msg::constellation_msg::PipelineId;
+use serde_json::{Map, Number, Value};
 use std::net::TcpStream;
 
+#[derive(Serialize)]
+struct PropertyDescriptor {
+    value: Value,
+    configurable: bool,
+    enumerable: bool,
+    writable: bool,
+}
+
+#[derive(Serialize)]
+struct PrototypeAndPropertiesReply {
+    from: String,
+    prototype: Value,
+    ownProperties: Map<String, Value>,
+    safeGetterValues: Map<String, Value>,
+}
+
 pub struct ObjectActor {
     pub name: String,
     pub uuid: String,
+    pub pipeline: PipelineId,
+    pub script_chan: IpcSender<DevtoolScriptControlMsg>,
 }
 
 impl Actor for ObjectActor {
@@ -17,22 +41,65 @@ impl Actor for ObjectActor {
     }
     fn handle_message(
         &self,
-        _: &ActorRegistry,
-        _: &str,
-        _: &Map<String, Value>,
-        _: &mut TcpStream,
+        registry: &ActorRegistry,
+        msg_type: &str,
+        _msg: &Map<String, Value>,
+        stream: &mut TcpStream,
     ) -> Result<ActorMessageStatus, ()> {
-        Ok(ActorMessageStatus::Ignored)
+        Ok(match msg_type {
+            "prototypeAndProperties" => {
+                let (tx, rx) = ipc::channel().map_err(|_| ())?;
+                self.script_chan
+                    .send(DevtoolScriptControlMsg::GetObjectProperties(
+                        self.pipeline,
+                        self.uuid.clone(),
+                        tx,
+                    ))
+                    .map_err(|_| ())?;
+                let properties = rx.recv().map_err(|_| ())?.unwrap_or(vec![]);
+
+                let mut own_properties = Map::new();
+                for (name, value) in properties {
+                    let descriptor = PropertyDescriptor {
+                        value: grip(registry, value, self.pipeline, &self.script_chan),
+                        configurable: true,
+                        enumerable: true,
+                        writable: true,
+                    };
+                    own_properties.insert(name, serde_json::to_value(descriptor).unwrap());
+                }
+                let mut prototype = Map::new();
+                prototype.insert("type".to_owned(), Value::String("null".to_owned()));
+
+                let msg = PrototypeAndPropertiesReply {
+                    from: self.name(),
+                    prototype: Value::Object(prototype),
+                    ownProperties: own_properties,
+                    safeGetterValues: Map::new(),
+                };
+                stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            _ => ActorMessageStatus::Ignored,
+        })
     }
 }
 
 impl ObjectActor {
-    pub fn new(registry: &ActorRegistry, uuid: String) -> String {
+    pub fn new(
+        registry: &ActorRegistry,
+        uuid: String,
+        pipeline: PipelineId,
+        script_chan: IpcSender<DevtoolScriptControlMsg>,
+    ) -> String {
         if !registry.script_actor_registered(uuid.clone()) {
             let name = registry.new_name("object");
             let actor = ObjectActor {
                 name: name.clone(),
                 uuid: uuid.clone(),
+                pipeline,
+                script_chan,
             };
 
             registry.register_script_actor(uuid, name.clone());
@@ -44,3 +111,61 @@ impl ObjectActor {
         }
     }
 }
+
+/// The representation of a JS value in the protocol, with an actor to
+/// inspect it for objects.
+pub fn grip(
+    registry: &ActorRegistry,
+    value: EvaluateJSReply,
+    pipeline: PipelineId,
+    script_chan: &IpcSender<DevtoolScriptControlMsg>,
+) -> Value {
+    match value {
+        VoidValue => {
+            let mut m = Map::new();
+            m.insert("type".to_owned(), Value::String("undefined".to_owned()));
+            Value::Object(m)
+        },
+        NullValue => {
+            let mut m = Map::new();
+            m.insert("type".to_owned(), Value::String("null".to_owned()));
+            Value::Object(m)
+        },
+        BooleanValue(val) => Value::Bool(val),
+        NumberValue(val) => {
+            if val.is_nan() {
+                let mut m = Map::new();
+                m.insert("type".to_owned(), Value::String("NaN".to_owned()));
+                Value::Object(m)
+            } else if val.is_infinite() {
+                let mut m = Map::new();
+                if val < 0. {
+                    m.insert("type".to_owned(), Value::String("-Infinity".to_owned()));
+                } else {
+                    m.insert("type".to_owned(), Value::String("Infinity".to_owned()));
+                }
+                Value::Object(m)
+            } else if val == 0. && val.is_sign_negative() {
+                let mut m = Map::new();
+                m.insert("type".to_owned(), Value::String("-0".to_owned()));
+                Value::Object(m)
+            } else {
+                Value::Number(Number::from_f64(val).unwrap())
+            }
+        },
+        StringValue(s) => Value::String(s),
+        ActorValue { class, uuid } => {
+            //TODO: make initial ActorValue message include these properties?
+            let mut m = Map::new();
+            let actor = ObjectActor::new(registry, uuid, pipeline, script_chan.clone());
+
+            m.insert("type".to_owned(), Value::String("object".to_owned()));
+            m.insert("class".to_owned(), Value::String(class));
+            m.insert("actor".to_owned(), Value::String(actor));
+            m.insert("extensible".to_owned(), Value::Bool(true));
+            m.insert("frozen".to_owned(), Value::Bool(false));
+            m.insert("sealed".to_owned(), Value::Bool(false));
+            Value::Object(m)
+        },
+    }
+}
@@ -35,13 +35,15 @@ use crate::actors::thread::ThreadActor;
 use crate::actors::timeline::TimelineActor;
 use crate::actors::worker::WorkerActor;
 use crate::protocol::JsonPacketStream;
+use crate::source_map::SourceMaps;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::{ChromeToDevtoolsControlMsg, ConsoleMessage, DevtoolsControlMsg};
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo, LogLevel, NetworkEvent};
-use devtools_traits::{PageError, ScriptToDevtoolsControlMsg, WorkerId};
+use devtools_traits::{PageError, ScriptToDevtoolsControlMsg, StackFrame, WorkerId};
 use embedder_traits::{EmbedderMsg, EmbedderProxy, PromptDefinition, PromptOrigin, PromptResult};
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::PipelineId;
+use serde_json::Value;
 use std::borrow::ToOwned;
 use std::cell::RefCell;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
@@ -73,6 +75,7 @@ mod actors {
     pub mod worker;
 }
 mod protocol;
+pub mod source_map;
 
 #[derive(Serialize)]
 struct ConsoleAPICall {
@@ -86,10 +89,11 @@ struct ConsoleAPICall {
 struct ConsoleMsg {
     level: String,
     timeStamp: u64,
-    arguments: Vec<String>,
+    arguments: Vec<Value>,
     filename: String,
     lineNumber: usize,
     columnNumber: usize,
+    stacktrace: Option<Vec<StackFrame>>,
 }
 
 #[derive(Serialize)]
@@ -190,6 +194,8 @@ fn run_server(
 
     let mut actor_workers: HashMap<(PipelineId, WorkerId), String> = HashMap::new();
 
+    let mut source_maps = SourceMaps::default();
+
     /// Process the input from a single devtools client until EOF.
     fn handle_client(actors: Arc<Mutex<ActorRegistry>>, mut stream: TcpStream) {
         debug!("connection established to {}", stream.peer_addr().unwrap());
@@ -373,9 +379,11 @@ fn run_server(
             Some(name) => name,
             None => return,
         };
-        let actors = actors.lock().unwrap();
+        let mut actors = actors.lock().unwrap();
         let console_actor = actors.find::<ConsoleActor>(&console_actor_name);
-        console_actor.handle_console_api(console_message);
+        console_actor.handle_console_api(&actors, console_message);
+        // Inspecting the values of the message needs their object actors.
+        actors.register_pending_actors();
     }
 
     fn find_console_actor(
@@ -603,30 +611,44 @@ fn run_server(
             ),
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ConsoleAPI(
                 id,
-                console_message,
+                mut console_message,
                 worker_id,
-            )) => handle_console_message(
-                actors.clone(),
-                id,
-                worker_id,
-                console_message,
-                &actor_pipelines,
-                &actor_workers,
-            ),
+            )) => {
+                source_maps.resolve_console_message(&mut console_message);
+                handle_console_message(
+                    actors.clone(),
+                    id,
+                    worker_id,
+                    console_message,
+                    &actor_pipelines,
+                    &actor_workers,
+                )
+            },
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ReportPageError(
                 id,
-                page_error,
-            )) => handle_page_error(actors.clone(), id, page_error, &actor_pipelines),
+                mut page_error,
+            )) => {
+                source_maps.resolve_page_error(&mut page_error);
+                handle_page_error(actors.clone(), id, page_error, &actor_pipelines)
+            },
+            DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ReportSourceMap(
+                _,
+                script_url,
+                map_url,
+                contents,
+            )) => source_maps.add(script_url, map_url, &contents),
             DevtoolsControlMsg::FromScript(ScriptToDevtoolsControlMsg::ReportCSSError(
                 id,
                 css_error,
             )) => {
                 let console_message = ConsoleMessage {
                     message: css_error.msg,
+                    arguments: vec![],
                     logLevel: LogLevel::Warn,
                     filename: css_error.filename,
                     lineNumber: css_error.line as usize,
                     columnNumber: css_error.column as usize,
+                    stacktrace: None,
                 };
                 handle_console_message(
                     actors.clone(),
@@ -0,0 +1,218 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Resolution of the locations in generated scripts, like minified ones, to
+//! the locations in their original sources through the source maps of the
+//! scripts.
+//! <https://sourcemaps.info/spec.html>
+
+use devtools_traits::{ConsoleMessage, PageError, StackFrame};
+use serde_json::Value;
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+
+/// A location in an original source, with 0-based line and column numbers.
+#[derive(Clone, Copy)]
+struct OriginalLocation {
+    source: usize,
+    line: u32,
+    column: u32,
+}
+
+/// A segment of the mappings of a generated line.
+struct Mapping {
+    generated_column: u32,
+    original: Option<OriginalLocation>,
+}
+
+struct SourceMap {
+    /// The URLs of the original sources.
+    sources: Vec<String>,
+    /// The mappings of each generated line, in the order of their columns.
+    lines: Vec<Vec<Mapping>>,
+}
+
+impl SourceMap {
+    /// Parses a source map in the version 3 format. Index maps, which are
+    /// made of sections, aren't supported.
+    fn parse(map_url: &ServoUrl, contents: &str) -> Option<SourceMap> {
+        // The map may start with a line that prevents it from running as a script.
+        let contents = if contents.starts_with(")]}'") {
+            contents.splitn(2, '\n').nth(1).unwrap_or("")
+        } else {
+            contents
+        };
+        let map: Value = serde_json::from_str(contents).ok()?;
+        if map.get("version")?.as_u64()? != 3 {
+            return None;
+        }
+
+        let source_root = map.get("sourceRoot").and_then(Value::as_str).unwrap_or("");
+        let sources = map
+            .get("sources")?
+            .as_array()?
+            .iter()
+            .map(|source| {
+                let source = format!("{}{}", source_root, source.as_str().unwrap_or(""));
+                map_url
+                    .join(&source)
+                    .map(|url| url.into_string())
+                    .unwrap_or(source)
+            })
+            .collect::<Vec<_>>();
+
+        let mut lines = vec![];
+        // The fields of the segments are relative to the ones of the previous
+        // segment, except for the generated column that is reset on every line.
+        let mut source = 0i64;
+        let mut original_line = 0i64;
+        let mut original_column = 0i64;
+        for line in map.get("mappings")?.as_str()?.split(';') {
+            let mut generated_column = 0i64;
+            let mut mappings = vec![];
+            for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+                let fields = decode_vlq(segment)?;
+                generated_column += *fields.first()?;
+                let original = if fields.len() >= 4 {
+                    source += fields[1];
+                    original_line += fields[2];
+                    original_column += fields[3];
+                    if source < 0 || source as usize >= sources.len() {
+                        return None;
+                    }
+                    Some(OriginalLocation {
+                        source: source as usize,
+                        line: original_line.max(0) as u32,
+                        column: original_column.max(0) as u32,
+                    })
+                } else {
+                    None
+                };
+                mappings.push(Mapping {
+                    generated_column: generated_column.max(0) as u32,
+                    original,
+                });
+            }
+            mappings.sort_by_key(|mapping| mapping.generated_column);
+            lines.push(mappings);
+        }
+
+        Some(SourceMap { sources, lines })
+    }
+
+    /// The original location of a 1-based location in the generated script.
+    fn resolve(&self, line: u32, column: u32) -> Option<(&str, u32, u32)> {
+        let mappings = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let index = match mappings.binary_search_by_key(&column, |mapping| mapping.generated_column)
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let original = mappings[index].original?;
+        Some((
+            &self.sources[original.source],
+            original.line + 1,
+            original.column + 1,
+        ))
+    }
+}
+
+/// Decodes the Base64 VLQ fields of a segment of mappings.
+fn decode_vlq(segment: &str) -> Option<Vec<i64>> {
+    let mut fields = vec![];
+    let mut value = 0i64;
+    let mut shift = 0;
+    for byte in segment.bytes() {
+        let digit = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as i64;
+        if shift > 60 {
+            return None;
+        }
+        value += (digit & 0b11111) << shift;
+        if digit & 0b100000 != 0 {
+            shift += 5;
+            continue;
+        }
+        // The lowest bit of the value is its sign.
+        let magnitude = value >> 1;
+        fields.push(if value & 1 == 1 {
+            -magnitude
+        } else {
+            magnitude
+        });
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 {
+        return None;
+    }
+    Some(fields)
+}
+
+/// The source maps of the scripts that have one, by URL of the script.
+#[derive(Default)]
+pub struct SourceMaps {
+    maps: HashMap<String, SourceMap>,
+}
+
+impl SourceMaps {
+    pub fn add(&mut self, script_url: ServoUrl, map_url: ServoUrl, contents: &str) {
+        match SourceMap::parse(&map_url, contents) {
+            Some(map) => {
+                self.maps.insert(script_url.into_string(), map);
+            },
+            None => warn!("Invalid source map {} of {}", map_url, script_url),
+        }
+    }
+
+    /// Replaces a 1-based location in a generated script by the one in its
+    /// original source, if the script has a source map.
+    fn resolve(&self, filename: &mut String, line: &mut u32, column: &mut u32) {
+        let resolved = self
+            .maps
+            .get(&*filename)
+            .and_then(|map| map.resolve(*line, *column));
+        if let Some((source, original_line, original_column)) = resolved {
+            *filename = source.to_owned();
+            *line = original_line;
+            *column = original_column;
+        }
+    }
+
+    fn resolve_stack_trace(&self, frames: &mut Option<Vec<StackFrame>>) {
+        for frame in frames.iter_mut().flatten() {
+            self.resolve(
+                &mut frame.filename,
+                &mut frame.lineNumber,
+                &mut frame.columnNumber,
+            );
+        }
+    }
+
+    pub fn resolve_page_error(&self, page_error: &mut PageError) {
+        self.resolve(
+            &mut page_error.sourceName,
+            &mut page_error.lineNumber,
+            &mut page_error.columnNumber,
+        );
+        self.resolve_stack_trace(&mut page_error.stacktrace);
+    }
+
+    pub fn resolve_console_message(&self, console_message: &mut ConsoleMessage) {
+        let mut line = console_message.lineNumber as u32;
+        let mut column = console_message.columnNumber as u32;
+        self.resolve(&mut console_message.filename, &mut line, &mut column);
+        console_message.lineNumber = line as usize;
+        console_message.columnNumber = column as usize;
+        self.resolve_stack_trace(&mut console_message.stacktrace);
+    }
+}
@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use devtools::source_map::SourceMaps;
+use devtools_traits::{PageError, StackFrame};
+use servo_url::ServoUrl;
+
+const SCRIPT: &str = "https://example.com/min.js";
+
+fn source_maps(contents: &str) -> SourceMaps {
+    let mut maps = SourceMaps::default();
+    maps.add(
+        ServoUrl::parse(SCRIPT).unwrap(),
+        ServoUrl::parse("https://example.com/maps/min.js.map").unwrap(),
+        contents,
+    );
+    maps
+}
+
+fn map(mappings: &str) -> String {
+    format!(
+        "{{\"version\": 3, \"sources\": [\"a.js\", \"b.js\"], \"mappings\": \"{}\"}}",
+        mappings
+    )
+}
+
+/// Resolves a 1-based location in the script through the maps.
+fn resolve(maps: &SourceMaps, line: u32, column: u32) -> (String, u32, u32) {
+    let mut page_error = PageError {
+        type_: "PageError".to_owned(),
+        errorMessage: "error".to_owned(),
+        sourceName: SCRIPT.to_owned(),
+        lineText: "".to_owned(),
+        lineNumber: line,
+        columnNumber: column,
+        category: "script".to_owned(),
+        timeStamp: 0,
+        error: true,
+        warning: false,
+        exception: true,
+        strict: false,
+        private: false,
+        stacktrace: None,
+    };
+    maps.resolve_page_error(&mut page_error);
+    (
+        page_error.sourceName,
+        page_error.lineNumber,
+        page_error.columnNumber,
+    )
+}
+
+fn unresolved(line: u32, column: u32) -> (String, u32, u32) {
+    (SCRIPT.to_owned(), line, column)
+}
+
+fn original(source: &str, line: u32, column: u32) -> (String, u32, u32) {
+    (format!("https://example.com/maps/{}", source), line, column)
+}
+
+#[test]
+fn test_source_map_resolves_locations() {
+    // Line 1: column 0 maps to a.js 1:1, column 4 to a.js 1:5.
+    // Line 2: column 0 maps to the next line of a.js.
+    // Line 3: column 0 maps to b.js, one line up and one column right.
+    let maps = source_maps(&map("AAAA,IAAI;AACA;ACDC"));
+    assert_eq!(resolve(&maps, 1, 1), original("a.js", 1, 1));
+    assert_eq!(resolve(&maps, 1, 4), original("a.js", 1, 1));
+    assert_eq!(resolve(&maps, 1, 5), original("a.js", 1, 5));
+    assert_eq!(resolve(&maps, 1, 100), original("a.js", 1, 5));
+    assert_eq!(resolve(&maps, 2, 1), original("a.js", 2, 5));
+    assert_eq!(resolve(&maps, 3, 1), original("b.js", 1, 6));
+}
+
+#[test]
+fn test_source_map_leaves_unmapped_locations() {
+    // Generated column 16 is the first mapped one of line 1, and line 2 has no
+    // mappings at all.
+    let maps = source_maps(&map("gBAAA;"));
+    assert_eq!(resolve(&maps, 1, 17), original("a.js", 1, 1));
+    assert_eq!(resolve(&maps, 1, 16), unresolved(1, 16));
+    assert_eq!(resolve(&maps, 2, 1), unresolved(2, 1));
+    assert_eq!(resolve(&maps, 3, 1), unresolved(3, 1));
+    assert_eq!(resolve(&maps, 0, 0), unresolved(0, 0));
+}
+
+#[test]
+fn test_source_map_segments_without_original_location() {
+    // A one-field segment ends the mapping that the segment before started.
+    let maps = source_maps(&map("AAAA,I"));
+    assert_eq!(resolve(&maps, 1, 1), original("a.js", 1, 1));
+    assert_eq!(resolve(&maps, 1, 5), unresolved(1, 5));
+}
+
+#[test]
+fn test_source_map_vlq_continuation_and_sign() {
+    // "gB" is 16 and "hB" is -16, which brings the original line back to 1.
+    let maps = source_maps(&map("AAgBA;AAhBA"));
+    assert_eq!(resolve(&maps, 1, 1), original("a.js", 17, 1));
+    assert_eq!(resolve(&maps, 2, 1), original("a.js", 1, 1));
+}
+
+#[test]
+fn test_source_map_rejects_invalid_vlq() {
+    // A truncated continuation, a character outside of Base64, and more
+    // continuation digits than fit in the value.
+    for mappings in &["AAAA,g", "AA*A", "ggggggggggggggA"] {
+        let maps = source_maps(&map(mappings));
+        assert_eq!(resolve(&maps, 1, 1), unresolved(1, 1), "{}", mappings);
+    }
+}
+
+#[test]
+fn test_source_map_rejects_malformed_maps() {
+    let maps = [
+        "not json".to_owned(),
+        "{\"version\": 2, \"sources\": [\"a.js\"], \"mappings\": \"AAAA\"}".to_owned(),
+        "{\"sources\": [\"a.js\"], \"mappings\": \"AAAA\"}".to_owned(),
+        "{\"version\": 3, \"mappings\": \"AAAA\"}".to_owned(),
+        "{\"version\": 3, \"sources\": [\"a.js\"]}".to_owned(),
+        // The source index goes past the end of the sources, then below zero.
+        map("AEAA"),
+        map("ADAA"),
+    ];
+    for contents in maps.iter() {
+        let maps = source_maps(contents);
+        assert_eq!(resolve(&maps, 1, 1), unresolved(1, 1), "{}", contents);
+    }
+}
+
+#[test]
+fn test_source_map_with_xssi_prefix_and_source_root() {
+    let maps = source_maps(
+        ")]}'\n{\"version\": 3, \"sourceRoot\": \"/src/\", \"sources\": [\"a.js\"], \"mappings\": \"AAAA\"}",
+    );
+    assert_eq!(
+        resolve(&maps, 1, 1),
+        ("https://example.com/src/a.js".to_owned(), 1, 1)
+    );
+}
+
+#[test]
+fn test_source_map_resolves_stack_frames() {
+    let maps = source_maps(&map("AAAA;AACA"));
+    let frame = |filename: &str, line| StackFrame {
+        filename: filename.to_owned(),
+        functionName: "f".to_owned(),
+        lineNumber: line,
+        columnNumber: 1,
+    };
+    let mut page_error = PageError {
+        type_: "PageError".to_owned(),
+        errorMessage: "error".to_owned(),
+        sourceName: SCRIPT.to_owned(),
+        lineText: "".to_owned(),
+        lineNumber: 2,
+        columnNumber: 1,
+        category: "script".to_owned(),
+        timeStamp: 0,
+        error: true,
+        warning: false,
+        exception: true,
+        strict: false,
+        private: false,
+        stacktrace: Some(vec![
+            frame(SCRIPT, 2),
+            frame("https://example.com/other.js", 2),
+        ]),
+    };
+    maps.resolve_page_error(&mut page_error);
+    let frames = page_error.stacktrace.unwrap();
+    assert_eq!(frames[0].filename, "https://example.com/maps/a.js");
+    assert_eq!(frames[0].lineNumber, 2);
+    assert_eq!(frames[1].filename, "https://example.com/other.js");
+    assert_eq!(frames[1].lineNumber, 2);
+}
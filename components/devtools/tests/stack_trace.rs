@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use devtools_traits::{source_mapping_url, StackFrame};
+
+fn frames(stack: &str) -> Vec<(String, String, u32, u32)> {
+    StackFrame::parse_stack(stack)
+        .into_iter()
+        .map(|frame| {
+            (
+                frame.functionName,
+                frame.filename,
+                frame.lineNumber,
+                frame.columnNumber,
+            )
+        })
+        .collect()
+}
+
+fn frame(function: &str, filename: &str, line: u32, column: u32) -> (String, String, u32, u32) {
+    (function.to_owned(), filename.to_owned(), line, column)
+}
+
+#[test]
+fn test_parse_stack() {
+    assert_eq!(
+        frames("inner@https://example.com/a.js:10:5\nouter@https://example.com/b.js:2:1\n"),
+        vec![
+            frame("inner", "https://example.com/a.js", 10, 5),
+            frame("outer", "https://example.com/b.js", 2, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_stack_frame_formats() {
+    assert_eq!(
+        frames(
+            "@http://localhost:8000/a.js:1:1\n\
+             f/<@http://localhost:8000/a.js:3:14\n\
+             g*@file:///tmp/a%40b.js:7:2\n\
+             h@http://localhost:8000/a.js line 2 > eval:1:5"
+        ),
+        vec![
+            frame("", "http://localhost:8000/a.js", 1, 1),
+            frame("f/<", "http://localhost:8000/a.js", 3, 14),
+            frame("g*", "file:///tmp/a%40b.js", 7, 2),
+            frame("h", "http://localhost:8000/a.js line 2 > eval", 1, 5),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_stack_skips_lines_that_are_not_frames() {
+    assert_eq!(
+        frames("\nnot a frame\nf@a.js:x:1\nf@a.js:1\nf@:1:2\nf@a.js:1:2"),
+        vec![frame("f", "", 1, 2), frame("f", "a.js", 1, 2)]
+    );
+    assert!(frames("").is_empty());
+}
+
+#[test]
+fn test_source_mapping_url() {
+    assert_eq!(
+        source_mapping_url("f();\n//# sourceMappingURL=f.js.map\n"),
+        Some("f.js.map")
+    );
+    assert_eq!(
+        source_mapping_url("f();\n//@ sourceMappingURL=f.js.map"),
+        Some("f.js.map")
+    );
+    assert_eq!(
+        source_mapping_url("f();\n  //# sourceMappingURL= f.js.map  \n\n"),
+        Some("f.js.map")
+    );
+}
+
+#[test]
+fn test_source_mapping_url_must_end_the_script() {
+    assert_eq!(
+        source_mapping_url("//# sourceMappingURL=f.js.map\nf();"),
+        None
+    );
+    assert_eq!(source_mapping_url("f();\n//# sourceMappingURL="), None);
+    assert_eq!(
+        source_mapping_url("f(); // sourceMappingURL=f.js.map"),
+        None
+    );
+    assert_eq!(source_mapping_url(""), None);
+}
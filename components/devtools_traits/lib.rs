@@ -86,11 +86,16 @@ pub enum ScriptToDevtoolsControlMsg {
 
     /// Report a page error for the given pipeline
     ReportPageError(PipelineId, PageError),
+
+    /// Report the source map of a script of the given pipeline, with the URLs
+    /// of the script and of the map, and the contents of the map.
+    ReportSourceMap(PipelineId, ServoUrl, ServoUrl, String),
 }
 
-/// Serialized JS return values
-/// TODO: generalize this beyond the EvaluateJS message?
-#[derive(Debug, Deserialize, Serialize)]
+/// Serialized JS values, returned by evaluations and passed to the console API.
+/// Objects are identified by the uuid with which their properties can be
+/// requested from the script thread.
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum EvaluateJSReply {
     VoidValue,
     NullValue,
@@ -217,6 +222,13 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Retrieve the own properties of the object with the given uuid that was
+    /// sent to the devtools by the given pipeline.
+    GetObjectProperties(
+        PipelineId,
+        String,
+        IpcSender<Option<Vec<(String, EvaluateJSReply)>>>,
+    ),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -225,6 +237,8 @@ pub struct Modification {
     pub newValue: Option<String>,
 }
 
+/// The level of a console message, or the console API method that isn't
+/// logging at a level that sent it.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum LogLevel {
     Log,
@@ -232,15 +246,76 @@ pub enum LogLevel {
     Info,
     Warn,
     Error,
+    Dir,
+    Table,
+    Count,
+    Group,
+    GroupCollapsed,
+    GroupEnd,
+    Time,
+    TimeLog,
+    TimeEnd,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ConsoleMessage {
+    /// The message as it is printed.
     pub message: String,
+    /// The values passed to the console API, which can be inspected.
+    pub arguments: Vec<EvaluateJSReply>,
     pub logLevel: LogLevel,
     pub filename: String,
     pub lineNumber: usize,
     pub columnNumber: usize,
+    /// The stack of the call to the console API, innermost frame first.
+    pub stacktrace: Option<Vec<StackFrame>>,
+}
+
+/// A frame of a JS stack trace, with 1-based line and column numbers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StackFrame {
+    pub filename: String,
+    pub functionName: String,
+    pub lineNumber: u32,
+    pub columnNumber: u32,
+}
+
+impl StackFrame {
+    /// Parses a stack in the SpiderMonkey format, which has a
+    /// `function@filename:line:column` line per frame. Lines that aren't
+    /// frames are skipped.
+    pub fn parse_stack(stack: &str) -> Vec<StackFrame> {
+        stack
+            .lines()
+            .filter_map(|frame| {
+                let at = frame.find('@')?;
+                let mut location = frame[at + 1..].rsplitn(3, ':');
+                let column = location.next()?.parse().ok()?;
+                let line = location.next()?.parse().ok()?;
+                Some(StackFrame {
+                    filename: location.next()?.to_owned(),
+                    functionName: frame[..at].to_owned(),
+                    lineNumber: line,
+                    columnNumber: column,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The URL of the `//# sourceMappingURL=` comment that ends a script, if any.
+/// <https://sourcemaps.info/spec.html#h.lmz475t4mvbx>
+pub fn source_mapping_url(source: &str) -> Option<&str> {
+    let line = source.trim_end().lines().last()?.trim();
+    let url = ["//# sourceMappingURL=", "//@ sourceMappingURL="]
+        .iter()
+        .find(|prefix| line.starts_with(*prefix))
+        .map(|prefix| line[prefix.len()..].trim())?;
+    if url.is_empty() {
+        None
+    } else {
+        Some(url)
+    }
 }
 
 bitflags! {
@@ -267,6 +342,8 @@ pub struct PageError {
     pub exception: bool,
     pub strict: bool,
     pub private: bool,
+    /// The stack of the exception, innermost frame first.
+    pub stacktrace: Option<Vec<StackFrame>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -16,10 +16,12 @@ pub mod resources;
 use crossbeam_channel::{Receiver, Sender};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
-use msg::constellation_msg::{InputMethodType, PipelineId, TopLevelBrowsingContextId};
+use msg::constellation_msg::{
+    BrowsingContextId, InputMethodType, PipelineId, TopLevelBrowsingContextId,
+};
 use servo_url::ServoUrl;
 use std::fmt::{Debug, Error, Formatter};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 
 pub use webxr_api::MainThreadWaker as EventLoopWaker;
 
@@ -185,12 +187,46 @@ pub enum EmbedderMsg {
     GetSelectedBluetoothDevice(Vec<String>, IpcSender<Option<String>>),
     /// Open file dialog to select files. Set boolean flag to true allows to select multiple files.
     SelectFiles(Vec<FilterPattern>, bool, IpcSender<Option<Vec<String>>>),
+    /// Open directory dialog to select a directory, of which all the files are uploaded.
+    SelectDirectory(IpcSender<Option<String>>),
     /// Open interface to request permission specified by prompt.
     PromptPermission(PermissionPrompt, IpcSender<PermissionRequest>),
     /// Request to present an IME to the user when an editable element is focused.
     ShowIME(InputMethodType),
     /// Request to hide the IME when the editable element is blurred.
     HideIME,
+    /// Open a picker for the value of a date or time input of the given
+    /// type, with its current value and its minimum and maximum values. The
+    /// embedder replies with the picked value, or `None` if it was dismissed.
+    ShowDateTimePicker(
+        InputMethodType,
+        String,
+        Option<String>,
+        Option<String>,
+        IpcSender<Option<String>>,
+    ),
+    /// Open a color chooser for a color input with its current value, in the
+    /// `#rrggbb` form. The embedder replies with the picked color in the same
+    /// form, or `None` if it was dismissed.
+    ShowColorPicker(String, IpcSender<Option<String>>),
+    /// Show a dropdown with the suggestions of the list of a text input that
+    /// match the value the user typed, below the input at the given rect of
+    /// the viewport. The embedder replies with the picked suggestion, or
+    /// `None` if it was dismissed.
+    ShowDatalistSuggestions(Vec<String>, DeviceIntRect, IpcSender<Option<String>>),
+    /// Hide the dropdown of the suggestions of a text input, because none
+    /// match its value anymore or it lost the focus.
+    HideDatalistSuggestions,
+    /// Show a bubble with the validation message of a form control that
+    /// doesn't satisfy its constraints, next to the control at the given rect
+    /// of the viewport.
+    ShowValidationMessage(String, DeviceIntRect),
+    /// An uncaught error was reported by a script of the page, whether the
+    /// page handled it or not.
+    ReportScriptError(ScriptError),
+    /// The content blocker blocked a request to the given URL, which is the
+    /// given number of requests it blocked in the webview.
+    ContentBlocked(ServoUrl, u32),
     /// Servo has shut down
     Shutdown,
     /// Report a complete sampled profile
@@ -198,6 +234,82 @@ pub enum EmbedderMsg {
     /// Notifies the embedder about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(MediaSessionEvent),
+    /// Request access to the MIDI ports of the system. The boolean is true if
+    /// system exclusive messages were requested. The first channel receives
+    /// the available ports, or `None` if access is refused, and the second one
+    /// is kept to notify about port changes and incoming messages.
+    RequestMidiAccess(
+        bool,
+        IpcSender<Option<Vec<MidiPortInfo>>>,
+        IpcSender<MidiEvent>,
+    ),
+    /// Send a message to the MIDI output port with the given id.
+    SendMidiMessage(String, Vec<u8>),
+    /// Lock the screen to an orientation. The embedder replies whether it
+    /// did, and rotates the screen if needed.
+    LockScreenOrientation(ScreenOrientationLock, IpcSender<bool>),
+    /// Let the screen rotate freely again.
+    UnlockScreenOrientation,
+    /// Hide the cursor and keep it in place, sending the motion of the mouse
+    /// instead. The embedder replies whether it did.
+    LockPointer(IpcSender<bool>),
+    /// Show the cursor and let it move again.
+    UnlockPointer,
+    /// A drag started in a document, with the strings of its data and their
+    /// types. The embedder can hand it over to the system if the pointer
+    /// leaves the window.
+    StartDrag(Vec<(String, String)>),
+    /// The drag that started in a document ended with the given operation.
+    EndDrag(DropEffect),
+    /// What dropping the drag from outside of the window where the pointer is
+    /// would do, to show it to the user.
+    SetDropEffect(DropEffect),
+    /// The screenshot requested with the given id was taken, or couldn't be.
+    ScreenshotTaken(u32, Result<Screenshot, ScreenshotError>),
+    /// A frame of the webview was composited for the frame capture with the
+    /// given id.
+    FrameCaptured(u32, Screenshot),
+    /// Register a new public key credential with an authenticator, for
+    /// `navigator.credentials.create()`. The embedder replies with the
+    /// credential, or with why it couldn't be created.
+    MakeCredential(
+        MakeCredentialRequest,
+        IpcSender<Result<AttestedCredential, AuthenticatorError>>,
+    ),
+    /// Sign a challenge with a public key credential of an authenticator, for
+    /// `navigator.credentials.get()`. The embedder replies with the
+    /// assertion, or with why there is none.
+    GetAssertion(
+        GetAssertionRequest,
+        IpcSender<Result<Assertion, AuthenticatorError>>,
+    ),
+    /// Get the current position of the device, with high accuracy if the
+    /// boolean is true. The embedder replies with the position, or `None` if
+    /// it couldn't be determined.
+    GetPosition(bool, IpcSender<Option<DevicePosition>>),
+    /// Start watching the position of the device, with high accuracy if the
+    /// boolean is true. The embedder sends the current position, and then
+    /// the new one whenever the device moves significantly, until the watch
+    /// is cleared. `None` is sent when the position couldn't be determined.
+    WatchPosition(PositionWatchId, bool, IpcSender<Option<DevicePosition>>),
+    /// Stop sending the positions of the given watch.
+    ClearPositionWatch(PositionWatchId),
+    /// Get the voices that speech can be synthesized with.
+    GetSpeechVoices(IpcSender<Vec<SpeechVoice>>),
+    /// Speak an utterance for the document of the given pipeline, instead
+    /// of the one it was speaking if any. The embedder sends what happens
+    /// to the channel, until the `End` or `Error` event.
+    Speak(PipelineId, SpeechUtterance, IpcSender<SpeechEvent>),
+    /// Pause the speech of the given pipeline.
+    PauseSpeech(PipelineId),
+    /// Resume the speech of the given pipeline.
+    ResumeSpeech(PipelineId),
+    /// Stop the speech of the given pipeline, without any more events.
+    CancelSpeech(PipelineId),
+    /// The frames of the webview got closer to or further from the viewport,
+    /// or some of them got throttled or unthrottled. All of its frames are
+    /// listed.
+    FrameProximityChanged(Vec<FrameProximity>),
 }
 
 impl Debug for EmbedderMsg {
@@ -224,18 +336,276 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::Panic(..) => write!(f, "Panic"),
             EmbedderMsg::GetSelectedBluetoothDevice(..) => write!(f, "GetSelectedBluetoothDevice"),
             EmbedderMsg::SelectFiles(..) => write!(f, "SelectFiles"),
+            EmbedderMsg::SelectDirectory(..) => write!(f, "SelectDirectory"),
             EmbedderMsg::PromptPermission(..) => write!(f, "PromptPermission"),
             EmbedderMsg::ShowIME(..) => write!(f, "ShowIME"),
             EmbedderMsg::HideIME => write!(f, "HideIME"),
+            EmbedderMsg::ShowDateTimePicker(..) => write!(f, "ShowDateTimePicker"),
+            EmbedderMsg::ShowColorPicker(..) => write!(f, "ShowColorPicker"),
+            EmbedderMsg::ShowDatalistSuggestions(..) => write!(f, "ShowDatalistSuggestions"),
+            EmbedderMsg::HideDatalistSuggestions => write!(f, "HideDatalistSuggestions"),
+            EmbedderMsg::ShowValidationMessage(..) => write!(f, "ShowValidationMessage"),
+            EmbedderMsg::ReportScriptError(..) => write!(f, "ReportScriptError"),
+            EmbedderMsg::ContentBlocked(..) => write!(f, "ContentBlocked"),
             EmbedderMsg::Shutdown => write!(f, "Shutdown"),
             EmbedderMsg::AllowOpeningBrowser(..) => write!(f, "AllowOpeningBrowser"),
             EmbedderMsg::BrowserCreated(..) => write!(f, "BrowserCreated"),
             EmbedderMsg::ReportProfile(..) => write!(f, "ReportProfile"),
             EmbedderMsg::MediaSessionEvent(..) => write!(f, "MediaSessionEvent"),
+            EmbedderMsg::RequestMidiAccess(..) => write!(f, "RequestMidiAccess"),
+            EmbedderMsg::SendMidiMessage(..) => write!(f, "SendMidiMessage"),
+            EmbedderMsg::LockScreenOrientation(..) => write!(f, "LockScreenOrientation"),
+            EmbedderMsg::UnlockScreenOrientation => write!(f, "UnlockScreenOrientation"),
+            EmbedderMsg::LockPointer(..) => write!(f, "LockPointer"),
+            EmbedderMsg::UnlockPointer => write!(f, "UnlockPointer"),
+            EmbedderMsg::StartDrag(..) => write!(f, "StartDrag"),
+            EmbedderMsg::EndDrag(..) => write!(f, "EndDrag"),
+            EmbedderMsg::SetDropEffect(..) => write!(f, "SetDropEffect"),
+            EmbedderMsg::ScreenshotTaken(..) => write!(f, "ScreenshotTaken"),
+            EmbedderMsg::FrameCaptured(..) => write!(f, "FrameCaptured"),
+            EmbedderMsg::MakeCredential(..) => write!(f, "MakeCredential"),
+            EmbedderMsg::GetAssertion(..) => write!(f, "GetAssertion"),
+            EmbedderMsg::GetPosition(..) => write!(f, "GetPosition"),
+            EmbedderMsg::WatchPosition(..) => write!(f, "WatchPosition"),
+            EmbedderMsg::ClearPositionWatch(..) => write!(f, "ClearPositionWatch"),
+            EmbedderMsg::GetSpeechVoices(..) => write!(f, "GetSpeechVoices"),
+            EmbedderMsg::Speak(..) => write!(f, "Speak"),
+            EmbedderMsg::PauseSpeech(..) => write!(f, "PauseSpeech"),
+            EmbedderMsg::ResumeSpeech(..) => write!(f, "ResumeSpeech"),
+            EmbedderMsg::CancelSpeech(..) => write!(f, "CancelSpeech"),
+            EmbedderMsg::FrameProximityChanged(..) => write!(f, "FrameProximityChanged"),
         }
     }
 }
 
+/// An uncaught error of a script.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScriptError {
+    pub message: String,
+    pub filename: String,
+    pub line_number: u32,
+    pub column_number: u32,
+    /// Whether the details of the error were hidden from the page, because
+    /// the script was fetched from another origin without CORS.
+    pub muted: bool,
+}
+
+/// The orientations the screen can be locked to.
+/// https://w3c.github.io/screen-orientation/#dom-orientationlocktype
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScreenOrientationLock {
+    Any,
+    Natural,
+    Landscape,
+    Portrait,
+    PortraitPrimary,
+    PortraitSecondary,
+    LandscapePrimary,
+    LandscapeSecondary,
+}
+
+/// The operation of a drag and drop.
+/// <https://html.spec.whatwg.org/multipage/#current-drag-operation>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DropEffect {
+    None,
+    Copy,
+    Link,
+    Move,
+}
+
+impl Default for DropEffect {
+    fn default() -> DropEffect {
+        DropEffect::None
+    }
+}
+
+/// The parameters of the authenticatorMakeCredential operation.
+/// <https://w3c.github.io/webauthn/#sctn-op-make-cred>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MakeCredentialRequest {
+    /// The SHA-256 hash of the client data, which the authenticator signs.
+    pub client_data_hash: Vec<u8>,
+    /// The serialized origin of the caller, to show it to the user.
+    pub origin: String,
+    pub rp_id: String,
+    pub rp_name: String,
+    pub user_id: Vec<u8>,
+    pub user_name: String,
+    pub user_display_name: String,
+    /// The COSE identifiers of the acceptable public key algorithms, from
+    /// the most preferred one.
+    pub algorithms: Vec<i32>,
+    /// The ids of the credentials of the user, which the authenticator
+    /// mustn't already have.
+    pub exclude_credentials: Vec<Vec<u8>>,
+    pub require_resident_key: bool,
+    pub require_user_verification: bool,
+    /// How long to wait for the user, in milliseconds.
+    pub timeout: u64,
+}
+
+/// A credential that was created by an authenticator.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    /// The CBOR attestation object, with the authenticator data that holds
+    /// the public key of the credential.
+    /// <https://w3c.github.io/webauthn/#sctn-attestation>
+    pub attestation_object: Vec<u8>,
+}
+
+/// The parameters of the authenticatorGetAssertion operation.
+/// <https://w3c.github.io/webauthn/#sctn-op-get-assertion>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetAssertionRequest {
+    /// The SHA-256 hash of the client data, which the authenticator signs.
+    pub client_data_hash: Vec<u8>,
+    /// The serialized origin of the caller, to show it to the user.
+    pub origin: String,
+    pub rp_id: String,
+    /// The ids of the credentials that can be used, or none if any
+    /// credential of the relying party can.
+    pub allow_credentials: Vec<Vec<u8>>,
+    pub require_user_verification: bool,
+    /// How long to wait for the user, in milliseconds.
+    pub timeout: u64,
+}
+
+/// A signature of the client data by a credential of an authenticator.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Assertion {
+    pub credential_id: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub user_handle: Option<Vec<u8>>,
+}
+
+/// Why an authenticator operation failed.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum AuthenticatorError {
+    /// The user cancelled or didn't respond in time, or there is no
+    /// credential to use.
+    NotAllowed,
+    /// The authenticator already has one of the excluded credentials.
+    InvalidState,
+    /// The authenticator supports none of the algorithms, or can't do what
+    /// was required, like verifying the user.
+    NotSupported,
+}
+
+/// A position of the device, for the Geolocation API.
+/// <https://w3c.github.io/geolocation-api/#coordinates_interface>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DevicePosition {
+    /// The latitude and longitude in decimal degrees, in the WGS84
+    /// coordinate system.
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The accuracy of the latitude and longitude, in meters.
+    pub accuracy: f64,
+    /// The height above the WGS84 ellipsoid and its accuracy, in meters.
+    pub altitude: Option<f64>,
+    pub altitude_accuracy: Option<f64>,
+    /// The direction of travel in degrees clockwise from the true north.
+    pub heading: Option<f64>,
+    /// The speed in meters per second.
+    pub speed: Option<f64>,
+    /// When the position was acquired, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Identifies a watch of the position of the device by the pipeline of the
+/// document that started it and the id that `watchPosition()` returned.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PositionWatchId(pub PipelineId, pub i32);
+
+/// A voice that speech can be synthesized with.
+/// <https://wicg.github.io/speech-api/#speechsynthesisvoice>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SpeechVoice {
+    /// The URI that identifies the voice.
+    pub uri: String,
+    pub name: String,
+    /// The BCP 47 language tag of the voice.
+    pub lang: String,
+    /// Whether the voice is synthesized without a remote service.
+    pub local: bool,
+    /// Whether the voice is the default one for its language.
+    pub default: bool,
+}
+
+/// Text to speak with a speech synthesizer.
+/// <https://wicg.github.io/speech-api/#speechsynthesisutterance>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechUtterance {
+    pub text: String,
+    /// The BCP 47 language tag of the text.
+    pub lang: String,
+    /// The URI of the voice to speak with, or `None` for the default voice
+    /// of the language.
+    pub voice: Option<String>,
+    /// The volume, between 0 and 1.
+    pub volume: f32,
+    /// The speed relative to the default one, between 0.1 and 10.
+    pub rate: f32,
+    /// The pitch relative to the default one, between 0 and 2.
+    pub pitch: f32,
+}
+
+/// Something that happened while speaking an utterance.
+/// <https://wicg.github.io/speech-api/#speechsynthesisevent>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechEvent {
+    pub kind: SpeechEventKind,
+    /// The range of the text that was being spoken, in UTF-16 code units.
+    pub char_index: u32,
+    pub char_length: u32,
+    /// The time since the utterance started being spoken, in seconds.
+    pub elapsed_time: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechEventKind {
+    Start,
+    End,
+    Error(SpeechError),
+    Pause,
+    Resume,
+    /// An SSML mark tag with the given name was reached.
+    Mark(String),
+    WordBoundary,
+    SentenceBoundary,
+}
+
+/// Why an utterance couldn't be spoken.
+/// <https://wicg.github.io/speech-api/#enumdef-speechsynthesiserrorcode>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechError {
+    AudioBusy,
+    AudioHardware,
+    Network,
+    SynthesisUnavailable,
+    SynthesisFailed,
+    LanguageUnavailable,
+    VoiceUnavailable,
+    TextTooLong,
+    InvalidArgument,
+    NotAllowed,
+}
+
+/// How close a frame of a webview is to the viewport of its parent, and
+/// whether it is throttled because of it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameProximity {
+    pub browsing_context_id: BrowsingContextId,
+    pub pipeline_id: PipelineId,
+    /// The distance from the frame to the viewport of its parent, in CSS
+    /// pixels, which is zero when they intersect.
+    pub distance: f32,
+    pub throttled: bool,
+}
+
 /// Filter for file selection;
 /// the `String` content is expected to be extension (e.g, "doc", without the prefixing ".")
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -303,6 +673,35 @@ pub enum MediaSessionEvent {
     SetPositionState(MediaPositionState),
 }
 
+/// https://webaudio.github.io/web-midi-api/#dom-midiporttype
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum MidiPortType {
+    Input,
+    Output,
+}
+
+/// A MIDI port exposed to content by the embedder.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MidiPortInfo {
+    /// An identifier that is unique among the ports of the system.
+    pub id: String,
+    pub manufacturer: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub port_type: MidiPortType,
+}
+
+/// Type of events sent from the embedder to script about MIDI ports.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum MidiEvent {
+    /// A port became available.
+    PortConnected(MidiPortInfo),
+    /// The port with the given id is no longer available.
+    PortDisconnected(String),
+    /// A message was received on the input port with the given id.
+    Message(String, Vec<u8>),
+}
+
 /// Enum with variants that match the DOM PermissionName enum
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum PermissionName {
@@ -317,6 +716,9 @@ pub enum PermissionName {
     BackgroundSync,
     Bluetooth,
     PersistentStorage,
+    Accelerometer,
+    Gyroscope,
+    Magnetometer,
 }
 
 /// Information required to display a permission prompt
@@ -324,6 +726,9 @@ pub enum PermissionName {
 pub enum PermissionPrompt {
     Insecure(PermissionName),
     Request(PermissionName),
+    /// A third-party document of the given site requests access to its
+    /// cookies while embedded in the current page.
+    StorageAccess(String),
 }
 
 /// Status for prompting user for permission.
@@ -332,3 +737,56 @@ pub enum PermissionRequest {
     Granted,
     Denied,
 }
+
+/// How the pixels of a screenshot are encoded.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScreenshotFormat {
+    /// A PNG file.
+    Png,
+    /// The RGB8 pixels, row by row from the top left corner.
+    Raw,
+}
+
+/// A screenshot of a webview, in device pixels.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub format: ScreenshotFormat,
+    pub data: Vec<u8>,
+}
+
+impl Debug for Screenshot {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(
+            f,
+            "Screenshot({}x{}, {:?}, {} bytes)",
+            self.width,
+            self.height,
+            self.format,
+            self.data.len()
+        )
+    }
+}
+
+/// Why a screenshot couldn't be taken.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScreenshotError {
+    /// No element of the document matches the selector.
+    NoSuchElement,
+    /// The area to take is empty, or entirely outside of the viewport.
+    EmptyArea,
+    /// The webview was closed, or the frame couldn't be read back.
+    Failed,
+}
+
+/// How the frames of a webview are delivered to the embedder while it
+/// captures them.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FrameCaptureOptions {
+    /// How the pixels of the frames are encoded.
+    pub format: ScreenshotFormat,
+    /// The most frames sent per second, or zero to send every composited
+    /// frame. Frames are only sent when the webview is painted again.
+    pub max_frame_rate: u32,
+}
@@ -58,10 +58,15 @@ pub enum Resource {
     UserAgentCSS,
     ServoCSS,
     PresentationalHintsCSS,
+    MathMLCSS,
     QuirksModeCSS,
     RippyPNG,
     MediaControlsCSS,
     MediaControlsJS,
+    AboutConfigHTML,
+    AboutMemoryHTML,
+    AboutCrashesHTML,
+    ContentBlockingList,
 }
 
 pub trait ResourceReaderMethods {
@@ -94,10 +99,15 @@ fn resources_for_tests() -> Box<dyn ResourceReaderMethods + Sync + Send> {
                 Resource::UserAgentCSS => "user-agent.css",
                 Resource::ServoCSS => "servo.css",
                 Resource::PresentationalHintsCSS => "presentational-hints.css",
+                Resource::MathMLCSS => "mathml.css",
                 Resource::QuirksModeCSS => "quirks-mode.css",
                 Resource::RippyPNG => "rippy.png",
                 Resource::MediaControlsCSS => "media-controls.css",
                 Resource::MediaControlsJS => "media-controls.js",
+                Resource::AboutConfigHTML => "about-config.html",
+                Resource::AboutMemoryHTML => "about-memory.html",
+                Resource::AboutCrashesHTML => "about-crashes.html",
+                Resource::ContentBlockingList => "content_blocking_list.txt",
             };
             let mut path = env::current_exe().unwrap();
             path = path.canonicalize().unwrap();
@@ -9,6 +9,7 @@ use crate::platform::font_context::FontContextHandle;
 pub use crate::platform::font_list::fallback_font_families;
 use crate::platform::font_template::FontTemplateData;
 use crate::text::glyph::{ByteIndex, GlyphData, GlyphId, GlyphStore};
+use crate::text::math::MathTable;
 use crate::text::shaping::ShaperMethods;
 use crate::text::Shaper;
 use app_units::Au;
@@ -24,6 +25,7 @@ use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use style::computed_values::{font_stretch, font_style, font_variant_caps, font_weight};
+use style::font_face::FontFaceRuleData;
 use style::properties::style_structs::Font as FontStyleStruct;
 use style::values::computed::font::{GenericFontFamily, SingleFontFamily};
 use unicode_script::Script;
@@ -37,6 +39,8 @@ macro_rules! ot_tag {
 pub const GPOS: u32 = ot_tag!('G', 'P', 'O', 'S');
 pub const GSUB: u32 = ot_tag!('G', 'S', 'U', 'B');
 pub const KERN: u32 = ot_tag!('k', 'e', 'r', 'n');
+pub const HEAD: u32 = ot_tag!('h', 'e', 'a', 'd');
+pub const MATH: u32 = ot_tag!('M', 'A', 'T', 'H');
 pub const LAST_RESORT_GLYPH_ADVANCE: FractionalPixel = 10.0;
 
 static TEXT_SHAPING_PERFORMANCE_COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -115,6 +119,78 @@ pub struct FontMetrics {
     pub line_gap: Au,
 }
 
+impl FontMetrics {
+    /// Replaces the ascent, descent and line gap of a font rendered at `pt_size` by the ones in
+    /// `overrides`, if any.
+    fn apply_overrides(&mut self, overrides: &FontMetricsOverrides, pt_size: Au) {
+        if overrides.ascent.is_none() && overrides.descent.is_none() && overrides.line_gap.is_none()
+        {
+            return;
+        }
+        let gap = self.line_gap - self.ascent - self.descent;
+        self.ascent = overrides
+            .ascent
+            .map_or(self.ascent, |ascent| pt_size.scale_by(ascent));
+        self.descent = overrides
+            .descent
+            .map_or(self.descent, |descent| pt_size.scale_by(descent));
+        let gap = overrides
+            .line_gap
+            .map_or(gap, |line_gap| pt_size.scale_by(line_gap));
+        self.line_gap = self.ascent + self.descent + gap;
+    }
+}
+
+/// The overrides of the metrics of a web font, from the `size-adjust`, `ascent-override`,
+/// `descent-override` and `line-gap-override` descriptors of its `@font-face` rule.
+///
+/// <https://drafts.csswg.org/css-fonts-5/#font-metrics-override-desc>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FontMetricsOverrides {
+    /// The multiplier for the size the font is rendered at.
+    pub size_adjust: f32,
+    /// The ascent, as a fraction of the adjusted font size.
+    pub ascent: Option<f32>,
+    /// The descent, as a fraction of the adjusted font size.
+    pub descent: Option<f32>,
+    /// The gap between lines, as a fraction of the adjusted font size.
+    pub line_gap: Option<f32>,
+}
+
+impl Default for FontMetricsOverrides {
+    fn default() -> Self {
+        FontMetricsOverrides {
+            size_adjust: 1.,
+            ascent: None,
+            descent: None,
+            line_gap: None,
+        }
+    }
+}
+
+impl<'a> From<&'a FontFaceRuleData> for FontMetricsOverrides {
+    fn from(rule: &'a FontFaceRuleData) -> Self {
+        FontMetricsOverrides {
+            size_adjust: rule
+                .size_adjust
+                .as_ref()
+                .map_or(1., |size_adjust| size_adjust.0.get()),
+            ascent: rule
+                .ascent_override
+                .as_ref()
+                .and_then(|value| value.fraction()),
+            descent: rule
+                .descent_override
+                .as_ref()
+                .and_then(|value| value.fraction()),
+            line_gap: rule
+                .line_gap_override
+                .as_ref()
+                .and_then(|value| value.fraction()),
+        }
+    }
+}
+
 /// `FontDescriptor` describes the parameters of a `Font`. It represents rendering a given font
 /// template at a particular size, with a particular font-variant-caps applied, etc. This contrasts
 /// with `FontTemplateDescriptor` in that the latter represents only the parameters inherent in the
@@ -145,6 +221,8 @@ pub struct Font {
     shaper: Option<Shaper>,
     shape_cache: RefCell<HashMap<ShapeCacheEntry, Arc<GlyphStore>>>,
     glyph_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
+    /// The parsed `MATH` table, once it has been looked up.
+    math_table: RefCell<Option<Option<Arc<MathTable>>>>,
     pub font_key: webrender_api::FontInstanceKey,
 }
 
@@ -154,8 +232,10 @@ impl Font {
         descriptor: FontDescriptor,
         actual_pt_size: Au,
         font_key: webrender_api::FontInstanceKey,
+        metrics_overrides: &FontMetricsOverrides,
     ) -> Font {
-        let metrics = handle.metrics();
+        let mut metrics = handle.metrics();
+        metrics.apply_overrides(metrics_overrides, actual_pt_size);
 
         Font {
             handle: handle,
@@ -165,6 +245,7 @@ impl Font {
             metrics,
             shape_cache: RefCell::new(HashMap::new()),
             glyph_advance_cache: RefCell::new(HashMap::new()),
+            math_table: RefCell::new(None),
             font_key,
         }
     }
@@ -317,6 +398,21 @@ impl Font {
         result
     }
 
+    /// Returns the `MATH` table of this font, if it is a math font.
+    pub fn math_table(&self) -> Option<Arc<MathTable>> {
+        self.math_table
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let head = self.table_for_tag(HEAD)?;
+                // https://docs.microsoft.com/en-us/typography/opentype/spec/head
+                let units_per_em = head.buffer().get(18..20)?;
+                let units_per_em = u16::from_be_bytes([units_per_em[0], units_per_em[1]]);
+                let math = self.table_for_tag(MATH)?;
+                MathTable::parse(math.buffer(), units_per_em, self.metrics.em_size).map(Arc::new)
+            })
+            .clone()
+    }
+
     #[inline]
     pub fn glyph_index(&self, codepoint: char) -> Option<GlyphId> {
         let codepoint = match self.descriptor.variant {
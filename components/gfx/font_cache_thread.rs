@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::font::{FontFamilyDescriptor, FontFamilyName, FontSearchScope};
+use crate::font::{FontFamilyDescriptor, FontFamilyName, FontMetricsOverrides, FontSearchScope};
 use crate::font_context::FontSource;
 use crate::font_template::{FontTemplate, FontTemplateDescriptor};
 use crate::platform::font_context::FontContextHandle;
@@ -34,6 +34,7 @@ pub struct FontTemplates {
 pub struct FontTemplateInfo {
     pub font_template: Arc<FontTemplateData>,
     pub font_key: webrender_api::FontKey,
+    pub metrics_overrides: FontMetricsOverrides,
 }
 
 impl FontTemplates {
@@ -46,14 +47,13 @@ impl FontTemplates {
         &mut self,
         desc: &FontTemplateDescriptor,
         fctx: &FontContextHandle,
-    ) -> Option<Arc<FontTemplateData>> {
+    ) -> Option<(Arc<FontTemplateData>, FontMetricsOverrides)> {
         // TODO(Issue #189): optimize lookup for
         // regular/bold/italic/bolditalic with fixed offsets and a
         // static decision table for fallback between these values.
         for template in &mut self.templates {
-            let maybe_template = template.data_for_descriptor(fctx, desc);
-            if maybe_template.is_some() {
-                return maybe_template;
+            if let Some(data) = template.data_for_descriptor(fctx, desc) {
+                return Some((data, template.metrics_overrides()));
             }
         }
 
@@ -65,7 +65,7 @@ impl FontTemplates {
                 template.data_for_approximate_descriptor(fctx, desc)
             {
                 if distance < best_distance {
-                    best_template_data = Some(template_data);
+                    best_template_data = Some((template_data, template.metrics_overrides()));
                     best_distance = distance
                 }
             }
@@ -78,23 +78,27 @@ impl FontTemplates {
         // pick the first valid font in the family if we failed
         // to find an exact match for the descriptor.
         for template in &mut self.templates {
-            let maybe_template = template.get();
-            if maybe_template.is_some() {
-                return maybe_template;
+            if let Some(data) = template.get() {
+                return Some((data, template.metrics_overrides()));
             }
         }
 
         None
     }
 
-    pub fn add_template(&mut self, identifier: Atom, maybe_data: Option<Vec<u8>>) {
+    pub fn add_template(
+        &mut self,
+        identifier: Atom,
+        maybe_data: Option<Vec<u8>>,
+        metrics_overrides: FontMetricsOverrides,
+    ) {
         for template in &self.templates {
             if *template.identifier() == identifier {
                 return;
             }
         }
 
-        if let Ok(template) = FontTemplate::new(identifier, maybe_data) {
+        if let Ok(template) = FontTemplate::new(identifier, maybe_data, metrics_overrides) {
             self.templates.push(template);
         }
     }
@@ -113,8 +117,19 @@ pub enum Command {
         Au,
         IpcSender<webrender_api::FontInstanceKey>,
     ),
-    AddWebFont(LowercaseString, EffectiveSources, IpcSender<()>),
-    AddDownloadedWebFont(LowercaseString, ServoUrl, Vec<u8>, IpcSender<()>),
+    AddWebFont(
+        LowercaseString,
+        EffectiveSources,
+        FontMetricsOverrides,
+        IpcSender<()>,
+    ),
+    AddDownloadedWebFont(
+        LowercaseString,
+        ServoUrl,
+        Vec<u8>,
+        FontMetricsOverrides,
+        IpcSender<()>,
+    ),
     Exit(IpcSender<()>),
     Ping,
 }
@@ -187,20 +202,41 @@ impl FontCache {
                             .entry((font_key, size))
                             .or_insert_with(|| {
                                 let key = webrender_api.generate_font_instance_key();
+                                // Let the glyphs of the emoji fonts that only have color
+                                // bitmaps be rasterized.
+                                let mut options = webrender_api::FontInstanceOptions::default();
+                                options.flags |= webrender_api::FontInstanceFlags::EMBEDDED_BITMAPS;
                                 let mut txn = webrender_api::Transaction::new();
-                                txn.add_font_instance(key, font_key, size, None, None, Vec::new());
+                                txn.add_font_instance(
+                                    key,
+                                    font_key,
+                                    size,
+                                    Some(options),
+                                    None,
+                                    Vec::new(),
+                                );
                                 webrender_api.update_resources(txn.resource_updates);
                                 key
                             });
 
                     let _ = result.send(instance_key);
                 },
-                Command::AddWebFont(family_name, sources, result) => {
-                    self.handle_add_web_font(family_name, sources, result);
+                Command::AddWebFont(family_name, sources, metrics_overrides, result) => {
+                    self.handle_add_web_font(family_name, sources, metrics_overrides, result);
                 },
-                Command::AddDownloadedWebFont(family_name, url, bytes, result) => {
+                Command::AddDownloadedWebFont(
+                    family_name,
+                    url,
+                    bytes,
+                    metrics_overrides,
+                    result,
+                ) => {
                     let templates = &mut self.web_families.get_mut(&family_name).unwrap();
-                    templates.add_template(Atom::from(url.to_string()), Some(bytes));
+                    templates.add_template(
+                        Atom::from(url.to_string()),
+                        Some(bytes),
+                        metrics_overrides,
+                    );
                     drop(result.send(()));
                 },
                 Command::Ping => (),
@@ -216,6 +252,7 @@ impl FontCache {
         &mut self,
         family_name: LowercaseString,
         mut sources: EffectiveSources,
+        metrics_overrides: FontMetricsOverrides,
         sender: IpcSender<()>,
     ) {
         let src = if let Some(src) = sources.next() {
@@ -268,6 +305,7 @@ impl FontCache {
                                 let msg = Command::AddWebFont(
                                     family_name.clone(),
                                     sources.clone(),
+                                    metrics_overrides,
                                     sender.clone(),
                                 );
                                 channel_to_self.send(msg).unwrap();
@@ -287,6 +325,7 @@ impl FontCache {
                                     let msg = Command::AddWebFont(
                                         family_name.clone(),
                                         sources.clone(),
+                                        metrics_overrides,
                                         sender.clone(),
                                     );
                                     channel_to_self.send(msg).unwrap();
@@ -297,6 +336,7 @@ impl FontCache {
                                 family_name.clone(),
                                 url.clone(),
                                 bytes,
+                                metrics_overrides,
                                 sender.clone(),
                             );
                             channel_to_self.send(command).unwrap();
@@ -310,12 +350,12 @@ impl FontCache {
                 let mut found = false;
                 for_each_variation(&font_face_name, |path| {
                     found = true;
-                    templates.add_template(Atom::from(&*path), None);
+                    templates.add_template(Atom::from(&*path), None, metrics_overrides);
                 });
                 if found {
                     sender.send(()).unwrap();
                 } else {
-                    let msg = Command::AddWebFont(family_name, sources, sender);
+                    let msg = Command::AddWebFont(family_name, sources, metrics_overrides, sender);
                     self.channel_to_self.send(msg).unwrap();
                 }
             },
@@ -344,7 +384,7 @@ impl FontCache {
         &mut self,
         template_descriptor: &FontTemplateDescriptor,
         family_name: &FontFamilyName,
-    ) -> Option<Arc<FontTemplateData>> {
+    ) -> Option<(Arc<FontTemplateData>, FontMetricsOverrides)> {
         let family_name = self.transform_family(family_name);
 
         // TODO(Issue #188): look up localized font family names if canonical name not found
@@ -355,7 +395,7 @@ impl FontCache {
 
             if s.templates.is_empty() {
                 for_each_variation(&family_name, |path| {
-                    s.add_template(Atom::from(&*path), None);
+                    s.add_template(Atom::from(&*path), None, FontMetricsOverrides::default());
                 });
             }
 
@@ -376,7 +416,7 @@ impl FontCache {
         &mut self,
         template_descriptor: &FontTemplateDescriptor,
         family_name: &FontFamilyName,
-    ) -> Option<Arc<FontTemplateData>> {
+    ) -> Option<(Arc<FontTemplateData>, FontMetricsOverrides)> {
         let family_name = LowercaseString::from(family_name);
 
         if self.web_families.contains_key(&family_name) {
@@ -387,7 +427,10 @@ impl FontCache {
         }
     }
 
-    fn get_font_template_info(&mut self, template: Arc<FontTemplateData>) -> FontTemplateInfo {
+    fn get_font_template_info(
+        &mut self,
+        (template, metrics_overrides): (Arc<FontTemplateData>, FontMetricsOverrides),
+    ) -> FontTemplateInfo {
         let webrender_api = &self.webrender_api;
         let webrender_fonts = &mut self.webrender_fonts;
 
@@ -408,6 +451,7 @@ impl FontCache {
         FontTemplateInfo {
             font_template: template,
             font_key: font_key,
+            metrics_overrides,
         }
     }
 
@@ -477,12 +521,14 @@ impl FontCacheThread {
         &self,
         family: FamilyName,
         sources: EffectiveSources,
+        metrics_overrides: FontMetricsOverrides,
         sender: IpcSender<()>,
     ) {
         self.chan
             .send(Command::AddWebFont(
                 LowercaseString::new(&family.name),
                 sources,
+                metrics_overrides,
                 sender,
             ))
             .unwrap();
@@ -183,6 +183,7 @@ impl<S: FontSource> FontContext<S> {
             FontVariantCaps::SmallCaps => descriptor.pt_size.scale_by(SMALL_CAPS_SCALE_FACTOR),
             FontVariantCaps::Normal => descriptor.pt_size,
         };
+        let actual_pt_size = actual_pt_size.scale_by(info.metrics_overrides.size_adjust);
 
         let handle = FontHandle::new_from_template(
             &self.platform_handle,
@@ -198,6 +199,7 @@ impl<S: FontSource> FontContext<S> {
             descriptor,
             actual_pt_size,
             font_instance_key,
+            &info.metrics_overrides,
         ))
     }
 }
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::font::FontHandleMethods;
+use crate::font::{FontHandleMethods, FontMetricsOverrides};
 use crate::platform::font::FontHandle;
 use crate::platform::font_context::FontContextHandle;
 use crate::platform::font_template::FontTemplateData;
@@ -90,6 +90,8 @@ pub struct FontTemplate {
     // GWTODO: Add code path to unset the strong_ref for web fonts!
     strong_ref: Option<Arc<FontTemplateData>>,
     is_valid: bool,
+    /// The metrics overrides of the `@font-face` rule this template comes from.
+    metrics_overrides: FontMetricsOverrides,
 }
 
 impl Debug for FontTemplate {
@@ -102,7 +104,11 @@ impl Debug for FontTemplate {
 /// is common, regardless of the number of instances of
 /// this font handle per thread.
 impl FontTemplate {
-    pub fn new(identifier: Atom, maybe_bytes: Option<Vec<u8>>) -> Result<FontTemplate, IoError> {
+    pub fn new(
+        identifier: Atom,
+        maybe_bytes: Option<Vec<u8>>,
+        metrics_overrides: FontMetricsOverrides,
+    ) -> Result<FontTemplate, IoError> {
         let maybe_data = match maybe_bytes {
             Some(_) => Some(FontTemplateData::new(identifier.clone(), maybe_bytes)?),
             None => None,
@@ -124,6 +130,7 @@ impl FontTemplate {
             weak_ref: maybe_weak_ref,
             strong_ref: maybe_strong_ref,
             is_valid: true,
+            metrics_overrides,
         })
     }
 
@@ -131,6 +138,10 @@ impl FontTemplate {
         &self.identifier
     }
 
+    pub fn metrics_overrides(&self) -> FontMetricsOverrides {
+        self.metrics_overrides
+    }
+
     /// Get the descriptor. Returns `None` when instantiating the data fails.
     pub fn descriptor(
         &mut self,
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use super::xml::{Attribute, Node};
-use crate::text::util::is_cjk;
+use crate::text::util::{is_cjk, is_emoji};
 use std::path::Path;
 use ucd::{Codepoint, UnicodeBlock};
 
@@ -464,6 +464,10 @@ pub fn system_default_family(generic_name: &str) -> Option<String> {
 pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec![];
 
+    if codepoint.map_or(false, is_emoji) {
+        families.push("Noto Color Emoji");
+    }
+
     if let Some(block) = codepoint.and_then(|c| c.block()) {
         match block {
             UnicodeBlock::Armenian => {
@@ -16,8 +16,8 @@ use freetype::freetype::{FT_F26Dot6, FT_Face, FT_FaceRec};
 use freetype::freetype::{FT_Get_Char_Index, FT_Get_Postscript_Name};
 use freetype::freetype::{FT_Get_Kerning, FT_Get_Sfnt_Table, FT_Load_Sfnt_Table};
 use freetype::freetype::{FT_GlyphSlot, FT_Library, FT_Long, FT_ULong};
+use freetype::freetype::{FT_Int, FT_Load_Glyph, FT_Select_Size, FT_Set_Char_Size};
 use freetype::freetype::{FT_Int32, FT_Kerning_Mode, FT_STYLE_FLAG_ITALIC};
-use freetype::freetype::{FT_Load_Glyph, FT_Set_Char_Size};
 use freetype::freetype::{FT_SizeRec, FT_Size_Metrics, FT_UInt, FT_Vector};
 use freetype::succeeded;
 use freetype::tt_os2::TT_OS2;
@@ -25,7 +25,7 @@ use servo_atoms::Atom;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_long};
 use std::sync::Arc;
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 use style::computed_values::font_stretch::T as FontStretch;
 use style::computed_values::font_weight::T as FontWeight;
 use style::values::computed::font::FontStyle;
@@ -34,12 +34,16 @@ use style::values::computed::font::FontStyle;
 // bindings due to bindgen not handling the way
 // the macro is defined.
 const FT_LOAD_TARGET_LIGHT: FT_Int32 = 1 << 16;
+const FT_LOAD_COLOR: FT_Int32 = 1 << 20;
+const FT_FACE_FLAG_FIXED_SIZES: FT_Long = 1 << 1;
 
 // Default to slight hinting, which is what most
 // Linux distros use by default, and is a better
 // default than no hinting.
 // TODO(gw): Make this configurable.
-const GLYPH_LOAD_FLAGS: FT_Int32 = FT_LOAD_TARGET_LIGHT;
+// The color flag makes the metrics those of the color glyphs of the
+// bitmap emoji fonts, and is ignored by the other fonts.
+const GLYPH_LOAD_FLAGS: FT_Int32 = FT_LOAD_TARGET_LIGHT | FT_LOAD_COLOR;
 
 fn fixed_to_float_ft(f: i32) -> f64 {
     fixed_to_float(6, f)
@@ -75,6 +79,10 @@ pub struct FontHandle {
     face: FT_Face,
     handle: FontContextHandle,
     can_do_fast_shaping: bool,
+    /// The ratio of the requested size to the size of the face, which
+    /// differ for the fonts that only come in a few sizes, like the
+    /// bitmap emoji fonts, whose glyphs get scaled to the requested size.
+    scale: f64,
 }
 
 impl Drop for FontHandle {
@@ -92,7 +100,7 @@ fn create_face(
     lib: FT_Library,
     template: &FontTemplateData,
     pt_size: Option<Au>,
-) -> Result<FT_Face, ()> {
+) -> Result<(FT_Face, f64), ()> {
     unsafe {
         let mut face: FT_Face = ptr::null_mut();
         let face_index = 0 as FT_Long;
@@ -120,11 +128,12 @@ fn create_face(
             return Err(());
         }
 
-        if let Some(s) = pt_size {
-            FontHandle::set_char_size(face, s).or(Err(()))?
-        }
+        let scale = match pt_size {
+            Some(s) => FontHandle::set_char_size(face, s)?,
+            None => 1.0,
+        };
 
-        Ok(face)
+        Ok((face, scale))
     }
 }
 
@@ -139,13 +148,14 @@ impl FontHandleMethods for FontHandle {
             return Err(());
         }
 
-        let face = create_face(ft_ctx, &template, pt_size)?;
+        let (face, scale) = create_face(ft_ctx, &template, pt_size)?;
 
         let mut handle = FontHandle {
             face: face,
             font_data: template,
             handle: fctx.clone(),
             can_do_fast_shaping: false,
+            scale: scale,
         };
         // TODO (#11310): Implement basic support for GPOS and GSUB.
         handle.can_do_fast_shaping =
@@ -249,7 +259,7 @@ impl FontHandleMethods for FontHandle {
                 &mut delta,
             );
         }
-        fixed_to_float_ft(delta.x as i32)
+        fixed_to_float_ft(delta.x as i32) * self.scale
     }
 
     fn can_do_fast_shaping(&self) -> bool {
@@ -267,7 +277,7 @@ impl FontHandleMethods for FontHandle {
                 let advance = (*slot).metrics.horiAdvance;
                 debug!("h_advance for {} is {}", glyph, advance);
                 let advance = advance as i32;
-                Some(fixed_to_float_ft(advance) as FractionalPixel)
+                Some(fixed_to_float_ft(advance) * self.scale as FractionalPixel)
             } else {
                 debug!("Unable to load glyph {}. reason: {:?}", glyph, res);
                 None
@@ -365,16 +375,40 @@ impl FontHandleMethods for FontHandle {
 }
 
 impl<'a> FontHandle {
-    fn set_char_size(face: FT_Face, pt_size: Au) -> Result<(), ()> {
+    /// Sets the size of `face`, returning the ratio of `pt_size` to the
+    /// size that was actually set.
+    fn set_char_size(face: FT_Face, pt_size: Au) -> Result<f64, ()> {
         let char_size = pt_size.to_f64_px() * 64.0 + 0.5;
 
         unsafe {
             let result = FT_Set_Char_Size(face, char_size as FT_F26Dot6, 0, 0, 0);
             if succeeded(result) {
-                Ok(())
-            } else {
-                Err(())
+                return Ok(1.0);
+            }
+
+            // Fonts which aren't scalable, like the CBDT emoji fonts, have
+            // to use one of their fixed sizes, the nearest one being picked.
+            if (*face).face_flags & FT_FACE_FLAG_FIXED_SIZES == 0 || (*face).num_fixed_sizes <= 0 {
+                return Err(());
+            }
+            let sizes =
+                slice::from_raw_parts((*face).available_sizes, (*face).num_fixed_sizes as usize);
+            let px_size = pt_size.to_f64_px();
+            let (index, ppem) = sizes
+                .iter()
+                .map(|size| fixed_to_float_ft(size.y_ppem as i32))
+                .enumerate()
+                .min_by(|&(_, a), &(_, b)| {
+                    (a - px_size)
+                        .abs()
+                        .partial_cmp(&(b - px_size).abs())
+                        .unwrap()
+                })
+                .unwrap();
+            if !succeeded(FT_Select_Size(face, index as FT_Int)) || ppem <= 0. {
+                return Err(());
             }
+            Ok(px_size / ppem)
         }
     }
 
@@ -408,7 +442,7 @@ impl<'a> FontHandle {
         // If this isn't true then we're scaling one of the axes wrong
         assert_eq!(metrics.x_ppem, metrics.y_ppem);
 
-        Au::from_f64_px(value * x_scale)
+        Au::from_f64_px(value * x_scale * self.scale)
     }
 
     fn os2_table(&self) -> Option<OS2Table> {
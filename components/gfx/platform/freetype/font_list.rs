@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use super::c_str_to_string;
-use crate::text::util::is_cjk;
+use crate::text::util::{is_cjk, is_emoji};
 use fontconfig::fontconfig::{FcChar8, FcResultMatch, FcSetSystem};
 use fontconfig::fontconfig::{FcConfigGetCurrent, FcConfigGetFonts, FcConfigSubstitute};
 use fontconfig::fontconfig::{FcDefaultSubstitute, FcFontMatch, FcNameParse, FcPatternGetString};
@@ -156,6 +156,10 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec!["DejaVu Serif", "FreeSerif", "DejaVu Sans", "FreeSans"];
 
     if let Some(codepoint) = codepoint {
+        if is_emoji(codepoint) {
+            families.push("Noto Color Emoji");
+        }
+
         if is_cjk(codepoint) {
             families.push("TakaoPGothic");
             families.push("Droid Sans Fallback");
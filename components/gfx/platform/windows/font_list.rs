@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::text::util::unicode_plane;
+use crate::text::util::{is_emoji, unicode_plane};
 use dwrote::{Font, FontCollection, FontDescriptor};
 use servo_atoms::Atom;
 use std::collections::HashMap;
@@ -76,6 +76,10 @@ pub fn fallback_font_families(codepoint: Option<char>) -> Vec<&'static str> {
     let mut families = vec!["Arial"];
 
     if let Some(codepoint) = codepoint {
+        if is_emoji(codepoint) {
+            families.push("Segoe UI Emoji");
+        }
+
         match unicode_plane(codepoint) {
             // https://en.wikipedia.org/wiki/Plane_(Unicode)#Basic_Multilingual_Plane
             0 => {
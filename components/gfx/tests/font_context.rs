@@ -4,7 +4,8 @@
 
 use app_units::Au;
 use gfx::font::{
-    fallback_font_families, FontDescriptor, FontFamilyDescriptor, FontFamilyName, FontSearchScope,
+    fallback_font_families, FontDescriptor, FontFamilyDescriptor, FontFamilyName,
+    FontMetricsOverrides, FontSearchScope,
 };
 use gfx::font_cache_thread::{FontTemplateInfo, FontTemplates};
 use gfx::font_context::{FontContext, FontContextHandle, FontSource};
@@ -63,7 +64,11 @@ impl TestFontSource {
         let file = File::open(path).unwrap();
         let identifier = Atom::from(identifier.unwrap_or(name));
 
-        family.add_template(identifier, Some(file.bytes().map(|b| b.unwrap()).collect()))
+        family.add_template(
+            identifier,
+            Some(file.bytes().map(|b| b.unwrap()).collect()),
+            FontMetricsOverrides::default(),
+        )
     }
 }
 
@@ -87,9 +92,10 @@ impl FontSource for TestFontSource {
         self.families
             .get_mut(family_descriptor.name())
             .and_then(|family| family.find_font_for_style(&template_descriptor, handle))
-            .map(|template| FontTemplateInfo {
+            .map(|(template, metrics_overrides)| FontTemplateInfo {
                 font_template: template,
                 font_key: webrender_api::FontKey(webrender_api::IdNamespace(0), 0),
+                metrics_overrides,
             })
     }
 }
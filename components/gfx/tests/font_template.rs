@@ -6,6 +6,7 @@
 #[cfg(not(target_os = "macos"))]
 #[test]
 fn test_font_template_descriptor() {
+    use gfx::font::FontMetricsOverrides;
     use gfx::font_context::FontContextHandle;
     use gfx::font_template::{FontTemplate, FontTemplateDescriptor};
     use servo_atoms::Atom;
@@ -34,6 +35,7 @@ fn test_font_template_descriptor() {
         let mut template = FontTemplate::new(
             Atom::from(filename),
             Some(file.bytes().map(|b| b.unwrap()).collect()),
+            FontMetricsOverrides::default(),
         )
         .unwrap();
 
@@ -0,0 +1,109 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use gfx::text::math::{GlyphPart, GlyphVariant, MathTable};
+
+fn push(data: &mut Vec<u8>, values: &[u16]) {
+    for value in values {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// A `MATH` table with a few constants and a vertical construction for glyph 5.
+fn math_table() -> Vec<u8> {
+    let mut data = vec![];
+    // Header: version 1.0, MathConstants at 10, no MathGlyphInfo, MathVariants at 224.
+    push(&mut data, &[1, 0, 10, 0, 224]);
+
+    // MathConstants
+    push(&mut data, &[70, 50, 0, 1500]);
+    for index in 0..51 {
+        let value = match index {
+            1 => 250,               // axisHeight
+            34 => 40,               // fractionRuleThickness
+            47 => 50,               // radicalRuleThickness
+            50 => (-500i16) as u16, // radicalKernAfterDegree
+            _ => 0,
+        };
+        push(&mut data, &[value, 0]);
+    }
+    push(&mut data, &[60]);
+    assert_eq!(data.len(), 224);
+
+    // MathVariants: overlap, coverage at 12, no horizontal glyphs, one vertical construction.
+    push(&mut data, &[20, 12, 0, 1, 0, 18]);
+    // Coverage format 1, with only glyph 5.
+    push(&mut data, &[1, 1, 5]);
+    // MathGlyphConstruction, with its assembly at 12 and two variants.
+    push(&mut data, &[12, 2, 6, 1200, 7, 1800]);
+    // GlyphAssembly: italics correction, then two parts.
+    push(&mut data, &[0, 0, 2]);
+    push(&mut data, &[8, 0, 100, 600, 0]);
+    push(&mut data, &[9, 100, 100, 500, 1]);
+    data
+}
+
+#[test]
+fn test_math_constants() {
+    let table = MathTable::parse(&math_table(), 1000, Au::from_px(20)).unwrap();
+    let constants = table.constants();
+    assert_eq!(constants.script_percent_scale_down, 70);
+    assert_eq!(constants.script_script_percent_scale_down, 50);
+    assert_eq!(constants.display_operator_min_height, Au::from_px(30));
+    assert_eq!(constants.axis_height, Au::from_px(5));
+    assert_eq!(constants.fraction_rule_thickness, Au::from_f64_px(0.8));
+    assert_eq!(constants.radical_rule_thickness, Au::from_px(1));
+    assert_eq!(constants.radical_kern_after_degree, Au::from_px(-10));
+    assert_eq!(constants.radical_degree_bottom_raise_percent, 60);
+    assert_eq!(constants.superscript_shift_up, Au(0));
+    assert_eq!(table.min_connector_overlap(), Au::from_f64_px(0.4));
+}
+
+#[test]
+fn test_math_vertical_construction() {
+    let table = MathTable::parse(&math_table(), 1000, Au::from_px(20)).unwrap();
+    assert!(table.vertical_construction(4).is_none());
+
+    let construction = table.vertical_construction(5).unwrap();
+    assert_eq!(
+        construction.variants,
+        vec![
+            GlyphVariant {
+                glyph: 6,
+                advance: Au::from_px(24),
+            },
+            GlyphVariant {
+                glyph: 7,
+                advance: Au::from_px(36),
+            },
+        ]
+    );
+    assert_eq!(
+        construction.parts,
+        vec![
+            GlyphPart {
+                glyph: 8,
+                start_connector_length: Au(0),
+                end_connector_length: Au::from_px(2),
+                full_advance: Au::from_px(12),
+                is_extender: false,
+            },
+            GlyphPart {
+                glyph: 9,
+                start_connector_length: Au::from_px(2),
+                end_connector_length: Au::from_px(2),
+                full_advance: Au::from_px(10),
+                is_extender: true,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_math_table_truncated() {
+    let data = math_table();
+    assert!(MathTable::parse(&data[..100], 1000, Au::from_px(20)).is_none());
+    assert!(MathTable::parse(&data, 0, Au::from_px(20)).is_none());
+}
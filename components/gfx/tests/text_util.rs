@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use gfx::text::util::{transform_text, CompressionMode};
+use gfx::text::util::{is_emoji_sequence_continuation, transform_text, CompressionMode};
 
 #[test]
 fn test_transform_compress_none() {
@@ -104,3 +104,18 @@ fn test_transform_compress_whitespace_newline_no_incoming() {
         assert_eq!(trimmed_str, oracle)
     }
 }
+
+#[test]
+fn test_emoji_sequence_continuation() {
+    // 👩‍👩‍👧 is 👩 ZWJ 👩 ZWJ 👧, and 👍🏽 is 👍 followed by a skin tone modifier.
+    let continuations: Vec<bool> = "👩\u{200D}👩\u{200D}👧👍🏽"
+        .chars()
+        .map(is_emoji_sequence_continuation)
+        .collect();
+    assert_eq!(
+        continuations,
+        [false, true, false, true, false, false, true]
+    );
+    assert!(is_emoji_sequence_continuation('\u{FE0F}'));
+    assert!(!is_emoji_sequence_continuation('a'));
+}
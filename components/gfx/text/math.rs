@@ -0,0 +1,263 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Support for the OpenType `MATH` table, which math fonts use to describe the
+//! layout parameters of formulas and the size variants of stretchy operators.
+//!
+//! <https://docs.microsoft.com/en-us/typography/opentype/spec/math>
+
+use crate::text::glyph::GlyphId;
+use app_units::Au;
+
+/// Layout parameters of a math font, already scaled to the size of the font.
+///
+/// Only the constants used by MathML Core layout are exposed.
+#[derive(Clone, Debug)]
+pub struct MathConstants {
+    pub script_percent_scale_down: i16,
+    pub script_script_percent_scale_down: i16,
+    pub display_operator_min_height: Au,
+    pub axis_height: Au,
+    pub subscript_shift_down: Au,
+    pub subscript_top_max: Au,
+    pub subscript_baseline_drop_min: Au,
+    pub superscript_shift_up: Au,
+    pub superscript_shift_up_cramped: Au,
+    pub superscript_bottom_min: Au,
+    pub superscript_baseline_drop_max: Au,
+    pub sub_superscript_gap_min: Au,
+    pub superscript_bottom_max_with_subscript: Au,
+    pub space_after_script: Au,
+    pub fraction_numerator_shift_up: Au,
+    pub fraction_numerator_display_style_shift_up: Au,
+    pub fraction_denominator_shift_down: Au,
+    pub fraction_denominator_display_style_shift_down: Au,
+    pub fraction_numerator_gap_min: Au,
+    pub fraction_num_display_style_gap_min: Au,
+    pub fraction_rule_thickness: Au,
+    pub fraction_denominator_gap_min: Au,
+    pub fraction_denom_display_style_gap_min: Au,
+    pub radical_vertical_gap: Au,
+    pub radical_display_style_vertical_gap: Au,
+    pub radical_rule_thickness: Au,
+    pub radical_extra_ascender: Au,
+    pub radical_kern_before_degree: Au,
+    pub radical_kern_after_degree: Au,
+    pub radical_degree_bottom_raise_percent: i16,
+}
+
+/// A larger version of a glyph, with its full advance in the direction of
+/// stretching.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphVariant {
+    pub glyph: GlyphId,
+    pub advance: Au,
+}
+
+/// A piece of a glyph assembly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphPart {
+    pub glyph: GlyphId,
+    pub start_connector_length: Au,
+    pub end_connector_length: Au,
+    pub full_advance: Au,
+    /// Whether this part can be repeated to make the assembly larger.
+    pub is_extender: bool,
+}
+
+/// The ways to stretch a glyph: a list of increasingly large variants, and
+/// optionally a set of parts from which arbitrarily large versions can be
+/// assembled.
+#[derive(Clone, Debug, Default)]
+pub struct GlyphConstruction {
+    pub variants: Vec<GlyphVariant>,
+    pub parts: Vec<GlyphPart>,
+}
+
+/// A parsed `MATH` table.
+#[derive(Debug)]
+pub struct MathTable {
+    data: Vec<u8>,
+    scale: f64,
+    constants: MathConstants,
+    variants: Option<usize>,
+}
+
+const MATH_CONSTANTS_RECORDS_OFFSET: usize = 8;
+const MATH_CONSTANTS_RECORD_COUNT: usize = 51;
+const MATH_VALUE_RECORD_SIZE: usize = 4;
+const GLYPH_PART_RECORD_SIZE: usize = 10;
+const GLYPH_PART_EXTENDER_FLAG: u16 = 0x0001;
+
+impl MathTable {
+    /// Parses the `MATH` table of a font with the given number of design units
+    /// per em, which is being used at the given size.
+    pub fn parse(data: &[u8], units_per_em: u16, em_size: Au) -> Option<MathTable> {
+        if units_per_em == 0 || read_u16(data, 0)? != 1 {
+            return None;
+        }
+        let scale = em_size.to_f64_px() / units_per_em as f64;
+        let constants = non_null_offset(data, 0, 4)?;
+        let variants = non_null_offset(data, 0, 8);
+
+        // The last constant follows the value records. Check that it is in
+        // bounds, so that reading the others can't fail.
+        let radical_degree_bottom_raise_percent = read_i16(
+            data,
+            constants + math_value_record(MATH_CONSTANTS_RECORD_COUNT),
+        )?;
+        let value = |index| {
+            let value = read_i16(data, constants + math_value_record(index)).unwrap();
+            Au::from_f64_px(value as f64 * scale)
+        };
+        let constants = MathConstants {
+            script_percent_scale_down: read_i16(data, constants)?,
+            script_script_percent_scale_down: read_i16(data, constants + 2)?,
+            display_operator_min_height: Au::from_f64_px(
+                read_u16(data, constants + 6)? as f64 * scale,
+            ),
+            axis_height: value(1),
+            subscript_shift_down: value(4),
+            subscript_top_max: value(5),
+            subscript_baseline_drop_min: value(6),
+            superscript_shift_up: value(7),
+            superscript_shift_up_cramped: value(8),
+            superscript_bottom_min: value(9),
+            superscript_baseline_drop_max: value(10),
+            sub_superscript_gap_min: value(11),
+            superscript_bottom_max_with_subscript: value(12),
+            space_after_script: value(13),
+            fraction_numerator_shift_up: value(28),
+            fraction_numerator_display_style_shift_up: value(29),
+            fraction_denominator_shift_down: value(30),
+            fraction_denominator_display_style_shift_down: value(31),
+            fraction_numerator_gap_min: value(32),
+            fraction_num_display_style_gap_min: value(33),
+            fraction_rule_thickness: value(34),
+            fraction_denominator_gap_min: value(35),
+            fraction_denom_display_style_gap_min: value(36),
+            radical_vertical_gap: value(45),
+            radical_display_style_vertical_gap: value(46),
+            radical_rule_thickness: value(47),
+            radical_extra_ascender: value(48),
+            radical_kern_before_degree: value(49),
+            radical_kern_after_degree: value(50),
+            radical_degree_bottom_raise_percent,
+        };
+
+        Some(MathTable {
+            data: data.to_vec(),
+            scale,
+            constants,
+            variants,
+        })
+    }
+
+    pub fn constants(&self) -> &MathConstants {
+        &self.constants
+    }
+
+    /// The minimum overlap between two connected parts of a glyph assembly.
+    pub fn min_connector_overlap(&self) -> Au {
+        self.variants
+            .and_then(|variants| read_u16(&self.data, variants))
+            .map_or(Au(0), |overlap| self.scale(overlap))
+    }
+
+    /// Returns the ways to stretch the given glyph vertically, if the font
+    /// provides any.
+    pub fn vertical_construction(&self, glyph: GlyphId) -> Option<GlyphConstruction> {
+        let data = &self.data[..];
+        let variants = self.variants?;
+        let coverage = non_null_offset(data, variants, 2)?;
+        let index = coverage_index(data, coverage, glyph)?;
+        if index >= read_u16(data, variants + 6)? as usize {
+            return None;
+        }
+        let construction = variants + read_u16(data, variants + 10 + 2 * index)? as usize;
+
+        let variant_count = read_u16(data, construction + 2)? as usize;
+        let variants = (0..variant_count)
+            .map(|i| {
+                let record = construction + 4 + 4 * i;
+                Some(GlyphVariant {
+                    glyph: read_u16(data, record)? as GlyphId,
+                    advance: self.scale(read_u16(data, record + 2)?),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let parts = match non_null_offset(data, construction, 0) {
+            Some(assembly) => {
+                let part_count = read_u16(data, assembly + MATH_VALUE_RECORD_SIZE)? as usize;
+                (0..part_count)
+                    .map(|i| {
+                        let record =
+                            assembly + MATH_VALUE_RECORD_SIZE + 2 + GLYPH_PART_RECORD_SIZE * i;
+                        Some(GlyphPart {
+                            glyph: read_u16(data, record)? as GlyphId,
+                            start_connector_length: self.scale(read_u16(data, record + 2)?),
+                            end_connector_length: self.scale(read_u16(data, record + 4)?),
+                            full_advance: self.scale(read_u16(data, record + 6)?),
+                            is_extender: read_u16(data, record + 8)? & GLYPH_PART_EXTENDER_FLAG !=
+                                0,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            },
+            None => vec![],
+        };
+
+        Some(GlyphConstruction { variants, parts })
+    }
+
+    fn scale(&self, design_units: u16) -> Au {
+        Au::from_f64_px(design_units as f64 * self.scale)
+    }
+}
+
+fn math_value_record(index: usize) -> usize {
+    MATH_CONSTANTS_RECORDS_OFFSET + MATH_VALUE_RECORD_SIZE * index
+}
+
+/// Returns the coverage index of a glyph in a coverage table.
+/// <https://docs.microsoft.com/en-us/typography/opentype/spec/chapter2#coverage-table>
+fn coverage_index(data: &[u8], coverage: usize, glyph: GlyphId) -> Option<usize> {
+    let count = read_u16(data, coverage + 2)? as usize;
+    match read_u16(data, coverage)? {
+        1 => (0..count).find_map(|i| match read_u16(data, coverage + 4 + 2 * i) {
+            Some(id) if id as GlyphId == glyph => Some(i),
+            _ => None,
+        }),
+        2 => (0..count).find_map(|i| {
+            let range = coverage + 4 + 6 * i;
+            let start = read_u16(data, range)? as GlyphId;
+            let end = read_u16(data, range + 2)? as GlyphId;
+            if start <= glyph && glyph <= end {
+                Some(read_u16(data, range + 4)? as usize + (glyph - start) as usize)
+            } else {
+                None
+            }
+        }),
+        _ => None,
+    }
+}
+
+/// Reads the offset at `position` in a subtable starting at `base`, and
+/// returns the position it points to, unless it is null.
+fn non_null_offset(data: &[u8], base: usize, position: usize) -> Option<usize> {
+    match read_u16(data, base + position)? {
+        0 => None,
+        offset => Some(base + offset as usize),
+    }
+}
+
+fn read_u16(data: &[u8], position: usize) -> Option<u16> {
+    let bytes = data.get(position..position + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_i16(data: &[u8], position: usize) -> Option<i16> {
+    read_u16(data, position).map(|value| value as i16)
+}
@@ -14,6 +14,8 @@ use std::slice::Iter;
 use std::sync::Arc;
 use style::str::char_is_whitespace;
 use unicode_bidi as bidi;
+use unicode_script::Script;
+use unicode_segmentation::UnicodeSegmentation;
 use xi_unicode::LineBreakLeafIter;
 
 thread_local! {
@@ -249,14 +251,14 @@ impl<'a> TextRun {
                 // keep-all, try increasing the slice.
                 continue;
             }
-            if slice.len() > 0 {
+            let mut start = slice.start;
+            for end in complex_context_breaks(&text[slice.clone()], options) {
+                let end = slice.start + end;
                 glyphs.push(GlyphRun {
-                    glyph_store: font.shape_text(&text[slice.clone()], options),
-                    range: Range::new(
-                        ByteIndex(slice.start as isize),
-                        ByteIndex(slice.len() as isize),
-                    ),
+                    glyph_store: font.shape_text(&text[start..end], options),
+                    range: Range::new(ByteIndex(start as isize), ByteIndex((end - start) as isize)),
                 });
+                start = end;
             }
             if whitespace.len() > 0 {
                 let mut options = options.clone();
@@ -446,3 +448,27 @@ impl<'a> TextRun {
         }
     }
 }
+
+/// The byte indices within `word` at which it can be broken, ending with its
+/// length unless it's empty.
+///
+/// UAX#14 leaves breaking the scripts written without spaces between words,
+/// like Thai, Lao, Khmer or Burmese, to a dictionary, and never breaks them
+/// on its own. Without a dictionary, they can be broken between any two
+/// grapheme clusters instead, so that long runs of them still wrap.
+fn complex_context_breaks(word: &str, options: &ShapingOptions) -> Vec<usize> {
+    let complex_context = match options.script {
+        Script::Thai | Script::Lao | Script::Khmer | Script::Myanmar => true,
+        _ => false,
+    };
+    if !complex_context || options.flags.contains(ShapingFlags::KEEP_ALL_FLAG) {
+        return if word.is_empty() {
+            vec![]
+        } else {
+            vec![word.len()]
+        };
+    }
+    word.grapheme_indices(true)
+        .map(|(start, grapheme)| start + grapheme.len())
+        .collect()
+}
@@ -156,3 +156,28 @@ pub fn is_cjk(codepoint: char) -> bool {
     // https://en.wikipedia.org/wiki/Plane_(Unicode)#Supplementary_Ideographic_Plane
     unicode_plane(codepoint) == 2
 }
+
+/// Whether `codepoint` is likely to be an emoji, which the color emoji fonts
+/// are worth falling back to for.
+pub fn is_emoji(codepoint: char) -> bool {
+    match codepoint {
+        '\u{2600}'..='\u{27BF}' => true,
+        '\u{2B00}'..='\u{2BFF}' => true,
+        '\u{1F000}'..='\u{1FAFF}' => true,
+        _ => false,
+    }
+}
+
+/// Whether `codepoint` is part of the emoji sequence that precedes it, rather
+/// than a character of its own: a zero width joiner, a variation selector,
+/// an emoji modifier, the combining enclosing keycap or a tag.
+/// <https://www.unicode.org/reports/tr51/#Emoji_Sequences>
+pub fn is_emoji_sequence_continuation(codepoint: char) -> bool {
+    match codepoint {
+        '\u{200D}' | '\u{20E3}' => true,
+        '\u{FE00}'..='\u{FE0F}' => true,
+        '\u{1F3FB}'..='\u{1F3FF}' => true,
+        '\u{E0020}'..='\u{E007F}' => true,
+        _ => false,
+    }
+}
@@ -52,7 +52,9 @@ use crate::traversal::PreorderFlowTraversal;
 use app_units::{Au, MAX_AU};
 use euclid::default::{Point2D, Rect, SideOffsets2D, Size2D};
 use gfx_traits::print_tree::PrintTree;
+use script_layout_interface::scrollbar::ScrollbarStyle;
 use serde::{Serialize, Serializer};
+use servo_arc::Arc as ServoArc;
 use servo_geometry::MaxRect;
 use std::cmp::{max, min};
 use std::fmt;
@@ -598,6 +600,14 @@ pub struct BlockFlow {
     /// Additional floating flow members.
     pub float: Option<Box<FloatedBlockInfo>>,
 
+    /// The style of the `::backdrop` of the element, if it is in the top layer.
+    #[serde(skip_serializing)]
+    pub backdrop_style: Option<ServoArc<ComputedValues>>,
+
+    /// The style of the scrollbars of the element, if it is a scroll container.
+    #[serde(skip_serializing)]
+    pub scrollbar_style: Option<ScrollbarStyle>,
+
     /// Various flags.
     flags: BlockFlowFlags,
 }
@@ -638,6 +648,8 @@ impl BlockFlow {
             ),
             fragment: fragment,
             float: float_kind.map(|kind| Box::new(FloatedBlockInfo::new(kind))),
+            backdrop_style: None,
+            scrollbar_style: None,
             flags: BlockFlowFlags::empty(),
         }
     }
@@ -879,6 +891,7 @@ impl BlockFlow {
             base: self.base.clone_with_children(new_children),
             fragment: self.fragment.clone(),
             float: self.float.clone(),
+            backdrop_style: self.backdrop_style.clone(),
             ..*self
         }
     }
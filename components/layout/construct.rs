@@ -898,9 +898,10 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         }
 
         let fragment = self.build_fragment_for_block(node);
-        let flow = FlowRef::new(Arc::new(BlockFlow::from_fragment_and_float_kind(
-            fragment, float_kind,
-        )));
+        let mut block_flow = BlockFlow::from_fragment_and_float_kind(fragment, float_kind);
+        block_flow.backdrop_style = node.backdrop_style(self.style_context());
+        block_flow.scrollbar_style = node.scrollbar_style(self.style_context());
+        let flow = FlowRef::new(Arc::new(block_flow));
         self.build_flow_for_block_like(flow, node)
     }
 
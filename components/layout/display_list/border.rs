@@ -8,6 +8,7 @@ use euclid::default::{Rect, SideOffsets2D as UntypedSideOffsets2D, Size2D as Unt
 use euclid::{SideOffsets2D, Size2D};
 use style::computed_values::border_image_outset::T as BorderImageOutset;
 use style::properties::style_structs::Border;
+use style::values::computed::BorderRadius as ComputedBorderRadius;
 use style::values::computed::NumberOrPercentage;
 use style::values::computed::{BorderCornerRadius, BorderImageWidth};
 use style::values::computed::{BorderImageSideWidth, NonNegativeLengthOrNumber};
@@ -103,6 +104,20 @@ pub fn radii(abs_bounds: Rect<Au>, border_style: &Border) -> BorderRadius {
     )
 }
 
+/// Determine the four corner radii of the rounded rectangle of an `inset()`
+/// shape, which are resolved like those of a border.
+pub fn shape_radii(abs_bounds: Rect<Au>, radius: &ComputedBorderRadius) -> BorderRadius {
+    overlapping_radii(
+        abs_bounds.size.to_layout(),
+        BorderRadius {
+            top_left: corner_radius(&radius.top_left, abs_bounds.size).to_layout(),
+            top_right: corner_radius(&radius.top_right, abs_bounds.size).to_layout(),
+            bottom_right: corner_radius(&radius.bottom_right, abs_bounds.size).to_layout(),
+            bottom_left: corner_radius(&radius.bottom_left, abs_bounds.size).to_layout(),
+        },
+    )
+}
+
 /// Calculates radii for the inner side.
 ///
 /// Radii usually describe the outer side of a border but for the lines to look nice
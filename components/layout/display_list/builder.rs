@@ -12,12 +12,14 @@ use crate::block::BlockFlow;
 use crate::context::LayoutContext;
 use crate::display_list::background::{self, get_cyclic};
 use crate::display_list::border;
+use crate::display_list::clip_path;
 use crate::display_list::gradient;
+use crate::display_list::items::ScrollbarThumbDisplayItem;
 use crate::display_list::items::{self, BaseDisplayItem, ClipScrollNode};
 use crate::display_list::items::{ClipScrollNodeIndex, ClipScrollNodeType, ClippingAndScrolling};
 use crate::display_list::items::{ClippingRegion, DisplayItem, DisplayItemMetadata, DisplayList};
 use crate::display_list::items::{CommonDisplayItem, DisplayListSection};
-use crate::display_list::items::{IframeDisplayItem, OpaqueNode};
+use crate::display_list::items::{IframeDisplayItem, OpaqueNode, ScrollOffsetMap};
 use crate::display_list::items::{PopAllTextShadowsDisplayItem, PushTextShadowDisplayItem};
 use crate::display_list::items::{StackingContext, StackingContextType, StickyFrameData};
 use crate::display_list::items::{TextOrientation, WebRenderImageInfo};
@@ -44,11 +46,17 @@ use ipc_channel::ipc;
 use msg::constellation_msg::PipelineId;
 use net_traits::image_cache::UsePlaceholder;
 use range::Range;
-use script_traits::IFrameSize;
+use script_layout_interface::scrollbar::WebkitScrollbarStyles;
+use script_layout_interface::scrollbar::{ScrollContainer, ScrollbarAxis, ScrollbarGeometry};
+use script_layout_interface::scrollbar::{ScrollbarMode, ScrollbarStyle, ScrollbarTheme};
+use script_traits::{IFrameSize, ScrollbarThumb};
 use servo_config::opts;
+use servo_config::pref;
 use servo_geometry::{self, MaxRect};
+use std::collections::hash_map::DefaultHasher;
 use std::default::Default;
 use std::f32;
+use std::hash::{Hash, Hasher};
 use std::mem;
 use std::sync::Arc;
 use style::computed_values::border_style::T as BorderStyle;
@@ -61,7 +69,7 @@ use style::properties::{style_structs, ComputedValues};
 use style::servo::restyle_damage::ServoRestyleDamage;
 use style::values::computed::effects::SimpleShadow;
 use style::values::computed::image::{Image, ImageLayer};
-use style::values::computed::{ClipRectOrAuto, Gradient, LengthOrAuto};
+use style::values::computed::{ClipRectOrAuto, Gradient, LengthOrAuto, Resize};
 use style::values::generics::background::BackgroundSize;
 use style::values::generics::image::{GradientKind, PaintWorklet};
 use style::values::specified::ui::CursorKind;
@@ -70,8 +78,8 @@ use style_traits::ToCss;
 use webrender_api::units::{LayoutRect, LayoutTransform, LayoutVector2D};
 use webrender_api::{self, BorderDetails, BorderRadius, BorderSide, BoxShadowClipMode, ColorF};
 use webrender_api::{ColorU, ExternalScrollId, FilterOp, GlyphInstance, ImageRendering, LineStyle};
-use webrender_api::{NinePatchBorder, NinePatchBorderSource, NormalBorder};
-use webrender_api::{ScrollSensitivity, StickyOffsetBounds};
+use webrender_api::{NinePatchBorder, NinePatchBorderSource, NormalBorder, PropertyBinding};
+use webrender_api::{PropertyBindingKey, ScrollSensitivity, StickyOffsetBounds};
 
 static THREAD_TINT_COLORS: [ColorF; 8] = [
     ColorF {
@@ -330,12 +338,21 @@ pub struct DisplayListBuildState<'a> {
 
     /// Stores text runs to answer text queries used to place a cursor inside text.
     pub indexable_text: IndexableText,
+
+    /// The scroll offsets of the scroll frames, which place the scrollbar
+    /// thumbs until the compositor moves them.
+    scroll_offsets: ScrollOffsetMap,
+
+    /// The scrollbar thumbs, which the compositor moves along their tracks as
+    /// their scroll frames scroll.
+    pub scrollbar_thumbs: Vec<ScrollbarThumb>,
 }
 
 impl<'a> DisplayListBuildState<'a> {
     pub fn new(
         layout_context: &'a LayoutContext,
         state: StackingContextCollectionState,
+        scroll_offsets: ScrollOffsetMap,
     ) -> DisplayListBuildState<'a> {
         DisplayListBuildState {
             layout_context: layout_context,
@@ -350,6 +367,8 @@ impl<'a> DisplayListBuildState<'a> {
             ),
             iframe_sizes: Vec::new(),
             indexable_text: IndexableText::default(),
+            scroll_offsets,
+            scrollbar_thumbs: Vec::new(),
         }
     }
 
@@ -1161,16 +1180,32 @@ impl Fragment {
             radius: border_radius,
             do_aa: true,
         });
-        state.add_display_item(DisplayItem::Border(CommonDisplayItem::with_data(
-            base,
-            webrender_api::BorderDisplayItem {
-                bounds: bounds.to_layout(),
-                common: items::empty_common_item_properties(),
-                widths: border_widths.to_layout(),
-                details,
-            },
-            Vec::new(),
-        )));
+        state.clipping_and_scrolling_scope(|state| {
+            // Hit testing only considers the bounds of the border, which reach
+            // beyond its rounded corners.
+            let base = if border_radius.is_zero() {
+                base
+            } else {
+                let clip_id = state.add_late_clip_node(bounds.to_layout(), border_radius);
+                state.current_clipping_and_scrolling = ClippingAndScrolling::simple(clip_id);
+                state.create_base_display_item(
+                    clip,
+                    self.node,
+                    get_cursor(&style, Cursor::Default),
+                    display_list_section,
+                )
+            };
+            state.add_display_item(DisplayItem::Border(CommonDisplayItem::with_data(
+                base,
+                webrender_api::BorderDisplayItem {
+                    bounds: bounds.to_layout(),
+                    common: items::empty_common_item_properties(),
+                    widths: border_widths.to_layout(),
+                    details,
+                },
+                Vec::new(),
+            )));
+        });
     }
 
     /// Add display item for image border.
@@ -2080,6 +2115,7 @@ impl Fragment {
 
         let indexable_text = IndexableTextItem {
             origin: stacking_relative_content_box.origin,
+            size: stacking_relative_content_box.size,
             text_run: text_fragment.run.clone(),
             range: text_fragment.range,
             baseline_origin,
@@ -2470,6 +2506,11 @@ impl BlockFlow {
                 preserved_state,
                 stacking_relative_border_box,
             );
+            self.setup_clip_scroll_node_for_clip_path(
+                state,
+                preserved_state,
+                stacking_relative_border_box,
+            );
         }
         self.base.clip = state
             .clip_stack
@@ -2693,6 +2734,49 @@ impl BlockFlow {
         state.current_clipping_and_scrolling = new_indices;
     }
 
+    /// Adds a clip node for a block to take the `clip-path` property into
+    /// account, so that it is clipped the same when painted and hit tested.
+    fn setup_clip_scroll_node_for_clip_path(
+        &mut self,
+        state: &mut StackingContextCollectionState,
+        preserved_state: &mut SavedStackingContextCollectionState,
+        stacking_relative_border_box: Rect<Au>,
+    ) {
+        let clip = {
+            let style = self.fragment.style();
+            clip_path::clip(
+                &style.get_svg().clip_path,
+                stacking_relative_border_box,
+                style.logical_border_width().to_physical(style.writing_mode),
+                self.fragment.border_padding.to_physical(style.writing_mode),
+                self.fragment.margin.to_physical(style.writing_mode),
+                border::radii(stacking_relative_border_box, style.get_border()),
+            )
+        };
+        let (clip_rect, radii) = match clip {
+            Some(clip) => clip,
+            None => return,
+        };
+
+        preserved_state.push_clip(state, clip_rect, self.positioning());
+
+        let mut clip = ClippingRegion::from_rect(clip_rect.to_layout());
+        if !radii.is_zero() {
+            clip.intersect_with_rounded_rect(clip_rect.to_layout(), radii)
+        }
+
+        let new_index = state.add_clip_scroll_node(ClipScrollNode {
+            parent_index: self.clipping_and_scrolling().scrolling,
+            clip,
+            content_rect: LayoutRect::zero(), // content_rect isn't important for clips.
+            node_type: ClipScrollNodeType::Clip,
+        });
+
+        let new_indices = ClippingAndScrolling::new(new_index, new_index);
+        self.base.clipping_and_scrolling = Some(new_indices);
+        state.current_clipping_and_scrolling = new_indices;
+    }
+
     fn create_pseudo_stacking_context_for_block(
         &mut self,
         stacking_context_type: StackingContextType,
@@ -2752,6 +2836,8 @@ impl BlockFlow {
     ) {
         let background_border_section = self.background_border_section();
 
+        self.build_display_list_for_backdrop(state);
+
         state.processing_scrolling_overflow_element = self.has_scrolling_overflow();
 
         let content_size = if state.processing_scrolling_overflow_element {
@@ -2777,9 +2863,213 @@ impl BlockFlow {
         self.base
             .build_display_items_for_debugging_tint(state, self.fragment.node);
 
+        if pref!(layout.scrollbars.enabled) {
+            self.build_display_list_for_scrollbars(state, stacking_relative_border_box);
+        }
+
         state.processing_scrolling_overflow_element = false;
     }
 
+    /// Paints the `::backdrop` of an element in the top layer, which covers the
+    /// whole viewport right below the element.
+    fn build_display_list_for_backdrop(&self, state: &mut DisplayListBuildState) {
+        let style = match self.backdrop_style {
+            Some(ref style) => style,
+            None => return,
+        };
+        let color = style.resolve_color(style.get_background().background_color);
+        if color.alpha == 0 {
+            return;
+        }
+        let base = state.create_base_display_item(
+            Rect::max_rect(),
+            self.fragment.node,
+            get_cursor(style, Cursor::Default),
+            DisplayListSection::BackgroundAndBorders,
+        );
+        state.add_display_item(DisplayItem::Rectangle(CommonDisplayItem::new(
+            base,
+            webrender_api::RectangleDisplayItem {
+                color: color.to_layout(),
+                common: items::empty_common_item_properties(),
+            },
+        )));
+    }
+
+    /// Adds the scrollbars of a scroll container, and the resizer of a resizable
+    /// element, on top of its padding box.
+    ///
+    /// The thumbs of a scroll frame are painted where they are when it isn't
+    /// scrolled, in reference frames whose transforms, and opacities for
+    /// overlay scrollbars, are bound to properties that the compositor updates
+    /// as it scrolls, so that they follow async scrolling without a display
+    /// list rebuild.
+    fn build_display_list_for_scrollbars(
+        &self,
+        state: &mut DisplayListBuildState,
+        stacking_relative_border_box: Rect<Au>,
+    ) {
+        let style = &*self.fragment.style;
+        let overflow_x = style.get_box().overflow_x;
+        let overflow_y = style.get_box().overflow_y;
+        if overflow_x == StyleOverflow::Visible && overflow_y == StyleOverflow::Visible {
+            return;
+        }
+
+        let theme = ScrollbarTheme::new(ScrollbarMode::from_prefs());
+        let scrollbar_style = self.scrollbar_style.unwrap_or_else(|| {
+            ScrollbarStyle::new(&theme, style, &WebkitScrollbarStyles::default())
+        });
+        let border_widths = style.logical_border_width().to_physical(style.writing_mode);
+        let padding_box = stacking_relative_border_box.inner_rect(border_widths);
+        let scroll_end = stacking_relative_border_box.origin +
+            self.base.overflow.scroll.origin.to_vector() +
+            self.base.overflow.scroll.size;
+        let geometry = ScrollbarGeometry::new(
+            &theme,
+            &ScrollContainer {
+                padding_box,
+                max_scroll: Size2D::new(
+                    (scroll_end.x - padding_box.max_x()).max(Au(0)),
+                    (scroll_end.y - padding_box.max_y()).max(Au(0)),
+                ),
+                scroll_position: Point2D::zero(),
+                overflow_x,
+                overflow_y,
+                thickness: theme.thickness(scrollbar_style.width),
+                resizable: style.get_box().resize != Resize::None,
+            },
+        );
+
+        let opacity = theme.opacity(None);
+        let thumb_color = scrollbar_style
+            .thumb_color
+            .map_or(theme.thumb_color, |color| color.to_layout());
+        let track_color = theme.track_color.map(|track_color| {
+            scrollbar_style
+                .track_color
+                .map_or(track_color, |color| color.to_layout())
+        });
+
+        // Scrollbars stay in place while the content scrolls underneath them.
+        let clipping_and_scrolling = if self.has_scrolling_overflow() {
+            ClippingAndScrolling::simple(
+                state.parent_clip_scroll_node_index(state.current_clipping_and_scrolling.scrolling),
+            )
+        } else {
+            state.current_clipping_and_scrolling
+        };
+        let node = self.fragment.node;
+        let add_rect = |state: &mut DisplayListBuildState, bounds: Rect<Au>, color: ColorF| {
+            if color.a <= 0. {
+                return;
+            }
+            let base = state.create_base_display_item_with_clipping_and_scrolling(
+                bounds,
+                node,
+                Some(Cursor::Default),
+                DisplayListSection::Outlines,
+                clipping_and_scrolling,
+            );
+            state.add_display_item(DisplayItem::Rectangle(CommonDisplayItem::new(
+                base,
+                webrender_api::RectangleDisplayItem {
+                    common: items::empty_common_item_properties(),
+                    color,
+                },
+            )));
+        };
+
+        let external_id = ExternalScrollId(
+            self.fragment.unique_id(),
+            state.layout_context.id.to_webrender(),
+        );
+        let scroll_offset = state
+            .scroll_offsets
+            .get(&external_id)
+            .cloned()
+            .unwrap_or_else(LayoutVector2D::zero);
+        for scrollbar in geometry.horizontal.iter().chain(geometry.vertical.iter()) {
+            if let Some(track_color) = track_color {
+                add_rect(state, scrollbar.track, track_color);
+            }
+            if !self.has_scrolling_overflow() {
+                let mut thumb_color = thumb_color;
+                thumb_color.a *= opacity;
+                add_rect(state, scrollbar.thumb, thumb_color);
+                continue;
+            }
+
+            let travel = scrollbar.thumb_travel();
+            let thumb = ScrollbarThumb {
+                scroll_id: external_id,
+                vertical: scrollbar.axis == ScrollbarAxis::Vertical,
+                transform_binding: property_binding_id(external_id, Some(scrollbar.axis)),
+                opacity_binding: match theme.mode {
+                    ScrollbarMode::Classic => None,
+                    ScrollbarMode::Overlay => Some(property_binding_id(external_id, None)),
+                },
+                thumb_offset_per_scroll: if scrollbar.max_scroll > Au(0) {
+                    travel.to_f32_px() / scrollbar.max_scroll.to_f32_px()
+                } else {
+                    0.
+                },
+                max_thumb_offset: travel.to_f32_px(),
+            };
+            let offset = thumb.offset(scroll_offset);
+            let translation = if thumb.vertical {
+                LayoutTransform::create_translation(0., offset, 0.)
+            } else {
+                LayoutTransform::create_translation(offset, 0., 0.)
+            };
+            let base = state.create_base_display_item_with_clipping_and_scrolling(
+                scrollbar.thumb,
+                node,
+                Some(Cursor::Default),
+                DisplayListSection::Outlines,
+                clipping_and_scrolling,
+            );
+            state.add_display_item(DisplayItem::ScrollbarThumb(Box::new(
+                ScrollbarThumbDisplayItem {
+                    base,
+                    item: webrender_api::RectangleDisplayItem {
+                        common: items::empty_common_item_properties(),
+                        color: thumb_color,
+                    },
+                    transform: PropertyBinding::Binding(
+                        PropertyBindingKey::new(thumb.transform_binding),
+                        translation,
+                    ),
+                    opacity: thumb
+                        .opacity_binding
+                        .map(|id| PropertyBinding::Binding(PropertyBindingKey::new(id), opacity)),
+                },
+            )));
+            state.scrollbar_thumbs.push(thumb);
+        }
+
+        let corner = match geometry.corner {
+            Some(corner) => corner,
+            None => return,
+        };
+        if let Some(track_color) = track_color {
+            add_rect(state, corner, track_color);
+        }
+        if style.get_box().resize != Resize::None {
+            // The resizer is always visible, even with overlay scrollbars.
+            let mut grip_color = thumb_color;
+            grip_color.a = theme.thumb_color.a.max(grip_color.a);
+            // A grip of dots along the diagonal of the corner.
+            let dot = corner.size.width / 5;
+            for i in 1..4 {
+                for j in (4 - i)..4 {
+                    let origin = Point2D::new(corner.origin.x + dot * j, corner.origin.y + dot * i);
+                    add_rect(state, Rect::new(origin, Size2D::new(dot, dot)), grip_color);
+                }
+            }
+        }
+    }
+
     pub fn build_display_list_for_block(
         &mut self,
         state: &mut DisplayListBuildState,
@@ -2933,6 +3223,14 @@ fn get_cursor(values: &ComputedValues, default_cursor: Cursor) -> Option<Cursor>
     })
 }
 
+/// The id of the property binding of the transform of the thumb of a scroll
+/// frame along `axis`, or of the opacity of all its thumbs if `axis` is `None`.
+fn property_binding_id(scroll_id: ExternalScrollId, axis: Option<ScrollbarAxis>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (scroll_id, axis).hash(&mut hasher);
+    hasher.finish()
+}
+
 /// Adjusts borders as appropriate to account for a fragment's status as the
 /// first or last fragment within the range of an element.
 ///
@@ -2994,6 +3292,8 @@ fn convert_text_run_to_glyphs(
 pub struct IndexableTextItem {
     /// The placement of the text item on the plane.
     pub origin: Point2D<Au>,
+    /// The size of the text item.
+    pub size: Size2D<Au>,
     /// The text run.
     pub text_run: Arc<TextRun>,
     /// The range of text within the text run.
@@ -3029,6 +3329,44 @@ impl IndexableText {
                 .range_index_of_advance(&item[0].range, offset.x),
         )
     }
+
+    /// Returns the index of the rendered character of a node that is closest to a point in the
+    /// coordinate space of the text items, counting from the start of the first text item of the
+    /// node.
+    pub fn text_index_at_point(&self, node: OpaqueNode, point: Point2D<Au>) -> Option<usize> {
+        let items = self.inner.get(&node)?;
+        // The items of a node are in line order, so the point is on the first line that doesn't
+        // end above it, or below the last one.
+        let position = items
+            .iter()
+            .position(|item| point.y < item.origin.y + item.size.height)
+            .unwrap_or(items.len() - 1);
+        let preceding_characters: usize = items
+            .windows(2)
+            .take(position)
+            .map(|pair| {
+                let (item, next) = (&pair[0], &pair[1]);
+                // Whitespace that is stripped at the end of a line belongs to the text run, but
+                // not to the range of any item.
+                let end = if Arc::ptr_eq(&item.text_run, &next.text_run) {
+                    next.range.begin()
+                } else {
+                    item.range.end()
+                };
+                item.text_run.text[item.range.begin().to_usize()..end.to_usize()]
+                    .chars()
+                    .count()
+            })
+            .sum();
+        let item = &items[position];
+        let advance = point.x - item.baseline_origin.x;
+        let index = if advance <= Au(0) {
+            0
+        } else {
+            item.text_run.range_index_of_advance(&item.range, advance)
+        };
+        Some(preceding_characters + index)
+    }
 }
 
 trait ToF32Px {
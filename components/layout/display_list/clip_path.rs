@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::display_list::border;
+use crate::display_list::ToLayout;
+use app_units::Au;
+use euclid::default::{Point2D, Rect, SideOffsets2D, Size2D};
+use style::values::computed::basic_shape::{BasicShape, ClippingShape, ShapeRadius};
+use style::values::computed::Position;
+use style::values::generics::basic_shape::{GeometryBox, ShapeBox, ShapeSource};
+use webrender_api::units::LayoutSize;
+use webrender_api::BorderRadius;
+
+/// Compute the rounded clip rect of a `clip-path`, which clips the box and its
+/// descendants both when painting and hit testing.
+///
+/// Returns `None` when the box isn't clipped, or when its shape isn't a
+/// rounded rectangle, which are the only clips WebRender can hit test.
+pub fn clip(
+    clip_path: &ClippingShape,
+    absolute_bounds: Rect<Au>,
+    border: SideOffsets2D<Au>,
+    border_padding: SideOffsets2D<Au>,
+    margin: SideOffsets2D<Au>,
+    border_radii: BorderRadius,
+) -> Option<(Rect<Au>, BorderRadius)> {
+    let (shape, reference_box) = match *clip_path {
+        ShapeSource::Shape(ref shape, reference_box) => (Some(&**shape), reference_box),
+        ShapeSource::Box(reference_box) => (None, Some(reference_box)),
+        // TODO: Support `url()` references to SVG clip paths and `path()`.
+        ShapeSource::ImageOrUrl(_) | ShapeSource::Path(_) | ShapeSource::None => return None,
+    };
+
+    // The boxes of SVG elements stand for the border box of CSS boxes.
+    let (reference_box, reference_radii) = match reference_box {
+        Some(GeometryBox::ShapeBox(ShapeBox::MarginBox)) => {
+            (absolute_bounds.outer_rect(margin), border_radii)
+        },
+        Some(GeometryBox::ShapeBox(ShapeBox::PaddingBox)) => (
+            absolute_bounds.inner_rect(border),
+            border::inner_radii(border_radii, border),
+        ),
+        Some(GeometryBox::ShapeBox(ShapeBox::ContentBox)) => (
+            absolute_bounds.inner_rect(border_padding),
+            border::inner_radii(border_radii, border_padding),
+        ),
+        Some(GeometryBox::ShapeBox(ShapeBox::BorderBox)) |
+        Some(GeometryBox::FillBox) |
+        Some(GeometryBox::StrokeBox) |
+        Some(GeometryBox::ViewBox) |
+        None => (absolute_bounds, border_radii),
+    };
+
+    match shape {
+        None => Some((reference_box, reference_radii)),
+        Some(shape) => shape_clip(shape, reference_box),
+    }
+}
+
+/// <https://drafts.csswg.org/css-shapes/#basic-shape-functions>
+fn shape_clip(shape: &BasicShape, reference_box: Rect<Au>) -> Option<(Rect<Au>, BorderRadius)> {
+    let size = reference_box.size;
+    match *shape {
+        BasicShape::Inset(ref inset) => {
+            let top = inset.rect.0.to_used_value(size.height);
+            let right = inset.rect.1.to_used_value(size.width);
+            let bottom = inset.rect.2.to_used_value(size.height);
+            let left = inset.rect.3.to_used_value(size.width);
+            let rect = Rect::new(
+                Point2D::new(reference_box.origin.x + left, reference_box.origin.y + top),
+                Size2D::new(
+                    Au(0).max(size.width - left - right),
+                    Au(0).max(size.height - top - bottom),
+                ),
+            );
+            Some((rect, border::shape_radii(rect, &inset.round)))
+        },
+        BasicShape::Circle(ref circle) => {
+            let center = shape_center(&circle.position, reference_box);
+            let to_left = center.x - reference_box.min_x();
+            let to_right = reference_box.max_x() - center.x;
+            let to_top = center.y - reference_box.min_y();
+            let to_bottom = reference_box.max_y() - center.y;
+            // Percentages refer to the normalized diagonal of the reference box.
+            let diagonal = Au::from_f32_px(
+                (size.width.to_f32_px().powi(2) + size.height.to_f32_px().powi(2)).sqrt() /
+                    2f32.sqrt(),
+            );
+            let radius = match circle.radius {
+                ShapeRadius::Length(ref length) => length.to_used_value(diagonal),
+                ShapeRadius::ClosestSide => to_left.min(to_right).min(to_top).min(to_bottom),
+                ShapeRadius::FarthestSide => to_left.max(to_right).max(to_top).max(to_bottom),
+            };
+            Some(ellipse_clip(center, Size2D::new(radius, radius)))
+        },
+        BasicShape::Ellipse(ref ellipse) => {
+            let center = shape_center(&ellipse.position, reference_box);
+            let radius_x = semiaxis(
+                &ellipse.semiaxis_x,
+                size.width,
+                center.x - reference_box.min_x(),
+                reference_box.max_x() - center.x,
+            );
+            let radius_y = semiaxis(
+                &ellipse.semiaxis_y,
+                size.height,
+                center.y - reference_box.min_y(),
+                reference_box.max_y() - center.y,
+            );
+            Some(ellipse_clip(center, Size2D::new(radius_x, radius_y)))
+        },
+        // TODO: WebRender can't clip to arbitrary polygons.
+        BasicShape::Polygon(_) => None,
+    }
+}
+
+fn shape_center(position: &Position, reference_box: Rect<Au>) -> Point2D<Au> {
+    Point2D::new(
+        reference_box.origin.x + position.horizontal.to_used_value(reference_box.size.width),
+        reference_box.origin.y + position.vertical.to_used_value(reference_box.size.height),
+    )
+}
+
+fn semiaxis(radius: &ShapeRadius, length: Au, to_start: Au, to_end: Au) -> Au {
+    match *radius {
+        ShapeRadius::Length(ref radius) => radius.to_used_value(length),
+        ShapeRadius::ClosestSide => to_start.min(to_end),
+        ShapeRadius::FarthestSide => to_start.max(to_end),
+    }
+}
+
+/// An ellipse is a rectangle whose corner radii are its semiaxes.
+fn ellipse_clip(center: Point2D<Au>, radii: Size2D<Au>) -> (Rect<Au>, BorderRadius) {
+    let rect = Rect::new(
+        Point2D::new(center.x - radii.width, center.y - radii.height),
+        Size2D::new(radii.width * 2, radii.height * 2),
+    );
+    let radii: LayoutSize = radii.to_layout();
+    (rect, BorderRadius::uniform_size(radii))
+}
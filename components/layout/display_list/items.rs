@@ -27,7 +27,7 @@ use webrender_api as wr;
 use webrender_api::units::{LayoutPixel, LayoutPoint, LayoutRect, LayoutSize, LayoutTransform};
 use webrender_api::{
     BorderRadius, ClipId, ClipMode, CommonItemProperties, ComplexClipRegion, ExternalScrollId,
-    FilterOp, GlyphInstance, GradientStop, ImageKey, MixBlendMode, PrimitiveFlags,
+    FilterOp, GlyphInstance, GradientStop, ImageKey, MixBlendMode, PrimitiveFlags, PropertyBinding,
     ScrollSensitivity, Shadow, SpatialId, StickyOffsetBounds, TransformStyle,
 };
 
@@ -384,6 +384,7 @@ pub enum DisplayItem {
     RadialGradient(Box<CommonDisplayItem<wr::RadialGradientDisplayItem, Vec<GradientStop>>>),
     Line(Box<CommonDisplayItem<wr::LineDisplayItem>>),
     BoxShadow(Box<CommonDisplayItem<wr::BoxShadowDisplayItem>>),
+    ScrollbarThumb(Box<ScrollbarThumbDisplayItem>),
     PushTextShadow(Box<PushTextShadowDisplayItem>),
     PopAllTextShadows(Box<PopAllTextShadowsDisplayItem>),
     Iframe(Box<IframeDisplayItem>),
@@ -624,6 +625,23 @@ impl<T, U> CommonDisplayItem<T, U> {
     }
 }
 
+/// A scrollbar thumb, which the compositor moves along its track as its scroll
+/// frame scrolls.
+#[derive(Clone, Serialize)]
+pub struct ScrollbarThumbDisplayItem {
+    /// Fields common to all display items.
+    pub base: BaseDisplayItem,
+
+    /// The thumb where it is when its scroll frame isn't scrolled.
+    pub item: wr::RectangleDisplayItem,
+
+    /// The translation of the thumb along its track.
+    pub transform: PropertyBinding<LayoutTransform>,
+
+    /// The opacity of the thumb, if it is an overlay scrollbar that fades out.
+    pub opacity: Option<PropertyBinding<f32>>,
+}
+
 /// Defines a text shadow that affects all items until the paired PopTextShadow.
 #[derive(Clone, Serialize)]
 pub struct PushTextShadowDisplayItem {
@@ -680,6 +698,7 @@ impl DisplayItem {
             DisplayItem::RadialGradient(ref gradient) => &gradient.base,
             DisplayItem::Line(ref line) => &line.base,
             DisplayItem::BoxShadow(ref box_shadow) => &box_shadow.base,
+            DisplayItem::ScrollbarThumb(ref thumb) => &thumb.base,
             DisplayItem::PushTextShadow(ref push_text_shadow) => &push_text_shadow.base,
             DisplayItem::PopAllTextShadows(ref pop_text_shadow) => &pop_text_shadow.base,
             DisplayItem::Iframe(ref iframe) => &iframe.base,
@@ -712,6 +731,7 @@ impl DisplayItem {
             DisplayItem::RadialGradient(ref item) => item.item.bounds,
             DisplayItem::Line(ref item) => item.item.area,
             DisplayItem::BoxShadow(ref item) => item.item.box_bounds,
+            DisplayItem::ScrollbarThumb(ref item) => item.item.common.clip_rect,
             DisplayItem::PushTextShadow(_) => LayoutRect::zero(),
             DisplayItem::PopAllTextShadows(_) => LayoutRect::zero(),
             DisplayItem::Iframe(ref item) => item.bounds,
@@ -749,6 +769,7 @@ impl fmt::Debug for DisplayItem {
                 DisplayItem::RadialGradient(_) => "RadialGradient",
                 DisplayItem::Line(_) => "Line",
                 DisplayItem::BoxShadow(_) => "BoxShadow",
+                DisplayItem::ScrollbarThumb(_) => "ScrollbarThumb",
                 DisplayItem::PushTextShadow(_) => "PushTextShadow",
                 DisplayItem::PopAllTextShadows(_) => "PopTextShadow",
                 DisplayItem::Iframe(_) => "Iframe",
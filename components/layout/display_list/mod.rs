@@ -12,6 +12,7 @@ pub use self::conversions::ToLayout;
 mod background;
 mod border;
 mod builder;
+mod clip_path;
 mod conversions;
 mod gradient;
 pub mod items;
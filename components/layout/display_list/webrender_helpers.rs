@@ -12,9 +12,9 @@ use crate::display_list::items::{DisplayItem, DisplayList, StackingContextType};
 use msg::constellation_msg::PipelineId;
 use webrender_api::units::LayoutPoint;
 use webrender_api::{
-    self, ClipId, CommonItemProperties, DisplayItem as WrDisplayItem, DisplayListBuilder,
-    PrimitiveFlags, PropertyBinding, PushStackingContextDisplayItem, RasterSpace,
-    ReferenceFrameKind, SpaceAndClipInfo, SpatialId, StackingContext,
+    self, ClipId, CommonItemProperties, DisplayItem as WrDisplayItem, DisplayListBuilder, FilterOp,
+    MixBlendMode, PrimitiveFlags, PropertyBinding, PushStackingContextDisplayItem, RasterSpace,
+    ReferenceFrameKind, SpaceAndClipInfo, SpatialId, StackingContext, TransformStyle,
 };
 
 struct ClipScrollState {
@@ -156,6 +156,45 @@ impl DisplayItem {
                 builder.push_item(&WrDisplayItem::BoxShadow(item.item));
                 IsContentful(false)
             },
+            DisplayItem::ScrollbarThumb(ref mut item) => {
+                let common = build_common_item_properties(&item.base, state);
+                let spatial_id = builder.push_reference_frame(
+                    LayoutPoint::zero(),
+                    common.spatial_id,
+                    TransformStyle::Flat,
+                    item.transform,
+                    ReferenceFrameKind::Transform,
+                );
+                if let Some(opacity) = item.opacity {
+                    let value = match opacity {
+                        PropertyBinding::Value(value) | PropertyBinding::Binding(_, value) => value,
+                    };
+                    builder.push_item(&WrDisplayItem::SetFilterOps);
+                    builder.push_iter(&[FilterOp::Opacity(opacity, value)]);
+                }
+                builder.push_item(&WrDisplayItem::PushStackingContext(
+                    PushStackingContextDisplayItem {
+                        origin: LayoutPoint::zero(),
+                        spatial_id,
+                        prim_flags: PrimitiveFlags::default(),
+                        stacking_context: StackingContext {
+                            transform_style: TransformStyle::Flat,
+                            mix_blend_mode: MixBlendMode::Normal,
+                            clip_id: None,
+                            raster_space: RasterSpace::Screen,
+                            cache_tiles: false,
+                            is_backdrop_root: false,
+                        },
+                    },
+                ));
+                item.item.common = CommonItemProperties {
+                    spatial_id,
+                    ..common
+                };
+                builder.push_item(&WrDisplayItem::Rectangle(item.item));
+                builder.pop_stacking_context();
+                IsContentful(false)
+            },
             DisplayItem::PushTextShadow(ref mut item) => {
                 let common = build_common_item_properties(&item.base, state);
                 builder.push_shadow(
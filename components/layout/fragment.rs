@@ -62,6 +62,7 @@ use style::servo::restyle_damage::ServoRestyleDamage;
 use style::str::char_is_whitespace;
 use style::values::computed::counters::ContentItem;
 use style::values::computed::{Length, Size, VerticalAlign};
+use style::values::generics::basic_shape::ShapeSource;
 use style::values::generics::box_::{Perspective, VerticalAlignKeyword};
 use style::values::generics::transform;
 use webrender_api;
@@ -2752,6 +2753,10 @@ impl Fragment {
             return true;
         }
 
+        if self.style().get_svg().clip_path != ShapeSource::None {
+            return true;
+        }
+
         if self.style().get_box().transform_style == TransformStyle::Preserve3d ||
             self.style().overrides_transform_style()
         {
@@ -9,7 +9,9 @@ use crate::context::LayoutContext;
 use crate::display_list::items::{DisplayList, OpaqueNode, ScrollOffsetMap};
 use crate::display_list::IndexableText;
 use crate::flow::{Flow, GetBaseFlow};
-use crate::fragment::{Fragment, FragmentBorderBoxIterator, SpecificFragmentInfo};
+use crate::fragment::{
+    CoordinateSystem, Fragment, FragmentBorderBoxIterator, SpecificFragmentInfo,
+};
 use crate::inline::InlineFragmentNodeFlags;
 use crate::opaque_node::OpaqueNodeMethods;
 use crate::sequential;
@@ -19,7 +21,8 @@ use euclid::default::{Point2D, Rect, Size2D, Vector2D};
 use euclid::Size2D as TypedSize2D;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::ScrollbarStyleResponse;
+use script_layout_interface::rpc::{BoxQuad, BoxQuadsResponse, TextIndexResponse};
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
 use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, StyleResponse};
@@ -31,6 +34,7 @@ use script_layout_interface::{LayoutElementType, LayoutNodeType};
 use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::UntrustedNodeAddress;
 use std::cmp::{max, min};
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 use style::computed_values::display::T as Display;
@@ -42,6 +46,7 @@ use style::logical_geometry::{BlockFlowDirection, InlineBaseDirection, WritingMo
 use style::properties::{style_structs, LonghandId, PropertyDeclarationId, PropertyId};
 use style::selector_parser::PseudoElement;
 use style_traits::{CSSPixel, ToCss};
+use webrender_api::units::LayoutTransform;
 use webrender_api::ExternalScrollId;
 
 /// Mutable data belonging to the LayoutThread.
@@ -62,6 +67,9 @@ pub struct LayoutThreadData {
     /// A queued response for the content boxes of a node.
     pub content_boxes_response: Vec<Rect<Au>>,
 
+    /// A queued response for the transformed border boxes of a node.
+    pub box_quads_response: Vec<BoxQuad>,
+
     /// A queued response for the client {top, left, width, height} of a node in pixels.
     pub client_rect_response: Rect<i32>,
 
@@ -80,6 +88,9 @@ pub struct LayoutThreadData {
     /// A queued response for the style of a node.
     pub style_response: StyleResponse,
 
+    /// A queued response for the style of the scrollbars of a node.
+    pub scrollbar_style_response: ScrollbarStyleResponse,
+
     /// Scroll offsets of scrolling regions.
     pub scroll_offsets: ScrollOffsetMap,
 
@@ -137,6 +148,13 @@ impl LayoutRPC for LayoutRPCImpl {
         ContentBoxesResponse(rw_data.content_boxes_response.clone())
     }
 
+    /// Requests the border boxes of a node and their transforms, as in the `getBoxQuads()` call.
+    fn box_quads(&self) -> BoxQuadsResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        BoxQuadsResponse(rw_data.box_quads_response.clone())
+    }
+
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress> {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -186,6 +204,12 @@ impl LayoutRPC for LayoutRPCImpl {
         rw_data.style_response.clone()
     }
 
+    fn scrollbar_style(&self) -> ScrollbarStyleResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.scrollbar_style_response.clone()
+    }
+
     fn text_index(&self) -> TextIndexResponse {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -256,6 +280,24 @@ impl FragmentBorderBoxIterator for CollectingFragmentBorderBoxIterator {
     }
 }
 
+/// Unions the border boxes of the fragments of every node.
+struct NodeBorderBoxesIterator {
+    boxes: HashMap<OpaqueNode, Rect<Au>>,
+}
+
+impl FragmentBorderBoxIterator for NodeBorderBoxesIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        self.boxes
+            .entry(fragment.node)
+            .and_modify(|rect| *rect = rect.union(border_box))
+            .or_insert(*border_box);
+    }
+
+    fn should_process(&mut self, _: &Fragment) -> bool {
+        true
+    }
+}
+
 enum Side {
     Left,
     Right,
@@ -396,6 +438,104 @@ pub fn process_content_boxes_request(
     iterator.rects
 }
 
+/// Returns the border boxes of all the nodes that have fragments, in CSS
+/// pixels, for measuring layout shifts.
+pub fn process_node_border_boxes_request(
+    layout_root: &mut dyn Flow,
+) -> HashMap<UntrustedNodeAddress, Rect<f32>> {
+    let mut iterator = NodeBorderBoxesIterator {
+        boxes: HashMap::new(),
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    iterator
+        .boxes
+        .into_iter()
+        .map(|(node, rect)| {
+            let rect = Rect::new(
+                Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+                Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+            );
+            (node.to_untrusted_node_address(), rect)
+        })
+        .collect()
+}
+
+/// Collects the border boxes of the fragments of a node, along with the
+/// transform of the stacking context they are in at the time they are visited.
+struct TransformedFragmentBorderBoxIterator {
+    node_address: OpaqueNode,
+    transform: LayoutTransform,
+    boxes: Vec<BoxQuad>,
+}
+
+impl FragmentBorderBoxIterator for TransformedFragmentBorderBoxIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        let writing_mode = fragment.style.writing_mode;
+        let border = fragment.border_width();
+        self.boxes.push(BoxQuad {
+            border_box: *border_box,
+            margin: fragment.margin.to_physical(writing_mode),
+            border: border.to_physical(writing_mode),
+            padding: (fragment.border_padding - border).to_physical(writing_mode),
+            transform: self.transform.to_untyped(),
+        });
+    }
+
+    fn should_process(&mut self, fragment: &Fragment) -> bool {
+        fragment.contains_node(self.node_address)
+    }
+}
+
+/// Returns the border boxes of the fragments of a node, each relative to its
+/// stacking context, together with the transform that maps the coordinate
+/// space of that stacking context to the one of the initial containing block.
+pub fn process_box_quads_request(
+    requested_node: OpaqueNode,
+    layout_root: &mut dyn Flow,
+) -> Vec<BoxQuad> {
+    fn doit(
+        flow: &mut dyn Flow,
+        level: i32,
+        iterator: &mut TransformedFragmentBorderBoxIterator,
+        transform: &LayoutTransform,
+    ) {
+        iterator.transform = *transform;
+        flow.iterate_through_fragment_border_boxes(iterator, level, &Point2D::zero());
+
+        for kid in flow.mut_base().child_iter_mut() {
+            let mut kid_transform = *transform;
+            if kid.is_block_flow() && kid.as_block().fragment.establishes_stacking_context() {
+                let origin = Point2D::new(kid.as_block().fragment.margin.inline_start, Au(0)) +
+                    kid.base().stacking_relative_position;
+                let border_box = kid
+                    .as_block()
+                    .stacking_relative_border_box(CoordinateSystem::Own);
+                let local_transform = kid
+                    .as_block()
+                    .fragment
+                    .transform_matrix(&border_box)
+                    .unwrap_or(LayoutTransform::identity());
+                kid_transform = local_transform
+                    .post_transform(&LayoutTransform::create_translation(
+                        origin.x.to_f32_px(),
+                        origin.y.to_f32_px(),
+                        0.,
+                    ))
+                    .post_transform(transform);
+            }
+            doit(kid, level + 1, iterator, &kid_transform);
+        }
+    }
+
+    let mut iterator = TransformedFragmentBorderBoxIterator {
+        node_address: requested_node,
+        transform: LayoutTransform::identity(),
+        boxes: vec![],
+    };
+    doit(layout_root, 0, &mut iterator, &LayoutTransform::identity());
+    iterator.boxes
+}
+
 struct FragmentClientRectQueryIterator {
     node_address: OpaqueNode,
     client_rect: Rect<i32>,
@@ -810,7 +950,11 @@ where
     let layout_el = match *pseudo {
         Some(PseudoElement::Before) => layout_el.get_before_pseudo(),
         Some(PseudoElement::After) => layout_el.get_after_pseudo(),
+        Some(PseudoElement::Backdrop) |
         Some(PseudoElement::DetailsSummary) |
+        Some(PseudoElement::WebkitScrollbar) |
+        Some(PseudoElement::WebkitScrollbarThumb) |
+        Some(PseudoElement::WebkitScrollbarTrack) |
         Some(PseudoElement::DetailsContent) |
         Some(PseudoElement::Selection) => None,
         // FIXME(emilio): What about the other pseudos? Probably they shouldn't
@@ -976,6 +1120,17 @@ pub fn process_style_query<N: LayoutNode>(requested_node: N) -> StyleResponse {
     StyleResponse(data.map(|d| d.styles.primary().clone()))
 }
 
+pub fn process_scrollbar_style_query<N: LayoutNode>(
+    context: &LayoutContext,
+    requested_node: N,
+) -> ScrollbarStyleResponse {
+    if requested_node.as_element().unwrap().borrow_data().is_none() {
+        return ScrollbarStyleResponse(None);
+    }
+    let element = requested_node.to_threadsafe().as_element().unwrap();
+    ScrollbarStyleResponse(element.scrollbar_style(&context.style_context))
+}
+
 enum InnerTextItem {
     Text(String),
     RequiredLineBreakCount(u32),
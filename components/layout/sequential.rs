@@ -5,6 +5,7 @@
 //! Implements sequential traversals over the DOM and flow trees.
 
 use crate::context::LayoutContext;
+use crate::display_list::items::ScrollOffsetMap;
 use crate::display_list::items::{self, CommonDisplayItem, DisplayItem, DisplayListSection};
 use crate::display_list::{DisplayListBuildState, StackingContextCollectionState};
 use crate::floats::SpeculatedFloatPlacement;
@@ -75,11 +76,12 @@ pub fn build_display_list_for_subtree<'a>(
     layout_context: &'a LayoutContext,
     background_color: webrender_api::ColorF,
     client_size: Size2D<Au>,
+    scroll_offsets: ScrollOffsetMap,
 ) -> DisplayListBuildState<'a> {
     let mut state = StackingContextCollectionState::new(layout_context.id);
     flow_root.collect_stacking_contexts(&mut state);
 
-    let mut state = DisplayListBuildState::new(layout_context, state);
+    let mut state = DisplayListBuildState::new(layout_context, state, scroll_offsets);
 
     // Create a base rectangle for the page background based on the root
     // background color.
@@ -245,9 +245,24 @@ impl TextRunScanner {
                 let (mut start_position, mut end_position) = (0, 0);
                 for (byte_index, character) in text.char_indices() {
                     if !character.is_control() {
-                        let font = font_group
-                            .borrow_mut()
-                            .find_by_codepoint(&mut font_context, character);
+                        // Keep the parts of an emoji sequence in the font of its first
+                        // character, so that they get shaped together into one glyph rather
+                        // than falling back to different fonts piece by piece.
+                        let keeps_font = match run_info.font {
+                            Some(ref font) => {
+                                util::is_emoji_sequence_continuation(character) ||
+                                    (text[..byte_index].ends_with('\u{200D}') &&
+                                        font.borrow().has_glyph_for(character))
+                            },
+                            None => false,
+                        };
+                        let font = if keeps_font {
+                            run_info.font.clone()
+                        } else {
+                            font_group
+                                .borrow_mut()
+                                .find_by_codepoint(&mut font_context, character)
+                        };
 
                         let bidi_level = match bidi_levels {
                             Some(levels) => levels[*paragraph_bytes_processed],
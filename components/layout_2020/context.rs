@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::display_list::WebRenderImageInfo;
+use euclid::Size2D;
 use fnv::FnvHashMap;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context::FontContext;
@@ -11,11 +12,17 @@ use net_traits::image_cache::{CanRequestImages, ImageCache, ImageState};
 use net_traits::image_cache::{ImageOrMetadataAvailable, UsePlaceholder};
 use parking_lot::RwLock;
 use script_layout_interface::{PendingImage, PendingImageState};
+use script_traits::Painter;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::cell::RefCell;
 use std::sync::{Arc, Mutex};
-use style::context::SharedStyleContext;
+use style::context::{RegisteredSpeculativePainter, SharedStyleContext};
 use style::dom::OpaqueNode;
+use style::properties::ComputedValues;
+use style::values::generics::image::PaintWorklet;
+use style::Atom;
+use style_traits::CSSPixel;
+use style_traits::ToCss;
 
 pub struct LayoutContext<'a> {
     pub id: PipelineId,
@@ -37,6 +44,9 @@ pub struct LayoutContext<'a> {
 
     pub webrender_image_cache:
         Arc<RwLock<FnvHashMap<(ServoUrl, UsePlaceholder), WebRenderImageInfo>>>,
+
+    /// Paint worklets
+    pub registered_painters: &'a dyn RegisteredPainters,
 }
 
 impl<'a> Drop for LayoutContext<'a> {
@@ -150,6 +160,53 @@ impl<'a> LayoutContext<'a> {
             None | Some(ImageOrMetadataAvailable::MetadataAvailable(_)) => None,
         }
     }
+
+    /// Draws a paint worklet image of the given size, and returns its
+    /// WebRender image info, or None if the worklet isn't registered.
+    /// The image URLs the worklet is missing are passed to the image cache
+    /// for loading.
+    pub fn get_webrender_image_for_paint_worklet(
+        &self,
+        node: OpaqueNode,
+        style: &ComputedValues,
+        paint_worklet: &PaintWorklet,
+        size: Size2D<f32, CSSPixel>,
+    ) -> Option<WebRenderImageInfo> {
+        let painter = self.registered_painters.get(&paint_worklet.name)?;
+        let properties = painter
+            .properties()
+            .iter()
+            .filter_map(|(name, id)| id.as_shorthand().err().map(|id| (name, id)))
+            .map(|(name, id)| (name.clone(), style.computed_value_to_string(id)))
+            .collect();
+        let arguments = paint_worklet
+            .arguments
+            .iter()
+            .map(|argument| argument.to_css_string())
+            .collect();
+        let device_pixel_ratio = self.style_context.device_pixel_ratio();
+        let draw_result = painter
+            .draw_a_paint_image(size, device_pixel_ratio, properties, arguments)
+            .ok()?;
+
+        for url in draw_result.missing_image_urls {
+            self.get_webrender_image_for_url(node, url, UsePlaceholder::No);
+        }
+        Some(WebRenderImageInfo {
+            width: draw_result.width,
+            height: draw_result.height,
+            key: draw_result.image_key,
+        })
+    }
+}
+
+/// A registered painter
+pub trait RegisteredPainter: RegisteredSpeculativePainter + Painter {}
+
+/// A set of registered painters
+pub trait RegisteredPainters: Sync {
+    /// Look up a painter
+    fn get(&self, name: &Atom) -> Option<&dyn RegisteredPainter>;
 }
 
 pub(crate) type LayoutFontContext = FontContext<FontCacheThread>;
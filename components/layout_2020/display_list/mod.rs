@@ -135,6 +135,16 @@ impl Fragment {
                     wr::ColorF::WHITE,
                 );
             },
+            Fragment::Rule(r) => {
+                builder.is_contentful = true;
+                let rect = r
+                    .rect
+                    .to_physical(r.style.writing_mode, containing_block)
+                    .translate(containing_block.origin.to_vector());
+                let mut common = builder.common_properties(rect.to_webrender());
+                common.hit_info = hit_info(&r.style, r.tag, Cursor::Default);
+                builder.wr.push_rect(&common, rgba(r.style.clone_color()));
+            },
         }
     }
 }
@@ -330,29 +340,32 @@ impl<'a> BuilderForBoxFragment<'a> {
                         if let Some(layer) =
                             background::layout_layer(self, builder, index, intrinsic)
                         {
-                            let image_rendering =
-                                image_rendering(self.fragment.style.clone_image_rendering());
-                            if layer.repeat {
-                                builder.wr.push_repeating_image(
-                                    &layer.common,
-                                    layer.bounds,
-                                    layer.tile_size,
-                                    layer.tile_spacing,
-                                    image_rendering,
-                                    wr::AlphaType::PremultipliedAlpha,
-                                    key,
-                                    wr::ColorF::WHITE,
-                                )
-                            } else {
-                                builder.wr.push_image(
-                                    &layer.common,
-                                    layer.bounds,
-                                    image_rendering,
-                                    wr::AlphaType::PremultipliedAlpha,
-                                    key,
-                                    wr::ColorF::WHITE,
-                                )
-                            }
+                            self.build_background_image(builder, &layer, key)
+                        }
+                    },
+                    Image::PaintWorklet(paint_worklet) => {
+                        // https://drafts.css-houdini.org/css-paint-api/#paint-notation
+                        // Paint images have no intrinsic dimensions,
+                        // and are drawn at the size of the background tile.
+                        let intrinsic = IntrinsicSizes {
+                            width: None,
+                            height: None,
+                            ratio: None,
+                        };
+                        if let Some(layer) =
+                            background::layout_layer(self, builder, index, intrinsic)
+                        {
+                            let size = Size2D::new(layer.tile_size.width, layer.tile_size.height);
+                            let key = match builder.context.get_webrender_image_for_paint_worklet(
+                                self.fragment.tag,
+                                &self.fragment.style,
+                                paint_worklet,
+                                size,
+                            ) {
+                                Some(WebRenderImageInfo { key: Some(key), .. }) => key,
+                                _ => continue,
+                            };
+                            self.build_background_image(builder, &layer, key)
                         }
                     },
                     // Gecko-only value, represented as a (boxed) empty enum on non-Gecko.
@@ -362,6 +375,36 @@ impl<'a> BuilderForBoxFragment<'a> {
         }
     }
 
+    fn build_background_image(
+        &self,
+        builder: &mut DisplayListBuilder,
+        layer: &background::BackgroundLayer,
+        key: wr::ImageKey,
+    ) {
+        let image_rendering = image_rendering(self.fragment.style.clone_image_rendering());
+        if layer.repeat {
+            builder.wr.push_repeating_image(
+                &layer.common,
+                layer.bounds,
+                layer.tile_size,
+                layer.tile_spacing,
+                image_rendering,
+                wr::AlphaType::PremultipliedAlpha,
+                key,
+                wr::ColorF::WHITE,
+            )
+        } else {
+            builder.wr.push_image(
+                &layer.common,
+                layer.bounds,
+                image_rendering,
+                wr::AlphaType::PremultipliedAlpha,
+                key,
+                wr::ColorF::WHITE,
+            )
+        }
+    }
+
     fn build_border(&mut self, builder: &mut DisplayListBuilder) {
         let b = self.fragment.style.get_border();
         let widths = SideOffsets2D::new(
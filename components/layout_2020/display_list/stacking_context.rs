@@ -245,7 +245,7 @@ impl Fragment {
             Fragment::Anonymous(fragment) => {
                 fragment.build_stacking_context_tree(builder, containing_block, stacking_context)
             },
-            Fragment::Text(_) | Fragment::Image(_) => {
+            Fragment::Text(_) | Fragment::Image(_) | Fragment::Rule(_) => {
                 stacking_context.fragments.push(StackingContextFragment {
                     section: StackingContextSection::Content,
                     space_and_clip: builder.current_space_and_clip,
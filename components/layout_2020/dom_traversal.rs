@@ -17,7 +17,7 @@ use script_layout_interface::wrapper_traits::{
 use servo_arc::Arc as ServoArc;
 use std::marker::PhantomData as marker;
 use std::sync::Arc;
-use style::dom::{OpaqueNode, TNode};
+use style::dom::{OpaqueNode, TElement, TNode};
 use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 use style::values::generics::counters::Content;
@@ -359,6 +359,12 @@ pub(crate) trait NodeExt<'dom>: 'dom + Copy + LayoutNode + Send + Sync {
     fn parent_node(self) -> Option<Self>;
     fn style(self, context: &LayoutContext) -> ServoArc<ComputedValues>;
 
+    /// Returns the local name of this node if it is a MathML element.
+    fn mathml_local_name(self) -> Option<LocalName>;
+
+    /// Returns the value of an attribute without namespace if this node is an element.
+    fn attribute(self, name: &str) -> Option<String>;
+
     fn as_opaque(self) -> OpaqueNode;
     fn layout_data_mut(&self) -> AtomicRefMut<LayoutDataForElement>;
     fn element_box_slot(&self) -> BoxSlot<'dom>;
@@ -415,6 +421,22 @@ where
         self.to_threadsafe().style(context.shared_context())
     }
 
+    fn mathml_local_name(self) -> Option<LocalName> {
+        let element = self.as_element()?;
+        if element.is_mathml_element() {
+            Some(element.local_name().clone())
+        } else {
+            None
+        }
+    }
+
+    fn attribute(self, name: &str) -> Option<String> {
+        let element = self.to_threadsafe().as_element()?;
+        element
+            .get_attr(&ns!(), &LocalName::from(name))
+            .map(|value| value.to_string())
+    }
+
     fn as_opaque(self) -> OpaqueNode {
         self.opaque()
     }
@@ -4,6 +4,8 @@
 
 use crate::flow::inline::InlineLevelBox;
 use crate::flow::BlockLevelBox;
+use crate::formatting_contexts::IndependentFormattingContext;
+use crate::grid::GridLevelBox;
 use atomic_refcell::AtomicRefCell;
 use servo_arc::Arc;
 
@@ -23,4 +25,6 @@ pub(super) enum LayoutBox {
     DisplayContents,
     BlockLevel(Arc<BlockLevelBox>),
     InlineLevel(Arc<InlineLevelBox>),
+    GridLevel(Arc<GridLevelBox>),
+    MathLevel(Arc<IndependentFormattingContext>),
 }
@@ -37,6 +37,27 @@ impl BlockFormattingContext {
         };
         (bfc, inline_content_sizes)
     }
+
+    /// Constructs the block formatting context of an anonymous block box
+    /// that only contains the given text runs.
+    pub fn construct_for_text_runs(
+        context: &LayoutContext,
+        text_runs: Vec<TextRun>,
+        content_sizes: ContentSizesRequest,
+    ) -> (Self, BoxContentSizes) {
+        let ifc = InlineFormattingContext {
+            inline_level_boxes: text_runs
+                .into_iter()
+                .map(|text_run| Arc::new(InlineLevelBox::TextRun(text_run)))
+                .collect(),
+        };
+        let content_sizes = content_sizes.compute(|| ifc.inline_content_sizes(context));
+        let bfc = Self {
+            contents: BlockContainer::InlineFormattingContext(ifc),
+            contains_floats: false,
+        };
+        (bfc, content_sizes)
+    }
 }
 
 struct BlockLevelJob<'dom, Node> {
@@ -23,6 +23,7 @@ use euclid::default::{Point2D, Rect, Size2D};
 use gfx_traits::print_tree::PrintTree;
 use script_layout_interface::wrapper_traits::LayoutNode;
 use servo_arc::Arc;
+use std::collections::HashMap;
 use style::dom::OpaqueNode;
 use style::properties::ComputedValues;
 use style::values::computed::Length;
@@ -274,6 +275,7 @@ impl FragmentTreeRoot {
                 Fragment::Box(_) |
                 Fragment::Text(_) |
                 Fragment::Image(_) |
+                Fragment::Rule(_) |
                 Fragment::Anonymous(_) => return None,
             };
 
@@ -295,6 +297,41 @@ impl FragmentTreeRoot {
         )
     }
 
+    /// Returns the border boxes of all the nodes that have box or text
+    /// fragments, in CSS pixels.
+    pub fn get_border_boxes(&self) -> HashMap<OpaqueNode, Rect<f32>> {
+        let mut boxes = HashMap::new();
+        self.find(|fragment, containing_block| {
+            let (tag, fragment_relative_rect) = match fragment {
+                Fragment::Box(fragment) => (
+                    fragment.tag,
+                    fragment
+                        .border_rect()
+                        .to_physical(fragment.style.writing_mode, &containing_block),
+                ),
+                Fragment::Text(fragment) => (
+                    fragment.tag,
+                    fragment
+                        .rect
+                        .to_physical(fragment.parent_style.writing_mode, &containing_block),
+                ),
+                Fragment::Image(_) | Fragment::Rule(_) | Fragment::Anonymous(_) => return None,
+            };
+
+            let rect = fragment_relative_rect.translate(containing_block.origin.to_vector());
+            let rect = Rect::new(
+                Point2D::new(rect.origin.x.px(), rect.origin.y.px()),
+                Size2D::new(rect.size.width.px(), rect.size.height.px()),
+            );
+            boxes
+                .entry(tag)
+                .and_modify(|border_box: &mut Rect<f32>| *border_box = border_box.union(&rect))
+                .or_insert(rect);
+            None::<()>
+        });
+        boxes
+    }
+
     pub fn get_border_dimensions_for_node(&self, requested_node: OpaqueNode) -> Rect<i32> {
         self.find(|fragment, containing_block| {
             let (style, padding_rect) = match fragment {
@@ -304,6 +341,7 @@ impl FragmentTreeRoot {
                 Fragment::Box(_) |
                 Fragment::Text(_) |
                 Fragment::Image(_) |
+                Fragment::Rule(_) |
                 Fragment::Anonymous(_) => return None,
             };
 
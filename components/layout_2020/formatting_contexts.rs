@@ -4,8 +4,11 @@
 
 use crate::context::LayoutContext;
 use crate::dom_traversal::{Contents, NodeExt};
+use crate::flow::inline::TextRun;
 use crate::flow::BlockFormattingContext;
 use crate::fragments::Fragment;
+use crate::grid::GridFormattingContext;
+use crate::math::MathFormattingContext;
 use crate::positioned::PositioningContext;
 use crate::replaced::ReplacedContent;
 use crate::sizing::{BoxContentSizes, ContentSizesRequest};
@@ -45,6 +48,12 @@ enum IndependentFormattingContextContents {
 
     // Not called FC in specs, but behaves close enough
     Replaced(ReplacedContent),
+
+    /// https://drafts.csswg.org/css-grid/
+    Grid(GridFormattingContext),
+
+    /// https://mathml-refresh.github.io/mathml-core/#layout-algorithms
+    Math(MathFormattingContext),
     // Other layout modes go here
 }
 
@@ -52,6 +61,8 @@ pub(crate) struct NonReplacedIFC<'a>(NonReplacedIFCKind<'a>);
 
 enum NonReplacedIFCKind<'a> {
     Flow(&'a BlockFormattingContext),
+    Grid(&'a GridFormattingContext),
+    Math(&'a MathFormattingContext),
 }
 
 impl IndependentFormattingContext {
@@ -80,6 +91,36 @@ impl IndependentFormattingContext {
                         contents: IndependentFormattingContextContents::Flow(bfc),
                     }
                 },
+                DisplayInside::Grid => {
+                    let (grid, content_sizes) = GridFormattingContext::construct(
+                        context,
+                        node,
+                        &style,
+                        non_replaced,
+                        content_sizes,
+                    );
+                    Self {
+                        tag: node.as_opaque(),
+                        style,
+                        content_sizes,
+                        contents: IndependentFormattingContextContents::Grid(grid),
+                    }
+                },
+                DisplayInside::Math => {
+                    let (math, content_sizes) = MathFormattingContext::construct(
+                        context,
+                        node,
+                        &style,
+                        non_replaced,
+                        content_sizes,
+                    );
+                    Self {
+                        tag: node.as_opaque(),
+                        style,
+                        content_sizes,
+                        contents: IndependentFormattingContextContents::Math(math),
+                    }
+                },
             },
             Err(replaced) => {
                 let content_sizes = content_sizes.compute(|| replaced.inline_content_sizes(&style));
@@ -93,6 +134,25 @@ impl IndependentFormattingContext {
         }
     }
 
+    /// Constructs an anonymous block container that only contains the given
+    /// text runs, like an anonymous grid item.
+    pub fn construct_for_text_runs<'dom>(
+        context: &LayoutContext,
+        node: impl NodeExt<'dom>,
+        style: Arc<ComputedValues>,
+        text_runs: Vec<TextRun>,
+        content_sizes: ContentSizesRequest,
+    ) -> Self {
+        let (bfc, content_sizes) =
+            BlockFormattingContext::construct_for_text_runs(context, text_runs, content_sizes);
+        Self {
+            tag: node.as_opaque(),
+            style,
+            content_sizes,
+            contents: IndependentFormattingContextContents::Flow(bfc),
+        }
+    }
+
     pub fn as_replaced(&self) -> Result<&ReplacedContent, NonReplacedIFC> {
         use self::IndependentFormattingContextContents as Contents;
         use self::NonReplacedIFC as NR;
@@ -100,11 +160,20 @@ impl IndependentFormattingContext {
         match &self.contents {
             Contents::Replaced(r) => Ok(r),
             Contents::Flow(f) => Err(NR(Kind::Flow(f))),
+            Contents::Grid(g) => Err(NR(Kind::Grid(g))),
+            Contents::Math(m) => Err(NR(Kind::Math(m))),
         }
     }
 }
 
 impl<'a> NonReplacedIFC<'a> {
+    pub fn as_math(&self) -> Option<&'a MathFormattingContext> {
+        match self.0 {
+            NonReplacedIFCKind::Math(math) => Some(math),
+            NonReplacedIFCKind::Flow(_) | NonReplacedIFCKind::Grid(_) => None,
+        }
+    }
+
     pub fn layout(
         &self,
         layout_context: &LayoutContext,
@@ -119,6 +188,15 @@ impl<'a> NonReplacedIFC<'a> {
                 containing_block,
                 tree_rank,
             ),
+            NonReplacedIFCKind::Grid(grid) => grid.layout(
+                layout_context,
+                positioning_context,
+                containing_block,
+                tree_rank,
+            ),
+            NonReplacedIFCKind::Math(math) => {
+                math.layout_as_independent(layout_context, positioning_context, containing_block)
+            },
         }
     }
 }
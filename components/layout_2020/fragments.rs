@@ -26,6 +26,7 @@ pub(crate) enum Fragment {
     Anonymous(AnonymousFragment),
     Text(TextFragment),
     Image(ImageFragment),
+    Rule(RuleFragment),
 }
 
 #[derive(Serialize)]
@@ -99,6 +100,16 @@ pub(crate) struct ImageFragment {
     pub image_key: ImageKey,
 }
 
+/// A rectangle filled with the text color, such as the bar of a fraction.
+#[derive(Serialize)]
+pub(crate) struct RuleFragment {
+    pub debug_id: DebugId,
+    pub tag: OpaqueNode,
+    #[serde(skip_serializing)]
+    pub style: ServoArc<ComputedValues>,
+    pub rect: Rect<Length>,
+}
+
 impl Fragment {
     pub fn position_mut(&mut self) -> &mut Vec2<Length> {
         match self {
@@ -106,6 +117,7 @@ impl Fragment {
             Fragment::Anonymous(f) => &mut f.rect.start_corner,
             Fragment::Text(f) => &mut f.rect.start_corner,
             Fragment::Image(f) => &mut f.rect.start_corner,
+            Fragment::Rule(f) => &mut f.rect.start_corner,
         }
     }
 
@@ -115,6 +127,7 @@ impl Fragment {
             Fragment::Anonymous(fragment) => fragment.print(tree),
             Fragment::Text(fragment) => fragment.print(tree),
             Fragment::Image(fragment) => fragment.print(tree),
+            Fragment::Rule(fragment) => fragment.print(tree),
         }
     }
 
@@ -131,10 +144,25 @@ impl Fragment {
             Fragment::Image(fragment) => fragment
                 .rect
                 .to_physical(fragment.style.writing_mode, &containing_block),
+            Fragment::Rule(fragment) => fragment
+                .rect
+                .to_physical(fragment.style.writing_mode, &containing_block),
         }
     }
 }
 
+/// The baseline of the first line of flow content, from its top.
+pub(crate) fn first_baseline(fragments: &[Fragment]) -> Option<Length> {
+    fragments.iter().find_map(|fragment| match fragment {
+        Fragment::Text(text) => Some(text.rect.start_corner.block + text.ascent),
+        Fragment::Anonymous(anonymous) => first_baseline(&anonymous.children)
+            .map(|baseline| anonymous.rect.start_corner.block + baseline),
+        Fragment::Box(box_) => first_baseline(&box_.children)
+            .map(|baseline| box_.content_rect.start_corner.block + baseline),
+        Fragment::Image(_) | Fragment::Rule(_) => None,
+    })
+}
+
 impl AnonymousFragment {
     pub fn no_op(mode: WritingMode) -> Self {
         Self {
@@ -316,6 +344,16 @@ impl ImageFragment {
     }
 }
 
+impl RuleFragment {
+    pub fn print(&self, tree: &mut PrintTree) {
+        tree.add_item(format!(
+            "Rule\
+                \nrect={:?}",
+            self.rect
+        ));
+    }
+}
+
 impl CollapsedBlockMargins {
     pub fn from_margin(margin: &Sides<Length>) -> Self {
         Self {
@@ -0,0 +1,191 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::context::LayoutContext;
+use crate::dom_traversal::{BoxSlot, Contents, NodeExt, NonReplacedContents, TraversalHandler};
+use crate::element_data::LayoutBox;
+use crate::flow::inline::TextRun;
+use crate::formatting_contexts::IndependentFormattingContext;
+use crate::grid::placement::{self, GridArea};
+use crate::grid::{GridFormattingContext, GridItem, GridLevelBox};
+use crate::positioned::AbsolutelyPositionedBox;
+use crate::sizing::{BoxContentSizes, ContentSizesRequest};
+use crate::style_ext::{ComputedValuesExt, DisplayGeneratingBox};
+use servo_arc::Arc;
+use style::properties::ComputedValues;
+use style::selector_parser::PseudoElement;
+
+impl GridFormattingContext {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        node: impl NodeExt<'dom>,
+        style: &Arc<ComputedValues>,
+        contents: NonReplacedContents,
+        content_sizes: ContentSizesRequest,
+    ) -> (Self, BoxContentSizes) {
+        let mut builder = GridItemsBuilder {
+            context,
+            node,
+            style,
+            children: vec![],
+            text_runs: vec![],
+            pending_space: false,
+        };
+        contents.traverse(context, node, style, &mut builder);
+        builder.end_anonymous_item();
+        let mut children = builder.children;
+
+        let item_styles = children
+            .iter()
+            .filter_map(|(child, _)| match child {
+                GridLevelBox::Item(item) => Some(&*item.contents.style),
+                GridLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let (areas, tracks) = placement::place_items(style, &item_styles);
+        let mut areas = areas.into_iter();
+        for (child, _) in &mut children {
+            if let GridLevelBox::Item(item) = child {
+                item.area = areas.next().unwrap();
+            }
+        }
+
+        let children = children
+            .into_iter()
+            .map(|(child, box_slot)| {
+                let child = Arc::new(child);
+                if let Some(box_slot) = box_slot {
+                    box_slot.set(LayoutBox::GridLevel(child.clone()));
+                }
+                child
+            })
+            .collect();
+        let grid = GridFormattingContext {
+            children,
+            rows: tracks.rows,
+            columns: tracks.columns,
+        };
+        let content_sizes = content_sizes.compute(|| grid.inline_content_sizes(style));
+        (grid, content_sizes)
+    }
+}
+
+struct GridItemsBuilder<'dom, 'a, Node> {
+    context: &'a LayoutContext<'a>,
+    node: Node,
+    style: &'a Arc<ComputedValues>,
+    /// The children of the grid container, with the box slots of the
+    /// elements that generated them. Anonymous grid items have none.
+    children: Vec<(GridLevelBox, Option<BoxSlot<'dom>>)>,
+    /// The text of the ongoing anonymous grid item, if any.
+    text_runs: Vec<TextRun>,
+    /// Whether the last text of the ongoing anonymous grid item ended with
+    /// whitespace, which collapses into a single space if more text follows.
+    pending_space: bool,
+}
+
+impl<'dom, 'a, Node> GridItemsBuilder<'dom, 'a, Node>
+where
+    Node: NodeExt<'dom>,
+{
+    /// Wraps the contiguous text of the grid container in an anonymous grid
+    /// item.
+    ///
+    /// https://drafts.csswg.org/css-grid/#grid-items
+    fn end_anonymous_item(&mut self) {
+        self.pending_space = false;
+        if self.text_runs.is_empty() {
+            return;
+        }
+        let anonymous_style = self
+            .context
+            .shared_context()
+            .stylist
+            .style_for_anonymous::<Node::ConcreteElement>(
+                &self.context.shared_context().guards,
+                &PseudoElement::ServoText,
+                self.style,
+            );
+        let contents = IndependentFormattingContext::construct_for_text_runs(
+            self.context,
+            self.node,
+            anonymous_style,
+            std::mem::replace(&mut self.text_runs, vec![]),
+            ContentSizesRequest::Inline,
+        );
+        let item = GridItem {
+            contents,
+            area: GridArea::default(),
+        };
+        self.children.push((GridLevelBox::Item(item), None));
+    }
+}
+
+impl<'dom, 'a, Node> TraversalHandler<'dom, Node> for GridItemsBuilder<'dom, 'a, Node>
+where
+    Node: NodeExt<'dom>,
+{
+    fn handle_text(&mut self, node: Node, text: String, parent_style: &Arc<ComputedValues>) {
+        // Text that is only whitespace doesn't generate a grid item.
+        let mut words = text.split_ascii_whitespace();
+        let first_word = match words.next() {
+            Some(word) => word,
+            None => {
+                self.pending_space |= !self.text_runs.is_empty();
+                return;
+            },
+        };
+        let mut collapsed = String::new();
+        let starts_with_space = text.starts_with(|c: char| c.is_ascii_whitespace());
+        if !self.text_runs.is_empty() && (self.pending_space || starts_with_space) {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(first_word);
+        for word in words {
+            collapsed.push(' ');
+            collapsed.push_str(word);
+        }
+        self.pending_space = text.ends_with(|c: char| c.is_ascii_whitespace());
+        self.text_runs.push(TextRun {
+            tag: node.as_opaque(),
+            parent_style: parent_style.clone(),
+            text: collapsed,
+        });
+    }
+
+    fn handle_element(
+        &mut self,
+        node: Node,
+        style: &Arc<ComputedValues>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        self.end_anonymous_item();
+        // Grid items are blockified, like absolutely-positioned children.
+        let DisplayGeneratingBox::OutsideInside { inside, .. } = display;
+        let child = if style.get_box().position.is_absolutely_positioned() {
+            GridLevelBox::OutOfFlowAbsolutelyPositionedBox(AbsolutelyPositionedBox::construct(
+                self.context,
+                node,
+                style.clone(),
+                inside,
+                contents,
+            ))
+        } else {
+            GridLevelBox::Item(GridItem {
+                contents: IndependentFormattingContext::construct(
+                    self.context,
+                    node,
+                    style.clone(),
+                    inside,
+                    contents,
+                    ContentSizesRequest::inline_if(!style.inline_size_is_length()),
+                ),
+                area: GridArea::default(),
+            })
+        };
+        self.children.push((child, Some(box_slot)));
+    }
+}
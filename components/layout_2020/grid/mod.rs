@@ -0,0 +1,644 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! CSS Grid layout.
+//!
+//! https://drafts.csswg.org/css-grid/
+//!
+//! Grid items are placed in the grid during box construction, and the
+//! tracks of the grid are sized during layout: the columns first, then the
+//! rows, given the block sizes of the items laid out in their columns.
+
+use crate::context::LayoutContext;
+use crate::formatting_contexts::{IndependentFormattingContext, IndependentLayout};
+use crate::fragments::{first_baseline, AnonymousFragment, BoxFragment};
+use crate::fragments::{CollapsedBlockMargins, Fragment};
+use crate::geom::flow_relative::{Rect, Sides, Vec2};
+use crate::grid::placement::GridArea;
+use crate::grid::track_sizing::{AvailableSpace, ItemContribution};
+use crate::positioned::{AbsolutelyPositionedBox, PositioningContext};
+use crate::sizing::ContentSizes;
+use crate::style_ext::ComputedValuesExt;
+use crate::ContainingBlock;
+use servo_arc::Arc;
+use style::computed_values::align_items::T as AlignItems;
+use style::computed_values::align_self::T as AlignSelf;
+use style::properties::ComputedValues;
+use style::values::computed::{Length, LengthOrAuto, LengthPercentageOrAuto};
+use style::values::computed::{NonNegativeLengthPercentageOrNormal, TrackSize};
+use style::values::generics::length::{GenericLengthPercentageOrNormal, MaxSize};
+use style::Zero;
+
+mod construct;
+mod placement;
+mod track_sizing;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GridFormattingContext {
+    children: Vec<Arc<GridLevelBox>>,
+    /// The sizing functions of the rows of the grid, explicit and implicit.
+    #[serde(skip_serializing)]
+    rows: Vec<TrackSize>,
+    /// The sizing functions of the columns of the grid, explicit and implicit.
+    #[serde(skip_serializing)]
+    columns: Vec<TrackSize>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) enum GridLevelBox {
+    Item(GridItem),
+    OutOfFlowAbsolutelyPositionedBox(AbsolutelyPositionedBox),
+}
+
+/// https://drafts.csswg.org/css-grid/#grid-items
+#[derive(Debug, Serialize)]
+pub(crate) struct GridItem {
+    contents: IndependentFormattingContext,
+    area: GridArea,
+}
+
+/// The alignment of a grid item in the block axis of its grid area.
+///
+/// https://drafts.csswg.org/css-align/#align-self-property
+#[derive(Clone, Copy, PartialEq)]
+enum BlockAlignment {
+    Stretch,
+    Start,
+    End,
+    Center,
+    Baseline,
+}
+
+/// A grid item laid out in the columns of its grid area, before the rows
+/// are sized.
+struct MeasuredItem<'a> {
+    fragment: BoxFragment,
+    /// The absolutely-positioned boxes found while laying out the item,
+    /// which are kept if this layout of the item is.
+    positioning_context: PositioningContext<'a>,
+    /// The block size of the margin box of the item.
+    outer_block_size: Length,
+    /// The baseline of the item, from the block-start edge of its margin box.
+    baseline: Length,
+}
+
+impl GridFormattingContext {
+    fn items(&self) -> impl Iterator<Item = &GridItem> {
+        self.children.iter().filter_map(|child| match &**child {
+            GridLevelBox::Item(item) => Some(item),
+            GridLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => None,
+        })
+    }
+
+    pub(crate) fn inline_content_sizes(&self, style: &ComputedValues) -> ContentSizes {
+        let contributions = self
+            .items()
+            .map(GridItem::inline_contribution)
+            .collect::<Vec<_>>();
+        let gap = resolve_gap(&style.get_position().column_gap, None);
+        let size = |available_space| {
+            let columns =
+                track_sizing::size_tracks(&self.columns, &contributions, available_space, gap);
+            total_size(&columns, gap)
+        };
+        ContentSizes {
+            min_content: size(AvailableSpace::MinContent),
+            max_content: size(AvailableSpace::MaxContent),
+        }
+    }
+
+    pub(crate) fn layout<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext<'a>,
+        containing_block: &ContainingBlock,
+        tree_rank: usize,
+    ) -> IndependentLayout {
+        let style = containing_block.style;
+        let position = style.get_position();
+        let column_gap = resolve_gap(&position.column_gap, Some(containing_block.inline_size));
+        let row_gap = resolve_gap(&position.row_gap, containing_block.block_size.non_auto());
+
+        let column_contributions = self
+            .items()
+            .map(GridItem::inline_contribution)
+            .collect::<Vec<_>>();
+        let columns = track_sizing::size_tracks(
+            &self.columns,
+            &column_contributions,
+            AvailableSpace::Definite(containing_block.inline_size),
+            column_gap,
+        );
+
+        let mut content_block_size = Length::zero();
+        let fragments =
+            positioning_context.adjust_static_positions(tree_rank, |positioning_context| {
+                let measured_items = self.measure_items(
+                    layout_context,
+                    positioning_context,
+                    style,
+                    &columns,
+                    column_gap,
+                );
+                let baseline_shims = self.baseline_shims(style, &measured_items);
+                let row_contributions = self
+                    .items()
+                    .zip(measured_items.iter().flatten())
+                    .zip(&baseline_shims)
+                    .map(|((item, measured), &shim)| {
+                        let size = measured.outer_block_size + shim;
+                        ItemContribution {
+                            start: item.area.row_start,
+                            end: item.area.row_end,
+                            min_content: size,
+                            max_content: size,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let available_block_size = match containing_block.block_size {
+                    LengthOrAuto::LengthPercentage(size) => AvailableSpace::Definite(size),
+                    LengthOrAuto::Auto => AvailableSpace::MaxContent,
+                };
+                let rows = track_sizing::size_tracks(
+                    &self.rows,
+                    &row_contributions,
+                    available_block_size,
+                    row_gap,
+                );
+                content_block_size = total_size(&rows, row_gap);
+
+                let mut baseline_shims = baseline_shims.into_iter();
+                self.children
+                    .iter()
+                    .zip(measured_items)
+                    .enumerate()
+                    .map(
+                        |(child_rank, (child, measured))| match (&**child, measured) {
+                            (GridLevelBox::Item(item), Some(measured)) => {
+                                let area = area_rect(
+                                    &item.area,
+                                    (&columns[..], column_gap),
+                                    (&rows[..], row_gap),
+                                );
+                                Fragment::Box(item.layout_in_area(
+                                    layout_context,
+                                    positioning_context,
+                                    style,
+                                    &area,
+                                    measured,
+                                    baseline_shims.next().unwrap(),
+                                    child_rank,
+                                ))
+                            },
+                            (GridLevelBox::OutOfFlowAbsolutelyPositionedBox(box_), _) => {
+                                // FIXME: The static position should be the start
+                                // of the grid area the box would have as an item.
+                                positioning_context.push(box_.to_hoisted(Vec2::zero(), child_rank));
+                                Fragment::Anonymous(AnonymousFragment::no_op(style.writing_mode))
+                            },
+                            (GridLevelBox::Item(_), None) => unreachable!(),
+                        },
+                    )
+                    .collect()
+            });
+
+        IndependentLayout {
+            fragments,
+            content_block_size,
+        }
+    }
+
+    /// Lays out the items in the columns of their grid areas, to find their
+    /// contributions to the rows.
+    fn measure_items<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        positioning_context: &PositioningContext<'a>,
+        style: &ComputedValues,
+        columns: &[Length],
+        column_gap: Length,
+    ) -> Vec<Option<MeasuredItem<'a>>> {
+        let collects_for_nearest_positioned_ancestor =
+            positioning_context.collects_for_nearest_positioned_ancestor();
+        self.children
+            .iter()
+            .enumerate()
+            .map(|(child_rank, child)| match &**child {
+                GridLevelBox::Item(item) => {
+                    let (_, inline_size) = track_span(
+                        columns,
+                        column_gap,
+                        item.area.column_start,
+                        item.area.column_end,
+                    );
+                    let area = ContainingBlock {
+                        inline_size,
+                        block_size: LengthOrAuto::Auto,
+                        style,
+                    };
+                    let mut positioning_context =
+                        PositioningContext::new_for_rayon(collects_for_nearest_positioned_ancestor);
+                    let fragment = positioning_context.layout_maybe_position_relative_fragment(
+                        layout_context,
+                        &area,
+                        &item.contents.style,
+                        |positioning_context| {
+                            item.layout(
+                                layout_context,
+                                positioning_context,
+                                &area,
+                                false,
+                                child_rank,
+                            )
+                        },
+                    );
+                    Some(MeasuredItem::new(fragment, positioning_context))
+                },
+                GridLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => None,
+            })
+            .collect()
+    }
+
+    /// The offsets by which items aligned to their baseline are shifted, so
+    /// that their baselines line up with the other such items starting in the
+    /// same row, for each item. The offsets also grow the rows.
+    ///
+    /// https://drafts.csswg.org/css-grid/#algo-baseline-shims
+    fn baseline_shims(
+        &self,
+        style: &ComputedValues,
+        measured_items: &[Option<MeasuredItem>],
+    ) -> Vec<Length> {
+        let mut row_baselines = vec![Length::zero(); self.rows.len()];
+        for (item, measured) in self.items().zip(measured_items.iter().flatten()) {
+            if item.alignment(style) == BlockAlignment::Baseline {
+                row_baselines[item.area.row_start].max_assign(measured.baseline);
+            }
+        }
+        self.items()
+            .zip(measured_items.iter().flatten())
+            .map(|(item, measured)| {
+                if item.alignment(style) == BlockAlignment::Baseline {
+                    row_baselines[item.area.row_start] - measured.baseline
+                } else {
+                    Length::zero()
+                }
+            })
+            .collect()
+    }
+}
+
+impl<'a> MeasuredItem<'a> {
+    fn new(fragment: BoxFragment, positioning_context: PositioningContext<'a>) -> Self {
+        let border_box_start = fragment.margin.block_start;
+        let content_start =
+            border_box_start + fragment.border.block_start + fragment.padding.block_start;
+        // Items without a baseline are aligned by the end of their border box.
+        let baseline = first_baseline(&fragment.children).map_or_else(
+            || {
+                content_start +
+                    fragment.content_rect.size.block +
+                    fragment.padding.block_end +
+                    fragment.border.block_end
+            },
+            |baseline| content_start + baseline,
+        );
+        MeasuredItem {
+            outer_block_size: outer_block_size(&fragment),
+            fragment,
+            positioning_context,
+            baseline,
+        }
+    }
+}
+
+impl GridItem {
+    fn inline_contribution(&self) -> ItemContribution {
+        let sizes = self
+            .contents
+            .content_sizes
+            .outer_inline(&self.contents.style);
+        ItemContribution {
+            start: self.area.column_start,
+            end: self.area.column_end,
+            min_content: sizes.min_content,
+            max_content: sizes.max_content,
+        }
+    }
+
+    fn alignment(&self, container_style: &ComputedValues) -> BlockAlignment {
+        let alignment = match self.contents.style.clone_align_self() {
+            AlignSelf::Auto => match container_style.clone_align_items() {
+                AlignItems::Stretch => BlockAlignment::Stretch,
+                AlignItems::FlexStart => BlockAlignment::Start,
+                AlignItems::FlexEnd => BlockAlignment::End,
+                AlignItems::Center => BlockAlignment::Center,
+                AlignItems::Baseline => BlockAlignment::Baseline,
+            },
+            AlignSelf::Stretch => BlockAlignment::Stretch,
+            AlignSelf::FlexStart => BlockAlignment::Start,
+            AlignSelf::FlexEnd => BlockAlignment::End,
+            AlignSelf::Center => BlockAlignment::Center,
+            AlignSelf::Baseline => BlockAlignment::Baseline,
+        };
+        // Replaced elements keep their own size.
+        if alignment == BlockAlignment::Stretch && self.contents.as_replaced().is_ok() {
+            return BlockAlignment::Start;
+        }
+        alignment
+    }
+
+    fn has_auto_block_margins(&self) -> bool {
+        let margin = self.contents.style.margin();
+        margin.block_start.is_auto() || margin.block_end.is_auto()
+    }
+
+    /// Whether the item is stretched to the block size of its grid area when
+    /// aligned with `stretch`.
+    ///
+    /// https://drafts.csswg.org/css-align/#valdef-align-self-stretch
+    fn stretches(&self) -> bool {
+        self.contents.style.box_size().block.is_auto() && !self.has_auto_block_margins()
+    }
+
+    /// Whether the layout of the item depends on the block size of its grid
+    /// area, even when it isn't stretched.
+    fn depends_on_area_block_size(&self) -> bool {
+        let style = &self.contents.style;
+        let is_percentage = |size: &LengthPercentageOrAuto| {
+            size.non_auto()
+                .map_or(false, |size| size.to_length().is_none())
+        };
+        let max_is_percentage = match style.max_box_size().block {
+            MaxSize::LengthPercentage(size) => size.to_length().is_none(),
+            MaxSize::None => false,
+        };
+        self.contents.as_replaced().is_ok() ||
+            self.has_auto_block_margins() ||
+            is_percentage(&style.box_size().block) ||
+            is_percentage(&style.min_box_size().block) ||
+            max_is_percentage
+    }
+
+    /// Lays out the item in its grid area once the rows are sized, reusing
+    /// its measured layout if it doesn't depend on the block size of the
+    /// area, and aligns it in the area.
+    fn layout_in_area<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext<'a>,
+        container_style: &ComputedValues,
+        area: &Rect<Length>,
+        measured: MeasuredItem<'a>,
+        baseline_shim: Length,
+        tree_rank: usize,
+    ) -> BoxFragment {
+        let alignment = self.alignment(container_style);
+        let stretch = alignment == BlockAlignment::Stretch && self.stretches();
+        let mut fragment = if self.depends_on_area_block_size() ||
+            (stretch && measured.outer_block_size != area.size.block)
+        {
+            let containing_block = ContainingBlock {
+                inline_size: area.size.inline,
+                block_size: LengthOrAuto::LengthPercentage(area.size.block),
+                style: container_style,
+            };
+            positioning_context.layout_maybe_position_relative_fragment(
+                layout_context,
+                &containing_block,
+                &self.contents.style,
+                |positioning_context| {
+                    self.layout(
+                        layout_context,
+                        positioning_context,
+                        &containing_block,
+                        stretch,
+                        tree_rank,
+                    )
+                },
+            )
+        } else {
+            positioning_context.append(measured.positioning_context);
+            measured.fragment
+        };
+
+        // Auto margins take precedence over the alignment.
+        let free_space = area.size.block - outer_block_size(&fragment);
+        let offset = match alignment {
+            _ if self.has_auto_block_margins() => Length::zero(),
+            BlockAlignment::Stretch | BlockAlignment::Start => Length::zero(),
+            BlockAlignment::End => free_space,
+            BlockAlignment::Center => free_space / 2.,
+            BlockAlignment::Baseline => baseline_shim,
+        };
+        fragment.content_rect.start_corner.inline += area.start_corner.inline;
+        fragment.content_rect.start_corner.block += area.start_corner.block + offset;
+        fragment
+    }
+
+    /// Lays out the item in its grid area, whose block size is only known
+    /// once the rows are sized. The item is stretched to fill the area in the
+    /// block axis if `stretch`.
+    fn layout<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext<'a>,
+        area: &ContainingBlock,
+        stretch: bool,
+        tree_rank: usize,
+    ) -> BoxFragment {
+        let style = &self.contents.style;
+        let cbis = area.inline_size;
+        let padding = style.padding().percentages_relative_to(cbis);
+        let border = style.border_width();
+        let margin = style.margin().percentages_relative_to(cbis);
+        let pb = &padding + &border;
+        let box_size = style.box_size().percentages_relative_to(area);
+        let max_box_size = style.max_box_size().percentages_relative_to(area);
+        let min_box_size = style
+            .min_box_size()
+            .percentages_relative_to(area)
+            .auto_is(Length::zero);
+
+        let (children, size) = match self.contents.as_replaced() {
+            Ok(replaced) => {
+                let size = replaced.used_size_as_if_inline_element(area, style);
+                (replaced.make_fragments(style, size.clone()), size)
+            },
+            Err(non_replaced) => {
+                // Items fill their grid area in the inline axis, unless they
+                // have auto margins.
+                let inline_size = box_size
+                    .inline
+                    .auto_is(|| {
+                        let available = cbis -
+                            pb.inline_sum() -
+                            margin.inline_start.auto_is(Length::zero) -
+                            margin.inline_end.auto_is(Length::zero);
+                        if margin.inline_start.is_auto() || margin.inline_end.is_auto() {
+                            self.contents.content_sizes.shrink_to_fit(available)
+                        } else {
+                            available
+                        }
+                    })
+                    .clamp_between_extremums(min_box_size.inline, max_box_size.inline);
+                let mut block_size = match area.block_size {
+                    LengthOrAuto::LengthPercentage(area_block_size) if stretch => {
+                        LengthOrAuto::LengthPercentage(
+                            area_block_size -
+                                pb.block_sum() -
+                                margin.block_start.auto_is(Length::zero) -
+                                margin.block_end.auto_is(Length::zero),
+                        )
+                    },
+                    _ => box_size.block,
+                };
+                if let LengthOrAuto::LengthPercentage(ref mut block_size) = block_size {
+                    *block_size =
+                        block_size.clamp_between_extremums(min_box_size.block, max_box_size.block);
+                }
+                let containing_block_for_children = ContainingBlock {
+                    inline_size,
+                    block_size,
+                    style,
+                };
+                let layout = non_replaced.layout(
+                    layout_context,
+                    positioning_context,
+                    &containing_block_for_children,
+                    tree_rank,
+                );
+                let block_size = block_size.auto_is(|| {
+                    layout
+                        .content_block_size
+                        .clamp_between_extremums(min_box_size.block, max_box_size.block)
+                });
+                let size = Vec2 {
+                    inline: inline_size,
+                    block: block_size,
+                };
+                (layout.fragments, size)
+            },
+        };
+
+        // Auto margins absorb the free space of the grid area.
+        let (inline_start, inline_end) = solve_margins(
+            margin.inline_start,
+            margin.inline_end,
+            cbis - pb.inline_sum() - size.inline,
+        );
+        let (block_start, block_end) = match area.block_size {
+            LengthOrAuto::LengthPercentage(area_block_size) => solve_margins(
+                margin.block_start,
+                margin.block_end,
+                area_block_size - pb.block_sum() - size.block,
+            ),
+            LengthOrAuto::Auto => (
+                margin.block_start.auto_is(Length::zero),
+                margin.block_end.auto_is(Length::zero),
+            ),
+        };
+        let margin = Sides {
+            inline_start,
+            inline_end,
+            block_start,
+            block_end,
+        };
+        let content_rect = Rect {
+            start_corner: Vec2 {
+                inline: margin.inline_start + pb.inline_start,
+                block: margin.block_start + pb.block_start,
+            },
+            size,
+        };
+        BoxFragment::new(
+            self.contents.tag,
+            style.clone(),
+            children,
+            content_rect,
+            padding,
+            border,
+            margin,
+            CollapsedBlockMargins::zero(),
+        )
+    }
+}
+
+/// Resolves the margins of a side of an item, given the space they may
+/// take, where auto margins share the space that the other doesn't take.
+fn solve_margins(start: LengthOrAuto, end: LengthOrAuto, space: Length) -> (Length, Length) {
+    match (start, end) {
+        (LengthOrAuto::Auto, LengthOrAuto::Auto) => {
+            let margin = (space / 2.).max(Length::zero());
+            (margin, margin)
+        },
+        (LengthOrAuto::Auto, LengthOrAuto::LengthPercentage(end)) => {
+            ((space - end).max(Length::zero()), end)
+        },
+        (LengthOrAuto::LengthPercentage(start), LengthOrAuto::Auto) => {
+            (start, (space - start).max(Length::zero()))
+        },
+        (LengthOrAuto::LengthPercentage(start), LengthOrAuto::LengthPercentage(end)) => {
+            (start, end)
+        },
+    }
+}
+
+fn outer_block_size(fragment: &BoxFragment) -> Length {
+    fragment.margin.block_sum() +
+        fragment.border.block_sum() +
+        fragment.padding.block_sum() +
+        fragment.content_rect.size.block
+}
+
+/// https://drafts.csswg.org/css-align/#column-row-gap
+fn resolve_gap(gap: &NonNegativeLengthPercentageOrNormal, basis: Option<Length>) -> Length {
+    match gap {
+        GenericLengthPercentageOrNormal::Normal => Length::zero(),
+        GenericLengthPercentageOrNormal::LengthPercentage(gap) => gap
+            .0
+            .percentage_relative_to(basis.unwrap_or_else(Length::zero)),
+    }
+}
+
+/// The offset of the given tracks from the start of the grid, and their size
+/// with the gaps between them.
+fn track_span(sizes: &[Length], gap: Length, start: usize, end: usize) -> (Length, Length) {
+    let offset = sizes[..start]
+        .iter()
+        .fold(gap * start as f32, |offset, &size| offset + size);
+    let size = sizes[start..end]
+        .iter()
+        .fold(gap * (end - start - 1) as f32, |sum, &size| sum + size);
+    (offset, size)
+}
+
+/// The rectangle of a grid area, given the sizes of the columns and of the
+/// rows with the gaps between them.
+fn area_rect(
+    area: &GridArea,
+    (columns, column_gap): (&[Length], Length),
+    (rows, row_gap): (&[Length], Length),
+) -> Rect<Length> {
+    let (inline_start, inline_size) =
+        track_span(columns, column_gap, area.column_start, area.column_end);
+    let (block_start, block_size) = track_span(rows, row_gap, area.row_start, area.row_end);
+    Rect {
+        start_corner: Vec2 {
+            inline: inline_start,
+            block: block_start,
+        },
+        size: Vec2 {
+            inline: inline_size,
+            block: block_size,
+        },
+    }
+}
+
+fn total_size(sizes: &[Length], gap: Length) -> Length {
+    if sizes.is_empty() {
+        return Length::zero();
+    }
+    track_span(sizes, gap, 0, sizes.len()).1
+}
@@ -0,0 +1,525 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The placement of grid items in the grid, and the tracks of the grid.
+//!
+//! https://drafts.csswg.org/css-grid/#placement
+
+use style::properties::ComputedValues;
+use style::values::computed::{GridAutoFlow, GridLine, GridTemplateAreas, GridTemplateComponent};
+use style::values::computed::{ImplicitGridTracks, TrackSize};
+use style::values::generics::grid::{RepeatCount, TrackListValue};
+use style::values::specified::position::AutoFlow;
+use style::values::CustomIdent;
+use style::Atom;
+
+/// The lines of the grid between which an item is placed in both axes, as
+/// indices of the first track of its area and of the track after its area.
+#[derive(Clone, Debug, Default, Serialize)]
+pub(crate) struct GridArea {
+    pub row_start: usize,
+    pub row_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+/// The tracks of the grid, explicit and implicit.
+pub(super) struct GridTracks {
+    pub rows: Vec<TrackSize>,
+    pub columns: Vec<TrackSize>,
+}
+
+/// Places the items of a grid container, whose styles are given in
+/// order-modified document order, returning the grid area of each item and
+/// the tracks of the grid.
+///
+/// https://drafts.csswg.org/css-grid/#auto-placement-algo
+pub(super) fn place_items(
+    container_style: &ComputedValues,
+    items: &[&ComputedValues],
+) -> (Vec<GridArea>, GridTracks) {
+    let position = container_style.get_position();
+    let areas = match &position.grid_template_areas {
+        GridTemplateAreas::Areas(areas) => Some(&*areas.0),
+        GridTemplateAreas::None => None,
+    };
+
+    let mut rows = ExplicitAxis::new(&position.grid_template_rows);
+    let mut columns = ExplicitAxis::new(&position.grid_template_columns);
+    if let Some(areas) = areas {
+        rows.track_count = rows.track_count.max(areas.strings.len());
+        columns.track_count = columns.track_count.max(areas.width as usize);
+        for area in areas.areas.iter() {
+            rows.add_area_lines(&area.name, area.rows.start, area.rows.end);
+            columns.add_area_lines(&area.name, area.columns.start, area.columns.end);
+        }
+    }
+
+    let flow = position.grid_auto_flow;
+    let mut placements = items
+        .iter()
+        .map(|style| {
+            let position = style.get_position();
+            let rows = rows.resolve(&position.grid_row_start, &position.grid_row_end);
+            let columns = columns.resolve(&position.grid_column_start, &position.grid_column_end);
+            match flow.autoflow {
+                AutoFlow::Row => ItemPlacement {
+                    major: rows,
+                    minor: columns,
+                },
+                AutoFlow::Column => ItemPlacement {
+                    major: columns,
+                    minor: rows,
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+    let (major_axis, minor_axis) = match flow.autoflow {
+        AutoFlow::Row => (&rows, &columns),
+        AutoFlow::Column => (&columns, &rows),
+    };
+    auto_place(&mut placements, flow, minor_axis.track_count as i32);
+
+    // Lines are numbered from the first line of the explicit grid, and the
+    // implicit grid can extend before it.
+    let (mut major_lines, mut minor_lines) = (
+        0..major_axis.track_count as i32,
+        0..minor_axis.track_count as i32,
+    );
+    for placement in &placements {
+        let (major, minor) = (
+            placement.major.expect_definite(),
+            placement.minor.expect_definite(),
+        );
+        major_lines.start = major_lines.start.min(major.0);
+        major_lines.end = major_lines.end.max(major.1);
+        minor_lines.start = minor_lines.start.min(minor.0);
+        minor_lines.end = minor_lines.end.max(minor.1);
+    }
+    let grid_areas = placements
+        .iter()
+        .map(|placement| {
+            let major = placement.major.expect_definite();
+            let minor = placement.minor.expect_definite();
+            let major = (
+                (major.0 - major_lines.start) as usize,
+                (major.1 - major_lines.start) as usize,
+            );
+            let minor = (
+                (minor.0 - minor_lines.start) as usize,
+                (minor.1 - minor_lines.start) as usize,
+            );
+            let (rows, columns) = match flow.autoflow {
+                AutoFlow::Row => (major, minor),
+                AutoFlow::Column => (minor, major),
+            };
+            GridArea {
+                row_start: rows.0,
+                row_end: rows.1,
+                column_start: columns.0,
+                column_end: columns.1,
+            }
+        })
+        .collect();
+
+    let (row_lines, column_lines) = match flow.autoflow {
+        AutoFlow::Row => (major_lines, minor_lines),
+        AutoFlow::Column => (minor_lines, major_lines),
+    };
+    let tracks = GridTracks {
+        rows: rows.tracks(row_lines, &position.grid_auto_rows),
+        columns: columns.tracks(column_lines, &position.grid_auto_columns),
+    };
+    (grid_areas, tracks)
+}
+
+/// The explicit grid in one axis.
+struct ExplicitAxis<'a> {
+    /// The sizing functions of the explicit tracks given by the template,
+    /// with repetitions expanded.
+    template: Vec<&'a TrackSize>,
+    /// The number of explicit tracks, which is larger than the template if
+    /// `grid-template-areas` has more tracks.
+    track_count: usize,
+    /// The names of each line of the explicit grid.
+    line_names: Vec<Vec<Atom>>,
+}
+
+impl<'a> ExplicitAxis<'a> {
+    fn new(template: &'a GridTemplateComponent) -> Self {
+        let mut tracks = vec![];
+        let mut line_names = vec![vec![]];
+        fn add_names(line_names: &mut Vec<Vec<Atom>>, names: &[CustomIdent]) {
+            let last = line_names.last_mut().unwrap();
+            last.extend(names.iter().map(|name| name.0.clone()));
+        }
+
+        if let GridTemplateComponent::TrackList(list) = template {
+            for (value, names) in list.values.iter().zip(list.line_names.iter()) {
+                add_names(&mut line_names, names);
+                match value {
+                    TrackListValue::TrackSize(size) => {
+                        tracks.push(size);
+                        line_names.push(vec![]);
+                    },
+                    TrackListValue::TrackRepeat(repeat) => {
+                        // FIXME: `auto-fill` and `auto-fit` should repeat the
+                        // tracks as many times as they fit in the container.
+                        let count = match repeat.count {
+                            RepeatCount::Number(count) => count.max(1) as usize,
+                            RepeatCount::AutoFill | RepeatCount::AutoFit => 1,
+                        };
+                        for _ in 0..count {
+                            for (size, names) in
+                                repeat.track_sizes.iter().zip(repeat.line_names.iter())
+                            {
+                                add_names(&mut line_names, names);
+                                tracks.push(size);
+                                line_names.push(vec![]);
+                            }
+                            if let Some(names) = repeat.line_names.last() {
+                                add_names(&mut line_names, names);
+                            }
+                        }
+                    },
+                }
+            }
+            if let Some(names) = list.line_names.last() {
+                add_names(&mut line_names, names);
+            }
+        }
+        // FIXME: `subgrid` is laid out as `none`.
+        ExplicitAxis {
+            track_count: tracks.len(),
+            template: tracks,
+            line_names,
+        }
+    }
+
+    /// Names the lines of an area of `grid-template-areas`, given as
+    /// one-based line numbers, `<name>-start` and `<name>-end`.
+    ///
+    /// https://drafts.csswg.org/css-grid/#implicit-named-lines
+    fn add_area_lines(&mut self, name: &Atom, start: u32, end: u32) {
+        let last_line = end as usize - 1;
+        if self.line_names.len() <= last_line {
+            self.line_names.resize(last_line + 1, vec![]);
+        }
+        self.line_names[start as usize - 1].push(Atom::from(format!("{}-start", name)));
+        self.line_names[last_line].push(Atom::from(format!("{}-end", name)));
+    }
+
+    /// The lines of the explicit grid with the given name.
+    fn lines_named<'b>(&'b self, name: &'b Atom) -> impl DoubleEndedIterator<Item = i32> + 'b {
+        self.line_names
+            .iter()
+            .take(self.track_count + 1)
+            .enumerate()
+            .filter(move |(_, names)| names.contains(name))
+            .map(|(line, _)| line as i32)
+    }
+
+    /// Resolves the `count`th line with the given name, counting forward
+    /// from the start of the explicit grid if `count` is positive, and
+    /// backward from its end otherwise. Every implicit line is assumed to
+    /// have the name.
+    fn nth_named_line(&self, name: &Atom, count: i32) -> i32 {
+        if count > 0 {
+            let mut lines = self.lines_named(name);
+            let mut remaining = count;
+            while let Some(line) = lines.next() {
+                remaining -= 1;
+                if remaining == 0 {
+                    return line;
+                }
+            }
+            self.track_count as i32 + remaining
+        } else {
+            let mut lines = self.lines_named(name).rev();
+            let mut remaining = -count;
+            while let Some(line) = lines.next() {
+                remaining -= 1;
+                if remaining == 0 {
+                    return line;
+                }
+            }
+            -remaining
+        }
+    }
+
+    /// Resolves a definite `<grid-line>`, given whether it is the start or
+    /// the end of a placement.
+    ///
+    /// https://drafts.csswg.org/css-grid/#line-placement
+    fn resolve_line(&self, line: &GridLine, is_start: bool) -> i32 {
+        debug_assert!(!line.is_auto() && !line.is_span);
+        if line.ident.is_empty() {
+            return if line.line_num > 0 {
+                line.line_num - 1
+            } else {
+                self.track_count as i32 + 1 + line.line_num
+            };
+        }
+        if line.line_num == 0 {
+            let suffix = if is_start { "start" } else { "end" };
+            let implicit_name = Atom::from(format!("{}-{}", line.ident, suffix));
+            if let Some(line) = self.lines_named(&implicit_name).next() {
+                return line;
+            }
+            return self.nth_named_line(&line.ident, 1);
+        }
+        self.nth_named_line(&line.ident, line.line_num)
+    }
+
+    /// Resolves a span from a definite line, towards the end of the grid if
+    /// `forward`, and towards its start otherwise.
+    fn resolve_span(&self, span: &GridLine, from: i32, forward: bool) -> i32 {
+        let count = span.line_num.max(1);
+        if span.ident.is_empty() {
+            return if forward { from + count } else { from - count };
+        }
+        let mut remaining = count;
+        let mut line = from;
+        while remaining > 0 {
+            line += if forward { 1 } else { -1 };
+            let is_implicit = line < 0 || line > self.track_count as i32;
+            if is_implicit ||
+                self.line_names
+                    .get(line as usize)
+                    .map_or(false, |names| names.contains(&span.ident))
+            {
+                remaining -= 1;
+            }
+        }
+        line
+    }
+
+    /// Resolves the placement of an item in this axis.
+    ///
+    /// https://drafts.csswg.org/css-grid/#grid-placement-errors
+    fn resolve(&self, start: &GridLine, end: &GridLine) -> AxisPlacement {
+        let is_definite = |line: &GridLine| !line.is_auto() && !line.is_span;
+        match (is_definite(start), is_definite(end)) {
+            (true, true) => {
+                let start = self.resolve_line(start, true);
+                let end = self.resolve_line(end, false);
+                if start < end {
+                    AxisPlacement::Definite(start, end)
+                } else if end < start {
+                    AxisPlacement::Definite(end, start)
+                } else {
+                    AxisPlacement::Definite(start, start + 1)
+                }
+            },
+            (true, false) => {
+                let start = self.resolve_line(start, true);
+                let end = if end.is_span {
+                    self.resolve_span(end, start, true)
+                } else {
+                    start + 1
+                };
+                AxisPlacement::Definite(start, end)
+            },
+            (false, true) => {
+                let end = self.resolve_line(end, false);
+                let start = if start.is_span {
+                    self.resolve_span(start, end, false)
+                } else {
+                    end - 1
+                };
+                AxisPlacement::Definite(start, end)
+            },
+            (false, false) => {
+                // A span to a named line is treated as `span 1` when
+                // auto-placing, and the span of the end line is ignored if
+                // both lines are spans.
+                let span = if start.is_span { start } else { end };
+                let span = if span.is_span && span.ident.is_empty() {
+                    span.line_num.max(1)
+                } else {
+                    1
+                };
+                AxisPlacement::Auto(span)
+            },
+        }
+    }
+
+    /// The sizing functions of the tracks between the given lines, that may
+    /// extend the explicit grid with implicit tracks on both sides.
+    ///
+    /// https://drafts.csswg.org/css-grid/#auto-tracks
+    fn tracks(&self, lines: std::ops::Range<i32>, auto: &ImplicitGridTracks) -> Vec<TrackSize> {
+        let auto = &auto.0;
+        if auto.is_empty() {
+            let auto = TrackSize::default();
+            return lines
+                .map(|line| match self.template.get(line as usize) {
+                    Some(size) if line >= 0 => (*size).clone(),
+                    _ => auto.clone(),
+                })
+                .collect();
+        }
+        lines
+            .map(|line| {
+                if line < 0 {
+                    // Implicit tracks before the explicit grid repeat the
+                    // implicit track sizes backward.
+                    let index = (-line - 1) as usize % auto.len();
+                    auto[auto.len() - 1 - index].clone()
+                } else if let Some(size) = self.template.get(line as usize) {
+                    (*size).clone()
+                } else {
+                    auto[(line as usize - self.template.len()) % auto.len()].clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The placement of an item in one axis, as the lines it starts and ends at
+/// relative to the first line of the explicit grid, or the number of tracks
+/// it spans if it is auto-placed.
+#[derive(Clone, Copy)]
+enum AxisPlacement {
+    Definite(i32, i32),
+    Auto(i32),
+}
+
+impl AxisPlacement {
+    fn expect_definite(&self) -> (i32, i32) {
+        match *self {
+            AxisPlacement::Definite(start, end) => (start, end),
+            AxisPlacement::Auto(_) => panic!("Accessing the lines of an auto-placed item"),
+        }
+    }
+
+    fn span(&self) -> i32 {
+        match *self {
+            AxisPlacement::Definite(start, end) => end - start,
+            AxisPlacement::Auto(span) => span,
+        }
+    }
+}
+
+/// The placement of an item in the axis that auto-placement fills first,
+/// which is the row axis for `grid-auto-flow: row`, and in the other one.
+struct ItemPlacement {
+    major: AxisPlacement,
+    minor: AxisPlacement,
+}
+
+/// Places the items that have an auto position in either axis.
+///
+/// https://drafts.csswg.org/css-grid/#auto-placement-algo
+fn auto_place(placements: &mut [ItemPlacement], flow: GridAutoFlow, minor_track_count: i32) {
+    // The cells occupied so far, as (major, minor) line ranges.
+    let mut occupied: Vec<((i32, i32), (i32, i32))> = placements
+        .iter()
+        .filter_map(|placement| match (placement.major, placement.minor) {
+            (AxisPlacement::Definite(a, b), AxisPlacement::Definite(c, d)) => {
+                Some(((a, b), (c, d)))
+            },
+            _ => None,
+        })
+        .collect();
+    let overlaps = |occupied: &[((i32, i32), (i32, i32))], major: (i32, i32), minor: (i32, i32)| {
+        occupied.iter().any(|&(other_major, other_minor)| {
+            major.0 < other_major.1 &&
+                other_major.0 < major.1 &&
+                minor.0 < other_minor.1 &&
+                other_minor.0 < minor.1
+        })
+    };
+
+    // The minor lines of the implicit grid, which auto-placement never
+    // extends.
+    let mut minor_start = 0;
+    let mut minor_end = minor_track_count;
+    for placement in placements.iter() {
+        match placement.minor {
+            AxisPlacement::Definite(start, end) => {
+                minor_start = minor_start.min(start);
+                minor_end = minor_end.max(end);
+            },
+            AxisPlacement::Auto(span) => minor_end = minor_end.max(minor_start + span),
+        }
+    }
+    let mut major_start = 0;
+    for placement in placements.iter() {
+        if let AxisPlacement::Definite(start, _) = placement.major {
+            major_start = major_start.min(start);
+        }
+    }
+
+    // Process the items locked to a given major track.
+    let mut cursors: Vec<(i32, i32)> = vec![];
+    for placement in placements.iter_mut() {
+        let major = match (placement.major, placement.minor) {
+            (AxisPlacement::Definite(start, end), AxisPlacement::Auto(_)) => (start, end),
+            _ => continue,
+        };
+        let span = placement.minor.span();
+        let mut start = if flow.dense {
+            minor_start
+        } else {
+            cursors
+                .iter()
+                .find(|&&(line, _)| line == major.0)
+                .map_or(minor_start, |&(_, cursor)| cursor)
+        };
+        while overlaps(&occupied, major, (start, start + span)) {
+            start += 1;
+        }
+        let minor = (start, start + span);
+        minor_end = minor_end.max(minor.1);
+        if !flow.dense {
+            match cursors.iter_mut().find(|(line, _)| *line == major.0) {
+                Some(cursor) => cursor.1 = minor.1,
+                None => cursors.push((major.0, minor.1)),
+            }
+        }
+        occupied.push((major, minor));
+        placement.minor = AxisPlacement::Definite(minor.0, minor.1);
+    }
+
+    // Position the remaining items.
+    let mut cursor = (major_start, minor_start);
+    for placement in placements.iter_mut() {
+        let major_span = match placement.major {
+            AxisPlacement::Auto(span) => span,
+            AxisPlacement::Definite(..) => continue,
+        };
+        if flow.dense {
+            cursor = (major_start, minor_start);
+        }
+        let minor = match placement.minor {
+            AxisPlacement::Definite(start, end) => {
+                if !flow.dense && start < cursor.1 {
+                    cursor.0 += 1;
+                }
+                while overlaps(&occupied, (cursor.0, cursor.0 + major_span), (start, end)) {
+                    cursor.0 += 1;
+                }
+                cursor.1 = start;
+                (start, end)
+            },
+            AxisPlacement::Auto(span) => loop {
+                if cursor.1 + span > minor_end {
+                    cursor.0 += 1;
+                    cursor.1 = minor_start;
+                    continue;
+                }
+                let minor = (cursor.1, cursor.1 + span);
+                if !overlaps(&occupied, (cursor.0, cursor.0 + major_span), minor) {
+                    break minor;
+                }
+                cursor.1 += 1;
+            },
+        };
+        let major = (cursor.0, cursor.0 + major_span);
+        occupied.push((major, minor));
+        placement.major = AxisPlacement::Definite(major.0, major.1);
+        placement.minor = AxisPlacement::Definite(minor.0, minor.1);
+    }
+}
@@ -0,0 +1,424 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The grid sizing algorithm, which sizes the tracks of one axis of a grid.
+//!
+//! https://drafts.csswg.org/css-grid/#algo-track-sizing
+
+use style::values::computed::{Length, LengthPercentage, TrackBreadth, TrackSize};
+use style::values::generics::grid::GenericTrackBreadth;
+use style::Zero;
+
+/// The space the tracks of an axis are sized in.
+#[derive(Clone, Copy)]
+pub(super) enum AvailableSpace {
+    Definite(Length),
+    MinContent,
+    MaxContent,
+}
+
+impl AvailableSpace {
+    fn definite(self) -> Option<Length> {
+        match self {
+            AvailableSpace::Definite(size) => Some(size),
+            AvailableSpace::MinContent | AvailableSpace::MaxContent => None,
+        }
+    }
+}
+
+/// The contributions of a grid item to the tracks it spans in one axis,
+/// which include its margins, borders and padding.
+pub(super) struct ItemContribution {
+    pub start: usize,
+    pub end: usize,
+    pub min_content: Length,
+    pub max_content: Length,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MinSizing {
+    Fixed(Length),
+    MinContent,
+    MaxContent,
+    Auto,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MaxSizing {
+    Fixed(Length),
+    MinContent,
+    MaxContent,
+    Auto,
+    Flex(f32),
+    FitContent(Length),
+}
+
+impl MinSizing {
+    fn is_intrinsic(self) -> bool {
+        match self {
+            MinSizing::MinContent | MinSizing::MaxContent | MinSizing::Auto => true,
+            MinSizing::Fixed(_) => false,
+        }
+    }
+}
+
+impl MaxSizing {
+    fn is_intrinsic(self) -> bool {
+        match self {
+            MaxSizing::MinContent |
+            MaxSizing::MaxContent |
+            MaxSizing::Auto |
+            MaxSizing::FitContent(_) => true,
+            MaxSizing::Fixed(_) | MaxSizing::Flex(_) => false,
+        }
+    }
+
+    fn flex_factor(self) -> Option<f32> {
+        match self {
+            MaxSizing::Flex(factor) => Some(factor),
+            _ => None,
+        }
+    }
+}
+
+struct Track {
+    min: MinSizing,
+    max: MaxSizing,
+    base_size: Length,
+    /// The growth limit, or `None` if it is infinite.
+    growth_limit: Option<Length>,
+}
+
+impl Track {
+    /// https://drafts.csswg.org/css-grid/#algo-init
+    fn new(size: &TrackSize, percentage_basis: Option<Length>) -> Self {
+        // Percentages are treated as `auto` when the available space is
+        // indefinite.
+        let resolve = |lp: &LengthPercentage| match percentage_basis {
+            Some(basis) => Some(lp.percentage_relative_to(basis)),
+            None => lp.to_length(),
+        };
+        let min = |breadth: &TrackBreadth| match breadth {
+            GenericTrackBreadth::Breadth(lp) => {
+                resolve(lp).map_or(MinSizing::Auto, MinSizing::Fixed)
+            },
+            GenericTrackBreadth::MinContent => MinSizing::MinContent,
+            GenericTrackBreadth::MaxContent => MinSizing::MaxContent,
+            GenericTrackBreadth::Auto | GenericTrackBreadth::Fr(_) => MinSizing::Auto,
+        };
+        let max = |breadth: &TrackBreadth| match breadth {
+            GenericTrackBreadth::Breadth(lp) => {
+                resolve(lp).map_or(MaxSizing::Auto, MaxSizing::Fixed)
+            },
+            GenericTrackBreadth::Fr(factor) => MaxSizing::Flex(*factor),
+            GenericTrackBreadth::MinContent => MaxSizing::MinContent,
+            GenericTrackBreadth::MaxContent => MaxSizing::MaxContent,
+            GenericTrackBreadth::Auto => MaxSizing::Auto,
+        };
+        let (min, max) = match size {
+            TrackSize::Breadth(breadth) => (min(breadth), max(breadth)),
+            TrackSize::Minmax(min_breadth, max_breadth) => (min(min_breadth), max(max_breadth)),
+            TrackSize::FitContent(breadth) => {
+                let limit = match breadth {
+                    GenericTrackBreadth::Breadth(lp) => resolve(lp),
+                    _ => None,
+                };
+                (
+                    MinSizing::Auto,
+                    limit.map_or(MaxSizing::MaxContent, MaxSizing::FitContent),
+                )
+            },
+        };
+        let base_size = match min {
+            MinSizing::Fixed(size) => size,
+            _ => Length::zero(),
+        };
+        let growth_limit = match max {
+            MaxSizing::Fixed(size) => Some(size.max(base_size)),
+            _ => None,
+        };
+        Track {
+            min,
+            max,
+            base_size,
+            growth_limit,
+        }
+    }
+
+    /// The growth limit, where an infinite one is treated as the base size.
+    fn finite_growth_limit(&self) -> Length {
+        self.growth_limit.unwrap_or(self.base_size)
+    }
+}
+
+/// Sizes the tracks of one axis, returning their sizes.
+///
+/// https://drafts.csswg.org/css-grid/#algo-track-sizing
+pub(super) fn size_tracks(
+    sizes: &[TrackSize],
+    items: &[ItemContribution],
+    available_space: AvailableSpace,
+    gap: Length,
+) -> Vec<Length> {
+    let mut tracks = sizes
+        .iter()
+        .map(|size| Track::new(size, available_space.definite()))
+        .collect::<Vec<_>>();
+    let spans_flexible_track = |item: &ItemContribution| {
+        tracks[item.start..item.end]
+            .iter()
+            .any(|track| track.max.flex_factor().is_some())
+    };
+    let (flexible_items, mut inflexible_items): (Vec<_>, Vec<_>) =
+        items.iter().partition(|item| spans_flexible_track(item));
+    inflexible_items.sort_by_key(|item| item.end - item.start);
+
+    // https://drafts.csswg.org/css-grid/#algo-content
+    for item in &inflexible_items {
+        let span = item.start..item.end;
+        let gaps = gap * (span.len() - 1) as f32;
+        let minimum = match available_space {
+            AvailableSpace::MaxContent => item.max_content,
+            AvailableSpace::MinContent | AvailableSpace::Definite(_) => item.min_content,
+        };
+        if span.len() == 1 {
+            let track = &mut tracks[item.start];
+            let contribution = match track.min {
+                MinSizing::MinContent => Some(item.min_content),
+                MinSizing::MaxContent => Some(item.max_content),
+                MinSizing::Auto => Some(minimum),
+                MinSizing::Fixed(_) => None,
+            };
+            if let Some(contribution) = contribution {
+                track.base_size.max_assign(contribution);
+            }
+            let contribution = match track.max {
+                MaxSizing::MinContent => Some(item.min_content),
+                MaxSizing::MaxContent | MaxSizing::Auto => Some(item.max_content),
+                MaxSizing::FitContent(limit) => {
+                    Some(item.max_content.min(limit).max(item.min_content))
+                },
+                MaxSizing::Fixed(_) | MaxSizing::Flex(_) => None,
+            };
+            if let Some(contribution) = contribution {
+                let growth_limit = track.growth_limit.unwrap_or_else(Length::zero);
+                track.growth_limit = Some(growth_limit.max(contribution));
+            }
+        } else {
+            // Items spanning several tracks are processed by increasing
+            // span, and distribute the space they need beyond the tracks
+            // they span to the tracks with an intrinsic sizing function.
+            let tracks = &mut tracks[span];
+            let indices = (0..tracks.len())
+                .filter(|&i| tracks[i].min.is_intrinsic())
+                .collect::<Vec<_>>();
+            let sum = tracks
+                .iter()
+                .map(|track| track.base_size)
+                .fold(gaps, |a, b| a + b);
+            distribute(tracks, &indices, minimum - sum, |track| {
+                (&mut track.base_size, track.growth_limit)
+            });
+
+            let indices = (0..tracks.len())
+                .filter(|&i| tracks[i].max.is_intrinsic())
+                .collect::<Vec<_>>();
+            for &i in &indices {
+                let track = &mut tracks[i];
+                track.growth_limit = Some(track.finite_growth_limit().max(track.base_size));
+            }
+            let sum = tracks
+                .iter()
+                .map(|track| track.finite_growth_limit())
+                .fold(gaps, |a, b| a + b);
+            distribute(tracks, &indices, item.max_content - sum, |track| {
+                (track.growth_limit.as_mut().unwrap(), None)
+            });
+        }
+        for track in &mut tracks {
+            if let Some(growth_limit) = &mut track.growth_limit {
+                growth_limit.max_assign(track.base_size);
+            }
+        }
+    }
+
+    // Items spanning flexible tracks grow the base sizes of the flexible
+    // tracks with an intrinsic minimum.
+    for item in &flexible_items {
+        let span = item.start..item.end;
+        let gaps = gap * (span.len() - 1) as f32;
+        let tracks = &mut tracks[span];
+        let indices = (0..tracks.len())
+            .filter(|&i| tracks[i].max.flex_factor().is_some() && tracks[i].min == MinSizing::Auto)
+            .collect::<Vec<_>>();
+        let sum = tracks
+            .iter()
+            .map(|track| track.base_size)
+            .fold(gaps, |a, b| a + b);
+        distribute(tracks, &indices, item.min_content - sum, |track| {
+            (&mut track.base_size, None)
+        });
+    }
+    for track in &mut tracks {
+        track.growth_limit = Some(track.finite_growth_limit().max(track.base_size));
+    }
+
+    let gaps = gap * (tracks.len().max(1) - 1) as f32;
+    let free_space = |tracks: &[Track]| {
+        available_space.definite().map(|available| {
+            let used = tracks
+                .iter()
+                .map(|track| track.base_size)
+                .fold(gaps, |a, b| a + b);
+            available - used
+        })
+    };
+
+    // https://drafts.csswg.org/css-grid/#algo-grow-tracks
+    match available_space {
+        AvailableSpace::Definite(_) => {
+            let free_space = free_space(&tracks).unwrap();
+            let indices = (0..tracks.len()).collect::<Vec<_>>();
+            distribute(&mut tracks, &indices, free_space, |track| {
+                let growth_limit = track.growth_limit;
+                (&mut track.base_size, growth_limit)
+            });
+        },
+        AvailableSpace::MaxContent => {
+            for track in &mut tracks {
+                track.base_size = track.finite_growth_limit();
+            }
+        },
+        AvailableSpace::MinContent => {},
+    }
+
+    // https://drafts.csswg.org/css-grid/#algo-flex-tracks
+    let flex_fraction = match available_space {
+        AvailableSpace::MinContent => None,
+        AvailableSpace::Definite(available) => {
+            let available = available - gaps;
+            Some(find_flex_fraction(&tracks, 0..tracks.len(), available))
+        },
+        AvailableSpace::MaxContent => {
+            let mut fraction = Length::zero();
+            for track in &tracks {
+                if let Some(factor) = track.max.flex_factor() {
+                    let size = if factor > 1. {
+                        track.base_size / factor
+                    } else {
+                        track.base_size
+                    };
+                    fraction.max_assign(size);
+                }
+            }
+            for item in &flexible_items {
+                let span = item.start..item.end;
+                let gaps = gap * (span.len() - 1) as f32;
+                fraction.max_assign(find_flex_fraction(&tracks, span, item.max_content - gaps));
+            }
+            Some(fraction)
+        },
+    };
+    if let Some(flex_fraction) = flex_fraction {
+        for track in &mut tracks {
+            if let Some(factor) = track.max.flex_factor() {
+                track.base_size.max_assign(flex_fraction * factor);
+            }
+        }
+    }
+
+    // https://drafts.csswg.org/css-grid/#algo-stretch
+    // FIXME: Only `align-content: normal` and `justify-content: normal` are
+    // supported, which stretch the tracks.
+    if let Some(free_space) = free_space(&tracks) {
+        let auto_tracks = tracks
+            .iter()
+            .filter(|track| track.max == MaxSizing::Auto)
+            .count();
+        if free_space > Length::zero() && auto_tracks > 0 {
+            let share = free_space / auto_tracks as f32;
+            for track in &mut tracks {
+                if track.max == MaxSizing::Auto {
+                    track.base_size += share;
+                }
+            }
+        }
+    }
+
+    tracks.into_iter().map(|track| track.base_size).collect()
+}
+
+/// Distributes `space` equally among the given tracks, without growing the
+/// size that `target` returns past its limit, if any, unless every track
+/// reached it.
+///
+/// https://drafts.csswg.org/css-grid/#extra-space
+fn distribute(
+    tracks: &mut [Track],
+    indices: &[usize],
+    mut space: Length,
+    target: impl Fn(&mut Track) -> (&mut Length, Option<Length>),
+) {
+    if indices.is_empty() {
+        return;
+    }
+    let mut unfrozen = indices.to_vec();
+    while space > Length::zero() && !unfrozen.is_empty() {
+        let share = space / unfrozen.len() as f32;
+        let mut still_unfrozen = vec![];
+        for &i in &unfrozen {
+            let (size, limit) = target(&mut tracks[i]);
+            let growth = match limit {
+                Some(limit) if limit - *size < share => (limit - *size).max(Length::zero()),
+                _ => {
+                    still_unfrozen.push(i);
+                    share
+                },
+            };
+            *size += growth;
+            space -= growth;
+        }
+        if still_unfrozen.len() == unfrozen.len() {
+            return;
+        }
+        unfrozen = still_unfrozen;
+    }
+    if space > Length::zero() {
+        // Every track reached its limit, so they grow past it.
+        let share = space / indices.len() as f32;
+        for &i in indices {
+            *target(&mut tracks[i]).0 += share;
+        }
+    }
+}
+
+/// Finds the size of a flexible length for the given tracks to fill `space`.
+///
+/// https://drafts.csswg.org/css-grid/#algo-find-fr-size
+fn find_flex_fraction(tracks: &[Track], span: std::ops::Range<usize>, space: Length) -> Length {
+    let mut inflexible = vec![false; tracks.len()];
+    loop {
+        let mut leftover = space;
+        let mut flex_factor_sum = 0.;
+        for i in span.clone() {
+            match tracks[i].max.flex_factor() {
+                Some(factor) if !inflexible[i] => flex_factor_sum += factor,
+                _ => leftover -= tracks[i].base_size,
+            }
+        }
+        let fraction = leftover.max(Length::zero()) / flex_factor_sum.max(1.);
+        let mut restart = false;
+        for i in span.clone() {
+            if let Some(factor) = tracks[i].max.flex_factor() {
+                if !inflexible[i] && fraction * factor < tracks[i].base_size {
+                    inflexible[i] = true;
+                    restart = true;
+                }
+            }
+        }
+        if !restart {
+            return fraction;
+        }
+    }
+}
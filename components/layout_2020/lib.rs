@@ -5,6 +5,8 @@
 #![deny(unsafe_code)]
 #![feature(exact_size_is_empty)]
 
+#[macro_use]
+extern crate html5ever;
 #[macro_use]
 extern crate serde;
 
@@ -17,8 +19,10 @@ mod flow;
 mod formatting_contexts;
 mod fragments;
 mod geom;
+mod grid;
 #[macro_use]
 pub mod layout_debug;
+mod math;
 mod opaque_node;
 mod positioned;
 pub mod query;
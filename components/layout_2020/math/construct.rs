@@ -0,0 +1,196 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::context::LayoutContext;
+use crate::dom_traversal::{BoxSlot, Contents, NodeExt, NonReplacedContents, TraversalHandler};
+use crate::element_data::LayoutBox;
+use crate::flow::BlockFormattingContext;
+use crate::formatting_contexts::IndependentFormattingContext;
+use crate::math::operator::Operator;
+use crate::math::{LineThickness, MathFormattingContext};
+use crate::sizing::{BoxContentSizes, ContentSizesRequest};
+use crate::style_ext::{ComputedValuesExt, DisplayGeneratingBox};
+use servo_arc::Arc;
+use style::properties::ComputedValues;
+
+impl MathFormattingContext {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        node: impl NodeExt<'dom>,
+        style: &Arc<ComputedValues>,
+        contents: NonReplacedContents,
+        content_sizes: ContentSizesRequest,
+    ) -> (Self, BoxContentSizes) {
+        let local_name = match contents {
+            NonReplacedContents::OfElement => node.mathml_local_name(),
+            NonReplacedContents::OfPseudoElement(_) => None,
+        };
+        let local_name = local_name.as_ref().map_or("", |name| &**name);
+
+        let math = match local_name {
+            "mi" | "mn" | "ms" | "mtext" => {
+                let (contents, inner_content_sizes) = BlockFormattingContext::construct(
+                    context,
+                    node,
+                    style,
+                    contents,
+                    ContentSizesRequest::Inline,
+                );
+                let inline_size = inner_content_sizes.expect_inline().max_content;
+                MathFormattingContext::Token {
+                    contents,
+                    inline_size,
+                }
+            },
+            "mo" => MathFormattingContext::Operator(Operator::construct(
+                context,
+                node,
+                style,
+                contents,
+                is_display_style(node),
+            )),
+            _ => {
+                let mut builder = MathChildrenBuilder {
+                    context,
+                    children: vec![],
+                };
+                contents.traverse(context, node, style, &mut builder);
+                let children = builder.children;
+                let display_style = is_display_style(node);
+
+                // Elements with an invalid number of children are laid out
+                // as rows.
+                let mut iter = children.iter().cloned();
+                match (local_name, children.len()) {
+                    ("mfrac", 2) => MathFormattingContext::Fraction {
+                        tag: node.as_opaque(),
+                        style: style.clone(),
+                        numerator: iter.next().unwrap(),
+                        denominator: iter.next().unwrap(),
+                        line_thickness: node
+                            .attribute("linethickness")
+                            .and_then(|value| LineThickness::parse(&value, style)),
+                        display_style,
+                    },
+                    ("msqrt", _) => MathFormattingContext::Radical {
+                        tag: node.as_opaque(),
+                        style: style.clone(),
+                        base: children,
+                        index: None,
+                        display_style,
+                    },
+                    ("mroot", 2) => MathFormattingContext::Radical {
+                        tag: node.as_opaque(),
+                        style: style.clone(),
+                        base: vec![iter.next().unwrap()],
+                        index: iter.next(),
+                        display_style,
+                    },
+                    ("msub", 2) => MathFormattingContext::Scripts {
+                        base: iter.next().unwrap(),
+                        subscript: iter.next(),
+                        superscript: None,
+                    },
+                    ("msup", 2) => MathFormattingContext::Scripts {
+                        base: iter.next().unwrap(),
+                        subscript: None,
+                        superscript: iter.next(),
+                    },
+                    ("msubsup", 3) => MathFormattingContext::Scripts {
+                        base: iter.next().unwrap(),
+                        subscript: iter.next(),
+                        superscript: iter.next(),
+                    },
+                    // FIXME: lay out munder, mover, munderover, mmultiscripts,
+                    // mspace, mpadded, and mtable.
+                    _ => MathFormattingContext::Row(children),
+                }
+            },
+        };
+        let content_sizes = content_sizes.compute(|| math.inline_content_sizes(context, style));
+        (math, content_sizes)
+    }
+}
+
+struct MathChildrenBuilder<'a> {
+    context: &'a LayoutContext<'a>,
+    children: Vec<Arc<IndependentFormattingContext>>,
+}
+
+impl<'dom, 'a, Node> TraversalHandler<'dom, Node> for MathChildrenBuilder<'a>
+where
+    Node: NodeExt<'dom>,
+{
+    fn handle_text(&mut self, _node: Node, _text: String, _parent_style: &Arc<ComputedValues>) {
+        // FIXME: Text is only rendered in token elements, other elements
+        // should wrap it in an anonymous `mtext`.
+    }
+
+    fn handle_element(
+        &mut self,
+        node: Node,
+        style: &Arc<ComputedValues>,
+        display: DisplayGeneratingBox,
+        contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        // FIXME: Out-of-flow children are laid out as if they were in flow.
+        let DisplayGeneratingBox::OutsideInside { inside, .. } = display;
+        let child = Arc::new(IndependentFormattingContext::construct(
+            self.context,
+            node,
+            style.clone(),
+            inside,
+            contents,
+            ContentSizesRequest::inline_if(!style.inline_size_is_length()),
+        ));
+        box_slot.set(LayoutBox::MathLevel(child.clone()));
+        self.children.push(child);
+    }
+}
+
+/// Whether the element is laid out in display style, rather than in the more
+/// compact style of inline formulas and of scripts.
+///
+/// https://mathml-refresh.github.io/mathml-core/#the-displaystyle-and-scriptlevel-attributes
+fn is_display_style<'dom>(node: impl NodeExt<'dom>) -> bool {
+    let mut node = node;
+    loop {
+        let local_name = match node.mathml_local_name() {
+            Some(local_name) => local_name,
+            None => return false,
+        };
+        match node.attribute("displaystyle") {
+            Some(ref value) if value.eq_ignore_ascii_case("true") => return true,
+            Some(ref value) if value.eq_ignore_ascii_case("false") => return false,
+            _ => {},
+        }
+        if &*local_name == "math" {
+            return node
+                .attribute("display")
+                .map_or(false, |display| display.eq_ignore_ascii_case("block"));
+        }
+
+        let parent = match node.parent_node() {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let parent_name = parent.mathml_local_name();
+        match parent_name.as_ref().map_or("", |name| &**name) {
+            "mfrac" => return false,
+            "msub" | "msup" | "msubsup" | "munder" | "mover" | "munderover" | "mmultiscripts" |
+            "mroot" => {
+                let mut first_child = parent.first_child();
+                while let Some(child) = first_child.filter(|child| !child.is_element()) {
+                    first_child = child.next_sibling();
+                }
+                if first_child != Some(node) {
+                    return false;
+                }
+            },
+            _ => {},
+        }
+        node = parent;
+    }
+}
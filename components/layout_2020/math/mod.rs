@@ -0,0 +1,813 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! MathML Core layout.
+//!
+//! https://mathml-refresh.github.io/mathml-core/#layout-algorithms
+//!
+//! Math boxes are laid out around a baseline: each of them has an inline
+//! size, an ascent above its baseline, and a descent below it.
+
+use crate::context::LayoutContext;
+use crate::flow::BlockFormattingContext;
+use crate::formatting_contexts::{IndependentFormattingContext, IndependentLayout};
+use crate::fragments::{first_baseline, BoxFragment, CollapsedBlockMargins, DebugId, Fragment};
+use crate::fragments::{RuleFragment, TextFragment};
+use crate::geom::flow_relative::{Rect, Vec2};
+use crate::math::operator::{GlyphSize, Operator};
+use crate::positioned::PositioningContext;
+use crate::sizing::ContentSizes;
+use crate::style_ext::ComputedValuesExt;
+use crate::ContainingBlock;
+use gfx::text::math::MathConstants;
+use servo_arc::Arc;
+use style::dom::OpaqueNode;
+use style::properties::ComputedValues;
+use style::values::computed::{Length, LengthOrAuto};
+use style::Zero;
+
+mod construct;
+mod operator;
+
+#[derive(Debug, Serialize)]
+pub(crate) enum MathFormattingContext {
+    /// `math`, `mrow`, and every element without a more specific layout.
+    Row(Vec<Arc<IndependentFormattingContext>>),
+    Fraction {
+        tag: OpaqueNode,
+        #[serde(skip_serializing)]
+        style: Arc<ComputedValues>,
+        numerator: Arc<IndependentFormattingContext>,
+        denominator: Arc<IndependentFormattingContext>,
+        line_thickness: Option<LineThickness>,
+        display_style: bool,
+    },
+    /// `msqrt`, whose base is a row, and `mroot`.
+    Radical {
+        tag: OpaqueNode,
+        #[serde(skip_serializing)]
+        style: Arc<ComputedValues>,
+        base: Vec<Arc<IndependentFormattingContext>>,
+        index: Option<Arc<IndependentFormattingContext>>,
+        display_style: bool,
+    },
+    /// `msub`, `msup`, and `msubsup`.
+    Scripts {
+        base: Arc<IndependentFormattingContext>,
+        subscript: Option<Arc<IndependentFormattingContext>>,
+        superscript: Option<Arc<IndependentFormattingContext>>,
+    },
+    Operator(Operator),
+    /// `mi`, `mn`, `ms`, and `mtext`, whose text is laid out on a single line.
+    Token {
+        contents: BlockFormattingContext,
+        inline_size: Length,
+    },
+}
+
+/// The `linethickness` attribute of `mfrac`.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub(crate) enum LineThickness {
+    Length(Length),
+    /// Relative to the default thickness.
+    Percentage(f32),
+}
+
+pub(crate) struct MathLayout {
+    pub fragments: Vec<Fragment>,
+    pub inline_size: Length,
+    pub ascent: Length,
+    pub descent: Length,
+}
+
+/// The ascent and descent that a stretchy operator should cover.
+#[derive(Clone, Copy)]
+struct StretchSize {
+    ascent: Length,
+    descent: Length,
+}
+
+/// The margin box of a child, not yet positioned.
+struct ChildLayout {
+    fragment: BoxFragment,
+    inline_size: Length,
+    ascent: Length,
+    descent: Length,
+}
+
+impl LineThickness {
+    /// FIXME: this only supports unitless zero, and `px`, `em`, and
+    /// percentage values.
+    fn parse(value: &str, style: &ComputedValues) -> Option<Self> {
+        let value = value.trim_matches(|c: char| c.is_ascii_whitespace());
+        let number = |suffix: &str| -> Option<f32> {
+            if value.ends_with(suffix) {
+                value[..value.len() - suffix.len()].parse().ok()
+            } else {
+                None
+            }
+        };
+        if let Some(percentage) = number("%") {
+            Some(LineThickness::Percentage(percentage / 100.))
+        } else if let Some(px) = number("px") {
+            Some(LineThickness::Length(Length::new(px)))
+        } else if let Some(em) = number("em") {
+            Some(LineThickness::Length(
+                style.get_font().font_size.size.0 * em,
+            ))
+        } else if value.parse::<f32>().ok() == Some(0.) {
+            Some(LineThickness::Length(Length::zero()))
+        } else {
+            None
+        }
+    }
+
+    fn resolve(self, default: Length) -> Length {
+        match self {
+            LineThickness::Length(length) => length,
+            LineThickness::Percentage(percentage) => default * percentage,
+        }
+        .max(Length::zero())
+    }
+}
+
+impl MathFormattingContext {
+    pub(crate) fn inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        style: &ComputedValues,
+    ) -> ContentSizes {
+        let size = match self {
+            MathFormattingContext::Row(children) => row_inline_size(children),
+            MathFormattingContext::Fraction {
+                numerator,
+                denominator,
+                ..
+            } => outer_inline_size(numerator).max(outer_inline_size(denominator)),
+            MathFormattingContext::Radical { base, index, .. } => {
+                let surd = operator::shape_operator(layout_context, style, "√", GlyphSize::Normal);
+                let index = match index {
+                    Some(index) => {
+                        let constants = operator::math_constants(layout_context, style);
+                        (Length::from(constants.radical_kern_before_degree) +
+                            outer_inline_size(index) +
+                            constants.radical_kern_after_degree.into())
+                        .max(Length::zero())
+                    },
+                    None => Length::zero(),
+                };
+                index + surd.advance + row_inline_size(base)
+            },
+            MathFormattingContext::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => {
+                let constants = operator::math_constants(layout_context, style);
+                let scripts = subscript
+                    .iter()
+                    .chain(superscript)
+                    .map(outer_inline_size)
+                    .fold(Length::zero(), Length::max);
+                outer_inline_size(base) + scripts + constants.space_after_script.into()
+            },
+            MathFormattingContext::Operator(operator) => {
+                let glyphs = operator::shape_operator(
+                    layout_context,
+                    style,
+                    &operator.text,
+                    operator.glyph_size(),
+                );
+                let (lspace, rspace) = operator.spacing(style);
+                lspace + glyphs.advance + rspace
+            },
+            MathFormattingContext::Token { inline_size, .. } => *inline_size,
+        };
+        // Formulas are not broken into lines.
+        ContentSizes {
+            min_content: size,
+            max_content: size,
+        }
+    }
+
+    /// Lays out a math element which is not itself in a formula, such as a
+    /// `math` element.
+    pub(crate) fn layout_as_independent<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext<'a>,
+        containing_block: &ContainingBlock,
+    ) -> IndependentLayout {
+        let layout = self.layout(
+            layout_context,
+            positioning_context,
+            containing_block,
+            containing_block.style,
+            None,
+        );
+
+        // Display formulas are centered.
+        let offset = ((containing_block.inline_size - layout.inline_size) / 2.).max(Length::zero());
+        let mut fragments = layout.fragments;
+        for fragment in &mut fragments {
+            fragment.position_mut().inline += offset;
+        }
+        IndependentLayout {
+            fragments,
+            content_block_size: layout.ascent + layout.descent,
+        }
+    }
+
+    fn layout<'a>(
+        &'a self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext<'a>,
+        containing_block: &ContainingBlock,
+        style: &ComputedValues,
+        stretch: Option<StretchSize>,
+    ) -> MathLayout {
+        let mut layout_child = |child: &'a Arc<IndependentFormattingContext>, stretch| {
+            layout_child(
+                layout_context,
+                positioning_context,
+                containing_block,
+                child,
+                stretch,
+            )
+        };
+        match self {
+            MathFormattingContext::Row(children) => layout_row(children, layout_child),
+            MathFormattingContext::Fraction {
+                tag,
+                style,
+                numerator,
+                denominator,
+                line_thickness,
+                display_style,
+            } => {
+                let numerator = layout_child(numerator, None);
+                let denominator = layout_child(denominator, None);
+                let constants = operator::math_constants(layout_context, style);
+                let default_thickness = constants.fraction_rule_thickness.into();
+                let thickness = line_thickness.map_or(default_thickness, |thickness| {
+                    thickness.resolve(default_thickness)
+                });
+                layout_fraction(
+                    numerator,
+                    denominator,
+                    thickness,
+                    *display_style,
+                    &constants,
+                    *tag,
+                    style,
+                )
+            },
+            MathFormattingContext::Radical {
+                tag,
+                style,
+                base,
+                index,
+                display_style,
+            } => {
+                let base = layout_row(base, &mut layout_child);
+                let index = index.as_ref().map(|index| layout_child(index, None));
+                layout_radical(layout_context, base, index, *display_style, *tag, style)
+            },
+            MathFormattingContext::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => {
+                let base_is_token = is_token_or_operator(base);
+                let base = layout_child(base, None);
+                let subscript = subscript.as_ref().map(|script| layout_child(script, None));
+                let superscript = superscript
+                    .as_ref()
+                    .map(|script| layout_child(script, None));
+                let constants = operator::math_constants(layout_context, style);
+                layout_scripts(base, base_is_token, subscript, superscript, &constants)
+            },
+            MathFormattingContext::Operator(operator) => {
+                let size = match stretch {
+                    Some(StretchSize { ascent, descent }) if operator.properties.stretchy => {
+                        GlyphSize::Stretched {
+                            ascent,
+                            descent,
+                            symmetric: operator.properties.symmetric,
+                        }
+                    },
+                    _ => operator.glyph_size(),
+                };
+                let glyphs = operator::shape_operator(layout_context, style, &operator.text, size);
+                let (lspace, rspace) = operator.spacing(style);
+                let fragments = if glyphs.glyphs.is_empty() {
+                    vec![]
+                } else {
+                    vec![Fragment::Text(TextFragment {
+                        debug_id: DebugId::new(),
+                        tag: operator.tag,
+                        parent_style: operator.style.clone(),
+                        rect: Rect {
+                            start_corner: Vec2 {
+                                inline: lspace,
+                                block: Length::zero(),
+                            },
+                            size: Vec2 {
+                                inline: glyphs.advance,
+                                block: glyphs.ascent + glyphs.descent,
+                            },
+                        },
+                        ascent: glyphs.ascent,
+                        font_key: glyphs.font_key,
+                        glyphs: glyphs.glyphs,
+                    })]
+                };
+                MathLayout {
+                    fragments,
+                    inline_size: lspace + glyphs.advance + rspace,
+                    ascent: glyphs.ascent,
+                    descent: glyphs.descent,
+                }
+            },
+            MathFormattingContext::Token {
+                contents,
+                inline_size,
+            } => {
+                let containing_block_for_children = ContainingBlock {
+                    inline_size: *inline_size,
+                    block_size: LengthOrAuto::Auto,
+                    style,
+                };
+                let layout = contents.layout(
+                    layout_context,
+                    positioning_context,
+                    &containing_block_for_children,
+                    0,
+                );
+                let block_size = layout.content_block_size;
+                let ascent = first_baseline(&layout.fragments).unwrap_or(block_size);
+                MathLayout {
+                    fragments: layout.fragments,
+                    inline_size: *inline_size,
+                    ascent,
+                    descent: block_size - ascent,
+                }
+            },
+        }
+    }
+}
+
+fn layout_child<'a>(
+    layout_context: &LayoutContext,
+    positioning_context: &mut PositioningContext<'a>,
+    containing_block: &ContainingBlock,
+    child: &'a Arc<IndependentFormattingContext>,
+    stretch: Option<StretchSize>,
+) -> ChildLayout {
+    let cbis = containing_block.inline_size;
+    let padding = child.style.padding().percentages_relative_to(cbis);
+    let border = child.style.border_width();
+    let margin = child
+        .style
+        .margin()
+        .percentages_relative_to(cbis)
+        .auto_is(Length::zero);
+    let pbm = &(&padding + &border) + &margin;
+
+    let (children, size, ascent) = match child.as_replaced() {
+        Ok(replaced) => {
+            let size = replaced.used_size_as_if_inline_element(containing_block, &child.style);
+            let fragments = replaced.make_fragments(&child.style, size.clone());
+            let ascent = size.block;
+            (fragments, size, ascent)
+        },
+        Err(non_replaced) => match non_replaced.as_math() {
+            Some(math) => {
+                let layout = math.layout(
+                    layout_context,
+                    positioning_context,
+                    containing_block,
+                    &child.style,
+                    stretch,
+                );
+                let size = Vec2 {
+                    inline: layout.inline_size,
+                    block: layout.ascent + layout.descent,
+                };
+                (layout.fragments, size, layout.ascent)
+            },
+            None => {
+                // Flow content is laid out on a single line, like the rest of
+                // the formula.
+                let inline_size = child
+                    .style
+                    .box_size()
+                    .inline
+                    .percentage_relative_to(cbis)
+                    .auto_is(|| child.content_sizes.expect_inline().max_content);
+                let containing_block_for_children = ContainingBlock {
+                    inline_size,
+                    block_size: LengthOrAuto::Auto,
+                    style: &child.style,
+                };
+                let layout = non_replaced.layout(
+                    layout_context,
+                    positioning_context,
+                    &containing_block_for_children,
+                    0,
+                );
+                let block_size = layout.content_block_size;
+                let ascent = first_baseline(&layout.fragments).unwrap_or(block_size);
+                let size = Vec2 {
+                    inline: inline_size,
+                    block: block_size,
+                };
+                (layout.fragments, size, ascent)
+            },
+        },
+    };
+
+    let inline_size = pbm.inline_sum() + size.inline;
+    let descent = size.block - ascent + pbm.block_end;
+    let ascent = pbm.block_start + ascent;
+    let content_rect = Rect {
+        start_corner: Vec2 {
+            inline: pbm.inline_start,
+            block: pbm.block_start,
+        },
+        size,
+    };
+    let fragment = BoxFragment::new(
+        child.tag,
+        child.style.clone(),
+        children,
+        content_rect,
+        padding,
+        border,
+        margin,
+        CollapsedBlockMargins::zero(),
+    );
+    ChildLayout {
+        fragment,
+        inline_size,
+        ascent,
+        descent,
+    }
+}
+
+impl ChildLayout {
+    /// Positions the margin box of the child, given the position of the
+    /// baseline in its parent.
+    fn place(self, inline_start: Length, baseline: Length) -> Fragment {
+        let mut fragment = self.fragment;
+        fragment.content_rect.start_corner.inline += inline_start;
+        fragment.content_rect.start_corner.block += baseline - self.ascent;
+        Fragment::Box(fragment)
+    }
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#horizontally-group-sub-expressions-mrow
+fn layout_row<'a>(
+    children: &'a [Arc<IndependentFormattingContext>],
+    mut layout_child: impl FnMut(
+        &'a Arc<IndependentFormattingContext>,
+        Option<StretchSize>,
+    ) -> ChildLayout,
+) -> MathLayout {
+    // Stretchy operators are laid out last, to cover their siblings.
+    let mut layouts: Vec<_> = children
+        .iter()
+        .map(|child| {
+            if is_stretchy_operator(child) {
+                None
+            } else {
+                Some(layout_child(child, None))
+            }
+        })
+        .collect();
+    let stretch = layouts
+        .iter()
+        .flatten()
+        .fold(None, |stretch: Option<StretchSize>, layout| {
+            Some(StretchSize {
+                ascent: stretch.map_or(layout.ascent, |s| s.ascent.max(layout.ascent)),
+                descent: stretch.map_or(layout.descent, |s| s.descent.max(layout.descent)),
+            })
+        });
+    for (layout, child) in layouts.iter_mut().zip(children) {
+        if layout.is_none() {
+            *layout = Some(layout_child(child, stretch));
+        }
+    }
+
+    let layouts: Vec<_> = layouts.into_iter().flatten().collect();
+    let ascent = layouts
+        .iter()
+        .map(|layout| layout.ascent)
+        .fold(Length::zero(), Length::max);
+    let descent = layouts
+        .iter()
+        .map(|layout| layout.descent)
+        .fold(Length::zero(), Length::max);
+    let mut inline_size = Length::zero();
+    let fragments = layouts
+        .into_iter()
+        .map(|layout| {
+            let inline_start = inline_size;
+            inline_size += layout.inline_size;
+            layout.place(inline_start, ascent)
+        })
+        .collect();
+    MathLayout {
+        fragments,
+        inline_size,
+        ascent,
+        descent,
+    }
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#fractions-mfrac
+fn layout_fraction(
+    numerator: ChildLayout,
+    denominator: ChildLayout,
+    thickness: Length,
+    display_style: bool,
+    constants: &MathConstants,
+    tag: OpaqueNode,
+    style: &Arc<ComputedValues>,
+) -> MathLayout {
+    // FIXME: fractions without a bar should use the stack constants.
+    let (numerator_shift, denominator_shift, numerator_gap_min, denominator_gap_min) =
+        if display_style {
+            (
+                constants.fraction_numerator_display_style_shift_up,
+                constants.fraction_denominator_display_style_shift_down,
+                constants.fraction_num_display_style_gap_min,
+                constants.fraction_denom_display_style_gap_min,
+            )
+        } else {
+            (
+                constants.fraction_numerator_shift_up,
+                constants.fraction_denominator_shift_down,
+                constants.fraction_numerator_gap_min,
+                constants.fraction_denominator_gap_min,
+            )
+        };
+    let axis = Length::from(constants.axis_height);
+    let numerator_shift = Length::from(numerator_shift)
+        .max(Length::from(numerator_gap_min) + axis + thickness / 2. + numerator.descent);
+    let denominator_shift = Length::from(denominator_shift)
+        .max(Length::from(denominator_gap_min) + thickness / 2. - axis + denominator.ascent);
+
+    let inline_size = numerator.inline_size.max(denominator.inline_size);
+    let ascent = (numerator_shift + numerator.ascent).max(axis + thickness / 2.);
+    let descent = (denominator_shift + denominator.descent).max(thickness / 2. - axis);
+
+    let mut fragments = vec![];
+    if thickness > Length::zero() {
+        fragments.push(rule(
+            tag,
+            style,
+            Rect {
+                start_corner: Vec2 {
+                    inline: Length::zero(),
+                    block: ascent - axis - thickness / 2.,
+                },
+                size: Vec2 {
+                    inline: inline_size,
+                    block: thickness,
+                },
+            },
+        ));
+    }
+    let numerator_start = (inline_size - numerator.inline_size) / 2.;
+    fragments.push(numerator.place(numerator_start, ascent - numerator_shift));
+    let denominator_start = (inline_size - denominator.inline_size) / 2.;
+    fragments.push(denominator.place(denominator_start, ascent + denominator_shift));
+    MathLayout {
+        fragments,
+        inline_size,
+        ascent,
+        descent,
+    }
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#radicals-msqrt-mroot
+fn layout_radical(
+    layout_context: &LayoutContext,
+    base: MathLayout,
+    index: Option<ChildLayout>,
+    display_style: bool,
+    tag: OpaqueNode,
+    style: &Arc<ComputedValues>,
+) -> MathLayout {
+    let constants = operator::math_constants(layout_context, style);
+    let thickness = Length::from(constants.radical_rule_thickness);
+    let mut gap = Length::from(if display_style {
+        constants.radical_display_style_vertical_gap
+    } else {
+        constants.radical_vertical_gap
+    });
+
+    let surd = operator::shape_operator(
+        layout_context,
+        style,
+        "√",
+        GlyphSize::Stretched {
+            ascent: base.ascent + gap + thickness,
+            descent: base.descent,
+            symmetric: false,
+        },
+    );
+    let surd_height = surd.ascent + surd.descent;
+    let target_height = base.ascent + base.descent + gap + thickness;
+    if surd_height > target_height {
+        gap += (surd_height - target_height) / 2.;
+    }
+
+    // The top of the surd is aligned with the top of the overbar.
+    let overbar_top = base.ascent + gap + thickness;
+    let mut ascent = overbar_top + Length::from(constants.radical_extra_ascender);
+    let mut descent = base.descent.max(surd_height - overbar_top);
+
+    let mut surd_start = Length::zero();
+    let index = index.map(|index| {
+        let kern_before = Length::from(constants.radical_kern_before_degree);
+        let kern_after = Length::from(constants.radical_kern_after_degree);
+        let raise = surd_height * (constants.radical_degree_bottom_raise_percent as f32 / 100.);
+        let shift_up = raise - (surd_height - overbar_top);
+        ascent.max_assign(shift_up + index.ascent);
+        descent.max_assign(index.descent - shift_up);
+        surd_start = (kern_before + index.inline_size + kern_after).max(Length::zero());
+        (index, kern_before, shift_up)
+    });
+
+    let mut fragments = vec![];
+    if let Some((index, kern_before, shift_up)) = index {
+        fragments.push(index.place(kern_before.max(Length::zero()), ascent - shift_up));
+    }
+    if !surd.glyphs.is_empty() {
+        fragments.push(Fragment::Text(TextFragment {
+            debug_id: DebugId::new(),
+            tag,
+            parent_style: style.clone(),
+            rect: Rect {
+                start_corner: Vec2 {
+                    inline: surd_start,
+                    block: ascent - overbar_top,
+                },
+                size: Vec2 {
+                    inline: surd.advance,
+                    block: surd_height,
+                },
+            },
+            ascent: surd.ascent,
+            font_key: surd.font_key,
+            glyphs: surd.glyphs,
+        }));
+    }
+    let base_start = surd_start + surd.advance;
+    fragments.push(rule(
+        tag,
+        style,
+        Rect {
+            start_corner: Vec2 {
+                inline: base_start,
+                block: ascent - overbar_top,
+            },
+            size: Vec2 {
+                inline: base.inline_size,
+                block: thickness,
+            },
+        },
+    ));
+    for mut fragment in base.fragments {
+        let position = fragment.position_mut();
+        position.inline += base_start;
+        position.block += ascent - base.ascent;
+        fragments.push(fragment);
+    }
+    MathLayout {
+        fragments,
+        inline_size: base_start + base.inline_size,
+        ascent,
+        descent,
+    }
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#subscripts-and-superscripts-msub-msup-msubsup
+fn layout_scripts(
+    base: ChildLayout,
+    base_is_token: bool,
+    subscript: Option<ChildLayout>,
+    superscript: Option<ChildLayout>,
+    constants: &MathConstants,
+) -> MathLayout {
+    // Scripts of tokens are not moved away by the size of their base, which
+    // includes the whole line height.
+    let mut subscript_shift = subscript.as_ref().map(|subscript| {
+        let mut shift = Length::from(constants.subscript_shift_down)
+            .max(subscript.ascent - constants.subscript_top_max.into());
+        if !base_is_token {
+            shift.max_assign(base.descent + constants.subscript_baseline_drop_min.into());
+        }
+        shift
+    });
+    // FIXME: use the cramped shift in cramped styles.
+    let mut superscript_shift = superscript.as_ref().map(|superscript| {
+        let mut shift = Length::from(constants.superscript_shift_up)
+            .max(superscript.descent + constants.superscript_bottom_min.into());
+        if !base_is_token {
+            shift.max_assign(base.ascent - constants.superscript_baseline_drop_max.into());
+        }
+        shift
+    });
+    if let (Some(subscript), Some(superscript), Some(subscript_shift), Some(superscript_shift)) = (
+        &subscript,
+        &superscript,
+        &mut subscript_shift,
+        &mut superscript_shift,
+    ) {
+        let gap =
+            (*subscript_shift - subscript.ascent) + (*superscript_shift - superscript.descent);
+        let gap_min = Length::from(constants.sub_superscript_gap_min);
+        if gap < gap_min {
+            // Raise the superscript as far as allowed, then lower the subscript.
+            let superscript_bottom = *superscript_shift - superscript.descent;
+            let raise = (Length::from(constants.superscript_bottom_max_with_subscript) -
+                superscript_bottom)
+                .max(Length::zero())
+                .min(gap_min - gap);
+            *superscript_shift += raise;
+            *subscript_shift += gap_min - gap - raise;
+        }
+    }
+
+    let mut ascent = base.ascent;
+    let mut descent = base.descent;
+    let mut scripts_inline_size = Length::zero();
+    if let (Some(subscript), Some(shift)) = (&subscript, subscript_shift) {
+        descent.max_assign(shift + subscript.descent);
+        scripts_inline_size.max_assign(subscript.inline_size);
+    }
+    if let (Some(superscript), Some(shift)) = (&superscript, superscript_shift) {
+        ascent.max_assign(shift + superscript.ascent);
+        scripts_inline_size.max_assign(superscript.inline_size);
+    }
+
+    let scripts_start = base.inline_size;
+    let mut fragments = vec![base.place(Length::zero(), ascent)];
+    if let (Some(subscript), Some(shift)) = (subscript, subscript_shift) {
+        fragments.push(subscript.place(scripts_start, ascent + shift));
+    }
+    if let (Some(superscript), Some(shift)) = (superscript, superscript_shift) {
+        fragments.push(superscript.place(scripts_start, ascent - shift));
+    }
+    MathLayout {
+        fragments,
+        inline_size: scripts_start + scripts_inline_size + constants.space_after_script.into(),
+        ascent,
+        descent,
+    }
+}
+
+fn rule(tag: OpaqueNode, style: &Arc<ComputedValues>, rect: Rect<Length>) -> Fragment {
+    Fragment::Rule(RuleFragment {
+        debug_id: DebugId::new(),
+        tag,
+        style: style.clone(),
+        rect,
+    })
+}
+
+fn as_math(child: &IndependentFormattingContext) -> Option<&MathFormattingContext> {
+    child.as_replaced().err()?.as_math()
+}
+
+fn is_stretchy_operator(child: &IndependentFormattingContext) -> bool {
+    match as_math(child) {
+        Some(MathFormattingContext::Operator(operator)) => operator.properties.stretchy,
+        _ => false,
+    }
+}
+
+fn is_token_or_operator(child: &IndependentFormattingContext) -> bool {
+    match as_math(child) {
+        Some(MathFormattingContext::Operator(_)) | Some(MathFormattingContext::Token { .. }) => {
+            true
+        },
+        _ => false,
+    }
+}
+
+fn outer_inline_size(child: &Arc<IndependentFormattingContext>) -> Length {
+    child.content_sizes.outer_inline(&child.style).max_content
+}
+
+fn row_inline_size(children: &[Arc<IndependentFormattingContext>]) -> Length {
+    children
+        .iter()
+        .map(outer_inline_size)
+        .fold(Length::zero(), |sum, size| sum + size)
+}
@@ -0,0 +1,587 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `mo` element, and the stretching of its glyph.
+//!
+//! https://mathml-refresh.github.io/mathml-core/#operator-fence-separator-or-accent-mo
+
+use crate::context::LayoutContext;
+use crate::dom_traversal::{BoxSlot, Contents, NodeExt, NonReplacedContents, TraversalHandler};
+use crate::element_data::LayoutBox;
+use crate::style_ext::DisplayGeneratingBox;
+use app_units::Au;
+use euclid::default::Point2D;
+use gfx::font::Font;
+use gfx::text::glyph::{GlyphData, GlyphStore};
+use gfx::text::math::{GlyphConstruction, MathConstants};
+use gfx_traits::ByteIndex;
+use servo_arc::Arc as ServoArc;
+use std::sync::Arc;
+use style::dom::OpaqueNode;
+use style::properties::ComputedValues;
+use style::values::computed::Length;
+use style::Zero;
+use webrender_api::FontInstanceKey;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Operator {
+    pub tag: OpaqueNode,
+    #[serde(skip_serializing)]
+    pub style: ServoArc<ComputedValues>,
+    pub text: String,
+    pub properties: OperatorProperties,
+    pub display_style: bool,
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#dictionary-based-attributes
+#[derive(Clone, Copy, Debug, Serialize)]
+pub(crate) struct OperatorProperties {
+    /// In units of 1/18 em.
+    pub lspace: u8,
+    /// In units of 1/18 em.
+    pub rspace: u8,
+    pub stretchy: bool,
+    pub symmetric: bool,
+    pub largeop: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Form {
+    Prefix,
+    Infix,
+    Postfix,
+}
+
+/// The size at which to draw the glyph of an operator.
+pub(super) enum GlyphSize {
+    Normal,
+    /// The size of large operators in display style.
+    Large,
+    /// Covering the given distances above and below the baseline.
+    Stretched {
+        ascent: Length,
+        descent: Length,
+        symmetric: bool,
+    },
+}
+
+pub(super) struct OperatorGlyphs {
+    pub font_key: FontInstanceKey,
+    pub glyphs: Vec<Arc<GlyphStore>>,
+    pub advance: Length,
+    pub ascent: Length,
+    pub descent: Length,
+}
+
+impl Operator {
+    pub fn construct<'dom>(
+        context: &LayoutContext,
+        node: impl NodeExt<'dom>,
+        style: &ServoArc<ComputedValues>,
+        contents: NonReplacedContents,
+        display_style: bool,
+    ) -> Self {
+        let mut builder = TextBuilder {
+            text: String::new(),
+        };
+        contents.traverse(context, node, style, &mut builder);
+        let text = builder.text.trim_matches(|c: char| c.is_ascii_whitespace());
+
+        let form = match node.attribute("form").as_ref().map(|form| &**form) {
+            Some("prefix") => Form::Prefix,
+            Some("infix") => Form::Infix,
+            Some("postfix") => Form::Postfix,
+            _ => form_from_position(node),
+        };
+        let mut properties = dictionary_properties(text, form);
+        let boolean = |name| match node.attribute(name) {
+            Some(ref value) if value.eq_ignore_ascii_case("true") => Some(true),
+            Some(ref value) if value.eq_ignore_ascii_case("false") => Some(false),
+            _ => None,
+        };
+        if let Some(stretchy) = boolean("stretchy") {
+            properties.stretchy = stretchy;
+        }
+        if let Some(symmetric) = boolean("symmetric") {
+            properties.symmetric = symmetric;
+        }
+        if let Some(largeop) = boolean("largeop") {
+            properties.largeop = largeop;
+        }
+
+        Operator {
+            tag: node.as_opaque(),
+            style: style.clone(),
+            text: text.to_owned(),
+            properties,
+            display_style,
+        }
+    }
+
+    /// The size of the glyph when the operator is not stretched.
+    pub fn glyph_size(&self) -> GlyphSize {
+        if self.properties.largeop && self.display_style {
+            GlyphSize::Large
+        } else {
+            GlyphSize::Normal
+        }
+    }
+
+    pub fn spacing(&self, style: &ComputedValues) -> (Length, Length) {
+        let em = style.get_font().font_size.size.0;
+        (
+            em * (self.properties.lspace as f32 / 18.),
+            em * (self.properties.rspace as f32 / 18.),
+        )
+    }
+}
+
+struct TextBuilder {
+    text: String,
+}
+
+impl<'dom, Node> TraversalHandler<'dom, Node> for TextBuilder
+where
+    Node: NodeExt<'dom>,
+{
+    fn handle_text(&mut self, _node: Node, text: String, _parent_style: &ServoArc<ComputedValues>) {
+        self.text.push_str(&text)
+    }
+
+    fn handle_element(
+        &mut self,
+        _node: Node,
+        _style: &ServoArc<ComputedValues>,
+        _display: DisplayGeneratingBox,
+        _contents: Contents,
+        box_slot: BoxSlot<'dom>,
+    ) {
+        // FIXME: render elements nested in operators.
+        box_slot.set(LayoutBox::DisplayContents)
+    }
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#dfn-form
+fn form_from_position<'dom>(node: impl NodeExt<'dom>) -> Form {
+    let parent = match node.parent_node() {
+        Some(parent) => parent,
+        None => return Form::Infix,
+    };
+    let mut siblings = vec![];
+    let mut next = parent.first_child();
+    while let Some(child) = next {
+        if child.is_element() {
+            siblings.push(child);
+        }
+        next = child.next_sibling();
+    }
+    if siblings.len() < 2 {
+        Form::Infix
+    } else if siblings.first() == Some(&node) {
+        Form::Prefix
+    } else if siblings.last() == Some(&node) {
+        Form::Postfix
+    } else {
+        Form::Infix
+    }
+}
+
+/// A small subset of the operator dictionary.
+///
+/// https://mathml-refresh.github.io/mathml-core/#operator-dictionary
+fn dictionary_properties(text: &str, form: Form) -> OperatorProperties {
+    // Operators without an entry for their form use the entry of another form.
+    let forms = [form, Form::Infix, Form::Postfix, Form::Prefix];
+    forms
+        .iter()
+        .find_map(|&form| dictionary_entry(text, form))
+        .unwrap_or_else(|| {
+            let spacing = if form == Form::Infix { 5 } else { 0 };
+            OperatorProperties {
+                lspace: spacing,
+                rspace: spacing,
+                stretchy: false,
+                symmetric: false,
+                largeop: false,
+            }
+        })
+}
+
+fn dictionary_entry(text: &str, form: Form) -> Option<OperatorProperties> {
+    let properties = |lspace, rspace| OperatorProperties {
+        lspace,
+        rspace,
+        stretchy: false,
+        symmetric: false,
+        largeop: false,
+    };
+    let entry = match (text, form) {
+        ("(", Form::Prefix) |
+        (")", Form::Postfix) |
+        ("[", Form::Prefix) |
+        ("]", Form::Postfix) |
+        ("{", Form::Prefix) |
+        ("}", Form::Postfix) |
+        ("⌈", Form::Prefix) |
+        ("⌉", Form::Postfix) |
+        ("⌊", Form::Prefix) |
+        ("⌋", Form::Postfix) |
+        ("⟨", Form::Prefix) |
+        ("⟩", Form::Postfix) |
+        ("|", _) |
+        ("‖", _) => OperatorProperties {
+            stretchy: true,
+            symmetric: true,
+            ..properties(0, 0)
+        },
+        ("∑", Form::Prefix) | ("∏", Form::Prefix) | ("∐", Form::Prefix) => {
+            OperatorProperties {
+                symmetric: true,
+                largeop: true,
+                ..properties(1, 2)
+            }
+        },
+        ("∫", Form::Prefix) |
+        ("∬", Form::Prefix) |
+        ("∭", Form::Prefix) |
+        ("∮", Form::Prefix) |
+        ("⋀", Form::Prefix) |
+        ("⋁", Form::Prefix) |
+        ("⋂", Form::Prefix) |
+        ("⋃", Form::Prefix) => OperatorProperties {
+            symmetric: true,
+            largeop: true,
+            ..properties(3, 3)
+        },
+        ("=", Form::Infix) |
+        ("<", Form::Infix) |
+        (">", Form::Infix) |
+        ("≠", Form::Infix) |
+        ("≤", Form::Infix) |
+        ("≥", Form::Infix) |
+        ("≈", Form::Infix) |
+        ("≡", Form::Infix) |
+        ("∼", Form::Infix) |
+        ("≃", Form::Infix) |
+        ("≅", Form::Infix) |
+        ("∝", Form::Infix) |
+        ("∈", Form::Infix) |
+        ("∉", Form::Infix) |
+        ("⊂", Form::Infix) |
+        ("⊃", Form::Infix) |
+        ("⊆", Form::Infix) |
+        ("⊇", Form::Infix) |
+        ("→", Form::Infix) |
+        ("←", Form::Infix) |
+        ("↔", Form::Infix) |
+        ("⇒", Form::Infix) |
+        ("⇐", Form::Infix) |
+        ("⇔", Form::Infix) => properties(5, 5),
+        ("+", Form::Infix) |
+        ("-", Form::Infix) |
+        ("−", Form::Infix) |
+        ("±", Form::Infix) |
+        ("∓", Form::Infix) |
+        ("×", Form::Infix) |
+        ("÷", Form::Infix) |
+        ("⋅", Form::Infix) |
+        ("∘", Form::Infix) |
+        ("∗", Form::Infix) |
+        ("∧", Form::Infix) |
+        ("∨", Form::Infix) |
+        ("∩", Form::Infix) |
+        ("∪", Form::Infix) |
+        ("⊕", Form::Infix) |
+        ("⊗", Form::Infix) => properties(4, 4),
+        (",", Form::Infix) | (";", Form::Infix) => properties(0, 3),
+        _ => return None,
+    };
+    Some(entry)
+}
+
+/// Returns the `MATH` table constants of the first font for this style, or
+/// values derived from its metrics if it is not a math font.
+pub(super) fn math_constants(
+    layout_context: &LayoutContext,
+    style: &ComputedValues,
+) -> MathConstants {
+    crate::context::with_thread_local_font_context(layout_context, |font_context| {
+        let font_group = font_context.font_group(style.clone_font());
+        let font = font_group
+            .borrow_mut()
+            .first(font_context)
+            .expect("could not find font");
+        let font = font.borrow();
+        font_math_constants(&font)
+    })
+}
+
+fn font_math_constants(font: &Font) -> MathConstants {
+    if let Some(table) = font.math_table() {
+        return table.constants().clone();
+    }
+
+    // https://mathml-refresh.github.io/mathml-core/#layout-constants-mathconstants
+    let em = font.metrics.em_size;
+    let x_height = font.metrics.x_height;
+    let rule_thickness = if font.metrics.underline_size > Au(0) {
+        font.metrics.underline_size
+    } else {
+        em / 18
+    };
+    MathConstants {
+        script_percent_scale_down: 71,
+        script_script_percent_scale_down: 50,
+        display_operator_min_height: Au(0),
+        axis_height: x_height / 2,
+        subscript_shift_down: x_height / 3,
+        subscript_top_max: x_height * 4 / 5,
+        subscript_baseline_drop_min: Au(0),
+        superscript_shift_up: x_height * 4 / 5,
+        superscript_shift_up_cramped: x_height * 3 / 5,
+        superscript_bottom_min: x_height / 4,
+        superscript_baseline_drop_max: Au(0),
+        sub_superscript_gap_min: rule_thickness * 4,
+        superscript_bottom_max_with_subscript: x_height * 4 / 5,
+        space_after_script: em / 24,
+        fraction_numerator_shift_up: Au(0),
+        fraction_numerator_display_style_shift_up: Au(0),
+        fraction_denominator_shift_down: Au(0),
+        fraction_denominator_display_style_shift_down: Au(0),
+        fraction_numerator_gap_min: rule_thickness,
+        fraction_num_display_style_gap_min: rule_thickness * 3,
+        fraction_rule_thickness: rule_thickness,
+        fraction_denominator_gap_min: rule_thickness,
+        fraction_denom_display_style_gap_min: rule_thickness * 3,
+        radical_vertical_gap: rule_thickness * 5 / 4,
+        radical_display_style_vertical_gap: rule_thickness + x_height / 4,
+        radical_rule_thickness: rule_thickness,
+        radical_extra_ascender: rule_thickness,
+        radical_kern_before_degree: em * 5 / 18,
+        radical_kern_after_degree: -em * 10 / 18,
+        radical_degree_bottom_raise_percent: 60,
+    }
+}
+
+/// Shapes the text of an operator, stretching it to the given size if it is a
+/// single character for which the font provides larger variants.
+pub(super) fn shape_operator(
+    layout_context: &LayoutContext,
+    style: &ComputedValues,
+    text: &str,
+    size: GlyphSize,
+) -> OperatorGlyphs {
+    use gfx::font::{ShapingFlags, ShapingOptions};
+
+    crate::context::with_thread_local_font_context(layout_context, |font_context| {
+        let font_group = font_context.font_group(style.clone_font());
+        let font = font_group
+            .borrow_mut()
+            .first(font_context)
+            .expect("could not find font");
+        let mut font = font.borrow_mut();
+        let font_key = font.font_key;
+        if text.is_empty() {
+            return OperatorGlyphs {
+                font_key,
+                glyphs: vec![],
+                advance: Length::zero(),
+                ascent: Length::zero(),
+                descent: Length::zero(),
+            };
+        }
+
+        let constants = font_math_constants(&font);
+        let axis = constants.axis_height;
+        let target = match size {
+            GlyphSize::Normal => None,
+            GlyphSize::Large => Some(constants.display_operator_min_height),
+            GlyphSize::Stretched {
+                ascent,
+                descent,
+                symmetric,
+            } => {
+                let (ascent, descent) = (Au::from(ascent), Au::from(descent));
+                if symmetric {
+                    Some((ascent - axis).max(descent + axis) * 2)
+                } else {
+                    Some(ascent + descent)
+                }
+            },
+        };
+
+        let mut chars = text.chars();
+        let single_char = match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        };
+        let stretched = target.and_then(|target| {
+            let glyph = font.glyph_index(single_char?)?;
+            let construction = font.math_table()?.vertical_construction(glyph)?;
+            let min_connector_overlap = font.math_table()?.min_connector_overlap();
+            stretch_glyph(&construction, target, min_connector_overlap)
+        });
+        let (glyphs, height) = match stretched {
+            Some((glyphs, height)) => (glyphs, height),
+            None => {
+                let options = ShapingOptions {
+                    letter_spacing: None,
+                    word_spacing: Au(0),
+                    script: unicode_script::Script::Common,
+                    flags: ShapingFlags::empty(),
+                };
+                let glyphs = font.shape_text(text, &options);
+                let ascent = font.metrics.ascent;
+                let descent = font.metrics.descent;
+                return OperatorGlyphs {
+                    font_key,
+                    advance: glyphs.total_advance().into(),
+                    glyphs: vec![glyphs],
+                    ascent: ascent.into(),
+                    descent: descent.into(),
+                };
+            },
+        };
+
+        // FIXME: We don’t know the ink bounds of glyphs, so assume that size
+        // variants and assemblies are centered on the math axis, which is how
+        // math fonts usually design them.
+        let ascent = height / 2 + axis;
+        let descent = height - ascent;
+        let glyphs = match glyphs {
+            StretchedGlyphs::Variant(glyph) => {
+                let advance = Au::from_f64_px(font.glyph_h_advance(glyph));
+                store(&[GlyphData::new(glyph, advance, None, true, true)])
+            },
+            StretchedGlyphs::Assembly(parts) => {
+                let last = parts.len() - 1;
+                let advance = parts
+                    .iter()
+                    .map(|&(glyph, _)| Au::from_f64_px(font.glyph_h_advance(glyph)))
+                    .max()
+                    .unwrap_or(Au(0));
+                // Parts are drawn on top of each other, so only the last one
+                // advances.
+                let data: Vec<_> = parts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(glyph, bottom))| {
+                        let offset = Point2D::new(Au(0), descent - bottom);
+                        let advance = if i == last { advance } else { Au(0) };
+                        GlyphData::new(glyph, advance, Some(offset), true, true)
+                    })
+                    .collect();
+                store(&data)
+            },
+        };
+        OperatorGlyphs {
+            font_key,
+            advance: glyphs.total_advance().into(),
+            glyphs: vec![glyphs],
+            ascent: ascent.into(),
+            descent: descent.into(),
+        }
+    })
+}
+
+enum StretchedGlyphs {
+    Variant(gfx::text::glyph::GlyphId),
+    /// Glyphs with the distance between their baseline and the bottom of the
+    /// assembly.
+    Assembly(Vec<(gfx::text::glyph::GlyphId, Au)>),
+}
+
+/// https://mathml-refresh.github.io/mathml-core/#algorithms-for-glyph-stretching
+fn stretch_glyph(
+    construction: &GlyphConstruction,
+    target: Au,
+    min_connector_overlap: Au,
+) -> Option<(StretchedGlyphs, Au)> {
+    if let Some(variant) = construction
+        .variants
+        .iter()
+        .find(|variant| variant.advance >= target)
+    {
+        return Some((StretchedGlyphs::Variant(variant.glyph), variant.advance));
+    }
+    if let Some(assembly) = assemble(construction, target, min_connector_overlap) {
+        return Some(assembly);
+    }
+    let largest = construction.variants.last()?;
+    Some((StretchedGlyphs::Variant(largest.glyph), largest.advance))
+}
+
+/// Repeats the extenders of a glyph assembly as few times as possible to
+/// reach the target size, then spreads the parts to cover it exactly.
+fn assemble(
+    construction: &GlyphConstruction,
+    target: Au,
+    min_connector_overlap: Au,
+) -> Option<(StretchedGlyphs, Au)> {
+    const MAX_REPETITIONS: usize = 100;
+
+    let parts = &construction.parts;
+    if parts.is_empty() {
+        return None;
+    }
+    let extenders = parts.iter().filter(|part| part.is_extender).count();
+    let advances = |repetitions: usize| {
+        parts
+            .iter()
+            .map(|part| {
+                let count = if part.is_extender { repetitions } else { 1 };
+                part.full_advance * count as i32
+            })
+            .fold(Au(0), |sum, advance| sum + advance)
+    };
+    let part_count = |repetitions: usize| parts.len() - extenders + extenders * repetitions;
+    let max_size = |repetitions: usize| {
+        advances(repetitions) - min_connector_overlap * (part_count(repetitions) as i32 - 1)
+    };
+
+    let mut repetitions = 1;
+    while max_size(repetitions) < target {
+        if extenders == 0 || repetitions == MAX_REPETITIONS {
+            return None;
+        }
+        repetitions += 1;
+    }
+
+    // Overlap connectors as much as possible, without shrinking the assembly
+    // below the target size or overlapping more than the connectors allow.
+    let count = part_count(repetitions);
+    let overlap = if count > 1 {
+        let max_overlap = parts
+            .iter()
+            .map(|part| part.start_connector_length.min(part.end_connector_length))
+            .filter(|&length| length > Au(0))
+            .min()
+            .unwrap_or(min_connector_overlap);
+        ((advances(repetitions) - target) / (count as i32 - 1))
+            .min(max_overlap)
+            .max(min_connector_overlap)
+    } else {
+        Au(0)
+    };
+
+    // Parts are listed from bottom to top.
+    let mut glyphs = vec![];
+    let mut bottom = Au(0);
+    for part in parts {
+        let count = if part.is_extender { repetitions } else { 1 };
+        for _ in 0..count {
+            glyphs.push((part.glyph, bottom));
+            bottom = bottom + part.full_advance - overlap;
+        }
+    }
+    let height = bottom + overlap;
+    Some((StretchedGlyphs::Assembly(glyphs), height))
+}
+
+fn store(data: &[GlyphData]) -> Arc<GlyphStore> {
+    let mut store = GlyphStore::new(1, false, false);
+    store.add_glyphs_for_byte_index(ByteIndex(0), data);
+    store.finalize_changes();
+    Arc::new(store)
+}
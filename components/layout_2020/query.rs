@@ -6,13 +6,15 @@
 
 use crate::context::LayoutContext;
 use crate::flow::FragmentTreeRoot;
+use crate::opaque_node::OpaqueNodeMethods;
 use app_units::Au;
 use euclid::default::{Point2D, Rect};
 use euclid::Size2D;
 use euclid::Vector2D;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
-use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::rpc::ScrollbarStyleResponse;
+use script_layout_interface::rpc::{BoxQuad, BoxQuadsResponse, TextIndexResponse};
 use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{NodeGeometryResponse, NodeScrollIdResponse};
 use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, StyleResponse};
@@ -44,6 +46,9 @@ pub struct LayoutThreadData {
     /// A queued response for the content boxes of a node.
     pub content_boxes_response: Vec<Rect<Au>>,
 
+    /// A queued response for the transformed border boxes of a node.
+    pub box_quads_response: Vec<BoxQuad>,
+
     /// A queued response for the client {top, left, width, height} of a node in pixels.
     pub client_rect_response: Rect<i32>,
 
@@ -62,6 +67,9 @@ pub struct LayoutThreadData {
     /// A queued response for the style of a node.
     pub style_response: StyleResponse,
 
+    /// A queued response for the style of the scrollbars of a node.
+    pub scrollbar_style_response: ScrollbarStyleResponse,
+
     /// Scroll offsets of scrolling regions.
     pub scroll_offsets: HashMap<ExternalScrollId, Vector2D<f32, LayoutPixel>>,
 
@@ -96,6 +104,13 @@ impl LayoutRPC for LayoutRPCImpl {
         ContentBoxesResponse(rw_data.content_boxes_response.clone())
     }
 
+    /// Requests the border boxes of a node and their transforms, as in the `getBoxQuads()` call.
+    fn box_quads(&self) -> BoxQuadsResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        BoxQuadsResponse(rw_data.box_quads_response.clone())
+    }
+
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress> {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -145,6 +160,12 @@ impl LayoutRPC for LayoutRPCImpl {
         rw_data.style_response.clone()
     }
 
+    fn scrollbar_style(&self) -> ScrollbarStyleResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.scrollbar_style_response.clone()
+    }
+
     fn text_index(&self) -> TextIndexResponse {
         let &LayoutRPCImpl(ref rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -180,6 +201,10 @@ pub fn process_content_boxes_request(_requested_node: OpaqueNode) -> Vec<Rect<Au
     vec![]
 }
 
+pub fn process_box_quads_request(_requested_node: OpaqueNode) -> Vec<BoxQuad> {
+    vec![]
+}
+
 pub fn process_node_geometry_request(
     requested_node: OpaqueNode,
     fragment_tree_root: Option<Arc<FragmentTreeRoot>>,
@@ -192,6 +217,18 @@ pub fn process_node_geometry_request(
     fragment_tree_root.get_border_dimensions_for_node(requested_node)
 }
 
+/// Returns the border boxes of all the nodes that have fragments, in CSS
+/// pixels, for measuring layout shifts.
+pub fn process_node_border_boxes_request(
+    fragment_tree_root: &FragmentTreeRoot,
+) -> HashMap<UntrustedNodeAddress, Rect<f32>> {
+    fragment_tree_root
+        .get_border_boxes()
+        .into_iter()
+        .map(|(node, rect)| (node.to_untrusted_node_address(), rect))
+        .collect()
+}
+
 pub fn process_node_scroll_id_request<N: LayoutNode>(
     id: PipelineId,
     requested_node: N,
@@ -227,6 +264,10 @@ pub fn process_style_query<N: LayoutNode>(_requested_node: N) -> StyleResponse {
     StyleResponse(None)
 }
 
+pub fn process_scrollbar_style_query<N: LayoutNode>(_requested_node: N) -> ScrollbarStyleResponse {
+    ScrollbarStyleResponse(None)
+}
+
 // https://html.spec.whatwg.org/multipage/#the-innertext-idl-attribute
 pub fn process_element_inner_text_query<N: LayoutNode>(_node: N) -> String {
     "".to_owned()
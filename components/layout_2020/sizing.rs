@@ -90,7 +90,7 @@ pub(crate) enum BoxContentSizes {
 }
 
 impl BoxContentSizes {
-    fn expect_inline(&self) -> &ContentSizes {
+    pub(crate) fn expect_inline(&self) -> &ContentSizes {
         match self {
             Self::NoneWereRequested => panic!("Accessing content size that was not requested"),
             Self::Inline(s) => s,
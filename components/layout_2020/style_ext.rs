@@ -41,6 +41,8 @@ pub(crate) enum DisplayOutside {
 pub(crate) enum DisplayInside {
     Flow,
     FlowRoot,
+    Grid,
+    Math,
 }
 
 pub(crate) trait ComputedValuesExt {
@@ -254,6 +256,8 @@ impl From<stylo::Display> for Display {
         let inside = match packed.inside() {
             stylo::DisplayInside::Flow => DisplayInside::Flow,
             stylo::DisplayInside::FlowRoot => DisplayInside::FlowRoot,
+            stylo::DisplayInside::Grid => DisplayInside::Grid,
+            stylo::DisplayInside::Math => DisplayInside::Math,
 
             // These should not be values of DisplayInside, but oh well
             stylo::DisplayInside::None => return Display::None,
@@ -31,7 +31,7 @@ use embedder_traits::resources::{self, Resource};
 use euclid::{default::Size2D as UntypedSize2D, Point2D, Rect, Scale, Size2D};
 use fnv::FnvHashMap;
 use fxhash::{FxHashMap, FxHashSet};
-use gfx::font;
+use gfx::font::{self, FontMetricsOverrides};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
@@ -51,10 +51,13 @@ use layout::flow_ref::FlowRef;
 use layout::incremental::{RelayoutMode, SpecialRestyleDamage};
 use layout::layout_debug;
 use layout::parallel;
-use layout::query::{process_client_rect_query, process_element_inner_text_query};
+use layout::query::process_node_border_boxes_request;
+use layout::query::process_scrollbar_style_query;
 use layout::query::{
-    process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
+    process_box_quads_request, process_content_box_request, process_content_boxes_request,
+    LayoutRPCImpl, LayoutThreadData,
 };
+use layout::query::{process_client_rect_query, process_element_inner_text_query};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
 use layout::query::{
     process_offset_parent_query, process_resolved_style_request, process_style_query,
@@ -67,7 +70,8 @@ use layout::wrapper::LayoutNodeLayoutData;
 use layout_traits::LayoutThreadFactory;
 use libc::c_void;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
-use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
+use metrics::{LayoutShiftMetrics, PaintTimeMetrics};
+use metrics::{ProfilerMetadataFactory, ProgressiveWebMetric};
 use msg::constellation_msg::{
     BackgroundHangMonitor, BackgroundHangMonitorRegister, HangAnnotation,
 };
@@ -80,13 +84,14 @@ use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType, Reflow};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
+use script_layout_interface::rpc::ScrollbarStyleResponse;
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, StyleResponse};
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
 use script_traits::{DrawAPaintImageResult, IFrameSizeMsg, PaintWorkletError, WindowSizeType};
 use script_traits::{Painter, WebrenderIpcSender};
-use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
+use script_traits::{ScrollState, ScrollbarThumb, UntrustedNodeAddress, WindowSizeData};
 use selectors::Element;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
@@ -242,12 +247,19 @@ pub struct LayoutThread {
     /// Paint time metrics.
     paint_time_metrics: PaintTimeMetrics,
 
+    /// Layout instability metrics.
+    layout_shift_metrics: LayoutShiftMetrics,
+
     /// The time a layout query has waited before serviced by layout thread.
     layout_query_waiting_time: Histogram,
 
     /// The sizes of all iframes encountered during the last layout operation.
     last_iframe_sizes: RefCell<HashMap<BrowsingContextId, Size2D<f32, CSSPixel>>>,
 
+    /// The scrollbar thumbs painted by the last layout operation, which the
+    /// compositor moves as their scroll frames scroll.
+    last_scrollbar_thumbs: RefCell<Vec<ScrollbarThumb>>,
+
     /// Flag that indicates if LayoutThread is busy handling a request.
     busy: Arc<AtomicBool>,
 
@@ -482,6 +494,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    FontMetricsOverrides::from(rule),
                     sender.clone(),
                 );
                 receiver.recv().unwrap();
@@ -495,6 +508,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    FontMetricsOverrides::from(rule),
                     (*font_cache_sender).clone(),
                 );
             }
@@ -592,12 +606,14 @@ impl LayoutThread {
                 indexable_text: IndexableText::default(),
                 content_box_response: None,
                 content_boxes_response: Vec::new(),
+                box_quads_response: Vec::new(),
                 client_rect_response: Rect::zero(),
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
                 resolved_style_response: String::new(),
                 offset_parent_response: OffsetParentResponse::empty(),
                 style_response: StyleResponse(None),
+                scrollbar_style_response: ScrollbarStyleResponse(None),
                 scroll_offsets: HashMap::new(),
                 text_index_response: TextIndexResponse(None),
                 nodes_from_point_response: vec![],
@@ -611,8 +627,10 @@ impl LayoutThread {
                 Timer::new()
             },
             paint_time_metrics: paint_time_metrics,
+            layout_shift_metrics: LayoutShiftMetrics::new(),
             layout_query_waiting_time: Histogram::new(),
             last_iframe_sizes: Default::default(),
+            last_scrollbar_thumbs: Default::default(),
             busy,
             load_webfonts_synchronously,
             dump_display_list,
@@ -705,6 +723,7 @@ impl LayoutThread {
                 LayoutHangAnnotation::UpdateScrollStateFromScript
             },
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
+            Msg::RegisterProperty(..) => LayoutHangAnnotation::RegisterProperty,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
         };
@@ -824,6 +843,9 @@ impl LayoutThread {
                 rw_data
                     .scroll_offsets
                     .insert(state.scroll_id, state.scroll_offset);
+                let _ = self
+                    .constellation_chan
+                    .send(ConstellationMsg::ScrolledFromScript(state));
 
                 let point = Point2D::new(-state.scroll_offset.x, -state.scroll_offset.y);
                 self.webrender_api.send_scroll_node(
@@ -872,6 +894,10 @@ impl LayoutThread {
                 };
                 self.registered_painters.0.insert(name, registered_painter);
             },
+            Msg::RegisterProperty(registration) => {
+                debug!("Registering custom property --{}", registration.name);
+                self.stylist.register_custom_property(registration);
+            },
             Msg::PrepareToExit(response_chan) => {
                 self.prepare_to_exit(response_chan);
                 return false;
@@ -1138,6 +1164,7 @@ impl LayoutThread {
                             layout_context,
                             background_color,
                             data.page_clip_rect.size,
+                            rw_data.scroll_offsets.clone(),
                         );
 
                         debug!("Done building display list.");
@@ -1198,6 +1225,16 @@ impl LayoutThread {
                             }
                         }
 
+                        let scrollbar_thumbs =
+                            std::mem::replace(&mut build_state.scrollbar_thumbs, vec![]);
+                        if *self.last_scrollbar_thumbs.borrow() != scrollbar_thumbs {
+                            *self.last_scrollbar_thumbs.borrow_mut() = scrollbar_thumbs.clone();
+                            let msg = ConstellationMsg::ScrollbarThumbs(self.id, scrollbar_thumbs);
+                            if let Err(e) = self.constellation_chan.send(msg) {
+                                warn!("Layout scrollbar thumbs to constellation failed ({}).", e);
+                            }
+                        }
+
                         rw_data.indexable_text = std::mem::replace(
                             &mut build_state.indexable_text,
                             IndexableText::default(),
@@ -1251,6 +1288,13 @@ impl LayoutThread {
                 self.paint_time_metrics
                     .maybe_observe_paint_time(self, epoch, is_contentful.0);
 
+                if pref!(dom.layout_instability.enabled) {
+                    self.layout_shift_metrics.observe_frame(
+                        process_node_border_boxes_request(layout_root),
+                        viewport_size.to_untyped(),
+                    );
+                }
+
                 self.webrender_api.send_display_list(
                     self.webrender_document,
                     epoch,
@@ -1299,6 +1343,9 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxesQuery(_) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
+                        &QueryMsg::BoxQuadsQuery(_) => {
+                            rw_data.box_quads_response = Vec::new();
+                        },
                         &QueryMsg::NodesFromPointQuery(..) => {
                             rw_data.nodes_from_point_response = Vec::new();
                         },
@@ -1320,7 +1367,10 @@ impl LayoutThread {
                         &QueryMsg::StyleQuery(_) => {
                             rw_data.style_response = StyleResponse(None);
                         },
-                        &QueryMsg::TextIndexQuery(..) => {
+                        &QueryMsg::ScrollbarStyleQuery(_) => {
+                            rw_data.scrollbar_style_response = ScrollbarStyleResponse(None);
+                        },
+                        &QueryMsg::TextIndexQuery(..) | &QueryMsg::TextIndexAtPointQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
                         &QueryMsg::ElementInnerTextQuery(_) => {
@@ -1367,7 +1417,8 @@ impl LayoutThread {
         };
 
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
-        let device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        let mut device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        device.set_prefers_color_scheme(data.prefers_color_scheme);
         let sheet_origins_affected_by_device_change = self.stylist.set_device(device, &guards);
 
         self.stylist
@@ -1608,6 +1659,8 @@ impl LayoutThread {
             None => vec![],
         };
         reflow_result.newly_transitioning_nodes = newly_transitioning_nodes;
+        reflow_result.pending_web_fonts = self.outstanding_web_fonts.load(Ordering::SeqCst) != 0;
+        reflow_result.layout_shifts = self.layout_shift_metrics.take_layout_shifts();
 
         let mut root_flow = match self.root_flow.borrow().clone() {
             Some(root_flow) => root_flow,
@@ -1622,6 +1675,9 @@ impl LayoutThread {
                 &QueryMsg::ContentBoxesQuery(node) => {
                     rw_data.content_boxes_response = process_content_boxes_request(node, root_flow);
                 },
+                &QueryMsg::BoxQuadsQuery(node) => {
+                    rw_data.box_quads_response = process_box_quads_request(node, root_flow);
+                },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
                         Au::from_f32_px(point_in_node.x),
@@ -1630,6 +1686,11 @@ impl LayoutThread {
                     rw_data.text_index_response =
                         TextIndexResponse(rw_data.indexable_text.text_index(node, point_in_node));
                 },
+                &QueryMsg::TextIndexAtPointQuery(node, point) => {
+                    let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                    rw_data.text_index_response =
+                        TextIndexResponse(rw_data.indexable_text.text_index_at_point(node, point));
+                },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response = process_client_rect_query(node, root_flow);
                 },
@@ -1654,6 +1715,10 @@ impl LayoutThread {
                     let node = unsafe { ServoLayoutNode::new(&node) };
                     rw_data.style_response = process_style_query(node);
                 },
+                &QueryMsg::ScrollbarStyleQuery(node) => {
+                    let node = unsafe { ServoLayoutNode::new(&node) };
+                    rw_data.scrollbar_style_response = process_scrollbar_style_query(context, node);
+                },
                 &QueryMsg::NodesFromPointQuery(client_point, ref reflow_goal) => {
                     let mut flags = match reflow_goal {
                         &NodesFromPointQueryType::Topmost => webrender_api::HitTestFlags::empty(),
@@ -1708,6 +1773,8 @@ impl LayoutThread {
             layout_scroll_states.insert(new_state.scroll_id, offset);
 
             if new_state.scroll_id.is_root() {
+                self.layout_shift_metrics
+                    .set_scroll_position((-offset).to_point().to_untyped());
                 script_scroll_states.push((UntrustedNodeAddress::from_id(0), offset))
             } else if let Some(node_id) = node_id_from_scroll_id(new_state.scroll_id.0 as usize) {
                 script_scroll_states.push((UntrustedNodeAddress::from_id(node_id), offset))
@@ -1719,7 +1786,7 @@ impl LayoutThread {
                 self.id,
                 script_scroll_states,
             ));
-        rw_data.scroll_offsets = layout_scroll_states
+        rw_data.scroll_offsets = layout_scroll_states;
     }
 
     fn tick_all_animations<'a, 'b>(
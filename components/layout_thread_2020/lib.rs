@@ -29,16 +29,22 @@ use embedder_traits::resources::{self, Resource};
 use euclid::{default::Size2D as UntypedSize2D, Point2D, Rect, Scale, Size2D};
 use fnv::FnvHashMap;
 use fxhash::FxHashMap;
+use gfx::font::FontMetricsOverrides;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use layout::context::LayoutContext;
+use layout::context::RegisteredPainter;
+use layout::context::RegisteredPainters;
 use layout::display_list::{DisplayListBuilder, WebRenderImageInfo};
 use layout::layout_debug;
+use layout::query::process_node_border_boxes_request;
+use layout::query::process_scrollbar_style_query;
 use layout::query::{
-    process_content_box_request, process_content_boxes_request, LayoutRPCImpl, LayoutThreadData,
+    process_box_quads_request, process_content_box_request, process_content_boxes_request,
+    LayoutRPCImpl, LayoutThreadData,
 };
 use layout::query::{process_element_inner_text_query, process_node_geometry_request};
 use layout::query::{process_node_scroll_area_request, process_node_scroll_id_request};
@@ -51,7 +57,8 @@ use layout::{BoxTreeRoot, FragmentTreeRoot};
 use layout_traits::LayoutThreadFactory;
 use libc::c_void;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
-use metrics::{PaintTimeMetrics, ProfilerMetadataFactory, ProgressiveWebMetric};
+use metrics::{LayoutShiftMetrics, PaintTimeMetrics};
+use metrics::{ProfilerMetadataFactory, ProgressiveWebMetric};
 use msg::constellation_msg::{
     BackgroundHangMonitor, BackgroundHangMonitorRegister, HangAnnotation,
 };
@@ -64,6 +71,7 @@ use profile_traits::time::{self as profile_time, profile, TimerMetadata};
 use profile_traits::time::{TimerMetadataFrameType, TimerMetadataReflowType};
 use script_layout_interface::message::{LayoutThreadInit, Msg, NodesFromPointQueryType};
 use script_layout_interface::message::{QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow};
+use script_layout_interface::rpc::ScrollbarStyleResponse;
 use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, StyleResponse};
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
@@ -212,6 +220,9 @@ pub struct LayoutThread {
     /// Paint time metrics.
     paint_time_metrics: PaintTimeMetrics,
 
+    /// Layout instability metrics.
+    layout_shift_metrics: LayoutShiftMetrics,
+
     /// Flag that indicates if LayoutThread is busy handling a request.
     busy: Arc<AtomicBool>,
 
@@ -441,6 +452,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    FontMetricsOverrides::from(rule),
                     sender.clone(),
                 );
                 receiver.recv().unwrap();
@@ -454,6 +466,7 @@ fn add_font_face_rules(
                 font_cache_thread.add_web_font(
                     font_face.family().clone(),
                     effective_sources,
+                    FontMetricsOverrides::from(rule),
                     (*font_cache_sender).clone(),
                 );
             }
@@ -548,12 +561,14 @@ impl LayoutThread {
                 display_list: None,
                 content_box_response: None,
                 content_boxes_response: Vec::new(),
+                box_quads_response: Vec::new(),
                 client_rect_response: Rect::zero(),
                 scroll_id_response: None,
                 scroll_area_response: Rect::zero(),
                 resolved_style_response: String::new(),
                 offset_parent_response: OffsetParentResponse::empty(),
                 style_response: StyleResponse(None),
+                scrollbar_style_response: ScrollbarStyleResponse(None),
                 scroll_offsets: HashMap::new(),
                 text_index_response: TextIndexResponse(None),
                 nodes_from_point_response: vec![],
@@ -567,6 +582,7 @@ impl LayoutThread {
                 Timer::new()
             },
             paint_time_metrics: paint_time_metrics,
+            layout_shift_metrics: LayoutShiftMetrics::new(),
             busy,
             load_webfonts_synchronously,
             relayout_event,
@@ -622,6 +638,7 @@ impl LayoutThread {
             image_cache: self.image_cache.clone(),
             font_cache_thread: Mutex::new(self.font_cache_thread.clone()),
             webrender_image_cache: self.webrender_image_cache.clone(),
+            registered_painters: &self.registered_painters,
             pending_images: if script_initiated_layout {
                 Some(Mutex::new(Vec::new()))
             } else {
@@ -653,6 +670,7 @@ impl LayoutThread {
                 LayoutHangAnnotation::UpdateScrollStateFromScript
             },
             Msg::RegisterPaint(..) => LayoutHangAnnotation::RegisterPaint,
+            Msg::RegisterProperty(..) => LayoutHangAnnotation::RegisterProperty,
             Msg::SetNavigationStart(..) => LayoutHangAnnotation::SetNavigationStart,
             Msg::GetRunningAnimations(..) => LayoutHangAnnotation::GetRunningAnimations,
         };
@@ -801,7 +819,27 @@ impl LayoutThread {
             Msg::SetFinalUrl(final_url) => {
                 self.url = final_url;
             },
-            Msg::RegisterPaint(_name, _properties, _painter) => {},
+            Msg::RegisterPaint(name, mut properties, painter) => {
+                debug!("Registering the painter");
+                let properties = properties
+                    .drain(..)
+                    .filter_map(|name| {
+                        let id = PropertyId::parse_enabled_for_all_content(&*name).ok()?;
+                        Some((name.clone(), id))
+                    })
+                    .filter(|&(_, ref id)| !id.is_shorthand())
+                    .collect();
+                let registered_painter = RegisteredPainterImpl {
+                    name: name.clone(),
+                    properties,
+                    painter,
+                };
+                self.registered_painters.0.insert(name, registered_painter);
+            },
+            Msg::RegisterProperty(registration) => {
+                debug!("Registering custom property --{}", registration.name);
+                self.stylist.register_custom_property(registration);
+            },
             Msg::PrepareToExit(response_chan) => {
                 self.prepare_to_exit(response_chan);
                 return false;
@@ -969,6 +1007,9 @@ impl LayoutThread {
                         &QueryMsg::ContentBoxesQuery(_) => {
                             rw_data.content_boxes_response = Vec::new();
                         },
+                        &QueryMsg::BoxQuadsQuery(_) => {
+                            rw_data.box_quads_response = Vec::new();
+                        },
                         &QueryMsg::NodesFromPointQuery(..) => {
                             rw_data.nodes_from_point_response = Vec::new();
                         },
@@ -990,7 +1031,10 @@ impl LayoutThread {
                         &QueryMsg::StyleQuery(_) => {
                             rw_data.style_response = StyleResponse(None);
                         },
-                        &QueryMsg::TextIndexQuery(..) => {
+                        &QueryMsg::ScrollbarStyleQuery(_) => {
+                            rw_data.scrollbar_style_response = ScrollbarStyleResponse(None);
+                        },
+                        &QueryMsg::TextIndexQuery(..) | &QueryMsg::TextIndexAtPointQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
                         &QueryMsg::ElementInnerTextQuery(_) => {
@@ -1031,7 +1075,8 @@ impl LayoutThread {
         };
 
         let had_used_viewport_units = self.stylist.device().used_viewport_units();
-        let device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        let mut device = Device::new(MediaType::screen(), initial_viewport, device_pixel_ratio);
+        device.set_prefers_color_scheme(data.prefers_color_scheme);
         let sheet_origins_affected_by_device_change = self.stylist.set_device(device, &guards);
 
         self.stylist
@@ -1238,6 +1283,8 @@ impl LayoutThread {
             None => Vec::new(),
         };
         reflow_result.pending_images = pending_images;
+        reflow_result.pending_web_fonts = self.outstanding_web_fonts.load(Ordering::SeqCst) != 0;
+        reflow_result.layout_shifts = self.layout_shift_metrics.take_layout_shifts();
         match *reflow_goal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match querymsg {
                 &QueryMsg::ContentBoxQuery(node) => {
@@ -1247,6 +1294,9 @@ impl LayoutThread {
                 &QueryMsg::ContentBoxesQuery(node) => {
                     rw_data.content_boxes_response = process_content_boxes_request(node);
                 },
+                &QueryMsg::BoxQuadsQuery(node) => {
+                    rw_data.box_quads_response = process_box_quads_request(node);
+                },
                 &QueryMsg::TextIndexQuery(node, point_in_node) => {
                     let point_in_node = Point2D::new(
                         Au::from_f32_px(point_in_node.x),
@@ -1254,6 +1304,10 @@ impl LayoutThread {
                     );
                     rw_data.text_index_response = process_text_index_request(node, point_in_node);
                 },
+                &QueryMsg::TextIndexAtPointQuery(node, point) => {
+                    let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                    rw_data.text_index_response = process_text_index_request(node, point);
+                },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response = process_node_geometry_request(
                         node,
@@ -1280,6 +1334,10 @@ impl LayoutThread {
                     let node = unsafe { ServoLayoutNode::new(&node) };
                     rw_data.style_response = process_style_query(node);
                 },
+                &QueryMsg::ScrollbarStyleQuery(node) => {
+                    let node = unsafe { ServoLayoutNode::new(&node) };
+                    rw_data.scrollbar_style_response = process_scrollbar_style_query(node);
+                },
                 &QueryMsg::NodesFromPointQuery(client_point, ref reflow_goal) => {
                     let mut flags = match reflow_goal {
                         &NodesFromPointQueryType::Topmost => webrender_api::HitTestFlags::empty(),
@@ -1331,6 +1389,8 @@ impl LayoutThread {
             layout_scroll_states.insert(new_state.scroll_id, offset);
 
             if new_state.scroll_id.is_root() {
+                self.layout_shift_metrics
+                    .set_scroll_position((-offset).to_point().to_untyped());
                 script_scroll_states.push((UntrustedNodeAddress::from_id(0), offset))
             } else if let Some(node_id) = node_id_from_scroll_id(new_state.scroll_id.0 as usize) {
                 script_scroll_states.push((UntrustedNodeAddress::from_id(node_id), offset))
@@ -1439,6 +1499,12 @@ impl LayoutThread {
             self.viewport_size.width.to_f32_px(),
             self.viewport_size.height.to_f32_px(),
         ));
+        if pref!(dom.layout_instability.enabled) {
+            self.layout_shift_metrics.observe_frame(
+                process_node_border_boxes_request(&fragment_tree),
+                viewport_size.to_untyped(),
+            );
+        }
         self.webrender_api.send_display_list(
             self.webrender_document,
             epoch,
@@ -1517,6 +1583,11 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
             "presentational-hints.css",
             &resources::read_bytes(Resource::PresentationalHintsCSS),
         )?,
+        parse_ua_stylesheet(
+            &shared_lock,
+            "mathml.css",
+            &resources::read_bytes(Resource::MathMLCSS),
+        )?,
     ];
 
     for &(ref contents, ref url) in &opts::get().user_stylesheets {
@@ -1601,6 +1672,8 @@ impl Painter for RegisteredPainterImpl {
     }
 }
 
+impl RegisteredPainter for RegisteredPainterImpl {}
+
 struct RegisteredPaintersImpl(FnvHashMap<Atom, RegisteredPainterImpl>);
 
 impl RegisteredSpeculativePainters for RegisteredPaintersImpl {
@@ -1610,3 +1683,11 @@ impl RegisteredSpeculativePainters for RegisteredPaintersImpl {
             .map(|painter| painter as &dyn RegisteredSpeculativePainter)
     }
 }
+
+impl RegisteredPainters for RegisteredPaintersImpl {
+    fn get(&self, name: &Atom) -> Option<&dyn RegisteredPainter> {
+        self.0
+            .get(&name)
+            .map(|painter| painter as &dyn RegisteredPainter)
+    }
+}
@@ -7,12 +7,14 @@ extern crate log;
 #[macro_use]
 extern crate malloc_size_of_derive;
 
+use euclid::default::{Point2D, Rect, Size2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use profile_traits::time::TimerMetadata;
 use profile_traits::time::{send_profile_data, ProfilerCategory, ProfilerChan};
 use script_traits::{ConstellationControlMsg, LayoutMsg, ProgressiveWebMetricType};
+use script_traits::{LayoutShift, LayoutShiftSource, UntrustedNodeAddress};
 use servo_config::opts;
 use servo_url::ServoUrl;
 use std::cell::{Cell, RefCell};
@@ -341,17 +343,26 @@ impl PaintTimeMetrics {
             return;
         }
 
-        if let Some(pending_metric) = self.pending_metrics.borrow_mut().remove(&epoch) {
+        let mut pending_metrics = self.pending_metrics.borrow_mut();
+        let pending_metric = pending_metrics.remove(&epoch);
+        // Older frames that were never presented on their own won't be reported.
+        pending_metrics.retain(|pending_epoch, _| *pending_epoch > epoch);
+
+        if let Some(pending_metric) = pending_metric {
             let profiler_metadata = pending_metric.0;
-            set_metric(
-                self,
-                profiler_metadata.clone(),
-                ProgressiveWebMetricType::FirstPaint,
-                ProfilerCategory::TimeToFirstPaint,
-                &self.first_paint,
-                Some(paint_time),
-                &self.url,
-            );
+            // A non-contentful frame may have been presented already, in which
+            // case only the first contentful paint is left to set.
+            if self.first_paint.get().is_none() {
+                set_metric(
+                    self,
+                    profiler_metadata.clone(),
+                    ProgressiveWebMetricType::FirstPaint,
+                    ProfilerCategory::TimeToFirstPaint,
+                    &self.first_paint,
+                    Some(paint_time),
+                    &self.url,
+                );
+            }
 
             if pending_metric.1 {
                 set_metric(
@@ -400,3 +411,149 @@ impl ProgressiveWebMetric for PaintTimeMetrics {
         &self.url
     }
 }
+
+/// A layout shift is attributed to at most this many nodes.
+/// <https://wicg.github.io/layout-instability/#dom-layoutshift-sources>
+const MAX_LAYOUT_SHIFT_SOURCES: usize = 5;
+
+// https://wicg.github.io/layout-instability/
+pub struct LayoutShiftMetrics {
+    /// The border boxes of the nodes in the previous frame, in document coordinates.
+    previous_boxes: RefCell<HashMap<UntrustedNodeAddress, Rect<f32>>>,
+    /// The viewport of the previous frame, in document coordinates.
+    previous_viewport: Cell<Rect<f32>>,
+    /// The scroll position of the page.
+    scroll_position: Cell<Point2D<f32>>,
+    /// The layout shifts that were not sent to the script thread yet.
+    pending_shifts: RefCell<Vec<LayoutShift>>,
+}
+
+impl LayoutShiftMetrics {
+    pub fn new() -> LayoutShiftMetrics {
+        LayoutShiftMetrics {
+            previous_boxes: RefCell::new(HashMap::new()),
+            previous_viewport: Cell::new(Rect::zero()),
+            scroll_position: Cell::new(Point2D::zero()),
+            pending_shifts: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn set_scroll_position(&self, scroll_position: Point2D<f32>) {
+        self.scroll_position.set(scroll_position);
+    }
+
+    /// Compares the border boxes of the nodes in a frame that is about to be
+    /// painted with the ones in the previous frame, and records the layout
+    /// shift of the frame if any of the nodes moved.
+    /// <https://wicg.github.io/layout-instability/#sec-layout-shift>
+    pub fn observe_frame(
+        &self,
+        boxes: HashMap<UntrustedNodeAddress, Rect<f32>>,
+        viewport_size: Size2D<f32>,
+    ) {
+        let viewport = Rect::new(self.scroll_position.get(), viewport_size);
+        let previous_boxes = self.previous_boxes.replace(boxes);
+        let previous_viewport = self.previous_viewport.replace(viewport);
+        let boxes = self.previous_boxes.borrow();
+
+        let mut impact_region = vec![];
+        let mut sources = vec![];
+        let mut max_distance: f32 = 0.;
+        for (node, rect) in boxes.iter() {
+            // Nodes that were just inserted don't shift.
+            let previous_rect = match previous_boxes.get(node) {
+                Some(previous_rect) => previous_rect,
+                None => continue,
+            };
+            // Only the starting points of boxes are compared, so resizing a
+            // node doesn't shift it.
+            if previous_rect.origin == rect.origin {
+                continue;
+            }
+            let previous_visible_rect = visible_rect(previous_rect, &previous_viewport);
+            let current_visible_rect = visible_rect(rect, &viewport);
+            if previous_visible_rect.is_none() && current_visible_rect.is_none() {
+                continue;
+            }
+            let distance = rect.origin - previous_rect.origin;
+            max_distance = max_distance.max(distance.x.abs()).max(distance.y.abs());
+            impact_region.extend(previous_visible_rect);
+            impact_region.extend(current_visible_rect);
+            sources.push(LayoutShiftSource {
+                node: *node,
+                previous_rect: previous_visible_rect.unwrap_or_else(Rect::zero),
+                current_rect: current_visible_rect.unwrap_or_else(Rect::zero),
+            });
+        }
+
+        let viewport_area = viewport.size.area();
+        if impact_region.is_empty() || viewport_area <= 0. {
+            return;
+        }
+        let impact_fraction = union_area(&impact_region) / viewport_area;
+        let distance_fraction =
+            (max_distance / viewport.size.width.max(viewport.size.height)).min(1.);
+
+        // The sources of the shift are the nodes whose impact regions are
+        // the largest.
+        let source_area =
+            |source: &LayoutShiftSource| union_area(&[source.previous_rect, source.current_rect]);
+        sources.sort_by(|a, b| {
+            source_area(b)
+                .partial_cmp(&source_area(a))
+                .unwrap_or(Ordering::Equal)
+        });
+        sources.truncate(MAX_LAYOUT_SHIFT_SOURCES);
+
+        self.pending_shifts.borrow_mut().push(LayoutShift {
+            time: precise_time_ns(),
+            score: (impact_fraction * distance_fraction) as f64,
+            sources,
+        });
+    }
+
+    /// Returns the layout shifts observed since the last call.
+    pub fn take_layout_shifts(&self) -> Vec<LayoutShift> {
+        std::mem::replace(&mut *self.pending_shifts.borrow_mut(), vec![])
+    }
+}
+
+/// Returns the part of `rect` that is in `viewport`, relative to the viewport.
+fn visible_rect(rect: &Rect<f32>, viewport: &Rect<f32>) -> Option<Rect<f32>> {
+    rect.intersection(viewport)
+        .filter(|rect| !rect.is_empty_or_negative())
+        .map(|rect| rect.translate(-viewport.origin.to_vector()))
+}
+
+/// Returns the area of the union of `rects`, by summing the heights covered
+/// by the rects in each of the vertical slabs their edges delimit.
+fn union_area(rects: &[Rect<f32>]) -> f32 {
+    let mut edges: Vec<f32> = rects
+        .iter()
+        .flat_map(|rect| vec![rect.min_x(), rect.max_x()])
+        .collect();
+    edges.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    edges.dedup();
+
+    let mut area = 0.;
+    for slab in edges.windows(2) {
+        let (left, right) = (slab[0], slab[1]);
+        let mut spans: Vec<(f32, f32)> = rects
+            .iter()
+            .filter(|rect| rect.min_x() <= left && rect.max_x() >= right)
+            .map(|rect| (rect.min_y(), rect.max_y()))
+            .collect();
+        spans.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        let mut height = 0.;
+        let mut covered_bottom = std::f32::NEG_INFINITY;
+        for (top, bottom) in spans {
+            let top = top.max(covered_bottom);
+            if bottom > top {
+                height += bottom - top;
+                covered_bottom = bottom;
+            }
+        }
+        area += height * (right - left);
+    }
+    area
+}
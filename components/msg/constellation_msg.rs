@@ -543,6 +543,7 @@ pub enum LayoutHangAnnotation {
     SetScrollStates,
     UpdateScrollStateFromScript,
     RegisterPaint,
+    RegisterProperty,
     SetNavigationStart,
     GetRunningAnimations,
 }
@@ -0,0 +1,228 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `Sec-CH-UA` request headers of the User-Agent Client Hints, and the
+//! `Accept-CH` and `Critical-CH` response headers with which origins ask for
+//! the high-entropy ones. The hints an origin asks for are remembered for the
+//! rest of the session.
+//! <https://wicg.github.io/ua-client-hints/>
+
+use crate::reporting::is_potentially_trustworthy;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use net_traits::request::{Destination, Origin, Request};
+use net_traits::user_agent_data::{BrandVersion, UserAgentMetadata, UserAgentOverride};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClientHint {
+    Ua,
+    UaArch,
+    UaBitness,
+    UaFullVersion,
+    UaFullVersionList,
+    UaMobile,
+    UaModel,
+    UaPlatform,
+    UaPlatformVersion,
+    UaWow64,
+}
+
+/// The hints that are sent to every potentially trustworthy URL.
+/// <https://wicg.github.io/ua-client-hints/#low-entropy-hint-table>
+const LOW_ENTROPY_HINTS: [ClientHint; 3] =
+    [ClientHint::Ua, ClientHint::UaMobile, ClientHint::UaPlatform];
+
+impl ClientHint {
+    fn from_token(token: &str) -> Option<ClientHint> {
+        Some(match &*token.to_ascii_lowercase() {
+            "sec-ch-ua" => ClientHint::Ua,
+            "sec-ch-ua-arch" => ClientHint::UaArch,
+            "sec-ch-ua-bitness" => ClientHint::UaBitness,
+            "sec-ch-ua-full-version" => ClientHint::UaFullVersion,
+            "sec-ch-ua-full-version-list" => ClientHint::UaFullVersionList,
+            "sec-ch-ua-mobile" => ClientHint::UaMobile,
+            "sec-ch-ua-model" => ClientHint::UaModel,
+            "sec-ch-ua-platform" => ClientHint::UaPlatform,
+            "sec-ch-ua-platform-version" => ClientHint::UaPlatformVersion,
+            "sec-ch-ua-wow64" => ClientHint::UaWow64,
+            _ => return None,
+        })
+    }
+
+    fn header_name(&self) -> &'static str {
+        match *self {
+            ClientHint::Ua => "sec-ch-ua",
+            ClientHint::UaArch => "sec-ch-ua-arch",
+            ClientHint::UaBitness => "sec-ch-ua-bitness",
+            ClientHint::UaFullVersion => "sec-ch-ua-full-version",
+            ClientHint::UaFullVersionList => "sec-ch-ua-full-version-list",
+            ClientHint::UaMobile => "sec-ch-ua-mobile",
+            ClientHint::UaModel => "sec-ch-ua-model",
+            ClientHint::UaPlatform => "sec-ch-ua-platform",
+            ClientHint::UaPlatformVersion => "sec-ch-ua-platform-version",
+            ClientHint::UaWow64 => "sec-ch-ua-wow64",
+        }
+    }
+
+    /// The value of the header of the hint, as a structured header.
+    fn header_value(&self, metadata: &UserAgentMetadata) -> String {
+        match *self {
+            ClientHint::Ua => brand_list(&metadata.brands),
+            ClientHint::UaArch => sf_string(&metadata.architecture),
+            ClientHint::UaBitness => sf_string(&metadata.bitness),
+            ClientHint::UaFullVersion => sf_string(&metadata.full_version),
+            ClientHint::UaFullVersionList => brand_list(&metadata.full_version_list),
+            ClientHint::UaMobile => sf_boolean(metadata.mobile),
+            ClientHint::UaModel => sf_string(&metadata.model),
+            ClientHint::UaPlatform => sf_string(&metadata.platform),
+            ClientHint::UaPlatformVersion => sf_string(&metadata.platform_version),
+            ClientHint::UaWow64 => sf_boolean(metadata.wow64),
+        }
+    }
+}
+
+fn sf_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn sf_boolean(value: bool) -> String {
+    String::from(if value { "?1" } else { "?0" })
+}
+
+fn brand_list(brands: &[BrandVersion]) -> String {
+    brands
+        .iter()
+        .map(|brand| {
+            format!(
+                "{};v={}",
+                sf_string(&brand.brand),
+                sf_string(&brand.version)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The hints listed in a header like `Accept-CH`.
+fn parse_hints(headers: &HeaderMap, name: &str) -> Vec<ClientHint> {
+    let mut hints = vec![];
+    for value in headers.get_all(name).iter() {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        for hint in value
+            .split(',')
+            .filter_map(|token| ClientHint::from_token(token.trim()))
+        {
+            if !hints.contains(&hint) {
+                hints.push(hint);
+            }
+        }
+    }
+    hints
+}
+
+/// The hints that each origin asked for with `Accept-CH`.
+#[derive(Clone, Debug, Default)]
+pub struct ClientHintsCache {
+    hints: HashMap<ImmutableOrigin, Vec<ClientHint>>,
+}
+
+impl ClientHintsCache {
+    pub fn new() -> ClientHintsCache {
+        Default::default()
+    }
+
+    /// Remembers the hints that the `Accept-CH` header of a navigation
+    /// response from `url` asks for, which replace the previous ones of its
+    /// origin.
+    /// <https://wicg.github.io/client-hints-infrastructure/#accept-ch-cache-update>
+    pub fn update_from_response(
+        &mut self,
+        url: &ServoUrl,
+        destination: Destination,
+        headers: &HeaderMap,
+    ) {
+        if destination != Destination::Document ||
+            !is_potentially_trustworthy(url) ||
+            !headers.contains_key("accept-ch")
+        {
+            return;
+        }
+        let hints = parse_hints(headers, "accept-ch");
+        if hints.is_empty() {
+            self.hints.remove(&url.origin());
+        } else {
+            self.hints.insert(url.origin(), hints);
+        }
+    }
+
+    fn accepted_hints(&self, origin: &ImmutableOrigin) -> &[ClientHint] {
+        self.hints.get(origin).map_or(&[], |hints| &hints[..])
+    }
+}
+
+/// The origin of which the accepted hints are sent with a request: the one
+/// of the URL for navigations, and the one of the document that made the
+/// request for the same-origin subresources. Other origins don't get the
+/// high-entropy hints, since they can't be delegated to them yet.
+fn hints_origin(request: &Request) -> Option<ImmutableOrigin> {
+    let url_origin = request.current_url().origin();
+    if request.destination == Destination::Document {
+        return Some(url_origin);
+    }
+    match request.origin {
+        Origin::Origin(ref origin) if *origin == url_origin => Some(url_origin),
+        _ => None,
+    }
+}
+
+/// Adds the headers of the low-entropy hints, and of the hints that the
+/// origin of the request asked for, to a request to a potentially
+/// trustworthy URL. Their values are the ones of the user agent override of
+/// the webview of the request, if any.
+/// <https://wicg.github.io/client-hints-infrastructure/#abstract-opdef-append-client-hints-to-request>
+pub fn set_client_hints_headers(
+    request: &mut Request,
+    cache: &ClientHintsCache,
+    ua_override: Option<&UserAgentOverride>,
+) {
+    if !pref!(network.client_hints.enabled) || !is_potentially_trustworthy(&request.current_url()) {
+        return;
+    }
+    let metadata = UserAgentMetadata::overridden_by(ua_override);
+    let origin = hints_origin(request);
+    let accepted = origin
+        .as_ref()
+        .map_or(&[][..], |origin| cache.accepted_hints(origin));
+    for hint in LOW_ENTROPY_HINTS.iter().chain(accepted) {
+        let name = HeaderName::from_static(hint.header_name());
+        if request.headers.contains_key(&name) {
+            continue;
+        }
+        if let Ok(value) = HeaderValue::from_str(&hint.header_value(&metadata)) {
+            request.headers.insert(name, value);
+        }
+    }
+}
+
+/// Whether a navigation has to be retried because its response lists hints
+/// in `Critical-CH` that its origin now accepts but that the request didn't
+/// have.
+/// <https://wicg.github.io/client-hints-infrastructure/#critical-ch>
+pub fn should_retry_with_critical_hints(
+    request: &Request,
+    headers: &HeaderMap,
+    cache: &ClientHintsCache,
+) -> bool {
+    if !pref!(network.client_hints.enabled) || request.destination != Destination::Document {
+        return false;
+    }
+    let accepted = cache.accepted_hints(&request.current_url().origin());
+    parse_hints(headers, "critical-ch")
+        .iter()
+        .any(|hint| accepted.contains(hint) && !request.headers.contains_key(hint.header_name()))
+}
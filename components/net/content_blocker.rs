@@ -0,0 +1,325 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Blocking of the requests to trackers and advertisers, with the rules of
+//! a list in the Adblock Plus filter format.
+//! <https://help.eyeo.com/adblockplus/how-to-write-filters>
+
+use embedder_traits::resources::{self, Resource};
+use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
+use net_traits::pub_domains::reg_suffix;
+use net_traits::request::{Destination, Origin, Request};
+use servo_url::ServoUrl;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+/// The types of requests that the options of a rule can restrict it to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RequestType {
+    Script,
+    Image,
+    Stylesheet,
+    Font,
+    Media,
+    Object,
+    XmlHttpRequest,
+    Other,
+}
+
+impl RequestType {
+    fn from_option(option: &str) -> Option<RequestType> {
+        Some(match option {
+            "script" => RequestType::Script,
+            "image" => RequestType::Image,
+            "stylesheet" => RequestType::Stylesheet,
+            "font" => RequestType::Font,
+            "media" => RequestType::Media,
+            "object" => RequestType::Object,
+            "xmlhttprequest" => RequestType::XmlHttpRequest,
+            "other" => RequestType::Other,
+            _ => return None,
+        })
+    }
+
+    /// The type of a request, or `None` for the navigations, which are
+    /// never blocked.
+    pub fn from_destination(destination: Destination) -> Option<RequestType> {
+        Some(match destination {
+            Destination::Document => return None,
+            Destination::Script |
+            Destination::Worker |
+            Destination::SharedWorker |
+            Destination::ServiceWorker |
+            Destination::AudioWorklet |
+            Destination::PaintWorklet |
+            Destination::Xslt => RequestType::Script,
+            Destination::Image => RequestType::Image,
+            Destination::Style => RequestType::Stylesheet,
+            Destination::Font => RequestType::Font,
+            Destination::Audio | Destination::Video | Destination::Track => RequestType::Media,
+            Destination::Object | Destination::Embed => RequestType::Object,
+            Destination::None => RequestType::XmlHttpRequest,
+            _ => RequestType::Other,
+        })
+    }
+}
+
+/// Where the pattern of a rule has to match in a URL.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Anchor {
+    /// Anywhere in the URL.
+    None,
+    /// At the start of the URL, for the patterns starting with `|`.
+    Start,
+    /// At the start of the host or of one of its subdomains, for the
+    /// patterns starting with `||`.
+    Host,
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    /// The lowercase pattern, without its anchors.
+    pattern: Vec<u8>,
+    anchor: Anchor,
+    /// Whether the pattern has to match at the end of the URL.
+    end_anchor: bool,
+    /// Whether the rule allows the requests that other rules block.
+    exception: bool,
+    /// Whether the rule only applies to third-party or first-party
+    /// requests, if it is restricted to either.
+    third_party: Option<bool>,
+    /// The types of requests the rule applies to.
+    included_types: Vec<RequestType>,
+    /// The types of requests the rule doesn't apply to.
+    excluded_types: Vec<RequestType>,
+}
+
+impl Rule {
+    /// Parses a line of a list, which is `None` for the comments, the
+    /// element hiding rules and the rules with unsupported options.
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
+            return None;
+        }
+        if line.contains("##") || line.contains("#@#") || line.contains("#?#") {
+            return None;
+        }
+
+        let (exception, line) = if line.starts_with("@@") {
+            (true, &line[2..])
+        } else {
+            (false, line)
+        };
+
+        let mut rule = Rule {
+            pattern: vec![],
+            anchor: Anchor::None,
+            end_anchor: false,
+            exception,
+            third_party: None,
+            included_types: vec![],
+            excluded_types: vec![],
+        };
+
+        let pattern = match line.rfind('$') {
+            Some(index) => {
+                for option in line[index + 1..].split(',') {
+                    let option = option.trim().to_ascii_lowercase();
+                    let (negated, option) = if option.starts_with('~') {
+                        (true, &option[1..])
+                    } else {
+                        (false, &*option)
+                    };
+                    if option == "third-party" {
+                        rule.third_party = Some(!negated);
+                        continue;
+                    }
+                    let request_type = RequestType::from_option(option)?;
+                    if negated {
+                        rule.excluded_types.push(request_type);
+                    } else {
+                        rule.included_types.push(request_type);
+                    }
+                }
+                &line[..index]
+            },
+            None => line,
+        };
+
+        let pattern = if pattern.starts_with("||") {
+            rule.anchor = Anchor::Host;
+            &pattern[2..]
+        } else if pattern.starts_with('|') {
+            rule.anchor = Anchor::Start;
+            &pattern[1..]
+        } else {
+            pattern
+        };
+        let pattern = if pattern.ends_with('|') {
+            rule.end_anchor = true;
+            &pattern[..pattern.len() - 1]
+        } else {
+            pattern
+        };
+        if pattern.is_empty() && rule.anchor == Anchor::None {
+            return None;
+        }
+        rule.pattern = pattern.to_ascii_lowercase().into_bytes();
+        Some(rule)
+    }
+
+    fn applies_to(&self, request_type: RequestType, third_party: bool) -> bool {
+        if self.third_party.map_or(false, |value| value != third_party) {
+            return false;
+        }
+        if self.excluded_types.contains(&request_type) {
+            return false;
+        }
+        self.included_types.is_empty() || self.included_types.contains(&request_type)
+    }
+
+    /// Whether the pattern matches the lowercase `url`, of which the host
+    /// starts at `host_start` and ends at `host_end`.
+    fn matches(&self, url: &[u8], host_start: usize, host_end: usize) -> bool {
+        match self.anchor {
+            Anchor::Start => matches_at(&self.pattern, url, self.end_anchor),
+            Anchor::Host => (host_start..host_end)
+                .filter(|&index| index == host_start || url[index - 1] == b'.')
+                .any(|index| matches_at(&self.pattern, &url[index..], self.end_anchor)),
+            Anchor::None => (0..url.len())
+                .any(|index| matches_at(&self.pattern, &url[index..], self.end_anchor)),
+        }
+    }
+}
+
+/// Whether a character matches the `^` separator placeholder.
+fn is_separator(character: u8) -> bool {
+    !(character.is_ascii_alphanumeric() ||
+        character == b'_' ||
+        character == b'-' ||
+        character == b'.' ||
+        character == b'%')
+}
+
+/// Whether `pattern` matches the start of `text`, or the whole of it when
+/// `to_end` is true.
+fn matches_at(pattern: &[u8], text: &[u8], to_end: bool) -> bool {
+    match pattern.split_first() {
+        None => !to_end || text.is_empty(),
+        Some((b'*', rest)) => {
+            (0..=text.len()).any(|index| matches_at(rest, &text[index..], to_end))
+        },
+        // The separator placeholder also matches the end of the URL.
+        Some((b'^', rest)) => match text.split_first() {
+            None => rest
+                .iter()
+                .all(|&character| character == b'*' || character == b'^'),
+            Some((&character, text)) => is_separator(character) && matches_at(rest, text, to_end),
+        },
+        Some((&expected, rest)) => match text.split_first() {
+            Some((&character, text)) => character == expected && matches_at(rest, text, to_end),
+            None => false,
+        },
+    }
+}
+
+/// Whether the host of `url` is in another site than the origin of the
+/// document that made the request.
+fn is_third_party(url: &ServoUrl, origin: &Origin) -> bool {
+    let origin_host = match *origin {
+        Origin::Origin(ref origin) => match origin.host() {
+            Some(host) => host.to_string(),
+            None => return true,
+        },
+        Origin::Client => return false,
+    };
+    match url.host_str() {
+        Some(host) => reg_suffix(host) != reg_suffix(&origin_host),
+        None => false,
+    }
+}
+
+pub struct ContentBlocker {
+    rules: Vec<Rule>,
+    /// The pipelines of which the requests are filtered, with the webview
+    /// their blocked requests are reported to.
+    pipelines: RwLock<HashMap<PipelineId, TopLevelBrowsingContextId>>,
+    /// The number of requests blocked in each webview.
+    blocked_requests: Mutex<HashMap<TopLevelBrowsingContextId, u32>>,
+}
+
+impl ContentBlocker {
+    /// Creates a content blocker with the rules of a list.
+    pub fn new(list: &str) -> ContentBlocker {
+        ContentBlocker {
+            rules: list.lines().filter_map(Rule::parse).collect(),
+            pipelines: RwLock::new(HashMap::new()),
+            blocked_requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Creates a content blocker with the list shipped with Servo.
+    pub fn from_servo_list() -> ContentBlocker {
+        ContentBlocker::new(&resources::read_string(Resource::ContentBlockingList))
+    }
+
+    /// Starts filtering the requests of a pipeline, or stops it when
+    /// `webview` is `None`.
+    pub fn set_pipeline_webview(
+        &self,
+        pipeline_id: PipelineId,
+        webview: Option<TopLevelBrowsingContextId>,
+    ) {
+        let mut pipelines = self.pipelines.write().unwrap();
+        match webview {
+            Some(webview) => pipelines.insert(pipeline_id, webview),
+            None => pipelines.remove(&pipeline_id),
+        };
+    }
+
+    /// Whether the rules block a request to `url` of the given type.
+    pub fn matches(&self, url: &ServoUrl, request_type: RequestType, third_party: bool) -> bool {
+        let host_range = match url.as_url().host_str() {
+            Some(host) => {
+                let start = url.as_str().find(host).unwrap_or(0);
+                (start, start + host.len())
+            },
+            None => (0, 0),
+        };
+        let url = url.as_str().to_ascii_lowercase().into_bytes();
+        let matching_rules = self
+            .rules
+            .iter()
+            .filter(|rule| rule.applies_to(request_type, third_party))
+            .filter(|rule| rule.matches(&url, host_range.0, host_range.1));
+        let mut blocked = false;
+        for rule in matching_rules {
+            if rule.exception {
+                return false;
+            }
+            blocked = true;
+        }
+        blocked
+    }
+
+    /// Whether a request has to be blocked, in which case the number of
+    /// blocked requests of its webview is returned with the webview.
+    pub fn should_block(&self, request: &Request) -> Option<(TopLevelBrowsingContextId, u32)> {
+        if !pref!(network.content_blocking.enabled) {
+            return None;
+        }
+        let webview = *self.pipelines.read().unwrap().get(&request.pipeline_id?)?;
+        let request_type = RequestType::from_destination(request.destination)?;
+        let url = request.current_url();
+        let third_party = is_third_party(&url, &request.origin);
+        if !self.matches(&url, request_type, third_party) {
+            return None;
+        }
+        let mut blocked_requests = self.blocked_requests.lock().unwrap();
+        let count = blocked_requests.entry(webview).or_insert(0);
+        *count += 1;
+        Some((webview, *count))
+    }
+}
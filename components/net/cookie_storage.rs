@@ -6,8 +6,11 @@
 //! http://tools.ietf.org/html/rfc6265
 
 use crate::cookie::Cookie;
+use hyper_serde::Serde;
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::PipelineId;
 use net_traits::pub_domains::reg_suffix;
-use net_traits::CookieSource;
+use net_traits::{CookieChange, CookieSource};
 use servo_url::ServoUrl;
 use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
@@ -19,6 +22,10 @@ pub struct CookieStorage {
     version: u32,
     cookies_map: HashMap<String, Vec<Cookie>>,
     max_per_host: usize,
+    /// The listeners to the changes of the cookies visible to the scripts
+    /// of a URL, by the pipeline they belong to, see `notify_change`.
+    #[serde(skip)]
+    observers: Vec<(PipelineId, ServoUrl, IpcSender<CookieChange>)>,
 }
 
 impl CookieStorage {
@@ -27,15 +34,48 @@ impl CookieStorage {
             version: 1,
             cookies_map: HashMap::new(),
             max_per_host: max_cookies,
+            observers: vec![],
         }
     }
 
+    /// Report the changes to the cookies visible to non-HTTP APIs at `url`
+    /// to `sender`, until `pipeline_id` stops observing them or `sender` is
+    /// closed.
+    pub fn observe_changes(
+        &mut self,
+        pipeline_id: PipelineId,
+        url: ServoUrl,
+        sender: IpcSender<CookieChange>,
+    ) {
+        self.observers.push((pipeline_id, url, sender));
+    }
+
+    /// Stop reporting the changes to the cookies to `pipeline_id`.
+    pub fn stop_observing_changes(&mut self, pipeline_id: PipelineId) {
+        self.observers.retain(|&(id, ..)| id != pipeline_id);
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.3
     pub fn remove(
         &mut self,
         cookie: &Cookie,
         url: &ServoUrl,
         source: CookieSource,
+    ) -> Result<Option<Cookie>, ()> {
+        let removed = self.take(cookie, url, source);
+        if let Ok(Some(ref removed)) = removed {
+            notify_change(&mut self.observers, removed, true);
+        }
+        removed
+    }
+
+    /// Remove the cookie `cookie` would replace, without telling the
+    /// observers, which `push` tells about the replacement instead.
+    fn take(
+        &mut self,
+        cookie: &Cookie,
+        url: &ServoUrl,
+        source: CookieSource,
     ) -> Result<Option<Cookie>, ()> {
         let domain = reg_host(cookie.cookie.domain().as_ref().unwrap_or(&""));
         let cookies = self.cookies_map.entry(domain).or_insert(vec![]);
@@ -86,9 +126,10 @@ impl CookieStorage {
     }
     pub fn clear_storage(&mut self, url: &ServoUrl) {
         let domain = reg_host(url.host_str().unwrap_or(""));
-        let cookies = self.cookies_map.entry(domain).or_insert(vec![]);
-        for cookie in cookies.iter_mut() {
-            cookie.set_expiry_time_negative();
+        if let Some(cookies) = self.cookies_map.remove(&domain) {
+            for cookie in &cookies {
+                notify_change(&mut self.observers, cookie, true);
+            }
         }
     }
 
@@ -99,34 +140,46 @@ impl CookieStorage {
             return;
         }
 
-        let old_cookie = self.remove(&cookie, url, source);
+        let old_cookie = self.take(&cookie, url, source);
         if old_cookie.is_err() {
             // This new cookie is not allowed to overwrite an existing one.
             return;
         }
 
         // Step 11
-        if let Some(old_cookie) = old_cookie.unwrap() {
+        let old_cookie = old_cookie.unwrap();
+        if let Some(ref old_cookie) = old_cookie {
             // Step 11.3
             cookie.creation_time = old_cookie.creation_time;
         }
 
+        // An expired cookie only removes the one it replaces.
+        if is_cookie_expired(&cookie) {
+            if let Some(ref old_cookie) = old_cookie {
+                notify_change(&mut self.observers, old_cookie, true);
+            }
+            return;
+        }
+
         // Step 12
         let domain = reg_host(&cookie.cookie.domain().as_ref().unwrap_or(&""));
         let cookies = self.cookies_map.entry(domain).or_insert(vec![]);
 
         if cookies.len() == self.max_per_host {
-            let old_len = cookies.len();
-            cookies.retain(|c| !is_cookie_expired(&c));
-            let new_len = cookies.len();
+            let expired = remove_expired(cookies);
+            for expired_cookie in &expired {
+                notify_change(&mut self.observers, expired_cookie, true);
+            }
 
             // https://www.ietf.org/id/draft-ietf-httpbis-cookie-alone-01.txt
-            if new_len == old_len &&
-                !evict_one_cookie(cookie.cookie.secure().unwrap_or(false), cookies)
-            {
-                return;
+            if expired.is_empty() {
+                match evict_one_cookie(cookie.cookie.secure().unwrap_or(false), cookies) {
+                    Some(evicted) => notify_change(&mut self.observers, &evicted, true),
+                    None => return,
+                }
             }
         }
+        notify_change(&mut self.observers, &cookie, false);
         cookies.push(cookie);
     }
 
@@ -149,7 +202,9 @@ impl CookieStorage {
         let domain = reg_host(url.host_str().unwrap_or(""));
         if let Entry::Occupied(mut entry) = self.cookies_map.entry(domain) {
             let cookies = entry.get_mut();
-            cookies.retain(|c| !is_cookie_expired(&c));
+            for expired_cookie in &remove_expired(cookies) {
+                notify_change(&mut self.observers, expired_cookie, true);
+            }
             if cookies.len() == 0 {
                 entry.remove_entry();
             }
@@ -223,6 +278,26 @@ fn reg_host<'a>(url: &'a str) -> String {
     reg_suffix(url).to_lowercase()
 }
 
+/// Tell the observers that `cookie` was stored, or removed if `deleted`.
+fn notify_change(
+    observers: &mut Vec<(PipelineId, ServoUrl, IpcSender<CookieChange>)>,
+    cookie: &Cookie,
+    deleted: bool,
+) {
+    observers.retain(|&(_, ref url, ref sender)| {
+        if !cookie.appropriate_for_url(url, CookieSource::NonHTTP) {
+            return true;
+        }
+        let data = Serde(cookie.cookie.clone());
+        let change = if deleted {
+            CookieChange::Deleted(data)
+        } else {
+            CookieChange::Changed(data)
+        };
+        sender.send(change).is_ok()
+    });
+}
+
 fn is_cookie_expired(cookie: &Cookie) -> bool {
     match cookie.expiry_time {
         Some(ref t) => t.to_timespec() <= time::get_time(),
@@ -230,23 +305,30 @@ fn is_cookie_expired(cookie: &Cookie) -> bool {
     }
 }
 
-fn evict_one_cookie(is_secure_cookie: bool, cookies: &mut Vec<Cookie>) -> bool {
+/// Remove the expired cookies of `cookies`, returning them.
+fn remove_expired(cookies: &mut Vec<Cookie>) -> Vec<Cookie> {
+    let (expired, alive): (Vec<Cookie>, Vec<Cookie>) =
+        cookies.drain(..).partition(is_cookie_expired);
+    *cookies = alive;
+    expired
+}
+
+/// Remove the cookie that was accessed the longest time ago to make room for
+/// a new cookie, returning it. Returns `None` if no cookie can be removed.
+fn evict_one_cookie(is_secure_cookie: bool, cookies: &mut Vec<Cookie>) -> Option<Cookie> {
     // Remove non-secure cookie with oldest access time
     let oldest_accessed: Option<(usize, Tm)> = get_oldest_accessed(false, cookies);
 
     if let Some((index, _)) = oldest_accessed {
-        cookies.remove(index);
+        Some(cookies.remove(index))
     } else {
         // All secure cookies were found
         if !is_secure_cookie {
-            return false;
+            return None;
         }
         let oldest_accessed: Option<(usize, Tm)> = get_oldest_accessed(true, cookies);
-        if let Some((index, _)) = oldest_accessed {
-            cookies.remove(index);
-        }
+        oldest_accessed.map(|(index, _)| cookies.remove(index))
     }
-    return true;
 }
 
 fn get_oldest_accessed(is_secure_cookie: bool, cookies: &mut Vec<Cookie>) -> Option<(usize, Tm)> {
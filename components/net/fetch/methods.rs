@@ -7,10 +7,13 @@ use crate::fetch::cors_cache::CorsCache;
 use crate::filemanager_thread::{FileManager, FILE_CHUNK_SIZE};
 use crate::http_loader::{determine_request_referrer, http_fetch, HttpState};
 use crate::http_loader::{set_default_accept, set_default_accept_language};
+use crate::network_conditions::Throttle;
 use crate::subresource_integrity::is_response_integrity_valid;
 use content_security_policy as csp;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use devtools_traits::DevtoolsControlMsg;
+use embedder_traits::resources::{self, Resource};
+use embedder_traits::{EmbedderMsg, EmbedderProxy};
 use headers::{AccessControlExposeHeaders, ContentType, HeaderMapExt, Range};
 use http::header::{self, HeaderMap, HeaderName};
 use hyper::Method;
@@ -57,6 +60,7 @@ pub struct FetchContext {
     pub devtools_chan: Option<Sender<DevtoolsControlMsg>>,
     pub filemanager: FileManager,
     pub file_token: FileTokenCheck,
+    pub embedder_proxy: EmbedderProxy,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
     pub timing: ServoArc<Mutex<ResourceFetchTiming>>,
 }
@@ -134,8 +138,8 @@ pub fn fetch_with_cors_cache(
     // Step 4.
     set_default_accept_language(&mut request.headers);
 
-    // Step 5.
-    // TODO: figure out what a Priority object is.
+    // Step 5. The priority is used when scheduling the fetch, see
+    // CoreResourceManager::fetch.
 
     // Step 6.
     // TODO: handle client hints headers.
@@ -145,8 +149,27 @@ pub fn fetch_with_cors_cache(
         // TODO: handle client hints headers.
     }
 
+    // The body of a keepalive request counts towards the limit of its fetch
+    // group until the fetch is done, see http_network_or_cache_fetch.
+    let keepalive_bytes = if request.keep_alive {
+        request.body.as_ref().map(|body| body.len() as u64)
+    } else {
+        None
+    };
+    if let Some(bytes) = keepalive_bytes {
+        context
+            .state
+            .begin_keepalive_request(request.pipeline_id, bytes);
+    }
+
     // Step 8.
     main_fetch(request, cache, false, false, target, &mut None, &context);
+
+    if let Some(bytes) = keepalive_bytes {
+        context
+            .state
+            .end_keepalive_request(request.pipeline_id, bytes);
+    }
 }
 
 /// https://www.w3.org/TR/CSP/#should-block-request
@@ -208,6 +231,18 @@ pub fn main_fetch(
         )))
     }
 
+    // Requests to trackers and advertisers fail like any network error, so
+    // that pages can't tell them apart from the other failed requests.
+    if let Some((webview, count)) = context.state.content_blocker.should_block(request) {
+        context.embedder_proxy.send((
+            Some(webview),
+            EmbedderMsg::ContentBlocked(request.current_url(), count),
+        ));
+        response = Some(Response::network_error(NetworkError::Internal(
+            "Blocked by the content blocker".into(),
+        )))
+    }
+
     // Step 3.
     // TODO: handle request abort.
 
@@ -440,11 +475,17 @@ pub fn main_fetch(
         response
     };
 
+    // The response bodies of the webview of the request may be throttled.
+    let download_throughput = context
+        .state
+        .network_conditions(request.pipeline_id)
+        .and_then(|conditions| conditions.download_throughput);
+
     // Step 19.
     let mut response_loaded = false;
     let mut response = if !response.is_network_error() && !request.integrity_metadata.is_empty() {
         // Step 19.1.
-        wait_for_response(&mut response, target, done_chan);
+        wait_for_response(&mut response, target, done_chan, download_throughput);
         response_loaded = true;
 
         // Step 19.2.
@@ -468,9 +509,10 @@ pub fn main_fetch(
         // by sync fetch, but we overload it here for simplicity
         target.process_response(&mut response);
         if !response_loaded {
-            wait_for_response(&mut response, target, done_chan);
+            wait_for_response(&mut response, target, done_chan, download_throughput);
         }
         // overloaded similarly to process_response
+        update_resource_timing(&response, context);
         target.process_response_eof(&response);
         return response;
     }
@@ -490,10 +532,11 @@ pub fn main_fetch(
 
     // Step 23.
     if !response_loaded {
-        wait_for_response(&mut response, target, done_chan);
+        wait_for_response(&mut response, target, done_chan, download_throughput);
     }
 
     // Step 24.
+    update_resource_timing(&response, context);
     target.process_response_eof(&response);
 
     if let Ok(http_cache) = context.state.http_cache.write() {
@@ -505,8 +548,23 @@ pub fn main_fetch(
     response
 }
 
-fn wait_for_response(response: &mut Response, target: Target, done_chan: &mut DoneChannel) {
+/// The timing of a network response is copied when its headers arrive,
+/// so the rest of the fetch is brought over before it is reported.
+fn update_resource_timing(response: &Response, context: &FetchContext) {
+    if response.is_network_error() {
+        return;
+    }
+    *response.get_resource_timing().lock().unwrap() = context.timing.lock().unwrap().clone();
+}
+
+fn wait_for_response(
+    response: &mut Response,
+    target: Target,
+    done_chan: &mut DoneChannel,
+    download_throughput: Option<u64>,
+) {
     if let Some(ref ch) = *done_chan {
+        let mut throttle = download_throughput.map(Throttle::new);
         loop {
             match ch
                 .1
@@ -514,6 +572,9 @@ fn wait_for_response(response: &mut Response, target: Target, done_chan: &mut Do
                 .expect("fetch worker should always send Done before terminating")
             {
                 Data::Payload(vec) => {
+                    if let Some(ref mut throttle) = throttle {
+                        throttle.transfer(vec.len());
+                    }
                     target.process_response_chunk(vec);
                 },
                 Data::Done => break,
@@ -604,6 +665,17 @@ fn range_not_satisfiable_error(response: &mut Response) {
     response.raw_status = Some((StatusCode::RANGE_NOT_SATISFIABLE.as_u16(), reason.into()));
 }
 
+/// The page of Servo's own that serves the about: URL with `path`, such as
+/// about:config.
+fn about_page(path: &str) -> Option<Resource> {
+    match path {
+        "config" => Some(Resource::AboutConfigHTML),
+        "memory" => Some(Resource::AboutMemoryHTML),
+        "crashes" => Some(Resource::AboutCrashesHTML),
+        _ => None,
+    }
+}
+
 /// [Scheme fetch](https://fetch.spec.whatwg.org#scheme-fetch)
 fn scheme_fetch(
     request: &mut Request,
@@ -626,6 +698,21 @@ fn scheme_fetch(
             response
         },
 
+        "about" => match about_page(url.path()) {
+            Some(page) => {
+                let mut response =
+                    Response::new(url, ResourceFetchTiming::new(request.timing_type()));
+                response
+                    .headers
+                    .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
+                *response.body.lock().unwrap() = ResponseBody::Done(resources::read_bytes(page));
+                response.status = Some((StatusCode::OK, "OK".to_string()));
+                response.raw_status = Some((StatusCode::OK.as_u16(), b"OK".to_vec()));
+                response
+            },
+            None => Response::network_error(NetworkError::Internal("Unknown about: page".into())),
+        },
+
         "http" | "https" => http_fetch(
             request, cache, false, false, false, target, done_chan, context,
         ),
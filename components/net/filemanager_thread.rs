@@ -21,7 +21,7 @@ use net_traits::http_percent_encode;
 use net_traits::response::{Response, ResponseBody};
 use servo_arc::Arc as ServoArc;
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::mem;
 use std::ops::Index;
@@ -187,6 +187,30 @@ impl FileManager {
                         );
                     });
             },
+            FileManagerThreadMsg::SelectDirectory(sender, origin, opt_test_path) => {
+                let store = self.store.clone();
+                let embedder = self.embedder_proxy.clone();
+                self.thread_pool
+                    .upgrade()
+                    .and_then(|pool| {
+                        pool.spawn(move || {
+                            store.select_directory(sender, origin, opt_test_path, embedder);
+                        });
+                        Some(())
+                    })
+                    .unwrap_or_else(|| {
+                        warn!(
+                            "FileManager tried to select a directory after CoreResourceManager has exited."
+                        );
+                    });
+            },
+            FileManagerThreadMsg::AddDroppedFiles(paths, sender, origin) => {
+                let files = paths
+                    .iter()
+                    .filter_map(|path| self.store.create_entry(path, &origin).ok())
+                    .collect();
+                let _ = sender.send(files);
+            },
             FileManagerThreadMsg::ReadFile(sender, id, origin) => {
                 self.read_file(sender, id, origin);
             },
@@ -633,6 +657,89 @@ impl FileManagerStore {
         }
     }
 
+    fn select_directory(
+        &self,
+        sender: IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        origin: FileOrigin,
+        opt_test_path: Option<String>,
+        embedder_proxy: EmbedderProxy,
+    ) {
+        // Check if the select_files preference is enabled
+        // to ensure process-level security against compromised script;
+        // Then try applying opt_test_path directly for testing convenience
+        let opt_s = if pref!(dom.testing.html_input_element.select_files.enabled) {
+            opt_test_path
+        } else {
+            let (ipc_sender, ipc_receiver) = ipc::channel().expect("Failed to create IPC channel!");
+            embedder_proxy.send((None, EmbedderMsg::SelectDirectory(ipc_sender)));
+            ipc_receiver.recv().unwrap_or_else(|e| {
+                warn!("Failed to receive directory from embedder ({:?}).", e);
+                None
+            })
+        };
+
+        let directory = match opt_s {
+            Some(s) => PathBuf::from(s),
+            None => {
+                let _ = sender.send(Err(FileManagerThreadError::UserCancelled));
+                return;
+            },
+        };
+
+        // The relative paths of the files start with the name of the directory.
+        let name = match directory.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.to_owned(),
+            None => {
+                let _ = sender.send(Err(FileManagerThreadError::FileSystemError(
+                    "Invalid directory path".to_string(),
+                )));
+                return;
+            },
+        };
+
+        let mut replies = vec![];
+        if let Err(e) = self.create_directory_entries(&directory, &name, &origin, &mut replies) {
+            let _ = sender.send(Err(e));
+            return;
+        }
+        let _ = sender.send(Ok(replies));
+    }
+
+    /// Creates entries for the files in the tree of `directory`, in the order
+    /// of their paths, with their paths relative to the selected directory
+    /// starting with `relative_path`.
+    fn create_directory_entries(
+        &self,
+        directory: &Path,
+        relative_path: &str,
+        origin: &str,
+        replies: &mut Vec<SelectedFile>,
+    ) -> Result<(), FileManagerThreadError> {
+        use net_traits::filemanager_thread::FileManagerThreadError::FileSystemError;
+
+        let mut paths = fs::read_dir(directory)
+            .map_err(|e| FileSystemError(e.to_string()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+            let relative_path = format!("{}/{}", relative_path, name);
+            if path.is_dir() {
+                self.create_directory_entries(&path, &relative_path, origin, replies)?;
+            } else {
+                let mut entry = self.create_entry(&path, origin)?;
+                entry.relative_path = relative_path;
+                replies.push(entry);
+            }
+        }
+        Ok(())
+    }
+
     fn create_entry(
         &self,
         file_path: &Path,
@@ -671,7 +778,7 @@ impl FileManagerStore {
                 origin: origin.to_string(),
                 file_impl: file_impl,
                 refs: AtomicUsize::new(1),
-                // Invalid here since create_entry is called by file selection
+                // Invalid here since create_entry is called by file selection and drops
                 is_valid_url: AtomicBool::new(false),
                 outstanding_tokens: Default::default(),
             },
@@ -689,6 +796,7 @@ impl FileManagerStore {
             modified: modified_epoch,
             size: file_size,
             type_string: type_string,
+            relative_path: String::new(),
         })
     }
 
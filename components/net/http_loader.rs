@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::client_hints::{self, ClientHintsCache};
 use crate::connector::{create_http_client, Connector, TlsConfig};
+use crate::content_blocker::ContentBlocker;
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
 use crate::decoder::Decoder;
@@ -10,7 +12,10 @@ use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{main_fetch, Data, DoneChannel, FetchContext, Target};
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
+use crate::network_conditions::{wait_for_network, Throttle};
+use crate::reporting::{self, ReportToCache};
 use crate::resource_thread::AuthCache;
+use crate::server_timing;
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
@@ -37,7 +42,8 @@ use net_traits::request::{CacheMode, CredentialsMode, Destination, Origin};
 use net_traits::request::{RedirectMode, Referrer, Request, RequestBuilder, RequestMode};
 use net_traits::request::{ResponseTainting, ServiceWorkersMode};
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
-use net_traits::{CookieSource, FetchMetadata, NetworkError, ReferrerPolicy};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::{CookieSource, FetchMetadata, NetworkConditions, NetworkError, ReferrerPolicy};
 use net_traits::{
     RedirectEndValue, RedirectStartValue, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue,
 };
@@ -48,6 +54,7 @@ use std::iter::FromIterator;
 use std::mem;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Condvar, Mutex, RwLock};
 use std::time::{Duration, SystemTime};
 use time::{self, Tm};
@@ -71,6 +78,8 @@ pub enum HttpCacheEntryState {
 
 pub struct HttpState {
     pub hsts_list: RwLock<HstsList>,
+    pub report_to_cache: RwLock<ReportToCache>,
+    pub client_hints: RwLock<ClientHintsCache>,
     pub cookie_jar: RwLock<CookieStorage>,
     pub http_cache: RwLock<HttpCache>,
     /// A map of cache key to entry state,
@@ -79,6 +88,20 @@ pub struct HttpState {
     pub http_cache_state: Mutex<HashMap<CacheKey, Arc<(Mutex<HttpCacheEntryState>, Condvar)>>>,
     pub auth_cache: RwLock<AuthCache>,
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
+    /// The body size of the keepalive requests in flight, per fetch group.
+    /// The request's pipeline stands in for its client's fetch group.
+    pub keepalive_bytes: Mutex<HashMap<Option<PipelineId>, u64>>,
+    /// The number of keepalive fetches started or waiting to start, which the exit waits
+    /// for, see CoreResourceManager::fetch.
+    pub keepalive_fetches: AtomicUsize,
+    /// The pipelines of the third-party documents without storage access, of
+    /// which the requests are made without cookies.
+    pub cookies_blocked_pipelines: RwLock<HashSet<PipelineId>>,
+    pub content_blocker: ContentBlocker,
+    /// The user agent that the webviews of the pipelines are overridden with.
+    pub user_agent_overrides: RwLock<HashMap<PipelineId, UserAgentOverride>>,
+    /// The network conditions that the webviews of the pipelines are emulated with.
+    pub network_conditions: RwLock<HashMap<PipelineId, NetworkConditions>>,
     pub client: Client<Connector, Body>,
 }
 
@@ -86,16 +109,113 @@ impl HttpState {
     pub fn new(tls_config: TlsConfig) -> HttpState {
         HttpState {
             hsts_list: RwLock::new(HstsList::new()),
+            report_to_cache: RwLock::new(ReportToCache::new()),
+            client_hints: RwLock::new(ClientHintsCache::new()),
             cookie_jar: RwLock::new(CookieStorage::new(150)),
             auth_cache: RwLock::new(AuthCache::new()),
             history_states: RwLock::new(HashMap::new()),
             http_cache: RwLock::new(HttpCache::new()),
             http_cache_state: Mutex::new(HashMap::new()),
+            keepalive_bytes: Mutex::new(HashMap::new()),
+            keepalive_fetches: AtomicUsize::new(0),
+            cookies_blocked_pipelines: RwLock::new(HashSet::new()),
+            content_blocker: ContentBlocker::new(""),
+            user_agent_overrides: RwLock::new(HashMap::new()),
+            network_conditions: RwLock::new(HashMap::new()),
             client: create_http_client(tls_config, HANDLE.lock().unwrap().executor()),
         }
     }
+
+    /// The user agent override of the webview of a pipeline, if any.
+    pub fn user_agent_override(
+        &self,
+        pipeline_id: Option<PipelineId>,
+    ) -> Option<UserAgentOverride> {
+        let pipeline_id = pipeline_id?;
+        self.user_agent_overrides
+            .read()
+            .unwrap()
+            .get(&pipeline_id)
+            .cloned()
+    }
+
+    /// The network conditions that the webview of a pipeline is emulated
+    /// with, if any.
+    pub fn network_conditions(&self, pipeline_id: Option<PipelineId>) -> Option<NetworkConditions> {
+        let pipeline_id = pipeline_id?;
+        self.network_conditions
+            .read()
+            .unwrap()
+            .get(&pipeline_id)
+            .cloned()
+    }
+
+    /// Records that a keepalive request with a body of `bytes` bytes is in
+    /// flight for the fetch group of `pipeline_id`.
+    pub fn begin_keepalive_request(&self, pipeline_id: Option<PipelineId>, bytes: u64) {
+        *self
+            .keepalive_bytes
+            .lock()
+            .unwrap()
+            .entry(pipeline_id)
+            .or_insert(0) += bytes;
+    }
+
+    /// Records that a keepalive request started with `begin_keepalive_request`
+    /// is done.
+    pub fn end_keepalive_request(&self, pipeline_id: Option<PipelineId>, bytes: u64) {
+        let mut keepalive_bytes = self.keepalive_bytes.lock().unwrap();
+        let remaining = match keepalive_bytes.get_mut(&pipeline_id) {
+            Some(inflight) => {
+                *inflight = inflight.saturating_sub(bytes);
+                *inflight
+            },
+            None => return,
+        };
+        if remaining == 0 {
+            keepalive_bytes.remove(&pipeline_id);
+        }
+    }
+
+    /// Records that a keepalive fetch started, or waits for a worker to start.
+    pub fn begin_keepalive_fetch(&self) {
+        self.keepalive_fetches.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Records that a keepalive fetch started with `begin_keepalive_fetch` is done.
+    pub fn end_keepalive_fetch(&self) {
+        self.keepalive_fetches.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Whether any keepalive fetch is in flight.
+    pub fn has_keepalive_fetches(&self) -> bool {
+        self.keepalive_fetches.load(Ordering::SeqCst) > 0
+    }
+
+    /// Whether the cookies of the requests of `pipeline_id` are blocked.
+    fn cookies_blocked(&self, pipeline_id: Option<PipelineId>) -> bool {
+        pipeline_id.map_or(false, |pipeline_id| {
+            self.cookies_blocked_pipelines
+                .read()
+                .unwrap()
+                .contains(&pipeline_id)
+        })
+    }
+
+    fn inflight_keepalive_bytes(&self, pipeline_id: Option<PipelineId>) -> u64 {
+        self.keepalive_bytes
+            .lock()
+            .unwrap()
+            .get(&pipeline_id)
+            .cloned()
+            .unwrap_or(0)
+    }
 }
 
+/// The most body bytes the keepalive requests of a fetch group can have in
+/// flight.
+pub const MAX_INFLIGHT_KEEPALIVE_BYTES: u64 = 64 * 1024;
+
 fn precise_time_ms() -> u64 {
     time::precise_time_ns() / (1000 * 1000)
 }
@@ -399,6 +519,12 @@ fn obtain_response(
         .lock()
         .unwrap()
         .set_attribute(ResourceAttribute::DomainLookupStart);
+    // The lookup happens in the connector, so it is as good as done here.
+    context
+        .timing
+        .lock()
+        .unwrap()
+        .set_attribute(ResourceAttribute::DomainLookupEnd);
 
     // TODO(#21261) connect_start: set if a persistent connection is *not* used and the last non-redirected
     // fetch passes the timing allow check
@@ -407,7 +533,7 @@ fn obtain_response(
         .timing
         .lock()
         .unwrap()
-        .set_attribute(ResourceAttribute::ConnectStart(connect_start));
+        .set_attribute(ResourceAttribute::ConnectStart);
 
     // https://url.spec.whatwg.org/#percent-encoded-bytes
     let request = HyperRequest::builder()
@@ -444,7 +570,7 @@ fn obtain_response(
         .timing
         .lock()
         .unwrap()
-        .set_attribute(ResourceAttribute::ConnectEnd(connect_end));
+        .set_attribute(ResourceAttribute::ConnectEnd);
 
     let request_id = request_id.map(|v| v.to_owned());
     let pipeline_id = pipeline_id.clone();
@@ -458,8 +584,6 @@ fn obtain_response(
             .and_then(move |res| {
                 let send_end = precise_time_ms();
 
-                // TODO(#21271) response_start: immediately after receiving first byte of response
-
                 let msg = if let Some(request_id) = request_id {
                     if let Some(pipeline_id) = pipeline_id {
                         Some(prepare_devtools_request(
@@ -589,6 +713,18 @@ pub fn http_fetch(
             return Response::network_error(NetworkError::Internal("CORS check failed".into()));
         }
 
+        // The endpoints of a document's reports are set by the headers of
+        // the response it is created from.
+        if request.destination == Destination::Document {
+            if let Some(url) = fetch_result.url().cloned() {
+                fetch_result.reporting_endpoints = reporting::reporting_endpoints(
+                    &url,
+                    &fetch_result.headers,
+                    &context.state.report_to_cache.read().unwrap(),
+                );
+            }
+        }
+
         fetch_result.return_internal = false;
         response = Some(fetch_result);
     }
@@ -912,8 +1048,17 @@ fn http_network_or_cache_fetch(
         http_request
             .headers
             .typed_insert(ContentLength(content_length_value));
-        if http_request.keep_alive {
-            // Step 5.8 TODO: needs request's client object
+        // Step 5.8. fetch_with_cors_cache already counts this request's body
+        // as in flight, for as long as the fetch lasts.
+        if http_request.keep_alive &&
+            context
+                .state
+                .inflight_keepalive_bytes(http_request.pipeline_id) >
+                MAX_INFLIGHT_KEEPALIVE_BYTES
+        {
+            return Response::network_error(NetworkError::Internal(
+                "Too much keepalive request data in flight".into(),
+            ));
         }
     }
 
@@ -949,12 +1094,21 @@ fn http_network_or_cache_fetch(
     }
 
     // Step 5.11
+    let ua_override = context.state.user_agent_override(http_request.pipeline_id);
     if !http_request.headers.contains_key(header::USER_AGENT) {
-        let user_agent = context.user_agent.clone().into_owned();
-        http_request
-            .headers
-            .typed_insert::<UserAgent>(user_agent.parse().unwrap());
+        let user_agent = ua_override
+            .as_ref()
+            .and_then(|ua_override| ua_override.user_agent.clone())
+            .unwrap_or_else(|| context.user_agent.clone().into_owned());
+        if let Ok(user_agent) = user_agent.parse::<UserAgent>() {
+            http_request.headers.typed_insert(user_agent);
+        }
     }
+    client_hints::set_client_hints_headers(
+        http_request,
+        &context.state.client_hints.read().unwrap(),
+        ua_override.as_ref(),
+    );
 
     match http_request.cache_mode {
         // Step 5.12
@@ -1002,13 +1156,14 @@ fn http_network_or_cache_fetch(
     // TODO some of this step can't be implemented yet
     if credentials_flag {
         // Substep 1
-        // TODO http://mxr.mozilla.org/servo/source/components/net/http_loader.rs#504
-        // XXXManishearth http_loader has block_cookies: support content blocking here too
-        set_request_cookies(
-            &current_url,
-            &mut http_request.headers,
-            &context.state.cookie_jar,
-        );
+        // The cookies of third-party documents without storage access are blocked.
+        if !context.state.cookies_blocked(http_request.pipeline_id) {
+            set_request_cookies(
+                &current_url,
+                &mut http_request.headers,
+                &context.state.cookie_jar,
+            );
+        }
         // Substep 2
         if !http_request.headers.contains_key(header::AUTHORIZATION) {
             // Substep 3
@@ -1210,8 +1365,28 @@ fn http_network_or_cache_fetch(
     // More Step 7
     if response.is_none() {
         // Substep 2
-        let forward_response =
+        let mut forward_response =
             http_network_fetch(http_request, credentials_flag, done_chan, context);
+        // The navigation is made again once with the hints that the response
+        // asked for in `Critical-CH`.
+        let retry = client_hints::should_retry_with_critical_hints(
+            http_request,
+            &forward_response.headers,
+            &context.state.client_hints.read().unwrap(),
+        );
+        if retry {
+            client_hints::set_client_hints_headers(
+                http_request,
+                &context.state.client_hints.read().unwrap(),
+                context
+                    .state
+                    .user_agent_override(http_request.pipeline_id)
+                    .as_ref(),
+            );
+            *done_chan = None;
+            forward_response =
+                http_network_fetch(http_request, credentials_flag, done_chan, context);
+        }
         // Substep 3
         if let Some((200..=399, _)) = forward_response.raw_status {
             if !http_request.method.is_safe() {
@@ -1376,6 +1551,17 @@ fn http_network_fetch(
     // Step 5
     let url = request.current_url();
 
+    // The network of the webview of the request may be emulated as offline
+    // or slow.
+    if let Some(conditions) = context.state.network_conditions(request.pipeline_id) {
+        if let Err(error) = wait_for_network(&conditions) {
+            return Response::network_error(error);
+        }
+        if let (Some(throughput), Some(body)) = (conditions.upload_throughput, &request.body) {
+            Throttle::new(throughput).transfer(body.len());
+        }
+    }
+
     let request_id = context
         .devtools_chan
         .as_ref()
@@ -1412,6 +1598,11 @@ fn http_network_fetch(
         Ok(wrapped_response) => wrapped_response,
         Err(error) => return Response::network_error(error),
     };
+    context
+        .timing
+        .lock()
+        .unwrap()
+        .set_attribute(ResourceAttribute::ResponseStart);
 
     if log_enabled!(log::Level::Info) {
         info!("{:?} response for {}", res.version(), url);
@@ -1447,6 +1638,14 @@ fn http_network_fetch(
     if !(is_same_origin || req_origin_in_timing_allow || wildcard_present) {
         context.timing.lock().unwrap().mark_timing_check_failed();
     }
+    // Server timing metrics are only exposed to origins passing the check.
+    context
+        .timing
+        .lock()
+        .unwrap()
+        .set_attribute(ResourceAttribute::ServerTiming(
+            server_timing::parse_server_timing(res.headers()),
+        ));
 
     let timing = context.timing.lock().unwrap().clone();
     let mut response = Response::new(url.clone(), timing);
@@ -1510,6 +1709,15 @@ fn http_network_fetch(
     let done_sender3 = done_sender.clone();
     let timing_ptr2 = context.timing.clone();
     let timing_ptr3 = context.timing.clone();
+    // The body is decoded as it is read, so its encoded size is only known
+    // from the headers.
+    let encoded_length = if res.headers().contains_key(header::CONTENT_ENCODING) {
+        res.headers()
+            .typed_get::<ContentLength>()
+            .map(|length| length.0)
+    } else {
+        None
+    };
     let url1 = request.url();
     let url2 = url1.clone();
     HANDLE.lock().unwrap().spawn(
@@ -1535,11 +1743,14 @@ fn http_network_fetch(
                     ResponseBody::Receiving(ref mut body) => mem::replace(body, vec![]),
                     _ => vec![],
                 };
+                let decoded = completed_body.len() as u64;
                 *body = ResponseBody::Done(completed_body);
-                timing_ptr2
-                    .lock()
-                    .unwrap()
-                    .set_attribute(ResourceAttribute::ResponseEnd);
+                let mut timing = timing_ptr2.lock().unwrap();
+                timing.set_attribute(ResourceAttribute::BodySize {
+                    encoded: encoded_length.unwrap_or(decoded),
+                    decoded,
+                });
+                timing.set_attribute(ResourceAttribute::ResponseEnd);
                 let _ = done_sender2.send(Data::Done);
                 future::ok(())
             })
@@ -1591,7 +1802,7 @@ fn http_network_fetch(
 
     // TODO this step isn't possible yet
     // Step 15
-    if credentials_flag {
+    if credentials_flag && !context.state.cookies_blocked(request.pipeline_id) {
         set_cookies_from_headers(&url, &response.headers, &context.state.cookie_jar);
     }
     context
@@ -1600,6 +1811,18 @@ fn http_network_fetch(
         .write()
         .unwrap()
         .update_hsts_list_from_response(&url, &response.headers);
+    context
+        .state
+        .report_to_cache
+        .write()
+        .unwrap()
+        .update_from_response(&url, &response.headers);
+    context
+        .state
+        .client_hints
+        .write()
+        .unwrap()
+        .update_from_response(&url, request.destination, &response.headers);
 
     // TODO these steps
     // Step 16
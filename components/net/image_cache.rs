@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::resource_thread::CoreResourceThreadPool;
 use embedder_traits::resources::{self, Resource};
 use immeta::load_from_buf;
 use net_traits::image::base::{load_from_memory, Image, ImageMetadata};
@@ -19,7 +20,6 @@ use std::collections::HashMap;
 use std::io;
 use std::mem;
 use std::sync::{Arc, Mutex};
-use std::thread;
 use webrender_api::units::DeviceIntSize;
 
 ///
@@ -32,6 +32,10 @@ use webrender_api::units::DeviceIntSize;
 ///     * For faster lookups, it might be useful to store the LoadKey in the
 ///       DOM once we have performed a first load.
 
+/// The number of threads of the pool that decodes the images of an image
+/// cache, off the threads that request them.
+const DECODER_THREADS: usize = 4;
+
 // ======================================================================
 // Helper functions.
 // ======================================================================
@@ -422,6 +426,9 @@ impl ImageCacheStore {
 
 pub struct ImageCacheImpl {
     store: Arc<Mutex<ImageCacheStore>>,
+
+    /// The threads that decode the images once they are loaded.
+    thread_pool: CoreResourceThreadPool,
 }
 
 impl ImageCache for ImageCacheImpl {
@@ -438,6 +445,7 @@ impl ImageCache for ImageCacheImpl {
                 placeholder_url: ServoUrl::parse("chrome://resources/rippy.png").unwrap(),
                 webrender_api: webrender_api,
             })),
+            thread_pool: CoreResourceThreadPool::new(DECODER_THREADS),
         }
     }
 
@@ -464,47 +472,35 @@ impl ImageCache for ImageCacheImpl {
             return result;
         }
 
-        let decoded = {
-            let result = store.pending_loads.get_cached(
-                url.clone(),
-                origin.clone(),
-                cors_setting,
-                can_request,
-            );
-            match result {
-                CacheResult::Hit(key, pl) => match (&pl.result, &pl.metadata) {
-                    (&Some(Ok(_)), _) => {
-                        debug!("Sync decoding {} ({:?})", url, key);
-                        decode_bytes_sync(key, &pl.bytes.as_slice(), pl.cors_status)
-                    },
-                    (&None, &Some(ref meta)) => {
-                        debug!("Metadata available for {} ({:?})", url, key);
-                        return Ok(ImageOrMetadataAvailable::MetadataAvailable(meta.clone()));
-                    },
-                    (&Some(Err(_)), _) | (&None, &None) => {
-                        debug!("{} ({:?}) is still pending", url, key);
-                        return Err(ImageState::Pending(key));
-                    },
+        let result =
+            store
+                .pending_loads
+                .get_cached(url.clone(), origin.clone(), cors_setting, can_request);
+        match result {
+            CacheResult::Hit(key, pl) => match (&pl.result, &pl.metadata) {
+                (&None, &Some(ref meta)) => {
+                    debug!("Metadata available for {} ({:?})", url, key);
+                    Ok(ImageOrMetadataAvailable::MetadataAvailable(meta.clone()))
                 },
-                CacheResult::Miss(Some((key, _pl))) => {
-                    debug!("Should be requesting {} ({:?})", url, key);
-                    return Err(ImageState::NotRequested(key));
+                // The image is decoded off this thread once it is loaded, the
+                // listeners of the load are notified when it is available.
+                (&Some(Ok(_)), _) => {
+                    debug!("{} ({:?}) is still decoding", url, key);
+                    Err(ImageState::Pending(key))
                 },
-                CacheResult::Miss(None) => {
-                    debug!("Couldn't find an entry for {}", url);
-                    return Err(ImageState::LoadError);
+                (&Some(Err(_)), _) | (&None, &None) => {
+                    debug!("{} ({:?}) is still pending", url, key);
+                    Err(ImageState::Pending(key))
                 },
-            }
-        };
-
-        // In the case where a decode is ongoing (or waiting in a queue) but we
-        // have the full response available, we decode the bytes synchronously
-        // and ignore the async decode when it finishes later.
-        // TODO: make this behaviour configurable according to the caller's needs.
-        store.handle_decoder(decoded);
-        match store.get_completed_image_if_available(url, origin, cors_setting, use_placeholder) {
-            Some(result) => result,
-            None => Err(ImageState::LoadError),
+            },
+            CacheResult::Miss(Some((key, _pl))) => {
+                debug!("Should be requesting {} ({:?})", url, key);
+                Err(ImageState::NotRequested(key))
+            },
+            CacheResult::Miss(None) => {
+                debug!("Couldn't find an entry for {}", url);
+                Err(ImageState::LoadError)
+            },
         }
     }
 
@@ -589,7 +585,7 @@ impl ImageCache for ImageCacheImpl {
                         };
 
                         let local_store = self.store.clone();
-                        thread::spawn(move || {
+                        self.thread_pool.spawn(move || {
                             let msg = decode_bytes_sync(key, &*bytes, cors_status);
                             debug!("Image decoded");
                             local_store.lock().unwrap().handle_decoder(msg);
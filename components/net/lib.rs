@@ -17,7 +17,9 @@ extern crate serde;
 #[macro_use]
 extern crate servo_config;
 
+pub mod client_hints;
 pub mod connector;
+pub mod content_blocker;
 pub mod cookie;
 pub mod cookie_storage;
 mod data_loader;
@@ -29,7 +31,10 @@ pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
 pub mod mime_classifier;
+pub mod network_conditions;
+pub mod reporting;
 pub mod resource_thread;
+pub mod server_timing;
 mod storage_thread;
 pub mod subresource_integrity;
 mod websocket_loader;
@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The emulation of the network conditions of a webview, which makes its
+//! requests fail as if it were offline, or slows them down with a latency
+//! and a throughput cap.
+
+use net_traits::{NetworkConditions, NetworkError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Waits for the latency of the emulated network before a request or a
+/// connection is made, or fails if the network is emulated as offline.
+pub fn wait_for_network(conditions: &NetworkConditions) -> Result<(), NetworkError> {
+    if conditions.offline {
+        return Err(NetworkError::Internal(
+            "The network is emulated as offline".into(),
+        ));
+    }
+    thread::sleep(Duration::from_millis(conditions.latency));
+    Ok(())
+}
+
+/// Spreads the transfer of a body over time, to keep to a throughput.
+pub struct Throttle {
+    /// The throughput, in bytes per second.
+    throughput: u64,
+    start: Instant,
+    transferred: u64,
+}
+
+impl Throttle {
+    pub fn new(throughput: u64) -> Throttle {
+        Throttle {
+            throughput: throughput.max(1),
+            start: Instant::now(),
+            transferred: 0,
+        }
+    }
+
+    /// Waits until `bytes` more bytes can be transferred without exceeding
+    /// the throughput.
+    pub fn transfer(&mut self, bytes: usize) {
+        self.transferred += bytes as u64;
+        let due = Duration::from_secs_f64(self.transferred as f64 / self.throughput as f64);
+        let elapsed = self.start.elapsed();
+        if due > elapsed {
+            thread::sleep(due - elapsed);
+        }
+    }
+}
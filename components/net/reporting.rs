@@ -0,0 +1,256 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Processing of the headers that configure where reports are delivered:
+//! `Reporting-Endpoints`, which applies to the document it comes with, and
+//! the older `Report-To`, whose endpoint groups are remembered for the
+//! origin until they expire.
+
+use http::HeaderMap;
+use net_traits::ReportingEndpoint;
+use servo_url::{Host, ImmutableOrigin, ServoUrl};
+use std::collections::HashMap;
+
+/// An endpoint group set by a `Report-To` header.
+/// <https://www.w3.org/TR/2018/WD-reporting-1-20180925/#endpoint-group>
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndpointGroup {
+    pub name: String,
+    pub urls: Vec<ServoUrl>,
+    pub max_age: u64,
+    pub timestamp: u64,
+}
+
+impl EndpointGroup {
+    pub fn is_expired(&self) -> bool {
+        (time::get_time().sec as u64).saturating_sub(self.timestamp) >= self.max_age
+    }
+}
+
+/// The `Report-To` endpoint groups of each origin.
+#[derive(Clone, Debug, Default)]
+pub struct ReportToCache {
+    groups: HashMap<ImmutableOrigin, Vec<EndpointGroup>>,
+}
+
+impl ReportToCache {
+    pub fn new() -> ReportToCache {
+        Default::default()
+    }
+
+    /// Remembers the endpoint groups of the `Report-To` header of a response
+    /// from `url`, replacing groups of the same names.
+    pub fn update_from_response(&mut self, url: &ServoUrl, headers: &HeaderMap) {
+        if !is_potentially_trustworthy(url) {
+            return;
+        }
+        let origin = url.origin();
+        for group in parse_report_to(url, headers) {
+            let groups = self.groups.entry(origin.clone()).or_insert(vec![]);
+            groups.retain(|existing| existing.name != group.name);
+            // A max_age of zero removes the group.
+            if group.max_age > 0 {
+                groups.push(group);
+            }
+        }
+    }
+
+    /// The endpoints of the unexpired endpoint groups of `origin`.
+    pub fn endpoints(&self, origin: &ImmutableOrigin) -> Vec<ReportingEndpoint> {
+        self.groups
+            .get(origin)
+            .into_iter()
+            .flat_map(|groups| groups.iter())
+            .filter(|group| !group.is_expired())
+            .filter_map(|group| {
+                group.urls.first().map(|url| ReportingEndpoint {
+                    name: group.name.clone(),
+                    url: url.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The endpoints reports about a response from `url` are delivered to. The
+/// `Reporting-Endpoints` header takes precedence over groups of the same
+/// name in `report_to_cache`.
+pub fn reporting_endpoints(
+    url: &ServoUrl,
+    headers: &HeaderMap,
+    report_to_cache: &ReportToCache,
+) -> Vec<ReportingEndpoint> {
+    if !is_potentially_trustworthy(url) {
+        return vec![];
+    }
+    let mut endpoints = parse_reporting_endpoints(url, headers);
+    for endpoint in report_to_cache.endpoints(&url.origin()) {
+        if !endpoints.iter().any(|e| e.name == endpoint.name) {
+            endpoints.push(endpoint);
+        }
+    }
+    endpoints
+}
+
+/// <https://w3c.github.io/reporting/#process-header>
+///
+/// The header is a structured field dictionary whose members map endpoint
+/// names to URL strings, e.g. `default="https://example.com/reports"`.
+pub fn parse_reporting_endpoints(url: &ServoUrl, headers: &HeaderMap) -> Vec<ReportingEndpoint> {
+    let mut endpoints: Vec<ReportingEndpoint> = vec![];
+    for value in headers.get_all("reporting-endpoints") {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        for (name, endpoint_url) in parse_dictionary(value) {
+            let endpoint_url = match ServoUrl::parse_with_base(Some(url), &endpoint_url) {
+                Ok(endpoint_url) => endpoint_url,
+                Err(_) => continue,
+            };
+            if !is_potentially_trustworthy(&endpoint_url) {
+                continue;
+            }
+            // Later members of a dictionary override earlier ones.
+            endpoints.retain(|endpoint| endpoint.name != name);
+            endpoints.push(ReportingEndpoint {
+                name,
+                url: endpoint_url,
+            });
+        }
+    }
+    endpoints
+}
+
+/// Parses the `Report-To` header, a comma separated list of JSON objects
+/// like `{"group": "name", "max_age": 86400, "endpoints": [{"url": "..."}]}`.
+/// <https://www.w3.org/TR/2018/WD-reporting-1-20180925/#header>
+pub fn parse_report_to(url: &ServoUrl, headers: &HeaderMap) -> Vec<EndpointGroup> {
+    let values: Vec<&str> = headers
+        .get_all("report-to")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    if values.is_empty() {
+        return vec![];
+    }
+    let list = format!("[{}]", values.join(","));
+    let items: Vec<serde_json::Value> = match serde_json::from_str(&list) {
+        Ok(items) => items,
+        Err(_) => return vec![],
+    };
+    let timestamp = time::get_time().sec as u64;
+    let mut groups: Vec<EndpointGroup> = vec![];
+    for item in items {
+        let name = match item.get("group") {
+            Some(group) => match group.as_str() {
+                Some(group) => group.to_owned(),
+                None => continue,
+            },
+            None => "default".to_owned(),
+        };
+        if groups.iter().any(|group| group.name == name) {
+            continue;
+        }
+        let max_age = match item.get("max_age").and_then(|max_age| max_age.as_u64()) {
+            Some(max_age) => max_age,
+            None => continue,
+        };
+        let urls = item
+            .get("endpoints")
+            .and_then(|endpoints| endpoints.as_array())
+            .map(|endpoints| {
+                endpoints
+                    .iter()
+                    .filter_map(|endpoint| endpoint.get("url")?.as_str())
+                    .filter_map(|endpoint_url| {
+                        ServoUrl::parse_with_base(Some(url), endpoint_url).ok()
+                    })
+                    .filter(is_potentially_trustworthy)
+                    .collect()
+            })
+            .unwrap_or(vec![]);
+        if urls.is_empty() && max_age > 0 {
+            continue;
+        }
+        groups.push(EndpointGroup {
+            name,
+            urls,
+            max_age,
+            timestamp,
+        });
+    }
+    groups
+}
+
+/// Parses the members of a structured field dictionary whose values are
+/// strings, ignoring any parameters. Members with other values are skipped.
+/// <https://httpwg.org/specs/rfc8941.html#parse-dictionary>
+fn parse_dictionary(input: &str) -> Vec<(String, String)> {
+    let mut members = vec![];
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.peek().map_or(false, |&c| c == ' ' || c == '\t') {
+            chars.next();
+        }
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.*".contains(c) {
+                key.push(c);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if key.is_empty() {
+            return members;
+        }
+        let mut value = None;
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some(c) if c == '"' || c == '\\' => string.push(c),
+                            _ => return members,
+                        },
+                        Some('"') => break,
+                        Some(c) if c >= ' ' && c <= '~' => string.push(c),
+                        _ => return members,
+                    }
+                }
+                value = Some(string);
+            }
+        }
+        // Skip anything up to the next member, which includes parameters
+        // and values that aren't strings.
+        while let Some(c) = chars.next() {
+            if c == ',' {
+                break;
+            }
+        }
+        if let Some(value) = value {
+            members.push((key, value));
+        }
+        if chars.peek().is_none() {
+            return members;
+        }
+    }
+}
+
+/// <https://w3c.github.io/webappsec-secure-contexts/#is-origin-trustworthy>
+pub fn is_potentially_trustworthy(url: &ServoUrl) -> bool {
+    if url.is_secure_scheme() {
+        return true;
+    }
+    match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost" || domain.ends_with(".localhost"),
+        Some(Host::Ipv4(address)) => address.is_loopback(),
+        Some(Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    }
+}
@@ -4,7 +4,9 @@
 
 //! A thread that takes a URL and streams back the binary data.
 
+use crate::client_hints::ClientHintsCache;
 use crate::connector::{create_http_client, create_tls_config, ALPN_H2_H1};
+use crate::content_blocker::ContentBlocker;
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
 use crate::fetch::cors_cache::CorsCache;
@@ -13,6 +15,7 @@ use crate::filemanager_thread::FileManager;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::reporting::ReportToCache;
 use crate::storage_thread::StorageThreadFactory;
 use crate::websocket_loader;
 use crossbeam_channel::Sender;
@@ -24,7 +27,7 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use net_traits::blob_url_store::parse_blob_url;
 use net_traits::filemanager_thread::FileTokenCheck;
-use net_traits::request::{Destination, RequestBuilder};
+use net_traits::request::{Destination, Request, RequestBuilder, RequestPriority};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::DiscardFetch;
@@ -41,14 +44,16 @@ use serde::{Deserialize, Serialize};
 use servo_arc::Arc as ServoArc;
 use servo_url::ServoUrl;
 use std::borrow::{Cow, ToOwned};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicUsize;
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Returns a tuple of (public, private) senders to the new threads.
 pub fn new_resource_threads(
@@ -145,11 +150,19 @@ fn create_http_states(
 
     let http_state = HttpState {
         hsts_list: RwLock::new(hsts_list),
+        report_to_cache: RwLock::new(ReportToCache::new()),
+        client_hints: RwLock::new(ClientHintsCache::new()),
         cookie_jar: RwLock::new(cookie_jar),
         auth_cache: RwLock::new(auth_cache),
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
+        keepalive_bytes: Mutex::new(HashMap::new()),
+        keepalive_fetches: AtomicUsize::new(0),
+        cookies_blocked_pipelines: RwLock::new(HashSet::new()),
+        content_blocker: ContentBlocker::from_servo_list(),
+        user_agent_overrides: RwLock::new(HashMap::new()),
+        network_conditions: RwLock::new(HashMap::new()),
         client: create_http_client(
             create_tls_config(&certs, ALPN_H2_H1),
             HANDLE.lock().unwrap().executor(),
@@ -158,11 +171,19 @@ fn create_http_states(
 
     let private_http_state = HttpState {
         hsts_list: RwLock::new(HstsList::from_servo_preload()),
+        report_to_cache: RwLock::new(ReportToCache::new()),
+        client_hints: RwLock::new(ClientHintsCache::new()),
         cookie_jar: RwLock::new(CookieStorage::new(150)),
         auth_cache: RwLock::new(AuthCache::new()),
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(HttpCache::new()),
         http_cache_state: Mutex::new(HashMap::new()),
+        keepalive_bytes: Mutex::new(HashMap::new()),
+        keepalive_fetches: AtomicUsize::new(0),
+        cookies_blocked_pipelines: RwLock::new(HashSet::new()),
+        content_blocker: ContentBlocker::from_servo_list(),
+        user_agent_overrides: RwLock::new(HashMap::new()),
+        network_conditions: RwLock::new(HashMap::new()),
         client: create_http_client(
             create_tls_config(&certs, ALPN_H2_H1),
             HANDLE.lock().unwrap().executor(),
@@ -276,6 +297,41 @@ impl ResourceChannelManager {
                     .clear_storage(&request);
                 return true;
             },
+            CoreResourceMsg::ObserveCookieChanges(pipeline_id, url, sender) => http_state
+                .cookie_jar
+                .write()
+                .unwrap()
+                .observe_changes(pipeline_id, url, sender),
+            CoreResourceMsg::StopObservingCookieChanges(pipeline_id) => http_state
+                .cookie_jar
+                .write()
+                .unwrap()
+                .stop_observing_changes(pipeline_id),
+            CoreResourceMsg::SetCookiesBlocked(pipeline_id, blocked) => {
+                let mut blocked_pipelines = http_state.cookies_blocked_pipelines.write().unwrap();
+                if blocked {
+                    blocked_pipelines.insert(pipeline_id);
+                } else {
+                    blocked_pipelines.remove(&pipeline_id);
+                }
+            },
+            CoreResourceMsg::SetContentBlocking(pipeline_id, webview) => http_state
+                .content_blocker
+                .set_pipeline_webview(pipeline_id, webview),
+            CoreResourceMsg::SetUserAgentOverride(pipeline_id, ua_override) => {
+                let mut overrides = http_state.user_agent_overrides.write().unwrap();
+                match ua_override {
+                    Some(ua_override) => overrides.insert(pipeline_id, ua_override),
+                    None => overrides.remove(&pipeline_id),
+                };
+            },
+            CoreResourceMsg::SetNetworkConditions(pipeline_id, conditions) => {
+                let mut network_conditions = http_state.network_conditions.write().unwrap();
+                match conditions {
+                    Some(conditions) => network_conditions.insert(pipeline_id, conditions),
+                    None => network_conditions.remove(&pipeline_id),
+                };
+            },
             CoreResourceMsg::FetchRedirect(req_init, res_init, sender, cancel_chan) => self
                 .resource_manager
                 .fetch(req_init, Some(res_init), sender, http_state, cancel_chan),
@@ -331,7 +387,10 @@ impl ResourceChannelManager {
                 let _ = sender.send(());
             },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
-            CoreResourceMsg::Exit(sender) => {
+            CoreResourceMsg::Exit(keepalive_timeout, sender) => {
+                // The state is saved once the fetches are done, since their responses can
+                // still set cookies.
+                self.resource_manager.exit(http_state, keepalive_timeout);
                 if let Some(ref config_dir) = self.config_dir {
                     match http_state.auth_cache.read() {
                         Ok(auth_cache) => {
@@ -348,7 +407,6 @@ impl ResourceChannelManager {
                         Err(_) => warn!("Error writing hsts list to disk"),
                     }
                 }
-                self.resource_manager.exit();
                 let _ = sender.send(());
                 return false;
             },
@@ -433,6 +491,7 @@ pub struct CoreResourceManager {
     devtools_chan: Option<Sender<DevtoolsControlMsg>>,
     swmanager_chan: Option<IpcSender<CustomResponseMediator>>,
     filemanager: FileManager,
+    embedder_proxy: EmbedderProxy,
     thread_pool: Arc<CoreResourceThreadPool>,
     certificate_path: Option<String>,
 }
@@ -479,10 +538,49 @@ impl ThreadPoolState {
     }
 }
 
+/// Work given to `CoreResourceThreadPool::spawn_with_priority`, waiting for
+/// a worker.
+struct PendingWork {
+    priority: u8,
+    /// Orders work of the same priority by submission.
+    sequence: u64,
+    work: Box<dyn FnOnce() + Send>,
+}
+
+impl PartialEq for PendingWork {
+    fn eq(&self, other: &PendingWork) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PendingWork {}
+
+impl PartialOrd for PendingWork {
+    fn partial_cmp(&self, other: &PendingWork) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingWork {
+    fn cmp(&self, other: &PendingWork) -> Ordering {
+        // Higher priorities first, then older work first.
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct PendingQueue {
+    work: BinaryHeap<PendingWork>,
+    next_sequence: u64,
+}
+
 /// Threadpool used by Fetch and file operations.
 pub struct CoreResourceThreadPool {
     pool: rayon::ThreadPool,
     state: Arc<Mutex<ThreadPoolState>>,
+    pending: Arc<Mutex<PendingQueue>>,
 }
 
 impl CoreResourceThreadPool {
@@ -492,7 +590,39 @@ impl CoreResourceThreadPool {
             .build()
             .unwrap();
         let state = Arc::new(Mutex::new(ThreadPoolState::new()));
-        CoreResourceThreadPool { pool: pool, state }
+        CoreResourceThreadPool {
+            pool: pool,
+            state,
+            pending: Default::default(),
+        }
+    }
+
+    /// Spawn work on the thread-pool, if still active. When all workers are
+    /// busy, the pending work with the highest priority runs first.
+    pub fn spawn_with_priority<OP>(&self, priority: u8, work: OP)
+    where
+        OP: FnOnce() + Send + 'static,
+    {
+        {
+            let mut pending = self.pending.lock().unwrap();
+            let sequence = pending.next_sequence;
+            pending.next_sequence += 1;
+            pending.work.push(PendingWork {
+                priority,
+                sequence,
+                work: Box::new(work),
+            });
+        }
+
+        // Every spawned job runs exactly one piece of pending work, though
+        // not necessarily the one pushed above.
+        let pending = self.pending.clone();
+        self.spawn(move || {
+            let work = pending.lock().unwrap().work.pop();
+            if let Some(work) = work {
+                (work.work)();
+            }
+        });
     }
 
     /// Spawn work on the thread-pool, if still active.
@@ -562,6 +692,26 @@ impl CoreResourceThreadPool {
     }
 }
 
+/// The order in which fetches waiting for a worker of the thread-pool get
+/// one, higher first. Resources that block rendering come before the others,
+/// and the request's priority hint moves it one step up or down.
+fn scheduling_priority(request: &Request) -> u8 {
+    let base = match request.destination {
+        Destination::Document | Destination::Script | Destination::Style | Destination::Font => 3,
+        Destination::Image |
+        Destination::Audio |
+        Destination::Video |
+        Destination::Track |
+        Destination::Report => 1,
+        _ => 2,
+    };
+    match request.priority {
+        RequestPriority::High => base + 1,
+        RequestPriority::Low => base - 1,
+        RequestPriority::Auto => base,
+    }
+}
+
 impl CoreResourceManager {
     pub fn new(
         user_agent: Cow<'static, str>,
@@ -576,14 +726,25 @@ impl CoreResourceManager {
             user_agent: user_agent,
             devtools_chan: devtools_channel,
             swmanager_chan: None,
-            filemanager: FileManager::new(embedder_proxy, Arc::downgrade(&pool_handle)),
+            filemanager: FileManager::new(embedder_proxy.clone(), Arc::downgrade(&pool_handle)),
+            embedder_proxy,
             thread_pool: pool_handle,
             certificate_path,
         }
     }
 
-    /// Exit the core resource manager.
-    pub fn exit(&mut self) {
+    /// Exit the core resource manager, once the keepalive fetches in flight, like beacons,
+    /// are done or `keepalive_timeout` has passed, since they outlive their document.
+    pub fn exit(&mut self, http_state: &HttpState, keepalive_timeout: Duration) {
+        let deadline = Instant::now() + keepalive_timeout;
+        while http_state.has_keepalive_fetches() {
+            if Instant::now() >= deadline {
+                warn!("Exiting with keepalive fetches still in flight");
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
         // Prevents further work from being spawned on the pool,
         // blocks until all workers in the pool are done,
         // or a short timeout has been reached.
@@ -617,6 +778,7 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_chan.clone();
         let filemanager = self.filemanager.clone();
+        let embedder_proxy = self.embedder_proxy.clone();
 
         let timing_type = match request_builder.destination {
             Destination::Document => ResourceTimingType::Navigation,
@@ -647,7 +809,16 @@ impl CoreResourceManager {
             _ => (FileTokenCheck::NotRequired, None),
         };
 
-        self.thread_pool.spawn(move || {
+        let priority = scheduling_priority(&request);
+
+        // Keepalive fetches are counted from now on, since the exit has to wait for the ones
+        // that didn't get a worker yet too.
+        let keep_alive = request.keep_alive;
+        if keep_alive {
+            http_state.begin_keepalive_fetch();
+        }
+
+        self.thread_pool.spawn_with_priority(priority, move || {
             // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
             // todo load context / mimesniff in fetch
             // todo referrer policy?
@@ -658,6 +829,7 @@ impl CoreResourceManager {
                 devtools_chan: dc,
                 filemanager: filemanager,
                 file_token,
+                embedder_proxy,
                 cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(cancel_chan))),
                 timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(request.timing_type()))),
             };
@@ -678,6 +850,10 @@ impl CoreResourceManager {
                 None => fetch(&mut request, &mut sender, &context),
             };
 
+            if keep_alive {
+                context.state.end_keepalive_fetch();
+            }
+
             // Remove token after fetch.
             if let Some(id) = blob_url_file_id.as_ref() {
                 context
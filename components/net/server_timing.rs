@@ -0,0 +1,124 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Parsing of the `Server-Timing` header, with which servers describe the
+//! metrics of a response to the resource timing of the page.
+//! <https://w3c.github.io/server-timing/>
+
+use http::HeaderMap;
+use net_traits::ServerTimingMetric;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// The metrics of all the `Server-Timing` headers of a response, in order.
+/// <https://w3c.github.io/server-timing/#the-server-timing-header-field>
+pub fn parse_server_timing(headers: &HeaderMap) -> Vec<ServerTimingMetric> {
+    headers
+        .get_all("server-timing")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(parse_metrics)
+        .collect()
+}
+
+/// Parses a comma separated list of `name *( OWS ";" OWS param )` metrics.
+/// Only the first `dur` and `desc` parameters of a metric are used, and
+/// metrics without a valid name are skipped.
+fn parse_metrics(input: &str) -> Vec<ServerTimingMetric> {
+    let mut metrics = vec![];
+    let mut chars = input.chars().peekable();
+    loop {
+        skip_whitespace(&mut chars);
+        let name = parse_token(&mut chars);
+        if !name.is_empty() {
+            let mut metric = ServerTimingMetric {
+                name,
+                duration: 0.,
+                description: String::new(),
+            };
+            let (mut has_duration, mut has_description) = (false, false);
+            loop {
+                skip_whitespace(&mut chars);
+                if chars.peek() != Some(&';') {
+                    break;
+                }
+                chars.next();
+                skip_whitespace(&mut chars);
+                let param = parse_token(&mut chars).to_ascii_lowercase();
+                skip_whitespace(&mut chars);
+                let mut value = String::new();
+                if chars.peek() == Some(&'=') {
+                    chars.next();
+                    skip_whitespace(&mut chars);
+                    value = if chars.peek() == Some(&'"') {
+                        parse_quoted_string(&mut chars)
+                    } else {
+                        parse_token(&mut chars)
+                    };
+                }
+                match &*param {
+                    "dur" if !has_duration => {
+                        has_duration = true;
+                        metric.duration = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|duration| duration.is_finite())
+                            .unwrap_or(0.);
+                    },
+                    "desc" if !has_description => {
+                        has_description = true;
+                        metric.description = value;
+                    },
+                    _ => {},
+                }
+            }
+            metrics.push(metric);
+        }
+        // Skip anything up to the next metric.
+        while let Some(c) = chars.next() {
+            if c == ',' {
+                break;
+            }
+        }
+        if chars.peek().is_none() {
+            return metrics;
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |&c| c == ' ' || c == '\t') {
+        chars.next();
+    }
+}
+
+/// <https://tools.ietf.org/html/rfc7230#section-3.2.6>
+fn parse_token(chars: &mut Peekable<Chars>) -> String {
+    let mut token = String::new();
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c) {
+            token.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    token
+}
+
+/// Parses a quoted string, the opening quote included. An unterminated
+/// string runs to the end of the input.
+/// <https://tools.ietf.org/html/rfc7230#section-3.2.6>
+fn parse_quoted_string(chars: &mut Peekable<Chars>) -> String {
+    chars.next();
+    let mut string = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => string.extend(chars.next()),
+            '"' => break,
+            c => string.push(c),
+        }
+    }
+    string
+}
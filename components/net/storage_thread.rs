@@ -5,15 +5,14 @@
 use crate::resource_thread;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use servo_config::pref;
 use servo_url::ServoUrl;
 use std::borrow::ToOwned;
 use std::collections::BTreeMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::thread;
 
-const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
-
 pub trait StorageThreadFactory {
     fn new(config_dir: Option<PathBuf>) -> Self;
 }
@@ -36,19 +35,29 @@ struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
     session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
+    /// The origins whose storage is persistent, rather than best-effort.
+    /// <https://storage.spec.whatwg.org/#bucket-mode>
+    persisted_origins: HashSet<String>,
     config_dir: Option<PathBuf>,
 }
 
 impl StorageManager {
     fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
         let mut local_data = HashMap::new();
+        let mut persisted_origins = HashSet::new();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
+            resource_thread::read_json_from_file(
+                &mut persisted_origins,
+                config_dir,
+                "persisted_origins.json",
+            );
         }
         StorageManager {
             port: port,
             session_data: HashMap::new(),
             local_data: local_data,
+            persisted_origins: persisted_origins,
             config_dir: config_dir,
         }
     }
@@ -82,6 +91,12 @@ impl StorageManager {
                     self.clear(sender, url, storage_type);
                     self.save_state()
                 },
+                StorageThreadMsg::Estimate(sender, url) => self.estimate(sender, url),
+                StorageThreadMsg::Persisted(sender, url) => self.persisted(sender, url),
+                StorageThreadMsg::Persist(sender, url) => {
+                    self.persist(sender, url);
+                    self.save_state()
+                },
                 StorageThreadMsg::Exit(sender) => {
                     // Nothing to do since we save localstorage set eagerly.
                     let _ = sender.send(());
@@ -94,9 +109,36 @@ impl StorageManager {
     fn save_state(&self) {
         if let Some(ref config_dir) = self.config_dir {
             resource_thread::write_json_to_file(&self.local_data, config_dir, "local_data.json");
+            resource_thread::write_json_to_file(
+                &self.persisted_origins,
+                config_dir,
+                "persisted_origins.json",
+            );
         }
     }
 
+    /// The number of bytes stored by `origin`, which its quota bounds.
+    /// Session storage counts too, so that it can't be used to get around
+    /// the quota.
+    // TODO: Count IndexedDB and the Cache API once they're implemented.
+    fn usage(&self, origin: &str) -> usize {
+        let local_data_size = self.local_data.get(origin).map_or(0, |&(total, _)| total);
+        let session_data_size = self.session_data.get(origin).map_or(0, |&(total, _)| total);
+        local_data_size + session_data_size
+    }
+
+    /// The number of bytes `origin` can store.
+    ///
+    /// This is the limit `set_item` enforces, the same for every origin, which
+    /// the `dom.storage.quota` pref sets in bytes. It isn't an estimate of the
+    /// space actually left to store data, which the spec suggests basing on
+    /// the disk space available and whether the storage of the origin is
+    /// persistent.
+    // TODO: Derive the quota from the disk space available to the profile.
+    fn quota(&self, _origin: &str) -> usize {
+        pref!(dom.storage.quota).max(0) as usize
+    }
+
     fn select_data(
         &self,
         storage_type: StorageType,
@@ -165,16 +207,8 @@ impl StorageManager {
     ) {
         let origin = self.origin_as_string(url);
 
-        let (this_storage_size, other_storage_size) = {
-            let local_data = self.select_data(StorageType::Local);
-            let session_data = self.select_data(StorageType::Session);
-            let local_data_size = local_data.get(&origin).map_or(0, |&(total, _)| total);
-            let session_data_size = session_data.get(&origin).map_or(0, |&(total, _)| total);
-            match storage_type {
-                StorageType::Local => (local_data_size, session_data_size),
-                StorageType::Session => (session_data_size, local_data_size),
-            }
-        };
+        let usage = self.usage(&origin);
+        let quota = self.quota(&origin);
 
         let data = self.select_data_mut(storage_type);
         if !data.contains_key(&origin) {
@@ -184,14 +218,14 @@ impl StorageManager {
         let message = data
             .get_mut(&origin)
             .map(|&mut (ref mut total, ref mut entry)| {
-                let mut new_total_size = this_storage_size + value.as_bytes().len();
+                let mut new_total_size = *total + value.as_bytes().len();
                 if let Some(old_value) = entry.get(&name) {
                     new_total_size -= old_value.as_bytes().len();
                 } else {
                     new_total_size += name.as_bytes().len();
                 }
 
-                if (new_total_size + other_storage_size) > QUOTA_SIZE_LIMIT {
+                if usage - *total + new_total_size > quota {
                     return Err(());
                 }
 
@@ -270,6 +304,32 @@ impl StorageManager {
             .unwrap();
     }
 
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-estimate>
+    fn estimate(&self, sender: IpcSender<(u64, u64)>, url: ServoUrl) {
+        let origin = self.origin_as_string(url);
+        let usage = self.usage(&origin) as u64;
+        let quota = self.quota(&origin) as u64;
+        sender.send((usage, quota)).unwrap();
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persisted>
+    fn persisted(&self, sender: IpcSender<bool>, url: ServoUrl) {
+        let origin = self.origin_as_string(url);
+        sender
+            .send(self.persisted_origins.contains(&origin))
+            .unwrap();
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persist>
+    ///
+    /// Script only sends this once the "persistent-storage" permission was
+    /// granted, it isn't checked again here.
+    fn persist(&mut self, sender: IpcSender<bool>, url: ServoUrl) {
+        let origin = self.origin_as_string(url);
+        self.persisted_origins.insert(origin);
+        sender.send(true).unwrap();
+    }
+
     fn origin_as_string(&self, url: ServoUrl) -> String {
         url.origin().ascii_serialization()
     }
@@ -0,0 +1,162 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::content_blocker::{ContentBlocker, RequestType};
+use servo_url::ServoUrl;
+
+fn blocks(list: &str, url: &str, request_type: RequestType, third_party: bool) -> bool {
+    let url = ServoUrl::parse(url).unwrap();
+    ContentBlocker::new(list).matches(&url, request_type, third_party)
+}
+
+#[test]
+fn test_host_anchored_rule() {
+    let list = "! Title: Test\n||ads.example^";
+    assert!(blocks(
+        list,
+        "https://ads.example/a.js",
+        RequestType::Script,
+        true
+    ));
+    assert!(blocks(
+        list,
+        "https://cdn.ads.example:8080/",
+        RequestType::Image,
+        false
+    ));
+    assert!(!blocks(
+        list,
+        "https://badads.example/",
+        RequestType::Image,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://ads.example.com/",
+        RequestType::Image,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://example.com/?ads.example",
+        RequestType::Image,
+        true
+    ));
+}
+
+#[test]
+fn test_wildcards_and_anchors() {
+    let list = "/banner/*/ad.\n|http://tracker.\n.gif|";
+    assert!(blocks(
+        list,
+        "https://a.test/banner/big/ad.png",
+        RequestType::Image,
+        false
+    ));
+    assert!(!blocks(
+        list,
+        "https://a.test/banner/ad.png",
+        RequestType::Image,
+        false
+    ));
+    assert!(blocks(
+        list,
+        "http://tracker.test/",
+        RequestType::Other,
+        false
+    ));
+    assert!(!blocks(
+        list,
+        "https://tracker.test/",
+        RequestType::Other,
+        false
+    ));
+    assert!(blocks(
+        list,
+        "https://a.test/pixel.gif",
+        RequestType::Image,
+        false
+    ));
+    assert!(!blocks(
+        list,
+        "https://a.test/pixel.gif?x",
+        RequestType::Image,
+        false
+    ));
+}
+
+#[test]
+fn test_rule_options() {
+    let list = "||tracker.test^$third-party,script\n||cdn.test^$~image";
+    assert!(blocks(
+        list,
+        "https://tracker.test/t.js",
+        RequestType::Script,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://tracker.test/t.js",
+        RequestType::Script,
+        false
+    ));
+    assert!(!blocks(
+        list,
+        "https://tracker.test/t.png",
+        RequestType::Image,
+        true
+    ));
+    assert!(blocks(
+        list,
+        "https://cdn.test/a.css",
+        RequestType::Stylesheet,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://cdn.test/a.png",
+        RequestType::Image,
+        true
+    ));
+}
+
+#[test]
+fn test_exception_rules() {
+    let list = "||ads.test^\n@@||ads.test/allowed/";
+    assert!(blocks(
+        list,
+        "https://ads.test/ad.js",
+        RequestType::Script,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://ads.test/allowed/ad.js",
+        RequestType::Script,
+        true
+    ));
+}
+
+#[test]
+fn test_ignored_lines() {
+    let list = "[Adblock Plus 2.0]\n! ||comment.test^\nexample.com##.ad\n||unknown.test^$popup";
+    assert!(!blocks(
+        list,
+        "https://comment.test/",
+        RequestType::Script,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://example.com/.ad",
+        RequestType::Script,
+        true
+    ));
+    assert!(!blocks(
+        list,
+        "https://unknown.test/",
+        RequestType::Script,
+        true
+    ));
+}
@@ -2,9 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use ipc_channel::ipc::{self, IpcReceiver};
+use msg::constellation_msg::TEST_PIPELINE_ID;
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
-use net_traits::CookieSource;
+use net_traits::{CookieChange, CookieSource};
 use servo_url::ServoUrl;
 
 #[test]
@@ -475,3 +477,74 @@ fn test_cookie_eviction_all_nonsecure_new_nonsecure() {
         "extra2=bar; extra3=bar; extra4=bar; extra5=bar; foo=bar"
     );
 }
+
+fn observe_changes(storage: &mut CookieStorage, url: &ServoUrl) -> IpcReceiver<CookieChange> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    storage.observe_changes(TEST_PIPELINE_ID, url.clone(), sender);
+    receiver
+}
+
+fn deleted_cookie_name(receiver: &IpcReceiver<CookieChange>) -> String {
+    match receiver.try_recv().unwrap() {
+        CookieChange::Deleted(cookie) => cookie.name().to_owned(),
+        CookieChange::Changed(cookie) => panic!("{} was changed, not deleted", cookie.name()),
+    }
+}
+
+#[test]
+fn test_cookie_changes_report_removals() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("http://home.example.org:8888/cookie-parser?0001").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    add_cookie_to_storage(&mut storage, &url, "baz=qux");
+    let receiver = observe_changes(&mut storage, &url);
+
+    add_cookie_to_storage(
+        &mut storage,
+        &url,
+        "foo=bar; expires=Sun, 18-Apr-2000 21:06:29 GMT",
+    );
+    assert_eq!(deleted_cookie_name(&receiver), "foo");
+
+    let cookie = cookie_rs::Cookie::parse("baz=").unwrap();
+    let cookie = Cookie::new_wrapped(cookie, &url, CookieSource::HTTP).unwrap();
+    assert!(storage.remove(&cookie, &url, CookieSource::HTTP).is_ok());
+    assert_eq!(deleted_cookie_name(&receiver), "baz");
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_cookie_changes_report_cleared_and_evicted_cookies() {
+    let mut storage = CookieStorage::new(2);
+    let url = ServoUrl::parse("http://home.example.org:8888/cookie-parser?0001").unwrap();
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    add_cookie_to_storage(&mut storage, &url, "baz=qux");
+    let receiver = observe_changes(&mut storage, &url);
+
+    add_cookie_to_storage(&mut storage, &url, "quux=corge");
+    assert_eq!(deleted_cookie_name(&receiver), "foo");
+    match receiver.try_recv().unwrap() {
+        CookieChange::Changed(cookie) => assert_eq!(cookie.name(), "quux"),
+        CookieChange::Deleted(cookie) => panic!("{} was deleted, not changed", cookie.name()),
+    }
+
+    storage.clear_storage(&url);
+    let mut names = vec![
+        deleted_cookie_name(&receiver),
+        deleted_cookie_name(&receiver),
+    ];
+    names.sort();
+    assert_eq!(names, vec!["baz", "quux"]);
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_cookie_changes_stop_with_the_pipeline() {
+    let mut storage = CookieStorage::new(5);
+    let url = ServoUrl::parse("http://home.example.org:8888/cookie-parser?0001").unwrap();
+    let receiver = observe_changes(&mut storage, &url);
+    storage.stop_observing_changes(TEST_PIPELINE_ID);
+
+    add_cookie_to_storage(&mut storage, &url, "foo=bar");
+    assert!(receiver.try_recv().is_err());
+}
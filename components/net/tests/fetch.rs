@@ -654,6 +654,36 @@ fn test_fetch_with_local_urls_only() {
     assert!(!local_response.is_network_error());
     assert!(server_response.is_network_error());
 }
+
+#[test]
+fn test_fetch_keepalive_body_limit() {
+    // The body of keepalive requests in flight can't exceed 64 kibibytes.
+
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let do_fetch = |body_size: usize| {
+        let origin = Origin::Origin(url.origin());
+        let mut request = Request::new(url.clone(), Some(origin), None);
+        request.referrer = Referrer::NoReferrer;
+        request.method = Method::POST;
+        request.body = Some(vec![0; body_size]);
+        request.keep_alive = true;
+
+        fetch(&mut request, None)
+    };
+
+    let small_response = do_fetch(64 * 1024);
+    let large_response = do_fetch(64 * 1024 + 1);
+
+    let _ = server.close();
+
+    assert!(!small_response.is_network_error());
+    assert!(large_response.is_network_error());
+}
 // NOTE(emilio): If this test starts failing:
 //
 // openssl req -x509 -nodes -days 3650 -newkey rsa:2048 \
@@ -685,6 +715,7 @@ fn test_fetch_with_hsts() {
         devtools_chan: None,
         filemanager: FileManager::new(create_embedder_proxy(), Weak::new()),
         file_token: FileTokenCheck::NotRequired,
+        embedder_proxy: create_embedder_proxy(),
         cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
@@ -738,6 +769,7 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
         devtools_chan: None,
         filemanager: FileManager::new(create_embedder_proxy(), Weak::new()),
         file_token: FileTokenCheck::NotRequired,
+        embedder_proxy: create_embedder_proxy(),
         cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
@@ -132,3 +132,29 @@ fn test_filemanager() {
         }
     }
 }
+
+#[test]
+fn test_filemanager_select_directory() {
+    let pool = CoreResourceThreadPool::new(1);
+    let pool_handle = Arc::new(pool);
+    let filemanager = FileManager::new(create_embedder_proxy(), Arc::downgrade(&pool_handle));
+    set_pref!(dom.testing.html_input_element.select_files.enabled, true);
+
+    let (tx, rx) = ipc::channel().unwrap();
+    filemanager.handle(FileManagerThreadMsg::SelectDirectory(
+        tx,
+        "test.com".to_string(),
+        Some("tests".to_string()),
+    ));
+    let selected = rx
+        .recv()
+        .expect("Broken channel")
+        .expect("The file manager failed to list the tests directory");
+
+    let jpeg = selected
+        .iter()
+        .find(|file| file.relative_path == "tests/test.jpeg")
+        .expect("test.jpeg is missing from the selected files");
+    assert_eq!(jpeg.filename, PathBuf::from("test.jpeg"));
+    assert_eq!(jpeg.type_string, "image/jpeg".to_string());
+}
@@ -32,7 +32,8 @@ use net::resource_thread::AuthCacheEntry;
 use net::test::replace_host_table;
 use net_traits::request::{CredentialsMode, Destination, RequestBuilder, RequestMode};
 use net_traits::response::ResponseBody;
-use net_traits::{CookieSource, NetworkError, ReferrerPolicy};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::{CookieSource, NetworkConditions, NetworkError, ReferrerPolicy};
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::collections::HashMap;
 use std::io::Write;
@@ -1418,6 +1419,78 @@ fn test_origin_set() {
     let _ = server.close();
 }
 
+#[test]
+fn test_user_agent_override_sets_the_user_agent_header() {
+    let handler = move |request: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        assert_eq!(
+            request.headers().get(header::USER_AGENT).unwrap(),
+            "Overridden/1.0"
+        );
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    context.state.user_agent_overrides.write().unwrap().insert(
+        TEST_PIPELINE_ID,
+        UserAgentOverride {
+            user_agent: Some("Overridden/1.0".to_owned()),
+            ..Default::default()
+        },
+    );
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(response
+        .internal_response
+        .unwrap()
+        .status
+        .unwrap()
+        .0
+        .is_success());
+}
+
+#[test]
+fn test_network_conditions_offline_fails_the_request() {
+    let handler = move |_: HyperRequest<Body>, _: &mut HyperResponse<Body>| {
+        panic!("The request should not reach the server");
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    context.state.network_conditions.write().unwrap().insert(
+        TEST_PIPELINE_ID,
+        NetworkConditions {
+            offline: true,
+            ..Default::default()
+        },
+    );
+
+    let mut request = RequestBuilder::new(url.clone())
+        .method(Method::GET)
+        .body(None)
+        .destination(Destination::Document)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(response.is_network_error());
+}
+
 #[test]
 fn test_determine_request_referrer_shorter_than_4k() {
     let mut headers = HeaderMap::new();
@@ -7,6 +7,7 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod content_blocker;
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
@@ -17,7 +18,9 @@ mod hsts;
 mod http_cache;
 mod http_loader;
 mod mime_classifier;
+mod reporting;
 mod resource_thread;
+mod server_timing;
 mod subresource_integrity;
 
 use crossbeam_channel::{unbounded, Sender};
@@ -98,8 +101,9 @@ fn new_fetch_context(
         state: Arc::new(HttpState::new(tls_config)),
         user_agent: DEFAULT_USER_AGENT.into(),
         devtools_chan: dc,
-        filemanager: FileManager::new(sender, pool_handle.unwrap_or_else(|| Weak::new())),
+        filemanager: FileManager::new(sender.clone(), pool_handle.unwrap_or_else(|| Weak::new())),
         file_token: FileTokenCheck::NotRequired,
+        embedder_proxy: sender,
         cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
@@ -0,0 +1,123 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use net::reporting::{
+    parse_report_to, parse_reporting_endpoints, reporting_endpoints, EndpointGroup, ReportToCache,
+};
+use servo_url::ServoUrl;
+
+fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static(name),
+        HeaderValue::from_static(value),
+    );
+    headers
+}
+
+#[test]
+fn test_parse_reporting_endpoints() {
+    let url = ServoUrl::parse("https://example.com/page").unwrap();
+    let headers = headers(
+        "reporting-endpoints",
+        "default=\"/reports\", csp=\"https://reports.example.com/csp\";x=1, bad=1",
+    );
+    let endpoints = parse_reporting_endpoints(&url, &headers);
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0].name, "default");
+    assert_eq!(endpoints[0].url.as_str(), "https://example.com/reports");
+    assert_eq!(endpoints[1].name, "csp");
+    assert_eq!(endpoints[1].url.as_str(), "https://reports.example.com/csp");
+}
+
+#[test]
+fn test_parse_reporting_endpoints_ignores_untrustworthy_urls() {
+    let url = ServoUrl::parse("https://example.com/page").unwrap();
+    let headers = headers(
+        "reporting-endpoints",
+        "default=\"http://example.com/reports\"",
+    );
+    assert!(parse_reporting_endpoints(&url, &headers).is_empty());
+}
+
+#[test]
+fn test_reporting_endpoints_requires_trustworthy_response() {
+    let url = ServoUrl::parse("http://example.com/page").unwrap();
+    let headers = headers(
+        "reporting-endpoints",
+        "default=\"https://example.com/reports\"",
+    );
+    assert!(reporting_endpoints(&url, &headers, &ReportToCache::new()).is_empty());
+}
+
+#[test]
+fn test_parse_report_to() {
+    let url = ServoUrl::parse("https://example.com/page").unwrap();
+    let headers = headers(
+        "report-to",
+        "{\"group\": \"csp\", \"max_age\": 60, \"endpoints\": [{\"url\": \"/csp\"}]}, \
+         {\"max_age\": 60, \"endpoints\": [{\"url\": \"https://example.com/default\"}]}",
+    );
+    let groups = parse_report_to(&url, &headers);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].name, "csp");
+    assert_eq!(groups[0].urls[0].as_str(), "https://example.com/csp");
+    assert_eq!(groups[1].name, "default");
+    assert_eq!(groups[1].max_age, 60);
+}
+
+#[test]
+fn test_report_to_cache() {
+    let url = ServoUrl::parse("https://example.com/page").unwrap();
+    let mut cache = ReportToCache::new();
+    cache.update_from_response(
+        &url,
+        &headers(
+            "report-to",
+            "{\"group\": \"csp\", \"max_age\": 60, \"endpoints\": [{\"url\": \"/csp\"}]}",
+        ),
+    );
+
+    let endpoints = reporting_endpoints(
+        &url,
+        &headers("reporting-endpoints", "default=\"/reports\""),
+        &cache,
+    );
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0].name, "default");
+    assert_eq!(endpoints[1].name, "csp");
+    assert_eq!(endpoints[1].url.as_str(), "https://example.com/csp");
+
+    // A max_age of zero removes the group.
+    cache.update_from_response(
+        &url,
+        &headers("report-to", "{\"group\": \"csp\", \"max_age\": 0}"),
+    );
+    assert!(cache.endpoints(&url.origin()).is_empty());
+}
+
+#[test]
+fn test_endpoint_group_from_the_future_is_not_expired() {
+    let group = EndpointGroup {
+        name: "default".to_owned(),
+        urls: vec![],
+        max_age: 10,
+        timestamp: time::get_time().sec as u64 + 3600,
+    };
+
+    assert!(!group.is_expired());
+}
+
+#[test]
+fn test_endpoint_group_is_expired_when_it_has_reached_its_max_age() {
+    let group = EndpointGroup {
+        name: "default".to_owned(),
+        urls: vec![],
+        max_age: 10,
+        timestamp: time::get_time().sec as u64 - 20,
+    };
+
+    assert!(group.is_expired());
+}
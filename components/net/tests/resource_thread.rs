@@ -10,6 +10,7 @@ use net_traits::CoreResourceMsg;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan;
 use std::net::IpAddr;
+use std::time::Duration;
 
 fn ip(s: &str) -> IpAddr {
     s.parse().unwrap()
@@ -29,7 +30,9 @@ fn test_exit() {
         None,
         None,
     );
-    resource_thread.send(CoreResourceMsg::Exit(sender)).unwrap();
+    resource_thread
+        .send(CoreResourceMsg::Exit(Duration::from_secs(0), sender))
+        .unwrap();
     receiver.recv().unwrap();
 }
 
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::header::{HeaderMap, HeaderValue};
+use net::server_timing::parse_server_timing;
+use net_traits::ServerTimingMetric;
+
+fn metric(name: &str, duration: f64, description: &str) -> ServerTimingMetric {
+    ServerTimingMetric {
+        name: name.to_owned(),
+        duration,
+        description: description.to_owned(),
+    }
+}
+
+fn headers(values: &[&'static str]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for value in values {
+        headers.append("server-timing", HeaderValue::from_static(value));
+    }
+    headers
+}
+
+#[test]
+fn test_parse_server_timing() {
+    let headers = headers(&["miss, db;dur=53, app;dur=47.2;desc=\"Main, app\""]);
+    assert_eq!(
+        parse_server_timing(&headers),
+        vec![
+            metric("miss", 0., ""),
+            metric("db", 53., ""),
+            metric("app", 47.2, "Main, app"),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_server_timing_of_several_headers() {
+    let headers = headers(&["cache;desc=hit", "total ; DUR = 12"]);
+    assert_eq!(
+        parse_server_timing(&headers),
+        vec![metric("cache", 0., "hit"), metric("total", 12., "")]
+    );
+}
+
+#[test]
+fn test_parse_server_timing_uses_first_parameters() {
+    let headers = headers(&["db;dur=1;dur=2;desc=a;desc=b;extra=\"x\""]);
+    assert_eq!(parse_server_timing(&headers), vec![metric("db", 1., "a")]);
+}
+
+#[test]
+fn test_parse_server_timing_skips_invalid_metrics() {
+    let headers = headers(&["\"quoted\", db;dur=abc, ;dur=1, app \"junk\", last"]);
+    assert_eq!(
+        parse_server_timing(&headers),
+        vec![
+            metric("db", 0., ""),
+            metric("app", 0., ""),
+            metric("last", 0., "")
+        ]
+    );
+}
+
+#[test]
+fn test_parse_server_timing_unescapes_descriptions() {
+    let headers = headers(&["db;desc=\"a \\\"quoted\\\" \\\\ value\""]);
+    assert_eq!(
+        parse_server_timing(&headers),
+        vec![metric("db", 0., "a \"quoted\" \\ value")]
+    );
+}
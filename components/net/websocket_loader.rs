@@ -7,6 +7,7 @@ use crate::cookie::Cookie;
 use crate::fetch::methods::should_be_blocked_due_to_bad_port;
 use crate::hosts::replace_host;
 use crate::http_loader::HttpState;
+use crate::network_conditions::wait_for_network;
 use embedder_traits::resources::{self, Resource};
 use headers::Host;
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
@@ -23,10 +24,11 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
 use url::Url;
+use ws::deflate::DeflateHandler;
 use ws::util::TcpStream;
 use ws::{
-    CloseCode, Factory, Handler, Handshake, Message, Request, Response as WsResponse, Sender,
-    WebSocket,
+    CloseCode, Factory, Frame, Handler, Handshake, Message, OpCode, Request,
+    Response as WsResponse, Sender, WebSocket,
 };
 use ws::{Error as WebSocketError, ErrorKind as WebSocketErrorKind, Result as WebSocketResult};
 
@@ -40,14 +42,17 @@ struct Client<'a> {
     resource_url: &'a ServoUrl,
     event_sender: &'a IpcSender<WebSocketNetworkEvent>,
     protocol_in_use: Option<String>,
+    extensions_in_use: Option<String>,
     certificate_path: Option<String>,
 }
 
 impl<'a> Factory for Client<'a> {
-    type Handler = Self;
+    /// The permessage-deflate extension is negotiated and applied by the
+    /// deflate handler, see https://tools.ietf.org/html/rfc7692.
+    type Handler = DeflateHandler<Self>;
 
     fn connection_made(&mut self, _: Sender) -> Self::Handler {
-        self.clone()
+        DeflateHandler::new(self.clone())
     }
 
     fn connection_lost(&mut self, _: Self::Handler) {
@@ -111,6 +116,7 @@ impl<'a> Handler for Client<'a> {
             .event_sender
             .send(WebSocketNetworkEvent::ConnectionEstablished {
                 protocol_in_use: self.protocol_in_use.clone(),
+                extensions_in_use: self.extensions_in_use.clone(),
             });
         Ok(())
     }
@@ -145,9 +151,27 @@ impl<'a> Handler for Client<'a> {
             }
             self.protocol_in_use = Some(protocol_name.into());
         }
+
+        self.extensions_in_use = res
+            .headers()
+            .iter()
+            .find(|&&(ref name, _)| name.eq_ignore_ascii_case("Sec-WebSocket-Extensions"))
+            .map(|&(_, ref value)| String::from_utf8_lossy(value).into_owned());
         Ok(())
     }
 
+    fn on_send_frame(&mut self, frame: Frame) -> WebSocketResult<Option<Frame>> {
+        // Let script know once a whole message has been handed to the connection,
+        // so that it can update bufferedAmount and send more data.
+        match frame.opcode() {
+            OpCode::Text | OpCode::Binary | OpCode::Continue if frame.is_final() => {
+                let _ = self.event_sender.send(WebSocketNetworkEvent::MessageSent);
+            },
+            _ => {},
+        }
+        Ok(Some(frame))
+    }
+
     fn on_close(&mut self, code: CloseCode, reason: &str) {
         debug!("Connection closing due to ({:?}) {}", code, reason);
         let _ = self.event_sender.send(WebSocketNetworkEvent::Close(
@@ -224,6 +248,14 @@ pub fn init(
                 return;
             }
 
+            if let Some(conditions) = http_state.network_conditions(req_builder.pipeline_id) {
+                if let Err(e) = wait_for_network(&conditions) {
+                    debug!("Failed to establish a WebSocket connection: {:?}", e);
+                    let _ = resource_event_sender.send(WebSocketNetworkEvent::Fail);
+                    return;
+                }
+            }
+
             let host = replace_host(req_builder.url.host_str().unwrap());
             let mut net_url = req_builder.url.clone().into_url();
             net_url.set_host(Some(&host)).unwrap();
@@ -250,6 +282,7 @@ pub fn init(
                 resource_url: &req_builder.url,
                 event_sender: &resource_event_sender,
                 protocol_in_use: None,
+                extensions_in_use: None,
                 certificate_path,
             };
             let mut ws = WebSocket::new(client).unwrap();
@@ -115,6 +115,10 @@ pub struct SelectedFile {
     pub size: u64,
     // https://w3c.github.io/FileAPI/#dfn-type
     pub type_string: String,
+    /// The path of the file relative to the parent of the selected directory,
+    /// or empty if the file was selected by itself.
+    // https://wicg.github.io/entries-api/#dom-file-webkitrelativepath
+    pub relative_path: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -135,6 +139,18 @@ pub enum FileManagerThreadMsg {
         Option<Vec<String>>,
     ),
 
+    /// Select a directory and all the files in its tree. Last field is pre-selected
+    /// directory path for testing
+    SelectDirectory(
+        IpcSender<FileManagerResult<Vec<SelectedFile>>>,
+        FileOrigin,
+        Option<String>,
+    ),
+
+    /// Add entries for files on the disk that the user dropped on a document,
+    /// skipping the ones that can't be opened
+    AddDroppedFiles(Vec<PathBuf>, IpcSender<Vec<SelectedFile>>, FileOrigin),
+
     /// Read FileID-indexed file in chunks, optionally check URL validity based on boolean flag
     ReadFile(
         IpcSender<FileManagerResult<ReadFileProgress>>,
@@ -14,11 +14,14 @@ extern crate malloc_size_of;
 extern crate malloc_size_of_derive;
 #[macro_use]
 extern crate serde;
+#[macro_use]
+extern crate servo_config;
 
 use crate::filemanager_thread::FileManagerThreadMsg;
 use crate::request::{Request, RequestBuilder};
 use crate::response::{HttpsState, Response, ResponseInit};
 use crate::storage_thread::StorageThreadMsg;
+use crate::user_agent_data::UserAgentOverride;
 use cookie::Cookie;
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap};
@@ -29,8 +32,9 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use ipc_channel::Error as IpcError;
 use mime::Mime;
-use msg::constellation_msg::HistoryStateId;
+use msg::constellation_msg::{HistoryStateId, PipelineId, TopLevelBrowsingContextId};
 use servo_url::ServoUrl;
+use std::time::Duration;
 use time::precise_time_ns;
 use webrender_api::ImageKey;
 
@@ -42,6 +46,7 @@ pub mod quality;
 pub mod request;
 pub mod response;
 pub mod storage_thread;
+pub mod user_agent_data;
 
 /// Image handling.
 ///
@@ -370,8 +375,13 @@ pub enum WebSocketDomAction {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebSocketNetworkEvent {
-    ConnectionEstablished { protocol_in_use: Option<String> },
+    ConnectionEstablished {
+        protocol_in_use: Option<String>,
+        extensions_in_use: Option<String>,
+    },
     MessageReceived(MessageData),
+    /// The last frame of an outgoing message was handed to the connection.
+    MessageSent,
     Close(Option<u16>, String),
     Fail,
 }
@@ -392,6 +402,22 @@ pub enum FetchChannels {
     Prefetch,
 }
 
+/// The conditions that the network of a webview is emulated with, to test
+/// how pages behave on slow or unreachable networks. The missing values are
+/// the ones of an unthrottled network.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct NetworkConditions {
+    /// Whether the requests fail as if the network couldn't be reached.
+    pub offline: bool,
+    /// The latency added to each request, in milliseconds.
+    pub latency: u64,
+    /// The maximum throughput of the response bodies, in bytes per second.
+    pub download_throughput: Option<u64>,
+    /// The maximum throughput of the request bodies, in bytes per second.
+    pub upload_throughput: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum CoreResourceMsg {
     Fetch(RequestBuilder, FetchChannels),
@@ -415,6 +441,23 @@ pub enum CoreResourceMsg {
         CookieSource,
     ),
     DeleteCookies(ServoUrl),
+    /// Report the changes to the cookies visible to non-HTTP APIs at a given URL
+    /// to a pipeline
+    ObserveCookieChanges(PipelineId, ServoUrl, IpcSender<CookieChange>),
+    /// Stop reporting the changes to the cookies to a pipeline
+    StopObservingCookieChanges(PipelineId),
+    /// Block or allow the cookies of the requests of a pipeline, which are
+    /// blocked for the third-party documents without storage access
+    SetCookiesBlocked(PipelineId, bool),
+    /// Filter the requests of a pipeline with the content blocker, reporting
+    /// the blocked ones to the given webview, or stop filtering them
+    SetContentBlocking(PipelineId, Option<TopLevelBrowsingContextId>),
+    /// Make the requests of a pipeline with the user agent its webview is
+    /// overridden with, or with the default one again
+    SetUserAgentOverride(PipelineId, Option<UserAgentOverride>),
+    /// Make the requests of a pipeline under the network conditions its
+    /// webview is emulated with, or under the real ones again
+    SetNetworkConditions(PipelineId, Option<NetworkConditions>),
     /// Get a history state by a given history state id
     GetHistoryState(HistoryStateId, IpcSender<Option<Vec<u8>>>),
     /// Set a history state for a given history state id
@@ -428,8 +471,9 @@ pub enum CoreResourceMsg {
     /// Message forwarded to file manager's handler
     ToFileManager(FileManagerThreadMsg),
     /// Break the load handler loop, send a reply when done cleaning up local resources
-    /// and exit
-    Exit(IpcSender<()>),
+    /// and exit. The keepalive fetches in flight are given up to the duration to complete,
+    /// and the state is saved to disk after them.
+    Exit(Duration, IpcSender<()>),
 }
 
 /// Instruct the resource thread to make a new request.
@@ -461,6 +505,7 @@ pub struct ResourceCorsData {
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct ResourceFetchTiming {
     pub domain_lookup_start: u64,
+    pub domain_lookup_end: u64,
     pub timing_check_passed: bool,
     pub timing_type: ResourceTimingType,
     /// Number of redirects until final resource (currently limited to 20)
@@ -475,6 +520,24 @@ pub struct ResourceFetchTiming {
     pub connect_start: u64,
     pub connect_end: u64,
     pub start_time: u64,
+    /// The size of the response as fetched from the network, headers included.
+    pub transfer_size: u64,
+    /// The size of the response body before any content codings are removed.
+    pub encoded_body_size: u64,
+    /// The size of the response body after any content codings are removed.
+    pub decoded_body_size: u64,
+    /// The metrics of the `Server-Timing` headers of the response.
+    pub server_timing: Vec<ServerTimingMetric>,
+}
+
+/// A metric that the server reported in a `Server-Timing` header.
+/// <https://w3c.github.io/server-timing/#the-server-timing-header-field>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct ServerTimingMetric {
+    pub name: String,
+    /// The duration in milliseconds, or zero when there is none.
+    pub duration: f64,
+    pub description: String,
 }
 
 pub enum RedirectStartValue {
@@ -500,16 +563,23 @@ pub enum ResourceTimeValue {
 pub enum ResourceAttribute {
     RedirectCount(u16),
     DomainLookupStart,
+    DomainLookupEnd,
     RequestStart,
     ResponseStart,
     RedirectStart(RedirectStartValue),
     RedirectEnd(RedirectEndValue),
     FetchStart,
-    ConnectStart(u64),
-    ConnectEnd(u64),
+    ConnectStart,
+    ConnectEnd,
     SecureConnectionStart,
     ResponseEnd,
     StartTime(ResourceTimeValue),
+    /// The encoded and decoded sizes of a body received from the network.
+    BodySize {
+        encoded: u64,
+        decoded: u64,
+    },
+    ServerTiming(Vec<ServerTimingMetric>),
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
@@ -526,6 +596,7 @@ impl ResourceFetchTiming {
             timing_type: timing_type,
             timing_check_passed: true,
             domain_lookup_start: 0,
+            domain_lookup_end: 0,
             redirect_count: 0,
             secure_connection_start: 0,
             request_start: 0,
@@ -537,11 +608,15 @@ impl ResourceFetchTiming {
             connect_end: 0,
             response_end: 0,
             start_time: 0,
+            transfer_size: 0,
+            encoded_body_size: 0,
+            decoded_body_size: 0,
+            server_timing: vec![],
         }
     }
 
-    // TODO currently this is being set with precise time ns when it should be time since
-    // time origin (as described in Performance::now)
+    /// Times are recorded with `precise_time_ns`, and made relative to the
+    /// time origin by the `PerformanceResourceTiming` they end up in.
     pub fn set_attribute(&mut self, attribute: ResourceAttribute) {
         let should_attribute_always_be_updated = match attribute {
             ResourceAttribute::FetchStart |
@@ -554,6 +629,7 @@ impl ResourceFetchTiming {
         }
         match attribute {
             ResourceAttribute::DomainLookupStart => self.domain_lookup_start = precise_time_ns(),
+            ResourceAttribute::DomainLookupEnd => self.domain_lookup_end = precise_time_ns(),
             ResourceAttribute::RedirectCount(count) => self.redirect_count = count,
             ResourceAttribute::RequestStart => self.request_start = precise_time_ns(),
             ResourceAttribute::ResponseStart => self.response_start = precise_time_ns(),
@@ -570,8 +646,8 @@ impl ResourceFetchTiming {
                 RedirectEndValue::ResponseEnd => self.redirect_end = self.response_end,
             },
             ResourceAttribute::FetchStart => self.fetch_start = precise_time_ns(),
-            ResourceAttribute::ConnectStart(val) => self.connect_start = val,
-            ResourceAttribute::ConnectEnd(val) => self.connect_end = val,
+            ResourceAttribute::ConnectStart => self.connect_start = precise_time_ns(),
+            ResourceAttribute::ConnectEnd => self.connect_end = precise_time_ns(),
             ResourceAttribute::SecureConnectionStart => {
                 self.secure_connection_start = precise_time_ns()
             },
@@ -581,6 +657,14 @@ impl ResourceFetchTiming {
                     if self.redirect_start == 0 || !self.timing_check_passed => {},
                 _ => self.start_time = self.get_time_value(val),
             },
+            ResourceAttribute::BodySize { encoded, decoded } => {
+                // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-transfersize
+                // approximates the size of the headers as 300 octets.
+                self.transfer_size = encoded + 300;
+                self.encoded_body_size = encoded;
+                self.decoded_body_size = decoded;
+            },
+            ResourceAttribute::ServerTiming(metrics) => self.server_timing = metrics,
         }
     }
 
@@ -596,15 +680,32 @@ impl ResourceFetchTiming {
     pub fn mark_timing_check_failed(&mut self) {
         self.timing_check_passed = false;
         self.domain_lookup_start = 0;
+        self.domain_lookup_end = 0;
         self.redirect_count = 0;
         self.request_start = 0;
         self.response_start = 0;
         self.redirect_start = 0;
         self.connect_start = 0;
         self.connect_end = 0;
+        self.secure_connection_start = 0;
+        self.transfer_size = 0;
+        self.encoded_body_size = 0;
+        self.decoded_body_size = 0;
+        self.server_timing.clear();
     }
 }
 
+/// An [endpoint](https://w3c.github.io/reporting/#endpoint) reports can be
+/// delivered to, as configured by the `Reporting-Endpoints` or `Report-To`
+/// headers of a response.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct ReportingEndpoint {
+    /// The name reports refer to the endpoint by.
+    pub name: String,
+    /// The URL the reports are POSTed to.
+    pub url: ServoUrl,
+}
+
 /// Metadata about a loaded resource, such as is obtained from HTTP headers.
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct Metadata {
@@ -638,6 +739,9 @@ pub struct Metadata {
     pub referrer_policy: Option<ReferrerPolicy>,
     /// Performance information for navigation events
     pub timing: Option<ResourceFetchTiming>,
+
+    /// The endpoints reports about the resource can be delivered to.
+    pub reporting_endpoints: Vec<ReportingEndpoint>,
 }
 
 impl Metadata {
@@ -655,6 +759,7 @@ impl Metadata {
             referrer: None,
             referrer_policy: None,
             timing: None,
+            reporting_endpoints: vec![],
         }
     }
 
@@ -686,6 +791,17 @@ pub enum CookieSource {
     NonHTTP,
 }
 
+/// A change to the cookies visible to non-HTTP APIs at a URL
+#[derive(Debug, Deserialize, Serialize)]
+pub enum CookieChange {
+    /// The cookie was stored, either as a new cookie or in place of the
+    /// cookie with the same name, domain and path
+    Changed(Serde<Cookie<'static>>),
+    /// The cookie was removed, either by an expired cookie with the same
+    /// name, domain and path, or because it expired or was evicted
+    Deleted(Serde<Cookie<'static>>),
+}
+
 /// Network errors that have to be exported out of the loaders
 #[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
 pub enum NetworkError {
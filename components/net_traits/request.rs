@@ -114,6 +114,16 @@ pub enum ParserMetadata {
     NotParserInserted,
 }
 
+/// A request's [priority](https://fetch.spec.whatwg.org/#request-priority),
+/// as given by the `fetchpriority` attribute or the `priority` member of
+/// `RequestInit`.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum RequestPriority {
+    High,
+    Low,
+    Auto,
+}
+
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct RequestBuilder {
     #[serde(
@@ -131,9 +141,11 @@ pub struct RequestBuilder {
     pub headers: HeaderMap,
     pub unsafe_request: bool,
     pub body: Option<Vec<u8>>,
+    pub keep_alive: bool,
     pub service_workers_mode: ServiceWorkersMode,
     // TODO: client object
     pub destination: Destination,
+    pub priority: RequestPriority,
     pub synchronous: bool,
     pub mode: RequestMode,
     pub cache_mode: CacheMode,
@@ -166,8 +178,10 @@ impl RequestBuilder {
             headers: HeaderMap::new(),
             unsafe_request: false,
             body: None,
+            keep_alive: false,
             service_workers_mode: ServiceWorkersMode::All,
             destination: Destination::None,
+            priority: RequestPriority::Auto,
             synchronous: false,
             mode: RequestMode::NoCors,
             cache_mode: CacheMode::Default,
@@ -212,11 +226,21 @@ impl RequestBuilder {
         self
     }
 
+    pub fn keep_alive(mut self, keep_alive: bool) -> RequestBuilder {
+        self.keep_alive = keep_alive;
+        self
+    }
+
     pub fn destination(mut self, destination: Destination) -> RequestBuilder {
         self.destination = destination;
         self
     }
 
+    pub fn priority(mut self, priority: RequestPriority) -> RequestBuilder {
+        self.priority = priority;
+        self
+    }
+
     pub fn synchronous(mut self, synchronous: bool) -> RequestBuilder {
         self.synchronous = synchronous;
         self
@@ -288,8 +312,10 @@ impl RequestBuilder {
         request.headers = self.headers;
         request.unsafe_request = self.unsafe_request;
         request.body = self.body;
+        request.keep_alive = self.keep_alive;
         request.service_workers_mode = self.service_workers_mode;
         request.destination = self.destination;
+        request.priority = self.priority;
         request.synchronous = self.synchronous;
         request.mode = self.mode;
         request.use_cors_preflight = self.use_cors_preflight;
@@ -341,7 +367,8 @@ pub struct Request {
     pub initiator: Initiator,
     /// <https://fetch.spec.whatwg.org/#concept-request-destination>
     pub destination: Destination,
-    // TODO: priority object
+    /// <https://fetch.spec.whatwg.org/#request-priority>
+    pub priority: RequestPriority,
     /// <https://fetch.spec.whatwg.org/#concept-request-origin>
     pub origin: Origin,
     /// <https://fetch.spec.whatwg.org/#concept-request-referrer>
@@ -396,6 +423,7 @@ impl Request {
             service_workers_mode: ServiceWorkersMode::All,
             initiator: Initiator::None,
             destination: Destination::None,
+            priority: RequestPriority::Auto,
             origin: origin.unwrap_or(Origin::Client),
             referrer: Referrer::Client,
             referrer_policy: None,
@@ -5,7 +5,7 @@
 //! The [Response](https://fetch.spec.whatwg.org/#responses) object
 //! resulting from a [fetch operation](https://fetch.spec.whatwg.org/#concept-fetch)
 use crate::{FetchMetadata, FilteredMetadata, Metadata, NetworkError, ReferrerPolicy};
-use crate::{ResourceFetchTiming, ResourceTimingType};
+use crate::{ReportingEndpoint, ResourceFetchTiming, ResourceTimingType};
 use headers::{ContentType, HeaderMapExt};
 use http::{HeaderMap, StatusCode};
 use hyper_serde::Serde;
@@ -116,6 +116,8 @@ pub struct Response {
     /// track network metrics
     #[ignore_malloc_size_of = "Mutex heap size undefined"]
     pub resource_timing: Arc<Mutex<ResourceFetchTiming>>,
+    /// The endpoints reports about this response can be delivered to.
+    pub reporting_endpoints: Vec<ReportingEndpoint>,
 }
 
 impl Response {
@@ -139,6 +141,7 @@ impl Response {
             return_internal: true,
             aborted: Arc::new(AtomicBool::new(false)),
             resource_timing: Arc::new(Mutex::new(resource_timing)),
+            reporting_endpoints: vec![],
         }
     }
 
@@ -175,6 +178,7 @@ impl Response {
             resource_timing: Arc::new(Mutex::new(ResourceFetchTiming::new(
                 ResourceTimingType::Error,
             ))),
+            reporting_endpoints: vec![],
         }
     }
 
@@ -310,6 +314,7 @@ impl Response {
             metadata.https_state = response.https_state;
             metadata.referrer = response.referrer.clone();
             metadata.referrer_policy = response.referrer_policy.clone();
+            metadata.reporting_endpoints = response.reporting_endpoints.clone();
             metadata
         };
 
@@ -41,6 +41,15 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, ServoUrl, StorageType),
 
+    /// gets the number of bytes stored by the origin of the url, and its quota
+    Estimate(IpcSender<(u64, u64)>, ServoUrl),
+
+    /// gets whether the storage of the origin of the url is persistent
+    Persisted(IpcSender<bool>, ServoUrl),
+
+    /// makes the storage of the origin of the url persistent
+    Persist(IpcSender<bool>, ServoUrl),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
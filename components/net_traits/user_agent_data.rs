@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The information about the user agent that the User-Agent Client Hints
+//! expose, which the embedder can change with the `network.client_hints`
+//! preferences. The empty ones stand for the values of the build.
+//! <https://wicg.github.io/ua-client-hints/#user-agent-metadata>
+
+/// The brand that is added to the brand lists so that sites don't rely on
+/// their order or contents.
+/// <https://wicg.github.io/ua-client-hints/#create-arbitrary-brands-section>
+const GREASE_BRAND: &str = "Not A(Brand";
+const GREASE_VERSION: &str = "99";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BrandVersion {
+    pub brand: String,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UserAgentMetadata {
+    /// The brands with their significant versions.
+    pub brands: Vec<BrandVersion>,
+    /// The brands with their full versions.
+    pub full_version_list: Vec<BrandVersion>,
+    pub full_version: String,
+    pub platform: String,
+    pub platform_version: String,
+    pub architecture: String,
+    pub model: String,
+    pub mobile: bool,
+    pub bitness: String,
+    pub wow64: bool,
+}
+
+/// What a webview presents itself as instead of the user agent of the build,
+/// which the embedder sets for compatibility testing and for the sites that
+/// need a specific user agent. The missing values are the default ones.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct UserAgentOverride {
+    /// The `User-Agent` header and `navigator.userAgent`.
+    pub user_agent: Option<String>,
+    /// `navigator.platform`.
+    pub platform: Option<String>,
+    /// The User-Agent Client Hints and `navigator.userAgentData`.
+    pub metadata: Option<UserAgentMetadata>,
+}
+
+impl UserAgentMetadata {
+    /// The metadata of a webview, which is the one of the preferences unless
+    /// the webview overrides it.
+    pub fn overridden_by(ua_override: Option<&UserAgentOverride>) -> UserAgentMetadata {
+        ua_override
+            .and_then(|ua_override| ua_override.metadata.clone())
+            .unwrap_or_else(UserAgentMetadata::from_prefs)
+    }
+
+    pub fn from_prefs() -> UserAgentMetadata {
+        let brand = pref!(network.client_hints.brand);
+        let full_version = or_default(
+            pref!(network.client_hints.full_version),
+            env!("CARGO_PKG_VERSION"),
+        );
+        let significant_version = full_version.split('.').next().unwrap_or("").to_owned();
+        let brand_list = |version: &str| {
+            vec![
+                BrandVersion {
+                    brand: GREASE_BRAND.to_owned(),
+                    version: GREASE_VERSION.to_owned(),
+                },
+                BrandVersion {
+                    brand: brand.clone(),
+                    version: version.to_owned(),
+                },
+            ]
+        };
+        UserAgentMetadata {
+            brands: brand_list(&significant_version),
+            full_version_list: brand_list(&full_version),
+            platform: or_default(pref!(network.client_hints.platform), default_platform()),
+            platform_version: pref!(network.client_hints.platform_version),
+            architecture: or_default(
+                pref!(network.client_hints.architecture),
+                default_architecture(),
+            ),
+            model: pref!(network.client_hints.model),
+            mobile: pref!(network.client_hints.mobile),
+            bitness: or_default(pref!(network.client_hints.bitness), default_bitness()),
+            wow64: false,
+            full_version,
+        }
+    }
+}
+
+fn or_default(value: String, default: &str) -> String {
+    if value.is_empty() {
+        default.to_owned()
+    } else {
+        value
+    }
+}
+
+fn default_platform() -> &'static str {
+    if cfg!(target_os = "android") {
+        "Android"
+    } else if cfg!(target_os = "linux") {
+        "Linux"
+    } else if cfg!(target_os = "macos") {
+        "macOS"
+    } else if cfg!(target_os = "ios") {
+        "iOS"
+    } else if cfg!(target_os = "windows") {
+        "Windows"
+    } else {
+        "Unknown"
+    }
+}
+
+fn default_architecture() -> &'static str {
+    if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+        "x86"
+    } else if cfg!(any(target_arch = "arm", target_arch = "aarch64")) {
+        "arm"
+    } else {
+        ""
+    }
+}
+
+fn default_bitness() -> &'static str {
+    if cfg!(target_pointer_width = "64") {
+        "64"
+    } else {
+        "32"
+    }
+}
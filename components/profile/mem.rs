@@ -8,7 +8,8 @@ use crate::time::duration_from_seconds;
 use ipc_channel::ipc::{self, IpcReceiver};
 use ipc_channel::router::ROUTER;
 use profile_traits::mem::ReportsChan;
-use profile_traits::mem::{ProfilerChan, ProfilerMsg, ReportKind, Reporter, ReporterRequest};
+use profile_traits::mem::{ProfilerChan, ProfilerMsg, Report, ReportKind};
+use profile_traits::mem::{Reporter, ReporterRequest};
 use std::borrow::ToOwned;
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -121,6 +122,11 @@ impl Profiler {
                 true
             },
 
+            ProfilerMsg::CollectReports(chan) => {
+                chan.send(self.collect_reports());
+                true
+            },
+
             ProfilerMsg::Exit => false,
         }
     }
@@ -130,6 +136,20 @@ impl Profiler {
         println!("Begin memory reports {}", elapsed.as_secs());
         println!("|");
 
+        let mut forest = ReportsForest::new();
+        for report in self.collect_reports() {
+            forest.insert(&report.path, report.size);
+        }
+        forest.print();
+
+        println!("|");
+        println!("End memory reports");
+        println!("");
+    }
+
+    /// Collects the reports of all the registered reporters. The paths of the reports of explicit
+    /// allocations start with "explicit".
+    fn collect_reports(&self) -> Vec<Report> {
         // Collect reports from memory reporters.
         //
         // This serializes the report-gathering. It might be worth creating a new scoped thread for
@@ -141,7 +161,7 @@ impl Profiler {
         // use that to compute the special "jemalloc-heap-unclassified" and
         // "system-heap-unclassified" values.
 
-        let mut forest = ReportsForest::new();
+        let mut result = vec![];
 
         let mut jemalloc_heap_reported_size = 0;
         let mut system_heap_reported_size = 0;
@@ -152,8 +172,8 @@ impl Profiler {
         for reporter in self.reporters.values() {
             let (chan, port) = ipc::channel().unwrap();
             reporter.collect_reports(ReportsChan(chan));
-            if let Ok(mut reports) = port.recv() {
-                for report in &mut reports {
+            if let Ok(reports) = port.recv() {
+                for mut report in reports {
                     // Add "explicit" to the start of the path, when appropriate.
                     match report.kind {
                         ReportKind::ExplicitJemallocHeapSize |
@@ -187,31 +207,28 @@ impl Profiler {
                         }
                     }
 
-                    // Insert the report.
-                    forest.insert(&report.path, report.size);
+                    result.push(report);
                 }
             }
         }
 
         // Compute and insert the heap-unclassified values.
         if let Some(jemalloc_heap_allocated_size) = jemalloc_heap_allocated_size {
-            forest.insert(
-                &path!["explicit", "jemalloc-heap-unclassified"],
-                jemalloc_heap_allocated_size - jemalloc_heap_reported_size,
-            );
+            result.push(Report {
+                path: path!["explicit", "jemalloc-heap-unclassified"],
+                kind: ReportKind::ExplicitJemallocHeapSize,
+                size: jemalloc_heap_allocated_size - jemalloc_heap_reported_size,
+            });
         }
         if let Some(system_heap_allocated_size) = system_heap_allocated_size {
-            forest.insert(
-                &path!["explicit", "system-heap-unclassified"],
-                system_heap_allocated_size - system_heap_reported_size,
-            );
+            result.push(Report {
+                path: path!["explicit", "system-heap-unclassified"],
+                kind: ReportKind::ExplicitSystemHeapSize,
+                size: system_heap_allocated_size - system_heap_reported_size,
+            });
         }
 
-        forest.print();
-
-        println!("|");
-        println!("End memory reports");
-        println!("");
+        result
     }
 }
 
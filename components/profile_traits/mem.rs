@@ -203,6 +203,10 @@ pub enum ProfilerMsg {
     /// Triggers printing of the memory profiling metrics.
     Print,
 
+    /// Collects the reports of all registered reporters and sends them on the channel, the way
+    /// they would be printed.
+    CollectReports(ReportsChan),
+
     /// Tells the memory profiler to shut down.
     Exit,
 }
@@ -7,6 +7,7 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::Ca
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasImageSource;
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasLineCap;
 use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasLineJoin;
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrix2DInit;
 use crate::dom::bindings::codegen::Bindings::ImageDataBinding::ImageDataMethods;
 use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
@@ -17,6 +18,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::canvasgradient::{CanvasGradient, CanvasGradientStyle, ToFillOrStrokeStyle};
 use crate::dom::canvaspattern::CanvasPattern;
 use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::dommatrixreadonly::dommatrix2dinit_to_matrix;
 use crate::dom::element::cors_setting_for_element;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
@@ -1442,6 +1444,17 @@ impl CanvasState {
         self.update_transform()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-settransform-matrix
+    pub fn set_transform_(&self, transform: &DOMMatrix2DInit) -> ErrorResult {
+        // Step 1.
+        let matrix = dommatrix2dinit_to_matrix(transform)?;
+        // Steps 2-3.
+        self.set_transform(
+            matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m31, matrix.m32,
+        );
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-resettransform
     pub fn reset_transform(&self) {
         self.state.borrow_mut().transform = Transform2D::identity();
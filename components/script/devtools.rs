@@ -11,22 +11,31 @@ use crate::dom::bindings::conversions::{jsstring_to_str, ConversionResult, FromJ
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::get_dictionary_property;
+use crate::dom::console::for_each_own_property;
 use crate::dom::document::AnimationFrameCallback;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::node::{window_from_node, Node, ShadowIncluding};
 use crate::dom::window::Window;
 use crate::realms::enter_realm;
+use crate::script_runtime::JSContext as SafeJSContext;
 use crate::script_thread::Documents;
+use devtools_traits::{source_mapping_url, ScriptToDevtoolsControlMsg, StackFrame};
 use devtools_traits::{AutoMargins, ComputedNodeLayout, TimelineMarkerType};
 use devtools_traits::{EvaluateJSReply, Modification, NodeInfo, TimelineMarker};
 use ipc_channel::ipc::IpcSender;
+use js::jsapi::{JSContext, JS_ClearPendingException, StackFormat};
 use js::jsval::UndefinedValue;
 use js::rust::wrappers::ObjectClassName;
+use js::rust::HandleValue;
 use msg::constellation_msg::PipelineId;
+use net_traits::request::RequestBuilder;
+use net_traits::{fetch_async, FetchResponseMsg};
+use servo_url::ServoUrl;
 use std::ffi::CStr;
 use std::str;
-use uuid::Uuid;
+use std::sync::Mutex;
 
 #[allow(unsafe_code)]
 pub fn handle_evaluate_js(global: &GlobalScope, eval: String, reply: IpcSender<EvaluateJSReply>) {
@@ -36,36 +45,148 @@ pub fn handle_evaluate_js(global: &GlobalScope, eval: String, reply: IpcSender<E
         let _ac = enter_realm(global);
         rooted!(in(*cx) let mut rval = UndefinedValue());
         global.evaluate_js_on_global_with_result(&eval, rval.handle_mut());
+        devtools_value(*cx, global, rval.handle())
+    };
+    reply.send(result).unwrap();
+}
+
+/// Serializes a JS value for the developer tools. Objects are kept alive by
+/// the global, so that their properties can be requested later.
+#[allow(unsafe_code)]
+pub unsafe fn devtools_value(
+    cx: *mut JSContext,
+    global: &GlobalScope,
+    value: HandleValue,
+) -> EvaluateJSReply {
+    if value.is_undefined() {
+        EvaluateJSReply::VoidValue
+    } else if value.is_boolean() {
+        EvaluateJSReply::BooleanValue(value.to_boolean())
+    } else if value.is_double() || value.is_int32() {
+        EvaluateJSReply::NumberValue(match FromJSValConvertible::from_jsval(cx, value, ()) {
+            Ok(ConversionResult::Success(v)) => v,
+            _ => unreachable!(),
+        })
+    } else if value.is_string() {
+        EvaluateJSReply::StringValue(String::from(jsstring_to_str(cx, value.to_string())))
+    } else if value.is_null() {
+        EvaluateJSReply::NullValue
+    } else if value.is_object() {
+        rooted!(in(cx) let obj = value.to_object());
+        let class_name = CStr::from_ptr(ObjectClassName(cx, obj.handle()));
+        let class_name = str::from_utf8(class_name.to_bytes()).unwrap();
 
-        if rval.is_undefined() {
-            EvaluateJSReply::VoidValue
-        } else if rval.is_boolean() {
-            EvaluateJSReply::BooleanValue(rval.to_boolean())
-        } else if rval.is_double() || rval.is_int32() {
-            EvaluateJSReply::NumberValue(
-                match FromJSValConvertible::from_jsval(*cx, rval.handle(), ()) {
-                    Ok(ConversionResult::Success(v)) => v,
-                    _ => unreachable!(),
-                },
-            )
-        } else if rval.is_string() {
-            EvaluateJSReply::StringValue(String::from(jsstring_to_str(*cx, rval.to_string())))
-        } else if rval.is_null() {
-            EvaluateJSReply::NullValue
-        } else {
-            assert!(rval.is_object());
-
-            rooted!(in(*cx) let obj = rval.to_object());
-            let class_name = CStr::from_ptr(ObjectClassName(*cx, obj.handle()));
-            let class_name = str::from_utf8(class_name.to_bytes()).unwrap();
-
-            EvaluateJSReply::ActorValue {
-                class: class_name.to_owned(),
-                uuid: Uuid::new_v4().to_string(),
-            }
+        EvaluateJSReply::ActorValue {
+            class: class_name.to_owned(),
+            uuid: global.register_devtools_object(obj.get()),
         }
+    } else {
+        // Symbols can't be converted to strings implicitly.
+        EvaluateJSReply::StringValue(String::from("Symbol()"))
+    }
+}
+
+/// Captures the JS stack of `cx`, innermost frame first.
+#[allow(unsafe_code)]
+pub fn capture_stack_trace(cx: SafeJSContext) -> Option<Vec<StackFrame>> {
+    unsafe {
+        capture_stack!(in(*cx) let stack);
+        stack
+            .and_then(|stack| stack.as_string(None, StackFormat::SpiderMonkey))
+            .map(|stack| StackFrame::parse_stack(&stack))
+    }
+}
+
+/// The stack trace of an exception, from the `stack` property of errors.
+#[allow(unsafe_code)]
+pub unsafe fn exception_stack_trace(
+    cx: *mut JSContext,
+    exception: HandleValue,
+) -> Option<Vec<StackFrame>> {
+    if !exception.is_object() {
+        return None;
+    }
+    rooted!(in(cx) let object = exception.to_object());
+    rooted!(in(cx) let mut stack = UndefinedValue());
+    match get_dictionary_property(cx, object.handle(), "stack", stack.handle_mut()) {
+        Ok(true) if stack.is_string() => {},
+        Ok(_) => return None,
+        Err(()) => {
+            JS_ClearPendingException(cx);
+            return None;
+        },
+    }
+    let stack = jsstring_to_str(cx, stack.to_string());
+    Some(StackFrame::parse_stack(&stack))
+}
+
+/// Fetches the source map that the `sourceMappingURL` comment at the end of
+/// a script points to, and reports it to the devtools, which resolve the
+/// locations in stack traces through it.
+/// <https://sourcemaps.info/spec.html#h.lmz475t4mvbx>
+pub fn report_source_map(global: &GlobalScope, script_url: &ServoUrl, source: &str) {
+    let chan = match global.devtools_chan() {
+        Some(chan) => chan.clone(),
+        None => return,
     };
-    reply.send(result).unwrap();
+    let map_url = match source_mapping_url(source).and_then(|url| script_url.join(url).ok()) {
+        Some(map_url) => map_url,
+        None => return,
+    };
+
+    let request = RequestBuilder::new(map_url.clone())
+        .origin(global.origin().immutable().clone())
+        .pipeline_id(Some(global.pipeline_id()));
+    let pipeline_id = global.pipeline_id();
+    let script_url = script_url.clone();
+    let data = Mutex::new(Some(vec![]));
+    fetch_async(
+        request,
+        &global.core_resource_thread(),
+        move |message| match message {
+            FetchResponseMsg::ProcessResponse(Err(_)) => *data.lock().unwrap() = None,
+            FetchResponseMsg::ProcessResponseChunk(mut chunk) => {
+                if let Some(data) = data.lock().unwrap().as_mut() {
+                    data.append(&mut chunk);
+                }
+            },
+            FetchResponseMsg::ProcessResponseEOF(Ok(_)) => {
+                if let Some(data) = data.lock().unwrap().take() {
+                    let map = String::from_utf8_lossy(&data).into_owned();
+                    let _ = chan.send(ScriptToDevtoolsControlMsg::ReportSourceMap(
+                        pipeline_id,
+                        script_url.clone(),
+                        map_url.clone(),
+                        map,
+                    ));
+                }
+            },
+            _ => {},
+        },
+    );
+}
+
+#[allow(unsafe_code)]
+pub fn handle_get_object_properties(
+    global: &GlobalScope,
+    uuid: String,
+    reply: IpcSender<Option<Vec<(String, EvaluateJSReply)>>>,
+) {
+    let object = match global.devtools_object(&uuid) {
+        Some(object) => object,
+        None => return reply.send(None).unwrap(),
+    };
+    let cx = global.get_cx();
+    let _ac = enter_realm(global);
+    rooted!(in(*cx) let object = object);
+    let mut properties = vec![];
+    // The object was kept alive by the global, so this is safe.
+    let result = unsafe {
+        for_each_own_property(*cx, object.handle(), |name, value| {
+            properties.push((name.into(), devtools_value(*cx, global, value)));
+        })
+    };
+    reply.send(result.ok().map(|()| properties)).unwrap();
 }
 
 pub fn handle_get_root_node(
@@ -14,6 +14,7 @@ use net_traits::request::RequestBuilder;
 use net_traits::{CoreResourceMsg, FetchChannels, FetchResponseMsg};
 use net_traits::{IpcSend, ResourceThreads};
 use servo_url::ServoUrl;
+use std::collections::HashMap;
 
 #[derive(Clone, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum LoadType {
@@ -64,12 +65,19 @@ impl Drop for LoadBlocker {
     }
 }
 
+/// Identifies a fetch started with `DocumentLoader::fetch_async_cancellable`.
+#[derive(Clone, Copy, Debug, Eq, Hash, JSTraceable, MallocSizeOf, PartialEq)]
+pub struct CancellableFetchId(u64);
+
 #[derive(JSTraceable, MallocSizeOf)]
 pub struct DocumentLoader {
     resource_threads: ResourceThreads,
     blocking_loads: Vec<LoadType>,
     events_inhibited: bool,
     cancellers: Vec<FetchCanceller>,
+    /// The cancellers of fetches that forget them once they complete.
+    cancellable_fetches: HashMap<CancellableFetchId, FetchCanceller>,
+    next_cancellable_fetch_id: u64,
 }
 
 impl DocumentLoader {
@@ -89,13 +97,16 @@ impl DocumentLoader {
             blocking_loads: initial_loads,
             events_inhibited: false,
             cancellers: Vec::new(),
+            cancellable_fetches: HashMap::new(),
+            next_cancellable_fetch_id: 0,
         }
     }
 
     pub fn cancel_all_loads(&mut self) -> bool {
-        let canceled_any = !self.cancellers.is_empty();
+        let canceled_any = !self.cancellers.is_empty() || !self.cancellable_fetches.is_empty();
         // Associated fetches will be canceled when dropping the canceller.
         self.cancellers.clear();
+        self.cancellable_fetches.clear();
         canceled_any
     }
 
@@ -138,6 +149,37 @@ impl DocumentLoader {
             .unwrap();
     }
 
+    /// Initiate a new fetch that does not block the document load event, and
+    /// is cancelled when the document's loads are, unless it was finished
+    /// with `finish_cancellable_fetch` first.
+    pub fn fetch_async_cancellable(
+        &mut self,
+        request: RequestBuilder,
+        fetch_target: IpcSender<FetchResponseMsg>,
+    ) -> CancellableFetchId {
+        let id = CancellableFetchId(self.next_cancellable_fetch_id);
+        self.next_cancellable_fetch_id += 1;
+        let mut canceller = FetchCanceller::new();
+        let cancel_receiver = canceller.initialize();
+        self.cancellable_fetches.insert(id, canceller);
+        self.resource_threads
+            .sender()
+            .send(CoreResourceMsg::Fetch(
+                request,
+                FetchChannels::ResponseMsg(fetch_target, Some(cancel_receiver)),
+            ))
+            .unwrap();
+        id
+    }
+
+    /// Forget the canceller of a completed fetch started with
+    /// `fetch_async_cancellable`.
+    pub fn finish_cancellable_fetch(&mut self, id: CancellableFetchId) {
+        if let Some(mut canceller) = self.cancellable_fetches.remove(&id) {
+            canceller.ignore();
+        }
+    }
+
     /// Mark an in-progress network request complete.
     pub fn finish_load(&mut self, load: &LoadType) {
         debug!(
@@ -109,7 +109,7 @@ impl AttrMethods for Attr {
             let value = owner.parse_attribute(&self.identifier.namespace, self.local_name(), value);
             self.set_value(value, &owner);
         } else {
-            *self.value.borrow_mut() = AttrValue::String(value.into());
+            *self.value.borrow_mut() = AttrValue::from_string_or_atom(value.into());
         }
     }
 
@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{same_object_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAssertionResponseBinding::{
+    self, AuthenticatorAssertionResponseMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use embedder_traits::Assertion;
+use js::jsapi::{Heap, JSObject};
+use std::ptr::NonNull;
+
+// https://w3c.github.io/webauthn/#authenticatorassertionresponse
+#[dom_struct]
+pub struct AuthenticatorAssertionResponse {
+    response: AuthenticatorResponse,
+    authenticator_data: Vec<u8>,
+    signature: Vec<u8>,
+    user_handle: Option<Vec<u8>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    authenticator_data_buffer: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "mozjs"]
+    signature_buffer: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "mozjs"]
+    user_handle_buffer: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorAssertionResponse {
+    fn new_inherited(
+        client_data_json: Vec<u8>,
+        assertion: Assertion,
+    ) -> AuthenticatorAssertionResponse {
+        AuthenticatorAssertionResponse {
+            response: AuthenticatorResponse::new_inherited(client_data_json),
+            authenticator_data: assertion.authenticator_data,
+            signature: assertion.signature,
+            user_handle: assertion.user_handle,
+            authenticator_data_buffer: Heap::default(),
+            signature_buffer: Heap::default(),
+            user_handle_buffer: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        client_data_json: Vec<u8>,
+        assertion: Assertion,
+    ) -> DomRoot<AuthenticatorAssertionResponse> {
+        reflect_dom_object(
+            Box::new(AuthenticatorAssertionResponse::new_inherited(
+                client_data_json,
+                assertion,
+            )),
+            global,
+            AuthenticatorAssertionResponseBinding::Wrap,
+        )
+    }
+}
+
+impl AuthenticatorAssertionResponseMethods for AuthenticatorAssertionResponse {
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-authenticatordata
+    fn AuthenticatorData(&self, cx: JSContext) -> NonNull<JSObject> {
+        same_object_array_buffer(
+            cx,
+            &self.authenticator_data_buffer,
+            &self.authenticator_data,
+        )
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-signature
+    fn Signature(&self, cx: JSContext) -> NonNull<JSObject> {
+        same_object_array_buffer(cx, &self.signature_buffer, &self.signature)
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorassertionresponse-userhandle
+    fn GetUserHandle(&self, cx: JSContext) -> Option<NonNull<JSObject>> {
+        self.user_handle
+            .as_ref()
+            .map(|user_handle| same_object_array_buffer(cx, &self.user_handle_buffer, user_handle))
+    }
+}
@@ -0,0 +1,70 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{same_object_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::AuthenticatorAttestationResponseBinding::{
+    self, AuthenticatorAttestationResponseMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use std::ptr::NonNull;
+
+// https://w3c.github.io/webauthn/#authenticatorattestationresponse
+#[dom_struct]
+pub struct AuthenticatorAttestationResponse {
+    response: AuthenticatorResponse,
+    attestation_object: Vec<u8>,
+    #[ignore_malloc_size_of = "mozjs"]
+    attestation_object_buffer: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorAttestationResponse {
+    fn new_inherited(
+        client_data_json: Vec<u8>,
+        attestation_object: Vec<u8>,
+    ) -> AuthenticatorAttestationResponse {
+        AuthenticatorAttestationResponse {
+            response: AuthenticatorResponse::new_inherited(client_data_json),
+            attestation_object,
+            attestation_object_buffer: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        client_data_json: Vec<u8>,
+        attestation_object: Vec<u8>,
+    ) -> DomRoot<AuthenticatorAttestationResponse> {
+        reflect_dom_object(
+            Box::new(AuthenticatorAttestationResponse::new_inherited(
+                client_data_json,
+                attestation_object,
+            )),
+            global,
+            AuthenticatorAttestationResponseBinding::Wrap,
+        )
+    }
+}
+
+impl AuthenticatorAttestationResponseMethods for AuthenticatorAttestationResponse {
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-attestationobject
+    fn AttestationObject(&self, cx: JSContext) -> NonNull<JSObject> {
+        same_object_array_buffer(
+            cx,
+            &self.attestation_object_buffer,
+            &self.attestation_object,
+        )
+    }
+
+    // https://w3c.github.io/webauthn/#dom-authenticatorattestationresponse-gettransports
+    fn GetTransports(&self) -> Vec<DOMString> {
+        // The embedder doesn't say how it reaches its authenticators.
+        vec![]
+    }
+}
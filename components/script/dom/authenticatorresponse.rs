@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::AuthenticatorResponseBinding::AuthenticatorResponseMethods;
+use crate::dom::bindings::reflector::Reflector;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::typedarray::{ArrayBuffer, CreateWith};
+use std::ptr::{self, NonNull};
+
+// https://w3c.github.io/webauthn/#authenticatorresponse
+#[dom_struct]
+pub struct AuthenticatorResponse {
+    reflector_: Reflector,
+    client_data_json: Vec<u8>,
+    #[ignore_malloc_size_of = "mozjs"]
+    client_data_json_buffer: Heap<*mut JSObject>,
+}
+
+impl AuthenticatorResponse {
+    pub fn new_inherited(client_data_json: Vec<u8>) -> AuthenticatorResponse {
+        AuthenticatorResponse {
+            reflector_: Reflector::new(),
+            client_data_json,
+            client_data_json_buffer: Heap::default(),
+        }
+    }
+}
+
+impl AuthenticatorResponseMethods for AuthenticatorResponse {
+    // https://w3c.github.io/webauthn/#dom-authenticatorresponse-clientdatajson
+    fn ClientDataJSON(&self, cx: JSContext) -> NonNull<JSObject> {
+        same_object_array_buffer(cx, &self.client_data_json_buffer, &self.client_data_json)
+    }
+}
+
+/// The ArrayBuffer of `bytes` for a `[SameObject]` attribute, which is
+/// created the first time the attribute is gotten and then kept in `buffer`.
+#[allow(unsafe_code)]
+pub fn same_object_array_buffer(
+    cx: JSContext,
+    buffer: &Heap<*mut JSObject>,
+    bytes: &[u8],
+) -> NonNull<JSObject> {
+    if buffer.get().is_null() {
+        rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+        assert!(unsafe {
+            ArrayBuffer::create(*cx, CreateWith::Slice(bytes), array_buffer.handle_mut()).is_ok()
+        });
+        buffer.set(array_buffer.get());
+    }
+    NonNull::new(buffer.get()).expect("got a null pointer")
+}
@@ -888,7 +888,9 @@ def getJSToNativeConversionInfo(type, descriptorProvider, failureCode=None,
 
         typeName = type.unroll().name  # unroll because it may be nullable
 
-        if isMember == "Union":
+        # Union and dictionary members outlive the conversion, so they hold
+        # the typed array in a traced heap slot.
+        if isMember in ("Union", "Dictionary"):
             typeName = "Heap" + typeName
 
         templateBody = fill(
@@ -904,10 +906,12 @@ def getJSToNativeConversionInfo(type, descriptorProvider, failureCode=None,
             failureCode=unwrapFailureCode + "\n",
         )
 
-        if isMember == "Union":
+        if isMember in ("Union", "Dictionary"):
             templateBody = "RootedTraceableBox::new(%s)" % templateBody
 
         declType = CGGeneric("typedarray::%s" % typeName)
+        if isMember == "Dictionary":
+            declType = CGWrapper(declType, pre="RootedTraceableBox<", post=">")
         if type.nullable():
             templateBody = "Some(%s)" % templateBody
             declType = CGWrapper(declType, pre="Option<", post=">")
@@ -2140,13 +2144,15 @@ def DOMClass(descriptor):
         globals_ = camel_to_upper_snake(descriptor.name)
     else:
         globals_ = 'EMPTY'
+    overrideBuiltins = toStringBool(descriptor.interface.getExtendedAttribute("OverrideBuiltins") is not None)
     return """\
 DOMClass {
     interface_chain: [ %s ],
     type_id: %s,
     malloc_size_of: %s as unsafe fn(&mut _, _) -> _,
     global: InterfaceObjectMap::Globals::%s,
-}""" % (prototypeChainString, DOMClassTypeId(descriptor), mallocSizeOf, globals_)
+    override_builtins: %s,
+}""" % (prototypeChainString, DOMClassTypeId(descriptor), mallocSizeOf, globals_, overrideBuiltins)
 
 
 class CGDOMJSClass(CGThing):
@@ -2622,7 +2628,7 @@ class CGConstructorEnabled(CGAbstractMethod):
         pref = iface.getExtendedAttribute("Pref")
         if pref:
             assert isinstance(pref, list) and len(pref) == 1
-            conditions.append('prefs::pref_map().get("%s").as_bool().unwrap_or(false)' % pref[0])
+            conditions.append('is_pref_enabled(aObj, "%s")' % pref[0])
 
         func = iface.getExtendedAttribute("Func")
         if func:
@@ -3017,6 +3023,12 @@ rooted!(in(*cx) let mut prototype_proto = ptr::null_mut::<JSObject>());
 %s;
 assert!(!prototype_proto.is_null());""" % getPrototypeProto)]
 
+        if self.descriptor.hasNamedPropertiesObject():
+            code.append(CGGeneric("""
+rooted!(in(*cx) let prototype_proto_proto = prototype_proto.get());
+%s::create_named_properties_object(cx, prototype_proto_proto.handle(), prototype_proto.handle_mut());
+assert!(!prototype_proto.is_null());""" % self.descriptor.concreteType))
+
         properties = {
             "id": name,
             "unscopables": "unscopable_names" if self.haveUnscopables else "&[]",
@@ -4257,11 +4269,12 @@ def getEnumValueName(value):
     # deal with possible name collisions from that by throwing if the
     # enum value is actually "_empty", and throw on any value
     # containing non-ASCII chars for now. Replace all chars other than
-    # [0-9A-Za-z_] with '_'.
+    # [0-9A-Za-z_] with '_', and prefix values starting with a digit
+    # with '_'.
     if re.match("[^\x20-\x7E]", value):
         raise SyntaxError('Enum value "' + value + '" contains non-ASCII characters')
     if re.match("^[0-9]", value):
-        raise SyntaxError('Enum value "' + value + '" starts with a digit')
+        value = '_' + value
     value = re.sub(r'[^0-9A-Za-z_]', '_', value)
     if re.match("^_[A-Z]|__", value):
         raise SyntaxError('Enum value "' + value + '" is reserved by the C++ spec')
@@ -4583,7 +4596,9 @@ class CGUnionConversionStruct(CGThing):
             conversions.append(CGIfWrapper("value.get().is_object()", templateBody))
 
         if dictionaryObject:
-            assert not hasObjectTypes
+            # Platform objects and sequences are distinguishable from
+            # dictionaries, and are tried first.
+            assert not object and not dateObject and not callbackObject and not mozMapObject
             conversions.append(dictionaryObject)
 
         stringTypes = [t for t in memberTypes if t.isString() or t.isEnum()]
@@ -5195,10 +5210,16 @@ class CGDOMJSProxyHandler_getOwnPropertyDescriptor(CGAbstractExternMethod):
             condition = "RUST_JSID_IS_STRING(id) || RUST_JSID_IS_INT(id)"
             if indexedGetter:
                 condition = "index.is_none() && (%s)" % condition
-            # Once we start supporting OverrideBuiltins we need to make
-            # ResolveOwnProperty or EnumerateOwnProperties filter out named
-            # properties that shadow prototype properties.
-            namedGet = """
+            # With [OverrideBuiltins], named properties are visible even when
+            # they shadow the properties of the prototype chain.
+            if self.descriptor.interface.getExtendedAttribute("OverrideBuiltins"):
+                namedGet = """
+if %s {
+    %s
+}
+""" % (condition, CGIndenter(CGProxyNamedGetter(self.descriptor, templateValues), 4).define())
+            else:
+                namedGet = """
 if %s {
     let mut has_on_proto = false;
     if !has_property_on_prototype(*cx, proxy_lt, id_lt, &mut has_on_proto) {
@@ -5433,7 +5454,16 @@ class CGDOMJSProxyHandler_hasOwn(CGAbstractExternMethod):
         condition = "RUST_JSID_IS_STRING(id) || RUST_JSID_IS_INT(id)"
         if indexedGetter:
             condition = "index.is_none() && (%s)" % condition
-        if namedGetter:
+        if namedGetter and self.descriptor.interface.getExtendedAttribute("OverrideBuiltins"):
+            named = """\
+if %s {
+    %s
+    *bp = result.is_some();
+    return true;
+}
+
+""" % (condition, CGIndenter(CGProxyNamedGetter(self.descriptor), 4).define())
+        elif namedGetter:
             named = """\
 if %s {
     let mut has_on_proto = false;
@@ -5531,6 +5561,15 @@ if !expando.is_null() {
         else:
             getNamed = ""
 
+        # With [OverrideBuiltins], the named properties are looked up before
+        # the properties of the prototype chain, which they shadow.
+        if self.descriptor.interface.getExtendedAttribute("OverrideBuiltins"):
+            getNamedBeforePrototype = getNamed
+            getNamedAfterPrototype = ""
+        else:
+            getNamedBeforePrototype = ""
+            getNamedAfterPrototype = getNamed
+
         return """\
 //MOZ_ASSERT(!xpc::WrapperFactory::IsXrayWrapper(proxy),
 //"Should not have a XrayWrapper here");
@@ -5540,6 +5579,7 @@ let vp_lt = MutableHandle::from_raw(vp);
 let id_lt = Handle::from_raw(id);
 let receiver_lt = Handle::from_raw(receiver);
 
+%s
 %s
 let mut found = false;
 if !get_property_on_prototype(*cx, proxy_lt, receiver_lt, id_lt, &mut found, vp_lt) {
@@ -5551,7 +5591,7 @@ if found {
 }
 %s
 vp.set(UndefinedValue());
-return true;""" % (getIndexedOrExpando, getNamed)
+return true;""" % (getIndexedOrExpando, getNamedBeforePrototype, getNamedAfterPrototype)
 
     def definition_body(self):
         return CGGeneric(self.getBody())
@@ -6132,6 +6172,7 @@ def generate_imports(config, cgthings, descriptors, callbacks=None, dictionaries
         'crate::dom::bindings::error::throw_dom_exception',
         'crate::dom::bindings::guard::Condition',
         'crate::dom::bindings::guard::Guard',
+        'crate::dom::bindings::guard::is_pref_enabled',
         'crate::dom::bindings::inheritance::Castable',
         'crate::dom::bindings::proxyhandler',
         'crate::dom::bindings::proxyhandler::ensure_expando_object',
@@ -288,6 +288,12 @@ class Descriptor(DescriptorProvider):
                 if iface:
                     iface.setUserData('hasConcreteDescendant', True)
 
+            # The named properties of globals are exposed by a named
+            # properties object on their prototype chain rather than by
+            # making the globals proxies.
+            if self.proxy and self.hasNamedPropertiesObject():
+                self.proxy = False
+
             if self.proxy:
                 iface = self.interface
                 while iface.parent:
@@ -411,6 +417,14 @@ class Descriptor(DescriptorProvider):
     def isExposedConditionally(self):
         return self.interface.isExposedConditionally()
 
+    def hasNamedPropertiesObject(self):
+        """
+        Returns true if the named properties of this interface live on a
+        named properties object on its prototype chain.
+        https://heycam.github.io/webidl/#named-properties-object
+        """
+        return self.isGlobal() and self.operations['NamedGetter'] is not None
+
     def isGlobal(self):
         """
         Returns true if this is the primary interface for a global object
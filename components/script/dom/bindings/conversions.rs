@@ -398,6 +398,12 @@ pub unsafe fn get_dom_class(obj: *mut JSObject) -> Result<&'static DOMClass, ()>
     if is_dom_proxy(obj) {
         trace!("proxy dom object");
         let dom_class: *const DOMClass = GetProxyHandlerExtra(obj) as *const DOMClass;
+        // The named properties object of the window is a proxy of the DOM
+        // family without any DOM class.
+        if dom_class.is_null() {
+            trace!("named properties object");
+            return Err(());
+        }
         return Ok(&*dom_class);
     }
     trace!("not a dom object");
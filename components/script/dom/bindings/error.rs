@@ -87,6 +87,12 @@ pub enum Error {
     NotReadable,
     /// OperationError DOMException
     Operation,
+    /// NotAllowedError DOMException
+    NotAllowed,
+    /// EncodingError DOMException
+    Encoding,
+    /// DataError DOMException
+    Data,
 
     /// TypeError JavaScript Error
     Type(String),
@@ -140,6 +146,9 @@ pub fn throw_dom_exception(cx: SafeJSContext, global: &GlobalScope, result: Erro
         Error::InvalidModification => DOMErrorName::InvalidModificationError,
         Error::NotReadable => DOMErrorName::NotReadableError,
         Error::Operation => DOMErrorName::OperationError,
+        Error::NotAllowed => DOMErrorName::NotAllowedError,
+        Error::Encoding => DOMErrorName::EncodingError,
+        Error::Data => DOMErrorName::DataError,
         Error::Type(message) => unsafe {
             assert!(!JS_IsExceptionPending(*cx));
             throw_type_error(*cx, &message);
@@ -175,6 +184,9 @@ pub struct ErrorInfo {
     pub lineno: c_uint,
     /// The column number.
     pub column: c_uint,
+    /// Whether the error was thrown by a script with muted errors.
+    /// <https://html.spec.whatwg.org/multipage/#muted-errors>
+    pub muted: bool,
 }
 
 impl ErrorInfo {
@@ -210,6 +222,7 @@ impl ErrorInfo {
             message: message,
             lineno: lineno,
             column: column,
+            muted: (*report).isMuted,
         })
     }
 
@@ -224,6 +237,7 @@ impl ErrorInfo {
             message: exception.stringifier().into(),
             lineno: 0,
             column: 0,
+            muted: false,
         })
     }
 }
@@ -254,6 +268,7 @@ pub unsafe fn report_pending_exception(cx: *mut JSContext, dispatch_event: bool,
                 filename: String::new(),
                 lineno: 0,
                 column: 0,
+                muted: false,
             })
     } else {
         match USVString::from_jsval(cx, value.handle(), ()) {
@@ -262,6 +277,7 @@ pub unsafe fn report_pending_exception(cx: *mut JSContext, dispatch_event: bool,
                 filename: String::new(),
                 lineno: 0,
                 column: 0,
+                muted: false,
             },
             _ => {
                 panic!("Uncaught exception: failed to stringify primitive");
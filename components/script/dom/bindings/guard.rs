@@ -6,9 +6,9 @@
 
 use crate::dom::bindings::codegen::InterfaceObjectMap;
 use crate::dom::bindings::interface::is_exposed_in;
+use crate::dom::globalscope::GlobalScope;
 use crate::script_runtime::JSContext;
 use js::rust::HandleObject;
-use servo_config::prefs;
 
 /// A container with a condition.
 pub struct Guard<T: Clone + Copy> {
@@ -41,7 +41,8 @@ impl<T: Clone + Copy> Guard<T> {
 pub enum Condition {
     /// The condition is satisfied if the function returns true.
     Func(fn(JSContext, HandleObject) -> bool),
-    /// The condition is satisfied if the preference is set.
+    /// The condition is satisfied if the preference is set, or if an origin trial enables it for
+    /// the global.
     Pref(&'static str),
     // The condition is satisfied if the interface is exposed in the global.
     Exposed(InterfaceObjectMap::Globals),
@@ -52,10 +53,17 @@ pub enum Condition {
 impl Condition {
     fn is_satisfied(&self, cx: JSContext, obj: HandleObject, global: HandleObject) -> bool {
         match *self {
-            Condition::Pref(name) => prefs::pref_map().get(name).as_bool().unwrap_or(false),
+            Condition::Pref(name) => is_pref_enabled(global, name),
             Condition::Func(f) => f(cx, obj),
             Condition::Exposed(globals) => is_exposed_in(global, globals),
             Condition::Satisfied => true,
         }
     }
 }
+
+/// Whether the preference is set, or an origin trial enables it for `global`.
+#[allow(unsafe_code)]
+pub fn is_pref_enabled(global: HandleObject, pref: &str) -> bool {
+    let global = unsafe { GlobalScope::from_object(global.get()) };
+    global.is_pref_enabled(pref)
+}
@@ -158,7 +158,14 @@ where
             let element = if definition.is_autonomous() {
                 DomRoot::upcast(HTMLElement::new(name.local, None, &*document))
             } else {
-                create_native_html_element(name, None, &*document, ElementCreator::ScriptCreated)
+                let element = create_native_html_element(
+                    name,
+                    None,
+                    &*document,
+                    ElementCreator::ScriptCreated,
+                );
+                element.set_is(definition.name.clone());
+                element
             };
 
             // Step 8.2 is performed in the generated caller code.
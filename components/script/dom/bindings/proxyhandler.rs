@@ -6,7 +6,7 @@
 
 #![deny(missing_docs)]
 
-use crate::dom::bindings::conversions::is_dom_proxy;
+use crate::dom::bindings::conversions::{get_dom_class, is_dom_proxy};
 use crate::dom::bindings::utils::delete_property_by_id;
 use js::glue::GetProxyHandlerFamily;
 use js::glue::{GetProxyPrivate, SetProxyPrivate};
@@ -32,8 +32,6 @@ pub unsafe extern "C" fn shadow_check_callback(
     object: RawHandleObject,
     id: RawHandleId,
 ) -> DOMProxyShadowsResult {
-    // TODO: support OverrideBuiltins when #12978 is fixed.
-
     rooted!(in(cx) let mut expando = ptr::null_mut::<JSObject>());
     get_expando_object(object, expando.handle_mut());
     if !expando.get().is_null() {
@@ -49,8 +47,17 @@ pub unsafe extern "C" fn shadow_check_callback(
         }
     }
 
-    // Our expando, if any, didn't shadow, so we're not shadowing at all.
-    DOMProxyShadowsResult::DoesntShadow
+    // With [OverrideBuiltins], any property of the prototype chain may be
+    // shadowed by a named property, now or later, so the lookups must always
+    // go through the proxy. The same goes for the named properties object of
+    // the window, which has no DOM class.
+    match get_dom_class(object.get()) {
+        Ok(dom_class) if !dom_class.override_builtins => {
+            // Our expando, if any, didn't shadow, so we're not shadowing at all.
+            DOMProxyShadowsResult::DoesntShadow
+        },
+        _ => DOMProxyShadowsResult::Shadows,
+    }
 }
 
 /// Initialize the infrastructure for DOM proxy objects.
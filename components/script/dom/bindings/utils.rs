@@ -105,6 +105,10 @@ pub struct DOMClass {
 
     /// The `Globals` flag for this global interface, if any.
     pub global: InterfaceObjectMap::Globals,
+
+    /// Whether the named properties of this interface shadow the properties
+    /// of its prototype chain, as with `[OverrideBuiltins]`.
+    pub override_builtins: bool,
 }
 unsafe impl Sync for DOMClass {}
 
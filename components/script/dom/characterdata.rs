@@ -24,19 +24,22 @@ use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::vtable_for;
 use dom_struct::dom_struct;
+use style::attr::AtomOrString;
 
 // https://dom.spec.whatwg.org/#characterdata
 #[dom_struct]
 pub struct CharacterData {
     node: Node,
-    data: DomRefCell<DOMString>,
+    /// Short data is an atom, shared by the many nodes repeating it, like the
+    /// whitespace between elements.
+    data: DomRefCell<AtomOrString>,
 }
 
 impl CharacterData {
     pub fn new_inherited(data: DOMString, document: &Document) -> CharacterData {
         CharacterData {
             node: Node::new_inherited(document),
-            data: DomRefCell::new(data),
+            data: DomRefCell::new(AtomOrString::new(data.into())),
         }
     }
 
@@ -60,8 +63,8 @@ impl CharacterData {
     }
 
     #[inline]
-    pub fn data(&self) -> Ref<DOMString> {
-        self.data.borrow()
+    pub fn data(&self) -> Ref<str> {
+        Ref::map(self.data.borrow(), |data| &**data)
     }
 
     #[inline]
@@ -89,7 +92,7 @@ impl CharacterData {
     // Queue a MutationObserver record before changing the content.
     fn queue_mutation_record(&self) {
         let mutation = Mutation::CharacterData {
-            old_value: self.data.borrow().clone(),
+            old_value: self.Data(),
         };
         MutationObserver::queue_a_mutation_record(self.upcast::<Node>(), mutation);
     }
@@ -98,7 +101,7 @@ impl CharacterData {
 impl CharacterDataMethods for CharacterData {
     // https://dom.spec.whatwg.org/#dom-characterdata-data
     fn Data(&self) -> DOMString {
-        self.data.borrow().clone()
+        DOMString::from(&**self.data.borrow())
     }
 
     // https://dom.spec.whatwg.org/#dom-characterdata-data
@@ -106,7 +109,7 @@ impl CharacterDataMethods for CharacterData {
         self.queue_mutation_record();
         let old_length = self.Length();
         let new_length = data.encode_utf16().count() as u32;
-        *self.data.borrow_mut() = data;
+        *self.data.borrow_mut() = AtomOrString::new(data.into());
         self.content_changed();
         let node = self.upcast::<Node>();
         node.ranges()
@@ -233,7 +236,7 @@ impl CharacterDataMethods for CharacterData {
             new_data.push_str(replacement_after);
             new_data.push_str(suffix);
         }
-        *self.data.borrow_mut() = DOMString::from(new_data);
+        *self.data.borrow_mut() = AtomOrString::new(new_data);
         self.content_changed();
         // Steps 8-11.
         let node = self.upcast::<Node>();
@@ -289,7 +292,7 @@ pub trait LayoutCharacterDataHelpers {
 impl LayoutCharacterDataHelpers for LayoutDom<CharacterData> {
     #[inline]
     unsafe fn data_for_layout(&self) -> &str {
-        &(*self.unsafe_get()).data.borrow_for_layout()
+        &**(*self.unsafe_get()).data.borrow_for_layout()
     }
 }
 
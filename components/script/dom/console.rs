@@ -2,20 +2,43 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::devtools::{capture_stack_trace, devtools_value};
+use crate::dom::bindings::conversions::{jsid_to_string, jsstring_to_str};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
-use devtools_traits::{ConsoleMessage, LogLevel, ScriptToDevtoolsControlMsg};
+use crate::script_runtime::JSContext;
+use devtools_traits::{ConsoleMessage, EvaluateJSReply, LogLevel, ScriptToDevtoolsControlMsg};
+use js::jsapi::{IsCallable, JSContext as RawJSContext, JS_ClearPendingException, JSITER_OWNONLY};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::{GetPropertyKeys, JS_GetPropertyById, JS_IsArrayObject, ObjectClassName};
+use js::rust::{HandleObject, HandleValue, IdVector, ToString};
+use std::ffi::CStr;
 use std::io;
 
+/// The largest number of properties shown in the preview of an object.
+const MAX_PREVIEWED_PROPERTIES: usize = 10;
+
 // https://developer.mozilla.org/en-US/docs/Web/API/Console
 pub struct Console(());
 
 impl Console {
-    fn send_to_devtools(global: &GlobalScope, level: LogLevel, message: DOMString) {
+    #[allow(unsafe_code)]
+    fn send_to_devtools(
+        global: &GlobalScope,
+        level: LogLevel,
+        message: DOMString,
+        data: &[HandleValue],
+    ) {
         if let Some(chan) = global.devtools_chan() {
-            let console_message = prepare_message(level, message);
+            let cx = global.get_cx();
+            // The values were passed to the console API, so they are alive.
+            let arguments = data
+                .iter()
+                .map(|value| unsafe { devtools_value(*cx, global, *value) })
+                .collect();
+            let console_message = prepare_message(cx, level, message, arguments);
             let worker_id = global
                 .downcast::<WorkerGlobalScope>()
                 .map(|worker| worker.get_worker_id());
@@ -27,6 +50,33 @@ impl Console {
             chan.send(devtools_message).unwrap();
         }
     }
+
+    /// Prints a message, indented by the groups that it is in, and sends it
+    /// to the devtools along with the values that it was formatted from.
+    /// <https://console.spec.whatwg.org/#printer>
+    fn print(global: &GlobalScope, level: LogLevel, message: DOMString, data: &[HandleValue]) {
+        with_stderr_lock(move || {
+            let indent = "  ".repeat(global.console_group_depth());
+            for line in message.lines() {
+                println!("{}{}", indent, line);
+            }
+            Self::send_to_devtools(global, level, message, data);
+        })
+    }
+
+    /// <https://console.spec.whatwg.org/#logger>
+    fn logger(cx: JSContext, global: &GlobalScope, level: LogLevel, data: Vec<HandleValue>) {
+        if data.is_empty() {
+            return;
+        }
+        let message = format_message(cx, &data);
+        Self::print(global, level, message, &data);
+    }
+
+    /// Prints a warning of the console API itself.
+    pub fn internal_warn(global: &GlobalScope, message: DOMString) {
+        Self::print(global, LogLevel::Warn, message, &[]);
+    }
 }
 
 // In order to avoid interleaving the stdout output of the Console API methods
@@ -45,97 +95,460 @@ where
 
 #[allow(non_snake_case)]
 impl Console {
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/log
-    pub fn Log(global: &GlobalScope, messages: Vec<DOMString>) {
-        with_stderr_lock(move || {
-            for message in messages {
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Log, message);
-            }
-        })
+    // https://console.spec.whatwg.org/#log
+    pub fn Log(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::logger(cx, global, LogLevel::Log, data);
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console
-    pub fn Debug(global: &GlobalScope, messages: Vec<DOMString>) {
-        with_stderr_lock(move || {
-            for message in messages {
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Debug, message);
-            }
-        })
+    // https://console.spec.whatwg.org/#debug
+    pub fn Debug(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::logger(cx, global, LogLevel::Debug, data);
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/info
-    pub fn Info(global: &GlobalScope, messages: Vec<DOMString>) {
-        with_stderr_lock(move || {
-            for message in messages {
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Info, message);
-            }
-        })
+    // https://console.spec.whatwg.org/#info
+    pub fn Info(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::logger(cx, global, LogLevel::Info, data);
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/warn
-    pub fn Warn(global: &GlobalScope, messages: Vec<DOMString>) {
-        with_stderr_lock(move || {
-            for message in messages {
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Warn, message);
-            }
-        })
+    // https://console.spec.whatwg.org/#warn
+    pub fn Warn(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::logger(cx, global, LogLevel::Warn, data);
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/error
-    pub fn Error(global: &GlobalScope, messages: Vec<DOMString>) {
-        with_stderr_lock(move || {
-            for message in messages {
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Error, message);
-            }
-        })
+    // https://console.spec.whatwg.org/#error
+    pub fn Error(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::logger(cx, global, LogLevel::Error, data);
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/assert
-    pub fn Assert(global: &GlobalScope, condition: bool, message: Option<DOMString>) {
-        with_stderr_lock(move || {
-            if !condition {
-                let message = message.unwrap_or_else(|| DOMString::from("no message"));
-                println!("Assertion failed: {}", message);
-                Self::send_to_devtools(global, LogLevel::Error, message);
-            }
-        })
+    // https://console.spec.whatwg.org/#assert
+    #[allow(unsafe_code)]
+    pub fn Assert(cx: JSContext, global: &GlobalScope, condition: bool, data: Vec<HandleValue>) {
+        // Step 1.
+        if condition {
+            return;
+        }
+        // Steps 2-5.
+        let message = match data.first() {
+            None => DOMString::from("Assertion failed"),
+            Some(first) if first.is_string() => {
+                let first = unsafe { jsstring_to_str(*cx, first.to_string()) };
+                let rest = format_message(cx, &data[1..]);
+                let mut message = format!("Assertion failed: {}", first);
+                if !rest.is_empty() {
+                    message.push(' ');
+                    message.push_str(&rest);
+                }
+                DOMString::from(message)
+            },
+            Some(_) => DOMString::from(format!("Assertion failed: {}", format_message(cx, &data))),
+        };
+        // Step 6.
+        Self::print(global, LogLevel::Error, message, &data);
+    }
+
+    // https://console.spec.whatwg.org/#table
+    #[allow(unsafe_code)]
+    pub fn Table(
+        cx: JSContext,
+        global: &GlobalScope,
+        tabular_data: HandleValue,
+        properties: Option<Vec<DOMString>>,
+    ) {
+        let message = if tabular_data.is_object() {
+            rooted!(in(*cx) let object = tabular_data.to_object());
+            unsafe { format_table(*cx, object.handle(), properties.as_deref()) }
+        } else {
+            None
+        };
+        match message {
+            Some(message) => Self::print(global, LogLevel::Table, message, &[tabular_data]),
+            // Data that isn't tabular is logged as is.
+            None => Self::logger(cx, global, LogLevel::Log, vec![tabular_data]),
+        }
+    }
+
+    // https://console.spec.whatwg.org/#dir
+    #[allow(unsafe_code)]
+    pub fn Dir(cx: JSContext, global: &GlobalScope, item: HandleValue) {
+        let message = if item.is_object() {
+            rooted!(in(*cx) let object = item.to_object());
+            let mut lines = vec![unsafe { class_name(*cx, object.handle()) }];
+            let _ = unsafe {
+                for_each_own_property(*cx, object.handle(), |name, value| {
+                    lines.push(format!("  {}: {}", name, stringify_value(*cx, value, 1)));
+                })
+            };
+            DOMString::from(lines.join("\n"))
+        } else {
+            format_message(cx, &[item])
+        };
+        Self::print(global, LogLevel::Dir, message, &[item]);
+    }
+
+    // https://console.spec.whatwg.org/#count
+    pub fn Count(global: &GlobalScope, label: DOMString) {
+        let count = global.count(label.clone());
+        let message = DOMString::from(format!("{}: {}", label, count));
+        Self::print(global, LogLevel::Count, message, &[]);
+    }
+
+    // https://console.spec.whatwg.org/#countreset
+    pub fn CountReset(global: &GlobalScope, label: DOMString) {
+        if global.count_reset(&label).is_err() {
+            let message = DOMString::from(format!("Counter '{}' does not exist", label));
+            Self::internal_warn(global, message);
+        }
+    }
+
+    // https://console.spec.whatwg.org/#group
+    pub fn Group(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::start_group(cx, global, LogLevel::Group, data);
+    }
+
+    // https://console.spec.whatwg.org/#groupcollapsed
+    pub fn GroupCollapsed(cx: JSContext, global: &GlobalScope, data: Vec<HandleValue>) {
+        Self::start_group(cx, global, LogLevel::GroupCollapsed, data);
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/time
+    // https://console.spec.whatwg.org/#groupend
+    pub fn GroupEnd(global: &GlobalScope) {
+        let depth = global.console_group_depth();
+        if depth == 0 {
+            return;
+        }
+        global.set_console_group_depth(depth - 1);
+        Self::send_to_devtools(global, LogLevel::GroupEnd, DOMString::new(), &[]);
+    }
+
+    // https://console.spec.whatwg.org/#time
     pub fn Time(global: &GlobalScope, label: DOMString) {
-        with_stderr_lock(move || {
-            if let Ok(()) = global.time(label.clone()) {
-                let message = DOMString::from(format!("{}: timer started", label));
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Log, message);
+        if let Ok(()) = global.time(label.clone()) {
+            let message = DOMString::from(format!("{}: timer started", label));
+            Self::print(global, LogLevel::Time, message, &[]);
+        } else {
+            let message = DOMString::from(format!("Timer '{}' already exists", label));
+            Self::internal_warn(global, message);
+        }
+    }
+
+    // https://console.spec.whatwg.org/#timelog
+    pub fn TimeLog(cx: JSContext, global: &GlobalScope, label: DOMString, data: Vec<HandleValue>) {
+        if let Ok(delta) = global.time_log(&label) {
+            let mut message = format!("{}: {}ms", label, delta);
+            if !data.is_empty() {
+                message.push(' ');
+                message.push_str(&format_message(cx, &data));
             }
-        })
+            Self::print(global, LogLevel::TimeLog, DOMString::from(message), &data);
+        } else {
+            Self::warn_missing_timer(global, &label);
+        }
     }
 
-    // https://developer.mozilla.org/en-US/docs/Web/API/Console/timeEnd
+    // https://console.spec.whatwg.org/#timeend
     pub fn TimeEnd(global: &GlobalScope, label: DOMString) {
-        with_stderr_lock(move || {
-            if let Ok(delta) = global.time_end(&label) {
-                let message = DOMString::from(format!("{}: {}ms", label, delta));
-                println!("{}", message);
-                Self::send_to_devtools(global, LogLevel::Log, message);
-            };
-        })
+        if let Ok(delta) = global.time_end(&label) {
+            let message = DOMString::from(format!("{}: {}ms", label, delta));
+            Self::print(global, LogLevel::TimeEnd, message, &[]);
+        } else {
+            Self::warn_missing_timer(global, &label);
+        }
+    }
+}
+
+impl Console {
+    fn start_group(cx: JSContext, global: &GlobalScope, level: LogLevel, data: Vec<HandleValue>) {
+        let label = if data.is_empty() {
+            DOMString::from("console.group")
+        } else {
+            format_message(cx, &data)
+        };
+        Self::print(global, level, label, &data);
+        global.set_console_group_depth(global.console_group_depth() + 1);
+    }
+
+    fn warn_missing_timer(global: &GlobalScope, label: &str) {
+        let message = DOMString::from(format!("Timer '{}' does not exist", label));
+        Self::internal_warn(global, message);
     }
 }
 
-fn prepare_message(log_level: LogLevel, message: DOMString) -> ConsoleMessage {
-    // TODO: Sending fake values for filename, lineNumber and columnNumber in LogMessage; adjust later
+fn prepare_message(
+    cx: JSContext,
+    log_level: LogLevel,
+    message: DOMString,
+    arguments: Vec<EvaluateJSReply>,
+) -> ConsoleMessage {
+    // The location of the message is the one of the innermost caller.
+    let stacktrace = capture_stack_trace(cx);
+    let (filename, line, column) = match stacktrace.as_ref().and_then(|frames| frames.first()) {
+        Some(frame) => (
+            frame.filename.clone(),
+            frame.lineNumber as usize,
+            frame.columnNumber as usize,
+        ),
+        None => (String::new(), 0, 0),
+    };
     ConsoleMessage {
         message: String::from(message),
+        arguments,
         logLevel: log_level,
-        filename: "test".to_owned(),
-        lineNumber: 1,
-        columnNumber: 1,
+        filename,
+        lineNumber: line,
+        columnNumber: column,
+        stacktrace,
+    }
+}
+
+/// Formats the values passed to a method of the console API into a message,
+/// replacing the format specifiers of a first string with the values after
+/// it.
+/// <https://console.spec.whatwg.org/#formatter>
+#[allow(unsafe_code)]
+fn format_message(cx: JSContext, data: &[HandleValue]) -> DOMString {
+    let mut parts = vec![];
+    let mut rest = data.iter();
+    if let Some(first) = data.first().filter(|first| first.is_string()) {
+        rest.next();
+        let format = unsafe { jsstring_to_str(*cx, first.to_string()) };
+        let mut message = String::new();
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            let specifier = match chars.peek() {
+                Some(&specifier) if c == '%' && "sdifoOc%".contains(specifier) => specifier,
+                _ => {
+                    message.push(c);
+                    continue;
+                },
+            };
+            chars.next();
+            if specifier == '%' {
+                message.push('%');
+                continue;
+            }
+            let value = match rest.next() {
+                Some(value) => value,
+                None => {
+                    message.push('%');
+                    message.push(specifier);
+                    continue;
+                },
+            };
+            match specifier {
+                'd' | 'i' if value.is_number() => {
+                    message.push_str(&value.to_number().trunc().to_string())
+                },
+                'f' if value.is_number() => message.push_str(&value.to_number().to_string()),
+                'd' | 'i' | 'f' => message.push_str("NaN"),
+                // Styling isn't supported.
+                'c' => {},
+                _ => message.push_str(&unsafe { stringify_value(*cx, *value, 0) }),
+            }
+        }
+        parts.push(message);
+    }
+    for value in rest {
+        parts.push(unsafe { stringify_value(*cx, *value, 0) });
+    }
+    DOMString::from(parts.join(" "))
+}
+
+/// The text of a value in a message, where the properties of objects are
+/// previewed up to the given depth.
+#[allow(unsafe_code)]
+unsafe fn stringify_value(cx: *mut RawJSContext, value: HandleValue, depth: usize) -> String {
+    if value.is_string() {
+        let string = jsstring_to_str(cx, value.to_string());
+        return if depth == 0 {
+            String::from(string)
+        } else {
+            format!("{:?}", &*string)
+        };
+    }
+    if value.is_symbol() {
+        // Symbols can't be converted to strings implicitly.
+        return String::from("Symbol()");
     }
+    if !value.is_object() {
+        return to_string(cx, value);
+    }
+
+    rooted!(in(cx) let object = value.to_object());
+    if IsCallable(object.get()) {
+        return String::from("function");
+    }
+    let mut is_array = false;
+    if !JS_IsArrayObject(cx, value, &mut is_array) {
+        JS_ClearPendingException(cx);
+    }
+    if !is_array && class_name(cx, object.handle()) != "Object" {
+        return to_string(cx, value);
+    }
+    if depth > 0 {
+        return String::from(if is_array { "[…]" } else { "{…}" });
+    }
+
+    let mut properties = vec![];
+    let mut truncated = false;
+    let _ = for_each_own_property(cx, object.handle(), |name, value| {
+        if properties.len() == MAX_PREVIEWED_PROPERTIES {
+            truncated = true;
+            return;
+        }
+        let value = stringify_value(cx, value, depth + 1);
+        properties.push(if is_array {
+            value
+        } else {
+            format!("{}: {}", name, value)
+        });
+    });
+    if truncated {
+        properties.push(String::from("…"));
+    }
+    if is_array {
+        format!("[{}]", properties.join(", "))
+    } else {
+        format!("{{{}}}", properties.join(", "))
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe fn to_string(cx: *mut RawJSContext, value: HandleValue) -> String {
+    let string = ToString(cx, value);
+    if string.is_null() {
+        JS_ClearPendingException(cx);
+        return String::new();
+    }
+    String::from(jsstring_to_str(cx, string))
+}
+
+#[allow(unsafe_code)]
+unsafe fn class_name(cx: *mut RawJSContext, object: HandleObject) -> String {
+    let class_name = CStr::from_ptr(ObjectClassName(cx, object));
+    class_name.to_string_lossy().into_owned()
+}
+
+/// Calls `f` with the names and values of the own enumerable string-keyed
+/// properties of `object`, failing if getting one of them did.
+#[allow(unsafe_code)]
+pub unsafe fn for_each_own_property<F>(
+    cx: *mut RawJSContext,
+    object: HandleObject,
+    mut f: F,
+) -> Result<(), ()>
+where
+    F: FnMut(DOMString, HandleValue),
+{
+    let ids = IdVector::new(cx);
+    if !GetPropertyKeys(cx, object, JSITER_OWNONLY, ids.get()) {
+        JS_ClearPendingException(cx);
+        return Err(());
+    }
+    for id in &*ids {
+        rooted!(in(cx) let id = *id);
+        let name = match jsid_to_string(cx, id.handle()) {
+            Some(name) => name,
+            None => continue,
+        };
+        rooted!(in(cx) let mut value = UndefinedValue());
+        if !JS_GetPropertyById(cx, object, id.handle(), value.handle_mut()) {
+            JS_ClearPendingException(cx);
+            return Err(());
+        }
+        f(name, value.handle());
+    }
+    Ok(())
+}
+
+/// Formats the rows of the properties of `object` into a table, with columns
+/// for the properties of the rows that are objects, or `None` if the
+/// properties can't be enumerated.
+#[allow(unsafe_code)]
+unsafe fn format_table(
+    cx: *mut RawJSContext,
+    object: HandleObject,
+    properties: Option<&[DOMString]>,
+) -> Option<DOMString> {
+    let mut columns: Vec<String> = vec![];
+    let mut has_values = false;
+    let mut rows = vec![];
+    for_each_own_property(cx, object, |index, row| {
+        let mut cells = vec![];
+        let mut value = None;
+        if row.is_object() && !IsCallable(row.to_object()) {
+            rooted!(in(cx) let row = row.to_object());
+            let _ = for_each_own_property(cx, row.handle(), |name, cell| {
+                let name = String::from(name);
+                if let Some(properties) = properties {
+                    if !properties.iter().any(|property| **property == *name) {
+                        return;
+                    }
+                }
+                if !columns.contains(&name) {
+                    columns.push(name.clone());
+                }
+                cells.push((name, stringify_value(cx, cell, 1)));
+            });
+        } else {
+            has_values = true;
+            value = Some(stringify_value(cx, row, 1));
+        }
+        rows.push((String::from(index), cells, value));
+    })
+    .ok()?;
+
+    // The columns given by `properties` are in its order.
+    if let Some(properties) = properties {
+        columns = properties
+            .iter()
+            .map(|property| String::from(property.clone()))
+            .filter(|property| columns.contains(property))
+            .collect();
+    }
+    let mut header = vec![String::from("(index)")];
+    header.extend(columns.iter().cloned());
+    if has_values {
+        header.push(String::from("Value"));
+    }
+    let mut table = vec![header];
+    for (index, cells, value) in rows {
+        let mut line = vec![index];
+        for column in &columns {
+            line.push(
+                cells
+                    .iter()
+                    .find(|(name, _)| name == column)
+                    .map_or(String::new(), |(_, cell)| cell.clone()),
+            );
+        }
+        if has_values {
+            line.push(value.unwrap_or_default());
+        }
+        table.push(line);
+    }
+
+    let widths: Vec<usize> = (0..table[0].len())
+        .map(|column| {
+            table
+                .iter()
+                .map(|line| line[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let format_line = |line: &[String]| {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| {
+                let padding = width - cell.chars().count();
+                format!(" {}{} ", cell, " ".repeat(padding))
+            })
+            .collect();
+        format!("|{}|", cells.join("|"))
+    };
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(width + 2)).collect();
+    let mut lines = vec![format_line(&table[0]), format!("|{}|", separator.join("|"))];
+    lines.extend(table[1..].iter().map(|line| format_line(line)));
+    Some(DOMString::from(lines.join("\n")))
 }
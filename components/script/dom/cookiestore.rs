@@ -0,0 +1,451 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CookieStoreBinding::{
+    self, CookieInit, CookieListItem, CookieSameSite, CookieStoreDeleteOptions,
+    CookieStoreGetOptions, CookieStoreMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cookiechangeevent::CookieChangeEvent;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::realms::InRealm;
+use crate::task_source::{TaskSource, TaskSourceName};
+use cookie::{Cookie, SameSite};
+use dom_struct::dom_struct;
+use hyper_serde::Serde;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::CookieSource::NonHTTP;
+use net_traits::CoreResourceMsg::{GetCookiesDataForUrl, ObserveCookieChanges, SetCookieForUrl};
+use net_traits::{CookieChange, IpcSend};
+use profile_traits::ipc as profile_ipc;
+use servo_url::ServoUrl;
+use std::rc::Rc;
+use time::{self, Timespec, Tm};
+use url::Position;
+
+/// The most bytes the name and the value of a cookie can have together.
+/// <https://wicg.github.io/cookie-store/#cookie-maximum-name-value-pair-size>
+const MAX_NAME_VALUE_PAIR_SIZE: usize = 4096;
+
+/// The most bytes an attribute value of a cookie can have.
+/// <https://wicg.github.io/cookie-store/#cookie-maximum-attribute-value-size>
+const MAX_ATTRIBUTE_VALUE_SIZE: usize = 1024;
+
+// https://wicg.github.io/cookie-store/#cookiestore
+#[dom_struct]
+pub struct CookieStore {
+    eventtarget: EventTarget,
+}
+
+impl CookieStore {
+    fn new_inherited() -> CookieStore {
+        CookieStore {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CookieStore> {
+        let cookie_store = reflect_dom_object(
+            Box::new(CookieStore::new_inherited()),
+            global,
+            CookieStoreBinding::Wrap,
+        );
+        // Only documents get change events, service workers would have to
+        // subscribe to them, which isn't supported.
+        if global.is::<Window>() {
+            cookie_store.observe_changes();
+        }
+        cookie_store
+    }
+
+    /// Ask the cookie jar to report the changes to the cookies of the
+    /// document, to fire `change` events for them.
+    fn observe_changes(&self) {
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_cookie_store = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let change: CookieChange = message.to().unwrap();
+                let cookie_store = trusted_cookie_store.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(cookie_changed: move || {
+                        cookie_store.root().handle_change(change);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue cookie change task {:?}", err);
+                }
+            }),
+        );
+        let _ = global.resource_threads().send(ObserveCookieChanges(
+            global.pipeline_id(),
+            global.get_url(),
+            sender,
+        ));
+    }
+
+    /// https://wicg.github.io/cookie-store/#process-cookie-changes
+    fn handle_change(&self, change: CookieChange) {
+        if self.cookies_blocked() {
+            return;
+        }
+        let (changed, deleted) = match change {
+            CookieChange::Changed(cookie) => (vec![cookie_list_item(&cookie)], vec![]),
+            CookieChange::Deleted(cookie) => (vec![], vec![cookie_list_item(&cookie)]),
+        };
+        let event = CookieChangeEvent::new(
+            &self.global(),
+            atom!("change"),
+            false,
+            false,
+            &changed,
+            &deleted,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// Whether the cookies are hidden from the document, like they are from
+    /// `document.cookie`.
+    fn cookies_blocked(&self) -> bool {
+        match self.global().downcast::<Window>() {
+            Some(window) => {
+                let document = window.Document();
+                document.is_cookie_averse() || document.cookies_blocked()
+            },
+            None => false,
+        }
+    }
+
+    /// The URL whose cookies the methods with `options` are about.
+    fn url_for_options(&self, url: &Option<USVString>) -> Fallible<ServoUrl> {
+        let global = self.global();
+        let creation_url = global.get_url();
+        let url = match *url {
+            Some(ref url) => url,
+            None => return Ok(creation_url),
+        };
+        let parsed = global
+            .api_base_url()
+            .join(&url.0)
+            .map_err(|_| Error::Type(format!("Invalid URL {}", url.0)))?;
+        if global.is::<Window>() {
+            if parsed.as_url()[..Position::AfterQuery] !=
+                creation_url.as_url()[..Position::AfterQuery]
+            {
+                return Err(Error::Type("The URL must be the document's URL".to_owned()));
+            }
+        } else if parsed.origin() != creation_url.origin() {
+            return Err(Error::Type("The URL must be same-origin".to_owned()));
+        }
+        Ok(parsed)
+    }
+
+    /// https://wicg.github.io/cookie-store/#query-cookies
+    fn query_cookies(&self, url: ServoUrl, name: Option<&str>) -> Vec<CookieListItem> {
+        if self.cookies_blocked() {
+            return vec![];
+        }
+        let global = self.global();
+        let (sender, receiver) = profile_ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let _ = global
+            .resource_threads()
+            .send(GetCookiesDataForUrl(url, sender, NonHTTP));
+        receiver
+            .recv()
+            .unwrap()
+            .iter()
+            .filter(|cookie| name.map_or(true, |name| cookie.name() == name))
+            .map(|cookie| cookie_list_item(cookie))
+            .collect()
+    }
+
+    /// https://wicg.github.io/cookie-store/#set-a-cookie
+    fn set_a_cookie(
+        &self,
+        name: &str,
+        value: &str,
+        expires: Option<Tm>,
+        domain: Option<&str>,
+        path: &str,
+        same_site: CookieSameSite,
+    ) -> ErrorResult {
+        let global = self.global();
+        let url = global.get_url();
+
+        // Step 1.
+        let name = name.trim_matches(|c| c == '\t' || c == ' ');
+        let value = value.trim_matches(|c| c == '\t' || c == ' ');
+
+        // Steps 2-3.
+        let is_forbidden = |c: char| c == ';' || (c.is_control() && c != '\t');
+        if name.chars().any(is_forbidden) || value.chars().any(is_forbidden) {
+            return Err(Error::Type("Invalid character in the cookie".to_owned()));
+        }
+        if name.contains('=') {
+            return Err(Error::Type(
+                "The name of a cookie can't contain '='".to_owned(),
+            ));
+        }
+
+        // Step 4.
+        if name.is_empty() {
+            let lower_value = value.to_ascii_lowercase();
+            if value.is_empty() ||
+                value.contains('=') ||
+                lower_value.starts_with("__host-") ||
+                lower_value.starts_with("__secure-")
+            {
+                return Err(Error::Type("Invalid cookie without a name".to_owned()));
+            }
+        }
+
+        // Step 5.
+        if name.to_ascii_lowercase().starts_with("__host-") && (domain.is_some() || path != "/") {
+            return Err(Error::Type(
+                "__Host- cookies can't have a domain or a path".to_owned(),
+            ));
+        }
+
+        // Step 6.
+        if name.len() + value.len() > MAX_NAME_VALUE_PAIR_SIZE {
+            return Err(Error::Type("The cookie is too large".to_owned()));
+        }
+
+        // Steps 7-8.
+        let mut cookie = Cookie::build(name.to_owned(), value.to_owned());
+        if let Some(domain) = domain {
+            let host = url.host_str().unwrap_or("");
+            if domain.starts_with('.') ||
+                (host != domain && !host.ends_with(&format!(".{}", domain))) ||
+                domain.len() > MAX_ATTRIBUTE_VALUE_SIZE
+            {
+                return Err(Error::Type(format!("Invalid cookie domain {}", domain)));
+            }
+            cookie = cookie.domain(domain.to_owned());
+        }
+
+        // Step 9.
+        if let Some(expires) = expires {
+            cookie = cookie.expires(expires);
+        }
+
+        // Step 10.
+        if !path.starts_with('/') {
+            return Err(Error::Type(
+                "The cookie path must start with '/'".to_owned(),
+            ));
+        }
+        let mut path = path.to_owned();
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+        if path.len() > MAX_ATTRIBUTE_VALUE_SIZE {
+            return Err(Error::Type("The cookie path is too long".to_owned()));
+        }
+        cookie = cookie.path(path);
+
+        // Step 11. The cookies of insecure origins can't be secure, which
+        // can only happen here with the secure context requirement unchecked.
+        if url.is_secure_scheme() {
+            cookie = cookie.secure(true);
+        }
+
+        // Step 12.
+        cookie = cookie.same_site(match same_site {
+            CookieSameSite::Strict => SameSite::Strict,
+            CookieSameSite::Lax => SameSite::Lax,
+            CookieSameSite::None => SameSite::None,
+        });
+
+        // Steps 13-16.
+        if !self.cookies_blocked() {
+            let _ = global.resource_threads().send(SetCookieForUrl(
+                url,
+                Serde(cookie.finish()),
+                NonHTTP,
+            ));
+        }
+        Ok(())
+    }
+
+    /// https://wicg.github.io/cookie-store/#delete-a-cookie
+    fn delete_a_cookie(&self, name: &str, domain: Option<&str>, path: &str) -> ErrorResult {
+        // A cookie that expired a day ago replaces the cookie to delete.
+        let expires = time::now_utc() - time::Duration::days(1);
+        self.set_a_cookie(
+            name,
+            "",
+            Some(expires),
+            domain,
+            path,
+            CookieSameSite::Strict,
+        )
+    }
+
+    /// Resolve a promise with the result of `operation`, rejecting it if the
+    /// origin is opaque or if the operation failed.
+    fn promise<F>(&self, comp: InRealm, operation: F) -> Rc<Promise>
+    where
+        F: FnOnce(&Rc<Promise>) -> ErrorResult,
+    {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        if !global.origin().immutable().is_tuple() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+        if let Err(error) = operation(&promise) {
+            promise.reject_error(error);
+        }
+        promise
+    }
+}
+
+impl CookieStoreMethods for CookieStore {
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-get
+    fn Get(&self, name: USVString, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            let cookies = self.query_cookies(self.global().get_url(), Some(&name.0));
+            promise.resolve_native(&cookies.into_iter().next());
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-get-options
+    fn Get_(&self, options: &CookieStoreGetOptions, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            if options.name.is_none() && options.url.is_none() {
+                return Err(Error::Type("The options are empty".to_owned()));
+            }
+            let url = self.url_for_options(&options.url)?;
+            let name = options.name.as_ref().map(|name| &*name.0);
+            let cookies = self.query_cookies(url, name);
+            promise.resolve_native(&cookies.into_iter().next());
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-getall
+    fn GetAll(&self, name: USVString, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            let cookies = self.query_cookies(self.global().get_url(), Some(&name.0));
+            promise.resolve_native(&cookies);
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-getall-options
+    fn GetAll_(&self, options: &CookieStoreGetOptions, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            let url = self.url_for_options(&options.url)?;
+            let name = options.name.as_ref().map(|name| &*name.0);
+            let cookies = self.query_cookies(url, name);
+            promise.resolve_native(&cookies);
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-set
+    fn Set(&self, name: USVString, value: USVString, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            self.set_a_cookie(&name.0, &value.0, None, None, "/", CookieSameSite::Strict)?;
+            promise.resolve_native(&());
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-set-options
+    fn Set_(&self, options: &CookieInit, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            let expires = options
+                .expires
+                .map(|expires| tm_from_milliseconds(*expires));
+            self.set_a_cookie(
+                &options.name.0,
+                &options.value.0,
+                expires,
+                options.domain.as_ref().map(|domain| &*domain.0),
+                &options.path.0,
+                options.sameSite,
+            )?;
+            promise.resolve_native(&());
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-delete
+    fn Delete(&self, name: USVString, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            self.delete_a_cookie(&name.0, None, "/")?;
+            promise.resolve_native(&());
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-delete-options
+    fn Delete_(&self, options: &CookieStoreDeleteOptions, comp: InRealm) -> Rc<Promise> {
+        self.promise(comp, |promise| {
+            self.delete_a_cookie(
+                &options.name.0,
+                options.domain.as_ref().map(|domain| &*domain.0),
+                &options.path.0,
+            )?;
+            promise.resolve_native(&());
+            Ok(())
+        })
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-cookiestore-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+}
+
+/// https://wicg.github.io/cookie-store/#create-a-cookielistitem
+fn cookie_list_item(cookie: &Cookie) -> CookieListItem {
+    let mut item = CookieListItem::empty();
+    item.name = Some(USVString(cookie.name().to_owned()));
+    item.value = Some(USVString(cookie.value().to_owned()));
+    item.domain = Some(cookie.domain().map(|domain| USVString(domain.to_owned())));
+    item.path = Some(USVString(cookie.path().unwrap_or("/").to_owned()));
+    item.expires = Some(
+        cookie
+            .expires()
+            .map(|expires| Finite::wrap(milliseconds_from_tm(expires))),
+    );
+    item.secure = Some(cookie.secure().unwrap_or(false));
+    item.sameSite = Some(match cookie.same_site() {
+        Some(SameSite::Strict) => CookieSameSite::Strict,
+        Some(SameSite::Lax) => CookieSameSite::Lax,
+        Some(SameSite::None) | None => CookieSameSite::None,
+    });
+    item.partitioned = Some(false);
+    item
+}
+
+fn tm_from_milliseconds(milliseconds: f64) -> Tm {
+    let seconds = (milliseconds / 1000.).floor();
+    let nanoseconds = (milliseconds - seconds * 1000.) * 1_000_000.;
+    time::at_utc(Timespec::new(seconds as i64, nanoseconds as i32))
+}
+
+fn milliseconds_from_tm(tm: Tm) -> f64 {
+    let timespec = tm.to_timespec();
+    timespec.sec as f64 * 1000. + timespec.nsec as f64 / 1_000_000.
+}
@@ -0,0 +1,38 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CredentialBinding::CredentialMethods;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::str::{DOMString, USVString};
+use dom_struct::dom_struct;
+
+// https://w3c.github.io/webappsec-credential-management/#credential
+#[dom_struct]
+pub struct Credential {
+    reflector_: Reflector,
+    id: USVString,
+    type_: DOMString,
+}
+
+impl Credential {
+    pub fn new_inherited(id: String, type_: &str) -> Credential {
+        Credential {
+            reflector_: Reflector::new(),
+            id: USVString(id),
+            type_: DOMString::from(type_),
+        }
+    }
+}
+
+impl CredentialMethods for Credential {
+    // https://w3c.github.io/webappsec-credential-management/#dom-credential-id
+    fn Id(&self) -> USVString {
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credential-type
+    fn Type(&self) -> DOMString {
+        self.type_.clone()
+    }
+}
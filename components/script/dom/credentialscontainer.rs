@@ -0,0 +1,395 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorassertionresponse::AuthenticatorAssertionResponse;
+use crate::dom::authenticatorattestationresponse::AuthenticatorAttestationResponse;
+use crate::dom::authenticatorresponse::AuthenticatorResponse;
+use crate::dom::bindings::codegen::Bindings::CredentialsContainerBinding::{
+    self, CredentialCreationOptions, CredentialRequestOptions, CredentialsContainerMethods,
+};
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::{
+    PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::credential::Credential;
+use crate::dom::document::get_registrable_domain_suffix_of_or_is_equal_to;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::publickeycredential::PublicKeyCredential;
+use crate::dom::subtlecrypto::buffer_source_bytes;
+use crate::realms::InRealm;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crate::webauthn::{self, ClientDataType, ES256, RS256, SOFTWARE_AUTHENTICATOR};
+use dom_struct::dom_struct;
+use embedder_traits::{
+    Assertion, AttestedCredential, AuthenticatorError, EmbedderMsg, GetAssertionRequest,
+    MakeCredentialRequest,
+};
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use url::Host;
+
+// https://w3c.github.io/webappsec-credential-management/#credentialscontainer
+#[dom_struct]
+pub struct CredentialsContainer {
+    reflector_: Reflector,
+}
+
+impl CredentialsContainer {
+    fn new_inherited() -> CredentialsContainer {
+        CredentialsContainer {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<CredentialsContainer> {
+        reflect_dom_object(
+            Box::new(CredentialsContainer::new_inherited()),
+            global,
+            CredentialsContainerBinding::Wrap,
+        )
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-createCredential>
+    fn create_public_key_credential(
+        &self,
+        options: &PublicKeyCredentialCreationOptions,
+        promise: &Rc<Promise>,
+    ) -> ErrorResult {
+        // Step 2.
+        if !self.is_same_origin_with_ancestors() {
+            return Err(Error::NotAllowed);
+        }
+
+        // Step 4.
+        let selection = &options.authenticatorSelection;
+        let timeout = ceremony_timeout(options.timeout, &selection.userVerification);
+
+        // Step 5.
+        let user_id = buffer_source_bytes(&options.user.id);
+        if user_id.is_empty() || user_id.len() > 64 {
+            return Err(Error::Type(
+                "The user id must be between 1 and 64 bytes long".to_owned(),
+            ));
+        }
+
+        // Steps 6-8.
+        let rp_id = options.rp.id.as_ref().map(|id| &**id);
+        let (origin, rp_id) = self.caller_origin_and_rp_id(rp_id)?;
+
+        // Steps 9-10.
+        let algorithms = if options.pubKeyCredParams.is_empty() {
+            vec![ES256, RS256]
+        } else {
+            let algorithms: Vec<i32> = options
+                .pubKeyCredParams
+                .iter()
+                .filter(|parameters| &*parameters.type_ == "public-key")
+                .map(|parameters| parameters.alg)
+                .collect();
+            if algorithms.is_empty() {
+                return Err(Error::NotSupported);
+            }
+            algorithms
+        };
+
+        // Steps 13-16. No extension is supported, and the document is
+        // same-origin with its ancestors.
+        let challenge = buffer_source_bytes(&options.challenge);
+        let client_data_json =
+            webauthn::client_data_json(ClientDataType::Create, &challenge, &origin, false);
+        let request = MakeCredentialRequest {
+            client_data_hash: webauthn::sha256(&client_data_json),
+            origin,
+            rp_id,
+            rp_name: options.rp.parent.name.to_string(),
+            user_id,
+            user_name: options.user.parent.name.to_string(),
+            user_display_name: options.user.displayName.to_string(),
+            algorithms,
+            exclude_credentials: options
+                .excludeCredentials
+                .iter()
+                .filter(|descriptor| &*descriptor.type_ == "public-key")
+                .map(|descriptor| buffer_source_bytes(&descriptor.id))
+                .collect(),
+            require_resident_key: match selection.residentKey {
+                Some(ref resident_key) => &**resident_key == "required",
+                None => selection.requireResidentKey,
+            },
+            require_user_verification: &*selection.userVerification == "required",
+            timeout,
+        };
+
+        // Steps 19-22.
+        self.run_authenticator_operation(
+            promise,
+            move |global, sender| {
+                if pref!(dom.webauthn.testing.enabled) {
+                    let result = SOFTWARE_AUTHENTICATOR
+                        .lock()
+                        .unwrap()
+                        .make_credential(&request);
+                    let _ = sender.send(result);
+                } else {
+                    global.send_to_embedder(EmbedderMsg::MakeCredential(request, sender));
+                }
+            },
+            move |global, credential: AttestedCredential| {
+                let response = AuthenticatorAttestationResponse::new(
+                    global,
+                    client_data_json,
+                    credential.attestation_object,
+                );
+                PublicKeyCredential::new(
+                    global,
+                    credential.credential_id,
+                    response.upcast::<AuthenticatorResponse>(),
+                )
+            },
+        );
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-getAssertion>
+    fn get_public_key_credential(
+        &self,
+        options: &PublicKeyCredentialRequestOptions,
+        promise: &Rc<Promise>,
+    ) -> ErrorResult {
+        // Step 2.
+        if !self.is_same_origin_with_ancestors() {
+            return Err(Error::NotAllowed);
+        }
+
+        // Step 4.
+        let timeout = ceremony_timeout(options.timeout, &options.userVerification);
+
+        // Steps 5-7.
+        let rp_id = options.rpId.as_ref().map(|id| &*id.0);
+        let (origin, rp_id) = self.caller_origin_and_rp_id(rp_id)?;
+
+        // Steps 10-13.
+        let challenge = buffer_source_bytes(&options.challenge);
+        let client_data_json =
+            webauthn::client_data_json(ClientDataType::Get, &challenge, &origin, false);
+        let request = GetAssertionRequest {
+            client_data_hash: webauthn::sha256(&client_data_json),
+            origin,
+            rp_id,
+            allow_credentials: options
+                .allowCredentials
+                .iter()
+                .filter(|descriptor| &*descriptor.type_ == "public-key")
+                .map(|descriptor| buffer_source_bytes(&descriptor.id))
+                .collect(),
+            require_user_verification: &*options.userVerification == "required",
+            timeout,
+        };
+
+        // Steps 17-20.
+        self.run_authenticator_operation(
+            promise,
+            move |global, sender| {
+                if pref!(dom.webauthn.testing.enabled) {
+                    let result = SOFTWARE_AUTHENTICATOR
+                        .lock()
+                        .unwrap()
+                        .get_assertion(&request);
+                    let _ = sender.send(result);
+                } else {
+                    global.send_to_embedder(EmbedderMsg::GetAssertion(request, sender));
+                }
+            },
+            move |global, assertion: Assertion| {
+                let credential_id = assertion.credential_id.clone();
+                let response =
+                    AuthenticatorAssertionResponse::new(global, client_data_json, assertion);
+                PublicKeyCredential::new(
+                    global,
+                    credential_id,
+                    response.upcast::<AuthenticatorResponse>(),
+                )
+            },
+        );
+        Ok(())
+    }
+
+    /// Sends a request to an authenticator with `send`, and then queues a
+    /// task to settle `promise` with the credential that `resolve` makes out
+    /// of the reply, or with the error of the authenticator.
+    fn run_authenticator_operation<T, S, R>(&self, promise: &Rc<Promise>, send: S, resolve: R)
+    where
+        T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+        S: FnOnce(&GlobalScope, IpcSender<Result<T, AuthenticatorError>>),
+        R: FnOnce(&GlobalScope, T) -> DomRoot<PublicKeyCredential> + Send + 'static,
+    {
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let mut pending = Some((
+            TrustedPromise::new(promise.clone()),
+            Trusted::new(self),
+            resolve,
+        ));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let result: Result<T, AuthenticatorError> = message.to().unwrap();
+                let (promise, container, resolve) = match pending.take() {
+                    Some(pending) => pending,
+                    None => {
+                        warn!("The authenticator replied more than once");
+                        return;
+                    },
+                };
+                let result = task_source.queue_with_canceller(
+                    task!(settle_credential_promise: move || {
+                        let promise = promise.root();
+                        match result {
+                            Ok(value) => {
+                                let credential = resolve(&*container.root().global(), value);
+                                promise.resolve_native(&*credential);
+                            },
+                            Err(error) => promise.reject_error(authenticator_error(error)),
+                        }
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue credential task {:?}", err);
+                }
+            }),
+        );
+        send(&*global, sender);
+    }
+
+    /// The serialized origin of the caller and the id of the relying party,
+    /// which is `rp_id` if it is allowed for the origin, and the effective
+    /// domain of the origin by default.
+    /// <https://w3c.github.io/webauthn/#rp-id>
+    fn caller_origin_and_rp_id(&self, rp_id: Option<&str>) -> Result<(String, String), Error> {
+        let global = self.global();
+        let origin = global.origin();
+        if !origin.is_tuple() {
+            return Err(Error::NotAllowed);
+        }
+        let effective_domain = match origin.effective_domain() {
+            Some(host @ Host::Domain(_)) => host,
+            _ => return Err(Error::Security),
+        };
+        let rp_id = match rp_id {
+            Some(rp_id) => get_registrable_domain_suffix_of_or_is_equal_to(rp_id, effective_domain)
+                .ok_or(Error::Security)?,
+            None => effective_domain,
+        };
+        Ok((origin.immutable().ascii_serialization(), rp_id.to_string()))
+    }
+
+    /// Whether the document is same-origin with the documents of all the
+    /// browsing contexts it is nested in, as credentials can't be used from
+    /// cross-origin frames.
+    fn is_same_origin_with_ancestors(&self) -> bool {
+        let document = self.global().as_window().Document();
+        let browsing_context = match document.browsing_context() {
+            Some(browsing_context) => browsing_context,
+            None => return false,
+        };
+        let mut ancestor = browsing_context.parent();
+        while let Some(parent) = ancestor {
+            // Documents in another event loop are of another site.
+            match parent.document() {
+                Some(ref parent_document)
+                    if parent_document.origin().same_origin(document.origin()) => {},
+                _ => return false,
+            }
+            ancestor = parent.parent();
+        }
+        true
+    }
+}
+
+impl CredentialsContainerMethods for CredentialsContainer {
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-get
+    fn Get(
+        &self,
+        options: RootedTraceableBox<CredentialRequestOptions>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        // Public key credentials are the only type of credential, so they
+        // are the only one that can be requested.
+        let result = match options.publicKey {
+            Some(ref public_key) => self.get_public_key_credential(public_key, &promise),
+            None => Err(Error::NotSupported),
+        };
+        if let Err(error) = result {
+            promise.reject_error(error);
+        }
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-store
+    fn Store(&self, _credential: &Credential, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        // https://w3c.github.io/webauthn/#sctn-storeCredential
+        promise.reject_error(Error::NotSupported);
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-create
+    fn Create(
+        &self,
+        options: RootedTraceableBox<CredentialCreationOptions>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let result = match options.publicKey {
+            Some(ref public_key) => self.create_public_key_credential(public_key, &promise),
+            None => Err(Error::NotSupported),
+        };
+        if let Err(error) = result {
+            promise.reject_error(error);
+        }
+        promise
+    }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-credentialscontainer-preventsilentaccess
+    fn PreventSilentAccess(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        // Public key credentials are never used without mediation, so there
+        // is no origin state to update.
+        promise.resolve_native(&());
+        promise
+    }
+}
+
+/// The timeout of a ceremony in milliseconds, clamped to the recommended
+/// range for its user verification requirement.
+/// <https://w3c.github.io/webauthn/#sctn-timeout-recommended-range>
+fn ceremony_timeout(timeout: Option<u32>, user_verification: &str) -> u64 {
+    let (min, max, default) = if user_verification == "discouraged" {
+        (30_000, 180_000, 120_000)
+    } else {
+        (300_000, 600_000, 300_000)
+    };
+    timeout.map_or(default, |timeout| (timeout as u64).max(min).min(max))
+}
+
+fn authenticator_error(error: AuthenticatorError) -> Error {
+    match error {
+        AuthenticatorError::NotAllowed => Error::NotAllowed,
+        AuthenticatorError::InvalidState => Error::InvalidState,
+        AuthenticatorError::NotSupported => Error::NotSupported,
+    }
+}
@@ -0,0 +1,150 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{
+    self, AesKeyAlgorithm, CryptoKeyMethods, EcKeyAlgorithm, HmacKeyAlgorithm, KeyAlgorithm,
+    KeyType, RsaHashedKeyAlgorithm,
+};
+use crate::dom::bindings::conversions::ToJSValConvertible;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+use crate::webcrypto::{self, Key};
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::MutableHandleValue;
+use js::typedarray::{CreateWith, Uint8Array};
+use std::ptr::{self, NonNull};
+
+unsafe_no_jsmanaged_fields!(Key);
+
+// https://w3c.github.io/webcrypto/#cryptokey-interface
+#[dom_struct]
+pub struct CryptoKey {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Defined in openssl"]
+    key: Key,
+    #[ignore_malloc_size_of = "Defined in mozjs"]
+    algorithm: Heap<*mut JSObject>,
+    #[ignore_malloc_size_of = "Defined in mozjs"]
+    usages: Heap<*mut JSObject>,
+}
+
+impl CryptoKey {
+    fn new_inherited(key: Key) -> CryptoKey {
+        CryptoKey {
+            reflector_: Reflector::new(),
+            key,
+            algorithm: Heap::default(),
+            usages: Heap::default(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, key: Key) -> DomRoot<CryptoKey> {
+        reflect_dom_object(
+            Box::new(CryptoKey::new_inherited(key)),
+            global,
+            CryptoKeyBinding::Wrap,
+        )
+    }
+
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+}
+
+impl CryptoKeyMethods for CryptoKey {
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-type
+    fn Type(&self) -> KeyType {
+        self.key.key_type()
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-extractable
+    fn Extractable(&self) -> bool {
+        self.key.extractable
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm
+    fn Algorithm(&self, cx: JSContext) -> NonNull<JSObject> {
+        if self.algorithm.get().is_null() {
+            rooted!(in(*cx) let mut algorithm = UndefinedValue());
+            unsafe { key_algorithm_to_jsval(cx, &self.key.algorithm, algorithm.handle_mut()) };
+            self.algorithm.set(algorithm.to_object());
+        }
+        NonNull::new(self.algorithm.get()).unwrap()
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-usages
+    fn Usages(&self, cx: JSContext) -> NonNull<JSObject> {
+        if self.usages.get().is_null() {
+            rooted!(in(*cx) let mut usages = UndefinedValue());
+            unsafe { self.key.usages.to_jsval(*cx, usages.handle_mut()) };
+            self.usages.set(usages.to_object());
+        }
+        NonNull::new(self.usages.get()).unwrap()
+    }
+}
+
+fn key_algorithm(name: &str) -> KeyAlgorithm {
+    KeyAlgorithm {
+        name: DOMString::from(name),
+    }
+}
+
+/// Converts the algorithm of a key to the dictionary which its `algorithm`
+/// attribute reflects.
+#[allow(unsafe_code)]
+unsafe fn key_algorithm_to_jsval(
+    cx: JSContext,
+    algorithm: &webcrypto::KeyAlgorithm,
+    rval: MutableHandleValue,
+) {
+    let name = algorithm.name().name();
+    match *algorithm {
+        webcrypto::KeyAlgorithm::Aes { length, .. } => AesKeyAlgorithm {
+            parent: key_algorithm(name),
+            length,
+        }
+        .to_jsval(*cx, rval),
+        webcrypto::KeyAlgorithm::Hmac { hash, length } => HmacKeyAlgorithm {
+            parent: key_algorithm(name),
+            hash: key_algorithm(hash.name()),
+            length,
+        }
+        .to_jsval(*cx, rval),
+        webcrypto::KeyAlgorithm::RsaHashed {
+            modulus_length,
+            ref public_exponent,
+            hash,
+            ..
+        } => {
+            rooted!(in(*cx) let mut exponent = ptr::null_mut::<JSObject>());
+            assert!(Uint8Array::create(
+                *cx,
+                CreateWith::Slice(public_exponent),
+                exponent.handle_mut()
+            )
+            .is_ok());
+            RsaHashedKeyAlgorithm {
+                parent: key_algorithm(name),
+                modulusLength: modulus_length,
+                publicExponent: RootedTraceableBox::from_box(Heap::boxed(exponent.get())),
+                hash: key_algorithm(hash.name()),
+            }
+            .to_jsval(*cx, rval)
+        },
+        webcrypto::KeyAlgorithm::Ec { named_curve, .. } => EcKeyAlgorithm {
+            parent: key_algorithm(name),
+            namedCurve: DOMString::from(named_curve.name()),
+        }
+        .to_jsval(*cx, rval),
+        webcrypto::KeyAlgorithm::Kdf { .. } => key_algorithm(name).to_jsval(*cx, rval),
+    }
+}
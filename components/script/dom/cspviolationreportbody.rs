@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding;
+use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::CSPViolationReportBodyMethods;
+use crate::dom::bindings::codegen::Bindings::CSPViolationReportBodyBinding::SecurityPolicyViolationEventDisposition;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::reporting::CSPViolationReportData;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct CSPViolationReportBody {
+    report_body: ReportBody,
+    data: CSPViolationReportData,
+}
+
+impl CSPViolationReportBody {
+    fn new_inherited(data: CSPViolationReportData) -> CSPViolationReportBody {
+        CSPViolationReportBody {
+            report_body: ReportBody::new_inherited(),
+            data,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        data: CSPViolationReportData,
+    ) -> DomRoot<CSPViolationReportBody> {
+        reflect_dom_object(
+            Box::new(CSPViolationReportBody::new_inherited(data)),
+            global,
+            CSPViolationReportBodyBinding::Wrap,
+        )
+    }
+}
+
+impl CSPViolationReportBodyMethods for CSPViolationReportBody {
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-documenturl
+    fn DocumentURL(&self) -> USVString {
+        USVString(self.data.document_url.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-referrer
+    fn GetReferrer(&self) -> Option<USVString> {
+        self.data.referrer.clone().map(USVString)
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-blockedurl
+    fn GetBlockedURL(&self) -> Option<USVString> {
+        self.data.blocked_url.clone().map(USVString)
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-effectivedirective
+    fn EffectiveDirective(&self) -> DOMString {
+        DOMString::from(self.data.effective_directive.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-originalpolicy
+    fn OriginalPolicy(&self) -> DOMString {
+        DOMString::from(self.data.original_policy.clone())
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-sourcefile
+    fn GetSourceFile(&self) -> Option<USVString> {
+        self.data.source_file.clone().map(USVString)
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-sample
+    fn GetSample(&self) -> Option<DOMString> {
+        self.data.sample.clone().map(DOMString::from)
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-disposition
+    fn Disposition(&self) -> SecurityPolicyViolationEventDisposition {
+        match &*self.data.disposition {
+            "report" => SecurityPolicyViolationEventDisposition::Report,
+            _ => SecurityPolicyViolationEventDisposition::Enforce,
+        }
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-statuscode
+    fn StatusCode(&self) -> u16 {
+        self.data.status_code
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-linenumber
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.data.line_number
+    }
+
+    // https://w3c.github.io/webappsec-csp/#dom-cspviolationreportbody-columnnumber
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.data.column_number
+    }
+}
@@ -2,17 +2,25 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::CSSBinding::PropertyDefinition;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::Reflector;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::cssunitvalue::CSSUnitValue;
 use crate::dom::window::Window;
 use crate::dom::worklet::Worklet;
 use cssparser::{serialize_identifier, Parser, ParserInput};
 use dom_struct::dom_struct;
+use servo_atoms::Atom;
 use style::context::QuirksMode;
+use style::custom_properties::{self, VariableValue};
 use style::parser::ParserContext;
+use style::properties_and_values::registry::PropertyRegistration;
+use style::properties_and_values::syntax::Descriptor;
 use style::stylesheets::supports_rule::{parse_condition_or_declaration, Declaration};
 use style::stylesheets::CssRuleType;
 use style_traits::ParsingMode;
@@ -22,6 +30,17 @@ pub struct CSS {
     reflector_: Reflector,
 }
 
+/// <https://drafts.css-houdini.org/css-typed-om-1/#numeric-factory>
+macro_rules! numeric_factories(
+    ( $([$factory:ident, $unit:expr],)* ) => (
+        $(
+            pub fn $factory(win: &Window, value: Finite<f64>) -> DomRoot<CSSUnitValue> {
+                CSSUnitValue::new(win.upcast(), *value, String::from($unit))
+            }
+        )*
+    );
+);
+
 #[allow(non_snake_case)]
 impl CSS {
     /// <http://dev.w3.org/csswg/cssom/#serialize-an-identifier>
@@ -75,4 +94,61 @@ impl CSS {
     pub fn PaintWorklet(win: &Window) -> DomRoot<Worklet> {
         win.paint_worklet()
     }
+
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#the-registerproperty-function>
+    pub fn RegisterProperty(win: &Window, definition: &PropertyDefinition) -> ErrorResult {
+        let name = custom_properties::parse_name(&definition.name).map_err(|()| Error::Syntax)?;
+        let syntax = Descriptor::from_str(&definition.syntax).map_err(|()| Error::Syntax)?;
+        let initial_value = match definition.initialValue {
+            Some(ref initial_value) => {
+                let mut input = ParserInput::new(initial_value);
+                let mut input = Parser::new(&mut input);
+                Some(VariableValue::parse(&mut input).map_err(|_| Error::Syntax)?)
+            },
+            None => None,
+        };
+        let registration = PropertyRegistration {
+            name: Atom::from(name),
+            syntax,
+            inherits: definition.inherits,
+            initial_value,
+            url_data: win.Document().url(),
+        };
+        registration.validate().map_err(|()| Error::Syntax)?;
+        win.Document()
+            .register_custom_property(registration)
+            .map_err(|()| Error::InvalidModification)
+    }
+
+    numeric_factories!(
+        [Number, "number"],
+        [Percent, "percent"],
+        [Em, "em"],
+        [Ex, "ex"],
+        [Ch, "ch"],
+        [Rem, "rem"],
+        [Vw, "vw"],
+        [Vh, "vh"],
+        [Vmin, "vmin"],
+        [Vmax, "vmax"],
+        [Cm, "cm"],
+        [Mm, "mm"],
+        [Q, "q"],
+        [In, "in"],
+        [Pt, "pt"],
+        [Pc, "pc"],
+        [Px, "px"],
+        [Deg, "deg"],
+        [Grad, "grad"],
+        [Rad, "rad"],
+        [Turn, "turn"],
+        [S, "s"],
+        [Ms, "ms"],
+        [Hz, "hz"],
+        [KHz, "khz"],
+        [Dpi, "dpi"],
+        [Dpcm, "dpcm"],
+        [Dppx, "dppx"],
+        [Fr, "fr"],
+    );
 }
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CSSKeywordValueBinding::{
+    CSSKeywordValueMethods, Wrap,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cssstylevalue::CSSStyleValue;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use cssparser::serialize_identifier;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct CSSKeywordValue {
+    style_value: CSSStyleValue,
+    value: DomRefCell<String>,
+}
+
+impl CSSKeywordValue {
+    fn new_inherited(value: String) -> CSSKeywordValue {
+        CSSKeywordValue {
+            style_value: CSSStyleValue::new_inherited(String::new()),
+            value: DomRefCell::new(value),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, value: String) -> DomRoot<CSSKeywordValue> {
+        reflect_dom_object(
+            Box::new(CSSKeywordValue::new_inherited(value)),
+            global,
+            Wrap,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csskeywordvalue-csskeywordvalue>
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window, value: USVString) -> Fallible<DomRoot<CSSKeywordValue>> {
+        if value.0.is_empty() {
+            return Err(Error::Type("Empty keyword".to_owned()));
+        }
+        Ok(CSSKeywordValue::new(window.upcast(), value.0))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#keywordvalue-serialization>
+    pub fn serialize(&self) -> String {
+        let mut serialization = String::new();
+        serialize_identifier(&self.value.borrow(), &mut serialization).unwrap();
+        serialization
+    }
+}
+
+impl CSSKeywordValueMethods for CSSKeywordValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csskeywordvalue-value>
+    fn Value(&self) -> USVString {
+        USVString(self.value.borrow().clone())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csskeywordvalue-value>
+    fn SetValue(&self, value: USVString) -> ErrorResult {
+        if value.0.is_empty() {
+            return Err(Error::Type("Empty keyword".to_owned()));
+        }
+        *self.value.borrow_mut() = value.0;
+        Ok(())
+    }
+}
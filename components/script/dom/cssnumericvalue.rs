@@ -0,0 +1,77 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSNumericValueBinding::CSSNumericValueMethods;
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrCSSNumericValue;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cssstylevalue::CSSStyleValue;
+use crate::dom::cssunitvalue::{parse_unit, CSSUnitValue, UnitCategory};
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+/// A numeric value, which is always a `CSSUnitValue` since the math values
+/// aren't supported.
+#[dom_struct]
+pub struct CSSNumericValue {
+    style_value: CSSStyleValue,
+}
+
+impl CSSNumericValue {
+    pub fn new_inherited() -> CSSNumericValue {
+        CSSNumericValue {
+            style_value: CSSStyleValue::new_inherited(String::new()),
+        }
+    }
+
+    fn unit_value(&self) -> &CSSUnitValue {
+        self.downcast::<CSSUnitValue>()
+            .expect("Numeric values should be unit values")
+    }
+
+    pub fn category(&self) -> UnitCategory {
+        self.unit_value().category()
+    }
+
+    /// The value converted to `unit`, if possible.
+    /// See `CSSUnitValue::convert`.
+    pub fn convert(&self, unit: &str) -> Option<f64> {
+        self.unit_value().convert(unit)
+    }
+
+    pub fn serialize(&self) -> String {
+        self.unit_value().serialize()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#rectify-a-numberish-value>
+    pub fn rectify(global: &GlobalScope, value: DoubleOrCSSNumericValue) -> DomRoot<Self> {
+        match value {
+            DoubleOrCSSNumericValue::Double(value) => {
+                DomRoot::upcast(CSSUnitValue::new(global, *value, String::from("number")))
+            },
+            DoubleOrCSSNumericValue::CSSNumericValue(value) => value,
+        }
+    }
+
+    /// The value as a numberish value, the reverse of `rectify` except that
+    /// numbers stay numeric values.
+    pub fn to_numberish(&self) -> DoubleOrCSSNumericValue {
+        DoubleOrCSSNumericValue::CSSNumericValue(DomRoot::from_ref(self))
+    }
+}
+
+impl CSSNumericValueMethods for CSSNumericValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssnumericvalue-to>
+    fn To(&self, unit: USVString) -> Fallible<DomRoot<CSSUnitValue>> {
+        let unit =
+            parse_unit(&unit.0).ok_or_else(|| Error::Type(format!("Invalid unit: {}", unit.0)))?;
+        let value = self.convert(&unit).ok_or_else(|| {
+            Error::Type(format!("Cannot convert {} to {}", self.serialize(), unit))
+        })?;
+        Ok(CSSUnitValue::new(&self.global(), value, unit))
+    }
+}
@@ -0,0 +1,204 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSRotateBinding::{CSSRotateMethods, Wrap};
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrCSSNumericValue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutDom};
+use crate::dom::cssnumericvalue::CSSNumericValue;
+use crate::dom::csstransformcomponent::{value_for_matrix, CSSTransformComponent};
+use crate::dom::cssunitvalue::{CSSUnitValue, UnitCategory};
+use crate::dom::dommatrixreadonly::DOMMatrixReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct CSSRotate {
+    transform_component: CSSTransformComponent,
+    x: MutDom<CSSNumericValue>,
+    y: MutDom<CSSNumericValue>,
+    z: MutDom<CSSNumericValue>,
+    angle: MutDom<CSSNumericValue>,
+}
+
+fn check_number(value: &CSSNumericValue) -> ErrorResult {
+    if value.category() != UnitCategory::Number {
+        return Err(Error::Type(format!(
+            "{} is not a number",
+            value.serialize()
+        )));
+    }
+    Ok(())
+}
+
+fn check_angle(value: &CSSNumericValue) -> ErrorResult {
+    if value.category() != UnitCategory::Angle {
+        return Err(Error::Type(format!(
+            "{} is not an angle",
+            value.serialize()
+        )));
+    }
+    Ok(())
+}
+
+impl CSSRotate {
+    fn new_inherited(
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: &CSSNumericValue,
+        angle: &CSSNumericValue,
+        is_2d: bool,
+    ) -> CSSRotate {
+        CSSRotate {
+            transform_component: CSSTransformComponent::new_inherited(is_2d),
+            x: MutDom::new(x),
+            y: MutDom::new(y),
+            z: MutDom::new(z),
+            angle: MutDom::new(angle),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: &CSSNumericValue,
+        angle: &CSSNumericValue,
+        is_2d: bool,
+    ) -> DomRoot<CSSRotate> {
+        reflect_dom_object(
+            Box::new(CSSRotate::new_inherited(x, y, z, angle, is_2d)),
+            global,
+            Wrap,
+        )
+    }
+
+    /// A rotation around the z axis.
+    pub fn new_2d(global: &GlobalScope, angle: &CSSNumericValue) -> DomRoot<CSSRotate> {
+        let number = |value| CSSUnitValue::new(global, value, String::from("number"));
+        CSSRotate::new(
+            global,
+            number(0.).upcast(),
+            number(0.).upcast(),
+            number(1.).upcast(),
+            angle,
+            true,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-cssrotate>
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window, angle: &CSSNumericValue) -> Fallible<DomRoot<CSSRotate>> {
+        check_angle(angle)?;
+        Ok(CSSRotate::new_2d(window.upcast(), angle))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-cssrotate-x-y-z-angle>
+    #[allow(non_snake_case)]
+    pub fn Constructor_(
+        window: &Window,
+        x: DoubleOrCSSNumericValue,
+        y: DoubleOrCSSNumericValue,
+        z: DoubleOrCSSNumericValue,
+        angle: &CSSNumericValue,
+    ) -> Fallible<DomRoot<CSSRotate>> {
+        let global = window.upcast::<GlobalScope>();
+        let x = CSSNumericValue::rectify(global, x);
+        let y = CSSNumericValue::rectify(global, y);
+        let z = CSSNumericValue::rectify(global, z);
+        check_number(&x)?;
+        check_number(&y)?;
+        check_number(&z)?;
+        check_angle(angle)?;
+        Ok(CSSRotate::new(global, &x, &y, &z, angle, false))
+    }
+
+    pub fn apply_to(&self, matrix: &DOMMatrixReadOnly) -> ErrorResult {
+        let angle = value_for_matrix(&self.angle.get(), "deg")?;
+        if self.transform_component.is_2d() {
+            matrix.rotate_axis_angle_self(0., 0., 1., angle);
+        } else {
+            matrix.rotate_axis_angle_self(
+                value_for_matrix(&self.x.get(), "number")?,
+                value_for_matrix(&self.y.get(), "number")?,
+                value_for_matrix(&self.z.get(), "number")?,
+                angle,
+            );
+        }
+        Ok(())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssrotate>
+    pub fn serialize(&self) -> String {
+        if self.transform_component.is_2d() {
+            format!("rotate({})", self.angle.get().serialize())
+        } else {
+            format!(
+                "rotate3d({}, {}, {}, {})",
+                self.x.get().serialize(),
+                self.y.get().serialize(),
+                self.z.get().serialize(),
+                self.angle.get().serialize()
+            )
+        }
+    }
+
+    fn set_coordinate(
+        &self,
+        coordinate: &MutDom<CSSNumericValue>,
+        value: DoubleOrCSSNumericValue,
+    ) -> ErrorResult {
+        let value = CSSNumericValue::rectify(&self.global(), value);
+        check_number(&value)?;
+        coordinate.set(&value);
+        Ok(())
+    }
+}
+
+impl CSSRotateMethods for CSSRotate {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-x>
+    fn X(&self) -> DoubleOrCSSNumericValue {
+        self.x.get().to_numberish()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-x>
+    fn SetX(&self, value: DoubleOrCSSNumericValue) -> ErrorResult {
+        self.set_coordinate(&self.x, value)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-y>
+    fn Y(&self) -> DoubleOrCSSNumericValue {
+        self.y.get().to_numberish()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-y>
+    fn SetY(&self, value: DoubleOrCSSNumericValue) -> ErrorResult {
+        self.set_coordinate(&self.y, value)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-z>
+    fn Z(&self) -> DoubleOrCSSNumericValue {
+        self.z.get().to_numberish()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-z>
+    fn SetZ(&self, value: DoubleOrCSSNumericValue) -> ErrorResult {
+        self.set_coordinate(&self.z, value)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-angle>
+    fn Angle(&self) -> DomRoot<CSSNumericValue> {
+        self.angle.get()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssrotate-angle>
+    fn SetAngle(&self, value: &CSSNumericValue) -> ErrorResult {
+        check_angle(value)?;
+        self.angle.set(value);
+        Ok(())
+    }
+}
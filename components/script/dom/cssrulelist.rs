@@ -18,6 +18,7 @@ use crate::stylesheet_loader::StylesheetLoader;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
 use style::shared_lock::Locked;
+use style::stylesheets::StylesheetLoader as StyleStylesheetLoader;
 use style::stylesheets::{CssRules, CssRulesHelpers, KeyframesRule, RulesMutateError};
 
 #[allow(unsafe_code)]
@@ -104,12 +105,12 @@ impl CSSRuleList {
         let index = idx as usize;
 
         let parent_stylesheet = self.parent_stylesheet.style_stylesheet();
-        let owner = self
+        // Constructed stylesheets have no owner to load imports for, and
+        // reject `@import` rules.
+        let loader = self
             .parent_stylesheet
             .get_owner()
-            .downcast::<HTMLElement>()
-            .unwrap();
-        let loader = StylesheetLoader::for_element(owner);
+            .map(|owner| StylesheetLoader::for_element(owner.downcast::<HTMLElement>().unwrap()));
         let new_rule = css_rules.with_raw_offset_arc(|arc| {
             arc.insert_rule(
                 &parent_stylesheet.shared_lock,
@@ -117,7 +118,9 @@ impl CSSRuleList {
                 &parent_stylesheet.contents,
                 index,
                 nested,
-                Some(&loader),
+                loader
+                    .as_ref()
+                    .map(|loader| loader as &dyn StyleStylesheetLoader),
             )
         })?;
 
@@ -160,6 +163,24 @@ impl CSSRuleList {
         }
     }
 
+    /// Recreates the DOM rules after the rules of the parent stylesheet were
+    /// replaced. Should only be called for CssRules-backed rules.
+    pub fn reset_rules(&self) {
+        let css_rules = if let RulesSource::Rules(ref rules) = self.rules {
+            rules
+        } else {
+            panic!("Called reset_rules on non-CssRule-backed CSSRuleList");
+        };
+        self.deparent_all();
+        let guard = self.parent_stylesheet.shared_lock().read();
+        *self.dom_rules.borrow_mut() = css_rules
+            .read_with(&guard)
+            .0
+            .iter()
+            .map(|_| MutNullableDom::new(None))
+            .collect();
+    }
+
     pub fn item(&self, idx: u32) -> Option<DomRoot<CSSRule>> {
         self.dom_rules.borrow().get(idx as usize).map(|rule| {
             rule.or_init(|| {
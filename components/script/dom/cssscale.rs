@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSScaleBinding::{CSSScaleMethods, Wrap};
+use crate::dom::bindings::codegen::UnionTypes::DoubleOrCSSNumericValue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutDom};
+use crate::dom::cssnumericvalue::CSSNumericValue;
+use crate::dom::csstransformcomponent::{value_for_matrix, CSSTransformComponent};
+use crate::dom::cssunitvalue::{CSSUnitValue, UnitCategory};
+use crate::dom::dommatrixreadonly::DOMMatrixReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct CSSScale {
+    transform_component: CSSTransformComponent,
+    x: MutDom<CSSNumericValue>,
+    y: MutDom<CSSNumericValue>,
+    z: MutDom<CSSNumericValue>,
+}
+
+fn check_number(value: &CSSNumericValue) -> ErrorResult {
+    if value.category() != UnitCategory::Number {
+        return Err(Error::Type(format!(
+            "{} is not a number",
+            value.serialize()
+        )));
+    }
+    Ok(())
+}
+
+impl CSSScale {
+    fn new_inherited(
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: &CSSNumericValue,
+        is_2d: bool,
+    ) -> CSSScale {
+        CSSScale {
+            transform_component: CSSTransformComponent::new_inherited(is_2d),
+            x: MutDom::new(x),
+            y: MutDom::new(y),
+            z: MutDom::new(z),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: &CSSNumericValue,
+        is_2d: bool,
+    ) -> DomRoot<CSSScale> {
+        reflect_dom_object(
+            Box::new(CSSScale::new_inherited(x, y, z, is_2d)),
+            global,
+            Wrap,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-cssscale>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        x: DoubleOrCSSNumericValue,
+        y: DoubleOrCSSNumericValue,
+        z: Option<DoubleOrCSSNumericValue>,
+    ) -> Fallible<DomRoot<CSSScale>> {
+        let global = window.upcast::<GlobalScope>();
+        let x = CSSNumericValue::rectify(global, x);
+        let y = CSSNumericValue::rectify(global, y);
+        check_number(&x)?;
+        check_number(&y)?;
+        let scale = match z {
+            Some(z) => {
+                let z = CSSNumericValue::rectify(global, z);
+                check_number(&z)?;
+                CSSScale::new(global, &x, &y, &z, false)
+            },
+            None => {
+                let z = CSSUnitValue::new(global, 1., String::from("number"));
+                CSSScale::new(global, &x, &y, z.upcast(), true)
+            },
+        };
+        Ok(scale)
+    }
+
+    pub fn apply_to(&self, matrix: &DOMMatrixReadOnly) -> ErrorResult {
+        let x = value_for_matrix(&self.x.get(), "number")?;
+        let y = value_for_matrix(&self.y.get(), "number")?;
+        let z = if self.transform_component.is_2d() {
+            1.
+        } else {
+            value_for_matrix(&self.z.get(), "number")?
+        };
+        matrix.scale_self(x, Some(y), z, 0., 0., 0.);
+        Ok(())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssscale>
+    pub fn serialize(&self) -> String {
+        if self.transform_component.is_2d() {
+            format!(
+                "scale({}, {})",
+                self.x.get().serialize(),
+                self.y.get().serialize()
+            )
+        } else {
+            format!(
+                "scale3d({}, {}, {})",
+                self.x.get().serialize(),
+                self.y.get().serialize(),
+                self.z.get().serialize()
+            )
+        }
+    }
+
+    fn set_coordinate(
+        &self,
+        coordinate: &MutDom<CSSNumericValue>,
+        value: DoubleOrCSSNumericValue,
+    ) -> ErrorResult {
+        let value = CSSNumericValue::rectify(&self.global(), value);
+        check_number(&value)?;
+        coordinate.set(&value);
+        Ok(())
+    }
+}
+
+impl CSSScaleMethods for CSSScale {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-x>
+    fn X(&self) -> DoubleOrCSSNumericValue {
+        self.x.get().to_numberish()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-x>
+    fn SetX(&self, value: DoubleOrCSSNumericValue) -> ErrorResult {
+        self.set_coordinate(&self.x, value)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-y>
+    fn Y(&self) -> DoubleOrCSSNumericValue {
+        self.y.get().to_numberish()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-y>
+    fn SetY(&self, value: DoubleOrCSSNumericValue) -> ErrorResult {
+        self.set_coordinate(&self.y, value)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-z>
+    fn Z(&self) -> DoubleOrCSSNumericValue {
+        self.z.get().to_numberish()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssscale-z>
+    fn SetZ(&self, value: DoubleOrCSSNumericValue) -> ErrorResult {
+        self.set_coordinate(&self.z, value)
+    }
+}
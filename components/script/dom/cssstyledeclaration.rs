@@ -13,7 +13,7 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::cssrule::CSSRule;
 use crate::dom::element::Element;
-use crate::dom::node::{document_from_node, stylesheets_owner_from_node, window_from_node, Node};
+use crate::dom::node::{document_from_node, window_from_node, Node};
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use servo_arc::Arc;
@@ -51,7 +51,7 @@ pub enum CSSStyleOwner {
 impl CSSStyleOwner {
     // Mutate the declaration block associated to this style owner, and
     // optionally indicate if it has changed (assumed to be true).
-    fn mutate_associated_block<F, R>(&self, f: F) -> R
+    pub fn mutate_associated_block<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut PropertyDeclarationBlock, &mut bool) -> R,
     {
@@ -115,15 +115,14 @@ impl CSSStyleOwner {
                 if changed {
                     // If this is changed, see also
                     // CSSStyleRule::SetSelectorText, which does the same thing.
-                    stylesheets_owner_from_node(rule.parent_stylesheet().owner().upcast::<Node>())
-                        .invalidate_stylesheets();
+                    rule.parent_stylesheet().invalidate_stylesheets();
                 }
                 result
             },
         }
     }
 
-    fn with_block<F, R>(&self, f: F) -> R
+    pub fn with_block<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&PropertyDeclarationBlock) -> R,
     {
@@ -146,14 +145,14 @@ impl CSSStyleOwner {
         }
     }
 
-    fn window(&self) -> DomRoot<Window> {
+    pub fn window(&self) -> DomRoot<Window> {
         match *self {
             CSSStyleOwner::Element(ref el) => window_from_node(&**el),
             CSSStyleOwner::CSSRule(ref rule, _) => DomRoot::from_ref(rule.global().as_window()),
         }
     }
 
-    fn base_url(&self) -> ServoUrl {
+    pub fn base_url(&self) -> ServoUrl {
         match *self {
             CSSStyleOwner::Element(ref el) => window_from_node(&**el).Document().base_url(),
             CSSStyleOwner::CSSRule(ref rule, _) => (*rule
@@ -194,7 +193,7 @@ macro_rules! css_properties(
     );
 );
 
-fn remove_property(decls: &mut PropertyDeclarationBlock, id: &PropertyId) -> bool {
+pub fn remove_property(decls: &mut PropertyDeclarationBlock, id: &PropertyId) -> bool {
     let first_declaration = decls.first_declaration_to_remove(id);
     let first_declaration = match first_declaration {
         Some(i) => i,
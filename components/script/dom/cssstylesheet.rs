@@ -2,28 +2,49 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding;
-use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::CSSStyleSheetMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleSheetBinding::{
+    CSSStyleSheetInit, CSSStyleSheetMethods,
+};
+use crate::dom::bindings::codegen::Bindings::MediaListBinding::MediaListMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::MediaListOrString;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::cssrulelist::{CSSRuleList, RulesSource};
+use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::node::{stylesheets_owner_from_node, Node};
+use crate::dom::promise::Promise;
 use crate::dom::stylesheet::StyleSheet;
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
+use crate::realms::{AlreadyInRealm, InRealm};
+use cssparser::{Parser, ParserInput};
 use dom_struct::dom_struct;
 use servo_arc::Arc;
+use servo_config::pref;
 use std::cell::Cell;
+use std::rc::Rc;
+use style::media_queries::MediaList;
+use style::parser::ParserContext;
 use style::shared_lock::SharedRwLock;
-use style::stylesheets::Stylesheet as StyleStyleSheet;
+use style::stylesheets::{CssRuleType, Origin, Stylesheet as StyleStyleSheet};
+use style_traits::ParsingMode;
 
 #[dom_struct]
 pub struct CSSStyleSheet {
     stylesheet: StyleSheet,
-    owner: Dom<Element>,
+    /// The element that owns the sheet, or `None` for the sheets created with
+    /// the constructor.
+    owner: Option<Dom<Element>>,
+    /// The documents and shadow roots that adopted the sheet.
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    adopters: DomRefCell<Vec<StyleSheetListOwner>>,
     rulelist: MutNullableDom<CSSRuleList>,
     #[ignore_malloc_size_of = "Arc"]
     style_stylesheet: Arc<StyleStyleSheet>,
@@ -31,8 +52,9 @@ pub struct CSSStyleSheet {
 }
 
 impl CSSStyleSheet {
+    #[allow(unrooted_must_root)]
     fn new_inherited(
-        owner: &Element,
+        owner: Option<&Element>,
         type_: DOMString,
         href: Option<DOMString>,
         title: Option<DOMString>,
@@ -40,7 +62,8 @@ impl CSSStyleSheet {
     ) -> CSSStyleSheet {
         CSSStyleSheet {
             stylesheet: StyleSheet::new_inherited(type_, href, title),
-            owner: Dom::from_ref(owner),
+            owner: owner.map(Dom::from_ref),
+            adopters: DomRefCell::new(vec![]),
             rulelist: MutNullableDom::new(None),
             style_stylesheet: stylesheet,
             origin_clean: Cell::new(true),
@@ -58,15 +81,73 @@ impl CSSStyleSheet {
     ) -> DomRoot<CSSStyleSheet> {
         reflect_dom_object(
             Box::new(CSSStyleSheet::new_inherited(
-                owner, type_, href, title, stylesheet,
+                Some(owner),
+                type_,
+                href,
+                title,
+                stylesheet,
             )),
             window,
             CSSStyleSheetBinding::Wrap,
         )
     }
 
-    pub fn owner(&self) -> DomRoot<Element> {
-        DomRoot::from_ref(&*self.owner)
+    /// <https://drafts.csswg.org/cssom/#dom-cssstylesheet-cssstylesheet>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        options: &CSSStyleSheetInit,
+    ) -> Fallible<DomRoot<CSSStyleSheet>> {
+        if !pref!(dom.constructable_stylesheets.enabled) {
+            return Err(Error::Type("Illegal constructor.".to_owned()));
+        }
+        let document = window.Document();
+        let url = match options.baseURL {
+            Some(ref base_url) => document
+                .base_url()
+                .join(base_url)
+                .map_err(|_| Error::NotAllowed)?,
+            None => document.base_url(),
+        };
+        let media_text = match options.media {
+            Some(MediaListOrString::MediaList(ref media)) => media.MediaText(),
+            Some(MediaListOrString::String(ref media)) => media.clone(),
+            None => DOMString::new(),
+        };
+        let context = ParserContext::new_for_cssom(
+            &url,
+            Some(CssRuleType::Media),
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+            window.css_error_reporter(),
+            None,
+        );
+        let mut input = ParserInput::new(&media_text);
+        let media = MediaList::parse(&context, &mut Parser::new(&mut input));
+        let shared_lock = document.style_shared_lock().clone();
+        let stylesheet = StyleStyleSheet::from_str(
+            "",
+            url,
+            Origin::Author,
+            Arc::new(shared_lock.wrap(media)),
+            shared_lock,
+            None,
+            window.css_error_reporter(),
+            document.quirks_mode(),
+            0,
+        );
+        stylesheet.set_disabled(options.disabled);
+        Ok(reflect_dom_object(
+            Box::new(CSSStyleSheet::new_inherited(
+                None,
+                "text/css".into(),
+                Some(DOMString::from(document.base_url().into_string())),
+                None,
+                Arc::new(stylesheet),
+            )),
+            window,
+            CSSStyleSheetBinding::Wrap,
+        ))
     }
 
     fn rulelist(&self) -> DomRoot<CSSRuleList> {
@@ -80,16 +161,70 @@ impl CSSStyleSheet {
         self.style_stylesheet.disabled()
     }
 
-    pub fn get_owner(&self) -> &Element {
-        &*self.owner
+    pub fn get_owner(&self) -> Option<&Element> {
+        self.owner.as_deref()
     }
 
     pub fn set_disabled(&self, disabled: bool) {
         if self.style_stylesheet.set_disabled(disabled) {
-            stylesheets_owner_from_node(self.owner().upcast::<Node>()).invalidate_stylesheets();
+            self.invalidate_stylesheets();
         }
     }
 
+    /// Whether the sheet was created with the constructor, and can be adopted
+    /// by `document` and its shadow roots.
+    /// <https://drafts.csswg.org/cssom/#concept-css-style-sheet-constructed-flag>
+    pub fn is_constructed_in(&self, document: &Document) -> bool {
+        self.owner.is_none() && *self.global().as_window().Document() == *document
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn add_adopter(&self, adopter: StyleSheetListOwner) {
+        let mut adopters = self.adopters.borrow_mut();
+        if !adopters.contains(&adopter) {
+            adopters.push(adopter);
+        }
+    }
+
+    pub fn remove_adopter(&self, adopter: &StyleSheetListOwner) {
+        self.adopters.borrow_mut().retain(|other| other != adopter);
+    }
+
+    /// Invalidates the styles of the document or shadow root that owns the
+    /// sheet, or of all the ones that adopted it, after the sheet changed.
+    pub fn invalidate_stylesheets(&self) {
+        match self.owner {
+            Some(ref owner) => {
+                stylesheets_owner_from_node(owner.upcast::<Node>()).invalidate_stylesheets()
+            },
+            None => {
+                for adopter in self.adopters.borrow().iter() {
+                    adopter.invalidate_stylesheets();
+                }
+            },
+        }
+    }
+
+    /// Replaces the rules of a constructed sheet with the ones in `text`,
+    /// ignoring any `@import` rule.
+    /// <https://drafts.csswg.org/cssom/#synchronously-replace-the-rules-of-a-cssstylesheet>
+    fn replace_rules(&self, text: &str) {
+        let global = self.global();
+        let url_data = self.style_stylesheet.contents.url_data.read().clone();
+        StyleStyleSheet::update_from_str(
+            &self.style_stylesheet,
+            text,
+            url_data,
+            None,
+            global.as_window().css_error_reporter(),
+            0,
+        );
+        if let Some(rulelist) = self.rulelist.get() {
+            rulelist.reset_rules();
+        }
+        self.invalidate_stylesheets();
+    }
+
     pub fn shared_lock(&self) -> &SharedRwLock {
         &self.style_stylesheet.shared_lock
     }
@@ -98,6 +233,10 @@ impl CSSStyleSheet {
         &self.style_stylesheet
     }
 
+    pub fn style_stylesheet_arc(&self) -> &Arc<StyleStyleSheet> {
+        &self.style_stylesheet
+    }
+
     pub fn set_origin_clean(&self, origin_clean: bool) {
         self.origin_clean.set(origin_clean);
     }
@@ -117,8 +256,11 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
-        self.rulelist()
-            .insert_rule(&rule, index, /* nested */ false)
+        let index = self
+            .rulelist()
+            .insert_rule(&rule, index, /* nested */ false)?;
+        self.invalidate_stylesheets();
+        Ok(index)
     }
 
     // https://drafts.csswg.org/cssom/#dom-cssstylesheet-deleterule
@@ -126,6 +268,37 @@ impl CSSStyleSheetMethods for CSSStyleSheet {
         if !self.origin_clean.get() {
             return Err(Error::Security);
         }
-        self.rulelist().remove_rule(index)
+        self.rulelist().remove_rule(index)?;
+        self.invalidate_stylesheets();
+        Ok(())
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-replace
+    fn Replace(&self, text: USVString) -> Rc<Promise> {
+        let global = self.global();
+        let in_realm_proof = AlreadyInRealm::assert(&global);
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&global, InRealm::Already(&in_realm_proof));
+        // Step 2.
+        if self.owner.is_some() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        // Steps 3-4. There are no imports to wait for, since they are ignored,
+        // so the rules are replaced right away.
+        self.replace_rules(&text.0);
+        promise.resolve_native(self);
+        promise
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-cssstylesheet-replacesync
+    fn ReplaceSync(&self, text: USVString) -> ErrorResult {
+        // Step 1.
+        if self.owner.is_some() {
+            return Err(Error::NotAllowed);
+        }
+        // Steps 2-4.
+        self.replace_rules(&text.0);
+        Ok(())
     }
 }
@@ -4,24 +4,39 @@
 
 use crate::dom::bindings::codegen::Bindings::CSSStyleValueBinding::CSSStyleValueMethods;
 use crate::dom::bindings::codegen::Bindings::CSSStyleValueBinding::Wrap;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::reflector::Reflector;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::csskeywordvalue::CSSKeywordValue;
+use crate::dom::csstransformvalue::CSSTransformValue;
+use crate::dom::cssunitvalue::CSSUnitValue;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use cssparser::Parser;
 use cssparser::ParserInput;
 use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_config::pref;
 use servo_url::ServoUrl;
+use style::context::QuirksMode;
+use style::properties::{parse_one_declaration_into, SourcePropertyDeclaration};
+use style::properties::{Importance, LonghandId, PropertyDeclarationBlock, PropertyId};
+use style_traits::ParsingMode;
 
 #[dom_struct]
 pub struct CSSStyleValue {
     reflector: Reflector,
+    /// The serialization of the value, for the values that aren't of one of
+    /// the subclasses.
     value: String,
 }
 
 impl CSSStyleValue {
-    fn new_inherited(value: String) -> CSSStyleValue {
+    pub fn new_inherited(value: String) -> CSSStyleValue {
         CSSStyleValue {
             reflector: Reflector::new(),
             value: value,
@@ -31,12 +46,103 @@ impl CSSStyleValue {
     pub fn new(global: &GlobalScope, value: String) -> DomRoot<CSSStyleValue> {
         reflect_dom_object(Box::new(CSSStyleValue::new_inherited(value)), global, Wrap)
     }
+
+    /// Whether the typed OM is exposed to `obj`: to paint worklets, which
+    /// get the style values of the elements they paint, and to windows if
+    /// the typed OM is enabled.
+    #[allow(unsafe_code)]
+    pub fn is_exposed(_: JSContext, obj: HandleObject) -> bool {
+        let global = unsafe { GlobalScope::from_object(obj.get()) };
+        if global.is::<Window>() {
+            pref!(dom.typed_om.enabled)
+        } else {
+            pref!(dom.worklet.enabled)
+        }
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssstylevalue-parse>
+    #[allow(non_snake_case)]
+    pub fn Parse(
+        global: &GlobalScope,
+        property: USVString,
+        css_text: USVString,
+    ) -> Fallible<DomRoot<CSSStyleValue>> {
+        let id = PropertyId::parse_enabled_for_all_content(&property.0)
+            .map_err(|()| Error::Type(format!("Unknown property: {}", property.0)))?;
+        let mut declarations = SourcePropertyDeclaration::new();
+        parse_one_declaration_into(
+            &mut declarations,
+            id.clone(),
+            &css_text.0,
+            &global.api_base_url(),
+            None,
+            ParsingMode::DEFAULT,
+            QuirksMode::NoQuirks,
+        )
+        .map_err(|()| Error::Type(format!("Invalid value for {}: {}", property.0, css_text.0)))?;
+        let mut block = PropertyDeclarationBlock::new();
+        block.extend(declarations.drain(), Importance::Normal);
+        let mut serialization = String::new();
+        block
+            .property_value_to_css(&id, &mut serialization)
+            .unwrap();
+        Ok(CSSStyleValue::reify(global, &id, serialization))
+    }
+
+    /// Reifies the serialization of a value of `property` from the style
+    /// system as the style value of its type in the typed OM, or as a plain
+    /// style value if the typed OM doesn't support its type.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#reify-as-a-cssstylevalue>
+    pub fn reify(
+        global: &GlobalScope,
+        property: &PropertyId,
+        serialization: String,
+    ) -> DomRoot<CSSStyleValue> {
+        // The values of custom properties are unparsed values, which aren't
+        // supported.
+        if let PropertyId::Custom(_) = *property {
+            return CSSStyleValue::new(global, serialization);
+        }
+        let is_transform = property.longhand_id() == Some(LonghandId::Transform);
+        let reified = {
+            let mut input = ParserInput::new(&serialization);
+            let mut parser = Parser::new(&mut input);
+            parser
+                .parse_entirely(|input| -> Result<DomRoot<CSSStyleValue>, _> {
+                    if let Ok(ident) = input.r#try(|input| input.expect_ident_cloned()) {
+                        return Ok(DomRoot::upcast(CSSKeywordValue::new(
+                            global,
+                            ident.to_string(),
+                        )));
+                    }
+                    if is_transform {
+                        return CSSTransformValue::parse(global, input).map(DomRoot::upcast);
+                    }
+                    CSSUnitValue::parse(global, input).map(DomRoot::upcast)
+                })
+                .ok()
+        };
+        reified.unwrap_or_else(|| CSSStyleValue::new(global, serialization))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#cssstylevalue-serialization>
+    pub fn serialize(&self) -> String {
+        if let Some(value) = self.downcast::<CSSUnitValue>() {
+            value.serialize()
+        } else if let Some(value) = self.downcast::<CSSKeywordValue>() {
+            value.serialize()
+        } else if let Some(value) = self.downcast::<CSSTransformValue>() {
+            value.serialize()
+        } else {
+            self.value.clone()
+        }
+    }
 }
 
 impl CSSStyleValueMethods for CSSStyleValue {
     /// <https://drafts.css-houdini.org/css-typed-om-1/#CSSStyleValue-stringification-behavior>
     fn Stringifier(&self) -> DOMString {
-        DOMString::from(&*self.value)
+        DOMString::from(self.serialize())
     }
 }
 
@@ -0,0 +1,99 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSTransformComponentBinding::CSSTransformComponentMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cssnumericvalue::CSSNumericValue;
+use crate::dom::cssrotate::CSSRotate;
+use crate::dom::cssscale::CSSScale;
+use crate::dom::csstranslate::CSSTranslate;
+use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::dommatrixreadonly::DOMMatrixReadOnly;
+use dom_struct::dom_struct;
+use euclid::default::Transform3D;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct CSSTransformComponent {
+    reflector_: Reflector,
+    is_2d: Cell<bool>,
+}
+
+impl CSSTransformComponent {
+    pub fn new_inherited(is_2d: bool) -> CSSTransformComponent {
+        CSSTransformComponent {
+            reflector_: Reflector::new(),
+            is_2d: Cell::new(is_2d),
+        }
+    }
+
+    pub fn is_2d(&self) -> bool {
+        self.is_2d.get()
+    }
+
+    /// Post-multiplies `matrix` by the matrix of the component, which fails
+    /// if the component has lengths that depend on the element it applies to.
+    pub fn apply_to(&self, matrix: &DOMMatrixReadOnly) -> ErrorResult {
+        if let Some(translate) = self.downcast::<CSSTranslate>() {
+            translate.apply_to(matrix)
+        } else if let Some(rotate) = self.downcast::<CSSRotate>() {
+            rotate.apply_to(matrix)
+        } else if let Some(scale) = self.downcast::<CSSScale>() {
+            scale.apply_to(matrix)
+        } else {
+            unreachable!("Unknown transform component")
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        if let Some(translate) = self.downcast::<CSSTranslate>() {
+            translate.serialize()
+        } else if let Some(rotate) = self.downcast::<CSSRotate>() {
+            rotate.serialize()
+        } else if let Some(scale) = self.downcast::<CSSScale>() {
+            scale.serialize()
+        } else {
+            unreachable!("Unknown transform component")
+        }
+    }
+}
+
+impl CSSTransformComponentMethods for CSSTransformComponent {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-csstransformcomponent>
+    fn Stringifier(&self) -> DOMString {
+        DOMString::from(self.serialize())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformcomponent-is2d>
+    fn Is2D(&self) -> bool {
+        self.is_2d.get()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformcomponent-is2d>
+    fn SetIs2D(&self, value: bool) {
+        self.is_2d.set(value);
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformcomponent-tomatrix>
+    fn ToMatrix(&self) -> Fallible<DomRoot<DOMMatrix>> {
+        let matrix = DOMMatrix::new(&self.global(), true, Transform3D::identity());
+        self.apply_to(matrix.upcast())?;
+        Ok(matrix)
+    }
+}
+
+/// The value of `value` converted to `unit` to compute a matrix, which
+/// requires the value not to depend on the element it applies to.
+pub fn value_for_matrix(value: &CSSNumericValue, unit: &str) -> Fallible<f64> {
+    value.convert(unit).ok_or_else(|| {
+        Error::Type(format!(
+            "Cannot compute a matrix with {}",
+            value.serialize()
+        ))
+    })
+}
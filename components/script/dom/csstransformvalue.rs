@@ -0,0 +1,309 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSTransformValueBinding::{
+    CSSTransformValueMethods, Wrap,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::cssrotate::CSSRotate;
+use crate::dom::cssscale::CSSScale;
+use crate::dom::cssstylevalue::CSSStyleValue;
+use crate::dom::csstransformcomponent::CSSTransformComponent;
+use crate::dom::csstranslate::CSSTranslate;
+use crate::dom::cssunitvalue::{CSSUnitValue, UnitCategory};
+use crate::dom::dommatrix::DOMMatrix;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use cssparser::{ParseError, Parser};
+use dom_struct::dom_struct;
+use euclid::default::Transform3D;
+
+#[dom_struct]
+pub struct CSSTransformValue {
+    style_value: CSSStyleValue,
+    components: Vec<Dom<CSSTransformComponent>>,
+}
+
+impl CSSTransformValue {
+    fn new_inherited(components: &[DomRoot<CSSTransformComponent>]) -> CSSTransformValue {
+        CSSTransformValue {
+            style_value: CSSStyleValue::new_inherited(String::new()),
+            components: components
+                .iter()
+                .map(|component| Dom::from_ref(&**component))
+                .collect(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        components: &[DomRoot<CSSTransformComponent>],
+    ) -> DomRoot<CSSTransformValue> {
+        reflect_dom_object(
+            Box::new(CSSTransformValue::new_inherited(components)),
+            global,
+            Wrap,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformvalue-csstransformvalue>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        transforms: Vec<DomRoot<CSSTransformComponent>>,
+    ) -> Fallible<DomRoot<CSSTransformValue>> {
+        if transforms.is_empty() {
+            return Err(Error::Type("Empty list of transforms".to_owned()));
+        }
+        Ok(CSSTransformValue::new(window.upcast(), &transforms))
+    }
+
+    /// Parses a list of transform functions that all have a component in
+    /// the typed OM.
+    pub fn parse<'i, 't>(
+        global: &GlobalScope,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<DomRoot<CSSTransformValue>, ParseError<'i, ()>> {
+        let mut components = vec![];
+        while !input.is_exhausted() {
+            let name = input.expect_function()?.clone();
+            let component =
+                input.parse_nested_block(|input| parse_component(global, &name, input))?;
+            components.push(component);
+        }
+        if components.is_empty() {
+            return Err(input.new_custom_error(()));
+        }
+        Ok(CSSTransformValue::new(global, &components))
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-csstransformvalue>
+    pub fn serialize(&self) -> String {
+        self.components
+            .iter()
+            .map(|component| component.serialize())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl CSSTransformValueMethods for CSSTransformValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformvalue-length>
+    fn Length(&self) -> u32 {
+        self.components.len() as u32
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformvalue-is2d>
+    fn Is2D(&self) -> bool {
+        self.components.iter().all(|component| component.is_2d())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstransformvalue-tomatrix>
+    fn ToMatrix(&self) -> Fallible<DomRoot<DOMMatrix>> {
+        let matrix = DOMMatrix::new(&self.global(), true, Transform3D::identity());
+        for component in &self.components {
+            component.apply_to(matrix.upcast())?;
+        }
+        Ok(matrix)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#csstransformvalue-determine-the-value-of-an-indexed-property>
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<CSSTransformComponent>> {
+        self.components
+            .get(index as usize)
+            .map(|component| DomRoot::from_ref(&**component))
+    }
+}
+
+/// Parses a value of `category`, where a zero number is accepted as a zero
+/// in `zero_unit` if any.
+fn parse_value<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+    categories: &[UnitCategory],
+    zero_unit: Option<&str>,
+) -> Result<DomRoot<CSSUnitValue>, ParseError<'i, ()>> {
+    let location = input.current_source_location();
+    let value = CSSUnitValue::parse(global, input)?;
+    if categories.contains(&value.category()) {
+        return Ok(value);
+    }
+    match zero_unit {
+        Some(unit) if value.category() == UnitCategory::Number && value.value() == 0. => {
+            Ok(CSSUnitValue::new(global, 0., unit.to_owned()))
+        },
+        _ => Err(location.new_custom_error(())),
+    }
+}
+
+fn parse_length_percentage<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSUnitValue>, ParseError<'i, ()>> {
+    parse_value(
+        global,
+        input,
+        &[UnitCategory::Length, UnitCategory::Percent],
+        Some("px"),
+    )
+}
+
+fn parse_length<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSUnitValue>, ParseError<'i, ()>> {
+    parse_value(global, input, &[UnitCategory::Length], Some("px"))
+}
+
+fn parse_number<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSUnitValue>, ParseError<'i, ()>> {
+    parse_value(global, input, &[UnitCategory::Number], None)
+}
+
+fn parse_angle<'i, 't>(
+    global: &GlobalScope,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSUnitValue>, ParseError<'i, ()>> {
+    parse_value(global, input, &[UnitCategory::Angle], Some("deg"))
+}
+
+fn translate(
+    global: &GlobalScope,
+    x: DomRoot<CSSUnitValue>,
+    y: DomRoot<CSSUnitValue>,
+    z: DomRoot<CSSUnitValue>,
+    is_2d: bool,
+) -> DomRoot<CSSTransformComponent> {
+    DomRoot::upcast(CSSTranslate::new(
+        global,
+        x.upcast(),
+        y.upcast(),
+        z.upcast(),
+        is_2d,
+    ))
+}
+
+fn scale(
+    global: &GlobalScope,
+    x: DomRoot<CSSUnitValue>,
+    y: DomRoot<CSSUnitValue>,
+    z: DomRoot<CSSUnitValue>,
+    is_2d: bool,
+) -> DomRoot<CSSTransformComponent> {
+    DomRoot::upcast(CSSScale::new(
+        global,
+        x.upcast(),
+        y.upcast(),
+        z.upcast(),
+        is_2d,
+    ))
+}
+
+/// Parses the arguments of the transform function `name`, for the functions
+/// that have a component in the typed OM.
+/// <https://drafts.css-houdini.org/css-typed-om-1/#reify-a-transform-function>
+fn parse_component<'i, 't>(
+    global: &GlobalScope,
+    name: &str,
+    input: &mut Parser<'i, 't>,
+) -> Result<DomRoot<CSSTransformComponent>, ParseError<'i, ()>> {
+    let px = |value| CSSUnitValue::new(global, value, String::from("px"));
+    let number = |value| CSSUnitValue::new(global, value, String::from("number"));
+    let component = match_ignore_ascii_case! { name,
+        "translate" => {
+            let x = parse_length_percentage(global, input)?;
+            let y = if input.r#try(|input| input.expect_comma()).is_ok() {
+                parse_length_percentage(global, input)?
+            } else {
+                px(0.)
+            };
+            translate(global, x, y, px(0.), true)
+        },
+        "translatex" => {
+            let x = parse_length_percentage(global, input)?;
+            translate(global, x, px(0.), px(0.), true)
+        },
+        "translatey" => {
+            let y = parse_length_percentage(global, input)?;
+            translate(global, px(0.), y, px(0.), true)
+        },
+        "translatez" => {
+            let z = parse_length(global, input)?;
+            translate(global, px(0.), px(0.), z, false)
+        },
+        "translate3d" => {
+            let x = parse_length_percentage(global, input)?;
+            input.expect_comma()?;
+            let y = parse_length_percentage(global, input)?;
+            input.expect_comma()?;
+            let z = parse_length(global, input)?;
+            translate(global, x, y, z, false)
+        },
+        "scale" => {
+            let x = parse_number(global, input)?;
+            let y = if input.r#try(|input| input.expect_comma()).is_ok() {
+                parse_number(global, input)?
+            } else {
+                number(x.value())
+            };
+            scale(global, x, y, number(1.), true)
+        },
+        "scalex" => {
+            let x = parse_number(global, input)?;
+            scale(global, x, number(1.), number(1.), true)
+        },
+        "scaley" => {
+            let y = parse_number(global, input)?;
+            scale(global, number(1.), y, number(1.), true)
+        },
+        "scalez" => {
+            let z = parse_number(global, input)?;
+            scale(global, number(1.), number(1.), z, false)
+        },
+        "scale3d" => {
+            let x = parse_number(global, input)?;
+            input.expect_comma()?;
+            let y = parse_number(global, input)?;
+            input.expect_comma()?;
+            let z = parse_number(global, input)?;
+            scale(global, x, y, z, false)
+        },
+        "rotate" => {
+            let angle = parse_angle(global, input)?;
+            DomRoot::upcast(CSSRotate::new_2d(global, angle.upcast()))
+        },
+        "rotatex" | "rotatey" | "rotatez" => {
+            let angle = parse_angle(global, input)?;
+            let axis = |axis_name: &str| {
+                if name.eq_ignore_ascii_case(axis_name) { number(1.) } else { number(0.) }
+            };
+            DomRoot::upcast(CSSRotate::new(
+                global,
+                axis("rotatex").upcast(),
+                axis("rotatey").upcast(),
+                axis("rotatez").upcast(),
+                angle.upcast(),
+                false,
+            ))
+        },
+        "rotate3d" => {
+            let x = parse_number(global, input)?;
+            input.expect_comma()?;
+            let y = parse_number(global, input)?;
+            input.expect_comma()?;
+            let z = parse_number(global, input)?;
+            input.expect_comma()?;
+            let angle = parse_angle(global, input)?;
+            let (x, y, z) = (x.upcast(), y.upcast(), z.upcast());
+            DomRoot::upcast(CSSRotate::new(global, x, y, z, angle.upcast(), false))
+        },
+        _ => return Err(input.new_custom_error(())),
+    };
+    Ok(component)
+}
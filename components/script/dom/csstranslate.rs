@@ -0,0 +1,165 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSTranslateBinding::{CSSTranslateMethods, Wrap};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutDom};
+use crate::dom::cssnumericvalue::CSSNumericValue;
+use crate::dom::csstransformcomponent::{value_for_matrix, CSSTransformComponent};
+use crate::dom::cssunitvalue::{CSSUnitValue, UnitCategory};
+use crate::dom::dommatrixreadonly::DOMMatrixReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct CSSTranslate {
+    transform_component: CSSTransformComponent,
+    x: MutDom<CSSNumericValue>,
+    y: MutDom<CSSNumericValue>,
+    z: MutDom<CSSNumericValue>,
+}
+
+fn check_length_percentage(value: &CSSNumericValue) -> ErrorResult {
+    match value.category() {
+        UnitCategory::Length | UnitCategory::Percent => Ok(()),
+        _ => Err(Error::Type(format!(
+            "{} is not a length or a percentage",
+            value.serialize()
+        ))),
+    }
+}
+
+fn check_length(value: &CSSNumericValue) -> ErrorResult {
+    if value.category() != UnitCategory::Length {
+        return Err(Error::Type(format!(
+            "{} is not a length",
+            value.serialize()
+        )));
+    }
+    Ok(())
+}
+
+impl CSSTranslate {
+    fn new_inherited(
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: &CSSNumericValue,
+        is_2d: bool,
+    ) -> CSSTranslate {
+        CSSTranslate {
+            transform_component: CSSTransformComponent::new_inherited(is_2d),
+            x: MutDom::new(x),
+            y: MutDom::new(y),
+            z: MutDom::new(z),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: &CSSNumericValue,
+        is_2d: bool,
+    ) -> DomRoot<CSSTranslate> {
+        reflect_dom_object(
+            Box::new(CSSTranslate::new_inherited(x, y, z, is_2d)),
+            global,
+            Wrap,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-csstranslate>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        x: &CSSNumericValue,
+        y: &CSSNumericValue,
+        z: Option<&CSSNumericValue>,
+    ) -> Fallible<DomRoot<CSSTranslate>> {
+        check_length_percentage(x)?;
+        check_length_percentage(y)?;
+        let translate = match z {
+            Some(z) => {
+                check_length(z)?;
+                CSSTranslate::new(window.upcast(), x, y, z, false)
+            },
+            None => {
+                let z = CSSUnitValue::new(window.upcast(), 0., String::from("px"));
+                CSSTranslate::new(window.upcast(), x, y, z.upcast(), true)
+            },
+        };
+        Ok(translate)
+    }
+
+    pub fn apply_to(&self, matrix: &DOMMatrixReadOnly) -> ErrorResult {
+        let x = value_for_matrix(&self.x.get(), "px")?;
+        let y = value_for_matrix(&self.y.get(), "px")?;
+        let z = if self.transform_component.is_2d() {
+            0.
+        } else {
+            value_for_matrix(&self.z.get(), "px")?
+        };
+        matrix.translate_self(x, y, z);
+        Ok(())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-csstranslate>
+    pub fn serialize(&self) -> String {
+        if self.transform_component.is_2d() {
+            format!(
+                "translate({}, {})",
+                self.x.get().serialize(),
+                self.y.get().serialize()
+            )
+        } else {
+            format!(
+                "translate3d({}, {}, {})",
+                self.x.get().serialize(),
+                self.y.get().serialize(),
+                self.z.get().serialize()
+            )
+        }
+    }
+}
+
+impl CSSTranslateMethods for CSSTranslate {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-x>
+    fn X(&self) -> DomRoot<CSSNumericValue> {
+        self.x.get()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-x>
+    fn SetX(&self, value: &CSSNumericValue) -> ErrorResult {
+        check_length_percentage(value)?;
+        self.x.set(value);
+        Ok(())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-y>
+    fn Y(&self) -> DomRoot<CSSNumericValue> {
+        self.y.get()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-y>
+    fn SetY(&self, value: &CSSNumericValue) -> ErrorResult {
+        check_length_percentage(value)?;
+        self.y.set(value);
+        Ok(())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-z>
+    fn Z(&self) -> DomRoot<CSSNumericValue> {
+        self.z.get()
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-csstranslate-z>
+    fn SetZ(&self, value: &CSSNumericValue) -> ErrorResult {
+        check_length(value)?;
+        self.z.set(value);
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CSSUnitValueBinding::{CSSUnitValueMethods, Wrap};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::USVString;
+use crate::dom::cssnumericvalue::CSSNumericValue;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use cssparser::{ParseError, Parser, Token};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::f64::consts::PI;
+use style_traits::{CssWriter, ToCss};
+
+/// The kinds of quantities that numeric values measure, between which they
+/// can't be converted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnitCategory {
+    Number,
+    Percent,
+    Length,
+    Angle,
+    Time,
+    Frequency,
+    Resolution,
+    Flex,
+}
+
+/// The category of `unit` and the factor that converts its values to the
+/// canonical unit of the category, if they can be converted without knowing
+/// the element they apply to. `unit` is in lowercase.
+fn unit_info(unit: &str) -> Option<(UnitCategory, Option<f64>)> {
+    Some(match unit {
+        "number" => (UnitCategory::Number, Some(1.)),
+        "percent" => (UnitCategory::Percent, Some(1.)),
+        "em" | "ex" | "ch" | "rem" | "vw" | "vh" | "vmin" | "vmax" => (UnitCategory::Length, None),
+        "px" => (UnitCategory::Length, Some(1.)),
+        "cm" => (UnitCategory::Length, Some(96. / 2.54)),
+        "mm" => (UnitCategory::Length, Some(96. / 25.4)),
+        "q" => (UnitCategory::Length, Some(96. / 101.6)),
+        "in" => (UnitCategory::Length, Some(96.)),
+        "pt" => (UnitCategory::Length, Some(4. / 3.)),
+        "pc" => (UnitCategory::Length, Some(16.)),
+        "deg" => (UnitCategory::Angle, Some(1.)),
+        "grad" => (UnitCategory::Angle, Some(0.9)),
+        "rad" => (UnitCategory::Angle, Some(180. / PI)),
+        "turn" => (UnitCategory::Angle, Some(360.)),
+        "ms" => (UnitCategory::Time, Some(1.)),
+        "s" => (UnitCategory::Time, Some(1000.)),
+        "hz" => (UnitCategory::Frequency, Some(1.)),
+        "khz" => (UnitCategory::Frequency, Some(1000.)),
+        "dppx" => (UnitCategory::Resolution, Some(1.)),
+        "dpi" => (UnitCategory::Resolution, Some(1. / 96.)),
+        "dpcm" => (UnitCategory::Resolution, Some(2.54 / 96.)),
+        "fr" => (UnitCategory::Flex, Some(1.)),
+        _ => return None,
+    })
+}
+
+/// The unit of the typed OM for a CSS unit or `number` or `percent`, if it
+/// is one that numeric values support.
+pub fn parse_unit(unit: &str) -> Option<String> {
+    let unit = unit.to_ascii_lowercase();
+    unit_info(&unit).map(|_| unit)
+}
+
+#[dom_struct]
+pub struct CSSUnitValue {
+    numeric_value: CSSNumericValue,
+    value: Cell<f64>,
+    /// The unit, in lowercase.
+    unit: String,
+}
+
+impl CSSUnitValue {
+    fn new_inherited(value: f64, unit: String) -> CSSUnitValue {
+        CSSUnitValue {
+            numeric_value: CSSNumericValue::new_inherited(),
+            value: Cell::new(value),
+            unit: unit,
+        }
+    }
+
+    /// Creates a value with a unit that `parse_unit` returned.
+    pub fn new(global: &GlobalScope, value: f64, unit: String) -> DomRoot<CSSUnitValue> {
+        debug_assert!(unit_info(&unit).is_some());
+        reflect_dom_object(
+            Box::new(CSSUnitValue::new_inherited(value, unit)),
+            global,
+            Wrap,
+        )
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-cssunitvalue>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        value: Finite<f64>,
+        unit: USVString,
+    ) -> Fallible<DomRoot<CSSUnitValue>> {
+        match parse_unit(&unit.0) {
+            Some(unit) => Ok(CSSUnitValue::new(window.upcast(), *value, unit)),
+            None => Err(Error::Type(format!("Invalid unit: {}", unit.0))),
+        }
+    }
+
+    /// Parses a number, a percentage or a dimension with a supported unit.
+    pub fn parse<'i, 't>(
+        global: &GlobalScope,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<DomRoot<CSSUnitValue>, ParseError<'i, ()>> {
+        let location = input.current_source_location();
+        let (value, unit) = match *input.next()? {
+            Token::Number { value, .. } => (value as f64, String::from("number")),
+            Token::Percentage { unit_value, .. } => {
+                (unit_value as f64 * 100., String::from("percent"))
+            },
+            Token::Dimension {
+                value, ref unit, ..
+            } => match parse_unit(unit) {
+                Some(ref unit) if unit == "number" || unit == "percent" => {
+                    return Err(location.new_custom_error(()));
+                },
+                Some(unit) => (value as f64, unit),
+                None => return Err(location.new_custom_error(())),
+            },
+            ref token => return Err(location.new_unexpected_token_error(token.clone())),
+        };
+        Ok(CSSUnitValue::new(global, value, unit))
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value.get()
+    }
+
+    pub fn category(&self) -> UnitCategory {
+        unit_info(&self.unit).unwrap().0
+    }
+
+    /// The value converted to `unit`, a unit that `parse_unit` returned, if
+    /// the units measure the same quantity and the conversion doesn't depend
+    /// on the element the value applies to.
+    pub fn convert(&self, unit: &str) -> Option<f64> {
+        if unit == self.unit {
+            return Some(self.value.get());
+        }
+        let (category, factor) = unit_info(&self.unit)?;
+        let (target_category, target_factor) = unit_info(unit)?;
+        if category != target_category {
+            return None;
+        }
+        Some(self.value.get() * factor? / target_factor?)
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#serialize-a-cssunitvalue>
+    pub fn serialize(&self) -> String {
+        let mut serialization = String::new();
+        (self.value.get() as f32)
+            .to_css(&mut CssWriter::new(&mut serialization))
+            .unwrap();
+        match &*self.unit {
+            "number" => {},
+            "percent" => serialization.push('%'),
+            unit => serialization.push_str(unit),
+        }
+        serialization
+    }
+}
+
+impl CSSUnitValueMethods for CSSUnitValue {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-value>
+    fn Value(&self) -> Finite<f64> {
+        Finite::wrap(self.value.get())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-value>
+    fn SetValue(&self, value: Finite<f64>) {
+        self.value.set(*value);
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-cssunitvalue-unit>
+    fn Unit(&self) -> USVString {
+        USVString(self.unit.clone())
+    }
+}
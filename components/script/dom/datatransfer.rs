@@ -0,0 +1,302 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DataTransferBinding::{self, DataTransferMethods};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::datatransferitem::ItemData;
+use crate::dom::datatransferitemlist::{DataStoreMode, DataTransferItemList};
+use crate::dom::element::Element;
+use crate::dom::filelist::FileList;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use embedder_traits::DropEffect;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use std::cell::Cell;
+
+/// The values of `effectAllowed`.
+/// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed>
+const EFFECTS_ALLOWED: [&str; 9] = [
+    "none",
+    "copy",
+    "copyLink",
+    "copyMove",
+    "link",
+    "linkMove",
+    "move",
+    "all",
+    "uninitialized",
+];
+
+/// <https://html.spec.whatwg.org/multipage/#datatransfer>
+#[dom_struct]
+pub struct DataTransfer {
+    reflector_: Reflector,
+    items: Dom<DataTransferItemList>,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    drop_effect: Cell<DropEffect>,
+    effect_allowed: DomRefCell<DOMString>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_types: DomRefCell<Option<Heap<JSVal>>>,
+    /// The generation of the items that `frozen_types` lists.
+    types_generation: Cell<Option<u32>>,
+    files: MutNullableDom<FileList>,
+    /// The generation of the items that `files` lists.
+    files_generation: Cell<Option<u32>>,
+}
+
+impl DataTransfer {
+    fn new_inherited(items: &DataTransferItemList, effect_allowed: &str) -> DataTransfer {
+        DataTransfer {
+            reflector_: Reflector::new(),
+            items: Dom::from_ref(items),
+            drop_effect: Cell::new(DropEffect::None),
+            effect_allowed: DomRefCell::new(DOMString::from(effect_allowed)),
+            frozen_types: DomRefCell::new(None),
+            types_generation: Cell::new(None),
+            files: Default::default(),
+            files_generation: Cell::new(None),
+        }
+    }
+
+    /// Creates a data transfer with a new drag data store in `mode`.
+    pub fn new(
+        window: &Window,
+        mode: Option<DataStoreMode>,
+        effect_allowed: &str,
+    ) -> DomRoot<DataTransfer> {
+        let items = DataTransferItemList::new(window, mode);
+        reflect_dom_object(
+            Box::new(DataTransfer::new_inherited(&items, effect_allowed)),
+            window,
+            DataTransferBinding::Wrap,
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window) -> DomRoot<DataTransfer> {
+        DataTransfer::new(window, Some(DataStoreMode::ReadWrite), "none")
+    }
+
+    pub fn items(&self) -> &DataTransferItemList {
+        &self.items
+    }
+
+    pub fn set_mode(&self, mode: Option<DataStoreMode>) {
+        self.items.set_mode(mode);
+    }
+
+    pub fn drop_effect(&self) -> DropEffect {
+        self.drop_effect.get()
+    }
+
+    pub fn set_drop_effect(&self, drop_effect: DropEffect) {
+        self.drop_effect.set(drop_effect);
+    }
+
+    pub fn effect_allowed(&self) -> DOMString {
+        self.effect_allowed.borrow().clone()
+    }
+
+    /// The types and data of the text items.
+    pub fn text_items(&self) -> Vec<(String, String)> {
+        self.items
+            .items()
+            .iter()
+            .filter_map(|item| match *item.data() {
+                ItemData::Text(ref data) => Some((item.type_().to_string(), data.to_string())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn readable(&self) -> bool {
+        match self.items.mode() {
+            Some(DataStoreMode::ReadWrite) | Some(DataStoreMode::ReadOnly) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The format of the data of `getData`, `setData` and `clearData`, and
+/// whether the first URL of a list is wanted.
+/// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-getdata>
+fn normalize_format(format: &str) -> (DOMString, bool) {
+    match &*format.to_ascii_lowercase() {
+        "text" => (DOMString::from("text/plain"), false),
+        "url" => (DOMString::from("text/uri-list"), true),
+        format => (DOMString::from(format), false),
+    }
+}
+
+pub fn drop_effect_from_str(value: &str) -> Option<DropEffect> {
+    match value {
+        "none" => Some(DropEffect::None),
+        "copy" => Some(DropEffect::Copy),
+        "link" => Some(DropEffect::Link),
+        "move" => Some(DropEffect::Move),
+        _ => None,
+    }
+}
+
+impl DataTransferMethods for DataTransfer {
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect
+    fn DropEffect(&self) -> DOMString {
+        DOMString::from(match self.drop_effect.get() {
+            DropEffect::None => "none",
+            DropEffect::Copy => "copy",
+            DropEffect::Link => "link",
+            DropEffect::Move => "move",
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect
+    fn SetDropEffect(&self, value: DOMString) {
+        if let Some(drop_effect) = drop_effect_from_str(&value) {
+            self.drop_effect.set(drop_effect);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed
+    fn EffectAllowed(&self) -> DOMString {
+        self.effect_allowed()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed
+    fn SetEffectAllowed(&self, value: DOMString) {
+        if self.items.mode() == Some(DataStoreMode::ReadWrite) && EFFECTS_ALLOWED.contains(&&*value)
+        {
+            *self.effect_allowed.borrow_mut() = value;
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-items
+    fn Items(&self) -> DomRoot<DataTransferItemList> {
+        DomRoot::from_ref(&*self.items)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdragimage
+    fn SetDragImage(&self, _image: &Element, _x: i32, _y: i32) {
+        // The drag feedback isn't rendered, so there is nothing to update.
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-types
+    fn Types(&self, cx: JSContext) -> JSVal {
+        let generation = self.items.generation();
+        if self.types_generation.get() == Some(generation) {
+            if let Some(types) = &*self.frozen_types.borrow() {
+                return types.get();
+            }
+        }
+
+        let items = self.items.items();
+        let mut types: Vec<DOMString> = items
+            .iter()
+            .filter(|item| item.is_text())
+            .map(|item| item.type_().clone())
+            .collect();
+        if items.iter().any(|item| !item.is_text()) {
+            types.push(DOMString::from("Files"));
+        }
+        let frozen_types = to_frozen_array(&types, cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_types.borrow_mut() = Some(Heap::default());
+        self.frozen_types
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_types);
+        self.types_generation.set(Some(generation));
+
+        frozen_types
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-getdata
+    fn GetData(&self, format: DOMString) -> DOMString {
+        // Steps 1-2.
+        if !self.readable() {
+            return DOMString::new();
+        }
+        // Steps 3-5.
+        let (format, first_url) = normalize_format(&format);
+        // Steps 6-7.
+        let data = match self.items.text_data(&format) {
+            Some(data) => data,
+            None => return DOMString::new(),
+        };
+        // Step 8.
+        if first_url {
+            let url = data
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+                .unwrap_or("");
+            return DOMString::from(url);
+        }
+        // Step 9.
+        data
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdata
+    fn SetData(&self, format: DOMString, data: DOMString) {
+        // Steps 1-2.
+        if self.items.mode() != Some(DataStoreMode::ReadWrite) {
+            return;
+        }
+        // Steps 3-5.
+        let (format, _) = normalize_format(&format);
+        self.items.set_text_data(format, data);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-cleardata
+    fn ClearData(&self, format: Option<DOMString>) {
+        // Steps 1-2.
+        if self.items.mode() != Some(DataStoreMode::ReadWrite) {
+            return;
+        }
+        // Steps 3-5.
+        match format {
+            Some(format) => self
+                .items
+                .remove_text_data(Some(&*normalize_format(&format).0)),
+            None => self.items.remove_text_data(None),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransfer-files
+    fn Files(&self) -> DomRoot<FileList> {
+        let window = self.global();
+        let window = window.as_window();
+        // The files are only listed while the data can be read.
+        if !self.readable() {
+            return FileList::new(window, vec![]);
+        }
+        let generation = self.items.generation();
+        if self.files_generation.get() == Some(generation) {
+            if let Some(files) = self.files.get() {
+                return files;
+            }
+        }
+        let files = self
+            .items
+            .items()
+            .iter()
+            .filter_map(|item| match *item.data() {
+                ItemData::File(ref file) => Some(DomRoot::from_ref(&**file)),
+                _ => None,
+            })
+            .collect();
+        let files = FileList::new(window, files);
+        self.files.set(Some(&files));
+        self.files_generation.set(Some(generation));
+        files
+    }
+}
@@ -0,0 +1,173 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DataTransferItemBinding::{
+    self, DataTransferItemMethods, FunctionStringCallback,
+};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransferitemlist::{DataStoreMode, DataTransferItemList};
+use crate::dom::file::File;
+use crate::dom::globalscope::GlobalScope;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+/// The data of an item of a drag data store.
+/// <https://html.spec.whatwg.org/multipage/#the-drag-data-item-kind>
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum ItemData {
+    Text(DOMString),
+    File(Dom<File>),
+    /// A file dragged from outside of the window, which the document can't
+    /// get until it is dropped.
+    HiddenFile,
+}
+
+/// <https://html.spec.whatwg.org/multipage/#datatransferitem>
+#[dom_struct]
+pub struct DataTransferItem {
+    reflector_: Reflector,
+    list: Dom<DataTransferItemList>,
+    type_: DOMString,
+    data: ItemData,
+    /// The callbacks of `getAsString` whose tasks haven't run yet, in order.
+    #[ignore_malloc_size_of = "Rc"]
+    pending_callbacks: DomRefCell<Vec<Rc<FunctionStringCallback>>>,
+}
+
+impl DataTransferItem {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(
+        list: &DataTransferItemList,
+        type_: DOMString,
+        data: ItemData,
+    ) -> DataTransferItem {
+        DataTransferItem {
+            reflector_: Reflector::new(),
+            list: Dom::from_ref(list),
+            type_,
+            data,
+            pending_callbacks: Default::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        list: &DataTransferItemList,
+        type_: DOMString,
+        data: ItemData,
+    ) -> DomRoot<DataTransferItem> {
+        reflect_dom_object(
+            Box::new(DataTransferItem::new_inherited(list, type_, data)),
+            global,
+            DataTransferItemBinding::Wrap,
+        )
+    }
+
+    pub fn type_(&self) -> &DOMString {
+        &self.type_
+    }
+
+    pub fn data(&self) -> &ItemData {
+        &self.data
+    }
+
+    pub fn is_text(&self) -> bool {
+        match self.data {
+            ItemData::Text(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The mode of the drag data store of the item, or `None` in the disabled
+    /// mode, once the item was removed from it or the store went away.
+    /// <https://html.spec.whatwg.org/multipage/#concept-datatransferitem-mode>
+    fn mode(&self) -> Option<DataStoreMode> {
+        if self.list.contains(self) {
+            self.list.mode()
+        } else {
+            None
+        }
+    }
+
+    fn invoke_pending_callback(&self, data: DOMString) {
+        let callback = {
+            let mut callbacks = self.pending_callbacks.borrow_mut();
+            if callbacks.is_empty() {
+                return;
+            }
+            callbacks.remove(0)
+        };
+        let _ = callback.Call__(data, ExceptionHandling::Report);
+    }
+}
+
+impl DataTransferItemMethods for DataTransferItem {
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitem-kind
+    fn Kind(&self) -> DOMString {
+        if self.mode().is_none() {
+            return DOMString::new();
+        }
+        match self.data {
+            ItemData::Text(_) => DOMString::from("string"),
+            ItemData::File(_) | ItemData::HiddenFile => DOMString::from("file"),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitem-type
+    fn Type(&self) -> DOMString {
+        if self.mode().is_none() {
+            return DOMString::new();
+        }
+        self.type_.clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitem-getasstring
+    fn GetAsString(&self, callback: Option<Rc<FunctionStringCallback>>) {
+        // Step 1.
+        let callback = match callback {
+            Some(callback) => callback,
+            None => return,
+        };
+        // Steps 2-3.
+        match self.mode() {
+            Some(DataStoreMode::ReadWrite) | Some(DataStoreMode::ReadOnly) => {},
+            _ => return,
+        }
+        // Step 4.
+        let data = match self.data {
+            ItemData::Text(ref data) => data.clone(),
+            _ => return,
+        };
+        // Step 5.
+        self.pending_callbacks.borrow_mut().push(callback);
+        let global = self.global();
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(invoke_function_string_callback: move || {
+                this.root().invoke_pending_callback(data);
+            }),
+            &global,
+        );
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitem-getasfile
+    fn GetAsFile(&self) -> Option<DomRoot<File>> {
+        match self.mode() {
+            Some(DataStoreMode::ReadWrite) | Some(DataStoreMode::ReadOnly) => {},
+            _ => return None,
+        }
+        match self.data {
+            ItemData::File(ref file) => Some(DomRoot::from_ref(&**file)),
+            _ => None,
+        }
+    }
+}
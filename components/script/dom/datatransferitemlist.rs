@@ -0,0 +1,219 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
+use crate::dom::bindings::codegen::Bindings::DataTransferItemListBinding::{
+    self, DataTransferItemListMethods,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::Blob;
+use crate::dom::datatransferitem::{DataTransferItem, ItemData};
+use crate::dom::file::File;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::ptr;
+
+/// What can be done with the items of a drag data store.
+/// <https://html.spec.whatwg.org/multipage/#drag-data-store-mode>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum DataStoreMode {
+    ReadWrite,
+    ReadOnly,
+    Protected,
+}
+
+/// The items of a drag data store, which are shared by its `DataTransfer`.
+/// <https://html.spec.whatwg.org/multipage/#datatransferitemlist>
+#[dom_struct]
+pub struct DataTransferItemList {
+    reflector_: Reflector,
+    items: DomRefCell<Vec<Dom<DataTransferItem>>>,
+    /// The mode of the store, or `None` while the list is disabled because
+    /// the store isn't associated with it.
+    mode: Cell<Option<DataStoreMode>>,
+    /// Changes every time that items are added or removed.
+    generation: Cell<u32>,
+}
+
+impl DataTransferItemList {
+    fn new_inherited(mode: Option<DataStoreMode>) -> DataTransferItemList {
+        DataTransferItemList {
+            reflector_: Reflector::new(),
+            items: DomRefCell::new(vec![]),
+            mode: Cell::new(mode),
+            generation: Cell::new(0),
+        }
+    }
+
+    pub fn new(window: &Window, mode: Option<DataStoreMode>) -> DomRoot<DataTransferItemList> {
+        reflect_dom_object(
+            Box::new(DataTransferItemList::new_inherited(mode)),
+            window,
+            DataTransferItemListBinding::Wrap,
+        )
+    }
+
+    pub fn mode(&self) -> Option<DataStoreMode> {
+        self.mode.get()
+    }
+
+    pub fn set_mode(&self, mode: Option<DataStoreMode>) {
+        self.mode.set(mode);
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation.get()
+    }
+
+    pub fn items(&self) -> Vec<DomRoot<DataTransferItem>> {
+        self.items
+            .borrow()
+            .iter()
+            .map(|item| DomRoot::from_ref(&**item))
+            .collect()
+    }
+
+    pub fn contains(&self, item: &DataTransferItem) -> bool {
+        self.items
+            .borrow()
+            .iter()
+            .any(|other| ptr::eq(&**other, item))
+    }
+
+    /// The data of the text item of type `format`, if there is one.
+    pub fn text_data(&self, format: &str) -> Option<DOMString> {
+        self.items
+            .borrow()
+            .iter()
+            .find_map(|item| match *item.data() {
+                ItemData::Text(ref data) if &**item.type_() == format => Some(data.clone()),
+                _ => None,
+            })
+    }
+
+    /// Replaces the text item of type `format`, if there is one, with one
+    /// for `data`.
+    pub fn set_text_data(&self, format: DOMString, data: DOMString) {
+        self.remove_text_data(Some(&*format));
+        self.push(format, ItemData::Text(data));
+    }
+
+    /// Removes the text item of type `format`, or all of them without one.
+    pub fn remove_text_data(&self, format: Option<&str>) {
+        let len = self.items.borrow().len();
+        self.items.borrow_mut().retain(|item| {
+            !item.is_text() || format.map_or(false, |format| &**item.type_() != format)
+        });
+        if self.items.borrow().len() != len {
+            self.changed();
+        }
+    }
+
+    pub fn add_file(&self, file: &File) -> DomRoot<DataTransferItem> {
+        let type_ = DOMString::from(file.upcast::<Blob>().Type().to_ascii_lowercase());
+        self.push(type_, ItemData::File(Dom::from_ref(file)))
+    }
+
+    /// Adds a file that can't be read yet, of which only the type is known.
+    pub fn add_hidden_file(&self, type_: DOMString) {
+        self.push(type_, ItemData::HiddenFile);
+    }
+
+    /// Removes all the file items.
+    pub fn remove_files(&self) {
+        self.items.borrow_mut().retain(|item| item.is_text());
+        self.changed();
+    }
+
+    #[allow(unrooted_must_root)]
+    fn push(&self, type_: DOMString, data: ItemData) -> DomRoot<DataTransferItem> {
+        let item = DataTransferItem::new(&self.global(), self, type_, data);
+        self.items.borrow_mut().push(Dom::from_ref(&*item));
+        self.changed();
+        item
+    }
+
+    fn changed(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
+}
+
+impl DataTransferItemListMethods for DataTransferItemList {
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-length
+    fn Length(&self) -> u32 {
+        if self.mode().is_none() {
+            return 0;
+        }
+        self.items.borrow().len() as u32
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-item
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<DataTransferItem>> {
+        if self.mode().is_none() {
+            return None;
+        }
+        self.items
+            .borrow()
+            .get(index as usize)
+            .map(|item| DomRoot::from_ref(&**item))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-add
+    fn Add(
+        &self,
+        data: DOMString,
+        type_: DOMString,
+    ) -> Fallible<Option<DomRoot<DataTransferItem>>> {
+        // Steps 1-2.
+        if self.mode() != Some(DataStoreMode::ReadWrite) {
+            return Ok(None);
+        }
+        // Step 3.
+        let type_ = DOMString::from(type_.to_ascii_lowercase());
+        if self.text_data(&type_).is_some() {
+            return Err(Error::NotSupported);
+        }
+        Ok(Some(self.push(type_, ItemData::Text(data))))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-add
+    fn Add_(&self, data: &File) -> Fallible<Option<DomRoot<DataTransferItem>>> {
+        // Steps 1-2.
+        if self.mode() != Some(DataStoreMode::ReadWrite) {
+            return Ok(None);
+        }
+        // Step 3.
+        Ok(Some(self.add_file(data)))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-remove
+    fn Remove(&self, index: u32) -> ErrorResult {
+        // Step 1.
+        if self.mode() != Some(DataStoreMode::ReadWrite) {
+            return Err(Error::InvalidState);
+        }
+        // Steps 2-3.
+        let index = index as usize;
+        if index < self.items.borrow().len() {
+            self.items.borrow_mut().remove(index);
+            self.changed();
+        }
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-clear
+    fn Clear(&self) {
+        if self.mode() != Some(DataStoreMode::ReadWrite) {
+            return;
+        }
+        self.items.borrow_mut().clear();
+        self.changed();
+    }
+}
@@ -6,12 +6,15 @@ use crate::devtools;
 use crate::dom::abstractworker::{SimpleWorkerErrorHandler, WorkerScriptMsg};
 use crate::dom::abstractworkerglobalscope::{run_worker_event_loop, WorkerEventLoopMethods};
 use crate::dom::abstractworkerglobalscope::{SendableWorkerScriptChan, WorkerThreadWorkerChan};
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DedicatedWorkerGlobalScopeBinding;
 use crate::dom::bindings::codegen::Bindings::DedicatedWorkerGlobalScopeBinding::DedicatedWorkerGlobalScopeMethods;
 use crate::dom::bindings::codegen::Bindings::MessagePortBinding::PostMessageOptions;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::FrameRequestCallback;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
-use crate::dom::bindings::error::{ErrorInfo, ErrorResult};
+use crate::dom::bindings::error::{Error, ErrorInfo, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, RootCollection, ThreadLocalStackRoots};
@@ -51,7 +54,9 @@ use net_traits::IpcSend;
 use script_traits::{WorkerGlobalScopeInit, WorkerScriptLoadOrigin};
 use servo_rand::random;
 use servo_url::ServoUrl;
-use std::mem::replace;
+use std::cell::Cell;
+use std::mem::{self, replace};
+use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::thread;
@@ -89,6 +94,9 @@ pub enum DedicatedWorkerScriptMsg {
     CommonWorker(TrustedWorkerAddress, WorkerScriptMsg),
     /// Wake-up call from the task queue.
     WakeUp,
+    /// An animation frame, for the worker to run its animation frame
+    /// callbacks.
+    AnimationFrame(TrustedWorkerAddress),
 }
 
 pub enum MixedMessage {
@@ -180,6 +188,16 @@ pub struct DedicatedWorkerGlobalScope {
     parent_sender: Box<dyn ScriptChan + Send>,
     #[ignore_malloc_size_of = "Arc"]
     image_cache: Arc<dyn ImageCache>,
+    /// Whether the worker can get animation frames, which it gets from the
+    /// window that owns it.
+    supports_animation_frames: bool,
+    /// <https://html.spec.whatwg.org/multipage/#animation-frame-callback-identifier>
+    animation_frame_ident: Cell<u32>,
+    /// <https://html.spec.whatwg.org/multipage/#list-of-animation-frame-callbacks>
+    #[ignore_malloc_size_of = "Rc is hard"]
+    animation_frame_list: DomRefCell<Vec<(u32, Option<Rc<FrameRequestCallback>>)>>,
+    /// Whether the worker is running animation frame callbacks.
+    running_animation_callbacks: Cell<bool>,
 }
 
 impl WorkerEventLoopMethods for DedicatedWorkerGlobalScope {
@@ -221,6 +239,7 @@ impl DedicatedWorkerGlobalScope {
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
         image_cache: Arc<dyn ImageCache>,
+        supports_animation_frames: bool,
     ) -> DedicatedWorkerGlobalScope {
         DedicatedWorkerGlobalScope {
             workerglobalscope: WorkerGlobalScope::new_inherited(
@@ -237,6 +256,10 @@ impl DedicatedWorkerGlobalScope {
             parent_sender: parent_sender,
             worker: DomRefCell::new(None),
             image_cache: image_cache,
+            supports_animation_frames,
+            animation_frame_ident: Cell::new(0),
+            animation_frame_list: DomRefCell::new(vec![]),
+            running_animation_callbacks: Cell::new(false),
         }
     }
 
@@ -253,6 +276,7 @@ impl DedicatedWorkerGlobalScope {
         receiver: Receiver<DedicatedWorkerScriptMsg>,
         closing: Arc<AtomicBool>,
         image_cache: Arc<dyn ImageCache>,
+        supports_animation_frames: bool,
     ) -> DomRoot<DedicatedWorkerGlobalScope> {
         let cx = runtime.cx();
         let scope = Box::new(DedicatedWorkerGlobalScope::new_inherited(
@@ -267,6 +291,7 @@ impl DedicatedWorkerGlobalScope {
             receiver,
             closing,
             image_cache,
+            supports_animation_frames,
         ));
         unsafe { DedicatedWorkerGlobalScopeBinding::Wrap(SafeJSContext::from_ptr(cx), scope) }
     }
@@ -286,6 +311,7 @@ impl DedicatedWorkerGlobalScope {
         worker_type: WorkerType,
         closing: Arc<AtomicBool>,
         image_cache: Arc<dyn ImageCache>,
+        supports_animation_frames: bool,
     ) {
         let serialized_worker_url = worker_url.to_string();
         let name = format!("WebWorker for {}", serialized_worker_url);
@@ -354,6 +380,7 @@ impl DedicatedWorkerGlobalScope {
                     receiver,
                     closing,
                     image_cache,
+                    supports_animation_frames,
                 );
                 // FIXME(njn): workers currently don't have a unique ID suitable for using in reporter
                 // registration (#6631), so we instead use a random number and cross our fingers.
@@ -472,6 +499,9 @@ impl DedicatedWorkerGlobalScope {
                 DevtoolScriptControlMsg::EvaluateJS(_pipe_id, string, sender) => {
                     devtools::handle_evaluate_js(self.upcast(), string, sender)
                 },
+                DevtoolScriptControlMsg::GetObjectProperties(_pipe_id, uuid, sender) => {
+                    devtools::handle_get_object_properties(self.upcast(), uuid, sender)
+                },
                 DevtoolScriptControlMsg::WantsLiveNotifications(_pipe_id, bool_val) => {
                     devtools::handle_wants_live_notifications(self.upcast(), bool_val)
                 },
@@ -484,10 +514,50 @@ impl DedicatedWorkerGlobalScope {
                 let _ar = AutoWorkerReset::new(self, linked_worker);
                 self.handle_script_event(msg);
             },
+            MixedMessage::FromWorker(DedicatedWorkerScriptMsg::AnimationFrame(linked_worker)) => {
+                let _ar = AutoWorkerReset::new(self, linked_worker);
+                self.run_the_animation_frame_callbacks();
+            },
             MixedMessage::FromWorker(DedicatedWorkerScriptMsg::WakeUp) => {},
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#run-the-animation-frame-callbacks>
+    fn run_the_animation_frame_callbacks(&self) {
+        rooted_vec!(let mut animation_frame_list);
+        mem::swap(
+            &mut *animation_frame_list,
+            &mut *self.animation_frame_list.borrow_mut(),
+        );
+
+        self.running_animation_callbacks.set(true);
+        let timing = self.upcast::<GlobalScope>().performance().Now();
+        for (_, callback) in animation_frame_list.drain(..) {
+            if let Some(callback) = callback {
+                let _ = callback.Call__(timing, ExceptionHandling::Report);
+            }
+        }
+        self.running_animation_callbacks.set(false);
+
+        let wants_animation_frames = !self.animation_frame_list.borrow().is_empty();
+        self.request_animation_frames(wants_animation_frames);
+    }
+
+    /// Tells the worker object whether the worker waits for animation frames.
+    fn request_animation_frames(&self, wants: bool) {
+        let worker = self.worker.borrow().as_ref().unwrap().clone();
+        let pipeline_id = self.upcast::<GlobalScope>().pipeline_id();
+        let task = Box::new(task!(request_worker_animation_frames: move || {
+            Worker::set_wants_animation_frames(worker, wants);
+        }));
+        let _ = self.parent_sender.send(CommonScriptMsg::Task(
+            WorkerEvent,
+            task,
+            Some(pipeline_id),
+            TaskSourceName::DOMManipulation,
+        ));
+    }
+
     // https://html.spec.whatwg.org/multipage/#runtime-script-errors-2
     #[allow(unsafe_code)]
     pub fn forward_error_to_worker_object(&self, error_info: ErrorInfo) {
@@ -602,4 +672,33 @@ impl DedicatedWorkerGlobalScopeMethods for DedicatedWorkerGlobalScope {
 
     // https://html.spec.whatwg.org/multipage/#handler-dedicatedworkerglobalscope-onmessage
     event_handler!(message, GetOnmessage, SetOnmessage);
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-animationframeprovider-requestanimationframe>
+    fn RequestAnimationFrame(&self, callback: Rc<FrameRequestCallback>) -> Fallible<u32> {
+        if !self.supports_animation_frames {
+            return Err(Error::NotSupported);
+        }
+        let ident = self.animation_frame_ident.get() + 1;
+        self.animation_frame_ident.set(ident);
+        let was_empty = {
+            let mut list = self.animation_frame_list.borrow_mut();
+            let was_empty = list.is_empty();
+            list.push((ident, Some(callback)));
+            was_empty
+        };
+        // The callbacks added while running callbacks get the next frame
+        // once these are done.
+        if was_empty && !self.running_animation_callbacks.get() {
+            self.request_animation_frames(true);
+        }
+        Ok(ident)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-animationframeprovider-cancelanimationframe>
+    fn CancelAnimationFrame(&self, ident: u32) {
+        let mut list = self.animation_frame_list.borrow_mut();
+        if let Some(pair) = list.iter_mut().find(|pair| pair.0 == ident) {
+            pair.1 = None;
+        }
+    }
 }
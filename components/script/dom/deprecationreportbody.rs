@@ -0,0 +1,75 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeprecationReportBodyBinding;
+use crate::dom::bindings::codegen::Bindings::DeprecationReportBodyBinding::DeprecationReportBodyMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::reporting::DeprecationReportData;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::JSObject;
+use std::ptr::NonNull;
+
+#[dom_struct]
+pub struct DeprecationReportBody {
+    report_body: ReportBody,
+    data: DeprecationReportData,
+}
+
+impl DeprecationReportBody {
+    fn new_inherited(data: DeprecationReportData) -> DeprecationReportBody {
+        DeprecationReportBody {
+            report_body: ReportBody::new_inherited(),
+            data,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        data: DeprecationReportData,
+    ) -> DomRoot<DeprecationReportBody> {
+        reflect_dom_object(
+            Box::new(DeprecationReportBody::new_inherited(data)),
+            global,
+            DeprecationReportBodyBinding::Wrap,
+        )
+    }
+}
+
+impl DeprecationReportBodyMethods for DeprecationReportBody {
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-id
+    fn Id(&self) -> DOMString {
+        DOMString::from(self.data.id.clone())
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-anticipatedremoval
+    fn GetAnticipatedRemoval(&self, _cx: JSContext) -> Option<NonNull<JSObject>> {
+        // None of the deprecated features have a removal date.
+        None
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-message
+    fn Message(&self) -> DOMString {
+        DOMString::from(self.data.message.clone())
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-sourcefile
+    fn GetSourceFile(&self) -> Option<DOMString> {
+        self.data.source_file.clone().map(DOMString::from)
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-linenumber
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.data.line_number
+    }
+
+    // https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-columnnumber
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.data.column_number
+    }
+}
@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding;
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::DeviceMotionEventMethods;
+use crate::dom::bindings::codegen::Bindings::DeviceMotionEventBinding::{
+    DeviceMotionEventAccelerationInit, DeviceMotionEventInit,
+};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::devicemotioneventacceleration::DeviceMotionEventAcceleration;
+use crate::dom::devicemotioneventrotationrate::DeviceMotionEventRotationRate;
+use crate::dom::deviceorientationevent::request_sensor_permissions;
+use crate::dom::event::Event;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+use std::rc::Rc;
+
+// https://w3c.github.io/deviceorientation/#devicemotion
+#[dom_struct]
+pub struct DeviceMotionEvent {
+    event: Event,
+    acceleration: Option<Dom<DeviceMotionEventAcceleration>>,
+    acceleration_including_gravity: Option<Dom<DeviceMotionEventAcceleration>>,
+    rotation_rate: Option<Dom<DeviceMotionEventRotationRate>>,
+    interval: f64,
+}
+
+impl DeviceMotionEvent {
+    fn new_inherited(
+        acceleration: Option<&DeviceMotionEventAcceleration>,
+        acceleration_including_gravity: Option<&DeviceMotionEventAcceleration>,
+        rotation_rate: Option<&DeviceMotionEventRotationRate>,
+        interval: f64,
+    ) -> DeviceMotionEvent {
+        DeviceMotionEvent {
+            event: Event::new_inherited(),
+            acceleration: acceleration.map(Dom::from_ref),
+            acceleration_including_gravity: acceleration_including_gravity.map(Dom::from_ref),
+            rotation_rate: rotation_rate.map(Dom::from_ref),
+            interval,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        acceleration: Option<&DeviceMotionEventAcceleration>,
+        acceleration_including_gravity: Option<&DeviceMotionEventAcceleration>,
+        rotation_rate: Option<&DeviceMotionEventRotationRate>,
+        interval: f64,
+    ) -> DomRoot<DeviceMotionEvent> {
+        let ev = reflect_dom_object(
+            Box::new(DeviceMotionEvent::new_inherited(
+                acceleration,
+                acceleration_including_gravity,
+                rotation_rate,
+                interval,
+            )),
+            window,
+            DeviceMotionEventBinding::Wrap,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &DeviceMotionEventInit,
+    ) -> Fallible<DomRoot<DeviceMotionEvent>> {
+        let rotation_rate = init.rotationRate.as_ref().map(|init| {
+            DeviceMotionEventRotationRate::new(
+                window,
+                init.alpha.map(|alpha| *alpha),
+                init.beta.map(|beta| *beta),
+                init.gamma.map(|gamma| *gamma),
+            )
+        });
+        Ok(DeviceMotionEvent::new(
+            window,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            acceleration(window, &init.acceleration).as_deref(),
+            acceleration(window, &init.accelerationIncludingGravity).as_deref(),
+            rotation_rate.as_deref(),
+            *init.interval,
+        ))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-requestpermission
+    #[allow(non_snake_case)]
+    pub fn RequestPermission(window: &Window) -> Rc<Promise> {
+        request_sensor_permissions(window.upcast(), false)
+    }
+}
+
+impl DeviceMotionEventMethods for DeviceMotionEvent {
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-acceleration
+    fn GetAcceleration(&self) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+        self.acceleration.as_deref().map(DomRoot::from_ref)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-accelerationincludinggravity
+    fn GetAccelerationIncludingGravity(&self) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+        self.acceleration_including_gravity
+            .as_deref()
+            .map(DomRoot::from_ref)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-rotationrate
+    fn GetRotationRate(&self) -> Option<DomRoot<DeviceMotionEventRotationRate>> {
+        self.rotation_rate.as_deref().map(DomRoot::from_ref)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-devicemotionevent-interval
+    fn Interval(&self) -> Finite<f64> {
+        Finite::wrap(self.interval)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
+
+/// The acceleration of an event for a member of its init dictionary.
+fn acceleration(
+    window: &Window,
+    init: &Option<DeviceMotionEventAccelerationInit>,
+) -> Option<DomRoot<DeviceMotionEventAcceleration>> {
+    init.as_ref().map(|init| {
+        DeviceMotionEventAcceleration::new(
+            window,
+            init.x.map(|x| *x),
+            init.y.map(|y| *y),
+            init.z.map(|z| *z),
+        )
+    })
+}
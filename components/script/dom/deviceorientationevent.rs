@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DeviceOrientationEventBinding;
+use crate::dom::bindings::codegen::Bindings::DeviceOrientationEventBinding::DeviceOrientationEventInit;
+use crate::dom::bindings::codegen::Bindings::DeviceOrientationEventBinding::DeviceOrientationEventMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::prompt_user_from_embedder;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::PermissionPrompt;
+use servo_atoms::Atom;
+use std::rc::Rc;
+
+// https://w3c.github.io/deviceorientation/#deviceorientation
+#[dom_struct]
+pub struct DeviceOrientationEvent {
+    event: Event,
+    alpha: Option<f64>,
+    beta: Option<f64>,
+    gamma: Option<f64>,
+    absolute: bool,
+}
+
+impl DeviceOrientationEvent {
+    fn new_inherited(
+        alpha: Option<f64>,
+        beta: Option<f64>,
+        gamma: Option<f64>,
+        absolute: bool,
+    ) -> DeviceOrientationEvent {
+        DeviceOrientationEvent {
+            event: Event::new_inherited(),
+            alpha,
+            beta,
+            gamma,
+            absolute,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        alpha: Option<f64>,
+        beta: Option<f64>,
+        gamma: Option<f64>,
+        absolute: bool,
+    ) -> DomRoot<DeviceOrientationEvent> {
+        let ev = reflect_dom_object(
+            Box::new(DeviceOrientationEvent::new_inherited(
+                alpha, beta, gamma, absolute,
+            )),
+            window,
+            DeviceOrientationEventBinding::Wrap,
+        );
+        ev.upcast::<Event>().init_event(type_, bubbles, cancelable);
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &DeviceOrientationEventInit,
+    ) -> Fallible<DomRoot<DeviceOrientationEvent>> {
+        Ok(DeviceOrientationEvent::new(
+            window,
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            init.alpha.map(|alpha| *alpha),
+            init.beta.map(|beta| *beta),
+            init.gamma.map(|gamma| *gamma),
+            init.absolute,
+        ))
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-requestpermission
+    #[allow(non_snake_case)]
+    pub fn RequestPermission(window: &Window, absolute: bool) -> Rc<Promise> {
+        request_sensor_permissions(window.upcast(), absolute)
+    }
+}
+
+impl DeviceOrientationEventMethods for DeviceOrientationEvent {
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-alpha
+    fn GetAlpha(&self) -> Option<Finite<f64>> {
+        self.alpha.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-beta
+    fn GetBeta(&self) -> Option<Finite<f64>> {
+        self.beta.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-gamma
+    fn GetGamma(&self) -> Option<Finite<f64>> {
+        self.gamma.map(Finite::wrap)
+    }
+
+    // https://w3c.github.io/deviceorientation/#dom-deviceorientationevent-absolute
+    fn Absolute(&self) -> bool {
+        self.absolute
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
+
+/// The permissions that the events of the sensors of the device need, the
+/// magnetometer being only needed for the orientation relative to the Earth.
+/// <https://w3c.github.io/deviceorientation/#permissions>
+fn sensor_permissions(absolute: bool) -> &'static [PermissionName] {
+    if absolute {
+        &[
+            PermissionName::Accelerometer,
+            PermissionName::Gyroscope,
+            PermissionName::Magnetometer,
+        ]
+    } else {
+        &[PermissionName::Accelerometer, PermissionName::Gyroscope]
+    }
+}
+
+/// Whether the events of the sensors of the device can be fired in a global.
+/// Like in other browsers, the sensors are usable until the user denies one
+/// of the permissions when asked through `requestPermission()`.
+pub fn sensor_permissions_granted(global: &GlobalScope, absolute: bool) -> bool {
+    let results = global.permission_state_invocation_results().borrow();
+    sensor_permissions(absolute)
+        .iter()
+        .all(|name| results.get(&name.to_string()) != Some(&PermissionState::Denied))
+}
+
+/// Asks the user for the permissions to use the sensors of the device that
+/// weren't asked for yet, and resolves with whether all of them are granted.
+/// <https://w3c.github.io/deviceorientation/#dom-devicemotionevent-requestpermission>
+pub fn request_sensor_permissions(global: &GlobalScope, absolute: bool) -> Rc<Promise> {
+    let promise = Promise::new(global);
+    let mut state = PermissionState::Granted;
+    for name in sensor_permissions(absolute) {
+        let previous_state = global
+            .permission_state_invocation_results()
+            .borrow()
+            .get(&name.to_string())
+            .cloned();
+        let name_state = match previous_state {
+            Some(previous_state) => previous_state,
+            None => {
+                let prompt =
+                    PermissionPrompt::Request(embedder_traits::PermissionName::from(*name));
+                let name_state = prompt_user_from_embedder(prompt, global);
+                global
+                    .permission_state_invocation_results()
+                    .borrow_mut()
+                    .insert(name.to_string(), name_state);
+                name_state
+            },
+        };
+        if name_state != PermissionState::Granted {
+            state = PermissionState::Denied;
+            break;
+        }
+    }
+    promise.resolve_native(&state);
+    promise
+}
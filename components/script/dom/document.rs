@@ -8,20 +8,34 @@ use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::BeforeUnloadEventBinding::BeforeUnloadEventBinding::BeforeUnloadEventMethods;
+use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
+use crate::dom::bindings::codegen::Bindings::DOMQuadBinding::DOMQuadInit;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::GeometryUtilsBinding::{
+    BoxQuadOptions, ConvertCoordinateOptions,
+};
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
+use crate::dom::bindings::codegen::Bindings::SanitizerBinding::SetHTMLOptions;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     FrameRequestCallback, ScrollBehavior, WindowMethods,
 };
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathNSResolver;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use crate::dom::bindings::codegen::UnionTypes::TextOrElementOrDocument as GeometryNode;
 use crate::dom::bindings::codegen::UnionTypes::{NodeOrString, StringOrElementCreationOptions};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -44,6 +58,9 @@ use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
 use crate::dom::documenttype::DocumentType;
 use crate::dom::domimplementation::DOMImplementation;
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::domquad::DOMQuad;
+use crate::dom::domrectreadonly::DOMRectReadOnly;
 use crate::dom::element::CustomElementCreationMode;
 use crate::dom::element::{
     Element, ElementCreator, ElementPerformFullscreenEnter, ElementPerformFullscreenExit,
@@ -51,13 +68,17 @@ use crate::dom::element::{
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventDefault, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::focusevent::FocusEvent;
+use crate::dom::fontfaceset::FontFaceSet;
+use crate::dom::geometryutils;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
+use crate::dom::htmlallcollection::HTMLAllCollection;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmlareaelement::HTMLAreaElement;
 use crate::dom::htmlbaseelement::HTMLBaseElement;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::htmldialogelement::HTMLDialogElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlembedelement::HTMLEmbedElement;
 use crate::dom::htmlformelement::{FormControl, FormControlElementHelpers, HTMLFormElement};
@@ -65,7 +86,9 @@ use crate::dom::htmlheadelement::HTMLHeadElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmliframeelement::HTMLIFrameElement;
 use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptResult};
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::htmltitleelement::HTMLTitleElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::location::Location;
@@ -76,9 +99,11 @@ use crate::dom::node::{LayoutNodeHelpers, Node, NodeDamage, NodeFlags, ShadowInc
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::permissions::prompt_user_from_embedder;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
+use crate::dom::sanitizer::SanitizerConfiguration;
 use crate::dom::selection::Selection;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::ShadowRoot;
@@ -95,6 +120,11 @@ use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
+use crate::dom::worker::Worker;
+use crate::dom::xpathexpression::XPathExpression;
+use crate::dom::xpathresult::XPathResult;
+use crate::drag_and_drop::{self, DragAndDrop};
+use crate::editing::{self, EditHistory};
 use crate::fetch::FetchCanceller;
 use crate::realms::{AlreadyInRealm, InRealm};
 use crate::script_runtime::JSContext;
@@ -104,30 +134,35 @@ use crate::stylesheet_set::StylesheetSetRef;
 use crate::task::TaskBox;
 use crate::task_source::{TaskSource, TaskSourceName};
 use crate::timers::OneshotTimerCallback;
+use app_units::Au;
 use canvas_traits::webgl::{self, SwapChainId, WebGLContextId, WebGLMsg};
 use content_security_policy::{self as csp, CspList};
 use cookie::Cookie;
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, PermissionPrompt};
 use encoding_rs::{Encoding, UTF_8};
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Size2D, Vector2D};
+use euclid::Rect;
 use html5ever::{LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
 use js::jsapi::{JSObject, JSRuntime};
-use keyboard_types::{Code, Key, KeyState};
+use js::jsval::JSVal;
+use js::rust::HandleValue;
+use keyboard_types::{Code, Key, KeyState, Modifiers};
 use metrics::{
     InteractiveFlag, InteractiveMetrics, InteractiveWindow, ProfilerMetadataFactory,
     ProgressiveWebMetric,
 };
 use mime::{self, Mime};
 use msg::constellation_msg::BrowsingContextId;
-use net_traits::pub_domains::is_pub_domain;
+use net_traits::pub_domains::{is_pub_domain, reg_host};
 use net_traits::request::RequestBuilder;
 use net_traits::response::HttpsState;
 use net_traits::CookieSource::NonHTTP;
-use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
+use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesBlocked, SetCookiesForUrl};
 use net_traits::{FetchResponseMsg, IpcSend, ReferrerPolicy};
 use num_traits::ToPrimitive;
 use percent_encoding::percent_decode;
@@ -135,7 +170,10 @@ use profile_traits::ipc as profile_ipc;
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType};
 use ref_slice::ref_slice;
 use script_layout_interface::message::{Msg, ReflowGoal};
+use script_layout_interface::rpc::TextIndexResponse;
+use script_layout_interface::scrollbar::{ScrollbarAxis, SCROLLBAR_PAGE_RATIO};
 use script_traits::{AnimationState, DocumentActivity, MouseButton, MouseEventType};
+use script_traits::{DragData, DragEventType, NavigationType};
 use script_traits::{
     MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
 };
@@ -158,11 +196,15 @@ use style::attr::AttrValue;
 use style::context::QuirksMode;
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::media_queries::{Device, MediaType};
+use style::properties_and_values::registry::PropertyRegistration;
 use style::selector_parser::{RestyleDamage, Snapshot};
 use style::shared_lock::SharedRwLock as StyleSharedRwLock;
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheet_set::DocumentStylesheetSet;
 use style::stylesheets::{Origin, OriginSet, Stylesheet};
+use style::values::computed::Resize;
+use style::values::specified::box_::DisplayOutside;
+use style_traits::CSSPixel;
 use url::Host;
 use uuid::Uuid;
 
@@ -175,6 +217,11 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// How close to the viewport, in CSS pixels, lazy loading elements start to
+/// load, so that they are ready by the time they are scrolled into view.
+/// <https://html.spec.whatwg.org/multipage/#lazy-load-root-margin>
+const LAZY_LOAD_ROOT_MARGIN: i32 = 1250;
+
 pub enum TouchEventResult {
     Processed(bool),
     Forwarded,
@@ -225,6 +272,18 @@ impl PendingRestyle {
     }
 }
 
+/// The drag of the thumb of a scrollbar.
+#[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
+struct ScrollbarDrag {
+    axis: ScrollbarAxis,
+    /// Where the drag started.
+    start_point: Point2D<f32>,
+    /// How far the scroll container was scrolled when the drag started.
+    start_position: f64,
+    /// How far the content scrolls per pixel the thumb moves.
+    scroll_per_pixel: f32,
+}
+
 /// <https://dom.spec.whatwg.org/#document>
 #[dom_struct]
 pub struct Document {
@@ -255,12 +314,23 @@ pub struct Document {
     scripts: MutNullableDom<HTMLCollection>,
     anchors: MutNullableDom<HTMLCollection>,
     applets: MutNullableDom<HTMLCollection>,
+    all: MutNullableDom<HTMLAllCollection>,
     /// Lock use for style attributes and author-origin stylesheet objects in this document.
     /// Can be acquired once for accessing many objects.
     style_shared_lock: StyleSharedRwLock,
     /// List of stylesheets associated with nodes in this document. |None| if the list needs to be refreshed.
     stylesheets: DomRefCell<DocumentStylesheetSet<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
+    /// The names of the custom properties registered with `CSS.registerProperty()`.
+    registered_custom_properties: DomRefCell<HashSet<Atom>>,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-document-fonts>
+    fonts: MutNullableDom<FontFaceSet>,
+    /// Whether layout reported web font loads in progress after the last reflow, or no reflow
+    /// happened yet.
+    web_font_loads_pending: Cell<bool>,
+    /// When the user last pressed a mouse button, a key or a touch point in the document, as given
+    /// by `time::precise_time_ns`.
+    last_input_time: Cell<Option<u64>>,
     ready_state: Cell<DocumentReadyState>,
     /// Whether the DOMContentLoaded event has already been dispatched.
     domcontentloaded_dispatched: Cell<bool>,
@@ -294,6 +364,10 @@ pub struct Document {
     /// Tracking this is not necessary for correctness. Instead, it is an optimization to avoid
     /// sending needless `ChangeRunningAnimationsState` messages to the compositor.
     running_animation_callbacks: Cell<bool>,
+    /// When the animation frame callbacks last ran, in nanoseconds.
+    last_animation_frame_time: Cell<u64>,
+    /// The dedicated workers of the window that wait for an animation frame.
+    animation_frame_workers: DomRefCell<Vec<Dom<Worker>>>,
     /// Tracks all outstanding loads related to this document.
     loader: DomRefCell<DocumentLoader>,
     /// The current active HTML parser, to allow resuming after interruptions.
@@ -313,7 +387,8 @@ pub struct Document {
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
-    /// Navigation Timing properties:
+    /// Navigation Timing properties, as given by `time::precise_time_ns`, or zero
+    /// until they happen:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
     dom_loading: Cell<u64>,
     dom_interactive: Cell<u64>,
@@ -323,8 +398,14 @@ pub struct Document {
     top_level_dom_complete: Cell<u64>,
     load_event_start: Cell<u64>,
     load_event_end: Cell<u64>,
+    /// When the unload event of the previous document of the browsing context
+    /// started and ended, if it had the same origin.
     unload_event_start: Cell<u64>,
     unload_event_end: Cell<u64>,
+    /// How the load of the document came to happen.
+    navigation_type: Cell<NavigationType>,
+    /// <https://dom.spec.whatwg.org/#document-allow-declarative-shadow-roots>
+    allow_declarative_shadow_roots: Cell<bool>,
     /// <https://html.spec.whatwg.org/multipage/#concept-document-https-state>
     https_state: Cell<HttpsState>,
     /// The document's origin.
@@ -338,6 +419,17 @@ pub struct Document {
     /// <https://w3c.github.io/uievents/#event-type-dblclick>
     #[ignore_malloc_size_of = "Defined in std"]
     last_click_info: DomRefCell<Option<(Instant, Point2D<f32>)>>,
+    /// The element whose resizer is being dragged.
+    resizing_element: MutNullableDom<Element>,
+    /// Where the drag of the resizer started, and the size of the element then.
+    #[ignore_malloc_size_of = "Defined in euclid"]
+    resize_origin: Cell<Option<(Point2D<f32>, Size2D<f32>)>>,
+    /// The scroll container whose scrollbar thumb is being dragged.
+    dragged_scroll_container: MutNullableDom<Element>,
+    scrollbar_drag: Cell<Option<ScrollbarDrag>>,
+    /// Whether the selection follows the mouse while its primary button is
+    /// held down.
+    selecting_text: Cell<bool>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-destructive-writes-counter>
     ignore_destructive_writes_counter: Cell<u32>,
     /// <https://html.spec.whatwg.org/multipage/#ignore-opens-during-unload-counter>
@@ -355,6 +447,20 @@ pub struct Document {
     dom_count: Cell<u32>,
     /// Entry node for fullscreen.
     fullscreen_element: MutNullableDom<Element>,
+    /// The modal dialogs in the top layer, in the order they were shown.
+    /// <https://fullscreen.spec.whatwg.org/#top-layer>
+    modal_dialogs: DomRefCell<Vec<Dom<HTMLDialogElement>>>,
+    /// The element the pointer is locked to.
+    pointer_lock_element: MutNullableDom<Element>,
+    /// Whether the embedder was asked to lock the pointer and didn't answer yet.
+    pointer_lock_pending: Cell<bool>,
+    /// Whether the user granted the document access to its cookies while it
+    /// is embedded in a document of another site.
+    /// <https://privacycg.github.io/storage-access/#has-storage-access>
+    storage_access_granted: Cell<bool>,
+    /// Where the mouse was at the last `mousemove` event, which the movement
+    /// of the next one is relative to.
+    last_mouse_move_point: Cell<Option<Point2D<f32>>>,
     /// Map from ID to set of form control elements that have that ID as
     /// their 'form' content attribute. Used to reset form controls
     /// whenever any element with the same ID as the form attribute
@@ -373,10 +479,21 @@ pub struct Document {
     salvageable: Cell<bool>,
     /// Whether the document was aborted with an active parser
     active_parser_was_aborted: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#iframe-load-in-progress>
+    iframe_load_in_progress: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#mute-iframe-load>
+    mute_iframe_load: Cell<bool>,
     /// Whether the unload event has already been fired.
     fired_unload: Cell<bool>,
+    /// When the unload event of the document started and ended, which the next
+    /// document of the browsing context reports.
+    fired_unload_timing: Cell<Option<(u64, u64)>>,
     /// List of responsive images
     responsive_images: DomRefCell<Vec<Dom<HTMLImageElement>>>,
+    /// The img and iframe elements whose load waits for them to get near the
+    /// viewport.
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    lazy_loading_elements: DomRefCell<Vec<Dom<Element>>>,
     /// Number of redirects for the document load
     redirect_count: Cell<u16>,
     /// Number of outstanding requests to prevent JS or layout from running.
@@ -403,6 +520,10 @@ pub struct Document {
     csp_list: DomRefCell<Option<CspList>>,
     /// https://w3c.github.io/slection-api/#dfn-selection
     selection: MutNullableDom<Selection>,
+    /// The edits of the editing hosts that can be undone and redone.
+    edit_history: EditHistory,
+    /// The drag-and-drop operation over the document.
+    drag_and_drop: DragAndDrop,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -454,7 +575,7 @@ impl CollectionFilter for AnchorsFilter {
     }
 }
 
-enum ElementLookupResult {
+pub enum ElementLookupResult {
     None,
     One(DomRoot<Element>),
     Many,
@@ -526,6 +647,17 @@ impl Document {
         if activity != DocumentActivity::FullyActive {
             self.window().suspend();
             media.suspend(&client_context_id);
+            // Animation frame callbacks stay queued until the document is
+            // shown again, the compositor doesn't need to tick it meanwhile.
+            if !self.animation_frame_list.borrow().is_empty() &&
+                !self.is_faking_animation_frames() &&
+                !self.window.animation_frames_suspended()
+            {
+                let event = ScriptMsg::ChangeRunningAnimationsState(
+                    AnimationState::NoAnimationCallbacksPresent,
+                );
+                self.window().send_to_constellation(event);
+            }
             return;
         }
 
@@ -535,6 +667,14 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::CachedPageNeededReflow);
         self.window().resume();
         media.resume(&client_context_id);
+        if !self.animation_frame_list.borrow().is_empty() &&
+            !self.is_faking_animation_frames() &&
+            !self.window.animation_frames_suspended()
+        {
+            let event =
+                ScriptMsg::ChangeRunningAnimationsState(AnimationState::AnimationCallbacksPresent);
+            self.window().send_to_constellation(event);
+        }
 
         if self.ready_state.get() != DocumentReadyState::Complete {
             return;
@@ -554,6 +694,12 @@ impl Document {
                     if document.page_showing.get() {
                         return;
                     }
+                    // Only a document that was kept in the back-forward cache
+                    // when it was unloaded is shown again, the others are
+                    // loaded anew by the constellation.
+                    if !document.salvageable.get() {
+                        return;
+                    }
                     // Step 4.6.2
                     document.page_showing.set(true);
                     // Step 4.6.4
@@ -865,12 +1011,12 @@ impl Document {
     pub fn set_ready_state(&self, state: DocumentReadyState) {
         match state {
             DocumentReadyState::Loading => {
-                update_with_current_time_ms(&self.dom_loading);
+                update_with_current_time_ns(&self.dom_loading);
             },
             DocumentReadyState::Complete => {
-                update_with_current_time_ms(&self.dom_complete);
+                update_with_current_time_ns(&self.dom_complete);
             },
-            DocumentReadyState::Interactive => update_with_current_time_ms(&self.dom_interactive),
+            DocumentReadyState::Interactive => update_with_current_time_ns(&self.dom_interactive),
         };
 
         self.ready_state.set(state);
@@ -884,6 +1030,16 @@ impl Document {
         self.scripting_enabled
     }
 
+    /// The edits of the editing hosts that can be undone and redone.
+    pub fn edit_history(&self) -> &EditHistory {
+        &self.edit_history
+    }
+
+    /// The drag-and-drop operation over the document.
+    pub fn drag_and_drop(&self) -> &DragAndDrop {
+        &self.drag_and_drop
+    }
+
     /// Return the element that currently has focus.
     // https://w3c.github.io/uievents/#events-focusevent-doc-focus
     pub fn get_focused_element(&self) -> Option<DomRoot<Element>> {
@@ -898,7 +1054,7 @@ impl Document {
 
     /// Request that the given element receive focus once the current transaction is complete.
     pub fn request_focus(&self, elem: &Element) {
-        if elem.is_focusable_area() {
+        if elem.is_focusable_area() && !self.is_inert(elem.upcast()) {
             self.possibly_focused.set(Some(elem))
         }
     }
@@ -973,7 +1129,7 @@ impl Document {
     pub fn handle_mouse_event(
         &self,
         js_runtime: *mut JSRuntime,
-        _button: MouseButton,
+        button: MouseButton,
         client_point: Point2D<f32>,
         mouse_event_type: MouseEventType,
         node_address: Option<UntrustedNodeAddress>,
@@ -987,11 +1143,35 @@ impl Document {
         };
         debug!("{}: at {:?}", mouse_event_type_string, client_point);
 
-        let el = node_address.and_then(|address| {
-            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
-            node.inclusive_ancestors(ShadowIncluding::No)
-                .filter_map(DomRoot::downcast::<Element>)
-                .next()
+        if let MouseEventType::MouseDown = mouse_event_type {
+            self.note_input();
+        }
+
+        let hit_node = node_address
+            .map(|address| unsafe { node::from_untrusted_node_address(js_runtime, address) });
+
+        // No mouse events are fired while something is dragged.
+        if drag_and_drop::handle_mouse_button(
+            self,
+            mouse_event_type,
+            button,
+            hit_node.as_deref(),
+            client_point,
+            pressed_mouse_buttons,
+        ) {
+            self.window
+                .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+            return;
+        }
+
+        // While the pointer is locked, mouse events go to the element it is
+        // locked to.
+        let el = self.pointer_lock_element.get().or_else(|| {
+            hit_node.as_ref().and_then(|node| {
+                node.inclusive_ancestors(ShadowIncluding::No)
+                    .filter_map(DomRoot::downcast::<Element>)
+                    .next()
+            })
         });
         let el = match el {
             Some(el) => el,
@@ -999,6 +1179,10 @@ impl Document {
         };
 
         let node = el.upcast::<Node>();
+        // Nothing outside of a modal dialog can be interacted with.
+        if self.is_inert(node) {
+            return;
+        }
         debug!("{} on {:?}", mouse_event_type_string, node.debug_str());
         // Prevent click event if form control element is disabled.
         if let MouseEventType::Click = mouse_event_type {
@@ -1050,14 +1234,38 @@ impl Document {
                     a.enter_formal_activation_state();
                 }
 
+                if !self.maybe_start_scrollbar_drag(&el, client_point) {
+                    self.maybe_start_resizing(&el, client_point);
+                }
+                let dragging = self.dragged_scroll_container.get().is_some() ||
+                    self.resizing_element.get().is_some();
+
                 let target = node.upcast();
-                event.fire(target);
+                let status = event.fire(target);
+
+                if button == MouseButton::Left &&
+                    status == EventStatus::NotCanceled &&
+                    !dragging &&
+                    self.pointer_lock_element.get().is_none()
+                {
+                    // Pressing the button on a draggable element may start a drag
+                    // instead of a selection.
+                    if !self.drag_and_drop.maybe_prepare_drag(&el, client_point) {
+                        if let Some(ref hit_node) = hit_node {
+                            self.maybe_start_selecting_text(hit_node, client_point);
+                        }
+                    }
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
                     a.exit_formal_activation_state();
                 }
 
+                self.resizing_element.set(None);
+                self.dragged_scroll_container.set(None);
+                self.selecting_text.set(false);
+
                 let target = node.upcast();
                 event.fire(target);
             },
@@ -1065,7 +1273,12 @@ impl Document {
 
         if let MouseEventType::Click = mouse_event_type {
             self.commit_focus_transaction(FocusType::Element);
-            self.maybe_fire_dblclick(client_point, node, pressed_mouse_buttons);
+            self.maybe_fire_dblclick(
+                client_point,
+                node,
+                hit_node.as_deref(),
+                pressed_mouse_buttons,
+            );
         }
 
         self.window
@@ -1076,6 +1289,7 @@ impl Document {
         &self,
         click_pos: Point2D<f32>,
         target: &Node,
+        hit_node: Option<&Node>,
         pressed_mouse_buttons: u16,
     ) {
         // https://w3c.github.io/uievents/#event-type-dblclick
@@ -1120,7 +1334,17 @@ impl Document {
                     None,
                     None,
                 );
-                event.upcast::<Event>().fire(target.upcast());
+                let status = event.upcast::<Event>().fire(target.upcast());
+
+                // Double-clicking text selects the word under the pointer.
+                if status == EventStatus::NotCanceled {
+                    let position =
+                        hit_node.and_then(|node| self.text_position_from_point(node, click_pos));
+                    if let (Some((text, offset)), Some(selection)) = (position, self.GetSelection())
+                    {
+                        selection.select_word_at(&text, offset);
+                    }
+                }
 
                 // When a double click occurs, self.last_click_info is left as None so that a
                 // third sequential click will not cause another double click.
@@ -1137,6 +1361,7 @@ impl Document {
         client_point: Point2D<f32>,
         target: &EventTarget,
         event_name: FireMouseEventType,
+        movement: Vector2D<f32>,
         pressed_mouse_buttons: u16,
     ) {
         let client_x = client_point.x.to_i32().unwrap_or(0);
@@ -1162,10 +1387,257 @@ impl Document {
             None,
             None,
         );
+        mouse_event.set_movement(movement.x as i32, movement.y as i32);
         let event = mouse_event.upcast::<Event>();
         event.fire(target);
     }
 
+    /// Handles a press on the scrollbar of the nearest scroll container that
+    /// has one at `client_point` among the inclusive ancestors of `target`:
+    /// presses on the thumb start dragging it, and presses on the track page
+    /// towards the pointer. Returns whether there was such a scrollbar.
+    fn maybe_start_scrollbar_drag(&self, target: &Element, client_point: Point2D<f32>) -> bool {
+        let point = Point2D::new(
+            Au::from_f32_px(client_point.x),
+            Au::from_f32_px(client_point.y),
+        );
+        let found = target
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find_map(|element| {
+                let scrollbar = element.scrollbar_geometry()?.scrollbar_at(point)?;
+                Some((element, scrollbar))
+            });
+        let (element, scrollbar) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+
+        let position = element.scroll_position(scrollbar.axis);
+        let direction = scrollbar.page_direction(point);
+        if direction != 0 {
+            let visible_length = match scrollbar.axis {
+                ScrollbarAxis::Horizontal => element.ClientWidth(),
+                ScrollbarAxis::Vertical => element.ClientHeight(),
+            };
+            let page = visible_length as f64 * SCROLLBAR_PAGE_RATIO as f64;
+            element.set_scroll_position(scrollbar.axis, position + direction as f64 * page);
+            return true;
+        }
+
+        self.scrollbar_drag.set(Some(ScrollbarDrag {
+            axis: scrollbar.axis,
+            start_point: client_point,
+            start_position: position,
+            scroll_per_pixel: scrollbar.scroll_per_thumb_offset(),
+        }));
+        self.dragged_scroll_container.set(Some(&element));
+        true
+    }
+
+    /// Scrolls the scroll container whose scrollbar thumb is being dragged so
+    /// that the thumb follows `client_point`.
+    fn drag_scrollbar_to(&self, client_point: Point2D<f32>, pressed_mouse_buttons: u16) {
+        let element = match self.dragged_scroll_container.get() {
+            Some(element) => element,
+            None => return,
+        };
+        // The drag ends when the primary button is released, even if the
+        // mouseup happened outside of this document.
+        let drag = match self.scrollbar_drag.get() {
+            Some(drag) if pressed_mouse_buttons & 1 != 0 => drag,
+            _ => {
+                self.dragged_scroll_container.set(None);
+                return;
+            },
+        };
+        let delta = match drag.axis {
+            ScrollbarAxis::Horizontal => client_point.x - drag.start_point.x,
+            ScrollbarAxis::Vertical => client_point.y - drag.start_point.y,
+        };
+        element.set_scroll_position(
+            drag.axis,
+            drag.start_position + (delta * drag.scroll_per_pixel) as f64,
+        );
+    }
+
+    /// Whether the key is a Space that activates the focused element, rather
+    /// than scrolling.
+    fn activates_focused_element(&self, event: &::keyboard_types::KeyboardEvent) -> bool {
+        event.code == Code::Space &&
+            self.get_focused_element()
+                .map_or(false, |focused| focused.as_maybe_activatable().is_some())
+    }
+
+    /// Starts dragging the resizer of the nearest resizable inclusive ancestor
+    /// of `target` if `client_point` is over it.
+    /// <https://drafts.csswg.org/css-ui/#resize>
+    fn maybe_start_resizing(&self, target: &Element, client_point: Point2D<f32>) {
+        let element = target
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| element.resize().is_some());
+        if let Some(element) = element {
+            if element.resizer_contains(client_point) {
+                self.resize_origin
+                    .set(Some((client_point, element.used_size())));
+                self.resizing_element.set(Some(&element));
+            }
+        }
+    }
+
+    /// The boundary point in the text under `client_point`, if `node`, which
+    /// is under it, is a text node.
+    pub fn text_position_from_point(
+        &self,
+        node: &Node,
+        client_point: Point2D<f32>,
+    ) -> Option<(DomRoot<Node>, u32)> {
+        let text = node.downcast::<Text>()?;
+        let viewport = self.window.current_viewport();
+        let point = client_point +
+            Vector2D::new(viewport.origin.x.to_f32_px(), viewport.origin.y.to_f32_px());
+        let TextIndexResponse(index) = self.window.text_index_at_point_query(node, point);
+        Some((
+            DomRoot::from_ref(node),
+            text.offset_of_rendered_character(index?),
+        ))
+    }
+
+    /// Starts selecting text at `client_point` when the primary button is
+    /// pressed over `node`, or abandons the selection if `node` isn't text.
+    fn maybe_start_selecting_text(&self, node: &Node, client_point: Point2D<f32>) {
+        let selection = match self.GetSelection() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let (text, offset) = match self.text_position_from_point(node, client_point) {
+            Some(position) => position,
+            None => return selection.Empty(),
+        };
+        // https://w3c.github.io/selection-api/#selectstart-event
+        let event = text
+            .upcast::<EventTarget>()
+            .fire_bubbling_cancelable_event(atom!("selectstart"));
+        if event.DefaultPrevented() {
+            return;
+        }
+        if selection.SetPosition(Some(&text), offset).is_ok() {
+            self.selecting_text.set(true);
+        }
+    }
+
+    /// Extends the selection to the text under `client_point` while the
+    /// primary button is held down after it started selecting text.
+    fn select_text_to(
+        &self,
+        node: Option<&Node>,
+        client_point: Point2D<f32>,
+        pressed_mouse_buttons: u16,
+    ) {
+        if !self.selecting_text.get() {
+            return;
+        }
+        // Selecting ends when the primary button is released, even if the
+        // mouseup happened outside of this document.
+        if pressed_mouse_buttons & 1 == 0 {
+            self.selecting_text.set(false);
+            return;
+        }
+        let (text, offset) =
+            match node.and_then(|node| self.text_position_from_point(node, client_point)) {
+                Some(position) => position,
+                None => return,
+            };
+        if let Some(selection) = self.GetSelection() {
+            let _ = selection.Extend(&text, offset);
+        }
+    }
+
+    /// Extends the selection of the document for the arrow keys and Home and
+    /// End with Shift held, unless a text control has the focus, and returns
+    /// whether it did.
+    fn modify_selection_for_key(&self, event: &::keyboard_types::KeyboardEvent) -> bool {
+        if event.state != KeyState::Down || !event.modifiers.contains(Modifiers::SHIFT) {
+            return false;
+        }
+        let word = event.modifiers.contains(Modifiers::ALT);
+        let (direction, granularity) = match event.key {
+            Key::ArrowLeft if word => ("backward", "word"),
+            Key::ArrowRight if word => ("forward", "word"),
+            Key::ArrowLeft => ("backward", "character"),
+            Key::ArrowRight => ("forward", "character"),
+            Key::Home if event.modifiers.contains(Modifiers::CONTROL) => {
+                ("backward", "documentboundary")
+            },
+            Key::End if event.modifiers.contains(Modifiers::CONTROL) => {
+                ("forward", "documentboundary")
+            },
+            _ => return false,
+        };
+        let focused = self.get_focused_element();
+        if focused.map_or(false, |focused| {
+            focused.is::<HTMLInputElement>() || focused.is::<HTMLTextAreaElement>()
+        }) {
+            return false;
+        }
+        let selection = match self.GetSelection() {
+            Some(selection) if selection.RangeCount() != 0 => selection,
+            _ => return false,
+        };
+        selection.Modify(
+            DOMString::from("extend"),
+            DOMString::from(direction),
+            DOMString::from(granularity),
+        );
+        true
+    }
+
+    /// Resizes the element whose resizer is being dragged so that its corner
+    /// follows `client_point`.
+    fn resize_to(&self, client_point: Point2D<f32>, pressed_mouse_buttons: u16) {
+        let element = match self.resizing_element.get() {
+            Some(element) => element,
+            None => return,
+        };
+        // The drag ends when the primary button is released, even if the
+        // mouseup happened outside of this document.
+        let resize = match element.resize() {
+            Some(resize) if pressed_mouse_buttons & 1 != 0 => resize,
+            _ => {
+                self.resizing_element.set(None);
+                return;
+            },
+        };
+        let (start_point, start_size) = match self.resize_origin.get() {
+            Some(origin) => origin,
+            None => return,
+        };
+        let style = match element.downcast::<HTMLElement>() {
+            Some(element) => element.Style(),
+            None => return,
+        };
+        let delta = client_point - start_point;
+        if resize != Resize::Vertical {
+            let width = (start_size.width + delta.x).max(0.);
+            let _ = style.SetProperty(
+                DOMString::from("width"),
+                DOMString::from(format!("{}px", width)),
+                DOMString::new(),
+            );
+        }
+        if resize != Resize::Horizontal {
+            let height = (start_size.height + delta.y).max(0.);
+            let _ = style.SetProperty(
+                DOMString::from("height"),
+                DOMString::from(format!("{}px", height)),
+                DOMString::new(),
+            );
+        }
+    }
+
     #[allow(unsafe_code)]
     pub fn handle_mouse_move_event(
         &self,
@@ -1185,12 +1657,42 @@ impl Document {
             Some(client_point) => client_point,
         };
 
-        let maybe_new_target = node_address.and_then(|address| {
-            let node = unsafe { node::from_untrusted_node_address(js_runtime, address) };
-            node.inclusive_ancestors(ShadowIncluding::No)
-                .filter_map(DomRoot::downcast::<Element>)
-                .next()
-        });
+        // The cursor doesn't move while the pointer is locked.
+        if self.pointer_lock_element.get().is_some() {
+            return;
+        }
+
+        let movement = self
+            .last_mouse_move_point
+            .replace(Some(client_point))
+            .map_or(Vector2D::zero(), |last_point| client_point - last_point);
+
+        let hit_node = node_address
+            .map(|address| unsafe { node::from_untrusted_node_address(js_runtime, address) });
+
+        // No mouse events are fired while something is dragged.
+        if drag_and_drop::handle_mouse_move(
+            self,
+            hit_node.as_deref(),
+            client_point,
+            pressed_mouse_buttons,
+        ) {
+            self.window
+                .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+            return;
+        }
+
+        self.resize_to(client_point, pressed_mouse_buttons);
+        self.drag_scrollbar_to(client_point, pressed_mouse_buttons);
+        self.select_text_to(hit_node.as_deref(), client_point, pressed_mouse_buttons);
+
+        let maybe_new_target = hit_node
+            .and_then(|node| {
+                node.inclusive_ancestors(ShadowIncluding::No)
+                    .filter_map(DomRoot::downcast::<Element>)
+                    .next()
+            })
+            .filter(|element| !self.is_inert(element.upcast()));
 
         // Send mousemove event to topmost target, unless it's an iframe, in which case the
         // compositor should have also sent an event to the inner document.
@@ -1203,6 +1705,7 @@ impl Document {
             client_point,
             new_target.upcast(),
             FireMouseEventType::Move,
+            movement,
             pressed_mouse_buttons,
         );
 
@@ -1241,6 +1744,7 @@ impl Document {
                 client_point,
                 old_target.upcast(),
                 FireMouseEventType::Out,
+                Vector2D::zero(),
                 pressed_mouse_buttons,
             );
 
@@ -1265,6 +1769,7 @@ impl Document {
                 client_point,
                 &new_target.upcast(),
                 FireMouseEventType::Over,
+                Vector2D::zero(),
                 pressed_mouse_buttons,
             );
 
@@ -1278,6 +1783,30 @@ impl Document {
             .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
     }
 
+    /// Handles a drag from outside of the window over the document, or its drop.
+    #[allow(unsafe_code)]
+    pub fn handle_drag_event(
+        &self,
+        js_runtime: *mut JSRuntime,
+        event_type: DragEventType,
+        client_point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>,
+        data: DragData,
+    ) {
+        debug!("drag {:?}: at {:?}", event_type, client_point);
+        let hit_node = node_address
+            .map(|address| unsafe { node::from_untrusted_node_address(js_runtime, address) });
+        drag_and_drop::handle_external_drag(
+            self,
+            event_type,
+            hit_node.as_deref(),
+            client_point,
+            data,
+        );
+        self.window
+            .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+    }
+
     #[allow(unsafe_code)]
     pub fn handle_wheel_event(
         &self,
@@ -1325,6 +1854,31 @@ impl Document {
         event.fire(target);
     }
 
+    /// The box of the nearest block-level inclusive ancestor of the node at `node_address`,
+    /// relative to the viewport, which a double-tap on the node zooms to.
+    #[allow(unsafe_code)]
+    pub fn double_tap_zoom_rect(
+        &self,
+        js_runtime: *mut JSRuntime,
+        node_address: Option<UntrustedNodeAddress>,
+    ) -> Option<Rect<f32, CSSPixel>> {
+        let node = unsafe { node::from_untrusted_node_address(js_runtime, node_address?) };
+        let block = node
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| {
+                element.style().map_or(false, |style| {
+                    style.get_box().clone_display().outside() == DisplayOutside::Block
+                })
+            })?;
+        let rect = block.upcast::<Node>().bounding_content_box()?;
+        let rect = rect.translate(-self.window.current_viewport().origin.to_vector());
+        Some(Rect::from_untyped(&Rect::new(
+            Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+            Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+        )))
+    }
+
     #[allow(unsafe_code)]
     pub fn handle_touch_event(
         &self,
@@ -1336,6 +1890,10 @@ impl Document {
     ) -> TouchEventResult {
         let TouchId(identifier) = touch_id;
 
+        if let TouchEventType::Down = event_type {
+            self.note_input();
+        }
+
         let event_name = match event_type {
             TouchEventType::Down => "touchstart",
             TouchEventType::Move => "touchmove",
@@ -1437,6 +1995,14 @@ impl Document {
 
     /// The entry point for all key processing for web content
     pub fn dispatch_key_event(&self, keyboard_event: ::keyboard_types::KeyboardEvent) {
+        // The user can always release the pointer with the Escape key.
+        if keyboard_event.state == KeyState::Down && keyboard_event.key == Key::Escape {
+            self.exit_pointer_lock();
+        }
+        if keyboard_event.state == KeyState::Down {
+            self.note_input();
+        }
+
         let focused = self.get_focused_element();
         let body = self.GetBody();
 
@@ -1495,8 +2061,17 @@ impl Document {
         }
 
         if cancel_state == EventDefault::Allowed {
-            let msg = EmbedderMsg::Keyboard(keyboard_event.clone());
-            self.send_to_embedder(msg);
+            // Keys that cancel a dialog, edit, select or activate the focused
+            // element aren't handed on to the embedder, whose compositor
+            // scrolls for the others.
+            if !self.cancel_modal_dialog_for_key(&keyboard_event) &&
+                !editing::handle_key(self, &keyboard_event) &&
+                !self.modify_selection_for_key(&keyboard_event) &&
+                !self.activates_focused_element(&keyboard_event)
+            {
+                let msg = EmbedderMsg::Keyboard(keyboard_event.clone());
+                self.send_to_embedder(msg);
+            }
 
             // This behavior is unspecced
             // We are supposed to dispatch synthetic click activation for Space and/or Return,
@@ -1638,16 +2213,16 @@ impl Document {
 
         // TODO: Should tick animation only when document is visible
 
+        // The callbacks of a throttled document wait until it gets close to
+        // the viewport again.
+        if self.window.animation_frames_suspended() {
+            return ident;
+        }
+
         // If we are running 'fake' animation frames, we unconditionally
         // set up a one-shot timer for script to execute the rAF callbacks.
         if self.is_faking_animation_frames() {
-            let callback = FakeRequestAnimationFrameCallback {
-                document: Trusted::new(self),
-            };
-            self.global().schedule_callback(
-                OneshotTimerCallback::FakeRequestAnimationFrame(callback),
-                MsDuration::new(FAKE_REQUEST_ANIMATION_FRAME_DELAY),
-            );
+            self.schedule_fake_animation_frame();
         } else if !self.running_animation_callbacks.get() {
             // No need to send a `ChangeRunningAnimationsState` if we're running animation callbacks:
             // we're guaranteed to already be in the "animation callbacks present" state.
@@ -1663,6 +2238,39 @@ impl Document {
         ident
     }
 
+    fn schedule_fake_animation_frame(&self) {
+        let callback = FakeRequestAnimationFrameCallback {
+            document: Trusted::new(self),
+        };
+        self.global().schedule_callback(
+            OneshotTimerCallback::FakeRequestAnimationFrame(callback),
+            MsDuration::new(FAKE_REQUEST_ANIMATION_FRAME_DELAY),
+        );
+    }
+
+    /// Stops ticking the animation frame callbacks when the window suspends
+    /// them because it is throttled, and ticks them again once it stops.
+    pub fn animation_frames_suspension_changed(&self) {
+        if self.animation_frame_list.borrow().is_empty() || !self.is_fully_active() {
+            return;
+        }
+        if !self.window.animation_frames_suspended() {
+            if self.is_faking_animation_frames() {
+                self.schedule_fake_animation_frame();
+            } else {
+                let event = ScriptMsg::ChangeRunningAnimationsState(
+                    AnimationState::AnimationCallbacksPresent,
+                );
+                self.window().send_to_constellation(event);
+            }
+        } else if !self.is_faking_animation_frames() {
+            let event = ScriptMsg::ChangeRunningAnimationsState(
+                AnimationState::NoAnimationCallbacksPresent,
+            );
+            self.window().send_to_constellation(event);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-window-cancelanimationframe>
     pub fn cancel_animation_frame(&self, ident: u32) {
         let mut list = self.animation_frame_list.borrow_mut();
@@ -1671,8 +2279,25 @@ impl Document {
         }
     }
 
+    /// When the next animation frame is expected, in nanoseconds, if any
+    /// callbacks are waiting for one. Frames are expected as often as fake
+    /// ones are scheduled.
+    pub fn next_animation_frame_time(&self) -> Option<u64> {
+        if self.animation_frame_list.borrow().is_empty() {
+            return None;
+        }
+        Some(self.last_animation_frame_time.get() + FAKE_REQUEST_ANIMATION_FRAME_DELAY * 1_000_000)
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#run-the-animation-frame-callbacks>
     pub fn run_the_animation_frame_callbacks(&self) {
+        // The callbacks of a document in the back-forward cache run once it
+        // is shown again, and the ones of a throttled document once it gets
+        // close to the viewport again.
+        if !self.is_fully_active() || self.window.animation_frames_suspended() {
+            return;
+        }
+
         rooted_vec!(let mut animation_frame_list);
         mem::swap(
             &mut *animation_frame_list,
@@ -1680,6 +2305,7 @@ impl Document {
         );
 
         self.running_animation_callbacks.set(true);
+        self.last_animation_frame_time.set(time::precise_time_ns());
         let was_faking_animation_frames = self.is_faking_animation_frames();
         let timing = self.global().performance().Now();
 
@@ -1748,6 +2374,49 @@ impl Document {
         }
     }
 
+    /// Starts or stops giving animation frames to a dedicated worker of the
+    /// window, as it starts or stops waiting for them.
+    pub fn set_worker_wants_animation_frames(&self, worker: &Worker, wants: bool) {
+        let mut workers = self.animation_frame_workers.borrow_mut();
+        let was_empty = workers.is_empty();
+        let position = workers.iter().position(|w| &**w == worker);
+        match (position, wants) {
+            (None, true) => workers.push(Dom::from_ref(worker)),
+            (Some(position), false) => {
+                workers.remove(position);
+            },
+            _ => return,
+        }
+        let state = match (was_empty, workers.is_empty()) {
+            (true, false) => AnimationState::WorkerAnimationCallbacksPresent,
+            (false, true) => AnimationState::NoWorkerAnimationCallbacksPresent,
+            _ => return,
+        };
+        self.window()
+            .send_to_constellation(ScriptMsg::ChangeRunningAnimationsState(state));
+    }
+
+    /// Gives an animation frame to the dedicated workers that wait for one,
+    /// unless the document is hidden or throttled.
+    pub fn tick_worker_animation_frames(&self) {
+        if !self.is_fully_active() || self.window.animation_frames_suspended() {
+            return;
+        }
+        let terminated: Vec<_> = self
+            .animation_frame_workers
+            .borrow()
+            .iter()
+            .filter(|worker| worker.is_closing())
+            .map(|worker| DomRoot::from_ref(&**worker))
+            .collect();
+        for worker in terminated {
+            self.set_worker_wants_animation_frames(&worker, false);
+        }
+        for worker in self.animation_frame_workers.borrow().iter() {
+            worker.send_animation_frame();
+        }
+    }
+
     pub fn fetch_async(
         &self,
         load: LoadType,
@@ -1807,7 +2476,7 @@ impl Document {
 
         // Servo measures when the top-level content (not iframes) is loaded.
         if (self.top_level_dom_complete.get() == 0) && loader.is_only_blocked_by_iframes() {
-            update_with_current_time_ms(&self.top_level_dom_complete);
+            update_with_current_time_ns(&self.top_level_dom_complete);
         }
 
         if loader.is_blocked() || loader.events_inhibited() {
@@ -1875,9 +2544,15 @@ impl Document {
 
     // https://html.spec.whatwg.org/multipage/#unload-a-document
     pub fn unload(&self, recursive_flag: bool) {
+        self.exit_pointer_lock();
         // TODO: Step 1, increase the event loop's termination nesting level by 1.
         // Step 2
         self.incr_ignore_opens_during_unload_counter();
+        // Without a back-forward cache, the document can't be kept alive to
+        // be shown again when traversing the session history.
+        if !pref!(dom.bfcache.enabled) {
+            self.salvageable.set(false);
+        }
         // Step 3-6
         if self.page_showing.get() {
             self.page_showing.set(false);
@@ -1904,7 +2579,10 @@ impl Document {
             event.set_trusted(true);
             let event_target = self.window.upcast::<EventTarget>();
             let has_listeners = event.has_listeners_for(&event_target, &atom!("unload"));
+            let start = time::precise_time_ns();
             let _ = self.window.dispatch_event_with_target_override(&event);
+            self.fired_unload_timing
+                .set(Some((start, time::precise_time_ns())));
             self.fired_unload.set(true);
             // Step 9
             if has_listeners {
@@ -1928,12 +2606,19 @@ impl Document {
         let global_scope = self.window.upcast::<GlobalScope>();
         // Step 10, 14
         // https://html.spec.whatwg.org/multipage/#unloading-document-cleanup-steps
+        // Make the websockets disappear, a document that still had live
+        // connections can't be kept in the back-forward cache.
+        if global_scope.close_websockets() {
+            self.salvageable.set(false);
+        }
         if !self.salvageable.get() {
             // Step 1 of clean-up steps.
             global_scope.close_event_sources();
-            let msg = ScriptMsg::DiscardDocument;
-            let _ = global_scope.script_to_constellation_chan().send(msg);
         }
+        // The constellation keeps the document in the session history only
+        // if it is salvageable, and discards it otherwise.
+        let msg = ScriptMsg::DocumentSalvageable(self.salvageable.get());
+        let _ = global_scope.script_to_constellation_chan().send(msg);
         // https://w3c.github.io/FileAPI/#lifeTime
         global_scope.clean_up_all_file_resources();
 
@@ -1996,7 +2681,7 @@ impl Document {
                     event.set_trusted(true);
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventStart
-                    update_with_current_time_ms(&document.load_event_start);
+                    update_with_current_time_ns(&document.load_event_start);
 
                     debug!("About to dispatch load for {:?}", document.url());
                     // FIXME(nox): Why are errors silenced here?
@@ -2005,13 +2690,15 @@ impl Document {
                     );
 
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
-                    update_with_current_time_ms(&document.load_event_end);
+                    update_with_current_time_ns(&document.load_event_end);
+                    document.update_navigation_timing_duration();
 
                     window.reflow(ReflowGoal::Full, ReflowReason::DocumentLoaded);
 
                     if let Some(fragment) = document.url().fragment() {
                         document.check_and_scroll_fragment(fragment);
                     }
+                    window.History().restore_user_state_after_load();
                 }),
                 self.window.upcast(),
             )
@@ -2217,7 +2904,7 @@ impl Document {
             "Complete before DOMContentLoaded?"
         );
 
-        update_with_current_time_ms(&self.dom_content_loaded_event_start);
+        update_with_current_time_ns(&self.dom_content_loaded_event_start);
 
         // Step 4.1.
         let window = self.window();
@@ -2229,7 +2916,7 @@ impl Document {
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"));
-                update_with_current_time_ms(&document.dom_content_loaded_event_end);
+                update_with_current_time_ns(&document.dom_content_loaded_event_end);
                 }),
                 window.upcast(),
             )
@@ -2381,6 +3068,42 @@ impl Document {
         self.unload_event_end.get()
     }
 
+    pub fn set_unload_event_timing(&self, start: u64, end: u64) {
+        self.unload_event_start.set(start);
+        self.unload_event_end.set(end);
+    }
+
+    pub fn fired_unload_timing(&self) -> Option<(u64, u64)> {
+        self.fired_unload_timing.get()
+    }
+
+    pub fn navigation_type(&self) -> NavigationType {
+        self.navigation_type.get()
+    }
+
+    pub fn set_navigation_type(&self, navigation_type: NavigationType) {
+        self.navigation_type.set(navigation_type);
+    }
+
+    pub fn allow_declarative_shadow_roots(&self) -> bool {
+        self.allow_declarative_shadow_roots.get()
+    }
+
+    pub fn set_allow_declarative_shadow_roots(&self, allow: bool) {
+        self.allow_declarative_shadow_roots.set(allow);
+    }
+
+    /// The navigation of the document lasts until the end of its load event, which is only
+    /// known once its timing entry was queued.
+    /// <https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-duration>
+    fn update_navigation_timing_duration(&self) {
+        let performance = self.window.Performance();
+        let duration = performance.to_dom_high_res_time_stamp(self.load_event_end.get());
+        for entry in performance.GetEntriesByType(DOMString::from("navigation")) {
+            entry.set_duration(duration);
+        }
+    }
+
     pub fn start_tti(&self) {
         if self.get_interactive_metrics().needs_tti() {
             self.tti_window.borrow_mut().start_window();
@@ -2493,16 +3216,69 @@ impl Document {
         }
     }
 
-    pub fn register_media_controls(&self, controls: &ShadowRoot) -> String {
-        let id = Uuid::new_v4().to_string();
-        self.media_controls
+    /// <https://html.spec.whatwg.org/multipage/#start-intersection-observing-a-lazy-loading-element>
+    pub fn start_intersection_observing_a_lazy_loading_element(&self, element: &Element) {
+        let mut elements = self.lazy_loading_elements.borrow_mut();
+        if !elements.iter().any(|x| **x == *element) {
+            elements.push(Dom::from_ref(element));
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#stop-intersection-observing-a-lazy-loading-element>
+    pub fn stop_intersection_observing_a_lazy_loading_element(&self, element: &Element) {
+        self.lazy_loading_elements
             .borrow_mut()
-            .insert(id.clone(), Dom::from_ref(controls));
-        id
+            .retain(|x| **x != *element);
     }
 
-    pub fn unregister_media_controls(&self, id: &str) {
-        if let Some(ref media_controls) = self.media_controls.borrow_mut().remove(id) {
+    /// Resumes the load of the lazy loading elements that got near the
+    /// viewport, which happens while updating the rendering, once the layout
+    /// is up to date.
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    pub fn run_the_lazy_load_intersection_steps(&self) {
+        if self.lazy_loading_elements.borrow().is_empty() {
+            return;
+        }
+        let margin = Au::from_px(LAZY_LOAD_ROOT_MARGIN);
+        let root = self.window.current_viewport().inflate(margin, margin);
+        let intersecting: Vec<DomRoot<Element>> = self
+            .lazy_loading_elements
+            .borrow()
+            .iter()
+            .filter(|element| {
+                // Elements that aren't rendered never intersect the viewport.
+                element
+                    .upcast::<Node>()
+                    .bounding_content_box()
+                    .map_or(false, |rect| {
+                        rect.min_x() <= root.max_x() &&
+                            root.min_x() <= rect.max_x() &&
+                            rect.min_y() <= root.max_y() &&
+                            root.min_y() <= rect.max_y()
+                    })
+            })
+            .map(|element| DomRoot::from_ref(&**element))
+            .collect();
+        for element in intersecting {
+            self.stop_intersection_observing_a_lazy_loading_element(&element);
+            if let Some(image) = element.downcast::<HTMLImageElement>() {
+                image.resume_lazy_load();
+            } else if let Some(iframe) = element.downcast::<HTMLIFrameElement>() {
+                iframe.resume_lazy_load();
+            }
+        }
+    }
+
+    pub fn register_media_controls(&self, controls: &ShadowRoot) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.media_controls
+            .borrow_mut()
+            .insert(id.clone(), Dom::from_ref(controls));
+        id
+    }
+
+    pub fn unregister_media_controls(&self, id: &str) {
+        if let Some(ref media_controls) = self.media_controls.borrow_mut().remove(id) {
             let media_controls = DomRoot::from_ref(&**media_controls);
             media_controls.Host().detach_shadow();
         } else {
@@ -2708,7 +3484,7 @@ impl LayoutDocumentHelpers for LayoutDom<Document> {
 // https://html.spec.whatwg.org/multipage/#is-a-registrable-domain-suffix-of-or-is-equal-to
 // The spec says to return a bool, we actually return an Option<Host> containing
 // the parsed host in the successful case, to avoid having to re-parse the host.
-fn get_registrable_domain_suffix_of_or_is_equal_to(
+pub fn get_registrable_domain_suffix_of_or_is_equal_to(
     host_suffix_string: &str,
     original_host: Host,
 ) -> Option<Host> {
@@ -2839,6 +3615,7 @@ impl Document {
             scripts: Default::default(),
             anchors: Default::default(),
             applets: Default::default(),
+            all: Default::default(),
             style_shared_lock: {
                 lazy_static! {
                     /// Per-process shared lock for author-origin stylesheets
@@ -2856,6 +3633,10 @@ impl Document {
             },
             stylesheets: DomRefCell::new(DocumentStylesheetSet::new()),
             stylesheet_list: MutNullableDom::new(None),
+            registered_custom_properties: DomRefCell::new(HashSet::new()),
+            fonts: MutNullableDom::new(None),
+            web_font_loads_pending: Cell::new(true),
+            last_input_time: Cell::new(None),
             ready_state: Cell::new(ready_state),
             domcontentloaded_dispatched: Cell::new(domcontentloaded_dispatched),
             possibly_focused: Default::default(),
@@ -2870,6 +3651,8 @@ impl Document {
             animation_frame_ident: Cell::new(0),
             animation_frame_list: DomRefCell::new(vec![]),
             running_animation_callbacks: Cell::new(false),
+            last_animation_frame_time: Cell::new(0),
+            animation_frame_workers: DomRefCell::new(vec![]),
             loader: DomRefCell::new(doc_loader),
             current_parser: Default::default(),
             reflow_timeout: Cell::new(None),
@@ -2888,17 +3671,29 @@ impl Document {
             load_event_end: Cell::new(Default::default()),
             unload_event_start: Cell::new(Default::default()),
             unload_event_end: Cell::new(Default::default()),
+            navigation_type: Cell::new(NavigationType::Navigate),
+            allow_declarative_shadow_roots: Cell::new(false),
             https_state: Cell::new(HttpsState::None),
             origin: origin,
             referrer: referrer,
             referrer_policy: Cell::new(referrer_policy),
             target_element: MutNullableDom::new(None),
             last_click_info: DomRefCell::new(None),
+            resizing_element: Default::default(),
+            resize_origin: Cell::new(None),
+            dragged_scroll_container: Default::default(),
+            scrollbar_drag: Cell::new(None),
+            selecting_text: Cell::new(false),
             ignore_destructive_writes_counter: Default::default(),
             ignore_opens_during_unload_counter: Default::default(),
             spurious_animation_frames: Cell::new(0),
             dom_count: Cell::new(1),
             fullscreen_element: MutNullableDom::new(None),
+            modal_dialogs: DomRefCell::new(vec![]),
+            pointer_lock_element: MutNullableDom::new(None),
+            pointer_lock_pending: Cell::new(false),
+            storage_access_granted: Cell::new(false),
+            last_mouse_move_point: Cell::new(None),
             form_id_listener_map: Default::default(),
             interactive_time: DomRefCell::new(interactive_time),
             tti_window: DomRefCell::new(InteractiveWindow::new()),
@@ -2907,8 +3702,12 @@ impl Document {
             page_showing: Cell::new(false),
             salvageable: Cell::new(true),
             active_parser_was_aborted: Cell::new(false),
+            iframe_load_in_progress: Cell::new(false),
+            mute_iframe_load: Cell::new(false),
             fired_unload: Cell::new(false),
+            fired_unload_timing: Cell::new(None),
             responsive_images: Default::default(),
+            lazy_loading_elements: Default::default(),
             redirect_count: Cell::new(0),
             completely_loaded: Cell::new(false),
             script_and_layout_blockers: Cell::new(0),
@@ -2919,6 +3718,8 @@ impl Document {
             dirty_webgl_contexts: DomRefCell::new(HashMap::new()),
             csp_list: DomRefCell::new(None),
             selection: MutNullableDom::new(None),
+            edit_history: Default::default(),
+            drag_and_drop: Default::default(),
         }
     }
 
@@ -2942,13 +3743,16 @@ impl Document {
                 .get_attribute(&ns!(), &local_name!("nonce"))
                 .map(|attr| Cow::Owned(attr.value().to_string())),
         };
-        // TODO: Instead of ignoring violations, report them.
-        self.get_csp_list()
-            .map(|c| {
-                c.should_elements_inline_type_behavior_be_blocked(&element, type_, source)
-                    .0
-            })
-            .unwrap_or(csp::CheckResult::Allowed)
+        let (result, violations) = match self.get_csp_list() {
+            Some(csp_list) => {
+                csp_list.should_elements_inline_type_behavior_be_blocked(&element, type_, source)
+            },
+            None => return csp::CheckResult::Allowed,
+        };
+        self.window
+            .upcast::<GlobalScope>()
+            .report_csp_violations(violations);
+        result
     }
 
     /// Prevent any JS or layout from running until the corresponding call to
@@ -3013,6 +3817,48 @@ impl Document {
         ))
     }
 
+    // https://wicg.github.io/sanitizer-api/#dom-document-parsehtml
+    #[allow(non_snake_case)]
+    pub fn ParseHTML(
+        window: &Window,
+        html: DOMString,
+        options: &SetHTMLOptions,
+    ) -> Fallible<DomRoot<Document>> {
+        // Step 1.
+        let doc = window.Document();
+        let docloader = DocumentLoader::new(&*doc.loader());
+        let document = Document::new(
+            window,
+            HasBrowsingContext::No,
+            None,
+            doc.origin().clone(),
+            IsHTMLDocument::HTMLDocument,
+            None,
+            None,
+            DocumentActivity::Inactive,
+            DocumentSource::FromParser,
+            docloader,
+            None,
+            None,
+            Default::default(),
+        );
+
+        // Step 2.
+        let sanitizer = SanitizerConfiguration::from_options(options)?;
+
+        // Step 3. The document has no browsing context, so scripting is
+        // disabled while parsing.
+        let url = document.url();
+        ServoParser::parse_html_document(&document, html, url);
+        document.set_ready_state(DocumentReadyState::Complete);
+
+        // Step 4.
+        sanitizer.sanitize(document.upcast());
+
+        // Step 5.
+        Ok(document)
+    }
+
     pub fn new(
         window: &Window,
         has_browsing_context: HasBrowsingContext,
@@ -3146,13 +3992,23 @@ impl Document {
         let window_size = self.window().window_size();
         let viewport_size = window_size.initial_viewport;
         let device_pixel_ratio = window_size.device_pixel_ratio;
-        Device::new(MediaType::screen(), viewport_size, device_pixel_ratio)
+        let mut device = Device::new(MediaType::screen(), viewport_size, device_pixel_ratio);
+        device.set_prefers_color_scheme(self.window().prefers_color_scheme());
+        device
     }
 
     pub fn salvageable(&self) -> bool {
         self.salvageable.get()
     }
 
+    pub fn set_iframe_load_in_progress(&self, in_progress: bool) {
+        self.iframe_load_in_progress.set(in_progress);
+    }
+
+    pub fn mute_iframe_load(&self) -> bool {
+        self.mute_iframe_load.get()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#appropriate-template-contents-owner-document>
     pub fn appropriate_template_contents_owner_document(&self) -> DomRoot<Document> {
         self.appropriate_template_contents_owner_document
@@ -3409,6 +4265,60 @@ impl Document {
         promise
     }
 
+    /// Adds a modal dialog on top of the top layer.
+    /// <https://fullscreen.spec.whatwg.org/#top-layer-add>
+    pub fn add_modal_dialog(&self, dialog: &HTMLDialogElement) {
+        let mut modal_dialogs = self.modal_dialogs.borrow_mut();
+        modal_dialogs.retain(|modal_dialog| &**modal_dialog != dialog);
+        modal_dialogs.push(Dom::from_ref(dialog));
+    }
+
+    pub fn remove_modal_dialog(&self, dialog: &HTMLDialogElement) {
+        self.modal_dialogs
+            .borrow_mut()
+            .retain(|modal_dialog| &**modal_dialog != dialog);
+    }
+
+    /// The topmost modal dialog, by which the document is blocked.
+    /// <https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog>
+    pub fn modal_dialog(&self) -> Option<DomRoot<HTMLDialogElement>> {
+        self.modal_dialogs
+            .borrow()
+            .last()
+            .map(|modal_dialog| DomRoot::from_ref(&**modal_dialog))
+    }
+
+    /// Whether the node can't be interacted with, because the document is
+    /// blocked by a modal dialog that doesn't contain it.
+    /// <https://html.spec.whatwg.org/multipage/#inert>
+    pub fn is_inert(&self, node: &Node) -> bool {
+        let modal_dialog = match self.modal_dialog() {
+            Some(modal_dialog) => modal_dialog,
+            None => return false,
+        };
+        !node
+            .inclusive_ancestors(ShadowIncluding::Yes)
+            .any(|ancestor| &*ancestor == modal_dialog.upcast::<Node>())
+    }
+
+    /// Cancels the topmost modal dialog when the Escape key is pressed.
+    /// <https://html.spec.whatwg.org/multipage/#cancel-dialog>
+    fn cancel_modal_dialog_for_key(
+        &self,
+        keyboard_event: &::keyboard_types::KeyboardEvent,
+    ) -> bool {
+        if keyboard_event.state != KeyState::Down || keyboard_event.key != Key::Escape {
+            return false;
+        }
+        match self.modal_dialog() {
+            Some(modal_dialog) => {
+                modal_dialog.cancel();
+                true
+            },
+            None => false,
+        }
+    }
+
     pub fn set_fullscreen_element(&self, element: Option<&Element>) {
         self.fullscreen_element.set(element);
     }
@@ -3433,6 +4343,178 @@ impl Document {
         }
     }
 
+    /// Whether the document is embedded in a document of another site.
+    fn is_third_party(&self) -> bool {
+        let browsing_context = match self.browsing_context() {
+            Some(browsing_context) => browsing_context,
+            None => return false,
+        };
+        if self.window.is_top_level() {
+            return false;
+        }
+        // The documents of a site share an event loop, so a top-level document
+        // that isn't in this one belongs to another site.
+        match browsing_context.top().document() {
+            Some(top_document) => reg_host(&top_document.url()) != reg_host(&self.url()),
+            None => true,
+        }
+    }
+
+    /// <https://privacycg.github.io/storage-access/#has-storage-access>
+    fn has_storage_access(&self) -> bool {
+        self.storage_access_granted.get() || !self.is_third_party()
+    }
+
+    /// Whether the cookies of the document are blocked, which is the case for
+    /// third-party documents without storage access when third-party cookies
+    /// are blocked.
+    pub fn cookies_blocked(&self) -> bool {
+        pref!(network.cookie.block_third_party) && !self.has_storage_access()
+    }
+
+    /// Tells the resource thread whether to block the cookies of the requests
+    /// of the document.
+    pub fn notify_cookies_blocked(&self, blocked: bool) {
+        if !pref!(network.cookie.block_third_party) {
+            return;
+        }
+        let _ = self
+            .window
+            .upcast::<GlobalScope>()
+            .resource_threads()
+            .send(SetCookiesBlocked(self.window.pipeline_id(), blocked));
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-element-requestpointerlock
+    pub fn request_pointer_lock(&self, element: &Element) {
+        // TODO: Only documents that are focused should be able to lock the
+        // pointer, and only in response to user activation.
+        if !element.upcast::<Node>().is_connected() ||
+            !self.is_fully_active() ||
+            self.pointer_lock_pending.get()
+        {
+            self.queue_pointer_lock_event(atom!("pointerlockerror"));
+            return;
+        }
+
+        // Moving the lock to another element of the document keeps the pointer
+        // locked.
+        if let Some(locked) = self.pointer_lock_element.get() {
+            if &*locked != element {
+                self.pointer_lock_element.set(Some(element));
+                self.queue_pointer_lock_event(atom!("pointerlockchange"));
+            }
+            return;
+        }
+
+        self.pointer_lock_pending.set(true);
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = self.window.task_manager().dom_manipulation_task_source();
+        let canceller = self
+            .window
+            .upcast::<GlobalScope>()
+            .task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_document = Trusted::new(self);
+        let trusted_element = Trusted::new(element);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let locked: bool = message.to().unwrap();
+                let document = trusted_document.clone();
+                let element = trusted_element.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(pointer_lock_applied: move || {
+                        document.root().pointer_lock_applied(&element.root(), locked);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue pointer lock task {:?}", err);
+                }
+            }),
+        );
+        self.send_to_embedder(EmbedderMsg::LockPointer(sender));
+    }
+
+    /// Handles the embedder's answer to locking the pointer to `element`.
+    fn pointer_lock_applied(&self, element: &Element, locked: bool) {
+        self.pointer_lock_pending.set(false);
+        if !locked {
+            self.upcast::<EventTarget>()
+                .fire_event(atom!("pointerlockerror"));
+            return;
+        }
+
+        // The element may have left the document in the meantime.
+        let node = element.upcast::<Node>();
+        if !node.is_connected() || &*node.owner_doc() != self || !self.is_fully_active() {
+            self.send_to_embedder(EmbedderMsg::UnlockPointer);
+            self.upcast::<EventTarget>()
+                .fire_event(atom!("pointerlockerror"));
+            return;
+        }
+
+        self.pointer_lock_element.set(Some(element));
+        self.window
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::PointerLockChanged(true))
+            .unwrap();
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("pointerlockchange"));
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-exitpointerlock
+    pub fn exit_pointer_lock(&self) {
+        if self.pointer_lock_element.get().is_none() {
+            return;
+        }
+        self.pointer_lock_element.set(None);
+        self.send_to_embedder(EmbedderMsg::UnlockPointer);
+        self.window
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::PointerLockChanged(false))
+            .unwrap();
+        self.queue_pointer_lock_event(atom!("pointerlockchange"));
+    }
+
+    pub fn pointer_lock_element(&self) -> Option<DomRoot<Element>> {
+        self.pointer_lock_element.get()
+    }
+
+    fn queue_pointer_lock_event(&self, name: Atom) {
+        self.window
+            .task_manager()
+            .dom_manipulation_task_source()
+            .queue_simple_event(self.upcast(), name, &self.window);
+    }
+
+    /// Fires `mousemove` at the element the pointer is locked to, with the
+    /// motion of the mouse, in CSS pixels.
+    pub fn handle_pointer_locked_move_event(
+        &self,
+        movement: Vector2D<f32>,
+        pressed_mouse_buttons: u16,
+    ) {
+        let element = match self.pointer_lock_element.get() {
+            Some(element) => element,
+            None => return,
+        };
+        // The cursor stays where it was when the pointer got locked.
+        let client_point = self
+            .last_mouse_move_point
+            .get()
+            .unwrap_or_else(Point2D::zero);
+        self.fire_mouse_event(
+            client_point,
+            element.upcast(),
+            FireMouseEventType::Move,
+            movement,
+            pressed_mouse_buttons,
+        );
+    }
+
     fn reset_form_owner_for_listeners(&self, id: &Atom) {
         let map = self.form_id_listener_map.borrow();
         if let Some(listeners) = map.get(id) {
@@ -3472,8 +4554,14 @@ impl Document {
         self.shadow_roots_styles_changed.set(false);
     }
 
+    /// The number of sheets in `styleSheets`, which doesn't include the
+    /// adopted ones.
     pub fn stylesheet_count(&self) -> usize {
-        self.stylesheets.borrow().len()
+        self.stylesheets
+            .borrow()
+            .iter()
+            .filter(|(sheet, _origin)| sheet.owner.is_some())
+            .count()
     }
 
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
@@ -3481,7 +4569,8 @@ impl Document {
 
         stylesheets
             .get(Origin::Author, index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .and_then(|s| s.owner.as_ref())
+            .and_then(|owner| owner.upcast::<Node>().get_cssom_stylesheet())
     }
 
     /// Add a stylesheet owned by `owner` to the list of document sheets, in the
@@ -3492,10 +4581,9 @@ impl Document {
         let insertion_point = stylesheets
             .iter()
             .map(|(sheet, _origin)| sheet)
-            .find(|sheet_in_doc| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_doc.owner.upcast())
+            .find(|sheet_in_doc| match sheet_in_doc.owner {
+                Some(ref other) => owner.upcast::<Node>().is_before(other.upcast()),
+                None => true,
             })
             .cloned();
 
@@ -3508,7 +4596,7 @@ impl Document {
             .unwrap();
 
         DocumentOrShadowRoot::add_stylesheet(
-            owner,
+            Some(owner),
             StylesheetSetRef::Document(stylesheets),
             sheet,
             insertion_point,
@@ -3525,17 +4613,88 @@ impl Document {
             .unwrap();
 
         DocumentOrShadowRoot::remove_stylesheet(
-            owner,
+            Some(owner),
             s,
             StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
         )
     }
 
+    /// Add a stylesheet adopted by the document after all the other sheets.
+    pub fn add_adopted_stylesheet(&self, sheet: &CSSStyleSheet) {
+        let sheet = sheet.style_stylesheet_arc().clone();
+        self.window
+            .layout_chan()
+            .send(Msg::AddStylesheet(sheet.clone(), None))
+            .unwrap();
+
+        DocumentOrShadowRoot::add_stylesheet(
+            None,
+            StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+            sheet,
+            None,
+            self.style_shared_lock(),
+        );
+    }
+
+    /// Remove a stylesheet adopted by the document.
+    pub fn remove_adopted_stylesheet(&self, sheet: &CSSStyleSheet) {
+        let sheet = sheet.style_stylesheet_arc();
+        self.window
+            .layout_chan()
+            .send(Msg::RemoveStylesheet(sheet.clone()))
+            .unwrap();
+
+        DocumentOrShadowRoot::remove_stylesheet(
+            None,
+            sheet,
+            StylesheetSetRef::Document(&mut *self.stylesheets.borrow_mut()),
+        )
+    }
+
+    /// Registers a custom property with layout, failing if a property with the
+    /// same name was already registered from script.
+    pub fn register_custom_property(&self, registration: PropertyRegistration) -> Result<(), ()> {
+        if !self
+            .registered_custom_properties
+            .borrow_mut()
+            .insert(registration.name.clone())
+        {
+            return Err(());
+        }
+        self.window
+            .layout_chan()
+            .send(Msg::RegisterProperty(registration))
+            .unwrap();
+        self.invalidate_stylesheets();
+        Ok(())
+    }
+
+    /// Notes that the user interacted with the document, so that the layout shifts that shortly
+    /// follow are not held against it.
+    /// <https://wicg.github.io/layout-instability/#sec-input-exclusion>
+    fn note_input(&self) {
+        self.last_input_time.set(Some(time::precise_time_ns()));
+    }
+
+    /// When the user last interacted with the document, as given by `time::precise_time_ns`.
+    pub fn last_input_time(&self) -> Option<u64> {
+        self.last_input_time.get()
+    }
+
+    /// Notes whether web fonts are still loading after a reflow, which settles the status of the
+    /// document's font face set.
+    pub fn set_web_font_loads_pending(&self, pending: bool) {
+        self.web_font_loads_pending.set(pending);
+        if let Some(fonts) = self.fonts.get() {
+            fonts.handle_loading_state(pending);
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-tree-accessors:determine-the-value-of-a-named-property
     // Support method for steps 1-3:
     // Count if there are 0, 1, or >1 elements that match the name.
     // (This takes the filter as a method so the window named getter can use it too)
-    fn look_up_named_elements(
+    pub fn look_up_named_elements(
         &self,
         name: &Atom,
         nameditem_filter: fn(&Node, &Atom) -> bool,
@@ -3634,6 +4793,12 @@ impl ProfilerMetadataFactory for Document {
 }
 
 impl DocumentMethods for Document {
+    // https://drafts.csswg.org/css-font-loading/#dom-document-fonts
+    fn Fonts(&self) -> DomRoot<FontFaceSet> {
+        self.fonts
+            .or_init(|| FontFaceSet::new(&self.window, self.web_font_loads_pending.get()))
+    }
+
     // https://drafts.csswg.org/cssom/#dom-document-stylesheets
     fn StyleSheets(&self) -> DomRoot<StyleSheetList> {
         self.stylesheet_list.or_init(|| {
@@ -3644,6 +4809,21 @@ impl DocumentMethods for Document {
         })
     }
 
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn AdoptedStyleSheets(&self, cx: JSContext) -> JSVal {
+        self.document_or_shadow_root.get_adopted_stylesheets(cx)
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    #[allow(unrooted_must_root)]
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        self.document_or_shadow_root.set_adopted_stylesheets(
+            cx,
+            value,
+            StyleSheetListOwner::Document(Dom::from_ref(self)),
+        )
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-implementation
     fn Implementation(&self) -> DomRoot<DOMImplementation> {
         self.implementation.or_init(|| DOMImplementation::new(self))
@@ -3673,6 +4853,26 @@ impl DocumentMethods for Document {
         false
     }
 
+    // https://w3c.github.io/editing/docs/execCommand/#execcommand()
+    fn ExecCommand(&self, command_id: DOMString, _show_ui: bool, value: DOMString) -> bool {
+        editing::exec_command(self, &command_id, value)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()
+    fn QueryCommandEnabled(&self, command_id: DOMString) -> bool {
+        editing::query_command_enabled(self, &command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandstate()
+    fn QueryCommandState(&self, command_id: DOMString) -> bool {
+        editing::query_command_state(self, &command_id)
+    }
+
+    // https://w3c.github.io/editing/docs/execCommand/#querycommandsupported()
+    fn QueryCommandSupported(&self, command_id: DOMString) -> bool {
+        editing::query_command_supported(&command_id)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-domain
     fn Domain(&self) -> DOMString {
         // Step 1.
@@ -4127,6 +5327,36 @@ impl DocumentMethods for Document {
         TreeWalker::new(self, root, what_to_show, filter)
     }
 
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    fn CreateExpression(
+        &self,
+        expression: DOMString,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        XPathExpression::new(&self.window, &expression, resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-creatensresolver
+    fn CreateNSResolver(&self, node_resolver: &Node) -> DomRoot<Node> {
+        DomRoot::from_ref(node_resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+    fn Evaluate(
+        &self,
+        expression: DOMString,
+        context_node: &Node,
+        resolver: Option<Rc<XPathNSResolver>>,
+        type_: u16,
+        result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        XPathExpression::new(&self.window, &expression, resolver)?.Evaluate(
+            context_node,
+            type_,
+            result,
+        )
+    }
+
     // https://html.spec.whatwg.org/multipage/#document.title
     fn Title(&self) -> DOMString {
         let title = self.GetDocumentElement().and_then(|root| {
@@ -4424,7 +5654,7 @@ impl DocumentMethods for Document {
 
     // https://html.spec.whatwg.org/multipage/#dom-document-cookie
     fn GetCookie(&self) -> Fallible<DOMString> {
-        if self.is_cookie_averse() {
+        if self.is_cookie_averse() || self.cookies_blocked() {
             return Ok(DOMString::new());
         }
 
@@ -4445,7 +5675,7 @@ impl DocumentMethods for Document {
 
     // https://html.spec.whatwg.org/multipage/#dom-document-cookie
     fn SetCookie(&self, cookie: DOMString) -> ErrorResult {
-        if self.is_cookie_averse() {
+        if self.is_cookie_averse() || self.cookies_blocked() {
             return Ok(());
         }
 
@@ -4556,6 +5786,12 @@ impl DocumentMethods for Document {
         // This method intentionally does nothing
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-document-all
+    fn All(&self) -> DomRoot<HTMLAllCollection> {
+        self.all
+            .or_init(|| HTMLAllCollection::new(&self.window, self))
+    }
+
     // https://html.spec.whatwg.org/multipage/#globaleventhandlers
     global_event_handlers!();
 
@@ -4586,6 +5822,41 @@ impl DocumentMethods for Document {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-getboxquads
+    fn GetBoxQuads(&self, options: &BoxQuadOptions) -> Fallible<Vec<DomRoot<DOMQuad>>> {
+        geometryutils::get_box_quads(self.upcast(), options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertquadfromnode
+    fn ConvertQuadFromNode(
+        &self,
+        quad: &DOMQuadInit,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMQuad>> {
+        geometryutils::convert_quad_from_node(self.upcast(), quad, &from, options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertrectfromnode
+    fn ConvertRectFromNode(
+        &self,
+        rect: &DOMRectReadOnly,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMQuad>> {
+        geometryutils::convert_rect_from_node(self.upcast(), rect, &from, options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertpointfromnode
+    fn ConvertPointFromNode(
+        &self,
+        point: &DOMPointInit,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMPoint>> {
+        geometryutils::convert_point_from_node(self.upcast(), point, &from, options)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-open
     fn Open(
         &self,
@@ -4631,9 +5902,8 @@ impl DocumentMethods for Document {
             return Ok(DomRoot::from_ref(self));
         }
 
-        // TODO: prompt to unload.
-        // TODO: set unload_event_start and unload_event_end
-
+        // Unlike a navigation, opening the input stream neither prompts to
+        // unload nor unloads the document, whose window stays the same.
         window_from_node(self).set_navigation_start();
 
         // Step 8
@@ -4672,17 +5942,21 @@ impl DocumentMethods for Document {
             if entry_responsible_document != DomRoot::from_ref(self) {
                 new_url.set_fragment(None);
             }
-            // TODO: https://github.com/servo/servo/issues/21939
-            self.set_url(new_url);
+            self.window.History().replace_url(new_url);
         }
 
         // Step 13
-        // TODO: https://github.com/servo/servo/issues/21938
+        // TODO: is initial about:blank, which is not tracked yet.
 
         // Step 14
-        self.set_quirks_mode(QuirksMode::NoQuirks);
+        if self.iframe_load_in_progress.get() {
+            self.mute_iframe_load.set(true);
+        }
 
         // Step 15
+        self.set_quirks_mode(QuirksMode::NoQuirks);
+
+        // Step 16
         let resource_threads = self
             .window
             .upcast::<GlobalScope>()
@@ -4692,13 +5966,13 @@ impl DocumentMethods for Document {
             DocumentLoader::new_with_threads(resource_threads, Some(self.url()));
         ServoParser::parse_html_script_input(self, self.url());
 
-        // Step 16
-        self.ready_state.set(DocumentReadyState::Loading);
-
         // Step 17
-        // Handled when creating the parser in step 15
+        // Handled when creating the parser in step 16
 
         // Step 18
+        self.ready_state.set(DocumentReadyState::Loading);
+
+        // Step 19
         Ok(DomRoot::from_ref(self))
     }
 
@@ -4829,6 +6103,100 @@ impl DocumentMethods for Document {
         self.exit_fullscreen()
     }
 
+    // https://w3c.github.io/pointerlock/#dom-documentorshadowroot-pointerlockelement
+    fn GetPointerLockElement(&self) -> Option<DomRoot<Element>> {
+        self.pointer_lock_element.get()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-exitpointerlock
+    fn ExitPointerLock(&self) {
+        self.exit_pointer_lock()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-document-onpointerlockchange
+    event_handler!(
+        pointerlockchange,
+        GetOnpointerlockchange,
+        SetOnpointerlockchange
+    );
+
+    // https://w3c.github.io/pointerlock/#dom-document-onpointerlockerror
+    event_handler!(
+        pointerlockerror,
+        GetOnpointerlockerror,
+        SetOnpointerlockerror
+    );
+
+    // https://privacycg.github.io/storage-access/#dom-document-hasstorageaccess
+    fn HasStorageAccess(&self) -> Rc<Promise> {
+        let global = self.global();
+        let in_realm_proof = AlreadyInRealm::assert(&global);
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&global, InRealm::Already(&in_realm_proof));
+        // Step 2.
+        if !self.is_fully_active() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+        // Step 3.
+        if !self.origin.is_tuple() {
+            promise.resolve_native(&false);
+            return promise;
+        }
+        // Steps 4-6.
+        promise.resolve_native(&self.has_storage_access());
+        promise
+    }
+
+    // https://privacycg.github.io/storage-access/#dom-document-requeststorageaccess
+    fn RequestStorageAccess(&self) -> Rc<Promise> {
+        let global = self.global();
+        let in_realm_proof = AlreadyInRealm::assert(&global);
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&global, InRealm::Already(&in_realm_proof));
+        // Step 2.
+        if !self.is_fully_active() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+        // Steps 3 and 5-6.
+        if self.has_storage_access() {
+            promise.resolve_native(&());
+            return promise;
+        }
+        // Step 4.
+        if !self.origin.is_tuple() {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        // Step 8.
+        let is_child_of_top_level = self
+            .browsing_context()
+            .and_then(|browsing_context| {
+                browsing_context
+                    .parent()
+                    .map(|parent| parent.parent().is_none())
+            })
+            .unwrap_or(false);
+        if !is_child_of_top_level {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        // TODO: Steps 7 and 9, the sandboxing flags of the frame and the user
+        // activation aren't available here.
+        // Steps 10-11.
+        let site = self.url().host_str().unwrap_or("").to_owned();
+        match prompt_user_from_embedder(PermissionPrompt::StorageAccess(site), &global) {
+            PermissionState::Granted => {
+                self.storage_access_granted.set(true);
+                self.notify_cookies_blocked(false);
+                promise.resolve_native(&());
+            },
+            _ => promise.reject_error(Error::NotAllowed),
+        }
+        promise
+    }
+
     // check-tidy: no specs after this line
     // Servo only API to get an instance of the controls of a specific
     // media element matching the given id.
@@ -4849,11 +6217,9 @@ impl DocumentMethods for Document {
     }
 }
 
-fn update_with_current_time_ms(marker: &Cell<u64>) {
+fn update_with_current_time_ns(marker: &Cell<u64>) {
     if marker.get() == 0 {
-        let time = time::get_time();
-        let current_time_ms = time.sec * 1000 + time.nsec as i64 / 1000000;
-        marker.set(current_time_ms as u64);
+        marker.set(time::precise_time_ns());
     }
 }
 
@@ -4,23 +4,33 @@
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::element::Element;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlmetaelement::HTMLMetaElement;
 use crate::dom::node::{self, Node, VecPreOrderInsertionHelper};
+use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use crate::stylesheet_set::StylesheetSetRef;
 use euclid::default::Point2D;
-use js::jsapi::JS_GetRuntime;
+use js::conversions::{ConversionResult, FromJSValConvertible};
+use js::jsapi::{Heap, JS_GetRuntime};
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 use script_layout_interface::message::{NodesFromPointQueryType, QueryMsg};
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
 use servo_atoms::Atom;
 use std::collections::HashMap;
 use std::fmt;
+use std::mem;
 use style::context::QuirksMode;
 use style::invalidation::media_queries::{MediaListKey, ToMediaListKey};
 use style::media_queries::MediaList;
@@ -32,7 +42,9 @@ use style::stylesheets::{CssRule, Origin, Stylesheet};
 pub struct StyleSheetInDocument {
     #[ignore_malloc_size_of = "Arc"]
     pub sheet: Arc<Stylesheet>,
-    pub owner: Dom<Element>,
+    /// The element that owns the sheet, or `None` for adopted stylesheets,
+    /// which come after all the other sheets.
+    pub owner: Option<Dom<Element>>,
 }
 
 impl fmt::Debug for StyleSheetInDocument {
@@ -80,12 +92,19 @@ impl ::style::stylesheets::StylesheetInDocument for StyleSheetInDocument {
 #[derive(JSTraceable, MallocSizeOf)]
 pub struct DocumentOrShadowRoot {
     window: Dom<Window>,
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    adopted_stylesheets: DomRefCell<Vec<Dom<CSSStyleSheet>>>,
+    /// The frozen array of the adopted stylesheets, until they change.
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_adopted_stylesheets: DomRefCell<Option<Heap<JSVal>>>,
 }
 
 impl DocumentOrShadowRoot {
     pub fn new(window: &Window) -> Self {
         Self {
             window: Dom::from_ref(window),
+            adopted_stylesheets: DomRefCell::new(vec![]),
+            frozen_adopted_stylesheets: DomRefCell::new(None),
         }
     }
 
@@ -171,19 +190,28 @@ impl DocumentOrShadowRoot {
 
         // Step 1 and Step 3
         let nodes = self.nodes_from_point(point, NodesFromPointQueryType::All);
-        let mut elements: Vec<DomRoot<Element>> = nodes
-            .iter()
-            .flat_map(|&untrusted_node_address| {
-                let node = unsafe {
-                    node::from_untrusted_node_address(js_runtime, untrusted_node_address)
-                };
-                DomRoot::downcast::<Element>(node)
-            })
-            .collect();
+        let mut elements: Vec<DomRoot<Element>> = vec![];
+        for &untrusted_node_address in &nodes {
+            let node =
+                unsafe { node::from_untrusted_node_address(js_runtime, untrusted_node_address) };
+            // Text is hit as part of the element it is in, like in
+            // `element_from_point`.
+            let element = match node.downcast::<Element>() {
+                Some(element) => DomRoot::from_ref(element),
+                None => match node.GetParentElement() {
+                    Some(element) => element,
+                    None => continue,
+                },
+            };
+            // An element is hit once per display item, but is only listed once.
+            if !elements.contains(&element) {
+                elements.push(element);
+            }
+        }
 
         // Step 4
         if let Some(root_element) = document_element {
-            if elements.last() != Some(&root_element) {
+            if !elements.contains(&root_element) {
                 elements.push(root_element);
             }
         }
@@ -211,10 +239,79 @@ impl DocumentOrShadowRoot {
         }
     }
 
-    /// Remove a stylesheet owned by `owner` from the list of document sheets.
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    pub fn get_adopted_stylesheets(&self, cx: JSContext) -> JSVal {
+        if let Some(sheets) = &*self.frozen_adopted_stylesheets.borrow() {
+            return sheets.get();
+        }
+
+        let sheets: Vec<DomRoot<CSSStyleSheet>> = self
+            .adopted_stylesheets
+            .borrow()
+            .iter()
+            .map(|sheet| DomRoot::from_ref(&**sheet))
+            .collect();
+        let frozen_sheets = to_frozen_array(sheets.as_slice(), cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_adopted_stylesheets.borrow_mut() = Some(Heap::default());
+        self.frozen_adopted_stylesheets
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_sheets);
+
+        frozen_sheets
+    }
+
+    /// Replaces the stylesheets adopted by `owner`, the document or shadow
+    /// root of `self`, with the sheets in `value`.
+    /// <https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets>
+    #[allow(unrooted_must_root, unsafe_code)]
+    pub fn set_adopted_stylesheets(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        owner: StyleSheetListOwner,
+    ) -> ErrorResult {
+        let sheets = match unsafe { Vec::<DomRoot<CSSStyleSheet>>::from_jsval(*cx, value, ()) } {
+            Ok(ConversionResult::Success(sheets)) => sheets,
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into_owned())),
+            Err(()) => return Err(Error::JSFailed),
+        };
+        let document = self.window.Document();
+        if sheets
+            .iter()
+            .any(|sheet| !sheet.is_constructed_in(&document))
+        {
+            return Err(Error::NotAllowed);
+        }
+
+        let new_sheets = sheets.iter().map(|sheet| Dom::from_ref(&**sheet)).collect();
+        let old_sheets: Vec<DomRoot<CSSStyleSheet>> =
+            mem::replace(&mut *self.adopted_stylesheets.borrow_mut(), new_sheets)
+                .iter()
+                .map(|sheet| DomRoot::from_ref(&**sheet))
+                .collect();
+        *self.frozen_adopted_stylesheets.borrow_mut() = None;
+
+        for sheet in distinct_sheets(&old_sheets) {
+            owner.remove_adopted_stylesheet(sheet);
+            sheet.remove_adopter(&owner);
+        }
+        for sheet in distinct_sheets(&sheets) {
+            owner.add_adopted_stylesheet(sheet);
+            sheet.add_adopter(owner.clone());
+        }
+        owner.invalidate_stylesheets();
+        Ok(())
+    }
+
+    /// Remove a stylesheet owned by `owner`, or adopted if `owner` is `None`,
+    /// from the list of document sheets.
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn remove_stylesheet(
-        owner: &Element,
+        owner: Option<&Element>,
         s: &Arc<Stylesheet>,
         mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
     ) {
@@ -225,17 +322,17 @@ impl DocumentOrShadowRoot {
             None,
             StyleSheetInDocument {
                 sheet: s.clone(),
-                owner: Dom::from_ref(owner),
+                owner: owner.map(Dom::from_ref),
             },
             &guard,
         );
     }
 
-    /// Add a stylesheet owned by `owner` to the list of document sheets, in the
-    /// correct tree position.
+    /// Add a stylesheet owned by `owner`, or adopted if `owner` is `None`, to
+    /// the list of document sheets, in the correct tree position.
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn add_stylesheet(
-        owner: &Element,
+        owner: Option<&Element>,
         mut stylesheets: StylesheetSetRef<StyleSheetInDocument>,
         sheet: Arc<Stylesheet>,
         insertion_point: Option<StyleSheetInDocument>,
@@ -245,13 +342,14 @@ impl DocumentOrShadowRoot {
         // that own stylesheets, but StylesheetOwner is more about loading
         // them...
         debug_assert!(
-            owner.as_stylesheet_owner().is_some() || owner.is::<HTMLMetaElement>(),
+            owner.map_or(true, |owner| owner.as_stylesheet_owner().is_some() ||
+                owner.is::<HTMLMetaElement>()),
             "Wat"
         );
 
         let sheet = StyleSheetInDocument {
             sheet,
-            owner: Dom::from_ref(owner),
+            owner: owner.map(Dom::from_ref),
         };
 
         let guard = style_shared_lock.read();
@@ -310,3 +408,14 @@ impl DocumentOrShadowRoot {
         elements.insert_pre_order(element, &root);
     }
 }
+
+/// The sheets in `sheets` in order, without the ones that appear again later,
+/// which is how they apply when a sheet is adopted more than once.
+fn distinct_sheets(sheets: &[DomRoot<CSSStyleSheet>]) -> Vec<&CSSStyleSheet> {
+    sheets
+        .iter()
+        .enumerate()
+        .filter(|&(index, sheet)| !sheets[index + 1..].contains(sheet))
+        .map(|(_, sheet)| &**sheet)
+        .collect()
+}
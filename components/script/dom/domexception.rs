@@ -39,6 +39,9 @@ pub enum DOMErrorName {
     DataCloneError = DOMExceptionConstants::DATA_CLONE_ERR,
     NotReadableError,
     OperationError,
+    NotAllowedError,
+    EncodingError,
+    DataError,
 }
 
 impl DOMErrorName {
@@ -68,6 +71,8 @@ impl DOMErrorName {
             "DataCloneError" => Some(DOMErrorName::DataCloneError),
             "NotReadableError" => Some(DOMErrorName::NotReadableError),
             "OperationError" => Some(DOMErrorName::OperationError),
+            "EncodingError" => Some(DOMErrorName::EncodingError),
+            "DataError" => Some(DOMErrorName::DataError),
             _ => None,
         }
     }
@@ -115,6 +120,11 @@ impl DOMException {
             DOMErrorName::OperationError => {
                 "The operation failed for an operation-specific reason."
             },
+            DOMErrorName::NotAllowedError => {
+                "The request is not allowed by the user agent or the platform in the current context."
+            },
+            DOMErrorName::EncodingError => "The encoding or decoding operation failed.",
+            DOMErrorName::DataError => "The provided data is inadequate.",
         };
 
         (
@@ -12,6 +12,7 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::dommatrixreadonly::{
     dommatrixinit_to_matrix, entries_to_matrix, transform_to_matrix, DOMMatrixReadOnly,
 };
@@ -434,4 +435,13 @@ impl DOMMatrixMethods for DOMMatrix {
         // Step 3.
         DomRoot::from_ref(&self)
     }
+
+    // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-setmatrixvalue
+    fn SetMatrixValue(&self, transformList: DOMString) -> Fallible<DomRoot<DOMMatrix>> {
+        // Steps 1-3.
+        self.upcast::<DOMMatrixReadOnly>()
+            .set_matrix_value(transformList)?;
+        // Step 4.
+        Ok(DomRoot::from_ref(&self))
+    }
 }
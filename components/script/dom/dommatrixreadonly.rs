@@ -3,7 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::{DomRefCell, Ref};
-use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{DOMMatrixInit, DOMMatrixMethods};
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{
+    DOMMatrix2DInit, DOMMatrixInit, DOMMatrixMethods,
+};
 use crate::dom::bindings::codegen::Bindings::DOMMatrixReadOnlyBinding::{
     DOMMatrixReadOnlyMethods, Wrap,
 };
@@ -14,6 +16,7 @@ use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
 use crate::dom::dommatrix::DOMMatrix;
 use crate::dom::dompoint::DOMPoint;
 use crate::dom::globalscope::GlobalScope;
@@ -21,7 +24,8 @@ use crate::dom::window::Window;
 use crate::script_runtime::JSContext;
 use cssparser::{Parser, ParserInput};
 use dom_struct::dom_struct;
-use euclid::{default::Transform3D, Angle};
+use euclid::default::{Transform2D, Transform3D};
+use euclid::Angle;
 use js::jsapi::JSObject;
 use js::rust::CustomAutoRooterGuard;
 use js::typedarray::CreateWith;
@@ -396,6 +400,18 @@ impl DOMMatrixReadOnly {
         // Step 3 in DOMMatrix.InvertSelf
     }
 
+    // https://drafts.fxtf.org/geometry-1/#dom-dommatrix-setmatrixvalue
+    pub fn set_matrix_value(&self, transformList: DOMString) -> Fallible<()> {
+        // Step 1.
+        let (is2D, matrix) = transform_to_matrix(transformList.to_string())?;
+        // Step 2.
+        self.is2D.set(is2D);
+        // Step 3.
+        *self.matrix.borrow_mut() = matrix;
+        Ok(())
+        // Step 4 in DOMMatrix.SetMatrixValue
+    }
+
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-fromfloat32array
     #[allow(unsafe_code)]
     pub fn FromFloat32Array(
@@ -654,18 +670,8 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
 
     // https://drafts.fxtf.org/geometry-1/#dom-dommatrixreadonly-transformpoint
     fn TransformPoint(&self, point: &DOMPointInit) -> DomRoot<DOMPoint> {
-        // Euclid always normalizes the homogeneous coordinate which is usually the right
-        // thing but may (?) not be compliant with the CSS matrix spec (or at least is
-        // probably not the behavior web authors will expect even if it is mathematically
-        // correct in the context of geometry computations).
-        // Since this is the only place where this is needed, better implement it here
-        // than in euclid (which does not have a notion of 4d points).
-        let mat = self.matrix.borrow();
-        let x = point.x * mat.m11 + point.y * mat.m21 + point.z * mat.m31 + point.w * mat.m41;
-        let y = point.x * mat.m12 + point.y * mat.m22 + point.z * mat.m32 + point.w * mat.m42;
-        let z = point.x * mat.m13 + point.y * mat.m23 + point.z * mat.m33 + point.w * mat.m43;
-        let w = point.x * mat.m14 + point.y * mat.m24 + point.z * mat.m34 + point.w * mat.m44;
-
+        let (x, y, z, w) =
+            transform_point(&self.matrix.borrow(), point.x, point.y, point.z, point.w);
         DOMPoint::new(&self.global(), x, y, z, w)
     }
 
@@ -696,6 +702,88 @@ impl DOMMatrixReadOnlyMethods for DOMMatrixReadOnly {
             NonNull::new_unchecked(array.get())
         }
     }
+
+    // https://drafts.fxtf.org/geometry-1/#dommatrixreadonly-stringification-behavior
+    fn Stringifier(&self) -> Fallible<DOMString> {
+        let matrix = self.matrix.borrow();
+        let entries = matrix.to_row_major_array();
+        // Step 1.
+        if !entries.iter().all(|entry| entry.is_finite()) {
+            return Err(error::Error::InvalidState);
+        }
+        // Steps 2-4.
+        let (function, entries) = if self.is2D.get() {
+            (
+                "matrix",
+                vec![
+                    matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m41, matrix.m42,
+                ],
+            )
+        } else {
+            ("matrix3d", entries.to_vec())
+        };
+        let entries: Vec<String> = entries.into_iter().map(number_to_string).collect();
+        // Step 5.
+        Ok(DOMString::from(format!(
+            "{}({})",
+            function,
+            entries.join(", ")
+        )))
+    }
+}
+
+/// Transforms a point with a matrix, without normalizing its homogeneous
+/// coordinate like euclid does. Euclid has no notion of 4D points, and this
+/// normalization is not the behavior web authors expect.
+/// <https://drafts.fxtf.org/geometry-1/#transform-a-point-with-a-matrix>
+pub fn transform_point(
+    mat: &Transform3D<f64>,
+    x: f64,
+    y: f64,
+    z: f64,
+    w: f64,
+) -> (f64, f64, f64, f64) {
+    (
+        x * mat.m11 + y * mat.m21 + z * mat.m31 + w * mat.m41,
+        x * mat.m12 + y * mat.m22 + z * mat.m32 + w * mat.m42,
+        x * mat.m13 + y * mat.m23 + z * mat.m33 + w * mat.m43,
+        x * mat.m14 + y * mat.m24 + z * mat.m34 + w * mat.m44,
+    )
+}
+
+/// Serializes a finite number the way ECMAScript's ToString does, which is
+/// how the entries of a matrix are serialized.
+/// <https://tc39.es/ecma262/#sec-numeric-types-number-tostring>
+fn number_to_string(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_owned();
+    }
+    if value < 0.0 {
+        return format!("-{}", number_to_string(-value));
+    }
+    // The shortest digits that round-trip, and the exponent n such that the
+    // value is 0.digits × 10^n.
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent[1..].parse::<i32>().unwrap() + 1;
+    if k <= n && n <= 21 {
+        digits + &"0".repeat((n - k) as usize)
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n > 0 { '+' } else { '-' };
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", rest)
+        };
+        format!("{}{}e{}{}", first, fraction, sign, (n - 1).abs())
+    }
 }
 
 // https://drafts.fxtf.org/geometry-1/#create-a-2d-matrix
@@ -740,68 +828,79 @@ pub fn entries_to_matrix(entries: &[f64]) -> Fallible<(bool, Transform3D<f64>)>
     }
 }
 
+fn same_value_zero(x: f64, y: f64) -> bool {
+    x == y || x.is_nan() && y.is_nan()
+}
+
+// https://drafts.fxtf.org/geometry-1/#validate-and-fixup-2d
+fn validate_and_fixup_2d(dict: &DOMMatrix2DInit) -> Fallible<(f64, f64, f64, f64, f64, f64)> {
+    // Step 1.
+    let conflicts = |alias: Option<f64>, member: Option<f64>| match (alias, member) {
+        (Some(alias), Some(member)) => !same_value_zero(alias, member),
+        _ => false,
+    };
+    if conflicts(dict.a, dict.m11) ||
+        conflicts(dict.b, dict.m12) ||
+        conflicts(dict.c, dict.m21) ||
+        conflicts(dict.d, dict.m22) ||
+        conflicts(dict.e, dict.m41) ||
+        conflicts(dict.f, dict.m42)
+    {
+        return Err(error::Error::Type("Invalid matrix initializer.".to_owned()));
+    }
+    // Step 2.
+    let m11 = dict.m11.unwrap_or(dict.a.unwrap_or(1.0));
+    // Step 3.
+    let m12 = dict.m12.unwrap_or(dict.b.unwrap_or(0.0));
+    // Step 4.
+    let m21 = dict.m21.unwrap_or(dict.c.unwrap_or(0.0));
+    // Step 5.
+    let m22 = dict.m22.unwrap_or(dict.d.unwrap_or(1.0));
+    // Step 6.
+    let m41 = dict.m41.unwrap_or(dict.e.unwrap_or(0.0));
+    // Step 7.
+    let m42 = dict.m42.unwrap_or(dict.f.unwrap_or(0.0));
+    Ok((m11, m12, m21, m22, m41, m42))
+}
+
 // https://drafts.fxtf.org/geometry-1/#validate-and-fixup
 pub fn dommatrixinit_to_matrix(dict: &DOMMatrixInit) -> Fallible<(bool, Transform3D<f64>)> {
     // Step 1.
-    if dict.a.is_some() && dict.m11.is_some() && dict.a.unwrap() != dict.m11.unwrap() ||
-        dict.b.is_some() && dict.m12.is_some() && dict.b.unwrap() != dict.m12.unwrap() ||
-        dict.c.is_some() && dict.m21.is_some() && dict.c.unwrap() != dict.m21.unwrap() ||
-        dict.d.is_some() && dict.m22.is_some() && dict.d.unwrap() != dict.m22.unwrap() ||
-        dict.e.is_some() && dict.m41.is_some() && dict.e.unwrap() != dict.m41.unwrap() ||
-        dict.f.is_some() && dict.m42.is_some() && dict.f.unwrap() != dict.m42.unwrap() ||
-        dict.is2D.is_some() &&
-            dict.is2D.unwrap() &&
-            (dict.m31 != 0.0 ||
-                dict.m32 != 0.0 ||
-                dict.m13 != 0.0 ||
-                dict.m23 != 0.0 ||
-                dict.m43 != 0.0 ||
-                dict.m14 != 0.0 ||
-                dict.m24 != 0.0 ||
-                dict.m34 != 0.0 ||
-                dict.m33 != 1.0 ||
-                dict.m44 != 1.0)
-    {
-        Err(error::Error::Type("Invalid matrix initializer.".to_owned()))
+    let (m11, m12, m21, m22, m41, m42) = validate_and_fixup_2d(&dict.parent)?;
+    let is_3d = dict.m31 != 0.0 ||
+        dict.m32 != 0.0 ||
+        dict.m13 != 0.0 ||
+        dict.m23 != 0.0 ||
+        dict.m43 != 0.0 ||
+        dict.m14 != 0.0 ||
+        dict.m24 != 0.0 ||
+        dict.m34 != 0.0 ||
+        dict.m33 != 1.0 ||
+        dict.m44 != 1.0;
+    // Step 2.
+    if dict.is2D == Some(true) && is_3d {
+        return Err(error::Error::Type("Invalid matrix initializer.".to_owned()));
+    }
+    // Steps 3-4.
+    let is_2d = dict.is2D.unwrap_or(!is_3d);
+    // https://drafts.fxtf.org/geometry-1/#create-a-dommatrix-from-the-dictionary
+    let matrix = if is_2d {
+        create_2d_matrix(&[m11, m12, m21, m22, m41, m42])
     } else {
-        let mut is_2d = dict.is2D;
-        // Step 2.
-        let m11 = dict.m11.unwrap_or(dict.a.unwrap_or(1.0));
-        // Step 3.
-        let m12 = dict.m12.unwrap_or(dict.b.unwrap_or(0.0));
-        // Step 4.
-        let m21 = dict.m21.unwrap_or(dict.c.unwrap_or(0.0));
-        // Step 5.
-        let m22 = dict.m22.unwrap_or(dict.d.unwrap_or(1.0));
-        // Step 6.
-        let m41 = dict.m41.unwrap_or(dict.e.unwrap_or(0.0));
-        // Step 7.
-        let m42 = dict.m42.unwrap_or(dict.f.unwrap_or(0.0));
-        // Step 8.
-        if is_2d.is_none() &&
-            (dict.m31 != 0.0 ||
-                dict.m32 != 0.0 ||
-                dict.m13 != 0.0 ||
-                dict.m23 != 0.0 ||
-                dict.m43 != 0.0 ||
-                dict.m14 != 0.0 ||
-                dict.m24 != 0.0 ||
-                dict.m34 != 0.0 ||
-                dict.m33 != 1.0 ||
-                dict.m44 != 1.0)
-        {
-            is_2d = Some(false);
-        }
-        // Step 9.
-        if is_2d.is_none() {
-            is_2d = Some(true);
-        }
-        let matrix = Transform3D::row_major(
+        create_3d_matrix(&[
             m11, m12, dict.m13, dict.m14, m21, m22, dict.m23, dict.m24, dict.m31, dict.m32,
             dict.m33, dict.m34, m41, m42, dict.m43, dict.m44,
-        );
-        Ok((is_2d.unwrap(), matrix))
-    }
+        ])
+    };
+    Ok((is_2d, matrix))
+}
+
+// https://drafts.fxtf.org/geometry-1/#create-a-dommatrix-from-the-2d-dictionary
+pub fn dommatrix2dinit_to_matrix(dict: &DOMMatrix2DInit) -> Fallible<Transform2D<f64>> {
+    // Step 1.
+    let (m11, m12, m21, m22, m41, m42) = validate_and_fixup_2d(dict)?;
+    // Step 2.
+    Ok(Transform2D::row_major(m11, m12, m21, m22, m41, m42))
 }
 
 #[inline]
@@ -814,9 +913,15 @@ fn normalize_point(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
     }
 }
 
+// https://drafts.fxtf.org/geometry-1/#parse-a-string-into-an-abstract-matrix
 pub fn transform_to_matrix(value: String) -> Fallible<(bool, Transform3D<f64>)> {
     use style::properties::longhands::transform;
 
+    // Step 1.
+    if value.is_empty() {
+        return Ok((true, Transform3D::identity()));
+    }
+
     let mut input = ParserInput::new(&value);
     let mut parser = Parser::new(&mut input);
     let url = ::servo_url::ServoUrl::parse("about:blank").unwrap();
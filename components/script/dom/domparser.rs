@@ -7,20 +7,32 @@ use crate::dom::bindings::codegen::Bindings::DOMParserBinding;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::DOMParserMethods;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Application_xhtml_xml;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Application_xml;
+use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Image_svg_xml;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Text_html;
 use crate::dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Text_xml;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentReadyState;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::DocumentSource;
 use crate::dom::document::{Document, HasBrowsingContext, IsHTMLDocument};
-use crate::dom::servoparser::ServoParser;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::Node;
+use crate::dom::servoparser::{ServoParser, XmlParseError};
+use crate::dom::text::Text;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use html5ever::{LocalName, Namespace, QualName};
 use script_traits::DocumentActivity;
+use servo_url::ServoUrl;
+
+/// The namespace of the root element of documents that failed to parse.
+const PARSERERROR_NAMESPACE: &str = "http://www.mozilla.org/newlayout/xml/parsererror.xml";
 
 #[dom_struct]
 pub struct DOMParser {
@@ -85,7 +97,7 @@ impl DOMParserMethods for DOMParser {
                 document.set_ready_state(DocumentReadyState::Complete);
                 Ok(document)
             },
-            Text_xml | Application_xml | Application_xhtml_xml => {
+            Text_xml | Application_xml | Application_xhtml_xml | Image_svg_xml => {
                 let document = Document::new(
                     &self.window,
                     HasBrowsingContext::No,
@@ -101,10 +113,54 @@ impl DOMParserMethods for DOMParser {
                     None,
                     Default::default(),
                 );
-                ServoParser::parse_xml_document(&document, s, url);
+                let result = ServoParser::parse_xml_document_from_string(&document, s, url.clone())
+                    .and_then(|_| match document.GetDocumentElement() {
+                        Some(_) => Ok(()),
+                        None => Err(XmlParseError {
+                            line: 1,
+                            message: String::from("no root element found"),
+                        }),
+                    });
+                if let Err(error) = result {
+                    replace_with_parser_error(&document, &error, &url);
+                }
                 document.set_ready_state(DocumentReadyState::Complete);
                 Ok(document)
             },
         }
     }
 }
+
+/// Replaces the contents of `document` with a `parsererror` element
+/// describing `error`, as DOMParser does for documents that are not
+/// well-formed.
+/// https://w3c.github.io/DOM-Parsing/#dom-domparser-parsefromstring
+fn replace_with_parser_error(document: &Document, error: &XmlParseError, url: &ServoUrl) {
+    // The parser may have built part of the tree before giving up.
+    Node::replace_all(None, document.upcast());
+
+    let name = QualName::new(
+        None,
+        Namespace::from(PARSERERROR_NAMESPACE),
+        LocalName::from("parsererror"),
+    );
+    let root = Element::create(
+        name,
+        None,
+        document,
+        ElementCreator::ScriptCreated,
+        CustomElementCreationMode::Synchronous,
+    );
+    let description = format!(
+        "XML Parsing Error: {}\nLocation: {}\nLine Number {}:",
+        error.message, url, error.line
+    );
+    let text = Text::new(DOMString::from(description), document);
+    root.upcast::<Node>()
+        .AppendChild(text.upcast())
+        .expect("Appending failed");
+    document
+        .upcast::<Node>()
+        .AppendChild(root.upcast())
+        .expect("Appending failed");
+}
@@ -2,13 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrixInit;
 use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
 use crate::dom::bindings::codegen::Bindings::DOMPointReadOnlyBinding::{
     DOMPointReadOnlyMethods, Wrap,
 };
 use crate::dom::bindings::error::Fallible;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::dommatrixreadonly::{dommatrixinit_to_matrix, transform_point};
+use crate::dom::dompoint::DOMPoint;
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
 use std::cell::Cell;
@@ -80,6 +83,15 @@ impl DOMPointReadOnlyMethods for DOMPointReadOnly {
     fn W(&self) -> f64 {
         self.w.get()
     }
+
+    // https://drafts.fxtf.org/geometry/#dom-dompointreadonly-matrixtransform
+    fn MatrixTransform(&self, matrix: &DOMMatrixInit) -> Fallible<DomRoot<DOMPoint>> {
+        // Step 1.
+        let (_, matrix) = dommatrixinit_to_matrix(matrix)?;
+        // Step 2.
+        let (x, y, z, w) = transform_point(&matrix, self.X(), self.Y(), self.Z(), self.W());
+        Ok(DOMPoint::new(&self.global(), x, y, z, w))
+    }
 }
 
 #[allow(non_snake_case)]
@@ -4,7 +4,9 @@
 
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding;
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
-use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::DOMRectReadOnlyMethods;
+use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::{
+    DOMRectInit, DOMRectReadOnlyMethods,
+};
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
@@ -42,6 +44,12 @@ impl DOMRect {
     ) -> Fallible<DomRoot<DOMRect>> {
         Ok(DOMRect::new(global, x, y, width, height))
     }
+
+    // https://drafts.fxtf.org/geometry/#dom-domrect-fromrect
+    #[allow(non_snake_case)]
+    pub fn FromRect(global: &GlobalScope, other: &DOMRectInit) -> DomRoot<DOMRect> {
+        DOMRect::new(global, other.x, other.y, other.width, other.height)
+    }
 }
 
 impl DOMRectMethods for DOMRect {
@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::{
-    DOMRectReadOnlyMethods, Wrap,
+    DOMRectInit, DOMRectReadOnlyMethods, Wrap,
 };
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
@@ -57,6 +57,12 @@ impl DOMRectReadOnly {
         Ok(DOMRectReadOnly::new(global, x, y, width, height))
     }
 
+    // https://drafts.fxtf.org/geometry/#dom-domrectreadonly-fromrect
+    #[allow(non_snake_case)]
+    pub fn FromRect(global: &GlobalScope, other: &DOMRectInit) -> DomRoot<DOMRectReadOnly> {
+        DOMRectReadOnly::new(global, other.x, other.y, other.width, other.height)
+    }
+
     pub fn set_x(&self, value: f64) {
         self.x.set(value);
     }
@@ -0,0 +1,148 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::DataTransferBinding::DataTransferMethods;
+use crate::dom::bindings::codegen::Bindings::DragEventBinding::{self, DragEventMethods};
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mouseevent::MouseEvent;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+
+/// <https://html.spec.whatwg.org/multipage/#dragevent>
+#[dom_struct]
+pub struct DragEvent {
+    mouseevent: MouseEvent,
+    data_transfer: MutNullableDom<DataTransfer>,
+}
+
+impl DragEvent {
+    fn new_inherited() -> DragEvent {
+        DragEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            data_transfer: Default::default(),
+        }
+    }
+
+    pub fn new_uninitialized(window: &Window) -> DomRoot<DragEvent> {
+        reflect_dom_object(
+            Box::new(DragEvent::new_inherited()),
+            window,
+            DragEventBinding::Wrap,
+        )
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        buttons: u16,
+        related_target: Option<&EventTarget>,
+        data_transfer: Option<&DataTransfer>,
+    ) -> DomRoot<DragEvent> {
+        let ev = DragEvent::new_uninitialized(window);
+        let mouse_event = ev.upcast::<MouseEvent>();
+        mouse_event.InitMouseEvent(
+            type_,
+            bool::from(can_bubble),
+            bool::from(cancelable),
+            view,
+            detail,
+            screen_x,
+            screen_y,
+            client_x,
+            client_y,
+            ctrl_key,
+            alt_key,
+            shift_key,
+            meta_key,
+            button,
+            related_target,
+        );
+        mouse_event.set_buttons(buttons);
+        ev.data_transfer.set(data_transfer);
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &DragEventBinding::DragEventInit,
+    ) -> Fallible<DomRoot<DragEvent>> {
+        let event = DragEvent::new(
+            window,
+            type_,
+            EventBubbles::from(init.parent.parent.parent.parent.bubbles),
+            EventCancelable::from(init.parent.parent.parent.parent.cancelable),
+            init.parent.parent.parent.view.as_deref(),
+            init.parent.parent.parent.detail,
+            init.parent.screenX,
+            init.parent.screenY,
+            init.parent.clientX,
+            init.parent.clientY,
+            init.parent.parent.ctrlKey,
+            init.parent.parent.altKey,
+            init.parent.parent.shiftKey,
+            init.parent.parent.metaKey,
+            init.parent.button,
+            init.parent.buttons,
+            init.parent.relatedTarget.as_deref(),
+            init.dataTransfer.as_deref(),
+        );
+        event
+            .upcast::<MouseEvent>()
+            .set_movement(init.parent.movementX, init.parent.movementY);
+        Ok(event)
+    }
+
+    /// The text that a trusted `drop` event drops, if any.
+    pub fn dropped_text(&self) -> Option<DOMString> {
+        let event = self.upcast::<Event>();
+        if !event.IsTrusted() || event.type_() != atom!("drop") {
+            return None;
+        }
+        let text = self
+            .data_transfer
+            .get()?
+            .GetData(DOMString::from("text/plain"));
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+}
+
+impl DragEventMethods for DragEvent {
+    // https://html.spec.whatwg.org/multipage/#dom-dragevent-datatransfer
+    fn GetDataTransfer(&self) -> Option<DomRoot<DataTransfer>> {
+        self.data_transfer.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
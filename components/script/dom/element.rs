@@ -8,16 +8,27 @@ use crate::dom::activation::Activatable;
 use crate::dom::attr::{Attr, AttrHelpersForLayout};
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref, RefMut};
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
+use crate::dom::bindings::codegen::Bindings::DOMQuadBinding::DOMQuadInit;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding;
-use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::{
+    CheckVisibilityOptions, ElementMethods, GetHTMLOptions, ScrollLogicalPosition,
+};
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
+use crate::dom::bindings::codegen::Bindings::GeometryUtilsBinding::{
+    BoxQuadOptions, ConvertCoordinateOptions,
+};
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::SanitizerBinding::SetHTMLOptions;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
+use crate::dom::bindings::codegen::UnionTypes::BooleanOrScrollIntoViewOptions;
 use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::TextOrElementOrDocument as GeometryNode;
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -36,9 +47,13 @@ use crate::dom::customelementregistry::{
 };
 use crate::dom::document::{determine_policy_for_token, Document, LayoutDocumentHelpers};
 use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::domquad::DOMQuad;
 use crate::dom::domrect::DOMRect;
+use crate::dom::domrectreadonly::DOMRectReadOnly;
 use crate::dom::domtokenlist::DOMTokenList;
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::geometryutils;
 use crate::dom::htmlanchorelement::HTMLAnchorElement;
 use crate::dom::htmlbodyelement::{HTMLBodyElement, HTMLBodyElementLayoutHelpers};
 use crate::dom::htmlbuttonelement::HTMLButtonElement;
@@ -75,17 +90,22 @@ use crate::dom::node::{ChildrenMutation, LayoutNodeHelpers, Node, ShadowIncludin
 use crate::dom::nodelist::NodeList;
 use crate::dom::promise::Promise;
 use crate::dom::raredata::ElementRareData;
-use crate::dom::servoparser::ServoParser;
+use crate::dom::sanitizer::SanitizerConfiguration;
+use crate::dom::servoparser::{serialize_html_fragment, ServoParser};
 use crate::dom::shadowroot::{IsUserAgentWidget, ShadowRoot};
+use crate::dom::stylepropertymapreadonly::StylePropertyMapReadOnly;
 use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::ReflowReason;
+use crate::dom::xmlserializer::serialize_xml;
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
 use crate::task::TaskOnce;
+use app_units::Au;
 use devtools_traits::AttrInfo;
 use dom_struct::dom_struct;
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
 use html5ever::serialize;
 use html5ever::serialize::SerializeOpts;
 use html5ever::serialize::TraversalScope;
@@ -94,15 +114,18 @@ use html5ever::{LocalName, Namespace, Prefix, QualName};
 use js::jsapi::Heap;
 use js::jsval::JSVal;
 use msg::constellation_msg::InputMethodType;
-use net_traits::request::CorsSettings;
+use net_traits::request::{CorsSettings, RequestPriority};
 use net_traits::ReferrerPolicy;
 use script_layout_interface::message::ReflowGoal;
+use script_layout_interface::scrollbar::{ScrollContainer, ScrollbarAxis, ScrollbarGeometry};
+use script_layout_interface::scrollbar::{ScrollbarMode, ScrollbarTheme};
 use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
 use selectors::matching::{ElementSelectorFlags, MatchingContext};
 use selectors::sink::Push;
 use selectors::Element as SelectorsElement;
 use servo_arc::Arc;
 use servo_atoms::Atom;
+use servo_config::pref;
 use std::borrow::Cow;
 use std::cell::Cell;
 use std::default::Default;
@@ -112,6 +135,7 @@ use std::rc::Rc;
 use std::str::FromStr;
 use style::applicable_declarations::ApplicableDeclarationBlock;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
+use style::computed_values::visibility::T as Visibility;
 use style::context::QuirksMode;
 use style::dom_apis;
 use style::element_state::ElementState;
@@ -119,9 +143,10 @@ use style::invalidation::element::restyle_hints::RestyleHint;
 use style::properties::longhands::{
     self, background_image, border_spacing, font_family, font_size,
 };
-use style::properties::longhands::{overflow_x, overflow_y};
+use style::properties::longhands::{box_sizing, overflow_x, overflow_y};
 use style::properties::{parse_style_attribute, PropertyDeclarationBlock};
 use style::properties::{ComputedValues, Importance, PropertyDeclaration};
+use style::properties::{LonghandId, PropertyId};
 use style::rule_tree::CascadeLevel;
 use style::selector_parser::extended_filtering;
 use style::selector_parser::{
@@ -132,8 +157,6 @@ use style::thread_state;
 use style::values::generics::NonNegative;
 use style::values::{computed, specified, CSSFloat};
 use style::CaseSensitivityExt;
-use xml5ever::serialize as xmlSerialize;
-use xml5ever::serialize::SerializeOpts as XmlSerializeOpts;
 use xml5ever::serialize::TraversalScope as XmlTraversalScope;
 use xml5ever::serialize::TraversalScope::ChildrenOnly as XmlChildrenOnly;
 use xml5ever::serialize::TraversalScope::IncludeNode as XmlIncludeNode;
@@ -405,6 +428,133 @@ impl Element {
             .map_or(false, |s| !s.get_box().clone_display().is_none())
     }
 
+    /// The `resize` value of the element if the user can resize it, which
+    /// requires its overflow not to be visible.
+    /// <https://drafts.csswg.org/css-ui/#resize>
+    pub fn resize(&self) -> Option<computed::Resize> {
+        if !pref!(layout.scrollbars.enabled) || !self.is::<HTMLElement>() {
+            return None;
+        }
+        let style = self.style()?;
+        let box_ = style.get_box();
+        if box_.clone_overflow_x() == overflow_x::computed_value::T::Visible &&
+            box_.clone_overflow_y() == overflow_y::computed_value::T::Visible
+        {
+            return None;
+        }
+        match box_.clone_resize() {
+            computed::Resize::None => None,
+            resize => Some(resize),
+        }
+    }
+
+    /// Whether `client_point` is over the resizer that layout draws in the
+    /// bottom-right corner of the padding box.
+    pub fn resizer_contains(&self, client_point: Point2D<f32>) -> bool {
+        let point = Point2D::new(
+            Au::from_f32_px(client_point.x),
+            Au::from_f32_px(client_point.y),
+        );
+        self.scrollbar_geometry()
+            .and_then(|geometry| geometry.corner)
+            .map_or(false, |corner| corner.contains(point))
+    }
+
+    /// The scrollbars and resizer that layout paints over the element, in
+    /// client coordinates, if it is a scroll container.
+    pub fn scrollbar_geometry(&self) -> Option<ScrollbarGeometry> {
+        if !pref!(layout.scrollbars.enabled) {
+            return None;
+        }
+        let style = self.style()?;
+        let box_ = style.get_box();
+        let overflow_x = box_.clone_overflow_x();
+        let overflow_y = box_.clone_overflow_y();
+        if overflow_x == overflow_x::computed_value::T::Visible &&
+            overflow_y == overflow_y::computed_value::T::Visible
+        {
+            return None;
+        }
+
+        let theme = ScrollbarTheme::new(ScrollbarMode::from_prefs());
+        let node = self.upcast::<Node>();
+        let scrollbar_style =
+            window_from_node(self).scrollbar_style_query(node.to_trusted_node_address())?;
+        let border_box = node.bounding_content_box_or_zero();
+        let client_rect = node.client_rect();
+        let padding_box = Rect::new(
+            border_box.origin +
+                Vector2D::new(
+                    Au::from_px(client_rect.origin.x),
+                    Au::from_px(client_rect.origin.y),
+                ),
+            Size2D::new(
+                Au::from_px(client_rect.size.width),
+                Au::from_px(client_rect.size.height),
+            ),
+        );
+        let scroll_area = node.scroll_area();
+        Some(ScrollbarGeometry::new(
+            &theme,
+            &ScrollContainer {
+                padding_box,
+                max_scroll: Size2D::new(
+                    Au::from_px((scroll_area.size.width - client_rect.size.width).max(0)),
+                    Au::from_px((scroll_area.size.height - client_rect.size.height).max(0)),
+                ),
+                scroll_position: Point2D::new(
+                    Au::from_f64_px(self.ScrollLeft()),
+                    Au::from_f64_px(self.ScrollTop()),
+                ),
+                overflow_x,
+                overflow_y,
+                thickness: theme.thickness(scrollbar_style.width),
+                resizable: box_.clone_resize() != computed::Resize::None,
+            },
+        ))
+    }
+
+    /// How far the element is scrolled along `axis`.
+    pub fn scroll_position(&self, axis: ScrollbarAxis) -> f64 {
+        match axis {
+            ScrollbarAxis::Horizontal => self.ScrollLeft(),
+            ScrollbarAxis::Vertical => self.ScrollTop(),
+        }
+    }
+
+    /// Scrolls the element to `position` along `axis`.
+    pub fn set_scroll_position(&self, axis: ScrollbarAxis, position: f64) {
+        match axis {
+            ScrollbarAxis::Horizontal => self.SetScrollLeft(position),
+            ScrollbarAxis::Vertical => self.SetScrollTop(position),
+        }
+    }
+
+    /// The size that the `width` and `height` properties currently resolve
+    /// to, taking `box-sizing` into account.
+    pub fn used_size(&self) -> Size2D<f32> {
+        let border_box_sizing = self.style().map_or(false, |style| {
+            style.get_position().clone_box_sizing() == box_sizing::computed_value::T::BorderBox
+        });
+        if border_box_sizing {
+            let size = self.upcast::<Node>().bounding_content_box_or_zero().size;
+            return Size2D::new(size.width.to_f32_px(), size.height.to_f32_px());
+        }
+        let window = window_from_node(self);
+        let resolved = |longhand| {
+            window
+                .resolved_style_query(
+                    self.upcast::<Node>().to_trusted_node_address(),
+                    None,
+                    PropertyId::Longhand(longhand),
+                )
+                .trim_end_matches("px")
+                .parse()
+                .unwrap_or(0.)
+        };
+        Size2D::new(resolved(LonghandId::Width), resolved(LonghandId::Height))
+    }
+
     // https://drafts.csswg.org/cssom-view/#potentially-scrollable
     fn potentially_scrollable(&self) -> bool {
         self.has_css_layout_box() && !self.has_any_visible_overflow()
@@ -446,7 +596,7 @@ impl Element {
         })
     }
 
-    fn shadow_root(&self) -> Option<DomRoot<ShadowRoot>> {
+    pub fn shadow_root(&self) -> Option<DomRoot<ShadowRoot>> {
         self.rare_data()
             .as_ref()?
             .shadow_root
@@ -461,7 +611,12 @@ impl Element {
     /// https://dom.spec.whatwg.org/#dom-element-attachshadow
     /// XXX This is not exposed to web content yet. It is meant to be used
     ///     for UA widgets only.
-    pub fn attach_shadow(&self, is_ua_widget: IsUserAgentWidget) -> Fallible<DomRoot<ShadowRoot>> {
+    pub fn attach_shadow(
+        &self,
+        is_ua_widget: IsUserAgentWidget,
+        mode: ShadowRootMode,
+        serializable: bool,
+    ) -> Fallible<DomRoot<ShadowRoot>> {
         // Step 1.
         if self.namespace != ns!(html) {
             return Err(Error::NotSupported);
@@ -498,7 +653,7 @@ impl Element {
         }
 
         // Steps 4, 5 and 6.
-        let shadow_root = ShadowRoot::new(self, &*self.node.owner_doc());
+        let shadow_root = ShadowRoot::new(self, &*self.node.owner_doc(), mode, serializable);
         self.ensure_rare_data().shadow_root = Some(Dom::from_ref(&*shadow_root));
         shadow_root
             .upcast::<Node>()
@@ -541,6 +696,30 @@ impl Element {
         true // whatwg/html#5239
     }
 
+    /// The state of the `contenteditable` attribute of an HTML element: `Some(true)` in the true
+    /// and plaintext-only states, `Some(false)` in the false state and `None` in the inherit
+    /// state.
+    /// <https://html.spec.whatwg.org/multipage/#attr-contenteditable>
+    pub fn content_editable_state(&self) -> Option<bool> {
+        if !self.is::<HTMLElement>() {
+            return None;
+        }
+        let attr = self.get_attribute(&ns!(), &local_name!("contenteditable"))?;
+        let value = attr.value().to_ascii_lowercase();
+        match &*value {
+            "" | "true" | "plaintext-only" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#editing-host
+    pub fn is_editing_host(&self) -> bool {
+        self.upcast::<Node>()
+            .editing_host()
+            .map_or(false, |host| &*host == self)
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-directionality
     pub fn directionality(&self) -> String {
         self.downcast::<HTMLElement>()
@@ -706,6 +885,9 @@ impl LayoutElementHelpers for LayoutDom<Element> {
             this.get_background_color()
         } else if let Some(this) = self.downcast::<HTMLTableSectionElement>() {
             this.get_background_color()
+        } else if let Some(this) = self.downcast::<HTMLInputElement>() {
+            // The swatch of color inputs shows their value.
+            this.color_for_layout()
         } else {
             None
         };
@@ -1248,18 +1430,7 @@ impl Element {
 
     #[allow(non_snake_case)]
     pub fn xmlSerialize(&self, traversal_scope: XmlTraversalScope) -> Fallible<DOMString> {
-        let mut writer = vec![];
-        match xmlSerialize::serialize(
-            &mut writer,
-            &self.upcast::<Node>(),
-            XmlSerializeOpts {
-                traversal_scope: traversal_scope,
-                ..Default::default()
-            },
-        ) {
-            Ok(()) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
-            Err(_) => panic!("Cannot serialize element"),
-        }
+        serialize_xml(self.upcast::<Node>(), traversal_scope, true)
     }
 
     pub fn root_element(&self) -> DomRoot<Element> {
@@ -1325,7 +1496,14 @@ impl Element {
         }
         // TODO: Check whether the element is being rendered (i.e. not hidden).
         let node = self.upcast::<Node>();
-        if node.get_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE) {
+        if node.get_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE) || self.is_editing_host() {
+            return true;
+        }
+        // https://html.spec.whatwg.org/multipage/#the-summary-element
+        if self
+            .downcast::<HTMLElement>()
+            .map_or(false, |element| element.is_summary_for_its_parent_details())
+        {
             return true;
         }
         // https://html.spec.whatwg.org/multipage/#specially-focusable
@@ -1540,7 +1718,7 @@ impl Element {
         if *namespace == ns!() {
             vtable_for(self.upcast()).parse_plain_attribute(local_name, value)
         } else {
-            AttrValue::String(value.into())
+            AttrValue::from_string_or_atom(value.into())
         }
     }
 
@@ -1643,7 +1821,10 @@ impl Element {
 
     pub fn set_url_attribute(&self, local_name: &LocalName, value: USVString) {
         assert!(*local_name == local_name.to_ascii_lowercase());
-        self.set_attribute(local_name, AttrValue::String(value.to_string()));
+        self.set_attribute(
+            local_name,
+            AttrValue::from_string_or_atom(value.to_string()),
+        );
     }
 
     pub fn get_string_attribute(&self, local_name: &LocalName) -> DOMString {
@@ -1655,7 +1836,7 @@ impl Element {
 
     pub fn set_string_attribute(&self, local_name: &LocalName, value: DOMString) {
         assert!(*local_name == local_name.to_ascii_lowercase());
-        self.set_attribute(local_name, AttrValue::String(value.into()));
+        self.set_attribute(local_name, AttrValue::from_string_or_atom(value.into()));
     }
 
     pub fn get_tokenlist_attribute(&self, local_name: &LocalName) -> Vec<Atom> {
@@ -1802,6 +1983,86 @@ impl Element {
         win.scroll_node(node, x, y, behavior);
     }
 
+    /// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+    // TODO: Take the writing mode into account, the block axis is assumed to
+    // be vertical and the inline one horizontal.
+    fn scroll_into_view(
+        &self,
+        behavior: ScrollBehavior,
+        block: ScrollLogicalPosition,
+        inline: ScrollLogicalPosition,
+    ) {
+        let node = self.upcast::<Node>();
+        let win = window_from_node(self);
+
+        // Layout positions boxes as if nothing was scrolled, so the element
+        // moves by the scroll positions of the scrolling boxes it is scrolled
+        // into view in before the next one.
+        let rect = node.bounding_content_box_or_zero();
+        let (x, y) = (rect.origin.x.to_f64_px(), rect.origin.y.to_f64_px());
+        let (width, height) = (rect.size.width.to_f64_px(), rect.size.height.to_f64_px());
+        let mut offset = Vector2D::<f64>::zero();
+
+        // Step 1
+        for ancestor in node.ancestors().filter_map(DomRoot::downcast::<Element>) {
+            // The root element scrolls with the viewport.
+            if *ancestor.root_element() == *ancestor {
+                break;
+            }
+            if !ancestor.has_css_layout_box() ||
+                !ancestor.has_scrolling_box() ||
+                !ancestor.has_overflow()
+            {
+                continue;
+            }
+            let border_box = ancestor.upcast::<Node>().bounding_content_box_or_zero();
+            let client_rect = win.client_rect_query(ancestor.upcast());
+            let scrollport_x = border_box.origin.x.to_f64_px() + client_rect.origin.x as f64;
+            let scrollport_y = border_box.origin.y.to_f64_px() + client_rect.origin.y as f64;
+            let scrollport_width = client_rect.size.width as f64;
+            let scrollport_height = client_rect.size.height as f64;
+            let max_x = (ancestor.ScrollWidth() as f64 - scrollport_width).max(0.);
+            let max_y = (ancestor.ScrollHeight() as f64 - scrollport_height).max(0.);
+            let new_x = scroll_into_view_position(
+                inline,
+                x - offset.x - scrollport_x,
+                width,
+                ancestor.ScrollLeft(),
+                scrollport_width,
+            )
+            .min(max_x)
+            .max(0.);
+            let new_y = scroll_into_view_position(
+                block,
+                y - offset.y - scrollport_y,
+                height,
+                ancestor.ScrollTop(),
+                scrollport_height,
+            )
+            .min(max_y)
+            .max(0.);
+            ancestor.scroll(new_x, new_y, behavior);
+            offset += Vector2D::new(new_x, new_y);
+        }
+
+        // The viewport, which clamps the scroll position itself.
+        let new_x = scroll_into_view_position(
+            inline,
+            x - offset.x,
+            width,
+            win.ScrollX() as f64,
+            win.InnerWidth() as f64,
+        );
+        let new_y = scroll_into_view_position(
+            block,
+            y - offset.y,
+            height,
+            win.ScrollY() as f64,
+            win.InnerHeight() as f64,
+        );
+        win.scroll(new_x, new_y, behavior);
+    }
+
     // https://w3c.github.io/DOM-Parsing/#parsing
     pub fn parse_fragment(&self, markup: DOMString) -> Fallible<DomRoot<DocumentFragment>> {
         // Steps 1-2.
@@ -2195,6 +2456,69 @@ impl ElementMethods for Element {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-scrollintoview
+    fn ScrollIntoView(&self, arg: BooleanOrScrollIntoViewOptions) {
+        // Steps 1-3
+        let (behavior, block, inline) = match arg {
+            BooleanOrScrollIntoViewOptions::Boolean(true) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::Start,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::Boolean(false) => (
+                ScrollBehavior::Auto,
+                ScrollLogicalPosition::End,
+                ScrollLogicalPosition::Nearest,
+            ),
+            BooleanOrScrollIntoViewOptions::ScrollIntoViewOptions(options) => {
+                (options.parent.behavior, options.block, options.inline)
+            },
+        };
+
+        // Step 4
+        if !self.has_css_layout_box() {
+            return;
+        }
+
+        // Step 5
+        self.scroll_into_view(behavior, block, inline);
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-getboxquads
+    fn GetBoxQuads(&self, options: &BoxQuadOptions) -> Fallible<Vec<DomRoot<DOMQuad>>> {
+        geometryutils::get_box_quads(self.upcast(), options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertquadfromnode
+    fn ConvertQuadFromNode(
+        &self,
+        quad: &DOMQuadInit,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMQuad>> {
+        geometryutils::convert_quad_from_node(self.upcast(), quad, &from, options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertrectfromnode
+    fn ConvertRectFromNode(
+        &self,
+        rect: &DOMRectReadOnly,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMQuad>> {
+        geometryutils::convert_rect_from_node(self.upcast(), rect, &from, options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertpointfromnode
+    fn ConvertPointFromNode(
+        &self,
+        point: &DOMPointInit,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMPoint>> {
+        geometryutils::convert_point_from_node(self.upcast(), point, &from, options)
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-element-scroll
     fn Scroll(&self, options: &ScrollToOptions) {
         // Step 1
@@ -2458,6 +2782,63 @@ impl ElementMethods for Element {
         self.upcast::<Node>().client_rect().size.height
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-element-checkvisibility
+    fn CheckVisibility(&self, options: &CheckVisibilityOptions) -> bool {
+        // Disconnected elements never have a box, so there is no need to ask
+        // layout about them.
+        if !self.upcast::<Node>().is_connected() {
+            return false;
+        }
+
+        // Step 1.
+        // Only styles are queried, which doesn't need a display list.
+        let style = match self.style() {
+            Some(style) => style,
+            None => return false,
+        };
+        let display = style.get_box().clone_display();
+        if display.is_none() || display.is_contents() {
+            return false;
+        }
+
+        // Step 2.
+        // TODO: content-visibility is not supported, so no ancestor can
+        // skip its contents.
+
+        // Step 3.
+        if options.checkOpacity || options.opacityProperty {
+            if style.get_effects().opacity == 0. {
+                return false;
+            }
+            let ancestors = self
+                .upcast::<Node>()
+                .inclusive_ancestors(ShadowIncluding::Yes)
+                .skip(1)
+                .filter_map(DomRoot::downcast::<Element>);
+            for ancestor in ancestors {
+                if ancestor
+                    .style()
+                    .map_or(false, |style| style.get_effects().opacity == 0.)
+                {
+                    return false;
+                }
+            }
+        }
+
+        // Step 4.
+        if options.checkVisibilityCSS || options.visibilityProperty {
+            if style.get_inherited_box().visibility != Visibility::Visible {
+                return false;
+            }
+        }
+
+        // Step 5.
+        // Nothing to do, as content-visibility is not supported.
+
+        // Step 6.
+        true
+    }
+
     /// <https://w3c.github.io/DOM-Parsing/#widl-Element-innerHTML>
     fn GetInnerHTML(&self) -> Fallible<DOMString> {
         let qname = QualName::new(
@@ -2534,6 +2915,36 @@ impl ElementMethods for Element {
         Ok(())
     }
 
+    // https://wicg.github.io/sanitizer-api/#dom-element-sethtml
+    fn SetHTML(&self, html: DOMString, options: &SetHTMLOptions) -> ErrorResult {
+        // Step 1.
+        let target = if let Some(template) = self.downcast::<HTMLTemplateElement>() {
+            DomRoot::upcast(template.Content())
+        } else {
+            DomRoot::from_ref(self.upcast())
+        };
+
+        // Step 2.
+        let sanitizer = SanitizerConfiguration::from_options(options)?;
+
+        // Scripts are never given markup by the safe variant.
+        if self.local_name() == &local_name!("script") &&
+            (self.namespace() == &ns!(html) || self.namespace() == &ns!(svg))
+        {
+            return Err(Error::Type("Can't set the HTML of a script".to_owned()));
+        }
+
+        // Step 3.
+        let fragment = self.parse_fragment(html)?;
+
+        // Step 4.
+        sanitizer.sanitize(fragment.upcast());
+
+        // Step 5.
+        Node::replace_all(Some(fragment.upcast()), &target);
+        Ok(())
+    }
+
     // https://dom.spec.whatwg.org/#dom-nondocumenttypechildnode-previouselementsibling
     fn GetPreviousElementSibling(&self) -> Option<DomRoot<Element>> {
         self.upcast::<Node>()
@@ -2729,11 +3140,37 @@ impl ElementMethods for Element {
         doc.enter_fullscreen(self)
     }
 
+    // https://w3c.github.io/pointerlock/#dom-element-requestpointerlock
+    fn RequestPointerLock(&self) {
+        let doc = document_from_node(self);
+        doc.request_pointer_lock(self);
+    }
+
+    // https://drafts.css-houdini.org/css-typed-om-1/#dom-element-computedstylemap
+    fn ComputedStyleMap(&self) -> DomRoot<StylePropertyMapReadOnly> {
+        StylePropertyMapReadOnly::new_computed(&window_from_node(self), self)
+    }
+
     // XXX Hidden under dom.shadowdom.enabled pref. Only exposed to be able
     //     to test partial Shadow DOM support for UA widgets.
     // https://dom.spec.whatwg.org/#dom-element-attachshadow
     fn AttachShadow(&self) -> Fallible<DomRoot<ShadowRoot>> {
-        self.attach_shadow(IsUserAgentWidget::No)
+        self.attach_shadow(IsUserAgentWidget::No, ShadowRootMode::Closed, false)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-element-gethtml
+    fn GetHTML(&self, options: &GetHTMLOptions) -> DOMString {
+        let context = QualName::new(
+            self.prefix().clone(),
+            self.namespace().clone(),
+            self.local_name().clone(),
+        );
+        serialize_html_fragment(
+            self.upcast(),
+            Some(context),
+            options.serializableShadowRoots,
+            &options.shadowRoots,
+        )
     }
 }
 
@@ -2969,6 +3406,9 @@ impl VirtualMethods for Element {
         if fullscreen.as_deref() == Some(self) {
             doc.exit_fullscreen();
         }
+        if doc.pointer_lock_element().as_deref() == Some(self) {
+            doc.exit_pointer_lock();
+        }
         if let Some(ref value) = *self.id_attribute.borrow() {
             doc.unregister_element_id(self, value.clone());
         }
@@ -3153,8 +3593,11 @@ impl<'a> SelectorsElement for DomRoot<Element> {
             NonTSPseudoClass::Disabled |
             NonTSPseudoClass::Checked |
             NonTSPseudoClass::Indeterminate |
+            NonTSPseudoClass::Modal |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
             NonTSPseudoClass::Target => Element::state(self).contains(pseudo_class.state_flag()),
         }
     }
@@ -3234,6 +3677,10 @@ impl Element {
                 let element = self.downcast::<HTMLLabelElement>().unwrap();
                 Some(element as &dyn Activatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLElement)) => {
+                let element = self.downcast::<HTMLElement>().unwrap();
+                Some(element as &dyn Activatable)
+            },
             _ => None,
         };
         element.and_then(|elem| {
@@ -3272,6 +3719,12 @@ impl Element {
                 let element = self.downcast::<HTMLButtonElement>().unwrap();
                 Some(element as &dyn Validatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(
+                HTMLElementTypeId::HTMLFieldSetElement,
+            )) => {
+                let element = self.downcast::<HTMLFieldSetElement>().unwrap();
+                Some(element as &dyn Validatable)
+            },
             NodeTypeId::Element(ElementTypeId::HTMLElement(
                 HTMLElementTypeId::HTMLObjectElement,
             )) => {
@@ -3696,3 +4149,85 @@ pub(crate) fn cors_setting_for_element(element: &Element) -> Option<CorsSettings
         _ => unreachable!(),
     })
 }
+
+/// https://html.spec.whatwg.org/multipage/#fetch-priority-attribute
+pub fn reflect_fetch_priority_attribute(element: &Element) -> DOMString {
+    let mut val = element.get_string_attribute(&LocalName::from("fetchpriority"));
+    val.make_ascii_lowercase();
+    match &*val {
+        "high" | "low" | "auto" => val,
+        _ => DOMString::from("auto"),
+    }
+}
+
+pub fn set_fetch_priority_attribute(element: &Element, value: DOMString) {
+    element.set_string_attribute(&LocalName::from("fetchpriority"), value);
+}
+
+/// https://html.spec.whatwg.org/multipage/#lazy-loading-attribute
+pub fn reflect_lazy_loading_attribute(element: &Element) -> DOMString {
+    let mut val = element.get_string_attribute(&LocalName::from("loading"));
+    val.make_ascii_lowercase();
+    match &*val {
+        "lazy" | "eager" => val,
+        _ => DOMString::from("eager"),
+    }
+}
+
+pub fn set_lazy_loading_attribute(element: &Element, value: DOMString) {
+    element.set_string_attribute(&LocalName::from("loading"), value);
+}
+
+/// https://html.spec.whatwg.org/multipage/#will-lazy-load-element-steps
+pub(crate) fn will_lazy_load_element(element: &Element) -> bool {
+    // Step 1.
+    if !pref!(dom.lazy_loading.enabled) || !document_from_node(element).is_scripting_enabled() {
+        return false;
+    }
+    // Step 2-3.
+    &*reflect_lazy_loading_attribute(element) == "lazy"
+}
+
+pub(crate) fn fetch_priority_for_element(element: &Element) -> RequestPriority {
+    match &*reflect_fetch_priority_attribute(element) {
+        "high" => RequestPriority::High,
+        "low" => RequestPriority::Low,
+        _ => RequestPriority::Auto,
+    }
+}
+
+/// The scroll position along an axis of a scrolling box that aligns an
+/// element as asked, given where the element starts in the scrolled content,
+/// its size, the current scroll position and the size of the scrollport.
+/// <https://drafts.csswg.org/cssom-view/#scroll-an-element-into-view>
+fn scroll_into_view_position(
+    alignment: ScrollLogicalPosition,
+    start: f64,
+    size: f64,
+    scroll: f64,
+    scrollport_size: f64,
+) -> f64 {
+    let end = start + size;
+    match alignment {
+        ScrollLogicalPosition::Start => start,
+        ScrollLogicalPosition::End => end - scrollport_size,
+        ScrollLogicalPosition::Center => start + (size - scrollport_size) / 2.,
+        ScrollLogicalPosition::Nearest => {
+            let outside_start = start < scroll;
+            let outside_end = end > scroll + scrollport_size;
+            if outside_start && outside_end {
+                scroll
+            } else if (outside_start && size < scrollport_size) ||
+                (outside_end && size > scrollport_size)
+            {
+                start
+            } else if (outside_start && size > scrollport_size) ||
+                (outside_end && size < scrollport_size)
+            {
+                end - scrollport_size
+            } else {
+                scroll
+            }
+        },
+    }
+}
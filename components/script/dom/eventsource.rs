@@ -36,6 +36,7 @@ use net_traits::{CoreResourceMsg, FetchChannels, FetchMetadata, FilteredMetadata
 use net_traits::{FetchResponseListener, FetchResponseMsg, NetworkError};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use servo_atoms::Atom;
+use servo_rand::random;
 use servo_url::ServoUrl;
 use std::cell::Cell;
 use std::mem;
@@ -44,6 +45,8 @@ use std::sync::{Arc, Mutex};
 use utf8;
 
 const DEFAULT_RECONNECTION_TIME: u64 = 5000;
+/// The longest the reconnection time is backed off to, in milliseconds.
+const MAX_RECONNECTION_BACKOFF: u64 = 60000;
 
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 struct GenerationId(u32);
@@ -63,11 +66,19 @@ pub struct EventSource {
     request: DomRefCell<Option<RequestBuilder>>,
     last_event_id: DomRefCell<DOMString>,
     reconnection_time: Cell<u64>,
+    /// How many times in a row the connection was reestablished without
+    /// being announced.
+    failed_reconnections: Cell<u32>,
     generation_id: Cell<GenerationId>,
 
     ready_state: Cell<ReadyState>,
     with_credentials: bool,
     canceller: DomRefCell<FetchCanceller>,
+    /// The sender the responses of the fetches of this event source are
+    /// routed through, dropped once the connection is closed so that the
+    /// route goes away with the last fetch.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    action_sender: DomRefCell<Option<ipc::IpcSender<FetchResponseMsg>>>,
 }
 
 enum ParserState {
@@ -82,7 +93,6 @@ struct EventSourceContext {
 
     event_source: Trusted<EventSource>,
     gen_id: GenerationId,
-    action_sender: ipc::IpcSender<FetchResponseMsg>,
 
     parser_state: ParserState,
     field: String,
@@ -111,6 +121,7 @@ impl EventSourceContext {
                 let event_source = event_source.root();
                 if event_source.ready_state.get() != ReadyState::Closed {
                     event_source.ready_state.set(ReadyState::Open);
+                    event_source.failed_reconnections.set(0);
                     event_source.upcast::<EventTarget>().fire_event(atom!("open"));
                 }
             }),
@@ -124,7 +135,7 @@ impl EventSourceContext {
         if self.gen_id != event_source.generation_id.get() {
             return;
         }
-        event_source.fail_the_connection();
+        event_source.cancel();
     }
 
     // https://html.spec.whatwg.org/multipage/#reestablish-the-connection
@@ -136,7 +147,6 @@ impl EventSourceContext {
         }
 
         let trusted_event_source = self.event_source.clone();
-        let action_sender = self.action_sender.clone();
         let global = event_source.global();
         // FIXME(nox): Why are errors silenced here?
         let _ = global.remote_event_task_source().queue(
@@ -154,17 +164,18 @@ impl EventSourceContext {
                 // Step 1.3.
                 event_source.upcast::<EventTarget>().fire_event(atom!("error"));
 
-                // Step 2.
-                let duration = Length::new(event_source.reconnection_time.get());
-
-                // Step 3.
-                // TODO: Optionally wait some more.
+                // Steps 2-3.
+                let failed_reconnections = event_source.failed_reconnections.get();
+                event_source.failed_reconnections.set(failed_reconnections.saturating_add(1));
+                let duration = Length::new(reconnection_delay(
+                    event_source.reconnection_time.get(),
+                    failed_reconnections,
+                ));
 
                 // Steps 4-5.
                 let callback = OneshotTimerCallback::EventSourceTimeout(
                     EventSourceTimeoutCallback {
                         event_source: trusted_event_source,
-                        action_sender,
                     }
                 );
                 // FIXME(nox): Why are errors silenced here?
@@ -182,10 +193,16 @@ impl EventSourceContext {
                 self.data.push_str(&self.value);
                 self.data.push('\n');
             },
-            "id" => mem::swap(&mut self.last_event_id, &mut self.value),
+            "id" => {
+                if !self.value.contains('\0') {
+                    mem::swap(&mut self.last_event_id, &mut self.value);
+                }
+            },
             "retry" => {
-                if let Ok(time) = u64::from_str(&self.value) {
-                    self.event_source.root().reconnection_time.set(time);
+                if self.value.bytes().all(|byte| byte.is_ascii_digit()) {
+                    if let Ok(time) = u64::from_str(&self.value) {
+                        self.event_source.root().reconnection_time.set(time);
+                    }
                 }
             },
             _ => (),
@@ -346,6 +363,9 @@ impl FetchResponseListener for EventSourceContext {
                         _ => unsafe_,
                     },
                 };
+                if meta.status.as_ref().map(|&(code, _)| code) != Some(200) {
+                    return self.fail_the_connection();
+                }
                 let mime = match meta.content_type {
                     None => return self.fail_the_connection(),
                     Some(ct) => <ContentType as Into<Mime>>::into(ct.into_inner()),
@@ -411,6 +431,14 @@ impl FetchResponseListener for EventSourceContext {
         if let Some(_) = self.incomplete_utf8.take() {
             self.parse("\u{FFFD}".chars());
         }
+        // An event that wasn't complete when the stream ended is discarded,
+        // the next stream starts with empty buffers.
+        self.parser_state = ParserState::Eol;
+        self.field.clear();
+        self.value.clear();
+        self.event_type.clear();
+        self.data.clear();
+        self.last_event_id.clear();
         self.reestablish_the_connection();
     }
 
@@ -451,11 +479,13 @@ impl EventSource {
             request: DomRefCell::new(None),
             last_event_id: DomRefCell::new(DOMString::from("")),
             reconnection_time: Cell::new(DEFAULT_RECONNECTION_TIME),
+            failed_reconnections: Cell::new(0),
             generation_id: Cell::new(GenerationId(0)),
 
             ready_state: Cell::new(ReadyState::Connecting),
             with_credentials: with_credentials,
             canceller: DomRefCell::new(Default::default()),
+            action_sender: DomRefCell::new(None),
         }
     }
 
@@ -483,6 +513,7 @@ impl EventSource {
                 let event_source = event_source.root();
                 if event_source.ready_state.get() != ReadyState::Closed {
                     event_source.ready_state.set(ReadyState::Closed);
+                    event_source.action_sender.borrow_mut().take();
                     event_source.upcast::<EventTarget>().fire_event(atom!("error"));
                 }
             }),
@@ -554,7 +585,6 @@ impl EventSource {
 
             event_source: Trusted::new(&ev),
             gen_id: ev.generation_id.get(),
-            action_sender: action_sender.clone(),
 
             parser_state: ParserState::Eol,
             field: String::new(),
@@ -577,6 +607,7 @@ impl EventSource {
                 listener.notify_fetch(message.to().unwrap());
             }),
         );
+        *ev.action_sender.borrow_mut() = Some(action_sender.clone());
         let cancel_receiver = ev.canceller.borrow_mut().initialize();
         global
             .core_resource_thread()
@@ -629,16 +660,30 @@ impl EventSourceMethods for EventSource {
         let GenerationId(prev_id) = self.generation_id.get();
         self.generation_id.set(GenerationId(prev_id + 1));
         self.canceller.borrow_mut().cancel();
+        self.action_sender.borrow_mut().take();
         self.ready_state.set(ReadyState::Closed);
     }
 }
 
+/// The time to wait before reestablishing the connection, which is backed off
+/// exponentially when the previous attempts failed, with some jitter so that
+/// the clients of an overloaded server don't all reconnect at once.
+/// <https://html.spec.whatwg.org/multipage/#reestablish-the-connection>
+fn reconnection_delay(reconnection_time: u64, failed_reconnections: u32) -> u64 {
+    if failed_reconnections == 0 {
+        return reconnection_time;
+    }
+    let backoff = reconnection_time
+        .saturating_mul(1 << failed_reconnections.min(16))
+        .min(MAX_RECONNECTION_BACKOFF)
+        .max(reconnection_time);
+    backoff.saturating_add(random::<u64>() % (backoff / 4 + 1))
+}
+
 #[derive(JSTraceable, MallocSizeOf)]
 pub struct EventSourceTimeoutCallback {
     #[ignore_malloc_size_of = "Because it is non-owning"]
     event_source: Trusted<EventSource>,
-    #[ignore_malloc_size_of = "Because it is non-owning"]
-    action_sender: ipc::IpcSender<FetchResponseMsg>,
 }
 
 impl EventSourceTimeoutCallback {
@@ -650,23 +695,32 @@ impl EventSourceTimeoutCallback {
         if event_source.ready_state.get() != ReadyState::Connecting {
             return;
         }
+        let action_sender = match event_source.action_sender.borrow().clone() {
+            Some(action_sender) => action_sender,
+            None => return,
+        };
         // Step 5.2
         let mut request = event_source.request();
         // Step 5.3
         if !event_source.last_event_id.borrow().is_empty() {
             //TODO(eijebong): Change this once typed header support custom values
-            request.headers.insert(
-                HeaderName::from_static("last-event-id"),
-                HeaderValue::from_str(&String::from(event_source.last_event_id.borrow().clone()))
-                    .unwrap(),
-            );
+            // The ID is sent encoded as UTF-8, unless it contains control
+            // characters that can't be part of a header value.
+            if let Ok(value) =
+                HeaderValue::from_bytes(event_source.last_event_id.borrow().as_bytes())
+            {
+                request
+                    .headers
+                    .insert(HeaderName::from_static("last-event-id"), value);
+            }
         }
         // Step 5.4
+        let cancel_receiver = event_source.canceller.borrow_mut().initialize();
         global
             .core_resource_thread()
             .send(CoreResourceMsg::Fetch(
                 request,
-                FetchChannels::ResponseMsg(self.action_sender, None),
+                FetchChannels::ResponseMsg(action_sender, Some(cancel_receiver)),
             ))
             .unwrap();
     }
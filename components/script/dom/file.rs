@@ -9,7 +9,7 @@ use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::blob::{blob_parts_to_bytes, normalize_type_string, Blob};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::window::Window;
@@ -22,11 +22,18 @@ pub struct File {
     blob: Blob,
     name: DOMString,
     modified: i64,
+    /// <https://wicg.github.io/entries-api/#dom-file-webkitrelativepath>
+    relative_path: DOMString,
 }
 
 impl File {
     #[allow(unrooted_must_root)]
-    fn new_inherited(blob_impl: &BlobImpl, name: DOMString, modified: Option<i64>) -> File {
+    fn new_inherited(
+        blob_impl: &BlobImpl,
+        name: DOMString,
+        modified: Option<i64>,
+        relative_path: DOMString,
+    ) -> File {
         File {
             blob: Blob::new_inherited(blob_impl),
             name: name,
@@ -38,6 +45,7 @@ impl File {
                     time.sec * 1000 + (time.nsec / 1000000) as i64
                 },
             },
+            relative_path: relative_path,
         }
     }
 
@@ -47,9 +55,25 @@ impl File {
         blob_impl: BlobImpl,
         name: DOMString,
         modified: Option<i64>,
+    ) -> DomRoot<File> {
+        File::new_with_relative_path(global, blob_impl, name, modified, DOMString::new())
+    }
+
+    #[allow(unrooted_must_root)]
+    fn new_with_relative_path(
+        global: &GlobalScope,
+        blob_impl: BlobImpl,
+        name: DOMString,
+        modified: Option<i64>,
+        relative_path: DOMString,
     ) -> DomRoot<File> {
         let file = reflect_dom_object(
-            Box::new(File::new_inherited(&blob_impl, name, modified)),
+            Box::new(File::new_inherited(
+                &blob_impl,
+                name,
+                modified,
+                relative_path,
+            )),
             global,
             FileBinding::Wrap,
         );
@@ -66,7 +90,7 @@ impl File {
                 .expect("File name encoding error"),
         );
 
-        File::new(
+        File::new_with_relative_path(
             window.upcast(),
             BlobImpl::new_from_file(
                 selected.id,
@@ -76,6 +100,7 @@ impl File {
             ),
             name,
             Some(selected.modified as i64),
+            DOMString::from(selected.relative_path),
         )
     }
 
@@ -122,4 +147,9 @@ impl FileMethods for File {
     fn LastModified(&self) -> i64 {
         self.modified
     }
+
+    // https://wicg.github.io/entries-api/#dom-file-webkitrelativepath
+    fn WebkitRelativePath(&self) -> USVString {
+        USVString(self.relative_path.to_string())
+    }
 }
@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::FontFaceSetBinding::{
+    self, FontFaceSetLoadStatus, FontFaceSetMethods,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+/// <https://drafts.csswg.org/css-font-loading/#fontfaceset>
+///
+/// Only the fonts of the `@font-face` rules of the document are tracked, through the web font
+/// loads that layout reports as pending after each reflow.
+#[dom_struct]
+pub struct FontFaceSet {
+    eventtarget: EventTarget,
+    /// <https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-readypromise-slot>
+    #[ignore_malloc_size_of = "Rc"]
+    ready_promise: DomRefCell<Rc<Promise>>,
+}
+
+impl FontFaceSet {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(ready_promise: Rc<Promise>) -> FontFaceSet {
+        FontFaceSet {
+            eventtarget: EventTarget::new_inherited(),
+            ready_promise: DomRefCell::new(ready_promise),
+        }
+    }
+
+    /// Creates the font face set of a document, whose ready promise is resolved right away if
+    /// `loading` is false.
+    pub fn new(window: &Window, loading: bool) -> DomRoot<FontFaceSet> {
+        let ready_promise = Promise::new(window.upcast::<GlobalScope>());
+        let set = reflect_dom_object(
+            Box::new(FontFaceSet::new_inherited(ready_promise)),
+            window,
+            FontFaceSetBinding::Wrap,
+        );
+        if !loading {
+            set.ready_promise.borrow().resolve_native(&*set);
+        }
+        set
+    }
+
+    /// Updates the status of the set after a reflow, replacing the ready promise by a pending
+    /// one if fonts started loading, and resolving it once they are done.
+    ///
+    /// <https://drafts.csswg.org/css-font-loading/#fontfaceset-pending-on-the-environment>
+    pub fn handle_loading_state(&self, loading: bool) {
+        let is_fulfilled = self.ready_promise.borrow().is_fulfilled();
+        if loading && is_fulfilled {
+            *self.ready_promise.borrow_mut() = Promise::new(&self.global());
+        } else if !loading && !is_fulfilled {
+            let ready_promise = self.ready_promise.borrow().clone();
+            ready_promise.resolve_native(self);
+        }
+    }
+}
+
+impl FontFaceSetMethods for FontFaceSet {
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/css-font-loading/#dom-fontfaceset-status
+    fn Status(&self) -> FontFaceSetLoadStatus {
+        if self.ready_promise.borrow().is_fulfilled() {
+            FontFaceSetLoadStatus::Loaded
+        } else {
+            FontFaceSetLoadStatus::Loading
+        }
+    }
+}
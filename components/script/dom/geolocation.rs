@@ -0,0 +1,377 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::GeolocationBinding::{
+    self, GeolocationMethods, PositionCallback, PositionErrorCallback, PositionOptions,
+};
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionErrorBinding::GeolocationPositionErrorConstants;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::geolocationposition::GeolocationPosition;
+use crate::dom::geolocationpositionerror::GeolocationPositionError;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, prompt_user_from_embedder};
+use crate::task::TaskOnce;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use embedder_traits::{self, DevicePosition, EmbedderMsg, PermissionPrompt, PositionWatchId};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_traits::MsDuration;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A call to `getCurrentPosition()` that is waiting for a position, or a
+/// call to `watchPosition()` that wasn't cleared yet.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PositionRequest {
+    id: i32,
+    /// Whether the request comes from `watchPosition()`, in which case it
+    /// lasts until `clearWatch()` is called with its id.
+    watch: bool,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    success_callback: Rc<PositionCallback>,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    error_callback: Option<Rc<PositionErrorCallback>>,
+    /// The timer that reports a timeout if the first position isn't
+    /// acquired in time.
+    timeout: Option<OneshotTimerHandle>,
+}
+
+/// Reports a timeout to the position request with the given id.
+/// <https://w3c.github.io/geolocation-api/#dfn-acquire-a-position>
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct GeolocationTimeoutCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    geolocation: Trusted<Geolocation>,
+    id: i32,
+}
+
+impl GeolocationTimeoutCallback {
+    pub fn invoke(self) {
+        self.geolocation.root().handle_timeout(self.id);
+    }
+}
+
+#[dom_struct]
+pub struct Geolocation {
+    reflector_: Reflector,
+    /// <https://w3c.github.io/geolocation-api/#dfn-cachedposition>
+    cached_position: MutNullableDom<GeolocationPosition>,
+    /// The pending requests, where the watches are the watchIDs of the spec.
+    /// <https://w3c.github.io/geolocation-api/#dfn-watchids>
+    requests: DomRefCell<Vec<PositionRequest>>,
+    request_id: Cell<i32>,
+}
+
+impl Geolocation {
+    fn new_inherited() -> Geolocation {
+        Geolocation {
+            reflector_: Reflector::new(),
+            cached_position: Default::default(),
+            requests: DomRefCell::new(vec![]),
+            request_id: Cell::new(0),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Geolocation> {
+        reflect_dom_object(
+            Box::new(Geolocation::new_inherited()),
+            global,
+            GeolocationBinding::Wrap,
+        )
+    }
+
+    fn is_fully_active(&self) -> bool {
+        self.global().as_window().Document().is_fully_active()
+    }
+
+    fn add_request(
+        &self,
+        watch: bool,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Rc<PositionErrorCallback>>,
+    ) -> i32 {
+        let id = self.request_id.get() + 1;
+        self.request_id.set(id);
+        self.requests.borrow_mut().push(PositionRequest {
+            id,
+            watch,
+            success_callback,
+            error_callback,
+            timeout: None,
+        });
+        id
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-request-a-position>
+    fn request_position(&self, id: i32, watch: bool, options: &PositionOptions) {
+        // TODO: Step 3. Check the "geolocation" policy-controlled feature.
+        // TODO: Step 5. Wait until the document is visible.
+
+        // Steps 6-7.1.
+        if !self.permission_granted() {
+            // Step 7.2.
+            self.queue_error(
+                id,
+                GeolocationPositionErrorConstants::PERMISSION_DENIED,
+                true,
+            );
+            return;
+        }
+
+        // Steps 7.3-7.5, where the embedder tells about the significant
+        // changes of the position of the watches.
+        self.acquire_position(id, watch, options);
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-request-permission-to-use>
+    fn permission_granted(&self) -> bool {
+        let global = self.global();
+        let state =
+            match get_descriptor_permission_state(PermissionName::Geolocation, Some(&global)) {
+                PermissionState::Prompt => {
+                    let prompt =
+                        PermissionPrompt::Request(embedder_traits::PermissionName::Geolocation);
+                    let state = prompt_user_from_embedder(prompt, &global);
+                    global
+                        .permission_state_invocation_results()
+                        .borrow_mut()
+                        .insert(PermissionName::Geolocation.to_string(), state);
+                    state
+                },
+                state => state,
+            };
+        state == PermissionState::Granted
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-acquire-a-position>
+    fn acquire_position(&self, id: i32, watch: bool, options: &PositionOptions) {
+        let global = self.global();
+
+        // Step 2.
+        let time = time::get_time();
+        let acquisition_time = (time.sec * 1000 + (time.nsec / 1000000) as i64) as u64;
+
+        // Step 6. A fresh enough cached position answers the request, but
+        // a watch still needs the next ones.
+        let cached_position = self.cached_position.get().filter(|position| {
+            options.maximumAge > 0 &&
+                position.timestamp() >=
+                    acquisition_time.saturating_sub(options.maximumAge as u64)
+        });
+        if let Some(position) = cached_position {
+            let this = Trusted::new(self);
+            let position = Trusted::new(&*position);
+            self.queue(task!(geolocation_call_back_with_cached_position: move || {
+                this.root().call_back_with_position(id, &position.root());
+            }));
+            if !watch {
+                return;
+            }
+        } else {
+            // Step 5.
+            let callback = GeolocationTimeoutCallback {
+                geolocation: Trusted::new(self),
+                id,
+            };
+            let timeout = global.schedule_callback(
+                OneshotTimerCallback::GeolocationTimeout(callback),
+                MsDuration::new(options.timeout as u64),
+            );
+            if let Some(request) = self.requests.borrow_mut().iter_mut().find(|r| r.id == id) {
+                request.timeout = Some(timeout);
+            }
+        }
+
+        // Step 8.
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let position: Option<DevicePosition> = message.to().unwrap();
+                let this = this.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(geolocation_position_acquired: move || {
+                        this.root().handle_position(id, position);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue geolocation task {:?}", err);
+                }
+            }),
+        );
+        let msg = if watch {
+            let watch_id = PositionWatchId(global.pipeline_id(), id);
+            EmbedderMsg::WatchPosition(watch_id, options.enableHighAccuracy, sender)
+        } else {
+            EmbedderMsg::GetPosition(options.enableHighAccuracy, sender)
+        };
+        global.send_to_embedder(msg);
+    }
+
+    /// Steps 9-10 of <https://w3c.github.io/geolocation-api/#dfn-acquire-a-position>
+    fn handle_position(&self, id: i32, position: Option<DevicePosition>) {
+        let position = match position {
+            Some(position) => position,
+            None => {
+                return self.call_back_with_error(
+                    id,
+                    GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                    false,
+                );
+            },
+        };
+        let position = GeolocationPosition::new(&self.global(), &position);
+        self.cached_position.set(Some(&position));
+        self.call_back_with_position(id, &position);
+    }
+
+    fn handle_timeout(&self, id: i32) {
+        // The timer already fired, so there is no need to unschedule it.
+        if let Some(request) = self.requests.borrow_mut().iter_mut().find(|r| r.id == id) {
+            request.timeout = None;
+        }
+        self.call_back_with_error(id, GeolocationPositionErrorConstants::TIMEOUT, false);
+    }
+
+    /// Returns the callbacks of the request with the given id if it is still
+    /// pending, and forgets it if it is done. Its timeout is cancelled,
+    /// since it either got a position or failed.
+    fn take_callbacks(
+        &self,
+        id: i32,
+        remove_watch: bool,
+    ) -> Option<(Rc<PositionCallback>, Option<Rc<PositionErrorCallback>>)> {
+        let mut requests = self.requests.borrow_mut();
+        let index = requests.iter().position(|request| request.id == id)?;
+        if let Some(timeout) = requests[index].timeout.take() {
+            self.global().unschedule_callback(timeout);
+        }
+        let callbacks = (
+            requests[index].success_callback.clone(),
+            requests[index].error_callback.clone(),
+        );
+        if remove_watch || !requests[index].watch {
+            requests.remove(index);
+        }
+        Some(callbacks)
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-call-back-with-position>
+    fn call_back_with_position(&self, id: i32, position: &GeolocationPosition) {
+        if let Some((success_callback, _)) = self.take_callbacks(id, false) {
+            let _ = success_callback.Call__(position, ExceptionHandling::Report);
+        }
+    }
+
+    /// <https://w3c.github.io/geolocation-api/#dfn-call-back-with-error>
+    fn call_back_with_error(&self, id: i32, code: u16, remove_watch: bool) {
+        if let Some((_, Some(error_callback))) = self.take_callbacks(id, remove_watch) {
+            let error = GeolocationPositionError::new(&self.global(), code);
+            let _ = error_callback.Call__(&error, ExceptionHandling::Report);
+        }
+    }
+
+    fn queue_error(&self, id: i32, code: u16, remove_watch: bool) {
+        let this = Trusted::new(self);
+        self.queue(task!(geolocation_call_back_with_error: move || {
+            this.root().call_back_with_error(id, code, remove_watch);
+        }));
+    }
+
+    fn queue<T: TaskOnce + 'static>(&self, task: T) {
+        let global = self.global();
+        let result = global.dom_manipulation_task_source().queue(task, &global);
+        if let Err(err) = result {
+            warn!("Could not queue geolocation task {:?}", err);
+        }
+    }
+}
+
+impl GeolocationMethods for Geolocation {
+    // https://w3c.github.io/geolocation-api/#getcurrentposition-method
+    fn GetCurrentPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Option<Rc<PositionErrorCallback>>>,
+        options: &PositionOptions,
+    ) {
+        let id = self.add_request(false, success_callback, error_callback.flatten());
+
+        // Step 1.
+        if !self.is_fully_active() {
+            self.queue_error(
+                id,
+                GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                true,
+            );
+            return;
+        }
+
+        // Step 2.
+        self.request_position(id, false, options);
+    }
+
+    // https://w3c.github.io/geolocation-api/#watchposition-method
+    fn WatchPosition(
+        &self,
+        success_callback: Rc<PositionCallback>,
+        error_callback: Option<Option<Rc<PositionErrorCallback>>>,
+        options: &PositionOptions,
+    ) -> i32 {
+        // Step 1.
+        if !self.is_fully_active() {
+            // The error callback is called with POSITION_UNAVAILABLE
+            // through a request that no id is returned for.
+            let id = self.add_request(false, success_callback, error_callback.flatten());
+            self.queue_error(
+                id,
+                GeolocationPositionErrorConstants::POSITION_UNAVAILABLE,
+                true,
+            );
+            return 0;
+        }
+
+        // Steps 2-3.
+        let id = self.add_request(true, success_callback, error_callback.flatten());
+        self.request_position(id, true, options);
+
+        // Step 4.
+        id
+    }
+
+    // https://w3c.github.io/geolocation-api/#clearwatch-method
+    fn ClearWatch(&self, watch_id: i32) {
+        let global = self.global();
+        let mut requests = self.requests.borrow_mut();
+        let index = match requests
+            .iter()
+            .position(|request| request.watch && request.id == watch_id)
+        {
+            Some(index) => index,
+            None => return,
+        };
+        let request = requests.remove(index);
+        if let Some(timeout) = request.timeout {
+            global.unschedule_callback(timeout);
+        }
+        global.send_to_embedder(EmbedderMsg::ClearPositionWatch(PositionWatchId(
+            global.pipeline_id(),
+            watch_id,
+        )));
+    }
+}
@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GeolocationCoordinatesBinding::{
+    self, GeolocationCoordinatesMethods,
+};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::DevicePosition;
+
+#[dom_struct]
+pub struct GeolocationCoordinates {
+    reflector_: Reflector,
+    latitude: f64,
+    longitude: f64,
+    accuracy: f64,
+    altitude: Option<f64>,
+    altitude_accuracy: Option<f64>,
+    heading: Option<f64>,
+    speed: Option<f64>,
+}
+
+impl GeolocationCoordinates {
+    fn new_inherited(position: &DevicePosition) -> GeolocationCoordinates {
+        GeolocationCoordinates {
+            reflector_: Reflector::new(),
+            latitude: position.latitude,
+            longitude: position.longitude,
+            accuracy: position.accuracy,
+            altitude: position.altitude,
+            altitude_accuracy: position.altitude_accuracy,
+            heading: position.heading,
+            speed: position.speed,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, position: &DevicePosition) -> DomRoot<GeolocationCoordinates> {
+        reflect_dom_object(
+            Box::new(GeolocationCoordinates::new_inherited(position)),
+            global,
+            GeolocationCoordinatesBinding::Wrap,
+        )
+    }
+}
+
+impl GeolocationCoordinatesMethods for GeolocationCoordinates {
+    // https://w3c.github.io/geolocation-api/#accuracy
+    fn Accuracy(&self) -> Finite<f64> {
+        Finite::wrap(self.accuracy)
+    }
+
+    // https://w3c.github.io/geolocation-api/#lat
+    fn Latitude(&self) -> Finite<f64> {
+        Finite::wrap(self.latitude)
+    }
+
+    // https://w3c.github.io/geolocation-api/#lon
+    fn Longitude(&self) -> Finite<f64> {
+        Finite::wrap(self.longitude)
+    }
+
+    // https://w3c.github.io/geolocation-api/#altitude
+    fn GetAltitude(&self) -> Option<Finite<f64>> {
+        self.altitude.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#altitudeaccuracy
+    fn GetAltitudeAccuracy(&self) -> Option<Finite<f64>> {
+        self.altitude_accuracy.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#heading
+    fn GetHeading(&self) -> Option<Finite<f64>> {
+        // The heading of a stationary device is NaN, which can't be
+        // represented by a restricted double, so it is null too.
+        self.heading.and_then(Finite::new)
+    }
+
+    // https://w3c.github.io/geolocation-api/#speed
+    fn GetSpeed(&self) -> Option<Finite<f64>> {
+        self.speed.and_then(Finite::new)
+    }
+}
@@ -0,0 +1,58 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionBinding::{
+    self, GeolocationPositionMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::geolocationcoordinates::GeolocationCoordinates;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::DevicePosition;
+
+#[dom_struct]
+pub struct GeolocationPosition {
+    reflector_: Reflector,
+    coords: Dom<GeolocationCoordinates>,
+    timestamp: u64,
+}
+
+impl GeolocationPosition {
+    fn new_inherited(coords: &GeolocationCoordinates, timestamp: u64) -> GeolocationPosition {
+        GeolocationPosition {
+            reflector_: Reflector::new(),
+            coords: Dom::from_ref(coords),
+            timestamp,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, position: &DevicePosition) -> DomRoot<GeolocationPosition> {
+        let coords = GeolocationCoordinates::new(global, position);
+        reflect_dom_object(
+            Box::new(GeolocationPosition::new_inherited(
+                &coords,
+                position.timestamp,
+            )),
+            global,
+            GeolocationPositionBinding::Wrap,
+        )
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl GeolocationPositionMethods for GeolocationPosition {
+    // https://w3c.github.io/geolocation-api/#coords-attribute
+    fn Coords(&self) -> DomRoot<GeolocationCoordinates> {
+        DomRoot::from_ref(&*self.coords)
+    }
+
+    // https://w3c.github.io/geolocation-api/#timestamp-attribute
+    fn Timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::GeolocationPositionErrorBinding::{
+    self, GeolocationPositionErrorConstants, GeolocationPositionErrorMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct GeolocationPositionError {
+    reflector_: Reflector,
+    code: u16,
+}
+
+impl GeolocationPositionError {
+    fn new_inherited(code: u16) -> GeolocationPositionError {
+        GeolocationPositionError {
+            reflector_: Reflector::new(),
+            code,
+        }
+    }
+
+    /// Creates an error with one of the codes of `GeolocationPositionErrorConstants`.
+    pub fn new(global: &GlobalScope, code: u16) -> DomRoot<GeolocationPositionError> {
+        reflect_dom_object(
+            Box::new(GeolocationPositionError::new_inherited(code)),
+            global,
+            GeolocationPositionErrorBinding::Wrap,
+        )
+    }
+}
+
+impl GeolocationPositionErrorMethods for GeolocationPositionError {
+    // https://w3c.github.io/geolocation-api/#code-attribute
+    fn Code(&self) -> u16 {
+        self.code
+    }
+
+    // https://w3c.github.io/geolocation-api/#message-attribute
+    fn Message(&self) -> DOMString {
+        // The message is only meant for debugging.
+        DOMString::from(match self.code {
+            GeolocationPositionErrorConstants::PERMISSION_DENIED => "User denied Geolocation",
+            GeolocationPositionErrorConstants::TIMEOUT => "Position acquisition timed out",
+            _ => "Position unavailable",
+        })
+    }
+}
@@ -0,0 +1,315 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The `GeometryUtils` methods shared by `Text`, `Element` and `Document`.
+//! <https://drafts.csswg.org/cssom-view/#the-geometryutils-interface>
+//!
+//! Every box of a node is mapped to the viewport of the top-most document
+//! that can be reached from the document of the node through same-origin
+//! iframes, and coordinates are converted between nodes through that space.
+
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
+use crate::dom::bindings::codegen::Bindings::DOMQuadBinding::DOMQuadInit;
+use crate::dom::bindings::codegen::Bindings::DOMRectReadOnlyBinding::DOMRectReadOnlyMethods;
+use crate::dom::bindings::codegen::Bindings::GeometryUtilsBinding::{
+    BoxQuadOptions, CSSBoxType, ConvertCoordinateOptions,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::TextOrElementOrDocument as GeometryNode;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::Document;
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::domquad::DOMQuad;
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use euclid::default::{Point2D, Rect, Size2D, Transform3D};
+use script_layout_interface::rpc::BoxQuad;
+
+/// A box of a node, in the coordinate space of that node.
+struct NodeBox {
+    rect: Rect<f32>,
+    /// Maps the coordinate space of the node to the viewport of the root
+    /// document of the boxes.
+    transform: Transform3D<f32>,
+}
+
+/// The boxes of a node, one per fragment.
+struct NodeBoxes {
+    boxes: Vec<NodeBox>,
+    /// The document whose viewport the boxes are mapped to.
+    root: DomRoot<Document>,
+}
+
+impl NodeBoxes {
+    /// The box that defines the coordinate space of a node, i.e. its first one.
+    fn first(&self) -> Fallible<&NodeBox> {
+        self.boxes.first().ok_or(Error::NotFound)
+    }
+}
+
+fn geometry_node(node: &GeometryNode) -> &Node {
+    match *node {
+        GeometryNode::Text(ref text) => text.upcast(),
+        GeometryNode::Element(ref element) => element.upcast(),
+        GeometryNode::Document(ref document) => document.upcast(),
+    }
+}
+
+/// Returns the rectangle of the given type of box around a fragment, in CSS pixels.
+fn box_rect(quad: &BoxQuad, box_type: CSSBoxType) -> Rect<f32> {
+    let rect = match box_type {
+        CSSBoxType::Margin => quad.border_box.outer_rect(quad.margin),
+        CSSBoxType::Border => quad.border_box,
+        CSSBoxType::Padding => quad.border_box.inner_rect(quad.border),
+        CSSBoxType::Content => quad
+            .border_box
+            .inner_rect(quad.border)
+            .inner_rect(quad.padding),
+    };
+    Rect::new(
+        Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+        Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()),
+    )
+}
+
+/// Returns the boxes of a node, mapped to the viewport of its root document.
+fn node_boxes(node: &Node, box_type: CSSBoxType) -> NodeBoxes {
+    let document = node.owner_doc();
+    let window = document.window();
+
+    let boxes = if node.is::<Document>() {
+        // The box of a document is its viewport.
+        let size = Size2D::new(window.InnerWidth() as f32, window.InnerHeight() as f32);
+        vec![NodeBox {
+            rect: Rect::new(Point2D::zero(), size),
+            transform: Transform3D::identity(),
+        }]
+    } else if !node.is_connected() {
+        vec![]
+    } else {
+        // Layout positions fragments as if nothing was scrolled.
+        // FIXME: The scroll offsets of the ancestors of the node are applied
+        // after all the transforms, which is wrong for a scroll container
+        // inside a transformed element.
+        let viewport = window.current_viewport();
+        let mut scroll_x = viewport.origin.x.to_f32_px();
+        let mut scroll_y = viewport.origin.y.to_f32_px();
+        for ancestor in node.ancestors() {
+            let offset = window.scroll_offset_query(&ancestor);
+            scroll_x += offset.x.abs();
+            scroll_y += offset.y.abs();
+        }
+        let scroll = Transform3D::create_translation(-scroll_x, -scroll_y, 0.);
+        window
+            .box_quads_query(node)
+            .iter()
+            .map(|quad| NodeBox {
+                rect: box_rect(quad, box_type),
+                transform: quad.transform.post_transform(&scroll),
+            })
+            .collect()
+    };
+
+    // Map the viewport of the document to the content box of its frame, as
+    // long as the document of the frame is same-origin.
+    let frame = document
+        .browsing_context()
+        .and_then(|proxy| proxy.frame_element().map(DomRoot::from_ref));
+    if let Some(frame) = frame {
+        if frame.owner_doc().origin().same_origin(document.origin()) {
+            let frame_boxes = node_boxes(frame.upcast(), CSSBoxType::Content);
+            if let Some(frame_box) = frame_boxes.boxes.first() {
+                let frame_transform = Transform3D::create_translation(
+                    frame_box.rect.origin.x,
+                    frame_box.rect.origin.y,
+                    0.,
+                )
+                .post_transform(&frame_box.transform);
+                return NodeBoxes {
+                    boxes: boxes
+                        .into_iter()
+                        .map(|node_box| NodeBox {
+                            rect: node_box.rect,
+                            transform: node_box.transform.post_transform(&frame_transform),
+                        })
+                        .collect(),
+                    root: frame_boxes.root,
+                };
+            }
+        }
+    }
+
+    NodeBoxes {
+        boxes,
+        root: document,
+    }
+}
+
+/// Returns the transform mapping the coordinate space described by
+/// `from_transform` to the one of the box `to`, relative to its origin.
+fn conversion(from_transform: &Transform3D<f32>, to: &NodeBox) -> Fallible<Transform3D<f32>> {
+    let inverse = to.transform.inverse().ok_or(Error::InvalidState)?;
+    Ok(from_transform
+        .post_transform(&inverse)
+        .post_transform(&Transform3D::create_translation(
+            -to.rect.origin.x,
+            -to.rect.origin.y,
+            0.,
+        )))
+}
+
+/// Maps a point with homogeneous coordinates through a transform.
+fn transform_point(transform: &Transform3D<f32>, point: &DOMPointInit) -> [f64; 4] {
+    let m = transform.to_row_major_array();
+    let mut result = [0.; 4];
+    for (i, value) in result.iter_mut().enumerate() {
+        *value = point.x * m[i] as f64 +
+            point.y * m[4 + i] as f64 +
+            point.z * m[8 + i] as f64 +
+            point.w * m[12 + i] as f64;
+    }
+    result
+}
+
+fn new_point(
+    global: &GlobalScope,
+    transform: &Transform3D<f32>,
+    x: f64,
+    y: f64,
+) -> DomRoot<DOMPoint> {
+    let point = DOMPointInit { x, y, z: 0., w: 1. };
+    let [x, y, z, w] = transform_point(transform, &point);
+    DOMPoint::new(global, x / w, y / w, z / w, 1.)
+}
+
+fn new_quad(
+    global: &GlobalScope,
+    transform: &Transform3D<f32>,
+    quad: &DOMQuadInit,
+) -> DomRoot<DOMQuad> {
+    let point = |init: &DOMPointInit| {
+        let [x, y, z, w] = transform_point(transform, init);
+        DOMPoint::new(global, x, y, z, w)
+    };
+    DOMQuad::new(
+        global,
+        &point(&quad.p1),
+        &point(&quad.p2),
+        &point(&quad.p3),
+        &point(&quad.p4),
+    )
+}
+
+/// Returns the quad of a rectangle mapped through a transform.
+fn rect_quad(
+    global: &GlobalScope,
+    transform: &Transform3D<f32>,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> DomRoot<DOMQuad> {
+    DOMQuad::new(
+        global,
+        &new_point(global, transform, x, y),
+        &new_point(global, transform, x + width, y),
+        &new_point(global, transform, x + width, y + height),
+        &new_point(global, transform, x, y + height),
+    )
+}
+
+/// Returns the transform converting coordinates relative to the `from_box` of
+/// `from` to coordinates relative to the `to_box` of `to`.
+fn node_conversion(
+    from: &Node,
+    from_box: CSSBoxType,
+    to: &Node,
+    to_box: CSSBoxType,
+) -> Fallible<Transform3D<f32>> {
+    let from = node_boxes(from, from_box);
+    let to = node_boxes(to, to_box);
+    if from.root != to.root {
+        return Err(Error::NotFound);
+    }
+    let from_box = from.first()?;
+    let from_transform =
+        Transform3D::create_translation(from_box.rect.origin.x, from_box.rect.origin.y, 0.)
+            .post_transform(&from_box.transform);
+    conversion(&from_transform, to.first()?)
+}
+
+/// <https://drafts.csswg.org/cssom-view/#dom-geometryutils-getboxquads>
+pub fn get_box_quads(node: &Node, options: &BoxQuadOptions) -> Fallible<Vec<DomRoot<DOMQuad>>> {
+    let global = node.owner_doc().global();
+    let boxes = node_boxes(node, options.box_);
+    let relative_to = match options.relativeTo {
+        Some(ref relative_to) => node_boxes(geometry_node(relative_to), CSSBoxType::Border),
+        None => node_boxes(node.owner_doc().upcast(), CSSBoxType::Border),
+    };
+    if boxes.root != relative_to.root {
+        return Err(Error::NotFound);
+    }
+    let relative_to = relative_to.first()?;
+    boxes
+        .boxes
+        .iter()
+        .map(|node_box| {
+            let transform = conversion(&node_box.transform, relative_to)?;
+            let rect = node_box.rect.cast::<f64>();
+            Ok(rect_quad(
+                &global,
+                &transform,
+                rect.origin.x,
+                rect.origin.y,
+                rect.size.width,
+                rect.size.height,
+            ))
+        })
+        .collect()
+}
+
+/// <https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertquadfromnode>
+pub fn convert_quad_from_node(
+    node: &Node,
+    quad: &DOMQuadInit,
+    from: &GeometryNode,
+    options: &ConvertCoordinateOptions,
+) -> Fallible<DomRoot<DOMQuad>> {
+    let transform = node_conversion(geometry_node(from), options.fromBox, node, options.toBox)?;
+    Ok(new_quad(&node.owner_doc().global(), &transform, quad))
+}
+
+/// <https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertrectfromnode>
+pub fn convert_rect_from_node(
+    node: &Node,
+    rect: &DOMRectReadOnly,
+    from: &GeometryNode,
+    options: &ConvertCoordinateOptions,
+) -> Fallible<DomRoot<DOMQuad>> {
+    let transform = node_conversion(geometry_node(from), options.fromBox, node, options.toBox)?;
+    Ok(rect_quad(
+        &node.owner_doc().global(),
+        &transform,
+        rect.X(),
+        rect.Y(),
+        rect.Width(),
+        rect.Height(),
+    ))
+}
+
+/// <https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertpointfromnode>
+pub fn convert_point_from_node(
+    node: &Node,
+    point: &DOMPointInit,
+    from: &GeometryNode,
+    options: &ConvertCoordinateOptions,
+) -> Fallible<DomRoot<DOMPoint>> {
+    let transform = node_conversion(geometry_node(from), options.fromBox, node, options.toBox)?;
+    let [x, y, z, w] = transform_point(&transform, point);
+    Ok(DOMPoint::new(&node.owner_doc().global(), x, y, z, w))
+}
@@ -2,8 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::devtools::{capture_stack_trace, exception_stack_trace};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSourceBinding::EventSourceMethods;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
@@ -37,11 +39,17 @@ use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
 use crate::dom::performanceobserver::VALID_ENTRY_TYPES;
 use crate::dom::promise::Promise;
+use crate::dom::report::Report;
+use crate::dom::reportingobserver::ReportingObserver;
+use crate::dom::websocket::WebSocket;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
+use crate::import_map::ImportMap;
 use crate::microtask::{Microtask, MicrotaskQueue, UserMicrotask};
+use crate::origin_trials::OriginTrials;
 use crate::realms::{enter_realm, InRealm};
+use crate::reporting::{self, ReportBodyData, ReportData};
 use crate::script_module::ModuleTree;
 use crate::script_runtime::{CommonScriptMsg, JSContext as SafeJSContext, ScriptChan, ScriptPort};
 use crate::script_thread::{MainThreadScriptChan, ScriptThread};
@@ -58,10 +66,10 @@ use crate::task_source::TaskSource;
 use crate::task_source::TaskSourceName;
 use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle};
 use crate::timers::{OneshotTimers, TimerCallback};
-use content_security_policy::CspList;
+use content_security_policy::{self as csp, CspList};
 use devtools_traits::{PageError, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, ScriptError};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::{IsWrapper, UnwrapObjectDynamic};
@@ -83,7 +91,9 @@ use net_traits::filemanager_thread::{
     FileManagerResult, FileManagerThreadMsg, ReadFileProgress, RelativePos,
 };
 use net_traits::image_cache::ImageCache;
-use net_traits::{CoreResourceMsg, CoreResourceThread, IpcSend, ResourceThreads};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::ResourceThreads;
+use net_traits::{CoreResourceMsg, CoreResourceThread, IpcSend, ReportingEndpoint};
 use profile_traits::{ipc as profile_ipc, mem as profile_mem, time as profile_time};
 use script_traits::serializable::{BlobData, BlobImpl, FileBlob};
 use script_traits::transferable::MessagePortImpl;
@@ -92,6 +102,7 @@ use script_traits::{
     ScriptToConstellationChan, TimerEvent,
 };
 use script_traits::{TimerEventId, TimerSchedulerMsg, TimerSource};
+use servo_config::{pref, prefs};
 use servo_url::{MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell, RefMut};
@@ -139,6 +150,17 @@ pub struct GlobalScope {
     /// Timers used by the Console API.
     console_timers: DomRefCell<HashMap<DOMString, u64>>,
 
+    /// Counters used by the Console API.
+    console_counters: DomRefCell<HashMap<DOMString, usize>>,
+
+    /// The number of groups of the Console API that the messages are in.
+    console_group_depth: Cell<usize>,
+
+    /// The objects that were sent to the developer tools, by the uuid with
+    /// which their properties can be requested.
+    #[ignore_malloc_size_of = "mozjs"]
+    devtools_objects: DomRefCell<HashMap<String, Box<Heap<*mut JSObject>>>>,
+
     /// module map is used when importing JavaScript modules
     /// https://html.spec.whatwg.org/multipage/#concept-settings-object-module-map
     #[ignore_malloc_size_of = "mozjs"]
@@ -147,6 +169,15 @@ pub struct GlobalScope {
     #[ignore_malloc_size_of = "mozjs"]
     inline_module_map: DomRefCell<HashMap<ScriptId, Rc<ModuleTree>>>,
 
+    /// https://html.spec.whatwg.org/multipage/#concept-global-import-map
+    #[ignore_malloc_size_of = "Defined in indexmap"]
+    import_map: DomRefCell<ImportMap>,
+
+    /// Whether import maps may still be registered, i.e. no module script
+    /// has started loading yet.
+    /// https://wicg.github.io/import-maps/#document-acquiring-import-maps
+    acquiring_import_maps: Cell<bool>,
+
     /// For providing instructions to an optional devtools server.
     #[ignore_malloc_size_of = "channels are hard"]
     devtools_chan: Option<IpcSender<ScriptToDevtoolsControlMsg>>,
@@ -169,6 +200,11 @@ pub struct GlobalScope {
     /// <https://html.spec.whatwg.org/multipage/#in-error-reporting-mode>
     in_error_reporting_mode: Cell<bool>,
 
+    /// Whether the classic script that is being evaluated has muted errors,
+    /// because it was fetched from another origin without CORS.
+    /// <https://html.spec.whatwg.org/multipage/#muted-errors>
+    running_script_muted_errors: Cell<bool>,
+
     /// Associated resource threads for use by DOM objects like XMLHttpRequest,
     /// including resource_thread, filemanager_thread and storage_thread
     resource_threads: ResourceThreads,
@@ -202,6 +238,9 @@ pub struct GlobalScope {
     /// Vector storing references of all eventsources.
     event_source_tracker: DOMTracker<EventSource>,
 
+    /// Vector storing references of all websockets.
+    websocket_tracker: DOMTracker<WebSocket>,
+
     /// Storage for watching rejected promises waiting for some client to
     /// consume their rejection.
     /// Promises in this list have been rejected in the last turn of the
@@ -227,12 +266,35 @@ pub struct GlobalScope {
     /// An optional string allowing the user agent to be set for testing.
     user_agent: Cow<'static, str>,
 
+    /// The user agent that the embedder overrides the one of the webview with.
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    user_agent_override: DomRefCell<Option<UserAgentOverride>>,
+
     #[ignore_malloc_size_of = "defined in wgpu"]
     gpu_id_hub: RefCell<Identities>,
 
     // https://w3c.github.io/performance-timeline/#supportedentrytypes-attribute
     #[ignore_malloc_size_of = "mozjs"]
     frozen_supported_performance_entry_types: DomRefCell<Option<Heap<JSVal>>>,
+
+    /// <https://w3c.github.io/reporting/#reporting-observer-list>
+    reporting_observers: DomRefCell<Vec<Dom<ReportingObserver>>>,
+
+    /// <https://w3c.github.io/reporting/#report-buffer>
+    report_buffer: DomRefCell<Vec<Dom<Report>>>,
+
+    /// The endpoints reports generated in this global can be delivered to.
+    /// <https://w3c.github.io/reporting/#endpoints>
+    reporting_endpoints: DomRefCell<Vec<ReportingEndpoint>>,
+
+    /// Reports waiting to be delivered to their endpoint.
+    pending_reports: DomRefCell<Vec<ReportData>>,
+
+    /// The experimental features enabled for this global only.
+    origin_trials: DomRefCell<OriginTrials>,
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-settings-object-cross-origin-isolated-capability>
+    cross_origin_isolated: Cell<bool>,
 }
 
 /// A wrapper for glue-code between the ipc router and the event-loop.
@@ -560,14 +622,20 @@ impl GlobalScope {
             pipeline_id,
             devtools_wants_updates: Default::default(),
             console_timers: DomRefCell::new(Default::default()),
+            console_counters: DomRefCell::new(Default::default()),
+            console_group_depth: Cell::new(0),
+            devtools_objects: DomRefCell::new(Default::default()),
             module_map: DomRefCell::new(Default::default()),
             inline_module_map: DomRefCell::new(Default::default()),
+            import_map: DomRefCell::new(Default::default()),
+            acquiring_import_maps: Cell::new(true),
             devtools_chan,
             mem_profiler_chan,
             time_profiler_chan,
             script_to_constellation_chan,
             scheduler_chan: scheduler_chan.clone(),
             in_error_reporting_mode: Default::default(),
+            running_script_muted_errors: Default::default(),
             resource_threads,
             timers: OneshotTimers::new(scheduler_chan),
             init_timers: Default::default(),
@@ -576,12 +644,20 @@ impl GlobalScope {
             microtask_queue,
             list_auto_close_worker: Default::default(),
             event_source_tracker: DOMTracker::new(),
+            websocket_tracker: DOMTracker::new(),
             uncaught_rejections: Default::default(),
             consumed_rejections: Default::default(),
             is_headless,
             user_agent,
+            user_agent_override: Default::default(),
             gpu_id_hub: RefCell::new(Identities::new()),
             frozen_supported_performance_entry_types: DomRefCell::new(Default::default()),
+            reporting_observers: Default::default(),
+            report_buffer: Default::default(),
+            reporting_endpoints: Default::default(),
+            pending_reports: Default::default(),
+            origin_trials: Default::default(),
+            cross_origin_isolated: Cell::new(false),
         }
     }
 
@@ -1730,6 +1806,25 @@ impl GlobalScope {
         canceled_any_fetch
     }
 
+    pub fn track_websocket(&self, websocket: &WebSocket) {
+        self.websocket_tracker.track(websocket);
+    }
+
+    /// Makes every websocket of this global disappear, returning whether any
+    /// of them was still connecting or open.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#make-disappear>
+    pub fn close_websockets(&self) -> bool {
+        let mut closed_any_connection = false;
+        self.websocket_tracker
+            .for_each(|websocket: DomRoot<WebSocket>| {
+                if websocket.make_disappear() {
+                    closed_any_connection = true;
+                }
+            });
+        closed_any_connection
+    }
+
     /// Returns the global scope of the realm that the given DOM object's reflector
     /// was created in.
     #[allow(unsafe_code)]
@@ -1827,6 +1922,24 @@ impl GlobalScope {
         &self.inline_module_map
     }
 
+    pub fn import_map(&self) -> &DomRefCell<ImportMap> {
+        &self.import_map
+    }
+
+    pub fn set_import_map(&self, import_map: ImportMap) {
+        *self.import_map.borrow_mut() = import_map;
+    }
+
+    pub fn acquiring_import_maps(&self) -> bool {
+        self.acquiring_import_maps.get()
+    }
+
+    /// Called when a module script starts loading, after which import maps
+    /// can no longer be registered.
+    pub fn stop_acquiring_import_maps(&self) {
+        self.acquiring_import_maps.set(false);
+    }
+
     #[allow(unsafe_code)]
     pub fn get_cx(&self) -> SafeJSContext {
         unsafe { SafeJSContext::from_ptr(Runtime::get()) }
@@ -1866,6 +1979,62 @@ impl GlobalScope {
             .map(|start| timestamp_in_ms(get_time()) - start)
     }
 
+    pub fn time_log(&self, label: &str) -> Result<u64, ()> {
+        self.console_timers
+            .borrow()
+            .get(label)
+            .ok_or(())
+            .map(|start| timestamp_in_ms(get_time()) - start)
+    }
+
+    /// Increments the counter of the Console API with the given label,
+    /// returning its new value.
+    pub fn count(&self, label: DOMString) -> usize {
+        let mut counters = self.console_counters.borrow_mut();
+        let count = counters.entry(label).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    pub fn count_reset(&self, label: &str) -> Result<(), ()> {
+        self.console_counters
+            .borrow_mut()
+            .get_mut(label)
+            .ok_or(())
+            .map(|count| *count = 0)
+    }
+
+    pub fn console_group_depth(&self) -> usize {
+        self.console_group_depth.get()
+    }
+
+    pub fn set_console_group_depth(&self, depth: usize) {
+        self.console_group_depth.set(depth);
+    }
+
+    /// Keeps an object that was sent to the developer tools alive, returning
+    /// the uuid with which it can be found again.
+    pub fn register_devtools_object(&self, object: *mut JSObject) -> String {
+        let mut objects = self.devtools_objects.borrow_mut();
+        if let Some(uuid) = objects
+            .iter()
+            .find(|(_, registered)| registered.get() == object)
+            .map(|(uuid, _)| uuid.clone())
+        {
+            return uuid;
+        }
+        let uuid = Uuid::new_v4().to_string();
+        objects.insert(uuid.clone(), Heap::boxed(object));
+        uuid
+    }
+
+    pub fn devtools_object(&self, uuid: &str) -> Option<*mut JSObject> {
+        self.devtools_objects
+            .borrow()
+            .get(uuid)
+            .map(|object| object.get())
+    }
+
     /// Get an `&IpcSender<ScriptToDevtoolsControlMsg>` to send messages
     /// to the devtools thread when available.
     pub fn devtools_chan(&self) -> Option<&IpcSender<ScriptToDevtoolsControlMsg>> {
@@ -1890,6 +2059,7 @@ impl GlobalScope {
                     exception: true,
                     strict: false,
                     private: false,
+                    stacktrace: capture_stack_trace(self.get_cx()),
                 },
             ));
         }
@@ -1984,6 +2154,7 @@ impl GlobalScope {
     }
 
     /// <https://html.spec.whatwg.org/multipage/#report-the-error>
+    #[allow(unsafe_code)]
     pub fn report_an_error(&self, error_info: ErrorInfo, value: HandleValue) {
         // Step 1.
         if self.in_error_reporting_mode.get() {
@@ -1994,18 +2165,32 @@ impl GlobalScope {
         self.in_error_reporting_mode.set(true);
 
         // Steps 3-6.
-        // FIXME(#13195): muted errors.
-        let event = ErrorEvent::new(
-            self,
-            atom!("error"),
-            EventBubbles::DoesNotBubble,
-            EventCancelable::Cancelable,
-            error_info.message.as_str().into(),
-            error_info.filename.as_str().into(),
-            error_info.lineno,
-            error_info.column,
-            value,
-        );
+        let muted = error_info.muted || self.running_script_muted_errors.get();
+        let event = if muted {
+            ErrorEvent::new(
+                self,
+                atom!("error"),
+                EventBubbles::DoesNotBubble,
+                EventCancelable::Cancelable,
+                "Script error.".into(),
+                "".into(),
+                0,
+                0,
+                HandleValue::null(),
+            )
+        } else {
+            ErrorEvent::new(
+                self,
+                atom!("error"),
+                EventBubbles::DoesNotBubble,
+                EventCancelable::Cancelable,
+                error_info.message.as_str().into(),
+                error_info.filename.as_str().into(),
+                error_info.lineno,
+                error_info.column,
+                value,
+            )
+        };
 
         // Step 7.
         let event_status = event.upcast::<Event>().fire(self.upcast::<EventTarget>());
@@ -2013,6 +2198,18 @@ impl GlobalScope {
         // Step 8.
         self.in_error_reporting_mode.set(false);
 
+        // The embedder is told about every error of the page, even the ones
+        // that the page handles.
+        if let Some(window) = self.downcast::<Window>() {
+            window.send_to_embedder(EmbedderMsg::ReportScriptError(ScriptError {
+                message: error_info.message.clone(),
+                filename: error_info.filename.clone(),
+                line_number: error_info.lineno,
+                column_number: error_info.column,
+                muted,
+            }));
+        }
+
         // Step 9.
         if event_status == EventStatus::NotCanceled {
             // https://html.spec.whatwg.org/multipage/#runtime-script-errors-2
@@ -2036,6 +2233,7 @@ impl GlobalScope {
                             exception: true,
                             strict: false,
                             private: false,
+                            stacktrace: unsafe { exception_stack_trace(*self.get_cx(), value) },
                         },
                     ));
                 }
@@ -2043,6 +2241,11 @@ impl GlobalScope {
         }
     }
 
+    /// Whether the classic script that is being evaluated has muted errors.
+    pub fn running_script_has_muted_errors(&self) -> bool {
+        self.running_script_muted_errors.get()
+    }
+
     /// Get the `&ResourceThreads` for this global scope.
     pub fn resource_threads(&self) -> &ResourceThreads {
         &self.resource_threads
@@ -2126,7 +2329,7 @@ impl GlobalScope {
 
     /// Evaluate JS code on this global scope.
     pub fn evaluate_js_on_global_with_result(&self, code: &str, rval: MutableHandleValue) -> bool {
-        self.evaluate_script_on_global_with_result(code, "", rval, 1)
+        self.evaluate_script_on_global_with_result(code, "", rval, 1, false)
     }
 
     /// Evaluate a JS script on this global scope.
@@ -2137,6 +2340,7 @@ impl GlobalScope {
         filename: &str,
         rval: MutableHandleValue,
         line_number: u32,
+        muted_errors: bool,
     ) -> bool {
         let metadata = profile_time::TimerMetadata {
             url: if filename.is_empty() {
@@ -2159,6 +2363,11 @@ impl GlobalScope {
 
                 let _aes = AutoEntryScript::new(self);
                 let options = CompileOptionsWrapper::new(*cx, filename.as_ptr(), line_number);
+                // The compiled script keeps the flag, and SpiderMonkey marks the
+                // reports of the errors it throws as muted, even from callbacks
+                // that run after the evaluation.
+                unsafe { (*options.ptr)._base.mutedErrors_ = muted_errors };
+                let was_muted = self.running_script_muted_errors.replace(muted_errors);
 
                 debug!("evaluating Dom string");
                 let result = unsafe {
@@ -2175,6 +2384,7 @@ impl GlobalScope {
                     debug!("error evaluating Dom string");
                     unsafe { report_pending_exception(*cx, true, InRealm::Entered(&ar)) };
                 }
+                self.running_script_muted_errors.set(was_muted);
 
                 maybe_resume_unwind();
                 result
@@ -2197,6 +2407,11 @@ impl GlobalScope {
         self.timers.unschedule_callback(handle);
     }
 
+    /// How long until the next timer of this global is due, if there is one.
+    pub fn time_until_next_timer(&self) -> Option<MsDuration> {
+        self.timers.time_until_next_timer()
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#timer-initialisation-steps>
     pub fn set_timeout_or_interval(
         &self,
@@ -2239,14 +2454,18 @@ impl GlobalScope {
         self.timers.suspend();
     }
 
-    pub fn slow_down_timers(&self) {
-        self.timers.slow_down();
+    pub fn slow_down_timers(&self, min_duration: MsDuration) {
+        self.timers.slow_down(min_duration);
     }
 
     pub fn speed_up_timers(&self) {
         self.timers.speed_up();
     }
 
+    pub fn set_timers_in_background(&self, in_background: bool) {
+        self.timers.set_in_background(in_background);
+    }
+
     fn timer_source(&self) -> TimerSource {
         if self.is::<Window>() {
             return TimerSource::FromWindow(self.pipeline_id());
@@ -2429,7 +2648,29 @@ impl GlobalScope {
     }
 
     pub fn get_user_agent(&self) -> Cow<'static, str> {
-        self.user_agent.clone()
+        match *self.user_agent_override.borrow() {
+            Some(UserAgentOverride {
+                user_agent: Some(ref user_agent),
+                ..
+            }) => Cow::Owned(user_agent.clone()),
+            _ => self.user_agent.clone(),
+        }
+    }
+
+    /// The platform that the webview is overridden with, if any.
+    pub fn get_platform(&self) -> Option<String> {
+        self.user_agent_override
+            .borrow()
+            .as_ref()
+            .and_then(|ua_override| ua_override.platform.clone())
+    }
+
+    pub fn user_agent_override(&self) -> Option<UserAgentOverride> {
+        self.user_agent_override.borrow().clone()
+    }
+
+    pub fn set_user_agent_override(&self, ua_override: Option<UserAgentOverride>) {
+        *self.user_agent_override.borrow_mut() = ua_override;
     }
 
     /// https://www.w3.org/TR/CSP/#get-csp-of-object
@@ -2441,11 +2682,149 @@ impl GlobalScope {
         None
     }
 
+    pub fn set_reporting_endpoints(&self, endpoints: Vec<ReportingEndpoint>) {
+        *self.reporting_endpoints.borrow_mut() = endpoints;
+    }
+
+    pub fn set_origin_trials(&self, origin_trials: OriginTrials) {
+        *self.origin_trials.borrow_mut() = origin_trials;
+    }
+
+    pub fn is_cross_origin_isolated(&self) -> bool {
+        self.cross_origin_isolated.get()
+    }
+
+    pub fn set_cross_origin_isolated(&self, cross_origin_isolated: bool) {
+        self.cross_origin_isolated.set(cross_origin_isolated);
+    }
+
+    /// Whether the boolean preference `pref` is set, or an origin trial enables the feature
+    /// behind it for this global.
+    pub fn is_pref_enabled(&self, pref: &str) -> bool {
+        prefs::pref_map().get(pref).as_bool().unwrap_or(false) ||
+            self.origin_trials.borrow().is_enabled(pref)
+    }
+
+    pub fn add_reporting_observer(&self, observer: &ReportingObserver) {
+        let mut observers = self.reporting_observers.borrow_mut();
+        if !observers.iter().any(|o| &**o == observer) {
+            observers.push(Dom::from_ref(observer));
+        }
+    }
+
+    pub fn remove_reporting_observer(&self, observer: &ReportingObserver) {
+        self.reporting_observers
+            .borrow_mut()
+            .retain(|o| &**o != observer);
+    }
+
+    pub fn buffered_reports(&self) -> Vec<DomRoot<Report>> {
+        self.report_buffer
+            .borrow()
+            .iter()
+            .map(|report| DomRoot::from_ref(&**report))
+            .collect()
+    }
+
+    /// Generates a report of type `type_` about this global, hands it to the
+    /// ReportingObservers interested in it, and queues it for delivery to the
+    /// endpoint named `destination`, if there is one.
+    /// <https://w3c.github.io/reporting/#generate-and-queue-a-report>
+    pub fn generate_report(&self, type_: &str, body: ReportBodyData, destination: &str) {
+        if !pref!(dom.reporting.enabled) {
+            return;
+        }
+        let data = ReportData::new(type_, &self.get_url(), body, destination);
+
+        // https://w3c.github.io/reporting/#notify-reporting-observers
+        let report = Report::new(self, &data);
+        let observers: Vec<DomRoot<ReportingObserver>> = self
+            .reporting_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect();
+        for observer in observers {
+            if observer.observes(report.type_()) {
+                observer.queue_report(&report);
+            }
+        }
+        let mut report_buffer = self.report_buffer.borrow_mut();
+        report_buffer.push(Dom::from_ref(&*report));
+        if report_buffer.len() > MAX_REPORT_BUFFER_SIZE {
+            report_buffer.remove(0);
+        }
+
+        if !self
+            .reporting_endpoints
+            .borrow()
+            .iter()
+            .any(|endpoint| endpoint.name == destination)
+        {
+            return;
+        }
+        let mut pending_reports = self.pending_reports.borrow_mut();
+        pending_reports.push(data);
+        if pending_reports.len() > 1 {
+            return;
+        }
+        // Reports generated in the same task are delivered together.
+        let global = Trusted::new(self);
+        let _ = self.dom_manipulation_task_source().queue(
+            task!(deliver_reports: move || {
+                global.root().deliver_pending_reports();
+            }),
+            self,
+        );
+    }
+
+    fn deliver_pending_reports(&self) {
+        let reports = mem::replace(&mut *self.pending_reports.borrow_mut(), vec![]);
+        let endpoints = self.reporting_endpoints.borrow().clone();
+        reporting::deliver_reports(self, &endpoints, reports);
+    }
+
+    /// Reports the use of a deprecated feature, with the identifier `id`.
+    /// <https://wicg.github.io/deprecation-reporting/#queue-deprecation-report>
+    pub fn report_deprecation(&self, id: &str, message: &str) {
+        let body = ReportBodyData::Deprecation(reporting::DeprecationReportData {
+            id: id.to_owned(),
+            message: message.to_owned(),
+            source_file: None,
+            line_number: None,
+            column_number: None,
+        });
+        self.generate_report("deprecation", body, "default");
+    }
+
+    /// Reports violations of the Content Security Policy of this global.
+    /// <https://w3c.github.io/webappsec-csp/#report-violation>
+    pub fn report_csp_violations(&self, violations: Vec<csp::Violation>) {
+        let referrer = self
+            .downcast::<Window>()
+            .map(|window| window.Document().Referrer())
+            .filter(|referrer| !referrer.is_empty())
+            .map(String::from);
+        for violation in violations {
+            warn!(
+                "Content Security Policy violation: {}",
+                violation.directive.name
+            );
+            let body = reporting::csp_violation_report(self, &violation, referrer.clone());
+            let destination = reporting::csp_report_destination(&violation).unwrap_or_default();
+            self.generate_report("csp-violation", body, &destination);
+        }
+    }
+
     pub fn wgpu_id_hub(&self) -> RefMut<Identities> {
         self.gpu_id_hub.borrow_mut()
     }
 }
 
+/// The most reports the report buffer holds.
+/// <https://w3c.github.io/reporting/#report-buffer>
+const MAX_REPORT_BUFFER_SIZE: usize = 100;
+
 fn timestamp_in_ms(time: Timespec) -> u64 {
     (time.sec * 1000 + (time.nsec / 1000000) as i64) as u64
 }
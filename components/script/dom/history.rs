@@ -2,24 +2,29 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding;
-use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::HistoryBinding::{HistoryMethods, ScrollRestoration};
 use crate::dom::bindings::codegen::Bindings::LocationBinding::LocationBinding::LocationMethods;
-use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::structuredclone;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
+use crate::dom::node::{Node, ShadowIncluding};
 use crate::dom::popstateevent::PopStateEvent;
 use crate::dom::window::Window;
 use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
+use euclid::Vector2D;
 use js::jsapi::Heap;
 use js::jsval::{JSVal, NullValue, UndefinedValue};
 use js::rust::HandleValue;
@@ -27,9 +32,11 @@ use msg::constellation_msg::{HistoryStateId, TraversalDirection};
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
 use profile_traits::ipc::channel;
-use script_traits::{ScriptMsg, StructuredSerializedData};
+use script_traits::StructuredSerializedData;
+use script_traits::{PersistedUserState, ScriptMsg, ScrollRestorationMode};
 use servo_url::ServoUrl;
 use std::cell::Cell;
+use webrender_api::units::LayoutPixel;
 
 enum PushOrReplace {
     Push,
@@ -44,6 +51,12 @@ pub struct History {
     #[ignore_malloc_size_of = "mozjs"]
     state: Heap<JSVal>,
     state_id: Cell<Option<HistoryStateId>>,
+    /// The state to persist in the current session history entry when the
+    /// document leaves it, as last reported to the constellation.
+    persisted_user_state: DomRefCell<PersistedUserState>,
+    /// The persisted state of the session history entry of a reloaded
+    /// document, whose scroll positions are restored once it has loaded.
+    user_state_to_restore: DomRefCell<Option<PersistedUserState>>,
 }
 
 impl History {
@@ -55,6 +68,8 @@ impl History {
             window: Dom::from_ref(&window),
             state: state,
             state_id: Cell::new(None),
+            persisted_user_state: DomRefCell::new(PersistedUserState::default()),
+            user_state_to_restore: DomRefCell::new(None),
         }
     }
 
@@ -84,7 +99,12 @@ impl History {
     // https://html.spec.whatwg.org/multipage/#history-traversal
     // Steps 5-16
     #[allow(unsafe_code)]
-    pub fn activate_state(&self, state_id: Option<HistoryStateId>, url: ServoUrl) {
+    pub fn activate_state(
+        &self,
+        state_id: Option<HistoryStateId>,
+        url: ServoUrl,
+        user_state: PersistedUserState,
+    ) {
         // Steps 5
         let document = self.window.Document();
         let old_url = document.url().clone();
@@ -94,8 +114,11 @@ impl History {
         let hash_changed = old_url.fragment() != url.fragment();
 
         // Step 8
+        // The scroll position is the one persisted in the entry, which is
+        // restored below, rather than that of the fragment.
         if let Some(fragment) = url.fragment() {
-            document.check_and_scroll_fragment(fragment);
+            let target = document.find_fragment_node(fragment);
+            document.set_target_element(target.as_deref());
         }
 
         // Step 11
@@ -143,6 +166,8 @@ impl History {
             );
         }
 
+        self.restore_persisted_user_state(user_state);
+
         // Step 16.3
         if hash_changed {
             let event = HashChangeEvent::new(
@@ -159,6 +184,124 @@ impl History {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#restore-persisted-state>
+    fn restore_persisted_user_state(&self, user_state: PersistedUserState) {
+        *self.persisted_user_state.borrow_mut() = user_state.clone();
+        if user_state.scroll_restoration_mode == ScrollRestorationMode::Manual {
+            // The page restores the scroll positions itself, the current ones
+            // are those of the entry.
+            let origin = self.window.current_viewport().origin;
+            self.persist_viewport_scroll_position(Vector2D::new(
+                origin.x.to_f32_px(),
+                origin.y.to_f32_px(),
+            ));
+            return;
+        }
+
+        let position = user_state.viewport_scroll_position;
+        self.window
+            .scroll(position.x as f64, position.y as f64, ScrollBehavior::Auto);
+        let document = self.window.Document();
+        for (path, position) in user_state.element_scroll_positions {
+            let node = match node_at_path(&document, &path) {
+                Some(node) => node,
+                None => continue,
+            };
+            if node.is::<Element>() {
+                self.window.scroll_node(
+                    &node,
+                    position.x as f64,
+                    position.y as f64,
+                    ScrollBehavior::Auto,
+                );
+            }
+        }
+    }
+
+    /// Sets the persisted state of the session history entry that the document
+    /// is the reloaded document of. The scroll restoration mode applies right
+    /// away, the scroll positions once the document has loaded.
+    pub fn set_user_state_to_restore(&self, user_state: PersistedUserState) {
+        self.persisted_user_state
+            .borrow_mut()
+            .scroll_restoration_mode = user_state.scroll_restoration_mode;
+        *self.user_state_to_restore.borrow_mut() = Some(user_state);
+    }
+
+    /// Restores the persisted state set with `set_user_state_to_restore`, now
+    /// that the document has loaded.
+    pub fn restore_user_state_after_load(&self) {
+        let user_state = self.user_state_to_restore.borrow_mut().take();
+        if let Some(user_state) = user_state {
+            self.restore_persisted_user_state(user_state);
+        }
+    }
+
+    /// Records the scroll position of the viewport in the state persisted in
+    /// the current session history entry.
+    pub fn persist_viewport_scroll_position(&self, position: Vector2D<f32, LayoutPixel>) {
+        self.update_persisted_user_state(|user_state| {
+            user_state.viewport_scroll_position = position;
+        });
+    }
+
+    /// Records the scroll positions of all the scrolled elements in the state
+    /// persisted in the current session history entry.
+    pub fn persist_element_scroll_positions(
+        &self,
+        positions: Vec<(DomRoot<Node>, Vector2D<f32, LayoutPixel>)>,
+    ) {
+        let positions = positions
+            .into_iter()
+            .filter_map(|(node, position)| Some((node_path(&node)?, position)))
+            .collect();
+        self.update_persisted_user_state(|user_state| {
+            user_state.element_scroll_positions = positions;
+        });
+    }
+
+    /// Records the scroll position of an element in the state persisted in
+    /// the current session history entry.
+    pub fn persist_element_scroll_position(
+        &self,
+        node: &Node,
+        position: Vector2D<f32, LayoutPixel>,
+    ) {
+        let path = match node_path(node) {
+            Some(path) => path,
+            None => return,
+        };
+        self.update_persisted_user_state(|user_state| {
+            let positions = &mut user_state.element_scroll_positions;
+            positions.retain(|(other_path, _)| *other_path != path);
+            if position != Vector2D::zero() {
+                positions.push((path, position));
+            }
+        });
+    }
+
+    /// Updates the state persisted in the current session history entry, and
+    /// reports it to the constellation if it changed.
+    fn update_persisted_user_state<F>(&self, update: F)
+    where
+        F: FnOnce(&mut PersistedUserState),
+    {
+        let user_state = {
+            let mut user_state = self.persisted_user_state.borrow_mut();
+            let old_user_state = user_state.clone();
+            update(&mut user_state);
+            if *user_state == old_user_state {
+                return;
+            }
+            user_state.clone()
+        };
+        let _ = self
+            .window
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::PersistUserState(user_state));
+    }
+
     pub fn remove_states(&self, states: Vec<HistoryStateId>) {
         let _ = self
             .window
@@ -167,6 +310,28 @@ impl History {
             .send(CoreResourceMsg::RemoveHistoryStates(states));
     }
 
+    /// The URL and history update steps without serialized data, which
+    /// replace the URL of the current entry but keep its state.
+    /// <https://html.spec.whatwg.org/multipage/#url-and-history-update-steps>
+    pub fn replace_url(&self, new_url: ServoUrl) {
+        let state_id = match self.state_id.get() {
+            Some(state_id) => state_id,
+            None => {
+                let state_id = HistoryStateId::new();
+                self.state_id.set(Some(state_id));
+                state_id
+            },
+        };
+        let msg = ScriptMsg::ReplaceHistoryState(state_id, new_url.clone());
+        let _ = self
+            .window
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(msg);
+
+        self.window.Document().set_url(new_url);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history-pushstate
     // https://html.spec.whatwg.org/multipage/#dom-history-replacestate
     fn push_or_replace_state(
@@ -286,6 +451,29 @@ impl History {
     }
 }
 
+/// The indices of a node and its ancestors among their siblings, from the
+/// child of the document down to the node, if the node is in the document.
+fn node_path(node: &Node) -> Option<Vec<u32>> {
+    let mut path = vec![];
+    for ancestor in node.inclusive_ancestors(ShadowIncluding::No) {
+        if ancestor.is::<Document>() {
+            path.reverse();
+            return Some(path);
+        }
+        path.push(ancestor.index());
+    }
+    None
+}
+
+/// The node at a path computed by `node_path`, if there is still one.
+fn node_at_path(document: &Document, path: &[u32]) -> Option<DomRoot<Node>> {
+    let mut node = DomRoot::from_ref(document.upcast::<Node>());
+    for index in path {
+        node = node.children().nth(*index as usize)?;
+    }
+    Some(node)
+}
+
 impl HistoryMethods for History {
     // https://html.spec.whatwg.org/multipage/#dom-history-state
     fn GetState(&self, _cx: JSContext) -> Fallible<JSVal> {
@@ -311,6 +499,34 @@ impl HistoryMethods for History {
         Ok(recv.recv().unwrap())
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn GetScrollRestoration(&self) -> Fallible<ScrollRestoration> {
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        Ok(
+            match self.persisted_user_state.borrow().scroll_restoration_mode {
+                ScrollRestorationMode::Auto => ScrollRestoration::Auto,
+                ScrollRestorationMode::Manual => ScrollRestoration::Manual,
+            },
+        )
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn SetScrollRestoration(&self, value: ScrollRestoration) -> ErrorResult {
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        let mode = match value {
+            ScrollRestoration::Auto => ScrollRestorationMode::Auto,
+            ScrollRestoration::Manual => ScrollRestorationMode::Manual,
+        };
+        self.update_persisted_user_state(|user_state| {
+            user_state.scroll_restoration_mode = mode;
+        });
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history-go
     fn Go(&self, delta: i32) -> ErrorResult {
         let direction = if delta > 0 {
@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::HTMLAllCollectionBinding;
+use crate::dom::bindings::codegen::Bindings::HTMLAllCollectionBinding::HTMLAllCollectionMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLCollectionBinding::HTMLCollectionMethods;
+use crate::dom::bindings::codegen::UnionTypes::HTMLCollectionOrElement;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::node::{window_from_node, Node};
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+/// The collection of all the elements of a document, which is what
+/// `document.all` returns.
+#[dom_struct]
+pub struct HTMLAllCollection {
+    reflector_: Reflector,
+    collection: Dom<HTMLCollection>,
+}
+
+impl HTMLAllCollection {
+    fn new_inherited(collection: &HTMLCollection) -> HTMLAllCollection {
+        HTMLAllCollection {
+            reflector_: Reflector::new(),
+            collection: Dom::from_ref(collection),
+        }
+    }
+
+    pub fn new(window: &Window, document: &Document) -> DomRoot<HTMLAllCollection> {
+        #[derive(JSTraceable, MallocSizeOf)]
+        struct AllElementsFilter;
+        impl CollectionFilter for AllElementsFilter {
+            fn filter(&self, _elem: &Element, _root: &Node) -> bool {
+                true
+            }
+        }
+
+        let collection =
+            HTMLCollection::create(window, document.upcast(), Box::new(AllElementsFilter));
+        reflect_dom_object(
+            Box::new(HTMLAllCollection::new_inherited(&collection)),
+            window,
+            HTMLAllCollectionBinding::Wrap,
+        )
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-get-all-named>
+    fn all_named_elements(&self, name: DOMString) -> Option<HTMLCollectionOrElement> {
+        #[derive(JSTraceable, MallocSizeOf)]
+        struct AllNamedElementFilter {
+            name: Atom,
+        }
+        impl CollectionFilter for AllNamedElementFilter {
+            fn filter(&self, elem: &Element, _root: &Node) -> bool {
+                is_all_named_element(elem, &self.name)
+            }
+        }
+
+        // Step 1.
+        if name.is_empty() {
+            return None;
+        }
+
+        // Step 2.
+        let name = Atom::from(name);
+        let mut elements = self
+            .collection
+            .elements_iter()
+            .filter(|elem| is_all_named_element(elem, &name));
+        let first = elements.next()?;
+
+        // Step 3.
+        if elements.next().is_none() {
+            return Some(HTMLCollectionOrElement::Element(first));
+        }
+
+        // Step 5.
+        let root = self.collection.root_node();
+        let filter = AllNamedElementFilter { name: name };
+        Some(HTMLCollectionOrElement::HTMLCollection(
+            HTMLCollection::create(&window_from_node(&*root), &root, Box::new(filter)),
+        ))
+    }
+}
+
+/// Whether an element is an "all"-named element with the given name, or has
+/// it as its id.
+/// <https://html.spec.whatwg.org/multipage/#all-named-elements>
+fn is_all_named_element(elem: &Element, name: &Atom) -> bool {
+    if elem.get_id().map_or(false, |id| id == *name) {
+        return true;
+    }
+    if *elem.namespace() != ns!(html) {
+        return false;
+    }
+    match *elem.local_name() {
+        local_name!("a") |
+        local_name!("button") |
+        local_name!("embed") |
+        local_name!("form") |
+        local_name!("frame") |
+        local_name!("frameset") |
+        local_name!("iframe") |
+        local_name!("img") |
+        local_name!("input") |
+        local_name!("map") |
+        local_name!("meta") |
+        local_name!("object") |
+        local_name!("select") |
+        local_name!("textarea") => elem.get_name().map_or(false, |n| n == *name),
+        _ => false,
+    }
+}
+
+impl HTMLAllCollectionMethods for HTMLAllCollection {
+    // https://html.spec.whatwg.org/multipage/#dom-htmlallcollection-length
+    fn Length(&self) -> u32 {
+        self.collection.Length()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-htmlallcollection-item
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<Element>> {
+        self.collection.Item(index)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-htmlallcollection-nameditem
+    fn NamedItem(&self, name: DOMString) -> Option<HTMLCollectionOrElement> {
+        self.all_named_elements(name)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-htmlallcollection-nameditem
+    fn NamedGetter(&self, name: DOMString) -> Option<HTMLCollectionOrElement> {
+        self.all_named_elements(name)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-htmlallcollection-item
+    fn Item(&self, name_or_index: Option<DOMString>) -> Option<HTMLCollectionOrElement> {
+        // Step 1.
+        let name_or_index = name_or_index?;
+
+        // Step 2.
+        // https://html.spec.whatwg.org/multipage/#concept-get-all-indexed-or-named
+        match name_or_index.parse::<u32>() {
+            Ok(index) if index != u32::max_value() && index.to_string() == *name_or_index => self
+                .collection
+                .Item(index)
+                .map(HTMLCollectionOrElement::Element),
+            _ => self.all_named_elements(name_or_index),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#htmlallcollection
+    fn SupportedPropertyNames(&self) -> Vec<DOMString> {
+        let mut result: Vec<DOMString> = vec![];
+        for elem in self.collection.elements_iter() {
+            let names = elem.get_id().into_iter().chain(
+                elem.get_name()
+                    .filter(|name| is_all_named_element(&elem, name)),
+            );
+            for name in names {
+                let name = DOMString::from(&*name);
+                if !name.is_empty() && !result.contains(&name) {
+                    result.push(name);
+                }
+            }
+        }
+        result
+    }
+}
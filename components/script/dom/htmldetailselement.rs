@@ -12,7 +12,7 @@ use crate::dom::document::Document;
 use crate::dom::element::AttributeMutation;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{window_from_node, Node, NodeDamage};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
@@ -70,6 +70,9 @@ impl VirtualMethods for HTMLDetailsElement {
         self.super_type().unwrap().attribute_mutated(attr, mutation);
 
         if attr.local_name() == &local_name!("open") {
+            // The content of the details is only laid out while it is open.
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+
             let counter = self.toggle_counter.get() + 1;
             self.toggle_counter.set(counter);
 
@@ -2,24 +2,34 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::document::{Document, FocusType};
+use crate::dom::element::{AttributeMutation, Element};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{
+    document_from_node, window_from_node, Node, ShadowIncluding, UnbindContext,
+};
+use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
 use html5ever::{LocalName, Prefix};
+use std::cell::Cell;
+use style::element_state::ElementState;
 
 #[dom_struct]
 pub struct HTMLDialogElement {
     htmlelement: HTMLElement,
     return_value: DomRefCell<DOMString>,
+    /// <https://html.spec.whatwg.org/multipage/#is-modal>
+    is_modal: Cell<bool>,
 }
 
 impl HTMLDialogElement {
@@ -31,6 +41,7 @@ impl HTMLDialogElement {
         HTMLDialogElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             return_value: DomRefCell::new(DOMString::new()),
+            is_modal: Cell::new(false),
         }
     }
 
@@ -48,6 +59,44 @@ impl HTMLDialogElement {
             HTMLDialogElementBinding::Wrap,
         )
     }
+
+    /// Removes the dialog from the top layer of its document, which makes the
+    /// rest of the document interactive again.
+    fn leave_top_layer(&self) {
+        if !self.is_modal.get() {
+            return;
+        }
+        self.is_modal.set(false);
+        self.upcast::<Element>()
+            .set_state(ElementState::IN_MODAL_STATE, false);
+        document_from_node(self).remove_modal_dialog(self);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dialog-focusing-steps>
+    fn run_focusing_steps(&self) {
+        let control = self
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .skip(1)
+            .filter_map(DomRoot::downcast::<Element>)
+            .find(|element| element.is_focusable_area());
+        let document = document_from_node(self);
+        document.begin_focus_transaction();
+        if let Some(control) = control {
+            document.request_focus(&control);
+        }
+        document.commit_focus_transaction(FocusType::Element);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#cancel-dialog>
+    pub fn cancel(&self) {
+        let event = self
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(atom!("cancel"));
+        if !event.DefaultPrevented() {
+            self.Close(None);
+        }
+    }
 }
 
 impl HTMLDialogElementMethods for HTMLDialogElement {
@@ -57,6 +106,38 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
     // https://html.spec.whatwg.org/multipage/#dom-dialog-open
     make_bool_setter!(SetOpen, "open");
 
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-show
+    fn Show(&self) {
+        // Step 1
+        if self.Open() {
+            return;
+        }
+
+        // Step 2
+        self.SetOpen(true);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-showmodal
+    fn ShowModal(&self) -> ErrorResult {
+        // Step 1 & 2
+        if self.Open() || !self.upcast::<Node>().is_connected() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3
+        self.SetOpen(true);
+
+        // Step 4 & 5 & 6
+        self.is_modal.set(true);
+        self.upcast::<Element>()
+            .set_state(ElementState::IN_MODAL_STATE, true);
+        document_from_node(self).add_modal_dialog(self);
+
+        // Step 7
+        self.run_focusing_steps();
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-dialog-returnvalue
     fn ReturnValue(&self) -> DOMString {
         let return_value = self.return_value.borrow();
@@ -87,7 +168,8 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             *self.return_value.borrow_mut() = new_value;
         }
 
-        // TODO: Step 4 implement pending dialog stack removal
+        // Step 4
+        self.leave_top_layer();
 
         // Step 5
         win.task_manager()
@@ -95,3 +177,24 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             .queue_simple_event(target, atom!("close"), &win);
     }
 }
+
+impl VirtualMethods for HTMLDialogElement {
+    fn super_type(&self) -> Option<&dyn VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+
+        if attr.local_name() == &local_name!("open") && mutation.is_removal() {
+            self.leave_top_layer();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        // https://html.spec.whatwg.org/multipage/#the-dialog-element:removing-steps
+        self.leave_top_layer();
+    }
+}
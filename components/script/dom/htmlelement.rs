@@ -2,9 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::activation::Activatable;
 use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::OnErrorEventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::HTMLDetailsElementBinding::HTMLDetailsElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLLabelElementBinding::HTMLLabelElementMethods;
@@ -20,9 +22,11 @@ use crate::dom::document::{Document, FocusType};
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::domstringmap::DOMStringMap;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlbodyelement::HTMLBodyElement;
 use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmldetailselement::HTMLDetailsElement;
 use crate::dom::htmlframesetelement::HTMLFrameSetElement;
 use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
@@ -30,6 +34,7 @@ use crate::dom::htmllabelelement::HTMLLabelElement;
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
 use crate::dom::node::{document_from_node, window_from_node};
 use crate::dom::node::{BindContext, Node, NodeFlags, ShadowIncluding};
+use crate::dom::stylepropertymap::StylePropertyMap;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
@@ -45,6 +50,7 @@ use style::element_state::*;
 pub struct HTMLElement {
     element: Element,
     style_decl: MutNullableDom<CSSStyleDeclaration>,
+    attribute_style_map: MutNullableDom<StylePropertyMap>,
     dataset: MutNullableDom<DOMStringMap>,
 }
 
@@ -72,6 +78,7 @@ impl HTMLElement {
                 document,
             ),
             style_decl: Default::default(),
+            attribute_style_map: Default::default(),
             dataset: Default::default(),
         }
     }
@@ -125,16 +132,14 @@ impl HTMLElement {
                 },
                 _ => {
                     if let Some(attr) = element.get_attribute(&ns!(), &local_name!("draggable")) {
-                        let value = attr.value();
-                        let is_true = match *value {
-                            AttrValue::String(ref string) => string == "true",
-                            _ => false,
-                        };
+                        let is_true = &**attr.value() == "true";
                         node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, is_true);
                     } else {
                         node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, false);
                     }
-                    //TODO set SEQUENTIALLY_FOCUSABLE flag if editing host
+                    if element.is_editing_host() || self.is_summary_for_its_parent_details() {
+                        node.set_flag(NodeFlags::SEQUENTIALLY_FOCUSABLE, true);
+                    }
                     //TODO set SEQUENTIALLY_FOCUSABLE flag if "sorting interface th elements"
                 },
             }
@@ -142,6 +147,27 @@ impl HTMLElement {
     }
 }
 
+impl HTMLElement {
+    /// <https://html.spec.whatwg.org/multipage/#summary-for-its-parent-details>
+    pub fn is_summary_for_its_parent_details(&self) -> bool {
+        if self.upcast::<Element>().local_name() != &local_name!("summary") {
+            return false;
+        }
+        let parent = match self.upcast::<Node>().GetParentNode() {
+            Some(parent) => parent,
+            None => return false,
+        };
+        if !parent.is::<HTMLDetailsElement>() {
+            return false;
+        }
+        parent
+            .children()
+            .filter_map(DomRoot::downcast::<HTMLElement>)
+            .find(|child| child.upcast::<Element>().local_name() == &local_name!("summary"))
+            .map_or(false, |summary| &*summary == self)
+    }
+}
+
 impl HTMLElementMethods for HTMLElement {
     // https://html.spec.whatwg.org/multipage/#the-style-attribute
     fn Style(&self) -> DomRoot<CSSStyleDeclaration> {
@@ -156,6 +182,12 @@ impl HTMLElementMethods for HTMLElement {
         })
     }
 
+    // https://drafts.css-houdini.org/css-typed-om-1/#dom-elementcssinlinestyle-attributestylemap
+    fn AttributeStyleMap(&self) -> DomRoot<StylePropertyMap> {
+        self.attribute_style_map
+            .or_init(|| StylePropertyMap::new_for_element(&window_from_node(self), self.upcast()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#attr-title
     make_getter!(Title, "title");
     // https://html.spec.whatwg.org/multipage/#attr-title
@@ -413,6 +445,10 @@ impl HTMLElementMethods for HTMLElement {
         // TODO: Mark the element as locked for focus and run the focusing steps.
         // https://html.spec.whatwg.org/multipage/#focusing-steps
         let document = document_from_node(self);
+        // Inert elements can't be focused.
+        if document.is_inert(self.upcast()) {
+            return;
+        }
         document.begin_focus_transaction();
         document.request_focus(self.upcast());
         document.commit_focus_transaction(FocusType::Element);
@@ -431,6 +467,18 @@ impl HTMLElementMethods for HTMLElement {
         document.commit_focus_transaction(FocusType::Element);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn Draggable(&self) -> bool {
+        self.is_draggable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn SetDraggable(&self, draggable: bool) {
+        let value = if draggable { "true" } else { "false" };
+        self.upcast::<Element>()
+            .set_string_attribute(&local_name!("draggable"), DOMString::from(value));
+    }
+
     // https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsetparent
     fn GetOffsetParent(&self) -> Option<DomRoot<Element>> {
         if self.is::<HTMLBodyElement>() || self.is::<HTMLHtmlElement>() {
@@ -570,6 +618,40 @@ impl HTMLElementMethods for HTMLElement {
             },
         );
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    fn ContentEditable(&self) -> DOMString {
+        let element = self.upcast::<Element>();
+        let value = element
+            .get_attribute(&ns!(), &local_name!("contenteditable"))
+            .map(|attr| attr.value().to_ascii_lowercase());
+        DOMString::from(match value.as_deref() {
+            Some("") | Some("true") => "true",
+            Some("false") => "false",
+            Some("plaintext-only") => "plaintext-only",
+            _ => "inherit",
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    fn SetContentEditable(&self, value: DOMString) -> ErrorResult {
+        let element = self.upcast::<Element>();
+        let value = value.to_ascii_lowercase();
+        match &*value {
+            "inherit" => {
+                element.remove_attribute(&ns!(), &local_name!("contenteditable"));
+            },
+            "true" | "false" | "plaintext-only" => element
+                .set_string_attribute(&local_name!("contenteditable"), DOMString::from(value)),
+            _ => return Err(Error::Syntax),
+        };
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-iscontenteditable
+    fn IsContentEditable(&self) -> bool {
+        self.upcast::<Node>().editing_host().is_some()
+    }
 }
 
 fn append_text_node_to_fragment(document: &Document, fragment: &DocumentFragment, text: String) {
@@ -674,6 +756,24 @@ impl HTMLElement {
             .remove_attribute(&ns!(), &local_name);
     }
 
+    // https://html.spec.whatwg.org/multipage/#the-draggable-attribute
+    pub fn is_draggable(&self) -> bool {
+        let element = self.upcast::<Element>();
+        if let Some(attr) = element.get_attribute(&ns!(), &local_name!("draggable")) {
+            match &*attr.value().to_ascii_lowercase() {
+                "true" => return true,
+                "false" => return false,
+                _ => {},
+            }
+        }
+        // In the auto state, images and links are draggable.
+        match *element.local_name() {
+            local_name!("img") => true,
+            local_name!("a") => element.has_attribute(&local_name!("href")),
+            _ => false,
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#category-label
     pub fn is_labelable_element(&self) -> bool {
         // Note: HTMLKeygenElement is omitted because Servo doesn't currently implement it
@@ -817,6 +917,27 @@ impl HTMLElement {
     }
 }
 
+impl Activatable for HTMLElement {
+    fn as_element(&self) -> &Element {
+        self.upcast::<Element>()
+    }
+
+    fn is_instance_activatable(&self) -> bool {
+        self.is_summary_for_its_parent_details()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#the-summary-element:activation-behaviour
+    fn activation_behavior(&self, _event: &Event, _target: &EventTarget) {
+        let details = self
+            .upcast::<Node>()
+            .GetParentNode()
+            .and_then(DomRoot::downcast::<HTMLDetailsElement>);
+        if let Some(details) = details {
+            details.SetOpen(!details.Open());
+        }
+    }
+}
+
 impl VirtualMethods for HTMLElement {
     fn super_type(&self) -> Option<&dyn VirtualMethods> {
         Some(self.upcast::<Element>() as &dyn VirtualMethods)
@@ -836,6 +957,7 @@ impl VirtualMethods for HTMLElement {
                     DOMString::from(&**attr.value()),
                 );
             },
+            (&local_name!("contenteditable"), _) => self.update_sequentially_focusable_status(),
             _ => {},
         }
     }
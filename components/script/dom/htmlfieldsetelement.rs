@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFieldSetElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLFieldSetElementBinding::HTMLFieldSetElementMethods;
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -10,11 +11,13 @@ use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element};
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
 use crate::dom::htmllegendelement::HTMLLegendElement;
 use crate::dom::node::{window_from_node, Node, ShadowIncluding};
+use crate::dom::validation::{fieldsets_validity_changed, report_validation_problem, Validatable};
 use crate::dom::validitystate::ValidityState;
 use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
@@ -26,6 +29,7 @@ use style::element_state::ElementState;
 pub struct HTMLFieldSetElement {
     htmlelement: HTMLElement,
     form_owner: MutNullableDom<HTMLFormElement>,
+    validity_state: MutNullableDom<ValidityState>,
 }
 
 impl HTMLFieldSetElement {
@@ -36,12 +40,13 @@ impl HTMLFieldSetElement {
     ) -> HTMLFieldSetElement {
         HTMLFieldSetElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE,
+                ElementState::IN_ENABLED_STATE | ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
             ),
             form_owner: Default::default(),
+            validity_state: Default::default(),
         }
     }
 
@@ -59,6 +64,39 @@ impl HTMLFieldSetElement {
             HTMLFieldSetElementBinding::Wrap,
         )
     }
+
+    /// Statically validates the constraints of the listed elements among the
+    /// descendants of the fieldset, like for a form. The error holds the
+    /// invalid elements of which the `invalid` event wasn't canceled.
+    /// <https://html.spec.whatwg.org/multipage/#statically-validate-the-constraints>
+    fn static_validation(&self) -> Result<(), Vec<DomRoot<Element>>> {
+        // Steps 1-3.
+        let invalid_controls = self
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .skip(1)
+            .filter_map(DomRoot::downcast::<Element>)
+            .filter(|element| {
+                element.as_maybe_validatable().map_or(false, |validatable| {
+                    validatable.is_instance_validatable() && !validatable.is_valid()
+                })
+            })
+            .collect::<Vec<_>>();
+        // Step 4.
+        if invalid_controls.is_empty() {
+            return Ok(());
+        }
+        // Steps 5-7.
+        Err(invalid_controls
+            .into_iter()
+            .filter(|element| {
+                let event = element
+                    .upcast::<EventTarget>()
+                    .fire_cancelable_event(atom!("invalid"));
+                !event.DefaultPrevented()
+            })
+            .collect())
+    }
 }
 
 impl HTMLFieldSetElementMethods for HTMLFieldSetElement {
@@ -77,10 +115,45 @@ impl HTMLFieldSetElementMethods for HTMLFieldSetElement {
         HTMLCollection::create(&window, self.upcast(), filter)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.is_instance_validatable()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-cva-validity
     fn Validity(&self) -> DomRoot<ValidityState> {
-        let window = window_from_node(self);
-        ValidityState::new(&window, self.upcast())
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        // The fieldset is barred from constraint validation, so the listed
+        // elements that it contains are checked instead.
+        self.static_validation().is_ok()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        let unhandled_invalid_controls = match self.static_validation() {
+            Ok(()) => return true,
+            Err(controls) => controls,
+        };
+        if let Some(element) = unhandled_invalid_controls.first() {
+            if let Some(validatable) = element.as_maybe_validatable() {
+                report_validation_problem(element, validatable.validation_message());
+            }
+        }
+        false
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-fieldset-disabled
@@ -152,19 +225,21 @@ impl VirtualMethods for HTMLFieldSetElement {
                             _ => false,
                         })
                 });
-                if disabled_state {
-                    for field in fields {
-                        let el = field.downcast::<Element>().unwrap();
+                for field in fields {
+                    let el = field.downcast::<Element>().unwrap();
+                    if disabled_state {
                         el.set_disabled_state(true);
                         el.set_enabled_state(false);
-                    }
-                } else {
-                    for field in fields {
-                        let el = field.downcast::<Element>().unwrap();
+                    } else {
                         el.check_disabled_attribute();
                         el.check_ancestors_disabled_state_for_form_control();
                     }
+                    // Disabled controls are barred from constraint validation.
+                    if let Some(validatable) = el.as_maybe_validatable() {
+                        validatable.set_validity_states();
+                    }
                 }
+                fieldsets_validity_changed(node);
             },
             &local_name!("form") => {
                 self.form_attribute_mutated(mutation);
@@ -174,6 +249,36 @@ impl VirtualMethods for HTMLFieldSetElement {
     }
 }
 
+impl Validatable for HTMLFieldSetElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state(&self) -> DomRoot<ValidityState> {
+        self.validity_state
+            .or_init(|| ValidityState::new(&window_from_node(self), self.upcast()))
+    }
+
+    fn is_instance_validatable(&self) -> bool {
+        // https://html.spec.whatwg.org/multipage/#the-fieldset-element%3Abarred-from-constraint-validation
+        false
+    }
+
+    fn set_validity_states(&self) {
+        // https://html.spec.whatwg.org/multipage/#selector-valid
+        // https://html.spec.whatwg.org/multipage/#selector-invalid
+        let invalid = self
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .skip(1)
+            .filter_map(DomRoot::downcast::<Element>)
+            .any(|element| element.state().contains(ElementState::IN_INVALID_STATE));
+        let element = self.upcast::<Element>();
+        element.set_state(ElementState::IN_VALID_STATE, !invalid);
+        element.set_state(ElementState::IN_INVALID_STATE, invalid);
+    }
+}
+
 impl FormControl for HTMLFieldSetElement {
     fn form_owner(&self) -> Option<DomRoot<HTMLFormElement>> {
         self.form_owner.get()
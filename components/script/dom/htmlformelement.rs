@@ -49,7 +49,7 @@ use crate::dom::node::{UnbindContext, VecPreOrderInsertionHelper};
 use crate::dom::nodelist::{NodeList, RadioListMode};
 use crate::dom::radionodelist::RadioNodeList;
 use crate::dom::submitevent::SubmitEvent;
-use crate::dom::validitystate::ValidationFlags;
+use crate::dom::validation::report_validation_problem;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
 use crate::task_source::TaskSource;
@@ -248,6 +248,16 @@ impl HTMLFormElementMethods for HTMLFormElement {
         self.reset(ResetFrom::FromForm);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-form-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.static_validation().is_ok()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-form-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.interactive_validation().is_ok()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-form-elements
     fn Elements(&self) -> DomRoot<HTMLFormControlsCollection> {
         #[derive(JSTraceable, MallocSizeOf)]
@@ -859,12 +869,20 @@ impl HTMLFormElement {
     /// <https://html.spec.whatwg.org/multipage/#interactively-validate-the-constraints>
     fn interactive_validation(&self) -> Result<(), ()> {
         // Step 1-3
-        let _unhandled_invalid_controls = match self.static_validation() {
+        let unhandled_invalid_controls = match self.static_validation() {
             Ok(()) => return Ok(()),
             Err(err) => err,
         };
-        // TODO: Report the problems with the constraints of at least one of
-        //       the elements given in unhandled invalid controls to the user
+        // Report the problem of the first unhandled invalid control to the
+        // user, which focuses it.
+        if let Some(element) = unhandled_invalid_controls
+            .first()
+            .and_then(|field| field.as_event_target().downcast::<Element>())
+        {
+            if let Some(validatable) = element.as_maybe_validatable() {
+                report_validation_problem(element, validatable.validation_message());
+            }
+        }
         // Step 4
         Err(())
     }
@@ -889,7 +907,7 @@ impl HTMLFormElement {
                         };
                         if !validatable.is_instance_validatable() {
                             None
-                        } else if validatable.validate(ValidationFlags::empty()) {
+                        } else if validatable.is_valid() {
                             None
                         } else {
                             Some(FormSubmittableElement::from_element(&el))
@@ -15,7 +15,9 @@ use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::Document;
 use crate::dom::domtokenlist::DOMTokenList;
-use crate::dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
+use crate::dom::element::RawLayoutElementHelpers;
+use crate::dom::element::{reflect_lazy_loading_attribute, set_lazy_loading_attribute};
+use crate::dom::element::{will_lazy_load_element, AttributeMutation, Element};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
@@ -81,6 +83,10 @@ pub struct HTMLIFrameElement {
     sandbox_allowance: Cell<Option<SandboxAllowance>>,
     load_blocker: DomRefCell<Option<LoadBlocker>>,
     visibility: Cell<bool>,
+    /// The navigation that waits for the element to get near the viewport.
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    #[ignore_malloc_size_of = "Defined in script_traits"]
+    lazy_load_resumption_steps: DomRefCell<Option<LoadData>>,
 }
 
 impl HTMLIFrameElement {
@@ -231,6 +237,7 @@ impl HTMLIFrameElement {
 
     /// <https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes>
     fn process_the_iframe_attributes(&self, mode: ProcessingMode) {
+        self.cancel_lazy_load();
         if self
             .upcast::<Element>()
             .has_attribute(&local_name!("srcdoc"))
@@ -330,6 +337,18 @@ impl HTMLIFrameElement {
             document.get_referrer_policy(),
         );
 
+        // https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes step 2.6
+        if will_lazy_load_element(self.upcast()) {
+            *self.lazy_load_resumption_steps.borrow_mut() = Some(load_data);
+            document.start_intersection_observing_a_lazy_loading_element(self.upcast());
+            return;
+        }
+        self.navigate_to_processed_url(load_data);
+    }
+
+    /// Navigates the nested browsing context to the URL of the src attribute,
+    /// replacing its initial `about:blank` page if that is the current one.
+    fn navigate_to_processed_url(&self, load_data: LoadData) {
         let pipeline_id = self.pipeline_id();
         // If the initial `about:blank` page is the current page, load with replacement enabled,
         // see https://html.spec.whatwg.org/multipage/#the-iframe-element:about:blank-3
@@ -343,6 +362,23 @@ impl HTMLIFrameElement {
         self.navigate_or_reload_child_browsing_context(load_data, NavigationType::Regular, replace);
     }
 
+    /// Starts the navigation that waited for the element to get near the
+    /// viewport, or for its loading attribute to stop being lazy.
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    pub fn resume_lazy_load(&self) {
+        let steps = self.lazy_load_resumption_steps.borrow_mut().take();
+        if let Some(load_data) = steps {
+            self.navigate_to_processed_url(load_data);
+        }
+    }
+
+    /// Forgets the navigation that waited for the element to get near the
+    /// viewport, if any.
+    fn cancel_lazy_load(&self) {
+        self.lazy_load_resumption_steps.borrow_mut().take();
+        document_from_node(self).stop_intersection_observing_a_lazy_loading_element(self.upcast());
+    }
+
     fn create_nested_browsing_context(&self) {
         // Synchronously create a new context and navigate it to about:blank.
         let url = ServoUrl::parse("about:blank").unwrap();
@@ -415,6 +451,7 @@ impl HTMLIFrameElement {
             sandbox_allowance: Cell::new(None),
             load_blocker: DomRefCell::new(None),
             visibility: Cell::new(true),
+            lazy_load_resumption_steps: DomRefCell::new(None),
         }
     }
 
@@ -465,8 +502,21 @@ impl HTMLIFrameElement {
         // TODO A cross-origin child document would not be easily accessible
         //      from this script thread. It's unclear how to implement
         //      steps 2, 3, and 5 efficiently in this case.
-        // TODO Step 2 - check child document `mute iframe load` flag
-        // TODO Step 3 - set child document  `mut iframe load` flag
+        let child_document = self.GetContentDocument();
+
+        // Step 2
+        if child_document
+            .as_ref()
+            .map_or(false, |document| document.mute_iframe_load())
+        {
+            LoadBlocker::terminate(&mut self.load_blocker.borrow_mut());
+            return;
+        }
+
+        // Step 3
+        if let Some(ref document) = child_document {
+            document.set_iframe_load_in_progress(true);
+        }
 
         // Step 4
         self.upcast::<EventTarget>().fire_event(atom!("load"));
@@ -474,7 +524,10 @@ impl HTMLIFrameElement {
         let mut blocker = self.load_blocker.borrow_mut();
         LoadBlocker::terminate(&mut blocker);
 
-        // TODO Step 5 - unset child document `mut iframe load` flag
+        // Step 5
+        if let Some(ref document) = child_document {
+            document.set_iframe_load_in_progress(false);
+        }
 
         let window = window_from_node(self);
         window.reflow(ReflowGoal::Full, ReflowReason::IFrameLoadEvent);
@@ -588,6 +641,16 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
     // https://html.spec.whatwg.org/multipage/#dom-dim-height
     make_dimension_setter!(SetHeight, "height");
 
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-loading
+    fn Loading(&self) -> DOMString {
+        reflect_lazy_loading_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-loading
+    fn SetLoading(&self, value: DOMString) {
+        set_lazy_loading_attribute(self.upcast::<Element>(), value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#other-elements,-attributes-and-apis:attr-iframe-frameborder
     make_getter!(FrameBorder, "frameborder");
     // https://html.spec.whatwg.org/multipage/#other-elements,-attributes-and-apis:attr-iframe-frameborder
@@ -660,6 +723,14 @@ impl VirtualMethods for HTMLIFrameElement {
                     self.process_the_iframe_attributes(ProcessingMode::NotFirstTime);
                 }
             },
+            // https://html.spec.whatwg.org/multipage/#attr-iframe-loading
+            name if &**name == "loading" => {
+                if !will_lazy_load_element(self.upcast()) {
+                    document_from_node(self)
+                        .stop_intersection_observing_a_lazy_loading_element(self.upcast());
+                    self.resume_lazy_load();
+                }
+            },
             _ => {},
         }
     }
@@ -705,6 +776,7 @@ impl VirtualMethods for HTMLIFrameElement {
 
         let mut blocker = self.load_blocker.borrow_mut();
         LoadBlocker::terminate(&mut blocker);
+        self.cancel_lazy_load();
 
         // https://html.spec.whatwg.org/multipage/#a-browsing-context-is-discarded
         let window = window_from_node(self);
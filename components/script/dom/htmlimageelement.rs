@@ -13,7 +13,7 @@ use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageE
 use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
-use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
@@ -21,7 +21,10 @@ use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::Document;
 use crate::dom::element::{cors_setting_for_element, referrer_policy_for_element};
+use crate::dom::element::{fetch_priority_for_element, reflect_fetch_priority_attribute};
 use crate::dom::element::{reflect_cross_origin_attribute, set_cross_origin_attribute};
+use crate::dom::element::{reflect_lazy_loading_attribute, set_lazy_loading_attribute};
+use crate::dom::element::{set_fetch_priority_attribute, will_lazy_load_element};
 use crate::dom::element::{
     AttributeMutation, CustomElementCreationMode, Element, ElementCreator, RawLayoutElementHelpers,
 };
@@ -40,6 +43,7 @@ use crate::dom::node::{
     document_from_node, window_from_node, BindContext, Node, NodeDamage, ShadowIncluding,
 };
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::promise::Promise;
 use crate::dom::values::UNSIGNED_LONG_MAX;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
@@ -47,6 +51,7 @@ use crate::fetch::create_a_potential_cors_request;
 use crate::image_listener::{add_cache_listener_for_element, ImageCacheListener};
 use crate::microtask::{Microtask, MicrotaskRunnable};
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::realms::InRealm;
 use crate::script_thread::ScriptThread;
 use crate::task_source::TaskSource;
 use app_units::{Au, AU_PER_PX};
@@ -70,19 +75,18 @@ use servo_url::origin::ImmutableOrigin;
 use servo_url::origin::MutableOrigin;
 use servo_url::ServoUrl;
 use std::cell::Cell;
-use std::char;
 use std::collections::HashSet;
 use std::default::Default;
 use std::i32;
+use std::iter::Peekable;
 use std::mem;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use style::attr::{
-    parse_double, parse_length, parse_unsigned_integer, AttrValue, LengthOrPercentageOrAuto,
-};
+use style::attr::{parse_length, AttrValue, LengthOrPercentageOrAuto};
 use style::context::QuirksMode;
 use style::media_queries::MediaList;
 use style::parser::ParserContext;
-use style::str::is_ascii_digit;
+use style::str::HTML_SPACE_CHARACTERS;
 use style::stylesheets::{CssRuleType, Origin};
 use style::values::specified::length::{Length, NoCalcLength};
 use style::values::specified::{source_size_list::SourceSizeList, AbsoluteLength};
@@ -94,6 +98,17 @@ enum ParseState {
     AfterDescriptor,
 }
 
+/// The image types that can be decoded, which a `<source>` of a `<picture>`
+/// can select with its `type` attribute.
+const SUPPORTED_IMAGE_TYPES: &[&str] = &[
+    "image/bmp",
+    "image/gif",
+    "image/jpeg",
+    "image/png",
+    "image/vnd.microsoft.icon",
+    "image/x-icon",
+];
+
 pub struct SourceSet {
     image_sources: Vec<ImageSource>,
     source_size: SourceSizeList,
@@ -158,6 +173,15 @@ pub struct HTMLImageElement {
     #[ignore_malloc_size_of = "SourceSet"]
     source_set: DomRefCell<SourceSet>,
     last_selected_source: DomRefCell<Option<USVString>>,
+    /// The URL, source and pixel density of the request that waits for the
+    /// element to get near the viewport.
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    #[ignore_malloc_size_of = "ServoUrl"]
+    lazy_load_resumption_steps: DomRefCell<Option<(ServoUrl, USVString, f64)>>,
+    /// Promises returned by `decode()` which wait for the current request to
+    /// become completely available or broken.
+    #[ignore_malloc_size_of = "promises are hard"]
+    pending_decode_promises: DomRefCell<Vec<Rc<Promise>>>,
 }
 
 impl HTMLImageElement {
@@ -394,7 +418,8 @@ impl HTMLImageElement {
             } else {
                 FromPictureOrSrcSet::No
             },
-        );
+        )
+        .priority(fetch_priority_for_element(self.upcast()));
 
         // This is a background load because the load blocker already fulfills the
         // purpose of delaying the document's load event.
@@ -467,12 +492,14 @@ impl HTMLImageElement {
             // TODO: https://html.spec.whatwg.org/multipage/#fire-a-progress-event-or-event
             self.upcast::<EventTarget>().fire_event(atom!("load"));
             self.upcast::<EventTarget>().fire_event(atom!("loadend"));
+            self.settle_pending_decode_promises(Ok(()));
         }
 
         // Fire image.onerror
         if trigger_image_error {
             self.upcast::<EventTarget>().fire_event(atom!("error"));
             self.upcast::<EventTarget>().fire_event(atom!("loadend"));
+            self.settle_pending_decode_promises(Err(Error::Encoding));
         }
 
         // Trigger reflow
@@ -506,6 +533,19 @@ impl HTMLImageElement {
         };
     }
 
+    /// Resolves or rejects the promises returned by `decode()` since the
+    /// current request last settled.
+    #[allow(unrooted_must_root)]
+    fn settle_pending_decode_promises(&self, result: ErrorResult) {
+        let promises = mem::replace(&mut *self.pending_decode_promises.borrow_mut(), vec![]);
+        for promise in promises {
+            match result {
+                Ok(ref value) => promise.resolve_native(value),
+                Err(ref error) => promise.reject_error(error.clone()),
+            }
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#abort-the-image-request>
     fn abort_request(&self, state: State, request: ImageRequestPhase) {
         let mut request = match request {
@@ -634,14 +674,8 @@ impl HTMLImageElement {
 
             // Step 4.8
             if let Some(x) = element.get_attribute(&ns!(), &local_name!("type")) {
-                // TODO Handle unsupported mime type
-                let mime = x.value().parse::<Mime>();
-                match mime {
-                    Ok(m) => match m.type_() {
-                        mime::IMAGE => (),
-                        _ => continue,
-                    },
-                    _ => continue,
+                if !is_supported_image_type(&x.value()) {
+                    continue;
                 }
             }
 
@@ -836,15 +870,36 @@ impl HTMLImageElement {
         self.fetch_image(&url);
     }
 
+    /// Starts the request that waited for the element to get near the
+    /// viewport, or for its loading attribute to stop being lazy.
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>
+    pub fn resume_lazy_load(&self) {
+        let steps = self.lazy_load_resumption_steps.borrow_mut().take();
+        if let Some((url, src, pixel_density)) = steps {
+            self.prepare_image_request(&url, &src, pixel_density);
+        }
+    }
+
+    /// Forgets the request that waited for the element to get near the
+    /// viewport, if any, since a new one is about to be selected.
+    fn cancel_lazy_load(&self) {
+        self.lazy_load_resumption_steps.borrow_mut().take();
+        document_from_node(self).stop_intersection_observing_a_lazy_loading_element(self.upcast());
+    }
+
     /// Step 8-12 of html.spec.whatwg.org/multipage/#update-the-image-data
     fn update_the_image_data_sync_steps(&self) {
+        self.cancel_lazy_load();
         let document = document_from_node(self);
         let window = document.window();
         let task_source = window.task_manager().dom_manipulation_task_source();
         let this = Trusted::new(self);
         let (src, pixel_density) = match self.select_image_source() {
             // Step 8
-            Some(data) => data,
+            Some(data) => {
+                *self.last_selected_source.borrow_mut() = Some(data.0.clone());
+                data
+            },
             None => {
                 self.abort_request(State::Broken, ImageRequestPhase::Current);
                 self.abort_request(State::Broken, ImageRequestPhase::Pending);
@@ -865,6 +920,7 @@ impl HTMLImageElement {
                         if src_present || Self::uses_srcset_or_picture(elem) {
                             this.upcast::<EventTarget>().fire_event(atom!("error"));
                         }
+                        this.settle_pending_decode_promises(Err(Error::Encoding));
                     }),
                     window.upcast(),
                 );
@@ -877,6 +933,12 @@ impl HTMLImageElement {
         let parsed_url = base_url.join(&src.0);
         match parsed_url {
             Ok(url) => {
+                // https://html.spec.whatwg.org/multipage/#update-the-image-data step 24
+                if will_lazy_load_element(self.upcast()) {
+                    *self.lazy_load_resumption_steps.borrow_mut() = Some((url, src, pixel_density));
+                    document.start_intersection_observing_a_lazy_loading_element(self.upcast());
+                    return;
+                }
                 // Step 13-17
                 self.prepare_image_request(&url, &src, pixel_density);
             },
@@ -895,7 +957,7 @@ impl HTMLImageElement {
                             current_request.source_url = Some(USVString(src))
                         }
                         this.upcast::<EventTarget>().fire_event(atom!("error"));
-
+                        this.settle_pending_decode_promises(Err(Error::Encoding));
                     }),
                     window.upcast(),
                 );
@@ -919,6 +981,10 @@ impl HTMLImageElement {
             current_request.state = State::Unavailable;
         }
 
+        // https://html.spec.whatwg.org/multipage/#dom-img-decode
+        // Pending decodes of the previous image are rejected.
+        self.settle_pending_decode_promises(Err(Error::Encoding));
+
         if !document.is_active() {
             // Step 1 (if the document is inactive)
             // TODO: use GlobalScope::enqueue_microtask,
@@ -985,6 +1051,7 @@ impl HTMLImageElement {
                             }
                             // TODO: restart animation, if set.
                             this.upcast::<EventTarget>().fire_event(atom!("load"));
+                            this.settle_pending_decode_promises(Ok(()));
                         }),
                         window.upcast(),
                     );
@@ -1204,6 +1271,7 @@ impl HTMLImageElement {
 
                 // Step 15.7
                 this.upcast::<EventTarget>().fire_event(atom!("load"));
+                this.settle_pending_decode_promises(Ok(()));
             }),
             window.upcast(),
         );
@@ -1249,6 +1317,8 @@ impl HTMLImageElement {
             form_owner: Default::default(),
             generation: Default::default(),
             source_set: DomRefCell::new(SourceSet::new()),
+            lazy_load_resumption_steps: DomRefCell::new(None),
+            pending_decode_promises: DomRefCell::new(vec![]),
             last_selected_source: DomRefCell::new(None),
         }
     }
@@ -1480,9 +1550,15 @@ impl HTMLImageElementMethods for HTMLImageElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-img-srcset
     make_url_getter!(Srcset, "srcset");
-    // https://html.spec.whatwg.org/multipage/#dom-img-src
+    // https://html.spec.whatwg.org/multipage/#dom-img-srcset
     make_url_setter!(SetSrcset, "srcset");
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-sizes
+    make_getter!(Sizes, "sizes");
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-sizes
+    make_setter!(SetSizes, "sizes");
+
     // https://html.spec.whatwg.org/multipage/#dom-img-crossOrigin
     fn GetCrossOrigin(&self) -> Option<DOMString> {
         reflect_cross_origin_attribute(self.upcast::<Element>())
@@ -1493,6 +1569,44 @@ impl HTMLImageElementMethods for HTMLImageElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-decoding
+    fn Decoding(&self) -> DOMString {
+        let mut val = self
+            .upcast::<Element>()
+            .get_string_attribute(&LocalName::from("decoding"));
+        val.make_ascii_lowercase();
+        match &*val {
+            "sync" | "async" | "auto" => val,
+            _ => DOMString::from("auto"),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-decoding
+    fn SetDecoding(&self, value: DOMString) {
+        self.upcast::<Element>()
+            .set_string_attribute(&LocalName::from("decoding"), value);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    fn Loading(&self) -> DOMString {
+        reflect_lazy_loading_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    fn SetLoading(&self, value: DOMString) {
+        set_lazy_loading_attribute(self.upcast::<Element>(), value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
     make_getter!(UseMap, "usemap");
     // https://html.spec.whatwg.org/multipage/#dom-img-usemap
@@ -1572,6 +1686,31 @@ impl HTMLImageElementMethods for HTMLImageElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-decode
+    fn Decode(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+
+        // Step 2.1.
+        if !document_from_node(self).is_fully_active() {
+            promise.reject_error(Error::Encoding);
+            return promise;
+        }
+
+        // Images are decoded off the main thread by the image cache before
+        // the response reaches the element, so a completely available image
+        // is already decoded.
+        match self.current_request.borrow().state {
+            State::CompletelyAvailable => promise.resolve_native(&()),
+            State::Broken => promise.reject_error(Error::Encoding),
+            State::PartiallyAvailable | State::Unavailable => {
+                self.pending_decode_promises
+                    .borrow_mut()
+                    .push(promise.clone());
+            },
+        }
+        promise
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-img-currentsrc
     fn CurrentSrc(&self) -> USVString {
         let current_request = self.current_request.borrow();
@@ -1643,6 +1782,14 @@ impl VirtualMethods for HTMLImageElement {
             &local_name!("width") |
             &local_name!("crossorigin") |
             &local_name!("sizes") => self.update_the_image_data(),
+            // https://html.spec.whatwg.org/multipage/#attr-img-loading
+            name if &**name == "loading" => {
+                if !will_lazy_load_element(self.upcast()) {
+                    document_from_node(self)
+                        .stop_intersection_observing_a_lazy_loading_element(self.upcast());
+                    self.resume_lazy_load();
+                }
+            },
             _ => {},
         }
     }
@@ -1705,6 +1852,9 @@ impl VirtualMethods for HTMLImageElement {
         let document = document_from_node(self);
         if context.tree_connected {
             document.register_responsive_image(self);
+            if self.lazy_load_resumption_steps.borrow().is_some() {
+                document.start_intersection_observing_a_lazy_loading_element(self.upcast());
+            }
         }
 
         // The element is inserted into a picture parent element
@@ -1720,6 +1870,7 @@ impl VirtualMethods for HTMLImageElement {
         self.super_type().unwrap().unbind_from_tree(context);
         let document = document_from_node(self);
         document.unregister_responsive_image(self);
+        document.stop_intersection_observing_a_lazy_loading_element(self.upcast());
 
         // The element is removed from a picture parent element
         // https://html.spec.whatwg.org/multipage/#relevant-mutations
@@ -1778,182 +1929,187 @@ fn image_dimension_setter(element: &Element, attr: LocalName, value: u32) {
     element.set_attribute(&attr, value);
 }
 
-/// Collect sequence of code points
-pub fn collect_sequence_characters<F>(s: &str, predicate: F) -> (&str, &str)
-where
-    F: Fn(&char) -> bool,
-{
-    for (i, ch) in s.chars().enumerate() {
-        if !predicate(&ch) {
-            return (&s[0..i], &s[i..]);
-        }
-    }
+/// Whether the `type` attribute of a `<source>` is an image type that can be
+/// decoded, ignoring its parameters. A missing or empty type matches any
+/// image.
+fn is_supported_image_type(value: &str) -> bool {
+    let essence = value
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim_matches(HTML_SPACE_CHARACTERS)
+        .to_ascii_lowercase();
+    essence.is_empty() || SUPPORTED_IMAGE_TYPES.contains(&&*essence)
+}
 
-    return (s, "");
+fn is_ascii_whitespace(c: &char) -> bool {
+    HTML_SPACE_CHARACTERS.contains(c)
 }
 
 /// Parse an `srcset` attribute - https://html.spec.whatwg.org/multipage/#parsing-a-srcset-attribute.
 pub fn parse_a_srcset_attribute(input: &str) -> Vec<ImageSource> {
-    let mut url_len = 0;
-    let mut candidates: Vec<ImageSource> = vec![];
-    while url_len < input.len() {
-        let position = &input[url_len..];
-        let (spaces, position) =
-            collect_sequence_characters(position, |c| *c == ',' || char::is_whitespace(*c));
-        // add the length of the url that we parse to advance the start index
-        let space_len = spaces.char_indices().count();
-        url_len += space_len;
-        if position.is_empty() {
+    // Step 1-2
+    let mut chars = input.chars().peekable();
+    let mut candidates = vec![];
+    loop {
+        // Step 3-4
+        while chars
+            .peek()
+            .map_or(false, |c| *c == ',' || is_ascii_whitespace(c))
+        {
+            chars.next();
+        }
+        // Step 5
+        if chars.peek().is_none() {
             return candidates;
         }
-        let (url, spaces) = collect_sequence_characters(position, |c| !char::is_whitespace(*c));
-        // add the counts of urls that we parse to advance the start index
-        url_len += url.chars().count();
-        let comma_count = url.chars().rev().take_while(|c| *c == ',').count();
-        let url: String = url
-            .chars()
-            .take(url.chars().count() - comma_count)
-            .collect();
-        // add 1 to start index, for the comma
-        url_len += comma_count + 1;
-        let (space, position) = collect_sequence_characters(spaces, |c| char::is_whitespace(*c));
-        let space_len = space.len();
-        url_len += space_len;
-        let mut descriptors = Vec::new();
-        let mut current_descriptor = String::new();
-        let mut state = ParseState::InDescriptor;
-        let mut char_stream = position.chars().enumerate();
-        let mut buffered: Option<(usize, char)> = None;
-        loop {
-            let next_char = buffered.take().or_else(|| char_stream.next());
-            if next_char.is_some() {
-                url_len += 1;
+        // Step 6
+        let mut url = String::new();
+        while let Some(c) = chars.peek().cloned() {
+            if is_ascii_whitespace(&c) {
+                break;
             }
-            match state {
-                ParseState::InDescriptor => match next_char {
-                    Some((_, ' ')) => {
-                        if !current_descriptor.is_empty() {
-                            descriptors.push(current_descriptor.clone());
-                            current_descriptor = String::new();
-                            state = ParseState::AfterDescriptor;
-                        }
-                        continue;
-                    },
-                    Some((_, ',')) => {
-                        if !current_descriptor.is_empty() {
-                            descriptors.push(current_descriptor.clone());
-                        }
-                        break;
-                    },
-                    Some((_, c @ '(')) => {
-                        current_descriptor.push(c);
-                        state = ParseState::InParens;
-                        continue;
-                    },
-                    Some((_, c)) => {
-                        current_descriptor.push(c);
-                    },
-                    None => {
-                        if !current_descriptor.is_empty() {
-                            descriptors.push(current_descriptor.clone());
-                        }
-                        break;
-                    },
+            url.push(c);
+            chars.next();
+        }
+        // Step 7-8
+        let descriptors = if url.ends_with(',') {
+            let len = url.trim_end_matches(',').len();
+            url.truncate(len);
+            vec![]
+        } else {
+            tokenize_descriptors(&mut chars)
+        };
+        // Step 9-15
+        if let Some(descriptor) = parse_descriptors(&descriptors) {
+            candidates.push(ImageSource { url, descriptor });
+        }
+        // Step 16
+    }
+}
+
+/// Step 8 of https://html.spec.whatwg.org/multipage/#parsing-a-srcset-attribute
+fn tokenize_descriptors<I>(chars: &mut Peekable<I>) -> Vec<String>
+where
+    I: Iterator<Item = char>,
+{
+    // Step 8.1
+    while chars.peek().map_or(false, is_ascii_whitespace) {
+        chars.next();
+    }
+    // Step 8.2-8.3
+    let mut descriptors = vec![];
+    let mut current_descriptor = String::new();
+    let mut state = ParseState::InDescriptor;
+    // Step 8.4
+    loop {
+        match state {
+            ParseState::InDescriptor => match chars.next() {
+                Some(c) if is_ascii_whitespace(&c) => {
+                    if !current_descriptor.is_empty() {
+                        descriptors.push(mem::replace(&mut current_descriptor, String::new()));
+                    }
+                    state = ParseState::AfterDescriptor;
                 },
-                ParseState::InParens => match next_char {
-                    Some((_, c @ ')')) => {
-                        current_descriptor.push(c);
-                        state = ParseState::InDescriptor;
-                        continue;
-                    },
-                    Some((_, c)) => {
-                        current_descriptor.push(c);
-                        continue;
-                    },
-                    None => {
-                        if !current_descriptor.is_empty() {
-                            descriptors.push(current_descriptor.clone());
-                        }
-                        break;
-                    },
+                Some(',') | None => {
+                    if !current_descriptor.is_empty() {
+                        descriptors.push(current_descriptor);
+                    }
+                    return descriptors;
                 },
-                ParseState::AfterDescriptor => match next_char {
-                    Some((_, ' ')) => {
-                        state = ParseState::AfterDescriptor;
-                        continue;
-                    },
-                    Some((idx, c)) => {
-                        state = ParseState::InDescriptor;
-                        buffered = Some((idx, c));
-                        continue;
-                    },
-                    None => {
-                        if !current_descriptor.is_empty() {
-                            descriptors.push(current_descriptor.clone());
-                        }
-                        break;
-                    },
+                Some('(') => {
+                    current_descriptor.push('(');
+                    state = ParseState::InParens;
                 },
-            }
+                Some(c) => current_descriptor.push(c),
+            },
+            ParseState::InParens => match chars.next() {
+                Some(')') => {
+                    current_descriptor.push(')');
+                    state = ParseState::InDescriptor;
+                },
+                Some(c) => current_descriptor.push(c),
+                None => {
+                    descriptors.push(current_descriptor);
+                    return descriptors;
+                },
+            },
+            ParseState::AfterDescriptor => match chars.peek().cloned() {
+                Some(c) if is_ascii_whitespace(&c) => {
+                    chars.next();
+                },
+                Some(_) => state = ParseState::InDescriptor,
+                None => return descriptors,
+            },
         }
+    }
+}
 
-        let mut error = false;
-        let mut width: Option<u32> = None;
-        let mut density: Option<f64> = None;
-        let mut future_compat_h: Option<u32> = None;
-        for descriptor in descriptors {
-            let (digits, remaining) =
-                collect_sequence_characters(&descriptor, |c| is_ascii_digit(c) || *c == '.');
-            let valid_non_negative_integer = parse_unsigned_integer(digits.chars());
-            let has_w = remaining == "w";
-            let valid_floating_point = parse_double(digits);
-            let has_x = remaining == "x";
-            let has_h = remaining == "h";
-            if valid_non_negative_integer.is_ok() && has_w {
-                let result = valid_non_negative_integer;
-                error = result.is_err();
+/// Step 9-14 of https://html.spec.whatwg.org/multipage/#parsing-a-srcset-attribute,
+/// which return `None` when there is a parse error.
+fn parse_descriptors(descriptors: &[String]) -> Option<Descriptor> {
+    let mut width = None;
+    let mut density = None;
+    let mut future_compat_h = None;
+    // Step 13
+    for descriptor in descriptors {
+        let (value, kind) = match descriptor.char_indices().last() {
+            Some((index, kind)) => (&descriptor[..index], kind),
+            None => return None,
+        };
+        match kind {
+            // Step 13.1
+            'w' => {
                 if width.is_some() || density.is_some() {
-                    error = true;
+                    return None;
                 }
-                if let Ok(w) = result {
-                    width = Some(w);
+                match parse_valid_non_negative_integer(value) {
+                    Some(w) if w > 0 => width = Some(w),
+                    _ => return None,
                 }
-            } else if valid_floating_point.is_ok() && has_x {
-                let result = valid_floating_point;
-                error = result.is_err();
+            },
+            // Step 13.2
+            'x' => {
                 if width.is_some() || density.is_some() || future_compat_h.is_some() {
-                    error = true;
+                    return None;
                 }
-                if let Ok(x) = result {
-                    density = Some(x);
+                let value = DOMString::from(value);
+                if !value.is_valid_floating_point_number_string() {
+                    return None;
                 }
-            } else if valid_non_negative_integer.is_ok() && has_h {
-                let result = valid_non_negative_integer;
-                error = result.is_err();
-                if density.is_some() || future_compat_h.is_some() {
-                    error = true;
+                match value.parse_floating_point_number() {
+                    Ok(x) if x >= 0. => density = Some(x),
+                    _ => return None,
                 }
-                if let Ok(h) = result {
-                    future_compat_h = Some(h);
+            },
+            // Step 13.3
+            'h' => {
+                if future_compat_h.is_some() || density.is_some() {
+                    return None;
                 }
-            } else {
-                error = true;
-            }
-        }
-        if future_compat_h.is_some() && width.is_none() {
-            error = true;
-        }
-        if !error {
-            let descriptor = Descriptor {
-                wid: width,
-                den: density,
-            };
-            let image_source = ImageSource {
-                url: url,
-                descriptor: descriptor,
-            };
-            candidates.push(image_source);
+                match parse_valid_non_negative_integer(value) {
+                    Some(h) if h > 0 => future_compat_h = Some(h),
+                    _ => return None,
+                }
+            },
+            // Step 13.4
+            _ => return None,
         }
     }
-    candidates
+    // Step 14
+    if future_compat_h.is_some() && width.is_none() {
+        return None;
+    }
+    Some(Descriptor {
+        wid: width,
+        den: density,
+    })
+}
+
+/// https://html.spec.whatwg.org/multipage/#valid-non-negative-integer
+fn parse_valid_non_negative_integer(value: &str) -> Option<u32> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
 }
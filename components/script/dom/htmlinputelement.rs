@@ -11,14 +11,17 @@ use crate::dom::bindings::codegen::Bindings::FileListBinding::FileListMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
 use crate::dom::element::{
     AttributeMutation, Element, LayoutElementHelpers, RawLayoutElementHelpers,
 };
@@ -34,6 +37,7 @@ use crate::dom::htmlformelement::{
     FormControl, FormDatum, FormDatumValue, FormSubmitter, HTMLFormElement,
 };
 use crate::dom::htmlformelement::{ResetFrom, SubmittedFrom};
+use crate::dom::htmloptionelement::HTMLOptionElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::node::{document_from_node, window_from_node};
@@ -42,10 +46,11 @@ use crate::dom::node::{
 };
 use crate::dom::nodelist::NodeList;
 use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
-use crate::dom::validation::Validatable;
-use crate::dom::validitystate::ValidationFlags;
+use crate::dom::validation::{fieldsets_validity_changed, Validatable};
+use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::script_runtime::JSContext as SafeJSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
 use crate::textinput::KeyReaction::{
     DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction,
 };
@@ -53,13 +58,17 @@ use crate::textinput::Lines::Single;
 use crate::textinput::{Direction, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes};
 use chrono::naive::{NaiveDate, NaiveDateTime};
 use chrono::{Datelike, Weekday};
+use cssparser::RGBA;
 use dom_struct::dom_struct;
-use embedder_traits::FilterPattern;
+use embedder_traits::{EmbedderMsg, FilterPattern};
 use encoding_rs::Encoding;
 use html5ever::{LocalName, Prefix};
+use ipc_channel::ipc::IpcReceiver;
+use ipc_channel::router::ROUTER;
 use js::jsapi::{
     ClippedTime, DateGetMsecSinceEpoch, Handle, JSObject, NewDateObject, ObjectIsDate,
 };
+use keyboard_types::Key;
 use msg::constellation_msg::InputMethodType;
 use net_traits::blob_url_store::get_blob_origin;
 use net_traits::filemanager_thread::FileManagerThreadMsg;
@@ -138,6 +147,39 @@ impl InputType {
         self.is_textual() || *self == InputType::Password
     }
 
+    // https://html.spec.whatwg.org/multipage/#date-state-(type=date)
+    // https://html.spec.whatwg.org/multipage/#time-state-(type=time)
+    fn is_date_or_time(&self) -> bool {
+        match *self {
+            InputType::Date |
+            InputType::DatetimeLocal |
+            InputType::Month |
+            InputType::Time |
+            InputType::Week => true,
+
+            _ => false,
+        }
+    }
+
+    /// Whether the value is picked with a picker of the embedder.
+    fn has_picker(&self) -> bool {
+        self.is_date_or_time() || *self == InputType::Color
+    }
+
+    /// Whether the suggestions of the list of the input are shown in a
+    /// dropdown while the user types its value.
+    fn has_suggestions_dropdown(&self) -> bool {
+        match *self {
+            InputType::Email |
+            InputType::Search |
+            InputType::Tel |
+            InputType::Text |
+            InputType::Url => true,
+
+            _ => false,
+        }
+    }
+
     fn to_str(&self) -> &str {
         match *self {
             InputType::Button => "button",
@@ -256,6 +298,9 @@ pub struct HTMLInputElement {
     filelist: MutNullableDom<FileList>,
     form_owner: MutNullableDom<HTMLFormElement>,
     labels_node_list: MutNullableDom<NodeList>,
+    validity_state: MutNullableDom<ValidityState>,
+    /// Whether the embedder shows the dropdown of the suggestions of the list.
+    suggestions_shown: Cell<bool>,
 }
 
 #[derive(JSTraceable)]
@@ -286,7 +331,9 @@ impl HTMLInputElement {
             .clone();
         HTMLInputElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE | ElementState::IN_READ_WRITE_STATE,
+                ElementState::IN_ENABLED_STATE |
+                    ElementState::IN_READ_WRITE_STATE |
+                    ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -310,6 +357,8 @@ impl HTMLInputElement {
             filelist: MutNullableDom::new(None),
             form_owner: Default::default(),
             labels_node_list: MutNullableDom::new(None),
+            validity_state: Default::default(),
+            suggestions_shown: Cell::new(false),
         }
     }
 
@@ -394,6 +443,11 @@ impl HTMLInputElement {
         self.input_type.get()
     }
 
+    /// Whether the text dropped on the input is inserted in its value.
+    pub fn accepts_text_drop(&self) -> bool {
+        self.input_type().is_textual_or_password() && self.upcast::<Element>().read_write_state()
+    }
+
     pub fn disable_sanitization(&self) {
         self.sanitization_flag.set(false);
     }
@@ -435,6 +489,9 @@ impl HTMLInputElement {
             .upcast::<Element>()
             .get_attribute(&ns!(), &local_name!("step"))
         {
+            if attr.value().eq_ignore_ascii_case("any") {
+                return None;
+            }
             if let Ok(step) = DOMString::from(attr.summarize().value).parse_floating_point_number()
             {
                 if step > 0.0 {
@@ -680,6 +737,91 @@ impl HTMLInputElement {
         self.SetValueAsNumber(value)
     }
 
+    // https://html.spec.whatwg.org/multipage/#have-range
+    fn has_reversed_range(&self) -> bool {
+        // Only the time state has a periodic domain.
+        match (self.input_type(), self.minimum(), self.maximum()) {
+            (InputType::Time, Some(min), Some(max)) => min > max,
+            _ => false,
+        }
+    }
+
+    fn value_as_number_for_validation(&self) -> Option<f64> {
+        if !self.does_value_as_number_apply() {
+            return None;
+        }
+        self.convert_string_to_number(&self.Value()).ok()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-being-missing
+    fn suffers_from_being_missing(&self) -> bool {
+        match self.input_type() {
+            // https://html.spec.whatwg.org/multipage/#checkbox-state-(type=checkbox):suffering-from-being-missing
+            InputType::Checkbox => self.Required() && !self.Checked(),
+            // https://html.spec.whatwg.org/multipage/#radio-button-state-(type=radio):suffering-from-being-missing
+            InputType::Radio => {
+                let group = self.radio_group_name();
+                radio_group_iter(self, group.as_ref()).any(|input| input.Required()) &&
+                    radio_group_iter(self, group.as_ref()).all(|input| !input.Checked())
+            },
+            // https://html.spec.whatwg.org/multipage/#file-upload-state-(type=file):suffering-from-being-missing
+            InputType::File => {
+                self.Required() &&
+                    self.filelist
+                        .get()
+                        .map_or(true, |files| files.Length() == 0)
+            },
+            // The required attribute doesn't apply to these states.
+            InputType::Color |
+            InputType::Hidden |
+            InputType::Range |
+            InputType::Submit |
+            InputType::Image |
+            InputType::Reset |
+            InputType::Button => false,
+            // https://html.spec.whatwg.org/multipage/#the-required-attribute:suffering-from-being-missing
+            _ => self.Required() && self.is_mutable() && self.Value().is_empty(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-an-underflow
+    pub fn suffers_from_range_underflow(&self) -> bool {
+        let value = match self.value_as_number_for_validation() {
+            Some(value) => value,
+            None => return false,
+        };
+        match (self.minimum(), self.maximum()) {
+            (Some(min), Some(max)) if self.has_reversed_range() => value > max && value < min,
+            (Some(min), _) => value < min,
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-an-overflow
+    pub fn suffers_from_range_overflow(&self) -> bool {
+        let value = match self.value_as_number_for_validation() {
+            Some(value) => value,
+            None => return false,
+        };
+        match (self.minimum(), self.maximum()) {
+            (Some(min), Some(max)) if self.has_reversed_range() => value > max && value < min,
+            (_, Some(max)) => value > max,
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-a-step-mismatch
+    pub fn suffers_from_step_mismatch(&self) -> bool {
+        let value = match self.value_as_number_for_validation() {
+            Some(value) => value,
+            None => return false,
+        };
+        match self.allowed_value_step() {
+            Some(allowed_value_step) => (value - self.step_base()) % allowed_value_step != 0.0,
+            None => false,
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#concept-input-list
     fn suggestions_source_element(&self) -> Option<DomRoot<HTMLElement>> {
         let list_string = self
@@ -718,6 +860,8 @@ pub trait LayoutHTMLInputElementHelpers {
     unsafe fn checked_state_for_layout(self) -> bool;
     #[allow(unsafe_code)]
     unsafe fn indeterminate_state_for_layout(self) -> bool;
+    #[allow(unsafe_code)]
+    unsafe fn color_for_layout(self) -> Option<RGBA>;
 }
 
 #[allow(unsafe_code)]
@@ -743,6 +887,8 @@ impl LayoutHTMLInputElementHelpers for LayoutDom<HTMLInputElement> {
         match (*self.unsafe_get()).input_type() {
             InputType::Checkbox | InputType::Radio => String::new(),
             InputType::File | InputType::Image => String::new(),
+            // The value of color inputs is shown as a swatch.
+            InputType::Color => String::new(),
             InputType::Button => get_raw_attr_value(self, ""),
             InputType::Submit => get_raw_attr_value(self, DEFAULT_SUBMIT_VALUE),
             InputType::Reset => get_raw_attr_value(self, DEFAULT_RESET_VALUE),
@@ -814,6 +960,27 @@ impl LayoutHTMLInputElementHelpers for LayoutDom<HTMLInputElement> {
             .get_state_for_layout()
             .contains(ElementState::IN_INDETERMINATE_STATE)
     }
+
+    #[allow(unrooted_must_root)]
+    #[allow(unsafe_code)]
+    unsafe fn color_for_layout(self) -> Option<RGBA> {
+        let input = &*self.unsafe_get();
+        if input.input_type() != InputType::Color {
+            return None;
+        }
+        let value = String::from((*input.textinput.borrow_for_layout()).get_content());
+        parse_simple_color(&value)
+    }
+}
+
+/// Parses a lowercase simple color in the `#rrggbb` form.
+/// <https://html.spec.whatwg.org/multipage/#rules-for-parsing-simple-colour-values>
+fn parse_simple_color(value: &str) -> Option<RGBA> {
+    if value.len() != 7 || !value.starts_with('#') {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(value.get(i..i + 2)?, 16).ok();
+    Some(RGBA::new(channel(1)?, channel(3)?, channel(5)?, 255))
 }
 
 impl TextControlElement for HTMLInputElement {
@@ -1015,6 +1182,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
             },
         }
 
+        self.update_validity_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         Ok(())
     }
@@ -1186,6 +1354,18 @@ impl HTMLInputElementMethods for HTMLInputElement {
     // https://html.spec.whatwg.org/multipage/#dom-input-multiple
     make_bool_setter!(SetMultiple, "multiple");
 
+    // https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory
+    fn Webkitdirectory(&self) -> bool {
+        self.upcast::<Element>()
+            .has_attribute(&LocalName::from("webkitdirectory"))
+    }
+
+    // https://wicg.github.io/entries-api/#dom-htmlinputelement-webkitdirectory
+    fn SetWebkitdirectory(&self, value: bool) {
+        self.upcast::<Element>()
+            .set_bool_attribute(&LocalName::from("webkitdirectory"), value)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-input-pattern
     make_getter!(Pattern, "pattern");
 
@@ -1223,6 +1403,36 @@ impl HTMLInputElementMethods for HTMLInputElement {
             .set_state(ElementState::IN_INDETERMINATE_STATE, val)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.is_instance_validatable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> DomRoot<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-lfe-labels
     // Different from make_labels_getter because this one
     // conditionally returns null.
@@ -1498,6 +1708,7 @@ impl HTMLInputElement {
             broadcast_radio_checked(self, self.radio_group_name().as_ref());
         }
 
+        self.update_validity_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         //TODO: dispatch change event
     }
@@ -1521,9 +1732,22 @@ impl HTMLInputElement {
         }
         self.textinput.borrow_mut().set_content(self.DefaultValue());
         self.value_dirty.set(false);
+        self.update_validity_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    /// Updates the `:valid` and `:invalid` states of the input, and the ones
+    /// of the other buttons of its group for a radio button, since whether
+    /// the group is missing a value depends on all of them.
+    fn update_validity_states(&self) {
+        if self.input_type() != InputType::Radio {
+            return self.validity_changed();
+        }
+        for input in radio_group_iter(self, self.radio_group_name().as_ref()) {
+            input.validity_changed();
+        }
+    }
+
     fn update_placeholder_shown_state(&self) {
         if !self.input_type().is_textual_or_password() {
             return;
@@ -1549,7 +1773,32 @@ impl HTMLInputElement {
         let filter = filter_from_accept(&self.Accept());
         let target = self.upcast::<EventTarget>();
 
-        if self.Multiple() {
+        if self.Webkitdirectory() {
+            // The first path is the directory to upload the files of.
+            let opt_test_path = match opt_test_paths {
+                Some(paths) => match paths.first() {
+                    Some(path) => Some(path.to_string()),
+                    None => return,
+                },
+                None => None,
+            };
+
+            let (chan, recv) = ipc::channel(self.global().time_profiler_chan().clone())
+                .expect("Error initializing channel");
+            let msg = FileManagerThreadMsg::SelectDirectory(chan, origin, opt_test_path);
+            let _ = resource_threads
+                .send(CoreResourceMsg::ToFileManager(msg))
+                .unwrap();
+
+            match recv.recv().expect("IpcSender side error") {
+                Ok(selected_files) => {
+                    for selected in selected_files {
+                        files.push(File::new_from_selected(&window, selected));
+                    }
+                },
+                Err(err) => error = Some(err),
+            };
+        } else if self.Multiple() {
             let opt_test_paths =
                 opt_test_paths.map(|paths| paths.iter().map(|p| p.to_string()).collect());
 
@@ -1600,12 +1849,171 @@ impl HTMLInputElement {
         } else {
             let filelist = FileList::new(&window, files);
             self.filelist.set(Some(&filelist));
+            self.update_validity_states();
 
             target.fire_bubbling_event(atom!("input"));
             target.fire_bubbling_event(atom!("change"));
         }
     }
 
+    /// Asks the embedder to show a picker for the value of a date, time or
+    /// color input, which is set once the user picked one.
+    fn show_picker(&self) {
+        let (sender, receiver) = ipc_channel::ipc::channel().expect("ipc channel failure");
+        let value = self.Value().to_string();
+        let msg = match self.input_type() {
+            InputType::Color => EmbedderMsg::ShowColorPicker(value, sender),
+            ty => {
+                let kind = match ty.as_ime_type() {
+                    Some(kind) => kind,
+                    None => return,
+                };
+                let element = self.upcast::<Element>();
+                let min = element
+                    .get_attribute(&ns!(), &local_name!("min"))
+                    .map(|attr| attr.summarize().value);
+                let max = element
+                    .get_attribute(&ns!(), &local_name!("max"))
+                    .map(|attr| attr.summarize().value);
+                EmbedderMsg::ShowDateTimePicker(kind, value, min, max, sender)
+            },
+        };
+        self.send_picker_msg(msg, receiver);
+    }
+
+    /// The values of the options of the list of the input that match its
+    /// current value, which the user can pick one of.
+    fn matching_suggestions(&self) -> Vec<String> {
+        let datalist = match self.suggestions_source_element() {
+            Some(datalist) => datalist,
+            None => return vec![],
+        };
+        let value = self.Value().to_lowercase();
+        if value.is_empty() {
+            return vec![];
+        }
+        datalist
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLOptionElement>)
+            .filter(|option| !option.Disabled())
+            .map(|option| String::from(option.Value()))
+            .filter(|suggestion| {
+                let suggestion = suggestion.to_lowercase();
+                !suggestion.is_empty() && suggestion != value && suggestion.contains(&value)
+            })
+            .collect()
+    }
+
+    /// Asks the embedder to show a dropdown below the input with the
+    /// suggestions that match the value the user typed, or to hide it if
+    /// none do.
+    fn update_suggestions(&self) {
+        if !self.input_type().has_suggestions_dropdown() {
+            return;
+        }
+        let suggestions = self.matching_suggestions();
+        if suggestions.is_empty() {
+            return self.hide_suggestions();
+        }
+        let (sender, receiver) = ipc_channel::ipc::channel().expect("ipc channel failure");
+        let rect = window_from_node(self).device_rect_in_viewport(self.upcast());
+        self.suggestions_shown.set(true);
+        self.send_picker_msg(
+            EmbedderMsg::ShowDatalistSuggestions(suggestions, rect, sender),
+            receiver,
+        );
+    }
+
+    fn hide_suggestions(&self) {
+        if self.suggestions_shown.get() {
+            self.suggestions_shown.set(false);
+            window_from_node(self).send_to_embedder(EmbedderMsg::HideDatalistSuggestions);
+        }
+    }
+
+    /// Sends a message that opens a picker to the embedder, and handles the
+    /// value that the user picked with it.
+    fn send_picker_msg(&self, msg: EmbedderMsg, receiver: IpcReceiver<Option<String>>) {
+        let window = window_from_node(self);
+        let task_source = window.task_manager().user_interaction_task_source();
+        let canceller = window
+            .upcast::<GlobalScope>()
+            .task_canceller(TaskSourceName::UserInteraction);
+        let trusted_input = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let value: Option<String> = message.to().unwrap();
+                let value = match value {
+                    Some(value) => DOMString::from(value),
+                    None => return,
+                };
+                let input = trusted_input.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(input_value_picked: move || {
+                        input.root().value_picked(value);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue input picker task {:?}", err);
+                }
+            }),
+        );
+
+        window.send_to_embedder(msg);
+    }
+
+    /// Handles the value that the user picked for a date, time or color
+    /// input, or among the suggestions of the list of a text input.
+    fn value_picked(&self, value: DOMString) {
+        self.suggestions_shown.set(false);
+        // The input may have changed in the meantime.
+        let input_type = self.input_type();
+        if !(input_type.has_picker() || input_type.has_suggestions_dropdown()) || !self.is_mutable()
+        {
+            return;
+        }
+        let old_value = self.Value();
+        let _ = self.SetValue(value);
+        if self.Value() != old_value {
+            self.value_changed_by_user();
+        }
+    }
+
+    /// Steps the value of a date or time input up or down with the arrow
+    /// keys, returning whether the key was handled.
+    fn handle_step_key(&self, event: &KeyboardEvent) -> bool {
+        let dir = match event.key() {
+            Key::ArrowUp => StepDirection::Up,
+            Key::ArrowDown => StepDirection::Down,
+            _ => return false,
+        };
+        if !self.is_mutable() {
+            return false;
+        }
+        let old_value = self.Value();
+        if self.step_up_or_down(1, dir).is_err() {
+            return false;
+        }
+        if self.Value() != old_value {
+            self.value_changed_by_user();
+        }
+        true
+    }
+
+    /// Fires the events of a change of the value that the user committed.
+    fn value_changed_by_user(&self) {
+        self.value_dirty.set(true);
+        self.update_placeholder_shown_state();
+        self.update_validity_states();
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"));
+        target.fire_bubbling_event(atom!("change"));
+    }
+
     // https://html.spec.whatwg.org/multipage/#value-sanitization-algorithm
     fn sanitize_value(&self, value: &mut DOMString) {
         // if sanitization_flag is false, we are setting content attributes
@@ -2164,6 +2572,8 @@ impl VirtualMethods for HTMLInputElement {
             },
             _ => {},
         }
+
+        self.update_validity_states();
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -2190,6 +2600,7 @@ impl VirtualMethods for HTMLInputElement {
         }
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.update_validity_states();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -2205,6 +2616,8 @@ impl VirtualMethods for HTMLInputElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_validity_states();
+        fieldsets_validity_changed(context.parent);
     }
 
     // This represents behavior for which the UIEvents spec and the
@@ -2245,11 +2658,24 @@ impl VirtualMethods for HTMLInputElement {
                     }
                 }
             }
+        } else if event.type_() == atom!("dblclick") &&
+            !event.DefaultPrevented() &&
+            self.input_type().is_textual_or_password()
+        {
+            // The preceding click moved the edit point under the pointer.
+            self.textinput.borrow_mut().select_word_at_edit_point();
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+            event.PreventDefault();
         } else if event.type_() == atom!("keydown") &&
             !event.DefaultPrevented() &&
             self.input_type().is_textual_or_password()
         {
             if let Some(keyevent) = event.downcast::<KeyboardEvent>() {
+                if self.input_type().is_date_or_time() && self.handle_step_key(keyevent) {
+                    event.mark_as_handled();
+                    return;
+                }
+
                 // This can't be inlined, as holding on to textinput.borrow_mut()
                 // during self.implicit_submission will cause a panic.
                 let action = self.textinput.borrow_mut().handle_keydown(keyevent);
@@ -2260,6 +2686,8 @@ impl VirtualMethods for HTMLInputElement {
                     DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
+                        self.update_validity_states();
+                        self.update_suggestions();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -2287,6 +2715,36 @@ impl VirtualMethods for HTMLInputElement {
                         &window,
                     );
             }
+        } else if event.type_() == atom!("drop") &&
+            !event.DefaultPrevented() &&
+            self.accepts_text_drop()
+        {
+            let text = event
+                .downcast::<DragEvent>()
+                .and_then(DragEvent::dropped_text);
+            if let Some(text) = text {
+                // The value of an input is a single line.
+                let text: String = text.chars().filter(|c| *c != '\n' && *c != '\r').collect();
+                self.textinput.borrow_mut().insert_string(text);
+                self.value_dirty.set(true);
+                self.update_placeholder_shown_state();
+                self.update_validity_states();
+                self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                let window = window_from_node(self);
+                let _ = window
+                    .task_manager()
+                    .user_interaction_task_source()
+                    .queue_event(
+                        &self.upcast(),
+                        atom!("input"),
+                        EventBubbles::Bubbles,
+                        EventCancelable::NotCancelable,
+                        &window,
+                    );
+                event.mark_as_handled();
+            }
+        } else if event.type_() == atom!("blur") {
+            self.hide_suggestions();
         } else if (event.type_() == atom!("compositionstart") ||
             event.type_() == atom!("compositionupdate") ||
             event.type_() == atom!("compositionend")) &&
@@ -2300,6 +2758,7 @@ impl VirtualMethods for HTMLInputElement {
                         .textinput
                         .borrow_mut()
                         .handle_compositionend(compositionevent);
+                    self.update_validity_states();
                     self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                 }
                 event.mark_as_handled();
@@ -2343,12 +2802,57 @@ impl FormControl for HTMLInputElement {
 }
 
 impl Validatable for HTMLInputElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state(&self) -> DomRoot<ValidityState> {
+        self.validity_state
+            .or_init(|| ValidityState::new(&window_from_node(self), self.upcast()))
+    }
+
     fn is_instance_validatable(&self) -> bool {
         // https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation
-        true
+        match self.input_type() {
+            InputType::Hidden | InputType::Reset | InputType::Button => return false,
+            _ => {},
+        }
+        // https://html.spec.whatwg.org/multipage/#the-readonly-attribute:barred-from-constraint-validation
+        let readonly = self.ReadOnly() &&
+            match self.input_type() {
+                InputType::Color | InputType::Range => false,
+                ty => ty.is_textual_or_password(),
+            };
+        // https://html.spec.whatwg.org/multipage/#the-datalist-element:barred-from-constraint-validation
+        let in_datalist = self
+            .upcast::<Node>()
+            .ancestors()
+            .any(|ancestor| ancestor.is::<HTMLDataListElement>());
+        !(self.upcast::<Element>().disabled_state() || readonly || in_datalist)
     }
-    fn validate(&self, _validate_flags: ValidationFlags) -> bool {
-        // call stub methods defined in validityState.rs file here according to the flags set in validate_flags
+
+    fn validate(&self, validate_flags: ValidationFlags) -> bool {
+        // TODO: check the constraints of the other flags set in validate_flags
+        if validate_flags.contains(ValidationFlags::VALUE_MISSING) &&
+            self.suffers_from_being_missing()
+        {
+            return false;
+        }
+        if validate_flags.contains(ValidationFlags::RANGE_UNDERFLOW) &&
+            self.suffers_from_range_underflow()
+        {
+            return false;
+        }
+        if validate_flags.contains(ValidationFlags::RANGE_OVERFLOW) &&
+            self.suffers_from_range_overflow()
+        {
+            return false;
+        }
+        if validate_flags.contains(ValidationFlags::STEP_MISMATCH) &&
+            self.suffers_from_step_mismatch()
+        {
+            return false;
+        }
         true
     }
 }
@@ -2369,6 +2873,8 @@ impl Activatable for HTMLInputElement {
             InputType::File |
             InputType::Checkbox |
             InputType::Radio => self.is_mutable(),
+            // The picker of date, time and color inputs opens on activation.
+            ty if ty.has_picker() => self.is_mutable(),
             _ => false,
         }
     }
@@ -2493,6 +2999,7 @@ impl Activatable for HTMLInputElement {
                 target.fire_bubbling_event(atom!("change"));
             },
             InputType::File => self.select_files(None),
+            ty if ty.has_picker() => self.show_picker(),
             _ => (),
         }
     }
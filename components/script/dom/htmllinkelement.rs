@@ -8,6 +8,8 @@ use crate::dom::bindings::codegen::Bindings::DOMTokenListBinding::DOMTokenListBi
 use crate::dom::bindings::codegen::Bindings::HTMLLinkElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLLinkElementBinding::HTMLLinkElementMethods;
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::cssstylesheet::CSSStyleSheet;
@@ -16,7 +18,9 @@ use crate::dom::domtokenlist::DOMTokenList;
 use crate::dom::element::{
     cors_setting_for_element, reflect_cross_origin_attribute, set_cross_origin_attribute,
 };
+use crate::dom::element::{reflect_fetch_priority_attribute, set_fetch_priority_attribute};
 use crate::dom::element::{AttributeMutation, Element, ElementCreator};
+use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::node::{
     document_from_node, stylesheets_owner_from_node, window_from_node, BindContext, Node,
@@ -24,11 +28,13 @@ use crate::dom::node::{
 };
 use crate::dom::stylesheet::StyleSheet as DOMStyleSheet;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::script_module::{fetch_external_module_script, ModuleOwner};
 use crate::stylesheet_loader::{StylesheetContextSource, StylesheetLoader, StylesheetOwner};
 use cssparser::{Parser as CssParser, ParserInput};
 use dom_struct::dom_struct;
 use embedder_traits::EmbedderMsg;
 use html5ever::{LocalName, Prefix};
+use net_traits::request::{CredentialsMode, Destination};
 use net_traits::ReferrerPolicy;
 use servo_arc::Arc;
 use std::borrow::ToOwned;
@@ -168,6 +174,16 @@ fn string_is_stylesheet(value: &Option<String>) -> bool {
     }
 }
 
+/// <https://html.spec.whatwg.org/multipage/#link-type-modulepreload>
+fn is_modulepreload(value: &Option<String>) -> bool {
+    match *value {
+        Some(ref value) => value
+            .split(HTML_SPACE_CHARACTERS)
+            .any(|s| s.eq_ignore_ascii_case("modulepreload")),
+        None => false,
+    }
+}
+
 /// Favicon spec usage in accordance with CEF implementation:
 /// only url of icon is required/used
 /// <https://html.spec.whatwg.org/multipage/#rel-icon>
@@ -196,6 +212,8 @@ impl VirtualMethods for HTMLLinkElement {
             &local_name!("href") => {
                 if string_is_stylesheet(&rel) {
                     self.handle_stylesheet_url(&attr.value());
+                } else if is_modulepreload(&rel) {
+                    self.handle_modulepreload_url(&attr.value());
                 } else if is_favicon(&rel) {
                     let sizes = get_attr(self.upcast(), &local_name!("sizes"));
                     self.handle_favicon_url(rel.as_ref().unwrap(), &attr.value(), &sizes);
@@ -242,6 +260,9 @@ impl VirtualMethods for HTMLLinkElement {
                 Some(ref href) if string_is_stylesheet(&rel) => {
                     self.handle_stylesheet_url(href);
                 },
+                Some(ref href) if is_modulepreload(&rel) => {
+                    self.handle_modulepreload_url(href);
+                },
                 Some(ref href) if is_favicon(&rel) => {
                     self.handle_favicon_url(rel.as_ref().unwrap(), href, &sizes);
                 },
@@ -333,6 +354,88 @@ impl HTMLLinkElement {
         );
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#link-type-modulepreload>
+    fn handle_modulepreload_url(&self, href: &str) {
+        let document = document_from_node(self);
+        if document.browsing_context().is_none() {
+            return;
+        }
+
+        // Step 1.
+        if href.is_empty() {
+            return;
+        }
+
+        // Steps 2-3.
+        let element = self.upcast::<Element>();
+        let destination = match get_attr(element, &LocalName::from("as")) {
+            None => Destination::Script,
+            Some(value) => match &*value.to_ascii_lowercase() {
+                "script" => Destination::Script,
+                "worker" => Destination::Worker,
+                "sharedworker" => Destination::SharedWorker,
+                "serviceworker" => Destination::ServiceWorker,
+                "audioworklet" => Destination::AudioWorklet,
+                "paintworklet" => Destination::PaintWorklet,
+                _ => Destination::None,
+            },
+        };
+
+        // Step 4.
+        if !destination.is_script_like() {
+            self.upcast::<EventTarget>().fire_event(atom!("error"));
+            return;
+        }
+
+        // Step 5.
+        let url = match document.base_url().join(href) {
+            Ok(url) => url,
+            Err(e) => {
+                debug!("Parsing url {} failed: {}", href, e);
+                return;
+            },
+        };
+
+        // Step 7.
+        let credentials_mode = reflect_cross_origin_attribute(element).map_or(
+            CredentialsMode::CredentialsSameOrigin,
+            |attr| match &*attr {
+                "use-credentials" => CredentialsMode::Include,
+                _ => CredentialsMode::CredentialsSameOrigin,
+            },
+        );
+
+        // Step 8.
+        let integrity_metadata = match get_attr(element, &local_name!("integrity")) {
+            Some(integrity) => integrity,
+            None => document
+                .global()
+                .import_map()
+                .borrow()
+                .resolve_a_module_integrity_metadata(&url),
+        };
+
+        // Step 11.
+        fetch_external_module_script(
+            ModuleOwner::ModulePreload(Trusted::new(self)),
+            url,
+            destination,
+            integrity_metadata,
+            credentials_mode,
+        );
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#link-type-modulepreload>
+    /// Step 11.
+    pub fn module_preload_finished(&self, succeeded: bool) {
+        let event = if succeeded {
+            atom!("load")
+        } else {
+            atom!("error")
+        };
+        self.upcast::<EventTarget>().fire_event(event);
+    }
+
     fn handle_favicon_url(&self, _rel: &str, href: &str, _sizes: &Option<String>) {
         let document = document_from_node(self);
         match document.base_url().join(href) {
@@ -462,6 +565,16 @@ impl HTMLLinkElementMethods for HTMLLinkElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-link-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-link-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value);
+    }
+
     // https://drafts.csswg.org/cssom/#dom-linkstyle-sheet
     fn GetSheet(&self) -> Option<DomRoot<DOMStyleSheet>> {
         self.get_cssom_stylesheet().map(DomRoot::upcast)
@@ -16,6 +16,7 @@ use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConsta
 use crate::dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorMethods;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
 use crate::dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, TextTrackMode};
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::InheritTypes::{ElementTypeId, HTMLElementTypeId};
@@ -345,6 +346,9 @@ pub struct HTMLMediaElement {
     volume: Cell<f64>,
     /// https://html.spec.whatwg.org/multipage/#dom-media-seeking
     seeking: Cell<bool>,
+    /// The seeks that the media engine hasn't reported as done yet. Only the
+    /// last one completes the seek, the earlier ones were aborted by it.
+    pending_seeks: Cell<u32>,
     /// https://html.spec.whatwg.org/multipage/#dom-media-muted
     muted: Cell<bool>,
     /// URL of the media resource, if any.
@@ -429,6 +433,7 @@ impl HTMLMediaElement {
             default_playback_start_position: Cell::new(0.),
             volume: Cell::new(1.0),
             seeking: Cell::new(false),
+            pending_seeks: Cell::new(0),
             resource_url: DomRefCell::new(None),
             blob_url: DomRefCell::new(None),
             played: DomRefCell::new(TimeRangesContainer::new()),
@@ -458,10 +463,23 @@ impl HTMLMediaElement {
 
     fn play_media(&self) {
         if let Some(ref player) = *self.player.borrow() {
-            if let Err(e) = player.lock().unwrap().set_rate(self.playbackRate.get()) {
+            let player = player.lock().unwrap();
+            // The media backend can't play at a rate of zero, so the player
+            // stays paused until the rate changes.
+            if self.playbackRate.get() == 0. {
+                if let Err(e) = player.pause() {
+                    warn!("Could not pause media {:?}", e);
+                }
+                return;
+            }
+            // FIXME: Rates other than 1 change the pitch of the audio, since
+            // servo-media doesn't time-stretch it, so the audio is played as
+            // if `preservesPitch` was false. That attribute is left out until
+            // the media backend can preserve the pitch.
+            if let Err(e) = player.set_rate(self.playbackRate.get()) {
                 warn!("Could not set the playback rate {:?}", e);
             }
-            if let Err(e) = player.lock().unwrap().play() {
+            if let Err(e) = player.play() {
                 warn!("Could not play media {:?}", e);
             }
         }
@@ -1122,9 +1140,8 @@ impl HTMLMediaElement {
             }
 
             // Step 6.7.
-            if !self.seeking.get() {
-                self.seeking.set(false);
-            }
+            self.seeking.set(false);
+            self.pending_seeks.set(0);
 
             // Step 6.8.
             let queue_timeupdate_event = self.playback_position.get() != 0.;
@@ -1248,15 +1265,14 @@ impl HTMLMediaElement {
         let time = f64::max(time, 0.);
 
         // Step 8.
-        // XXX(ferjm) seekable attribute: we need to get the information about
-        //            what's been decoded and buffered so far from servo-media
-        //            and add the seekable attribute as a TimeRange.
-        if let Some(ref current_fetch_context) = *self.current_fetch_context.borrow() {
-            if !current_fetch_context.is_seekable() {
-                self.seeking.set(false);
-                return;
-            }
-        }
+        let time =
+            match nearest_position(&self.seekable_ranges(), time, self.playback_position.get()) {
+                Some(time) => time,
+                None => {
+                    self.seeking.set(false);
+                    return;
+                },
+            };
 
         // Step 9.
         // servo-media with gstreamer does not support inaccurate seeking for now.
@@ -1267,9 +1283,13 @@ impl HTMLMediaElement {
         task_source.queue_simple_event(self.upcast(), atom!("seeking"), &window);
 
         // Step 11.
+        self.playback_position.set(time);
+
+        // Step 12.
         if let Some(ref player) = *self.player.borrow() {
-            if let Err(e) = player.lock().unwrap().seek(time) {
-                eprintln!("Seek error {:?}", e);
+            match player.lock().unwrap().seek(time) {
+                Ok(()) => self.pending_seeks.set(self.pending_seeks.get() + 1),
+                Err(e) => eprintln!("Seek error {:?}", e),
             }
         }
 
@@ -1284,7 +1304,9 @@ impl HTMLMediaElement {
         self.seeking.set(false);
 
         // Step 15.
-        self.time_marches_on();
+        // The time marches on steps only queue a timeupdate event when the
+        // position advanced through normal playback, so that the one below is
+        // the only timeupdate event of the seek.
 
         // Step 16.
         let window = window_from_node(self);
@@ -1295,6 +1317,54 @@ impl HTMLMediaElement {
         task_source.queue_simple_event(self.upcast(), atom!("seeked"), &window);
     }
 
+    /// The time ranges of the media data that the media engine has buffered.
+    fn buffered_ranges(&self) -> TimeRangesContainer {
+        let mut buffered = TimeRangesContainer::new();
+        if let Some(ref player) = *self.player.borrow() {
+            if let Ok(ranges) = player.lock().unwrap().buffered() {
+                for range in ranges {
+                    let _ = buffered.add(range.start as f64, range.end as f64);
+                }
+            }
+        }
+        buffered
+    }
+
+    /// The whole resource can be seeked to when its server supports range
+    /// requests, otherwise only the media data that was already buffered can.
+    /// <https://html.spec.whatwg.org/multipage/#dom-media-seekable>
+    fn seekable_ranges(&self) -> TimeRangesContainer {
+        if self.ready_state.get() == ReadyState::HaveNothing {
+            return TimeRangesContainer::new();
+        }
+        let is_seekable = self
+            .current_fetch_context
+            .borrow()
+            .as_ref()
+            .map_or(false, |context| context.is_seekable());
+        let duration = self.duration.get();
+        if is_seekable && duration.is_finite() {
+            let mut seekable = TimeRangesContainer::new();
+            let _ = seekable.add(0., duration);
+            return seekable;
+        }
+        self.buffered_ranges()
+    }
+
+    /// Applies a change of the playback rate to the media engine.
+    fn update_playback_rate(&self) {
+        if self.is_potentially_playing() {
+            self.play_media();
+        }
+        self.send_media_session_event(MediaSessionEvent::SetPositionState(
+            MediaPositionState::new(
+                self.duration.get(),
+                self.playbackRate.get(),
+                self.playback_position.get(),
+            ),
+        ));
+    }
+
     /// https://html.spec.whatwg.org/multipage/#poster-frame
     pub fn process_poster_response(&self, image: ImageResponse) {
         if !self.show_poster.get() {
@@ -1775,6 +1845,12 @@ impl HTMLMediaElement {
                 }
             },
             PlayerEvent::PositionChanged(position) => {
+                // The current playback position was already set to the new
+                // one by the seek, and the positions the media engine reports
+                // until the seek is done are the ones from before it.
+                if self.seeking.get() {
+                    return;
+                }
                 let position = position as f64;
                 let _ = self
                     .played
@@ -1799,6 +1875,15 @@ impl HTMLMediaElement {
                 // Continuation of
                 // https://html.spec.whatwg.org/multipage/#dom-media-seek
 
+                // Step 3.
+                // A seek that started after this one aborted it, so only the
+                // last one fires the seeked event.
+                let pending_seeks = self.pending_seeks.get().saturating_sub(1);
+                self.pending_seeks.set(pending_seeks);
+                if pending_seeks > 0 {
+                    return;
+                }
+
                 // Step 13.
                 let task = MediaElementMicrotask::SeekedTask {
                     elem: DomRoot::from_ref(self),
@@ -1852,7 +1937,9 @@ impl HTMLMediaElement {
             // if we are already showing the controls.
             return;
         }
-        let shadow_root = element.attach_shadow(IsUserAgentWidget::Yes).unwrap();
+        let shadow_root = element
+            .attach_shadow(IsUserAgentWidget::Yes, ShadowRootMode::Closed, false)
+            .unwrap();
         let document = document_from_node(self);
         let script = HTMLScriptElement::new(
             local_name!("script"),
@@ -2247,13 +2334,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
         if *value != self.playbackRate.get() {
             self.playbackRate.set(*value);
             self.queue_ratechange_event();
-            if self.is_potentially_playing() {
-                if let Some(ref player) = *self.player.borrow() {
-                    if let Err(e) = player.lock().unwrap().set_rate(*value) {
-                        warn!("Could not set the playback rate {:?}", e);
-                    }
-                }
-            }
+            self.update_playback_rate();
         }
 
         Ok(())
@@ -2314,15 +2395,12 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-media-buffered
     fn Buffered(&self) -> DomRoot<TimeRanges> {
-        let mut buffered = TimeRangesContainer::new();
-        if let Some(ref player) = *self.player.borrow() {
-            if let Ok(ranges) = player.lock().unwrap().buffered() {
-                for range in ranges {
-                    let _ = buffered.add(range.start as f64, range.end as f64);
-                }
-            }
-        }
-        TimeRanges::new(self.global().as_window(), buffered)
+        TimeRanges::new(self.global().as_window(), self.buffered_ranges())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-seekable
+    fn Seekable(&self) -> DomRoot<TimeRanges> {
+        TimeRanges::new(self.global().as_window(), self.seekable_ranges())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-audiotracks
@@ -2502,6 +2580,31 @@ impl MicrotaskRunnable for MediaElementMicrotask {
     }
 }
 
+/// The position in `ranges` that is the nearest to `time`, or to `current`
+/// when two positions are as near.
+/// <https://html.spec.whatwg.org/multipage/#dom-media-seek> Step 8.
+pub fn nearest_position(ranges: &TimeRangesContainer, time: f64, current: f64) -> Option<f64> {
+    let mut nearest: Option<f64> = None;
+    for index in 0..ranges.len() {
+        let (start, end) = match (ranges.start(index), ranges.end(index)) {
+            (Ok(start), Ok(end)) => (start, end),
+            _ => continue,
+        };
+        let position = time.max(start).min(end);
+        nearest = Some(match nearest {
+            Some(nearest) if (nearest - time).abs() < (position - time).abs() => nearest,
+            Some(nearest)
+                if (nearest - time).abs() == (position - time).abs() &&
+                    (nearest - current).abs() <= (position - current).abs() =>
+            {
+                nearest
+            },
+            _ => position,
+        });
+    }
+    nearest
+}
+
 enum Resource {
     Object,
     Url(ServoUrl),
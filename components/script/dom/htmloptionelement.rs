@@ -23,6 +23,7 @@ use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::htmlselectelement::HTMLSelectElement;
 use crate::dom::node::{BindContext, Node, ShadowIncluding, UnbindContext};
 use crate::dom::text::Text;
+use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
@@ -127,6 +128,7 @@ impl HTMLOptionElement {
                 select.pick_option(self);
             }
             select.ask_for_reset();
+            select.validity_changed();
         }
     }
 
@@ -332,6 +334,7 @@ impl VirtualMethods for HTMLOptionElement {
             .next()
         {
             select.ask_for_reset();
+            select.validity_changed();
         }
 
         let node = self.upcast::<Node>();
@@ -2,12 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::devtools::report_source_map;
 use crate::document_loader::LoadType;
 use crate::dom::attr::Attr;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLScriptElementBinding;
 use crate::dom::bindings::codegen::Bindings::HTMLScriptElementBinding::HTMLScriptElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::error::{report_pending_exception, throw_dom_exception};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
@@ -18,6 +20,9 @@ use crate::dom::document::Document;
 use crate::dom::element::{
     cors_setting_for_element, reflect_cross_origin_attribute, set_cross_origin_attribute,
 };
+use crate::dom::element::{
+    fetch_priority_for_element, reflect_fetch_priority_attribute, set_fetch_priority_attribute,
+};
 use crate::dom::element::{AttributeMutation, Element, ElementCreator};
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::globalscope::GlobalScope;
@@ -27,9 +32,12 @@ use crate::dom::node::{BindContext, ChildrenMutation, CloneChildrenFlag, Node};
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::fetch::create_a_potential_cors_request;
+use crate::import_map::ImportMap;
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
+use crate::realms::{enter_realm, InRealm};
 use crate::script_module::fetch_inline_module_script;
-use crate::script_module::{fetch_external_module_script, ModuleOwner};
+use crate::script_module::{fetch_external_module_script, ModuleOwner, ModuleTree};
+use crate::speculation_rules::{prefetch_a_document, SpeculationRuleSet};
 use content_security_policy as csp;
 use dom_struct::dom_struct;
 use encoding_rs::Encoding;
@@ -40,9 +48,10 @@ use js::jsval::UndefinedValue;
 use msg::constellation_msg::PipelineId;
 use net_traits::request::{CorsSettings, CredentialsMode, Destination, Referrer, RequestBuilder};
 use net_traits::ReferrerPolicy;
-use net_traits::{FetchMetadata, FetchResponseListener, Metadata, NetworkError};
+use net_traits::{FetchMetadata, FetchResponseListener, FilteredMetadata, Metadata, NetworkError};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
 use std::cell::Cell;
@@ -148,6 +157,8 @@ pub static SCRIPT_JS_MIMES: StaticStringVec = &[
 pub enum ScriptType {
     Classic,
     Module,
+    ImportMap,
+    SpeculationRules,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -156,6 +167,8 @@ pub struct ScriptOrigin {
     url: ServoUrl,
     external: bool,
     type_: ScriptType,
+    /// <https://html.spec.whatwg.org/multipage/#muted-errors>
+    muted_errors: bool,
 }
 
 impl ScriptOrigin {
@@ -165,6 +178,7 @@ impl ScriptOrigin {
             url: url,
             external: false,
             type_,
+            muted_errors: false,
         }
     }
 
@@ -174,6 +188,7 @@ impl ScriptOrigin {
             url: url,
             external: true,
             type_,
+            muted_errors: false,
         }
     }
 
@@ -197,6 +212,8 @@ struct ClassicContext {
     data: Vec<u8>,
     /// The response metadata received to date.
     metadata: Option<Metadata>,
+    /// Whether the response is opaque, which mutes the errors of the script.
+    muted_errors: bool,
     /// The initial URL requested.
     url: ServoUrl,
     /// Indicates whether the request failed, and why
@@ -211,6 +228,17 @@ impl FetchResponseListener for ClassicContext {
     fn process_request_eof(&mut self) {} // TODO(KiChjang): Perhaps add custom steps to perform fetch here?
 
     fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>) {
+        self.muted_errors = match metadata {
+            Ok(FetchMetadata::Filtered {
+                filtered: FilteredMetadata::Opaque,
+                ..
+            }) |
+            Ok(FetchMetadata::Filtered {
+                filtered: FilteredMetadata::OpaqueRedirect,
+                ..
+            }) => true,
+            _ => false,
+        };
         self.metadata = metadata.ok().map(|meta| match meta {
             FetchMetadata::Unfiltered(m) => m,
             FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
@@ -258,11 +286,13 @@ impl FetchResponseListener for ClassicContext {
 
             // Step 7.
             let (source_text, _, _) = encoding.decode(&self.data);
-            ScriptOrigin::external(
+            let mut script = ScriptOrigin::external(
                 DOMString::from(source_text),
                 metadata.final_url,
                 ScriptType::Classic,
-            )
+            );
+            script.muted_errors = self.muted_errors;
+            script
         });
 
         // Step 9.
@@ -354,7 +384,8 @@ fn fetch_a_classic_script(
         Referrer::ReferrerUrl(doc.url()),
         doc.get_referrer_policy(),
         integrity_metadata,
-    );
+    )
+    .priority(fetch_priority_for_element(script.upcast()));
 
     // TODO: Step 3, Add custom steps to perform fetch
 
@@ -364,6 +395,7 @@ fn fetch_a_classic_script(
         character_encoding: character_encoding,
         data: vec![],
         metadata: None,
+        muted_errors: false,
         url: url.clone(),
         status: Ok(()),
         resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
@@ -496,7 +528,7 @@ impl HTMLScriptElement {
 
         // Step 17.
         let credentials_mode = match script_type {
-            ScriptType::Classic => None,
+            ScriptType::Classic | ScriptType::ImportMap | ScriptType::SpeculationRules => None,
             ScriptType::Module => Some(reflect_cross_origin_attribute(element).map_or(
                 CredentialsMode::CredentialsSameOrigin,
                 |attr| match &*attr {
@@ -529,6 +561,12 @@ impl HTMLScriptElement {
         if let Some(src) = element.get_attribute(&ns!(), &local_name!("src")) {
             // Step 24.
 
+            // Import maps and speculation rules can only be inline.
+            if script_type == ScriptType::ImportMap || script_type == ScriptType::SpeculationRules {
+                self.queue_error_event();
+                return;
+            }
+
             // Step 24.1.
             let src = src.value();
 
@@ -592,11 +630,21 @@ impl HTMLScriptElement {
                     }
                 },
                 ScriptType::Module => {
+                    // Module scripts without an integrity attribute fall back
+                    // to the integrity map of the import map.
+                    let integrity_metadata = match im_attribute {
+                        Some(_) => integrity_metadata.to_owned(),
+                        None => doc
+                            .global()
+                            .import_map()
+                            .borrow()
+                            .resolve_a_module_integrity_metadata(&url),
+                    };
                     fetch_external_module_script(
                         ModuleOwner::Window(Trusted::new(self)),
                         url.clone(),
                         Destination::Script,
-                        integrity_metadata.to_owned(),
+                        integrity_metadata,
                         credentials_mode.unwrap(),
                     );
 
@@ -608,6 +656,7 @@ impl HTMLScriptElement {
                         doc.add_asap_script(self);
                     };
                 },
+                ScriptType::ImportMap | ScriptType::SpeculationRules => unreachable!(),
             }
         } else {
             // Step 25.
@@ -655,6 +704,18 @@ impl HTMLScriptElement {
                         credentials_mode.unwrap(),
                     );
                 },
+                ScriptType::ImportMap => {
+                    // https://wicg.github.io/import-maps/#integration-prepare-a-script
+                    if !doc.global().acquiring_import_maps() {
+                        warn!("Import maps must be registered before any module script loads");
+                        self.queue_error_event();
+                        return;
+                    }
+                    self.execute(result);
+                },
+                ScriptType::SpeculationRules => {
+                    self.execute(result);
+                },
             }
         }
     }
@@ -758,6 +819,12 @@ impl HTMLScriptElement {
                 assert!(old_script.is_none());
                 self.run_a_module_script(&script, false);
             },
+            ScriptType::ImportMap => {
+                self.register_an_import_map(&script);
+            },
+            ScriptType::SpeculationRules => {
+                self.register_speculation_rules(&script);
+            },
         }
 
         // Step 5.
@@ -789,11 +856,13 @@ impl HTMLScriptElement {
         };
         rooted!(in(*window.get_cx()) let mut rval = UndefinedValue());
         let global = window.upcast::<GlobalScope>();
+        report_source_map(global, &script.url, &script.text);
         global.evaluate_script_on_global_with_result(
             &script.text,
             script.url.as_str(),
             rval.handle_mut(),
             line_number,
+            script.muted_errors,
         );
     }
 
@@ -827,13 +896,13 @@ impl HTMLScriptElement {
 
                 let module_record = module_tree.get_record().borrow();
                 if let Some(record) = &*module_record {
-                    let evaluated = module_tree.execute_module(global, record.handle());
-
-                    if let Err(exception) = evaluated {
-                        module_tree.set_error(Some(exception.clone()));
-                        module_tree.report_error(&global);
-                        return;
-                    }
+                    // Steps 7-8.
+                    let evaluation_promise = module_tree.execute_module(global, record.handle());
+                    ModuleTree::report_evaluation_error(
+                        module_tree.clone(),
+                        global,
+                        &evaluation_promise,
+                    );
                 }
             }
         } else {
@@ -851,18 +920,63 @@ impl HTMLScriptElement {
 
                 let module_record = module_tree.get_record().borrow();
                 if let Some(record) = &*module_record {
-                    let evaluated = module_tree.execute_module(global, record.handle());
-
-                    if let Err(exception) = evaluated {
-                        module_tree.set_error(Some(exception.clone()));
-                        module_tree.report_error(&global);
-                        return;
-                    }
+                    // Steps 7-8.
+                    let evaluation_promise = module_tree.execute_module(global, record.handle());
+                    ModuleTree::report_evaluation_error(
+                        module_tree.clone(),
+                        global,
+                        &evaluation_promise,
+                    );
                 }
             }
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#register-an-import-map>
+    #[allow(unsafe_code)]
+    fn register_an_import_map(&self, script: &ScriptOrigin) {
+        let window = window_from_node(self);
+        let global = window.upcast::<GlobalScope>();
+        match ImportMap::parse(&script.text, &script.url) {
+            Ok(import_map) => {
+                global.set_import_map(import_map);
+                // TODO: merge subsequent import maps into the existing one,
+                // for now only the first import map of a document is used.
+                global.stop_acquiring_import_maps();
+            },
+            Err(error) => {
+                let cx = global.get_cx();
+                unsafe {
+                    let ar = enter_realm(&*global);
+                    throw_dom_exception(cx, &global, error);
+                    report_pending_exception(*cx, true, InRealm::Entered(&ar));
+                }
+            },
+        }
+    }
+
+    /// <https://wicg.github.io/nav-speculation/speculation-rules.html#register-speculation-rules>
+    #[allow(unsafe_code)]
+    fn register_speculation_rules(&self, script: &ScriptOrigin) {
+        let document = document_from_node(self);
+        match SpeculationRuleSet::parse(&script.text, &script.url) {
+            Ok(rule_set) => {
+                for (url, referrer_policy) in rule_set.prefetch_candidates() {
+                    prefetch_a_document(&document, url, referrer_policy);
+                }
+            },
+            Err(error) => {
+                let global = document.global();
+                let cx = global.get_cx();
+                unsafe {
+                    let ar = enter_realm(&*global);
+                    throw_dom_exception(cx, &global, error);
+                    report_pending_exception(*cx, true, InRealm::Entered(&ar));
+                }
+            },
+        }
+    }
+
     pub fn queue_error_event(&self) {
         let window = window_from_node(self);
         window
@@ -927,6 +1041,14 @@ impl HTMLScriptElement {
                     return Some(ScriptType::Module);
                 }
 
+                if pref!(dom.import_maps.enabled) && &***ty == "importmap" {
+                    return Some(ScriptType::ImportMap);
+                }
+
+                if pref!(dom.speculation_rules.enabled) && &***ty == "speculationrules" {
+                    return Some(ScriptType::SpeculationRules);
+                }
+
                 if SCRIPT_JS_MIMES
                     .contains(&ty.to_ascii_lowercase().trim_matches(HTML_SPACE_CHARACTERS))
                 {
@@ -1095,6 +1217,16 @@ impl HTMLScriptElementMethods for HTMLScriptElement {
         set_cross_origin_attribute(self.upcast::<Element>(), value);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-script-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-script-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-script-text
     fn Text(&self) -> DOMString {
         self.upcast::<Node>().child_text_content()
@@ -19,6 +19,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::document::Document;
 use crate::dom::element::{AttributeMutation, Element};
 use crate::dom::htmlcollection::CollectionFilter;
+use crate::dom::htmldatalistelement::HTMLDataListElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{FormControl, FormDatum, FormDatumValue, HTMLFormElement};
@@ -27,7 +28,7 @@ use crate::dom::htmloptionelement::HTMLOptionElement;
 use crate::dom::htmloptionscollection::HTMLOptionsCollection;
 use crate::dom::node::{window_from_node, BindContext, Node, UnbindContext};
 use crate::dom::nodelist::NodeList;
-use crate::dom::validation::Validatable;
+use crate::dom::validation::{fieldsets_validity_changed, Validatable};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use dom_struct::dom_struct;
@@ -63,6 +64,7 @@ pub struct HTMLSelectElement {
     options: MutNullableDom<HTMLOptionsCollection>,
     form_owner: MutNullableDom<HTMLFormElement>,
     labels_node_list: MutNullableDom<NodeList>,
+    validity_state: MutNullableDom<ValidityState>,
 }
 
 static DEFAULT_SELECT_SIZE: u32 = 0;
@@ -75,7 +77,7 @@ impl HTMLSelectElement {
     ) -> HTMLSelectElement {
         HTMLSelectElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE,
+                ElementState::IN_ENABLED_STATE | ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -83,6 +85,7 @@ impl HTMLSelectElement {
             options: Default::default(),
             form_owner: Default::default(),
             labels_node_list: Default::default(),
+            validity_state: Default::default(),
         }
     }
 
@@ -122,6 +125,7 @@ impl HTMLSelectElement {
             opt.set_dirtiness(false);
         }
         self.ask_for_reset();
+        self.validity_changed();
     }
 
     // https://html.spec.whatwg.org/multipage/#ask-for-a-reset
@@ -183,6 +187,31 @@ impl HTMLSelectElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#placeholder-label-option
+    fn placeholder_label_option(&self) -> Option<DomRoot<HTMLOptionElement>> {
+        if !self.Required() || self.Multiple() || self.display_size() != 1 {
+            return None;
+        }
+        self.list_of_options().next().filter(|option| {
+            option.Value().is_empty() &&
+                option
+                    .upcast::<Node>()
+                    .GetParentNode()
+                    .map_or(false, |parent| &*parent == self.upcast::<Node>())
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#the-select-element:suffering-from-being-missing
+    fn suffers_from_being_missing(&self) -> bool {
+        if !self.Required() {
+            return false;
+        }
+        let placeholder = self.placeholder_label_option();
+        !self
+            .list_of_options()
+            .any(|option| option.Selected() && Some(&option) != placeholder.as_ref())
+    }
+
     // https://html.spec.whatwg.org/multipage/#concept-select-size
     fn display_size(&self) -> u32 {
         if self.Size() == 0 {
@@ -198,10 +227,34 @@ impl HTMLSelectElement {
 }
 
 impl HTMLSelectElementMethods for HTMLSelectElement {
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.is_instance_validatable()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-cva-validity
     fn Validity(&self) -> DomRoot<ValidityState> {
-        let window = window_from_node(self);
-        ValidityState::new(&window, self.upcast())
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-select-add
@@ -230,6 +283,12 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
     // https://html.spec.whatwg.org/multipage/#dom-select-multiple
     make_bool_setter!(SetMultiple, "multiple");
 
+    // https://html.spec.whatwg.org/multipage/#dom-select-required
+    make_bool_getter!(Required, "required");
+
+    // https://html.spec.whatwg.org/multipage/#dom-select-required
+    make_bool_setter!(SetRequired, "required");
+
     // https://html.spec.whatwg.org/multipage/#dom-fe-name
     make_getter!(Name, "name");
 
@@ -329,6 +388,7 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
         for opt in opt_iter {
             opt.set_selectedness(false);
         }
+        self.validity_changed();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-select-selectedindex
@@ -355,6 +415,7 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
                 opt.set_selectedness(false);
             }
         }
+        self.validity_changed();
     }
 }
 
@@ -385,6 +446,8 @@ impl VirtualMethods for HTMLSelectElement {
             },
             _ => {},
         }
+
+        self.validity_changed();
     }
 
     fn bind_to_tree(&self, context: &BindContext) {
@@ -394,6 +457,7 @@ impl VirtualMethods for HTMLSelectElement {
 
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.validity_changed();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -409,6 +473,8 @@ impl VirtualMethods for HTMLSelectElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.validity_changed();
+        fieldsets_validity_changed(context.parent);
     }
 
     fn parse_plain_attribute(&self, local_name: &LocalName, value: DOMString) -> AttrValue {
@@ -437,13 +503,28 @@ impl FormControl for HTMLSelectElement {
 }
 
 impl Validatable for HTMLSelectElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state(&self) -> DomRoot<ValidityState> {
+        self.validity_state
+            .or_init(|| ValidityState::new(&window_from_node(self), self.upcast()))
+    }
+
     fn is_instance_validatable(&self) -> bool {
-        true
+        // https://html.spec.whatwg.org/multipage/#enabling-and-disabling-form-controls%3A-the-disabled-attribute%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#the-datalist-element%3Abarred-from-constraint-validation
+        !self.upcast::<Element>().disabled_state() &&
+            !self
+                .upcast::<Node>()
+                .ancestors()
+                .any(|ancestor| ancestor.is::<HTMLDataListElement>())
     }
+
     fn validate(&self, validate_flags: ValidationFlags) -> bool {
-        if validate_flags.is_empty() {}
-        // Need more flag check for different validation types later
-        true
+        !(validate_flags.contains(ValidationFlags::VALUE_MISSING) &&
+            self.suffers_from_being_missing())
     }
 }
 
@@ -50,6 +50,13 @@ impl HTMLTemplateElement {
             HTMLTemplateElementBinding::Wrap,
         )
     }
+
+    /// Makes the shadow root a declarative template is parsed into its
+    /// template contents.
+    /// <https://html.spec.whatwg.org/multipage/#parsing-main-inhead:template-contents>
+    pub fn set_contents(&self, contents: &DocumentFragment) {
+        self.contents.set(Some(contents));
+    }
 }
 
 impl HTMLTemplateElementMethods for HTMLTemplateElement {
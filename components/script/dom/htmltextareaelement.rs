@@ -15,10 +15,12 @@ use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
 use crate::dom::element::RawLayoutElementHelpers;
 use crate::dom::element::{AttributeMutation, Element};
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmldatalistelement::HTMLDataListElement;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
 use crate::dom::htmlformelement::{FormControl, HTMLFormElement};
@@ -30,7 +32,8 @@ use crate::dom::node::{
 };
 use crate::dom::nodelist::NodeList;
 use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
-use crate::dom::validation::Validatable;
+use crate::dom::validation::{fieldsets_validity_changed, Validatable};
+use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::textinput::{
     Direction, KeyReaction, Lines, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes,
@@ -54,6 +57,7 @@ pub struct HTMLTextAreaElement {
     value_dirty: Cell<bool>,
     form_owner: MutNullableDom<HTMLFormElement>,
     labels_node_list: MutNullableDom<NodeList>,
+    validity_state: MutNullableDom<ValidityState>,
 }
 
 pub trait LayoutHTMLTextAreaElementHelpers {
@@ -139,7 +143,9 @@ impl HTMLTextAreaElement {
             .clone();
         HTMLTextAreaElement {
             htmlelement: HTMLElement::new_inherited_with_state(
-                ElementState::IN_ENABLED_STATE | ElementState::IN_READ_WRITE_STATE,
+                ElementState::IN_ENABLED_STATE |
+                    ElementState::IN_READ_WRITE_STATE |
+                    ElementState::IN_VALID_STATE,
                 local_name,
                 prefix,
                 document,
@@ -156,6 +162,7 @@ impl HTMLTextAreaElement {
             value_dirty: Cell::new(false),
             form_owner: Default::default(),
             labels_node_list: Default::default(),
+            validity_state: Default::default(),
         }
     }
 
@@ -319,7 +326,9 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
             // Step 4
             textinput.clear_selection_to_limit(Direction::Forward);
         }
+        drop(textinput);
 
+        self.validity_changed();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
@@ -329,6 +338,36 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
         num_units as u32
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.is_instance_validatable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> DomRoot<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-lfe-labels
     make_labels_getter!(Labels, labels_node_list);
 
@@ -394,9 +433,14 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
 impl HTMLTextAreaElement {
     pub fn reset(&self) {
         // https://html.spec.whatwg.org/multipage/#the-textarea-element:concept-form-reset-control
-        let mut textinput = self.textinput.borrow_mut();
-        textinput.set_content(self.DefaultValue());
+        self.textinput.borrow_mut().set_content(self.DefaultValue());
         self.value_dirty.set(false);
+        self.validity_changed();
+    }
+
+    /// Whether the text dropped on the textarea is inserted in its value.
+    pub fn accepts_text_drop(&self) -> bool {
+        self.upcast::<Element>().read_write_state()
     }
 
     #[allow(unrooted_must_root)]
@@ -483,6 +527,8 @@ impl VirtualMethods for HTMLTextAreaElement {
             },
             _ => {},
         }
+
+        self.validity_changed();
     }
 
     fn bind_to_tree(&self, context: &BindContext) {
@@ -492,6 +538,7 @@ impl VirtualMethods for HTMLTextAreaElement {
 
         self.upcast::<Element>()
             .check_ancestors_disabled_state_for_form_control();
+        self.validity_changed();
     }
 
     fn parse_plain_attribute(&self, name: &LocalName, value: DOMString) -> AttrValue {
@@ -524,6 +571,8 @@ impl VirtualMethods for HTMLTextAreaElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.validity_changed();
+        fieldsets_validity_changed(context.parent);
     }
 
     // The cloning steps for textarea elements must propagate the raw value
@@ -562,6 +611,10 @@ impl VirtualMethods for HTMLTextAreaElement {
             //TODO: set the editing position for text inputs
 
             document_from_node(self).request_focus(self.upcast());
+        } else if event.type_() == atom!("dblclick") && !event.DefaultPrevented() {
+            self.textinput.borrow_mut().select_word_at_edit_point();
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+            event.PreventDefault();
         } else if event.type_() == atom!("keydown") && !event.DefaultPrevented() {
             if let Some(kevent) = event.downcast::<KeyboardEvent>() {
                 // This can't be inlined, as holding on to textinput.borrow_mut()
@@ -572,6 +625,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::DispatchInput => {
                         self.value_dirty.set(true);
                         self.update_placeholder_shown_state();
+                        self.validity_changed();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
                     },
@@ -596,6 +650,32 @@ impl VirtualMethods for HTMLTextAreaElement {
                         &window,
                     );
             }
+        } else if event.type_() == atom!("drop") &&
+            !event.DefaultPrevented() &&
+            self.accepts_text_drop()
+        {
+            let text = event
+                .downcast::<DragEvent>()
+                .and_then(DragEvent::dropped_text);
+            if let Some(text) = text {
+                self.textinput.borrow_mut().insert_string(text);
+                self.value_dirty.set(true);
+                self.update_placeholder_shown_state();
+                self.validity_changed();
+                self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                let window = window_from_node(self);
+                let _ = window
+                    .task_manager()
+                    .user_interaction_task_source()
+                    .queue_event(
+                        &self.upcast(),
+                        atom!("input"),
+                        EventBubbles::Bubbles,
+                        EventCancelable::NotCancelable,
+                        &window,
+                    );
+                event.mark_as_handled();
+            }
         } else if event.type_() == atom!("compositionstart") ||
             event.type_() == atom!("compositionupdate") ||
             event.type_() == atom!("compositionend")
@@ -637,4 +717,34 @@ impl FormControl for HTMLTextAreaElement {
     }
 }
 
-impl Validatable for HTMLTextAreaElement {}
+impl Validatable for HTMLTextAreaElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state(&self) -> DomRoot<ValidityState> {
+        self.validity_state
+            .or_init(|| ValidityState::new(&window_from_node(self), self.upcast()))
+    }
+
+    fn is_instance_validatable(&self) -> bool {
+        // https://html.spec.whatwg.org/multipage/#enabling-and-disabling-form-controls%3A-the-disabled-attribute%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#the-textarea-element%3Abarred-from-constraint-validation
+        // https://html.spec.whatwg.org/multipage/#the-datalist-element%3Abarred-from-constraint-validation
+        !self.upcast::<Element>().disabled_state() &&
+            !self.ReadOnly() &&
+            !self
+                .upcast::<Node>()
+                .ancestors()
+                .any(|ancestor| ancestor.is::<HTMLDataListElement>())
+    }
+
+    fn validate(&self, validate_flags: ValidationFlags) -> bool {
+        // https://html.spec.whatwg.org/multipage/#the-textarea-element%3Asuffering-from-being-missing
+        let mutable = !self.upcast::<Element>().disabled_state() && !self.ReadOnly();
+        !(validate_flags.contains(ValidationFlags::VALUE_MISSING) &&
+            self.Required() &&
+            mutable &&
+            self.textinput.borrow().is_empty())
+    }
+}
@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::LayoutShiftBinding;
+use crate::dom::bindings::codegen::Bindings::LayoutShiftBinding::LayoutShiftMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::layoutshiftattribution::LayoutShiftAttribution;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+
+/// Layout shifts that happen this long after an input, in nanoseconds, are
+/// flagged as expected by the user.
+/// <https://wicg.github.io/layout-instability/#sec-input-exclusion>
+const RECENT_INPUT_NS: u64 = 500_000_000;
+
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift>
+#[dom_struct]
+pub struct LayoutShift {
+    entry: PerformanceEntry,
+    value: f64,
+    had_recent_input: bool,
+    last_input_time: f64,
+    sources: Vec<Dom<LayoutShiftAttribution>>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_sources: DomRefCell<Option<Heap<JSVal>>>,
+}
+
+impl LayoutShift {
+    fn new_inherited(
+        start_time: f64,
+        value: f64,
+        had_recent_input: bool,
+        last_input_time: f64,
+        sources: &[DomRoot<LayoutShiftAttribution>],
+    ) -> LayoutShift {
+        LayoutShift {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("layout-shift"),
+                DOMString::from("layout-shift"),
+                start_time,
+                0.,
+            ),
+            value,
+            had_recent_input,
+            last_input_time,
+            sources: sources
+                .iter()
+                .map(|source| Dom::from_ref(&**source))
+                .collect(),
+            frozen_sources: DomRefCell::new(None),
+        }
+    }
+
+    /// Creates the entry of a layout shift of `window` with the given score,
+    /// that layout observed at `time`, in nanoseconds.
+    /// <https://wicg.github.io/layout-instability/#report-the-layout-shift>
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        time: u64,
+        score: f64,
+        sources: &[DomRoot<LayoutShiftAttribution>],
+    ) -> DomRoot<LayoutShift> {
+        let performance = window.Performance();
+        let last_input = window.Document().last_input_time();
+        let had_recent_input =
+            last_input.map_or(false, |input| time.saturating_sub(input) < RECENT_INPUT_NS);
+        let last_input_time =
+            last_input.map_or(0., |input| performance.to_dom_high_res_time_stamp(input));
+        let entry = LayoutShift::new_inherited(
+            performance.to_dom_high_res_time_stamp(time),
+            score,
+            had_recent_input,
+            last_input_time,
+            sources,
+        );
+        reflect_dom_object(Box::new(entry), window, LayoutShiftBinding::Wrap)
+    }
+}
+
+impl LayoutShiftMethods for LayoutShift {
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-value
+    fn Value(&self) -> Finite<f64> {
+        Finite::wrap(self.value)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-hadrecentinput
+    fn HadRecentInput(&self) -> bool {
+        self.had_recent_input
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-lastinputtime
+    fn LastInputTime(&self) -> Finite<f64> {
+        Finite::wrap(self.last_input_time)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshift-sources
+    fn Sources(&self, cx: JSContext) -> JSVal {
+        if let Some(sources) = &*self.frozen_sources.borrow() {
+            return sources.get();
+        }
+
+        let sources: Vec<DomRoot<LayoutShiftAttribution>> = self
+            .sources
+            .iter()
+            .map(|source| DomRoot::from_ref(&**source))
+            .collect();
+        let frozen_sources = to_frozen_array(&sources, cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_sources.borrow_mut() = Some(Heap::default());
+        self.frozen_sources
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_sources);
+
+        frozen_sources
+    }
+}
@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::LayoutShiftAttributionBinding;
+use crate::dom::bindings::codegen::Bindings::LayoutShiftAttributionBinding::LayoutShiftAttributionMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use euclid::default::Rect;
+
+/// A node that moved in a layout shift, with its visible rects before and after.
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift-attribution>
+#[dom_struct]
+pub struct LayoutShiftAttribution {
+    reflector_: Reflector,
+    node: Dom<Node>,
+    previous_rect: Dom<DOMRectReadOnly>,
+    current_rect: Dom<DOMRectReadOnly>,
+}
+
+impl LayoutShiftAttribution {
+    fn new_inherited(
+        node: &Node,
+        previous_rect: &DOMRectReadOnly,
+        current_rect: &DOMRectReadOnly,
+    ) -> LayoutShiftAttribution {
+        LayoutShiftAttribution {
+            reflector_: Reflector::new(),
+            node: Dom::from_ref(node),
+            previous_rect: Dom::from_ref(previous_rect),
+            current_rect: Dom::from_ref(current_rect),
+        }
+    }
+
+    /// Creates an attribution to `node`, which moved from `previous_rect` to
+    /// `current_rect`, relative to the viewport.
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        node: &Node,
+        previous_rect: Rect<f32>,
+        current_rect: Rect<f32>,
+    ) -> DomRoot<LayoutShiftAttribution> {
+        let to_dom_rect = |rect: Rect<f32>| {
+            DOMRectReadOnly::new(
+                window.upcast(),
+                rect.origin.x as f64,
+                rect.origin.y as f64,
+                rect.size.width as f64,
+                rect.size.height as f64,
+            )
+        };
+        let previous_rect = to_dom_rect(previous_rect);
+        let current_rect = to_dom_rect(current_rect);
+        reflect_dom_object(
+            Box::new(LayoutShiftAttribution::new_inherited(
+                node,
+                &previous_rect,
+                &current_rect,
+            )),
+            window,
+            LayoutShiftAttributionBinding::Wrap,
+        )
+    }
+}
+
+impl LayoutShiftAttributionMethods for LayoutShiftAttribution {
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-node
+    fn GetNode(&self) -> Option<DomRoot<Node>> {
+        Some(DomRoot::from_ref(&*self.node))
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-previousrect
+    fn PreviousRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.previous_rect)
+    }
+
+    // https://wicg.github.io/layout-instability/#dom-layoutshiftattribution-currentrect
+    fn CurrentRect(&self) -> DomRoot<DOMRectReadOnly> {
+        DomRoot::from_ref(&*self.current_rect)
+    }
+}
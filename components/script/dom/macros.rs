@@ -496,6 +496,10 @@ macro_rules! global_event_handlers(
         event_handler!(suspend, GetOnsuspend, SetOnsuspend);
         event_handler!(timeupdate, GetOntimeupdate, SetOntimeupdate);
         event_handler!(toggle, GetOntoggle, SetOntoggle);
+        event_handler!(touchcancel, GetOntouchcancel, SetOntouchcancel);
+        event_handler!(touchend, GetOntouchend, SetOntouchend);
+        event_handler!(touchmove, GetOntouchmove, SetOntouchmove);
+        event_handler!(touchstart, GetOntouchstart, SetOntouchstart);
         event_handler!(transitionend, GetOntransitionend, SetOntransitionend);
         event_handler!(volumechange, GetOnvolumechange, SetOnvolumechange);
         event_handler!(waiting, GetOnwaiting, SetOnwaiting);
@@ -598,45 +602,6 @@ macro_rules! rooted_vec {
     };
 }
 
-/// DOM struct implementation for simple interfaces inheriting from PerformanceEntry.
-macro_rules! impl_performance_entry_struct(
-    ($binding:ident, $struct:ident, $type:expr) => (
-        use crate::dom::bindings::codegen::Bindings::$binding;
-        use crate::dom::bindings::reflector::reflect_dom_object;
-        use crate::dom::bindings::root::DomRoot;
-        use crate::dom::bindings::str::DOMString;
-        use crate::dom::globalscope::GlobalScope;
-        use crate::dom::performanceentry::PerformanceEntry;
-        use dom_struct::dom_struct;
-
-        #[dom_struct]
-        pub struct $struct {
-            entry: PerformanceEntry,
-        }
-
-        impl $struct {
-            fn new_inherited(name: DOMString, start_time: f64, duration: f64)
-                -> $struct {
-                $struct {
-                    entry: PerformanceEntry::new_inherited(name,
-                                                           DOMString::from($type),
-                                                           start_time,
-                                                           duration)
-                }
-            }
-
-            #[allow(unrooted_must_root)]
-            pub fn new(global: &GlobalScope,
-                       name: DOMString,
-                       start_time: f64,
-                       duration: f64) -> DomRoot<$struct> {
-                let entry = $struct::new_inherited(name, start_time, duration);
-                reflect_dom_object(Box::new(entry), global, $binding::Wrap)
-            }
-        }
-    );
-);
-
 macro_rules! handle_potential_webgl_error {
     ($context:expr, $call:expr, $return_on_error:expr) => {
         match $call {
@@ -0,0 +1,225 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::MIDIAccessBinding::{self, MIDIAccessMethods};
+use crate::dom::bindings::codegen::Bindings::NavigatorBinding::MIDIOptions;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::midiinput::MIDIInput;
+use crate::dom::midiinputmap::MIDIInputMap;
+use crate::dom::midioutput::MIDIOutput;
+use crate::dom::midioutputmap::MIDIOutputMap;
+use crate::dom::midiport::MIDIPort;
+use crate::dom::permissions::{get_descriptor_permission_state, prompt_user_from_embedder};
+use crate::dom::promise::Promise;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::{self, EmbedderMsg, MidiEvent, MidiPortInfo, MidiPortType, PermissionPrompt};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct MIDIAccess {
+    eventtarget: EventTarget,
+    inputs: Dom<MIDIInputMap>,
+    outputs: Dom<MIDIOutputMap>,
+    sysex_enabled: bool,
+}
+
+impl MIDIAccess {
+    fn new_inherited(global: &GlobalScope, sysex_enabled: bool) -> MIDIAccess {
+        MIDIAccess {
+            eventtarget: EventTarget::new_inherited(),
+            inputs: Dom::from_ref(&*MIDIInputMap::new(global)),
+            outputs: Dom::from_ref(&*MIDIOutputMap::new(global)),
+            sysex_enabled,
+        }
+    }
+
+    fn new(global: &GlobalScope, sysex_enabled: bool) -> DomRoot<MIDIAccess> {
+        reflect_dom_object(
+            Box::new(MIDIAccess::new_inherited(global, sysex_enabled)),
+            global,
+            MIDIAccessBinding::Wrap,
+        )
+    }
+
+    /// https://webaudio.github.io/web-midi-api/#dom-navigator-requestmidiaccess
+    pub fn request(global: &GlobalScope, options: &MIDIOptions, promise: &Rc<Promise>) {
+        // System exclusive messages can do much more than play notes,
+        // so access to them needs the user's permission.
+        if options.sysex && !MIDIAccess::sysex_permission_granted(global) {
+            promise.reject_error(Error::Security);
+            return;
+        }
+
+        let access = MIDIAccess::new(global, options.sysex);
+        let (ports_sender, ports_receiver) = ipc::channel().expect("ipc channel failure");
+        let (event_sender, event_receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+
+        let trusted_access = Trusted::new(&*access);
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        let ports_task_source = task_source.clone();
+        let ports_canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        ROUTER.add_route(
+            ports_receiver.to_opaque(),
+            Box::new(move |message| {
+                let ports: Option<Vec<MidiPortInfo>> = message.to().unwrap();
+                let promise = match trusted_promise.take() {
+                    Some(promise) => promise,
+                    None => {
+                        warn!("MIDI ports were sent more than once");
+                        return;
+                    },
+                };
+                let access = trusted_access.clone();
+                let result = ports_task_source.queue_with_canceller(
+                    task!(midi_access_requested: move || {
+                        let access = access.root();
+                        let promise = promise.root();
+                        match ports {
+                            Some(ports) => {
+                                for info in ports {
+                                    access.add_port(info);
+                                }
+                                promise.resolve_native(&*access);
+                            },
+                            None => promise.reject_error(Error::Security),
+                        }
+                    }),
+                    &ports_canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue MIDI access task {:?}", err);
+                }
+            }),
+        );
+
+        let trusted_access = Trusted::new(&*access);
+        ROUTER.add_route(
+            event_receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: MidiEvent = message.to().unwrap();
+                let access = trusted_access.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(handle_midi_event: move || {
+                        access.root().handle_midi_event(event);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue MIDI event task {:?}", err);
+                }
+            }),
+        );
+
+        global.send_to_embedder(EmbedderMsg::RequestMidiAccess(
+            options.sysex,
+            ports_sender,
+            event_sender,
+        ));
+    }
+
+    /// https://webaudio.github.io/web-midi-api/#permissions-integration
+    fn sysex_permission_granted(global: &GlobalScope) -> bool {
+        let state = match get_descriptor_permission_state(PermissionName::Midi, Some(global)) {
+            PermissionState::Prompt => {
+                let prompt = PermissionPrompt::Request(embedder_traits::PermissionName::Midi);
+                let state = prompt_user_from_embedder(prompt, global);
+                global
+                    .permission_state_invocation_results()
+                    .borrow_mut()
+                    .insert(PermissionName::Midi.to_string(), state);
+                state
+            },
+            state => state,
+        };
+        state == PermissionState::Granted
+    }
+
+    pub fn sysex_enabled(&self) -> bool {
+        self.sysex_enabled
+    }
+
+    fn add_port(&self, info: MidiPortInfo) -> DomRoot<MIDIPort> {
+        let global = self.global();
+        match info.port_type {
+            MidiPortType::Input => {
+                let input = MIDIInput::new(&global, self, info);
+                self.inputs.add(&input);
+                DomRoot::upcast(input)
+            },
+            MidiPortType::Output => {
+                let output = MIDIOutput::new(&global, self, info);
+                self.outputs.add(&output);
+                DomRoot::upcast(output)
+            },
+        }
+    }
+
+    fn find_port(&self, id: &str) -> Option<DomRoot<MIDIPort>> {
+        self.inputs
+            .find(id)
+            .map(DomRoot::upcast)
+            .or_else(|| self.outputs.find(id).map(DomRoot::upcast))
+    }
+
+    fn handle_midi_event(&self, event: MidiEvent) {
+        match event {
+            // https://webaudio.github.io/web-midi-api/#dfn-midiport-connected
+            MidiEvent::PortConnected(info) => {
+                let port = match self.find_port(&info.id) {
+                    Some(port) => {
+                        port.set_connected();
+                        port
+                    },
+                    None => self.add_port(info),
+                };
+                port.fire_statechange();
+            },
+            // https://webaudio.github.io/web-midi-api/#dfn-midiport-disconnected
+            MidiEvent::PortDisconnected(id) => {
+                if let Some(port) = self.find_port(&id) {
+                    port.set_disconnected();
+                    port.fire_statechange();
+                }
+            },
+            MidiEvent::Message(id, data) => {
+                if let Some(input) = self.inputs.find(&id) {
+                    input.receive_message(data);
+                }
+            },
+        }
+    }
+}
+
+impl MIDIAccessMethods for MIDIAccess {
+    // https://webaudio.github.io/web-midi-api/#dom-midiaccess-inputs
+    fn Inputs(&self) -> DomRoot<MIDIInputMap> {
+        DomRoot::from_ref(&*self.inputs)
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiaccess-outputs
+    fn Outputs(&self) -> DomRoot<MIDIOutputMap> {
+        DomRoot::from_ref(&*self.outputs)
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiaccess-onstatechange
+    event_handler!(statechange, GetOnstatechange, SetOnstatechange);
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiaccess-sysexenabled
+    fn SysexEnabled(&self) -> bool {
+        self.sysex_enabled
+    }
+}
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::MIDIConnectionEventBinding::{
+    self, MIDIConnectionEventInit, MIDIConnectionEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::midiport::MIDIPort;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct MIDIConnectionEvent {
+    event: Event,
+    port: Dom<MIDIPort>,
+}
+
+impl MIDIConnectionEvent {
+    fn new_inherited(port: &MIDIPort) -> MIDIConnectionEvent {
+        MIDIConnectionEvent {
+            event: Event::new_inherited(),
+            port: Dom::from_ref(port),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        port: &MIDIPort,
+    ) -> DomRoot<MIDIConnectionEvent> {
+        let ev = reflect_dom_object(
+            Box::new(MIDIConnectionEvent::new_inherited(port)),
+            global,
+            MIDIConnectionEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    // https://webaudio.github.io/web-midi-api/#midiconnectionevent-interface
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &MIDIConnectionEventInit,
+    ) -> Fallible<DomRoot<MIDIConnectionEvent>> {
+        Ok(MIDIConnectionEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            &init.port,
+        ))
+    }
+}
+
+impl MIDIConnectionEventMethods for MIDIConnectionEvent {
+    // https://webaudio.github.io/web-midi-api/#dom-midiconnectionevent-port
+    fn Port(&self) -> DomRoot<MIDIPort> {
+        DomRoot::from_ref(&*self.port)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
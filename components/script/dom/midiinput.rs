@@ -0,0 +1,75 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::codegen::Bindings::MIDIInputBinding::{self, MIDIInputMethods};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::midiaccess::MIDIAccess;
+use crate::dom::midimessageevent::MIDIMessageEvent;
+use crate::dom::midiport::MIDIPort;
+use dom_struct::dom_struct;
+use embedder_traits::MidiPortInfo;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct MIDIInput {
+    port: MIDIPort,
+}
+
+impl MIDIInput {
+    fn new_inherited(access: &MIDIAccess, info: MidiPortInfo) -> MIDIInput {
+        MIDIInput {
+            port: MIDIPort::new_inherited(access, info),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        access: &MIDIAccess,
+        info: MidiPortInfo,
+    ) -> DomRoot<MIDIInput> {
+        reflect_dom_object(
+            Box::new(MIDIInput::new_inherited(access, info)),
+            global,
+            MIDIInputBinding::Wrap,
+        )
+    }
+
+    /// https://webaudio.github.io/web-midi-api/#midiinput-interface
+    pub fn receive_message(&self, data: Vec<u8>) {
+        // Messages are only delivered to open ports.
+        if !self.port.is_open() {
+            return;
+        }
+        let event = MIDIMessageEvent::new(
+            &self.global(),
+            atom!("midimessage"),
+            false,
+            false,
+            Some(data),
+        );
+        event.upcast::<Event>().fire(self.upcast());
+    }
+}
+
+impl MIDIInputMethods for MIDIInput {
+    // https://webaudio.github.io/web-midi-api/#dom-midiinput-onmidimessage
+    fn GetOnmidimessage(&self) -> Option<Rc<EventHandlerNonNull>> {
+        self.upcast::<EventTarget>()
+            .get_event_handler_common("midimessage")
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiinput-onmidimessage
+    fn SetOnmidimessage(&self, listener: Option<Rc<EventHandlerNonNull>>) {
+        self.upcast::<EventTarget>()
+            .set_event_handler_common("midimessage", listener);
+        // Setting the handler implicitly opens the port.
+        self.port.open();
+    }
+}
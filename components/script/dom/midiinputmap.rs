@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MIDIInputMapBinding;
+use crate::dom::bindings::codegen::Bindings::MIDIInputMapBinding::MIDIInputMapMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::iterable::Iterable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::midiinput::MIDIInput;
+use crate::dom::midiport::MIDIPort;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct MIDIInputMap {
+    reflector_: Reflector,
+    inputs: DomRefCell<Vec<Dom<MIDIInput>>>,
+}
+
+impl MIDIInputMap {
+    fn new_inherited() -> MIDIInputMap {
+        MIDIInputMap {
+            reflector_: Reflector::new(),
+            inputs: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<MIDIInputMap> {
+        reflect_dom_object(
+            Box::new(MIDIInputMap::new_inherited()),
+            global,
+            MIDIInputMapBinding::MIDIInputMapWrap,
+        )
+    }
+
+    pub fn add(&self, input: &MIDIInput) {
+        self.inputs.borrow_mut().push(Dom::from_ref(input));
+    }
+
+    pub fn find(&self, id: &str) -> Option<DomRoot<MIDIInput>> {
+        self.inputs
+            .borrow()
+            .iter()
+            .find(|input| &**input.upcast::<MIDIPort>().id() == id)
+            .map(|input| DomRoot::from_ref(&**input))
+    }
+}
+
+impl MIDIInputMapMethods for MIDIInputMap {
+    // https://webaudio.github.io/web-midi-api/#midiinputmap-interface
+    fn Size(&self) -> u32 {
+        self.inputs.borrow().len() as u32
+    }
+
+    // https://webaudio.github.io/web-midi-api/#midiinputmap-interface
+    fn Get(&self, key: DOMString) -> Option<DomRoot<MIDIInput>> {
+        self.find(&key)
+    }
+
+    // https://webaudio.github.io/web-midi-api/#midiinputmap-interface
+    fn Has(&self, key: DOMString) -> bool {
+        self.find(&key).is_some()
+    }
+}
+
+impl Iterable for MIDIInputMap {
+    type Key = DOMString;
+    type Value = DomRoot<MIDIInput>;
+
+    fn get_iterable_length(&self) -> u32 {
+        self.Size()
+    }
+
+    fn get_value_at_index(&self, index: u32) -> DomRoot<MIDIInput> {
+        DomRoot::from_ref(&*self.inputs.borrow()[index as usize])
+    }
+
+    fn get_key_at_index(&self, index: u32) -> DOMString {
+        self.inputs.borrow()[index as usize]
+            .upcast::<MIDIPort>()
+            .id()
+            .clone()
+    }
+}
@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::MIDIMessageEventBinding::{
+    self, MIDIMessageEventInit, MIDIMessageEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::JSObject;
+use js::typedarray::{CreateWith, Uint8Array};
+use servo_atoms::Atom;
+use std::ptr::{self, NonNull};
+
+#[dom_struct]
+pub struct MIDIMessageEvent {
+    event: Event,
+    data: Option<Vec<u8>>,
+}
+
+impl MIDIMessageEvent {
+    fn new_inherited(data: Option<Vec<u8>>) -> MIDIMessageEvent {
+        MIDIMessageEvent {
+            event: Event::new_inherited(),
+            data,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        data: Option<Vec<u8>>,
+    ) -> DomRoot<MIDIMessageEvent> {
+        let ev = reflect_dom_object(
+            Box::new(MIDIMessageEvent::new_inherited(data)),
+            global,
+            MIDIMessageEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    // https://webaudio.github.io/web-midi-api/#midimessageevent-interface
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: RootedTraceableBox<MIDIMessageEventInit>,
+    ) -> Fallible<DomRoot<MIDIMessageEvent>> {
+        Ok(MIDIMessageEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init.parent.bubbles,
+            init.parent.cancelable,
+            init.data.as_ref().map(|data| data.to_vec()),
+        ))
+    }
+}
+
+impl MIDIMessageEventMethods for MIDIMessageEvent {
+    // https://webaudio.github.io/web-midi-api/#dom-midimessageevent-data
+    #[allow(unsafe_code)]
+    fn GetData(&self, cx: JSContext) -> Option<NonNull<JSObject>> {
+        let data = self.data.as_ref()?;
+        rooted!(in (*cx) let mut array = ptr::null_mut::<JSObject>());
+        unsafe {
+            if Uint8Array::create(*cx, CreateWith::Slice(data), array.handle_mut()).is_err() {
+                return None;
+            }
+            Some(NonNull::new_unchecked(array.get()))
+        }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
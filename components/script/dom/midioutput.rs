@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MIDIOutputBinding::{self, MIDIOutputMethods};
+use crate::dom::bindings::codegen::Bindings::MIDIPortBinding::{
+    MIDIPortDeviceState, MIDIPortMethods,
+};
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::midiaccess::MIDIAccess;
+use crate::dom::midiport::MIDIPort;
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, MidiPortInfo};
+use script_traits::MsDuration;
+use std::cell::Cell;
+
+/// Sends a message that was waiting for its timestamp.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct MidiSendCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    output: Trusted<MIDIOutput>,
+    id: u32,
+}
+
+impl MidiSendCallback {
+    pub fn invoke(self) {
+        self.output.root().send_pending_message(self.id);
+    }
+}
+
+/// A message sent with a timestamp in the future.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PendingMidiMessage {
+    id: u32,
+    timer: OneshotTimerHandle,
+    data: Vec<u8>,
+}
+
+#[dom_struct]
+pub struct MIDIOutput {
+    port: MIDIPort,
+    pending_messages: DomRefCell<Vec<PendingMidiMessage>>,
+    next_message_id: Cell<u32>,
+}
+
+impl MIDIOutput {
+    fn new_inherited(access: &MIDIAccess, info: MidiPortInfo) -> MIDIOutput {
+        MIDIOutput {
+            port: MIDIPort::new_inherited(access, info),
+            pending_messages: Default::default(),
+            next_message_id: Cell::new(0),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        access: &MIDIAccess,
+        info: MidiPortInfo,
+    ) -> DomRoot<MIDIOutput> {
+        reflect_dom_object(
+            Box::new(MIDIOutput::new_inherited(access, info)),
+            global,
+            MIDIOutputBinding::Wrap,
+        )
+    }
+
+    fn send_message(&self, data: Vec<u8>) {
+        self.global().send_to_embedder(EmbedderMsg::SendMidiMessage(
+            self.port.id().to_string(),
+            data,
+        ));
+    }
+
+    fn send_pending_message(&self, id: u32) {
+        let message = {
+            let mut pending_messages = self.pending_messages.borrow_mut();
+            let index = match pending_messages.iter().position(|message| message.id == id) {
+                Some(index) => index,
+                None => return,
+            };
+            pending_messages.remove(index)
+        };
+        self.send_message(message.data);
+    }
+}
+
+impl MIDIOutputMethods for MIDIOutput {
+    // https://webaudio.github.io/web-midi-api/#dom-midioutput-send
+    fn Send(&self, data: Vec<u8>, timestamp: Finite<f64>) -> Fallible<()> {
+        // Step 1.
+        let has_sysex = match validate_midi_messages(&data) {
+            Some(has_sysex) => has_sysex,
+            None => return Err(Error::Type("Invalid MIDI message".to_owned())),
+        };
+
+        // Step 2.
+        if has_sysex && !self.port.sysex_enabled() {
+            return Err(Error::InvalidAccess);
+        }
+
+        // Step 3.
+        if self.port.State() == MIDIPortDeviceState::Disconnected {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 4.
+        self.port.open();
+
+        // Step 5. A timestamp of zero, or in the past, means now.
+        let global = self.global();
+        let delay = *timestamp - *global.performance().Now();
+        if delay <= 0. {
+            self.send_message(data);
+            return Ok(());
+        }
+        let id = self.next_message_id.get();
+        self.next_message_id.set(id.wrapping_add(1));
+        let callback = OneshotTimerCallback::MidiSend(MidiSendCallback {
+            output: Trusted::new(self),
+            id,
+        });
+        let timer = global.schedule_callback(callback, MsDuration::new(delay.ceil() as u64));
+        self.pending_messages
+            .borrow_mut()
+            .push(PendingMidiMessage { id, timer, data });
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midioutput-clear
+    fn Clear(&self) {
+        let global = self.global();
+        for message in self.pending_messages.borrow_mut().drain(..) {
+            global.unschedule_callback(message.timer);
+        }
+    }
+}
+
+/// Checks that `data` is a sequence of complete MIDI messages, without running
+/// status. Returns whether any of them is a system exclusive message, or `None`
+/// if the data is invalid.
+pub fn validate_midi_messages(data: &[u8]) -> Option<bool> {
+    let mut has_sysex = false;
+    let mut index = 0;
+    while index < data.len() {
+        let status = data[index];
+        let length = match status {
+            0x80..=0xBF | 0xE0..=0xEF | 0xF2 => 3,
+            0xC0..=0xDF | 0xF1 | 0xF3 => 2,
+            0xF6 | 0xF8 | 0xFA..=0xFC | 0xFE | 0xFF => 1,
+            0xF0 => {
+                // A system exclusive message runs up to the next end of
+                // exclusive byte.
+                has_sysex = true;
+                data[index + 1..]
+                    .iter()
+                    .position(|&byte| byte == 0xF7)
+                    .map(|end| end + 2)?
+            },
+            _ => return None,
+        };
+        let message = data.get(index..index + length)?;
+        let data_bytes = if status == 0xF0 {
+            &message[1..length - 1]
+        } else {
+            &message[1..]
+        };
+        if data_bytes.iter().any(|&byte| byte >= 0x80) {
+            return None;
+        }
+        index += length;
+    }
+    Some(has_sysex)
+}
@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MIDIOutputMapBinding;
+use crate::dom::bindings::codegen::Bindings::MIDIOutputMapBinding::MIDIOutputMapMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::iterable::Iterable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::midioutput::MIDIOutput;
+use crate::dom::midiport::MIDIPort;
+use dom_struct::dom_struct;
+
+#[dom_struct]
+pub struct MIDIOutputMap {
+    reflector_: Reflector,
+    outputs: DomRefCell<Vec<Dom<MIDIOutput>>>,
+}
+
+impl MIDIOutputMap {
+    fn new_inherited() -> MIDIOutputMap {
+        MIDIOutputMap {
+            reflector_: Reflector::new(),
+            outputs: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<MIDIOutputMap> {
+        reflect_dom_object(
+            Box::new(MIDIOutputMap::new_inherited()),
+            global,
+            MIDIOutputMapBinding::MIDIOutputMapWrap,
+        )
+    }
+
+    pub fn add(&self, output: &MIDIOutput) {
+        self.outputs.borrow_mut().push(Dom::from_ref(output));
+    }
+
+    pub fn find(&self, id: &str) -> Option<DomRoot<MIDIOutput>> {
+        self.outputs
+            .borrow()
+            .iter()
+            .find(|output| &**output.upcast::<MIDIPort>().id() == id)
+            .map(|output| DomRoot::from_ref(&**output))
+    }
+}
+
+impl MIDIOutputMapMethods for MIDIOutputMap {
+    // https://webaudio.github.io/web-midi-api/#midioutputmap-interface
+    fn Size(&self) -> u32 {
+        self.outputs.borrow().len() as u32
+    }
+
+    // https://webaudio.github.io/web-midi-api/#midioutputmap-interface
+    fn Get(&self, key: DOMString) -> Option<DomRoot<MIDIOutput>> {
+        self.find(&key)
+    }
+
+    // https://webaudio.github.io/web-midi-api/#midioutputmap-interface
+    fn Has(&self, key: DOMString) -> bool {
+        self.find(&key).is_some()
+    }
+}
+
+impl Iterable for MIDIOutputMap {
+    type Key = DOMString;
+    type Value = DomRoot<MIDIOutput>;
+
+    fn get_iterable_length(&self) -> u32 {
+        self.Size()
+    }
+
+    fn get_value_at_index(&self, index: u32) -> DomRoot<MIDIOutput> {
+        DomRoot::from_ref(&*self.outputs.borrow()[index as usize])
+    }
+
+    fn get_key_at_index(&self, index: u32) -> DOMString {
+        self.outputs.borrow()[index as usize]
+            .upcast::<MIDIPort>()
+            .id()
+            .clone()
+    }
+}
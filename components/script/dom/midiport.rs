@@ -0,0 +1,206 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::MIDIOutputBinding::MIDIOutputMethods;
+use crate::dom::bindings::codegen::Bindings::MIDIPortBinding::{
+    MIDIPortConnectionState, MIDIPortDeviceState, MIDIPortMethods, MIDIPortType,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::Dom;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::midiaccess::MIDIAccess;
+use crate::dom::midiconnectionevent::MIDIConnectionEvent;
+use crate::dom::midioutput::MIDIOutput;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use embedder_traits::{MidiPortInfo, MidiPortType};
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct MIDIPort {
+    eventtarget: EventTarget,
+    access: Dom<MIDIAccess>,
+    id: DOMString,
+    manufacturer: Option<DOMString>,
+    name: Option<DOMString>,
+    version: Option<DOMString>,
+    port_type: MIDIPortType,
+    state: Cell<MIDIPortDeviceState>,
+    connection: Cell<MIDIPortConnectionState>,
+}
+
+impl MIDIPort {
+    pub fn new_inherited(access: &MIDIAccess, info: MidiPortInfo) -> MIDIPort {
+        MIDIPort {
+            eventtarget: EventTarget::new_inherited(),
+            access: Dom::from_ref(access),
+            id: DOMString::from(info.id),
+            manufacturer: info.manufacturer.map(DOMString::from),
+            name: info.name.map(DOMString::from),
+            version: info.version.map(DOMString::from),
+            port_type: match info.port_type {
+                MidiPortType::Input => MIDIPortType::Input,
+                MidiPortType::Output => MIDIPortType::Output,
+            },
+            state: Cell::new(MIDIPortDeviceState::Connected),
+            connection: Cell::new(MIDIPortConnectionState::Closed),
+        }
+    }
+
+    pub fn id(&self) -> &DOMString {
+        &self.id
+    }
+
+    pub fn sysex_enabled(&self) -> bool {
+        self.access.sysex_enabled()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.connection.get() == MIDIPortConnectionState::Open
+    }
+
+    /// https://webaudio.github.io/web-midi-api/#dom-midiport-open
+    pub fn open(&self) {
+        // Step 3.
+        if self.connection.get() == MIDIPortConnectionState::Open {
+            return;
+        }
+
+        // Steps 4-5.
+        let connection = match self.state.get() {
+            MIDIPortDeviceState::Disconnected => MIDIPortConnectionState::Pending,
+            MIDIPortDeviceState::Connected => MIDIPortConnectionState::Open,
+        };
+        if self.connection.get() != connection {
+            self.connection.set(connection);
+            self.queue_statechange();
+        }
+    }
+
+    /// https://webaudio.github.io/web-midi-api/#dom-midiport-close
+    pub fn close(&self) {
+        // Step 3.
+        if self.connection.get() == MIDIPortConnectionState::Closed {
+            return;
+        }
+
+        // Step 4.
+        if let Some(output) = self.downcast::<MIDIOutput>() {
+            output.Clear();
+        }
+
+        // Steps 5-6.
+        self.connection.set(MIDIPortConnectionState::Closed);
+        self.queue_statechange();
+    }
+
+    /// Called when the embedder reports that the device of this port became
+    /// available again.
+    pub fn set_connected(&self) {
+        self.state.set(MIDIPortDeviceState::Connected);
+        // A pending port is opened as soon as its device is reconnected.
+        if self.connection.get() == MIDIPortConnectionState::Pending {
+            self.connection.set(MIDIPortConnectionState::Open);
+        }
+    }
+
+    /// Called when the embedder reports that the device of this port went
+    /// away.
+    pub fn set_disconnected(&self) {
+        self.state.set(MIDIPortDeviceState::Disconnected);
+        if self.connection.get() == MIDIPortConnectionState::Open {
+            self.connection.set(MIDIPortConnectionState::Pending);
+        }
+    }
+
+    /// Queues a task to fire `statechange` at this port and its MIDIAccess,
+    /// for changes made by script rather than by the device.
+    fn queue_statechange(&self) {
+        let global = self.global();
+        let port = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(midi_port_statechange: move || {
+                port.root().fire_statechange();
+            }),
+            &global,
+        );
+    }
+
+    /// Fires a `statechange` event at this port, and then at the MIDIAccess
+    /// object it belongs to.
+    pub fn fire_statechange(&self) {
+        let global = self.global();
+        let event = MIDIConnectionEvent::new(&global, atom!("statechange"), false, false, self);
+        event.upcast::<Event>().fire(self.upcast());
+        let event = MIDIConnectionEvent::new(&global, atom!("statechange"), false, false, self);
+        event.upcast::<Event>().fire(self.access.upcast());
+    }
+}
+
+impl MIDIPortMethods for MIDIPort {
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-manufacturer
+    fn GetManufacturer(&self) -> Option<DOMString> {
+        self.manufacturer.clone()
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-name
+    fn GetName(&self) -> Option<DOMString> {
+        self.name.clone()
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-type
+    fn Type(&self) -> MIDIPortType {
+        self.port_type
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-version
+    fn GetVersion(&self) -> Option<DOMString> {
+        self.version.clone()
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-state
+    fn State(&self) -> MIDIPortDeviceState {
+        self.state.get()
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-connection
+    fn Connection(&self) -> MIDIPortConnectionState {
+        self.connection.get()
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-onstatechange
+    event_handler!(statechange, GetOnstatechange, SetOnstatechange);
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-open
+    fn Open(&self, comp: InRealm) -> Rc<Promise> {
+        // Steps 1-2.
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        self.open();
+        // Step 6.
+        promise.resolve_native(self);
+        promise
+    }
+
+    // https://webaudio.github.io/web-midi-api/#dom-midiport-close
+    fn Close(&self, comp: InRealm) -> Rc<Promise> {
+        // Steps 1-2.
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        self.close();
+        // Step 7.
+        promise.resolve_native(self);
+        promise
+    }
+}
@@ -224,6 +224,9 @@ pub mod audioparam;
 pub mod audioscheduledsourcenode;
 pub mod audiotrack;
 pub mod audiotracklist;
+pub mod authenticatorassertionresponse;
+pub mod authenticatorattestationresponse;
+pub mod authenticatorresponse;
 pub mod baseaudiocontext;
 pub mod beforeunloadevent;
 pub mod bindings;
@@ -254,7 +257,13 @@ pub mod compositionevent;
 pub mod console;
 pub mod constantsourcenode;
 mod create;
+pub mod cookiechangeevent;
+pub mod cookiestore;
+pub mod credential;
+pub mod credentialscontainer;
 pub mod crypto;
+pub mod cryptokey;
+pub mod cspviolationreportbody;
 pub mod css;
 pub mod cssconditionrule;
 pub mod cssfontfacerule;
@@ -262,19 +271,36 @@ pub mod cssgroupingrule;
 pub mod cssimportrule;
 pub mod csskeyframerule;
 pub mod csskeyframesrule;
+pub mod csskeywordvalue;
 pub mod cssmediarule;
 pub mod cssnamespacerule;
+pub mod cssnumericvalue;
+pub mod csspropertyrule;
+pub mod cssrotate;
 pub mod cssrule;
 pub mod cssrulelist;
+pub mod cssscale;
 pub mod cssstyledeclaration;
 pub mod cssstylerule;
 pub mod cssstylesheet;
 pub mod cssstylevalue;
 pub mod csssupportsrule;
+pub mod csstransformcomponent;
+pub mod csstransformvalue;
+pub mod csstranslate;
+pub mod cssunitvalue;
 pub mod cssviewportrule;
 pub mod customelementregistry;
 pub mod customevent;
+pub mod datatransfer;
+pub mod datatransferitem;
+pub mod datatransferitemlist;
 pub mod dedicatedworkerglobalscope;
+pub mod deprecationreportbody;
+pub mod devicemotionevent;
+pub mod devicemotioneventacceleration;
+pub mod devicemotioneventrotationrate;
+pub mod deviceorientationevent;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
 pub mod document;
@@ -294,6 +320,7 @@ pub mod domrectreadonly;
 pub mod domstringlist;
 pub mod domstringmap;
 pub mod domtokenlist;
+pub mod dragevent;
 pub mod element;
 pub mod errorevent;
 pub mod event;
@@ -308,6 +335,7 @@ pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
 pub mod focusevent;
+pub mod fontfaceset;
 pub mod formdata;
 pub mod formdataevent;
 pub mod gainnode;
@@ -316,6 +344,11 @@ pub mod gamepadbutton;
 pub mod gamepadbuttonlist;
 pub mod gamepadevent;
 pub mod gamepadlist;
+pub mod geolocation;
+pub mod geolocationcoordinates;
+pub mod geolocationposition;
+pub mod geolocationpositionerror;
+pub mod geometryutils;
 pub mod globalscope;
 pub mod gpu;
 pub mod gpuadapter;
@@ -335,6 +368,7 @@ pub mod gpushaderstage;
 pub mod hashchangeevent;
 pub mod headers;
 pub mod history;
+pub mod htmlallcollection;
 pub mod htmlanchorelement;
 pub mod htmlareaelement;
 pub mod htmlaudioelement;
@@ -408,9 +442,12 @@ pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
 pub mod identityhub;
+pub mod idledeadline;
 pub mod imagedata;
 pub mod inputevent;
 pub mod keyboardevent;
+pub mod layoutshift;
+pub mod layoutshiftattribution;
 pub mod location;
 pub mod mediadevices;
 pub mod mediaelementaudiosourcenode;
@@ -426,6 +463,14 @@ pub mod mediastreamtrack;
 pub mod messagechannel;
 pub mod messageevent;
 pub mod messageport;
+pub mod midiaccess;
+pub mod midiconnectionevent;
+pub mod midiinput;
+pub mod midiinputmap;
+pub mod midimessageevent;
+pub mod midioutput;
+pub mod midioutputmap;
+pub mod midiport;
 pub mod mimetype;
 pub mod mimetypearray;
 pub mod mouseevent;
@@ -435,6 +480,8 @@ pub mod namednodemap;
 pub mod navigationpreloadmanager;
 pub mod navigator;
 pub mod navigatorinfo;
+pub mod navigatoruadata;
+pub mod networkinformation;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
@@ -450,6 +497,7 @@ pub mod paintworkletglobalscope;
 pub mod pannernode;
 pub mod performance;
 pub mod performanceentry;
+pub mod performancelongtasktiming;
 pub mod performancemark;
 pub mod performancemeasure;
 pub mod performancenavigation;
@@ -458,6 +506,7 @@ pub mod performanceobserver;
 pub mod performanceobserverentrylist;
 pub mod performancepainttiming;
 pub mod performanceresourcetiming;
+pub mod performanceservertiming;
 pub mod permissions;
 pub mod permissionstatus;
 pub mod plugin;
@@ -468,9 +517,13 @@ pub mod progressevent;
 pub mod promise;
 pub mod promisenativehandler;
 pub mod promiserejectionevent;
+pub mod publickeycredential;
 pub mod radionodelist;
 pub mod range;
 pub mod raredata;
+pub mod report;
+pub mod reportbody;
+pub mod reportingobserver;
 pub mod request;
 pub mod response;
 pub mod rtcicecandidate;
@@ -478,24 +531,36 @@ pub mod rtcpeerconnection;
 pub mod rtcpeerconnectioniceevent;
 pub mod rtcsessiondescription;
 pub mod rtctrackevent;
+pub mod sanitizer;
 pub mod screen;
+pub mod screenorientation;
 pub mod selection;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
+pub mod servointernals;
 pub mod servoparser;
 pub mod shadowroot;
+pub mod speechsynthesis;
+pub mod speechsynthesiserrorevent;
+pub mod speechsynthesisevent;
+pub mod speechsynthesisutterance;
+pub mod speechsynthesisvoice;
 pub mod stereopannernode;
 pub mod storage;
 pub mod storageevent;
+pub mod storagemanager;
+pub mod stylepropertymap;
 pub mod stylepropertymapreadonly;
 pub mod stylesheet;
 pub mod stylesheetlist;
 pub mod submitevent;
+pub mod subtlecrypto;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
+pub mod taskattributiontiming;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingpairiterable;
@@ -574,6 +639,9 @@ pub mod xmlhttprequest;
 pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
 pub mod xmlserializer;
+pub mod xpathevaluator;
+pub mod xpathexpression;
+pub mod xpathresult;
 pub mod xrframe;
 pub mod xrinputsource;
 pub mod xrinputsourcearray;
@@ -592,3 +660,4 @@ pub mod xrview;
 pub mod xrviewerpose;
 pub mod xrviewport;
 pub mod xrwebgllayer;
+pub mod xsltprocessor;
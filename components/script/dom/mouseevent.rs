@@ -41,6 +41,8 @@ pub struct MouseEvent {
     meta_key: Cell<bool>,
     button: Cell<i16>,
     buttons: Cell<u16>,
+    movement_x: Cell<i32>,
+    movement_y: Cell<i32>,
     related_target: MutNullableDom<EventTarget>,
     point_in_target: Cell<Option<Point2D<f32>>>,
 }
@@ -65,6 +67,8 @@ impl MouseEvent {
             meta_key: Cell::new(false),
             button: Cell::new(0),
             buttons: Cell::new(0),
+            movement_x: Cell::new(0),
+            movement_y: Cell::new(0),
             related_target: Default::default(),
             point_in_target: Cell::new(None),
         }
@@ -152,12 +156,24 @@ impl MouseEvent {
             init.relatedTarget.as_deref(),
             None,
         );
+        event.set_movement(init.movementX, init.movementY);
         Ok(event)
     }
 
     pub fn point_in_target(&self) -> Option<Point2D<f32>> {
         self.point_in_target.get()
     }
+
+    /// Sets the buttons that are pressed, which `initMouseEvent` leaves as is.
+    pub fn set_buttons(&self, buttons: u16) {
+        self.buttons.set(buttons);
+    }
+
+    /// Sets how far the mouse moved since the previous `mousemove` event.
+    pub fn set_movement(&self, movement_x: i32, movement_y: i32) {
+        self.movement_x.set(movement_x);
+        self.movement_y.set(movement_y);
+    }
 }
 
 impl MouseEventMethods for MouseEvent {
@@ -283,6 +299,16 @@ impl MouseEventMethods for MouseEvent {
         self.buttons.get()
     }
 
+    // https://w3c.github.io/pointerlock/#dom-mouseevent-movementx
+    fn MovementX(&self) -> i32 {
+        self.movement_x.get()
+    }
+
+    // https://w3c.github.io/pointerlock/#dom-mouseevent-movementy
+    fn MovementY(&self) -> i32 {
+        self.movement_y.get()
+    }
+
     // https://w3c.github.io/uievents/#widl-MouseEvent-relatedTarget
     fn GetRelatedTarget(&self) -> Option<DomRoot<EventTarget>> {
         self.related_target.get()
@@ -28,6 +28,9 @@ pub struct MutationObserver {
     callback: Rc<MutationCallback>,
     record_queue: DomRefCell<Vec<DomRoot<MutationRecord>>>,
     node_list: DomRefCell<Vec<DomRoot<Node>>>,
+    /// The nodes that have a transient registered observer of this observer,
+    /// which are removed when the records are next delivered.
+    transient_node_list: DomRefCell<Vec<DomRoot<Node>>>,
 }
 
 pub enum Mutation<'a> {
@@ -51,9 +54,13 @@ pub enum Mutation<'a> {
 pub struct RegisteredObserver {
     pub observer: DomRoot<MutationObserver>,
     options: ObserverOptions,
+    /// For a transient registered observer, the node on which the registered
+    /// observer it was created from is registered.
+    /// <https://dom.spec.whatwg.org/#transient-registered-observer>
+    source: Option<DomRoot<Node>>,
 }
 
-#[derive(JSTraceable, MallocSizeOf)]
+#[derive(Clone, JSTraceable, MallocSizeOf)]
 pub struct ObserverOptions {
     attribute_old_value: bool,
     attributes: bool,
@@ -61,7 +68,9 @@ pub struct ObserverOptions {
     character_data_old_value: bool,
     child_list: bool,
     subtree: bool,
-    attribute_filter: Vec<DOMString>,
+    /// The local names of the observed attributes, as atoms so that
+    /// unobserved attributes are skipped without comparing strings.
+    attribute_filter: Vec<LocalName>,
 }
 
 impl MutationObserver {
@@ -76,6 +85,7 @@ impl MutationObserver {
             callback: callback,
             record_queue: DomRefCell::new(vec![]),
             node_list: DomRefCell::new(vec![]),
+            transient_node_list: DomRefCell::new(vec![]),
         }
     }
 
@@ -113,7 +123,8 @@ impl MutationObserver {
         for mo in &notify_list {
             let queue: Vec<DomRoot<MutationRecord>> = mo.record_queue.borrow().clone();
             mo.record_queue.borrow_mut().clear();
-            // TODO: Step 5.3 Remove all transient registered observers whose observer is mo.
+            // Step 5.3
+            mo.remove_transient_registered_observers(|_| true);
             if !queue.is_empty() {
                 let _ = mo
                     .callback
@@ -157,12 +168,7 @@ impl MutationObserver {
                             if *namespace != ns!() {
                                 continue;
                             }
-                            if !registered
-                                .options
-                                .attribute_filter
-                                .iter()
-                                .any(|s| &**s == &**name)
-                            {
+                            if !registered.options.attribute_filter.contains(name) {
                                 continue;
                             }
                         }
@@ -247,12 +253,75 @@ impl MutationObserver {
         // Step 5
         MutationObserver::queue_mutation_observer_microtask();
     }
+
+    /// Step 11 of <https://dom.spec.whatwg.org/#concept-node-remove>, which
+    /// keeps the observers of the subtree a node is removed from observing it
+    /// until the records are next delivered.
+    pub fn add_transient_registered_observers(node: &Node, parent: &Node) {
+        if !node.global().as_window().get_exists_mut_observer() {
+            return;
+        }
+        for ancestor in parent.inclusive_ancestors(ShadowIncluding::No) {
+            let transient_observers: Vec<RegisteredObserver> =
+                match ancestor.registered_mutation_observers() {
+                    Some(registered) => registered
+                        .iter()
+                        .filter(|registered| registered.options.subtree)
+                        .map(|registered| RegisteredObserver {
+                            observer: registered.observer.clone(),
+                            options: registered.options.clone(),
+                            source: Some(
+                                registered
+                                    .source
+                                    .clone()
+                                    .unwrap_or_else(|| ancestor.clone()),
+                            ),
+                        })
+                        .collect(),
+                    None => continue,
+                };
+            for transient_observer in transient_observers {
+                transient_observer
+                    .observer
+                    .transient_node_list
+                    .borrow_mut()
+                    .push(DomRoot::from_ref(node));
+                node.add_mutation_observer(transient_observer);
+            }
+        }
+    }
+
+    /// Removes the transient registered observers of this observer whose
+    /// source is registered on a node for which `source_filter` returns true.
+    fn remove_transient_registered_observers<F>(&self, source_filter: F)
+    where
+        F: Fn(&Node) -> bool,
+    {
+        self.transient_node_list.borrow_mut().retain(|node| {
+            let mut registered_observers = node.registered_mutation_observers_mut();
+            registered_observers.retain(|registered| {
+                &*registered.observer != self ||
+                    registered
+                        .source
+                        .as_ref()
+                        .map_or(true, |source| !source_filter(&**source))
+            });
+            registered_observers
+                .iter()
+                .any(|registered| &*registered.observer == self && registered.source.is_some())
+        });
+    }
 }
 
 impl MutationObserverMethods for MutationObserver {
     /// <https://dom.spec.whatwg.org/#dom-mutationobserver-observe>
     fn Observe(&self, target: &Node, options: &MutationObserverInit) -> Fallible<()> {
-        let attribute_filter = options.attributeFilter.clone().unwrap_or(vec![]);
+        let attribute_filter: Vec<LocalName> = options
+            .attributeFilter
+            .iter()
+            .flatten()
+            .map(|name| LocalName::from(&**name))
+            .collect();
         let attribute_old_value = options.attributeOldValue.unwrap_or(false);
         let mut attributes = options.attributes.unwrap_or(false);
         let mut character_data = options.characterData.unwrap_or(false);
@@ -305,11 +374,11 @@ impl MutationObserverMethods for MutationObserver {
             let mut replaced = false;
             for registered in &mut *target.registered_mutation_observers_mut() {
                 if &*registered.observer as *const MutationObserver !=
-                    self as *const MutationObserver
+                    self as *const MutationObserver ||
+                    registered.source.is_some()
                 {
                     continue;
                 }
-                // TODO: remove matching transient registered observers
                 registered.options.attribute_old_value = attribute_old_value;
                 registered.options.attributes = attributes;
                 registered.options.character_data = character_data;
@@ -322,6 +391,11 @@ impl MutationObserverMethods for MutationObserver {
             !replaced
         };
 
+        // Step 7.2
+        if !add_new_observer {
+            self.remove_transient_registered_observers(|source| source == target);
+        }
+
         // Step 8
         if add_new_observer {
             target.add_mutation_observer(RegisteredObserver {
@@ -335,6 +409,7 @@ impl MutationObserverMethods for MutationObserver {
                     attribute_filter,
                     child_list,
                 },
+                source: None,
             });
 
             self.node_list.borrow_mut().push(DomRoot::from_ref(target));
@@ -3,27 +3,58 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding;
-use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::codegen::Bindings::NavigatorBinding::{MIDIOptions, NavigatorMethods};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::credentialscontainer::CredentialsContainer;
 use crate::dom::gamepadlist::GamepadList;
+use crate::dom::geolocation::Geolocation;
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::gpu::GPU;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
+use crate::dom::midiaccess::MIDIAccess;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
+use crate::dom::navigatoruadata::NavigatorUAData;
+use crate::dom::networkinformation::NetworkInformation;
+use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::promise::Promise;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::window::Window;
+use crate::dom::xmlhttprequest::Extractable;
 use crate::dom::xrsystem::XRSystem;
+use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
 use crate::realms::InRealm;
+use crate::script_thread::ScriptThread;
 use dom_struct::dom_struct;
+use http::header::{self, HeaderMap, HeaderValue};
+use http::Method;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use net_traits::request::{
+    is_cors_safelisted_request_header, CredentialsMode, Destination, Referrer, RequestBuilder,
+    RequestMode,
+};
+use net_traits::{CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseListener};
+use net_traits::{NetworkError, ResourceFetchTiming, ResourceTimingType};
+use servo_url::ServoUrl;
+use std::cell::Cell;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// The most body bytes the beacons of a document can have in flight. This
+/// is the same quota the net layer enforces for keepalive requests.
+const MAX_INFLIGHT_BEACON_BYTES: u64 = 64 * 1024;
 
 #[dom_struct]
 pub struct Navigator {
@@ -38,6 +69,13 @@ pub struct Navigator {
     permissions: MutNullableDom<Permissions>,
     mediasession: MutNullableDom<MediaSession>,
     gpu: MutNullableDom<GPU>,
+    connection: MutNullableDom<NetworkInformation>,
+    user_agent_data: MutNullableDom<NavigatorUAData>,
+    storage: MutNullableDom<StorageManager>,
+    credentials: MutNullableDom<CredentialsContainer>,
+    geolocation: MutNullableDom<Geolocation>,
+    /// The body size of the beacons that were sent and haven't completed yet.
+    inflight_beacon_bytes: Cell<u64>,
 }
 
 impl Navigator {
@@ -54,6 +92,12 @@ impl Navigator {
             permissions: Default::default(),
             mediasession: Default::default(),
             gpu: Default::default(),
+            connection: Default::default(),
+            user_agent_data: Default::default(),
+            storage: Default::default(),
+            credentials: Default::default(),
+            geolocation: Default::default(),
+            inflight_beacon_bytes: Cell::new(0),
         }
     }
 
@@ -64,6 +108,19 @@ impl Navigator {
             NavigatorBinding::Wrap,
         )
     }
+
+    /// Fires `change` at `navigator.connection`, if it was ever used.
+    pub fn update_connection(&self) {
+        if let Some(connection) = self.connection.get() {
+            connection.changed();
+        }
+    }
+
+    /// Records that a beacon with a body of `bytes` bytes completed.
+    fn finish_beacon(&self, bytes: u64) {
+        self.inflight_beacon_bytes
+            .set(self.inflight_beacon_bytes.get().saturating_sub(bytes));
+    }
 }
 
 impl NavigatorMethods for Navigator {
@@ -104,7 +161,9 @@ impl NavigatorMethods for Navigator {
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-platform
     fn Platform(&self) -> DOMString {
-        navigatorinfo::Platform()
+        self.global()
+            .get_platform()
+            .map_or_else(navigatorinfo::Platform, DOMString::from)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-useragent
@@ -117,11 +176,132 @@ impl NavigatorMethods for Navigator {
         navigatorinfo::AppVersion()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-navigator-online
+    fn OnLine(&self) -> bool {
+        ScriptThread::network_status().online
+    }
+
+    // https://wicg.github.io/netinfo/#navigatornetworkinformation-interface
+    fn Connection(&self) -> DomRoot<NetworkInformation> {
+        self.connection
+            .or_init(|| NetworkInformation::new(self.global().as_window()))
+    }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-navigator-maxtouchpoints
+    fn MaxTouchPoints(&self) -> i32 {
+        self.global().as_window().max_touch_points() as i32
+    }
+
+    // https://wicg.github.io/ua-client-hints/#dom-navigatorua-useragentdata
+    fn UserAgentData(&self) -> DomRoot<NavigatorUAData> {
+        self.user_agent_data
+            .or_init(|| NavigatorUAData::new(&self.global()))
+    }
+
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-navigator-bluetooth
     fn Bluetooth(&self) -> DomRoot<Bluetooth> {
         self.bluetooth.or_init(|| Bluetooth::new(&self.global()))
     }
 
+    // https://w3c.github.io/beacon/#sec-sendBeacon-method
+    fn SendBeacon(&self, url: USVString, data: Option<BodyInit>) -> Fallible<bool> {
+        let global = self.global();
+        let window = global.as_window();
+
+        // Step 1-3
+        let url = match global.api_base_url().join(&url) {
+            Ok(url) => url,
+            Err(_) => return Err(Error::Type("Invalid beacon URL".to_owned())),
+        };
+
+        // Step 4
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::Type("Beacon URLs must be http or https".to_owned()));
+        }
+
+        // Step 5
+        let mut headers = HeaderMap::new();
+        let mut cors_mode = RequestMode::NoCors;
+
+        // Step 6
+        let body = data.map(|data| data.extract());
+        let bytes = body
+            .as_ref()
+            .map_or(0, |&(ref bytes, _)| bytes.len() as u64);
+
+        // Step 6.1. Beacons share the quota of keepalive requests, and the
+        // ones that don't fit in what is left of it aren't queued.
+        if self.inflight_beacon_bytes.get() + bytes > MAX_INFLIGHT_BEACON_BYTES {
+            return Ok(false);
+        }
+
+        // Step 6.2-6.3
+        let body = body.map(|(bytes, content_type)| {
+            if let Some(value) = content_type.and_then(|ct| HeaderValue::from_str(&ct).ok()) {
+                if !is_cors_safelisted_request_header(&"content-type", &value) {
+                    cors_mode = RequestMode::CorsMode;
+                }
+                headers.insert(header::CONTENT_TYPE, value);
+            }
+            bytes
+        });
+
+        // Step 7.1
+        let document = window.Document();
+        let request = RequestBuilder::new(url.clone())
+            .method(Method::POST)
+            .headers(headers)
+            .body(body)
+            .keep_alive(true)
+            .destination(Destination::None)
+            .mode(cors_mode)
+            .credentials_mode(CredentialsMode::Include)
+            .origin(global.origin().immutable().clone())
+            .referrer(Some(Referrer::ReferrerUrl(document.url())))
+            .referrer_policy(document.get_referrer_policy())
+            .pipeline_id(Some(global.pipeline_id()));
+
+        // Step 7.2. The beacon goes straight to the resource thread rather
+        // than through the document loader, so it isn't cancelled when the
+        // document goes away, as happens when it is sent from a pagehide or
+        // visibilitychange handler.
+        self.inflight_beacon_bytes
+            .set(self.inflight_beacon_bytes.get() + bytes);
+        let context = BeaconContext {
+            navigator: Trusted::new(self),
+            url,
+            bytes,
+            resource_timing: ResourceFetchTiming::new(ResourceTimingType::Resource),
+        };
+        let listener = NetworkListener {
+            context: Arc::new(Mutex::new(context)),
+            task_source: global.networking_task_source(),
+            canceller: None,
+        };
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                listener.notify_fetch(message.to().unwrap());
+            }),
+        );
+        global
+            .core_resource_thread()
+            .send(CoreResourceMsg::Fetch(
+                request,
+                FetchChannels::ResponseMsg(action_sender, None),
+            ))
+            .unwrap();
+
+        // Step 8
+        Ok(true)
+    }
+
     // https://html.spec.whatwg.org/multipage/#navigatorlanguage
     fn Language(&self) -> DOMString {
         navigatorinfo::Language()
@@ -182,6 +362,13 @@ impl NavigatorMethods for Navigator {
         promise
     }
 
+    // https://webaudio.github.io/web-midi-api/#dom-navigator-requestmidiaccess
+    fn RequestMIDIAccess(&self, options: &MIDIOptions, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        MIDIAccess::request(&self.global(), options, &promise);
+        promise
+    }
+
     /// https://immersive-web.github.io/webxr/#dom-navigator-xr
     fn Xr(&self) -> DomRoot<XRSystem> {
         self.xr
@@ -214,4 +401,63 @@ impl NavigatorMethods for Navigator {
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
+
+    // https://w3c.github.io/webappsec-credential-management/#dom-navigator-credentials
+    fn Credentials(&self) -> DomRoot<CredentialsContainer> {
+        self.credentials
+            .or_init(|| CredentialsContainer::new(&self.global()))
+    }
+
+    // https://w3c.github.io/geolocation-api/#navigator_interface
+    fn Geolocation(&self) -> DomRoot<Geolocation> {
+        self.geolocation
+            .or_init(|| Geolocation::new(&self.global()))
+    }
+}
+
+/// Tracks a beacon until its fetch completes, to give the bytes it takes
+/// from the quota back. The response itself is ignored.
+struct BeaconContext {
+    navigator: Trusted<Navigator>,
+    url: ServoUrl,
+    bytes: u64,
+    resource_timing: ResourceFetchTiming,
+}
+
+impl PreInvoke for BeaconContext {}
+
+impl FetchResponseListener for BeaconContext {
+    fn process_request_body(&mut self) {}
+
+    fn process_request_eof(&mut self) {}
+
+    fn process_response(&mut self, _metadata: Result<FetchMetadata, NetworkError>) {}
+
+    fn process_response_chunk(&mut self, _chunk: Vec<u8>) {}
+
+    fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
+        self.navigator.root().finish_beacon(self.bytes);
+    }
+
+    fn resource_timing_mut(&mut self) -> &mut ResourceFetchTiming {
+        &mut self.resource_timing
+    }
+
+    fn resource_timing(&self) -> &ResourceFetchTiming {
+        &self.resource_timing
+    }
+
+    fn submit_resource_timing(&mut self) {
+        network_listener::submit_timing(self)
+    }
+}
+
+impl ResourceTimingListener for BeaconContext {
+    fn resource_timing_information(&self) -> (InitiatorType, ServoUrl) {
+        (InitiatorType::Beacon, self.url.clone())
+    }
+
+    fn resource_timing_global(&self) -> DomRoot<GlobalScope> {
+        self.navigator.root().global()
+    }
 }
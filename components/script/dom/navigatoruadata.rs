@@ -0,0 +1,117 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::NavigatorUADataBinding::{
+    self, NavigatorUABrandVersion, NavigatorUADataMethods, UADataValues, UALowEntropyJSON,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use net_traits::user_agent_data::{BrandVersion, UserAgentMetadata};
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct NavigatorUAData {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Defined in net_traits"]
+    metadata: UserAgentMetadata,
+    #[ignore_malloc_size_of = "mozjs"]
+    brands: Heap<JSVal>,
+}
+
+impl NavigatorUAData {
+    fn new_inherited(metadata: UserAgentMetadata) -> NavigatorUAData {
+        NavigatorUAData {
+            reflector_: Reflector::new(),
+            metadata,
+            brands: Heap::default(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<NavigatorUAData> {
+        let data = reflect_dom_object(
+            Box::new(NavigatorUAData::new_inherited(
+                UserAgentMetadata::overridden_by(global.user_agent_override().as_ref()),
+            )),
+            global,
+            NavigatorUADataBinding::Wrap,
+        );
+
+        let _ac = enter_realm(global);
+        let brands = brand_versions(&data.metadata.brands);
+        data.brands.set(to_frozen_array(&brands, global.get_cx()));
+        data
+    }
+}
+
+fn brand_versions(brands: &[BrandVersion]) -> Vec<NavigatorUABrandVersion> {
+    brands
+        .iter()
+        .map(|brand| NavigatorUABrandVersion {
+            brand: Some(DOMString::from(&*brand.brand)),
+            version: Some(DOMString::from(&*brand.version)),
+        })
+        .collect()
+}
+
+impl NavigatorUADataMethods for NavigatorUAData {
+    // https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-brands
+    fn Brands(&self, _cx: JSContext) -> JSVal {
+        self.brands.get()
+    }
+
+    // https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-mobile
+    fn Mobile(&self) -> bool {
+        self.metadata.mobile
+    }
+
+    // https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-platform
+    fn Platform(&self) -> DOMString {
+        DOMString::from(&*self.metadata.platform)
+    }
+
+    // https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-gethighentropyvalues
+    fn GetHighEntropyValues(&self, hints: Vec<DOMString>, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let metadata = &self.metadata;
+        let string = |value: &str| Some(DOMString::from(value));
+        let mut values = UADataValues::empty();
+        values.brands = Some(brand_versions(&metadata.brands));
+        values.mobile = Some(metadata.mobile);
+        values.platform = string(&metadata.platform);
+        for hint in hints {
+            match &*hint {
+                "architecture" => values.architecture = string(&metadata.architecture),
+                "bitness" => values.bitness = string(&metadata.bitness),
+                "fullVersionList" => {
+                    values.fullVersionList = Some(brand_versions(&metadata.full_version_list))
+                },
+                "model" => values.model = string(&metadata.model),
+                "platformVersion" => values.platformVersion = string(&metadata.platform_version),
+                "uaFullVersion" => values.uaFullVersion = string(&metadata.full_version),
+                "wow64" => values.wow64 = Some(metadata.wow64),
+                _ => {},
+            }
+        }
+        promise.resolve_native(&values);
+        promise
+    }
+
+    // https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-tojson
+    fn ToJSON(&self) -> UALowEntropyJSON {
+        UALowEntropyJSON {
+            brands: Some(brand_versions(&self.metadata.brands)),
+            mobile: Some(self.metadata.mobile),
+            platform: Some(DOMString::from(&*self.metadata.platform)),
+        }
+    }
+}
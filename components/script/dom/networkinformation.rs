@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::NetworkInformationBinding::{
+    self, EffectiveConnectionType, NetworkInformationMethods,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use crate::script_thread::ScriptThread;
+use dom_struct::dom_struct;
+use script_traits::EffectiveConnectionType as ConnectionType;
+
+/// The granularity of the bandwidth estimate, in megabits per second.
+/// <https://wicg.github.io/netinfo/#dfn-downlink>
+const DOWNLINK_GRANULARITY: f64 = 0.025;
+
+/// The granularity of the round-trip time estimate, in milliseconds.
+/// <https://wicg.github.io/netinfo/#dfn-rtt>
+const RTT_GRANULARITY: u64 = 25;
+
+#[dom_struct]
+pub struct NetworkInformation {
+    eventtarget: EventTarget,
+}
+
+impl NetworkInformation {
+    fn new_inherited() -> NetworkInformation {
+        NetworkInformation {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<NetworkInformation> {
+        reflect_dom_object(
+            Box::new(NetworkInformation::new_inherited()),
+            window,
+            NetworkInformationBinding::Wrap,
+        )
+    }
+
+    /// https://wicg.github.io/netinfo/#handling-changes-to-the-underlying-connection
+    pub fn changed(&self) {
+        self.upcast::<EventTarget>().fire_event(atom!("change"));
+    }
+}
+
+impl NetworkInformationMethods for NetworkInformation {
+    // https://wicg.github.io/netinfo/#dom-networkinformation-effectivetype
+    fn EffectiveType(&self) -> EffectiveConnectionType {
+        match ScriptThread::network_status().effective_type {
+            ConnectionType::Slow2G => EffectiveConnectionType::Slow_2g,
+            ConnectionType::TwoG => EffectiveConnectionType::_2g,
+            ConnectionType::ThreeG => EffectiveConnectionType::_3g,
+            ConnectionType::FourG => EffectiveConnectionType::_4g,
+        }
+    }
+
+    // https://wicg.github.io/netinfo/#dom-networkinformation-downlink
+    fn Downlink(&self) -> f64 {
+        let downlink = ScriptThread::network_status().downlink;
+        (downlink / DOWNLINK_GRANULARITY).round() * DOWNLINK_GRANULARITY
+    }
+
+    // https://wicg.github.io/netinfo/#dom-networkinformation-rtt
+    fn Rtt(&self) -> u64 {
+        let rtt = ScriptThread::network_status().rtt;
+        (rtt + RTT_GRANULARITY / 2) / RTT_GRANULARITY * RTT_GRANULARITY
+    }
+
+    // https://wicg.github.io/netinfo/#dom-networkinformation-savedata
+    fn SaveData(&self) -> bool {
+        ScriptThread::network_status().save_data
+    }
+
+    // https://wicg.github.io/netinfo/#dom-networkinformation-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+}
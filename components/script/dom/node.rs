@@ -61,7 +61,7 @@ use crate::dom::raredata::NodeRareData;
 use crate::dom::shadowroot::{LayoutShadowRootHelpers, ShadowRoot};
 use crate::dom::stylesheetlist::StyleSheetListOwner;
 use crate::dom::svgsvgelement::{LayoutSVGSVGElementHelpers, SVGSVGElement};
-use crate::dom::text::Text;
+use crate::dom::text::{LayoutTextHelpers, Text};
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::Window;
 use crate::script_runtime::JSContext;
@@ -782,6 +782,23 @@ impl Node {
         }
     }
 
+    /// The editing host of this node, if it is editable or an editing host itself.
+    /// <https://html.spec.whatwg.org/multipage/#editing-host>
+    pub fn editing_host(&self) -> Option<DomRoot<Element>> {
+        let mut host = None;
+        for element in self
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+        {
+            match element.content_editable_state() {
+                Some(true) => host = Some(element),
+                Some(false) => break,
+                None => {},
+            }
+        }
+        host
+    }
+
     pub fn is_parent_of(&self, child: &Node) -> bool {
         child
             .parent_node
@@ -1271,6 +1288,27 @@ impl Node {
             _ => false,
         }
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-window-nameditem-filter>
+    pub fn is_window_named_item(&self, name: &Atom) -> bool {
+        let html_elem_type = match self.type_id() {
+            NodeTypeId::Element(ElementTypeId::HTMLElement(type_)) => type_,
+            _ => return false,
+        };
+        let elem = self
+            .downcast::<Element>()
+            .expect("Node with an Element::HTMLElement NodeTypeID must be an Element");
+        if elem.get_id().map_or(false, |id| id == *name) {
+            return true;
+        }
+        match html_elem_type {
+            HTMLElementTypeId::HTMLEmbedElement |
+            HTMLElementTypeId::HTMLFormElement |
+            HTMLElementTypeId::HTMLImageElement |
+            HTMLElementTypeId::HTMLObjectElement => elem.get_name().map_or(false, |n| n == *name),
+            _ => false,
+        }
+    }
 }
 
 /// Iterate through `nodes` until we find a `Node` that is not in `not_in`
@@ -1485,6 +1523,10 @@ impl LayoutNodeHelpers for LayoutDom<Node> {
             return unsafe { input.selection_for_layout() };
         }
 
+        if let Some(text) = self.downcast::<Text>() {
+            return unsafe { text.selection_for_layout() };
+        }
+
         None
     }
 
@@ -2175,7 +2217,8 @@ impl Node {
         let old_next_sibling = node.GetNextSibling();
         // Steps 9-10 are handled in unbind_from_tree.
         parent.remove_child(node, cached_index);
-        // Step 11. transient registered observers
+        // Step 11.
+        MutationObserver::add_transient_registered_observers(node, parent);
         // Step 12.
         if let SuppressObserver::Unsuppressed = suppress_observers {
             vtable_for(&parent).children_changed(&ChildrenMutation::replace(
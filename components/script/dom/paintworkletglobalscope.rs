@@ -22,9 +22,10 @@ use crate::dom::stylepropertymapreadonly::StylePropertyMapReadOnly;
 use crate::dom::worklet::WorkletExecutor;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
+use crate::dom::workletglobalscope::WorkletRegistration;
 use crate::dom::workletglobalscope::WorkletTask;
 use crate::script_runtime::JSContext;
-use crossbeam_channel::{unbounded, Sender};
+use crossbeam_channel::Sender;
 use dom_struct::dom_struct;
 use euclid::Scale;
 use euclid::Size2D;
@@ -51,7 +52,6 @@ use profile_traits::ipc;
 use script_traits::Painter;
 use script_traits::{DrawAPaintImageResult, PaintWorkletError};
 use servo_atoms::Atom;
-use servo_config::pref;
 use servo_url::ServoUrl;
 use std::cell::Cell;
 use std::collections::hash_map::Entry;
@@ -427,24 +427,19 @@ impl PaintWorkletGlobalScope {
                 arguments: Vec<String>,
             ) -> Result<DrawAPaintImageResult, PaintWorkletError> {
                 let name = self.name.clone();
-                let (sender, receiver) = unbounded();
-                let task = PaintWorkletTask::DrawAPaintImage(
-                    name,
-                    size,
-                    device_pixel_ratio,
-                    properties,
-                    arguments,
-                    sender,
-                );
                 self.executor
                     .lock()
                     .expect("Locking a painter.")
-                    .schedule_a_worklet_task(WorkletTask::Paint(task));
-
-                let timeout = pref!(dom.worklet.timeout_ms) as u64;
-
-                receiver
-                    .recv_timeout(Duration::from_millis(timeout))
+                    .perform_a_worklet_task_with_timeout(|sender| {
+                        WorkletTask::Paint(PaintWorkletTask::DrawAPaintImage(
+                            name,
+                            size,
+                            device_pixel_ratio,
+                            properties,
+                            arguments,
+                            sender,
+                        ))
+                    })
                     .map_err(|e| PaintWorkletError::from(e))
             }
         }
@@ -600,8 +595,11 @@ impl PaintWorkletGlobalScopeMethods for PaintWorkletGlobalScope {
         // Inform layout that there is a registered paint worklet.
         // TODO: layout will end up getting this message multiple times.
         let painter = self.painter(name.clone());
-        self.worklet_global
-            .register_paint_worklet(name, properties, painter);
+        self.worklet_global.register(WorkletRegistration::Paint {
+            name,
+            properties,
+            painter,
+        });
 
         Ok(())
     }
@@ -6,14 +6,24 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceEntryList as DOMPerformanceEntryList;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
-    DOMHighResTimeStamp, PerformanceMethods,
+    DOMHighResTimeStamp, MemoryAttribution, MemoryBreakdownEntry, MemoryMeasurement,
+    PerformanceMarkOptions, PerformanceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::PerformanceNavigationTimingMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::PerformanceResourceTimingMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrDouble, StringOrPerformanceMeasureOptions,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::TrustedPromise;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performanceentry::PerformanceEntry;
@@ -22,14 +32,27 @@ use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigation::PerformanceNavigation;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
 use crate::dom::performanceobserver::PerformanceObserver as DOMPerformanceObserver;
+use crate::dom::performanceresourcetiming::PerformanceResourceTiming;
+use crate::dom::promise::Promise;
 use crate::dom::window::Window;
+use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
 use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, NullValue, UndefinedValue};
+use js::rust::{HandleObject, HandleValue};
 use metrics::ToMs;
+use profile_traits::mem::{ProfilerMsg, Report, ReportsChan};
+use servo_url::{ImmutableOrigin, ServoUrl};
 use std::cell::Cell;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
 
-const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
+pub const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "navigationStart",
     "unloadEventStart",
     "unloadEventEnd",
@@ -105,18 +128,14 @@ impl PerformanceEntryList {
 
     fn get_last_entry_start_time_with_name_and_type(
         &self,
-        name: DOMString,
-        entry_type: DOMString,
-    ) -> f64 {
-        match self
-            .entries
+        name: &str,
+        entry_type: &str,
+    ) -> Option<f64> {
+        self.entries
             .iter()
             .rev()
             .find(|e| *e.entry_type() == *entry_type && *e.name() == *name)
-        {
-            Some(entry) => entry.start_time(),
-            None => 0.,
-        }
+            .map(|entry| entry.start_time())
     }
 }
 
@@ -255,6 +274,14 @@ impl Performance {
         observers.remove(index);
     }
 
+    /// Whether any performance observer is interested in entries of `entry_type`.
+    pub fn has_observers_for(&self, entry_type: &str) -> bool {
+        self.observers
+            .borrow()
+            .iter()
+            .any(|o| o.entry_types.iter().any(|t| &**t == entry_type))
+    }
+
     /// Queue a notification for each performance observer interested in
     /// this type of performance entry and queue a low priority task to
     /// notify the observers if no other notification task is already queued.
@@ -264,11 +291,6 @@ impl Performance {
     /// Also this algorithm has been extented according to :
     /// <https://w3c.github.io/resource-timing/#sec-extensions-performance-interface>
     pub fn queue_entry(&self, entry: &PerformanceEntry) -> Option<usize> {
-        // https://w3c.github.io/performance-timeline/#dfn-determine-eligibility-for-adding-a-performance-entry
-        if entry.entry_type() == "resource" && !self.should_queue_resource_entry(entry) {
-            return None;
-        }
-
         // Steps 1-3.
         // Add the performance entry to the list of performance entries that have not
         // been notified to each performance observer owner, filtering the ones it's
@@ -283,18 +305,24 @@ impl Performance {
         }
 
         // Step 4.
-        //add the new entry to the buffer.
-        self.buffer
-            .borrow_mut()
-            .entries
-            .push(DomRoot::from_ref(entry));
-
-        let entry_last_index = self.buffer.borrow_mut().entries.len() - 1;
+        // Add the new entry to the buffer, if it is eligible for it.
+        // https://w3c.github.io/performance-timeline/#dfn-determine-eligibility-for-adding-a-performance-entry
+        // Long task and layout shift entries are only ever delivered to observers.
+        let entry_index = if entry.entry_type() == "longtask" ||
+            entry.entry_type() == "layout-shift" ||
+            entry.entry_type() == "resource" && !self.should_queue_resource_entry(entry)
+        {
+            None
+        } else {
+            let mut buffer = self.buffer.borrow_mut();
+            buffer.entries.push(DomRoot::from_ref(entry));
+            Some(buffer.entries.len() - 1)
+        };
 
         // Step 5.
         // If there is already a queued notification task, we just bail out.
         if self.pending_notification_observers_task.get() {
-            return None;
+            return entry_index;
         }
 
         // Step 6.
@@ -303,7 +331,7 @@ impl Performance {
         let task_source = self.global().performance_timeline_task_source();
         task_source.queue_notification(&self.global());
 
-        Some(entry_last_index)
+        entry_index
     }
 
     /// Observers notifications task.
@@ -332,10 +360,92 @@ impl Performance {
         }
     }
 
-    fn now(&self) -> f64 {
+    pub fn now(&self) -> f64 {
         (time::precise_time_ns() - self.navigation_start_precise).to_ms()
     }
 
+    /// Converts a timestamp taken with `precise_time_ns`, such as the ones
+    /// recorded while fetching a resource, to one relative to the time origin.
+    /// Zero stands for something that didn't happen, and is kept as is.
+    pub fn to_dom_high_res_time_stamp(&self, time_ns: u64) -> f64 {
+        if time_ns == 0 {
+            return 0.;
+        }
+        time_ns
+            .saturating_sub(self.navigation_start_precise)
+            .to_ms()
+    }
+
+    /// <https://w3c.github.io/user-timing/#convert-a-mark-to-a-timestamp>
+    fn convert_mark_to_timestamp(&self, mark: &StringOrDouble) -> Fallible<f64> {
+        match *mark {
+            StringOrDouble::String(ref name) => self.convert_name_to_timestamp(name),
+            StringOrDouble::Double(time) if *time < 0. => {
+                Err(Error::Type("Timestamps must not be negative".to_owned()))
+            },
+            StringOrDouble::Double(time) => Ok(*time),
+        }
+    }
+
+    /// <https://w3c.github.io/user-timing/#convert-a-name-to-a-timestamp>
+    fn convert_name_to_timestamp(&self, name: &str) -> Fallible<f64> {
+        let global = self.global();
+        let window = match global.downcast::<Window>() {
+            Some(window) if INVALID_ENTRY_NAMES.contains(&name) => window,
+            _ => {
+                return self
+                    .buffer
+                    .borrow()
+                    .get_last_entry_start_time_with_name_and_type(name, "mark")
+                    .ok_or(Error::Syntax);
+            },
+        };
+        if name == "navigationStart" {
+            return Ok(0.);
+        }
+        let document = window.Document();
+        let timing = self.Timing();
+        let resource_timing = timing.upcast::<PerformanceResourceTiming>();
+        let time = match name {
+            "unloadEventStart" => timing.UnloadEventStart(),
+            "unloadEventEnd" => timing.UnloadEventEnd(),
+            "redirectStart" => resource_timing.RedirectStart(),
+            "redirectEnd" => resource_timing.RedirectEnd(),
+            "fetchStart" => resource_timing.FetchStart(),
+            "domainLookupStart" => resource_timing.DomainLookupStart(),
+            "domainLookupEnd" => resource_timing.DomainLookupEnd(),
+            "connectStart" => resource_timing.ConnectStart(),
+            "connectEnd" => resource_timing.ConnectEnd(),
+            "secureConnectionStart" => resource_timing.SecureConnectionStart(),
+            "requestStart" => resource_timing.RequestStart(),
+            "responseStart" => resource_timing.ResponseStart(),
+            "responseEnd" => resource_timing.ResponseEnd(),
+            "domLoading" => {
+                Finite::wrap(self.to_dom_high_res_time_stamp(document.get_dom_loading()))
+            },
+            "domInteractive" => timing.DomInteractive(),
+            "domContentLoadedEventStart" => timing.DomContentLoadedEventStart(),
+            "domContentLoadedEventEnd" => timing.DomContentLoadedEventEnd(),
+            "domComplete" => timing.DomComplete(),
+            "loadEventStart" => timing.LoadEventStart(),
+            "loadEventEnd" => timing.LoadEventEnd(),
+            _ => unreachable!(),
+        };
+        // Timings of what didn't happen yet can't be used.
+        if *time == 0. {
+            return Err(Error::InvalidAccess);
+        }
+        Ok(*time)
+    }
+
+    /// Whether `measureUserAgentSpecificMemory` is exposed to `obj`, which is only
+    /// the case for cross-origin isolated windows.
+    #[allow(unsafe_code)]
+    pub fn is_cross_origin_isolated(_: JSContext, obj: HandleObject) -> bool {
+        let global = unsafe { GlobalScope::from_object(obj.get()) };
+        global.is::<Window>() && global.is_cross_origin_isolated()
+    }
+
     fn can_add_resource_timing_entry(&self) -> bool {
         self.resource_timing_buffer_current_size.get() <=
             self.resource_timing_buffer_size_limit.get()
@@ -346,8 +456,12 @@ impl Performance {
                 .resource_timing_secondary_entries
                 .borrow_mut()
                 .pop_front();
-            if let Some(ref entry) = entry {
-                self.queue_entry(entry);
+            // The entries were delivered to the observers when they were
+            // queued, so they only need adding to the buffer.
+            if let Some(entry) = entry {
+                self.buffer.borrow_mut().entries.push(entry);
+                self.resource_timing_buffer_current_size
+                    .set(self.resource_timing_buffer_current_size.get() + 1);
             } else {
                 break;
             }
@@ -460,20 +574,17 @@ impl PerformanceMethods for Performance {
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-mark
-    fn Mark(&self, mark_name: DOMString) -> Fallible<()> {
-        let global = self.global();
+    fn Mark(
+        &self,
+        mark_name: DOMString,
+        mark_options: RootedTraceableBox<PerformanceMarkOptions>,
+    ) -> Fallible<DomRoot<PerformanceMark>> {
         // Step 1.
-        if global.is::<Window>() && INVALID_ENTRY_NAMES.contains(&mark_name.as_ref()) {
-            return Err(Error::Syntax);
-        }
-
-        // Steps 2 to 6.
-        let entry = PerformanceMark::new(&global, mark_name, self.now(), 0.);
-        // Steps 7 and 8.
+        let entry = PerformanceMark::Constructor(&self.global(), mark_name, mark_options)?;
+        // Step 2.
         self.queue_entry(&entry.upcast::<PerformanceEntry>());
-
-        // Step 9.
-        Ok(())
+        // Step 3.
+        Ok(entry)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmarks
@@ -487,40 +598,85 @@ impl PerformanceMethods for Performance {
     fn Measure(
         &self,
         measure_name: DOMString,
-        start_mark: Option<DOMString>,
+        start_or_measure_options: StringOrPerformanceMeasureOptions,
         end_mark: Option<DOMString>,
-    ) -> Fallible<()> {
-        // Steps 1 and 2.
-        let end_time = match end_mark {
-            Some(name) => self
-                .buffer
-                .borrow()
-                .get_last_entry_start_time_with_name_and_type(DOMString::from("mark"), name),
-            None => self.now(),
+    ) -> Fallible<DomRoot<PerformanceMeasure>> {
+        let options = match start_or_measure_options {
+            StringOrPerformanceMeasureOptions::PerformanceMeasureOptions(ref options)
+                if !options.detail.get().is_undefined() ||
+                    options.start.is_some() ||
+                    options.duration.is_some() ||
+                    options.end.is_some() =>
+            {
+                Some(options)
+            },
+            _ => None,
+        };
+
+        // Step 1.
+        if let Some(options) = options {
+            if end_mark.is_some() {
+                return Err(Error::Type(
+                    "endMark can't be given with measure options".to_owned(),
+                ));
+            }
+            if options.start.is_none() && options.end.is_none() {
+                return Err(Error::Type(
+                    "Measure options need a start or an end".to_owned(),
+                ));
+            }
+            if options.start.is_some() && options.duration.is_some() && options.end.is_some() {
+                return Err(Error::Type(
+                    "Measure options can't have a start, a duration and an end".to_owned(),
+                ));
+            }
+        }
+
+        // Step 2.
+        let end_time = match (&end_mark, options) {
+            (Some(end_mark), _) => self.convert_name_to_timestamp(end_mark)?,
+            (None, Some(options)) => match (&options.end, &options.start, options.duration) {
+                (Some(end), _, _) => self.convert_mark_to_timestamp(end)?,
+                (None, Some(start), Some(duration)) => {
+                    self.convert_mark_to_timestamp(start)? + *duration
+                },
+                _ => self.now(),
+            },
+            (None, None) => self.now(),
         };
 
         // Step 3.
-        let start_time = match start_mark {
-            Some(name) => self
-                .buffer
-                .borrow()
-                .get_last_entry_start_time_with_name_and_type(DOMString::from("mark"), name),
-            None => 0.,
+        let start_time = match (&start_or_measure_options, options) {
+            (_, Some(options)) => match (&options.start, options.duration, &options.end) {
+                (Some(start), _, _) => self.convert_mark_to_timestamp(start)?,
+                (None, Some(duration), Some(_)) => end_time - *duration,
+                _ => 0.,
+            },
+            (StringOrPerformanceMeasureOptions::String(start_mark), None) => {
+                self.convert_name_to_timestamp(start_mark)?
+            },
+            _ => 0.,
         };
 
-        // Steps 4 to 8.
+        // Steps 4-8.
+        let global = self.global();
+        rooted!(in(*global.get_cx()) let mut detail = NullValue());
+        if let Some(options) = options {
+            detail.set(options.detail.get());
+        }
         let entry = PerformanceMeasure::new(
-            &self.global(),
+            &global,
             measure_name,
             start_time,
             end_time - start_time,
-        );
+            detail.handle(),
+        )?;
 
-        // Step 9 and 10.
+        // Step 9.
         self.queue_entry(&entry.upcast::<PerformanceEntry>());
 
-        // Step 11.
-        Ok(())
+        // Step 10.
+        Ok(entry)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmeasures
@@ -549,6 +705,120 @@ impl PerformanceMethods for Performance {
         GetOnresourcetimingbufferfull,
         SetOnresourcetimingbufferfull
     );
+
+    // https://wicg.github.io/performance-measure-memory/#dom-performance-measureuseragentspecificmemory
+    fn MeasureUserAgentSpecificMemory(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let origin = global.origin().immutable().clone();
+        let task_source = global.performance_timeline_task_source();
+        let canceller = global.task_canceller(TaskSourceName::PerformanceTimeline);
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let reports: Vec<Report> = message.to().unwrap();
+                let promise = match trusted_promise.take() {
+                    Some(promise) => promise,
+                    None => return,
+                };
+                let result = task_source.queue_with_canceller(
+                    task!(memory_measured: move || {
+                        let measurement = memory_measurement(reports, &origin);
+                        promise.root().resolve_native(&measurement);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue memory measurement task {:?}", err);
+                }
+            }),
+        );
+        global
+            .mem_profiler_chan()
+            .send(ProfilerMsg::CollectReports(ReportsChan(sender)));
+        promise
+    }
+}
+
+/// Sets `detail` to a structured clone of `value`, or to null if there is no value.
+/// <https://w3c.github.io/user-timing/#the-performancemark-constructor>
+pub fn clone_detail(
+    global: &GlobalScope,
+    value: HandleValue,
+    detail: &Heap<JSVal>,
+) -> Fallible<()> {
+    if value.is_null_or_undefined() {
+        detail.set(NullValue());
+        return Ok(());
+    }
+    let cx = global.get_cx();
+    let data = structuredclone::write(cx, value, None)?;
+    rooted!(in(*cx) let mut clone = UndefinedValue());
+    if structuredclone::read(global, data, clone.handle_mut()).is_err() {
+        return Err(Error::DataClone);
+    }
+    detail.set(clone.get());
+    Ok(())
+}
+
+/// Turns the reports of the memory profiler into a measurement of the memory used by the
+/// agents of `origin`. The reports of a script thread or of a layout thread have paths starting
+/// with `explicit` and `url(...)`, which lists the URLs of the documents they are for, followed
+/// by what was measured.
+/// <https://wicg.github.io/performance-measure-memory/#create-memory-measurement-result>
+fn memory_measurement(reports: Vec<Report>, origin: &ImmutableOrigin) -> MemoryMeasurement {
+    let mut entries: BTreeMap<(String, &'static str), u64> = BTreeMap::new();
+    for report in reports {
+        let (urls, type_) = match &*report.path {
+            [explicit, urls, type_, ..] if explicit == "explicit" && urls.starts_with("url(") => {
+                (urls, type_)
+            },
+            _ => continue,
+        };
+        let type_ = match &**type_ {
+            "js" => "JavaScript",
+            "dom-tree" => "DOM",
+            "layout-thread" => "Layout",
+            _ => continue,
+        };
+        *entries.entry((urls.clone(), type_)).or_insert(0) += report.size as u64;
+    }
+
+    let mut bytes = 0;
+    let mut breakdown = vec![];
+    for ((urls, type_), size) in entries {
+        let urls: Vec<ServoUrl> = urls
+            .trim_start_matches("url(")
+            .trim_end_matches(')')
+            .split(", ")
+            .filter_map(|url| ServoUrl::parse(url).ok())
+            .collect();
+        // Memory of other origins isn't attributed to anything the caller could see.
+        if urls.is_empty() || urls.iter().any(|url| url.origin() != *origin) {
+            continue;
+        }
+        bytes += size;
+        breakdown.push(MemoryBreakdownEntry {
+            bytes: Some(size),
+            attribution: Some(
+                urls.into_iter()
+                    .map(|url| MemoryAttribution {
+                        url: Some(USVString(url.into_string())),
+                        container: None,
+                        scope: Some(DOMString::from("Window")),
+                    })
+                    .collect(),
+            ),
+            types: Some(vec![DOMString::from(type_)]),
+        });
+    }
+    MemoryMeasurement {
+        bytes: Some(bytes),
+        breakdown: Some(breakdown),
+    }
 }
 
 // https://www.w3.org/TR/hr-time-2/#clock-resolution
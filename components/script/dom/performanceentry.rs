@@ -11,6 +11,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performance::reduce_timing_resolution;
 use dom_struct::dom_struct;
+use std::cell::Cell;
 
 #[dom_struct]
 pub struct PerformanceEntry {
@@ -18,7 +19,7 @@ pub struct PerformanceEntry {
     name: DOMString,
     entry_type: DOMString,
     start_time: f64,
-    duration: f64,
+    duration: Cell<f64>,
 }
 
 impl PerformanceEntry {
@@ -33,7 +34,7 @@ impl PerformanceEntry {
             name,
             entry_type,
             start_time,
-            duration,
+            duration: Cell::new(duration),
         }
     }
 
@@ -62,7 +63,12 @@ impl PerformanceEntry {
     }
 
     pub fn duration(&self) -> f64 {
-        self.duration
+        self.duration.get()
+    }
+
+    /// Sets the duration of an entry that lasts until after it is queued.
+    pub fn set_duration(&self, duration: f64) {
+        self.duration.set(duration);
     }
 }
 
@@ -84,6 +90,6 @@ impl PerformanceEntryMethods for PerformanceEntry {
 
     // https://w3c.github.io/performance-timeline/#dom-performanceentry-duration
     fn Duration(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.duration)
+        reduce_timing_resolution(self.duration.get())
     }
 }
@@ -0,0 +1,154 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding;
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding::PerformanceLongTaskTimingMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
+use crate::dom::window::Window;
+use crate::dom::windowproxy::WindowProxy;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use metrics::ToMs;
+
+/// Tasks that run for longer than this are reported as long tasks.
+/// <https://w3c.github.io/longtasks/#long-task>
+pub const LONG_TASK_THRESHOLD_NS: u64 = 50_000_000;
+
+/// <https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming>
+#[dom_struct]
+pub struct PerformanceLongTaskTiming {
+    entry: PerformanceEntry,
+    attribution: Dom<TaskAttributionTiming>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_attribution: DomRefCell<Option<Heap<JSVal>>>,
+}
+
+impl PerformanceLongTaskTiming {
+    fn new_inherited(
+        name: &str,
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> PerformanceLongTaskTiming {
+        PerformanceLongTaskTiming {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from(name),
+                DOMString::from("longtask"),
+                start_time,
+                duration,
+            ),
+            attribution: Dom::from_ref(attribution),
+            frozen_attribution: DomRefCell::new(None),
+        }
+    }
+
+    /// Creates the entry that `window` observes for a task of `culprit` that
+    /// ran from `start` to `end`, in nanoseconds.
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        window: &Window,
+        culprit: &Window,
+        start: u64,
+        end: u64,
+    ) -> DomRoot<PerformanceLongTaskTiming> {
+        let (name, container) = attribute(window, culprit);
+        let attribution = TaskAttributionTiming::new(window.upcast(), container.as_deref());
+        let start_time = window.Performance().to_dom_high_res_time_stamp(start);
+        let entry = PerformanceLongTaskTiming::new_inherited(
+            name,
+            start_time,
+            (end - start).to_ms(),
+            &attribution,
+        );
+        reflect_dom_object(
+            Box::new(entry),
+            window,
+            PerformanceLongTaskTimingBinding::Wrap,
+        )
+    }
+}
+
+/// Names the relationship between the browsing context of `observer` and the
+/// one of `culprit`, and finds the container element in the former that the
+/// task can be attributed to.
+/// <https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming>
+fn attribute(observer: &Window, culprit: &Window) -> (&'static str, Option<DomRoot<Element>>) {
+    let observer_proxy = observer.window_proxy();
+    let culprit_proxy = culprit.window_proxy();
+    if observer_proxy.browsing_context_id() == culprit_proxy.browsing_context_id() {
+        return ("self", None);
+    }
+    let same_origin = observer
+        .upcast::<GlobalScope>()
+        .origin()
+        .same_origin(culprit.upcast::<GlobalScope>().origin());
+
+    // The culprit is in a frame nested in the observer.
+    let mut child: &WindowProxy = &culprit_proxy;
+    while let Some(parent) = child.parent() {
+        if parent.browsing_context_id() == observer_proxy.browsing_context_id() {
+            let name = if same_origin {
+                "same-origin-descendant"
+            } else {
+                "cross-origin-descendant"
+            };
+            return (name, child.frame_element().map(DomRoot::from_ref));
+        }
+        child = parent;
+    }
+
+    // The observer is in a frame nested in the culprit.
+    let mut ancestor = observer_proxy.parent();
+    while let Some(proxy) = ancestor {
+        if proxy.browsing_context_id() == culprit_proxy.browsing_context_id() {
+            let name = if same_origin {
+                "same-origin-ancestor"
+            } else {
+                "cross-origin-ancestor"
+            };
+            return (name, None);
+        }
+        ancestor = proxy.parent();
+    }
+
+    if same_origin {
+        ("same-origin", None)
+    } else {
+        ("cross-origin-unreachable", None)
+    }
+}
+
+impl PerformanceLongTaskTimingMethods for PerformanceLongTaskTiming {
+    // https://w3c.github.io/longtasks/#dom-performancelongtasktiming-attribution
+    fn Attribution(&self, cx: JSContext) -> JSVal {
+        if let Some(attribution) = &*self.frozen_attribution.borrow() {
+            return attribution.get();
+        }
+
+        let attribution = [DomRoot::from_ref(&*self.attribution)];
+        let frozen_attribution = to_frozen_array(&attribution, cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_attribution.borrow_mut() = Some(Heap::default());
+        self.frozen_attribution
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_attribution);
+
+        frozen_attribution
+    }
+}
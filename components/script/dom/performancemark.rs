@@ -2,4 +2,88 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-impl_performance_entry_struct!(PerformanceMarkBinding, PerformanceMark, "mark");
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMarkOptions;
+use crate::dom::bindings::codegen::Bindings::PerformanceMarkBinding::{
+    self, PerformanceMarkMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performance::{clone_detail, INVALID_ENTRY_NAMES};
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
+
+/// <https://w3c.github.io/user-timing/#performancemark>
+#[dom_struct]
+pub struct PerformanceMark {
+    entry: PerformanceEntry,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    detail: Heap<JSVal>,
+}
+
+impl PerformanceMark {
+    fn new_inherited(name: DOMString, start_time: f64) -> PerformanceMark {
+        PerformanceMark {
+            entry: PerformanceEntry::new_inherited(name, DOMString::from("mark"), start_time, 0.),
+            detail: Heap::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        start_time: f64,
+        detail: HandleValue,
+    ) -> Fallible<DomRoot<PerformanceMark>> {
+        let mark = reflect_dom_object(
+            Box::new(PerformanceMark::new_inherited(name, start_time)),
+            global,
+            PerformanceMarkBinding::Wrap,
+        );
+        // The detail is cloned once the mark is in its final memory location.
+        clone_detail(global, detail, &mark.detail)?;
+        Ok(mark)
+    }
+
+    // https://w3c.github.io/user-timing/#dom-performancemark-constructor
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        mark_name: DOMString,
+        mark_options: RootedTraceableBox<PerformanceMarkOptions>,
+    ) -> Fallible<DomRoot<PerformanceMark>> {
+        // Step 1.
+        if global.is::<Window>() && INVALID_ENTRY_NAMES.contains(&&*mark_name) {
+            return Err(Error::Syntax);
+        }
+
+        // Steps 2-4.
+        let start_time = match mark_options.startTime {
+            Some(start_time) if *start_time < 0. => {
+                return Err(Error::Type("startTime must not be negative".to_owned()));
+            },
+            Some(start_time) => *start_time,
+            None => global.performance().now(),
+        };
+
+        // Steps 5-6.
+        PerformanceMark::new(global, mark_name, start_time, mark_options.detail.handle())
+    }
+}
+
+impl PerformanceMarkMethods for PerformanceMark {
+    // https://w3c.github.io/user-timing/#dom-performancemark-detail
+    fn Detail(&self, _cx: JSContext) -> JSVal {
+        self.detail.get()
+    }
+}
@@ -2,4 +2,67 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-impl_performance_entry_struct!(PerformanceMeasureBinding, PerformanceMeasure, "measure");
+use crate::dom::bindings::codegen::Bindings::PerformanceMeasureBinding::{
+    self, PerformanceMeasureMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performance::clone_detail;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::script_runtime::JSContext;
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
+
+/// <https://w3c.github.io/user-timing/#performancemeasure>
+#[dom_struct]
+pub struct PerformanceMeasure {
+    entry: PerformanceEntry,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    detail: Heap<JSVal>,
+}
+
+impl PerformanceMeasure {
+    fn new_inherited(name: DOMString, start_time: f64, duration: f64) -> PerformanceMeasure {
+        PerformanceMeasure {
+            entry: PerformanceEntry::new_inherited(
+                name,
+                DOMString::from("measure"),
+                start_time,
+                duration,
+            ),
+            detail: Heap::default(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        start_time: f64,
+        duration: f64,
+        detail: HandleValue,
+    ) -> Fallible<DomRoot<PerformanceMeasure>> {
+        let measure = reflect_dom_object(
+            Box::new(PerformanceMeasure::new_inherited(
+                name, start_time, duration,
+            )),
+            global,
+            PerformanceMeasureBinding::Wrap,
+        );
+        // The detail is cloned once the measure is in its final memory location.
+        clone_detail(global, detail, &measure.detail)?;
+        Ok(measure)
+    }
+}
+
+impl PerformanceMeasureMethods for PerformanceMeasure {
+    // https://w3c.github.io/user-timing/#dom-performancemeasure-detail
+    fn Detail(&self, _cx: JSContext) -> JSVal {
+        self.detail.get()
+    }
+}
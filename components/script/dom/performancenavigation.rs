@@ -10,6 +10,7 @@ use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
 use dom_struct::dom_struct;
+use script_traits::NavigationType;
 
 #[dom_struct]
 pub struct PerformanceNavigation {
@@ -35,7 +36,11 @@ impl PerformanceNavigation {
 impl PerformanceNavigationMethods for PerformanceNavigation {
     // https://w3c.github.io/navigation-timing/#dom-performancenavigation-type
     fn Type(&self) -> u16 {
-        PerformanceNavigationConstants::TYPE_NAVIGATE
+        match self.global().as_window().Document().navigation_type() {
+            NavigationType::Navigate => PerformanceNavigationConstants::TYPE_NAVIGATE,
+            NavigationType::Reload => PerformanceNavigationConstants::TYPE_RELOAD,
+            NavigationType::BackForward => PerformanceNavigationConstants::TYPE_BACK_FORWARD,
+        }
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigation-redirectcount
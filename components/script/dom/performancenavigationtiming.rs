@@ -7,13 +7,15 @@ use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding:
 use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::{
     self, NavigationType,
 };
-use crate::dom::bindings::num::Finite;
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::document::Document;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::performanceresourcetiming::{InitiatorType, PerformanceResourceTiming};
 use dom_struct::dom_struct;
+use net_traits::ResourceFetchTiming;
+use script_traits::NavigationType as DocumentNavigationType;
 
 #[dom_struct]
 // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming
@@ -22,95 +24,100 @@ use dom_struct::dom_struct;
 pub struct PerformanceNavigationTiming {
     // https://w3c.github.io/navigation-timing/#PerformanceResourceTiming
     performanceresourcetiming: PerformanceResourceTiming,
-    navigation_start: u64,
-    navigation_start_precise: u64,
     document: Dom<Document>,
-    nav_type: NavigationType,
 }
 
 impl PerformanceNavigationTiming {
+    #[allow(unrooted_must_root)]
     fn new_inherited(
-        nav_start: u64,
-        nav_start_precise: u64,
+        global: &GlobalScope,
         document: &Document,
+        resource_timing: &ResourceFetchTiming,
     ) -> PerformanceNavigationTiming {
         PerformanceNavigationTiming {
-            performanceresourcetiming: PerformanceResourceTiming::new_inherited(
+            performanceresourcetiming: PerformanceResourceTiming::from_resource_timing(
+                global,
                 document.url(),
                 InitiatorType::Navigation,
                 None,
-                nav_start_precise as f64,
+                resource_timing,
             ),
-            navigation_start: nav_start,
-            navigation_start_precise: nav_start_precise,
             document: Dom::from_ref(document),
-            nav_type: NavigationType::Navigate,
         }
     }
 
     pub fn new(
         global: &GlobalScope,
-        nav_start: u64,
-        nav_start_precise: u64,
         document: &Document,
+        resource_timing: &ResourceFetchTiming,
     ) -> DomRoot<PerformanceNavigationTiming> {
         reflect_dom_object(
             Box::new(PerformanceNavigationTiming::new_inherited(
-                nav_start,
-                nav_start_precise,
+                global,
                 document,
+                resource_timing,
             )),
             global,
             PerformanceNavigationTimingBinding::Wrap,
         )
     }
+
+    /// Converts one of the timings that the document records with `precise_time_ns`.
+    fn document_time(&self, time_ns: u64) -> DOMHighResTimeStamp {
+        let performance = self.global().performance();
+        reduce_timing_resolution(performance.to_dom_high_res_time_stamp(time_ns))
+    }
 }
 
 // https://w3c.github.io/navigation-timing/
 impl PerformanceNavigationTimingMethods for PerformanceNavigationTiming {
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventstart
     fn UnloadEventStart(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_unload_event_start() as f64)
+        self.document_time(self.document.get_unload_event_start())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventend
     fn UnloadEventEnd(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_unload_event_end() as f64)
+        self.document_time(self.document.get_unload_event_end())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-dominteractive
     fn DomInteractive(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_interactive() as f64)
+        self.document_time(self.document.get_dom_interactive())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcontentloadedeventstart
     fn DomContentLoadedEventStart(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_content_loaded_event_start() as f64)
+        self.document_time(self.document.get_dom_content_loaded_event_start())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcontentloadedeventstart
     fn DomContentLoadedEventEnd(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_content_loaded_event_end() as f64)
+        self.document_time(self.document.get_dom_content_loaded_event_end())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcomplete
     fn DomComplete(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_dom_complete() as f64)
+        self.document_time(self.document.get_dom_complete())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-loadeventstart
     fn LoadEventStart(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_load_event_start() as f64)
+        self.document_time(self.document.get_load_event_start())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-loadeventend
     fn LoadEventEnd(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_load_event_end() as f64)
+        self.document_time(self.document.get_load_event_end())
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-type
     fn Type(&self) -> NavigationType {
-        self.nav_type.clone()
+        match self.document.navigation_type() {
+            DocumentNavigationType::Navigate => NavigationType::Navigate,
+            DocumentNavigationType::Reload => NavigationType::Reload,
+            DocumentNavigationType::BackForward => NavigationType::Back_forward,
+        }
     }
 
     // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-redirectcount
@@ -121,6 +128,6 @@ impl PerformanceNavigationTimingMethods for PerformanceNavigationTiming {
     // check-tidy: no specs after this line
     // Servo-only timing for when top-level content (not iframes) is complete
     fn TopLevelDomComplete(&self) -> DOMHighResTimeStamp {
-        Finite::wrap(self.document.get_top_level_dom_complete() as f64)
+        self.document_time(self.document.get_top_level_dom_complete())
     }
 }
@@ -27,12 +27,14 @@ use std::rc::Rc;
 /// List of allowed performance entry types, in alphabetical order.
 pub const VALID_ENTRY_TYPES: &'static [&'static str] = &[
     // "frame", //TODO Frame Timing API
-    "mark",       // User Timing API
-    "measure",    // User Timing API
-    "navigation", // Navigation Timing API
-    "paint",      // Paint Timing API
-    "resource",   // Resource Timing API
-                  // "server", XXX Server Timing API
+    "layout-shift", // Layout Instability API
+    "longtask",     // Long Tasks API
+    "mark",         // User Timing API
+    "measure",      // User Timing API
+    "navigation",   // Navigation Timing API
+    "paint",        // Paint Timing API
+    "resource",     // Resource Timing API
+                    // "server", XXX Server Timing API
 ];
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
@@ -174,11 +176,9 @@ impl PerformanceObserverMethods for PerformanceObserver {
 
             // Step 6.3
             if entry_types.is_empty() {
-                Console::Warn(
+                Console::internal_warn(
                     &*self.global(),
-                    vec![DOMString::from(
-                        "No valid entry type provided to observe().",
-                    )],
+                    DOMString::from("No valid entry type provided to observe()."),
                 );
                 return Ok(());
             }
@@ -193,11 +193,9 @@ impl PerformanceObserverMethods for PerformanceObserver {
         } else if let Some(entry_type) = &options.type_ {
             // Step 7.2
             if !VALID_ENTRY_TYPES.contains(&entry_type.as_ref()) {
-                Console::Warn(
+                Console::internal_warn(
                     &*self.global(),
-                    vec![DOMString::from(
-                        "No valid entry type provided to observe().",
-                    )],
+                    DOMString::from("No valid entry type provided to observe()."),
                 );
                 return Ok(());
             }
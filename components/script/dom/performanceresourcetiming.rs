@@ -2,18 +2,24 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
 use crate::dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::{
     self, PerformanceResourceTimingMethods,
 };
-use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceservertiming::PerformanceServerTiming;
+use crate::script_runtime::JSContext;
 use dom_struct::dom_struct;
-use net_traits::ResourceFetchTiming;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use net_traits::{ResourceFetchTiming, ServerTimingMetric};
 use servo_url::ServoUrl;
 
 // TODO UA may choose to limit how many resources are included as PerformanceResourceTiming objects
@@ -23,13 +29,15 @@ use servo_url::ServoUrl;
 // TODO Cross origin resources MUST BE INCLUDED as PerformanceResourceTiming objects
 // https://w3c.github.io/resource-timing/#sec-cross-origin-resources
 
-// TODO CSS, Beacon
 #[derive(Debug, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum InitiatorType {
     LocalName(String),
+    /// Resources that stylesheets refer to, such as background images.
+    Css,
     Navigation,
     XMLHttpRequest,
     Fetch,
+    Beacon,
     Other,
 }
 
@@ -53,87 +61,64 @@ pub struct PerformanceResourceTiming {
     transfer_size: u64,     //size in octets
     encoded_body_size: u64, //size in octets
     decoded_body_size: u64, //size in octets
+    server_timing: Vec<ServerTimingMetric>,
+    #[ignore_malloc_size_of = "mozjs"]
+    frozen_server_timing: DomRefCell<Option<Heap<JSVal>>>,
 }
 
 // TODO(#21269): next_hop
 // TODO(#21264): worker_start
-// TODO(#21258): fetch_start
-// TODO(#21259): domain_lookup_start
-// TODO(#21260): domain_lookup_end
-// TODO(#21261): connect_start
-// TODO(#21262): connect_end
 impl PerformanceResourceTiming {
-    pub fn new_inherited(
-        url: ServoUrl,
-        initiator_type: InitiatorType,
-        next_hop: Option<DOMString>,
-        fetch_start: f64,
-    ) -> PerformanceResourceTiming {
-        let entry_type = if initiator_type == InitiatorType::Navigation {
-            DOMString::from("navigation")
-        } else {
-            DOMString::from("resource")
-        };
-        PerformanceResourceTiming {
-            entry: PerformanceEntry::new_inherited(
-                DOMString::from(url.into_string()),
-                entry_type,
-                0.,
-                0.,
-            ),
-            initiator_type: initiator_type,
-            next_hop: next_hop,
-            worker_start: 0.,
-            redirect_start: 0.,
-            redirect_end: 0.,
-            fetch_start: fetch_start,
-            domain_lookup_end: 0.,
-            domain_lookup_start: 0.,
-            connect_start: 0.,
-            connect_end: 0.,
-            secure_connection_start: 0.,
-            request_start: 0.,
-            response_start: 0.,
-            response_end: 0.,
-            transfer_size: 0,
-            encoded_body_size: 0,
-            decoded_body_size: 0,
-        }
-    }
-
     //TODO fetch start should be in RFT
     #[allow(unrooted_must_root)]
-    fn from_resource_timing(
+    pub fn from_resource_timing(
+        global: &GlobalScope,
         url: ServoUrl,
         initiator_type: InitiatorType,
         next_hop: Option<DOMString>,
         resource_timing: &ResourceFetchTiming,
     ) -> PerformanceResourceTiming {
+        let performance = global.performance();
+        let time = |time_ns| performance.to_dom_high_res_time_stamp(time_ns);
+        let response_end = time(resource_timing.response_end);
+        // The navigation of a document starts at its time origin, and lasts until the end of
+        // its load event.
+        let (entry_type, start_time, duration) = if initiator_type == InitiatorType::Navigation {
+            (DOMString::from("navigation"), 0., 0.)
+        } else {
+            let start_time = time(resource_timing.start_time);
+            (
+                DOMString::from("resource"),
+                start_time,
+                response_end - start_time,
+            )
+        };
         PerformanceResourceTiming {
             entry: PerformanceEntry::new_inherited(
                 DOMString::from(url.into_string()),
-                DOMString::from("resource"),
-                resource_timing.start_time as f64,
-                resource_timing.response_end as f64 - resource_timing.start_time as f64,
+                entry_type,
+                start_time,
+                duration,
             ),
             initiator_type: initiator_type,
             next_hop: next_hop,
             worker_start: 0.,
-            redirect_start: resource_timing.redirect_start as f64,
-            redirect_end: resource_timing.redirect_end as f64,
-            fetch_start: resource_timing.fetch_start as f64,
-            domain_lookup_start: resource_timing.domain_lookup_start as f64,
-            //TODO (#21260)
-            domain_lookup_end: 0.,
-            connect_start: resource_timing.connect_start as f64,
-            connect_end: resource_timing.connect_end as f64,
-            secure_connection_start: resource_timing.secure_connection_start as f64,
-            request_start: resource_timing.request_start as f64,
-            response_start: resource_timing.response_start as f64,
-            response_end: resource_timing.response_end as f64,
-            transfer_size: 0,
-            encoded_body_size: 0,
-            decoded_body_size: 0,
+            redirect_start: time(resource_timing.redirect_start),
+            redirect_end: time(resource_timing.redirect_end),
+            fetch_start: time(resource_timing.fetch_start),
+            domain_lookup_start: time(resource_timing.domain_lookup_start),
+            domain_lookup_end: time(resource_timing.domain_lookup_end),
+            connect_start: time(resource_timing.connect_start),
+            connect_end: time(resource_timing.connect_end),
+            secure_connection_start: time(resource_timing.secure_connection_start),
+            request_start: time(resource_timing.request_start),
+            response_start: time(resource_timing.response_start),
+            response_end,
+            transfer_size: resource_timing.transfer_size,
+            encoded_body_size: resource_timing.encoded_body_size,
+            decoded_body_size: resource_timing.decoded_body_size,
+            server_timing: resource_timing.server_timing.clone(),
+            frozen_server_timing: DomRefCell::new(None),
         }
     }
 
@@ -146,6 +131,7 @@ impl PerformanceResourceTiming {
     ) -> DomRoot<PerformanceResourceTiming> {
         reflect_dom_object(
             Box::new(PerformanceResourceTiming::from_resource_timing(
+                global,
                 url,
                 initiator_type,
                 next_hop,
@@ -163,9 +149,11 @@ impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
     fn InitiatorType(&self) -> DOMString {
         match self.initiator_type {
             InitiatorType::LocalName(ref n) => DOMString::from(n.clone()),
+            InitiatorType::Css => DOMString::from("css"),
             InitiatorType::Navigation => DOMString::from("navigation"),
             InitiatorType::XMLHttpRequest => DOMString::from("xmlhttprequest"),
             InitiatorType::Fetch => DOMString::from("fetch"),
+            InitiatorType::Beacon => DOMString::from("beacon"),
             InitiatorType::Other => DOMString::from("other"),
         }
     }
@@ -249,4 +237,29 @@ impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
     fn ResponseEnd(&self) -> DOMHighResTimeStamp {
         reduce_timing_resolution(self.response_end)
     }
+
+    // https://w3c.github.io/server-timing/#dom-performanceresourcetiming-servertiming
+    fn ServerTiming(&self, cx: JSContext) -> JSVal {
+        if let Some(server_timing) = &*self.frozen_server_timing.borrow() {
+            return server_timing.get();
+        }
+
+        let global = self.global();
+        let entries: Vec<_> = self
+            .server_timing
+            .iter()
+            .map(|metric| PerformanceServerTiming::new(&global, metric))
+            .collect();
+        let frozen_server_timing = to_frozen_array(&entries, cx);
+
+        // Safety: need to create the Heap value in its final memory location before setting it.
+        *self.frozen_server_timing.borrow_mut() = Some(Heap::default());
+        self.frozen_server_timing
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .set(frozen_server_timing);
+
+        frozen_server_timing
+    }
 }
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::codegen::Bindings::PerformanceServerTimingBinding::{
+    self, PerformanceServerTimingMethods,
+};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use net_traits::ServerTimingMetric;
+
+/// <https://w3c.github.io/server-timing/#the-performanceservertiming-interface>
+#[dom_struct]
+pub struct PerformanceServerTiming {
+    reflector_: Reflector,
+    name: DOMString,
+    duration: f64,
+    description: DOMString,
+}
+
+impl PerformanceServerTiming {
+    fn new_inherited(metric: &ServerTimingMetric) -> PerformanceServerTiming {
+        PerformanceServerTiming {
+            reflector_: Reflector::new(),
+            name: DOMString::from(metric.name.clone()),
+            duration: metric.duration,
+            description: DOMString::from(metric.description.clone()),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        metric: &ServerTimingMetric,
+    ) -> DomRoot<PerformanceServerTiming> {
+        reflect_dom_object(
+            Box::new(PerformanceServerTiming::new_inherited(metric)),
+            global,
+            PerformanceServerTimingBinding::Wrap,
+        )
+    }
+}
+
+impl PerformanceServerTimingMethods for PerformanceServerTiming {
+    // https://w3c.github.io/server-timing/#dom-performanceservertiming-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://w3c.github.io/server-timing/#dom-performanceservertiming-duration
+    fn Duration(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.duration)
+    }
+
+    // https://w3c.github.io/server-timing/#dom-performanceservertiming-description
+    fn Description(&self) -> DOMString {
+        self.description.clone()
+    }
+}
@@ -352,10 +352,14 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/sensors/#secure-context
+        PermissionName::Accelerometer => false,
+        PermissionName::Gyroscope => false,
+        PermissionName::Magnetometer => false,
     }
 }
 
-fn prompt_user_from_embedder(prompt: PermissionPrompt, gs: &GlobalScope) -> PermissionState {
+pub fn prompt_user_from_embedder(prompt: PermissionPrompt, gs: &GlobalScope) -> PermissionState {
     let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
     gs.send_to_embedder(EmbedderMsg::PromptPermission(prompt, sender));
 
@@ -388,6 +392,9 @@ impl From<PermissionName> for embedder_traits::PermissionName {
             PermissionName::Persistent_storage => {
                 embedder_traits::PermissionName::PersistentStorage
             },
+            PermissionName::Accelerometer => embedder_traits::PermissionName::Accelerometer,
+            PermissionName::Gyroscope => embedder_traits::PermissionName::Gyroscope,
+            PermissionName::Magnetometer => embedder_traits::PermissionName::Magnetometer,
         }
     }
 }
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::authenticatorresponse::{same_object_array_buffer, AuthenticatorResponse};
+use crate::dom::bindings::codegen::Bindings::PublicKeyCredentialBinding::{
+    self, AuthenticationExtensionsClientOutputs, PublicKeyCredentialMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::credential::Credential;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::JSContext;
+use crate::webauthn;
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+// https://w3c.github.io/webauthn/#iface-pkcredential
+#[dom_struct]
+pub struct PublicKeyCredential {
+    credential: Credential,
+    raw_id: Vec<u8>,
+    #[ignore_malloc_size_of = "mozjs"]
+    raw_id_buffer: Heap<*mut JSObject>,
+    response: Dom<AuthenticatorResponse>,
+}
+
+impl PublicKeyCredential {
+    fn new_inherited(raw_id: Vec<u8>, response: &AuthenticatorResponse) -> PublicKeyCredential {
+        PublicKeyCredential {
+            credential: Credential::new_inherited(webauthn::base64url(&raw_id), "public-key"),
+            raw_id,
+            raw_id_buffer: Heap::default(),
+            response: Dom::from_ref(response),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        raw_id: Vec<u8>,
+        response: &AuthenticatorResponse,
+    ) -> DomRoot<PublicKeyCredential> {
+        reflect_dom_object(
+            Box::new(PublicKeyCredential::new_inherited(raw_id, response)),
+            global,
+            PublicKeyCredentialBinding::Wrap,
+        )
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-isuserverifyingplatformauthenticatoravailable
+    #[allow(non_snake_case)]
+    pub fn IsUserVerifyingPlatformAuthenticatorAvailable(global: &GlobalScope) -> Rc<Promise> {
+        let promise = Promise::new(global);
+        // The authenticators of the embedder may be security keys, so only
+        // the software authenticator is known to verify the user.
+        promise.resolve_native(&pref!(dom.webauthn.testing.enabled));
+        promise
+    }
+}
+
+impl PublicKeyCredentialMethods for PublicKeyCredential {
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-rawid
+    fn RawId(&self, cx: JSContext) -> NonNull<JSObject> {
+        same_object_array_buffer(cx, &self.raw_id_buffer, &self.raw_id)
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-response
+    fn Response(&self) -> DomRoot<AuthenticatorResponse> {
+        DomRoot::from_ref(&*self.response)
+    }
+
+    // https://w3c.github.io/webauthn/#dom-publickeycredential-getclientextensionresults
+    fn GetClientExtensionResults(&self) -> AuthenticationExtensionsClientOutputs {
+        AuthenticationExtensionsClientOutputs::empty()
+    }
+}
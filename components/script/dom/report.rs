@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::ReportBinding;
+use crate::dom::bindings::codegen::Bindings::ReportBinding::ReportMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cspviolationreportbody::CSPViolationReportBody;
+use crate::dom::deprecationreportbody::DeprecationReportBody;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::reportbody::ReportBody;
+use crate::reporting::{ReportBodyData, ReportData};
+use dom_struct::dom_struct;
+
+/// <https://w3c.github.io/reporting/#report>
+#[dom_struct]
+pub struct Report {
+    reflector_: Reflector,
+    type_: DOMString,
+    url: DOMString,
+    body: Option<Dom<ReportBody>>,
+}
+
+impl Report {
+    fn new_inherited(data: &ReportData, body: Option<&ReportBody>) -> Report {
+        Report {
+            reflector_: Reflector::new(),
+            type_: DOMString::from(data.type_.clone()),
+            url: DOMString::from(data.url.clone()),
+            body: body.map(Dom::from_ref),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, data: &ReportData) -> DomRoot<Report> {
+        let body = match data.body {
+            ReportBodyData::CSPViolation(ref body) => {
+                DomRoot::upcast::<ReportBody>(CSPViolationReportBody::new(global, body.clone()))
+            },
+            ReportBodyData::Deprecation(ref body) => {
+                DomRoot::upcast::<ReportBody>(DeprecationReportBody::new(global, body.clone()))
+            },
+        };
+        reflect_dom_object(
+            Box::new(Report::new_inherited(data, Some(&body))),
+            global,
+            ReportBinding::Wrap,
+        )
+    }
+
+    pub fn type_(&self) -> &DOMString {
+        &self.type_
+    }
+}
+
+impl ReportMethods for Report {
+    // https://w3c.github.io/reporting/#dom-report-type
+    fn Type(&self) -> DOMString {
+        self.type_.clone()
+    }
+
+    // https://w3c.github.io/reporting/#dom-report-url
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    // https://w3c.github.io/reporting/#dom-report-body
+    fn GetBody(&self) -> Option<DomRoot<ReportBody>> {
+        self.body.as_ref().map(|body| DomRoot::from_ref(&**body))
+    }
+}
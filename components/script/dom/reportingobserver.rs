@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding;
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::ReportingObserverCallback;
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::ReportingObserverMethods;
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::ReportingObserverOptions;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::report::Report;
+use crate::task_source::TaskSource;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+/// <https://w3c.github.io/reporting/#reportingobserver>
+#[dom_struct]
+pub struct ReportingObserver {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<ReportingObserverCallback>,
+    /// The report types observed, or `None` for all of them.
+    types: Option<Vec<DOMString>>,
+    buffered: bool,
+    /// <https://w3c.github.io/reporting/#reportingobserver-report-queue>
+    report_queue: DomRefCell<Vec<Dom<Report>>>,
+}
+
+impl ReportingObserver {
+    fn new_inherited(
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> ReportingObserver {
+        ReportingObserver {
+            reflector_: Reflector::new(),
+            callback,
+            types: options.types.clone(),
+            buffered: options.buffered,
+            report_queue: DomRefCell::new(vec![]),
+        }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> Fallible<DomRoot<ReportingObserver>> {
+        Ok(reflect_dom_object(
+            Box::new(ReportingObserver::new_inherited(callback, options)),
+            global,
+            ReportingObserverBinding::Wrap,
+        ))
+    }
+
+    /// Whether this observer is interested in reports of type `type_`.
+    pub fn observes(&self, type_: &DOMString) -> bool {
+        self.types
+            .as_ref()
+            .map_or(true, |types| types.contains(type_))
+    }
+
+    /// Appends `report` to the report queue, and queues a task to invoke the
+    /// callback if the queue was empty.
+    /// <https://w3c.github.io/reporting/#add-report>
+    pub fn queue_report(&self, report: &Report) {
+        let mut report_queue = self.report_queue.borrow_mut();
+        report_queue.push(Dom::from_ref(report));
+        if report_queue.len() > 1 {
+            return;
+        }
+        let global = self.global();
+        let observer = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(invoke_reporting_observer: move || {
+                observer.root().invoke();
+            }),
+            &global,
+        );
+    }
+
+    /// <https://w3c.github.io/reporting/#invoke-observers>
+    fn invoke(&self) {
+        let reports = self.TakeRecords();
+        if reports.is_empty() {
+            return;
+        }
+        let _ = self
+            .callback
+            .Call_(self, reports, self, ExceptionHandling::Report);
+    }
+}
+
+impl ReportingObserverMethods for ReportingObserver {
+    // https://w3c.github.io/reporting/#dom-reportingobserver-observe
+    fn Observe(&self) {
+        let global = self.global();
+        global.add_reporting_observer(self);
+        if !self.buffered {
+            return;
+        }
+        for report in global.buffered_reports() {
+            if self.observes(report.type_()) {
+                self.queue_report(&report);
+            }
+        }
+    }
+
+    // https://w3c.github.io/reporting/#dom-reportingobserver-disconnect
+    fn Disconnect(&self) {
+        self.global().remove_reporting_observer(self);
+    }
+
+    // https://w3c.github.io/reporting/#dom-reportingobserver-takerecords
+    fn TakeRecords(&self) -> Vec<DomRoot<Report>> {
+        self.report_queue
+            .borrow_mut()
+            .drain(..)
+            .map(|report| DomRoot::from_ref(&*report))
+            .collect()
+    }
+}
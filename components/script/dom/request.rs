@@ -14,6 +14,7 @@ use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestMode;
+use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestPriority;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestRedirect;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -35,6 +36,7 @@ use net_traits::request::RedirectMode as NetTraitsRequestRedirect;
 use net_traits::request::Referrer as NetTraitsRequestReferrer;
 use net_traits::request::Request as NetTraitsRequest;
 use net_traits::request::RequestMode as NetTraitsRequestMode;
+use net_traits::request::RequestPriority as NetTraitsRequestPriority;
 use net_traits::request::{Origin, Window};
 use net_traits::ReferrerPolicy as MsgReferrerPolicy;
 use servo_url::ServoUrl;
@@ -164,12 +166,16 @@ impl Request {
         request.cache_mode = temporary_request.cache_mode;
         request.redirect_mode = temporary_request.redirect_mode;
         request.integrity_metadata = temporary_request.integrity_metadata;
+        request.keep_alive = temporary_request.keep_alive;
+        request.priority = temporary_request.priority;
 
         // Step 14
         if init.body.is_some() ||
             init.cache.is_some() ||
             init.credentials.is_some() ||
             init.integrity.is_some() ||
+            init.keepalive.is_some() ||
+            init.priority.is_some() ||
             init.headers.is_some() ||
             init.method.is_some() ||
             init.mode.is_some() ||
@@ -282,7 +288,15 @@ impl Request {
             request.integrity_metadata = integrity;
         }
 
-        // Step 26 TODO: "If init["keepalive"] exists..."
+        // Step 26
+        if let Some(init_keepalive) = init.keepalive {
+            request.keep_alive = init_keepalive;
+        }
+
+        // https://fetch.spec.whatwg.org/#dom-request, "If init["priority"] exists..."
+        if let Some(init_priority) = init.priority {
+            request.priority = init_priority.into();
+        }
 
         // Step 27.1
         if let Some(init_method) = init.method.as_ref() {
@@ -414,7 +428,8 @@ impl Request {
 
         // Step 35-36
         if let Some(Some(ref init_body)) = init.body {
-            // Step 36.2 TODO "If init["keepalive"] exists and is true..."
+            // Step 36.2 The keepalive flag only rules out ReadableStream
+            // bodies, which aren't supported yet, see step 39.
 
             // Step 36.3
             let extracted_body_tmp = init_body.extract();
@@ -642,6 +657,11 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    // https://fetch.spec.whatwg.org/#dom-request-keepalive
+    fn Keepalive(&self) -> bool {
+        self.request.borrow().keep_alive
+    }
+
     // https://fetch.spec.whatwg.org/#dom-body-bodyused
     fn BodyUsed(&self) -> bool {
         self.body_used.get()
@@ -875,6 +895,16 @@ impl Into<ReferrerPolicy> for MsgReferrerPolicy {
     }
 }
 
+impl Into<NetTraitsRequestPriority> for RequestPriority {
+    fn into(self) -> NetTraitsRequestPriority {
+        match self {
+            RequestPriority::High => NetTraitsRequestPriority::High,
+            RequestPriority::Low => NetTraitsRequestPriority::Low,
+            RequestPriority::Auto => NetTraitsRequestPriority::Auto,
+        }
+    }
+}
+
 impl Into<NetTraitsRequestRedirect> for RequestRedirect {
     fn into(self) -> NetTraitsRequestRedirect {
         match self {
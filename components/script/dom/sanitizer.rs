@@ -0,0 +1,653 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::SanitizerBinding::{
+    self, SanitizerConfig, SanitizerMethods, SetHTMLOptions,
+};
+use crate::dom::bindings::codegen::UnionTypes::SanitizerOrSanitizerConfig;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::htmltemplateelement::HTMLTemplateElement;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use html5ever::LocalName;
+use servo_url::ServoUrl;
+use std::collections::HashSet;
+
+/// The elements allowed by the built-in safe default configuration.
+/// https://wicg.github.io/sanitizer-api/#built-in-safe-default-configuration
+static DEFAULT_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "article",
+    "aside",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "dd",
+    "del",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "ins",
+    "kbd",
+    "li",
+    "main",
+    "mark",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "search",
+    "section",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "u",
+    "ul",
+    "var",
+    "wbr",
+];
+
+/// The attributes allowed by the built-in safe default configuration.
+static DEFAULT_ATTRIBUTES: &[&str] = &[
+    "abbr", "cite", "colspan", "datetime", "dir", "headers", "href", "hreflang", "lang",
+    "reversed", "rowspan", "scope", "span", "start", "title", "type", "value",
+];
+
+/// Attributes whose value is removed if it is a `javascript:` URL, whatever
+/// the element and the namespace of the attribute (this covers `xlink:href`).
+/// A superset of the built-in navigating URL attributes list.
+/// https://wicg.github.io/sanitizer-api/#sanitize-core
+static URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "codebase",
+    "data",
+    "formaction",
+    "href",
+    "icon",
+    "longdesc",
+    "manifest",
+    "ping",
+    "poster",
+    "src",
+];
+
+/// Event handler content attributes, the ones of HTML and of the other
+/// specifications whose events Servo can fire.
+/// https://html.spec.whatwg.org/multipage/#ix-event-handlers
+static EVENT_HANDLER_ATTRIBUTES: &[&str] = &[
+    "onabort",
+    "onafterprint",
+    "onanimationcancel",
+    "onanimationend",
+    "onanimationiteration",
+    "onanimationstart",
+    "onauxclick",
+    "onbeforeinput",
+    "onbeforeprint",
+    "onbeforetoggle",
+    "onbeforeunload",
+    "onblur",
+    "oncancel",
+    "oncanplay",
+    "oncanplaythrough",
+    "onchange",
+    "onclick",
+    "onclose",
+    "oncontextlost",
+    "oncontextmenu",
+    "oncontextrestored",
+    "oncopy",
+    "oncuechange",
+    "oncut",
+    "ondblclick",
+    "ondrag",
+    "ondragend",
+    "ondragenter",
+    "ondragexit",
+    "ondragleave",
+    "ondragover",
+    "ondragstart",
+    "ondrop",
+    "ondurationchange",
+    "onemptied",
+    "onended",
+    "onerror",
+    "onfocus",
+    "onfocusin",
+    "onfocusout",
+    "onformdata",
+    "ongotpointercapture",
+    "onhashchange",
+    "oninput",
+    "oninvalid",
+    "onkeydown",
+    "onkeypress",
+    "onkeyup",
+    "onlanguagechange",
+    "onload",
+    "onloadeddata",
+    "onloadedmetadata",
+    "onloadstart",
+    "onlostpointercapture",
+    "onmessage",
+    "onmessageerror",
+    "onmousedown",
+    "onmouseenter",
+    "onmouseleave",
+    "onmousemove",
+    "onmouseout",
+    "onmouseover",
+    "onmouseup",
+    "onoffline",
+    "ononline",
+    "onpagehide",
+    "onpageshow",
+    "onpaste",
+    "onpause",
+    "onplay",
+    "onplaying",
+    "onpointercancel",
+    "onpointerdown",
+    "onpointerenter",
+    "onpointerleave",
+    "onpointermove",
+    "onpointerout",
+    "onpointerover",
+    "onpointerup",
+    "onpopstate",
+    "onprogress",
+    "onratechange",
+    "onrejectionhandled",
+    "onreset",
+    "onresize",
+    "onscroll",
+    "onscrollend",
+    "onsecuritypolicyviolation",
+    "onseeked",
+    "onseeking",
+    "onselect",
+    "onselectionchange",
+    "onselectstart",
+    "onshow",
+    "onslotchange",
+    "onstalled",
+    "onstorage",
+    "onsubmit",
+    "onsuspend",
+    "ontimeupdate",
+    "ontoggle",
+    "ontouchcancel",
+    "ontouchend",
+    "ontouchmove",
+    "ontouchstart",
+    "ontransitioncancel",
+    "ontransitionend",
+    "ontransitionrun",
+    "ontransitionstart",
+    "onunhandledrejection",
+    "onunload",
+    "onvolumechange",
+    "onvrdisplayactivate",
+    "onvrdisplayblur",
+    "onvrdisplayconnect",
+    "onvrdisplaydeactivate",
+    "onvrdisplaydisconnect",
+    "onvrdisplayfocus",
+    "onvrdisplaypresentchange",
+    "onwaiting",
+    "onwheel",
+];
+
+/// SVG elements whose `attributeName` can make them animate another attribute.
+/// https://wicg.github.io/sanitizer-api/#built-in-animating-url-attributes-list
+static ANIMATING_ELEMENTS: &[&str] = &["animate", "animateMotion", "animateTransform", "set"];
+
+/// A sanitizer configuration. Element and attribute names refer to HTML
+/// elements and to attributes without a namespace.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct SanitizerConfiguration {
+    /// The elements that are kept, or `None` if all elements not in
+    /// `remove_elements` are kept.
+    elements: Option<HashSet<LocalName>>,
+    remove_elements: HashSet<LocalName>,
+    replace_with_children_elements: HashSet<LocalName>,
+    /// The attributes that are kept, or `None` if all attributes not in
+    /// `remove_attributes` are kept.
+    attributes: Option<HashSet<LocalName>>,
+    remove_attributes: HashSet<LocalName>,
+    comments: bool,
+    data_attributes: bool,
+}
+
+fn names(list: &[&str]) -> HashSet<LocalName> {
+    list.iter().map(|name| LocalName::from(*name)).collect()
+}
+
+fn names_from_sequence(list: &Option<Vec<DOMString>>) -> Option<HashSet<LocalName>> {
+    list.as_ref()
+        .map(|list| list.iter().map(|name| LocalName::from(&**name)).collect())
+}
+
+fn sequence_from_names(names: &HashSet<LocalName>) -> Vec<DOMString> {
+    let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+    names.sort();
+    names.into_iter().map(DOMString::from).collect()
+}
+
+/// https://wicg.github.io/sanitizer-api/#remove-unsafe
+fn is_unsafe_element(element: &Element) -> bool {
+    match *element.namespace() {
+        ns!(html) => match *element.local_name() {
+            local_name!("embed") |
+            local_name!("frame") |
+            local_name!("iframe") |
+            local_name!("object") |
+            local_name!("script") => true,
+            _ => false,
+        },
+        ns!(svg) => match *element.local_name() {
+            local_name!("script") | local_name!("use") => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn is_event_handler_attribute(name: &LocalName) -> bool {
+    EVENT_HANDLER_ATTRIBUTES.binary_search(&&**name).is_ok()
+}
+
+/// Whether the attribute makes an SVG animation element animate an `href`,
+/// which would let it set a `javascript:` URL after sanitizing.
+/// https://wicg.github.io/sanitizer-api/#built-in-animating-url-attributes-list
+fn is_animating_url_attribute(element: &Element, name: &LocalName, value: &str) -> bool {
+    *element.namespace() == ns!(svg) &&
+        ANIMATING_ELEMENTS.contains(&&**element.local_name()) &&
+        &**name == "attributeName" &&
+        match value.trim_matches(|c: char| c.is_ascii_whitespace()) {
+            "href" | "xlink:href" => true,
+            _ => false,
+        }
+}
+
+/// https://wicg.github.io/sanitizer-api/#contains-a-javascript-url
+fn contains_javascript_url(value: &str) -> bool {
+    ServoUrl::parse(value).map_or(false, |url| url.scheme() == "javascript")
+}
+
+impl SanitizerConfiguration {
+    /// https://wicg.github.io/sanitizer-api/#built-in-safe-default-configuration
+    pub fn default_configuration() -> SanitizerConfiguration {
+        SanitizerConfiguration {
+            elements: Some(names(DEFAULT_ELEMENTS)),
+            remove_elements: HashSet::new(),
+            replace_with_children_elements: HashSet::new(),
+            attributes: Some(names(DEFAULT_ATTRIBUTES)),
+            remove_attributes: HashSet::new(),
+            comments: false,
+            data_attributes: false,
+        }
+    }
+
+    /// https://wicg.github.io/sanitizer-api/#sanitizer-set-a-configuration
+    ///
+    /// A configuration without any members stands for the default one, since
+    /// an omitted configuration argument is passed as an empty dictionary.
+    fn from_dictionary(config: &SanitizerConfig) -> Fallible<SanitizerConfiguration> {
+        let is_empty = config.elements.is_none() &&
+            config.removeElements.is_none() &&
+            config.replaceWithChildrenElements.is_none() &&
+            config.attributes.is_none() &&
+            config.removeAttributes.is_none() &&
+            config.comments.is_none() &&
+            config.dataAttributes.is_none();
+        if is_empty {
+            return Ok(SanitizerConfiguration::default_configuration());
+        }
+
+        if config.elements.is_some() && config.removeElements.is_some() {
+            return Err(Error::Type(
+                "elements and removeElements can't both be given".to_owned(),
+            ));
+        }
+        if config.attributes.is_some() && config.removeAttributes.is_some() {
+            return Err(Error::Type(
+                "attributes and removeAttributes can't both be given".to_owned(),
+            ));
+        }
+
+        Ok(SanitizerConfiguration {
+            elements: names_from_sequence(&config.elements),
+            remove_elements: names_from_sequence(&config.removeElements).unwrap_or_default(),
+            replace_with_children_elements: names_from_sequence(
+                &config.replaceWithChildrenElements,
+            )
+            .unwrap_or_default(),
+            attributes: names_from_sequence(&config.attributes),
+            remove_attributes: names_from_sequence(&config.removeAttributes).unwrap_or_default(),
+            comments: config.comments.unwrap_or(true),
+            data_attributes: config.dataAttributes.unwrap_or(true),
+        })
+    }
+
+    /// https://wicg.github.io/sanitizer-api/#get-a-sanitizer-instance-from-options
+    pub fn from_options(options: &SetHTMLOptions) -> Fallible<SanitizerConfiguration> {
+        match options.sanitizer {
+            None => Ok(SanitizerConfiguration::default_configuration()),
+            Some(SanitizerOrSanitizerConfig::Sanitizer(ref sanitizer)) => {
+                Ok(sanitizer.configuration.borrow().clone())
+            },
+            Some(SanitizerOrSanitizerConfig::SanitizerConfig(ref config)) => {
+                SanitizerConfiguration::from_dictionary(config)
+            },
+        }
+    }
+
+    fn element_allowed(&self, element: &Element) -> bool {
+        if *element.namespace() != ns!(html) {
+            // Foreign elements can't be named in the configuration yet.
+            return self.elements.is_none();
+        }
+        let name = element.local_name();
+        if self.remove_elements.contains(name) {
+            return false;
+        }
+        self.elements
+            .as_ref()
+            .map_or(true, |elements| elements.contains(name))
+    }
+
+    fn attribute_allowed(&self, namespace_is_null: bool, name: &LocalName) -> bool {
+        if !namespace_is_null {
+            return self.attributes.is_none();
+        }
+        if self.remove_attributes.contains(name) {
+            return false;
+        }
+        match self.attributes {
+            Some(ref attributes) => {
+                attributes.contains(name) || (self.data_attributes && name.starts_with("data-"))
+            },
+            None => self.data_attributes || !name.starts_with("data-"),
+        }
+    }
+
+    /// Removes everything the configuration doesn't allow from the
+    /// descendants of `node`. Only the safe variants of the API are exposed,
+    /// so script-capable elements, event handlers, `javascript:` URLs and SVG
+    /// animations of `href` are removed whatever the configuration says.
+    ///
+    /// https://wicg.github.io/sanitizer-api/#sanitize-core
+    pub fn sanitize(&self, node: &Node) {
+        for child in node.children().collect::<Vec<_>>() {
+            match child.type_id() {
+                NodeTypeId::CharacterData(CharacterDataTypeId::Text(_)) => {},
+                NodeTypeId::CharacterData(CharacterDataTypeId::Comment) => {
+                    if !self.comments {
+                        child.remove_self();
+                    }
+                },
+                NodeTypeId::Element(_) => self.sanitize_element(node, &child),
+                _ => child.remove_self(),
+            }
+        }
+    }
+
+    fn sanitize_element(&self, parent: &Node, child: &Node) {
+        let element = child.downcast::<Element>().unwrap();
+
+        if let Some(template) = element.downcast::<HTMLTemplateElement>() {
+            self.sanitize(template.Content().upcast());
+        }
+
+        if is_unsafe_element(element) {
+            child.remove_self();
+            return;
+        }
+
+        if *element.namespace() == ns!(html) &&
+            self.replace_with_children_elements
+                .contains(element.local_name())
+        {
+            self.sanitize(child);
+            for grandchild in child.children().collect::<Vec<_>>() {
+                parent.InsertBefore(&grandchild, Some(child)).unwrap();
+            }
+            child.remove_self();
+            return;
+        }
+
+        if !self.element_allowed(element) {
+            child.remove_self();
+            return;
+        }
+
+        let removed: Vec<_> = element
+            .attrs()
+            .iter()
+            .filter(|attr| {
+                let name = attr.local_name();
+                let namespace_is_null = *attr.namespace() == ns!();
+                if is_event_handler_attribute(name) && namespace_is_null {
+                    return true;
+                }
+                if URL_ATTRIBUTES.contains(&&**name) && contains_javascript_url(&attr.value()) {
+                    return true;
+                }
+                if namespace_is_null && is_animating_url_attribute(element, name, &attr.value()) {
+                    return true;
+                }
+                !self.attribute_allowed(namespace_is_null, name)
+            })
+            .map(|attr| (attr.namespace().clone(), attr.local_name().clone()))
+            .collect();
+        for (namespace, local_name) in removed {
+            element.remove_attribute(&namespace, &local_name);
+        }
+
+        self.sanitize(child);
+    }
+}
+
+/// https://wicg.github.io/sanitizer-api/#sanitizer
+#[dom_struct]
+pub struct Sanitizer {
+    reflector_: Reflector,
+    configuration: DomRefCell<SanitizerConfiguration>,
+}
+
+impl Sanitizer {
+    fn new_inherited(configuration: SanitizerConfiguration) -> Sanitizer {
+        Sanitizer {
+            reflector_: Reflector::new(),
+            configuration: DomRefCell::new(configuration),
+        }
+    }
+
+    pub fn new(window: &Window, configuration: SanitizerConfiguration) -> DomRoot<Sanitizer> {
+        reflect_dom_object(
+            Box::new(Sanitizer::new_inherited(configuration)),
+            window,
+            SanitizerBinding::Wrap,
+        )
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-sanitizer
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        configuration: &SanitizerConfig,
+    ) -> Fallible<DomRoot<Sanitizer>> {
+        let configuration = SanitizerConfiguration::from_dictionary(configuration)?;
+        Ok(Sanitizer::new(window, configuration))
+    }
+}
+
+impl SanitizerMethods for Sanitizer {
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-get
+    fn Get(&self) -> SanitizerConfig {
+        let configuration = self.configuration.borrow();
+        SanitizerConfig {
+            removeElements: match configuration.elements {
+                Some(_) => None,
+                None => Some(sequence_from_names(&configuration.remove_elements)),
+            },
+            elements: configuration.elements.as_ref().map(sequence_from_names),
+            replaceWithChildrenElements: Some(sequence_from_names(
+                &configuration.replace_with_children_elements,
+            )),
+            removeAttributes: match configuration.attributes {
+                Some(_) => None,
+                None => Some(sequence_from_names(&configuration.remove_attributes)),
+            },
+            attributes: configuration.attributes.as_ref().map(sequence_from_names),
+            comments: Some(configuration.comments),
+            dataAttributes: Some(configuration.data_attributes),
+        }
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-allowelement
+    fn AllowElement(&self, element: DOMString) {
+        let name = LocalName::from(&*element);
+        let configuration = &mut *self.configuration.borrow_mut();
+        configuration.remove_elements.remove(&name);
+        configuration.replace_with_children_elements.remove(&name);
+        if let Some(ref mut elements) = configuration.elements {
+            elements.insert(name);
+        }
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-removeelement
+    fn RemoveElement(&self, element: DOMString) {
+        let name = LocalName::from(&*element);
+        let configuration = &mut *self.configuration.borrow_mut();
+        configuration.replace_with_children_elements.remove(&name);
+        match configuration.elements {
+            Some(ref mut elements) => {
+                elements.remove(&name);
+            },
+            None => {
+                configuration.remove_elements.insert(name);
+            },
+        }
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-replaceelementwithchildren
+    fn ReplaceElementWithChildren(&self, element: DOMString) {
+        let name = LocalName::from(&*element);
+        let configuration = &mut *self.configuration.borrow_mut();
+        configuration.remove_elements.remove(&name);
+        if let Some(ref mut elements) = configuration.elements {
+            elements.remove(&name);
+        }
+        configuration.replace_with_children_elements.insert(name);
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-allowattribute
+    fn AllowAttribute(&self, attribute: DOMString) {
+        let name = LocalName::from(&*attribute);
+        let configuration = &mut *self.configuration.borrow_mut();
+        configuration.remove_attributes.remove(&name);
+        if let Some(ref mut attributes) = configuration.attributes {
+            attributes.insert(name);
+        }
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-removeattribute
+    fn RemoveAttribute(&self, attribute: DOMString) {
+        let name = LocalName::from(&*attribute);
+        let configuration = &mut *self.configuration.borrow_mut();
+        match configuration.attributes {
+            Some(ref mut attributes) => {
+                attributes.remove(&name);
+            },
+            None => {
+                configuration.remove_attributes.insert(name);
+            },
+        }
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-setcomments
+    fn SetComments(&self, allow: bool) {
+        self.configuration.borrow_mut().comments = allow;
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-setdataattributes
+    fn SetDataAttributes(&self, allow: bool) {
+        self.configuration.borrow_mut().data_attributes = allow;
+    }
+
+    // https://wicg.github.io/sanitizer-api/#dom-sanitizer-removeunsafe
+    fn RemoveUnsafe(&self) {
+        let configuration = &mut *self.configuration.borrow_mut();
+        let unsafe_elements = names(&["embed", "frame", "iframe", "object", "script"]);
+        match configuration.elements {
+            Some(ref mut elements) => {
+                elements.retain(|name| !unsafe_elements.contains(name));
+            },
+            None => configuration.remove_elements.extend(unsafe_elements),
+        }
+        // Event handler attributes are always removed when sanitizing.
+        if let Some(ref mut attributes) = configuration.attributes {
+            attributes.retain(|name| !is_event_handler_attribute(name));
+        }
+    }
+}
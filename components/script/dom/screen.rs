@@ -8,8 +8,9 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::screenorientation::ScreenOrientation;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use euclid::Size2D;
@@ -22,6 +23,7 @@ use webrender_api::units::DeviceIntSize;
 pub struct Screen {
     reflector_: Reflector,
     window: Dom<Window>,
+    orientation: MutNullableDom<ScreenOrientation>,
 }
 
 impl Screen {
@@ -29,6 +31,7 @@ impl Screen {
         Screen {
             reflector_: Reflector::new(),
             window: Dom::from_ref(&window),
+            orientation: Default::default(),
         }
     }
 
@@ -40,7 +43,7 @@ impl Screen {
         )
     }
 
-    fn screen_size(&self) -> Size2D<u32, CSSPixel> {
+    pub fn screen_size(&self) -> Size2D<u32, CSSPixel> {
         let (send, recv) =
             ipc::channel::<DeviceIntSize>(self.global().time_profiler_chan().clone()).unwrap();
         self.window
@@ -65,6 +68,13 @@ impl Screen {
         let screen = recv.recv().unwrap_or(Size2D::zero());
         (screen.to_f32() / dpr).to_u32()
     }
+
+    /// Updates the orientation of the screen after the embedder rotated it.
+    pub fn update_orientation(&self) {
+        if let Some(orientation) = self.orientation.get() {
+            orientation.update();
+        }
+    }
 }
 
 impl ScreenMethods for Screen {
@@ -97,4 +107,10 @@ impl ScreenMethods for Screen {
     fn PixelDepth(&self) -> u32 {
         24
     }
+
+    // https://w3c.github.io/screen-orientation/#dom-screen-orientation
+    fn Orientation(&self) -> DomRoot<ScreenOrientation> {
+        self.orientation
+            .or_init(|| ScreenOrientation::new(&self.window, self))
+    }
 }
@@ -0,0 +1,235 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ScreenOrientationBinding::{
+    self, OrientationLockType, OrientationType, ScreenOrientationMethods,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::screen::Screen;
+use crate::dom::window::Window;
+use crate::realms::InRealm;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, ScreenOrientationLock};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use profile_traits::ipc as profile_ipc;
+use script_traits::ScriptMsg;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct ScreenOrientation {
+    eventtarget: EventTarget,
+    window: Dom<Window>,
+    screen: Dom<Screen>,
+    type_: Cell<OrientationType>,
+    angle: Cell<u16>,
+    /// The orientation the screen is being locked to, and the promise that
+    /// is resolved once it is.
+    #[ignore_malloc_size_of = "promises are hard"]
+    pending_lock: DomRefCell<Option<(OrientationLockType, Rc<Promise>)>>,
+}
+
+impl ScreenOrientation {
+    fn new_inherited(window: &Window, screen: &Screen) -> ScreenOrientation {
+        let angle = ScreenOrientation::query_angle(window);
+        ScreenOrientation {
+            eventtarget: EventTarget::new_inherited(),
+            window: Dom::from_ref(window),
+            screen: Dom::from_ref(screen),
+            type_: Cell::new(ScreenOrientation::orientation_type(screen, angle)),
+            angle: Cell::new(angle),
+            pending_lock: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(window: &Window, screen: &Screen) -> DomRoot<ScreenOrientation> {
+        reflect_dom_object(
+            Box::new(ScreenOrientation::new_inherited(window, screen)),
+            window,
+            ScreenOrientationBinding::Wrap,
+        )
+    }
+
+    fn query_angle(window: &Window) -> u16 {
+        let global = window.upcast::<GlobalScope>();
+        let (send, recv) =
+            profile_ipc::channel::<u16>(global.time_profiler_chan().clone()).unwrap();
+        global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::GetScreenOrientationAngle(send))
+            .unwrap();
+        recv.recv().unwrap_or(0)
+    }
+
+    /// The orientation type of a screen rotated by `angle`. The primary
+    /// orientations are the natural one and the one a quarter turn clockwise
+    /// from it, and the secondary ones are their opposites.
+    fn orientation_type(screen: &Screen, angle: u16) -> OrientationType {
+        let size = screen.screen_size();
+        // Square screens are portrait, like in media queries.
+        let is_landscape = size.width > size.height;
+        let is_primary = angle < 180;
+        match (is_landscape, is_primary) {
+            (false, true) => OrientationType::Portrait_primary,
+            (false, false) => OrientationType::Portrait_secondary,
+            (true, true) => OrientationType::Landscape_primary,
+            (true, false) => OrientationType::Landscape_secondary,
+        }
+    }
+
+    /// Whether the screen is in an orientation it can be locked to with
+    /// `lock`.
+    fn matches(&self, lock: OrientationLockType) -> bool {
+        let type_ = self.type_.get();
+        let is_landscape = type_ == OrientationType::Landscape_primary ||
+            type_ == OrientationType::Landscape_secondary;
+        match lock {
+            OrientationLockType::Any => true,
+            OrientationLockType::Natural => self.angle.get() == 0,
+            OrientationLockType::Landscape => is_landscape,
+            OrientationLockType::Portrait => !is_landscape,
+            OrientationLockType::Portrait_primary => type_ == OrientationType::Portrait_primary,
+            OrientationLockType::Portrait_secondary => type_ == OrientationType::Portrait_secondary,
+            OrientationLockType::Landscape_primary => type_ == OrientationType::Landscape_primary,
+            OrientationLockType::Landscape_secondary => {
+                type_ == OrientationType::Landscape_secondary
+            },
+        }
+    }
+
+    /// Rejects the promise of the lock being applied, if any, with an
+    /// `AbortError`.
+    fn abort_pending_lock(&self) {
+        if let Some((_, promise)) = self.pending_lock.borrow_mut().take() {
+            promise.reject_error(Error::Abort);
+        }
+    }
+
+    /// https://w3c.github.io/screen-orientation/#dfn-screen-orientation-change-steps
+    pub fn update(&self) {
+        let angle = ScreenOrientation::query_angle(&self.window);
+        let type_ = ScreenOrientation::orientation_type(&self.screen, angle);
+        if angle == self.angle.get() && type_ == self.type_.get() {
+            return;
+        }
+        self.angle.set(angle);
+        self.type_.set(type_);
+        self.upcast::<EventTarget>().fire_event(atom!("change"));
+
+        let locked = match *self.pending_lock.borrow() {
+            Some((lock, _)) => self.matches(lock),
+            None => false,
+        };
+        if locked {
+            if let Some((_, promise)) = self.pending_lock.borrow_mut().take() {
+                promise.resolve_native(&());
+            }
+        }
+    }
+
+    /// Handles the embedder's answer to locking the screen for `promise`.
+    fn lock_applied(&self, promise: &Rc<Promise>, accepted: bool) {
+        let lock = match *self.pending_lock.borrow() {
+            // The lock was aborted in the meantime.
+            Some((lock, ref pending)) if Rc::ptr_eq(pending, promise) => lock,
+            _ => return,
+        };
+        if !accepted {
+            self.pending_lock.borrow_mut().take();
+            promise.reject_error(Error::NotSupported);
+        } else if self.matches(lock) {
+            self.pending_lock.borrow_mut().take();
+            promise.resolve_native(&());
+        }
+        // Otherwise, the promise is resolved once the screen rotated.
+    }
+}
+
+impl ScreenOrientationMethods for ScreenOrientation {
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-lock
+    fn Lock(&self, orientation: OrientationLockType, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new_in_current_realm(&global, comp);
+        if !self.window.Document().is_fully_active() {
+            promise.reject_error(Error::InvalidState);
+            return promise;
+        }
+
+        self.abort_pending_lock();
+        *self.pending_lock.borrow_mut() = Some((orientation, promise.clone()));
+
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_orientation = Trusted::new(self);
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let accepted: bool = message.to().unwrap();
+                let promise = match trusted_promise.take() {
+                    Some(promise) => promise,
+                    None => return,
+                };
+                let orientation = trusted_orientation.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(screen_orientation_locked: move || {
+                        orientation.root().lock_applied(&promise.root(), accepted);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue screen orientation lock task {:?}", err);
+                }
+            }),
+        );
+
+        let lock = match orientation {
+            OrientationLockType::Any => ScreenOrientationLock::Any,
+            OrientationLockType::Natural => ScreenOrientationLock::Natural,
+            OrientationLockType::Landscape => ScreenOrientationLock::Landscape,
+            OrientationLockType::Portrait => ScreenOrientationLock::Portrait,
+            OrientationLockType::Portrait_primary => ScreenOrientationLock::PortraitPrimary,
+            OrientationLockType::Portrait_secondary => ScreenOrientationLock::PortraitSecondary,
+            OrientationLockType::Landscape_primary => ScreenOrientationLock::LandscapePrimary,
+            OrientationLockType::Landscape_secondary => ScreenOrientationLock::LandscapeSecondary,
+        };
+        global.send_to_embedder(EmbedderMsg::LockScreenOrientation(lock, sender));
+        promise
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-unlock
+    fn Unlock(&self) {
+        if !self.window.Document().is_fully_active() {
+            return;
+        }
+        self.abort_pending_lock();
+        self.global()
+            .send_to_embedder(EmbedderMsg::UnlockScreenOrientation);
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-type
+    fn Type(&self) -> OrientationType {
+        self.type_.get()
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-angle
+    fn Angle(&self) -> u16 {
+        self.angle.get()
+    }
+
+    // https://w3c.github.io/screen-orientation/#dom-screenorientation-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+}
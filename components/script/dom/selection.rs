@@ -2,6 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
 use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
 use crate::dom::bindings::codegen::Bindings::SelectionBinding::{SelectionMethods, Wrap};
@@ -11,13 +14,17 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::node::{window_from_node, Node};
 use crate::dom::range::Range;
+use crate::dom::text::Text;
+use crate::segmentation::{next_sentence_boundary, next_word_boundary, word_at};
 use crate::task_source::TaskSource;
 use dom_struct::dom_struct;
 use std::cell::Cell;
+use std::iter;
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf)]
 enum Direction {
@@ -26,6 +33,15 @@ enum Direction {
     Directionless,
 }
 
+/// How far `Selection.modify()` moves the focus of the selection.
+#[derive(Clone, Copy, PartialEq)]
+enum Granularity {
+    Character,
+    Word,
+    Sentence,
+    DocumentBoundary,
+}
+
 #[dom_struct]
 pub struct Selection {
     reflector_: Reflector,
@@ -33,6 +49,8 @@ pub struct Selection {
     range: MutNullableDom<Range>,
     direction: Cell<Direction>,
     task_queued: Cell<bool>,
+    /// The text nodes whose selected text layout highlights.
+    highlighted: DomRefCell<Vec<Dom<Text>>>,
 }
 
 impl Selection {
@@ -43,6 +61,7 @@ impl Selection {
             range: MutNullableDom::new(None),
             direction: Cell::new(Direction::Directionless),
             task_queued: Cell::new(false),
+            highlighted: DomRefCell::new(vec![]),
         }
     }
 
@@ -94,6 +113,7 @@ impl Selection {
                 task!(selectionchange_task_steps: move || {
                     let this = this.root();
                     this.task_queued.set(false);
+                    this.update_highlight();
                     this.document.upcast::<EventTarget>().fire_event(atom!("selectionchange"));
                 }),
                 window.upcast(),
@@ -105,6 +125,279 @@ impl Selection {
     fn is_same_root(&self, node: &Node) -> bool {
         &*node.GetRootNode(&GetRootNodeOptions::empty()) == self.document.upcast::<Node>()
     }
+
+    /// Marks the text that the selection covers for layout to highlight, and
+    /// unmarks the text that it no longer covers.
+    fn update_highlight(&self) {
+        let mut highlighted = vec![];
+        if let Some(range) = self.range.get().filter(|range| !range.Collapsed()) {
+            let start = range.StartContainer();
+            let end = range.EndContainer();
+            let root = self.document.upcast::<Node>();
+            for node in iter::once(start.clone()).chain(start.following_nodes(root)) {
+                if let Some(text) = node.downcast::<Text>() {
+                    let begin = if node == start {
+                        range.StartOffset()
+                    } else {
+                        0
+                    };
+                    let finish = if node == end {
+                        range.EndOffset()
+                    } else {
+                        node.len()
+                    };
+                    match range.ComparePoint(&node, begin) {
+                        Ok(0) => {},
+                        Ok(1) => break,
+                        _ => continue,
+                    }
+                    let data = text.upcast::<CharacterData>().data();
+                    text.set_selected(Some(
+                        utf16_offset_to_byte_offset(&data, begin)..
+                            utf16_offset_to_byte_offset(&data, finish),
+                    ));
+                    highlighted.push(Dom::from_ref(text));
+                }
+                if node == end {
+                    break;
+                }
+            }
+        }
+        // The caret is painted in the text of an editing host.
+        if let Some(range) = self.range.get().filter(|range| range.Collapsed()) {
+            let node = range.StartContainer();
+            if let Some(text) = node.downcast::<Text>() {
+                if node.editing_host().is_some() {
+                    let data = text.upcast::<CharacterData>().data();
+                    let offset = utf16_offset_to_byte_offset(&data, range.StartOffset());
+                    text.set_selected(Some(offset..offset));
+                    highlighted.push(Dom::from_ref(text));
+                }
+            }
+        }
+        for text in self.highlighted.borrow().iter() {
+            if !highlighted.contains(text) {
+                text.set_selected(None);
+            }
+        }
+        *self.highlighted.borrow_mut() = highlighted;
+    }
+
+    /// Where the focus of the selection ends up when it moves by `granularity`
+    /// from the boundary point (`node`, `offset`).
+    fn moved_focus(
+        &self,
+        node: &Node,
+        offset: u32,
+        forward: bool,
+        granularity: Granularity,
+    ) -> Option<(DomRoot<Node>, u32)> {
+        if granularity == Granularity::DocumentBoundary {
+            let root = self.document.GetDocumentElement()?;
+            let root = DomRoot::upcast::<Node>(root);
+            let offset = if forward { root.children_count() } else { 0 };
+            return Some((root, offset));
+        }
+        let mut cursor = TextCursor::new(self.document.upcast(), node, offset, forward)?;
+        match granularity {
+            Granularity::Character => {
+                cursor.step(forward);
+            },
+            // Words and sentences end with their text node.
+            Granularity::Word => cursor.move_to_boundary(forward, next_word_boundary),
+            Granularity::Sentence => cursor.move_to_boundary(forward, next_sentence_boundary),
+            Granularity::DocumentBoundary => unreachable!(),
+        }
+        Some((cursor.node, cursor.offset as u32))
+    }
+
+    /// Selects the word around the boundary point (`node`, `offset`) of a
+    /// text node, or the whitespace or punctuation there, as double-clicking
+    /// it does.
+    pub fn select_word_at(&self, node: &Node, offset: u32) {
+        let data = match node.downcast::<CharacterData>() {
+            Some(text) => text.data(),
+            None => return,
+        };
+        let word = word_at(&data, utf16_offset_to_byte_offset(&data, offset));
+        let start = data[..word.start].encode_utf16().count() as u32;
+        let end = data[..word.end].encode_utf16().count() as u32;
+        let _ = self.SetBaseAndExtent(node, start, node, end);
+    }
+}
+
+/// The byte offset in `data` of the character at `offset` in UTF-16 code units.
+fn utf16_offset_to_byte_offset(data: &str, offset: u32) -> usize {
+    let mut units = 0;
+    for (index, c) in data.char_indices() {
+        if units >= offset {
+            return index;
+        }
+        units += c.len_utf16() as u32;
+    }
+    data.len()
+}
+
+/// Whether `node` is a text node whose text is rendered, which is where the
+/// focus of the selection can be moved to.
+fn is_rendered_text(node: &Node) -> bool {
+    let text = match node.downcast::<CharacterData>() {
+        Some(text) if node.is::<Text>() => text,
+        _ => return false,
+    };
+    if text.data().trim().is_empty() {
+        return false;
+    }
+    node.GetParentElement()
+        .map_or(false, |parent| parent.has_css_layout_box())
+}
+
+/// A position in the rendered text of a document, which moves by characters
+/// within text nodes and across them.
+struct TextCursor {
+    root: DomRoot<Node>,
+    node: DomRoot<Node>,
+    units: Vec<u16>,
+    offset: usize,
+}
+
+impl TextCursor {
+    /// A cursor at the boundary point (`node`, `offset`), or in the closest
+    /// rendered text node in the direction of `forward` if it isn't in one.
+    fn new(root: &Node, node: &Node, offset: u32, forward: bool) -> Option<TextCursor> {
+        let (node, offset) = if node.is::<Text>() {
+            (DomRoot::from_ref(node), offset as usize)
+        } else {
+            let text = if forward {
+                match node.children().nth(offset as usize) {
+                    Some(child) => iter::once(child.clone()).chain(child.following_nodes(root)),
+                    None => {
+                        let last = node
+                            .descending_last_children()
+                            .last()
+                            .unwrap_or_else(|| DomRoot::from_ref(node));
+                        iter::once(last.clone()).chain(last.following_nodes(root))
+                    },
+                }
+                .find(|candidate| is_rendered_text(candidate))?
+            } else {
+                let before = match offset
+                    .checked_sub(1)
+                    .and_then(|index| node.children().nth(index as usize))
+                {
+                    Some(child) => child.descending_last_children().last().unwrap_or(child),
+                    None => DomRoot::from_ref(node),
+                };
+                iter::once(before.clone())
+                    .chain(before.preceding_nodes(root))
+                    .find(|candidate| is_rendered_text(candidate))?
+            };
+            let offset = if forward { 0 } else { text.len() as usize };
+            (text, offset)
+        };
+        let units = text_units(&node);
+        Some(TextCursor {
+            root: DomRoot::from_ref(root),
+            node,
+            offset: offset.min(units.len()),
+            units,
+        })
+    }
+
+    /// The rendered text node after or before the one of the cursor.
+    fn adjacent_node(&self, forward: bool) -> Option<DomRoot<Node>> {
+        if forward {
+            self.node
+                .following_nodes(&self.root)
+                .find(|node| is_rendered_text(node))
+        } else {
+            self.node
+                .preceding_nodes(&self.root)
+                .find(|node| is_rendered_text(node))
+        }
+    }
+
+    /// Moves the cursor to the boundary that `boundary` finds in the text of
+    /// its node in the direction of `forward`, going into the adjacent
+    /// rendered text nodes until one has such a boundary.
+    fn move_to_boundary(
+        &mut self,
+        forward: bool,
+        boundary: fn(&str, usize, bool) -> Option<usize>,
+    ) {
+        loop {
+            let text = String::from_utf16_lossy(&self.units);
+            let index = utf16_offset_to_byte_offset(&text, self.offset as u32);
+            if let Some(found) = boundary(&text, index, forward) {
+                self.offset = text[..found].encode_utf16().count();
+                return;
+            }
+            match self.adjacent_node(forward) {
+                Some(node) => {
+                    self.units = text_units(&node);
+                    self.offset = if forward { 0 } else { self.units.len() };
+                    self.node = node;
+                },
+                None => {
+                    self.offset = if forward { self.units.len() } else { 0 };
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Whether the cursor is at the end or start of its text node.
+    fn at_edge(&self, forward: bool) -> bool {
+        if forward {
+            self.offset == self.units.len()
+        } else {
+            self.offset == 0
+        }
+    }
+
+    /// Moves the cursor over one character, into the adjacent rendered text
+    /// node if it is at the edge of its own.
+    fn step(&mut self, forward: bool) {
+        if self.at_edge(forward) {
+            let node = match self.adjacent_node(forward) {
+                Some(node) => node,
+                None => return,
+            };
+            self.units = text_units(&node);
+            self.offset = if forward { 0 } else { self.units.len() };
+            self.node = node;
+        }
+        let (_, length) = character_at(&self.units, self.offset, forward);
+        if forward {
+            self.offset += length;
+        } else {
+            self.offset -= length;
+        }
+    }
+}
+
+pub fn text_units(node: &Node) -> Vec<u16> {
+    node.downcast::<CharacterData>()
+        .map_or(vec![], |text| text.data().encode_utf16().collect())
+}
+
+/// The character after or before `offset` in `units`, and its length in code
+/// units. `units` must not be empty in that direction.
+pub fn character_at(units: &[u16], offset: usize, forward: bool) -> (char, usize) {
+    let units = if forward {
+        &units[offset..units.len().min(offset + 2)]
+    } else {
+        &units[offset.saturating_sub(2)..offset]
+    };
+    let decoded = if forward {
+        std::char::decode_utf16(units.iter().cloned()).next()
+    } else {
+        std::char::decode_utf16(units.iter().cloned()).last()
+    };
+    match decoded {
+        Some(Ok(c)) => (c, c.len_utf16()),
+        _ => (std::char::REPLACEMENT_CHARACTER, 1),
+    }
 }
 
 impl SelectionMethods for Selection {
@@ -233,8 +526,7 @@ impl SelectionMethods for Selection {
     }
 
     // https://w3c.github.io/selection-api/#dom-selection-empty
-    // TODO: When implementing actual selection UI, this may be the correct
-    // method to call as the abandon-selection action
+    // This is the abandon-selection action of the selection UI.
     fn Empty(&self) {
         self.clear_range();
     }
@@ -272,8 +564,7 @@ impl SelectionMethods for Selection {
     }
 
     // https://w3c.github.io/selection-api/#dom-selection-setposition
-    // TODO: When implementing actual selection UI, this may be the correct
-    // method to call as the start-of-selection action, after a
+    // This is the start-of-selection action of the selection UI, after a
     // selectstart event has fired and not been cancelled.
     fn SetPosition(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
         self.Collapse(node, offset)
@@ -298,8 +589,7 @@ impl SelectionMethods for Selection {
     }
 
     // https://w3c.github.io/selection-api/#dom-selection-extend
-    // TODO: When implementing actual selection UI, this may be the correct
-    // method to call as the continue-selection action
+    // This is the continue-selection action of the selection UI.
     fn Extend(&self, node: &Node, offset: u32) -> ErrorResult {
         if !self.is_same_root(node) {
             // Step 1
@@ -361,6 +651,61 @@ impl SelectionMethods for Selection {
         return Ok(());
     }
 
+    // https://w3c.github.io/selection-api/#dom-selection-modify
+    fn Modify(&self, alter: DOMString, direction: DOMString, granularity: DOMString) {
+        // Step 1
+        let extend = match &*alter.to_ascii_lowercase() {
+            "move" => false,
+            "extend" => true,
+            _ => return,
+        };
+        // Step 2
+        // TODO: "left" and "right" should depend on the direction of the text.
+        let forward = match &*direction.to_ascii_lowercase() {
+            "forward" | "right" => true,
+            "backward" | "left" => false,
+            _ => return,
+        };
+        // Step 3
+        // TODO: Support the granularities that depend on line breaks and
+        // paragraphs.
+        let granularity = match &*granularity.to_ascii_lowercase() {
+            "character" => Granularity::Character,
+            "word" => Granularity::Word,
+            "sentence" => Granularity::Sentence,
+            "documentboundary" => Granularity::DocumentBoundary,
+            _ => return,
+        };
+        // Step 4
+        let range = match self.range.get() {
+            Some(range) => range,
+            None => return,
+        };
+
+        // Moving a selection by a character collapses it to the side it moves
+        // towards.
+        if !extend && !range.Collapsed() && granularity == Granularity::Character {
+            let _ = if forward {
+                self.CollapseToEnd()
+            } else {
+                self.CollapseToStart()
+            };
+            return;
+        }
+
+        let focus = self.GetFocusNode().unwrap();
+        let (node, offset) =
+            match self.moved_focus(&focus, self.FocusOffset(), forward, granularity) {
+                Some(position) => position,
+                None => return,
+            };
+        let _ = if extend {
+            self.Extend(&node, offset)
+        } else {
+            self.Collapse(Some(&node), offset)
+        };
+    }
+
     // https://w3c.github.io/selection-api/#dom-selection-setbaseandextent
     fn SetBaseAndExtent(
         &self,
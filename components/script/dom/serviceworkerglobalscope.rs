@@ -9,9 +9,10 @@ use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding::ServiceWorkerGlobalScopeMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::{DomRoot, RootCollection, ThreadLocalStackRoots};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom, RootCollection, ThreadLocalStackRoots};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone;
+use crate::dom::cookiestore::CookieStore;
 use crate::dom::dedicatedworkerglobalscope::AutoWorkerReset;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
@@ -162,6 +163,8 @@ pub struct ServiceWorkerGlobalScope {
     swmanager_sender: IpcSender<ServiceWorkerMsg>,
 
     scope_url: ServoUrl,
+
+    cookie_store: MutNullableDom<CookieStore>,
 }
 
 impl WorkerEventLoopMethods for ServiceWorkerGlobalScope {
@@ -216,6 +219,7 @@ impl ServiceWorkerGlobalScope {
             time_out_port,
             swmanager_sender: swmanager_sender,
             scope_url: scope_url,
+            cookie_store: Default::default(),
         }
     }
 
@@ -371,6 +375,9 @@ impl ServiceWorkerGlobalScope {
                     DevtoolScriptControlMsg::EvaluateJS(_pipe_id, string, sender) => {
                         devtools::handle_evaluate_js(self.upcast(), string, sender)
                     },
+                    DevtoolScriptControlMsg::GetObjectProperties(_pipe_id, uuid, sender) => {
+                        devtools::handle_get_object_properties(self.upcast(), uuid, sender)
+                    },
                     DevtoolScriptControlMsg::WantsLiveNotifications(_pipe_id, bool_val) => {
                         devtools::handle_wants_live_notifications(self.upcast(), bool_val)
                     },
@@ -463,4 +470,10 @@ impl ServiceWorkerGlobalScopeMethods for ServiceWorkerGlobalScope {
 
     // https://w3c.github.io/ServiceWorker/#dom-serviceworkerglobalscope-onmessageerror
     event_handler!(messageerror, GetOnmessageerror, SetOnmessageerror);
+
+    // https://wicg.github.io/cookie-store/#dom-serviceworkerglobalscope-cookiestore
+    fn CookieStore(&self) -> DomRoot<CookieStore> {
+        self.cookie_store
+            .or_init(|| CookieStore::new(self.upcast()))
+    }
 }
@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The privileged API of Servo's own diagnostic pages: about:config,
+//! about:memory and about:crashes.
+
+use crate::dom::bindings::codegen::Bindings::ServoInternalsBinding::{
+    ServoCrashReport, ServoMemoryReport, ServoPref,
+};
+use crate::dom::bindings::codegen::UnionTypes::BooleanOrDoubleOrString;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::rust::HandleObject;
+use profile_traits::ipc as profile_ipc;
+use profile_traits::mem::{ProfilerMsg, Report, ReportsChan};
+use script_traits::{CrashReport, ScriptMsg};
+use servo_config::prefs::{self, PrefValue};
+use servo_url::ServoUrl;
+use std::rc::Rc;
+
+/// The paths of the about: pages that the API is exposed to.
+const INTERNAL_PAGES: [&str; 3] = ["config", "memory", "crashes"];
+
+#[dom_struct]
+pub struct ServoInternals {
+    reflector_: Reflector,
+}
+
+#[allow(non_snake_case)]
+impl ServoInternals {
+    /// Whether the API is exposed to `obj`, which is only the case for the
+    /// top-level documents of Servo's own about: pages.
+    #[allow(unsafe_code)]
+    pub fn is_exposed(_: JSContext, obj: HandleObject) -> bool {
+        let global = unsafe { GlobalScope::from_object(obj.get()) };
+        match global.downcast::<Window>() {
+            Some(window) => {
+                window.is_top_level() &&
+                    window.has_document() &&
+                    is_internal_page(&window.get_url())
+            },
+            None => false,
+        }
+    }
+
+    pub fn GetPrefs(_: &Window) -> Vec<ServoPref> {
+        let prefs = prefs::pref_map();
+        let mut entries: Vec<ServoPref> = prefs
+            .iter()
+            .filter_map(|(name, value)| {
+                let is_default = value == prefs.get_default(&name);
+                Some(ServoPref {
+                    value: to_union(value)?,
+                    name: DOMString::from(name),
+                    isDefault: is_default,
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    pub fn SetPref(_: &Window, name: DOMString, value: BooleanOrDoubleOrString) -> Fallible<()> {
+        // Values must have the type of the preference.
+        let value = match (prefs::pref_map().get(&name), value) {
+            (PrefValue::Missing, _) => return Err(Error::NotFound),
+            (PrefValue::Bool(_), BooleanOrDoubleOrString::Boolean(value)) => PrefValue::Bool(value),
+            (PrefValue::Int(_), BooleanOrDoubleOrString::Double(value)) if value.fract() == 0. => {
+                PrefValue::Int(*value as i64)
+            },
+            (PrefValue::Float(_), BooleanOrDoubleOrString::Double(value)) => {
+                PrefValue::Float(*value)
+            },
+            (PrefValue::Str(_), BooleanOrDoubleOrString::String(value)) => {
+                PrefValue::Str(value.into())
+            },
+            _ => {
+                return Err(Error::Type(format!(
+                    "Invalid value for the preference {}",
+                    name
+                )))
+            },
+        };
+        prefs::pref_map()
+            .set(&name, value)
+            .map_err(|_| Error::NotFound)
+    }
+
+    pub fn ResetPref(_: &Window, name: DOMString) -> Fallible<()> {
+        prefs::pref_map()
+            .reset(&name)
+            .map(|_| ())
+            .map_err(|_| Error::NotFound)
+    }
+
+    pub fn GetMemoryReports(window: &Window) -> Rc<Promise> {
+        let global = window.upcast::<GlobalScope>();
+        let promise = Promise::new(global);
+
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let mut trusted_promise = Some(TrustedPromise::new(promise.clone()));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let reports: Vec<Report> = message.to().unwrap();
+                let promise = match trusted_promise.take() {
+                    Some(promise) => promise,
+                    None => return,
+                };
+                let result = task_source.queue_with_canceller(
+                    task!(memory_reports_collected: move || {
+                        let reports: Vec<ServoMemoryReport> = reports
+                            .into_iter()
+                            .map(|report| ServoMemoryReport {
+                                path: report.path.into_iter().map(DOMString::from).collect(),
+                                size: Finite::wrap(report.size as f64),
+                            })
+                            .collect();
+                        promise.root().resolve_native(&reports);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue memory reports task {:?}", err);
+                }
+            }),
+        );
+        global
+            .mem_profiler_chan()
+            .send(ProfilerMsg::CollectReports(ReportsChan(sender)));
+        promise
+    }
+
+    pub fn GetCrashReports(window: &Window) -> Vec<ServoCrashReport> {
+        let global = window.upcast::<GlobalScope>();
+        let (sender, receiver) =
+            profile_ipc::channel::<Vec<CrashReport>>(global.time_profiler_chan().clone())
+                .expect("ipc channel failure");
+        global
+            .script_to_constellation_chan()
+            .send(ScriptMsg::GetCrashReports(sender))
+            .unwrap();
+        receiver
+            .recv()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|report| ServoCrashReport {
+                reason: DOMString::from(report.reason),
+                backtrace: report.backtrace.map(DOMString::from),
+                url: report.url.map(|url| DOMString::from(url.into_string())),
+                time: report.time,
+            })
+            .collect()
+    }
+}
+
+fn is_internal_page(url: &ServoUrl) -> bool {
+    url.scheme() == "about" && INTERNAL_PAGES.contains(&url.path())
+}
+
+fn to_union(value: PrefValue) -> Option<BooleanOrDoubleOrString> {
+    Some(match value {
+        PrefValue::Bool(value) => BooleanOrDoubleOrString::Boolean(value),
+        PrefValue::Int(value) => BooleanOrDoubleOrString::Double(Finite::new(value as f64)?),
+        PrefValue::Float(value) => BooleanOrDoubleOrString::Double(Finite::new(value)?),
+        PrefValue::Str(value) => BooleanOrDoubleOrString::String(DOMString::from(value)),
+        PrefValue::Missing => return None,
+    })
+}
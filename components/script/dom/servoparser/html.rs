@@ -5,8 +5,11 @@
 #![allow(unrooted_must_root)]
 
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
 use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
@@ -18,16 +21,18 @@ use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::node::Node;
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::servoparser::{ParsingAlgorithm, Sink};
+use crate::dom::shadowroot::ShadowRoot;
 use html5ever::buffer_queue::BufferQueue;
 use html5ever::serialize::TraversalScope;
-use html5ever::serialize::TraversalScope::IncludeNode;
-use html5ever::serialize::{AttrRef, Serialize, Serializer};
+use html5ever::serialize::TraversalScope::{ChildrenOnly, IncludeNode};
+use html5ever::serialize::{serialize, AttrRef, Serialize, SerializeOpts, Serializer};
 use html5ever::tokenizer::{Tokenizer as HtmlTokenizer, TokenizerOpts, TokenizerResult};
 use html5ever::tree_builder::{Tracer as HtmlTracer, TreeBuilder, TreeBuilderOpts};
-use html5ever::QualName;
+use html5ever::{LocalName, QualName};
 use js::jsapi::JSTracer;
 use servo_url::ServoUrl;
 use std::io;
+use style::attr::AttrValue;
 
 #[derive(JSTraceable, MallocSizeOf)]
 #[unrooted_must_root_lint::must_root]
@@ -49,6 +54,7 @@ impl Tokenizer {
             current_line: 1,
             script: Default::default(),
             parsing_algorithm: parsing_algorithm,
+            parse_error: None,
         };
 
         let options = TreeBuilderOpts {
@@ -121,15 +127,21 @@ unsafe impl JSTraceable for HtmlTokenizer<TreeBuilder<Dom<Node>, Sink>> {
 
 fn start_element<S: Serializer>(node: &Element, serializer: &mut S) -> io::Result<()> {
     let name = QualName::new(None, node.namespace().clone(), node.local_name().clone());
-    let attrs = node
-        .attrs()
-        .iter()
-        .map(|attr| {
-            let qname = QualName::new(None, attr.namespace().clone(), attr.local_name().clone());
-            let value = attr.value().clone();
-            (qname, value)
-        })
-        .collect::<Vec<_>>();
+    let mut attrs = vec![];
+    // https://html.spec.whatwg.org/multipage/#serialising-html-fragments
+    // The is value of a customized built-in element is serialized when the
+    // element doesn't have an is content attribute.
+    if let Some(is) = node.get_is() {
+        if node.get_attribute(&ns!(), &LocalName::from("is")).is_none() {
+            let qname = QualName::new(None, ns!(), LocalName::from("is"));
+            attrs.push((qname, AttrValue::String(is.to_string())));
+        }
+    }
+    attrs.extend(node.attrs().iter().map(|attr| {
+        let qname = QualName::new(None, attr.namespace().clone(), attr.local_name().clone());
+        let value = attr.value().clone();
+        (qname, value)
+    }));
     let attr_refs = attrs.iter().map(|&(ref qname, ref value)| {
         let ar: AttrRef = (&qname, &**value);
         ar
@@ -143,14 +155,49 @@ fn end_element<S: Serializer>(node: &Element, serializer: &mut S) -> io::Result<
     serializer.end_elem(name)
 }
 
+fn template_qualname() -> QualName {
+    QualName::new(None, ns!(html), local_name!("template"))
+}
+
+/// Serializes a shadow root as a declarative shadow root, that is a template
+/// element with a shadowrootmode attribute.
+fn start_shadow_root<S: Serializer>(
+    shadow_root: &ShadowRoot,
+    serializer: &mut S,
+) -> io::Result<()> {
+    let mode = match shadow_root.Mode() {
+        ShadowRootMode::Open => "open",
+        ShadowRootMode::Closed => "closed",
+    };
+    let mut attrs = vec![(
+        QualName::new(None, ns!(), LocalName::from("shadowrootmode")),
+        mode,
+    )];
+    if shadow_root.Serializable() {
+        attrs.push((
+            QualName::new(None, ns!(), LocalName::from("shadowrootserializable")),
+            "",
+        ));
+    }
+    let attr_refs = attrs.iter().map(|&(ref qname, value)| {
+        let ar: AttrRef = (&qname, value);
+        ar
+    });
+    serializer.start_elem(template_qualname(), attr_refs)
+}
+
 enum SerializationCommand {
     OpenElement(DomRoot<Element>),
     CloseElement(DomRoot<Element>),
+    OpenShadowRoot(DomRoot<ShadowRoot>),
+    CloseShadowRoot(DomRoot<ShadowRoot>),
     SerializeNonelement(DomRoot<Node>),
 }
 
-struct SerializationIterator {
+struct SerializationIterator<'a> {
     stack: Vec<SerializationCommand>,
+    serializable_shadow_roots: bool,
+    shadow_roots: &'a [DomRoot<ShadowRoot>],
 }
 
 fn rev_children_iter(n: &Node) -> impl Iterator<Item = DomRoot<Node>> {
@@ -164,19 +211,47 @@ fn rev_children_iter(n: &Node) -> impl Iterator<Item = DomRoot<Node>> {
     }
 }
 
-impl SerializationIterator {
-    fn new(node: &Node, skip_first: bool) -> SerializationIterator {
-        let mut ret = SerializationIterator { stack: vec![] };
+impl<'a> SerializationIterator<'a> {
+    fn new(
+        node: &Node,
+        skip_first: bool,
+        serializable_shadow_roots: bool,
+        shadow_roots: &'a [DomRoot<ShadowRoot>],
+    ) -> SerializationIterator<'a> {
+        let mut ret = SerializationIterator {
+            stack: vec![],
+            serializable_shadow_roots,
+            shadow_roots,
+        };
         if skip_first || node.is::<DocumentFragment>() || node.is::<Document>() {
-            for c in rev_children_iter(node) {
-                ret.push_node(&*c);
-            }
+            ret.push_children(node);
         } else {
             ret.push_node(node);
         }
         ret
     }
 
+    /// Pushes the children of `n`, preceded by its shadow root when it has
+    /// to be serialized.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/#serialising-html-fragments>
+    fn push_children(&mut self, n: &Node) {
+        for c in rev_children_iter(n) {
+            self.push_node(&*c);
+        }
+        let shadow_root = n
+            .downcast::<Element>()
+            .and_then(|e| e.shadow_root())
+            .filter(|shadow_root| {
+                (self.serializable_shadow_roots && shadow_root.Serializable()) ||
+                    self.shadow_roots.iter().any(|s| s == shadow_root)
+            });
+        if let Some(shadow_root) = shadow_root {
+            self.stack
+                .push(SerializationCommand::OpenShadowRoot(shadow_root));
+        }
+    }
+
     fn push_node(&mut self, n: &Node) {
         match n.downcast::<Element>() {
             Some(e) => self
@@ -189,33 +264,91 @@ impl SerializationIterator {
     }
 }
 
-impl Iterator for SerializationIterator {
+impl<'a> Iterator for SerializationIterator<'a> {
     type Item = SerializationCommand;
 
     fn next(&mut self) -> Option<SerializationCommand> {
         let res = self.stack.pop();
 
-        if let Some(SerializationCommand::OpenElement(ref e)) = res {
-            self.stack
-                .push(SerializationCommand::CloseElement(e.clone()));
-            for c in rev_children_iter(&*e.upcast::<Node>()) {
-                self.push_node(&c);
-            }
+        match res {
+            Some(SerializationCommand::OpenElement(ref e)) => {
+                self.stack
+                    .push(SerializationCommand::CloseElement(e.clone()));
+                self.push_children(e.upcast());
+            },
+            Some(SerializationCommand::OpenShadowRoot(ref s)) => {
+                self.stack
+                    .push(SerializationCommand::CloseShadowRoot(s.clone()));
+                for c in s.upcast::<Node>().rev_children() {
+                    self.push_node(&c);
+                }
+            },
+            _ => {},
         }
 
         res
     }
 }
 
+/// A node to serialize, along with the shadow roots to serialize within it.
+struct HtmlSerialization<'a> {
+    node: &'a Node,
+    serializable_shadow_roots: bool,
+    shadow_roots: &'a [DomRoot<ShadowRoot>],
+}
+
 impl<'a> Serialize for &'a Node {
     fn serialize<S: Serializer>(
         &self,
         serializer: &mut S,
         traversal_scope: TraversalScope,
     ) -> io::Result<()> {
-        let node = *self;
+        HtmlSerialization {
+            node: *self,
+            serializable_shadow_roots: false,
+            shadow_roots: &[],
+        }
+        .serialize(serializer, traversal_scope)
+    }
+}
 
-        let iter = SerializationIterator::new(node, traversal_scope != IncludeNode);
+/// Serializes the children of `node`, including the shadow roots that are
+/// serializable when `serializable_shadow_roots` is set, and the ones in
+/// `shadow_roots`.
+///
+/// <https://html.spec.whatwg.org/multipage/#html-fragment-serialisation-algorithm>
+pub fn serialize_html_fragment(
+    node: &Node,
+    context: Option<QualName>,
+    serializable_shadow_roots: bool,
+    shadow_roots: &[DomRoot<ShadowRoot>],
+) -> DOMString {
+    let mut writer = vec![];
+    let node = HtmlSerialization {
+        node,
+        serializable_shadow_roots,
+        shadow_roots,
+    };
+    let opts = SerializeOpts {
+        traversal_scope: ChildrenOnly(context),
+        ..Default::default()
+    };
+    serialize(&mut writer, &node, opts).expect("Cannot serialize node");
+    DOMString::from(String::from_utf8(writer).unwrap())
+}
+
+impl<'a> Serialize for HtmlSerialization<'a> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: &mut S,
+        traversal_scope: TraversalScope,
+    ) -> io::Result<()> {
+        let iter = SerializationIterator::new(
+            self.node,
+            traversal_scope != IncludeNode,
+            self.serializable_shadow_roots,
+            self.shadow_roots,
+        );
 
         for cmd in iter {
             match cmd {
@@ -227,6 +360,14 @@ impl<'a> Serialize for &'a Node {
                     end_element(&&n, serializer)?;
                 },
 
+                SerializationCommand::OpenShadowRoot(s) => {
+                    start_shadow_root(&s, serializer)?;
+                },
+
+                SerializationCommand::CloseShadowRoot(_) => {
+                    serializer.end_elem(template_qualname())?;
+                },
+
                 SerializationCommand::SerializeNonelement(n) => match n.type_id() {
                     NodeTypeId::DocumentType => {
                         let doctype = n.downcast::<DocumentType>().unwrap();
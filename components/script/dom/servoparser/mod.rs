@@ -11,6 +11,7 @@ use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageE
 use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::ServoParserBinding;
+use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootMode;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -32,9 +33,11 @@ use crate::dom::node::{Node, ShadowIncluding};
 use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
 use crate::dom::processinginstruction::ProcessingInstruction;
+use crate::dom::shadowroot::IsUserAgentWidget;
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::vtable_for;
 use crate::network_listener::PreInvoke;
+use crate::origin_trials::{self, OriginTrials};
 use crate::script_thread::ScriptThread;
 use content_security_policy::{self as csp, CspList};
 use dom_struct::dom_struct;
@@ -45,6 +48,7 @@ use html5ever::tendril::fmt::UTF8;
 use html5ever::tendril::{ByteTendril, StrTendril, TendrilSink};
 use html5ever::tree_builder::{ElementFlags, NextParserState, NodeOrText, QuirksMode, TreeSink};
 use html5ever::{Attribute, ExpandedName, LocalName, QualName};
+use http::header::HeaderMap;
 use hyper_serde::Serde;
 use mime::{self, Mime};
 use msg::constellation_msg::PipelineId;
@@ -67,6 +71,8 @@ mod html;
 mod prefetch;
 mod xml;
 
+pub use self::html::serialize_html_fragment;
+
 #[dom_struct]
 /// The parser maintains two input streams: one for input from script through
 /// document.write(), and one for input from network.
@@ -123,6 +129,14 @@ pub struct ElementAttribute {
     value: DOMString,
 }
 
+/// A well-formedness error reported while parsing an XML document.
+#[derive(Clone, Debug, JSTraceable, MallocSizeOf)]
+pub struct XmlParseError {
+    /// The line the parser was at when it hit the error.
+    pub line: u64,
+    pub message: String,
+}
+
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum ParsingAlgorithm {
     Normal,
@@ -256,6 +270,37 @@ impl ServoParser {
         parser.parse_string_chunk(String::from(input));
     }
 
+    /// Parses `input` as a complete XML document, as DOMParser does, and
+    /// returns the first well-formedness error the parser reported. The
+    /// document keeps whatever the parser built before and after the error.
+    pub fn parse_xml_document_from_string(
+        document: &Document,
+        input: DOMString,
+        url: ServoUrl,
+    ) -> Result<(), XmlParseError> {
+        let parser = ServoParser::new(
+            document,
+            Tokenizer::Xml(self::xml::Tokenizer::new(document, url)),
+            LastChunkState::NotReceived,
+            ParserKind::Normal,
+        );
+        parser.parse_string_chunk(String::from(input));
+
+        // There is no more input, so let the tokenizer process the end of
+        // the file, which is where unclosed elements are reported.
+        let mut tokenizer = parser.tokenizer.borrow_mut();
+        tokenizer.end();
+        document.set_current_parser(None);
+        let error = match *tokenizer {
+            Tokenizer::Xml(ref mut tokenizer) => tokenizer.take_parse_error(),
+            _ => unreachable!(),
+        };
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
     pub fn script_nesting_level(&self) -> usize {
         self.script_nesting_level.get()
     }
@@ -338,6 +383,10 @@ impl ServoParser {
 
         self.tokenize(|tokenizer| tokenizer.feed(&mut input));
 
+        if self.aborted.get() {
+            return;
+        }
+
         if self.suspended.get() {
             // Parser got suspended, insert remaining input at end of
             // script input, following anything written by scripts executed
@@ -349,6 +398,12 @@ impl ServoParser {
         }
 
         assert!(input.is_empty());
+
+        // A script executed while tokenizing the input may have closed the
+        // parser, whose explicit EOF comes after everything written before.
+        if self.last_chunk_received.get() && self.script_nesting_level.get() == 0 {
+            self.parse_sync();
+        }
     }
 
     // Steps 4-6 of https://html.spec.whatwg.org/multipage/#dom-document-close
@@ -363,6 +418,13 @@ impl ServoParser {
             return;
         }
 
+        if self.script_nesting_level.get() > 0 {
+            // A script executed by this parser closed it, the input written
+            // before this call is still being tokenized further up the
+            // stack, which runs the parser to the end afterwards.
+            return;
+        }
+
         // Step 6.
         self.parse_sync();
     }
@@ -517,14 +579,20 @@ impl ServoParser {
         }
         self.tokenize(|tokenizer| tokenizer.feed(&mut *self.network_input.borrow_mut()));
 
-        if self.suspended.get() {
+        if self.suspended.get() || self.aborted.get() {
             return;
         }
 
         assert!(self.network_input.borrow().is_empty());
 
         if self.last_chunk_received.get() {
-            self.finish();
+            if self.network_decoder.borrow().is_some() {
+                // A script closed the parser while it was tokenizing, so the
+                // decoder has yet to be flushed.
+                self.do_parse_sync();
+            } else {
+                self.finish();
+            }
         }
     }
 
@@ -759,25 +827,50 @@ impl FetchResponseListener for ParserContext {
         // TODO: Implement step 1 (local scheme special case)
         let csp_list = metadata.as_ref().and_then(|m| {
             let h = m.headers.as_ref()?;
-            let mut csp = h.get_all("content-security-policy").iter();
-            // This silently ignores the CSP if it contains invalid Unicode.
-            // We should probably report an error somewhere.
-            let c = csp.next().and_then(|c| c.to_str().ok())?;
-            let mut csp_list = CspList::parse(
-                c,
-                csp::PolicySource::Header,
-                csp::PolicyDisposition::Enforce,
-            );
-            for c in csp {
-                let c = c.to_str().ok()?;
-                csp_list.append(CspList::parse(
-                    c,
-                    csp::PolicySource::Header,
-                    csp::PolicyDisposition::Enforce,
-                ));
+            let mut csp_list: Option<CspList> = None;
+            let policies = h
+                .get_all("content-security-policy")
+                .iter()
+                .map(|c| (c, csp::PolicyDisposition::Enforce))
+                .chain(
+                    h.get_all("content-security-policy-report-only")
+                        .iter()
+                        .map(|c| (c, csp::PolicyDisposition::Report)),
+                );
+            for (c, disposition) in policies {
+                // This silently ignores a CSP if it contains invalid Unicode.
+                // We should probably report an error somewhere.
+                let c = match c.to_str() {
+                    Ok(c) => c,
+                    Err(_) => continue,
+                };
+                let parsed = CspList::parse(c, csp::PolicySource::Header, disposition);
+                match csp_list {
+                    Some(ref mut csp_list) => csp_list.append(parsed),
+                    None => csp_list = Some(parsed),
+                }
             }
-            Some(csp_list)
+            csp_list
         });
+        let reporting_endpoints = metadata
+            .as_ref()
+            .map(|m| m.reporting_endpoints.clone())
+            .unwrap_or_default();
+        let origin_trial_tokens = metadata
+            .as_ref()
+            .and_then(|m| m.headers.as_ref())
+            .map(|h| origin_trials::tokens_from_headers(h))
+            .unwrap_or_default();
+        let (opener_policy_is_same_origin, embedder_policy_requires_corp) = metadata
+            .as_ref()
+            .and_then(|m| m.headers.as_ref())
+            .map(|h| {
+                (
+                    header_policy_is(h, "cross-origin-opener-policy", "same-origin"),
+                    header_policy_is(h, "cross-origin-embedder-policy", "require-corp"),
+                )
+            })
+            .unwrap_or((false, false));
 
         let parser = match ScriptThread::page_headers_available(&self.id, metadata) {
             Some(parser) => parser,
@@ -788,6 +881,27 @@ impl FetchResponseListener for ParserContext {
         }
 
         parser.document.set_csp_list(csp_list);
+        let global = parser.document.window().upcast::<GlobalScope>();
+        global.set_reporting_endpoints(reporting_endpoints);
+        global.set_origin_trials(OriginTrials::new(
+            parser.document.origin().immutable(),
+            &origin_trial_tokens,
+        ));
+        // A nested document is only cross-origin isolated along with its parent, and the
+        // opener policy only matters for top-level documents.
+        // https://html.spec.whatwg.org/multipage/#cross-origin-isolation-mode
+        let window = parser.document.window();
+        let cross_origin_isolated = embedder_policy_requires_corp &&
+            match window.window_proxy().parent() {
+                Some(parent) => parent.document().map_or(false, |document| {
+                    document
+                        .window()
+                        .upcast::<GlobalScope>()
+                        .is_cross_origin_isolated()
+                }),
+                None => opener_policy_is_same_origin,
+            };
+        global.set_cross_origin_isolated(cross_origin_isolated);
 
         self.parser = Some(Trusted::new(&*parser));
 
@@ -885,8 +999,8 @@ impl FetchResponseListener for ParserContext {
         }
 
         match status {
-            // are we throwing this away or can we use it?
-            Ok(_) => (),
+            // The timing of the whole fetch, redirects included, replaces the partial one.
+            Ok(resource_timing) => self.resource_timing = resource_timing,
             // TODO(Savago): we should send a notification to callers #5463.
             Err(err) => debug!("Failed to load page URL {}, error: {:?}", self.url, err),
         }
@@ -903,8 +1017,11 @@ impl FetchResponseListener for ParserContext {
         //TODO only update if this is the current document resource
         if let Some(pushed_index) = self.pushed_entry_index {
             let document = &parser.document;
-            let performance_entry =
-                PerformanceNavigationTiming::new(&document.global(), 0, 0, &document);
+            let performance_entry = PerformanceNavigationTiming::new(
+                &document.global(),
+                &document,
+                &self.resource_timing,
+            );
             document
                 .global()
                 .performance()
@@ -932,9 +1049,8 @@ impl FetchResponseListener for ParserContext {
 
         let document = &parser.document;
 
-        //TODO nav_start and nav_start_precise
         let performance_entry =
-            PerformanceNavigationTiming::new(&document.global(), 0, 0, &document);
+            PerformanceNavigationTiming::new(&document.global(), &document, &self.resource_timing);
         self.pushed_entry_index = document
             .global()
             .performance()
@@ -950,6 +1066,15 @@ pub struct FragmentContext<'a> {
 }
 
 #[allow(unrooted_must_root)]
+/// Whether the policy of the header `name` is `value`, ignoring its parameters.
+fn header_policy_is(headers: &HeaderMap, name: &str, value: &str) -> bool {
+    headers
+        .get(name)
+        .and_then(|policy| policy.to_str().ok())
+        .and_then(|policy| policy.split(';').next())
+        .map_or(false, |policy| policy.trim() == value)
+}
+
 fn insert(
     parent: &Node,
     reference_child: Option<&Node>,
@@ -957,6 +1082,8 @@ fn insert(
     parsing_algorithm: ParsingAlgorithm,
 ) {
     match child {
+        NodeOrText::AppendNode(ref n)
+            if attach_declarative_shadow_root(parent, n, parsing_algorithm) => {},
         NodeOrText::AppendNode(n) => {
             // https://html.spec.whatwg.org/multipage/#insert-a-foreign-element
             // applies if this is an element; if not, it may be
@@ -988,6 +1115,60 @@ fn insert(
     }
 }
 
+/// Attaches a shadow root to `host` when `template` is a declarative shadow
+/// root, in which case the template isn't inserted and its contents are
+/// parsed into the shadow root instead. Returns whether it did.
+///
+/// <https://html.spec.whatwg.org/multipage/#parsing-main-inhead:template-start-tag>
+fn attach_declarative_shadow_root(
+    host: &Node,
+    template: &Node,
+    parsing_algorithm: ParsingAlgorithm,
+) -> bool {
+    if !pref!(dom.shadowdom.enabled) || parsing_algorithm == ParsingAlgorithm::Fragment {
+        return false;
+    }
+    let template = match template.downcast::<HTMLTemplateElement>() {
+        Some(template) => template,
+        None => return false,
+    };
+    let document = host.owner_doc();
+    if !document.is_html_document() || !document.allow_declarative_shadow_roots() {
+        return false;
+    }
+    // The topmost element of the stack of open elements is never a
+    // declarative shadow host.
+    if host
+        .GetParentNode()
+        .map_or(true, |parent| parent.is::<Document>())
+    {
+        return false;
+    }
+    let host = match host.downcast::<Element>() {
+        Some(host) => host,
+        None => return false,
+    };
+
+    let element = template.upcast::<Element>();
+    let mode = element
+        .get_attribute(&ns!(), &LocalName::from("shadowrootmode"))
+        .map(|attr| attr.value().to_ascii_lowercase());
+    let mode = match mode.as_ref().map(|mode| &**mode) {
+        Some("open") => ShadowRootMode::Open,
+        Some("closed") => ShadowRootMode::Closed,
+        _ => return false,
+    };
+    let serializable = element.has_attribute(&LocalName::from("shadowrootserializable"));
+
+    match host.attach_shadow(IsUserAgentWidget::No, mode, serializable) {
+        Ok(shadow_root) => {
+            template.set_contents(shadow_root.upcast());
+            true
+        },
+        Err(_) => false,
+    }
+}
+
 #[derive(JSTraceable, MallocSizeOf)]
 #[unrooted_must_root_lint::must_root]
 pub struct Sink {
@@ -996,6 +1177,9 @@ pub struct Sink {
     current_line: u64,
     script: MutNullableDom<HTMLScriptElement>,
     parsing_algorithm: ParsingAlgorithm,
+    /// The first parse error reported by the tree builder, if any. Only
+    /// XML parsing looks at this, to detect well-formedness errors.
+    parse_error: Option<XmlParseError>,
 }
 
 impl Sink {
@@ -1127,6 +1311,12 @@ impl TreeSink for Sink {
 
     fn parse_error(&mut self, msg: Cow<'static, str>) {
         debug!("Parse error: {}", msg);
+        if self.parse_error.is_none() {
+            self.parse_error = Some(XmlParseError {
+                line: self.current_line,
+                message: msg.into_owned(),
+            });
+        }
     }
 
     fn set_quirks_mode(&mut self, mode: QuirksMode) {
@@ -9,7 +9,7 @@ use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::document::Document;
 use crate::dom::htmlscriptelement::HTMLScriptElement;
 use crate::dom::node::Node;
-use crate::dom::servoparser::{ParsingAlgorithm, Sink};
+use crate::dom::servoparser::{ParsingAlgorithm, Sink, XmlParseError};
 use js::jsapi::JSTracer;
 use servo_url::ServoUrl;
 use xml5ever::buffer_queue::BufferQueue;
@@ -31,6 +31,7 @@ impl Tokenizer {
             current_line: 1,
             script: Default::default(),
             parsing_algorithm: ParsingAlgorithm::Normal,
+            parse_error: None,
         };
 
         let tb = XmlTreeBuilder::new(sink, Default::default());
@@ -54,6 +55,10 @@ impl Tokenizer {
     pub fn url(&self) -> &ServoUrl {
         &self.inner.sink.sink.base_url
     }
+
+    pub fn take_parse_error(&mut self) -> Option<XmlParseError> {
+        self.inner.sink.sink.parse_error.take()
+    }
 }
 
 #[allow(unsafe_code)]
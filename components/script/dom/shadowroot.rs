@@ -3,22 +3,29 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::GetHTMLOptions;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRootBinding::ShadowRootMethods;
 use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::{self, ShadowRootMode};
+use crate::dom::bindings::error::ErrorResult;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::document::Document;
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
 use crate::dom::element::Element;
 use crate::dom::node::{Node, NodeDamage, NodeFlags, ShadowIncluding, UnbindContext};
+use crate::dom::servoparser::serialize_html_fragment;
 use crate::dom::stylesheetlist::{StyleSheetList, StyleSheetListOwner};
 use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
 use crate::stylesheet_set::StylesheetSetRef;
 use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use js::rust::HandleValue;
 use selectors::context::QuirksMode;
 use servo_arc::Arc;
 use servo_atoms::Atom;
@@ -46,11 +53,20 @@ pub struct ShadowRoot {
     author_styles: DomRefCell<AuthorStyles<StyleSheetInDocument>>,
     stylesheet_list: MutNullableDom<StyleSheetList>,
     window: Dom<Window>,
+    /// <https://dom.spec.whatwg.org/#shadowroot-mode>
+    mode: ShadowRootMode,
+    /// <https://dom.spec.whatwg.org/#shadowroot-serializable>
+    serializable: bool,
 }
 
 impl ShadowRoot {
     #[allow(unrooted_must_root)]
-    fn new_inherited(host: &Element, document: &Document) -> ShadowRoot {
+    fn new_inherited(
+        host: &Element,
+        document: &Document,
+        mode: ShadowRootMode,
+        serializable: bool,
+    ) -> ShadowRoot {
         let document_fragment = DocumentFragment::new_inherited(document);
         let node = document_fragment.upcast::<Node>();
         node.set_flag(NodeFlags::IS_IN_SHADOW_TREE, true);
@@ -66,12 +82,24 @@ impl ShadowRoot {
             author_styles: DomRefCell::new(AuthorStyles::new()),
             stylesheet_list: MutNullableDom::new(None),
             window: Dom::from_ref(document.window()),
+            mode,
+            serializable,
         }
     }
 
-    pub fn new(host: &Element, document: &Document) -> DomRoot<ShadowRoot> {
+    pub fn new(
+        host: &Element,
+        document: &Document,
+        mode: ShadowRootMode,
+        serializable: bool,
+    ) -> DomRoot<ShadowRoot> {
         reflect_dom_object(
-            Box::new(ShadowRoot::new_inherited(host, document)),
+            Box::new(ShadowRoot::new_inherited(
+                host,
+                document,
+                mode,
+                serializable,
+            )),
             document.window(),
             ShadowRootBinding::Wrap,
         )
@@ -90,8 +118,15 @@ impl ShadowRoot {
         None
     }
 
+    /// The number of sheets in `styleSheets`, which doesn't include the
+    /// adopted ones.
     pub fn stylesheet_count(&self) -> usize {
-        self.author_styles.borrow().stylesheets.len()
+        self.author_styles
+            .borrow()
+            .stylesheets
+            .iter()
+            .filter(|sheet| sheet.owner.is_some())
+            .count()
     }
 
     pub fn stylesheet_at(&self, index: usize) -> Option<DomRoot<CSSStyleSheet>> {
@@ -99,7 +134,8 @@ impl ShadowRoot {
 
         stylesheets
             .get(index)
-            .and_then(|s| s.owner.upcast::<Node>().get_cssom_stylesheet())
+            .and_then(|s| s.owner.as_ref())
+            .and_then(|owner| owner.upcast::<Node>().get_cssom_stylesheet())
     }
 
     /// Add a stylesheet owned by `owner` to the list of shadow root sheets, in the
@@ -109,14 +145,13 @@ impl ShadowRoot {
         let stylesheets = &mut self.author_styles.borrow_mut().stylesheets;
         let insertion_point = stylesheets
             .iter()
-            .find(|sheet_in_shadow| {
-                owner
-                    .upcast::<Node>()
-                    .is_before(sheet_in_shadow.owner.upcast())
+            .find(|sheet_in_shadow| match sheet_in_shadow.owner {
+                Some(ref other) => owner.upcast::<Node>().is_before(other.upcast()),
+                None => true,
             })
             .cloned();
         DocumentOrShadowRoot::add_stylesheet(
-            owner,
+            Some(owner),
             StylesheetSetRef::Author(stylesheets),
             sheet,
             insertion_point,
@@ -128,12 +163,32 @@ impl ShadowRoot {
     #[allow(unrooted_must_root)] // Owner needs to be rooted already necessarily.
     pub fn remove_stylesheet(&self, owner: &Element, s: &Arc<Stylesheet>) {
         DocumentOrShadowRoot::remove_stylesheet(
-            owner,
+            Some(owner),
             s,
             StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
         )
     }
 
+    /// Add a stylesheet adopted by the shadow root after all the other sheets.
+    pub fn add_adopted_stylesheet(&self, sheet: &CSSStyleSheet) {
+        DocumentOrShadowRoot::add_stylesheet(
+            None,
+            StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
+            sheet.style_stylesheet_arc().clone(),
+            None,
+            self.document.style_shared_lock(),
+        );
+    }
+
+    /// Remove a stylesheet adopted by the shadow root.
+    pub fn remove_adopted_stylesheet(&self, sheet: &CSSStyleSheet) {
+        DocumentOrShadowRoot::remove_stylesheet(
+            None,
+            sheet.style_stylesheet_arc(),
+            StylesheetSetRef::Author(&mut self.author_styles.borrow_mut().stylesheets),
+        )
+    }
+
     pub fn invalidate_stylesheets(&self) {
         self.document.invalidate_shadow_roots_stylesheets();
         self.author_styles.borrow_mut().stylesheets.force_dirty();
@@ -219,7 +274,12 @@ impl ShadowRootMethods for ShadowRoot {
 
     /// https://dom.spec.whatwg.org/#dom-shadowroot-mode
     fn Mode(&self) -> ShadowRootMode {
-        ShadowRootMode::Closed
+        self.mode
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-shadowroot-serializable
+    fn Serializable(&self) -> bool {
+        self.serializable
     }
 
     /// https://dom.spec.whatwg.org/#dom-shadowroot-host
@@ -228,6 +288,16 @@ impl ShadowRootMethods for ShadowRoot {
         host.expect("Trying to get host from a detached shadow root")
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-shadowroot-gethtml
+    fn GetHTML(&self, options: &GetHTMLOptions) -> DOMString {
+        serialize_html_fragment(
+            self.upcast(),
+            None,
+            options.serializableShadowRoots,
+            &options.shadowRoots,
+        )
+    }
+
     // https://drafts.csswg.org/cssom/#dom-document-stylesheets
     fn StyleSheets(&self) -> DomRoot<StyleSheetList> {
         self.stylesheet_list.or_init(|| {
@@ -237,6 +307,21 @@ impl ShadowRootMethods for ShadowRoot {
             )
         })
     }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    fn AdoptedStyleSheets(&self, cx: JSContext) -> JSVal {
+        self.document_or_shadow_root.get_adopted_stylesheets(cx)
+    }
+
+    // https://drafts.csswg.org/cssom/#dom-documentorshadowroot-adoptedstylesheets
+    #[allow(unrooted_must_root)]
+    fn SetAdoptedStyleSheets(&self, cx: JSContext, value: HandleValue) -> ErrorResult {
+        self.document_or_shadow_root.set_adopted_stylesheets(
+            cx,
+            value,
+            StyleSheetListOwner::ShadowRoot(Dom::from_ref(self)),
+        )
+    }
 }
 
 #[allow(unsafe_code)]
@@ -0,0 +1,358 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisBinding::{
+    self, SpeechSynthesisMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::{
+    SpeechSynthesisErrorCode, SpeechSynthesisErrorEventInit,
+};
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisEventBinding::SpeechSynthesisEventInit;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::speechsynthesiserrorevent::SpeechSynthesisErrorEvent;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::speechsynthesisvoice::SpeechSynthesisVoice;
+use crate::task_source::{TaskSource, TaskSourceName};
+use dom_struct::dom_struct;
+use embedder_traits::{EmbedderMsg, SpeechError, SpeechEvent, SpeechEventKind, SpeechVoice};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::mem;
+
+/// The speech synthesis of a window, which speaks its utterances one after
+/// the other with the speech synthesizer of the embedder.
+#[dom_struct]
+pub struct SpeechSynthesis {
+    eventtarget: EventTarget,
+    /// The utterances to speak, starting with the one being spoken if any.
+    queue: DomRefCell<VecDeque<Dom<SpeechSynthesisUtterance>>>,
+    /// Whether the first utterance of the queue was handed to the embedder.
+    speaking: Cell<bool>,
+    paused: Cell<bool>,
+    /// Identifies the utterance that is being spoken, so that the events of
+    /// the canceled ones are ignored.
+    speech_id: Cell<u32>,
+    voices: DomRefCell<Vec<Dom<SpeechSynthesisVoice>>>,
+}
+
+impl SpeechSynthesis {
+    fn new_inherited() -> SpeechSynthesis {
+        SpeechSynthesis {
+            eventtarget: EventTarget::new_inherited(),
+            queue: DomRefCell::new(VecDeque::new()),
+            speaking: Cell::new(false),
+            paused: Cell::new(false),
+            speech_id: Cell::new(0),
+            voices: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<SpeechSynthesis> {
+        let synthesis = reflect_dom_object(
+            Box::new(SpeechSynthesis::new_inherited()),
+            global,
+            SpeechSynthesisBinding::Wrap,
+        );
+        synthesis.request_voices();
+        synthesis
+    }
+
+    /// Asks the embedder for its voices, which `getVoices()` returns once
+    /// `voiceschanged` was fired.
+    fn request_voices(&self) {
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let voices: Vec<SpeechVoice> = message.to().unwrap();
+                let this = this.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(speech_voices_received: move || {
+                        this.root().set_voices(voices);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue speech voices task {:?}", err);
+                }
+            }),
+        );
+        global.send_to_embedder(EmbedderMsg::GetSpeechVoices(sender));
+    }
+
+    fn set_voices(&self, voices: Vec<SpeechVoice>) {
+        if voices.is_empty() {
+            return;
+        }
+        let global = self.global();
+        *self.voices.borrow_mut() = voices
+            .into_iter()
+            .map(|voice| Dom::from_ref(&*SpeechSynthesisVoice::new(&global, voice)))
+            .collect();
+        self.upcast::<EventTarget>()
+            .fire_event(atom!("voiceschanged"));
+    }
+
+    /// Hands the first utterance of the queue to the embedder, unless one is
+    /// already being spoken or the synthesis is paused.
+    fn speak_next(&self) {
+        if self.paused.get() || self.speaking.get() {
+            return;
+        }
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+        self.speaking.set(true);
+        let id = self.speech_id.get() + 1;
+        self.speech_id.set(id);
+
+        let global = self.global();
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: SpeechEvent = message.to().unwrap();
+                let this = this.clone();
+                let result = task_source.queue_with_canceller(
+                    task!(handle_speech_event: move || {
+                        this.root().handle_speech_event(id, event);
+                    }),
+                    &canceller,
+                );
+                if let Err(err) = result {
+                    warn!("Could not queue speech event task {:?}", err);
+                }
+            }),
+        );
+
+        // Utterances without a language are spoken in the one of the
+        // document.
+        let document_lang = global
+            .as_window()
+            .Document()
+            .GetDocumentElement()
+            .map_or(String::new(), |element| element.get_lang());
+        global.send_to_embedder(EmbedderMsg::Speak(
+            global.pipeline_id(),
+            utterance.to_speech_utterance(document_lang),
+            sender,
+        ));
+    }
+
+    fn handle_speech_event(&self, id: u32, event: SpeechEvent) {
+        if id != self.speech_id.get() || !self.speaking.get() {
+            return;
+        }
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+        let done = match event.kind {
+            SpeechEventKind::End | SpeechEventKind::Error(_) => true,
+            _ => false,
+        };
+        if done {
+            self.queue.borrow_mut().pop_front();
+            self.speaking.set(false);
+        }
+
+        let mut init = event_init(utterance.clone());
+        init.charIndex = event.char_index;
+        init.charLength = event.char_length;
+        init.elapsedTime = Finite::new(event.elapsed_time).unwrap_or(init.elapsedTime);
+        let type_ = match event.kind {
+            SpeechEventKind::Start => atom!("start"),
+            SpeechEventKind::End => atom!("end"),
+            SpeechEventKind::Pause => atom!("pause"),
+            SpeechEventKind::Resume => atom!("resume"),
+            SpeechEventKind::Mark(name) => {
+                init.name = DOMString::from(name);
+                atom!("mark")
+            },
+            SpeechEventKind::WordBoundary => {
+                init.name = DOMString::from("word");
+                atom!("boundary")
+            },
+            SpeechEventKind::SentenceBoundary => {
+                init.name = DOMString::from("sentence");
+                atom!("boundary")
+            },
+            SpeechEventKind::Error(error) => {
+                self.fire_error(init, error_code(error));
+                self.speak_next();
+                return;
+            },
+        };
+        let event = SpeechSynthesisEvent::new(&self.global(), type_, &init);
+        event.upcast::<Event>().fire(utterance.upcast());
+
+        if done {
+            self.speak_next();
+        }
+    }
+
+    fn fire_error(&self, init: SpeechSynthesisEventInit, error: SpeechSynthesisErrorCode) {
+        let utterance = init.utterance.clone();
+        let init = SpeechSynthesisErrorEventInit {
+            parent: init,
+            error,
+        };
+        let event = SpeechSynthesisErrorEvent::new(&self.global(), atom!("error"), &init);
+        event.upcast::<Event>().fire(utterance.upcast());
+    }
+
+    /// Fires an error at an utterance that was removed from the queue by
+    /// `cancel()`.
+    fn queue_cancel_error(
+        &self,
+        utterance: &SpeechSynthesisUtterance,
+        error: SpeechSynthesisErrorCode,
+    ) {
+        let global = self.global();
+        let this = Trusted::new(self);
+        let utterance = Trusted::new(utterance);
+        let result = global.dom_manipulation_task_source().queue(
+            task!(fire_speech_cancel_error: move || {
+                this.root().fire_error(event_init(utterance.root()), error);
+            }),
+            &global,
+        );
+        if let Err(err) = result {
+            warn!("Could not queue speech error task {:?}", err);
+        }
+    }
+}
+
+fn event_init(utterance: DomRoot<SpeechSynthesisUtterance>) -> SpeechSynthesisEventInit {
+    SpeechSynthesisEventInit {
+        parent: EventInit::empty(),
+        charIndex: 0,
+        charLength: 0,
+        elapsedTime: Finite::wrap(0.),
+        name: DOMString::new(),
+        utterance,
+    }
+}
+
+fn error_code(error: SpeechError) -> SpeechSynthesisErrorCode {
+    match error {
+        SpeechError::AudioBusy => SpeechSynthesisErrorCode::Audio_busy,
+        SpeechError::AudioHardware => SpeechSynthesisErrorCode::Audio_hardware,
+        SpeechError::Network => SpeechSynthesisErrorCode::Network,
+        SpeechError::SynthesisUnavailable => SpeechSynthesisErrorCode::Synthesis_unavailable,
+        SpeechError::SynthesisFailed => SpeechSynthesisErrorCode::Synthesis_failed,
+        SpeechError::LanguageUnavailable => SpeechSynthesisErrorCode::Language_unavailable,
+        SpeechError::VoiceUnavailable => SpeechSynthesisErrorCode::Voice_unavailable,
+        SpeechError::TextTooLong => SpeechSynthesisErrorCode::Text_too_long,
+        SpeechError::InvalidArgument => SpeechSynthesisErrorCode::Invalid_argument,
+        SpeechError::NotAllowed => SpeechSynthesisErrorCode::Not_allowed,
+    }
+}
+
+impl SpeechSynthesisMethods for SpeechSynthesis {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-pending
+    fn Pending(&self) -> bool {
+        self.queue.borrow().len() > self.speaking.get() as usize
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-speaking
+    fn Speaking(&self) -> bool {
+        self.speaking.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-paused
+    fn Paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-onvoiceschanged
+    event_handler!(voiceschanged, GetOnvoiceschanged, SetOnvoiceschanged);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-speak
+    fn Speak(&self, utterance: &SpeechSynthesisUtterance) -> ErrorResult {
+        // The spec doesn't say what to throw when another speech synthesis
+        // owns the utterance: https://github.com/w3c/speech-api/issues/8
+        if !utterance.set_synthesis(self) {
+            return Err(Error::InvalidState);
+        }
+        self.queue.borrow_mut().push_back(Dom::from_ref(utterance));
+        self.speak_next();
+        Ok(())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-cancel
+    fn Cancel(&self) {
+        let queue = mem::replace(&mut *self.queue.borrow_mut(), VecDeque::new());
+        let mut queue = queue.into_iter();
+        if self.speaking.get() {
+            self.speaking.set(false);
+            let global = self.global();
+            global.send_to_embedder(EmbedderMsg::CancelSpeech(global.pipeline_id()));
+            if let Some(utterance) = queue.next() {
+                self.queue_cancel_error(&utterance, SpeechSynthesisErrorCode::Interrupted);
+            }
+        }
+        for utterance in queue {
+            self.queue_cancel_error(&utterance, SpeechSynthesisErrorCode::Canceled);
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-pause
+    fn Pause(&self) {
+        if self.paused.get() {
+            return;
+        }
+        self.paused.set(true);
+        if self.speaking.get() {
+            let global = self.global();
+            global.send_to_embedder(EmbedderMsg::PauseSpeech(global.pipeline_id()));
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-resume
+    fn Resume(&self) {
+        if !self.paused.get() {
+            return;
+        }
+        self.paused.set(false);
+        if self.speaking.get() {
+            let global = self.global();
+            global.send_to_embedder(EmbedderMsg::ResumeSpeech(global.pipeline_id()));
+        } else {
+            self.speak_next();
+        }
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-getvoices
+    fn GetVoices(&self) -> Vec<DomRoot<SpeechSynthesisVoice>> {
+        self.voices
+            .borrow()
+            .iter()
+            .map(|voice| DomRoot::from_ref(&**voice))
+            .collect()
+    }
+}
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::{
+    self, SpeechSynthesisErrorCode, SpeechSynthesisErrorEventInit, SpeechSynthesisErrorEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct SpeechSynthesisErrorEvent {
+    event: SpeechSynthesisEvent,
+    error: SpeechSynthesisErrorCode,
+}
+
+impl SpeechSynthesisErrorEvent {
+    fn new_inherited(init: &SpeechSynthesisErrorEventInit) -> SpeechSynthesisErrorEvent {
+        SpeechSynthesisErrorEvent {
+            event: SpeechSynthesisEvent::new_inherited(&init.parent),
+            error: init.error,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        init: &SpeechSynthesisErrorEventInit,
+    ) -> DomRoot<SpeechSynthesisErrorEvent> {
+        let ev = reflect_dom_object(
+            Box::new(SpeechSynthesisErrorEvent::new_inherited(init)),
+            global,
+            SpeechSynthesisErrorEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(
+                type_,
+                init.parent.parent.bubbles,
+                init.parent.parent.cancelable,
+            );
+        }
+        ev
+    }
+
+    // https://wicg.github.io/speech-api/#speechsynthesiserrorevent
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &SpeechSynthesisErrorEventInit,
+    ) -> Fallible<DomRoot<SpeechSynthesisErrorEvent>> {
+        Ok(SpeechSynthesisErrorEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init,
+        ))
+    }
+}
+
+impl SpeechSynthesisErrorEventMethods for SpeechSynthesisErrorEvent {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesiserrorevent-error
+    fn Error(&self) -> SpeechSynthesisErrorCode {
+        self.error
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.upcast::<Event>().IsTrusted()
+    }
+}
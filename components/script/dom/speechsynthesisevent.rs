@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisEventBinding::{
+    self, SpeechSynthesisEventInit, SpeechSynthesisEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use servo_atoms::Atom;
+
+#[dom_struct]
+pub struct SpeechSynthesisEvent {
+    event: Event,
+    utterance: Dom<SpeechSynthesisUtterance>,
+    char_index: u32,
+    char_length: u32,
+    elapsed_time: Finite<f32>,
+    name: DOMString,
+}
+
+impl SpeechSynthesisEvent {
+    pub fn new_inherited(init: &SpeechSynthesisEventInit) -> SpeechSynthesisEvent {
+        SpeechSynthesisEvent {
+            event: Event::new_inherited(),
+            utterance: Dom::from_ref(&*init.utterance),
+            char_index: init.charIndex,
+            char_length: init.charLength,
+            elapsed_time: init.elapsedTime,
+            name: init.name.clone(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        type_: Atom,
+        init: &SpeechSynthesisEventInit,
+    ) -> DomRoot<SpeechSynthesisEvent> {
+        let ev = reflect_dom_object(
+            Box::new(SpeechSynthesisEvent::new_inherited(init)),
+            global,
+            SpeechSynthesisEventBinding::Wrap,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, init.parent.bubbles, init.parent.cancelable);
+        }
+        ev
+    }
+
+    // https://wicg.github.io/speech-api/#speechsynthesisevent
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        type_: DOMString,
+        init: &SpeechSynthesisEventInit,
+    ) -> Fallible<DomRoot<SpeechSynthesisEvent>> {
+        Ok(SpeechSynthesisEvent::new(
+            &window.global(),
+            Atom::from(type_),
+            init,
+        ))
+    }
+}
+
+impl SpeechSynthesisEventMethods for SpeechSynthesisEvent {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-utterance
+    fn Utterance(&self) -> DomRoot<SpeechSynthesisUtterance> {
+        DomRoot::from_ref(&*self.utterance)
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-charindex
+    fn CharIndex(&self) -> u32 {
+        self.char_index
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-charlength
+    fn CharLength(&self) -> u32 {
+        self.char_length
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-elapsedtime
+    fn ElapsedTime(&self) -> Finite<f32> {
+        self.elapsed_time
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisevent-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisUtteranceBinding::{
+    self, SpeechSynthesisUtteranceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisVoiceBinding::SpeechSynthesisVoiceMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::speechsynthesis::SpeechSynthesis;
+use crate::dom::speechsynthesisvoice::SpeechSynthesisVoice;
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use embedder_traits::SpeechUtterance;
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct SpeechSynthesisUtterance {
+    eventtarget: EventTarget,
+    text: DomRefCell<DOMString>,
+    lang: DomRefCell<DOMString>,
+    voice: MutNullableDom<SpeechSynthesisVoice>,
+    /// The speech synthesis that the utterance was first given to, which
+    /// is the only one that can speak it.
+    synthesis: MutNullableDom<SpeechSynthesis>,
+    volume: Cell<f32>,
+    rate: Cell<f32>,
+    pitch: Cell<f32>,
+}
+
+impl SpeechSynthesisUtterance {
+    fn new_inherited(text: DOMString) -> SpeechSynthesisUtterance {
+        SpeechSynthesisUtterance {
+            eventtarget: EventTarget::new_inherited(),
+            text: DomRefCell::new(text),
+            lang: DomRefCell::new(DOMString::new()),
+            voice: Default::default(),
+            synthesis: Default::default(),
+            volume: Cell::new(1.),
+            rate: Cell::new(1.),
+            pitch: Cell::new(1.),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, text: DOMString) -> DomRoot<SpeechSynthesisUtterance> {
+        reflect_dom_object(
+            Box::new(SpeechSynthesisUtterance::new_inherited(text)),
+            global,
+            SpeechSynthesisUtteranceBinding::Wrap,
+        )
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-speechsynthesisutterance
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        text: Option<DOMString>,
+    ) -> DomRoot<SpeechSynthesisUtterance> {
+        SpeechSynthesisUtterance::new(window.upcast(), text.unwrap_or_default())
+    }
+
+    /// Gives the utterance to a speech synthesis, which fails if another
+    /// one already owns it.
+    pub fn set_synthesis(&self, synthesis: &SpeechSynthesis) -> bool {
+        match self.synthesis.get() {
+            Some(owner) => &*owner == synthesis,
+            None => {
+                self.synthesis.set(Some(synthesis));
+                true
+            },
+        }
+    }
+
+    /// What the embedder needs to speak the utterance, in the language of
+    /// the document if it has none.
+    pub fn to_speech_utterance(&self, document_lang: String) -> SpeechUtterance {
+        let lang = self.lang.borrow();
+        SpeechUtterance {
+            text: String::from(self.text.borrow().clone()),
+            lang: if lang.is_empty() {
+                document_lang
+            } else {
+                String::from(lang.clone())
+            },
+            voice: self.voice.get().map(|voice| String::from(voice.VoiceURI())),
+            volume: self.volume.get().max(0.).min(1.),
+            rate: self.rate.get().max(0.1).min(10.),
+            pitch: self.pitch.get().max(0.).min(2.),
+        }
+    }
+}
+
+impl SpeechSynthesisUtteranceMethods for SpeechSynthesisUtterance {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-text
+    fn Text(&self) -> DOMString {
+        self.text.borrow().clone()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-text
+    fn SetText(&self, value: DOMString) {
+        *self.text.borrow_mut() = value;
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-lang
+    fn Lang(&self) -> DOMString {
+        self.lang.borrow().clone()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-lang
+    fn SetLang(&self, value: DOMString) {
+        *self.lang.borrow_mut() = value;
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-voice
+    fn GetVoice(&self) -> Option<DomRoot<SpeechSynthesisVoice>> {
+        self.voice.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-voice
+    fn SetVoice(&self, value: Option<&SpeechSynthesisVoice>) {
+        self.voice.set(value);
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-volume
+    fn Volume(&self) -> Finite<f32> {
+        Finite::wrap(self.volume.get())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-volume
+    fn SetVolume(&self, value: Finite<f32>) {
+        self.volume.set(*value);
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-rate
+    fn Rate(&self) -> Finite<f32> {
+        Finite::wrap(self.rate.get())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-rate
+    fn SetRate(&self, value: Finite<f32>) {
+        self.rate.set(*value);
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-pitch
+    fn Pitch(&self) -> Finite<f32> {
+        Finite::wrap(self.pitch.get())
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-pitch
+    fn SetPitch(&self, value: Finite<f32>) {
+        self.pitch.set(*value);
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onstart
+    event_handler!(start, GetOnstart, SetOnstart);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onend
+    event_handler!(end, GetOnend, SetOnend);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onpause
+    event_handler!(pause, GetOnpause, SetOnpause);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onmark
+    event_handler!(mark, GetOnmark, SetOnmark);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onboundary
+    event_handler!(boundary, GetOnboundary, SetOnboundary);
+}
@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisVoiceBinding::{
+    self, SpeechSynthesisVoiceMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use dom_struct::dom_struct;
+use embedder_traits::SpeechVoice;
+
+#[dom_struct]
+pub struct SpeechSynthesisVoice {
+    reflector_: Reflector,
+    uri: DOMString,
+    name: DOMString,
+    lang: DOMString,
+    local: bool,
+    default: bool,
+}
+
+impl SpeechSynthesisVoice {
+    fn new_inherited(voice: SpeechVoice) -> SpeechSynthesisVoice {
+        SpeechSynthesisVoice {
+            reflector_: Reflector::new(),
+            uri: DOMString::from(voice.uri),
+            name: DOMString::from(voice.name),
+            lang: DOMString::from(voice.lang),
+            local: voice.local,
+            default: voice.default,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, voice: SpeechVoice) -> DomRoot<SpeechSynthesisVoice> {
+        reflect_dom_object(
+            Box::new(SpeechSynthesisVoice::new_inherited(voice)),
+            global,
+            SpeechSynthesisVoiceBinding::Wrap,
+        )
+    }
+}
+
+impl SpeechSynthesisVoiceMethods for SpeechSynthesisVoice {
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-voiceuri
+    fn VoiceURI(&self) -> DOMString {
+        self.uri.clone()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-name
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-lang
+    fn Lang(&self) -> DOMString {
+        self.lang.clone()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-localservice
+    fn LocalService(&self) -> bool {
+        self.local
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-default
+    fn Default(&self) -> bool {
+        self.default
+    }
+}
@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::StorageManagerBinding::{
+    self, StorageEstimate, StorageManagerMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::{get_descriptor_permission_state, prompt_user_from_embedder};
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use dom_struct::dom_struct;
+use embedder_traits::{self, PermissionPrompt};
+use ipc_channel::ipc::IpcSender;
+use net_traits::storage_thread::StorageThreadMsg;
+use net_traits::IpcSend;
+use profile_traits::ipc;
+use std::rc::Rc;
+
+// https://storage.spec.whatwg.org/#storagemanager
+#[dom_struct]
+pub struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<StorageManager> {
+        reflect_dom_object(
+            Box::new(StorageManager::new_inherited()),
+            global,
+            StorageManagerBinding::Wrap,
+        )
+    }
+
+    fn get_storage_thread(&self) -> IpcSender<StorageThreadMsg> {
+        self.global().resource_threads().sender()
+    }
+
+    /// Whether the global has a storage shelf, which opaque origins don't.
+    /// <https://storage.spec.whatwg.org/#obtain-a-local-storage-shelf>
+    fn has_storage_shelf(&self) -> bool {
+        self.global().origin().immutable().is_tuple()
+    }
+
+    /// Whether the storage of the origin of the global is persistent.
+    fn persisted(&self) -> bool {
+        let global = self.global();
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Persisted(sender, global.get_url()))
+            .unwrap();
+        receiver.recv().unwrap()
+    }
+}
+
+impl StorageManagerMethods for StorageManager {
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persisted
+    fn Persisted(&self, comp: InRealm) -> Rc<Promise> {
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        // Steps 2-3.
+        if !self.has_storage_shelf() {
+            promise.reject_error(Error::Type("The origin is opaque".to_owned()));
+            return promise;
+        }
+        // Step 4.
+        promise.resolve_native(&self.persisted());
+        promise
+    }
+
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persist
+    fn Persist(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&global, comp);
+        // Steps 2-3.
+        if !self.has_storage_shelf() {
+            promise.reject_error(Error::Type("The origin is opaque".to_owned()));
+            return promise;
+        }
+        // Steps 4.2-4.3. The storage being persistent already, there's no
+        // need to ask for the permission again.
+        if self.persisted() {
+            promise.resolve_native(&true);
+            return promise;
+        }
+        // Step 4.1.
+        if request_permission(&global) != PermissionState::Granted {
+            promise.resolve_native(&false);
+            return promise;
+        }
+        // Steps 4.4-4.5.
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Persist(sender, global.get_url()))
+            .unwrap();
+        promise.resolve_native(&receiver.recv().unwrap());
+        promise
+    }
+
+    // https://storage.spec.whatwg.org/#dom-storagemanager-estimate
+    fn Estimate(&self, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        // Step 1.
+        let promise = Promise::new_in_current_realm(&global, comp);
+        // Steps 2-3.
+        if !self.has_storage_shelf() {
+            promise.reject_error(Error::Type("The origin is opaque".to_owned()));
+            return promise;
+        }
+        // Step 4.
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        self.get_storage_thread()
+            .send(StorageThreadMsg::Estimate(sender, global.get_url()))
+            .unwrap();
+        let (usage, quota) = receiver.recv().unwrap();
+        let mut estimate = StorageEstimate::empty();
+        estimate.usage = Some(usage);
+        estimate.quota = Some(quota);
+        promise.resolve_native(&estimate);
+        promise
+    }
+}
+
+/// https://w3c.github.io/permissions/#request-permission-to-use
+fn request_permission(global: &GlobalScope) -> PermissionState {
+    let name = PermissionName::Persistent_storage;
+    match get_descriptor_permission_state(name, Some(global)) {
+        PermissionState::Prompt => {},
+        state => return state,
+    }
+    let prompt = PermissionPrompt::Request(embedder_traits::PermissionName::from(name));
+    let state = prompt_user_from_embedder(prompt, global);
+    global
+        .permission_state_invocation_results()
+        .borrow_mut()
+        .insert(name.to_string(), state);
+    state
+}
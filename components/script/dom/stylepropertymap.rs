@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::StylePropertyMapBinding::{
+    StylePropertyMapMethods, Wrap,
+};
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::CSSStyleValueOrString;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::cssstyledeclaration::{remove_property, CSSStyleOwner};
+use crate::dom::element::Element;
+use crate::dom::stylepropertymapreadonly::{StylePropertyMapEntries, StylePropertyMapReadOnly};
+use crate::dom::window::Window;
+use dom_struct::dom_struct;
+use style::properties::{parse_one_declaration_into, SourcePropertyDeclaration};
+use style::properties::{Importance, PropertyDeclarationBlock, PropertyId};
+use style_traits::ParsingMode;
+
+#[dom_struct]
+pub struct StylePropertyMap {
+    map: StylePropertyMapReadOnly,
+}
+
+impl StylePropertyMap {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(owner: CSSStyleOwner) -> StylePropertyMap {
+        StylePropertyMap {
+            map: StylePropertyMapReadOnly::new_inherited(StylePropertyMapEntries::Declared(owner)),
+        }
+    }
+
+    /// The map of the declarations of the style attribute of `element`.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-elementcssinlinestyle-attributestylemap>
+    #[allow(unrooted_must_root)]
+    pub fn new_for_element(window: &Window, element: &Element) -> DomRoot<StylePropertyMap> {
+        reflect_dom_object(
+            Box::new(StylePropertyMap::new_inherited(CSSStyleOwner::Element(
+                Dom::from_ref(element),
+            ))),
+            window,
+            Wrap,
+        )
+    }
+
+    fn owner(&self) -> &CSSStyleOwner {
+        self.map
+            .declared_owner()
+            .expect("Style property maps reflect declaration blocks")
+    }
+}
+
+impl StylePropertyMapMethods for StylePropertyMap {
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-set>
+    fn Set(&self, property: DOMString, values: Vec<CSSStyleValueOrString>) -> Fallible<()> {
+        // Step 1.
+        let id = PropertyId::parse_enabled_for_all_content(&property)
+            .map_err(|()| Error::Type(format!("Unknown property: {}", property)))?;
+        // Step 3. List-valued properties aren't supported, so there must be
+        // a single value.
+        let value = match &*values {
+            [value] => value,
+            _ => return Err(Error::Type(format!("{} takes a single value", property))),
+        };
+        let css_text = match *value {
+            CSSStyleValueOrString::CSSStyleValue(ref value) => value.serialize(),
+            CSSStyleValueOrString::String(ref value) => String::from(value.clone()),
+        };
+        // Step 5.
+        let owner = self.owner();
+        let window = owner.window();
+        let mut declarations = SourcePropertyDeclaration::new();
+        parse_one_declaration_into(
+            &mut declarations,
+            id,
+            &css_text,
+            &owner.base_url(),
+            window.css_error_reporter(),
+            ParsingMode::DEFAULT,
+            window.Document().quirks_mode(),
+        )
+        .map_err(|()| Error::Type(format!("Invalid value for {}: {}", property, css_text)))?;
+        owner.mutate_associated_block(|pdb, changed| {
+            let mut updates = Default::default();
+            *changed = pdb.prepare_for_update(&declarations, Importance::Normal, &mut updates);
+            if *changed {
+                pdb.update(declarations.drain(), Importance::Normal, &mut updates);
+            }
+        });
+        Ok(())
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-delete>
+    fn Delete(&self, property: DOMString) {
+        let id = match PropertyId::parse_enabled_for_all_content(&property) {
+            Ok(id) => id,
+            Err(..) => return,
+        };
+        self.owner().mutate_associated_block(|pdb, changed| {
+            *changed = remove_property(pdb, &id);
+        });
+    }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-clear>
+    fn Clear(&self) {
+        self.owner().mutate_associated_block(|pdb, changed| {
+            *changed = !pdb.declarations().is_empty();
+            *pdb = PropertyDeclarationBlock::new();
+        });
+    }
+}
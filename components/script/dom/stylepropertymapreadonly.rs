@@ -4,36 +4,54 @@
 
 use crate::dom::bindings::codegen::Bindings::StylePropertyMapReadOnlyBinding::StylePropertyMapReadOnlyMethods;
 use crate::dom::bindings::codegen::Bindings::StylePropertyMapReadOnlyBinding::Wrap;
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::reflector::{DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::cssstyledeclaration::CSSStyleOwner;
 use crate::dom::cssstylevalue::CSSStyleValue;
+use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::{window_from_node, Node};
+use crate::dom::window::Window;
 use dom_struct::dom_struct;
 use servo_atoms::Atom;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::iter::Iterator;
 use style::custom_properties;
+use style::properties::{PropertyId, ShorthandId};
+
+/// Where the values of a style property map come from.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub enum StylePropertyMapEntries {
+    /// A snapshot of values, like the ones paint worklets get.
+    Snapshot(HashMap<Atom, Dom<CSSStyleValue>>),
+    /// The computed values of an element.
+    Computed(Dom<Element>),
+    /// The declarations of a declaration block, like the style attribute of
+    /// an element.
+    Declared(CSSStyleOwner),
+}
 
 #[dom_struct]
 pub struct StylePropertyMapReadOnly {
     reflector: Reflector,
-    entries: HashMap<Atom, Dom<CSSStyleValue>>,
+    entries: StylePropertyMapEntries,
 }
 
 impl StylePropertyMapReadOnly {
-    fn new_inherited<Entries>(entries: Entries) -> StylePropertyMapReadOnly
-    where
-        Entries: IntoIterator<Item = (Atom, Dom<CSSStyleValue>)>,
-    {
+    #[allow(unrooted_must_root)]
+    pub fn new_inherited(entries: StylePropertyMapEntries) -> StylePropertyMapReadOnly {
         StylePropertyMapReadOnly {
             reflector: Reflector::new(),
-            entries: entries.into_iter().collect(),
+            entries: entries,
         }
     }
 
+    #[allow(unrooted_must_root)]
     pub fn from_iter<Entries>(
         global: &GlobalScope,
         entries: Entries,
@@ -54,35 +72,134 @@ impl StylePropertyMapReadOnly {
         }
         let iter = keys.drain(..).zip(values.iter().cloned());
         reflect_dom_object(
-            Box::new(StylePropertyMapReadOnly::new_inherited(iter)),
+            Box::new(StylePropertyMapReadOnly::new_inherited(
+                StylePropertyMapEntries::Snapshot(iter.collect()),
+            )),
             global,
             Wrap,
         )
     }
+
+    /// The map of the computed values of `element`.
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-element-computedstylemap>
+    #[allow(unrooted_must_root)]
+    pub fn new_computed(window: &Window, element: &Element) -> DomRoot<StylePropertyMapReadOnly> {
+        reflect_dom_object(
+            Box::new(StylePropertyMapReadOnly::new_inherited(
+                StylePropertyMapEntries::Computed(Dom::from_ref(element)),
+            )),
+            window,
+            Wrap,
+        )
+    }
+
+    /// The declaration block the map reflects, if it reflects one.
+    pub fn declared_owner(&self) -> Option<&CSSStyleOwner> {
+        match self.entries {
+            StylePropertyMapEntries::Declared(ref owner) => Some(owner),
+            _ => None,
+        }
+    }
+
+    /// The serialization of the value of `property`, if the map has it.
+    fn serialized_value(&self, property: &PropertyId) -> Option<String> {
+        let mut serialization = String::new();
+        match self.entries {
+            StylePropertyMapEntries::Snapshot(_) => return None,
+            StylePropertyMapEntries::Computed(ref element) => {
+                let node = element.upcast::<Node>();
+                if !node.is_connected() {
+                    return None;
+                }
+                match property.as_shorthand() {
+                    Ok(_) => {
+                        serialization = String::from(window_from_node(node).resolved_style_query(
+                            node.to_trusted_node_address(),
+                            None,
+                            property.clone(),
+                        ));
+                    },
+                    Err(longhand_or_custom) => {
+                        serialization = element
+                            .style()?
+                            .computed_value_to_string(longhand_or_custom);
+                    },
+                }
+            },
+            StylePropertyMapEntries::Declared(ref owner) => {
+                owner.with_block(|pdb| {
+                    pdb.property_value_to_css(property, &mut serialization)
+                        .unwrap();
+                });
+            },
+        }
+        if serialization.is_empty() {
+            return None;
+        }
+        Some(serialization)
+    }
+
+    /// The names of the properties that the map has a value for.
+    fn properties(&self) -> Vec<DOMString> {
+        match self.entries {
+            StylePropertyMapEntries::Snapshot(ref entries) => {
+                entries.keys().map(|key| DOMString::from(&**key)).collect()
+            },
+            StylePropertyMapEntries::Computed(ref element) => {
+                let style = match element.style() {
+                    Some(style) => style,
+                    None => return vec![],
+                };
+                let longhands = ShorthandId::All
+                    .longhands()
+                    .filter(|longhand| PropertyId::Longhand(*longhand).enabled_for_all_content())
+                    .map(|longhand| DOMString::from(longhand.name()));
+                let custom_properties = style
+                    .custom_properties()
+                    .into_iter()
+                    .flat_map(|map| map.keys())
+                    .map(|name| DOMString::from(format!("--{}", name)));
+                longhands.chain(custom_properties).collect()
+            },
+            StylePropertyMapEntries::Declared(ref owner) => owner.with_block(|pdb| {
+                pdb.declarations()
+                    .iter()
+                    .map(|declaration| DOMString::from(&*declaration.id().name()))
+                    .collect()
+            }),
+        }
+    }
 }
 
 impl StylePropertyMapReadOnlyMethods for StylePropertyMapReadOnly {
     /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-get>
     fn Get(&self, property: DOMString) -> Option<DomRoot<CSSStyleValue>> {
-        // TODO: avoid constructing an Atom
-        self.entries
-            .get(&Atom::from(property))
-            .map(|value| DomRoot::from_ref(&**value))
+        if let StylePropertyMapEntries::Snapshot(ref entries) = self.entries {
+            // TODO: avoid constructing an Atom
+            return entries
+                .get(&Atom::from(property))
+                .map(|value| DomRoot::from_ref(&**value));
+        }
+        let id = PropertyId::parse_enabled_for_all_content(&property).ok()?;
+        let serialization = self.serialized_value(&id)?;
+        Some(CSSStyleValue::reify(&self.global(), &id, serialization))
     }
 
     /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-has>
     fn Has(&self, property: DOMString) -> bool {
-        // TODO: avoid constructing an Atom
-        self.entries.contains_key(&Atom::from(property))
+        if let StylePropertyMapEntries::Snapshot(ref entries) = self.entries {
+            // TODO: avoid constructing an Atom
+            return entries.contains_key(&Atom::from(property));
+        }
+        PropertyId::parse_enabled_for_all_content(&property)
+            .ok()
+            .and_then(|id| self.serialized_value(&id))
+            .is_some()
     }
 
     /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-getproperties>
     fn GetProperties(&self) -> Vec<DOMString> {
-        let mut result: Vec<DOMString> = self
-            .entries
-            .keys()
-            .map(|key| DOMString::from(&**key))
-            .collect();
+        let mut result = self.properties();
         // https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymap-getproperties
         // requires this sort order
         result.sort_by(|key1, key2| {
@@ -102,4 +219,9 @@ impl StylePropertyMapReadOnlyMethods for StylePropertyMapReadOnly {
         });
         result
     }
+
+    /// <https://drafts.css-houdini.org/css-typed-om-1/#dom-stylepropertymapreadonly-size>
+    fn Size(&self) -> u32 {
+        self.properties().len() as u32
+    }
 }
@@ -17,7 +17,7 @@ use servo_arc::Arc;
 use style::stylesheets::Stylesheet;
 
 #[unrooted_must_root_lint::must_root]
-#[derive(JSTraceable, MallocSizeOf)]
+#[derive(Clone, JSTraceable, MallocSizeOf, PartialEq)]
 pub enum StyleSheetListOwner {
     Document(Dom<Document>),
     ShadowRoot(Dom<ShadowRoot>),
@@ -56,6 +56,24 @@ impl StyleSheetListOwner {
         }
     }
 
+    pub fn add_adopted_stylesheet(&self, sheet: &CSSStyleSheet) {
+        match *self {
+            StyleSheetListOwner::Document(ref doc) => doc.add_adopted_stylesheet(sheet),
+            StyleSheetListOwner::ShadowRoot(ref shadow_root) => {
+                shadow_root.add_adopted_stylesheet(sheet)
+            },
+        }
+    }
+
+    pub fn remove_adopted_stylesheet(&self, sheet: &CSSStyleSheet) {
+        match *self {
+            StyleSheetListOwner::Document(ref doc) => doc.remove_adopted_stylesheet(sheet),
+            StyleSheetListOwner::ShadowRoot(ref shadow_root) => {
+                shadow_root.remove_adopted_stylesheet(sheet)
+            },
+        }
+    }
+
     pub fn invalidate_stylesheets(&self) {
         match *self {
             StyleSheetListOwner::Document(ref doc) => doc.invalidate_stylesheets(),
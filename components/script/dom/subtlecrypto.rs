@@ -0,0 +1,705 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::KeyUsage;
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    self, Algorithm, CryptoKeyPair, JsonWebKey, KeyFormat, SubtleCryptoMethods,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    AesCbcParams, AesCtrParams, AesDerivedKeyParams, AesGcmParams, AesKeyGenParams, EcKeyGenParams,
+    EcKeyImportParams, EcdhKeyDeriveParams, EcdsaParams, HkdfParams, HmacImportParams,
+    HmacKeyGenParams, Pbkdf2Params, RsaHashedImportParams, RsaHashedKeyGenParams, RsaOaepParams,
+    RsaPssParams,
+};
+use crate::dom::bindings::codegen::UnionTypes::{ArrayBufferViewOrArrayBuffer, ObjectOrString};
+use crate::dom::bindings::conversions::{
+    ConversionResult, FromJSValConvertible, ToJSValConvertible,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::cryptokey::CryptoKey;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+use crate::webcrypto::{
+    self, AlgorithmName, GeneratedKey, Jwk, KeyMaterial, NamedCurve, NormalizedAlgorithm,
+    Operation, Params,
+};
+use crossbeam_channel::{bounded, Sender};
+use dom_struct::dom_struct;
+use js::jsapi::{Heap, JSObject};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::HandleValue;
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use std::ptr;
+use std::rc::Rc;
+use std::thread;
+
+/// The number of threads running the cryptographic operations of the
+/// content process.
+const CRYPTO_THREADS: usize = 4;
+
+/// The most operations that can wait for a thread to run them, past which
+/// new operations are rejected.
+const MAX_PENDING_OPERATIONS: usize = 256;
+
+type CryptoJob = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    /// The threads running the operations of every `SubtleCrypto` of the
+    /// content process, or `None` if none of them could be spawned.
+    static ref CRYPTO_THREAD_POOL: Option<Sender<CryptoJob>> = spawn_crypto_threads();
+}
+
+fn spawn_crypto_threads() -> Option<Sender<CryptoJob>> {
+    let (sender, receiver) = bounded::<CryptoJob>(MAX_PENDING_OPERATIONS);
+    let mut spawned_any = false;
+    for _ in 0..CRYPTO_THREADS {
+        let receiver = receiver.clone();
+        let spawned = thread::Builder::new()
+            .name("WebCrypto".to_owned())
+            .spawn(move || {
+                for job in receiver.iter() {
+                    job();
+                }
+            });
+        match spawned {
+            Ok(_) => spawned_any = true,
+            Err(error) => warn!("Failed to spawn a WebCrypto thread ({:?}).", error),
+        }
+    }
+    if spawned_any {
+        Some(sender)
+    } else {
+        None
+    }
+}
+
+// https://w3c.github.io/webcrypto/#subtlecrypto-interface
+#[dom_struct]
+pub struct SubtleCrypto {
+    reflector_: Reflector,
+}
+
+impl SubtleCrypto {
+    fn new_inherited() -> SubtleCrypto {
+        SubtleCrypto {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<SubtleCrypto> {
+        reflect_dom_object(
+            Box::new(SubtleCrypto::new_inherited()),
+            global,
+            SubtleCryptoBinding::Wrap,
+        )
+    }
+
+    /// Runs `operation` in parallel, and then queues a task to settle
+    /// `promise` with its result, converted by `resolve`. The spec queues
+    /// these tasks on the crypto task source, which is the DOM manipulation
+    /// one here. `promise` is rejected right away if too many operations are
+    /// already waiting to run.
+    fn run_in_parallel<T, F, R>(&self, promise: &Rc<Promise>, operation: F, resolve: R)
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Fallible<T> + Send + 'static,
+        R: FnOnce(&GlobalScope, &Promise, T) + Send + 'static,
+    {
+        let global = self.global();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let trusted_promise = TrustedPromise::new(promise.clone());
+        let trusted_subtle = Trusted::new(self);
+        let job: CryptoJob = Box::new(move || {
+            let result = operation();
+            let _ = task_source.queue_with_canceller(
+                task!(settle_crypto_promise: move || {
+                    let promise = trusted_promise.root();
+                    match result {
+                        Ok(value) => resolve(&trusted_subtle.root().global(), &promise, value),
+                        Err(error) => promise.reject_error(error),
+                    }
+                }),
+                &canceller,
+            );
+        });
+        let scheduled = match *CRYPTO_THREAD_POOL {
+            Some(ref sender) => sender.try_send(job).is_ok(),
+            None => false,
+        };
+        if !scheduled {
+            promise.reject_error(Error::Operation);
+        }
+    }
+}
+
+impl SubtleCryptoMethods for SubtleCrypto {
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-encrypt
+    fn Encrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::Encrypt) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let key = key.key().clone();
+        let data = buffer_source_bytes(&data);
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::encrypt(&algorithm, &key, &data),
+            resolve_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-decrypt
+    fn Decrypt(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::Decrypt) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let key = key.key().clone();
+        let data = buffer_source_bytes(&data);
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::decrypt(&algorithm, &key, &data),
+            resolve_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-sign
+    fn Sign(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::Sign) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let key = key.key().clone();
+        let data = buffer_source_bytes(&data);
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::sign(&algorithm, &key, &data),
+            resolve_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-verify
+    fn Verify(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        key: &CryptoKey,
+        signature: ArrayBufferViewOrArrayBuffer,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::Verify) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let key = key.key().clone();
+        let signature = buffer_source_bytes(&signature);
+        let data = buffer_source_bytes(&data);
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::verify(&algorithm, &key, &signature, &data),
+            |_, promise, valid| promise.resolve_native(&valid),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-digest
+    fn Digest(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::Digest) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let data = buffer_source_bytes(&data);
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::digest(algorithm.name, &data),
+            resolve_bytes,
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-generateKey
+    fn GenerateKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::GenerateKey) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::generate_key(&algorithm, extractable, &key_usages),
+            |global, promise, generated| match generated {
+                GeneratedKey::Key(key) => promise.resolve_native(&CryptoKey::new(global, key)),
+                GeneratedKey::Pair { public, private } => {
+                    let pair = CryptoKeyPair {
+                        publicKey: Some(CryptoKey::new(global, public)),
+                        privateKey: Some(CryptoKey::new(global, private)),
+                    };
+                    promise.resolve_native(&pair);
+                },
+            },
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveKey
+    fn DeriveKey(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        derived_key_type: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithms =
+            normalize_algorithm(cx, &algorithm, Operation::DeriveBits).and_then(|algorithm| {
+                let import = normalize_algorithm(cx, &derived_key_type, Operation::ImportKey)?;
+                let length = normalize_algorithm(cx, &derived_key_type, Operation::GetKeyLength)?;
+                Ok((algorithm, import, length))
+            });
+        let (algorithm, import, length) = match algorithms {
+            Ok(algorithms) => algorithms,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let key = base_key.key().clone();
+        self.run_in_parallel(
+            &promise,
+            move || {
+                webcrypto::derive_key(&algorithm, &key, &import, &length, extractable, &key_usages)
+            },
+            |global, promise, key| promise.resolve_native(&CryptoKey::new(global, key)),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveBits
+    fn DeriveBits(
+        &self,
+        cx: JSContext,
+        algorithm: ObjectOrString,
+        base_key: &CryptoKey,
+        length: u32,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let algorithm = match normalize_algorithm(cx, &algorithm, Operation::DeriveBits) {
+            Ok(algorithm) => algorithm,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        let key = base_key.key().clone();
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::derive_bits(&algorithm, &key, Some(length)),
+            resolve_bytes,
+        );
+        promise
+    }
+
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-importKey
+    fn ImportKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key_data: *mut JSObject,
+        algorithm: ObjectOrString,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let arguments = unsafe { key_material(cx, format, key_data) }.and_then(|material| {
+            Ok((
+                material,
+                normalize_algorithm(cx, &algorithm, Operation::ImportKey)?,
+            ))
+        });
+        let (material, algorithm) = match arguments {
+            Ok(arguments) => arguments,
+            Err(error) => {
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::import_key(format, material, &algorithm, extractable, &key_usages),
+            |global, promise, key| promise.resolve_native(&CryptoKey::new(global, key)),
+        );
+        promise
+    }
+
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-exportKey
+    fn ExportKey(&self, format: KeyFormat, key: &CryptoKey, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(&self.global(), comp);
+        let key = key.key().clone();
+        self.run_in_parallel(
+            &promise,
+            move || webcrypto::export_key(format, &key),
+            |global, promise, material| match material {
+                KeyMaterial::Bytes(bytes) => resolve_bytes(global, promise, bytes),
+                KeyMaterial::Jwk(jwk) => promise.resolve_native(&jwk_to_dictionary(jwk)),
+            },
+        );
+        promise
+    }
+}
+
+pub fn buffer_source_bytes(buffer_source: &ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match *buffer_source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref buffer) => buffer.to_vec(),
+    }
+}
+
+#[allow(unsafe_code)]
+fn resolve_bytes(global: &GlobalScope, promise: &Promise, bytes: Vec<u8>) {
+    let cx = global.get_cx();
+    let _ac = enter_realm(global);
+    rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+    assert!(unsafe {
+        ArrayBuffer::create(*cx, CreateWith::Slice(&bytes), array_buffer.handle_mut()).is_ok()
+    });
+    promise.resolve_native(&RootedTraceableBox::from_box(Heap::boxed(
+        array_buffer.get(),
+    )));
+}
+
+#[allow(unsafe_code)]
+unsafe fn convert<T>(cx: JSContext, value: HandleValue) -> Fallible<T>
+where
+    T: FromJSValConvertible<Config = ()>,
+{
+    match T::from_jsval(*cx, value, ()) {
+        Ok(ConversionResult::Success(value)) => Ok(value),
+        Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+/// Takes the data of importKey, which is a JsonWebKey dictionary for the jwk
+/// format, and a BufferSource otherwise.
+#[allow(unsafe_code)]
+unsafe fn key_material(
+    cx: JSContext,
+    format: KeyFormat,
+    key_data: *mut JSObject,
+) -> Fallible<KeyMaterial> {
+    if format == KeyFormat::Jwk {
+        rooted!(in(*cx) let value = ObjectValue(key_data));
+        let jwk = convert::<JsonWebKey>(cx, value.handle())?;
+        return Ok(KeyMaterial::Jwk(jwk_from_dictionary(&jwk)));
+    }
+    typedarray!(in(*cx) let array_buffer: ArrayBuffer = key_data);
+    if let Ok(array_buffer) = array_buffer {
+        return Ok(KeyMaterial::Bytes(array_buffer.to_vec()));
+    }
+    typedarray!(in(*cx) let view: ArrayBufferView = key_data);
+    match view {
+        Ok(view) => Ok(KeyMaterial::Bytes(view.to_vec())),
+        Err(()) => Err(Error::Type("The key data isn't a BufferSource".to_owned())),
+    }
+}
+
+fn jwk_from_dictionary(jwk: &JsonWebKey) -> Jwk {
+    let string = |member: &Option<DOMString>| member.as_ref().map(|value| value.to_string());
+    Jwk {
+        kty: string(&jwk.kty),
+        use_: string(&jwk.use_),
+        key_ops: jwk
+            .key_ops
+            .as_ref()
+            .map(|key_ops| key_ops.iter().map(|op| op.to_string()).collect()),
+        alg: string(&jwk.alg),
+        ext: jwk.ext,
+        crv: string(&jwk.crv),
+        x: string(&jwk.x),
+        y: string(&jwk.y),
+        d: string(&jwk.d),
+        n: string(&jwk.n),
+        e: string(&jwk.e),
+        p: string(&jwk.p),
+        q: string(&jwk.q),
+        dp: string(&jwk.dp),
+        dq: string(&jwk.dq),
+        qi: string(&jwk.qi),
+        has_other_primes: jwk.oth.as_ref().map_or(false, |oth| !oth.is_empty()),
+        k: string(&jwk.k),
+    }
+}
+
+fn jwk_to_dictionary(jwk: Jwk) -> JsonWebKey {
+    let string = |member: Option<String>| member.map(DOMString::from);
+    let mut dictionary = JsonWebKey::empty();
+    dictionary.kty = string(jwk.kty);
+    dictionary.use_ = string(jwk.use_);
+    dictionary.key_ops = jwk
+        .key_ops
+        .map(|key_ops| key_ops.into_iter().map(DOMString::from).collect());
+    dictionary.alg = string(jwk.alg);
+    dictionary.ext = jwk.ext;
+    dictionary.crv = string(jwk.crv);
+    dictionary.x = string(jwk.x);
+    dictionary.y = string(jwk.y);
+    dictionary.d = string(jwk.d);
+    dictionary.n = string(jwk.n);
+    dictionary.e = string(jwk.e);
+    dictionary.p = string(jwk.p);
+    dictionary.q = string(jwk.q);
+    dictionary.dp = string(jwk.dp);
+    dictionary.dq = string(jwk.dq);
+    dictionary.qi = string(jwk.qi);
+    dictionary.k = string(jwk.k);
+    dictionary
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-normalize-an-algorithm
+#[allow(unsafe_code)]
+fn normalize_algorithm(
+    cx: JSContext,
+    algorithm: &ObjectOrString,
+    operation: Operation,
+) -> Fallible<NormalizedAlgorithm> {
+    // A string is the same as a dictionary with only that name.
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    match *algorithm {
+        ObjectOrString::Object(ref object) => value.set(ObjectValue(object.get())),
+        ObjectOrString::String(ref name) => unsafe {
+            Algorithm { name: name.clone() }.to_jsval(*cx, value.handle_mut())
+        },
+    }
+    let value = value.handle();
+
+    let initial = unsafe { convert::<Algorithm>(cx, value)? };
+    let name = match AlgorithmName::from_name(&initial.name) {
+        Some(name) if name.supports(operation) => name,
+        _ => return Err(Error::NotSupported),
+    };
+
+    let params = unsafe {
+        match (name, operation) {
+            (AlgorithmName::AesCbc, Operation::Encrypt) |
+            (AlgorithmName::AesCbc, Operation::Decrypt) => {
+                let params = convert::<RootedTraceableBox<AesCbcParams>>(cx, value)?;
+                Params::AesCbc {
+                    iv: buffer_source_bytes(&params.iv),
+                }
+            },
+            (AlgorithmName::AesCtr, Operation::Encrypt) |
+            (AlgorithmName::AesCtr, Operation::Decrypt) => {
+                let params = convert::<RootedTraceableBox<AesCtrParams>>(cx, value)?;
+                Params::AesCtr {
+                    counter: buffer_source_bytes(&params.counter),
+                    length: params.length,
+                }
+            },
+            (AlgorithmName::AesGcm, Operation::Encrypt) |
+            (AlgorithmName::AesGcm, Operation::Decrypt) => {
+                let params = convert::<RootedTraceableBox<AesGcmParams>>(cx, value)?;
+                Params::AesGcm {
+                    iv: buffer_source_bytes(&params.iv),
+                    additional_data: params
+                        .additionalData
+                        .as_ref()
+                        .map_or(vec![], buffer_source_bytes),
+                    tag_length: params.tagLength.unwrap_or(128),
+                }
+            },
+            (AlgorithmName::RsaOaep, Operation::Encrypt) |
+            (AlgorithmName::RsaOaep, Operation::Decrypt) => {
+                let params = convert::<RootedTraceableBox<RsaOaepParams>>(cx, value)?;
+                Params::RsaOaep {
+                    label: params.label.as_ref().map_or(vec![], buffer_source_bytes),
+                }
+            },
+            (AlgorithmName::RsaPss, Operation::Sign) |
+            (AlgorithmName::RsaPss, Operation::Verify) => {
+                let params = convert::<RsaPssParams>(cx, value)?;
+                Params::RsaPss {
+                    salt_length: params.saltLength,
+                }
+            },
+            (AlgorithmName::Ecdsa, Operation::Sign) | (AlgorithmName::Ecdsa, Operation::Verify) => {
+                let params = convert::<RootedTraceableBox<EcdsaParams>>(cx, value)?;
+                Params::Ecdsa {
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            (AlgorithmName::AesCbc, Operation::GenerateKey) |
+            (AlgorithmName::AesCtr, Operation::GenerateKey) |
+            (AlgorithmName::AesGcm, Operation::GenerateKey) => {
+                let params = convert::<AesKeyGenParams>(cx, value)?;
+                Params::AesKey {
+                    length: params.length,
+                }
+            },
+            (AlgorithmName::AesCbc, Operation::GetKeyLength) |
+            (AlgorithmName::AesCtr, Operation::GetKeyLength) |
+            (AlgorithmName::AesGcm, Operation::GetKeyLength) => {
+                let params = convert::<AesDerivedKeyParams>(cx, value)?;
+                Params::AesKey {
+                    length: params.length,
+                }
+            },
+            (AlgorithmName::Hmac, Operation::GenerateKey) => {
+                let params = convert::<RootedTraceableBox<HmacKeyGenParams>>(cx, value)?;
+                Params::HmacKey {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    length: params.length,
+                }
+            },
+            (AlgorithmName::Hmac, Operation::ImportKey) |
+            (AlgorithmName::Hmac, Operation::GetKeyLength) => {
+                let params = convert::<RootedTraceableBox<HmacImportParams>>(cx, value)?;
+                Params::HmacKey {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    length: params.length,
+                }
+            },
+            (AlgorithmName::RsaOaep, Operation::GenerateKey) |
+            (AlgorithmName::RsaPss, Operation::GenerateKey) => {
+                let params = convert::<RootedTraceableBox<RsaHashedKeyGenParams>>(cx, value)?;
+                Params::RsaHashedKeyGen {
+                    modulus_length: params.modulusLength,
+                    public_exponent: buffer_source_bytes(&params.publicExponent),
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            (AlgorithmName::RsaOaep, Operation::ImportKey) |
+            (AlgorithmName::RsaPss, Operation::ImportKey) => {
+                let params = convert::<RootedTraceableBox<RsaHashedImportParams>>(cx, value)?;
+                Params::RsaHashedImport {
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            (AlgorithmName::Ecdsa, Operation::GenerateKey) |
+            (AlgorithmName::Ecdh, Operation::GenerateKey) => {
+                let params = convert::<EcKeyGenParams>(cx, value)?;
+                Params::EcKey {
+                    named_curve: named_curve(&params.namedCurve)?,
+                }
+            },
+            (AlgorithmName::Ecdsa, Operation::ImportKey) |
+            (AlgorithmName::Ecdh, Operation::ImportKey) => {
+                let params = convert::<EcKeyImportParams>(cx, value)?;
+                Params::EcKey {
+                    named_curve: named_curve(&params.namedCurve)?,
+                }
+            },
+            (AlgorithmName::Ecdh, Operation::DeriveBits) => {
+                let params = convert::<EcdhKeyDeriveParams>(cx, value)?;
+                Params::Ecdh {
+                    public: params.public.key().clone(),
+                }
+            },
+            (AlgorithmName::Pbkdf2, Operation::DeriveBits) => {
+                let params = convert::<RootedTraceableBox<Pbkdf2Params>>(cx, value)?;
+                Params::Pbkdf2 {
+                    salt: buffer_source_bytes(&params.salt),
+                    iterations: params.iterations,
+                    hash: normalize_hash(cx, &params.hash)?,
+                }
+            },
+            (AlgorithmName::Hkdf, Operation::DeriveBits) => {
+                let params = convert::<RootedTraceableBox<HkdfParams>>(cx, value)?;
+                Params::Hkdf {
+                    hash: normalize_hash(cx, &params.hash)?,
+                    salt: buffer_source_bytes(&params.salt),
+                    info: buffer_source_bytes(&params.info),
+                }
+            },
+            _ => Params::None,
+        }
+    };
+    Ok(NormalizedAlgorithm { name, params })
+}
+
+/// Normalizes the `hash` member of the parameters of an algorithm, which
+/// must be one of the digest algorithms.
+fn normalize_hash(cx: JSContext, hash: &ObjectOrString) -> Fallible<webcrypto::Hash> {
+    let algorithm = normalize_algorithm(cx, hash, Operation::Digest)?;
+    algorithm.name.hash().ok_or(Error::NotSupported)
+}
+
+fn named_curve(name: &DOMString) -> Fallible<NamedCurve> {
+    NamedCurve::from_name(name).ok_or(Error::NotSupported)
+}
@@ -14,11 +14,13 @@ use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::str::USVString;
 use crate::dom::promise::Promise;
+use crate::dom::testworkletglobalscope::TestWorkletTask;
 use crate::dom::window::Window;
 use crate::dom::worklet::Worklet;
 use crate::dom::workletglobalscope::WorkletGlobalScopeType;
+use crate::dom::workletglobalscope::WorkletTask;
 use crate::realms::InRealm;
-use crate::script_thread::ScriptThread;
+use crossbeam_channel::unbounded;
 use dom_struct::dom_struct;
 use std::rc::Rc;
 
@@ -63,9 +65,14 @@ impl TestWorkletMethods for TestWorklet {
     }
 
     fn Lookup(&self, key: DOMString) -> Option<DOMString> {
-        let id = self.worklet.worklet_id();
-        let pool = ScriptThread::worklet_thread_pool();
-        pool.test_worklet_lookup(id, String::from(key))
+        let (sender, receiver) = unbounded();
+        let task = TestWorkletTask::Lookup(String::from(key), sender);
+        self.worklet
+            .executor()
+            .schedule_a_worklet_task(WorkletTask::Test(task));
+        receiver
+            .recv()
+            .expect("Test worklet has died?")
             .map(DOMString::from)
     }
 }
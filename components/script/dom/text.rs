@@ -3,30 +3,46 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
+use crate::dom::bindings::codegen::Bindings::DOMQuadBinding::DOMQuadInit;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::GeometryUtilsBinding::{
+    BoxQuadOptions, ConvertCoordinateOptions,
+};
 use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use crate::dom::bindings::codegen::Bindings::TextBinding::{self, TextMethods};
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::TextOrElementOrDocument as GeometryNode;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{DomRoot, LayoutDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::characterdata::CharacterData;
 use crate::dom::document::Document;
-use crate::dom::node::Node;
+use crate::dom::dompoint::DOMPoint;
+use crate::dom::domquad::DOMQuad;
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::geometryutils;
+use crate::dom::node::{Node, NodeDamage};
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::ops::Range;
 
 /// An HTML text node.
 #[dom_struct]
 pub struct Text {
     characterdata: CharacterData,
+    /// The bytes of the data that the selection of the document covers, which
+    /// layout highlights.
+    selected: Cell<Option<(usize, usize)>>,
 }
 
 impl Text {
     pub fn new_inherited(text: DOMString, document: &Document) -> Text {
         Text {
             characterdata: CharacterData::new_inherited(text, document),
+            selected: Cell::new(None),
         }
     }
 
@@ -43,6 +59,61 @@ impl Text {
         let document = window.Document();
         Ok(Text::new(text, &document))
     }
+
+    /// The offset in the data of the `index`th character that layout renders
+    /// for this node. Collapsible whitespace is rendered as a single space,
+    /// and not at all at the start of the node.
+    pub fn offset_of_rendered_character(&self, index: usize) -> u32 {
+        let collapse = self
+            .upcast::<Node>()
+            .GetParentElement()
+            .and_then(|parent| parent.style())
+            .map_or(true, |style| {
+                !style.get_inherited_text().white_space.preserve_spaces()
+            });
+        let data = self.upcast::<CharacterData>().data();
+        let mut rendered = 0;
+        let mut offset = 0;
+        let mut after_whitespace = collapse;
+        for c in data.chars() {
+            let whitespace = collapse && char::is_ascii_whitespace(&c);
+            if !(whitespace && after_whitespace) {
+                if rendered == index {
+                    break;
+                }
+                rendered += 1;
+            }
+            after_whitespace = whitespace;
+            offset += c.len_utf16() as u32;
+        }
+        offset
+    }
+
+    /// Sets the bytes of the data that the selection of the document covers.
+    pub fn set_selected(&self, selected: Option<Range<usize>>) {
+        let selected = selected
+            .filter(|range| !range.is_empty())
+            .map(|range| (range.start, range.end));
+        if self.selected.get() != selected {
+            self.selected.set(selected);
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+}
+
+pub trait LayoutTextHelpers {
+    #[allow(unsafe_code)]
+    unsafe fn selection_for_layout(&self) -> Option<Range<usize>>;
+}
+
+impl LayoutTextHelpers for LayoutDom<Text> {
+    #[allow(unsafe_code)]
+    unsafe fn selection_for_layout(&self) -> Option<Range<usize>> {
+        (*self.unsafe_get())
+            .selected
+            .get()
+            .map(|(start, end)| start..end)
+    }
 }
 
 impl TextMethods for Text {
@@ -101,4 +172,39 @@ impl TextMethods for Text {
         }
         DOMString::from(text)
     }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-getboxquads
+    fn GetBoxQuads(&self, options: &BoxQuadOptions) -> Fallible<Vec<DomRoot<DOMQuad>>> {
+        geometryutils::get_box_quads(self.upcast(), options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertquadfromnode
+    fn ConvertQuadFromNode(
+        &self,
+        quad: &DOMQuadInit,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMQuad>> {
+        geometryutils::convert_quad_from_node(self.upcast(), quad, &from, options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertrectfromnode
+    fn ConvertRectFromNode(
+        &self,
+        rect: &DOMRectReadOnly,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMQuad>> {
+        geometryutils::convert_rect_from_node(self.upcast(), rect, &from, options)
+    }
+
+    // https://drafts.csswg.org/cssom-view/#dom-geometryutils-convertpointfromnode
+    fn ConvertPointFromNode(
+        &self,
+        point: &DOMPointInit,
+        from: GeometryNode,
+        options: &ConvertCoordinateOptions,
+    ) -> Fallible<DomRoot<DOMPoint>> {
+        geometryutils::convert_point_from_node(self.upcast(), point, &from, options)
+    }
 }
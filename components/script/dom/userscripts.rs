@@ -44,6 +44,7 @@ pub fn load_script(head: &HTMLHeadElement) {
                     &file.to_string_lossy(),
                     rval.handle_mut(),
                     1,
+                    false,
                 );
         }
     }));
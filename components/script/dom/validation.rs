@@ -1,13 +1,151 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
-use crate::dom::validitystate::ValidationFlags;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLElementBinding::HTMLElementMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlfieldsetelement::HTMLFieldSetElement;
+use crate::dom::node::{window_from_node, Node, ShadowIncluding};
+use crate::dom::validitystate::{ValidationFlags, ValidityState};
+use embedder_traits::EmbedderMsg;
+use style::element_state::ElementState;
+
+/// The messages shown to the user for the constraints of a control, in the
+/// order in which they are checked.
+const VALIDATION_MESSAGES: [(ValidationFlags, &str); 9] = [
+    (
+        ValidationFlags::VALUE_MISSING,
+        "Please fill out this field.",
+    ),
+    (
+        ValidationFlags::TYPE_MISMATCH,
+        "Please enter a valid value.",
+    ),
+    (
+        ValidationFlags::PATTERN_MISMATCH,
+        "Please match the requested format.",
+    ),
+    (ValidationFlags::TOO_LONG, "Please shorten this text."),
+    (ValidationFlags::TOO_SHORT, "Please lengthen this text."),
+    (ValidationFlags::RANGE_UNDERFLOW, "The value is too low."),
+    (ValidationFlags::RANGE_OVERFLOW, "The value is too high."),
+    (
+        ValidationFlags::STEP_MISMATCH,
+        "Please enter a value that matches the step.",
+    ),
+    (ValidationFlags::BAD_INPUT, "Please enter a valid value."),
+];
 
 pub trait Validatable {
+    fn as_element(&self) -> &Element;
+
+    /// The validity state of the control, which holds its custom validity
+    /// error message.
+    fn validity_state(&self) -> DomRoot<ValidityState>;
+
+    /// <https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation>
     fn is_instance_validatable(&self) -> bool {
         true
     }
+
+    /// Whether the control satisfies the constraints of `validate_flags`.
     fn validate(&self, _validate_flags: ValidationFlags) -> bool {
         true
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#concept-fv-valid>
+    fn is_valid(&self) -> bool {
+        self.validity_state().custom_error_message().is_empty() &&
+            self.validate(ValidationFlags::all())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#check-validity-steps>
+    fn check_validity(&self) -> bool {
+        if !self.is_instance_validatable() || self.is_valid() {
+            return true;
+        }
+        self.as_element()
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(atom!("invalid"));
+        false
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#report-validity-steps>
+    fn report_validity(&self) -> bool {
+        if !self.is_instance_validatable() || self.is_valid() {
+            return true;
+        }
+        let event = self
+            .as_element()
+            .upcast::<EventTarget>()
+            .fire_cancelable_event(atom!("invalid"));
+        if !event.DefaultPrevented() {
+            report_validation_problem(self.as_element(), self.validation_message());
+        }
+        false
+    }
+
+    /// Updates the `:valid` and `:invalid` states of the element.
+    fn set_validity_states(&self) {
+        let element = self.as_element();
+        let candidate = self.is_instance_validatable();
+        let valid = self.is_valid();
+        element.set_state(ElementState::IN_VALID_STATE, candidate && valid);
+        element.set_state(ElementState::IN_INVALID_STATE, candidate && !valid);
+    }
+
+    /// Updates the `:valid` and `:invalid` states of the element, and the
+    /// ones of its ancestor fieldsets, after its validity may have changed.
+    fn validity_changed(&self) {
+        self.set_validity_states();
+        if let Some(parent) = self.as_element().upcast::<Node>().GetParentNode() {
+            fieldsets_validity_changed(&parent);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage>
+    fn validation_message(&self) -> DOMString {
+        if !self.is_instance_validatable() || self.is_valid() {
+            return DOMString::new();
+        }
+        let custom_error_message = self.validity_state().custom_error_message().clone();
+        if !custom_error_message.is_empty() {
+            return custom_error_message;
+        }
+        VALIDATION_MESSAGES
+            .iter()
+            .find(|&&(flag, _)| !self.validate(flag))
+            .map_or(DOMString::new(), |&(_, message)| DOMString::from(message))
+    }
+}
+
+/// Updates the `:valid` and `:invalid` states of the fieldsets among the
+/// inclusive ancestors of `node`, which depend on the controls they contain.
+pub fn fieldsets_validity_changed(node: &Node) {
+    for fieldset in node
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLFieldSetElement>)
+    {
+        fieldset.set_validity_states();
+    }
+}
+
+/// Focuses a control that doesn't satisfy its constraints and shows the
+/// problem to the user in a bubble next to it.
+pub fn report_validation_problem(element: &Element, message: DOMString) {
+    if let Some(html_element) = element.downcast::<HTMLElement>() {
+        html_element.Focus();
+    }
+    let window = window_from_node(element);
+    let rect = window.device_rect_in_viewport(element.upcast());
+    window.send_to_embedder(EmbedderMsg::ShowValidationMessage(
+        String::from(message),
+        rect,
+    ));
 }
@@ -2,13 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ValidityStateBinding;
 use crate::dom::bindings::codegen::Bindings::ValidityStateBinding::ValidityStateMethods;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
 use crate::dom::element::Element;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
+use std::cell::Ref;
 
 // https://html.spec.whatwg.org/multipage/#validity-states
 #[derive(JSTraceable, MallocSizeOf)]
@@ -47,7 +50,8 @@ bitflags! {
 pub struct ValidityState {
     reflector_: Reflector,
     element: Dom<Element>,
-    state: ValidityStatus,
+    /// <https://html.spec.whatwg.org/multipage/#custom-validity-error-message>
+    custom_error_message: DomRefCell<DOMString>,
 }
 
 impl ValidityState {
@@ -55,7 +59,7 @@ impl ValidityState {
         ValidityState {
             reflector_: Reflector::new(),
             element: Dom::from_ref(element),
-            state: ValidityStatus::Valid,
+            custom_error_message: DomRefCell::new(DOMString::new()),
         }
     }
 
@@ -66,61 +70,79 @@ impl ValidityState {
             ValidityStateBinding::Wrap,
         )
     }
+
+    pub fn custom_error_message(&self) -> Ref<DOMString> {
+        self.custom_error_message.borrow()
+    }
+
+    pub fn set_custom_error_message(&self, error: DOMString) {
+        *self.custom_error_message.borrow_mut() = error;
+        if let Some(validatable) = self.element.as_maybe_validatable() {
+            validatable.validity_changed();
+        }
+    }
+
+    /// Whether the element doesn't satisfy the constraint of `flag`.
+    fn suffers_from(&self, flag: ValidationFlags) -> bool {
+        self.element
+            .as_maybe_validatable()
+            .map_or(false, |validatable| !validatable.validate(flag))
+    }
 }
 
 impl ValidityStateMethods for ValidityState {
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valuemissing
     fn ValueMissing(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::VALUE_MISSING)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-typemismatch
     fn TypeMismatch(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::TYPE_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-patternmismatch
     fn PatternMismatch(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::PATTERN_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-toolong
     fn TooLong(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::TOO_LONG)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-tooshort
     fn TooShort(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::TOO_SHORT)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeunderflow
     fn RangeUnderflow(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::RANGE_UNDERFLOW)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeoverflow
     fn RangeOverflow(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::RANGE_OVERFLOW)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-stepmismatch
     fn StepMismatch(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::STEP_MISMATCH)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-badinput
     fn BadInput(&self) -> bool {
-        false
+        self.suffers_from(ValidationFlags::BAD_INPUT)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-customerror
     fn CustomError(&self) -> bool {
-        false
+        !self.custom_error_message.borrow().is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valid
     fn Valid(&self) -> bool {
-        false
+        !self.CustomError() && !self.suffers_from(ValidationFlags::all())
     }
 }
@@ -20,8 +20,6 @@ use crate::dom::event::{Event, EventBubbles, EventCancelable};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
-use crate::script_runtime::CommonScriptMsg;
-use crate::script_runtime::ScriptThreadEventCategory::WebSocketEvent;
 use crate::task::{TaskCanceller, TaskOnce};
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::TaskSource;
@@ -41,8 +39,17 @@ use script_traits::serializable::BlobImpl;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use std::borrow::ToOwned;
 use std::cell::Cell;
+use std::collections::VecDeque;
 use std::ptr;
 
+/// The amount of data handed to the net thread at once. Past this, messages
+/// are kept here until the connection has written the earlier ones.
+const MAX_BYTES_IN_FLIGHT: u64 = 1 << 20;
+
+/// The number of messages handed to the net thread at once. ws-rs only queues
+/// a few events per connection, and sending into a full queue would block.
+const MAX_MESSAGES_IN_FLIGHT: usize = 4;
+
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 enum WebSocketRequestState {
     Connecting = 0,
@@ -109,11 +116,19 @@ pub struct WebSocket {
     url: ServoUrl,
     ready_state: Cell<WebSocketRequestState>,
     buffered_amount: Cell<u64>,
-    clearing_buffer: Cell<bool>, //Flag to tell if there is a running thread to clear buffered_amount
+    /// Messages sent by script that weren't handed to the net thread yet,
+    /// because too much data is already in flight.
+    pending_messages: DomRefCell<VecDeque<MessageData>>,
+    /// The sizes of the messages handed to the net thread that the connection
+    /// didn't write yet.
+    in_flight_messages: DomRefCell<VecDeque<u64>>,
+    /// A close requested by script, waiting for the pending messages to go out.
+    pending_close: DomRefCell<Option<(Option<u16>, Option<String>)>>,
     #[ignore_malloc_size_of = "Defined in std"]
     sender: IpcSender<WebSocketDomAction>,
     binary_type: Cell<BinaryType>,
-    protocol: DomRefCell<String>, //Subprotocol selected by server
+    protocol: DomRefCell<String>,   //Subprotocol selected by server
+    extensions: DomRefCell<String>, //Extensions negotiated with the server
 }
 
 impl WebSocket {
@@ -123,10 +138,13 @@ impl WebSocket {
             url: url,
             ready_state: Cell::new(WebSocketRequestState::Connecting),
             buffered_amount: Cell::new(0),
-            clearing_buffer: Cell::new(false),
+            pending_messages: DomRefCell::new(VecDeque::new()),
+            in_flight_messages: DomRefCell::new(VecDeque::new()),
+            pending_close: DomRefCell::new(None),
             sender: sender,
             binary_type: Cell::new(BinaryType::Blob),
             protocol: DomRefCell::new("".to_owned()),
+            extensions: DomRefCell::new("".to_owned()),
         }
     }
 
@@ -200,13 +218,15 @@ impl WebSocket {
         ) = ProfiledIpc::channel(global.time_profiler_chan().clone()).unwrap();
 
         let ws = WebSocket::new(global, url_record.clone(), dom_action_sender);
+        global.track_websocket(&ws);
         let address = Trusted::new(&*ws);
 
         // Step 8.
         let request = RequestBuilder::new(url_record)
             .origin(global.origin().immutable().clone())
             .mode(RequestMode::WebSocket { protocols })
-            .referrer(Some(Referrer::NoReferrer));
+            .referrer(Some(Referrer::NoReferrer))
+            .pipeline_id(Some(global.pipeline_id()));
 
         let channels = FetchChannels::WebSocket {
             event_sender: resource_event_sender,
@@ -221,10 +241,14 @@ impl WebSocket {
         ROUTER.add_route(
             dom_event_receiver.to_opaque(),
             Box::new(move |message| match message.to().unwrap() {
-                WebSocketNetworkEvent::ConnectionEstablished { protocol_in_use } => {
+                WebSocketNetworkEvent::ConnectionEstablished {
+                    protocol_in_use,
+                    extensions_in_use,
+                } => {
                     let open_thread = ConnectionEstablishedTask {
                         address: address.clone(),
                         protocol_in_use,
+                        extensions_in_use,
                     };
                     task_source
                         .queue_with_canceller(open_thread, &canceller)
@@ -239,6 +263,14 @@ impl WebSocket {
                         .queue_with_canceller(message_thread, &canceller)
                         .unwrap();
                 },
+                WebSocketNetworkEvent::MessageSent => {
+                    let sent_task = MessageSentTask {
+                        address: address.clone(),
+                    };
+                    task_source
+                        .queue_with_canceller(sent_task, &canceller)
+                        .unwrap();
+                },
                 WebSocketNetworkEvent::Fail => {
                     fail_the_websocket_connection(address.clone(), &task_source, &canceller);
                 },
@@ -268,8 +300,6 @@ impl WebSocket {
             WebSocketRequestState::Closing | WebSocketRequestState::Closed => true,
         };
 
-        let address = Trusted::new(self);
-
         match data_byte_len.checked_add(self.buffered_amount.get()) {
             None => panic!(),
             Some(new_amount) => self.buffered_amount.set(new_amount),
@@ -279,25 +309,77 @@ impl WebSocket {
             return Ok(false);
         }
 
-        if !self.clearing_buffer.get() && self.ready_state.get() == WebSocketRequestState::Open {
-            self.clearing_buffer.set(true);
-
-            let task = Box::new(BufferedAmountTask { address: address });
-
-            let pipeline_id = self.global().pipeline_id();
-            self.global()
-                .script_chan()
-                // TODO: Use a dedicated `websocket-task-source` task source instead.
-                .send(CommonScriptMsg::Task(
-                    WebSocketEvent,
-                    task,
-                    Some(pipeline_id),
-                    WebsocketTaskSource::NAME,
-                ))
-                .unwrap();
+        Ok(true)
+    }
+
+    fn queue_message(&self, message: MessageData) {
+        self.pending_messages.borrow_mut().push_back(message);
+        self.send_pending_messages();
+    }
+
+    /// Hands pending messages to the net thread, as long as the data in flight
+    /// stays within bounds. A single message is always let through, however
+    /// large it is.
+    fn send_pending_messages(&self) {
+        loop {
+            {
+                let in_flight = self.in_flight_messages.borrow();
+                let in_flight_bytes: u64 = in_flight.iter().sum();
+                if in_flight.len() >= MAX_MESSAGES_IN_FLIGHT ||
+                    (!in_flight.is_empty() && in_flight_bytes >= MAX_BYTES_IN_FLIGHT)
+                {
+                    break;
+                }
+            }
+            let message = match self.pending_messages.borrow_mut().pop_front() {
+                Some(message) => message,
+                None => break,
+            };
+            let data_byte_len = match message {
+                MessageData::Text(ref data) => data.len(),
+                MessageData::Binary(ref data) => data.len(),
+            };
+            self.in_flight_messages
+                .borrow_mut()
+                .push_back(data_byte_len as u64);
+            let _ = self.sender.send(WebSocketDomAction::SendMessage(message));
         }
 
-        Ok(true)
+        if self.pending_messages.borrow().is_empty() {
+            if let Some((code, reason)) = self.pending_close.borrow_mut().take() {
+                let _ = self.sender.send(WebSocketDomAction::Close(code, reason));
+            }
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#make-disappear>
+    ///
+    /// Returns whether the connection was still connecting or open.
+    pub fn make_disappear(&self) -> bool {
+        match self.ready_state.get() {
+            WebSocketRequestState::Connecting | WebSocketRequestState::Open => {},
+            WebSocketRequestState::Closing | WebSocketRequestState::Closed => return false,
+        }
+        // No event will be fired at this websocket anymore, so the messages
+        // waiting to be sent can be dropped along with the connection.
+        self.ready_state.set(WebSocketRequestState::Closed);
+        self.pending_messages.borrow_mut().clear();
+        *self.pending_close.borrow_mut() = None;
+        let _ = self.sender.send(WebSocketDomAction::Close(
+            Some(close_code::GOING_AWAY),
+            None,
+        ));
+        true
+    }
+
+    /// Called when the connection wrote the oldest message in flight.
+    fn message_sent(&self) {
+        // https://html.spec.whatwg.org/multipage/#dom-websocket-bufferedamount
+        if let Some(data_byte_len) = self.in_flight_messages.borrow_mut().pop_front() {
+            self.buffered_amount
+                .set(self.buffered_amount.get().saturating_sub(data_byte_len));
+        }
+        self.send_pending_messages();
     }
 
     pub fn origin(&self) -> ImmutableOrigin {
@@ -343,6 +425,11 @@ impl WebSocketMethods for WebSocket {
         self.binary_type.set(btype)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-websocket-extensions
+    fn Extensions(&self) -> DOMString {
+        DOMString::from(self.extensions.borrow().clone())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-websocket-protocol
     fn Protocol(&self) -> DOMString {
         DOMString::from(self.protocol.borrow().clone())
@@ -354,9 +441,7 @@ impl WebSocketMethods for WebSocket {
         let send_data = self.send_impl(data_byte_len)?;
 
         if send_data {
-            let _ = self
-                .sender
-                .send(WebSocketDomAction::SendMessage(MessageData::Text(data.0)));
+            self.queue_message(MessageData::Text(data.0));
         }
 
         Ok(())
@@ -373,9 +458,7 @@ impl WebSocketMethods for WebSocket {
 
         if send_data {
             let bytes = blob.get_bytes().unwrap_or(vec![]);
-            let _ = self
-                .sender
-                .send(WebSocketDomAction::SendMessage(MessageData::Binary(bytes)));
+            self.queue_message(MessageData::Binary(bytes));
         }
 
         Ok(())
@@ -388,9 +471,7 @@ impl WebSocketMethods for WebSocket {
         let send_data = self.send_impl(data_byte_len as u64)?;
 
         if send_data {
-            let _ = self
-                .sender
-                .send(WebSocketDomAction::SendMessage(MessageData::Binary(bytes)));
+            self.queue_message(MessageData::Binary(bytes));
         }
         Ok(())
     }
@@ -402,9 +483,7 @@ impl WebSocketMethods for WebSocket {
         let send_data = self.send_impl(data_byte_len as u64)?;
 
         if send_data {
-            let _ = self
-                .sender
-                .send(WebSocketDomAction::SendMessage(MessageData::Binary(bytes)));
+            self.queue_message(MessageData::Binary(bytes));
         }
         Ok(())
     }
@@ -448,8 +527,10 @@ impl WebSocketMethods for WebSocket {
 
                 // Kick off _Start the WebSocket Closing Handshake_
                 // https://tools.ietf.org/html/rfc6455#section-7.1.2
+                // once the messages sent before are on their way.
                 let reason = reason.map(|reason| reason.0);
-                let _ = self.sender.send(WebSocketDomAction::Close(code, reason));
+                *self.pending_close.borrow_mut() = Some((code, reason));
+                self.send_pending_messages();
             },
         }
         Ok(()) //Return Ok
@@ -461,6 +542,7 @@ impl WebSocketMethods for WebSocket {
 struct ConnectionEstablishedTask {
     address: Trusted<WebSocket>,
     protocol_in_use: Option<String>,
+    extensions_in_use: Option<String>,
 }
 
 impl TaskOnce for ConnectionEstablishedTask {
@@ -468,11 +550,18 @@ impl TaskOnce for ConnectionEstablishedTask {
     fn run_once(self) {
         let ws = self.address.root();
 
+        // The websocket was made to disappear while connecting.
+        if ws.ready_state.get() == WebSocketRequestState::Closed {
+            return;
+        }
+
         // Step 1.
         ws.ready_state.set(WebSocketRequestState::Open);
 
-        // Step 2: Extensions.
-        // TODO: Set extensions to extensions in use.
+        // Step 2.
+        if let Some(extensions) = self.extensions_in_use {
+            *ws.extensions.borrow_mut() = extensions;
+        }
 
         // Step 3.
         if let Some(protocol_name) = self.protocol_in_use {
@@ -484,21 +573,14 @@ impl TaskOnce for ConnectionEstablishedTask {
     }
 }
 
-struct BufferedAmountTask {
+/// Task queued when the connection wrote a message sent by script.
+struct MessageSentTask {
     address: Trusted<WebSocket>,
 }
 
-impl TaskOnce for BufferedAmountTask {
-    // See https://html.spec.whatwg.org/multipage/#dom-websocket-bufferedamount
-    //
-    // To be compliant with standards, we need to reset bufferedAmount only when the event loop
-    // reaches step 1.  In our implementation, the bytes will already have been sent on a background
-    // thread.
+impl TaskOnce for MessageSentTask {
     fn run_once(self) {
-        let ws = self.address.root();
-
-        ws.buffered_amount.set(0);
-        ws.clearing_buffer.set(false);
+        self.address.root().message_sent();
     }
 }
 
@@ -2,16 +2,19 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
+use crate::dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryBinding::HistoryMethods;
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryListBinding::MediaQueryListMethods;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
-    self, FrameRequestCallback, WindowMethods, WindowPostMessageOptions,
+    self, FrameRequestCallback, IdleRequestCallback, IdleRequestOptions, WindowMethods,
+    WindowPostMessageOptions,
 };
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
 use crate::dom::bindings::codegen::UnionTypes::{RequestOrUSVString, StringOrFunction};
@@ -27,26 +30,39 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
 use crate::dom::bindings::weakref::DOMTracker;
 use crate::dom::bluetooth::BluetoothExtraPermissionData;
+use crate::dom::cookiestore::CookieStore;
 use crate::dom::crypto::Crypto;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::customelementregistry::CustomElementRegistry;
-use crate::dom::document::{AnimationFrameCallback, Document};
+use crate::dom::devicemotionevent::DeviceMotionEvent;
+use crate::dom::devicemotioneventacceleration::DeviceMotionEventAcceleration;
+use crate::dom::devicemotioneventrotationrate::DeviceMotionEventRotationRate;
+use crate::dom::deviceorientationevent::{sensor_permissions_granted, DeviceOrientationEvent};
+use crate::dom::document::{AnimationFrameCallback, Document, ElementLookupResult};
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventStatus};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
+use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
+use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::idledeadline::IdleDeadline;
+use crate::dom::layoutshift::LayoutShift;
+use crate::dom::layoutshiftattribution::LayoutShiftAttribution;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
 use crate::dom::mediaquerylistevent::MediaQueryListEvent;
 use crate::dom::messageevent::MessageEvent;
 use crate::dom::navigator::Navigator;
+use crate::dom::node::ShadowIncluding;
 use crate::dom::node::{document_from_node, from_untrusted_node_address, Node, NodeDamage};
 use crate::dom::performance::Performance;
+use crate::dom::performanceentry::PerformanceEntry;
 use crate::dom::promise::Promise;
 use crate::dom::screen::Screen;
 use crate::dom::selection::Selection;
+use crate::dom::speechsynthesis::SpeechSynthesis;
 use crate::dom::storage::Storage;
 use crate::dom::testrunner::TestRunner;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
@@ -64,8 +80,9 @@ use crate::script_thread::{ImageCacheMsg, MainThreadScriptChan, MainThreadScript
 use crate::script_thread::{ScriptThread, SendableMainThreadScriptChan};
 use crate::task_manager::TaskManager;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::timers::{IsInterval, TimerCallback};
+use crate::timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle, TimerCallback};
 use crate::webdriver_handlers::jsval_to_webdriver;
+use crate::window_named_properties;
 use app_units::Au;
 use base64;
 use bluetooth_traits::BluetoothRequest;
@@ -87,7 +104,8 @@ use js::jsapi::{GCReason, JS_GC};
 use js::jsval::UndefinedValue;
 use js::jsval::{JSVal, NullValue};
 use js::rust::wrappers::JS_DefineProperty;
-use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleValue};
+use js::rust::MutableHandleObject;
+use js::rust::{CustomAutoRooter, CustomAutoRooterGuard, HandleObject, HandleValue};
 use media::WindowGLContext;
 use msg::constellation_msg::{BrowsingContextId, PipelineId};
 use net_traits::image_cache::{ImageCache, ImageResponder, ImageResponse};
@@ -99,23 +117,30 @@ use profile_traits::ipc as ProfiledIpc;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::{ProfilerChan as TimeProfilerChan, ProfilerMsg};
 use script_layout_interface::message::{Msg, QueryMsg, Reflow, ReflowGoal, ScriptReflow};
-use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, LayoutRPC};
+use script_layout_interface::rpc::{BoxQuad, BoxQuadsResponse, ContentBoxResponse};
+use script_layout_interface::rpc::{ContentBoxesResponse, LayoutRPC};
 use script_layout_interface::rpc::{
     NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse,
 };
+use script_layout_interface::scrollbar::ScrollbarStyle;
 use script_layout_interface::{PendingImageState, TrustedNodeAddress};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
+use script_traits::LayoutShift as ObservedLayoutShift;
+use script_traits::{ColorScheme, DeviceEmulation, DeviceSensorReading, FrameThrottlingPolicy};
 use script_traits::{ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData};
 use script_traits::{
-    ScriptMsg, ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
+    MsDuration, NavigationType, NetworkStatus, ScriptMsg, ScriptToConstellationChan, ScrollState,
+    StructuredSerializedData, TimerEventId,
 };
 use script_traits::{TimerSchedulerMsg, WebrenderIpcSender, WindowSizeData, WindowSizeType};
 use selectors::attr::CaseSensitivity;
+use servo_atoms::Atom;
 use servo_geometry::{f32_rect_to_au_rect, MaxRect};
 use servo_url::{Host, ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::borrow::ToOwned;
 use std::cell::Cell;
+use std::cmp;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::default::Default;
@@ -123,23 +148,32 @@ use std::env;
 use std::fs;
 use std::io::{stderr, stdout, Write};
 use std::mem;
+use std::ptr::NonNull;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use style::computed_values::scroll_behavior;
 use style::dom::OpaqueNode;
 use style::error_reporting::{ContextualParseError, ParseErrorReporter};
 use style::media_queries;
 use style::parser::ParserContext as CssParserContext;
 use style::properties::{ComputedValues, PropertyId};
 use style::selector_parser::PseudoElement;
+use style::servo::media_queries::PrefersColorScheme;
 use style::str::HTML_SPACE_CHARACTERS;
 use style::stylesheets::CssRuleType;
 use style_traits::{CSSPixel, DevicePixel, ParsingMode};
 use url::Position;
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPixel};
+use webrender_api::units::{
+    DeviceIntPoint, DeviceIntRect, DeviceIntSize, LayoutPixel, LayoutPoint,
+};
 use webrender_api::{DocumentId, ExternalScrollId};
 use webvr_traits::WebVRMsg;
 
+/// The minimum time between two deviceorientation or devicemotion events,
+/// which is about a frame at 60Hz.
+const DEVICE_SENSOR_EVENT_INTERVAL_NS: u64 = 16_000_000;
+
 /// Current state of the window object
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
 enum WindowState {
@@ -170,6 +204,7 @@ pub enum ReflowReason {
     IFrameLoadEvent,
     MissingExplicitReflow,
     ElementStateChanged,
+    DeviceEmulationChanged,
 }
 
 #[dom_struct]
@@ -179,6 +214,8 @@ pub struct Window {
     script_chan: MainThreadScriptChan,
     task_manager: TaskManager,
     navigator: MutNullableDom<Navigator>,
+    speech_synthesis: MutNullableDom<SpeechSynthesis>,
+    cookie_store: MutNullableDom<CookieStore>,
     #[ignore_malloc_size_of = "Arc"]
     image_cache: Arc<dyn ImageCache>,
     #[ignore_malloc_size_of = "channels are hard"]
@@ -191,6 +228,10 @@ pub struct Window {
     performance: MutNullableDom<Performance>,
     navigation_start: Cell<u64>,
     navigation_start_precise: Cell<u64>,
+    /// When the last deviceorientation and devicemotion events were fired, in
+    /// nanoseconds, to throttle them to about one per frame.
+    last_device_orientation_event: Cell<u64>,
+    last_device_motion_event: Cell<u64>,
     screen: MutNullableDom<Screen>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
@@ -226,6 +267,25 @@ pub struct Window {
     /// The current size of the window, in pixels.
     window_size: Cell<WindowSizeData>,
 
+    /// The device that the webview of this window emulates, if any.
+    device_emulation: DomRefCell<Option<DeviceEmulation>>,
+
+    /// Whether the browsing context of this window is visible, otherwise its
+    /// timers are slowed down.
+    is_visible: Cell<bool>,
+
+    /// How this window is throttled because its frame is far from the
+    /// viewport, if it is.
+    frame_throttling: Cell<Option<FrameThrottlingPolicy>>,
+
+    /// The distances from the iframes of the document to the viewport, as
+    /// last reported to the constellation.
+    iframe_viewport_distances: DomRefCell<HashMap<BrowsingContextId, f32>>,
+
+    /// The scroll nodes that were last scrolled smoothly, whose smooth
+    /// scrolls may still be ongoing in the compositor.
+    smooth_scroll_ids: DomRefCell<HashSet<ExternalScrollId>>,
+
     /// A handle for communicating messages to the bluetooth thread.
     #[ignore_malloc_size_of = "channels are hard"]
     bluetooth_thread: IpcSender<BluetoothRequest>,
@@ -332,6 +392,41 @@ pub struct Window {
     /// A mechanism to force the compositor to process events.
     #[ignore_malloc_size_of = "traits are cumbersome"]
     event_loop_waker: Option<Box<dyn EventLoopWaker>>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-idle-callback-identifier>
+    idle_callback_identifier: Cell<u32>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-idle-request-callbacks>
+    idle_request_callbacks: DomRefCell<Vec<IdleRequest>>,
+
+    /// <https://w3c.github.io/requestidlecallback/#dfn-list-of-runnable-idle-callbacks>
+    runnable_idle_callbacks: DomRefCell<Vec<IdleRequest>>,
+}
+
+/// A callback passed to `requestIdleCallback`.
+#[derive(JSTraceable, MallocSizeOf)]
+struct IdleRequest {
+    handle: u32,
+    #[ignore_malloc_size_of = "Rc is hard"]
+    callback: Rc<IdleRequestCallback>,
+    /// The timer that invokes the callback once its timeout expires.
+    timeout: Option<OneshotTimerHandle>,
+}
+
+/// Invokes an idle callback whose timeout expired before an idle period
+/// allowed it to run.
+/// <https://w3c.github.io/requestidlecallback/#invoke-idle-callback-timeout-algorithm>
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct IdleCallbackTimeoutCallback {
+    #[ignore_malloc_size_of = "non-owning"]
+    window: Trusted<Window>,
+    handle: u32,
+}
+
+impl IdleCallbackTimeoutCallback {
+    pub fn invoke(self) {
+        self.window.root().invoke_idle_callback_timeout(self.handle);
+    }
 }
 
 impl Window {
@@ -339,6 +434,73 @@ impl Window {
         &self.task_manager
     }
 
+    /// Whether any idle callbacks are waiting for an idle period.
+    pub fn has_idle_callbacks(&self) -> bool {
+        !self.idle_request_callbacks.borrow().is_empty() ||
+            !self.runnable_idle_callbacks.borrow().is_empty()
+    }
+
+    /// Makes the idle callbacks requested so far runnable in the idle period
+    /// that is starting.
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    pub fn start_idle_period(&self) {
+        // Steps 3-4.
+        let pending = mem::replace(&mut *self.idle_request_callbacks.borrow_mut(), vec![]);
+        self.runnable_idle_callbacks.borrow_mut().extend(pending);
+    }
+
+    /// Invokes the first runnable idle callback if the idle period doesn't end
+    /// before `deadline`, in nanoseconds, and returns whether it did.
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callbacks-algorithm>
+    pub fn invoke_idle_callback(&self, deadline: u64) -> bool {
+        // Step 2.
+        if time::precise_time_ns() >= deadline {
+            return false;
+        }
+        // Step 2.1.
+        let request = {
+            let mut runnable = self.runnable_idle_callbacks.borrow_mut();
+            if runnable.is_empty() {
+                return false;
+            }
+            runnable.remove(0)
+        };
+        // Steps 2.2-2.4.
+        self.invoke_idle_request(request, deadline, false);
+        true
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#invoke-idle-callback-timeout-algorithm>
+    fn invoke_idle_callback_timeout(&self, handle: u32) {
+        // Steps 2-3.
+        if let Some(mut request) = self.take_idle_request(handle) {
+            // The timer that got us here has already fired.
+            request.timeout = None;
+            self.invoke_idle_request(request, time::precise_time_ns(), true);
+        }
+    }
+
+    fn invoke_idle_request(&self, request: IdleRequest, deadline: u64, did_timeout: bool) {
+        if let Some(timeout) = request.timeout {
+            self.upcast::<GlobalScope>().unschedule_callback(timeout);
+        }
+        let deadline = IdleDeadline::new(self, deadline, did_timeout);
+        let _ = request
+            .callback
+            .Call__(&deadline, ExceptionHandling::Report);
+    }
+
+    /// Removes the idle callback with `handle` from whichever list it is in.
+    fn take_idle_request(&self, handle: u32) -> Option<IdleRequest> {
+        for list in &[&self.idle_request_callbacks, &self.runnable_idle_callbacks] {
+            let mut list = list.borrow_mut();
+            if let Some(index) = list.iter().position(|request| request.handle == handle) {
+                return Some(list.remove(index));
+            }
+        }
+        None
+    }
+
     pub fn get_exists_mut_observer(&self) -> bool {
         self.exists_mut_observer.get()
     }
@@ -430,6 +592,94 @@ impl Window {
         })
     }
 
+    /// The window proxies of the document-tree child browsing contexts of
+    /// this window, in tree order.
+    fn child_window_proxies(&self) -> impl Iterator<Item = DomRoot<WindowProxy>> {
+        self.Document()
+            .upcast::<Node>()
+            .traverse_preorder(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLIFrameElement>)
+            .filter_map(|iframe| iframe.GetContentWindow())
+    }
+
+    /// Creates the named properties object of the `Window` interface.
+    #[allow(unsafe_code)]
+    pub unsafe fn create_named_properties_object(
+        cx: JSContext,
+        proto: HandleObject,
+        object: MutableHandleObject,
+    ) {
+        window_named_properties::create(*cx, proto, object)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#named-access-on-the-window-object>
+    #[allow(unsafe_code)]
+    pub fn named_property(&self, name: DOMString) -> Option<NonNull<JSObject>> {
+        #[derive(JSTraceable, MallocSizeOf)]
+        struct NamedElementFilter {
+            name: Atom,
+        }
+        impl CollectionFilter for NamedElementFilter {
+            fn filter(&self, elem: &Element, _root: &Node) -> bool {
+                elem.upcast::<Node>().is_window_named_item(&self.name)
+            }
+        }
+
+        if name.is_empty() {
+            return None;
+        }
+
+        // Step 2.
+        if let Some(window_proxy) = self
+            .child_window_proxies()
+            .find(|window_proxy| window_proxy.get_name() == name)
+        {
+            unsafe {
+                return Some(NonNull::new_unchecked(
+                    window_proxy.reflector().get_jsobject().get(),
+                ));
+            }
+        }
+
+        // Steps 3-4.
+        let document = self.Document();
+        let name = Atom::from(name);
+        match document.look_up_named_elements(&name, Node::is_window_named_item) {
+            ElementLookupResult::None => None,
+            ElementLookupResult::One(element) => unsafe {
+                Some(NonNull::new_unchecked(
+                    element.reflector().get_jsobject().get(),
+                ))
+            },
+            ElementLookupResult::Many => {
+                let filter = NamedElementFilter { name: name };
+                let collection = HTMLCollection::create(self, document.upcast(), Box::new(filter));
+                unsafe {
+                    Some(NonNull::new_unchecked(
+                        collection.reflector().get_jsobject().get(),
+                    ))
+                }
+            },
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-window-nameditem>
+    pub fn supported_property_names(&self) -> Vec<DOMString> {
+        let mut names: Vec<DOMString> = vec![];
+        let child_names = self
+            .child_window_proxies()
+            .map(|window_proxy| window_proxy.get_name());
+        let element_names = self
+            .Document()
+            .supported_property_names_impl(Node::is_window_named_item);
+        for name in child_names.chain(element_names) {
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
     pub fn bluetooth_thread(&self) -> IpcSender<BluetoothRequest> {
         self.bluetooth_thread.clone()
     }
@@ -832,6 +1082,18 @@ impl WindowMethods for Window {
         self.navigator.or_init(|| Navigator::new(self))
     }
 
+    // https://wicg.github.io/speech-api/#dom-window-speechsynthesis
+    fn SpeechSynthesis(&self) -> DomRoot<SpeechSynthesis> {
+        self.speech_synthesis
+            .or_init(|| SpeechSynthesis::new(self.upcast()))
+    }
+
+    // https://wicg.github.io/cookie-store/#dom-window-cookiestore
+    fn CookieStore(&self) -> DomRoot<CookieStore> {
+        self.cookie_store
+            .or_init(|| CookieStore::new(self.upcast()))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowtimers-settimeout
     fn SetTimeout(
         &self,
@@ -947,6 +1209,16 @@ impl WindowMethods for Window {
     // https://html.spec.whatwg.org/multipage/#windoweventhandlers
     window_event_handlers!();
 
+    // https://w3c.github.io/deviceorientation/#dom-window-ondeviceorientation
+    event_handler!(
+        deviceorientation,
+        GetOndeviceorientation,
+        SetOndeviceorientation
+    );
+
+    // https://w3c.github.io/deviceorientation/#dom-window-ondevicemotion
+    event_handler!(devicemotion, GetOndevicemotion, SetOndevicemotion);
+
     // https://developer.mozilla.org/en-US/docs/Web/API/Window/screen
     fn Screen(&self) -> DomRoot<Screen> {
         self.screen.or_init(|| Screen::new(self))
@@ -974,6 +1246,50 @@ impl WindowMethods for Window {
         doc.cancel_animation_frame(ident);
     }
 
+    /// <https://w3c.github.io/requestidlecallback/#the-requestidlecallback-method>
+    fn RequestIdleCallback(
+        &self,
+        callback: Rc<IdleRequestCallback>,
+        options: &IdleRequestOptions,
+    ) -> u32 {
+        // Steps 2-3.
+        let handle = self.idle_callback_identifier.get() + 1;
+        self.idle_callback_identifier.set(handle);
+
+        // Step 5.
+        let timeout = if options.timeout > 0 {
+            let callback = IdleCallbackTimeoutCallback {
+                window: Trusted::new(self),
+                handle,
+            };
+            Some(self.upcast::<GlobalScope>().schedule_callback(
+                OneshotTimerCallback::IdleCallbackTimeout(callback),
+                MsDuration::new(options.timeout as u64),
+            ))
+        } else {
+            None
+        };
+
+        // Step 4.
+        self.idle_request_callbacks.borrow_mut().push(IdleRequest {
+            handle,
+            callback,
+            timeout,
+        });
+
+        // Step 6.
+        handle
+    }
+
+    /// <https://w3c.github.io/requestidlecallback/#the-cancelidlecallback-method>
+    fn CancelIdleCallback(&self, handle: u32) {
+        if let Some(request) = self.take_idle_request(handle) {
+            if let Some(timeout) = request.timeout {
+                self.upcast::<GlobalScope>().unschedule_callback(timeout);
+            }
+        }
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-window-postmessage
     fn PostMessage(
         &self,
@@ -1180,8 +1496,10 @@ impl WindowMethods for Window {
 
     // https://drafts.csswg.org/cssom-view/#dom-window-resizeto
     fn ResizeTo(&self, width: i32, height: i32) {
-        // Step 1
-        //TODO determine if this operation is allowed
+        // Steps 1-2
+        if !self.is_movable_or_resizable() {
+            return;
+        }
         let dpr = self.device_pixel_ratio();
         let size = Size2D::new(width, height).to_f32() * dpr;
         self.send_to_embedder(EmbedderMsg::ResizeTo(size.to_i32()));
@@ -1199,8 +1517,10 @@ impl WindowMethods for Window {
 
     // https://drafts.csswg.org/cssom-view/#dom-window-moveto
     fn MoveTo(&self, x: i32, y: i32) {
-        // Step 1
-        //TODO determine if this operation is allowed
+        // Steps 1-2
+        if !self.is_movable_or_resizable() {
+            return;
+        }
         let dpr = self.device_pixel_ratio();
         let point = Point2D::new(x, y).to_f32() * dpr;
         let msg = EmbedderMsg::MoveTo(point.to_i32());
@@ -1478,33 +1798,71 @@ impl Window {
         }
 
         //TODO Step 11
-        //let document = self.Document();
         // Step 12
+        let root_element = self.Document().GetDocumentElement();
         let global_scope = self.upcast::<GlobalScope>();
         let x = x.to_f32().unwrap_or(0.0f32);
         let y = y.to_f32().unwrap_or(0.0f32);
-        self.update_viewport_for_scroll(x, y);
+        let behavior = self.resolve_scroll_behavior(behavior, root_element.as_deref());
+        if behavior == ScrollBehavior::Instant {
+            self.update_viewport_for_scroll(x, y);
+        }
         self.perform_a_scroll(
             x,
             y,
             global_scope.pipeline_id().root_scroll_id(),
             behavior,
-            None,
+            root_element.as_deref(),
         );
     }
 
+    /// Whether a scroll with the given behavior, of a box with the given
+    /// associated element, is smooth or instant.
+    /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
+    pub fn resolve_scroll_behavior(
+        &self,
+        behavior: ScrollBehavior,
+        element: Option<&Element>,
+    ) -> ScrollBehavior {
+        let smooth = match behavior {
+            ScrollBehavior::Auto => element
+                .and_then(|element| element.style())
+                .map_or(false, |style| {
+                    style.get_box().clone_scroll_behavior() == scroll_behavior::T::Smooth
+                }),
+            ScrollBehavior::Instant => false,
+            ScrollBehavior::Smooth => true,
+        };
+        if smooth {
+            ScrollBehavior::Smooth
+        } else {
+            ScrollBehavior::Instant
+        }
+    }
+
     /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
     pub fn perform_a_scroll(
         &self,
         x: f32,
         y: f32,
         scroll_id: ExternalScrollId,
-        _behavior: ScrollBehavior,
-        _element: Option<&Element>,
+        behavior: ScrollBehavior,
+        element: Option<&Element>,
     ) {
-        // TODO Step 1
-        // TODO(mrobinson, #18709): Add smooth scrolling support to WebRender so that we can
-        // properly process ScrollBehavior here.
+        // Smooth scrolls are animated by the compositor, which reports the
+        // positions it scrolls through like for the scrolls of the user.
+        if self.resolve_scroll_behavior(behavior, element) == ScrollBehavior::Smooth {
+            self.smooth_scroll_ids.borrow_mut().insert(scroll_id);
+            let position = LayoutPoint::new(x, y);
+            self.send_to_constellation(ScriptMsg::ScrollSmoothly(scroll_id, position));
+            return;
+        }
+
+        // Step 1
+        if self.smooth_scroll_ids.borrow_mut().remove(&scroll_id) {
+            self.send_to_constellation(ScriptMsg::AbortSmoothScroll(scroll_id));
+        }
+
         self.layout_chan
             .send(Msg::UpdateScrollStateFromScript(ScrollState {
                 scroll_id,
@@ -1516,13 +1874,45 @@ impl Window {
     pub fn update_viewport_for_scroll(&self, x: f32, y: f32) {
         let size = self.current_viewport.get().size;
         let new_viewport = Rect::new(Point2D::new(Au::from_f32_px(x), Au::from_f32_px(y)), size);
-        self.current_viewport.set(new_viewport)
+        self.current_viewport.set(new_viewport);
+        self.History()
+            .persist_viewport_scroll_position(Vector2D::new(x, y));
+        self.update_iframe_viewport_distances();
     }
 
     pub fn device_pixel_ratio(&self) -> Scale<f32, CSSPixel, DevicePixel> {
         self.window_size.get().device_pixel_ratio
     }
 
+    /// The rect of the content box of a node in the viewport, in device
+    /// pixels, which the embedder shows its popups for the node next to.
+    pub fn device_rect_in_viewport(&self, node: &Node) -> DeviceIntRect {
+        let rect = node.bounding_content_box_or_zero();
+        let viewport = self.current_viewport();
+        let scale = self.device_pixel_ratio().get();
+        DeviceIntRect::new(
+            DeviceIntPoint::new(
+                ((rect.origin.x - viewport.origin.x).to_f32_px() * scale) as i32,
+                ((rect.origin.y - viewport.origin.y).to_f32_px() * scale) as i32,
+            ),
+            DeviceIntSize::new(
+                (rect.size.width.to_f32_px() * scale) as i32,
+                (rect.size.height.to_f32_px() * scale) as i32,
+            ),
+        )
+    }
+
+    /// Whether script may move or resize the window of this browsing context,
+    /// which is only the case for popups opened by script.
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-moveto>
+    fn is_movable_or_resizable(&self) -> bool {
+        self.undiscarded_window_proxy()
+            .map_or(false, |window_proxy| {
+                window_proxy.parent().is_none() && window_proxy.is_auxiliary()
+            })
+    }
+
     fn client_window(&self) -> (Size2D<u32, CSSPixel>, Point2D<i32, CSSPixel>) {
         let timer_profile_chan = self.global().time_profiler_chan().clone();
         let (send, recv) =
@@ -1618,6 +2008,7 @@ impl Window {
             document: self.Document().upcast::<Node>().to_trusted_node_address(),
             stylesheets_changed,
             window_size: self.window_size.get(),
+            prefers_color_scheme: self.prefers_color_scheme(),
             origin: self.origin().immutable().clone(),
             reflow_goal,
             script_join_chan: join_chan,
@@ -1690,9 +2081,44 @@ impl Window {
             ScriptThread::note_newly_transitioning_nodes(complete.newly_transitioning_nodes);
         }
 
+        self.Document()
+            .set_web_font_loads_pending(complete.pending_web_fonts);
+
+        if !complete.layout_shifts.is_empty() {
+            self.report_layout_shifts(complete.layout_shifts);
+        }
+
         true
     }
 
+    /// Delivers the layout shifts that layout observed to the layout shift observers.
+    /// <https://wicg.github.io/layout-instability/#report-the-layout-shift>
+    fn report_layout_shifts(&self, shifts: Vec<ObservedLayoutShift>) {
+        let performance = self.Performance();
+        if !performance.has_observers_for("layout-shift") {
+            return;
+        }
+        let js_runtime = self.js_runtime.borrow();
+        let js_runtime = js_runtime.as_ref().unwrap();
+        for shift in shifts {
+            let sources: Vec<DomRoot<LayoutShiftAttribution>> = shift
+                .sources
+                .into_iter()
+                .map(|source| {
+                    let node = unsafe { from_untrusted_node_address(js_runtime.rt(), source.node) };
+                    LayoutShiftAttribution::new(
+                        self,
+                        &node,
+                        source.previous_rect,
+                        source.current_rect,
+                    )
+                })
+                .collect();
+            let entry = LayoutShift::new(self, shift.time, shift.score, &sources);
+            performance.queue_entry(entry.upcast::<PerformanceEntry>());
+        }
+    }
+
     /// Reflows the page if it's possible to do so and the page is dirty. This
     /// method will wait for the layout thread to complete (but see the `TODO`
     /// below). If there is no window size yet, the page is presumed invisible
@@ -1721,6 +2147,10 @@ impl Window {
                     (!for_display && self.Document().needs_paint()) ||
                     self.suppress_reflow.get()
             );
+
+            if issued_reflow && for_display {
+                self.update_iframe_viewport_distances();
+            }
         } else {
             debug!(
                 "Document doesn't need reflow - skipping it (reason {:?})",
@@ -1794,6 +2224,16 @@ impl Window {
         rects
     }
 
+    /// The border boxes of the fragments of a node, with the transforms
+    /// mapping them to the coordinate space of the initial containing block.
+    pub fn box_quads_query(&self, node: &Node) -> Vec<BoxQuad> {
+        if !self.layout_reflow(QueryMsg::BoxQuadsQuery(node.to_opaque())) {
+            return vec![];
+        }
+        let BoxQuadsResponse(boxes) = self.layout_rpc.box_quads();
+        boxes
+    }
+
     pub fn client_rect_query(&self, node: &Node) -> UntypedRect<i32> {
         if !self.layout_reflow(QueryMsg::ClientRectQuery(node.to_opaque())) {
             return Rect::zero();
@@ -1821,12 +2261,20 @@ impl Window {
             return;
         }
 
+        let element = node.downcast::<Element>();
+        let behavior = self.resolve_scroll_behavior(behavior, element);
+
         // The scroll offsets are immediatly updated since later calls
         // to topScroll and others may access the properties before
-        // webrender has a chance to update the offsets.
-        self.scroll_offsets
-            .borrow_mut()
-            .insert(node.to_opaque(), Vector2D::new(x_ as f32, y_ as f32));
+        // webrender has a chance to update the offsets. Smooth scrolls
+        // update them as they go.
+        if behavior == ScrollBehavior::Instant {
+            self.scroll_offsets
+                .borrow_mut()
+                .insert(node.to_opaque(), Vector2D::new(x_ as f32, y_ as f32));
+            self.History()
+                .persist_element_scroll_position(node, Vector2D::new(x_ as f32, y_ as f32));
+        }
 
         let NodeScrollIdResponse(scroll_id) = self.layout_rpc.node_scroll_id();
 
@@ -1836,7 +2284,7 @@ impl Window {
             y_.to_f32().unwrap_or(0.0f32),
             scroll_id,
             behavior,
-            None,
+            element,
         );
     }
 
@@ -1888,6 +2336,13 @@ impl Window {
         self.layout_rpc.style().0
     }
 
+    pub fn scrollbar_style_query(&self, node: TrustedNodeAddress) -> Option<ScrollbarStyle> {
+        if !self.layout_reflow(QueryMsg::ScrollbarStyleQuery(node)) {
+            return None;
+        }
+        self.layout_rpc.scrollbar_style().0
+    }
+
     pub fn text_index_query(
         &self,
         node: &Node,
@@ -1899,6 +2354,19 @@ impl Window {
         self.layout_rpc.text_index()
     }
 
+    /// The index of the rendered character of the text node `node` that is
+    /// closest to `point`, relative to the initial containing block.
+    pub fn text_index_at_point_query(
+        &self,
+        node: &Node,
+        point: UntypedPoint2D<f32>,
+    ) -> TextIndexResponse {
+        if !self.layout_reflow(QueryMsg::TextIndexAtPointQuery(node.to_opaque(), point)) {
+            return TextIndexResponse(None);
+        }
+        self.layout_rpc.text_index()
+    }
+
     #[allow(unsafe_code)]
     pub fn init_window_proxy(&self, window_proxy: &WindowProxy) {
         assert!(self.window_proxy.get().is_none());
@@ -1941,7 +2409,7 @@ impl Window {
         &self,
         replace: HistoryEntryReplacement,
         force_reload: bool,
-        load_data: LoadData,
+        mut load_data: LoadData,
     ) {
         let doc = self.Document();
         // TODO: Important re security. See https://github.com/servo/servo/issues/23373
@@ -2007,7 +2475,10 @@ impl Window {
                 // then put it in the delaying load events mode.
                 self.window_proxy().start_delaying_load_events_mode();
             }
-            // TODO: step 11, navigationType.
+            // Step 11
+            if force_reload {
+                load_data.navigation_type = NavigationType::Reload;
+            }
             // Step 12, 13
             ScriptThread::navigate(pipeline_id, load_data, replace);
         };
@@ -2026,6 +2497,162 @@ impl Window {
         self.window_size.get()
     }
 
+    /// Makes the window emulate a device, or stop emulating one, and reports
+    /// the media queries that this changes. The new size of the viewport
+    /// comes separately, as a resize.
+    pub fn set_device_emulation(&self, emulation: Option<DeviceEmulation>) {
+        let old_color_scheme = self.prefers_color_scheme();
+        *self.device_emulation.borrow_mut() = emulation;
+        if self.prefers_color_scheme() != old_color_scheme {
+            self.reflow(ReflowGoal::Full, ReflowReason::DeviceEmulationChanged);
+            self.evaluate_media_queries_and_report_changes();
+        }
+    }
+
+    /// The color scheme that the user prefers, or that the emulated device
+    /// prefers.
+    pub fn prefers_color_scheme(&self) -> PrefersColorScheme {
+        let color_scheme = self
+            .device_emulation
+            .borrow()
+            .as_ref()
+            .and_then(|emulation| emulation.prefers_color_scheme);
+        match color_scheme {
+            Some(ColorScheme::Dark) => PrefersColorScheme::Dark,
+            Some(ColorScheme::Light) | None => PrefersColorScheme::Light,
+        }
+    }
+
+    /// The number of simultaneous touch points of the emulated device, which
+    /// is zero otherwise.
+    pub fn max_touch_points(&self) -> u32 {
+        self.device_emulation
+            .borrow()
+            .as_ref()
+            .map_or(0, |emulation| emulation.max_touch_points)
+    }
+
+    /// Whether the `ontouch*` event handlers are exposed, which they are to
+    /// the windows emulating a touch device when they are created.
+    #[allow(unsafe_code)]
+    pub fn touch_events_exposed(_: JSContext, obj: HandleObject) -> bool {
+        let global = unsafe { GlobalScope::from_object(obj.get()) };
+        global
+            .downcast::<Window>()
+            .map_or(false, |window| window.max_touch_points() > 0)
+    }
+
+    /// Fires `change` at `screen.orientation` if the embedder rotated the
+    /// screen.
+    pub fn update_screen_orientation(&self) {
+        if let Some(screen) = self.screen.get() {
+            screen.update_orientation();
+        }
+    }
+
+    /// Fires `online` or `offline` if the network was lost or regained, and
+    /// `change` at `navigator.connection` if the connection changed.
+    /// https://html.spec.whatwg.org/multipage/#dom-navigator-online
+    pub fn update_network_status(&self, old: NetworkStatus, new: NetworkStatus) {
+        if old.online != new.online {
+            let name = if new.online {
+                atom!("online")
+            } else {
+                atom!("offline")
+            };
+            self.upcast::<EventTarget>().fire_event(name);
+        }
+        let connection_changed = old.effective_type != new.effective_type ||
+            old.downlink != new.downlink ||
+            old.rtt != new.rtt ||
+            old.save_data != new.save_data;
+        if connection_changed {
+            if let Some(navigator) = self.navigator.get() {
+                navigator.update_connection();
+            }
+        }
+    }
+
+    /// Fires the deviceorientation or devicemotion event of a reading of the
+    /// sensors of the device, unless an event of the same kind was fired less
+    /// than a frame ago or the user denied the use of the sensors.
+    /// <https://w3c.github.io/deviceorientation/#deviceorientation-model>
+    /// <https://w3c.github.io/deviceorientation/#devicemotion-model>
+    pub fn handle_device_sensor_reading(&self, reading: DeviceSensorReading) {
+        if !pref!(dom.deviceorientation.enabled) || !self.Document().is_fully_active() {
+            return;
+        }
+        let (last_event, absolute) = match reading {
+            DeviceSensorReading::Orientation(ref orientation) => {
+                (&self.last_device_orientation_event, orientation.absolute)
+            },
+            DeviceSensorReading::Motion(_) => (&self.last_device_motion_event, false),
+        };
+        if !sensor_permissions_granted(self.upcast(), absolute) {
+            return;
+        }
+        let now = time::precise_time_ns();
+        if now < last_event.get() + DEVICE_SENSOR_EVENT_INTERVAL_NS {
+            return;
+        }
+        last_event.set(now);
+
+        let finite = |value: Option<f64>| value.filter(|value| value.is_finite());
+        let event = match reading {
+            DeviceSensorReading::Orientation(orientation) => {
+                DomRoot::upcast::<Event>(DeviceOrientationEvent::new(
+                    self,
+                    atom!("deviceorientation"),
+                    false,
+                    false,
+                    finite(orientation.alpha),
+                    finite(orientation.beta),
+                    finite(orientation.gamma),
+                    orientation.absolute,
+                ))
+            },
+            DeviceSensorReading::Motion(motion) => {
+                let acceleration = |values: Option<[f64; 3]>| {
+                    values.map(|[x, y, z]| {
+                        DeviceMotionEventAcceleration::new(
+                            self,
+                            finite(Some(x)),
+                            finite(Some(y)),
+                            finite(Some(z)),
+                        )
+                    })
+                };
+                let acceleration_including_gravity =
+                    acceleration(motion.acceleration_including_gravity);
+                let acceleration = acceleration(motion.acceleration);
+                let rotation_rate = motion.rotation_rate.map(|[alpha, beta, gamma]| {
+                    DeviceMotionEventRotationRate::new(
+                        self,
+                        finite(Some(alpha)),
+                        finite(Some(beta)),
+                        finite(Some(gamma)),
+                    )
+                });
+                // The events can't be fired more often than they are throttled
+                // to, whatever the interval of the readings is.
+                let interval = motion
+                    .interval
+                    .max(DEVICE_SENSOR_EVENT_INTERVAL_NS as f64 / 1_000_000.);
+                DomRoot::upcast::<Event>(DeviceMotionEvent::new(
+                    self,
+                    atom!("devicemotion"),
+                    false,
+                    false,
+                    acceleration.as_deref(),
+                    acceleration_including_gravity.as_deref(),
+                    rotation_rate.as_deref(),
+                    interval,
+                ))
+            },
+        };
+        event.fire(self.upcast());
+    }
+
     pub fn get_url(&self) -> ServoUrl {
         self.Document().url()
     }
@@ -2185,10 +2812,85 @@ impl Window {
 
     /// Slow down/speed up timers based on visibility.
     pub fn alter_resource_utilization(&self, visible: bool) {
-        if visible {
-            self.upcast::<GlobalScope>().speed_up_timers();
+        self.is_visible.set(visible);
+        self.update_timer_min_duration();
+        self.upcast::<GlobalScope>()
+            .set_timers_in_background(!visible);
+    }
+
+    /// Clamps the timers to the greatest of the minimum durations that the
+    /// visibility and the throttling of the window impose, if any.
+    fn update_timer_min_duration(&self) {
+        let hidden_min_duration = if self.is_visible.get() {
+            None
         } else {
-            self.upcast::<GlobalScope>().slow_down_timers();
+            Some(pref!(js.timers.minimum_duration) as u64)
+        };
+        let throttled_min_duration = self
+            .frame_throttling
+            .get()
+            .and_then(|policy| policy.timer_min_duration);
+        match cmp::max(hidden_min_duration, throttled_min_duration) {
+            Some(duration) => self
+                .upcast::<GlobalScope>()
+                .slow_down_timers(MsDuration::new(duration)),
+            None => self.upcast::<GlobalScope>().speed_up_timers(),
+        }
+    }
+
+    /// Throttles the window because its frame is far from the viewport, or
+    /// stops throttling it.
+    pub fn set_frame_throttling(&self, policy: Option<FrameThrottlingPolicy>) {
+        let were_animation_frames_suspended = self.animation_frames_suspended();
+        self.frame_throttling.set(policy);
+        self.update_timer_min_duration();
+        if self.animation_frames_suspended() != were_animation_frames_suspended {
+            self.Document().animation_frames_suspension_changed();
+        }
+    }
+
+    /// Whether the animation frame callbacks wait until the frame of the
+    /// window gets close to the viewport again.
+    pub fn animation_frames_suspended(&self) -> bool {
+        self.frame_throttling
+            .get()
+            .map_or(false, |policy| policy.suspend_animation_frames)
+    }
+
+    /// Tells the constellation how far from the viewport the iframes of the
+    /// document are, for the ones whose distance changed since the last time,
+    /// so that it can throttle the ones far from it.
+    fn update_iframe_viewport_distances(&self) {
+        if !pref!(dom.frame_throttling.enabled) {
+            return;
+        }
+        let viewport = self.current_viewport.get();
+        let distances: HashMap<_, _> = self
+            .Document()
+            .iter_iframes()
+            .filter_map(|iframe| {
+                let browsing_context_id = iframe.browsing_context_id()?;
+                // An iframe that isn't rendered is never in the viewport.
+                let distance = iframe
+                    .upcast::<Node>()
+                    .bounding_content_box()
+                    .map_or(std::f32::INFINITY, |rect| {
+                        viewport_distance(&viewport, &rect)
+                    });
+                Some((browsing_context_id, distance))
+            })
+            .collect();
+        let changes: Vec<_> = {
+            let old_distances = self.iframe_viewport_distances.borrow();
+            distances
+                .iter()
+                .filter(|&(id, distance)| old_distances.get(id) != Some(distance))
+                .map(|(id, distance)| (*id, *distance))
+                .collect()
+        };
+        *self.iframe_viewport_distances.borrow_mut() = distances;
+        if !changes.is_empty() {
+            self.send_to_constellation(ScriptMsg::IFrameViewportDistances(changes));
         }
     }
 
@@ -2239,6 +2941,7 @@ impl Window {
         pipelineid: PipelineId,
         parent_info: Option<PipelineId>,
         window_size: WindowSizeData,
+        device_emulation: Option<DeviceEmulation>,
         origin: MutableOrigin,
         navigation_start: u64,
         navigation_start_precise: u64,
@@ -2287,6 +2990,8 @@ impl Window {
             image_cache_chan,
             image_cache,
             navigator: Default::default(),
+            speech_synthesis: Default::default(),
+            cookie_store: Default::default(),
             location: Default::default(),
             history: Default::default(),
             custom_element_registry: Default::default(),
@@ -2295,6 +3000,8 @@ impl Window {
             performance: Default::default(),
             navigation_start: Cell::new(navigation_start),
             navigation_start_precise: Cell::new(navigation_start_precise),
+            last_device_orientation_event: Cell::new(0),
+            last_device_motion_event: Cell::new(0),
             screen: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
@@ -2309,6 +3016,11 @@ impl Window {
             layout_chan,
             layout_rpc,
             window_size: Cell::new(window_size),
+            device_emulation: DomRefCell::new(device_emulation),
+            is_visible: Cell::new(true),
+            frame_throttling: Cell::new(None),
+            iframe_viewport_distances: Default::default(),
+            smooth_scroll_ids: Default::default(),
             current_viewport: Cell::new(Rect::zero()),
             suppress_reflow: Cell::new(true),
             pending_reflow_count: Default::default(),
@@ -2339,6 +3051,9 @@ impl Window {
             replace_surrogates,
             player_context,
             event_loop_waker,
+            idle_callback_identifier: Cell::new(0),
+            idle_request_callbacks: Default::default(),
+            runnable_idle_callbacks: Default::default(),
         });
 
         unsafe { WindowBinding::Wrap(JSContext::from_ptr(runtime.cx()), win) }
@@ -2373,6 +3088,20 @@ fn should_move_clip_rect(clip_rect: UntypedRect<Au>, new_viewport: UntypedRect<f
         (clip_rect.max_y() - new_viewport.max_y()).abs() <= viewport_scroll_margin.height
 }
 
+/// The distance between a rectangle and the viewport along the axis where they
+/// are the furthest apart, in CSS pixels, which is zero when they intersect.
+fn viewport_distance(viewport: &UntypedRect<Au>, rect: &UntypedRect<Au>) -> f32 {
+    let horizontal = cmp::max(
+        viewport.min_x() - rect.max_x(),
+        rect.min_x() - viewport.max_x(),
+    );
+    let vertical = cmp::max(
+        viewport.min_y() - rect.max_y(),
+        rect.min_y() - viewport.max_y(),
+    );
+    cmp::max(cmp::max(horizontal, vertical), Au(0)).to_f32_px()
+}
+
 fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &ReflowReason) {
     let mut debug_msg = format!("**** pipeline={}", id);
     debug_msg.push_str(match *reflow_goal {
@@ -2381,6 +3110,7 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
         ReflowGoal::LayoutQuery(ref query_msg, _) => match query_msg {
             &QueryMsg::ContentBoxQuery(_n) => "\tContentBoxQuery",
             &QueryMsg::ContentBoxesQuery(_n) => "\tContentBoxesQuery",
+            &QueryMsg::BoxQuadsQuery(_n) => "\tBoxQuadsQuery",
             &QueryMsg::NodesFromPointQuery(..) => "\tNodesFromPointQuery",
             &QueryMsg::ClientRectQuery(_n) => "\tClientRectQuery",
             &QueryMsg::NodeScrollGeometryQuery(_n) => "\tNodeScrollGeometryQuery",
@@ -2388,7 +3118,9 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::ResolvedStyleQuery(_, _, _) => "\tResolvedStyleQuery",
             &QueryMsg::OffsetParentQuery(_n) => "\tOffsetParentQuery",
             &QueryMsg::StyleQuery(_n) => "\tStyleQuery",
+            &QueryMsg::ScrollbarStyleQuery(_n) => "\tScrollbarStyleQuery",
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
+            &QueryMsg::TextIndexAtPointQuery(..) => "\tTextIndexAtPointQuery",
             &QueryMsg::ElementInnerTextQuery(_) => "\tElementInnerTextQuery",
             &QueryMsg::InnerWindowDimensionsQuery(_) => "\tInnerWindowDimensionsQuery",
         },
@@ -2415,6 +3147,7 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
         ReflowReason::IFrameLoadEvent => "\tIFrameLoadEvent",
         ReflowReason::MissingExplicitReflow => "\tMissingExplicitReflow",
         ReflowReason::ElementStateChanged => "\tElementStateChanged",
+        ReflowReason::DeviceEmulationChanged => "\tDeviceEmulationChanged",
     });
 
     println!("{}", debug_msg);
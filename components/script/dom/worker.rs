@@ -5,6 +5,7 @@
 use crate::dom::abstractworker::SimpleWorkerErrorHandler;
 use crate::dom::abstractworker::WorkerScriptMsg;
 use crate::dom::bindings::codegen::Bindings::MessagePortBinding::PostMessageOptions;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::{WorkerMethods, WorkerOptions};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
@@ -21,6 +22,7 @@ use crate::dom::dedicatedworkerglobalscope::{
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::messageevent::MessageEvent;
+use crate::dom::window::Window;
 use crate::dom::workerglobalscope::prepare_workerscope_init;
 use crate::realms::enter_realm;
 use crate::script_runtime::JSContext;
@@ -51,6 +53,8 @@ pub struct Worker {
     #[ignore_malloc_size_of = "Arc"]
     closing: Arc<AtomicBool>,
     terminated: Cell<bool>,
+    /// Whether the worker got an animation frame that it hasn't run yet.
+    animation_frame_pending: Cell<bool>,
 }
 
 impl Worker {
@@ -60,6 +64,7 @@ impl Worker {
             sender: sender,
             closing: closing,
             terminated: Cell::new(false),
+            animation_frame_pending: Cell::new(false),
         }
     }
 
@@ -131,6 +136,7 @@ impl Worker {
             worker_options.type_,
             closing,
             global.image_cache(),
+            global.is::<Window>(),
         );
 
         Ok(worker)
@@ -140,6 +146,35 @@ impl Worker {
         self.terminated.get()
     }
 
+    /// Whether the worker is closing, because it was terminated or closed
+    /// itself.
+    pub fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+    }
+
+    /// Called once the worker ran the callbacks of an animation frame or
+    /// got new ones to run, with whether it waits for the next frame.
+    pub fn set_wants_animation_frames(address: TrustedWorkerAddress, wants: bool) {
+        let worker = address.root();
+        worker.animation_frame_pending.set(false);
+        if let Some(window) = worker.global().downcast::<Window>() {
+            window
+                .Document()
+                .set_worker_wants_animation_frames(&worker, wants && !worker.is_closing());
+        }
+    }
+
+    /// Gives an animation frame to the worker, unless it is still running
+    /// the callbacks of the previous one.
+    pub fn send_animation_frame(&self) {
+        if self.animation_frame_pending.replace(true) {
+            return;
+        }
+        let _ = self
+            .sender
+            .send(DedicatedWorkerScriptMsg::AnimationFrame(Trusted::new(self)));
+    }
+
     pub fn handle_message(address: TrustedWorkerAddress, data: StructuredSerializedData) {
         let worker = address.root();
 
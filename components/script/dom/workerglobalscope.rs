@@ -76,6 +76,7 @@ pub fn prepare_workerscope_init(
         origin: global.origin().immutable().clone(),
         is_headless: global.is_headless(),
         user_agent: global.get_user_agent(),
+        user_agent_override: global.user_agent_override(),
     };
 
     init
@@ -124,7 +125,7 @@ impl WorkerGlobalScope {
     ) -> Self {
         // Install a pipeline-namespace in the current thread.
         PipelineNamespace::auto_install();
-        Self {
+        let scope = Self {
             globalscope: GlobalScope::new_inherited(
                 init.pipeline_id,
                 init.to_devtools_sender,
@@ -150,7 +151,11 @@ impl WorkerGlobalScope {
             from_devtools_receiver,
             navigation_start_precise: precise_time_ns(),
             performance: Default::default(),
-        }
+        };
+        scope
+            .globalscope
+            .set_user_agent_override(init.user_agent_override);
+        scope
     }
 
     pub fn runtime_handle(&self) -> ParentRuntime {
@@ -10,6 +10,7 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::gpu::GPU;
 use crate::dom::navigatorinfo;
 use crate::dom::permissions::Permissions;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use dom_struct::dom_struct;
 
@@ -19,6 +20,7 @@ pub struct WorkerNavigator {
     reflector_: Reflector,
     permissions: MutNullableDom<Permissions>,
     gpu: MutNullableDom<GPU>,
+    storage: MutNullableDom<StorageManager>,
 }
 
 impl WorkerNavigator {
@@ -27,6 +29,7 @@ impl WorkerNavigator {
             reflector_: Reflector::new(),
             permissions: Default::default(),
             gpu: Default::default(),
+            storage: Default::default(),
         }
     }
 
@@ -77,7 +80,9 @@ impl WorkerNavigatorMethods for WorkerNavigator {
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-platform
     fn Platform(&self) -> DOMString {
-        navigatorinfo::Platform()
+        self.global()
+            .get_platform()
+            .map_or_else(navigatorinfo::Platform, DOMString::from)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-useragent
@@ -105,4 +110,9 @@ impl WorkerNavigatorMethods for WorkerNavigator {
     fn Gpu(&self) -> DomRoot<GPU> {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
 }
@@ -26,7 +26,6 @@ use crate::dom::bindings::trace::JSTraceable;
 use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
-use crate::dom::testworkletglobalscope::TestWorkletTask;
 use crate::dom::window::Window;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
@@ -41,7 +40,7 @@ use crate::script_runtime::ScriptThreadEventCategory;
 use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::task::TaskBox;
 use crate::task_source::TaskSourceName;
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
 use dom_struct::dom_struct;
 use js::jsapi::JSGCParamKey;
 use js::jsapi::JSTracer;
@@ -52,6 +51,7 @@ use net_traits::request::Destination;
 use net_traits::request::RequestBuilder;
 use net_traits::request::RequestMode;
 use net_traits::IpcSend;
+use servo_config::pref;
 use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
 use std::cmp::max;
@@ -62,6 +62,7 @@ use std::sync::atomic::AtomicIsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use style::thread_state::{self, ThreadState};
 use swapper::swapper;
 use swapper::Swapper;
@@ -103,6 +104,12 @@ impl Worklet {
         self.worklet_id
     }
 
+    /// An executor which schedules tasks for this worklet on the primary
+    /// worklet thread.
+    pub fn executor(&self) -> WorkletExecutor {
+        ScriptThread::worklet_thread_pool().executor(self.worklet_id)
+    }
+
     #[allow(dead_code)]
     pub fn worklet_global_scope_type(&self) -> WorkletGlobalScopeType {
         self.global_type
@@ -338,12 +345,9 @@ impl WorkletThreadPool {
         self.wake_threads();
     }
 
-    /// For testing.
-    pub fn test_worklet_lookup(&self, id: WorkletId, key: String) -> Option<String> {
-        let (sender, receiver) = unbounded();
-        let msg = WorkletData::Task(id, WorkletTask::Test(TestWorkletTask::Lookup(key, sender)));
-        let _ = self.primary_sender.send(msg);
-        receiver.recv().expect("Test worklet has died?")
+    /// An executor which schedules tasks for the given worklet on the primary thread.
+    pub(crate) fn executor(&self, worklet_id: WorkletId) -> WorkletExecutor {
+        WorkletExecutor::new(worklet_id, self.primary_sender.clone())
     }
 
     fn wake_threads(&self) {
@@ -756,4 +760,19 @@ impl WorkletExecutor {
             .primary_sender
             .send(WorkletData::Task(self.worklet_id, task));
     }
+
+    /// Schedule a worklet task which replies on the sender that `task` is
+    /// given, and wait for the reply for at most `dom.worklet.timeout_ms`.
+    /// This is for the tasks that another thread blocks on, like drawing a
+    /// paint image or rendering an audio quantum, which must not hang when
+    /// a worklet script is slow.
+    pub fn perform_a_worklet_task_with_timeout<T, F>(&self, task: F) -> Result<T, RecvTimeoutError>
+    where
+        F: FnOnce(Sender<T>) -> WorkletTask,
+    {
+        let (sender, receiver) = unbounded();
+        self.schedule_a_worklet_task(task(sender));
+        let timeout = pref!(dom.worklet.timeout_ms) as u64;
+        receiver.recv_timeout(Duration::from_millis(timeout))
+    }
 }
@@ -91,19 +91,13 @@ impl WorkletGlobalScope {
             .evaluate_js_on_global_with_result(&*script, rval.handle_mut())
     }
 
-    /// Register a paint worklet to the script thread.
-    pub fn register_paint_worklet(
-        &self,
-        name: Atom,
-        properties: Vec<Atom>,
-        painter: Box<dyn Painter>,
-    ) {
+    /// Send something that the worklet code registered to the script thread,
+    /// where the window of the worklet makes it available.
+    pub fn register(&self, registration: WorkletRegistration) {
         self.to_script_thread_sender
-            .send(MainThreadScriptMsg::RegisterPaintWorklet {
+            .send(MainThreadScriptMsg::WorkletRegistration {
                 pipeline_id: self.globalscope.pipeline_id(),
-                name,
-                properties,
-                painter,
+                registration,
             })
             .expect("Worklet thread outlived script thread.");
     }
@@ -196,6 +190,18 @@ impl WorkletGlobalScopeType {
     }
 }
 
+/// Something registered by the code running in a worklet global, which every
+/// global of the worklet registers in turn.
+#[derive(Debug)]
+pub enum WorkletRegistration {
+    /// <https://drafts.css-houdini.org/css-paint-api/#dom-paintworkletglobalscope-registerpaint>
+    Paint {
+        name: Atom,
+        properties: Vec<Atom>,
+        painter: Box<dyn Painter>,
+    },
+}
+
 /// A task which can be performed in the context of a worklet global.
 pub enum WorkletTask {
     Test(TestWorkletTask),
@@ -19,6 +19,7 @@ use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::{is_token, ByteString, DOMString, USVString};
 use crate::dom::blob::{normalize_type_string, Blob};
+use crate::dom::console::Console;
 use crate::dom::document::DocumentSource;
 use crate::dom::document::{Document, HasBrowsingContext, IsHTMLDocument};
 use crate::dom::event::{Event, EventBubbles, EventCancelable};
@@ -68,6 +69,7 @@ use net_traits::{ResourceFetchTiming, ResourceTimingType};
 use script_traits::serializable::BlobImpl;
 use script_traits::DocumentActivity;
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_url::ServoUrl;
 use std::borrow::ToOwned;
 use std::cell::Cell;
@@ -104,6 +106,8 @@ struct XHRContext {
 
 #[derive(Clone)]
 pub enum XHRProgress {
+    /// Notify that the request body has been transmitted
+    RequestEndOfBody(GenerationId),
     /// Notify that headers have been received
     HeadersReceived(GenerationId, Option<HeaderMap>, Option<(u16, Vec<u8>)>),
     /// Partial progress (after receiving headers), containing portion of the response
@@ -117,6 +121,7 @@ pub enum XHRProgress {
 impl XHRProgress {
     fn generation_id(&self) -> GenerationId {
         match *self {
+            XHRProgress::RequestEndOfBody(id) |
             XHRProgress::HeadersReceived(id, _, _) |
             XHRProgress::Loading(id, _) |
             XHRProgress::Done(id) |
@@ -147,7 +152,6 @@ pub struct XMLHttpRequest {
     response_headers: DomRefCell<HeaderMap>,
     #[ignore_malloc_size_of = "Defined in hyper"]
     override_mime_type: DomRefCell<Option<Mime>>,
-    override_charset: DomRefCell<Option<&'static Encoding>>,
 
     // Associated concepts
     #[ignore_malloc_size_of = "Defined in hyper"]
@@ -158,6 +162,7 @@ pub struct XMLHttpRequest {
     request_body_len: Cell<usize>,
     sync: Cell<bool>,
     upload_complete: Cell<bool>,
+    upload_listener: Cell<bool>,
     send_flag: Cell<bool>,
 
     timeout_cancel: DomRefCell<Option<OneshotTimerHandle>>,
@@ -196,7 +201,6 @@ impl XMLHttpRequest {
             response_json: Heap::default(),
             response_headers: DomRefCell::new(HeaderMap::new()),
             override_mime_type: DomRefCell::new(None),
-            override_charset: DomRefCell::new(None),
 
             request_method: DomRefCell::new(Method::GET),
             request_url: DomRefCell::new(None),
@@ -204,6 +208,7 @@ impl XMLHttpRequest {
             request_body_len: Cell::new(0),
             sync: Cell::new(false),
             upload_complete: Cell::new(false),
+            upload_listener: Cell::new(false),
             send_flag: Cell::new(false),
 
             timeout_cancel: DomRefCell::new(None),
@@ -242,11 +247,14 @@ impl XMLHttpRequest {
     ) {
         impl FetchResponseListener for XHRContext {
             fn process_request_body(&mut self) {
-                // todo
+                // The body is transmitted in one go, its end is what
+                // the upload progress is reported for.
             }
 
             fn process_request_eof(&mut self) {
-                // todo
+                self.xhr
+                    .root()
+                    .process_partial_response(XHRProgress::RequestEndOfBody(self.gen_id));
             }
 
             fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>) {
@@ -430,6 +438,14 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
                 *self.status_text.borrow_mut() = ByteString::new(vec![]);
                 self.status.set(0);
 
+                if self.sync_in_window() {
+                    self.global().report_deprecation(
+                        "XMLHttpRequestSynchronousInNonWorkerOutsideBeforeUnload",
+                        "Synchronous XMLHttpRequest on the main thread is deprecated because of \
+                         its detrimental effects to the end user's experience.",
+                    );
+                }
+
                 // Step 13
                 if self.ready_state.get() != XMLHttpRequestState::Opened {
                     self.change_ready_state(XMLHttpRequestState::Opened);
@@ -606,6 +622,20 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
         // Step 8
         self.send_flag.set(true);
 
+        // https://xhr.spec.whatwg.org/#upload-listener-flag
+        let has_handlers = self.upload.upcast::<EventTarget>().has_handlers();
+        self.upload_listener.set(has_handlers);
+
+        if self.sync_in_window() && pref!(dom.xhr.sync_deprecation_warning.enabled) {
+            Console::internal_warn(
+                &*self.global(),
+                DOMString::from(
+                    "Synchronous XMLHttpRequest on the main thread is deprecated \
+                     because of its detrimental effects to the end user's experience.",
+                ),
+            );
+        }
+
         // Step 9
         if !self.sync.get() {
             // If one of the event handlers below aborts the fetch by calling
@@ -617,7 +647,7 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
                 return Ok(());
             }
             // Substep 2
-            if !self.upload_complete.get() {
+            if !self.upload_complete.get() && self.upload_listener.get() {
                 self.dispatch_upload_progress_event(atom!("loadstart"), Ok(Some(0)));
                 if self.generation_id.get() != gen_id {
                     return Ok(());
@@ -627,7 +657,6 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
 
         // Step 5
         //TODO - set referrer_policy/referrer_url in request
-        let has_handlers = self.upload.upcast::<EventTarget>().has_handlers();
         let credentials_mode = if self.with_credentials.get() {
             CredentialsMode::Include
         } else {
@@ -837,22 +866,11 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
             },
             _ => {},
         }
-        // Step 2
-        let override_mime = mime.parse::<Mime>().map_err(|_| Error::Syntax)?;
-        // Step 3
-        let mime_str = override_mime.as_ref();
-        let mime_parts: Vec<&str> = mime_str.split(";").collect();
-        let mime_no_params = if mime_parts.len() > 1 {
-            mime_parts[0].parse().unwrap()
-        } else {
-            override_mime.clone()
-        };
-
-        *self.override_mime_type.borrow_mut() = Some(mime_no_params);
-        // Step 4
-        let value = override_mime.get_param(mime::CHARSET);
-        *self.override_charset.borrow_mut() =
-            value.and_then(|value| Encoding::for_label(value.as_ref().as_bytes()));
+        // Steps 2-3
+        let override_mime = mime
+            .parse::<Mime>()
+            .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+        *self.override_mime_type.borrow_mut() = Some(override_mime);
         Ok(())
     }
 
@@ -1052,21 +1070,18 @@ impl XMLHttpRequest {
         }
 
         match progress {
+            XHRProgress::RequestEndOfBody(_) => {
+                self.process_request_end_of_body();
+            },
             XHRProgress::HeadersReceived(_, headers, status) => {
                 assert!(self.ready_state.get() == XMLHttpRequestState::Opened);
                 // For synchronous requests, this should not fire any events, and just store data
-                // XXXManishearth Find a way to track partial progress of the send (onprogresss for XHRUpload)
 
-                // Part of step 13, send() (processing request end of file)
-                // Substep 1
-                self.upload_complete.set(true);
-                // Substeps 2-4
-                if !self.sync.get() {
-                    self.dispatch_upload_progress_event(atom!("progress"), Ok(None));
-                    return_if_fetch_was_terminated!();
-                    self.dispatch_upload_progress_event(atom!("load"), Ok(None));
-                    return_if_fetch_was_terminated!();
-                    self.dispatch_upload_progress_event(atom!("loadend"), Ok(None));
+                // The net stack only reports the end of the request body for
+                // http(s) requests with a body, it is reached with the response
+                // otherwise.
+                if !self.upload_complete.get() {
+                    self.process_request_end_of_body();
                     return_if_fetch_was_terminated!();
                 }
                 // Part of step 13, send() (processing response)
@@ -1135,6 +1150,14 @@ impl XMLHttpRequest {
                 // Part of step 11, send() (processing response end of file)
                 // XXXManishearth handle errors, if any (substep 2)
 
+                // Subsubstep 5, when no chunk of the body reported progress.
+                if !self.sync.get() &&
+                    self.ready_state.get() == XMLHttpRequestState::HeadersReceived
+                {
+                    self.dispatch_response_progress_event(atom!("progress"));
+                    return_if_fetch_was_terminated!();
+                }
+
                 // Subsubsteps 6-8
                 self.send_flag.set(false);
 
@@ -1164,10 +1187,12 @@ impl XMLHttpRequest {
                 let upload_complete = &self.upload_complete;
                 if !upload_complete.get() {
                     upload_complete.set(true);
-                    self.dispatch_upload_progress_event(Atom::from(errormsg), Err(()));
-                    return_if_fetch_was_terminated!();
-                    self.dispatch_upload_progress_event(atom!("loadend"), Err(()));
-                    return_if_fetch_was_terminated!();
+                    if self.upload_listener.get() {
+                        self.dispatch_upload_progress_event(Atom::from(errormsg), Err(()));
+                        return_if_fetch_was_terminated!();
+                        self.dispatch_upload_progress_event(atom!("loadend"), Err(()));
+                        return_if_fetch_was_terminated!();
+                    }
                 }
                 self.dispatch_response_progress_event(Atom::from(errormsg));
                 return_if_fetch_was_terminated!();
@@ -1176,6 +1201,30 @@ impl XMLHttpRequest {
         }
     }
 
+    /// <https://xhr.spec.whatwg.org/#the-send()-method> (processRequestEndOfBody)
+    fn process_request_end_of_body(&self) {
+        // Step 1
+        if self.upload_complete.get() {
+            return;
+        }
+        self.upload_complete.set(true);
+        // Step 2
+        if self.sync.get() || !self.upload_listener.get() {
+            return;
+        }
+        // Steps 3-6
+        let gen_id = self.generation_id.get();
+        self.dispatch_upload_progress_event(atom!("progress"), Ok(None));
+        if self.generation_id.get() != gen_id {
+            return;
+        }
+        self.dispatch_upload_progress_event(atom!("load"), Ok(None));
+        if self.generation_id.get() != gen_id {
+            return;
+        }
+        self.dispatch_upload_progress_event(atom!("loadend"), Ok(None));
+    }
+
     fn terminate_ongoing_fetch(&self) {
         self.canceller.borrow_mut().cancel();
         let GenerationId(prev_id) = self.generation_id.get();
@@ -1323,8 +1372,7 @@ impl XMLHttpRequest {
         }
 
         let mime_type = self.final_mime_type();
-        // TODO: prescan the response to determine encoding if final charset is null
-        let charset = self.final_charset().unwrap_or(UTF_8);
+        let mut charset = self.final_charset();
         let temp_doc: DomRoot<Document>;
         match mime_type {
             Some(ref mime) if mime.type_() == mime::TEXT && mime.subtype() == mime::HTML => {
@@ -1333,7 +1381,10 @@ impl XMLHttpRequest {
                     return None;
                 } else {
                     // Step 6
-                    temp_doc = self.document_text_html();
+                    if charset.is_none() {
+                        charset = prescan_the_byte_stream(&self.response.borrow());
+                    }
+                    temp_doc = self.document_text_html(charset.unwrap_or(UTF_8));
                 }
             },
             // Step 7
@@ -1355,7 +1406,7 @@ impl XMLHttpRequest {
             },
         }
         // Step 9
-        temp_doc.set_encoding(charset);
+        temp_doc.set_encoding(charset.unwrap_or(UTF_8));
         // Step 13
         self.response_xml.set(Some(&temp_doc));
         return self.response_xml.get();
@@ -1413,8 +1464,7 @@ impl XMLHttpRequest {
         self.response_json.get()
     }
 
-    fn document_text_html(&self) -> DomRoot<Document> {
-        let charset = self.final_charset().unwrap_or(UTF_8);
+    fn document_text_html(&self, charset: &'static Encoding) -> DomRoot<Document> {
         let wr = self.global();
         let response = self.response.borrow();
         let (decoded, _, _) = charset.decode(&response);
@@ -1517,19 +1567,26 @@ impl XMLHttpRequest {
         Ok(())
     }
 
+    /// <https://xhr.spec.whatwg.org/#final-charset>
     fn final_charset(&self) -> Option<&'static Encoding> {
-        if self.override_charset.borrow().is_some() {
-            self.override_charset.borrow().clone()
-        } else {
-            match self.response_headers.borrow().typed_get::<ContentType>() {
-                Some(ct) => {
-                    let mime: Mime = ct.into();
-                    let value = mime.get_param(mime::CHARSET);
-                    value.and_then(|value| Encoding::for_label(value.as_ref().as_bytes()))
-                },
-                None => None,
+        // Steps 1-2
+        let response_mime: Option<Mime> = self
+            .response_headers
+            .borrow()
+            .typed_get::<ContentType>()
+            .map(Into::into);
+        let mut label = response_mime
+            .as_ref()
+            .and_then(|mime| mime.get_param(mime::CHARSET))
+            .map(|value| value.as_str().to_owned());
+        // Step 3
+        if let Some(ref override_mime) = *self.override_mime_type.borrow() {
+            if let Some(value) = override_mime.get_param(mime::CHARSET) {
+                label = Some(value.as_str().to_owned());
             }
         }
+        // Steps 4-6
+        label.and_then(|label| Encoding::for_label(label.as_bytes()))
     }
 
     fn final_mime_type(&self) -> Option<Mime> {
@@ -1618,6 +1675,211 @@ fn serialize_document(doc: &Document) -> Fallible<DOMString> {
     }
 }
 
+/// <https://html.spec.whatwg.org/multipage/#prescan-a-byte-stream-to-determine-its-encoding>
+fn prescan_the_byte_stream(bytes: &[u8]) -> Option<&'static Encoding> {
+    let bytes = &bytes[..cmp::min(bytes.len(), 1024)];
+    let starts_with_ignore_case = |position: usize, prefix: &[u8]| {
+        bytes.len() >= position + prefix.len() &&
+            bytes[position..position + prefix.len()].eq_ignore_ascii_case(prefix)
+    };
+    let mut position = 0;
+    while position < bytes.len() {
+        if starts_with_ignore_case(position, b"<!--") {
+            // The end of the comment may overlap with its start, as in `<!-->`.
+            match find_subsequence(&bytes[position + 2..], b"-->") {
+                Some(index) => position += 2 + index + 2,
+                None => return None,
+            }
+        } else if starts_with_ignore_case(position, b"<meta") &&
+            bytes
+                .get(position + 5)
+                .map_or(false, |&b| b.is_ascii_whitespace() || b == b'/')
+        {
+            position += 5;
+            let mut attribute_list = vec![];
+            let mut got_pragma = false;
+            let mut need_pragma = None;
+            let mut charset = None;
+            while let Some((name, value)) = get_an_attribute(bytes, &mut position) {
+                if attribute_list.contains(&name) {
+                    continue;
+                }
+                match &*name {
+                    b"http-equiv" => {
+                        if value == b"content-type" {
+                            got_pragma = true;
+                        }
+                    },
+                    b"content" => {
+                        if charset.is_none() {
+                            if let Some(encoding) = extract_a_character_encoding_from_meta(&value) {
+                                charset = Some(encoding);
+                                need_pragma = Some(true);
+                            }
+                        }
+                    },
+                    b"charset" => {
+                        if charset.is_none() {
+                            charset = Encoding::for_label(&value);
+                            need_pragma = Some(false);
+                        }
+                    },
+                    _ => {},
+                }
+                attribute_list.push(name);
+            }
+            match (need_pragma, charset) {
+                (Some(need_pragma), Some(charset)) if got_pragma || !need_pragma => {
+                    if charset == encoding_rs::UTF_16BE || charset == encoding_rs::UTF_16LE {
+                        return Some(UTF_8);
+                    }
+                    if charset == encoding_rs::X_USER_DEFINED {
+                        return Some(encoding_rs::WINDOWS_1252);
+                    }
+                    return Some(charset);
+                },
+                _ => {},
+            }
+        } else if bytes[position] == b'<' &&
+            (bytes
+                .get(position + 1)
+                .map_or(false, |b| b.is_ascii_alphabetic()) ||
+                (bytes.get(position + 1) == Some(&b'/') &&
+                    bytes
+                        .get(position + 2)
+                        .map_or(false, |b| b.is_ascii_alphabetic())))
+        {
+            while position < bytes.len() &&
+                !bytes[position].is_ascii_whitespace() &&
+                bytes[position] != b'>'
+            {
+                position += 1;
+            }
+            while get_an_attribute(bytes, &mut position).is_some() {}
+        } else if starts_with_ignore_case(position, b"<!") ||
+            starts_with_ignore_case(position, b"</") ||
+            starts_with_ignore_case(position, b"<?")
+        {
+            match bytes[position..].iter().position(|&b| b == b'>') {
+                Some(index) => position += index,
+                None => return None,
+            }
+        }
+        position += 1;
+    }
+    None
+}
+
+fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// <https://html.spec.whatwg.org/multipage/#concept-get-attributes-when-sniffing>
+fn get_an_attribute(bytes: &[u8], position: &mut usize) -> Option<(Vec<u8>, Vec<u8>)> {
+    let skip_whitespace = |position: &mut usize| {
+        while *position < bytes.len() && bytes[*position].is_ascii_whitespace() {
+            *position += 1;
+        }
+    };
+    // Step 1
+    while *position < bytes.len() &&
+        (bytes[*position].is_ascii_whitespace() || bytes[*position] == b'/')
+    {
+        *position += 1;
+    }
+    // Step 2
+    if *bytes.get(*position)? == b'>' {
+        return None;
+    }
+    // Steps 3-4
+    let mut name = vec![];
+    let mut value = vec![];
+    loop {
+        let byte = *bytes.get(*position)?;
+        if byte == b'=' && !name.is_empty() {
+            *position += 1;
+            break;
+        } else if byte.is_ascii_whitespace() {
+            // Steps 5-6
+            skip_whitespace(position);
+            if *bytes.get(*position)? != b'=' {
+                return Some((name, value));
+            }
+            *position += 1;
+            break;
+        } else if byte == b'/' || byte == b'>' {
+            return Some((name, value));
+        }
+        name.push(byte.to_ascii_lowercase());
+        *position += 1;
+    }
+    // Step 8
+    skip_whitespace(position);
+    // Step 9
+    let byte = *bytes.get(*position)?;
+    if byte == b'"' || byte == b'\'' {
+        *position += 1;
+        loop {
+            let next = *bytes.get(*position)?;
+            *position += 1;
+            if next == byte {
+                return Some((name, value));
+            }
+            value.push(next.to_ascii_lowercase());
+        }
+    } else if byte == b'>' {
+        return Some((name, value));
+    }
+    value.push(byte.to_ascii_lowercase());
+    *position += 1;
+    // Step 10
+    loop {
+        let byte = *bytes.get(*position)?;
+        if byte.is_ascii_whitespace() || byte == b'>' {
+            return Some((name, value));
+        }
+        value.push(byte.to_ascii_lowercase());
+        *position += 1;
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/#algorithm-for-extracting-a-character-encoding-from-a-meta-element>
+fn extract_a_character_encoding_from_meta(value: &[u8]) -> Option<&'static Encoding> {
+    let mut position = 0;
+    loop {
+        // Step 2
+        position += find_subsequence(&value[position..], b"charset")? + b"charset".len();
+        // Step 3
+        while position < value.len() && value[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        // Step 4
+        if value.get(position) != Some(&b'=') {
+            continue;
+        }
+        position += 1;
+        // Step 5
+        while position < value.len() && value[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        // Step 6
+        let quote = *value.get(position)?;
+        if quote == b'"' || quote == b'\'' {
+            let rest = &value[position + 1..];
+            let end = rest.iter().position(|&b| b == quote)?;
+            return Encoding::for_label(&rest[..end]);
+        }
+        let rest = &value[position..];
+        let end = rest
+            .iter()
+            .position(|&b| b.is_ascii_whitespace() || b == b';')
+            .unwrap_or(rest.len());
+        return Encoding::for_label(&rest[..end]);
+    }
+}
+
 impl Extractable for BodyInit {
     // https://fetch.spec.whatwg.org/#concept-bodyinit-extract
     fn extract(&self) -> (Vec<u8>, Option<DOMString>) {
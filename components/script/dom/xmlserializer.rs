@@ -2,16 +2,28 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
 use crate::dom::bindings::codegen::Bindings::XMLSerializerBinding;
 use crate::dom::bindings::codegen::Bindings::XMLSerializerBinding::XMLSerializerMethods;
 use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId, TextTypeId};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::xmlname::{xml_name_type, XMLName};
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::documenttype::DocumentType;
+use crate::dom::element::Element;
+use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::node::Node;
+use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::window::Window;
 use dom_struct::dom_struct;
-use xml5ever::serialize::{serialize, SerializeOpts, TraversalScope};
+use html5ever::{LocalName, Namespace};
+use std::collections::{HashMap, HashSet};
+use xml5ever::serialize::TraversalScope;
 
 #[dom_struct]
 pub struct XMLSerializer {
@@ -42,20 +54,546 @@ impl XMLSerializer {
 }
 
 impl XMLSerializerMethods for XMLSerializer {
-    // https://w3c.github.io/DOM-Parsing/#the-xmlserializer-interface
+    // https://w3c.github.io/DOM-Parsing/#dom-xmlserializer-serializetostring
     fn SerializeToString(&self, root: &Node) -> Fallible<DOMString> {
-        let mut writer = vec![];
-        match serialize(
-            &mut writer,
-            &root,
-            SerializeOpts {
-                traversal_scope: TraversalScope::IncludeNode,
-            },
-        ) {
-            Ok(_) => Ok(DOMString::from(String::from_utf8(writer).unwrap())),
-            Err(_) => Err(Error::Type(String::from(
-                "root must be a Node or an Attr object",
-            ))),
+        serialize_xml(root, TraversalScope::IncludeNode, false)
+    }
+}
+
+/// https://w3c.github.io/DOM-Parsing/#dfn-namespace-prefix-map
+///
+/// The null namespace is represented by the empty namespace.
+type NamespacePrefixMap = HashMap<Namespace, Vec<DOMString>>;
+
+/// Produces an [XML serialization][1] of `node`. With
+/// `TraversalScope::ChildrenOnly`, only the children of `node` are
+/// serialized, as the `innerHTML` getter does for XML documents.
+///
+/// [1]: https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization
+pub fn serialize_xml(
+    node: &Node,
+    traversal_scope: TraversalScope,
+    require_well_formed: bool,
+) -> Fallible<DOMString> {
+    // Steps 1-3.
+    let mut prefix_map = NamespacePrefixMap::new();
+    prefix_map.insert(ns!(xml), vec![DOMString::from("xml")]);
+    let mut serializer = XmlSerializer {
+        require_well_formed,
+        prefix_index: 1,
+        markup: String::new(),
+    };
+
+    // Step 4. Every error thrown by the algorithm becomes an
+    // InvalidStateError.
+    let result = match traversal_scope {
+        TraversalScope::IncludeNode => serializer.serialize_node(node, &ns!(), &prefix_map),
+        TraversalScope::ChildrenOnly(_) => serializer.serialize_children(node, &ns!(), &prefix_map),
+    };
+    result
+        .map(|_| DOMString::from(serializer.markup))
+        .map_err(|_| Error::InvalidState)
+}
+
+struct XmlSerializer {
+    require_well_formed: bool,
+    /// https://w3c.github.io/DOM-Parsing/#dfn-generating-a-prefix
+    prefix_index: u32,
+    markup: String,
+}
+
+impl XmlSerializer {
+    /// https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-algorithm
+    fn serialize_node(
+        &mut self,
+        node: &Node,
+        namespace: &Namespace,
+        prefix_map: &NamespacePrefixMap,
+    ) -> Fallible<()> {
+        match node.type_id() {
+            NodeTypeId::Element(_) => {
+                let element = node.downcast::<Element>().unwrap();
+                self.serialize_element(element, namespace, prefix_map)
+            },
+            NodeTypeId::Document(_) => {
+                let document = node.downcast::<Document>().unwrap();
+                if self.require_well_formed && document.GetDocumentElement().is_none() {
+                    return Err(Error::InvalidState);
+                }
+                self.serialize_children(node, namespace, prefix_map)
+            },
+            NodeTypeId::DocumentFragment(_) => self.serialize_children(node, namespace, prefix_map),
+            NodeTypeId::DocumentType => {
+                let doctype = node.downcast::<DocumentType>().unwrap();
+                self.serialize_doctype(doctype)
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Comment) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                if self.require_well_formed &&
+                    (!is_xml_chars(&data) || data.contains("--") || data.ends_with('-'))
+                {
+                    return Err(Error::InvalidState);
+                }
+                self.markup.push_str("<!--");
+                self.markup.push_str(&data);
+                self.markup.push_str("-->");
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(TextTypeId::CDATASection)) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                if self.require_well_formed && (!is_xml_chars(&data) || data.contains("]]>")) {
+                    return Err(Error::InvalidState);
+                }
+                self.markup.push_str("<![CDATA[");
+                self.markup.push_str(&data);
+                self.markup.push_str("]]>");
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(TextTypeId::Text)) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                if self.require_well_formed && !is_xml_chars(&data) {
+                    return Err(Error::InvalidState);
+                }
+                for c in data.chars() {
+                    match c {
+                        '&' => self.markup.push_str("&amp;"),
+                        '<' => self.markup.push_str("&lt;"),
+                        '>' => self.markup.push_str("&gt;"),
+                        c => self.markup.push(c),
+                    }
+                }
+                Ok(())
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::ProcessingInstruction) => {
+                let pi = node.downcast::<ProcessingInstruction>().unwrap();
+                let target = pi.target();
+                let data = pi.upcast::<CharacterData>().data();
+                if self.require_well_formed &&
+                    (target.contains(':') ||
+                        target.eq_ignore_ascii_case("xml") ||
+                        !is_xml_chars(&data) ||
+                        data.contains("?>"))
+                {
+                    return Err(Error::InvalidState);
+                }
+                self.markup.push_str("<?");
+                self.markup.push_str(target);
+                self.markup.push(' ');
+                self.markup.push_str(&data);
+                self.markup.push_str("?>");
+                Ok(())
+            },
+            // An Attr serializes to the empty string.
+            NodeTypeId::Attr => Ok(()),
+        }
+    }
+
+    fn serialize_children(
+        &mut self,
+        node: &Node,
+        namespace: &Namespace,
+        prefix_map: &NamespacePrefixMap,
+    ) -> Fallible<()> {
+        let children = match node.downcast::<HTMLTemplateElement>() {
+            Some(template) => template.Content().upcast::<Node>().children(),
+            None => node.children(),
+        };
+        for child in children {
+            self.serialize_node(&child, namespace, prefix_map)?;
+        }
+        Ok(())
+    }
+
+    /// https://w3c.github.io/DOM-Parsing/#xml-serializing-a-documenttype-node
+    fn serialize_doctype(&mut self, doctype: &DocumentType) -> Fallible<()> {
+        let public_id = doctype.public_id();
+        let system_id = doctype.system_id();
+
+        // Steps 1-2.
+        if self.require_well_formed &&
+            (!public_id.chars().all(is_pubid_char) ||
+                !is_xml_chars(system_id) ||
+                (system_id.contains('"') && system_id.contains('\'')))
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 3-10.
+        self.markup.push_str("<!DOCTYPE ");
+        self.markup.push_str(doctype.name());
+        if !public_id.is_empty() {
+            self.markup.push_str(" PUBLIC \"");
+            self.markup.push_str(public_id);
+            self.markup.push('"');
+        }
+        if !system_id.is_empty() {
+            if public_id.is_empty() {
+                self.markup.push_str(" SYSTEM");
+            }
+            self.markup.push_str(" \"");
+            self.markup.push_str(system_id);
+            self.markup.push('"');
+        }
+        self.markup.push('>');
+        Ok(())
+    }
+
+    /// https://w3c.github.io/DOM-Parsing/#xml-serializing-an-element-node
+    fn serialize_element(
+        &mut self,
+        element: &Element,
+        namespace: &Namespace,
+        prefix_map: &NamespacePrefixMap,
+    ) -> Fallible<()> {
+        let local_name = element.local_name();
+
+        // Step 1.
+        if self.require_well_formed && !is_xml_ncname(local_name) {
+            return Err(Error::InvalidState);
+        }
+
+        // Steps 2-7.
+        self.markup.push('<');
+        let qualified_name;
+        let mut ignore_namespace_definition_attribute = false;
+        let mut map = prefix_map.clone();
+        let mut local_prefixes_map = HashMap::new();
+
+        // Step 8.
+        let local_default_namespace =
+            record_namespace_information(element, &mut map, &mut local_prefixes_map);
+
+        // Steps 9-10.
+        let mut inherited_namespace = namespace.clone();
+        let ns = element.namespace();
+
+        if inherited_namespace == *ns {
+            // Step 11.
+            if local_default_namespace.is_some() {
+                ignore_namespace_definition_attribute = true;
+            }
+            qualified_name = if *ns == ns!(xml) {
+                format!("xml:{}", local_name)
+            } else {
+                local_name.to_string()
+            };
+            self.markup.push_str(&qualified_name);
+        } else {
+            // Step 12.1.
+            let mut prefix = element.prefix().as_ref().map(|p| DOMString::from(&**p));
+
+            // Step 12.2.
+            let mut candidate_prefix = preferred_prefix(&map, ns, prefix.as_ref());
+
+            // Step 12.3.
+            if prefix.as_ref().map_or(false, |p| &**p == "xmlns") {
+                if self.require_well_formed {
+                    return Err(Error::InvalidState);
+                }
+                candidate_prefix = prefix.clone();
+            }
+
+            if let Some(candidate_prefix) = candidate_prefix {
+                // Step 12.4.
+                qualified_name = format!("{}:{}", candidate_prefix, local_name);
+                if let Some(ref default) = local_default_namespace {
+                    if **default != *ns!(xml) {
+                        inherited_namespace = Namespace::from(&**default);
+                    }
+                }
+                self.markup.push_str(&qualified_name);
+            } else if let Some(mut element_prefix) = prefix.take() {
+                // Step 12.5.1.
+                if local_prefixes_map.contains_key(&element_prefix) {
+                    element_prefix = self.generate_prefix(&mut map, ns);
+                } else {
+                    // Step 12.5.2.
+                    add_prefix(&mut map, ns, element_prefix.clone());
+                }
+
+                // Steps 12.5.3-12.5.4.
+                qualified_name = format!("{}:{}", element_prefix, local_name);
+                self.markup.push_str(&qualified_name);
+                self.markup.push_str(" xmlns:");
+                self.markup.push_str(&element_prefix);
+                self.markup.push_str("=\"");
+                self.serialize_attribute_value(ns)?;
+                self.markup.push('"');
+
+                // Step 12.5.5.
+                if let Some(ref default) = local_default_namespace {
+                    inherited_namespace = Namespace::from(&**default);
+                }
+            } else if local_default_namespace
+                .as_ref()
+                .map_or(true, |default| **default != **ns)
+            {
+                // Step 12.6.
+                ignore_namespace_definition_attribute = true;
+                qualified_name = local_name.to_string();
+                inherited_namespace = ns.clone();
+                self.markup.push_str(&qualified_name);
+                self.markup.push_str(" xmlns=\"");
+                self.serialize_attribute_value(ns)?;
+                self.markup.push('"');
+            } else {
+                // Step 12.7.
+                qualified_name = local_name.to_string();
+                inherited_namespace = ns.clone();
+                self.markup.push_str(&qualified_name);
+            }
+        }
+
+        // Step 13.
+        self.serialize_attributes(
+            element,
+            &mut map,
+            &local_prefixes_map,
+            ignore_namespace_definition_attribute,
+        )?;
+
+        // Steps 14-17.
+        let node = element.upcast::<Node>();
+        let has_children = match element.downcast::<HTMLTemplateElement>() {
+            Some(template) => template.Content().upcast::<Node>().children_count() > 0,
+            None => node.children_count() > 0,
+        };
+        if *ns == ns!(html) && !has_children && element.is_void() {
+            self.markup.push_str(" />");
+            return Ok(());
+        }
+        if *ns != ns!(html) && !has_children {
+            self.markup.push_str("/>");
+            return Ok(());
         }
+        self.markup.push('>');
+
+        // Steps 18-19.
+        self.serialize_children(node, &inherited_namespace, &map)?;
+
+        // Step 20.
+        self.markup.push_str("</");
+        self.markup.push_str(&qualified_name);
+        self.markup.push('>');
+        Ok(())
+    }
+
+    /// https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-of-the-attributes
+    fn serialize_attributes(
+        &mut self,
+        element: &Element,
+        map: &mut NamespacePrefixMap,
+        local_prefixes_map: &HashMap<DOMString, Namespace>,
+        ignore_namespace_definition_attribute: bool,
+    ) -> Fallible<()> {
+        // Step 2.
+        let mut localname_set: HashSet<(Namespace, LocalName)> = HashSet::new();
+
+        // Step 3.
+        for attr in element.attrs().iter() {
+            let attribute_namespace = attr.namespace();
+            let attr_prefix = attr.prefix().map(|p| DOMString::from(&**p));
+
+            // Steps 3.1-3.2.
+            let key = (attribute_namespace.clone(), attr.local_name().clone());
+            if self.require_well_formed && localname_set.contains(&key) {
+                return Err(Error::InvalidState);
+            }
+            localname_set.insert(key);
+
+            // Steps 3.3-3.4.
+            let mut candidate_prefix = None;
+
+            // Step 3.5.
+            if *attribute_namespace != ns!() {
+                candidate_prefix = preferred_prefix(map, attribute_namespace, attr_prefix.as_ref());
+
+                if *attribute_namespace == ns!(xmlns) {
+                    // Step 3.5.2.1.
+                    let value = attr.value();
+                    let redundant = match attr_prefix {
+                        None => ignore_namespace_definition_attribute,
+                        Some(_) => local_prefixes_map
+                            .get(&DOMString::from(&**attr.local_name()))
+                            .map_or(true, |ns| **ns != **value),
+                    };
+                    if **value == *ns!(xml) || redundant {
+                        continue;
+                    }
+
+                    // Steps 3.5.2.2-3.5.2.3. Only prefixed declarations
+                    // can't be used to undeclare a namespace.
+                    if self.require_well_formed &&
+                        (**value == *ns!(xmlns) || (attr_prefix.is_some() && value.is_empty()))
+                    {
+                        return Err(Error::InvalidState);
+                    }
+
+                    // Step 3.5.2.4.
+                    if attr_prefix.as_ref().map_or(false, |p| &**p == "xmlns") {
+                        candidate_prefix = attr_prefix.clone();
+                    }
+                } else if candidate_prefix.is_none() {
+                    // Step 3.5.3.
+                    let prefix = self.generate_prefix(map, attribute_namespace);
+                    self.markup.push_str(" xmlns:");
+                    self.markup.push_str(&prefix);
+                    self.markup.push_str("=\"");
+                    self.serialize_attribute_value(attribute_namespace)?;
+                    self.markup.push('"');
+                    candidate_prefix = Some(prefix);
+                }
+            }
+
+            // Steps 3.6-3.7.
+            self.markup.push(' ');
+            if let Some(prefix) = candidate_prefix {
+                self.markup.push_str(&prefix);
+                self.markup.push(':');
+            }
+
+            // Step 3.8.
+            let local_name = attr.local_name();
+            if self.require_well_formed &&
+                (!is_xml_ncname(local_name) ||
+                    (&**local_name == "xmlns" && *attribute_namespace == ns!()))
+            {
+                return Err(Error::InvalidState);
+            }
+
+            // Step 3.9.
+            self.markup.push_str(local_name);
+            self.markup.push_str("=\"");
+            self.serialize_attribute_value(&**attr.value())?;
+            self.markup.push('"');
+        }
+        Ok(())
+    }
+
+    /// https://w3c.github.io/DOM-Parsing/#dfn-serializing-an-attribute-value
+    ///
+    /// Tabs and line breaks are escaped too, so that they survive the
+    /// attribute value normalization of a later parse.
+    fn serialize_attribute_value(&mut self, value: &str) -> Fallible<()> {
+        if self.require_well_formed && !is_xml_chars(value) {
+            return Err(Error::InvalidState);
+        }
+        for c in value.chars() {
+            match c {
+                '&' => self.markup.push_str("&amp;"),
+                '"' => self.markup.push_str("&quot;"),
+                '<' => self.markup.push_str("&lt;"),
+                '>' => self.markup.push_str("&gt;"),
+                '\t' => self.markup.push_str("&#9;"),
+                '\n' => self.markup.push_str("&#10;"),
+                '\r' => self.markup.push_str("&#13;"),
+                c => self.markup.push(c),
+            }
+        }
+        Ok(())
+    }
+
+    /// https://w3c.github.io/DOM-Parsing/#dfn-generating-a-prefix
+    fn generate_prefix(
+        &mut self,
+        map: &mut NamespacePrefixMap,
+        namespace: &Namespace,
+    ) -> DOMString {
+        let prefix = DOMString::from(format!("ns{}", self.prefix_index));
+        self.prefix_index += 1;
+        add_prefix(map, namespace, prefix.clone());
+        prefix
+    }
+}
+
+/// https://w3c.github.io/DOM-Parsing/#dfn-recording-the-namespace-information
+fn record_namespace_information(
+    element: &Element,
+    map: &mut NamespacePrefixMap,
+    local_prefixes_map: &mut HashMap<DOMString, Namespace>,
+) -> Option<DOMString> {
+    // Step 1.
+    let mut default_namespace_attr_value = None;
+
+    // Step 2.
+    for attr in element.attrs().iter() {
+        if *attr.namespace() != ns!(xmlns) {
+            continue;
+        }
+        match attr.prefix() {
+            // Step 2.3.1.
+            None => default_namespace_attr_value = Some(DOMString::from(&**attr.value())),
+            Some(_) => {
+                // Steps 2.3.2.1-2.3.2.2.
+                let prefix_definition = DOMString::from(&**attr.local_name());
+                let namespace_definition = Namespace::from(&**attr.value());
+
+                // Step 2.3.2.3.
+                if namespace_definition == ns!(xml) {
+                    continue;
+                }
+
+                // Step 2.3.2.5.
+                if map
+                    .get(&namespace_definition)
+                    .map_or(false, |prefixes| prefixes.contains(&prefix_definition))
+                {
+                    continue;
+                }
+
+                // Steps 2.3.2.6-2.3.2.7.
+                add_prefix(map, &namespace_definition, prefix_definition.clone());
+                local_prefixes_map.insert(prefix_definition, namespace_definition);
+            },
+        }
+    }
+
+    // Step 3.
+    default_namespace_attr_value
+}
+
+/// https://w3c.github.io/DOM-Parsing/#dfn-retrieving-a-preferred-prefix-string
+fn preferred_prefix(
+    map: &NamespacePrefixMap,
+    namespace: &Namespace,
+    preferred: Option<&DOMString>,
+) -> Option<DOMString> {
+    let candidates = map.get(namespace)?;
+    candidates
+        .iter()
+        .find(|prefix| Some(*prefix) == preferred)
+        .or_else(|| candidates.last())
+        .cloned()
+}
+
+/// https://w3c.github.io/DOM-Parsing/#dfn-add
+fn add_prefix(map: &mut NamespacePrefixMap, namespace: &Namespace, prefix: DOMString) {
+    map.entry(namespace.clone())
+        .or_insert_with(Vec::new)
+        .push(prefix);
+}
+
+/// Whether `name` matches the `Name` production and has no colon.
+fn is_xml_ncname(name: &str) -> bool {
+    !name.contains(':') && xml_name_type(name) != XMLName::InvalidXMLName
+}
+
+/// Whether all characters of `data` match the `Char` production.
+/// https://www.w3.org/TR/xml/#NT-Char
+fn is_xml_chars(data: &str) -> bool {
+    data.chars().all(|c| match c {
+        '\u{9}' | '\u{A}' | '\u{D}' => true,
+        '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..='\u{10FFFF}' => true,
+        _ => false,
+    })
+}
+
+/// https://www.w3.org/TR/xml/#NT-PubidChar
+fn is_pubid_char(c: char) -> bool {
+    match c {
+        ' ' | '\r' | '\n' | 'a'..='z' | 'A'..='Z' | '0'..='9' => true,
+        '-' | '\'' | '(' | ')' | '+' | ',' | '.' | '/' | ':' | '=' | '?' | ';' | '!' | '*' |
+        '#' | '@' | '$' | '_' | '%' => true,
+        _ => false,
     }
 }
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding;
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathEvaluatorMethods;
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathNSResolver;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::dom::xpathexpression::XPathExpression;
+use crate::dom::xpathresult::XPathResult;
+use dom_struct::dom_struct;
+use std::rc::Rc;
+
+/// <https://dom.spec.whatwg.org/#interface-xpathevaluator>
+#[dom_struct]
+pub struct XPathEvaluator {
+    reflector_: Reflector,
+    window: Dom<Window>,
+}
+
+impl XPathEvaluator {
+    fn new_inherited(window: &Window) -> XPathEvaluator {
+        XPathEvaluator {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<XPathEvaluator> {
+        reflect_dom_object(
+            Box::new(XPathEvaluator::new_inherited(window)),
+            window,
+            XPathEvaluatorBinding::Wrap,
+        )
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window) -> Fallible<DomRoot<XPathEvaluator>> {
+        Ok(XPathEvaluator::new(window))
+    }
+}
+
+impl XPathEvaluatorMethods for XPathEvaluator {
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    fn CreateExpression(
+        &self,
+        expression: DOMString,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        XPathExpression::new(&self.window, &expression, resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-creatensresolver
+    fn CreateNSResolver(&self, node_resolver: &Node) -> DomRoot<Node> {
+        DomRoot::from_ref(node_resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+    fn Evaluate(
+        &self,
+        expression: DOMString,
+        context_node: &Node,
+        resolver: Option<Rc<XPathNSResolver>>,
+        type_: u16,
+        result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        XPathExpression::new(&self.window, &expression, resolver)?.Evaluate(
+            context_node,
+            type_,
+            result,
+        )
+    }
+}
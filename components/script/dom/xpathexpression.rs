@@ -0,0 +1,122 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathNSResolver;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding;
+use crate::dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::dom::xpathresult::XPathResult;
+use crate::xpath::{self, Environment, EvaluationContext, Expr};
+use dom_struct::dom_struct;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// <https://dom.spec.whatwg.org/#interface-xpathexpression>
+#[dom_struct]
+pub struct XPathExpression {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    #[ignore_malloc_size_of = "Defined in xpath"]
+    expr: Expr,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    resolver: Option<Rc<XPathNSResolver>>,
+}
+
+impl XPathExpression {
+    fn new_inherited(
+        window: &Window,
+        expr: Expr,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> XPathExpression {
+        XPathExpression {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            expr,
+            resolver,
+        }
+    }
+
+    /// Parses `expression`, whose namespace prefixes are resolved with
+    /// `resolver` when it is evaluated.
+    /// <https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression>
+    pub fn new(
+        window: &Window,
+        expression: &str,
+        resolver: Option<Rc<XPathNSResolver>>,
+    ) -> Fallible<DomRoot<XPathExpression>> {
+        let expr = xpath::parse(expression).map_err(to_dom_error)?;
+        Ok(reflect_dom_object(
+            Box::new(XPathExpression::new_inherited(window, expr, resolver)),
+            window,
+            XPathExpressionBinding::Wrap,
+        ))
+    }
+}
+
+impl XPathExpressionMethods for XPathExpression {
+    // https://dom.spec.whatwg.org/#dom-xpathexpression-evaluate
+    fn Evaluate(
+        &self,
+        context_node: &Node,
+        type_: u16,
+        // Results are never reused, which the specification allows.
+        _result: Option<&XPathResult>,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        let environment = ResolverEnvironment {
+            resolver: self.resolver.as_ref().map(|resolver| &**resolver),
+            threw: Cell::new(false),
+        };
+        let value = xpath::evaluate(
+            &self.expr,
+            &EvaluationContext::new(context_node, &environment),
+        );
+        if environment.threw.get() {
+            return Err(Error::JSFailed);
+        }
+        let value = value.map_err(to_dom_error)?;
+        XPathResult::new(&self.window, type_, value, &context_node.owner_doc())
+    }
+}
+
+/// Resolves the namespace prefixes of an expression with the
+/// `XPathNSResolver` passed by script.
+struct ResolverEnvironment<'a> {
+    resolver: Option<&'a XPathNSResolver>,
+    /// Whether the resolver threw, in which case the exception is pending
+    /// and evaluation has failed.
+    threw: Cell<bool>,
+}
+
+impl<'a> Environment for ResolverEnvironment<'a> {
+    fn resolve_namespace_prefix(&self, prefix: &str) -> Option<String> {
+        let resolver = self.resolver?;
+        if self.threw.get() {
+            return None;
+        }
+        match resolver
+            .LookupNamespaceURI__(Some(DOMString::from(prefix)), ExceptionHandling::Rethrow)
+        {
+            Ok(Some(namespace)) if !namespace.is_empty() => Some(String::from(namespace)),
+            Ok(_) => None,
+            Err(_) => {
+                self.threw.set(true);
+                None
+            },
+        }
+    }
+}
+
+fn to_dom_error(error: xpath::Error) -> Error {
+    match error {
+        xpath::Error::InvalidExpression(_) => Error::Syntax,
+        xpath::Error::Namespace(_) => Error::Namespace,
+        xpath::Error::Type(message) => Error::Type(message),
+    }
+}
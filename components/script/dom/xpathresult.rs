@@ -0,0 +1,199 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding;
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultConstants;
+use crate::dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+use crate::xpath;
+use dom_struct::dom_struct;
+use std::cell::Cell;
+
+/// <https://dom.spec.whatwg.org/#interface-xpathresult>
+#[dom_struct]
+pub struct XPathResult {
+    reflector_: Reflector,
+    result_type: u16,
+    number_value: f64,
+    string_value: DOMString,
+    boolean_value: bool,
+    /// The nodes of a node-set result, in document order.
+    nodes: Vec<Dom<Node>>,
+    /// The index of the node `iterateNext` returns next.
+    iterator_position: Cell<usize>,
+    /// Iterator results become invalid when this document changes.
+    document: Dom<Document>,
+    document_version: u64,
+}
+
+impl XPathResult {
+    fn new_inherited(
+        result_type: u16,
+        value: xpath::Value,
+        document: &Document,
+    ) -> Fallible<XPathResult> {
+        let (mut number_value, mut string_value, mut boolean_value) = (0., String::new(), false);
+        let mut nodes = vec![];
+        match result_type {
+            XPathResultConstants::NUMBER_TYPE => number_value = value.number(),
+            XPathResultConstants::STRING_TYPE => string_value = value.string(),
+            XPathResultConstants::BOOLEAN_TYPE => boolean_value = value.boolean(),
+            _ => {
+                nodes = value
+                    .into_node_set()
+                    .map_err(|_| Error::Type("The result is not a node-set".to_owned()))?;
+                if result_type == XPathResultConstants::ANY_UNORDERED_NODE_TYPE ||
+                    result_type == XPathResultConstants::FIRST_ORDERED_NODE_TYPE
+                {
+                    nodes.truncate(1);
+                }
+            },
+        }
+        Ok(XPathResult {
+            reflector_: Reflector::new(),
+            result_type,
+            number_value,
+            string_value: string_value.into(),
+            boolean_value,
+            nodes: nodes.iter().map(|node| Dom::from_ref(&**node)).collect(),
+            iterator_position: Cell::new(0),
+            document: Dom::from_ref(document),
+            document_version: document.upcast::<Node>().inclusive_descendants_version(),
+        })
+    }
+
+    /// Creates the result of an expression that evaluated to `value`,
+    /// converted to `requested_type`.
+    /// <https://dom.spec.whatwg.org/#dom-xpathexpression-evaluate>
+    pub fn new(
+        window: &Window,
+        requested_type: u16,
+        value: xpath::Value,
+        document: &Document,
+    ) -> Fallible<DomRoot<XPathResult>> {
+        let result_type = match requested_type {
+            XPathResultConstants::ANY_TYPE => match value {
+                xpath::Value::NodeSet(_) => XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE,
+                xpath::Value::Boolean(_) => XPathResultConstants::BOOLEAN_TYPE,
+                xpath::Value::Number(_) => XPathResultConstants::NUMBER_TYPE,
+                xpath::Value::String(_) => XPathResultConstants::STRING_TYPE,
+            },
+            XPathResultConstants::NUMBER_TYPE..=XPathResultConstants::FIRST_ORDERED_NODE_TYPE => {
+                requested_type
+            },
+            _ => return Err(Error::NotSupported),
+        };
+        Ok(reflect_dom_object(
+            Box::new(XPathResult::new_inherited(result_type, value, document)?),
+            window,
+            XPathResultBinding::Wrap,
+        ))
+    }
+
+    fn is_iterator(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE ||
+            self.result_type == XPathResultConstants::ORDERED_NODE_ITERATOR_TYPE
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_SNAPSHOT_TYPE ||
+            self.result_type == XPathResultConstants::ORDERED_NODE_SNAPSHOT_TYPE
+    }
+
+    fn check_type(&self, result_type: u16) -> Fallible<()> {
+        if self.result_type != result_type {
+            return Err(Error::Type("The result is of a different type".to_owned()));
+        }
+        Ok(())
+    }
+}
+
+impl XPathResultMethods for XPathResult {
+    // https://dom.spec.whatwg.org/#dom-xpathresult-resulttype
+    fn ResultType(&self) -> u16 {
+        self.result_type
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-numbervalue
+    fn GetNumberValue(&self) -> Fallible<f64> {
+        self.check_type(XPathResultConstants::NUMBER_TYPE)?;
+        Ok(self.number_value)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-stringvalue
+    fn GetStringValue(&self) -> Fallible<DOMString> {
+        self.check_type(XPathResultConstants::STRING_TYPE)?;
+        Ok(self.string_value.clone())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-booleanvalue
+    fn GetBooleanValue(&self) -> Fallible<bool> {
+        self.check_type(XPathResultConstants::BOOLEAN_TYPE)?;
+        Ok(self.boolean_value)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-singlenodevalue
+    fn GetSingleNodeValue(&self) -> Fallible<Option<DomRoot<Node>>> {
+        if self.result_type != XPathResultConstants::ANY_UNORDERED_NODE_TYPE &&
+            self.result_type != XPathResultConstants::FIRST_ORDERED_NODE_TYPE
+        {
+            return Err(Error::Type("The result is not a single node".to_owned()));
+        }
+        Ok(self.nodes.first().map(|node| DomRoot::from_ref(&**node)))
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-invaliditeratorstate
+    fn InvalidIteratorState(&self) -> bool {
+        self.is_iterator() &&
+            self.document
+                .upcast::<Node>()
+                .inclusive_descendants_version() !=
+                self.document_version
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotlength
+    fn GetSnapshotLength(&self) -> Fallible<u32> {
+        if !self.is_snapshot() {
+            return Err(Error::Type("The result is not a snapshot".to_owned()));
+        }
+        Ok(self.nodes.len() as u32)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-iteratenext
+    fn IterateNext(&self) -> Fallible<Option<DomRoot<Node>>> {
+        if !self.is_iterator() {
+            return Err(Error::Type("The result is not an iterator".to_owned()));
+        }
+        if self.InvalidIteratorState() {
+            return Err(Error::InvalidState);
+        }
+        let position = self.iterator_position.get();
+        let node = self
+            .nodes
+            .get(position)
+            .map(|node| DomRoot::from_ref(&**node));
+        if node.is_some() {
+            self.iterator_position.set(position + 1);
+        }
+        Ok(node)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotitem
+    fn SnapshotItem(&self, index: u32) -> Fallible<Option<DomRoot<Node>>> {
+        if !self.is_snapshot() {
+            return Err(Error::Type("The result is not a snapshot".to_owned()));
+        }
+        Ok(self
+            .nodes
+            .get(index as usize)
+            .map(|node| DomRoot::from_ref(&**node)))
+    }
+}
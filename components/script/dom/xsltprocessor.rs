@@ -0,0 +1,313 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::document_loader::DocumentLoader;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
+    DocumentMethods, DocumentReadyState,
+};
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::XSLTProcessorBinding;
+use crate::dom::bindings::codegen::Bindings::XSLTProcessorBinding::XSLTProcessorMethods;
+use crate::dom::bindings::conversions::{
+    ConversionResult, FromJSValConvertible, StringificationBehavior,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::{Document, DocumentSource, HasBrowsingContext, IsHTMLDocument};
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::{CloneChildrenFlag, Node};
+use crate::dom::text::Text;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+use crate::xpath;
+use crate::xslt::{self, ExpandedName, OutputMethod, Stylesheet};
+use dom_struct::dom_struct;
+use html5ever::{LocalName, Namespace, QualName};
+use js::conversions::ToJSValConvertible;
+use js::jsval::{BooleanValue, DoubleValue, JSVal, NullValue, UndefinedValue};
+use js::rust::HandleValue;
+use script_traits::DocumentActivity;
+use std::collections::HashMap;
+
+/// The namespace Gecko wraps the result of the text output method in.
+const TRANSFORMIIX_NAMESPACE: &str = "http://www.mozilla.org/TransforMiix";
+
+/// A stylesheet parameter set from script. Other values are converted to
+/// strings.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+enum Parameter {
+    Boolean(bool),
+    Number(f64),
+    String(DOMString),
+}
+
+#[dom_struct]
+pub struct XSLTProcessor {
+    reflector_: Reflector,
+    window: Dom<Window>,
+    /// A copy of the imported stylesheet, so that later changes to the
+    /// original don't affect transformations.
+    stylesheet: MutNullableDom<Node>,
+    /// Parameters keyed by namespace and local name.
+    parameters: DomRefCell<HashMap<(DOMString, DOMString), Parameter>>,
+}
+
+impl XSLTProcessor {
+    fn new_inherited(window: &Window) -> XSLTProcessor {
+        XSLTProcessor {
+            reflector_: Reflector::new(),
+            window: Dom::from_ref(window),
+            stylesheet: Default::default(),
+            parameters: DomRefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<XSLTProcessor> {
+        reflect_dom_object(
+            Box::new(XSLTProcessor::new_inherited(window)),
+            window,
+            XSLTProcessorBinding::Wrap,
+        )
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window) -> Fallible<DomRoot<XSLTProcessor>> {
+        Ok(XSLTProcessor::new(window))
+    }
+
+    fn compile_stylesheet(&self) -> Fallible<Stylesheet> {
+        let stylesheet = self.stylesheet.get().ok_or(Error::InvalidState)?;
+        Stylesheet::compile(&stylesheet).map_err(to_dom_error)
+    }
+
+    fn xpath_parameters(&self) -> Vec<(ExpandedName, xpath::Value)> {
+        self.parameters
+            .borrow()
+            .iter()
+            .map(|(&(ref namespace, ref local), parameter)| {
+                let name = ExpandedName {
+                    namespace: String::from(namespace.clone()),
+                    local: String::from(local.clone()),
+                };
+                let value = match *parameter {
+                    Parameter::Boolean(value) => xpath::Value::Boolean(value),
+                    Parameter::Number(value) => xpath::Value::Number(value),
+                    Parameter::String(ref value) => {
+                        xpath::Value::String(String::from(value.clone()))
+                    },
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// Creates the document `transformToDocument` returns.
+    fn create_document(&self, is_html: bool) -> DomRoot<Document> {
+        let doc = self.window.Document();
+        let loader = DocumentLoader::new(&*doc.loader());
+        let is_html_document = if is_html {
+            IsHTMLDocument::HTMLDocument
+        } else {
+            IsHTMLDocument::NonHTMLDocument
+        };
+        Document::new(
+            &self.window,
+            HasBrowsingContext::No,
+            None,
+            doc.origin().clone(),
+            is_html_document,
+            None,
+            None,
+            DocumentActivity::Inactive,
+            DocumentSource::NotFromParser,
+            loader,
+            None,
+            None,
+            Default::default(),
+        )
+    }
+}
+
+impl XSLTProcessorMethods for XSLTProcessor {
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-importstylesheet
+    fn ImportStylesheet(&self, style: &Node) -> ErrorResult {
+        let copy = Node::clone(style, None, CloneChildrenFlag::CloneChildren);
+        Stylesheet::compile(&copy).map_err(to_dom_error)?;
+        self.stylesheet.set(Some(&copy));
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-transformtofragment
+    fn TransformToFragment(
+        &self,
+        source: &Node,
+        output: &Document,
+    ) -> Fallible<DomRoot<DocumentFragment>> {
+        let stylesheet = self.compile_stylesheet()?;
+        xslt::transform(
+            &stylesheet,
+            source,
+            &self.xpath_parameters(),
+            output,
+            output.is_html_document(),
+        )
+        .map_err(to_dom_error)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-transformtodocument
+    fn TransformToDocument(&self, source: &Node) -> Fallible<DomRoot<Document>> {
+        let stylesheet = self.compile_stylesheet()?;
+        let parameters = self.xpath_parameters();
+
+        // https://www.w3.org/TR/1999/REC-xslt-19991116#output
+        let is_html = match stylesheet.output {
+            OutputMethod::Html => true,
+            OutputMethod::Xml | OutputMethod::Text => false,
+            OutputMethod::Unspecified => {
+                // Whether HTML is used depends on the result, so a first
+                // transformation has to look at it.
+                let result =
+                    xslt::transform(&stylesheet, source, &parameters, &source.owner_doc(), false)
+                        .map_err(to_dom_error)?;
+                has_html_root(result.upcast())
+            },
+        };
+
+        let document = self.create_document(is_html);
+        let result = xslt::transform(&stylesheet, source, &parameters, &document, is_html)
+            .map_err(to_dom_error)?;
+        let root = if stylesheet.output == OutputMethod::Text {
+            // The text is wrapped in an element to make the result a
+            // well-formed document, as Gecko does.
+            let name = QualName::new(
+                None,
+                Namespace::from(TRANSFORMIIX_NAMESPACE),
+                LocalName::from("result"),
+            );
+            let wrapper = Element::create(
+                name,
+                None,
+                &document,
+                ElementCreator::ScriptCreated,
+                CustomElementCreationMode::Synchronous,
+            );
+            let text = Text::new(result.upcast::<Node>().descendant_text_content(), &document);
+            wrapper.upcast::<Node>().AppendChild(text.upcast())?;
+            DomRoot::upcast::<Node>(wrapper)
+        } else {
+            DomRoot::upcast::<Node>(result)
+        };
+        document.upcast::<Node>().AppendChild(&root)?;
+        document.set_ready_state(DocumentReadyState::Complete);
+        Ok(document)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-setparameter
+    #[allow(unsafe_code)]
+    fn SetParameter(
+        &self,
+        cx: JSContext,
+        namespace: Option<DOMString>,
+        local_name: DOMString,
+        value: HandleValue,
+    ) -> ErrorResult {
+        let parameter = if value.get().is_boolean() {
+            Parameter::Boolean(value.get().to_boolean())
+        } else if value.get().is_number() {
+            Parameter::Number(value.get().to_number())
+        } else {
+            let conversion =
+                unsafe { DOMString::from_jsval(*cx, value, StringificationBehavior::Default) };
+            match conversion {
+                Ok(ConversionResult::Success(string)) => Parameter::String(string),
+                Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into())),
+                Err(()) => return Err(Error::JSFailed),
+            }
+        };
+        self.parameters
+            .borrow_mut()
+            .insert((namespace.unwrap_or_default(), local_name), parameter);
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-getparameter
+    #[allow(unsafe_code)]
+    fn GetParameter(
+        &self,
+        cx: JSContext,
+        namespace: Option<DOMString>,
+        local_name: DOMString,
+    ) -> JSVal {
+        let key = (namespace.unwrap_or_default(), local_name);
+        match self.parameters.borrow().get(&key) {
+            Some(&Parameter::Boolean(value)) => BooleanValue(value),
+            Some(&Parameter::Number(value)) => DoubleValue(value),
+            Some(&Parameter::String(ref value)) => unsafe {
+                rooted!(in(*cx) let mut string = UndefinedValue());
+                value.to_jsval(*cx, string.handle_mut());
+                string.get()
+            },
+            None => NullValue(),
+        }
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-removeparameter
+    fn RemoveParameter(&self, namespace: Option<DOMString>, local_name: DOMString) {
+        let key = (namespace.unwrap_or_default(), local_name);
+        self.parameters.borrow_mut().remove(&key);
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-clearparameters
+    fn ClearParameters(&self) {
+        self.parameters.borrow_mut().clear();
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xsltprocessor-reset
+    fn Reset(&self) {
+        self.stylesheet.set(None);
+        self.parameters.borrow_mut().clear();
+    }
+}
+
+/// Whether the result tree is HTML by the rules of the default output
+/// method: its first element is an `html` element in no namespace, and only
+/// whitespace comes before it.
+fn has_html_root(result: &Node) -> bool {
+    for child in result.children() {
+        if let Some(element) = child.downcast::<Element>() {
+            return *element.namespace() == ns!() &&
+                element.local_name().eq_ignore_ascii_case("html");
+        }
+        if let Some(text) = child.downcast::<Text>() {
+            let data = text.upcast::<CharacterData>().data();
+            if !data
+                .chars()
+                .all(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n')
+            {
+                return false;
+            }
+        }
+    }
+    false
+}
+
+fn to_dom_error(error: xslt::Error) -> Error {
+    debug!("XSLT transformation failed: {:?}", error);
+    match error {
+        xslt::Error::XPath(xpath::Error::InvalidExpression(_)) => Error::Syntax,
+        xslt::Error::XPath(xpath::Error::Namespace(_)) => Error::Namespace,
+        xslt::Error::XPath(xpath::Error::Type(message)) => Error::Type(message),
+        xslt::Error::Stylesheet(_) | xslt::Error::Terminated | xslt::Error::TooMuchRecursion => {
+            Error::Operation
+        },
+        xslt::Error::ResultTree(_) => Error::HierarchyRequest,
+    }
+}
@@ -0,0 +1,642 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The drag-and-drop processing model of a document.
+//!
+//! Dragging starts when the pointer moves far enough with the primary button pressed on a
+//! draggable element, which gets a `dragstart` event to fill the data transfer of the drag. Every
+//! move of the pointer after that is an iteration of the processing model: `drag` is fired at the
+//! source, `dragenter` and `dragleave` as the pointer goes over other elements, and `dragover`
+//! at the current target, whose listeners choose the operation that a drop would do. No mouse
+//! events are fired while dragging. Releasing the button drops the data on the current target,
+//! and the source gets a `dragend` event with the operation that was done.
+//!
+//! Drags from outside of the window, which the embedder reports, go through the same steps
+//! without a source: the document only gets the files that were dragged once they are dropped.
+//! <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::datatransferitemlist::DataStoreMode;
+use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
+use crate::dom::element::Element;
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventDefault};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::file::File;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::editing;
+use embedder_traits::{DropEffect, EmbedderMsg};
+use euclid::default::Point2D;
+use script_traits::{DragData, DragEventType, DraggedFile, MouseButton, MouseEventType};
+use servo_atoms::Atom;
+use std::cell::Cell;
+
+/// How far the pointer has to move, in CSS pixels, with the primary button pressed on a
+/// draggable element before the element is dragged.
+const DRAG_THRESHOLD: f32 = 4.;
+
+/// The state of the drag-and-drop operation over a document.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct DragAndDrop {
+    /// The draggable element that the primary button was pressed on, and where, until the
+    /// pointer moves far enough to drag it.
+    pending_source: MutNullableDom<Element>,
+    pending_point: Cell<Option<Point2D<f32>>>,
+    /// The element being dragged, for drags that started in the document.
+    /// <https://html.spec.whatwg.org/multipage/#source-node>
+    source: MutNullableDom<Element>,
+    /// The data transfer of the drag that is going on, if any.
+    data_transfer: MutNullableDom<DataTransfer>,
+    /// <https://html.spec.whatwg.org/multipage/#immediate-user-selection>
+    immediate_user_selection: MutNullableDom<Element>,
+    /// <https://html.spec.whatwg.org/multipage/#current-target-element>
+    current_target: MutNullableDom<EventTarget>,
+    /// <https://html.spec.whatwg.org/multipage/#current-drag-operation>
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    current_drag_operation: Cell<DropEffect>,
+    /// Whether the click that follows the release of the button that ended a drag is ignored.
+    suppress_click: Cell<bool>,
+}
+
+impl DragAndDrop {
+    fn is_dragging(&self) -> bool {
+        self.data_transfer.get().is_some()
+    }
+
+    /// Remembers the draggable inclusive ancestor of `target`, if any, that the primary button
+    /// was pressed on, and returns whether there is one.
+    pub fn maybe_prepare_drag(&self, target: &Element, client_point: Point2D<f32>) -> bool {
+        let source = target
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLElement>)
+            .find(|element| element.is_draggable());
+        let source = match source {
+            Some(source) => source,
+            None => return false,
+        };
+        self.pending_source.set(Some(source.upcast()));
+        self.pending_point.set(Some(client_point));
+        true
+    }
+
+    fn reset(&self) {
+        self.source.set(None);
+        self.data_transfer.set(None);
+        self.immediate_user_selection.set(None);
+        self.current_target.set(None);
+        self.current_drag_operation.set(DropEffect::None);
+    }
+}
+
+/// Starts a drag or continues the one that is going on for a move of the pointer to
+/// `client_point`, over `hit_node`. Returns whether the move is part of a drag, in which case
+/// no mouse events are fired for it.
+pub fn handle_mouse_move(
+    document: &Document,
+    hit_node: Option<&Node>,
+    client_point: Point2D<f32>,
+    pressed_mouse_buttons: u16,
+) -> bool {
+    let state = document.drag_and_drop();
+    if state.source.get().is_some() {
+        // The drag fails if the button was released outside of the document.
+        if pressed_mouse_buttons & 1 == 0 {
+            state.current_drag_operation.set(DropEffect::None);
+            end_drag(document, None, client_point, pressed_mouse_buttons);
+        } else {
+            process_drag(document, hit_node, client_point, pressed_mouse_buttons);
+        }
+        return true;
+    }
+
+    let source = match state.pending_source.get() {
+        Some(source) => source,
+        None => return false,
+    };
+    if pressed_mouse_buttons & 1 == 0 {
+        state.pending_source.set(None);
+        return false;
+    }
+    let start = state.pending_point.get().unwrap_or(client_point);
+    if (client_point - start).length() < DRAG_THRESHOLD {
+        return false;
+    }
+    state.pending_source.set(None);
+    state.pending_point.set(None);
+    start_drag(document, &source, client_point, pressed_mouse_buttons)
+}
+
+/// Ends the drag when the primary button is released, and ignores the click that follows.
+/// Returns whether the mouse event is part of a drag, in which case it isn't fired.
+pub fn handle_mouse_button(
+    document: &Document,
+    event_type: MouseEventType,
+    button: MouseButton,
+    hit_node: Option<&Node>,
+    client_point: Point2D<f32>,
+    pressed_mouse_buttons: u16,
+) -> bool {
+    let state = document.drag_and_drop();
+    match event_type {
+        MouseEventType::MouseDown => {
+            state.suppress_click.set(false);
+            state.source.get().is_some()
+        },
+        MouseEventType::MouseUp => {
+            if button == MouseButton::Left {
+                state.pending_source.set(None);
+            }
+            if state.source.get().is_none() {
+                return false;
+            }
+            if button == MouseButton::Left {
+                process_drag(document, hit_node, client_point, pressed_mouse_buttons);
+                if state.is_dragging() {
+                    end_drag(document, hit_node, client_point, pressed_mouse_buttons);
+                }
+                state.suppress_click.set(true);
+            }
+            true
+        },
+        MouseEventType::Click => state.suppress_click.replace(false),
+    }
+}
+
+/// Handles a drag from outside of the window, of which the embedder reports the moves over
+/// the document and the drop.
+pub fn handle_external_drag(
+    document: &Document,
+    event_type: DragEventType,
+    hit_node: Option<&Node>,
+    client_point: Point2D<f32>,
+    data: DragData,
+) {
+    let state = document.drag_and_drop();
+    // The document is busy with a drag of its own.
+    if state.source.get().is_some() {
+        return;
+    }
+    let started = !state.is_dragging();
+    if started {
+        if event_type == DragEventType::Leave {
+            return;
+        }
+        let data_transfer = DataTransfer::new(document.window(), None, "uninitialized");
+        state.data_transfer.set(Some(&data_transfer));
+    }
+    let data_transfer = state.data_transfer.get().unwrap();
+    if started || event_type == DragEventType::Drop {
+        set_dragged_data(document, &data_transfer, data);
+    }
+
+    match event_type {
+        DragEventType::Enter | DragEventType::Over => {
+            process_drag(document, hit_node, client_point, 0);
+        },
+        DragEventType::Leave => {
+            state.current_drag_operation.set(DropEffect::None);
+            end_drag(document, None, client_point, 0);
+        },
+        DragEventType::Drop => {
+            if started {
+                process_drag(document, hit_node, client_point, 0);
+            }
+            if state.is_dragging() {
+                end_drag(document, hit_node, client_point, 0);
+            }
+        },
+    }
+    let operation = match event_type {
+        DragEventType::Leave => return,
+        DragEventType::Drop => state.current_drag_operation.replace(DropEffect::None),
+        _ => state.current_drag_operation.get(),
+    };
+    document
+        .window()
+        .send_to_embedder(EmbedderMsg::SetDropEffect(operation));
+}
+
+/// Replaces the items of `data_transfer` with the dragged `data`.
+fn set_dragged_data(document: &Document, data_transfer: &DataTransfer, data: DragData) {
+    let items = data_transfer.items();
+    items.remove_text_data(None);
+    items.remove_files();
+    for (format, text) in data.strings {
+        items.set_text_data(
+            DOMString::from(format.to_ascii_lowercase()),
+            DOMString::from(text),
+        );
+    }
+    for file in data.files {
+        match file {
+            DraggedFile::Hidden(name) => {
+                let type_ = mime_guess::from_path(&name)
+                    .first()
+                    .map_or(String::new(), |mime| mime.to_string());
+                items.add_hidden_file(DOMString::from(type_));
+            },
+            DraggedFile::Dropped(selected) => {
+                items.add_file(&File::new_from_selected(document.window(), selected));
+            },
+            // The constellation doesn't give paths to documents.
+            DraggedFile::Path(_) => {},
+        }
+    }
+}
+
+/// Fires `dragstart` at `source`, and starts dragging it unless the event was canceled.
+fn start_drag(
+    document: &Document,
+    source: &Element,
+    client_point: Point2D<f32>,
+    pressed_mouse_buttons: u16,
+) -> bool {
+    let state = document.drag_and_drop();
+    let data_transfer = DataTransfer::new(document.window(), None, "uninitialized");
+    add_default_data(&data_transfer, source);
+    state.source.set(Some(source));
+    state.data_transfer.set(Some(&data_transfer));
+
+    let event = fire_dnd_event(
+        document,
+        atom!("dragstart"),
+        source.upcast(),
+        None,
+        client_point,
+        pressed_mouse_buttons,
+    );
+    if event.DefaultPrevented() {
+        state.reset();
+        return false;
+    }
+    document
+        .window()
+        .send_to_embedder(EmbedderMsg::StartDrag(data_transfer.text_items()));
+    true
+}
+
+/// The data that dragging an image or a link starts with.
+fn add_default_data(data_transfer: &DataTransfer, source: &Element) {
+    let url = if let Some(image) = source.downcast::<HTMLImageElement>() {
+        DOMString::from(image.CurrentSrc().0)
+    } else if source.is::<HTMLAnchorElement>() {
+        let href = source.get_string_attribute(&local_name!("href"));
+        match source.owner_document().base_url().join(&href) {
+            Ok(url) => DOMString::from(url.into_string()),
+            Err(_) => return,
+        }
+    } else {
+        return;
+    };
+    if url.is_empty() {
+        return;
+    }
+    let items = data_transfer.items();
+    items.set_text_data(DOMString::from("text/uri-list"), url.clone());
+    items.set_text_data(DOMString::from("text/plain"), url);
+}
+
+/// An iteration of the processing model, for the pointer at `client_point` over `hit_node`.
+/// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+fn process_drag(
+    document: &Document,
+    hit_node: Option<&Node>,
+    client_point: Point2D<f32>,
+    pressed_mouse_buttons: u16,
+) {
+    let state = document.drag_and_drop();
+    let data_transfer = match state.data_transfer.get() {
+        Some(data_transfer) => data_transfer,
+        None => return,
+    };
+
+    // Step 1.
+    if let Some(source) = state.source.get() {
+        let event = fire_dnd_event(
+            document,
+            atom!("drag"),
+            source.upcast(),
+            None,
+            client_point,
+            pressed_mouse_buttons,
+        );
+        if event.DefaultPrevented() {
+            state.current_drag_operation.set(DropEffect::None);
+            return end_drag(document, None, client_point, pressed_mouse_buttons);
+        }
+    }
+
+    // Step 2.
+    let selection = hit_node.and_then(|node| {
+        node.inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<Element>)
+            .next()
+    });
+    if selection != state.immediate_user_selection.get() {
+        state.immediate_user_selection.set(selection.as_deref());
+        let previous_target = state.current_target.get();
+        match selection {
+            None => state.current_target.set(None),
+            Some(selection) => {
+                let event = fire_dnd_event(
+                    document,
+                    atom!("dragenter"),
+                    selection.upcast(),
+                    previous_target.as_deref(),
+                    client_point,
+                    pressed_mouse_buttons,
+                );
+                if event.DefaultPrevented() || accepts_text_drop(selection.upcast()) {
+                    state.current_target.set(Some(selection.upcast()));
+                } else {
+                    // The body, or the document without one, is the target otherwise.
+                    let fallback = match document.GetBody() {
+                        Some(body) => DomRoot::from_ref(body.upcast::<EventTarget>()),
+                        None => DomRoot::from_ref(document.upcast::<EventTarget>()),
+                    };
+                    if previous_target.as_ref() != Some(&fallback) {
+                        fire_dnd_event(
+                            document,
+                            atom!("dragenter"),
+                            &fallback,
+                            previous_target.as_deref(),
+                            client_point,
+                            pressed_mouse_buttons,
+                        );
+                    }
+                    state.current_target.set(Some(&fallback));
+                }
+            },
+        }
+
+        // Step 3.
+        let current_target = state.current_target.get();
+        if let Some(previous_target) = previous_target {
+            if current_target.as_ref() != Some(&previous_target) {
+                fire_dnd_event(
+                    document,
+                    atom!("dragleave"),
+                    &previous_target,
+                    current_target.as_deref(),
+                    client_point,
+                    pressed_mouse_buttons,
+                );
+            }
+        }
+    }
+
+    // Step 4.
+    let operation = match state.current_target.get() {
+        Some(target) => {
+            let event = fire_dnd_event(
+                document,
+                atom!("dragover"),
+                &target,
+                None,
+                client_point,
+                pressed_mouse_buttons,
+            );
+            if event.DefaultPrevented() {
+                allowed_operation(&data_transfer.effect_allowed(), data_transfer.drop_effect())
+            } else if accepts_text_drop(&target) {
+                DropEffect::Copy
+            } else {
+                DropEffect::None
+            }
+        },
+        None => DropEffect::None,
+    };
+    state.current_drag_operation.set(operation);
+}
+
+/// Drops the data on the current target, or cancels the drag if there is none or if it doesn't
+/// accept the drop, then tells the source how it ended.
+/// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+fn end_drag(
+    document: &Document,
+    hit_node: Option<&Node>,
+    client_point: Point2D<f32>,
+    pressed_mouse_buttons: u16,
+) {
+    let state = document.drag_and_drop();
+    let data_transfer = match state.data_transfer.get() {
+        Some(data_transfer) => data_transfer,
+        None => return,
+    };
+
+    match state.current_target.get() {
+        Some(target) if state.current_drag_operation.get() != DropEffect::None => {
+            let event = fire_dnd_event(
+                document,
+                atom!("drop"),
+                &target,
+                None,
+                client_point,
+                pressed_mouse_buttons,
+            );
+            let operation = if event.DefaultPrevented() {
+                data_transfer.drop_effect()
+            } else if event.get_cancel_state() == EventDefault::Handled {
+                // A text control inserted the text.
+                state.current_drag_operation.get()
+            } else if drop_in_editing_host(document, &target, hit_node, client_point) {
+                state.current_drag_operation.get()
+            } else {
+                DropEffect::None
+            };
+            state.current_drag_operation.set(operation);
+        },
+        Some(target) => {
+            fire_dnd_event(
+                document,
+                atom!("dragleave"),
+                &target,
+                None,
+                client_point,
+                pressed_mouse_buttons,
+            );
+        },
+        None => {},
+    }
+
+    if let Some(source) = state.source.get() {
+        fire_dnd_event(
+            document,
+            atom!("dragend"),
+            source.upcast(),
+            None,
+            client_point,
+            pressed_mouse_buttons,
+        );
+        let operation = state.current_drag_operation.get();
+        document
+            .window()
+            .send_to_embedder(EmbedderMsg::EndDrag(operation));
+    }
+
+    let operation = state.current_drag_operation.get();
+    state.reset();
+    // Embedders are told about the operation of external drops after this.
+    state.current_drag_operation.set(operation);
+}
+
+/// Inserts the dropped text in the editing host of `target`, at the text under the pointer.
+/// Returns whether there was text to insert.
+fn drop_in_editing_host(
+    document: &Document,
+    target: &EventTarget,
+    hit_node: Option<&Node>,
+    client_point: Point2D<f32>,
+) -> bool {
+    let host = match target.downcast::<Node>().and_then(Node::editing_host) {
+        Some(host) => host,
+        None => return false,
+    };
+    let text = match document.drag_and_drop().data_transfer.get() {
+        Some(data_transfer) => data_transfer.items().text_data("text/plain"),
+        None => None,
+    };
+    let text = match text {
+        Some(text) if !text.is_empty() => text,
+        _ => return false,
+    };
+    let position = hit_node.and_then(|node| document.text_position_from_point(node, client_point));
+    editing::insert_from_drop(document, &host, position, text);
+    true
+}
+
+/// Whether `target` inserts the text dropped on it when the drop isn't canceled: text
+/// controls and editable content do.
+fn accepts_text_drop(target: &EventTarget) -> bool {
+    if let Some(input) = target.downcast::<HTMLInputElement>() {
+        return input.accepts_text_drop();
+    }
+    if let Some(textarea) = target.downcast::<HTMLTextAreaElement>() {
+        return textarea.accepts_text_drop();
+    }
+    target
+        .downcast::<Node>()
+        .map_or(false, |node| node.editing_host().is_some())
+}
+
+/// Fires the drag-and-drop event `name` at `target`, with the data transfer in the mode that
+/// the event can use it in.
+/// <https://html.spec.whatwg.org/multipage/#fire-a-dnd-event>
+fn fire_dnd_event(
+    document: &Document,
+    name: Atom,
+    target: &EventTarget,
+    related_target: Option<&EventTarget>,
+    client_point: Point2D<f32>,
+    pressed_mouse_buttons: u16,
+) -> DomRoot<Event> {
+    let state = document.drag_and_drop();
+    let data_transfer = state.data_transfer.get();
+
+    if let Some(ref data_transfer) = data_transfer {
+        // Steps 2-4.
+        let mode = match &*name {
+            "dragstart" => DataStoreMode::ReadWrite,
+            "drop" => DataStoreMode::ReadOnly,
+            _ => DataStoreMode::Protected,
+        };
+        data_transfer.set_mode(Some(mode));
+        let drop_effect = match &*name {
+            "dragenter" | "dragover" => {
+                initial_drop_effect(&data_transfer.effect_allowed(), state.source.get())
+            },
+            "drop" | "dragend" => state.current_drag_operation.get(),
+            _ => DropEffect::None,
+        };
+        data_transfer.set_drop_effect(drop_effect);
+    }
+
+    // Steps 5-9.
+    let cancelable = match &*name {
+        "dragleave" | "dragend" => EventCancelable::NotCancelable,
+        _ => EventCancelable::Cancelable,
+    };
+    let client_x = client_point.x as i32;
+    let client_y = client_point.y as i32;
+    let window = document.window();
+    let event = DragEvent::new(
+        window,
+        DOMString::from(&*name),
+        EventBubbles::Bubbles,
+        cancelable,
+        Some(window),
+        0,
+        client_x,
+        client_y,
+        client_x,
+        client_y,
+        false,
+        false,
+        false,
+        false,
+        0,
+        pressed_mouse_buttons,
+        related_target,
+        data_transfer.as_deref(),
+    );
+    let event = DomRoot::upcast::<Event>(event);
+    event.set_trusted(true);
+    event.fire(target);
+
+    // Step 10.
+    if let Some(data_transfer) = data_transfer {
+        data_transfer.set_mode(None);
+    }
+    event
+}
+
+/// The `dropEffect` of a `dragenter` or `dragover` event.
+/// <https://html.spec.whatwg.org/multipage/#dropEffect-initialisation>
+fn initial_drop_effect(effect_allowed: &str, source: Option<DomRoot<Element>>) -> DropEffect {
+    match effect_allowed {
+        "none" => DropEffect::None,
+        "copy" | "copyLink" | "copyMove" | "all" => DropEffect::Copy,
+        "link" | "linkMove" => DropEffect::Link,
+        "move" => DropEffect::Move,
+        _ => match source {
+            Some(ref source)
+                if source.is::<HTMLAnchorElement>() &&
+                    source.has_attribute(&local_name!("href")) =>
+            {
+                DropEffect::Link
+            },
+            _ => DropEffect::Copy,
+        },
+    }
+}
+
+/// The operation of a `dragover` event that was canceled with `drop_effect`, which has to be
+/// one of the `effect_allowed`.
+/// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+fn allowed_operation(effect_allowed: &str, drop_effect: DropEffect) -> DropEffect {
+    let allowed: &[&str] = match drop_effect {
+        DropEffect::None => &[],
+        DropEffect::Copy => &["uninitialized", "copy", "copyLink", "copyMove", "all"],
+        DropEffect::Link => &["uninitialized", "link", "copyLink", "linkMove", "all"],
+        DropEffect::Move => &["uninitialized", "move", "copyMove", "linkMove", "all"],
+    };
+    if allowed.contains(&effect_allowed) {
+        drop_effect
+    } else {
+        DropEffect::None
+    }
+}
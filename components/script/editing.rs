@@ -0,0 +1,1352 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The editing of the content of editing hosts, the elements with a `contenteditable`
+//! attribute, by the user.
+//!
+//! Edits happen at the selection: text and line breaks are inserted at the caret, after the
+//! selected content is deleted, and deleting without a selection deletes the character or line
+//! break next to the caret. Every edit by the user is announced to the editing host with a
+//! cancelable `beforeinput` event before the DOM changes, and every edit with an `input` event
+//! after. The edits are also what `document.execCommand` does.
+//! <https://w3c.github.io/input-events/>
+//! <https://w3c.github.io/editing/docs/execCommand/>
+//!
+//! The edits of a document can be undone and redone. Its history keeps copies of the content of
+//! the editing host before and after each edit, which are restored in its place.
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::codegen::Bindings::TextBinding::TextMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::document::Document;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::event::{Event, EventDefault};
+use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmldivelement::HTMLDivElement;
+use crate::dom::inputevent::InputEvent;
+use crate::dom::node::{CloneChildrenFlag, Node, ShadowIncluding};
+use crate::dom::range::Range;
+use crate::dom::selection::{character_at, text_units};
+use crate::dom::text::Text;
+use html5ever::{LocalName, QualName};
+use keyboard_types::{Key, KeyState, KeyboardEvent, Modifiers};
+use std::iter;
+use style::values::specified::box_::DisplayOutside;
+
+/// A boundary point: a node, and an offset in it.
+type Position = (DomRoot<Node>, u32);
+
+/// A boundary point in an editing host that doesn't depend on its nodes: the indices of the
+/// children to go through from the host to get to the node, and the offset in it.
+type Path = (Vec<u32>, u32);
+
+/// The most edits that can be undone.
+const MAX_UNDO_TRANSACTIONS: usize = 100;
+
+/// The edits that the user makes with the keyboard, or scripts with `execCommand`.
+/// <https://w3c.github.io/input-events/#interface-InputEvent-Attributes>
+enum EditCommand {
+    InsertText(DOMString),
+    InsertFromDrop(DOMString),
+    InsertParagraph,
+    InsertLineBreak,
+    DeleteContentBackward,
+    DeleteContentForward,
+    FormatBold,
+    FormatItalic,
+    FormatUnderline,
+    HistoryUndo,
+    HistoryRedo,
+}
+
+impl EditCommand {
+    /// The edit that a key press makes, if any.
+    fn for_key(event: &KeyboardEvent) -> Option<EditCommand> {
+        if event
+            .modifiers
+            .intersects(Modifiers::CONTROL | Modifiers::META) &&
+            !event.modifiers.contains(Modifiers::ALT)
+        {
+            let shift = event.modifiers.contains(Modifiers::SHIFT);
+            let key = match event.key {
+                Key::Character(ref key) => key.to_lowercase(),
+                _ => return None,
+            };
+            return Some(match &*key {
+                "z" if shift => EditCommand::HistoryRedo,
+                "z" => EditCommand::HistoryUndo,
+                "y" => EditCommand::HistoryRedo,
+                "b" => EditCommand::FormatBold,
+                "i" => EditCommand::FormatItalic,
+                "u" => EditCommand::FormatUnderline,
+                _ => return None,
+            });
+        }
+        if event
+            .modifiers
+            .intersects(Modifiers::CONTROL | Modifiers::META | Modifiers::ALT)
+        {
+            return None;
+        }
+        Some(match event.key {
+            Key::Character(ref text) => EditCommand::InsertText(DOMString::from(text.clone())),
+            Key::Enter if event.modifiers.contains(Modifiers::SHIFT) => {
+                EditCommand::InsertLineBreak
+            },
+            Key::Enter => EditCommand::InsertParagraph,
+            Key::Backspace => EditCommand::DeleteContentBackward,
+            Key::Delete => EditCommand::DeleteContentForward,
+            _ => return None,
+        })
+    }
+
+    fn input_type(&self) -> &'static str {
+        match *self {
+            EditCommand::InsertText(_) => "insertText",
+            EditCommand::InsertFromDrop(_) => "insertFromDrop",
+            EditCommand::InsertParagraph => "insertParagraph",
+            EditCommand::InsertLineBreak => "insertLineBreak",
+            EditCommand::DeleteContentBackward => "deleteContentBackward",
+            EditCommand::DeleteContentForward => "deleteContentForward",
+            EditCommand::FormatBold => "formatBold",
+            EditCommand::FormatItalic => "formatItalic",
+            EditCommand::FormatUnderline => "formatUnderline",
+            EditCommand::HistoryUndo => "historyUndo",
+            EditCommand::HistoryRedo => "historyRedo",
+        }
+    }
+
+    /// The edit of an `execCommand` command, whose names are case-insensitive.
+    /// <https://w3c.github.io/editing/docs/execCommand/#commands>
+    fn for_command(command_id: &str, value: DOMString) -> Option<EditCommand> {
+        Some(match &*command_id.to_ascii_lowercase() {
+            "bold" => EditCommand::FormatBold,
+            "italic" => EditCommand::FormatItalic,
+            "underline" => EditCommand::FormatUnderline,
+            "inserttext" => EditCommand::InsertText(value),
+            "insertparagraph" => EditCommand::InsertParagraph,
+            "insertlinebreak" => EditCommand::InsertLineBreak,
+            "delete" => EditCommand::DeleteContentBackward,
+            "forwarddelete" => EditCommand::DeleteContentForward,
+            "undo" => EditCommand::HistoryUndo,
+            "redo" => EditCommand::HistoryRedo,
+            _ => return None,
+        })
+    }
+
+    /// The element that is added around text to give it the format of the command.
+    fn format_element(&self) -> Option<LocalName> {
+        match *self {
+            EditCommand::FormatBold => Some(local_name!("b")),
+            EditCommand::FormatItalic => Some(local_name!("i")),
+            EditCommand::FormatUnderline => Some(local_name!("u")),
+            _ => None,
+        }
+    }
+
+    /// Whether `element` gives its content the format of the command.
+    fn applies_format(&self, element: &Element) -> bool {
+        match (self, element.local_name()) {
+            (EditCommand::FormatBold, &local_name!("b")) |
+            (EditCommand::FormatBold, &local_name!("strong")) |
+            (EditCommand::FormatItalic, &local_name!("i")) |
+            (EditCommand::FormatItalic, &local_name!("em")) |
+            (EditCommand::FormatUnderline, &local_name!("u")) => true,
+            _ => false,
+        }
+    }
+
+    fn data(&self) -> Option<DOMString> {
+        match *self {
+            EditCommand::InsertText(ref text) | EditCommand::InsertFromDrop(ref text) => {
+                Some(text.clone())
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Edits the content of the focused editing host, or moves the caret in it, for a key that
+/// was pressed and whose events weren't canceled. Returns whether the key was handled.
+pub fn handle_key(document: &Document, event: &KeyboardEvent) -> bool {
+    if event.state != KeyState::Down {
+        return false;
+    }
+    let host = match document.get_focused_element() {
+        Some(focused) if focused.is_editing_host() => focused,
+        _ => return false,
+    };
+    if !event.modifiers.contains(Modifiers::SHIFT) {
+        let direction = match event.key {
+            Key::ArrowLeft => Some("backward"),
+            Key::ArrowRight => Some("forward"),
+            _ => None,
+        };
+        if let Some(direction) = direction {
+            move_caret(
+                document,
+                &host,
+                direction,
+                event.modifiers.contains(Modifiers::ALT),
+            );
+            return true;
+        }
+    }
+    let command = match EditCommand::for_key(event) {
+        Some(command) => command,
+        None => return false,
+    };
+    let host = match command {
+        EditCommand::HistoryUndo | EditCommand::HistoryRedo => {
+            match command_host(document, &command) {
+                Some(host) => host,
+                None => return true,
+            }
+        },
+        _ => host,
+    };
+    edit(document, &host, command, true);
+    true
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#execcommand()>
+pub fn exec_command(document: &Document, command_id: &str, value: DOMString) -> bool {
+    let command = match EditCommand::for_command(command_id, value) {
+        Some(command) => command,
+        None => return false,
+    };
+    match command_host(document, &command) {
+        Some(host) => edit(document, &host, command, false),
+        None => false,
+    }
+}
+
+/// Inserts `text` that was dropped on `host` at `position`, or at the selection if the
+/// position isn't in the host. Returns whether the text was inserted.
+pub fn insert_from_drop(
+    document: &Document,
+    host: &Element,
+    position: Option<Position>,
+    text: DOMString,
+) -> bool {
+    if let (Some(selection), Some((node, offset))) = (document.GetSelection(), position) {
+        if host.upcast::<Node>().is_inclusive_ancestor_of(&node) {
+            let _ = selection.Collapse(Some(&node), offset);
+        }
+    }
+    edit(document, host, EditCommand::InsertFromDrop(text), true)
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandsupported()>
+pub fn query_command_supported(command_id: &str) -> bool {
+    EditCommand::for_command(command_id, DOMString::new()).is_some()
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandenabled()>
+pub fn query_command_enabled(document: &Document, command_id: &str) -> bool {
+    EditCommand::for_command(command_id, DOMString::new())
+        .map_or(false, |command| command_host(document, &command).is_some())
+}
+
+/// Whether the selected text has the format of a formatting command.
+/// <https://w3c.github.io/editing/docs/execCommand/#querycommandstate()>
+pub fn query_command_state(document: &Document, command_id: &str) -> bool {
+    let command = match EditCommand::for_command(command_id, DOMString::new()) {
+        Some(command) => command,
+        None => return false,
+    };
+    if command.format_element().is_none() {
+        return false;
+    }
+    let host = match selection_host(document) {
+        Some(host) => host,
+        None => return false,
+    };
+    let host = host.upcast::<Node>();
+    let range = match document.GetSelection().and_then(|s| s.GetRangeAt(0).ok()) {
+        Some(range) => range,
+        None => return false,
+    };
+    if range.Collapsed() {
+        return formatting_ancestor(&range.StartContainer(), host, &command).is_some();
+    }
+    let texts = selected_texts(&range, host);
+    !texts.is_empty() &&
+        texts
+            .iter()
+            .all(|text| formatting_ancestor(text, host, &command).is_some())
+}
+
+/// The editing host that `command` edits: the one of the edit that is undone or redone, or
+/// the one that the selection is in.
+fn command_host(document: &Document, command: &EditCommand) -> Option<DomRoot<Element>> {
+    match *command {
+        EditCommand::HistoryUndo => document.edit_history().undo_host(),
+        EditCommand::HistoryRedo => document.edit_history().redo_host(),
+        _ => selection_host(document),
+    }
+}
+
+/// The editing host that the selection is in, if all of it is in one.
+fn selection_host(document: &Document) -> Option<DomRoot<Element>> {
+    let range = document.GetSelection()?.GetRangeAt(0).ok()?;
+    let host = range.StartContainer().editing_host()?;
+    if host
+        .upcast::<Node>()
+        .is_inclusive_ancestor_of(&range.EndContainer())
+    {
+        Some(host)
+    } else {
+        None
+    }
+}
+
+/// Moves the caret by a character or a word, without leaving `host`.
+fn move_caret(document: &Document, host: &Element, direction: &str, word: bool) {
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return,
+    };
+    let host = host.upcast::<Node>();
+    let caret = caret_range(document, host);
+    let granularity = if word { "word" } else { "character" };
+    selection.Modify(
+        DOMString::from("move"),
+        DOMString::from(direction),
+        DOMString::from(granularity),
+    );
+    if let Some(focus) = selection.GetFocusNode() {
+        if !host.is_inclusive_ancestor_of(&focus) {
+            let _ = selection.Collapse(Some(&caret.0), caret.1);
+        }
+    }
+}
+
+/// Where the caret is in `host`, which is placed at the start of the host if the selection
+/// isn't in it. The selection is deleted if it isn't collapsed.
+fn caret_range(document: &Document, host: &Node) -> Position {
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return (DomRoot::from_ref(host), 0),
+    };
+    if let Ok(range) = selection.GetRangeAt(0) {
+        let start = range.StartContainer();
+        let end = range.EndContainer();
+        if host.is_inclusive_ancestor_of(&start) && host.is_inclusive_ancestor_of(&end) {
+            return (start, range.StartOffset());
+        }
+    }
+    let start = first_position(host);
+    let _ = selection.Collapse(Some(&start.0), start.1);
+    start
+}
+
+/// Performs `command` in `host`, and returns whether it changed anything. Edits by the user
+/// can be canceled with their `beforeinput` event, unlike the ones of `execCommand`.
+fn edit(document: &Document, host: &Element, command: EditCommand, by_user: bool) -> bool {
+    let node = host.upcast::<Node>();
+    if by_user && !fire_input_event(document, host, "beforeinput", &command) {
+        return false;
+    }
+    let history = document.edit_history();
+    let edited = match command {
+        EditCommand::HistoryUndo => history.undo(document),
+        EditCommand::HistoryRedo => history.redo(document),
+        _ => {
+            let before = Snapshot::take(document, node);
+            let edited = perform(document, node, &command);
+            if edited {
+                // Text typed in a row is undone at once.
+                let coalesce = by_user && command.input_type() == "insertText";
+                let after = Snapshot::take(document, node);
+                history.record(host, command.input_type(), before, after, coalesce);
+            }
+            edited
+        },
+    };
+    if edited {
+        fire_input_event(document, host, "input", &command);
+    }
+    edited
+}
+
+/// Changes the content of `host` for `command` at the selection, and returns whether it
+/// changed anything.
+fn perform(document: &Document, host: &Node, command: &EditCommand) -> bool {
+    if let Some(name) = command.format_element() {
+        return format(document, host, command, name);
+    }
+    let mut caret = caret_range(document, host);
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+
+    // Delete the selected content first.
+    let mut deleted = false;
+    if let Ok(range) = selection.GetRangeAt(0) {
+        if !range.Collapsed() {
+            if range.DeleteContents().is_err() {
+                return false;
+            }
+            caret = (range.StartContainer(), range.StartOffset());
+            let block = block_ancestor(&caret.0, host);
+            ensure_placeholder(document, &block);
+            deleted = true;
+        }
+    }
+
+    let caret = match *command {
+        EditCommand::InsertText(ref text) | EditCommand::InsertFromDrop(ref text) => {
+            insert_text(document, host, caret, text)
+        },
+        EditCommand::InsertParagraph => insert_paragraph(document, host, caret),
+        EditCommand::InsertLineBreak => insert_line_break(document, host, caret),
+        EditCommand::DeleteContentBackward if !deleted => delete_backward(document, host, caret),
+        EditCommand::DeleteContentForward if !deleted => delete_forward(document, host, caret),
+        _ => caret,
+    };
+    let caret = normalize(caret);
+    let _ = selection.Collapse(Some(&caret.0), caret.1);
+    true
+}
+
+/// Gives the selected text in `host` the format of `command`, by putting it in `name`
+/// elements, or removes the format if all of it has it already. Returns whether any text was
+/// selected.
+fn format(document: &Document, host: &Node, command: &EditCommand, name: LocalName) -> bool {
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+    let range = match selection.GetRangeAt(0) {
+        Ok(range) if !range.Collapsed() => range,
+        _ => return false,
+    };
+    // Split the text at the ends of the selection, which the range follows, so that text
+    // nodes are either selected or not.
+    split_selected_text(&range.EndContainer(), range.EndOffset());
+    split_selected_text(&range.StartContainer(), range.StartOffset());
+    let texts = selected_texts(&range, host);
+    let (first, last) = match (texts.first(), texts.last()) {
+        (Some(first), Some(last)) => (first.clone(), last.clone()),
+        _ => return false,
+    };
+
+    let formatted = texts
+        .iter()
+        .all(|text| formatting_ancestor(text, host, command).is_some());
+    for text in &texts {
+        if formatted {
+            while let Some(ancestor) = formatting_ancestor(text, host, command) {
+                unwrap(&isolate(text, &ancestor));
+            }
+        } else if formatting_ancestor(text, host, command).is_none() {
+            add_format(document, text, name.clone());
+        }
+    }
+    let _ = selection.SetBaseAndExtent(&first, 0, &last, last.len());
+    true
+}
+
+/// Splits the text node `node` at `offset`, unless it is at one of its ends.
+fn split_selected_text(node: &Node, offset: u32) {
+    if let Some(text) = node.downcast::<Text>() {
+        if offset > 0 && offset < node.len() {
+            let _ = text.SplitText(offset);
+        }
+    }
+}
+
+/// The non-empty text nodes in `host` that `range` selects some of.
+fn selected_texts(range: &Range, host: &Node) -> Vec<DomRoot<Node>> {
+    let (start, start_offset) = (range.StartContainer(), range.StartOffset());
+    let (end, end_offset) = (range.EndContainer(), range.EndOffset());
+    host.traverse_preorder(ShadowIncluding::No)
+        .filter(|node| node.is::<Text>() && node.len() > 0)
+        .filter(|node| range.IntersectsNode(node))
+        .filter(|node| {
+            !(*node == start && start_offset == node.len()) && !(*node == end && end_offset == 0)
+        })
+        .collect()
+}
+
+/// The inclusive ancestor of `node` in `host` that gives it the format of `command`.
+fn formatting_ancestor(node: &Node, host: &Node, command: &EditCommand) -> Option<DomRoot<Node>> {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .take_while(|ancestor| &**ancestor != host)
+        .find(|ancestor| {
+            ancestor
+                .downcast::<Element>()
+                .map_or(false, |element| command.applies_format(element))
+        })
+}
+
+/// Puts `text` in a new `name` element, or in the one right before it.
+fn add_format(document: &Document, text: &Node, name: LocalName) {
+    if let Some(previous) = text.GetPreviousSibling() {
+        let is_format = previous
+            .downcast::<Element>()
+            .map_or(false, |element| *element.local_name() == name);
+        if is_format {
+            let _ = previous.AppendChild(text);
+            return;
+        }
+    }
+    let parent = match text.GetParentNode() {
+        Some(parent) => parent,
+        None => return,
+    };
+    let element = Element::create(
+        QualName::new(None, ns!(html), name),
+        None,
+        document,
+        ElementCreator::ScriptCreated,
+        CustomElementCreationMode::Synchronous,
+    );
+    let element = element.upcast::<Node>();
+    if parent.ReplaceChild(element, text).is_ok() {
+        let _ = element.AppendChild(text);
+    }
+}
+
+/// Splits the ancestors of `node` up to `ancestor`, so that `node` is the only content of the
+/// part of `ancestor` that contains it, and returns that part.
+fn isolate(node: &Node, ancestor: &Node) -> DomRoot<Node> {
+    let mut node = DomRoot::from_ref(node);
+    loop {
+        let parent = node.GetParentNode().unwrap();
+        let is_ancestor = &*parent == ancestor;
+        if node.GetNextSibling().is_some() {
+            split_off(&parent, node.index() + 1);
+        }
+        let parent = if node.GetPreviousSibling().is_some() {
+            split_off(&parent, node.index())
+        } else {
+            parent
+        };
+        if is_ancestor {
+            return parent;
+        }
+        node = parent;
+    }
+}
+
+/// Replaces `node` with its children.
+fn unwrap(node: &Node) {
+    let parent = match node.GetParentNode() {
+        Some(parent) => parent,
+        None => return,
+    };
+    while let Some(child) = node.GetFirstChild() {
+        let _ = parent.InsertBefore(&child, Some(node));
+    }
+    node.remove_self();
+}
+
+/// Fires a `beforeinput` or `input` event for `command` at `host`, and returns whether it
+/// wasn't canceled.
+fn fire_input_event(
+    document: &Document,
+    host: &Element,
+    type_: &str,
+    command: &EditCommand,
+) -> bool {
+    let window = document.window();
+    let event = InputEvent::new(
+        window,
+        DOMString::from(type_),
+        true,
+        type_ == "beforeinput",
+        Some(window),
+        0,
+        command.data(),
+        false,
+        DOMString::from(command.input_type()),
+    );
+    let event = event.upcast::<Event>();
+    event.set_trusted(true);
+    event.fire(host.upcast());
+    event.get_cancel_state() != EventDefault::Prevented
+}
+
+fn insert_text(document: &Document, host: &Node, caret: Position, text: &DOMString) -> Position {
+    let (node, offset) = caret;
+    if let Some(data) = node.downcast::<CharacterData>() {
+        let text = preserve_spaces(&data.data(), offset, text);
+        let length = text.encode_utf16().count() as u32;
+        if data.InsertData(offset, text).is_err() {
+            return (node, offset);
+        }
+        return (node, offset + length);
+    }
+
+    // Add to an adjacent text node if there is one, and to a new one otherwise.
+    let before = offset
+        .checked_sub(1)
+        .and_then(|index| node.children().nth(index as usize));
+    if let Some(before) = before.filter(|before| before.is::<Text>()) {
+        let length = before.len();
+        return insert_text(document, host, (before, length), text);
+    }
+    let after = node.children().nth(offset as usize);
+    if let Some(after) = after.filter(|after| after.is::<Text>()) {
+        return insert_text(document, host, (after, 0), text);
+    }
+    let offset = remove_placeholder(&node, offset);
+    let text = preserve_spaces("", 0, text);
+    let new_text = Text::new(text, document);
+    let child = node.children().nth(offset as usize);
+    if node
+        .InsertBefore(new_text.upcast(), child.as_deref())
+        .is_err()
+    {
+        return (node, offset);
+    }
+    let length = new_text.upcast::<Node>().len();
+    (DomRoot::upcast(new_text), length)
+}
+
+/// Replaces the spaces of `text` that would be collapsed away when inserted at `offset` in
+/// `data` with non-breaking spaces, so that they are rendered.
+fn preserve_spaces(data: &str, offset: u32, text: &DOMString) -> DOMString {
+    let units: Vec<u16> = data.encode_utf16().collect();
+    let offset = offset as usize;
+    let mut previous_is_space =
+        offset == 0 || character_at(&units, offset, false).0.is_ascii_whitespace();
+    let next_is_space =
+        offset == units.len() || character_at(&units, offset, true).0.is_ascii_whitespace();
+    let mut result = String::new();
+    let mut characters = text.chars().peekable();
+    while let Some(c) = characters.next() {
+        let is_last = characters.peek().is_none();
+        if c == ' ' && (previous_is_space || (is_last && next_is_space)) {
+            result.push('\u{a0}');
+        } else {
+            result.push(c);
+        }
+        previous_is_space = c == ' ';
+    }
+    DOMString::from(result)
+}
+
+fn insert_paragraph(document: &Document, host: &Node, caret: Position) -> Position {
+    let block = block_ancestor(&caret.0, host);
+    let (block, caret) = if is_splittable(&block, host) {
+        (block, caret)
+    } else {
+        wrap_line(document, &block, caret)
+    };
+    let new_block = split_block(&block, caret);
+    ensure_placeholder(document, &block);
+    ensure_placeholder(document, &new_block);
+    first_position(&new_block)
+}
+
+fn insert_line_break(document: &Document, host: &Node, caret: Position) -> Position {
+    let (parent, index) = split_text(caret);
+    let br = HTMLBRElement::new(local_name!("br"), None, document);
+    let br = br.upcast::<Node>();
+    let child = parent.children().nth(index as usize);
+    if parent.InsertBefore(br, child.as_deref()).is_err() {
+        return (parent, index);
+    }
+    // A line break at the end of a block doesn't start a new line by itself.
+    let block = block_ancestor(&parent, host);
+    if leaf_after(&block, &parent, index + 1).is_none() {
+        let placeholder = HTMLBRElement::new(local_name!("br"), None, document);
+        let next = br.GetNextSibling();
+        let _ = parent.InsertBefore(placeholder.upcast(), next.as_deref());
+    }
+    (parent, index + 1)
+}
+
+fn delete_backward(document: &Document, host: &Node, caret: Position) -> Position {
+    let (node, offset) = caret;
+    if node.is::<Text>() && offset > 0 {
+        let units = text_units(&node);
+        let (_, length) = character_at(&units, offset as usize, false);
+        let offset = offset - length as u32;
+        let _ = node
+            .downcast::<CharacterData>()
+            .unwrap()
+            .DeleteData(offset, length as u32);
+        return remove_if_empty(document, host, (node, offset));
+    }
+
+    let previous = match leaf_before(host, &node, offset) {
+        Some(previous) => previous,
+        None => return (node, offset),
+    };
+    let block = block_ancestor(&node, host);
+    let previous_block = block_ancestor(&previous, host);
+    if block != previous_block {
+        return merge_blocks(document, &previous_block, &block, (node, offset));
+    }
+    if previous.is::<Text>() {
+        let length = previous.len();
+        return delete_backward(document, host, (previous, length));
+    }
+    remove_leaf(document, host, &previous)
+}
+
+fn delete_forward(document: &Document, host: &Node, caret: Position) -> Position {
+    let (node, offset) = caret;
+    if node.is::<Text>() && offset < node.len() {
+        let units = text_units(&node);
+        let (_, length) = character_at(&units, offset as usize, true);
+        let _ = node
+            .downcast::<CharacterData>()
+            .unwrap()
+            .DeleteData(offset, length as u32);
+        return remove_if_empty(document, host, (node, offset));
+    }
+
+    let next = match leaf_after(host, &node, offset) {
+        Some(next) => next,
+        None => return (node, offset),
+    };
+    let block = block_ancestor(&node, host);
+    if block != block_ancestor(&next, host) {
+        // Joining the next block to this one is what deleting backward from its start does.
+        let start = if next.is::<Text>() {
+            (next, 0)
+        } else {
+            let parent = next.GetParentNode().unwrap();
+            let index = next.index();
+            (parent, index)
+        };
+        delete_backward(document, host, start);
+        return (node, offset);
+    }
+    if next.is::<Text>() {
+        return delete_forward(document, host, (next, 0));
+    }
+    remove_leaf(document, host, &next)
+}
+
+/// Removes a line break or a replaced element, and returns the position where it was.
+fn remove_leaf(document: &Document, host: &Node, leaf: &Node) -> Position {
+    let parent = leaf.GetParentNode().unwrap();
+    let index = leaf.index();
+    leaf.remove_self();
+    let block = block_ancestor(&parent, host);
+    ensure_placeholder(document, &block);
+    merge_adjacent_text(&parent, index)
+}
+
+/// Moves the content of `block` into `previous_block`, and returns the position where they
+/// are joined. `caret` is at the start of `block`.
+fn merge_blocks(
+    document: &Document,
+    previous_block: &Node,
+    block: &Node,
+    caret: Position,
+) -> Position {
+    if previous_block.is_ancestor_of(block) {
+        // The block follows the inline content of the previous one, which its content joins.
+        let parent = block.GetParentNode().unwrap();
+        let index = block.index();
+        while let Some(child) = block.GetFirstChild() {
+            let _ = parent.InsertBefore(&child, Some(block));
+        }
+        block.remove_self();
+        return merge_adjacent_text(&parent, index);
+    }
+
+    let index = remove_placeholder(previous_block, previous_block.children_count());
+    let moved: Vec<DomRoot<Node>> = if block.is_ancestor_of(previous_block) {
+        // The caret is on a line of inline content after the previous block, which is the
+        // content that joins it.
+        let first = block_child_at(block, &caret);
+        let mut moved = vec![];
+        for child in first
+            .into_iter()
+            .flat_map(|first| iter::once(first.clone()).chain(first.following_siblings()))
+        {
+            if is_block(&child) {
+                break;
+            }
+            if child.is::<HTMLBRElement>() {
+                child.remove_self();
+                break;
+            }
+            moved.push(child);
+        }
+        moved
+    } else {
+        remove_placeholder(block, 0);
+        block.children().collect()
+    };
+    for child in &moved {
+        let _ = previous_block.AppendChild(child);
+    }
+    if !block.is_ancestor_of(previous_block) && block.children_count() == 0 {
+        block.remove_self();
+    }
+    ensure_placeholder(document, previous_block);
+    merge_adjacent_text(previous_block, index)
+}
+
+/// The child of `block` that contains `position`, or that is right after it.
+fn block_child_at(block: &Node, position: &Position) -> Option<DomRoot<Node>> {
+    let (node, offset) = position;
+    if &**node == block {
+        return block.children().nth(*offset as usize);
+    }
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .find(|ancestor| block.is_parent_of(ancestor))
+}
+
+/// Removes an empty text node at `caret`, and joins the text nodes around it.
+fn remove_if_empty(document: &Document, host: &Node, caret: Position) -> Position {
+    let (node, offset) = caret;
+    if node.len() != 0 {
+        return (node, offset);
+    }
+    let parent = match node.GetParentNode() {
+        Some(parent) => parent,
+        None => return (node, offset),
+    };
+    let index = node.index();
+    node.remove_self();
+    let block = block_ancestor(&parent, host);
+    ensure_placeholder(document, &block);
+    merge_adjacent_text(&parent, index)
+}
+
+/// Joins the text nodes before and after the position (`parent`, `index`) if there are
+/// two, and returns the position between their text.
+fn merge_adjacent_text(parent: &Node, index: u32) -> Position {
+    let before = index
+        .checked_sub(1)
+        .and_then(|index| parent.children().nth(index as usize));
+    let after = parent.children().nth(index as usize);
+    if let (Some(before), Some(after)) = (before, after) {
+        if let (Some(first), Some(second)) = (
+            before.downcast::<CharacterData>(),
+            after.downcast::<CharacterData>(),
+        ) {
+            if before.is::<Text>() && after.is::<Text>() {
+                let offset = before.len();
+                first.AppendData(second.Data());
+                after.remove_self();
+                return (before, offset);
+            }
+        }
+    }
+    (DomRoot::from_ref(parent), index)
+}
+
+/// Splits the text node of `caret` at its offset, and returns the position between the two
+/// halves. Positions that aren't in a text node are returned as they are.
+fn split_text(caret: Position) -> Position {
+    let (node, offset) = caret;
+    let text = match node.downcast::<Text>() {
+        Some(text) => text,
+        None => return (node, offset),
+    };
+    let parent = match node.GetParentNode() {
+        Some(parent) => parent,
+        None => return (node, offset),
+    };
+    match text.SplitText(offset) {
+        Ok(after) => {
+            let index = after.upcast::<Node>().index();
+            (parent, index)
+        },
+        Err(_) => (node, offset),
+    }
+}
+
+/// Splits `block` and the inline elements that contain `caret` in it, and returns the new
+/// block that has the content after the caret.
+fn split_block(block: &Node, caret: Position) -> DomRoot<Node> {
+    let (mut parent, mut index) = split_text(caret);
+    while &*parent != block {
+        let clone = split_off(&parent, index);
+        index = clone.index();
+        parent = parent.GetParentNode().unwrap();
+    }
+    split_off(block, index)
+}
+
+/// Moves the children of `node` from `index` on to a shallow copy of it that is inserted
+/// after it, and returns the copy.
+fn split_off(node: &Node, index: u32) -> DomRoot<Node> {
+    let clone = Node::clone(node, None, CloneChildrenFlag::DoNotCloneChildren);
+    if let Some(element) = clone.downcast::<Element>() {
+        element.remove_attribute(&ns!(), &local_name!("id"));
+    }
+    let moved: Vec<_> = node.children().skip(index as usize).collect();
+    for child in moved {
+        let _ = clone.AppendChild(&child);
+    }
+    if let Some(parent) = node.GetParentNode() {
+        let next = node.GetNextSibling();
+        let _ = parent.InsertBefore(&clone, next.as_deref());
+    }
+    clone
+}
+
+/// Wraps the line of inline content of `container` that contains `caret` in a new `div`, so
+/// that it can be split into paragraphs.
+fn wrap_line(document: &Document, container: &Node, caret: Position) -> (DomRoot<Node>, Position) {
+    let child = block_child_at(container, &caret);
+    let mut line = vec![];
+    // The content of the line before the caret.
+    let before = match child {
+        Some(ref child) => child.GetPreviousSibling(),
+        None => container.GetLastChild(),
+    };
+    for sibling in before
+        .into_iter()
+        .flat_map(|before| iter::once(before.clone()).chain(before.preceding_siblings()))
+    {
+        if is_block(&sibling) || sibling.is::<HTMLBRElement>() {
+            break;
+        }
+        line.insert(0, sibling);
+    }
+    // The content of the line after the caret, up to the line break that ends it.
+    let mut line_break = None;
+    for sibling in child
+        .iter()
+        .flat_map(|child| iter::once(child.clone()).chain(child.following_siblings()))
+    {
+        if is_block(&sibling) {
+            break;
+        }
+        if sibling.is::<HTMLBRElement>() {
+            line_break = Some(sibling);
+            break;
+        }
+        line.push(sibling);
+    }
+
+    let div = HTMLDivElement::new(local_name!("div"), None, document);
+    let div = DomRoot::upcast::<Node>(div);
+    let reference = line
+        .first()
+        .cloned()
+        .or_else(|| line_break.clone())
+        .or_else(|| child.clone());
+    let _ = container.InsertBefore(&div, reference.as_deref());
+    let caret_index = line.len() as u32;
+    for node in &line {
+        let _ = div.AppendChild(node);
+    }
+    if let Some(line_break) = line_break {
+        line_break.remove_self();
+    }
+    let caret = if &*caret.0 == container {
+        // The caret was between children of the container: it is now after the moved content
+        // that was before it.
+        let index = match child {
+            Some(ref child) if div.is_parent_of(child) => child.index(),
+            _ => caret_index,
+        };
+        (div.clone(), index)
+    } else {
+        caret
+    };
+    (div, caret)
+}
+
+/// The inclusive ancestor of `node` in `host` that is a block, or `host` if there is none.
+fn block_ancestor(node: &Node, host: &Node) -> DomRoot<Node> {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .take_while(|ancestor| &**ancestor != host)
+        .find(|ancestor| is_block(ancestor))
+        .unwrap_or_else(|| DomRoot::from_ref(host))
+}
+
+/// Whether `node` is an element whose box isn't inline-level.
+fn is_block(node: &Node) -> bool {
+    node.downcast::<Element>()
+        .and_then(|element| element.style())
+        .map_or(false, |style| {
+            let display = style.get_box().clone_display();
+            !display.is_none() && display.outside() != DisplayOutside::Inline
+        })
+}
+
+/// Whether `block` is a block-level box in `host` that can be split into paragraphs.
+fn is_splittable(block: &Node, host: &Node) -> bool {
+    block != host &&
+        block
+            .downcast::<Element>()
+            .and_then(|element| element.style())
+            .map_or(false, |style| {
+                style.get_box().clone_display().outside() == DisplayOutside::Block
+            })
+}
+
+/// Whether `node` takes up room on a line: non-empty text, line breaks and replaced elements.
+fn is_leaf(node: &Node) -> bool {
+    if node.is::<Text>() {
+        return node.len() > 0;
+    }
+    match node.downcast::<Element>() {
+        Some(element) => match *element.local_name() {
+            local_name!("br") |
+            local_name!("img") |
+            local_name!("hr") |
+            local_name!("input") |
+            local_name!("textarea") |
+            local_name!("select") |
+            local_name!("button") |
+            local_name!("iframe") |
+            local_name!("video") |
+            local_name!("audio") |
+            local_name!("canvas") |
+            local_name!("embed") |
+            local_name!("object") => true,
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+/// The last leaf before the position (`node`, `offset`) in `host`.
+fn leaf_before(host: &Node, node: &Node, offset: u32) -> Option<DomRoot<Node>> {
+    let before = if node.is::<CharacterData>() {
+        node.preceding_nodes(host).next()?
+    } else {
+        match offset
+            .checked_sub(1)
+            .and_then(|index| node.children().nth(index as usize))
+        {
+            Some(child) => child.descending_last_children().last().unwrap_or(child),
+            None => node.preceding_nodes(host).next()?,
+        }
+    };
+    iter::once(before.clone())
+        .chain(before.preceding_nodes(host))
+        .find(|candidate| is_leaf(candidate))
+}
+
+/// The first leaf after the position (`node`, `offset`) in `host`, skipping the line breaks
+/// that only keep empty blocks open.
+fn leaf_after(host: &Node, node: &Node, offset: u32) -> Option<DomRoot<Node>> {
+    let candidates: Box<dyn Iterator<Item = DomRoot<Node>>> = if node.is::<CharacterData>() {
+        Box::new(node.following_nodes(host))
+    } else {
+        match node.children().nth(offset as usize) {
+            Some(child) => Box::new(iter::once(child.clone()).chain(child.following_nodes(host))),
+            None => {
+                let last = node
+                    .descending_last_children()
+                    .last()
+                    .unwrap_or_else(|| DomRoot::from_ref(node));
+                Box::new(last.following_nodes(host))
+            },
+        }
+    };
+    candidates
+        .filter(|candidate| is_leaf(candidate))
+        .find(|candidate| !is_placeholder(host, candidate))
+}
+
+/// Whether `node` is a line break that ends its block, which keeps the block from being
+/// empty rather than starting a new line.
+fn is_placeholder(host: &Node, node: &Node) -> bool {
+    if !node.is::<HTMLBRElement>() {
+        return false;
+    }
+    let block = block_ancestor(node, host);
+    !node
+        .following_nodes(&block)
+        .any(|following| is_leaf(&following))
+}
+
+/// Adds a line break to `block` if it has no content, so that it keeps its height.
+fn ensure_placeholder(document: &Document, block: &Node) {
+    if block
+        .traverse_preorder(ShadowIncluding::No)
+        .any(|node| is_leaf(&node))
+    {
+        return;
+    }
+    let br = HTMLBRElement::new(local_name!("br"), None, document);
+    let _ = block.AppendChild(br.upcast());
+}
+
+/// Removes the line break that keeps `node` from being empty, if it has one and nothing else,
+/// and returns `offset` adjusted for the removal.
+fn remove_placeholder(node: &Node, offset: u32) -> u32 {
+    let last = match node.GetLastChild() {
+        Some(last) if last.is::<HTMLBRElement>() => last,
+        _ => return offset,
+    };
+    let only_placeholder = !node
+        .traverse_preorder(ShadowIncluding::No)
+        .any(|descendant| is_leaf(&descendant) && *descendant != *last);
+    if !only_placeholder {
+        return offset;
+    }
+    let index = last.index();
+    last.remove_self();
+    if offset > index {
+        offset - 1
+    } else {
+        offset
+    }
+}
+
+/// The first position in `node` where text goes: the start of its first text, or the start of
+/// its innermost first inline element.
+fn first_position(node: &Node) -> Position {
+    if let Some(text) = node
+        .traverse_preorder(ShadowIncluding::No)
+        .find(|descendant| is_leaf(descendant))
+        .filter(|leaf| leaf.is::<Text>())
+    {
+        return (text, 0);
+    }
+    let mut container = DomRoot::from_ref(node);
+    while let Some(child) = container.GetFirstChild() {
+        if !child.is::<Element>() || is_leaf(&child) || is_block(&child) {
+            break;
+        }
+        container = child;
+    }
+    (container, 0)
+}
+
+/// Moves a position between children to the end or start of an adjacent text node, where
+/// the caret is painted.
+fn normalize(position: Position) -> Position {
+    let (node, offset) = position;
+    if node.is::<CharacterData>() {
+        return (node, offset);
+    }
+    let before = offset
+        .checked_sub(1)
+        .and_then(|index| node.children().nth(index as usize));
+    if let Some(before) = before.filter(|before| before.is::<Text>()) {
+        let length = before.len();
+        return (before, length);
+    }
+    if let Some(after) = node
+        .children()
+        .nth(offset as usize)
+        .filter(|after| after.is::<Text>())
+    {
+        return (after, 0);
+    }
+    (node, offset)
+}
+
+/// The edits of the editing hosts of a document that can be undone, and the undone edits that
+/// can be redone.
+/// <https://w3c.github.io/editing/docs/execCommand/#the-undo-command>
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+pub struct EditHistory {
+    undo_stack: DomRefCell<Vec<Transaction>>,
+    redo_stack: DomRefCell<Vec<Transaction>>,
+}
+
+/// An edit of an editing host: copies of its content before and after it, and where the
+/// selection was in it.
+#[derive(JSTraceable, MallocSizeOf)]
+#[unrooted_must_root_lint::must_root]
+struct Transaction {
+    host: Dom<Element>,
+    input_type: String,
+    before: Vec<Dom<Node>>,
+    before_selection: (Path, Path),
+    after: Vec<Dom<Node>>,
+    after_selection: (Path, Path),
+}
+
+impl EditHistory {
+    /// The editing host of the edit that would be undone.
+    fn undo_host(&self) -> Option<DomRoot<Element>> {
+        self.undo_stack
+            .borrow()
+            .last()
+            .map(|transaction| DomRoot::from_ref(&*transaction.host))
+    }
+
+    /// The editing host of the edit that would be redone.
+    fn redo_host(&self) -> Option<DomRoot<Element>> {
+        self.redo_stack
+            .borrow()
+            .last()
+            .map(|transaction| DomRoot::from_ref(&*transaction.host))
+    }
+
+    /// Adds an edit that can be undone, which makes the undone edits impossible to redo. With
+    /// `coalesce`, the edit is undone together with the previous one if they are of the same
+    /// type.
+    #[allow(unrooted_must_root)]
+    fn record(
+        &self,
+        host: &Element,
+        input_type: &str,
+        before: Snapshot,
+        after: Snapshot,
+        coalesce: bool,
+    ) {
+        self.redo_stack.borrow_mut().clear();
+        let mut undo_stack = self.undo_stack.borrow_mut();
+        if coalesce {
+            if let Some(last) = undo_stack.last_mut() {
+                if &*last.host == host && last.input_type == input_type {
+                    last.after = after.stored_children();
+                    last.after_selection = after.selection;
+                    return;
+                }
+            }
+        }
+        undo_stack.push(Transaction {
+            host: Dom::from_ref(host),
+            input_type: input_type.to_owned(),
+            before: before.stored_children(),
+            before_selection: before.selection,
+            after: after.stored_children(),
+            after_selection: after.selection,
+        });
+        if undo_stack.len() > MAX_UNDO_TRANSACTIONS {
+            undo_stack.remove(0);
+        }
+    }
+
+    /// Restores the content of the editing host of the last edit from before it, and returns
+    /// whether there was an edit to undo.
+    #[allow(unrooted_must_root)]
+    fn undo(&self, document: &Document) -> bool {
+        let (host, snapshot) = match self.undo_stack.borrow().last() {
+            Some(transaction) => (
+                DomRoot::from_ref(&*transaction.host),
+                Snapshot::from_stored(&transaction.before, &transaction.before_selection),
+            ),
+            None => return false,
+        };
+        snapshot.restore(document, host.upcast());
+        self.redo_stack
+            .borrow_mut()
+            .extend(self.undo_stack.borrow_mut().pop());
+        true
+    }
+
+    /// Restores the content of the editing host of the last undone edit from after it, and
+    /// returns whether there was an edit to redo.
+    #[allow(unrooted_must_root)]
+    fn redo(&self, document: &Document) -> bool {
+        let (host, snapshot) = match self.redo_stack.borrow().last() {
+            Some(transaction) => (
+                DomRoot::from_ref(&*transaction.host),
+                Snapshot::from_stored(&transaction.after, &transaction.after_selection),
+            ),
+            None => return false,
+        };
+        snapshot.restore(document, host.upcast());
+        self.undo_stack
+            .borrow_mut()
+            .extend(self.redo_stack.borrow_mut().pop());
+        true
+    }
+}
+
+/// Copies of the children of an editing host, and where the selection is in it.
+struct Snapshot {
+    children: Vec<DomRoot<Node>>,
+    selection: (Path, Path),
+}
+
+impl Snapshot {
+    fn take(document: &Document, host: &Node) -> Snapshot {
+        let children = host
+            .children()
+            .map(|child| Node::clone(&child, None, CloneChildrenFlag::CloneChildren))
+            .collect();
+        let selection = document
+            .GetSelection()
+            .and_then(|selection| selection.GetRangeAt(0).ok())
+            .filter(|range| {
+                host.is_inclusive_ancestor_of(&range.StartContainer()) &&
+                    host.is_inclusive_ancestor_of(&range.EndContainer())
+            })
+            .map_or(Default::default(), |range| {
+                (
+                    (path_to(host, &range.StartContainer()), range.StartOffset()),
+                    (path_to(host, &range.EndContainer()), range.EndOffset()),
+                )
+            });
+        Snapshot {
+            children,
+            selection,
+        }
+    }
+
+    fn from_stored(children: &[Dom<Node>], selection: &(Path, Path)) -> Snapshot {
+        Snapshot {
+            children: children
+                .iter()
+                .map(|child| DomRoot::from_ref(&**child))
+                .collect(),
+            selection: selection.clone(),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn stored_children(&self) -> Vec<Dom<Node>> {
+        self.children
+            .iter()
+            .map(|child| Dom::from_ref(&**child))
+            .collect()
+    }
+
+    /// Replaces the content of `host` with copies of the one of the snapshot, and selects what
+    /// was selected then.
+    fn restore(&self, document: &Document, host: &Node) {
+        while let Some(child) = host.GetFirstChild() {
+            child.remove_self();
+        }
+        for child in &self.children {
+            let copy = Node::clone(child, None, CloneChildrenFlag::CloneChildren);
+            let _ = host.AppendChild(&copy);
+        }
+        if let Some(selection) = document.GetSelection() {
+            let start = position_at(host, &self.selection.0);
+            let end = position_at(host, &self.selection.1);
+            let _ = selection.SetBaseAndExtent(&start.0, start.1, &end.0, end.1);
+        }
+    }
+}
+
+/// The path from `host` to its inclusive descendant `node`.
+fn path_to(host: &Node, node: &Node) -> Vec<u32> {
+    let mut path: Vec<u32> = node
+        .inclusive_ancestors(ShadowIncluding::No)
+        .take_while(|ancestor| &**ancestor != host)
+        .map(|ancestor| ancestor.index())
+        .collect();
+    path.reverse();
+    path
+}
+
+/// The boundary point that `path` leads to in `host`, or the closest one that exists.
+fn position_at(host: &Node, path: &Path) -> Position {
+    let mut node = DomRoot::from_ref(host);
+    for index in &path.0 {
+        match node.children().nth(*index as usize) {
+            Some(child) => node = child,
+            None => return (node.clone(), node.len()),
+        }
+    }
+    let offset = path.1.min(node.len());
+    (node, offset)
+}
@@ -2,10 +2,12 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::document_loader::CancellableFetchId;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseBinding::ResponseMethods;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DOMResponseType;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
@@ -19,6 +21,7 @@ use crate::dom::promise::Promise;
 use crate::dom::request::Request;
 use crate::dom::response::Response;
 use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::dom::window::Window;
 use crate::network_listener::{
     self, submit_timing_data, NetworkListener, PreInvoke, ResourceTimingListener,
 };
@@ -45,6 +48,8 @@ struct FetchContext {
     response_object: Trusted<Response>,
     body: Vec<u8>,
     resource_timing: ResourceFetchTiming,
+    /// The fetch's entry in its document's loader, released once it completes.
+    cancellable_fetch_id: Option<CancellableFetchId>,
 }
 
 /// RAII fetch canceller object. By default initialized to not having a canceller
@@ -106,8 +111,10 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         headers: request.headers.clone(),
         unsafe_request: request.unsafe_request,
         body: request.body.clone(),
+        keep_alive: request.keep_alive,
         service_workers_mode: ServiceWorkersMode::All,
         destination: request.destination,
+        priority: request.priority,
         synchronous: request.synchronous,
         mode: request.mode.clone(),
         cache_mode: request.cache_mode,
@@ -173,9 +180,10 @@ pub fn Fetch(
         response_object: Trusted::new(&*response),
         body: vec![],
         resource_timing: ResourceFetchTiming::new(timing_type),
+        cancellable_fetch_id: None,
     }));
     let listener = NetworkListener {
-        context: fetch_context,
+        context: fetch_context.clone(),
         task_source: global.networking_task_source(),
         canceller: Some(global.task_canceller(TaskSourceName::Networking)),
     };
@@ -186,12 +194,24 @@ pub fn Fetch(
             listener.notify_fetch(message.to().unwrap());
         }),
     );
-    core_resource_thread
-        .send(NetTraitsFetch(
-            request_init,
-            FetchChannels::ResponseMsg(action_sender, None),
-        ))
-        .unwrap();
+    // Fetches made by a document are cancelled when it is aborted, except
+    // for keepalive requests, which are allowed to outlive it.
+    match global.downcast::<Window>() {
+        Some(window) if !request_init.keep_alive => {
+            let id = window
+                .Document()
+                .loader_mut()
+                .fetch_async_cancellable(request_init, action_sender);
+            // The responses are handled in tasks, which can't run before this.
+            fetch_context.lock().unwrap().cancellable_fetch_id = Some(id);
+        },
+        _ => core_resource_thread
+            .send(NetTraitsFetch(
+                request_init,
+                FetchChannels::ResponseMsg(action_sender, None),
+            ))
+            .unwrap(),
+    }
 
     promise
 }
@@ -268,6 +288,11 @@ impl FetchResponseListener for FetchContext {
         let response = self.response_object.root();
         let _ac = enter_realm(&*response);
         response.finish(mem::replace(&mut self.body, vec![]));
+        if let Some(id) = self.cancellable_fetch_id.take() {
+            if let Some(window) = response.global().downcast::<Window>() {
+                window.Document().loader_mut().finish_cancellable_fetch(id);
+            }
+        }
         // TODO
         // ... trailerObject is not supported in Servo yet.
     }
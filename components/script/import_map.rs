@@ -0,0 +1,419 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Import maps, which allow web pages to control the resolution of
+//! bare module specifiers.
+//! https://html.spec.whatwg.org/multipage/#import-maps
+
+use crate::dom::bindings::error::{Error, Fallible};
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+use servo_url::ServoUrl;
+use std::cmp::Ordering;
+
+/// https://html.spec.whatwg.org/multipage/#module-specifier-map
+pub type ModuleSpecifierMap = IndexMap<String, Option<ServoUrl>>;
+
+/// https://html.spec.whatwg.org/multipage/#module-integrity-map
+pub type ModuleIntegrityMap = IndexMap<ServoUrl, String>;
+
+/// https://html.spec.whatwg.org/multipage/#import-map
+#[derive(Clone, Debug, Default, JSTraceable)]
+pub struct ImportMap {
+    /// https://html.spec.whatwg.org/multipage/#concept-import-map-imports
+    imports: ModuleSpecifierMap,
+    /// https://html.spec.whatwg.org/multipage/#concept-import-map-scopes
+    scopes: IndexMap<ServoUrl, ModuleSpecifierMap>,
+    /// https://html.spec.whatwg.org/multipage/#concept-import-map-integrity
+    integrity: ModuleIntegrityMap,
+}
+
+impl ImportMap {
+    /// https://html.spec.whatwg.org/multipage/#parse-an-import-map-string
+    pub fn parse(input: &str, base_url: &ServoUrl) -> Fallible<ImportMap> {
+        // Step 1.
+        let parsed: Value = serde_json::from_str(input).map_err(|_| Error::Syntax)?;
+
+        // Step 2.
+        let parsed = match parsed {
+            Value::Object(object) => object,
+            _ => {
+                return Err(Error::Type(
+                    "The top-level value must be a JSON object".to_owned(),
+                ))
+            },
+        };
+
+        // Steps 3-4.
+        let imports = match parsed.get("imports") {
+            Some(Value::Object(imports)) => {
+                sort_and_normalize_a_module_specifier_map(imports, base_url)
+            },
+            Some(_) => {
+                return Err(Error::Type(
+                    "The \"imports\" top-level key must be a JSON object".to_owned(),
+                ))
+            },
+            None => ModuleSpecifierMap::new(),
+        };
+
+        // Steps 5-6.
+        let scopes = match parsed.get("scopes") {
+            Some(Value::Object(scopes)) => sort_and_normalize_scopes(scopes, base_url)?,
+            Some(_) => {
+                return Err(Error::Type(
+                    "The \"scopes\" top-level key must be a JSON object".to_owned(),
+                ))
+            },
+            None => IndexMap::new(),
+        };
+
+        // Steps 7-8.
+        let integrity = match parsed.get("integrity") {
+            Some(Value::Object(integrity)) => normalize_a_module_integrity_map(integrity, base_url),
+            Some(_) => {
+                return Err(Error::Type(
+                    "The \"integrity\" top-level key must be a JSON object".to_owned(),
+                ))
+            },
+            None => ModuleIntegrityMap::new(),
+        };
+
+        // Step 9.
+        for key in parsed.keys() {
+            if key != "imports" && key != "scopes" && key != "integrity" {
+                warn!("Invalid top-level key \"{}\" in import map, ignoring.", key);
+            }
+        }
+
+        // Step 10.
+        Ok(ImportMap {
+            imports,
+            scopes,
+            integrity,
+        })
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#resolve-a-module-specifier
+    /// Steps 4-13, with the import map of the relevant global.
+    pub fn resolve_a_module_specifier(
+        &self,
+        specifier: &str,
+        base_url: &ServoUrl,
+    ) -> Fallible<ServoUrl> {
+        // Step 5.
+        let base_url_string = base_url.as_str();
+
+        // Step 6.
+        let as_url = resolve_a_url_like_module_specifier(specifier, base_url);
+
+        // Step 7.
+        let normalized_specifier = match as_url {
+            Some(ref url) => url.as_str(),
+            None => specifier,
+        };
+
+        // Step 8.
+        for (scope_prefix, scope_imports) in &self.scopes {
+            let scope_prefix = scope_prefix.as_str();
+            // Step 8.1.
+            if scope_prefix == base_url_string ||
+                (scope_prefix.ends_with('/') && base_url_string.starts_with(scope_prefix))
+            {
+                // Step 8.1.1.
+                let scope_imports_match =
+                    resolve_an_imports_match(normalized_specifier, as_url.as_ref(), scope_imports)?;
+
+                // Step 8.1.2.
+                if let Some(url) = scope_imports_match {
+                    return Ok(url);
+                }
+            }
+        }
+
+        // Step 9.
+        let top_level_imports_match =
+            resolve_an_imports_match(normalized_specifier, as_url.as_ref(), &self.imports)?;
+
+        // Step 10.
+        if let Some(url) = top_level_imports_match {
+            return Ok(url);
+        }
+
+        // Step 11.
+        if let Some(url) = as_url {
+            return Ok(url);
+        }
+
+        // Step 13.
+        Err(Error::Type(format!(
+            "Relative references to \"{}\" must start with \"/\", \"./\" or \"../\"",
+            specifier
+        )))
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#resolving-a-module-integrity-metadata
+    pub fn resolve_a_module_integrity_metadata(&self, url: &ServoUrl) -> String {
+        // Steps 2-3.
+        self.integrity.get(url).cloned().unwrap_or_default()
+    }
+}
+
+/// https://html.spec.whatwg.org/multipage/#resolving-an-imports-match
+fn resolve_an_imports_match(
+    normalized_specifier: &str,
+    as_url: Option<&ServoUrl>,
+    specifier_map: &ModuleSpecifierMap,
+) -> Fallible<Option<ServoUrl>> {
+    // Step 1.
+    for (specifier_key, resolution_result) in specifier_map {
+        // Step 1.1.
+        if specifier_key == normalized_specifier {
+            return match resolution_result {
+                // Step 1.1.2.
+                Some(url) => Ok(Some(url.clone())),
+                // Step 1.1.1.
+                None => Err(Error::Type(format!(
+                    "Resolution of \"{}\" was blocked by a null entry",
+                    specifier_key
+                ))),
+            };
+        }
+
+        // Step 1.2.
+        if specifier_key.ends_with('/') &&
+            normalized_specifier.starts_with(specifier_key.as_str()) &&
+            as_url.map_or(true, is_special)
+        {
+            // Step 1.2.1.
+            let resolution_result = match resolution_result {
+                Some(url) => url,
+                None => {
+                    return Err(Error::Type(format!(
+                        "Resolution of \"{}\" was blocked by a null entry",
+                        specifier_key
+                    )))
+                },
+            };
+
+            // Step 1.2.3.
+            let after_prefix = &normalized_specifier[specifier_key.len()..];
+
+            // Step 1.2.4.
+            assert!(resolution_result.as_str().ends_with('/'));
+
+            // Steps 1.2.5-1.2.6.
+            let url = match ServoUrl::parse_with_base(Some(resolution_result), after_prefix) {
+                Ok(url) => url,
+                Err(_) => {
+                    return Err(Error::Type(format!(
+                        "Resolution of \"{}\" was blocked since \"{}\" could not be parsed \
+                         relative to \"{}\"",
+                        normalized_specifier, after_prefix, resolution_result
+                    )))
+                },
+            };
+
+            // Step 1.2.8.
+            if !url.as_str().starts_with(resolution_result.as_str()) {
+                return Err(Error::Type(format!(
+                    "Resolution of \"{}\" was blocked due to it backtracking above its prefix \
+                     \"{}\"",
+                    normalized_specifier, specifier_key
+                )));
+            }
+
+            // Step 1.2.9.
+            return Ok(Some(url));
+        }
+    }
+
+    // Step 2.
+    Ok(None)
+}
+
+/// https://html.spec.whatwg.org/multipage/#resolving-a-url-like-module-specifier
+pub fn resolve_a_url_like_module_specifier(
+    specifier: &str,
+    base_url: &ServoUrl,
+) -> Option<ServoUrl> {
+    // Step 1.
+    if specifier.starts_with('/') || specifier.starts_with("./") || specifier.starts_with("../") {
+        return ServoUrl::parse_with_base(Some(base_url), specifier).ok();
+    }
+
+    // Steps 2-4.
+    ServoUrl::parse(specifier).ok()
+}
+
+/// https://html.spec.whatwg.org/multipage/#normalizing-a-specifier-key
+fn normalize_a_specifier_key(specifier_key: &str, base_url: &ServoUrl) -> Option<String> {
+    // Step 1.
+    if specifier_key.is_empty() {
+        warn!("Specifier keys of import maps may not be the empty string, ignoring.");
+        return None;
+    }
+
+    // Steps 2-4.
+    match resolve_a_url_like_module_specifier(specifier_key, base_url) {
+        Some(url) => Some(url.into_string()),
+        None => Some(specifier_key.to_owned()),
+    }
+}
+
+/// https://html.spec.whatwg.org/multipage/#sorting-and-normalizing-a-module-specifier-map
+fn sort_and_normalize_a_module_specifier_map(
+    original_map: &Map<String, Value>,
+    base_url: &ServoUrl,
+) -> ModuleSpecifierMap {
+    // Step 1.
+    let mut normalized = ModuleSpecifierMap::new();
+
+    // Step 2.
+    for (specifier_key, value) in original_map {
+        // Steps 2.1-2.2.
+        let normalized_specifier_key = match normalize_a_specifier_key(specifier_key, base_url) {
+            Some(key) => key,
+            None => continue,
+        };
+
+        // Step 2.3.
+        let value = match value {
+            Value::String(value) => value,
+            _ => {
+                warn!(
+                    "Addresses of import maps must be strings, ignoring \"{}\".",
+                    specifier_key
+                );
+                normalized.insert(normalized_specifier_key, None);
+                continue;
+            },
+        };
+
+        // Steps 2.4-2.5.
+        let address_url = match resolve_a_url_like_module_specifier(value, base_url) {
+            Some(url) => url,
+            None => {
+                warn!(
+                    "Invalid address \"{}\" for \"{}\", ignoring.",
+                    value, specifier_key
+                );
+                normalized.insert(normalized_specifier_key, None);
+                continue;
+            },
+        };
+
+        // Step 2.6.
+        if specifier_key.ends_with('/') && !address_url.as_str().ends_with('/') {
+            warn!(
+                "Invalid address \"{}\" for package specifier key \"{}\", package addresses \
+                 must end with \"/\".",
+                address_url, specifier_key
+            );
+            normalized.insert(normalized_specifier_key, None);
+            continue;
+        }
+
+        // Step 2.7.
+        normalized.insert(normalized_specifier_key, Some(address_url));
+    }
+
+    // Step 3.
+    normalized.sort_by(|a, _, b, _| code_unit_cmp(b, a));
+    normalized
+}
+
+/// https://html.spec.whatwg.org/multipage/#sorting-and-normalizing-scopes
+fn sort_and_normalize_scopes(
+    original_map: &Map<String, Value>,
+    base_url: &ServoUrl,
+) -> Fallible<IndexMap<ServoUrl, ModuleSpecifierMap>> {
+    // Step 1.
+    let mut normalized = IndexMap::new();
+
+    // Step 2.
+    for (scope_prefix, potential_specifier_map) in original_map {
+        // Step 2.1.
+        let potential_specifier_map = match potential_specifier_map {
+            Value::Object(map) => map,
+            _ => {
+                return Err(Error::Type(format!(
+                    "The value of the scope \"{}\" must be a JSON object",
+                    scope_prefix
+                )))
+            },
+        };
+
+        // Steps 2.2-2.3.
+        let scope_prefix_url = match ServoUrl::parse_with_base(Some(base_url), scope_prefix) {
+            Ok(url) => url,
+            Err(_) => {
+                warn!("Invalid scope prefix URL \"{}\", ignoring.", scope_prefix);
+                continue;
+            },
+        };
+
+        // Steps 2.4-2.5.
+        normalized.insert(
+            scope_prefix_url,
+            sort_and_normalize_a_module_specifier_map(potential_specifier_map, base_url),
+        );
+    }
+
+    // Step 3.
+    normalized.sort_by(|a: &ServoUrl, _, b: &ServoUrl, _| code_unit_cmp(b.as_str(), a.as_str()));
+    Ok(normalized)
+}
+
+/// https://html.spec.whatwg.org/multipage/#normalizing-a-module-integrity-map
+fn normalize_a_module_integrity_map(
+    original_map: &Map<String, Value>,
+    base_url: &ServoUrl,
+) -> ModuleIntegrityMap {
+    // Step 1.
+    let mut normalized = ModuleIntegrityMap::new();
+
+    // Step 2.
+    for (key, value) in original_map {
+        // Steps 2.1-2.2.
+        let resolved_url = match resolve_a_url_like_module_specifier(key, base_url) {
+            Some(url) => url,
+            None => {
+                warn!("Invalid integrity key \"{}\", ignoring.", key);
+                continue;
+            },
+        };
+
+        // Step 2.3.
+        let value = match value {
+            Value::String(value) => value,
+            _ => {
+                warn!(
+                    "Integrity metadata of \"{}\" must be a string, ignoring.",
+                    key
+                );
+                continue;
+            },
+        };
+
+        // Step 2.4.
+        normalized.insert(resolved_url, value.clone());
+    }
+
+    // Step 3.
+    normalized
+}
+
+/// https://url.spec.whatwg.org/#is-special
+fn is_special(url: &ServoUrl) -> bool {
+    match url.scheme() {
+        "ftp" | "file" | "http" | "https" | "ws" | "wss" => true,
+        _ => false,
+    }
+}
+
+/// https://html.spec.whatwg.org/multipage/#code-unit-less-than
+fn code_unit_cmp(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
@@ -65,12 +65,18 @@ pub mod document_loader;
 mod dom;
 #[warn(deprecated)]
 mod canvas_state;
+#[warn(deprecated)]
+mod drag_and_drop;
+#[warn(deprecated)]
+mod editing;
 mod euclidext;
 #[warn(deprecated)]
 pub mod fetch;
 #[warn(deprecated)]
 mod image_listener;
 #[warn(deprecated)]
+mod import_map;
+#[warn(deprecated)]
 mod init;
 #[warn(deprecated)]
 mod layout_image;
@@ -81,8 +87,12 @@ mod microtask;
 #[warn(deprecated)]
 mod network_listener;
 #[warn(deprecated)]
+mod origin_trials;
+#[warn(deprecated)]
 mod realms;
 #[warn(deprecated)]
+mod reporting;
+#[warn(deprecated)]
 mod script_module;
 #[warn(deprecated)]
 pub mod script_runtime;
@@ -90,10 +100,14 @@ pub mod script_runtime;
 #[allow(unsafe_code)]
 pub mod script_thread;
 #[warn(deprecated)]
+mod segmentation;
+#[warn(deprecated)]
 mod serviceworker_manager;
 #[warn(deprecated)]
 mod serviceworkerjob;
 #[warn(deprecated)]
+mod speculation_rules;
+#[warn(deprecated)]
 mod stylesheet_loader;
 #[warn(deprecated)]
 mod stylesheet_set;
@@ -112,7 +126,18 @@ mod timers;
 #[warn(deprecated)]
 mod unpremultiplytable;
 #[warn(deprecated)]
+mod webauthn;
+#[warn(deprecated)]
+mod webcrypto;
+#[warn(deprecated)]
 mod webdriver_handlers;
+#[warn(deprecated)]
+#[allow(unsafe_code)]
+mod window_named_properties;
+#[warn(deprecated)]
+mod xpath;
+#[warn(deprecated)]
+mod xslt;
 
 pub use init::{init, init_service_workers};
 pub use script_runtime::JSEngineSetup;
@@ -39,11 +39,6 @@ pub fn submit_timing<T: ResourceTimingListener + FetchResponseListener>(listener
     }
 
     let (initiator_type, url) = listener.resource_timing_information();
-    if initiator_type == InitiatorType::Other {
-        warn!("Ignoring InitiatorType::Other resource {:?}", url);
-        return;
-    }
-
     submit_timing_data(
         &listener.resource_timing_global(),
         url,
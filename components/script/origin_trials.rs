@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Experimental features enabled for the documents of some origins only,
+//! rather than everywhere by their preference.
+//!
+//! Features are named after the boolean preference that otherwise enables
+//! them, such as `dom.webgpu.enabled`, and anything guarded by `[Pref]` in
+//! WebIDL is exposed to the documents they are enabled for. The embedder
+//! enables them with two string preferences, each holding `;`-separated
+//! entries:
+//!
+//! * `dom.origin_trials.allowlist`, with entries of an origin followed by
+//!   the features its documents get, such as
+//!   `https://example.com dom.webgpu.enabled`.
+//! * `dom.origin_trials.tokens`, with entries of a token, an origin and the
+//!   features that the documents of the origin get when they are served with
+//!   the token in an `Origin-Trial` response header.
+
+use http::header::HeaderMap;
+use servo_config::pref;
+use servo_url::{ImmutableOrigin, ServoUrl};
+use std::collections::HashSet;
+
+/// The experimental features enabled for a document.
+#[derive(Clone, Debug, Default, JSTraceable, MallocSizeOf)]
+pub struct OriginTrials {
+    features: HashSet<String>,
+}
+
+impl OriginTrials {
+    /// The features enabled for the documents of `origin` that are served with
+    /// `tokens`.
+    pub fn new(origin: &ImmutableOrigin, tokens: &[String]) -> OriginTrials {
+        let mut features = HashSet::new();
+        for entry in entries(&pref!(dom.origin_trials.allowlist)) {
+            if let Some((entry_origin, entry_features)) = entry.split_first() {
+                if matches_origin(entry_origin, origin) {
+                    features.extend(entry_features.iter().map(|feature| feature.to_string()));
+                }
+            }
+        }
+        for entry in entries(&pref!(dom.origin_trials.tokens)) {
+            if let [token, entry_origin, entry_features @ ..] = &*entry {
+                if tokens.iter().any(|t| t == *token) && matches_origin(entry_origin, origin) {
+                    features.extend(entry_features.iter().map(|feature| feature.to_string()));
+                }
+            }
+        }
+        OriginTrials { features }
+    }
+
+    /// Whether the feature named after the preference `pref` is enabled.
+    pub fn is_enabled(&self, pref: &str) -> bool {
+        self.features.contains(pref)
+    }
+}
+
+/// The tokens in the `Origin-Trial` headers of a response.
+pub fn tokens_from_headers(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all("origin-trial")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn entries(value: &str) -> impl Iterator<Item = Vec<&str>> {
+    value
+        .split(';')
+        .map(|entry| entry.split_whitespace().collect::<Vec<_>>())
+        .filter(|entry| !entry.is_empty())
+}
+
+fn matches_origin(entry_origin: &str, origin: &ImmutableOrigin) -> bool {
+    ServoUrl::parse(entry_origin).map_or(false, |url| url.origin() == *origin)
+}
@@ -0,0 +1,219 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The data of the reports generated by the [Reporting API], and their
+//! delivery to the endpoints a document configures with the
+//! `Reporting-Endpoints` and `Report-To` headers.
+//!
+//! [Reporting API]: https://w3c.github.io/reporting/
+
+use crate::dom::globalscope::GlobalScope;
+use content_security_policy as csp;
+use http::header::{self, HeaderMap, HeaderValue};
+use http::Method;
+use net_traits::request::{CredentialsMode, Destination, Referrer, RequestBuilder, RequestMode};
+use net_traits::{CoreResourceMsg, FetchChannels, ReportingEndpoint};
+use servo_url::ServoUrl;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The body of a report of type "csp-violation".
+/// <https://w3c.github.io/webappsec-csp/#cspviolationreportbody>
+#[derive(Clone, JSTraceable, MallocSizeOf, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CSPViolationReportData {
+    #[serde(rename = "documentURL")]
+    pub document_url: String,
+    pub referrer: Option<String>,
+    #[serde(rename = "blockedURL")]
+    pub blocked_url: Option<String>,
+    pub effective_directive: String,
+    pub original_policy: String,
+    pub source_file: Option<String>,
+    pub sample: Option<String>,
+    pub disposition: String,
+    pub status_code: u16,
+    pub line_number: Option<u32>,
+    pub column_number: Option<u32>,
+}
+
+/// The body of a report of type "deprecation".
+/// <https://wicg.github.io/deprecation-reporting/#deprecationreportbody>
+#[derive(Clone, JSTraceable, MallocSizeOf, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeprecationReportData {
+    pub id: String,
+    pub message: String,
+    pub source_file: Option<String>,
+    pub line_number: Option<u32>,
+    pub column_number: Option<u32>,
+}
+
+#[derive(Clone, JSTraceable, MallocSizeOf, Serialize)]
+#[serde(untagged)]
+pub enum ReportBodyData {
+    CSPViolation(CSPViolationReportData),
+    Deprecation(DeprecationReportData),
+}
+
+/// <https://w3c.github.io/reporting/#concept-reports>
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct ReportData {
+    pub type_: String,
+    pub url: String,
+    pub body: ReportBodyData,
+    /// The name of the endpoint the report is delivered to.
+    pub destination: String,
+    /// When the report was generated, in milliseconds since the epoch.
+    pub timestamp: u64,
+}
+
+impl ReportData {
+    pub fn new(type_: &str, url: &ServoUrl, body: ReportBodyData, destination: &str) -> Self {
+        ReportData {
+            type_: type_.to_owned(),
+            url: strip_url_for_report(url),
+            body,
+            destination: destination.to_owned(),
+            timestamp: now_ms(),
+        }
+    }
+}
+
+/// The members of a report in a delivery request body.
+#[derive(Serialize)]
+struct SerializedReport<'a> {
+    age: u64,
+    #[serde(rename = "type")]
+    type_: &'a str,
+    url: &'a str,
+    user_agent: &'a str,
+    body: &'a ReportBodyData,
+}
+
+/// Builds the body of a report for a violation of a Content Security Policy.
+/// <https://w3c.github.io/webappsec-csp/#deprecated-serialize-violation>
+pub fn csp_violation_report(
+    global: &GlobalScope,
+    violation: &csp::Violation,
+    referrer: Option<String>,
+) -> ReportBodyData {
+    let blocked_url = match violation.resource {
+        csp::ViolationResource::Url(ref url) => url.to_string(),
+        _ => "inline".to_owned(),
+    };
+    let original_policy = violation
+        .policy
+        .directive_set
+        .iter()
+        .map(|directive| {
+            let mut serialized = directive.name.clone();
+            for token in &directive.value {
+                serialized.push(' ');
+                serialized.push_str(token);
+            }
+            serialized
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let disposition = match violation.policy.disposition {
+        csp::PolicyDisposition::Enforce => "enforce",
+        csp::PolicyDisposition::Report => "report",
+    };
+    ReportBodyData::CSPViolation(CSPViolationReportData {
+        document_url: strip_url_for_report(&global.get_url()),
+        referrer,
+        blocked_url: Some(blocked_url),
+        effective_directive: violation.directive.name.clone(),
+        original_policy,
+        source_file: None,
+        sample: None,
+        disposition: disposition.to_owned(),
+        status_code: 200,
+        line_number: None,
+        column_number: None,
+    })
+}
+
+/// The endpoint named by the `report-to` directive of the violated policy.
+pub fn csp_report_destination(violation: &csp::Violation) -> Option<String> {
+    violation
+        .policy
+        .directive_set
+        .iter()
+        .find(|directive| directive.name == "report-to")
+        .and_then(|directive| directive.value.first().cloned())
+}
+
+/// Sends `reports` to the endpoints in `endpoints` they are destined for,
+/// one request per endpoint. Reports for unknown endpoints are dropped.
+/// <https://w3c.github.io/reporting/#send-reports>
+pub fn deliver_reports(
+    global: &GlobalScope,
+    endpoints: &[ReportingEndpoint],
+    reports: Vec<ReportData>,
+) {
+    let user_agent = global.get_user_agent();
+    let now = now_ms();
+    for endpoint in endpoints {
+        let serialized: Vec<SerializedReport> = reports
+            .iter()
+            .filter(|report| report.destination == endpoint.name)
+            .map(|report| SerializedReport {
+                age: now.saturating_sub(report.timestamp),
+                type_: &report.type_,
+                url: &report.url,
+                user_agent: &user_agent,
+                body: &report.body,
+            })
+            .collect();
+        if serialized.is_empty() {
+            continue;
+        }
+        let body = match serde_json::to_vec(&serialized) {
+            Ok(body) => body,
+            Err(_) => continue,
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/reports+json"),
+        );
+        let request = RequestBuilder::new(endpoint.url.clone())
+            .method(Method::POST)
+            .headers(headers)
+            .body(Some(body))
+            .destination(Destination::Report)
+            .mode(RequestMode::CorsMode)
+            .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+            .origin(global.origin().immutable().clone())
+            .referrer(Some(Referrer::NoReferrer))
+            .pipeline_id(Some(global.pipeline_id()));
+
+        // Nothing is done with the response.
+        let _ = global
+            .core_resource_thread()
+            .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
+    }
+}
+
+/// Reports leave out the credentials and fragment of the URLs in them.
+/// <https://w3c.github.io/reporting/#strip-url-for-use-in-reports>
+fn strip_url_for_report(url: &ServoUrl) -> String {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return url.scheme().to_owned();
+    }
+    let mut url = url.clone();
+    url.set_fragment(None);
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.into_string()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
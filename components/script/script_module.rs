@@ -10,7 +10,7 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowBinding::WindowMethods;
 use crate::dom::bindings::conversions::jsstring_to_str;
 use crate::dom::bindings::error::report_pending_exception;
-use crate::dom::bindings::error::Error;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
@@ -21,6 +21,7 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::document::Document;
 use crate::dom::element::Element;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmllinkelement::HTMLLinkElement;
 use crate::dom::htmlscriptelement::{HTMLScriptElement, ScriptId};
 use crate::dom::htmlscriptelement::{ScriptOrigin, ScriptType, SCRIPT_JS_MIMES};
 use crate::dom::node::document_from_node;
@@ -62,6 +63,7 @@ use net_traits::request::{Referrer, RequestBuilder, RequestMode};
 use net_traits::{FetchMetadata, Metadata};
 use net_traits::{FetchResponseListener, NetworkError};
 use net_traits::{ResourceFetchTiming, ResourceTimingType};
+use servo_config::pref;
 use servo_url::ServoUrl;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
@@ -71,7 +73,6 @@ use std::ptr;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use url::ParseError as UrlParseError;
 
 use indexmap::IndexSet;
 
@@ -441,13 +442,19 @@ impl ModuleTree {
         }
     }
 
+    /// https://html.spec.whatwg.org/multipage/#run-a-module-script
+    /// Step 7: evaluate the module record, returning its evaluation promise.
+    ///
+    /// The promise settles once the module and its dependencies have been
+    /// evaluated, which for a module using top-level await is only once its
+    /// awaited promises settle. The SpiderMonkey version in use evaluates the
+    /// whole graph synchronously, so the promise is settled on return.
     #[allow(unsafe_code)]
-    pub fn execute_module(
-        &self,
-        global: &GlobalScope,
-        module_record: HandleObject,
-    ) -> Result<(), ModuleError> {
+    pub fn execute_module(&self, global: &GlobalScope, module_record: HandleObject) -> Rc<Promise> {
         let _ac = JSAutoRealm::new(*global.get_cx(), *global.reflector().get_jsobject());
+        let in_realm_proof = AlreadyInRealm::assert(global);
+        let evaluation_promise =
+            Promise::new_in_current_realm(global, InRealm::Already(&in_realm_proof));
 
         unsafe {
             if !ModuleEvaluate(*global.get_cx(), module_record) {
@@ -460,14 +467,32 @@ impl ModuleTree {
                 ));
                 JS_ClearPendingException(*global.get_cx());
 
-                Err(ModuleError::RawException(RootedTraceableBox::from_box(
-                    Heap::boxed(exception.get()),
-                )))
+                evaluation_promise.reject(global.get_cx(), exception.handle());
             } else {
                 debug!("module evaluated successfully");
-                Ok(())
+                evaluation_promise.resolve_native(&());
             }
         }
+
+        evaluation_promise
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#run-a-module-script
+    /// Step 8: upon rejection of the evaluation promise, report its reason.
+    /// The reason also becomes the error of the module tree, so that running
+    /// the module again reports it rather than evaluating it.
+    pub fn report_evaluation_error(
+        module_tree: Rc<ModuleTree>,
+        global: &GlobalScope,
+        evaluation_promise: &Promise,
+    ) {
+        let handler = PromiseNativeHandler::new(
+            global,
+            None,
+            Some(Box::new(ModuleEvaluationErrorHandler { module_tree })),
+        );
+        let _realm = enter_realm(global);
+        evaluation_promise.append_native_handler(&handler);
     }
 
     #[allow(unsafe_code)]
@@ -562,24 +587,33 @@ impl ModuleTree {
                     return Err(get_element_error);
                 }
 
+                // TODO: JSON and CSS module scripts need the module type from the
+                // import attributes (`with { type: "json" }`) of this request, but
+                // the SpiderMonkey version we embed neither parses import
+                // attributes nor supports synthetic modules. Every requested
+                // module is treated as a JavaScript module until the engine does.
                 rooted!(in(*global.get_cx()) let specifier = GetRequestedModuleSpecifier(
                     *global.get_cx(), element.handle()
                 ));
 
                 let url = ModuleTree::resolve_module_specifier(
-                    *global.get_cx(),
+                    &global,
                     &base_url,
                     specifier.handle().into_handle(),
                 );
 
-                if url.is_err() {
-                    let specifier_error =
-                        gen_type_error(&global, "Wrong module specifier".to_owned());
-
-                    return Err(specifier_error);
+                match url {
+                    Ok(url) => {
+                        specifier_urls.insert(url);
+                    },
+                    Err(error) => {
+                        let message = match error {
+                            Error::Type(message) => message,
+                            _ => "Wrong module specifier".to_owned(),
+                        };
+                        return Err(gen_type_error(&global, message));
+                    },
                 }
-
-                specifier_urls.insert(url.unwrap());
             }
         }
 
@@ -589,17 +623,24 @@ impl ModuleTree {
     /// The following module specifiers are allowed by the spec:
     ///  - a valid absolute URL
     ///  - a valid relative URL that starts with "/", "./" or "../"
+    ///  - a bare specifier mapped by the import map of the global
     ///
-    /// Bareword module specifiers are currently disallowed as these may be given
-    /// special meanings in the future.
+    /// Without import maps, bareword module specifiers are disallowed.
     /// https://html.spec.whatwg.org/multipage/#resolve-a-module-specifier
     #[allow(unsafe_code)]
     fn resolve_module_specifier(
-        cx: *mut JSContext,
+        global: &GlobalScope,
         url: &ServoUrl,
         specifier: RawHandle<*mut JSString>,
-    ) -> Result<ServoUrl, UrlParseError> {
-        let specifier_str = unsafe { jsstring_to_str(cx, *specifier) };
+    ) -> Fallible<ServoUrl> {
+        let specifier_str = unsafe { jsstring_to_str(*global.get_cx(), *specifier) };
+
+        if pref!(dom.import_maps.enabled) {
+            return global
+                .import_map()
+                .borrow()
+                .resolve_a_module_specifier(&specifier_str, url);
+        }
 
         // Step 1.
         if let Ok(specifier_url) = ServoUrl::parse(&specifier_str) {
@@ -611,11 +652,15 @@ impl ModuleTree {
             !specifier_str.starts_with("./") &&
             !specifier_str.starts_with("../")
         {
-            return Err(UrlParseError::InvalidDomainCharacter);
+            return Err(Error::Type(format!(
+                "Relative references to \"{}\" must start with \"/\", \"./\" or \"../\"",
+                specifier_str
+            )));
         }
 
         // Step 3.
-        return ServoUrl::parse_with_base(Some(url), &specifier_str.clone());
+        ServoUrl::parse_with_base(Some(url), &specifier_str)
+            .map_err(|_| Error::Type("Wrong module specifier".to_owned()))
     }
 
     /// https://html.spec.whatwg.org/multipage/#finding-the-first-parse-error
@@ -686,13 +731,37 @@ impl Callback for ModuleHandler {
     }
 }
 
+/// Reports the error a module evaluation promise is rejected with.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ModuleEvaluationErrorHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    module_tree: Rc<ModuleTree>,
+}
+
+impl Callback for ModuleEvaluationErrorHandler {
+    #[allow(unsafe_code)]
+    fn callback(&self, cx: *mut JSContext, v: HandleValue) {
+        let global = unsafe {
+            let in_realm_proof = AlreadyInRealm::assert_for_cx(SafeJSContext::from_ptr(cx));
+            GlobalScope::from_context(cx, InRealm::Already(&in_realm_proof))
+        };
+        self.module_tree.set_error(Some(ModuleError::RawException(
+            RootedTraceableBox::from_box(Heap::boxed(v.get())),
+        )));
+        self.module_tree.report_error(&global);
+    }
+}
+
 /// The owner of the module
-/// It can be `worker` or `script` element
+/// It can be `worker`, `script` element or a `link` element preloading
+/// the module
 #[derive(Clone)]
 pub enum ModuleOwner {
     #[allow(dead_code)]
     Worker(TrustedWorkerAddress),
     Window(Trusted<HTMLScriptElement>),
+    /// https://html.spec.whatwg.org/multipage/#link-type-modulepreload
+    ModulePreload(Trusted<HTMLLinkElement>),
 }
 
 impl ModuleOwner {
@@ -700,6 +769,15 @@ impl ModuleOwner {
         match &self {
             ModuleOwner::Worker(worker) => (*worker.root().clone()).global(),
             ModuleOwner::Window(script) => (*script.root()).global(),
+            ModuleOwner::ModulePreload(link) => (*link.root()).global(),
+        }
+    }
+
+    fn document(&self) -> Option<DomRoot<Document>> {
+        match &self {
+            ModuleOwner::Worker(_) => None,
+            ModuleOwner::Window(script) => Some(document_from_node(&*script.root())),
+            ModuleOwner::ModulePreload(link) => Some(document_from_node(&*link.root())),
         }
     }
 
@@ -744,10 +822,10 @@ impl ModuleOwner {
     pub fn finish_module_load(&self, module_url: Option<ServoUrl>, is_top_level: bool) {
         match &self {
             ModuleOwner::Worker(_) => unimplemented!(),
-            ModuleOwner::Window(script) => {
+            ModuleOwner::Window(_) | ModuleOwner::ModulePreload(_) => {
                 let global = self.global();
 
-                let document = document_from_node(&*script.root());
+                let document = self.document().unwrap();
 
                 let module_map = global.get_module_map().borrow();
 
@@ -767,12 +845,13 @@ impl ModuleOwner {
 
                     (module_tree, load)
                 } else {
+                    let script_id = match &self {
+                        ModuleOwner::Window(script) => script.root().get_script_id(),
+                        _ => unreachable!("Only script elements own inline module scripts"),
+                    };
                     let module_tree = {
                         let inline_module_map = global.get_inline_module_map().borrow();
-                        inline_module_map
-                            .get(&script.root().get_script_id())
-                            .unwrap()
-                            .clone()
+                        inline_module_map.get(&script_id).unwrap().clone()
                     };
 
                     let base_url = document.base_url();
@@ -841,19 +920,29 @@ impl ModuleOwner {
                     },
                 };
 
-                if is_top_level {
-                    let r#async = script
-                        .root()
-                        .upcast::<Element>()
-                        .has_attribute(&local_name!("async"));
-
-                    if !r#async && (&*script.root()).get_parser_inserted() {
-                        document.deferred_script_loaded(&*script.root(), load);
-                    } else if !r#async && !(&*script.root()).get_non_blocking() {
-                        document.asap_in_order_script_loaded(&*script.root(), load);
-                    } else {
-                        document.asap_script_loaded(&*script.root(), load);
-                    };
+                if !is_top_level {
+                    return;
+                }
+
+                match &self {
+                    ModuleOwner::Window(script) => {
+                        let r#async = script
+                            .root()
+                            .upcast::<Element>()
+                            .has_attribute(&local_name!("async"));
+
+                        if !r#async && (&*script.root()).get_parser_inserted() {
+                            document.deferred_script_loaded(&*script.root(), load);
+                        } else if !r#async && !(&*script.root()).get_non_blocking() {
+                            document.asap_in_order_script_loaded(&*script.root(), load);
+                        } else {
+                            document.asap_script_loaded(&*script.root(), load);
+                        };
+                    },
+                    ModuleOwner::ModulePreload(link) => {
+                        link.root().module_preload_finished(load.is_ok());
+                    },
+                    ModuleOwner::Worker(_) => unreachable!(),
                 }
             },
         }
@@ -939,6 +1028,8 @@ impl FetchResponseListener for ModuleContext {
                 if let Ok(content_type) = Mime::from_str(&content_type.to_string()) {
                     let essence_mime = content_type.essence_str();
 
+                    // TODO: accept application/json and text/css once module
+                    // types can be requested, see resolve_requested_module_specifiers.
                     if !SCRIPT_JS_MIMES.contains(&essence_mime) {
                         return Err(NetworkError::Internal(format!(
                             "Invalid MIME type: {}",
@@ -1096,7 +1187,7 @@ unsafe extern "C" fn HostResolveImportedModule(
     }
 
     // Step 5.
-    let url = ModuleTree::resolve_module_specifier(*global_scope.get_cx(), &base_url, specifier);
+    let url = ModuleTree::resolve_module_specifier(&global_scope, &base_url, specifier);
 
     // Step 6.
     assert!(url.is_ok());
@@ -1132,6 +1223,8 @@ pub fn fetch_external_module_script(
     integrity_metadata: String,
     credentials_mode: CredentialsMode,
 ) -> Rc<Promise> {
+    owner.global().stop_acquiring_import_maps();
+
     // Step 1.
     fetch_single_module_script(
         owner,
@@ -1222,10 +1315,7 @@ pub fn fetch_single_module_script(
         _ => RequestMode::CorsMode,
     };
 
-    let document: Option<DomRoot<Document>> = match &owner {
-        ModuleOwner::Worker(_) => None,
-        ModuleOwner::Window(script) => Some(document_from_node(&*script.root())),
-    };
+    let document: Option<DomRoot<Document>> = owner.document();
 
     // Step 7-8.
     let request = RequestBuilder::new(url.clone())
@@ -1280,6 +1370,7 @@ pub fn fetch_inline_module_script(
     credentials_mode: CredentialsMode,
 ) {
     let global = owner.global();
+    global.stop_acquiring_import_maps();
 
     let module_tree = ModuleTree::new(url.clone());
 
@@ -1424,13 +1515,17 @@ fn fetch_module_descendants(
                     }
 
                     // Step 2.
+                    let integrity_metadata = global
+                        .import_map()
+                        .borrow()
+                        .resolve_a_module_integrity_metadata(requested_url);
                     fetch_single_module_script(
                         owner.clone(),
                         requested_url.clone(),
                         destination.clone(),
                         Referrer::Client,
                         ParserMetadata::NotParserInserted,
-                        "".to_owned(),
+                        integrity_metadata,
                         credentials_mode.clone(),
                         Some(module_tree.url.clone()),
                         false,
@@ -229,8 +229,6 @@ unsafe extern "C" fn promise_rejection_tracker(
     state: PromiseRejectionHandlingState,
     _data: *mut c_void,
 ) {
-    // TODO: Step 2 - If script's muted errors is true, terminate these steps.
-
     // Step 3.
     let cx = JSContext::from_ptr(cx);
     let in_realm_proof = AlreadyInRealm::assert_for_cx(cx);
@@ -238,6 +236,11 @@ unsafe extern "C" fn promise_rejection_tracker(
 
     wrap_panic(
         AssertUnwindSafe(|| {
+            // Step 2.
+            if global.running_script_has_muted_errors() {
+                return;
+            }
+
             match state {
                 // Step 4.
                 PromiseRejectionHandlingState::Unhandled => {
@@ -656,8 +659,9 @@ fn in_range<T: PartialOrd + Copy>(val: T, min: T, max: T) -> Option<T> {
     }
 }
 
+/// The size of the DOM object that `obj` reflects, if any.
 #[allow(unsafe_code)]
-unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
+pub unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
     match get_dom_class(obj) {
         Ok(v) => {
             let dom_object = private_from_object(obj) as *const c_void;
@@ -20,9 +20,11 @@
 use crate::devtools;
 use crate::document_loader::DocumentLoader;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::{
     DocumentMethods, DocumentReadyState,
 };
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
 use crate::dom::bindings::codegen::Bindings::TransitionEventBinding::TransitionEventInit;
@@ -55,6 +57,7 @@ use crate::dom::node::{
     from_untrusted_node_address, window_from_node, Node, NodeDamage, ShadowIncluding,
 };
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performancelongtasktiming::{PerformanceLongTaskTiming, LONG_TASK_THRESHOLD_NS};
 use crate::dom::performancepainttiming::PerformancePaintTiming;
 use crate::dom::serviceworker::TrustedServiceWorkerAddress;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
@@ -66,12 +69,14 @@ use crate::dom::windowproxy::WindowProxy;
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::worklet::WorkletThreadPool;
 use crate::dom::workletglobalscope::WorkletGlobalScopeInit;
+use crate::dom::workletglobalscope::WorkletRegistration;
 use crate::fetch::FetchCanceller;
 use crate::microtask::{Microtask, MicrotaskQueue};
 use crate::realms::enter_realm;
-use crate::script_runtime::{get_reports, new_rt_and_cx, JSContext, Runtime, ScriptPort};
+use crate::script_runtime::{get_reports, get_size, new_rt_and_cx, JSContext, Runtime, ScriptPort};
 use crate::script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory};
 use crate::serviceworkerjob::{Job, JobQueue};
+use crate::speculation_rules::prefetch_a_document;
 use crate::task_manager::TaskManager;
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
@@ -119,36 +124,44 @@ use msg::constellation_msg::{PipelineNamespace, TopLevelBrowsingContextId};
 use net_traits::image_cache::{ImageCache, PendingImageResponse};
 use net_traits::request::{CredentialsMode, Destination, RedirectMode, RequestBuilder};
 use net_traits::storage_thread::StorageType;
+use net_traits::user_agent_data::UserAgentOverride;
 use net_traits::{FetchMetadata, FetchResponseListener, FetchResponseMsg};
 use net_traits::{
     Metadata, NetworkError, ReferrerPolicy, ResourceFetchTiming, ResourceThreads,
     ResourceTimingType,
 };
 use percent_encoding::percent_decode;
-use profile_traits::mem::{self as profile_mem, OpaqueSender, ReportsChan};
+use profile_traits::mem::{self as profile_mem, OpaqueSender, Report, ReportKind, ReportsChan};
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
 use script_layout_interface::message::{self, LayoutThreadInit, Msg, ReflowGoal};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::CompositorEvent::{
-    CompositionEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent, TouchEvent,
-    WheelEvent,
+    CompositionEvent, DoubleTapEvent, DragEvent, KeyboardEvent, MouseButtonEvent, MouseMoveEvent,
+    PointerLockedMoveEvent, ResizeEvent, TouchEvent, WheelEvent,
 };
+use script_traits::NetworkStatus;
+use script_traits::NewLayoutInfo;
+use script_traits::PersistedUserState;
 use script_traits::StructuredSerializedData;
-use script_traits::{CompositorEvent, ConstellationControlMsg};
 use script_traits::{
-    DiscardBrowsingContext, DocumentActivity, EventResult, HistoryEntryReplacement,
+    CompositorEvent, ConstellationControlMsg, DeviceEmulation, FrameThrottlingPolicy,
+};
+use script_traits::{
+    DeviceSensorReading, DiscardBrowsingContext, DocumentActivity, EventResult,
+    HistoryEntryReplacement,
 };
 use script_traits::{InitialScriptState, JsEvalResult, LayoutMsg, LoadData, LoadOrigin};
-use script_traits::{MediaSessionActionType, MouseButton, MouseEventType, NewLayoutInfo};
-use script_traits::{Painter, ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory};
-use script_traits::{ScriptToConstellationChan, TimerSchedulerMsg};
+use script_traits::{MediaSessionActionType, MouseButton, MouseEventType, NavigationType};
+use script_traits::{ProgressiveWebMetricType, ScriptMsg, ScriptThreadFactory};
+use script_traits::{ScreenshotArea, ScriptToConstellationChan, TimerSchedulerMsg};
 use script_traits::{TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta};
 use script_traits::{UpdatePipelineIdReason, WebrenderIpcSender, WindowSizeData, WindowSizeType};
-use servo_atoms::Atom;
+use servo_config::prefs;
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use std::borrow::Cow;
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::{hash_map, HashMap, HashSet};
 use std::default::Default;
 use std::ops::Deref;
@@ -162,6 +175,7 @@ use std::thread;
 use std::time::{Duration, SystemTime};
 use style::dom::OpaqueNode;
 use style::thread_state::{self, ThreadState};
+use style_traits::CSSPixel;
 use time::{at_utc, get_time, precise_time_ns, Timespec};
 use url::Position;
 use webrender_api::units::LayoutPixel;
@@ -170,6 +184,10 @@ use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
 
+/// The longest an idle period lasts, in nanoseconds.
+/// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+const MAX_IDLE_PERIOD_NS: u64 = 50_000_000;
+
 thread_local!(static SCRIPT_THREAD_ROOT: Cell<Option<*const ScriptThread>> = Cell::new(None));
 
 pub unsafe fn trace_thread(tr: *mut JSTracer) {
@@ -213,6 +231,11 @@ struct InProgressLoad {
     navigation_start: u64,
     /// High res timestamp reporting the time when the browser started this load.
     navigation_start_precise: u64,
+    /// How the load came to happen.
+    navigation_type: NavigationType,
+    /// The persisted state of the session history entry loaded, if this is the
+    /// reload of its discarded document.
+    persisted_user_state: Option<PersistedUserState>,
     /// For cancelling the fetch
     canceller: FetchCanceller,
     /// Flag for sharing with the layout thread that is not yet created.
@@ -232,6 +255,8 @@ impl InProgressLoad {
         url: ServoUrl,
         origin: MutableOrigin,
         layout_is_busy: Arc<AtomicBool>,
+        navigation_type: NavigationType,
+        persisted_user_state: Option<PersistedUserState>,
     ) -> InProgressLoad {
         let current_time = get_time();
         let navigation_start_precise = precise_time_ns();
@@ -252,6 +277,8 @@ impl InProgressLoad {
             origin: origin,
             navigation_start: (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64,
             navigation_start_precise: navigation_start_precise,
+            navigation_type,
+            persisted_user_state,
             canceller: Default::default(),
             layout_is_busy: layout_is_busy,
         }
@@ -274,12 +301,11 @@ pub enum MainThreadScriptMsg {
     /// Notifies the script thread that a new worklet has been loaded, and thus the page should be
     /// reflowed.
     WorkletLoaded(PipelineId),
-    /// Notifies the script thread that a new paint worklet has been registered.
-    RegisterPaintWorklet {
+    /// Notifies the script thread that a worklet global scope has registered
+    /// something, like a paint worklet.
+    WorkletRegistration {
         pipeline_id: PipelineId,
-        name: Atom,
-        properties: Vec<Atom>,
-        painter: Box<dyn Painter>,
+        registration: WorkletRegistration,
     },
     /// Dispatches a job queue.
     DispatchJobQueue { scope_url: ServoUrl },
@@ -696,6 +722,25 @@ pub struct ScriptThread {
 
     /// Code is running as a consequence of a user interaction
     is_user_interacting: Cell<bool>,
+
+    /// When the next idle period may start, in nanoseconds.
+    next_idle_period: Cell<u64>,
+
+    /// The network connection, as last reported by the embedder.
+    network_status: Cell<NetworkStatus>,
+
+    /// The user agent that the webviews of the pipelines are overridden with,
+    /// which the windows of the pipelines still loading get once created.
+    user_agent_overrides: DomRefCell<HashMap<PipelineId, UserAgentOverride>>,
+
+    /// The devices that the webviews of the pipelines emulate, which the
+    /// windows of the pipelines still loading get once created.
+    device_emulations: DomRefCell<HashMap<PipelineId, DeviceEmulation>>,
+
+    /// How the pipelines whose frames are far from the viewport are
+    /// throttled, which the windows of the pipelines still loading get once
+    /// created.
+    frame_throttlings: DomRefCell<HashMap<PipelineId, FrameThrottlingPolicy>>,
 }
 
 /// In the event of thread panic, all data on the stack runs its destructor. However, there
@@ -797,6 +842,8 @@ impl ScriptThreadFactory for ScriptThread {
                     load_data.url.clone(),
                     origin,
                     layout_is_busy,
+                    load_data.navigation_type,
+                    load_data.persisted_user_state.clone(),
                 );
                 script_thread.pre_page_load(new_load, load_data);
 
@@ -1071,6 +1118,17 @@ impl ScriptThread {
         })
     }
 
+    /// The network connection, as last reported by the embedder.
+    pub fn network_status() -> NetworkStatus {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            root.get()
+                .map_or(NetworkStatus::default(), |script_thread| {
+                    let script_thread = unsafe { &*script_thread };
+                    script_thread.network_status.get()
+                })
+        })
+    }
+
     pub fn get_fully_active_document_ids() -> HashSet<PipelineId> {
         SCRIPT_THREAD_ROOT.with(|root| {
             root.get().map_or(HashSet::new(), |script_thread| {
@@ -1146,26 +1204,32 @@ impl ScriptThread {
         })
     }
 
-    fn handle_register_paint_worklet(
+    fn handle_worklet_registration(
         &self,
         pipeline_id: PipelineId,
-        name: Atom,
-        properties: Vec<Atom>,
-        painter: Box<dyn Painter>,
+        registration: WorkletRegistration,
     ) {
         let window = self.documents.borrow().find_window(pipeline_id);
         let window = match window {
             Some(window) => window,
             None => {
                 return warn!(
-                    "Paint worklet registered after pipeline {} closed.",
+                    "Worklet registration after pipeline {} closed.",
                     pipeline_id
                 );
             },
         };
-        let _ = window
-            .layout_chan()
-            .send(Msg::RegisterPaint(name, properties, painter));
+        match registration {
+            WorkletRegistration::Paint {
+                name,
+                properties,
+                painter,
+            } => {
+                let _ = window
+                    .layout_chan()
+                    .send(Msg::RegisterPaint(name, properties, painter));
+            },
+        }
     }
 
     pub fn push_new_element_queue() {
@@ -1384,6 +1448,11 @@ impl ScriptThread {
 
             node_ids: Default::default(),
             is_user_interacting: Cell::new(false),
+            next_idle_period: Cell::new(0),
+            network_status: Cell::new(NetworkStatus::default()),
+            user_agent_overrides: DomRefCell::new(HashMap::new()),
+            device_emulations: DomRefCell::new(HashMap::new()),
+            frame_throttlings: DomRefCell::new(HashMap::new()),
         }
     }
 
@@ -1403,6 +1472,79 @@ impl ScriptThread {
         debug!("Stopped script thread.");
     }
 
+    /// Whether any event is waiting to be handled, in which case the event loop isn't idle.
+    fn has_pending_events(&self) -> bool {
+        self.task_queue.has_pending_tasks() ||
+            !self.control_port.is_empty() ||
+            !self.image_cache_port.is_empty() ||
+            (self.devtools_chan.is_some() && !self.devtools_port.is_empty())
+    }
+
+    /// How long until the next idle period is due, if any fully active document has idle
+    /// callbacks waiting for one.
+    fn time_until_idle_period(&self) -> Option<Duration> {
+        let has_idle_callbacks = self.documents.borrow().iter().any(|(_, document)| {
+            document.is_fully_active() && document.window().has_idle_callbacks()
+        });
+        if !has_idle_callbacks {
+            return None;
+        }
+        let now = precise_time_ns();
+        Some(Duration::from_nanos(
+            self.next_idle_period.get().saturating_sub(now),
+        ))
+    }
+
+    /// Runs idle callbacks while the event loop has nothing else to do, until the next timer
+    /// fires or the next animation frame is due, and for at most `MAX_IDLE_PERIOD_NS`.
+    /// <https://w3c.github.io/requestidlecallback/#start-an-idle-period-algorithm>
+    fn run_idle_period(&self) {
+        let now = precise_time_ns();
+        let documents: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .filter(|(_, document)| document.is_fully_active())
+            .collect();
+
+        let deadline =
+            documents
+                .iter()
+                .fold(now + MAX_IDLE_PERIOD_NS, |deadline, (_, document)| {
+                    let next_timer = document
+                        .window()
+                        .upcast::<GlobalScope>()
+                        .time_until_next_timer()
+                        .map(|duration| now + duration.get() * 1_000_000);
+                    // A frame that is already late doesn't come any sooner by waiting for it.
+                    let next_frame = document
+                        .next_animation_frame_time()
+                        .filter(|time| *time > now);
+                    next_timer
+                        .into_iter()
+                        .chain(next_frame)
+                        .fold(deadline, cmp::min)
+                });
+
+        // Callbacks requested during this idle period wait for the next one. Waiting at least
+        // a millisecond keeps a timer that is already due from making the event loop spin.
+        self.next_idle_period
+            .set(cmp::max(deadline, now + 1_000_000));
+
+        for (id, document) in documents {
+            let window = document.window();
+            if !window.has_idle_callbacks() {
+                continue;
+            }
+            window.start_idle_period();
+            while self.profile_event(ScriptThreadEventCategory::ScriptEvent, Some(id), || {
+                window.invoke_idle_callback(deadline)
+            }) {
+                self.perform_a_microtask_checkpoint();
+            }
+        }
+    }
+
     /// Handle incoming control messages.
     fn handle_msgs(&self) -> bool {
         use self::MixedMessage::FromScript;
@@ -1433,19 +1575,33 @@ impl ScriptThread {
 
         // Receive at least one message so we don't spinloop.
         debug!("Waiting for event.");
-        let mut event = select! {
-            recv(self.task_queue.select()) -> msg => {
-                self.task_queue.take_tasks(msg.unwrap());
-                let event = self
-                    .task_queue
-                    .recv()
-                    .expect("Spurious wake-up of the event-loop, task-queue has no tasks available");
-                FromScript(event)
-            },
-            recv(self.control_port) -> msg => FromConstellation(msg.unwrap()),
-            recv(self.devtools_chan.as_ref().map(|_| &self.devtools_port).unwrap_or(&crossbeam_channel::never())) -> msg
-                => FromDevtools(msg.unwrap()),
-            recv(self.image_cache_port) -> msg => FromImageCache(msg.unwrap()),
+        // While idle callbacks are pending, stop waiting once the next idle period is due.
+        let mut event = loop {
+            let idle_period = match self.time_until_idle_period() {
+                Some(duration) => crossbeam_channel::after(duration),
+                None => crossbeam_channel::never(),
+            };
+            let event = select! {
+                recv(self.task_queue.select()) -> msg => {
+                    self.task_queue.take_tasks(msg.unwrap());
+                    let event = self
+                        .task_queue
+                        .recv()
+                        .expect("Spurious wake-up of the event-loop, task-queue has no tasks available");
+                    Some(FromScript(event))
+                },
+                recv(self.control_port) -> msg => Some(FromConstellation(msg.unwrap())),
+                recv(self.devtools_chan.as_ref().map(|_| &self.devtools_port).unwrap_or(&crossbeam_channel::never())) -> msg
+                    => Some(FromDevtools(msg.unwrap())),
+                recv(self.image_cache_port) -> msg => Some(FromImageCache(msg.unwrap())),
+                recv(idle_period) -> _ => None,
+            };
+            match event {
+                Some(event) => break event,
+                // Events that arrived at the same time are handled first.
+                None if self.has_pending_events() => {},
+                None => self.run_idle_period(),
+            }
         };
         debug!("Got event.");
 
@@ -1620,6 +1776,10 @@ impl ScriptThread {
                 // minimize unnecessary work.
                 window.reflow(ReflowGoal::Full, ReflowReason::MissingExplicitReflow);
             }
+
+            // Now that the layout is up to date, start loading the lazy
+            // loading elements that got near the viewport, e.g. by scrolling.
+            document.run_the_lazy_load_intersection_steps();
         }
 
         true
@@ -1636,7 +1796,7 @@ impl ScriptThread {
             MixedMessage::FromImageCache(_) => ScriptThreadEventCategory::ImageCacheMsg,
             MixedMessage::FromScript(ref inner_msg) => match *inner_msg {
                 MainThreadScriptMsg::Common(CommonScriptMsg::Task(category, ..)) => category,
-                MainThreadScriptMsg::RegisterPaintWorklet { .. } => {
+                MainThreadScriptMsg::WorkletRegistration { .. } => {
                     ScriptThreadEventCategory::WorkletEvent
                 },
                 _ => ScriptThreadEventCategory::ScriptEvent,
@@ -1698,8 +1858,17 @@ impl ScriptThread {
                     Resize(id, ..) => Some(id),
                     ResizeInactive(id, ..) => Some(id),
                     UnloadDocument(id) => Some(id),
+                    SetUnloadEventTiming(id, ..) => Some(id),
                     ExitPipeline(id, ..) => Some(id),
                     ExitScriptThread => None,
+                    SetPreference(..) => None,
+                    SetNetworkStatus(..) => None,
+                    DeviceSensorReading(..) => None,
+                    SetUserAgentOverride(id, ..) => Some(id),
+                    SetDeviceEmulation(id, ..) => Some(id),
+                    SetFrameThrottling(id, ..) => Some(id),
+                    GetScreenshotRect(id, ..) => Some(id),
+                    Prefetch(id, ..) => Some(id),
                     SendEvent(id, ..) => Some(id),
                     Viewport(id, ..) => Some(id),
                     SetScrollState(id, ..) => Some(id),
@@ -1715,6 +1884,7 @@ impl ScriptThread {
                     FocusIFrame(id, ..) => Some(id),
                     WebDriverScriptCommand(id, ..) => Some(id),
                     TickAllAnimations(id) => Some(id),
+                    TickWorkerAnimationFrames(id) => Some(id),
                     // FIXME https://github.com/servo/servo/issues/15079
                     TransitionEnd(..) => None,
                     WebFontLoaded(id) => Some(id),
@@ -1739,7 +1909,7 @@ impl ScriptThread {
                 },
                 MainThreadScriptMsg::Common(CommonScriptMsg::CollectReports(_)) => None,
                 MainThreadScriptMsg::WorkletLoaded(pipeline_id) => Some(pipeline_id),
-                MainThreadScriptMsg::RegisterPaintWorklet { pipeline_id, .. } => Some(pipeline_id),
+                MainThreadScriptMsg::WorkletRegistration { pipeline_id, .. } => Some(pipeline_id),
                 MainThreadScriptMsg::DispatchJobQueue { .. } => None,
                 MainThreadScriptMsg::Inactive => None,
                 MainThreadScriptMsg::WakeUp => None,
@@ -1821,9 +1991,38 @@ impl ScriptThread {
             }
             doc.record_tti_if_necessary();
         }
+        if let Some(pipeline_id) = pipeline_id {
+            if end - start > LONG_TASK_THRESHOLD_NS {
+                self.report_long_task(pipeline_id, start, end);
+            }
+        }
         value
     }
 
+    /// Delivers a task of the document with `pipeline_id` that ran from `start`
+    /// to `end` to the long task observers of every document of this thread.
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    fn report_long_task(&self, pipeline_id: PipelineId, start: u64, end: u64) {
+        let culprit = match self.documents.borrow().find_window(pipeline_id) {
+            Some(window) => window,
+            None => return,
+        };
+        let windows: Vec<DomRoot<Window>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, doc)| DomRoot::from_ref(doc.window()))
+            .collect();
+        for window in windows {
+            let performance = window.Performance();
+            if !performance.has_observers_for("longtask") {
+                continue;
+            }
+            let entry = PerformanceLongTaskTiming::new(&window, &culprit, start, end);
+            performance.queue_entry(&entry.upcast::<PerformanceEntry>());
+        }
+    }
+
     fn handle_msg_from_constellation(&self, msg: ConstellationControlMsg) {
         match msg {
             ConstellationControlMsg::StopDelayingLoadEventsMode(pipeline_id) => {
@@ -1855,6 +2054,9 @@ impl ScriptThread {
             ConstellationControlMsg::UnloadDocument(pipeline_id) => {
                 self.handle_unload_document(pipeline_id)
             },
+            ConstellationControlMsg::SetUnloadEventTiming(pipeline_id, start, end) => {
+                self.handle_set_unload_event_timing(pipeline_id, start, end)
+            },
             ConstellationControlMsg::SendEvent(id, event) => self.handle_event(id, event),
             ConstellationControlMsg::ResizeInactive(id, new_size) => {
                 self.handle_resize_inactive_msg(id, new_size)
@@ -1905,8 +2107,13 @@ impl ScriptThread {
                 new_pipeline_id,
                 reason,
             ),
-            ConstellationControlMsg::UpdateHistoryState(pipeline_id, history_state_id, url) => {
-                self.handle_update_history_state_msg(pipeline_id, history_state_id, url)
+            ConstellationControlMsg::UpdateHistoryState(
+                pipeline_id,
+                history_state_id,
+                url,
+                user_state,
+            ) => {
+                self.handle_update_history_state_msg(pipeline_id, history_state_id, url, user_state)
             },
             ConstellationControlMsg::RemoveHistoryStates(pipeline_id, history_states) => {
                 self.handle_remove_history_states(pipeline_id, history_states)
@@ -1920,6 +2127,9 @@ impl ScriptThread {
             ConstellationControlMsg::TickAllAnimations(pipeline_id) => {
                 self.handle_tick_all_animations(pipeline_id)
             },
+            ConstellationControlMsg::TickWorkerAnimationFrames(pipeline_id) => {
+                self.handle_tick_worker_animation_frames(pipeline_id)
+            },
             ConstellationControlMsg::TransitionEnd(unsafe_node, name, duration) => {
                 self.handle_transition_event(unsafe_node, name, duration)
             },
@@ -1955,6 +2165,33 @@ impl ScriptThread {
             ConstellationControlMsg::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action)
             },
+            ConstellationControlMsg::SetPreference(name, value) => {
+                if let Err(e) = prefs::pref_map().set(&name, value) {
+                    warn!("Failed to set preference {}: {}", name, e);
+                }
+            },
+            ConstellationControlMsg::SetNetworkStatus(status) => {
+                self.handle_network_status_changed(status)
+            },
+            ConstellationControlMsg::DeviceSensorReading(
+                top_level_browsing_context_id,
+                reading,
+            ) => self.handle_device_sensor_reading(top_level_browsing_context_id, reading),
+            ConstellationControlMsg::SetUserAgentOverride(pipeline_id, ua_override) => {
+                self.handle_set_user_agent_override(pipeline_id, ua_override)
+            },
+            ConstellationControlMsg::SetDeviceEmulation(pipeline_id, emulation) => {
+                self.handle_set_device_emulation(pipeline_id, emulation)
+            },
+            ConstellationControlMsg::SetFrameThrottling(pipeline_id, policy) => {
+                self.handle_set_frame_throttling(pipeline_id, policy)
+            },
+            ConstellationControlMsg::GetScreenshotRect(pipeline_id, area, reply) => {
+                self.handle_get_screenshot_rect(pipeline_id, area, reply)
+            },
+            ConstellationControlMsg::Prefetch(pipeline_id, url) => {
+                self.handle_prefetch(pipeline_id, url)
+            },
             msg @ ConstellationControlMsg::AttachLayout(..) |
             msg @ ConstellationControlMsg::Viewport(..) |
             msg @ ConstellationControlMsg::SetScrollState(..) |
@@ -1975,12 +2212,10 @@ impl ScriptThread {
             MainThreadScriptMsg::WorkletLoaded(pipeline_id) => {
                 self.handle_worklet_loaded(pipeline_id)
             },
-            MainThreadScriptMsg::RegisterPaintWorklet {
+            MainThreadScriptMsg::WorkletRegistration {
                 pipeline_id,
-                name,
-                properties,
-                painter,
-            } => self.handle_register_paint_worklet(pipeline_id, name, properties, painter),
+                registration,
+            } => self.handle_worklet_registration(pipeline_id, registration),
             MainThreadScriptMsg::DispatchJobQueue { scope_url } => {
                 self.job_queue_map.run_job(scope_url, self)
             },
@@ -1996,6 +2231,14 @@ impl ScriptThread {
                 Some(window) => devtools::handle_evaluate_js(window.upcast(), s, reply),
                 None => return warn!("Message sent to closed pipeline {}.", id),
             },
+            DevtoolScriptControlMsg::GetObjectProperties(id, uuid, reply) => {
+                match documents.find_window(id) {
+                    Some(window) => {
+                        devtools::handle_get_object_properties(window.upcast(), uuid, reply)
+                    },
+                    None => return warn!("Message sent to closed pipeline {}.", id),
+                }
+            },
             DevtoolScriptControlMsg::GetRootNode(id, reply) => {
                 devtools::handle_get_root_node(&*documents, id, reply)
             },
@@ -2311,6 +2554,7 @@ impl ScriptThread {
         warn!("Page rect message sent to nonexistent pipeline");
     }
 
+    #[allow(unsafe_code)]
     fn handle_set_scroll_state(
         &self,
         id: PipelineId,
@@ -2327,14 +2571,23 @@ impl ScriptThread {
         };
 
         let mut scroll_offsets = HashMap::new();
+        let mut element_scroll_positions = vec![];
         for &(node_address, ref scroll_offset) in scroll_states {
             if node_address == UntrustedNodeAddress(ptr::null()) {
                 window.update_viewport_for_scroll(-scroll_offset.x, -scroll_offset.y);
             } else {
                 scroll_offsets.insert(OpaqueNode(node_address.0 as usize), -*scroll_offset);
+                if *scroll_offset != Vector2D::zero() {
+                    let js_runtime = self.js_runtime.rt();
+                    let node = unsafe { from_untrusted_node_address(js_runtime, node_address) };
+                    element_scroll_positions.push((node, -*scroll_offset));
+                }
             }
         }
-        window.set_scroll_offsets(scroll_offsets)
+        window.set_scroll_offsets(scroll_offsets);
+        window
+            .History()
+            .persist_element_scroll_positions(element_scroll_positions)
     }
 
     fn handle_new_layout(&self, new_layout_info: NewLayoutInfo, origin: MutableOrigin) {
@@ -2407,6 +2660,8 @@ impl ScriptThread {
             load_data.url.clone(),
             origin,
             layout_is_busy.clone(),
+            load_data.navigation_type,
+            load_data.persisted_user_state.clone(),
         );
         if load_data.url.as_str() == "about:blank" {
             self.start_page_load_about_blank(new_load, load_data.js_eval_result);
@@ -2417,6 +2672,7 @@ impl ScriptThread {
         }
     }
 
+    #[allow(unsafe_code)]
     fn collect_reports(&self, reports_chan: ReportsChan) {
         let documents = self.documents.borrow();
         let urls = itertools::join(documents.iter().map(|(_, d)| d.url().to_string()), ", ");
@@ -2424,6 +2680,18 @@ impl ScriptThread {
 
         let mut reports = vec![];
         reports.extend(get_reports(*self.get_cx(), path_seg));
+        for (_, document) in documents.iter() {
+            let size = document
+                .upcast::<Node>()
+                .traverse_preorder(ShadowIncluding::Yes)
+                .map(|node| unsafe { get_size(node.reflector().get_jsobject().get()) })
+                .sum();
+            reports.push(Report {
+                path: path![format!("url({})", document.url()), "dom-tree"],
+                kind: ReportKind::ExplicitJemallocHeapSize,
+                size,
+            });
+        }
         reports_chan.send(reports);
     }
 
@@ -2561,6 +2829,19 @@ impl ScriptThread {
         let document = self.documents.borrow().find_document(pipeline_id);
         if let Some(document) = document {
             document.unload(false);
+            // The next document of the browsing context reports when the unload event
+            // was fired.
+            if let Some((start, end)) = document.fired_unload_timing() {
+                let msg = ScriptMsg::DocumentUnloaded(start, end);
+                let _ = self.script_sender.send((pipeline_id, msg));
+            }
+        }
+    }
+
+    fn handle_set_unload_event_timing(&self, pipeline_id: PipelineId, start: u64, end: u64) {
+        let document = self.documents.borrow().find_document(pipeline_id);
+        if let Some(document) = document {
+            document.set_unload_event_timing(start, end);
         }
     }
 
@@ -2601,6 +2882,7 @@ impl ScriptThread {
         pipeline_id: PipelineId,
         history_state_id: Option<HistoryStateId>,
         url: ServoUrl,
+        user_state: PersistedUserState,
     ) {
         let window = self.documents.borrow().find_window(pipeline_id);
         match window {
@@ -2610,7 +2892,9 @@ impl ScriptThread {
                     pipeline_id
                 );
             },
-            Some(window) => window.History().activate_state(history_state_id, url),
+            Some(window) => window
+                .History()
+                .activate_state(history_state_id, url, user_state),
         }
     }
 
@@ -2814,6 +3098,9 @@ impl ScriptThread {
         debug!("Exiting pipeline {}.", id);
 
         self.closed_pipelines.borrow_mut().insert(id);
+        self.user_agent_overrides.borrow_mut().remove(&id);
+        self.device_emulations.borrow_mut().remove(&id);
+        self.frame_throttlings.borrow_mut().remove(&id);
 
         // Check if the exit message is for an in progress load.
         let idx = self
@@ -2870,6 +3157,7 @@ impl ScriptThread {
 
             // We discard the browsing context after requesting layout shut down,
             // to avoid running layout on detached iframes.
+            document.notify_cookies_blocked(false);
             let window = document.window();
             if discard_bc == DiscardBrowsingContext::Yes {
                 window.discard_browsing_context();
@@ -2920,6 +3208,16 @@ impl ScriptThread {
         document.run_the_animation_frame_callbacks();
     }
 
+    /// Gives an animation frame to the dedicated workers of a pipeline that
+    /// wait for one.
+    fn handle_tick_worker_animation_frames(&self, id: PipelineId) {
+        let document = match { self.documents.borrow().find_document(id) } {
+            Some(document) => document,
+            None => return warn!("Message sent to closed pipeline {}.", id),
+        };
+        document.tick_worker_animation_frames();
+    }
+
     /// Handles firing of transition events.
     fn handle_transition_event(
         &self,
@@ -3216,6 +3514,10 @@ impl ScriptThread {
             incomplete.pipeline_id,
             incomplete.parent_info,
             incomplete.window_size,
+            self.device_emulations
+                .borrow()
+                .get(&incomplete.pipeline_id)
+                .cloned(),
             origin.clone(),
             incomplete.navigation_start,
             incomplete.navigation_start_precise,
@@ -3236,6 +3538,12 @@ impl ScriptThread {
             self.player_context.clone(),
             self.event_loop_waker.as_ref().map(|w| (*w).clone_box()),
         );
+        window.upcast::<GlobalScope>().set_user_agent_override(
+            self.user_agent_overrides
+                .borrow()
+                .get(&incomplete.pipeline_id)
+                .cloned(),
+        );
 
         // Initialize the browsing context for the window.
         let window_proxy = self.local_window_proxy(
@@ -3311,6 +3619,11 @@ impl ScriptThread {
             referrer_policy,
             incomplete.canceller,
         );
+        document.set_navigation_type(incomplete.navigation_type);
+        if let Some(user_state) = incomplete.persisted_user_state {
+            window.History().set_user_state_to_restore(user_state);
+        }
+        document.set_allow_declarative_shadow_roots(true);
         document.set_ready_state(DocumentReadyState::Loading);
 
         self.documents
@@ -3318,6 +3631,7 @@ impl ScriptThread {
             .insert(incomplete.pipeline_id, &*document);
 
         window.init_document(&document);
+        document.notify_cookies_blocked(document.cookies_blocked());
 
         // For any similar-origin iframe, ensure that the contentWindow/contentDocument
         // APIs resolve to the new window/document as soon as parsing starts.
@@ -3367,6 +3681,15 @@ impl ScriptThread {
             window.alter_resource_utilization(false);
         }
 
+        let frame_throttling = self
+            .frame_throttlings
+            .borrow()
+            .get(&incomplete.pipeline_id)
+            .cloned();
+        if frame_throttling.is_some() {
+            window.set_frame_throttling(frame_throttling);
+        }
+
         document.get_current_parser().unwrap()
     }
 
@@ -3538,6 +3861,38 @@ impl ScriptThread {
                 self.handle_wheel_event(pipeline_id, delta, point, node_address);
             },
 
+            PointerLockedMoveEvent(delta, pressed_mouse_buttons) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document.handle_pointer_locked_move_event(delta, pressed_mouse_buttons);
+            },
+
+            DragEvent(event_type, point, node_address, data) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                document.handle_drag_event(
+                    self.js_runtime.rt(),
+                    event_type,
+                    point,
+                    node_address,
+                    data,
+                );
+            },
+
+            DoubleTapEvent(node_address) => {
+                let document = match { self.documents.borrow().find_document(pipeline_id) } {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                let rect = document.double_tap_zoom_rect(self.js_runtime.rt(), node_address);
+                let message = ScriptMsg::DoubleTapZoomRect(rect);
+                self.script_sender.send((pipeline_id, message)).unwrap();
+            },
+
             KeyboardEvent(key_event) => {
                 let document = match { self.documents.borrow().find_document(pipeline_id) } {
                     Some(document) => document,
@@ -3717,6 +4072,9 @@ impl ScriptThread {
             uievent.upcast::<Event>().fire(window.upcast());
         }
 
+        // https://w3c.github.io/screen-orientation/#dfn-screen-orientation-change-steps
+        window.update_screen_orientation();
+
         // https://html.spec.whatwg.org/multipage/#event-loop-processing-model
         // Step 7.7 - evaluate media queries and report changes
         // Since we have resized, we need to re-evaluate MQLs
@@ -3922,6 +4280,130 @@ impl ScriptThread {
         };
     }
 
+    fn handle_network_status_changed(&self, status: NetworkStatus) {
+        let old = self.network_status.replace(status);
+        let windows: Vec<DomRoot<Window>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .collect();
+        for window in windows {
+            window.update_network_status(old, status);
+        }
+    }
+
+    fn handle_device_sensor_reading(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        reading: DeviceSensorReading,
+    ) {
+        let windows: Vec<DomRoot<Window>> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| DomRoot::from_ref(document.window()))
+            .filter(|window| {
+                window.window_proxy().top_level_browsing_context_id() ==
+                    top_level_browsing_context_id
+            })
+            .collect();
+        for window in windows {
+            window.handle_device_sensor_reading(reading);
+        }
+    }
+
+    fn handle_set_user_agent_override(
+        &self,
+        pipeline_id: PipelineId,
+        ua_override: Option<UserAgentOverride>,
+    ) {
+        if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+            window
+                .upcast::<GlobalScope>()
+                .set_user_agent_override(ua_override.clone());
+        }
+        let mut overrides = self.user_agent_overrides.borrow_mut();
+        match ua_override {
+            Some(ua_override) => overrides.insert(pipeline_id, ua_override),
+            None => overrides.remove(&pipeline_id),
+        };
+    }
+
+    fn handle_set_device_emulation(
+        &self,
+        pipeline_id: PipelineId,
+        emulation: Option<DeviceEmulation>,
+    ) {
+        if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+            window.set_device_emulation(emulation.clone());
+        }
+        let mut emulations = self.device_emulations.borrow_mut();
+        match emulation {
+            Some(emulation) => emulations.insert(pipeline_id, emulation),
+            None => emulations.remove(&pipeline_id),
+        };
+    }
+
+    fn handle_set_frame_throttling(
+        &self,
+        pipeline_id: PipelineId,
+        policy: Option<FrameThrottlingPolicy>,
+    ) {
+        if let Some(window) = self.documents.borrow().find_window(pipeline_id) {
+            window.set_frame_throttling(policy);
+        }
+        let mut throttlings = self.frame_throttlings.borrow_mut();
+        match policy {
+            Some(policy) => throttlings.insert(pipeline_id, policy),
+            None => throttlings.remove(&pipeline_id),
+        };
+    }
+
+    fn handle_prefetch(&self, pipeline_id: PipelineId, url: ServoUrl) {
+        if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+            prefetch_a_document(&document, url, None);
+        }
+    }
+
+    fn handle_get_screenshot_rect(
+        &self,
+        pipeline_id: PipelineId,
+        area: ScreenshotArea,
+        reply: IpcSender<Option<euclid::Rect<f32, CSSPixel>>>,
+    ) {
+        let document = self.documents.borrow().find_document(pipeline_id);
+        let rect = document.and_then(|document| match area {
+            ScreenshotArea::Viewport => Some(euclid::Rect::new(
+                euclid::Point2D::zero(),
+                document.window().window_size().initial_viewport,
+            )),
+            // The scrolling area of the viewport, which is at least as large
+            // as the viewport.
+            ScreenshotArea::FullPage => document.GetDocumentElement().map(|root| {
+                euclid::Rect::new(
+                    euclid::Point2D::zero(),
+                    euclid::Size2D::new(root.ScrollWidth() as f32, root.ScrollHeight() as f32),
+                )
+            }),
+            ScreenshotArea::Rect(rect) => Some(rect),
+            ScreenshotArea::Element(selector) => document
+                .QuerySelector(DOMString::from(selector))
+                .ok()
+                .and_then(|element| element)
+                .map(|element| {
+                    let rect = element.GetBoundingClientRect();
+                    euclid::Rect::new(
+                        euclid::Point2D::new(rect.X() as f32, rect.Y() as f32),
+                        euclid::Size2D::new(rect.Width() as f32, rect.Height() as f32),
+                    )
+                }),
+        });
+        if let Err(e) = reply.send(rect) {
+            warn!("Sending the screenshot rectangle failed ({:?}).", e);
+        }
+    }
+
     pub fn enqueue_microtask(job: Microtask) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
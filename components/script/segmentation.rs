@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The boundaries of the words and sentences of text, which the caret and
+//! the selection move by, and which double-clicking selects.
+//! <https://www.unicode.org/reports/tr29/>
+//!
+//! Finding the words of the scripts written without spaces between them,
+//! like Chinese, Japanese or Thai, takes a dictionary, so each of their
+//! characters is a word of its own here.
+
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Whether `segment`, which is between two word boundaries, is a word
+/// rather than whitespace or punctuation.
+pub fn is_word(segment: &str) -> bool {
+    segment.chars().any(|c| c.is_alphanumeric())
+}
+
+/// The byte range of the segment of `text` between the word boundaries
+/// around the byte index `index`. When `index` is right after a word, that
+/// word is preferred to the whitespace or punctuation that follows it.
+pub fn word_at(text: &str, index: usize) -> Range<usize> {
+    let mut previous: Option<Range<usize>> = None;
+    for (start, segment) in text.split_word_bound_indices() {
+        let range = start..start + segment.len();
+        if index < range.end {
+            if let Some(previous) = previous {
+                if index == range.start && !is_word(segment) && is_word(&text[previous.clone()]) {
+                    return previous;
+                }
+            }
+            return range;
+        }
+        previous = Some(range);
+    }
+    previous.unwrap_or(text.len()..text.len())
+}
+
+/// The byte index of the end of the word after the byte index `index` of
+/// `text` if `forward`, or of the start of the word before it otherwise, or
+/// `None` if there is no word in that direction.
+pub fn next_word_boundary(text: &str, index: usize, forward: bool) -> Option<usize> {
+    if forward {
+        text.split_word_bound_indices()
+            .find(|&(start, segment)| start + segment.len() > index && is_word(segment))
+            .map(|(start, segment)| start + segment.len())
+    } else {
+        text.split_word_bound_indices()
+            .rev()
+            .find(|&(start, segment)| start < index && is_word(segment))
+            .map(|(start, _)| start)
+    }
+}
+
+/// The byte index of the end of the sentence after the byte index `index`
+/// of `text` if `forward`, or of the start of the sentence before it
+/// otherwise, or `None` if there is no sentence in that direction.
+pub fn next_sentence_boundary(text: &str, index: usize, forward: bool) -> Option<usize> {
+    if forward {
+        text.split_sentence_bound_indices()
+            .map(|(start, sentence)| start + sentence.len())
+            .find(|&end| end > index)
+    } else {
+        text.split_sentence_bound_indices()
+            .rev()
+            .map(|(start, _)| start)
+            .find(|&start| start < index)
+    }
+}
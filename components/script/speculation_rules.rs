@@ -0,0 +1,221 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Speculation rules, which let web pages list the documents the user is
+//! likely to navigate to next, so that they can be fetched ahead of time.
+//! https://wicg.github.io/nav-speculation/speculation-rules.html
+
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::document::{determine_policy_for_token, Document};
+use http::header::{HeaderName, HeaderValue};
+use http::HeaderMap;
+use net_traits::request::{CredentialsMode, Destination, Referrer, RequestBuilder, RequestMode};
+use net_traits::{CoreResourceMsg, FetchChannels, IpcSend, ReferrerPolicy};
+use serde_json::{Map, Value};
+use servo_url::ServoUrl;
+
+/// https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule
+#[derive(Clone, Debug)]
+struct SpeculationRule {
+    /// https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-urls
+    urls: Vec<ServoUrl>,
+    /// https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-referrer-policy
+    referrer_policy: Option<ReferrerPolicy>,
+}
+
+/// https://wicg.github.io/nav-speculation/speculation-rules.html#speculation-rule-set
+#[derive(Clone, Debug, Default)]
+pub struct SpeculationRuleSet {
+    prefetch: Vec<SpeculationRule>,
+    prerender: Vec<SpeculationRule>,
+}
+
+impl SpeculationRuleSet {
+    /// https://wicg.github.io/nav-speculation/speculation-rules.html#parse-speculation-rules
+    pub fn parse(input: &str, base_url: &ServoUrl) -> Fallible<SpeculationRuleSet> {
+        // Step 1.
+        let parsed: Value = serde_json::from_str(input).map_err(|_| Error::Syntax)?;
+
+        // Step 2.
+        let parsed = match parsed {
+            Value::Object(object) => object,
+            _ => {
+                return Err(Error::Type(
+                    "The top-level value must be a JSON object".to_owned(),
+                ))
+            },
+        };
+
+        // Steps 3-4.
+        Ok(SpeculationRuleSet {
+            prefetch: parse_rules(&parsed, "prefetch", base_url)?,
+            prerender: parse_rules(&parsed, "prerender", base_url)?,
+        })
+    }
+
+    /// The URLs to prefetch, with the referrer policy of their requests.
+    ///
+    /// Prerendering isn't supported, so prerender candidates are prefetched
+    /// instead, which still saves the network round trip of the navigation.
+    pub fn prefetch_candidates(&self) -> Vec<(ServoUrl, Option<ReferrerPolicy>)> {
+        let mut candidates: Vec<(ServoUrl, Option<ReferrerPolicy>)> = vec![];
+        for rule in self.prefetch.iter().chain(self.prerender.iter()) {
+            for url in &rule.urls {
+                if candidates.iter().all(|(candidate, _)| candidate != url) {
+                    candidates.push((url.clone(), rule.referrer_policy));
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// Parse the list of rules under `key`, ignoring the rules that are invalid.
+fn parse_rules(
+    parsed: &Map<String, Value>,
+    key: &str,
+    base_url: &ServoUrl,
+) -> Fallible<Vec<SpeculationRule>> {
+    match parsed.get(key) {
+        Some(Value::Array(rules)) => Ok(rules
+            .iter()
+            .filter_map(|rule| {
+                let rule = parse_a_speculation_rule(rule, base_url);
+                if rule.is_none() {
+                    warn!("Ignoring invalid {} speculation rule", key);
+                }
+                rule
+            })
+            .collect()),
+        Some(_) => Err(Error::Type(format!(
+            "The \"{}\" top-level key must be a JSON array",
+            key
+        ))),
+        None => Ok(vec![]),
+    }
+}
+
+/// https://wicg.github.io/nav-speculation/speculation-rules.html#parse-a-speculation-rule
+fn parse_a_speculation_rule(input: &Value, base_url: &ServoUrl) -> Option<SpeculationRule> {
+    // Step 1.
+    let input = match *input {
+        Value::Object(ref input) => input,
+        _ => return None,
+    };
+
+    // Step 2.
+    const KNOWN_KEYS: &[&str] = &[
+        "source",
+        "urls",
+        "requires",
+        "referrer_policy",
+        "relative_to",
+        "where",
+        "eagerness",
+        "expects_no_vary_search",
+        "tag",
+    ];
+    if input.keys().any(|key| !KNOWN_KEYS.contains(&&**key)) {
+        return None;
+    }
+
+    // Steps 3-4. Only list rules are supported, document rules, which match
+    // the links of the document, aren't.
+    match input.get("source") {
+        Some(Value::String(source)) if source == "list" => {},
+        None if input.contains_key("urls") => {},
+        _ => return None,
+    }
+
+    // Step 5.
+    let urls = match input.get("urls") {
+        Some(Value::Array(urls)) => urls,
+        _ => return None,
+    };
+    let mut parsed_urls = vec![];
+    for url in urls {
+        let url = match *url {
+            Value::String(ref url) => url,
+            _ => return None,
+        };
+        // Only http(s) URLs can be prefetched, the others are skipped.
+        match base_url.join(url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => parsed_urls.push(url),
+            _ => {},
+        }
+    }
+
+    // Step 6.
+    match input.get("requires") {
+        Some(Value::Array(requirements)) => {
+            let known = requirements.iter().all(|requirement| {
+                requirement.as_str() == Some("anonymous-client-ip-when-cross-origin")
+            });
+            if !known {
+                return None;
+            }
+        },
+        Some(_) => return None,
+        None => {},
+    }
+
+    // Step 7.
+    let referrer_policy = match input.get("referrer_policy") {
+        Some(Value::String(policy)) if policy.is_empty() => None,
+        Some(Value::String(policy)) => Some(determine_policy_for_token(policy)?),
+        Some(_) => return None,
+        None => None,
+    };
+
+    Some(SpeculationRule {
+        urls: parsed_urls,
+        referrer_policy,
+    })
+}
+
+/// Prefetch a document the user may navigate to into the HTTP cache, so that
+/// the navigation to it doesn't wait on the network.
+///
+/// Only same-origin documents are prefetched: the HTTP cache isn't
+/// partitioned by top-level site, and a credentialed request to another site
+/// would tell it which page the user is on before they decide to go there.
+/// For the same reason, requests of private browsers go to their own cache,
+/// through the resource threads of the document.
+/// https://wicg.github.io/nav-speculation/prefetch.html#prefetch
+pub fn prefetch_a_document(
+    document: &Document,
+    url: ServoUrl,
+    referrer_policy: Option<ReferrerPolicy>,
+) {
+    if !document.is_fully_active() {
+        return;
+    }
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return;
+    }
+    if url.origin() != *document.origin().immutable() {
+        debug!("Not prefetching cross-origin {}", url);
+        return;
+    }
+
+    debug!("Prefetch {}", url);
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        HeaderName::from_static("sec-purpose"),
+        HeaderValue::from_static("prefetch"),
+    );
+    let request = RequestBuilder::new(url)
+        .destination(Destination::Document)
+        .mode(RequestMode::SameOrigin)
+        .credentials_mode(CredentialsMode::Include)
+        .headers(headers)
+        .origin(document.origin().immutable().clone())
+        .pipeline_id(Some(document.window().pipeline_id()))
+        .referrer(Some(Referrer::ReferrerUrl(document.url())))
+        .referrer_policy(referrer_policy.or(document.get_referrer_policy()));
+    let _ = document
+        .loader()
+        .resource_threads()
+        .send(CoreResourceMsg::Fetch(request, FetchChannels::Prefetch));
+}
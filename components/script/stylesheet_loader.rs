@@ -8,7 +8,7 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::element::{fetch_priority_for_element, Element};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
@@ -327,7 +327,8 @@ impl<'a> StylesheetLoader<'a> {
             Referrer::ReferrerUrl(document.url()),
             referrer_policy,
             integrity_metadata,
-        );
+        )
+        .priority(fetch_priority_for_element(self.elem.upcast()));
 
         document.fetch_async(LoadType::Stylesheet(url), request, action_sender);
     }
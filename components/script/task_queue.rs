@@ -173,6 +173,11 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
         &self.port
     }
 
+    /// Whether tasks are waiting to be taken, in which case the event-loop isn't idle.
+    pub fn has_pending_tasks(&self) -> bool {
+        !self.port.is_empty() || !self.msg_queue.borrow().is_empty()
+    }
+
     /// Take a message from the front of the queue, without waiting if empty.
     pub fn recv(&self) -> Result<T, ()> {
         self.msg_queue.borrow_mut().pop_front().ok_or(())
@@ -15,6 +15,22 @@ pub mod area {
     pub use crate::dom::htmlareaelement::{Area, Shape};
 }
 
+pub mod htmlmediaelement {
+    pub use crate::dom::htmlmediaelement::nearest_position;
+}
+
+pub mod import_map {
+    pub use crate::import_map::ImportMap;
+}
+
+pub mod midi {
+    pub use crate::dom::midioutput::validate_midi_messages;
+}
+
+pub mod speculation_rules {
+    pub use crate::speculation_rules::SpeculationRuleSet;
+}
+
 #[allow(non_snake_case)]
 pub mod size_of {
     use crate::dom::characterdata::CharacterData;
@@ -67,3 +83,10 @@ pub mod srcset {
 pub mod timeranges {
     pub use crate::dom::timeranges::TimeRangesContainer;
 }
+
+pub mod xpath {
+    pub use crate::xpath::{
+        compare, number_to_string, parse, string_to_number, tokenize, Axis, BinaryOperator, Error,
+        Expr, NodeTest, PathStart, QName, Step, Token, Value,
+    };
+}
@@ -8,6 +8,7 @@ use crate::clipboard_provider::ClipboardProvider;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::keyboardevent::KeyboardEvent;
+use crate::segmentation::{is_word, word_at};
 use keyboard_types::{Key, KeyState, Modifiers, ShortcutMatcher};
 use std::borrow::ToOwned;
 use std::cmp::min;
@@ -712,6 +713,20 @@ impl<T: ClipboardProvider> TextInput<T> {
         self.assert_ok_selection();
     }
 
+    /// Select the word around the edit point, or the whitespace or
+    /// punctuation there, e.g. when the user double-clicks it.
+    pub fn select_word_at_edit_point(&mut self) {
+        let UTF8Bytes(index) = self.edit_point.index;
+        let word = word_at(&self.lines[self.edit_point.line], index);
+        self.selection_origin = Some(TextPoint {
+            line: self.edit_point.line,
+            index: UTF8Bytes(word.start),
+        });
+        self.edit_point.index = UTF8Bytes(word.end);
+        self.selection_direction = SelectionDirection::Forward;
+        self.assert_ok_selection();
+    }
+
     /// Remove the current selection.
     pub fn clear_selection(&mut self) {
         self.selection_origin = None;
@@ -750,7 +765,7 @@ impl<T: ClipboardProvider> TextInput<T> {
                             None => break,
                             Some(x) => {
                                 shift_temp += UTF8Bytes(x.len() as usize);
-                                if x.chars().any(|x| x.is_alphabetic() || x.is_numeric()) {
+                                if is_word(x) {
                                     break;
                                 }
                             },
@@ -775,7 +790,7 @@ impl<T: ClipboardProvider> TextInput<T> {
                             None => break,
                             Some(x) => {
                                 shift_temp += UTF8Bytes(x.len() as usize);
-                                if x.chars().any(|x| x.is_alphabetic() || x.is_numeric()) {
+                                if is_word(x) {
                                     break;
                                 }
                             },
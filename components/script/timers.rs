@@ -9,8 +9,11 @@ use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::str::DOMString;
 use crate::dom::document::FakeRequestAnimationFrameCallback;
 use crate::dom::eventsource::EventSourceTimeoutCallback;
+use crate::dom::geolocation::GeolocationTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::midioutput::MidiSendCallback;
 use crate::dom::testbinding::TestBindingCallback;
+use crate::dom::window::IdleCallbackTimeoutCallback;
 use crate::dom::xmlhttprequest::XHRTimeoutCallback;
 use euclid::Length;
 use ipc_channel::ipc::IpcSender;
@@ -26,6 +29,7 @@ use std::cmp::{self, Ord, Ordering};
 use std::collections::HashMap;
 use std::default::Default;
 use std::rc::Rc;
+use time::precise_time_ns;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, JSTraceable, MallocSizeOf, Ord, PartialEq, PartialOrd)]
 pub struct OneshotTimerHandle(i32);
@@ -56,6 +60,14 @@ pub struct OneshotTimers {
     ///  - a timer was added with an earlier callback time. In this case the
     ///    original timer is rescheduled when it is the next one to get called.
     expected_event_id: Cell<TimerEventId>,
+    /// Whether the timers belong to a window in the background, whose
+    /// wake-ups are aligned and limited by a budget.
+    in_background: Cell<bool>,
+    /// How long, in ms, the timers can still run in the background before
+    /// their wake-ups get delayed. Negative when overspent.
+    background_budget: Cell<f64>,
+    /// When the background budget was last regenerated.
+    background_budget_updated_at: Cell<MsDuration>,
 }
 
 #[derive(DenyPublicFields, JSTraceable, MallocSizeOf)]
@@ -76,6 +88,9 @@ pub enum OneshotTimerCallback {
     JsTimer(JsTimerTask),
     TestBindingCallback(TestBindingCallback),
     FakeRequestAnimationFrame(FakeRequestAnimationFrameCallback),
+    IdleCallbackTimeout(IdleCallbackTimeoutCallback),
+    GeolocationTimeout(GeolocationTimeoutCallback),
+    MidiSend(MidiSendCallback),
 }
 
 impl OneshotTimerCallback {
@@ -86,6 +101,9 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::JsTimer(task) => task.invoke(this, js_timers),
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
             OneshotTimerCallback::FakeRequestAnimationFrame(callback) => callback.invoke(),
+            OneshotTimerCallback::IdleCallbackTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::GeolocationTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::MidiSend(callback) => callback.invoke(),
         }
     }
 }
@@ -123,6 +141,9 @@ impl OneshotTimers {
             suspended_since: Cell::new(None),
             suspension_offset: Cell::new(Length::new(0)),
             expected_event_id: Cell::new(TimerEventId(0)),
+            in_background: Cell::new(false),
+            background_budget: Cell::new(0.),
+            background_budget_updated_at: Cell::new(Length::new(0)),
         }
     }
 
@@ -175,6 +196,18 @@ impl OneshotTimers {
         }
     }
 
+    /// How long until the next timer is due, if there is one.
+    pub fn time_until_next_timer(&self) -> Option<MsDuration> {
+        let timers = self.timers.borrow();
+        let timer = timers.last()?;
+        Some(Length::new(
+            timer
+                .scheduled_for
+                .get()
+                .saturating_sub(self.base_time().get()),
+        ))
+    }
+
     fn is_next_timer(&self, handle: OneshotTimerHandle) -> bool {
         match self.timers.borrow().last() {
             None => false,
@@ -216,10 +249,16 @@ impl OneshotTimers {
             timers_to_run.push(timers.pop().unwrap());
         }
 
+        let start = precise_time_ns();
         for timer in timers_to_run {
             let callback = timer.callback;
             callback.invoke(global, &self.js_timers);
         }
+        if self.in_background.get() {
+            let spent = (precise_time_ns() - start) as f64 / 1_000_000.;
+            let budget = self.regenerate_background_budget();
+            self.background_budget.set(budget - spent);
+        }
 
         self.schedule_timer_call();
     }
@@ -233,15 +272,63 @@ impl OneshotTimers {
         }
     }
 
-    pub fn slow_down(&self) {
-        let duration = pref!(js.timers.minimum_duration) as u64;
-        self.js_timers.set_min_duration(MsDuration::new(duration));
+    pub fn slow_down(&self, min_duration: MsDuration) {
+        self.js_timers.set_min_duration(min_duration);
     }
 
     pub fn speed_up(&self) {
         self.js_timers.remove_min_duration();
     }
 
+    /// Aligns the wake-ups of the timers and limits them by a budget while
+    /// their window is in the background, or stops doing so.
+    pub fn set_in_background(&self, in_background: bool) {
+        if self.in_background.get() == in_background {
+            return;
+        }
+        self.in_background.set(in_background);
+        if in_background {
+            self.background_budget
+                .set(pref!(js.timers.background.budget.max) as f64);
+            self.background_budget_updated_at.set(precise_time_ms());
+        }
+        self.schedule_timer_call();
+    }
+
+    /// Regenerates the background budget for the time elapsed since it was
+    /// last regenerated, up to its maximum, and returns it.
+    fn regenerate_background_budget(&self) -> f64 {
+        let now = precise_time_ms();
+        let elapsed = (now - self.background_budget_updated_at.get()).get() as f64;
+        let budget = self.background_budget.get() +
+            elapsed * pref!(js.timers.background.budget.regeneration_rate);
+        let budget = budget.min(pref!(js.timers.background.budget.max) as f64);
+        self.background_budget.set(budget);
+        self.background_budget_updated_at.set(now);
+        budget
+    }
+
+    /// When to wake up for a timer due at `due` while in the background: not
+    /// before the budget is regenerated, unless that would delay it by more
+    /// than the maximum delay, and at a multiple of the alignment so that the
+    /// wake-ups of the timers coincide.
+    fn background_wake_up(&self, due: MsDuration) -> MsDuration {
+        let mut wake_up = due.get();
+        let budget = self.regenerate_background_budget();
+        let regeneration_rate = pref!(js.timers.background.budget.regeneration_rate);
+        if pref!(js.timers.background.budget.enabled) && budget < 0. && regeneration_rate > 0. {
+            let regenerated_at =
+                precise_time_ms().get() + (-budget / regeneration_rate).ceil() as u64;
+            let max_delay = cmp::max(pref!(js.timers.background.max_delay), 0) as u64;
+            wake_up = cmp::max(wake_up, cmp::min(regenerated_at, wake_up + max_delay));
+        }
+        let alignment = cmp::max(pref!(js.timers.background.alignment), 0) as u64;
+        if alignment > 0 {
+            wake_up = (wake_up + alignment - 1) / alignment * alignment;
+        }
+        Length::new(wake_up)
+    }
+
     pub fn suspend(&self) {
         // Suspend is idempotent: do nothing if the timers are already suspended.
         if self.suspended_since.get().is_some() {
@@ -279,12 +366,13 @@ impl OneshotTimers {
         if let Some(timer) = timers.last() {
             let expected_event_id = self.invalidate_expected_event_id();
 
-            let delay = Length::new(
-                timer
-                    .scheduled_for
-                    .get()
-                    .saturating_sub(precise_time_ms().get()),
-            );
+            // The timers are scheduled for a time that leaves out the time
+            // spent suspended.
+            let mut wake_up = timer.scheduled_for + self.suspension_offset.get();
+            if self.in_background.get() {
+                wake_up = self.background_wake_up(wake_up);
+            }
+            let delay = Length::new(wake_up.get().saturating_sub(precise_time_ms().get()));
             let request = TimerEventRequest(
                 self.timer_event_chan
                     .borrow()
@@ -509,7 +597,11 @@ impl JsTimers {
 
 // see step 7 of https://html.spec.whatwg.org/multipage/#timer-initialisation-steps
 fn clamp_duration(nesting_level: u32, unclamped: MsDuration) -> MsDuration {
-    let lower_bound = if nesting_level > 5 { 4 } else { 0 };
+    let lower_bound = if nesting_level as i64 > pref!(js.timers.nesting_level) {
+        cmp::max(pref!(js.timers.nested_minimum_duration), 0) as u64
+    } else {
+        0
+    };
 
     cmp::max(Length::new(lower_bound), unclamped)
 }
@@ -0,0 +1,310 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The client side of Web Authentication: the client data that authenticators
+//! sign, and a software authenticator that replaces the one of the embedder
+//! when the dom.webauthn.testing.enabled pref is set.
+//! <https://w3c.github.io/webauthn/>
+
+use crate::webcrypto::padded_bytes;
+use embedder_traits::{
+    Assertion, AttestedCredential, AuthenticatorError, GetAssertionRequest, MakeCredentialRequest,
+};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::{hash, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::rand::rand_bytes;
+use openssl::sign::Signer;
+use std::sync::Mutex;
+
+/// The COSE identifier of ECDSA with SHA-256 on the P-256 curve.
+/// <https://www.iana.org/assignments/cose/cose.xhtml#algorithms>
+pub const ES256: i32 = -7;
+
+/// The COSE identifier of RSASSA-PKCS1-v1_5 with SHA-256.
+pub const RS256: i32 = -257;
+
+/// The flags of the authenticator data.
+/// <https://w3c.github.io/webauthn/#flags>
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// The type of the operation that the client data is collected for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClientDataType {
+    Create,
+    Get,
+}
+
+/// The JSON-compatible serialization of the client data, with its members in
+/// the order of the spec so that relying parties can check it without
+/// parsing it.
+/// <https://w3c.github.io/webauthn/#clientdatajson-serialization>
+pub fn client_data_json(
+    type_: ClientDataType,
+    challenge: &[u8],
+    origin: &str,
+    cross_origin: bool,
+) -> Vec<u8> {
+    let type_ = match type_ {
+        ClientDataType::Create => "webauthn.create",
+        ClientDataType::Get => "webauthn.get",
+    };
+    format!(
+        "{{\"type\":{},\"challenge\":{},\"origin\":{},\"crossOrigin\":{}}}",
+        json_string(type_),
+        json_string(&base64url(challenge)),
+        json_string(origin),
+        cross_origin,
+    )
+    .into_bytes()
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).expect("Strings are always serializable")
+}
+
+/// The unpadded base64url encoding of credential ids and challenges.
+pub fn base64url(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+pub fn sha256(data: &[u8]) -> Vec<u8> {
+    hash(MessageDigest::sha256(), data)
+        .expect("SHA-256 is always available")
+        .to_vec()
+}
+
+/// Encodes the few CBOR items that attestation objects and credential public
+/// keys are made of.
+/// <https://tools.ietf.org/html/rfc7049>
+struct CborWriter(Vec<u8>);
+
+impl CborWriter {
+    fn header(&mut self, major_type: u8, value: u64) {
+        let major_type = major_type << 5;
+        if value < 24 {
+            self.0.push(major_type | value as u8);
+        } else if value <= u8::max_value() as u64 {
+            self.0.push(major_type | 24);
+            self.0.push(value as u8);
+        } else if value <= u16::max_value() as u64 {
+            self.0.push(major_type | 25);
+            self.0.extend_from_slice(&(value as u16).to_be_bytes());
+        } else if value <= u32::max_value() as u64 {
+            self.0.push(major_type | 26);
+            self.0.extend_from_slice(&(value as u32).to_be_bytes());
+        } else {
+            self.0.push(major_type | 27);
+            self.0.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+
+    fn int(&mut self, value: i64) {
+        if value < 0 {
+            self.header(1, (-1 - value) as u64);
+        } else {
+            self.header(0, value as u64);
+        }
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.header(2, value.len() as u64);
+        self.0.extend_from_slice(value);
+    }
+
+    fn text(&mut self, value: &str) {
+        self.header(3, value.len() as u64);
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn map(&mut self, len: usize) {
+        self.header(5, len as u64);
+    }
+}
+
+/// The authenticator data, which is what the signatures of an authenticator
+/// cover, along with the hash of the client data.
+/// <https://w3c.github.io/webauthn/#sctn-authenticator-data>
+fn authenticator_data(
+    rp_id: &str,
+    flags: u8,
+    sign_count: u32,
+    attested_credential_data: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut data = sha256(rp_id.as_bytes());
+    data.push(flags);
+    data.extend_from_slice(&sign_count.to_be_bytes());
+    if let Some(attested_credential_data) = attested_credential_data {
+        data.extend_from_slice(attested_credential_data);
+    }
+    data
+}
+
+/// An attestation object in the "none" attestation statement format, as
+/// only the relying parties that don't ask for an attestation are supported.
+/// <https://w3c.github.io/webauthn/#sctn-none-attestation>
+fn none_attestation_object(authenticator_data: &[u8]) -> Vec<u8> {
+    let mut writer = CborWriter(vec![]);
+    writer.map(3);
+    writer.text("fmt");
+    writer.text("none");
+    writer.text("attStmt");
+    writer.map(0);
+    writer.text("authData");
+    writer.bytes(authenticator_data);
+    writer.0
+}
+
+/// The public key of an ES256 credential as a COSE_Key.
+/// <https://w3c.github.io/webauthn/#sctn-encoded-credPubKey-examples>
+fn cose_public_key(key: &EcKey<Private>) -> Result<Vec<u8>, AuthenticatorError> {
+    let mut context = BigNumContext::new().map_err(|_| AuthenticatorError::NotAllowed)?;
+    let mut x = BigNum::new().map_err(|_| AuthenticatorError::NotAllowed)?;
+    let mut y = BigNum::new().map_err(|_| AuthenticatorError::NotAllowed)?;
+    key.public_key()
+        .affine_coordinates_gfp(key.group(), &mut x, &mut y, &mut context)
+        .map_err(|_| AuthenticatorError::NotAllowed)?;
+
+    let mut writer = CborWriter(vec![]);
+    writer.map(5);
+    // kty: EC2
+    writer.int(1);
+    writer.int(2);
+    // alg: ES256
+    writer.int(3);
+    writer.int(ES256 as i64);
+    // crv: P-256
+    writer.int(-1);
+    writer.int(1);
+    writer.int(-2);
+    writer.bytes(&padded_bytes(&x, 32));
+    writer.int(-3);
+    writer.bytes(&padded_bytes(&y, 32));
+    Ok(writer.0)
+}
+
+struct StoredCredential {
+    id: Vec<u8>,
+    rp_id: String,
+    user_handle: Vec<u8>,
+    key: EcKey<Private>,
+    sign_count: u32,
+}
+
+/// An authenticator that keeps its ES256 credentials in memory and always
+/// considers the user to be present and verified, for testing.
+#[derive(Default)]
+pub struct SoftwareAuthenticator {
+    credentials: Vec<StoredCredential>,
+}
+
+lazy_static! {
+    /// The software authenticator, which is shared by all the documents of
+    /// the content process.
+    pub static ref SOFTWARE_AUTHENTICATOR: Mutex<SoftwareAuthenticator> =
+        Mutex::new(SoftwareAuthenticator::default());
+}
+
+impl SoftwareAuthenticator {
+    /// <https://w3c.github.io/webauthn/#sctn-op-make-cred>
+    pub fn make_credential(
+        &mut self,
+        request: &MakeCredentialRequest,
+    ) -> Result<AttestedCredential, AuthenticatorError> {
+        if !request.algorithms.contains(&ES256) {
+            return Err(AuthenticatorError::NotSupported);
+        }
+        let excluded = self.credentials.iter().any(|credential| {
+            credential.rp_id == request.rp_id &&
+                request.exclude_credentials.contains(&credential.id)
+        });
+        if excluded {
+            return Err(AuthenticatorError::InvalidState);
+        }
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)
+            .map_err(|_| AuthenticatorError::NotAllowed)?;
+        let key = EcKey::generate(&group).map_err(|_| AuthenticatorError::NotAllowed)?;
+        let mut id = vec![0; 32];
+        rand_bytes(&mut id).map_err(|_| AuthenticatorError::NotAllowed)?;
+
+        // The attested credential data has no AAGUID, like with the "none"
+        // attestation conveyance.
+        // https://w3c.github.io/webauthn/#sctn-attested-credential-data
+        let mut attested_credential_data = vec![0; 16];
+        attested_credential_data.extend_from_slice(&(id.len() as u16).to_be_bytes());
+        attested_credential_data.extend_from_slice(&id);
+        attested_credential_data.extend(cose_public_key(&key)?);
+        let authenticator_data = authenticator_data(
+            &request.rp_id,
+            USER_PRESENT | USER_VERIFIED | ATTESTED_CREDENTIAL_DATA,
+            0,
+            Some(&attested_credential_data),
+        );
+
+        // All the credentials are client-side discoverable, so a new one
+        // replaces the one of the same user for the relying party.
+        self.credentials.retain(|credential| {
+            credential.rp_id != request.rp_id || credential.user_handle != request.user_id
+        });
+        self.credentials.push(StoredCredential {
+            id: id.clone(),
+            rp_id: request.rp_id.clone(),
+            user_handle: request.user_id.clone(),
+            key,
+            sign_count: 0,
+        });
+
+        Ok(AttestedCredential {
+            credential_id: id,
+            attestation_object: none_attestation_object(&authenticator_data),
+        })
+    }
+
+    /// <https://w3c.github.io/webauthn/#sctn-op-get-assertion>
+    pub fn get_assertion(
+        &mut self,
+        request: &GetAssertionRequest,
+    ) -> Result<Assertion, AuthenticatorError> {
+        // The most recently created credential is used when several match.
+        let credential = self
+            .credentials
+            .iter_mut()
+            .rev()
+            .find(|credential| {
+                credential.rp_id == request.rp_id &&
+                    (request.allow_credentials.is_empty() ||
+                        request.allow_credentials.contains(&credential.id))
+            })
+            .ok_or(AuthenticatorError::NotAllowed)?;
+
+        credential.sign_count += 1;
+        let authenticator_data = authenticator_data(
+            &request.rp_id,
+            USER_PRESENT | USER_VERIFIED,
+            credential.sign_count,
+            None,
+        );
+        let key = PKey::from_ec_key(credential.key.clone())
+            .map_err(|_| AuthenticatorError::NotAllowed)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &key)
+            .map_err(|_| AuthenticatorError::NotAllowed)?;
+        let signature = signer
+            .update(&authenticator_data)
+            .and_then(|_| signer.update(&request.client_data_hash))
+            .and_then(|_| signer.sign_to_vec())
+            .map_err(|_| AuthenticatorError::NotAllowed)?;
+
+        Ok(Assertion {
+            credential_id: credential.id.clone(),
+            authenticator_data,
+            signature,
+            user_handle: Some(credential.user_handle.clone()),
+        })
+    }
+}
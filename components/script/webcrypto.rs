@@ -0,0 +1,1538 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The cryptographic operations of `crypto.subtle`, which run off the script
+//! thread, on keys and algorithm parameters that were taken out of their DOM
+//! objects and dictionaries beforehand.
+//! <https://w3c.github.io/webcrypto/>
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{KeyType, KeyUsage};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::KeyFormat;
+use crate::dom::bindings::error::{Error, Fallible};
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::derive::Deriver;
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use openssl::ecdsa::EcdsaSig;
+use openssl::encrypt::{Decrypter, Encrypter};
+use openssl::hash::{self, MessageDigest};
+use openssl::memcmp;
+use openssl::nid::Nid;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::pkey::{HasPublic, PKey, Private, Public};
+use openssl::rand::rand_bytes;
+use openssl::rsa::{Padding, Rsa};
+use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+use openssl::symm::{self, Cipher};
+use std::convert::TryFrom;
+
+/// The names of the supported algorithms.
+/// <https://w3c.github.io/webcrypto/#algorithm-overview>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlgorithmName {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+    AesCbc,
+    AesCtr,
+    AesGcm,
+    Hmac,
+    RsaOaep,
+    RsaPss,
+    Ecdsa,
+    Ecdh,
+    Pbkdf2,
+    Hkdf,
+}
+
+const ALGORITHM_NAMES: [AlgorithmName; 14] = [
+    AlgorithmName::Sha1,
+    AlgorithmName::Sha256,
+    AlgorithmName::Sha384,
+    AlgorithmName::Sha512,
+    AlgorithmName::AesCbc,
+    AlgorithmName::AesCtr,
+    AlgorithmName::AesGcm,
+    AlgorithmName::Hmac,
+    AlgorithmName::RsaOaep,
+    AlgorithmName::RsaPss,
+    AlgorithmName::Ecdsa,
+    AlgorithmName::Ecdh,
+    AlgorithmName::Pbkdf2,
+    AlgorithmName::Hkdf,
+];
+
+impl AlgorithmName {
+    /// The algorithm whose name matches `name` case-insensitively.
+    pub fn from_name(name: &str) -> Option<AlgorithmName> {
+        ALGORITHM_NAMES
+            .iter()
+            .cloned()
+            .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AlgorithmName::Sha1 => "SHA-1",
+            AlgorithmName::Sha256 => "SHA-256",
+            AlgorithmName::Sha384 => "SHA-384",
+            AlgorithmName::Sha512 => "SHA-512",
+            AlgorithmName::AesCbc => "AES-CBC",
+            AlgorithmName::AesCtr => "AES-CTR",
+            AlgorithmName::AesGcm => "AES-GCM",
+            AlgorithmName::Hmac => "HMAC",
+            AlgorithmName::RsaOaep => "RSA-OAEP",
+            AlgorithmName::RsaPss => "RSA-PSS",
+            AlgorithmName::Ecdsa => "ECDSA",
+            AlgorithmName::Ecdh => "ECDH",
+            AlgorithmName::Pbkdf2 => "PBKDF2",
+            AlgorithmName::Hkdf => "HKDF",
+        }
+    }
+
+    /// The hash function that this algorithm is, if any.
+    pub fn hash(self) -> Option<Hash> {
+        match self {
+            AlgorithmName::Sha1 => Some(Hash::Sha1),
+            AlgorithmName::Sha256 => Some(Hash::Sha256),
+            AlgorithmName::Sha384 => Some(Hash::Sha384),
+            AlgorithmName::Sha512 => Some(Hash::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Whether this algorithm supports `operation`, whose parameters the
+    /// dictionary to convert the algorithm to depends on.
+    pub fn supports(self, operation: Operation) -> bool {
+        use self::AlgorithmName::*;
+        match operation {
+            Operation::Digest => self.hash().is_some(),
+            Operation::Encrypt | Operation::Decrypt => match self {
+                AesCbc | AesCtr | AesGcm | RsaOaep => true,
+                _ => false,
+            },
+            Operation::Sign | Operation::Verify => match self {
+                Hmac | RsaPss | Ecdsa => true,
+                _ => false,
+            },
+            Operation::GenerateKey => match self {
+                AesCbc | AesCtr | AesGcm | Hmac | RsaOaep | RsaPss | Ecdsa | Ecdh => true,
+                _ => false,
+            },
+            Operation::DeriveBits => match self {
+                Ecdh | Pbkdf2 | Hkdf => true,
+                _ => false,
+            },
+            Operation::ImportKey => self.hash().is_none(),
+            Operation::GetKeyLength => match self {
+                AesCbc | AesCtr | AesGcm | Hmac | Pbkdf2 | Hkdf => true,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// The operations which algorithms are normalized for.
+/// <https://w3c.github.io/webcrypto/#algorithm-normalization-internal>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Encrypt,
+    Decrypt,
+    Sign,
+    Verify,
+    Digest,
+    GenerateKey,
+    DeriveBits,
+    ImportKey,
+    GetKeyLength,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hash {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Hash {
+    pub fn name(self) -> &'static str {
+        match self {
+            Hash::Sha1 => "SHA-1",
+            Hash::Sha256 => "SHA-256",
+            Hash::Sha384 => "SHA-384",
+            Hash::Sha512 => "SHA-512",
+        }
+    }
+
+    fn message_digest(self) -> MessageDigest {
+        match self {
+            Hash::Sha1 => MessageDigest::sha1(),
+            Hash::Sha256 => MessageDigest::sha256(),
+            Hash::Sha384 => MessageDigest::sha384(),
+            Hash::Sha512 => MessageDigest::sha512(),
+        }
+    }
+
+    /// The size of the blocks of the hash function, in bits, which is the
+    /// default length of the HMAC keys using it.
+    fn block_size(self) -> u32 {
+        match self {
+            Hash::Sha1 | Hash::Sha256 => 512,
+            Hash::Sha384 | Hash::Sha512 => 1024,
+        }
+    }
+
+    /// The size of the output of the hash function, in bytes.
+    fn output_size(self) -> usize {
+        match self {
+            Hash::Sha1 => 20,
+            Hash::Sha256 => 32,
+            Hash::Sha384 => 48,
+            Hash::Sha512 => 64,
+        }
+    }
+
+    /// The suffix of the JSON Web Algorithms names using this hash function.
+    fn jwa_suffix(self) -> &'static str {
+        match self {
+            Hash::Sha1 => "1",
+            Hash::Sha256 => "256",
+            Hash::Sha384 => "384",
+            Hash::Sha512 => "512",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NamedCurve {
+    P256,
+    P384,
+}
+
+impl NamedCurve {
+    pub fn from_name(name: &str) -> Option<NamedCurve> {
+        match name {
+            "P-256" => Some(NamedCurve::P256),
+            "P-384" => Some(NamedCurve::P384),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedCurve::P256 => "P-256",
+            NamedCurve::P384 => "P-384",
+        }
+    }
+
+    fn nid(self) -> Nid {
+        match self {
+            NamedCurve::P256 => Nid::X9_62_PRIME256V1,
+            NamedCurve::P384 => Nid::SECP384R1,
+        }
+    }
+
+    /// The size of the coordinates of the points of the curve, in bytes.
+    fn coordinate_size(self) -> usize {
+        match self {
+            NamedCurve::P256 => 32,
+            NamedCurve::P384 => 48,
+        }
+    }
+
+    fn group(self) -> Fallible<EcGroup> {
+        EcGroup::from_curve_name(self.nid()).map_err(|_| Error::Operation)
+    }
+}
+
+/// An algorithm along with its parameters, converted from the dictionary of
+/// its name for an operation.
+/// <https://w3c.github.io/webcrypto/#dfn-normalize-an-algorithm>
+pub struct NormalizedAlgorithm {
+    pub name: AlgorithmName,
+    pub params: Params,
+}
+
+pub enum Params {
+    /// Algorithm
+    None,
+    /// AesKeyGenParams and AesDerivedKeyParams
+    AesKey {
+        length: u16,
+    },
+    /// HmacKeyGenParams and HmacImportParams
+    HmacKey {
+        hash: Hash,
+        length: Option<u32>,
+    },
+    RsaHashedKeyGen {
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: Hash,
+    },
+    RsaHashedImport {
+        hash: Hash,
+    },
+    /// EcKeyGenParams and EcKeyImportParams
+    EcKey {
+        named_curve: NamedCurve,
+    },
+    AesCbc {
+        iv: Vec<u8>,
+    },
+    AesCtr {
+        counter: Vec<u8>,
+        length: u8,
+    },
+    AesGcm {
+        iv: Vec<u8>,
+        additional_data: Vec<u8>,
+        tag_length: u8,
+    },
+    RsaOaep {
+        label: Vec<u8>,
+    },
+    RsaPss {
+        salt_length: u32,
+    },
+    Ecdsa {
+        hash: Hash,
+    },
+    Ecdh {
+        public: Key,
+    },
+    Pbkdf2 {
+        salt: Vec<u8>,
+        iterations: u32,
+        hash: Hash,
+    },
+    Hkdf {
+        hash: Hash,
+        salt: Vec<u8>,
+        info: Vec<u8>,
+    },
+}
+
+/// The algorithm of a key, which its `algorithm` attribute reflects.
+#[derive(Clone, Debug)]
+pub enum KeyAlgorithm {
+    Aes {
+        name: AlgorithmName,
+        length: u16,
+    },
+    Hmac {
+        hash: Hash,
+        length: u32,
+    },
+    RsaHashed {
+        name: AlgorithmName,
+        modulus_length: u32,
+        public_exponent: Vec<u8>,
+        hash: Hash,
+    },
+    Ec {
+        name: AlgorithmName,
+        named_curve: NamedCurve,
+    },
+    Kdf {
+        name: AlgorithmName,
+    },
+}
+
+impl KeyAlgorithm {
+    pub fn name(&self) -> AlgorithmName {
+        match *self {
+            KeyAlgorithm::Aes { name, .. } |
+            KeyAlgorithm::RsaHashed { name, .. } |
+            KeyAlgorithm::Ec { name, .. } |
+            KeyAlgorithm::Kdf { name } => name,
+            KeyAlgorithm::Hmac { .. } => AlgorithmName::Hmac,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum KeyData {
+    Secret(Vec<u8>),
+    Private(PKey<Private>),
+    Public(PKey<Public>),
+}
+
+/// The internal slots of a `CryptoKey`.
+#[derive(Clone)]
+pub struct Key {
+    pub algorithm: KeyAlgorithm,
+    pub data: KeyData,
+    pub extractable: bool,
+    pub usages: Vec<KeyUsage>,
+}
+
+impl Key {
+    pub fn key_type(&self) -> KeyType {
+        match self.data {
+            KeyData::Secret(_) => KeyType::Secret,
+            KeyData::Private(_) => KeyType::Private,
+            KeyData::Public(_) => KeyType::Public,
+        }
+    }
+
+    fn new(algorithm: KeyAlgorithm, data: KeyData, extractable: bool, usages: &[KeyUsage]) -> Key {
+        Key {
+            algorithm,
+            data,
+            extractable,
+            usages: usages.to_vec(),
+        }
+    }
+
+    fn secret(&self) -> Fallible<&[u8]> {
+        match self.data {
+            KeyData::Secret(ref secret) => Ok(secret),
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    fn private(&self) -> Fallible<&PKey<Private>> {
+        match self.data {
+            KeyData::Private(ref key) => Ok(key),
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    fn public(&self) -> Fallible<&PKey<Public>> {
+        match self.data {
+            KeyData::Public(ref key) => Ok(key),
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    /// Checks that this key can be used with `algorithm` for `usage`.
+    fn check(&self, algorithm: &NormalizedAlgorithm, usage: KeyUsage) -> Fallible<()> {
+        if self.algorithm.name() != algorithm.name || !self.usages.contains(&usage) {
+            return Err(Error::InvalidAccess);
+        }
+        Ok(())
+    }
+}
+
+/// The result of generateKey.
+pub enum GeneratedKey {
+    Key(Key),
+    Pair { public: Key, private: Key },
+}
+
+/// A JSON Web Key, whose members are those of the `JsonWebKey` dictionary.
+/// <https://tools.ietf.org/html/rfc7517>
+#[derive(Default)]
+pub struct Jwk {
+    pub kty: Option<String>,
+    pub use_: Option<String>,
+    pub key_ops: Option<Vec<String>>,
+    pub alg: Option<String>,
+    pub ext: Option<bool>,
+    pub crv: Option<String>,
+    pub x: Option<String>,
+    pub y: Option<String>,
+    pub d: Option<String>,
+    pub n: Option<String>,
+    pub e: Option<String>,
+    pub p: Option<String>,
+    pub q: Option<String>,
+    pub dp: Option<String>,
+    pub dq: Option<String>,
+    pub qi: Option<String>,
+    pub has_other_primes: bool,
+    pub k: Option<String>,
+}
+
+/// The data of imported or exported keys.
+pub enum KeyMaterial {
+    Bytes(Vec<u8>),
+    Jwk(Jwk),
+}
+
+const ENCRYPTION_USAGES: [KeyUsage; 4] = [
+    KeyUsage::Encrypt,
+    KeyUsage::Decrypt,
+    KeyUsage::WrapKey,
+    KeyUsage::UnwrapKey,
+];
+const SIGNATURE_USAGES: [KeyUsage; 2] = [KeyUsage::Sign, KeyUsage::Verify];
+const DERIVATION_USAGES: [KeyUsage; 2] = [KeyUsage::DeriveKey, KeyUsage::DeriveBits];
+
+/// Throws a SyntaxError if any of `usages` isn't one of `allowed`.
+fn check_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Fallible<()> {
+    if usages.iter().all(|usage| allowed.contains(usage)) {
+        Ok(())
+    } else {
+        Err(Error::Syntax)
+    }
+}
+
+fn usages_among(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Vec<KeyUsage> {
+    usages
+        .iter()
+        .cloned()
+        .filter(|usage| allowed.contains(usage))
+        .collect()
+}
+
+/// The usages that the public and private keys of a key pair of the
+/// algorithm `name` may have.
+fn key_pair_usages(name: AlgorithmName) -> (&'static [KeyUsage], &'static [KeyUsage]) {
+    match name {
+        AlgorithmName::RsaOaep => (
+            &[KeyUsage::Encrypt, KeyUsage::WrapKey],
+            &[KeyUsage::Decrypt, KeyUsage::UnwrapKey],
+        ),
+        AlgorithmName::Ecdh => (&[], &DERIVATION_USAGES),
+        _ => (&[KeyUsage::Verify], &[KeyUsage::Sign]),
+    }
+}
+
+fn random_bytes(length: usize) -> Fallible<Vec<u8>> {
+    let mut bytes = vec![0; length];
+    rand_bytes(&mut bytes).map_err(|_| Error::Operation)?;
+    Ok(bytes)
+}
+
+fn aes_cipher(name: AlgorithmName, key: &[u8]) -> Fallible<Cipher> {
+    Ok(match (name, key.len()) {
+        (AlgorithmName::AesCbc, 16) => Cipher::aes_128_cbc(),
+        (AlgorithmName::AesCbc, 24) => Cipher::aes_192_cbc(),
+        (AlgorithmName::AesCbc, 32) => Cipher::aes_256_cbc(),
+        (AlgorithmName::AesCtr, 16) => Cipher::aes_128_ctr(),
+        (AlgorithmName::AesCtr, 24) => Cipher::aes_192_ctr(),
+        (AlgorithmName::AesCtr, 32) => Cipher::aes_256_ctr(),
+        (AlgorithmName::AesGcm, 16) => Cipher::aes_128_gcm(),
+        (AlgorithmName::AesGcm, 24) => Cipher::aes_192_gcm(),
+        (AlgorithmName::AesGcm, 32) => Cipher::aes_256_gcm(),
+        _ => return Err(Error::Operation),
+    })
+}
+
+/// https://w3c.github.io/webcrypto/#sha-operations
+pub fn digest(name: AlgorithmName, data: &[u8]) -> Fallible<Vec<u8>> {
+    let hash_function = name.hash().ok_or(Error::NotSupported)?;
+    hash::hash(hash_function.message_digest(), data)
+        .map(|digest| digest.to_vec())
+        .map_err(|_| Error::Operation)
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-generateKey
+pub fn generate_key(
+    algorithm: &NormalizedAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<GeneratedKey> {
+    let name = algorithm.name;
+    let generated = match algorithm.params {
+        Params::AesKey { length } => {
+            check_usages(usages, &ENCRYPTION_USAGES)?;
+            if length != 128 && length != 192 && length != 256 {
+                return Err(Error::Operation);
+            }
+            let secret = random_bytes(length as usize / 8)?;
+            GeneratedKey::Key(Key::new(
+                KeyAlgorithm::Aes { name, length },
+                KeyData::Secret(secret),
+                extractable,
+                usages,
+            ))
+        },
+        Params::HmacKey { hash, length } => {
+            check_usages(usages, &SIGNATURE_USAGES)?;
+            let length = length.unwrap_or(hash.block_size());
+            if length == 0 {
+                return Err(Error::Operation);
+            }
+            let secret = random_bytes((length as usize + 7) / 8)?;
+            GeneratedKey::Key(Key::new(
+                KeyAlgorithm::Hmac { hash, length },
+                KeyData::Secret(secret),
+                extractable,
+                usages,
+            ))
+        },
+        Params::RsaHashedKeyGen {
+            modulus_length,
+            ref public_exponent,
+            hash,
+        } => {
+            let (public_usages, private_usages) = key_pair_usages(name);
+            check_usages(usages, &[public_usages, private_usages].concat())?;
+            let exponent = BigNum::from_slice(public_exponent).map_err(|_| Error::Operation)?;
+            let rsa =
+                Rsa::generate_with_e(modulus_length, &exponent).map_err(|_| Error::Operation)?;
+            let private = PKey::from_rsa(rsa).map_err(|_| Error::Operation)?;
+            let key_algorithm = KeyAlgorithm::RsaHashed {
+                name,
+                modulus_length,
+                public_exponent: public_exponent.clone(),
+                hash,
+            };
+            key_pair(key_algorithm, private, extractable, usages)?
+        },
+        Params::EcKey { named_curve } => {
+            let (public_usages, private_usages) = key_pair_usages(name);
+            check_usages(usages, &[public_usages, private_usages].concat())?;
+            let ec_key = EcKey::generate(&named_curve.group()?).map_err(|_| Error::Operation)?;
+            let private = PKey::from_ec_key(ec_key).map_err(|_| Error::Operation)?;
+            let key_algorithm = KeyAlgorithm::Ec { name, named_curve };
+            key_pair(key_algorithm, private, extractable, usages)?
+        },
+        _ => return Err(Error::NotSupported),
+    };
+
+    // Step 9.
+    let private_usages = match generated {
+        GeneratedKey::Key(ref key) => &key.usages,
+        GeneratedKey::Pair { ref private, .. } => &private.usages,
+    };
+    if private_usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(generated)
+}
+
+fn key_pair(
+    algorithm: KeyAlgorithm,
+    private: PKey<Private>,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<GeneratedKey> {
+    let (public_usages, private_usages) = key_pair_usages(algorithm.name());
+    let public = public_key(&private)?;
+    Ok(GeneratedKey::Pair {
+        // Public keys are always extractable.
+        public: Key::new(
+            algorithm.clone(),
+            KeyData::Public(public),
+            true,
+            &usages_among(usages, public_usages),
+        ),
+        private: Key::new(
+            algorithm,
+            KeyData::Private(private),
+            extractable,
+            &usages_among(usages, private_usages),
+        ),
+    })
+}
+
+fn public_key(private: &PKey<Private>) -> Fallible<PKey<Public>> {
+    private
+        .public_key_to_der()
+        .and_then(|spki| PKey::public_key_from_der(&spki))
+        .map_err(|_| Error::Operation)
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-importKey
+pub fn import_key(
+    format: KeyFormat,
+    material: KeyMaterial,
+    algorithm: &NormalizedAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Key> {
+    let name = algorithm.name;
+    let key = match algorithm.params {
+        Params::None
+            if name == AlgorithmName::AesCbc ||
+                name == AlgorithmName::AesCtr ||
+                name == AlgorithmName::AesGcm =>
+        {
+            check_usages(usages, &ENCRYPTION_USAGES)?;
+            let secret = match (format, material) {
+                (KeyFormat::Raw, KeyMaterial::Bytes(bytes)) => bytes,
+                (KeyFormat::Jwk, KeyMaterial::Jwk(jwk)) => {
+                    check_jwk(&jwk, "oct", "enc", usages, extractable)?;
+                    let secret = decode_jwk_member(&jwk.k)?;
+                    let suffix = &name.name()[4..];
+                    let alg = format!("A{}{}", secret.len() * 8, suffix);
+                    if jwk.alg.as_ref().map_or(false, |jwk_alg| *jwk_alg != alg) {
+                        return Err(Error::Data);
+                    }
+                    secret
+                },
+                _ => return Err(Error::NotSupported),
+            };
+            let length = match secret.len() {
+                16 | 24 | 32 => secret.len() as u16 * 8,
+                _ => return Err(Error::Data),
+            };
+            Key::new(
+                KeyAlgorithm::Aes { name, length },
+                KeyData::Secret(secret),
+                extractable,
+                usages,
+            )
+        }
+        Params::HmacKey { hash, length } => {
+            check_usages(usages, &SIGNATURE_USAGES)?;
+            let secret = match (format, material) {
+                (KeyFormat::Raw, KeyMaterial::Bytes(bytes)) => bytes,
+                (KeyFormat::Jwk, KeyMaterial::Jwk(jwk)) => {
+                    check_jwk(&jwk, "oct", "sig", usages, extractable)?;
+                    let alg = format!("HS{}", hash.jwa_suffix());
+                    if jwk.alg.as_ref().map_or(false, |jwk_alg| *jwk_alg != alg) {
+                        return Err(Error::Data);
+                    }
+                    decode_jwk_member(&jwk.k)?
+                },
+                _ => return Err(Error::NotSupported),
+            };
+            let data_length = secret.len() as u32 * 8;
+            if data_length == 0 {
+                return Err(Error::Data);
+            }
+            let length = match length {
+                Some(length) if length > data_length || length + 8 <= data_length => {
+                    return Err(Error::Data);
+                },
+                Some(length) => length,
+                None => data_length,
+            };
+            Key::new(
+                KeyAlgorithm::Hmac { hash, length },
+                KeyData::Secret(secret),
+                extractable,
+                usages,
+            )
+        },
+        Params::None if name == AlgorithmName::Pbkdf2 || name == AlgorithmName::Hkdf => {
+            check_usages(usages, &DERIVATION_USAGES)?;
+            if extractable {
+                return Err(Error::Syntax);
+            }
+            let secret = match (format, material) {
+                (KeyFormat::Raw, KeyMaterial::Bytes(bytes)) => bytes,
+                _ => return Err(Error::NotSupported),
+            };
+            Key::new(
+                KeyAlgorithm::Kdf { name },
+                KeyData::Secret(secret),
+                extractable,
+                usages,
+            )
+        },
+        Params::RsaHashedImport { hash } => {
+            let (public_usages, private_usages) = key_pair_usages(name);
+            let data = match (format, material) {
+                (KeyFormat::Spki, KeyMaterial::Bytes(bytes)) => {
+                    KeyData::Public(PKey::public_key_from_der(&bytes).map_err(|_| Error::Data)?)
+                },
+                (KeyFormat::Pkcs8, KeyMaterial::Bytes(bytes)) => {
+                    KeyData::Private(PKey::private_key_from_pkcs8(&bytes).map_err(|_| Error::Data)?)
+                },
+                (KeyFormat::Jwk, KeyMaterial::Jwk(jwk)) => {
+                    let use_ = if name == AlgorithmName::RsaOaep {
+                        "enc"
+                    } else {
+                        "sig"
+                    };
+                    check_jwk(&jwk, "RSA", use_, usages, extractable)?;
+                    if jwk
+                        .alg
+                        .as_ref()
+                        .map_or(false, |alg| *alg != rsa_jwa(name, hash))
+                    {
+                        return Err(Error::Data);
+                    }
+                    rsa_key_from_jwk(&jwk)?
+                },
+                _ => return Err(Error::NotSupported),
+            };
+            let (modulus_length, public_exponent) = match data {
+                KeyData::Private(ref key) => {
+                    check_usages(usages, private_usages)?;
+                    rsa_parameters(key)?
+                },
+                KeyData::Public(ref key) => {
+                    check_usages(usages, public_usages)?;
+                    rsa_parameters(key)?
+                },
+                KeyData::Secret(_) => unreachable!(),
+            };
+            let key_algorithm = KeyAlgorithm::RsaHashed {
+                name,
+                modulus_length,
+                public_exponent,
+                hash,
+            };
+            Key::new(key_algorithm, data, extractable, usages)
+        },
+        Params::EcKey { named_curve } => {
+            let (public_usages, private_usages) = key_pair_usages(name);
+            let data = match (format, material) {
+                (KeyFormat::Raw, KeyMaterial::Bytes(bytes)) => {
+                    let group = named_curve.group()?;
+                    let mut context = BigNumContext::new().map_err(|_| Error::Operation)?;
+                    let point = EcPoint::from_bytes(&group, &bytes, &mut context)
+                        .map_err(|_| Error::Data)?;
+                    let ec_key = EcKey::from_public_key(&group, &point).map_err(|_| Error::Data)?;
+                    KeyData::Public(PKey::from_ec_key(ec_key).map_err(|_| Error::Data)?)
+                },
+                (KeyFormat::Spki, KeyMaterial::Bytes(bytes)) => {
+                    KeyData::Public(PKey::public_key_from_der(&bytes).map_err(|_| Error::Data)?)
+                },
+                (KeyFormat::Pkcs8, KeyMaterial::Bytes(bytes)) => {
+                    KeyData::Private(PKey::private_key_from_pkcs8(&bytes).map_err(|_| Error::Data)?)
+                },
+                (KeyFormat::Jwk, KeyMaterial::Jwk(jwk)) => {
+                    let use_ = if name == AlgorithmName::Ecdh {
+                        "enc"
+                    } else {
+                        "sig"
+                    };
+                    check_jwk(&jwk, "EC", use_, usages, extractable)?;
+                    if jwk
+                        .crv
+                        .as_ref()
+                        .map_or(true, |crv| crv != named_curve.name())
+                    {
+                        return Err(Error::Data);
+                    }
+                    if name == AlgorithmName::Ecdsa {
+                        let alg = format!("ES{}", &named_curve.name()[2..]);
+                        if jwk.alg.as_ref().map_or(false, |jwk_alg| *jwk_alg != alg) {
+                            return Err(Error::Data);
+                        }
+                    }
+                    ec_key_from_jwk(&jwk, named_curve)?
+                },
+                _ => return Err(Error::NotSupported),
+            };
+            let curve = match data {
+                KeyData::Private(ref key) => {
+                    check_usages(usages, private_usages)?;
+                    key.ec_key().map(|key| key.group().curve_name())
+                },
+                KeyData::Public(ref key) => {
+                    check_usages(usages, public_usages)?;
+                    key.ec_key().map(|key| key.group().curve_name())
+                },
+                KeyData::Secret(_) => unreachable!(),
+            };
+            if curve.map_err(|_| Error::Data)? != Some(named_curve.nid()) {
+                return Err(Error::Data);
+            }
+            Key::new(
+                KeyAlgorithm::Ec { name, named_curve },
+                data,
+                extractable,
+                usages,
+            )
+        },
+        _ => return Err(Error::NotSupported),
+    };
+
+    // Step 9.
+    if key.key_type() != KeyType::Public && key.usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(key)
+}
+
+fn rsa_jwa(name: AlgorithmName, hash: Hash) -> String {
+    match (name, hash) {
+        (AlgorithmName::RsaOaep, Hash::Sha1) => "RSA-OAEP".to_owned(),
+        (AlgorithmName::RsaOaep, _) => format!("RSA-OAEP-{}", hash.jwa_suffix()),
+        _ => format!("PS{}", hash.jwa_suffix()),
+    }
+}
+
+/// The modulus length, in bits, and the public exponent of an RSA key.
+fn rsa_parameters<T>(key: &PKey<T>) -> Fallible<(u32, Vec<u8>)>
+where
+    T: HasPublic,
+{
+    let rsa = key.rsa().map_err(|_| Error::Data)?;
+    Ok((rsa.n().num_bits() as u32, rsa.e().to_vec()))
+}
+
+/// Checks the members of `jwk` that all the kinds of keys share.
+fn check_jwk(
+    jwk: &Jwk,
+    kty: &str,
+    use_: &str,
+    usages: &[KeyUsage],
+    extractable: bool,
+) -> Fallible<()> {
+    if jwk.kty.as_ref().map_or(true, |jwk_kty| jwk_kty != kty) {
+        return Err(Error::Data);
+    }
+    if !usages.is_empty() && jwk.use_.as_ref().map_or(false, |jwk_use| jwk_use != use_) {
+        return Err(Error::Data);
+    }
+    if let Some(ref key_ops) = jwk.key_ops {
+        if usages
+            .iter()
+            .any(|usage| !key_ops.iter().any(|op| op == usage.as_str()))
+        {
+            return Err(Error::Data);
+        }
+    }
+    if extractable && jwk.ext == Some(false) {
+        return Err(Error::Data);
+    }
+    Ok(())
+}
+
+fn decode_jwk_member(member: &Option<String>) -> Fallible<Vec<u8>> {
+    let member = member.as_ref().ok_or(Error::Data)?;
+    base64::decode_config(member, base64::URL_SAFE_NO_PAD).map_err(|_| Error::Data)
+}
+
+fn jwk_big_num(member: &Option<String>) -> Fallible<BigNum> {
+    BigNum::from_slice(&decode_jwk_member(member)?).map_err(|_| Error::Data)
+}
+
+fn encode_jwk_member(bytes: &[u8]) -> Option<String> {
+    Some(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+}
+
+/// The big-endian bytes of `number`, padded to `length` bytes.
+pub fn padded_bytes(number: &BigNumRef, length: usize) -> Vec<u8> {
+    let bytes = number.to_vec();
+    let mut padded = vec![0; length.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+fn rsa_key_from_jwk(jwk: &Jwk) -> Fallible<KeyData> {
+    let n = jwk_big_num(&jwk.n)?;
+    let e = jwk_big_num(&jwk.e)?;
+    if jwk.d.is_none() {
+        let rsa = Rsa::from_public_components(n, e).map_err(|_| Error::Data)?;
+        return Ok(KeyData::Public(
+            PKey::from_rsa(rsa).map_err(|_| Error::Data)?,
+        ));
+    }
+    if jwk.has_other_primes {
+        // Keys with more than two primes aren't supported by openssl.
+        return Err(Error::NotSupported);
+    }
+    let rsa = Rsa::from_private_components(
+        n,
+        e,
+        jwk_big_num(&jwk.d)?,
+        jwk_big_num(&jwk.p)?,
+        jwk_big_num(&jwk.q)?,
+        jwk_big_num(&jwk.dp)?,
+        jwk_big_num(&jwk.dq)?,
+        jwk_big_num(&jwk.qi)?,
+    )
+    .map_err(|_| Error::Data)?;
+    if !rsa.check_key().unwrap_or(false) {
+        return Err(Error::Data);
+    }
+    Ok(KeyData::Private(
+        PKey::from_rsa(rsa).map_err(|_| Error::Data)?,
+    ))
+}
+
+fn ec_key_from_jwk(jwk: &Jwk, named_curve: NamedCurve) -> Fallible<KeyData> {
+    let group = named_curve.group()?;
+    let x = jwk_big_num(&jwk.x)?;
+    let y = jwk_big_num(&jwk.y)?;
+    let public =
+        EcKey::from_public_key_affine_coordinates(&group, &x, &y).map_err(|_| Error::Data)?;
+    if jwk.d.is_none() {
+        return Ok(KeyData::Public(
+            PKey::from_ec_key(public).map_err(|_| Error::Data)?,
+        ));
+    }
+    let d = jwk_big_num(&jwk.d)?;
+    let private =
+        EcKey::from_private_components(&group, &d, public.public_key()).map_err(|_| Error::Data)?;
+    private.check_key().map_err(|_| Error::Data)?;
+    Ok(KeyData::Private(
+        PKey::from_ec_key(private).map_err(|_| Error::Data)?,
+    ))
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-exportKey
+pub fn export_key(format: KeyFormat, key: &Key) -> Fallible<KeyMaterial> {
+    // Step 6.
+    if !key.extractable {
+        return Err(Error::InvalidAccess);
+    }
+
+    let material = match (format, &key.algorithm, &key.data) {
+        (_, &KeyAlgorithm::Kdf { .. }, _) => return Err(Error::NotSupported),
+        (KeyFormat::Raw, _, &KeyData::Secret(ref secret)) => KeyMaterial::Bytes(secret.clone()),
+        (KeyFormat::Jwk, algorithm, &KeyData::Secret(ref secret)) => {
+            let alg = match *algorithm {
+                KeyAlgorithm::Aes { name, length } => format!("A{}{}", length, &name.name()[4..]),
+                KeyAlgorithm::Hmac { hash, .. } => format!("HS{}", hash.jwa_suffix()),
+                _ => return Err(Error::NotSupported),
+            };
+            KeyMaterial::Jwk(Jwk {
+                kty: Some("oct".to_owned()),
+                k: encode_jwk_member(secret),
+                alg: Some(alg),
+                ..jwk_for_key(key)
+            })
+        },
+        (KeyFormat::Raw, &KeyAlgorithm::Ec { .. }, &KeyData::Public(ref public)) => {
+            let ec_key = public.ec_key().map_err(|_| Error::Operation)?;
+            let mut context = BigNumContext::new().map_err(|_| Error::Operation)?;
+            let bytes = ec_key
+                .public_key()
+                .to_bytes(
+                    ec_key.group(),
+                    PointConversionForm::UNCOMPRESSED,
+                    &mut context,
+                )
+                .map_err(|_| Error::Operation)?;
+            KeyMaterial::Bytes(bytes)
+        },
+        (KeyFormat::Spki, _, &KeyData::Public(ref public)) => {
+            KeyMaterial::Bytes(public.public_key_to_der().map_err(|_| Error::Operation)?)
+        },
+        (KeyFormat::Pkcs8, _, &KeyData::Private(ref private)) => {
+            KeyMaterial::Bytes(pkcs8(private)?)
+        },
+        (KeyFormat::Jwk, &KeyAlgorithm::RsaHashed { name, hash, .. }, data) => {
+            let mut jwk = match *data {
+                KeyData::Public(ref public) => {
+                    let rsa = public.rsa().map_err(|_| Error::Operation)?;
+                    Jwk {
+                        n: encode_jwk_member(&rsa.n().to_vec()),
+                        e: encode_jwk_member(&rsa.e().to_vec()),
+                        ..Jwk::default()
+                    }
+                },
+                KeyData::Private(ref private) => {
+                    let rsa = private.rsa().map_err(|_| Error::Operation)?;
+                    let member = |number: Option<&BigNumRef>| {
+                        number
+                            .map(|number| number.to_vec())
+                            .and_then(|bytes| encode_jwk_member(&bytes))
+                            .ok_or(Error::Operation)
+                    };
+                    Jwk {
+                        n: encode_jwk_member(&rsa.n().to_vec()),
+                        e: encode_jwk_member(&rsa.e().to_vec()),
+                        d: encode_jwk_member(&rsa.d().to_vec()),
+                        p: Some(member(rsa.p())?),
+                        q: Some(member(rsa.q())?),
+                        dp: Some(member(rsa.dmp1())?),
+                        dq: Some(member(rsa.dmq1())?),
+                        qi: Some(member(rsa.iqmp())?),
+                        ..Jwk::default()
+                    }
+                },
+                KeyData::Secret(_) => return Err(Error::Operation),
+            };
+            jwk.kty = Some("RSA".to_owned());
+            jwk.alg = Some(rsa_jwa(name, hash));
+            KeyMaterial::Jwk(merge_jwk(jwk, jwk_for_key(key)))
+        },
+        (KeyFormat::Jwk, &KeyAlgorithm::Ec { named_curve, .. }, data) => {
+            let size = named_curve.coordinate_size();
+            let (public, d) = match *data {
+                KeyData::Public(ref public) => {
+                    (public.ec_key().map_err(|_| Error::Operation)?, None)
+                },
+                KeyData::Private(ref private) => {
+                    let ec_key = private.ec_key().map_err(|_| Error::Operation)?;
+                    let d = encode_jwk_member(&padded_bytes(ec_key.private_key(), size));
+                    let public = EcKey::from_public_key(ec_key.group(), ec_key.public_key())
+                        .map_err(|_| Error::Operation)?;
+                    (public, d)
+                },
+                KeyData::Secret(_) => return Err(Error::Operation),
+            };
+            let mut context = BigNumContext::new().map_err(|_| Error::Operation)?;
+            let mut x = BigNum::new().map_err(|_| Error::Operation)?;
+            let mut y = BigNum::new().map_err(|_| Error::Operation)?;
+            public
+                .public_key()
+                .affine_coordinates_gfp(public.group(), &mut x, &mut y, &mut context)
+                .map_err(|_| Error::Operation)?;
+            let jwk = Jwk {
+                kty: Some("EC".to_owned()),
+                crv: Some(named_curve.name().to_owned()),
+                x: encode_jwk_member(&padded_bytes(&x, size)),
+                y: encode_jwk_member(&padded_bytes(&y, size)),
+                d,
+                ..Jwk::default()
+            };
+            KeyMaterial::Jwk(merge_jwk(jwk, jwk_for_key(key)))
+        },
+        _ => return Err(Error::InvalidAccess),
+    };
+    Ok(material)
+}
+
+/// The members of the JSON Web Key of `key` which don't depend on its kind.
+fn jwk_for_key(key: &Key) -> Jwk {
+    Jwk {
+        key_ops: Some(
+            key.usages
+                .iter()
+                .map(|usage| usage.as_str().to_owned())
+                .collect(),
+        ),
+        ext: Some(key.extractable),
+        ..Jwk::default()
+    }
+}
+
+fn merge_jwk(jwk: Jwk, common: Jwk) -> Jwk {
+    Jwk {
+        key_ops: common.key_ops,
+        ext: common.ext,
+        ..jwk
+    }
+}
+
+/// The PrivateKeyInfo DER encoding of `key`, which openssl only outputs
+/// within a PEM document.
+fn pkcs8(key: &PKey<Private>) -> Fallible<Vec<u8>> {
+    let pem = key
+        .private_key_to_pem_pkcs8()
+        .map_err(|_| Error::Operation)?;
+    let pem = String::from_utf8(pem).map_err(|_| Error::Operation)?;
+    let base64: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    base64::decode(&base64).map_err(|_| Error::Operation)
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-encrypt
+pub fn encrypt(algorithm: &NormalizedAlgorithm, key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    key.check(algorithm, KeyUsage::Encrypt)?;
+    match algorithm.params {
+        Params::AesCbc { ref iv } => {
+            let secret = key.secret()?;
+            if iv.len() != 16 {
+                return Err(Error::Operation);
+            }
+            symm::encrypt(aes_cipher(algorithm.name, secret)?, secret, Some(iv), data)
+                .map_err(|_| Error::Operation)
+        },
+        Params::AesCtr {
+            ref counter,
+            length,
+        } => aes_ctr(key.secret()?, counter, length, data),
+        Params::AesGcm {
+            ref iv,
+            ref additional_data,
+            tag_length,
+        } => {
+            let secret = key.secret()?;
+            check_gcm_parameters(iv, tag_length)?;
+            let mut tag = vec![0; tag_length as usize / 8];
+            let mut ciphertext = symm::encrypt_aead(
+                aes_cipher(algorithm.name, secret)?,
+                secret,
+                Some(iv),
+                additional_data,
+                data,
+                &mut tag,
+            )
+            .map_err(|_| Error::Operation)?;
+            ciphertext.extend(tag);
+            Ok(ciphertext)
+        },
+        Params::RsaOaep { ref label } => {
+            let public = key.public()?;
+            let hash = rsa_hash(key)?.message_digest();
+            let mut encrypter = Encrypter::new(public).map_err(|_| Error::Operation)?;
+            encrypter
+                .set_rsa_padding(Padding::PKCS1_OAEP)
+                .and_then(|_| encrypter.set_rsa_oaep_md(hash))
+                .and_then(|_| encrypter.set_rsa_mgf1_md(hash))
+                .and_then(|_| encrypter.set_rsa_oaep_label(label))
+                .map_err(|_| Error::Operation)?;
+            let mut ciphertext =
+                vec![0; encrypter.encrypt_len(data).map_err(|_| Error::Operation)?];
+            let length = encrypter
+                .encrypt(data, &mut ciphertext)
+                .map_err(|_| Error::Operation)?;
+            ciphertext.truncate(length);
+            Ok(ciphertext)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-decrypt
+pub fn decrypt(algorithm: &NormalizedAlgorithm, key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    key.check(algorithm, KeyUsage::Decrypt)?;
+    match algorithm.params {
+        Params::AesCbc { ref iv } => {
+            let secret = key.secret()?;
+            if iv.len() != 16 {
+                return Err(Error::Operation);
+            }
+            symm::decrypt(aes_cipher(algorithm.name, secret)?, secret, Some(iv), data)
+                .map_err(|_| Error::Operation)
+        },
+        Params::AesCtr {
+            ref counter,
+            length,
+        } => aes_ctr(key.secret()?, counter, length, data),
+        Params::AesGcm {
+            ref iv,
+            ref additional_data,
+            tag_length,
+        } => {
+            let secret = key.secret()?;
+            check_gcm_parameters(iv, tag_length)?;
+            let tag_size = tag_length as usize / 8;
+            if data.len() < tag_size {
+                return Err(Error::Operation);
+            }
+            let (ciphertext, tag) = data.split_at(data.len() - tag_size);
+            symm::decrypt_aead(
+                aes_cipher(algorithm.name, secret)?,
+                secret,
+                Some(iv),
+                additional_data,
+                ciphertext,
+                tag,
+            )
+            .map_err(|_| Error::Operation)
+        },
+        Params::RsaOaep { ref label } => {
+            let private = key.private()?;
+            let hash = rsa_hash(key)?.message_digest();
+            let mut decrypter = Decrypter::new(private).map_err(|_| Error::Operation)?;
+            decrypter
+                .set_rsa_padding(Padding::PKCS1_OAEP)
+                .and_then(|_| decrypter.set_rsa_oaep_md(hash))
+                .and_then(|_| decrypter.set_rsa_mgf1_md(hash))
+                .and_then(|_| decrypter.set_rsa_oaep_label(label))
+                .map_err(|_| Error::Operation)?;
+            let mut plaintext = vec![0; decrypter.decrypt_len(data).map_err(|_| Error::Operation)?];
+            let length = decrypter
+                .decrypt(data, &mut plaintext)
+                .map_err(|_| Error::Operation)?;
+            plaintext.truncate(length);
+            Ok(plaintext)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn check_gcm_parameters(iv: &[u8], tag_length: u8) -> Fallible<()> {
+    match tag_length {
+        32 | 64 | 96 | 104 | 112 | 120 | 128 if !iv.is_empty() => Ok(()),
+        _ => Err(Error::Operation),
+    }
+}
+
+/// Encrypts or decrypts `data` with AES in counter mode, in which only the
+/// rightmost `length` bits of the counter block get incremented, while
+/// openssl increments all of them, so the data is split wherever the counter
+/// wraps around.
+fn aes_ctr(secret: &[u8], counter: &[u8], length: u8, data: &[u8]) -> Fallible<Vec<u8>> {
+    if counter.len() != 16 || length == 0 || length > 128 {
+        return Err(Error::Operation);
+    }
+    let cipher = aes_cipher(AlgorithmName::AesCtr, secret)?;
+    let mask = if length == 128 {
+        std::u128::MAX
+    } else {
+        (1 << length) - 1
+    };
+    let blocks = (data.len() as u128 + 15) / 16;
+    if length < 128 && blocks > 1 << length {
+        // The counter would be reused.
+        return Err(Error::Operation);
+    }
+
+    let mut counter_bytes = [0; 16];
+    counter_bytes.copy_from_slice(counter);
+    let mut block = u128::from_be_bytes(counter_bytes);
+    let mut output = Vec::with_capacity(data.len());
+    let mut remaining = data;
+    while !remaining.is_empty() {
+        let blocks_before_wrapping = (mask - (block & mask)).checked_add(1);
+        let size = blocks_before_wrapping
+            .and_then(|blocks| blocks.checked_mul(16))
+            .map_or(remaining.len(), |size| {
+                size.min(remaining.len() as u128) as usize
+            });
+        let (chunk, rest) = remaining.split_at(size);
+        output.extend(
+            symm::encrypt(cipher, secret, Some(&block.to_be_bytes()), chunk)
+                .map_err(|_| Error::Operation)?,
+        );
+        block &= !mask;
+        remaining = rest;
+    }
+    Ok(output)
+}
+
+fn rsa_hash(key: &Key) -> Fallible<Hash> {
+    match key.algorithm {
+        KeyAlgorithm::RsaHashed { hash, .. } => Ok(hash),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+/// The salt length of RSA-PSS signatures, which OpenSSL takes as an `i32`.
+/// Longer salts can't fit in a signature anyway.
+fn pss_salt_length(salt_length: u32) -> Fallible<RsaPssSaltlen> {
+    i32::try_from(salt_length)
+        .map(RsaPssSaltlen::custom)
+        .map_err(|_| Error::Operation)
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-sign
+pub fn sign(algorithm: &NormalizedAlgorithm, key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    key.check(algorithm, KeyUsage::Sign)?;
+    match algorithm.params {
+        Params::None if algorithm.name == AlgorithmName::Hmac => hmac(key, data),
+        Params::RsaPss { salt_length } => {
+            let private = key.private()?;
+            let hash = rsa_hash(key)?.message_digest();
+            let salt_length = pss_salt_length(salt_length)?;
+            let mut signer = Signer::new(hash, private).map_err(|_| Error::Operation)?;
+            signer
+                .set_rsa_padding(Padding::PKCS1_PSS)
+                .and_then(|_| signer.set_rsa_pss_saltlen(salt_length))
+                .and_then(|_| signer.set_rsa_mgf1_md(hash))
+                .and_then(|_| signer.update(data))
+                .and_then(|_| signer.sign_to_vec())
+                .map_err(|_| Error::Operation)
+        },
+        Params::Ecdsa { hash } => {
+            let ec_key = key.private()?.ec_key().map_err(|_| Error::Operation)?;
+            let size = ec_coordinate_size(key)?;
+            let digest = hash::hash(hash.message_digest(), data).map_err(|_| Error::Operation)?;
+            let signature = EcdsaSig::sign(&digest, &ec_key).map_err(|_| Error::Operation)?;
+            let mut bytes = padded_bytes(signature.r(), size);
+            bytes.extend(padded_bytes(signature.s(), size));
+            Ok(bytes)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-verify
+pub fn verify(
+    algorithm: &NormalizedAlgorithm,
+    key: &Key,
+    signature: &[u8],
+    data: &[u8],
+) -> Fallible<bool> {
+    key.check(algorithm, KeyUsage::Verify)?;
+    match algorithm.params {
+        Params::None if algorithm.name == AlgorithmName::Hmac => {
+            let expected = hmac(key, data)?;
+            Ok(expected.len() == signature.len() && memcmp::eq(&expected, signature))
+        },
+        Params::RsaPss { salt_length } => {
+            let public = key.public()?;
+            let hash = rsa_hash(key)?.message_digest();
+            let salt_length = pss_salt_length(salt_length)?;
+            let mut verifier = Verifier::new(hash, public).map_err(|_| Error::Operation)?;
+            verifier
+                .set_rsa_padding(Padding::PKCS1_PSS)
+                .and_then(|_| verifier.set_rsa_pss_saltlen(salt_length))
+                .and_then(|_| verifier.set_rsa_mgf1_md(hash))
+                .and_then(|_| verifier.update(data))
+                .map_err(|_| Error::Operation)?;
+            // A malformed signature is just an invalid one.
+            Ok(verifier.verify(signature).unwrap_or(false))
+        },
+        Params::Ecdsa { hash } => {
+            let ec_key = key.public()?.ec_key().map_err(|_| Error::Operation)?;
+            let size = ec_coordinate_size(key)?;
+            if signature.len() != 2 * size {
+                return Ok(false);
+            }
+            let (r, s) = signature.split_at(size);
+            let signature = BigNum::from_slice(r)
+                .and_then(|r| Ok((r, BigNum::from_slice(s)?)))
+                .and_then(|(r, s)| EcdsaSig::from_private_components(r, s))
+                .map_err(|_| Error::Operation)?;
+            let digest = hash::hash(hash.message_digest(), data).map_err(|_| Error::Operation)?;
+            Ok(signature.verify(&digest, &ec_key).unwrap_or(false))
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn ec_coordinate_size(key: &Key) -> Fallible<usize> {
+    match key.algorithm {
+        KeyAlgorithm::Ec { named_curve, .. } => Ok(named_curve.coordinate_size()),
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn hmac(key: &Key, data: &[u8]) -> Fallible<Vec<u8>> {
+    let hash = match key.algorithm {
+        KeyAlgorithm::Hmac { hash, .. } => hash,
+        _ => return Err(Error::InvalidAccess),
+    };
+    hmac_with_secret(hash, key.secret()?, data)
+}
+
+fn hmac_with_secret(hash: Hash, secret: &[u8], data: &[u8]) -> Fallible<Vec<u8>> {
+    let key = PKey::hmac(secret).map_err(|_| Error::Operation)?;
+    let mut signer = Signer::new(hash.message_digest(), &key).map_err(|_| Error::Operation)?;
+    signer
+        .update(data)
+        .and_then(|_| signer.sign_to_vec())
+        .map_err(|_| Error::Operation)
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveBits
+pub fn derive_bits(
+    algorithm: &NormalizedAlgorithm,
+    key: &Key,
+    length: Option<u32>,
+) -> Fallible<Vec<u8>> {
+    key.check(algorithm, KeyUsage::DeriveBits)?;
+    derive(algorithm, key, length)
+}
+
+/// https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveKey
+pub fn derive_key(
+    algorithm: &NormalizedAlgorithm,
+    key: &Key,
+    derived_key_algorithm: &NormalizedAlgorithm,
+    length_algorithm: &NormalizedAlgorithm,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<Key> {
+    key.check(algorithm, KeyUsage::DeriveKey)?;
+    let length = key_length(length_algorithm)?;
+    let secret = derive(algorithm, key, length)?;
+    import_key(
+        KeyFormat::Raw,
+        KeyMaterial::Bytes(secret),
+        derived_key_algorithm,
+        extractable,
+        usages,
+    )
+}
+
+/// The length of the keys of `algorithm`, in bits, or `None` if it is
+/// variable.
+/// <https://w3c.github.io/webcrypto/#dfn-get-key-length>
+fn key_length(algorithm: &NormalizedAlgorithm) -> Fallible<Option<u32>> {
+    match algorithm.params {
+        Params::AesKey { length } => match length {
+            128 | 192 | 256 => Ok(Some(length as u32)),
+            _ => Err(Error::Operation),
+        },
+        Params::HmacKey { hash, length } => match length.unwrap_or(hash.block_size()) {
+            0 => Err(Error::Type("The length of HMAC keys can't be 0".to_owned())),
+            length => Ok(Some(length)),
+        },
+        Params::None => Ok(None),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+fn derive(algorithm: &NormalizedAlgorithm, key: &Key, length: Option<u32>) -> Fallible<Vec<u8>> {
+    match algorithm.params {
+        Params::Ecdh { ref public } => {
+            let private = key.private()?;
+            let peer = public.public()?;
+            if public.algorithm.name() != AlgorithmName::Ecdh {
+                return Err(Error::InvalidAccess);
+            }
+            match (&public.algorithm, &key.algorithm) {
+                (
+                    &KeyAlgorithm::Ec {
+                        named_curve: public_curve,
+                        ..
+                    },
+                    &KeyAlgorithm::Ec { named_curve, .. },
+                ) if public_curve == named_curve => {},
+                _ => return Err(Error::InvalidAccess),
+            }
+            let mut deriver = Deriver::new(private).map_err(|_| Error::Operation)?;
+            deriver.set_peer(peer).map_err(|_| Error::Operation)?;
+            let mut secret = vec![0; deriver.len().map_err(|_| Error::Operation)?];
+            let size = deriver.derive(&mut secret).map_err(|_| Error::Operation)?;
+            secret.truncate(size);
+            match length {
+                None => Ok(secret),
+                Some(length) if length as usize > secret.len() * 8 => Err(Error::Operation),
+                Some(length) => {
+                    secret.truncate((length as usize + 7) / 8);
+                    if length % 8 != 0 {
+                        let last = secret.len() - 1;
+                        secret[last] &= 0xff << (8 - length % 8);
+                    }
+                    Ok(secret)
+                },
+            }
+        },
+        Params::Pbkdf2 {
+            ref salt,
+            iterations,
+            hash,
+        } => {
+            let length = match length {
+                Some(length) if length != 0 && length % 8 == 0 => length as usize / 8,
+                _ => return Err(Error::Operation),
+            };
+            if iterations == 0 {
+                return Err(Error::Operation);
+            }
+            let mut bits = vec![0; length];
+            pbkdf2_hmac(
+                key.secret()?,
+                salt,
+                iterations as usize,
+                hash.message_digest(),
+                &mut bits,
+            )
+            .map_err(|_| Error::Operation)?;
+            Ok(bits)
+        },
+        Params::Hkdf {
+            hash,
+            ref salt,
+            ref info,
+        } => {
+            let length = match length {
+                Some(length) if length % 8 == 0 => length as usize / 8,
+                _ => return Err(Error::Operation),
+            };
+            hkdf(hash, key.secret()?, salt, info, length)
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// https://tools.ietf.org/html/rfc5869
+fn hkdf(hash: Hash, secret: &[u8], salt: &[u8], info: &[u8], length: usize) -> Fallible<Vec<u8>> {
+    let size = hash.output_size();
+    if length > 255 * size {
+        return Err(Error::Operation);
+    }
+    // An empty salt is the same as a salt of zeros to HMAC, which openssl
+    // doesn't take empty keys for.
+    let zeros = vec![0; size];
+    let salt = if salt.is_empty() { &zeros } else { salt };
+    let pseudorandom_key = hmac_with_secret(hash, salt, secret)?;
+
+    let mut output = Vec::with_capacity(length + size);
+    let mut block = vec![];
+    let mut counter = 1u8;
+    while output.len() < length {
+        let mut input = block;
+        input.extend(info);
+        input.push(counter);
+        block = hmac_with_secret(hash, &pseudorandom_key, &input)?;
+        output.extend(&block);
+        counter = counter.wrapping_add(1);
+    }
+    output.truncate(length);
+    Ok(output)
+}
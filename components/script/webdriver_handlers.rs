@@ -21,6 +21,7 @@ use crate::dom::bindings::conversions::{
 };
 use crate::dom::bindings::error::{throw_dom_exception, Error};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
@@ -402,6 +403,39 @@ fn get_element_in_view_center_point(element: &Element) -> Option<Point2D<i64>> {
         })
 }
 
+/// Hit tests the in-view center point of an element, the same way pointer
+/// events dispatched there would be.
+///
+/// <https://w3c.github.io/webdriver/#element-click>
+fn check_element_is_in_view_and_not_obscured(element: &Element) -> Result<(), ErrorStatus> {
+    // https://w3c.github.io/webdriver/#dfn-in-view
+    let center_point =
+        get_element_in_view_center_point(element).ok_or(ErrorStatus::ElementNotInteractable)?;
+    let paint_tree = window_from_node(element.upcast::<Node>())
+        .Document()
+        .ElementsFromPoint(
+            Finite::wrap(center_point.x as f64),
+            Finite::wrap(center_point.y as f64),
+        );
+    // Step 6
+    if !paint_tree.iter().any(|hit| &**hit == element) {
+        return Err(ErrorStatus::ElementNotInteractable);
+    }
+
+    // Step 7
+    // https://w3c.github.io/webdriver/#dfn-obscuring
+    match paint_tree.first() {
+        Some(topmost)
+            if element
+                .upcast::<Node>()
+                .is_inclusive_ancestor_of(topmost.upcast()) =>
+        {
+            Ok(())
+        },
+        _ => Err(ErrorStatus::ElementClickIntercepted),
+    }
+}
+
 pub fn handle_get_element_in_view_center_point(
     documents: &Documents,
     pipeline: PipelineId,
@@ -1078,12 +1112,6 @@ pub fn handle_element_click(
                 // Step 5
                 // TODO: scroll into view
 
-                // Step 6
-                // TODO: return error if still not in view
-
-                // Step 7
-                // TODO: return error if obscured
-
                 // Step 8
                 match node.downcast::<HTMLOptionElement>() {
                     Some(option_element) => {
@@ -1105,6 +1133,11 @@ pub fn handle_element_click(
                             },
                         };
 
+                        // Steps 6 - 7
+                        if let Some(container) = parent_node.downcast::<Element>() {
+                            check_element_is_in_view_and_not_obscured(container)?;
+                        }
+
                         // Steps 8.2 - 8.4
                         let event_target = parent_node.upcast::<EventTarget>();
                         event_target.fire_event(atom!("mouseover"));
@@ -1147,7 +1180,13 @@ pub fn handle_element_click(
 
                         Ok(None)
                     },
-                    None => Ok(Some(node.unique_id())),
+                    None => {
+                        // Steps 6 - 7
+                        check_element_is_in_view_and_not_obscured(
+                            node.downcast::<Element>().unwrap(),
+                        )?;
+                        Ok(Some(node.unique_id()))
+                    },
                 }
             }),
         )
@@ -0,0 +1,174 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The named properties object of the `Window` interface, which sits between
+//! `Window.prototype` and `EventTarget.prototype` and exposes the child
+//! browsing contexts and the named elements of the document of the window.
+//! <https://html.spec.whatwg.org/multipage/#named-access-on-the-window-object>
+
+use crate::dom::bindings::conversions::{jsid_to_string, ToJSValConvertible};
+use crate::dom::bindings::proxyhandler::{
+    fill_property_descriptor, get_prototype_if_ordinary, is_extensible, prevent_extensions,
+};
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use js::glue::{CreateProxyHandler, ProxyTraps};
+use js::jsapi::Handle as RawHandle;
+use js::jsapi::HandleId as RawHandleId;
+use js::jsapi::HandleObject as RawHandleObject;
+use js::jsapi::MutableHandle as RawMutableHandle;
+use js::jsapi::{jsid, AutoIdVector, GetStaticPrototype, JSContext, JSErrNum};
+use js::jsapi::{JS_AtomizeAndPinString, JS_HasOwnPropertyById, UndefinedHandleValue};
+use js::jsapi::{ObjectOpResult, PropertyDescriptor};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::{AppendToAutoIdVector, NewProxyObject, RUST_INTERNED_STRING_TO_JSID};
+use js::rust::{Handle, HandleObject, MutableHandle, MutableHandleObject};
+use std::ffi::CString;
+use std::ptr;
+
+struct NamedPropertiesHandler(*const libc::c_void);
+unsafe impl Sync for NamedPropertiesHandler {}
+
+lazy_static! {
+    static ref HANDLER: NamedPropertiesHandler =
+        NamedPropertiesHandler(unsafe { CreateProxyHandler(&PROXY_TRAPS, ptr::null()) });
+}
+
+/// Creates the named properties object of a window, whose prototype is
+/// `proto`.
+pub unsafe fn create(cx: *mut JSContext, proto: HandleObject, mut object: MutableHandleObject) {
+    object.set(NewProxyObject(
+        cx,
+        HANDLER.0,
+        Handle::from_raw(UndefinedHandleValue),
+        proto.get(),
+    ));
+    assert!(!object.is_null());
+}
+
+/// The window of the realm of the named properties object.
+unsafe fn window_from_proxy(proxy: RawHandleObject) -> DomRoot<Window> {
+    DomRoot::downcast::<Window>(GlobalScope::from_object(proxy.get()))
+        .expect("The named properties object only exists in windows")
+}
+
+/// <https://heycam.github.io/webidl/#named-property-visibility>
+unsafe fn is_visible(cx: *mut JSContext, window: &Window, id: RawHandleId) -> bool {
+    // Step 2. The named properties object itself has no own properties, so
+    // the own properties of the window and those of its prototype are the
+    // only ones that can shadow it.
+    let window_object = window.reflector().get_jsobject();
+    rooted!(in(cx) let window_proto = GetStaticPrototype(window_object.get()));
+    for object in &[window_object.get(), window_proto.get()] {
+        rooted!(in(cx) let object = *object);
+        let mut found = false;
+        if !JS_HasOwnPropertyById(cx, object.handle().into(), id, &mut found) || found {
+            return false;
+        }
+    }
+    true
+}
+
+#[allow(non_snake_case)]
+unsafe extern "C" fn getOwnPropertyDescriptor(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    mut desc: RawMutableHandle<PropertyDescriptor>,
+) -> bool {
+    desc.obj = ptr::null_mut();
+    let name = match jsid_to_string(cx, Handle::from_raw(id)) {
+        Some(name) => name,
+        None => return true,
+    };
+    let window = window_from_proxy(proxy);
+    if !is_visible(cx, &window, id) {
+        return true;
+    }
+    if let Some(object) = window.named_property(name) {
+        rooted!(in(cx) let mut value = UndefinedValue());
+        object.to_jsval(cx, value.handle_mut());
+        desc.value = value.get();
+        // The named properties are writable and configurable, but not
+        // enumerable because of [LegacyUnenumerableNamedProperties].
+        fill_property_descriptor(MutableHandle::from_raw(desc), proxy.get(), 0);
+    }
+    true
+}
+
+#[allow(non_snake_case)]
+unsafe extern "C" fn defineProperty(
+    _cx: *mut JSContext,
+    _proxy: RawHandleObject,
+    _id: RawHandleId,
+    _desc: RawHandle<PropertyDescriptor>,
+    result: *mut ObjectOpResult,
+) -> bool {
+    (*result).code_ = JSErrNum::JSMSG_CANT_DEFINE_WINDOW_NAMED_PROPERTY as ::libc::uintptr_t;
+    true
+}
+
+unsafe extern "C" fn delete(
+    _cx: *mut JSContext,
+    _proxy: RawHandleObject,
+    _id: RawHandleId,
+    result: *mut ObjectOpResult,
+) -> bool {
+    (*result).code_ = JSErrNum::JSMSG_CANT_DELETE_WINDOW_NAMED_PROPERTY as ::libc::uintptr_t;
+    true
+}
+
+unsafe extern "C" fn own_property_keys(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    props: *mut AutoIdVector,
+) -> bool {
+    let window = window_from_proxy(proxy);
+    for name in window.supported_property_names() {
+        let cstring = CString::new(name).unwrap();
+        rooted!(in(cx) let jsstring = JS_AtomizeAndPinString(cx, cstring.as_ptr()));
+        rooted!(in(cx) let mut id: jsid);
+        RUST_INTERNED_STRING_TO_JSID(cx, jsstring.get(), id.handle_mut());
+        AppendToAutoIdVector(props, id.handle());
+    }
+    true
+}
+
+#[allow(non_snake_case)]
+unsafe extern "C" fn className(_cx: *mut JSContext, _proxy: RawHandleObject) -> *const i8 {
+    b"WindowProperties\0" as *const u8 as *const i8
+}
+
+static PROXY_TRAPS: ProxyTraps = ProxyTraps {
+    enter: None,
+    getOwnPropertyDescriptor: Some(getOwnPropertyDescriptor),
+    defineProperty: Some(defineProperty),
+    ownPropertyKeys: Some(own_property_keys),
+    delete_: Some(delete),
+    enumerate: None,
+    getPrototypeIfOrdinary: Some(get_prototype_if_ordinary),
+    preventExtensions: Some(prevent_extensions),
+    isExtensible: Some(is_extensible),
+    has: None,
+    get: None,
+    set: None,
+    call: None,
+    construct: None,
+    hasOwn: None,
+    getOwnEnumerablePropertyKeys: None,
+    nativeCall: None,
+    hasInstance: None,
+    objectClassIs: None,
+    className: Some(className),
+    fun_toString: None,
+    boxedValue_unbox: None,
+    defaultValue: None,
+    trace: None,
+    finalize: None,
+    objectMoved: None,
+    isCallable: None,
+    isConstructor: None,
+};
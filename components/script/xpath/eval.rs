@@ -0,0 +1,581 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::functions::{self, number_to_string, string_to_number};
+use super::parser::{Axis, BinaryOperator, Expr, NodeTest, PathStart, Step};
+use super::{Environment, Error};
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeConstants;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::processinginstruction::ProcessingInstruction;
+use std::cmp::Ordering;
+
+/// The result of evaluating an expression.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#section-Introduction>
+#[derive(Clone)]
+pub enum Value {
+    /// Nodes in document order, without duplicates.
+    NodeSet(Vec<DomRoot<Node>>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-boolean>
+    pub fn boolean(&self) -> bool {
+        match *self {
+            Value::NodeSet(ref nodes) => !nodes.is_empty(),
+            Value::Boolean(value) => value,
+            Value::Number(value) => value != 0. && !value.is_nan(),
+            Value::String(ref value) => !value.is_empty(),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-number>
+    pub fn number(&self) -> f64 {
+        match *self {
+            Value::NodeSet(_) => string_to_number(&self.string()),
+            Value::Boolean(value) => {
+                if value {
+                    1.
+                } else {
+                    0.
+                }
+            },
+            Value::Number(value) => value,
+            Value::String(ref value) => string_to_number(value),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-string>
+    pub fn string(&self) -> String {
+        match *self {
+            Value::NodeSet(ref nodes) => nodes.first().map_or(String::new(), |n| string_value(n)),
+            Value::Boolean(value) => value.to_string(),
+            Value::Number(value) => number_to_string(value),
+            Value::String(ref value) => value.clone(),
+        }
+    }
+
+    pub fn into_node_set(self) -> Result<Vec<DomRoot<Node>>, Error> {
+        match self {
+            Value::NodeSet(nodes) => Ok(nodes),
+            _ => Err(Error::Type(String::from("expected a node-set"))),
+        }
+    }
+}
+
+/// The context in which an expression is evaluated.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#section-Introduction>
+pub struct EvaluationContext<'a> {
+    pub node: DomRoot<Node>,
+    /// The 1-based position of `node` in the node-set being processed.
+    pub position: usize,
+    pub size: usize,
+    pub environment: &'a dyn Environment,
+}
+
+impl<'a> EvaluationContext<'a> {
+    pub fn new(node: &Node, environment: &'a dyn Environment) -> EvaluationContext<'a> {
+        EvaluationContext {
+            node: DomRoot::from_ref(node),
+            position: 1,
+            size: 1,
+            environment,
+        }
+    }
+
+    fn with_node(
+        &self,
+        node: DomRoot<Node>,
+        position: usize,
+        size: usize,
+    ) -> EvaluationContext<'a> {
+        EvaluationContext {
+            node,
+            position,
+            size,
+            environment: self.environment,
+        }
+    }
+}
+
+/// Evaluates `expr` in `context`.
+pub fn evaluate(expr: &Expr, context: &EvaluationContext) -> Result<Value, Error> {
+    match *expr {
+        Expr::Binary(ref left, op, ref right) => evaluate_binary(left, op, right, context),
+        Expr::Negate(ref operand) => Ok(Value::Number(-evaluate(operand, context)?.number())),
+        Expr::Path(ref start, ref steps) => {
+            let mut nodes = match *start {
+                PathStart::Root => vec![root_of(&context.node)],
+                PathStart::ContextNode => vec![context.node.clone()],
+                PathStart::Filter(ref filter) => evaluate(filter, context)?.into_node_set()?,
+            };
+            for step in steps {
+                nodes = evaluate_step(step, &nodes, context)?;
+            }
+            Ok(Value::NodeSet(nodes))
+        },
+        Expr::Filter(ref primary, ref predicates) => {
+            let nodes = evaluate(primary, context)?.into_node_set()?;
+            let nodes = apply_predicates(predicates, nodes, context)?;
+            Ok(Value::NodeSet(nodes))
+        },
+        Expr::Literal(ref literal) => Ok(Value::String(literal.clone())),
+        Expr::Number(number) => Ok(Value::Number(number)),
+        Expr::Variable(ref name) => context
+            .environment
+            .variable(name)
+            .ok_or_else(|| Error::InvalidExpression(format!("unknown variable {}", name.local))),
+        Expr::Function(ref name, ref arguments) => {
+            if name.prefix.is_none() {
+                if let Some(result) = functions::call(&name.local, arguments, context) {
+                    return result;
+                }
+            }
+            context
+                .environment
+                .call_function(name, arguments, context)
+                .unwrap_or_else(|| {
+                    Err(Error::InvalidExpression(format!(
+                        "unknown function {}",
+                        name.local
+                    )))
+                })
+        },
+    }
+}
+
+fn evaluate_binary(
+    left: &Expr,
+    op: BinaryOperator,
+    right: &Expr,
+    context: &EvaluationContext,
+) -> Result<Value, Error> {
+    // `and` and `or` don't evaluate their right operand when the left one
+    // decides the result.
+    match op {
+        BinaryOperator::Or => {
+            return Ok(Value::Boolean(
+                evaluate(left, context)?.boolean() || evaluate(right, context)?.boolean(),
+            ));
+        },
+        BinaryOperator::And => {
+            return Ok(Value::Boolean(
+                evaluate(left, context)?.boolean() && evaluate(right, context)?.boolean(),
+            ));
+        },
+        _ => {},
+    }
+
+    let left = evaluate(left, context)?;
+    let right = evaluate(right, context)?;
+    Ok(match op {
+        BinaryOperator::Equal |
+        BinaryOperator::NotEqual |
+        BinaryOperator::Less |
+        BinaryOperator::LessOrEqual |
+        BinaryOperator::Greater |
+        BinaryOperator::GreaterOrEqual => Value::Boolean(compare(&left, op, &right)),
+        BinaryOperator::Add => Value::Number(left.number() + right.number()),
+        BinaryOperator::Subtract => Value::Number(left.number() - right.number()),
+        BinaryOperator::Multiply => Value::Number(left.number() * right.number()),
+        BinaryOperator::Divide => Value::Number(left.number() / right.number()),
+        // Like JavaScript's `%`, which is what XPath asks for.
+        BinaryOperator::Modulo => Value::Number(left.number() % right.number()),
+        BinaryOperator::Union => {
+            let mut nodes = left.into_node_set()?;
+            nodes.extend(right.into_node_set()?);
+            sort_in_document_order(&mut nodes);
+            Value::NodeSet(nodes)
+        },
+        BinaryOperator::Or | BinaryOperator::And => unreachable!(),
+    })
+}
+
+/// Compares two values with one of the equality or relational operators.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#booleans>
+pub fn compare(left: &Value, op: BinaryOperator, right: &Value) -> bool {
+    match (left, right) {
+        (&Value::NodeSet(ref left), &Value::NodeSet(ref right)) => left.iter().any(|l| {
+            let l = Value::String(string_value(l));
+            right
+                .iter()
+                .any(|r| compare_atomic(&l, op, &Value::String(string_value(r))))
+        }),
+        (&Value::NodeSet(ref nodes), other) => compare_node_set(nodes, op, other, false),
+        (other, &Value::NodeSet(ref nodes)) => compare_node_set(nodes, op, other, true),
+        _ => compare_atomic(left, op, right),
+    }
+}
+
+/// Compares each node of `nodes` with `other`, which is on the right of the
+/// operator unless `swapped` is set.
+fn compare_node_set(
+    nodes: &[DomRoot<Node>],
+    op: BinaryOperator,
+    other: &Value,
+    swapped: bool,
+) -> bool {
+    if let Value::Boolean(_) = *other {
+        let value = Value::Boolean(!nodes.is_empty());
+        return if swapped {
+            compare_atomic(other, op, &value)
+        } else {
+            compare_atomic(&value, op, other)
+        };
+    }
+    nodes.iter().any(|node| {
+        let string = string_value(node);
+        let value = match *other {
+            Value::Number(_) => Value::Number(string_to_number(&string)),
+            _ => Value::String(string),
+        };
+        if swapped {
+            compare_atomic(other, op, &value)
+        } else {
+            compare_atomic(&value, op, other)
+        }
+    })
+}
+
+fn compare_atomic(left: &Value, op: BinaryOperator, right: &Value) -> bool {
+    match op {
+        BinaryOperator::Equal | BinaryOperator::NotEqual => {
+            let equal = match (left, right) {
+                (&Value::Boolean(_), _) | (_, &Value::Boolean(_)) => {
+                    left.boolean() == right.boolean()
+                },
+                (&Value::Number(_), _) | (_, &Value::Number(_)) => left.number() == right.number(),
+                _ => left.string() == right.string(),
+            };
+            equal == (op == BinaryOperator::Equal)
+        },
+        BinaryOperator::Less => left.number() < right.number(),
+        BinaryOperator::LessOrEqual => left.number() <= right.number(),
+        BinaryOperator::Greater => left.number() > right.number(),
+        BinaryOperator::GreaterOrEqual => left.number() >= right.number(),
+        _ => unreachable!(),
+    }
+}
+
+fn evaluate_step(
+    step: &Step,
+    nodes: &[DomRoot<Node>],
+    context: &EvaluationContext,
+) -> Result<Vec<DomRoot<Node>>, Error> {
+    let mut result = vec![];
+    for node in nodes {
+        let mut selected = vec![];
+        for candidate in axis_nodes(step.axis, node) {
+            if matches_node_test(&step.test, step.axis, &candidate, context)? {
+                selected.push(candidate);
+            }
+        }
+        result.extend(apply_predicates(&step.predicates, selected, context)?);
+    }
+    if nodes.len() > 1 || step.axis.is_reverse() {
+        sort_in_document_order(&mut result);
+    }
+    Ok(result)
+}
+
+/// Filters `nodes`, which are in proximity order, through `predicates`.
+fn apply_predicates(
+    predicates: &[Expr],
+    mut nodes: Vec<DomRoot<Node>>,
+    context: &EvaluationContext,
+) -> Result<Vec<DomRoot<Node>>, Error> {
+    for predicate in predicates {
+        let size = nodes.len();
+        let mut kept = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let predicate_context = context.with_node(node.clone(), index + 1, size);
+            let keep = match evaluate(predicate, &predicate_context)? {
+                Value::Number(position) => position == (index + 1) as f64,
+                value => value.boolean(),
+            };
+            if keep {
+                kept.push(node);
+            }
+        }
+        nodes = kept;
+    }
+    Ok(nodes)
+}
+
+/// The nodes on `axis` from `node`, in proximity order.
+fn axis_nodes(axis: Axis, node: &Node) -> Vec<DomRoot<Node>> {
+    let is_attr = node.is::<Attr>();
+    match axis {
+        Axis::Child => {
+            if is_attr {
+                return vec![];
+            }
+            node.children().filter(|n| in_data_model(n)).collect()
+        },
+        Axis::Descendant | Axis::DescendantOrSelf => {
+            let mut nodes = vec![];
+            if axis == Axis::DescendantOrSelf {
+                nodes.push(DomRoot::from_ref(node));
+            }
+            if !is_attr {
+                nodes.extend(
+                    node.traverse_preorder(ShadowIncluding::No)
+                        .skip(1)
+                        .filter(|n| in_data_model(n)),
+                );
+            }
+            nodes
+        },
+        Axis::Parent => parent(node).into_iter().collect(),
+        Axis::Ancestor | Axis::AncestorOrSelf => {
+            let mut nodes = vec![];
+            if axis == Axis::AncestorOrSelf {
+                nodes.push(DomRoot::from_ref(node));
+            }
+            let mut current = parent(node);
+            while let Some(ancestor) = current {
+                current = parent(&ancestor);
+                nodes.push(ancestor);
+            }
+            nodes
+        },
+        Axis::FollowingSibling => {
+            if is_attr {
+                return vec![];
+            }
+            node.following_siblings()
+                .filter(|n| in_data_model(n))
+                .collect()
+        },
+        Axis::PrecedingSibling => {
+            if is_attr {
+                return vec![];
+            }
+            node.preceding_siblings()
+                .filter(|n| in_data_model(n))
+                .collect()
+        },
+        Axis::Following => {
+            let mut nodes = vec![];
+            let mut current = match node.downcast::<Attr>() {
+                // Everything inside the owner element follows its attributes.
+                Some(attr) => match attr.GetOwnerElement() {
+                    Some(owner) => {
+                        let owner = DomRoot::upcast::<Node>(owner);
+                        nodes.extend(
+                            owner
+                                .traverse_preorder(ShadowIncluding::No)
+                                .skip(1)
+                                .filter(|n| in_data_model(n)),
+                        );
+                        Some(owner)
+                    },
+                    None => None,
+                },
+                None => Some(DomRoot::from_ref(node)),
+            };
+            while let Some(ancestor) = current {
+                for sibling in ancestor.following_siblings() {
+                    nodes.extend(
+                        sibling
+                            .traverse_preorder(ShadowIncluding::No)
+                            .filter(|n| in_data_model(n)),
+                    );
+                }
+                current = ancestor.GetParentNode();
+            }
+            nodes
+        },
+        Axis::Preceding => {
+            let start = match node.downcast::<Attr>() {
+                Some(attr) => match attr.GetOwnerElement() {
+                    Some(owner) => DomRoot::upcast::<Node>(owner),
+                    None => return vec![],
+                },
+                None => DomRoot::from_ref(node),
+            };
+            let ancestors: Vec<_> = start.inclusive_ancestors(ShadowIncluding::No).collect();
+            let root = ancestors.last().unwrap().clone();
+            let mut nodes = vec![];
+            for candidate in root.traverse_preorder(ShadowIncluding::No) {
+                if candidate == start {
+                    break;
+                }
+                if in_data_model(&candidate) && !ancestors.contains(&candidate) {
+                    nodes.push(candidate);
+                }
+            }
+            nodes.reverse();
+            nodes
+        },
+        Axis::Attribute => match node.downcast::<Element>() {
+            Some(element) => element
+                .attrs()
+                .iter()
+                .filter(|attr| *attr.namespace() != ns!(xmlns))
+                .map(|attr| DomRoot::from_ref(attr.upcast::<Node>()))
+                .collect(),
+            None => vec![],
+        },
+        // Namespace nodes are not supported, so this axis is always empty.
+        Axis::Namespace => vec![],
+        Axis::SelfAxis => vec![DomRoot::from_ref(node)],
+    }
+}
+
+/// Doctypes are not part of the XPath data model.
+fn in_data_model(node: &Node) -> bool {
+    node.type_id() != NodeTypeId::DocumentType
+}
+
+/// The parent of `node` in the XPath data model, where the parent of an
+/// attribute is its owner element.
+pub fn parent(node: &Node) -> Option<DomRoot<Node>> {
+    match node.downcast::<Attr>() {
+        Some(attr) => attr.GetOwnerElement().map(DomRoot::upcast),
+        None => node.GetParentNode(),
+    }
+}
+
+/// The root node of the tree `node` is in.
+fn root_of(node: &Node) -> DomRoot<Node> {
+    let mut root = DomRoot::from_ref(node);
+    while let Some(parent) = parent(&root) {
+        root = parent;
+    }
+    root
+}
+
+fn matches_node_test(
+    test: &NodeTest,
+    axis: Axis,
+    node: &Node,
+    context: &EvaluationContext,
+) -> Result<bool, Error> {
+    // The principal node type of the attribute axis is attribute, and
+    // element for every other one this implementation supports.
+    let is_principal = |node: &Node| {
+        if axis == Axis::Attribute {
+            node.is::<Attr>()
+        } else {
+            node.is::<Element>()
+        }
+    };
+    Ok(match *test {
+        NodeTest::Node => true,
+        NodeTest::Text => match node.type_id() {
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(_)) => true,
+            _ => false,
+        },
+        NodeTest::Comment => {
+            node.type_id() == NodeTypeId::CharacterData(CharacterDataTypeId::Comment)
+        },
+        NodeTest::ProcessingInstruction(ref target) => {
+            match node.downcast::<ProcessingInstruction>() {
+                Some(pi) => target
+                    .as_ref()
+                    .map_or(true, |target| **pi.target() == **target),
+                None => false,
+            }
+        },
+        NodeTest::Wildcard => is_principal(node),
+        NodeTest::NamespaceWildcard(ref prefix) => {
+            if !is_principal(node) {
+                return Ok(false);
+            }
+            let namespace = resolve_prefix(prefix, context)?;
+            *node_namespace(node) == *namespace
+        },
+        NodeTest::Name(ref name) => {
+            if !is_principal(node) {
+                return Ok(false);
+            }
+            match name.prefix {
+                Some(ref prefix) => {
+                    let namespace = resolve_prefix(prefix, context)?;
+                    *node_namespace(node) == *namespace && node_local_name(node) == name.local
+                },
+                None => {
+                    // https://html.spec.whatwg.org/multipage/#interactions-with-xpath-and-xslt
+                    match node.downcast::<Element>() {
+                        Some(element) if element.html_element_in_html_document() => {
+                            **element.local_name() == *name.local.to_ascii_lowercase()
+                        },
+                        _ => *node_namespace(node) == ns!() && node_local_name(node) == name.local,
+                    }
+                },
+            }
+        },
+    })
+}
+
+fn resolve_prefix(prefix: &str, context: &EvaluationContext) -> Result<String, Error> {
+    if prefix == "xml" {
+        return Ok(String::from(&*ns!(xml)));
+    }
+    context
+        .environment
+        .resolve_namespace_prefix(prefix)
+        .ok_or_else(|| Error::Namespace(format!("unknown namespace prefix {}", prefix)))
+}
+
+fn node_namespace(node: &Node) -> String {
+    if let Some(element) = node.downcast::<Element>() {
+        return String::from(&**element.namespace());
+    }
+    if let Some(attr) = node.downcast::<Attr>() {
+        return String::from(&**attr.namespace());
+    }
+    String::new()
+}
+
+fn node_local_name(node: &Node) -> String {
+    if let Some(element) = node.downcast::<Element>() {
+        return String::from(&**element.local_name());
+    }
+    if let Some(attr) = node.downcast::<Attr>() {
+        return String::from(&**attr.local_name());
+    }
+    if let Some(pi) = node.downcast::<ProcessingInstruction>() {
+        return String::from(&**pi.target());
+    }
+    String::new()
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#dt-string-value>
+pub fn string_value(node: &Node) -> String {
+    match node.type_id() {
+        NodeTypeId::Attr => String::from(node.downcast::<Attr>().unwrap().Value()),
+        NodeTypeId::CharacterData(_) => {
+            String::from(&*node.downcast::<CharacterData>().unwrap().data())
+        },
+        NodeTypeId::DocumentType => String::new(),
+        _ => String::from(node.descendant_text_content()),
+    }
+}
+
+/// Sorts `nodes` in document order and removes duplicates.
+pub fn sort_in_document_order(nodes: &mut Vec<DomRoot<Node>>) {
+    nodes.sort_by(|a, b| {
+        if a == b {
+            return Ordering::Equal;
+        }
+        let position = a.CompareDocumentPosition(b);
+        if position & NodeConstants::DOCUMENT_POSITION_FOLLOWING != 0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    });
+    nodes.dedup();
+}
@@ -0,0 +1,303 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The core function library.
+//!
+//! <https://www.w3.org/TR/1999/REC-xpath-19991116/#corelib>
+
+use super::eval::{evaluate, sort_in_document_order, string_value, EvaluationContext, Value};
+use super::parser::Expr;
+use super::Error;
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::processinginstruction::ProcessingInstruction;
+use std::f64;
+
+/// Calls the core library function `name`, or returns `None` if there is no
+/// such function.
+pub fn call(
+    name: &str,
+    arguments: &[Expr],
+    context: &EvaluationContext,
+) -> Option<Result<Value, Error>> {
+    let (min, max): (usize, Option<usize>) = match name {
+        "last" | "position" | "true" | "false" => (0, Some(0)),
+        "count" | "id" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round" => {
+            (1, Some(1))
+        },
+        "local-name" | "namespace-uri" | "name" | "string" | "string-length" |
+        "normalize-space" | "number" => (0, Some(1)),
+        "starts-with" | "contains" | "substring-before" | "substring-after" => (2, Some(2)),
+        "substring" => (2, Some(3)),
+        "translate" => (3, Some(3)),
+        "concat" => (2, None),
+        _ => return None,
+    };
+    if arguments.len() < min || max.map_or(false, |max| arguments.len() > max) {
+        return Some(Err(Error::InvalidExpression(format!(
+            "wrong number of arguments to {}",
+            name
+        ))));
+    }
+    Some(call_checked(name, arguments, context))
+}
+
+fn call_checked(
+    name: &str,
+    arguments: &[Expr],
+    context: &EvaluationContext,
+) -> Result<Value, Error> {
+    let arg = |index: usize| evaluate(&arguments[index], context);
+    let string_arg = |index: usize| -> Result<String, Error> {
+        match arguments.get(index) {
+            Some(argument) => Ok(evaluate(argument, context)?.string()),
+            None => Ok(string_value(&context.node)),
+        }
+    };
+    // The node a node-set argument stands for, defaulting to the context
+    // node.
+    let node_arg = || -> Result<Option<DomRoot<Node>>, Error> {
+        match arguments.first() {
+            Some(argument) => Ok(evaluate(argument, context)?
+                .into_node_set()?
+                .into_iter()
+                .next()),
+            None => Ok(Some(context.node.clone())),
+        }
+    };
+
+    Ok(match name {
+        // Node set functions.
+        "last" => Value::Number(context.size as f64),
+        "position" => Value::Number(context.position as f64),
+        "count" => Value::Number(arg(0)?.into_node_set()?.len() as f64),
+        "id" => Value::NodeSet(id(&arg(0)?, &context.node)),
+        "local-name" => Value::String(node_arg()?.map_or(String::new(), |n| local_name(&n))),
+        "namespace-uri" => Value::String(node_arg()?.map_or(String::new(), |n| namespace_uri(&n))),
+        "name" => Value::String(node_arg()?.map_or(String::new(), |n| qualified_name(&n))),
+
+        // String functions.
+        "string" => Value::String(string_arg(0)?),
+        "concat" => {
+            let mut result = String::new();
+            for index in 0..arguments.len() {
+                result.push_str(&string_arg(index)?);
+            }
+            Value::String(result)
+        },
+        "starts-with" => Value::Boolean(string_arg(0)?.starts_with(&*string_arg(1)?)),
+        "contains" => Value::Boolean(string_arg(0)?.contains(&*string_arg(1)?)),
+        "substring-before" => {
+            let string = string_arg(0)?;
+            let pattern = string_arg(1)?;
+            Value::String(
+                string
+                    .find(&*pattern)
+                    .map_or(String::new(), |index| string[..index].to_owned()),
+            )
+        },
+        "substring-after" => {
+            let string = string_arg(0)?;
+            let pattern = string_arg(1)?;
+            Value::String(string.find(&*pattern).map_or(String::new(), |index| {
+                string[index + pattern.len()..].to_owned()
+            }))
+        },
+        "substring" => {
+            let string = string_arg(0)?;
+            let start = round(arg(1)?.number());
+            let end = match arguments.get(2) {
+                Some(length) => start + round(evaluate(length, context)?.number()),
+                None => f64::INFINITY,
+            };
+            // Characters are counted from 1, and a character at position p
+            // is kept if start <= p < end, which is false whenever either
+            // bound is NaN.
+            Value::String(
+                string
+                    .chars()
+                    .enumerate()
+                    .filter(|&(index, _)| {
+                        let position = (index + 1) as f64;
+                        position >= start && position < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect(),
+            )
+        },
+        "string-length" => Value::Number(string_arg(0)?.chars().count() as f64),
+        "normalize-space" => Value::String(
+            string_arg(0)?
+                .split(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n')
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        "translate" => {
+            let string = string_arg(0)?;
+            let from: Vec<char> = string_arg(1)?.chars().collect();
+            let to: Vec<char> = string_arg(2)?.chars().collect();
+            Value::String(
+                string
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|&f| f == c) {
+                        Some(index) => to.get(index).cloned(),
+                        None => Some(c),
+                    })
+                    .collect(),
+            )
+        },
+
+        // Boolean functions.
+        "boolean" => Value::Boolean(arg(0)?.boolean()),
+        "not" => Value::Boolean(!arg(0)?.boolean()),
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        "lang" => Value::Boolean(lang(&context.node, &string_arg(0)?)),
+
+        // Number functions.
+        "number" => match arguments.first() {
+            Some(argument) => Value::Number(evaluate(argument, context)?.number()),
+            None => Value::Number(string_to_number(&string_value(&context.node))),
+        },
+        "sum" => Value::Number(
+            arg(0)?
+                .into_node_set()?
+                .iter()
+                .map(|node| string_to_number(&string_value(node)))
+                .sum(),
+        ),
+        "floor" => Value::Number(arg(0)?.number().floor()),
+        "ceiling" => Value::Number(arg(0)?.number().ceil()),
+        "round" => Value::Number(round(arg(0)?.number())),
+        _ => unreachable!(),
+    })
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-id>
+fn id(value: &Value, context_node: &Node) -> Vec<DomRoot<Node>> {
+    let tokens = match *value {
+        Value::NodeSet(ref nodes) => nodes
+            .iter()
+            .map(|node| string_value(node))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => value.string(),
+    };
+    let document = context_node.owner_doc();
+    let mut nodes: Vec<DomRoot<Node>> = tokens
+        .split_whitespace()
+        .filter_map(|token| document.GetElementById(DOMString::from(token)))
+        .map(DomRoot::upcast)
+        .collect();
+    sort_in_document_order(&mut nodes);
+    nodes
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-lang>
+fn lang(node: &Node, lang: &str) -> bool {
+    let value = node
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<Element>)
+        .find_map(|element| {
+            element
+                .get_attribute(&ns!(xml), &local_name!("lang"))
+                .map(|attr| String::from(&**attr.value()))
+        });
+    let value = match value {
+        Some(value) => value,
+        None => return false,
+    };
+    let value = value.to_ascii_lowercase();
+    let lang = lang.to_ascii_lowercase();
+    value == lang || (value.starts_with(&*lang) && value[lang.len()..].starts_with('-'))
+}
+
+fn local_name(node: &Node) -> String {
+    if let Some(element) = node.downcast::<Element>() {
+        return String::from(&**element.local_name());
+    }
+    if let Some(attr) = node.downcast::<Attr>() {
+        return String::from(&**attr.local_name());
+    }
+    if let Some(pi) = node.downcast::<ProcessingInstruction>() {
+        return String::from(pi.target().clone());
+    }
+    String::new()
+}
+
+fn namespace_uri(node: &Node) -> String {
+    if let Some(element) = node.downcast::<Element>() {
+        return String::from(&**element.namespace());
+    }
+    if let Some(attr) = node.downcast::<Attr>() {
+        return String::from(&**attr.namespace());
+    }
+    String::new()
+}
+
+fn qualified_name(node: &Node) -> String {
+    if let Some(element) = node.downcast::<Element>() {
+        return match *element.prefix() {
+            Some(ref prefix) => format!("{}:{}", &**prefix, &**element.local_name()),
+            None => String::from(&**element.local_name()),
+        };
+    }
+    if let Some(attr) = node.downcast::<Attr>() {
+        return String::from(attr.qualified_name());
+    }
+    local_name(node)
+}
+
+/// Rounds to the closest integer, and towards positive infinity for halves.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-round>
+fn round(number: f64) -> f64 {
+    if number.is_nan() || number.is_infinite() {
+        return number;
+    }
+    if number < 0. && number >= -0.5 {
+        return -0.;
+    }
+    (number + 0.5).floor()
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-string>
+pub fn number_to_string(number: f64) -> String {
+    if number.is_nan() {
+        return String::from("NaN");
+    }
+    if number.is_infinite() {
+        return String::from(if number > 0. { "Infinity" } else { "-Infinity" });
+    }
+    if number == 0. {
+        // Negative zero too.
+        return String::from("0");
+    }
+    // Rust never uses exponential notation when displaying floats, and
+    // leaves out the fractional part of integers.
+    number.to_string()
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#function-number>
+pub fn string_to_number(string: &str) -> f64 {
+    let trimmed = string.trim_matches(|c| c == ' ' || c == '\t' || c == '\r' || c == '\n');
+    let digits = if trimmed.starts_with('-') {
+        &trimmed[1..]
+    } else {
+        trimmed
+    };
+    let is_number = !digits.is_empty() &&
+        digits != "." &&
+        digits.chars().all(|c| c.is_ascii_digit() || c == '.') &&
+        digits.matches('.').count() <= 1;
+    if !is_number {
+        return f64::NAN;
+    }
+    trimmed.parse().unwrap_or(f64::NAN)
+}
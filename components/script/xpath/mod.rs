@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An implementation of [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/)
+//! over the DOM, used by XSLT and `document.evaluate`.
+//!
+//! Expressions are parsed once with `parse` and can then be evaluated any
+//! number of times against different context nodes. Whatever the expression
+//! can't resolve by itself, like namespace prefixes and variables, is looked
+//! up through an `Environment`.
+
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::node::Node;
+
+mod eval;
+mod functions;
+mod parser;
+
+pub use self::eval::{compare, evaluate, parent, string_value, EvaluationContext, Value};
+pub use self::functions::{number_to_string, string_to_number};
+pub use self::parser::{
+    parse, tokenize, Axis, BinaryOperator, Expr, NodeTest, PathStart, QName, Step, Token,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The expression is not valid XPath, or calls an unknown function.
+    InvalidExpression(String),
+    /// A namespace prefix in the expression has no known namespace.
+    Namespace(String),
+    /// A value was used where a node-set is required.
+    Type(String),
+}
+
+/// What an expression can refer to beyond the context node.
+pub trait Environment {
+    /// Returns the namespace URI a prefix in the expression stands for.
+    fn resolve_namespace_prefix(&self, prefix: &str) -> Option<String>;
+
+    /// Returns the value of a variable reference.
+    fn variable(&self, _name: &QName) -> Option<Value> {
+        None
+    }
+
+    /// Calls a function that is not part of the core function library, if
+    /// this environment knows it.
+    fn call_function(
+        &self,
+        _name: &QName,
+        _arguments: &[Expr],
+        _context: &EvaluationContext,
+    ) -> Option<Result<Value, Error>> {
+        None
+    }
+}
+
+/// Parses and evaluates `expression` with `node` as its context node.
+pub fn evaluate_str(
+    expression: &str,
+    node: &Node,
+    environment: &dyn Environment,
+) -> Result<Value, Error> {
+    let expr = parse(expression)?;
+    evaluate(&expr, &EvaluationContext::new(node, environment))
+}
+
+/// Sorts `nodes` in document order and removes duplicates.
+pub fn sort_in_document_order(nodes: &mut Vec<DomRoot<Node>>) {
+    self::eval::sort_in_document_order(nodes)
+}
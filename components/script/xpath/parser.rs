@@ -0,0 +1,783 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A tokenizer and recursive descent parser for XPath 1.0 expressions.
+//!
+//! <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>
+
+use super::Error;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A qualified name as written in an expression, before its prefix is
+/// resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QName {
+    pub prefix: Option<String>,
+    pub local: String,
+}
+
+impl QName {
+    fn parse(name: &str) -> QName {
+        let mut parts = name.splitn(2, ':');
+        let first = parts.next().unwrap_or("");
+        match parts.next() {
+            Some(local) => QName {
+                prefix: Some(first.to_owned()),
+                local: local.to_owned(),
+            },
+            None => QName {
+                prefix: None,
+                local: first.to_owned(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Union,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#axes>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfAxis,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Axis> {
+        Some(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfAxis,
+            _ => return None,
+        })
+    }
+
+    /// Whether the proximity position of nodes on this axis counts
+    /// backwards from the context node.
+    pub fn is_reverse(&self) -> bool {
+        match *self {
+            Axis::Ancestor | Axis::AncestorOrSelf | Axis::Preceding | Axis::PrecedingSibling => {
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#node-tests>
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeTest {
+    /// `*`
+    Wildcard,
+    /// `prefix:*`
+    NamespaceWildcard(String),
+    Name(QName),
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub axis: Axis,
+    pub test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+/// Where a path starts looking for its first step.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathStart {
+    /// An absolute path, starting at the root of the context node's tree.
+    Root,
+    /// A relative location path, starting at the context node.
+    ContextNode,
+    /// A filter expression followed by `/` or `//`.
+    Filter(Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Binary(Box<Expr>, BinaryOperator, Box<Expr>),
+    Negate(Box<Expr>),
+    Path(PathStart, Vec<Step>),
+    /// A primary expression followed by one or more predicates.
+    Filter(Box<Expr>, Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Variable(QName),
+    Function(QName, Vec<Expr>),
+}
+
+/// A token of an expression.
+/// <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    DoubleColon,
+    Slash,
+    DoubleSlash,
+    Operator(BinaryOperator),
+    NameTest(NodeTest),
+    NodeType(String),
+    FunctionName(QName),
+    AxisName(Axis),
+    Literal(String),
+    Number(f64),
+    Variable(QName),
+}
+
+impl Token {
+    /// Whether a `*` or an NCName following this token is an operator.
+    /// <https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex>
+    fn allows_operator_after(&self) -> bool {
+        match *self {
+            Token::At |
+            Token::DoubleColon |
+            Token::LeftParen |
+            Token::LeftBracket |
+            Token::Comma |
+            Token::Slash |
+            Token::DoubleSlash |
+            Token::Operator(_) => false,
+            _ => true,
+        }
+    }
+}
+
+fn is_name_start_char(c: char) -> bool {
+    match c {
+        'A'..='Z' | '_' | 'a'..='z' => true,
+        '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{2FF}' => true,
+        '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}' | '\u{200C}'..='\u{200D}' => true,
+        '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}' => true,
+        '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}' => true,
+        _ => false,
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c) ||
+        match c {
+            '-' | '.' | '0'..='9' | '\u{B7}' => true,
+            '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}' => true,
+            _ => false,
+        }
+}
+
+fn is_whitespace(c: char) -> bool {
+    match c {
+        ' ' | '\t' | '\r' | '\n' => true,
+        _ => false,
+    }
+}
+
+struct Lexer<'a> {
+    input: Peekable<Chars<'a>>,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.input.peek().map_or(false, |c| is_whitespace(*c)) {
+            self.input.next();
+        }
+    }
+
+    fn ncname(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(&c) = self.input.peek() {
+            if !is_name_char(c) {
+                break;
+            }
+            name.push(c);
+            self.input.next();
+        }
+        name
+    }
+
+    fn number(&mut self, mut text: String) -> Result<Token, Error> {
+        while let Some(&c) = self.input.peek() {
+            if !(c.is_ascii_digit() || (c == '.' && !text.contains('.'))) {
+                break;
+            }
+            text.push(c);
+            self.input.next();
+        }
+        text.parse()
+            .map(Token::Number)
+            .map_err(|_| Error::InvalidExpression(format!("invalid number {}", text)))
+    }
+
+    fn push(&mut self, token: Token) {
+        self.tokens.push(token);
+    }
+
+    fn operator_allowed(&self) -> bool {
+        self.tokens
+            .last()
+            .map_or(false, |token| token.allows_operator_after())
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, Error> {
+        loop {
+            self.skip_whitespace();
+            let c = match self.input.next() {
+                Some(c) => c,
+                None => return Ok(self.tokens),
+            };
+            let token = match c {
+                '(' => Token::LeftParen,
+                ')' => Token::RightParen,
+                '[' => Token::LeftBracket,
+                ']' => Token::RightBracket,
+                '@' => Token::At,
+                ',' => Token::Comma,
+                '|' => Token::Operator(BinaryOperator::Union),
+                '+' => Token::Operator(BinaryOperator::Add),
+                '-' => Token::Operator(BinaryOperator::Subtract),
+                '=' => Token::Operator(BinaryOperator::Equal),
+                ':' if self.input.peek() == Some(&':') => {
+                    self.input.next();
+                    Token::DoubleColon
+                },
+                '!' if self.input.peek() == Some(&'=') => {
+                    self.input.next();
+                    Token::Operator(BinaryOperator::NotEqual)
+                },
+                '<' | '>' => {
+                    let or_equal = self.input.peek() == Some(&'=');
+                    if or_equal {
+                        self.input.next();
+                    }
+                    Token::Operator(match (c, or_equal) {
+                        ('<', false) => BinaryOperator::Less,
+                        ('<', true) => BinaryOperator::LessOrEqual,
+                        (_, false) => BinaryOperator::Greater,
+                        (_, true) => BinaryOperator::GreaterOrEqual,
+                    })
+                },
+                '/' => {
+                    if self.input.peek() == Some(&'/') {
+                        self.input.next();
+                        Token::DoubleSlash
+                    } else {
+                        Token::Slash
+                    }
+                },
+                '.' => match self.input.peek() {
+                    Some(&'.') => {
+                        self.input.next();
+                        Token::DotDot
+                    },
+                    Some(c) if c.is_ascii_digit() => self.number(String::from("."))?,
+                    _ => Token::Dot,
+                },
+                '0'..='9' => self.number(c.to_string())?,
+                '"' | '\'' => {
+                    let mut literal = String::new();
+                    loop {
+                        match self.input.next() {
+                            Some(end) if end == c => break,
+                            Some(other) => literal.push(other),
+                            None => {
+                                return Err(Error::InvalidExpression(String::from(
+                                    "unterminated string literal",
+                                )))
+                            },
+                        }
+                    }
+                    Token::Literal(literal)
+                },
+                '$' => {
+                    let name = self.qname()?;
+                    Token::Variable(name)
+                },
+                '*' => {
+                    if self.operator_allowed() {
+                        Token::Operator(BinaryOperator::Multiply)
+                    } else {
+                        Token::NameTest(NodeTest::Wildcard)
+                    }
+                },
+                c if is_name_start_char(c) => {
+                    let mut name = c.to_string();
+                    name.push_str(&self.ncname());
+                    self.name(name)?
+                },
+                c => {
+                    return Err(Error::InvalidExpression(format!(
+                        "unexpected character {}",
+                        c
+                    )))
+                },
+            };
+            self.push(token);
+        }
+    }
+
+    fn qname(&mut self) -> Result<QName, Error> {
+        let mut name = self.ncname();
+        if name.is_empty() {
+            return Err(Error::InvalidExpression(String::from("expected a name")));
+        }
+        if self.input.peek() == Some(&':') {
+            let mut lookahead = self.input.clone();
+            lookahead.next();
+            if lookahead.peek().map_or(false, |c| is_name_start_char(*c)) {
+                self.input.next();
+                name.push(':');
+                name.push_str(&self.ncname());
+            }
+        }
+        Ok(QName::parse(&name))
+    }
+
+    /// Classifies a name that starts with the NCName `name`.
+    fn name(&mut self, mut name: String) -> Result<Token, Error> {
+        // Operator names.
+        if self.operator_allowed() {
+            return Ok(Token::Operator(match &*name {
+                "and" => BinaryOperator::And,
+                "or" => BinaryOperator::Or,
+                "mod" => BinaryOperator::Modulo,
+                "div" => BinaryOperator::Divide,
+                _ => {
+                    return Err(Error::InvalidExpression(format!(
+                        "expected an operator, found {}",
+                        name
+                    )))
+                },
+            }));
+        }
+
+        // A prefixed name or a `prefix:*` name test.
+        if self.input.peek() == Some(&':') {
+            let mut lookahead = self.input.clone();
+            lookahead.next();
+            match lookahead.peek() {
+                Some(&'*') => {
+                    self.input.next();
+                    self.input.next();
+                    return Ok(Token::NameTest(NodeTest::NamespaceWildcard(name)));
+                },
+                Some(c) if is_name_start_char(*c) => {
+                    self.input.next();
+                    name.push(':');
+                    name.push_str(&self.ncname());
+                },
+                _ => {},
+            }
+        }
+
+        // Look past any whitespace to tell axis names, node types and
+        // function names apart from name tests.
+        let mut lookahead = self.input.clone();
+        while lookahead.peek().map_or(false, |c| is_whitespace(*c)) {
+            lookahead.next();
+        }
+        match lookahead.peek() {
+            Some(&'(') => match &*name {
+                "comment" | "text" | "processing-instruction" | "node" => Ok(Token::NodeType(name)),
+                _ => Ok(Token::FunctionName(QName::parse(&name))),
+            },
+            Some(&':') => {
+                lookahead.next();
+                if lookahead.peek() != Some(&':') {
+                    return Ok(Token::NameTest(NodeTest::Name(QName::parse(&name))));
+                }
+                match Axis::from_name(&name) {
+                    Some(axis) => Ok(Token::AxisName(axis)),
+                    None => Err(Error::InvalidExpression(format!("unknown axis {}", name))),
+                }
+            },
+            _ => Ok(Token::NameTest(NodeTest::Name(QName::parse(&name)))),
+        }
+    }
+}
+
+/// Splits an XPath 1.0 expression into tokens.
+pub fn tokenize(expression: &str) -> Result<Vec<Token>, Error> {
+    let lexer = Lexer {
+        input: expression.chars().peekable(),
+        tokens: vec![],
+    };
+    lexer.tokenize()
+}
+
+/// Parses an XPath 1.0 expression.
+pub fn parse(expression: &str) -> Result<Expr, Error> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+    };
+    let expr = parser.expr()?;
+    if parser.position != tokens.len() {
+        return Err(Error::InvalidExpression(String::from(
+            "unexpected token after the end of the expression",
+        )));
+    }
+    Ok(expr)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), Error> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(Error::InvalidExpression(format!("expected {:?}", token)))
+        }
+    }
+
+    fn peek_operator(&self) -> Option<BinaryOperator> {
+        match self.peek() {
+            Some(&Token::Operator(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    /// Parses a left-associative chain of binary operators from
+    /// `operators`, with operands parsed by `operand`.
+    fn binary<F>(&mut self, operators: &[BinaryOperator], operand: F) -> Result<Expr, Error>
+    where
+        F: Fn(&mut Self) -> Result<Expr, Error>,
+    {
+        let mut left = operand(self)?;
+        while let Some(op) = self.peek_operator() {
+            if !operators.contains(&op) {
+                break;
+            }
+            self.position += 1;
+            let right = operand(self)?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        self.binary(&[BinaryOperator::Or], Self::and_expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, Error> {
+        self.binary(&[BinaryOperator::And], Self::equality_expr)
+    }
+
+    fn equality_expr(&mut self) -> Result<Expr, Error> {
+        self.binary(
+            &[BinaryOperator::Equal, BinaryOperator::NotEqual],
+            Self::relational_expr,
+        )
+    }
+
+    fn relational_expr(&mut self) -> Result<Expr, Error> {
+        self.binary(
+            &[
+                BinaryOperator::Less,
+                BinaryOperator::LessOrEqual,
+                BinaryOperator::Greater,
+                BinaryOperator::GreaterOrEqual,
+            ],
+            Self::additive_expr,
+        )
+    }
+
+    fn additive_expr(&mut self) -> Result<Expr, Error> {
+        self.binary(
+            &[BinaryOperator::Add, BinaryOperator::Subtract],
+            Self::multiplicative_expr,
+        )
+    }
+
+    fn multiplicative_expr(&mut self) -> Result<Expr, Error> {
+        self.binary(
+            &[
+                BinaryOperator::Multiply,
+                BinaryOperator::Divide,
+                BinaryOperator::Modulo,
+            ],
+            Self::unary_expr,
+        )
+    }
+
+    fn unary_expr(&mut self) -> Result<Expr, Error> {
+        if self.eat(&Token::Operator(BinaryOperator::Subtract)) {
+            return Ok(Expr::Negate(Box::new(self.unary_expr()?)));
+        }
+        self.binary(&[BinaryOperator::Union], Self::path_expr)
+    }
+
+    fn path_expr(&mut self) -> Result<Expr, Error> {
+        let starts_filter = match self.peek() {
+            Some(Token::Variable(_)) |
+            Some(Token::LeftParen) |
+            Some(Token::Literal(_)) |
+            Some(Token::Number(_)) |
+            Some(Token::FunctionName(_)) => true,
+            _ => false,
+        };
+        if !starts_filter {
+            return self.location_path();
+        }
+
+        let primary = self.primary_expr()?;
+        let mut predicates = vec![];
+        while self.peek() == Some(&Token::LeftBracket) {
+            predicates.push(self.predicate()?);
+        }
+        let filter = if predicates.is_empty() {
+            primary
+        } else {
+            Expr::Filter(Box::new(primary), predicates)
+        };
+
+        let mut steps = vec![];
+        match self.peek() {
+            Some(Token::Slash) => {
+                self.position += 1;
+            },
+            Some(Token::DoubleSlash) => {
+                self.position += 1;
+                steps.push(descendant_or_self_step());
+            },
+            _ => return Ok(filter),
+        }
+        self.relative_location_path(&mut steps)?;
+        Ok(Expr::Path(PathStart::Filter(Box::new(filter)), steps))
+    }
+
+    fn location_path(&mut self) -> Result<Expr, Error> {
+        let mut steps = vec![];
+        match self.peek() {
+            Some(Token::Slash) => {
+                self.position += 1;
+                // A lone `/` selects the root node.
+                if self.starts_step() {
+                    self.relative_location_path(&mut steps)?;
+                }
+                Ok(Expr::Path(PathStart::Root, steps))
+            },
+            Some(Token::DoubleSlash) => {
+                self.position += 1;
+                steps.push(descendant_or_self_step());
+                self.relative_location_path(&mut steps)?;
+                Ok(Expr::Path(PathStart::Root, steps))
+            },
+            _ => {
+                self.relative_location_path(&mut steps)?;
+                Ok(Expr::Path(PathStart::ContextNode, steps))
+            },
+        }
+    }
+
+    fn starts_step(&self) -> bool {
+        match self.peek() {
+            Some(Token::Dot) |
+            Some(Token::DotDot) |
+            Some(Token::At) |
+            Some(Token::AxisName(_)) |
+            Some(Token::NameTest(_)) |
+            Some(Token::NodeType(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn relative_location_path(&mut self, steps: &mut Vec<Step>) -> Result<(), Error> {
+        steps.push(self.step()?);
+        loop {
+            match self.peek() {
+                Some(Token::Slash) => {
+                    self.position += 1;
+                },
+                Some(Token::DoubleSlash) => {
+                    self.position += 1;
+                    steps.push(descendant_or_self_step());
+                },
+                _ => return Ok(()),
+            }
+            steps.push(self.step()?);
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, Error> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::SelfAxis,
+                test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: vec![],
+            });
+        }
+
+        let axis = match self.peek() {
+            Some(&Token::AxisName(axis)) => {
+                self.position += 1;
+                self.expect(&Token::DoubleColon)?;
+                axis
+            },
+            Some(Token::At) => {
+                self.position += 1;
+                Axis::Attribute
+            },
+            _ => Axis::Child,
+        };
+
+        let test = match self.next() {
+            Some(Token::NameTest(test)) => test.clone(),
+            Some(Token::NodeType(node_type)) => {
+                self.expect(&Token::LeftParen)?;
+                let test = match &**node_type {
+                    "comment" => NodeTest::Comment,
+                    "text" => NodeTest::Text,
+                    "node" => NodeTest::Node,
+                    _ => match self.peek() {
+                        Some(Token::Literal(target)) => {
+                            self.position += 1;
+                            NodeTest::ProcessingInstruction(Some(target.clone()))
+                        },
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(&Token::RightParen)?;
+                test
+            },
+            _ => {
+                return Err(Error::InvalidExpression(String::from(
+                    "expected a node test",
+                )))
+            },
+        };
+
+        let mut predicates = vec![];
+        while self.peek() == Some(&Token::LeftBracket) {
+            predicates.push(self.predicate()?);
+        }
+        Ok(Step {
+            axis,
+            test,
+            predicates,
+        })
+    }
+
+    fn predicate(&mut self) -> Result<Expr, Error> {
+        self.expect(&Token::LeftBracket)?;
+        let expr = self.expr()?;
+        self.expect(&Token::RightBracket)?;
+        Ok(expr)
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Some(Token::Variable(name)) => Ok(Expr::Variable(name.clone())),
+            Some(Token::LeftParen) => {
+                let expr = self.expr()?;
+                self.expect(&Token::RightParen)?;
+                Ok(expr)
+            },
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal.clone())),
+            Some(&Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::FunctionName(name)) => {
+                self.expect(&Token::LeftParen)?;
+                let mut arguments = vec![];
+                if !self.eat(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.expr()?);
+                        if self.eat(&Token::RightParen) {
+                            break;
+                        }
+                        self.expect(&Token::Comma)?;
+                    }
+                }
+                Ok(Expr::Function(name.clone(), arguments))
+            },
+            _ => Err(Error::InvalidExpression(String::from(
+                "expected a primary expression",
+            ))),
+        }
+    }
+}
+
+/// The step `//` abbreviates.
+fn descendant_or_self_step() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        test: NodeTest::Node,
+        predicates: vec![],
+    }
+}
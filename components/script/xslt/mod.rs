@@ -0,0 +1,47 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A basic [XSLT 1.0](https://www.w3.org/TR/1999/REC-xslt-19991116) processor,
+//! used by `XSLTProcessor`.
+//!
+//! `Stylesheet::compile` collects the templates and top-level variables of a
+//! stylesheet tree. The instructions themselves are read from that tree while
+//! `transform` instantiates templates into a result fragment.
+//!
+//! Not supported yet: `xsl:import`, `xsl:include`, `xsl:key`, attribute sets,
+//! the `document()`, `key()` and `format-number()` functions, and the
+//! `count`, `from` and `level` attributes of `xsl:number`.
+
+use crate::xpath;
+
+mod stylesheet;
+mod transform;
+
+pub use self::stylesheet::{ExpandedName, OutputMethod, Stylesheet};
+pub use self::transform::transform;
+
+/// The namespace of XSLT instructions and top-level elements.
+pub const XSLT_NAMESPACE: &str = "http://www.w3.org/1999/XSL/Transform";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// An expression or pattern in the stylesheet failed to parse or evaluate.
+    XPath(xpath::Error),
+    /// The stylesheet is malformed, or uses something this processor doesn't
+    /// support.
+    Stylesheet(String),
+    /// `xsl:message` asked for the transformation to stop.
+    Terminated,
+    /// Templates nest too deeply, most likely because of infinite recursion.
+    TooMuchRecursion,
+    /// A node couldn't be inserted where the stylesheet put it in the result
+    /// tree.
+    ResultTree(String),
+}
+
+impl From<xpath::Error> for Error {
+    fn from(error: xpath::Error) -> Error {
+        Error::XPath(error)
+    }
+}
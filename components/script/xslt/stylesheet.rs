@@ -0,0 +1,254 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use super::{Error, XSLT_NAMESPACE};
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::node::Node;
+use crate::xpath::{self, Axis, BinaryOperator, Expr, NodeTest, PathStart};
+use html5ever::{LocalName, Namespace};
+
+/// A name after its prefix has been resolved.
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#qname>
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ExpandedName {
+    pub namespace: String,
+    pub local: String,
+}
+
+impl ExpandedName {
+    /// Resolves a QName written in an attribute of `element`, using the
+    /// namespace declarations in scope there. Unprefixed names are in no
+    /// namespace, whatever the default namespace is.
+    pub fn resolve(element: &Element, qname: &str) -> Result<ExpandedName, Error> {
+        let mut parts = qname.trim().splitn(2, ':');
+        let first = parts.next().unwrap_or("");
+        match parts.next() {
+            Some(local) => {
+                let namespace = element
+                    .upcast::<Node>()
+                    .LookupNamespaceURI(Some(DOMString::from(first)))
+                    .ok_or_else(|| {
+                        Error::Stylesheet(format!("unknown namespace prefix {}", first))
+                    })?;
+                Ok(ExpandedName {
+                    namespace: String::from(namespace),
+                    local: local.to_owned(),
+                })
+            },
+            None => Ok(ExpandedName {
+                namespace: String::new(),
+                local: first.to_owned(),
+            }),
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#output>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputMethod {
+    Xml,
+    Html,
+    Text,
+    /// No `xsl:output` element picked a method, so it depends on the result.
+    Unspecified,
+}
+
+/// One alternative of a template's match pattern.
+pub struct Pattern {
+    pub expr: Expr,
+    pub priority: f64,
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Defining-Template-Rules>
+pub struct Template {
+    /// The `xsl:template` element, used to resolve namespace prefixes.
+    pub element: DomRoot<Element>,
+    /// The instructions to instantiate, including any `xsl:param`.
+    pub body: Vec<DomRoot<Node>>,
+    pub patterns: Vec<Pattern>,
+    pub name: Option<ExpandedName>,
+    pub mode: Option<ExpandedName>,
+}
+
+pub struct Stylesheet {
+    /// The template rules, in document order.
+    pub templates: Vec<Template>,
+    /// Top-level `xsl:variable` and `xsl:param` elements, in document order.
+    pub variables: Vec<DomRoot<Element>>,
+    pub output: OutputMethod,
+}
+
+impl Stylesheet {
+    /// Compiles the stylesheet rooted at `node`, which is either an
+    /// `xsl:stylesheet` element, a literal result element used as a
+    /// stylesheet, or a document with either of those as its root.
+    pub fn compile(node: &Node) -> Result<Stylesheet, Error> {
+        let root = match node.downcast::<Document>() {
+            Some(document) => document.GetDocumentElement(),
+            None => node.downcast::<Element>().map(DomRoot::from_ref),
+        };
+        let root = root
+            .ok_or_else(|| Error::Stylesheet(String::from("the stylesheet has no root element")))?;
+
+        let mut stylesheet = Stylesheet {
+            templates: vec![],
+            variables: vec![],
+            output: OutputMethod::Unspecified,
+        };
+
+        if !is_xslt_element(&root, "stylesheet") && !is_xslt_element(&root, "transform") {
+            // https://www.w3.org/TR/1999/REC-xslt-19991116#result-element-stylesheet
+            let version = root.get_attribute(
+                &Namespace::from(XSLT_NAMESPACE),
+                &LocalName::from("version"),
+            );
+            if version.is_none() {
+                return Err(Error::Stylesheet(String::from(
+                    "the root element is not an XSLT stylesheet",
+                )));
+            }
+            stylesheet.templates.push(Template {
+                body: vec![DomRoot::from_ref(root.upcast::<Node>())],
+                patterns: vec![Pattern {
+                    expr: xpath::parse("/")?,
+                    priority: 0.5,
+                }],
+                element: root,
+                name: None,
+                mode: None,
+            });
+            return Ok(stylesheet);
+        }
+
+        for child in root.upcast::<Node>().children() {
+            let element = match DomRoot::downcast::<Element>(child) {
+                Some(element) => element,
+                None => continue,
+            };
+            // Top-level elements in other namespaces are ignored.
+            if **element.namespace() != *XSLT_NAMESPACE {
+                continue;
+            }
+            match &**element.local_name() {
+                "template" => stylesheet.templates.push(compile_template(element)?),
+                "variable" | "param" => stylesheet.variables.push(element),
+                "output" => {
+                    if let Some(method) = get_attribute(&element, "method") {
+                        stylesheet.output = match method.trim() {
+                            "xml" => OutputMethod::Xml,
+                            "html" => OutputMethod::Html,
+                            "text" => OutputMethod::Text,
+                            _ => OutputMethod::Unspecified,
+                        };
+                    }
+                },
+                "import" | "include" | "key" => {
+                    return Err(Error::Stylesheet(format!(
+                        "xsl:{} is not supported",
+                        element.local_name()
+                    )));
+                },
+                // These only affect things this processor doesn't do, like
+                // whitespace stripping of the source tree.
+                "strip-space" | "preserve-space" | "decimal-format" | "namespace-alias" |
+                "attribute-set" => {},
+                name => {
+                    return Err(Error::Stylesheet(format!(
+                        "xsl:{} is not allowed at the top level",
+                        name
+                    )));
+                },
+            }
+        }
+        Ok(stylesheet)
+    }
+}
+
+fn compile_template(element: DomRoot<Element>) -> Result<Template, Error> {
+    let name = match get_attribute(&element, "name") {
+        Some(name) => Some(ExpandedName::resolve(&element, &name)?),
+        None => None,
+    };
+    let mode = match get_attribute(&element, "mode") {
+        Some(mode) => Some(ExpandedName::resolve(&element, &mode)?),
+        None => None,
+    };
+    let priority = match get_attribute(&element, "priority") {
+        Some(priority) => match priority.trim().parse::<f64>() {
+            Ok(priority) => Some(priority),
+            Err(_) => {
+                return Err(Error::Stylesheet(format!("invalid priority {}", priority)));
+            },
+        },
+        None => None,
+    };
+
+    let mut patterns = vec![];
+    if let Some(pattern) = get_attribute(&element, "match") {
+        let mut alternatives = vec![];
+        split_union(xpath::parse(&pattern)?, &mut alternatives);
+        for expr in alternatives {
+            let priority = priority.unwrap_or_else(|| default_priority(&expr));
+            patterns.push(Pattern { expr, priority });
+        }
+    }
+    if patterns.is_empty() && name.is_none() {
+        return Err(Error::Stylesheet(String::from(
+            "xsl:template needs a match or name attribute",
+        )));
+    }
+
+    Ok(Template {
+        body: element.upcast::<Node>().children().collect(),
+        element,
+        patterns,
+        name,
+        mode,
+    })
+}
+
+/// Splits a pattern into the alternatives of its top-level `|` operators.
+fn split_union(expr: Expr, alternatives: &mut Vec<Expr>) {
+    match expr {
+        Expr::Binary(left, BinaryOperator::Union, right) => {
+            split_union(*left, alternatives);
+            split_union(*right, alternatives);
+        },
+        expr => alternatives.push(expr),
+    }
+}
+
+/// <https://www.w3.org/TR/1999/REC-xslt-19991116#conflict>
+fn default_priority(pattern: &Expr) -> f64 {
+    let step = match *pattern {
+        Expr::Path(PathStart::ContextNode, ref steps) if steps.len() == 1 => &steps[0],
+        _ => return 0.5,
+    };
+    if !step.predicates.is_empty() || (step.axis != Axis::Child && step.axis != Axis::Attribute) {
+        return 0.5;
+    }
+    match step.test {
+        NodeTest::Name(_) | NodeTest::ProcessingInstruction(Some(_)) => 0.,
+        NodeTest::NamespaceWildcard(_) => -0.25,
+        _ => -0.5,
+    }
+}
+
+/// Whether `element` is the XSLT element `local_name`.
+pub fn is_xslt_element(element: &Element, local_name: &str) -> bool {
+    **element.namespace() == *XSLT_NAMESPACE && **element.local_name() == *local_name
+}
+
+/// Returns the value of the attribute without namespace `name`.
+pub fn get_attribute(element: &Element, name: &str) -> Option<String> {
+    element
+        .get_attribute(&ns!(), &LocalName::from(name))
+        .map(|attr| String::from(&**attr.value()))
+}
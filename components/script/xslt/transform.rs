@@ -0,0 +1,986 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Instantiation of templates into a result tree.
+//!
+//! <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Creating-the-Result-Tree>
+
+use super::stylesheet::{get_attribute, is_xslt_element, ExpandedName, Stylesheet, Template};
+use super::{Error, XSLT_NAMESPACE};
+use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::comment::Comment;
+use crate::dom::document::Document;
+use crate::dom::documentfragment::DocumentFragment;
+use crate::dom::element::{CustomElementCreationMode, Element, ElementCreator};
+use crate::dom::node::{CloneChildrenFlag, Node};
+use crate::dom::processinginstruction::ProcessingInstruction;
+use crate::dom::text::Text;
+use crate::xpath::{self, Environment, EvaluationContext, Expr, PathStart, QName, Value};
+use html5ever::{LocalName, Namespace, Prefix, QualName};
+use std::cmp::Ordering;
+
+/// How deeply templates may nest before the transformation is abandoned.
+const MAX_DEPTH: usize = 512;
+
+type Variables = Vec<(ExpandedName, Value)>;
+
+/// Transforms `source` with `stylesheet` and returns the result tree as a
+/// fragment of `document`.
+///
+/// `parameters` override the top-level parameters of the stylesheet. When
+/// `html_output` is set, literal result elements and `xsl:element`s in no
+/// namespace are created as HTML elements.
+pub fn transform(
+    stylesheet: &Stylesheet,
+    source: &Node,
+    parameters: &[(ExpandedName, Value)],
+    document: &Document,
+    html_output: bool,
+) -> Result<DomRoot<DocumentFragment>, Error> {
+    let mut transformer = Transformer {
+        stylesheet,
+        document,
+        html_output,
+        globals: vec![],
+        depth: 0,
+    };
+    let context = Context {
+        node: DomRoot::from_ref(source),
+        position: 1,
+        size: 1,
+    };
+
+    // https://www.w3.org/TR/1999/REC-xslt-19991116#top-level-variables
+    for element in &stylesheet.variables {
+        let name = variable_name(element)?;
+        let passed = parameters.iter().find(|&&(ref n, _)| *n == name);
+        let value = match passed {
+            Some(&(_, ref value)) if is_xslt_element(element, "param") => value.clone(),
+            _ => transformer.variable_value(element, &context, &mut vec![])?,
+        };
+        transformer.globals.push((name, value));
+    }
+
+    let fragment = document.CreateDocumentFragment();
+    transformer.apply_templates(vec![context.node.clone()], None, &[], fragment.upcast())?;
+    fragment.upcast::<Node>().Normalize();
+    Ok(fragment)
+}
+
+/// The current node and its position in the current node list.
+struct Context {
+    node: DomRoot<Node>,
+    position: usize,
+    size: usize,
+}
+
+struct Transformer<'a> {
+    stylesheet: &'a Stylesheet,
+    /// The document result nodes are created in.
+    document: &'a Document,
+    html_output: bool,
+    globals: Variables,
+    depth: usize,
+}
+
+/// What expressions in an instruction can refer to.
+struct InstructionEnvironment<'a> {
+    /// The instruction, whose namespace declarations are in scope.
+    instruction: &'a Element,
+    variables: &'a [(ExpandedName, Value)],
+    globals: &'a [(ExpandedName, Value)],
+    current: &'a Node,
+}
+
+impl<'a> Environment for InstructionEnvironment<'a> {
+    fn resolve_namespace_prefix(&self, prefix: &str) -> Option<String> {
+        self.instruction
+            .upcast::<Node>()
+            .LookupNamespaceURI(Some(DOMString::from(prefix)))
+            .map(String::from)
+    }
+
+    fn variable(&self, name: &QName) -> Option<Value> {
+        let namespace = match name.prefix {
+            Some(ref prefix) => self.resolve_namespace_prefix(prefix)?,
+            None => String::new(),
+        };
+        let name = ExpandedName {
+            namespace,
+            local: name.local.clone(),
+        };
+        self.variables
+            .iter()
+            .rev()
+            .chain(self.globals.iter().rev())
+            .find(|&&(ref n, _)| *n == name)
+            .map(|&(_, ref value)| value.clone())
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#add-func>
+    fn call_function(
+        &self,
+        name: &QName,
+        arguments: &[Expr],
+        context: &EvaluationContext,
+    ) -> Option<Result<Value, xpath::Error>> {
+        if name.prefix.is_some() {
+            return None;
+        }
+        match (&*name.local, arguments.len()) {
+            ("current", 0) => Some(Ok(Value::NodeSet(vec![DomRoot::from_ref(self.current)]))),
+            ("generate-id", 0) => Some(Ok(Value::String(generate_id(&context.node)))),
+            ("generate-id", 1) => Some(
+                xpath::evaluate(&arguments[0], context)
+                    .and_then(Value::into_node_set)
+                    .map(|nodes| {
+                        Value::String(nodes.first().map_or(String::new(), |n| generate_id(n)))
+                    }),
+            ),
+            ("system-property", 1) => Some(xpath::evaluate(&arguments[0], context).map(|name| {
+                Value::String(match &*name.string() {
+                    "xsl:version" => String::from("1"),
+                    "xsl:vendor" => String::from("Servo"),
+                    "xsl:vendor-url" => String::from("https://servo.org/"),
+                    _ => String::new(),
+                })
+            })),
+            _ => None,
+        }
+    }
+}
+
+/// A string that identifies `node` for as long as it is alive.
+fn generate_id(node: &Node) -> String {
+    format!("id{:x}", node as *const Node as usize)
+}
+
+impl<'a> Transformer<'a> {
+    fn evaluate(
+        &self,
+        instruction: &Element,
+        expression: &str,
+        context: &Context,
+        variables: &[(ExpandedName, Value)],
+    ) -> Result<Value, Error> {
+        let expr = xpath::parse(expression)?;
+        let environment = InstructionEnvironment {
+            instruction,
+            variables,
+            globals: &self.globals,
+            current: &context.node,
+        };
+        let evaluation_context = EvaluationContext {
+            node: context.node.clone(),
+            position: context.position,
+            size: context.size,
+            environment: &environment,
+        };
+        Ok(xpath::evaluate(&expr, &evaluation_context)?)
+    }
+
+    /// Evaluates the required attribute `name` of `instruction` as an
+    /// expression.
+    fn evaluate_attribute(
+        &self,
+        instruction: &Element,
+        name: &str,
+        context: &Context,
+        variables: &[(ExpandedName, Value)],
+    ) -> Result<Value, Error> {
+        let expression = required_attribute(instruction, name)?;
+        self.evaluate(instruction, &expression, context, variables)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#attribute-value-templates>
+    fn attribute_value_template(
+        &self,
+        instruction: &Element,
+        template: &str,
+        context: &Context,
+        variables: &[(ExpandedName, Value)],
+    ) -> Result<String, Error> {
+        let mut result = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    result.push('{');
+                },
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    result.push('}');
+                },
+                '{' => {
+                    // Braces inside string literals don't end the expression.
+                    let mut expression = String::new();
+                    let mut quote = None;
+                    loop {
+                        match chars.next() {
+                            Some('}') if quote.is_none() => break,
+                            Some(c) => {
+                                if quote == Some(c) {
+                                    quote = None;
+                                } else if quote.is_none() && (c == '"' || c == '\'') {
+                                    quote = Some(c);
+                                }
+                                expression.push(c);
+                            },
+                            None => {
+                                return Err(Error::Stylesheet(format!(
+                                    "unterminated expression in {}",
+                                    template
+                                )));
+                            },
+                        }
+                    }
+                    let value = self.evaluate(instruction, &expression, context, variables)?;
+                    result.push_str(&value.string());
+                },
+                '}' => {
+                    return Err(Error::Stylesheet(format!("unmatched }} in {}", template)));
+                },
+                c => result.push(c),
+            }
+        }
+        Ok(result)
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#section-Applying-Template-Rules>
+    fn apply_templates(
+        &mut self,
+        nodes: Vec<DomRoot<Node>>,
+        mode: Option<&ExpandedName>,
+        parameters: &[(ExpandedName, Value)],
+        parent: &Node,
+    ) -> Result<(), Error> {
+        let size = nodes.len();
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+            };
+            match self.find_template(&context.node, mode)? {
+                Some(template) => {
+                    self.instantiate_template(template, &context, parameters, parent)?
+                },
+                None => self.apply_built_in_template(&context, mode, parent)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#built-in-rule>
+    fn apply_built_in_template(
+        &mut self,
+        context: &Context,
+        mode: Option<&ExpandedName>,
+        parent: &Node,
+    ) -> Result<(), Error> {
+        let node = &context.node;
+        match node.type_id() {
+            NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) | NodeTypeId::Element(_) => {
+                let children = node.children().filter(|n| is_in_data_model(n)).collect();
+                self.apply_templates(children, mode, &[], parent)
+            },
+            NodeTypeId::Attr | NodeTypeId::CharacterData(CharacterDataTypeId::Text(_)) => {
+                self.append_text(parent, &xpath::string_value(node))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Finds the template rule that matches `node` in `mode` with the
+    /// highest priority, preferring later templates when they tie.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#conflict>
+    fn find_template(
+        &self,
+        node: &Node,
+        mode: Option<&ExpandedName>,
+    ) -> Result<Option<&'a Template>, Error> {
+        let mut best: Option<(&'a Template, f64)> = None;
+        for template in &self.stylesheet.templates {
+            if template.mode.as_ref() != mode {
+                continue;
+            }
+            for pattern in &template.patterns {
+                if best.map_or(false, |(_, priority)| pattern.priority < priority) {
+                    continue;
+                }
+                if self.matches(&pattern.expr, node, &template.element)? {
+                    best = Some((template, pattern.priority));
+                }
+            }
+        }
+        Ok(best.map(|(template, _)| template))
+    }
+
+    /// Whether `node` matches `pattern`, which it does if `node` is selected
+    /// by the pattern as an expression from the node or one of its
+    /// ancestors.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#patterns>
+    fn matches(&self, pattern: &Expr, node: &Node, template: &Element) -> Result<bool, Error> {
+        let absolute = match *pattern {
+            Expr::Path(PathStart::Root, ref steps) => {
+                if steps.is_empty() {
+                    return Ok(xpath::parent(node).is_none() && !node.is::<Element>());
+                }
+                true
+            },
+            _ => false,
+        };
+        let environment = InstructionEnvironment {
+            instruction: template,
+            variables: &[],
+            globals: &self.globals,
+            current: node,
+        };
+        let mut candidate = Some(DomRoot::from_ref(node));
+        while let Some(context_node) = candidate {
+            let context = EvaluationContext::new(&context_node, &environment);
+            let selected = xpath::evaluate(pattern, &context)?.into_node_set()?;
+            if selected.iter().any(|selected| &**selected == node) {
+                return Ok(true);
+            }
+            // Absolute patterns select the same nodes from everywhere.
+            if absolute {
+                break;
+            }
+            candidate = xpath::parent(&context_node);
+        }
+        Ok(false)
+    }
+
+    fn instantiate_template(
+        &mut self,
+        template: &Template,
+        context: &Context,
+        parameters: &[(ExpandedName, Value)],
+        parent: &Node,
+    ) -> Result<(), Error> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(Error::TooMuchRecursion);
+        }
+        let mut variables = vec![];
+        for node in &template.body {
+            // https://www.w3.org/TR/1999/REC-xslt-19991116#section-Values-of-Variables-and-Parameters
+            if let Some(element) = node.downcast::<Element>() {
+                if is_xslt_element(element, "param") {
+                    let name = variable_name(element)?;
+                    let value = match parameters.iter().find(|&&(ref n, _)| *n == name) {
+                        Some(&(_, ref value)) => value.clone(),
+                        None => self.variable_value(element, context, &mut variables)?,
+                    };
+                    variables.push((name, value));
+                    continue;
+                }
+            }
+            self.execute(node, context, &mut variables, parent)?;
+        }
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Instantiates the children of `instruction`.
+    fn execute_children(
+        &mut self,
+        instruction: &Node,
+        context: &Context,
+        variables: &mut Variables,
+        parent: &Node,
+    ) -> Result<(), Error> {
+        // Variables go out of scope at the end of their parent.
+        let scope = variables.len();
+        for child in instruction.children() {
+            self.execute(&child, context, variables, parent)?;
+        }
+        variables.truncate(scope);
+        Ok(())
+    }
+
+    /// Instantiates the stylesheet node `node`, appending what it produces
+    /// to `parent`.
+    fn execute(
+        &mut self,
+        node: &Node,
+        context: &Context,
+        variables: &mut Variables,
+        parent: &Node,
+    ) -> Result<(), Error> {
+        match node.type_id() {
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text(_)) => {
+                // Whitespace-only text is stripped from stylesheets.
+                // https://www.w3.org/TR/1999/REC-xslt-19991116#strip
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                if !data.chars().all(is_xml_whitespace) {
+                    self.append_text(parent, &data)?;
+                }
+                Ok(())
+            },
+            NodeTypeId::Element(_) => {
+                let element = node.downcast::<Element>().unwrap();
+                if **element.namespace() == *XSLT_NAMESPACE {
+                    self.execute_instruction(element, context, variables, parent)
+                } else {
+                    self.literal_result_element(element, context, variables, parent)
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#literal-result-element>
+    fn literal_result_element(
+        &mut self,
+        element: &Element,
+        context: &Context,
+        variables: &mut Variables,
+        parent: &Node,
+    ) -> Result<(), Error> {
+        let result = self.create_element(
+            element.namespace().clone(),
+            element.prefix().clone(),
+            element.local_name().clone(),
+        );
+        for attr in element.attrs().iter() {
+            let namespace = attr.namespace();
+            if **namespace == *XSLT_NAMESPACE {
+                continue;
+            }
+            let value = if *namespace == ns!(xmlns) {
+                // The declaration of the XSLT namespace isn't copied.
+                if **attr.value() == *XSLT_NAMESPACE {
+                    continue;
+                }
+                String::from(&**attr.value())
+            } else {
+                self.attribute_value_template(element, &attr.value(), context, variables)?
+            };
+            let name = QualName::new(
+                attr.prefix().cloned(),
+                namespace.clone(),
+                attr.local_name().clone(),
+            );
+            set_attribute(&result, name, value);
+        }
+        self.append(parent, result.upcast())?;
+        self.execute_children(element.upcast(), context, variables, result.upcast())
+    }
+
+    fn execute_instruction(
+        &mut self,
+        instruction: &Element,
+        context: &Context,
+        variables: &mut Variables,
+        parent: &Node,
+    ) -> Result<(), Error> {
+        match &**instruction.local_name() {
+            "apply-templates" => {
+                let nodes = match get_attribute(instruction, "select") {
+                    Some(select) => self
+                        .evaluate(instruction, &select, context, variables)?
+                        .into_node_set()?,
+                    None => context
+                        .node
+                        .children()
+                        .filter(|n| is_in_data_model(n))
+                        .collect(),
+                };
+                let nodes = self.sort(instruction, nodes, variables)?;
+                let mode = match get_attribute(instruction, "mode") {
+                    Some(mode) => Some(ExpandedName::resolve(instruction, &mode)?),
+                    None => None,
+                };
+                let parameters = self.parameters(instruction, context, variables)?;
+                self.apply_templates(nodes, mode.as_ref(), &parameters, parent)
+            },
+            "call-template" => {
+                let name =
+                    ExpandedName::resolve(instruction, &required_attribute(instruction, "name")?)?;
+                let stylesheet = self.stylesheet;
+                let template = stylesheet
+                    .templates
+                    .iter()
+                    .rev()
+                    .find(|template| template.name.as_ref() == Some(&name))
+                    .ok_or_else(|| {
+                        Error::Stylesheet(format!("no template is named {}", name.local))
+                    })?;
+                let parameters = self.parameters(instruction, context, variables)?;
+                self.instantiate_template(template, context, &parameters, parent)
+            },
+            "for-each" => {
+                let nodes = self
+                    .evaluate_attribute(instruction, "select", context, variables)?
+                    .into_node_set()?;
+                let nodes = self.sort(instruction, nodes, variables)?;
+                let size = nodes.len();
+                for (index, node) in nodes.into_iter().enumerate() {
+                    let context = Context {
+                        node,
+                        position: index + 1,
+                        size,
+                    };
+                    self.execute_children(instruction.upcast(), &context, variables, parent)?;
+                }
+                Ok(())
+            },
+            "value-of" => {
+                let value = self.evaluate_attribute(instruction, "select", context, variables)?;
+                self.append_text(parent, &value.string())
+            },
+            "text" => {
+                let text = instruction.upcast::<Node>().descendant_text_content();
+                self.append_text(parent, &text)
+            },
+            "if" => {
+                if self
+                    .evaluate_attribute(instruction, "test", context, variables)?
+                    .boolean()
+                {
+                    self.execute_children(instruction.upcast(), context, variables, parent)?;
+                }
+                Ok(())
+            },
+            "choose" => {
+                for child in instruction.upcast::<Node>().children() {
+                    let branch = match child.downcast::<Element>() {
+                        Some(branch) => branch,
+                        None => continue,
+                    };
+                    let taken = if is_xslt_element(branch, "when") {
+                        self.evaluate_attribute(branch, "test", context, variables)?
+                            .boolean()
+                    } else {
+                        is_xslt_element(branch, "otherwise")
+                    };
+                    if taken {
+                        return self.execute_children(&child, context, variables, parent);
+                    }
+                }
+                Ok(())
+            },
+            "variable" | "param" => {
+                let name = variable_name(instruction)?;
+                let value = self.variable_value(instruction, context, variables)?;
+                variables.push((name, value));
+                Ok(())
+            },
+            "copy" => self.copy(instruction, context, variables, parent),
+            "copy-of" => {
+                match self.evaluate_attribute(instruction, "select", context, variables)? {
+                    Value::NodeSet(nodes) => {
+                        for node in nodes {
+                            self.copy_of(&node, parent)?;
+                        }
+                        Ok(())
+                    },
+                    value => self.append_text(parent, &value.string()),
+                }
+            },
+            "element" => {
+                let name = self.attribute_value_template(
+                    instruction,
+                    &required_attribute(instruction, "name")?,
+                    context,
+                    variables,
+                )?;
+                let (prefix, local) = split_qname(&name)?;
+                let namespace = match get_attribute(instruction, "namespace") {
+                    Some(namespace) => Namespace::from(self.attribute_value_template(
+                        instruction,
+                        &namespace,
+                        context,
+                        variables,
+                    )?),
+                    None => lookup_namespace(instruction, prefix.as_ref())?,
+                };
+                let result = self.create_element(namespace, prefix, local);
+                self.append(parent, result.upcast())?;
+                self.execute_children(instruction.upcast(), context, variables, result.upcast())
+            },
+            "attribute" => {
+                let name = self.attribute_value_template(
+                    instruction,
+                    &required_attribute(instruction, "name")?,
+                    context,
+                    variables,
+                )?;
+                let (prefix, local) = split_qname(&name)?;
+                let namespace = match get_attribute(instruction, "namespace") {
+                    Some(namespace) => Namespace::from(self.attribute_value_template(
+                        instruction,
+                        &namespace,
+                        context,
+                        variables,
+                    )?),
+                    // Unprefixed attributes are in no namespace.
+                    None if prefix.is_none() => ns!(),
+                    None => lookup_namespace(instruction, prefix.as_ref())?,
+                };
+                let value = self.text_content(instruction, context, variables)?;
+                // Attributes can only be added to an element.
+                if let Some(element) = parent.downcast::<Element>() {
+                    set_attribute(element, QualName::new(prefix, namespace, local), value);
+                }
+                Ok(())
+            },
+            "comment" => {
+                let data = self.text_content(instruction, context, variables)?;
+                let comment = Comment::new(DOMString::from(data), self.document);
+                self.append(parent, comment.upcast())
+            },
+            "processing-instruction" => {
+                let target = self.attribute_value_template(
+                    instruction,
+                    &required_attribute(instruction, "name")?,
+                    context,
+                    variables,
+                )?;
+                let data = self.text_content(instruction, context, variables)?;
+                let pi = ProcessingInstruction::new(
+                    DOMString::from(target),
+                    DOMString::from(data),
+                    self.document,
+                );
+                self.append(parent, pi.upcast())
+            },
+            "number" => {
+                let number = match get_attribute(instruction, "value") {
+                    Some(value) => {
+                        let value = self.evaluate(instruction, &value, context, variables)?;
+                        xpath::number_to_string(value.number().round())
+                    },
+                    // With the default level and count, this is the position
+                    // of the current node among its siblings of the same kind.
+                    None => (1 + context
+                        .node
+                        .preceding_siblings()
+                        .filter(|sibling| same_kind(sibling, &context.node))
+                        .count())
+                    .to_string(),
+                };
+                self.append_text(parent, &number)
+            },
+            "message" => {
+                let message = self.text_content(instruction, context, variables)?;
+                debug!("xsl:message: {}", message);
+                match get_attribute(instruction, "terminate") {
+                    Some(ref terminate) if terminate == "yes" => Err(Error::Terminated),
+                    _ => Ok(()),
+                }
+            },
+            // These are handled by their parent instruction, and the fallback
+            // is only used for instructions from a later version of XSLT.
+            "sort" | "with-param" | "fallback" => Ok(()),
+            name => Err(Error::Stylesheet(format!(
+                "xsl:{} is not a supported instruction",
+                name
+            ))),
+        }
+    }
+
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#copying>
+    fn copy(
+        &mut self,
+        instruction: &Element,
+        context: &Context,
+        variables: &mut Variables,
+        parent: &Node,
+    ) -> Result<(), Error> {
+        let node = &context.node;
+        match node.type_id() {
+            NodeTypeId::Element(_) => {
+                let element = node.downcast::<Element>().unwrap();
+                let result = self.create_element(
+                    element.namespace().clone(),
+                    element.prefix().clone(),
+                    element.local_name().clone(),
+                );
+                self.append(parent, result.upcast())?;
+                self.execute_children(instruction.upcast(), context, variables, result.upcast())
+            },
+            NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) => {
+                self.execute_children(instruction.upcast(), context, variables, parent)
+            },
+            _ => self.copy_of(node, parent),
+        }
+    }
+
+    /// Appends a deep copy of `node` to `parent`.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#copy-of>
+    fn copy_of(&self, node: &Node, parent: &Node) -> Result<(), Error> {
+        match node.type_id() {
+            NodeTypeId::Attr => {
+                let attr = node.downcast::<Attr>().unwrap();
+                if let Some(element) = parent.downcast::<Element>() {
+                    let name = QualName::new(
+                        attr.prefix().cloned(),
+                        attr.namespace().clone(),
+                        attr.local_name().clone(),
+                    );
+                    set_attribute(element, name, String::from(&**attr.value()));
+                }
+                Ok(())
+            },
+            NodeTypeId::Document(_) | NodeTypeId::DocumentFragment(_) => {
+                for child in node.children().filter(|n| is_in_data_model(n)) {
+                    self.copy_of(&child, parent)?;
+                }
+                Ok(())
+            },
+            NodeTypeId::DocumentType => Ok(()),
+            _ => {
+                let copy = Node::clone(node, Some(self.document), CloneChildrenFlag::CloneChildren);
+                self.append(parent, &copy)
+            },
+        }
+    }
+
+    /// Evaluates the value of an `xsl:variable`, `xsl:param` or
+    /// `xsl:with-param` element.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#variable-values>
+    fn variable_value(
+        &mut self,
+        element: &Element,
+        context: &Context,
+        variables: &mut Variables,
+    ) -> Result<Value, Error> {
+        if let Some(select) = get_attribute(element, "select") {
+            return self.evaluate(element, &select, context, variables);
+        }
+        if element.upcast::<Node>().children_count() == 0 {
+            return Ok(Value::String(String::new()));
+        }
+        // A result tree fragment, which can be used like a node-set.
+        let fragment = self.document.CreateDocumentFragment();
+        self.execute_children(element.upcast(), context, variables, fragment.upcast())?;
+        fragment.upcast::<Node>().Normalize();
+        Ok(Value::NodeSet(vec![DomRoot::upcast(fragment)]))
+    }
+
+    /// Evaluates the `xsl:with-param` children of `instruction`.
+    fn parameters(
+        &mut self,
+        instruction: &Element,
+        context: &Context,
+        variables: &mut Variables,
+    ) -> Result<Variables, Error> {
+        let mut parameters = vec![];
+        for child in instruction.upcast::<Node>().children() {
+            let element = match child.downcast::<Element>() {
+                Some(element) if is_xslt_element(element, "with-param") => element,
+                _ => continue,
+            };
+            let name = variable_name(element)?;
+            let value = self.variable_value(element, context, variables)?;
+            parameters.push((name, value));
+        }
+        Ok(parameters)
+    }
+
+    /// Sorts `nodes` with the `xsl:sort` children of `instruction`, if any.
+    /// <https://www.w3.org/TR/1999/REC-xslt-19991116#sorting>
+    fn sort(
+        &self,
+        instruction: &Element,
+        nodes: Vec<DomRoot<Node>>,
+        variables: &[(ExpandedName, Value)],
+    ) -> Result<Vec<DomRoot<Node>>, Error> {
+        let sorts: Vec<DomRoot<Element>> = instruction
+            .upcast::<Node>()
+            .children()
+            .filter_map(DomRoot::downcast::<Element>)
+            .filter(|element| is_xslt_element(element, "sort"))
+            .collect();
+        if sorts.is_empty() {
+            return Ok(nodes);
+        }
+
+        let size = nodes.len();
+        let mut keyed = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let context = Context {
+                node,
+                position: index + 1,
+                size,
+            };
+            let mut keys = vec![];
+            for sort in &sorts {
+                let select = get_attribute(sort, "select").unwrap_or_else(|| String::from("."));
+                let value = self.evaluate(sort, &select, &context, variables)?;
+                keys.push(value);
+            }
+            keyed.push((keys, context.node));
+        }
+
+        let mut options = vec![];
+        for sort in &sorts {
+            let numeric = get_attribute(sort, "data-type").map_or(false, |t| t == "number");
+            let descending = get_attribute(sort, "order").map_or(false, |o| o == "descending");
+            options.push((numeric, descending));
+        }
+        // This is a stable sort, so nodes with equal keys stay in order.
+        keyed.sort_by(|&(ref a, _), &(ref b, _)| {
+            for (index, &(numeric, descending)) in options.iter().enumerate() {
+                let ordering = if numeric {
+                    compare_numbers(a[index].number(), b[index].number())
+                } else {
+                    a[index].string().cmp(&b[index].string())
+                };
+                let ordering = if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+        Ok(keyed.into_iter().map(|(_, node)| node).collect())
+    }
+
+    /// Instantiates the children of `instruction` and returns the text they
+    /// produce, as the content of `xsl:attribute`, `xsl:comment`,
+    /// `xsl:processing-instruction` and `xsl:message`.
+    fn text_content(
+        &mut self,
+        instruction: &Element,
+        context: &Context,
+        variables: &mut Variables,
+    ) -> Result<String, Error> {
+        let fragment = self.document.CreateDocumentFragment();
+        self.execute_children(instruction.upcast(), context, variables, fragment.upcast())?;
+        Ok(String::from(
+            fragment.upcast::<Node>().descendant_text_content(),
+        ))
+    }
+
+    fn create_element(
+        &self,
+        namespace: Namespace,
+        prefix: Option<Prefix>,
+        local_name: LocalName,
+    ) -> DomRoot<Element> {
+        let namespace = if self.html_output && namespace == ns!() {
+            ns!(html)
+        } else {
+            namespace
+        };
+        Element::create(
+            QualName::new(prefix, namespace, local_name),
+            None,
+            self.document,
+            ElementCreator::ScriptCreated,
+            CustomElementCreationMode::Synchronous,
+        )
+    }
+
+    fn append(&self, parent: &Node, child: &Node) -> Result<(), Error> {
+        parent
+            .AppendChild(child)
+            .map(|_| ())
+            .map_err(|error| Error::ResultTree(format!("{:?}", error)))
+    }
+
+    fn append_text(&self, parent: &Node, text: &str) -> Result<(), Error> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let text = Text::new(DOMString::from(text), self.document);
+        self.append(parent, text.upcast())
+    }
+}
+
+/// Sets an attribute on a result element, replacing any attribute with the
+/// same expanded name.
+fn set_attribute(element: &Element, name: QualName, value: String) {
+    element.remove_attribute(&name.ns, &name.local);
+    let prefix = name.prefix.clone();
+    element.set_attribute_from_parser(name, DOMString::from(value), prefix);
+}
+
+fn required_attribute(element: &Element, name: &str) -> Result<String, Error> {
+    get_attribute(element, name).ok_or_else(|| {
+        Error::Stylesheet(format!(
+            "xsl:{} needs a {} attribute",
+            element.local_name(),
+            name
+        ))
+    })
+}
+
+/// The expanded name of an `xsl:variable`, `xsl:param` or `xsl:with-param`.
+fn variable_name(element: &Element) -> Result<ExpandedName, Error> {
+    ExpandedName::resolve(element, &required_attribute(element, "name")?)
+}
+
+/// Splits the QName `name` into its prefix and local name.
+fn split_qname(name: &str) -> Result<(Option<Prefix>, LocalName), Error> {
+    let mut parts = name.trim().splitn(2, ':');
+    let first = parts.next().unwrap_or("");
+    let (prefix, local) = match parts.next() {
+        Some(local) => (Some(Prefix::from(first)), local),
+        None => (None, first),
+    };
+    if local.is_empty() || local.contains(':') {
+        return Err(Error::Stylesheet(format!("{} is not a valid QName", name)));
+    }
+    Ok((prefix, LocalName::from(local)))
+}
+
+/// Resolves the prefix of a name created by `xsl:element` or
+/// `xsl:attribute`, which uses the default namespace when there's no prefix.
+fn lookup_namespace(instruction: &Element, prefix: Option<&Prefix>) -> Result<Namespace, Error> {
+    let namespace = instruction
+        .upcast::<Node>()
+        .LookupNamespaceURI(prefix.map(|prefix| DOMString::from(&**prefix)));
+    match (namespace, prefix) {
+        (Some(namespace), _) => Ok(Namespace::from(String::from(namespace))),
+        (None, None) => Ok(ns!()),
+        (None, Some(prefix)) => Err(Error::Stylesheet(format!(
+            "unknown namespace prefix {}",
+            prefix
+        ))),
+    }
+}
+
+/// Whether two nodes would be counted together by `xsl:number`.
+fn same_kind(a: &Node, b: &Node) -> bool {
+    match (a.downcast::<Element>(), b.downcast::<Element>()) {
+        (Some(a), Some(b)) => a.namespace() == b.namespace() && a.local_name() == b.local_name(),
+        (None, None) => a.type_id() == b.type_id(),
+        _ => false,
+    }
+}
+
+/// Orders numbers for sorting, with NaN before every other number.
+fn compare_numbers(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).unwrap(),
+    }
+}
+
+/// Doctypes are not part of the XPath data model.
+fn is_in_data_model(node: &Node) -> bool {
+    node.type_id() != NodeTypeId::DocumentType
+}
+
+fn is_xml_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\r' || c == '\n'
+}
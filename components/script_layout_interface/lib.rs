@@ -15,6 +15,7 @@ extern crate malloc_size_of_derive;
 
 pub mod message;
 pub mod rpc;
+pub mod scrollbar;
 pub mod wrapper_traits;
 
 use atomic_refcell::AtomicRefCell;
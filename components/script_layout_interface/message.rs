@@ -13,8 +13,8 @@ use metrics::PaintTimeMetrics;
 use msg::constellation_msg::{BackgroundHangMonitorRegister, BrowsingContextId, PipelineId};
 use net_traits::image_cache::ImageCache;
 use profile_traits::mem::ReportsChan;
-use script_traits::Painter;
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
+use script_traits::{LayoutShift, Painter};
 use script_traits::{ScrollState, UntrustedNodeAddress, WindowSizeData};
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
@@ -24,7 +24,9 @@ use std::sync::Arc;
 use style::context::QuirksMode;
 use style::dom::OpaqueNode;
 use style::properties::PropertyId;
+use style::properties_and_values::registry::PropertyRegistration;
 use style::selector_parser::PseudoElement;
+use style::servo::media_queries::PrefersColorScheme;
 use style::stylesheets::Stylesheet;
 
 /// Asynchronous messages that script can send to layout.
@@ -98,6 +100,9 @@ pub enum Msg {
     /// Tells layout that script has added some paint worklet modules.
     RegisterPaint(Atom, Vec<Atom>, Box<dyn Painter>),
 
+    /// Tells layout that script has registered a custom property.
+    RegisterProperty(PropertyRegistration),
+
     /// Send to layout the precise time when the navigation started.
     SetNavigationStart(u64),
 
@@ -115,10 +120,16 @@ pub enum NodesFromPointQueryType {
 pub enum QueryMsg {
     ContentBoxQuery(OpaqueNode),
     ContentBoxesQuery(OpaqueNode),
+    /// The border boxes of a node, along with the transforms that map them to
+    /// the coordinate space of the initial containing block.
+    BoxQuadsQuery(OpaqueNode),
     ClientRectQuery(OpaqueNode),
     NodeScrollGeometryQuery(OpaqueNode),
     OffsetParentQuery(OpaqueNode),
     TextIndexQuery(OpaqueNode, Point2D<f32>),
+    /// The index of the character of a text node that is closest to a point,
+    /// relative to the initial containing block.
+    TextIndexAtPointQuery(OpaqueNode, Point2D<f32>),
     NodesFromPointQuery(Point2D<f32>, NodesFromPointQueryType),
 
     // FIXME(nox): The following queries use the TrustedNodeAddress to
@@ -127,6 +138,9 @@ pub enum QueryMsg {
     NodeScrollIdQuery(TrustedNodeAddress),
     ResolvedStyleQuery(TrustedNodeAddress, Option<PseudoElement>, PropertyId),
     StyleQuery(TrustedNodeAddress),
+    /// The style of the scrollbars of an element, with its `::-webkit-scrollbar`
+    /// pseudo-elements mapped onto it.
+    ScrollbarStyleQuery(TrustedNodeAddress),
     ElementInnerTextQuery(TrustedNodeAddress),
     InnerWindowDimensionsQuery(BrowsingContextId),
 }
@@ -148,16 +162,19 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::TextIndexAtPointQuery(..) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::BoxQuadsQuery(_) |
                 QueryMsg::ClientRectQuery(_) |
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
                 QueryMsg::ResolvedStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
-                QueryMsg::StyleQuery(_) => false,
+                QueryMsg::StyleQuery(_) |
+                QueryMsg::ScrollbarStyleQuery(_) => false,
             },
         }
     }
@@ -170,16 +187,19 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::TextIndexAtPointQuery(..) |
                 QueryMsg::ElementInnerTextQuery(_) => true,
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
+                QueryMsg::BoxQuadsQuery(_) |
                 QueryMsg::ClientRectQuery(_) |
                 QueryMsg::NodeScrollGeometryQuery(_) |
                 QueryMsg::NodeScrollIdQuery(_) |
                 QueryMsg::ResolvedStyleQuery(..) |
                 QueryMsg::OffsetParentQuery(_) |
                 QueryMsg::InnerWindowDimensionsQuery(_) |
-                QueryMsg::StyleQuery(_) => false,
+                QueryMsg::StyleQuery(_) |
+                QueryMsg::ScrollbarStyleQuery(_) => false,
             },
         }
     }
@@ -198,6 +218,10 @@ pub struct ReflowComplete {
     pub pending_images: Vec<PendingImage>,
     /// The list of nodes that initiated a CSS transition.
     pub newly_transitioning_nodes: Vec<UntrustedNodeAddress>,
+    /// Whether web fonts of the page are still loading.
+    pub pending_web_fonts: bool,
+    /// The layout shifts of the frames painted since the last reflow.
+    pub layout_shifts: Vec<LayoutShift>,
 }
 
 /// Information needed for a script-initiated reflow.
@@ -210,6 +234,8 @@ pub struct ScriptReflow {
     pub stylesheets_changed: bool,
     /// The current window size.
     pub window_size: WindowSizeData,
+    /// The color scheme that the user prefers.
+    pub prefers_color_scheme: PrefersColorScheme,
     /// The channel that we send a notification to.
     pub script_join_chan: Sender<ReflowComplete>,
     /// The goal of this reflow.
@@ -2,8 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::scrollbar::ScrollbarStyle;
 use app_units::Au;
-use euclid::default::Rect;
+use euclid::default::{Rect, SideOffsets2D, Transform3D};
 use euclid::Size2D;
 use script_traits::UntrustedNodeAddress;
 use servo_arc::Arc;
@@ -24,6 +25,8 @@ pub trait LayoutRPC {
     fn content_box(&self) -> ContentBoxResponse;
     /// Requests the dimensions of all the content boxes, as in the `getClientRects()` call.
     fn content_boxes(&self) -> ContentBoxesResponse;
+    /// Requests the border boxes of a node and their transforms, as in the `getBoxQuads()` call.
+    fn box_quads(&self) -> BoxQuadsResponse;
     /// Requests the geometry of this node. Used by APIs such as `clientTop`.
     fn node_geometry(&self) -> NodeGeometryResponse;
     /// Requests the scroll geometry of this node. Used by APIs such as `scrollTop`.
@@ -36,6 +39,9 @@ pub trait LayoutRPC {
     /// Requests the styles for an element. Contains a `None` value if the element is in a `display:
     /// none` subtree.
     fn style(&self) -> StyleResponse;
+    /// Requests the style of the scrollbars of an element. Contains a `None`
+    /// value if the element is not a scroll container.
+    fn scrollbar_style(&self) -> ScrollbarStyleResponse;
     fn text_index(&self) -> TextIndexResponse;
     /// Requests the list of nodes from the given point.
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress>;
@@ -49,6 +55,22 @@ pub struct ContentBoxResponse(pub Option<Rect<Au>>);
 
 pub struct ContentBoxesResponse(pub Vec<Rect<Au>>);
 
+/// The border box of a fragment of a node, with the widths of the margin,
+/// border and padding around it.
+#[derive(Clone)]
+pub struct BoxQuad {
+    /// The border box, relative to the stacking context of the fragment.
+    pub border_box: Rect<Au>,
+    pub margin: SideOffsets2D<Au>,
+    pub border: SideOffsets2D<Au>,
+    pub padding: SideOffsets2D<Au>,
+    /// The transform that maps the coordinate space of the stacking context of
+    /// the fragment to the one of the initial containing block.
+    pub transform: Transform3D<f32>,
+}
+
+pub struct BoxQuadsResponse(pub Vec<BoxQuad>);
+
 pub struct NodeGeometryResponse {
     pub client_rect: Rect<i32>,
 }
@@ -75,5 +97,8 @@ impl OffsetParentResponse {
 #[derive(Clone)]
 pub struct StyleResponse(pub Option<Arc<ComputedValues>>);
 
+#[derive(Clone)]
+pub struct ScrollbarStyleResponse(pub Option<ScrollbarStyle>);
+
 #[derive(Clone)]
 pub struct TextIndexResponse(pub Option<usize>);
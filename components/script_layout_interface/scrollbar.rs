@@ -0,0 +1,395 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The appearance and geometry of the scrollbars that layout paints for scroll
+//! containers. Script uses the same geometry to hit test them.
+
+use app_units::Au;
+use euclid::default::{Point2D, Rect, Size2D};
+pub use script_traits::SCROLLBAR_PAGE_RATIO;
+pub use script_traits::{OVERLAY_SCROLLBAR_FADE_DURATION, OVERLAY_SCROLLBAR_VISIBLE_DURATION};
+use servo_arc::Arc;
+use servo_config::pref;
+use style::computed_values::overflow_x::T as Overflow;
+use style::computed_values::scrollbar_width::T as ScrollbarWidth;
+use style::properties::ComputedValues;
+use style::values::computed::Size;
+use style::values::generics::ui::ScrollbarColor;
+use style::values::RGBA;
+use webrender_api::ColorF;
+
+/// How scrollbars are presented, as chosen by the embedder.
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
+pub enum ScrollbarMode {
+    /// Scrollbars with a track that are always visible.
+    Classic,
+    /// Thin scrollbars without a track that are only shown while scrolling.
+    Overlay,
+}
+
+impl ScrollbarMode {
+    /// The mode the embedder chose with the `layout.scrollbars.overlay` pref.
+    pub fn from_prefs() -> ScrollbarMode {
+        if pref!(layout.scrollbars.overlay) {
+            ScrollbarMode::Overlay
+        } else {
+            ScrollbarMode::Classic
+        }
+    }
+}
+
+/// The appearance of the scrollbars in a given mode.
+#[derive(Clone, Copy, Debug)]
+pub struct ScrollbarTheme {
+    pub mode: ScrollbarMode,
+    /// The thickness of `scrollbar-width: auto` scrollbars.
+    pub width: Au,
+    /// The thickness of `scrollbar-width: thin` scrollbars.
+    pub thin_width: Au,
+    /// The shortest a thumb gets.
+    pub min_thumb_length: Au,
+    /// The size of the corner that resizes an element.
+    pub resizer_size: Au,
+    /// The color of the thumbs when `scrollbar-color` is `auto`.
+    pub thumb_color: ColorF,
+    /// The color of the tracks when `scrollbar-color` is `auto`, if the
+    /// tracks are painted at all.
+    pub track_color: Option<ColorF>,
+}
+
+impl ScrollbarTheme {
+    pub fn new(mode: ScrollbarMode) -> ScrollbarTheme {
+        match mode {
+            ScrollbarMode::Classic => ScrollbarTheme {
+                mode,
+                width: Au::from_px(12),
+                thin_width: Au::from_px(8),
+                min_thumb_length: Au::from_px(16),
+                resizer_size: Au::from_px(15),
+                thumb_color: ColorF::new(0.5, 0.5, 0.5, 0.8),
+                track_color: Some(ColorF::new(0.9, 0.9, 0.9, 0.8)),
+            },
+            ScrollbarMode::Overlay => ScrollbarTheme {
+                mode,
+                width: Au::from_px(8),
+                thin_width: Au::from_px(6),
+                min_thumb_length: Au::from_px(16),
+                resizer_size: Au::from_px(15),
+                thumb_color: ColorF::new(0.0, 0.0, 0.0, 0.5),
+                track_color: None,
+            },
+        }
+    }
+
+    /// The thickness of the scrollbars for a `scrollbar-width` value.
+    pub fn thickness(&self, scrollbar_width: ScrollbarWidth) -> Au {
+        match scrollbar_width {
+            ScrollbarWidth::Auto => self.width,
+            ScrollbarWidth::Thin => self.thin_width,
+            ScrollbarWidth::None => Au(0),
+        }
+    }
+
+    /// The opacity of the scrollbars `elapsed` seconds after their scroll
+    /// container last scrolled, or `None` if it never did.
+    pub fn opacity(&self, elapsed: Option<f64>) -> f32 {
+        match self.mode {
+            ScrollbarMode::Classic => 1.,
+            ScrollbarMode::Overlay => script_traits::overlay_scrollbar_opacity(elapsed),
+        }
+    }
+
+    /// The `scrollbar-width` that a `::-webkit-scrollbar` pseudo-element maps
+    /// onto, given whether it is displayed and the thickness it asks for.
+    pub fn webkit_scrollbar_width(&self, displayed: bool, thickness: Option<Au>) -> ScrollbarWidth {
+        match thickness {
+            _ if !displayed => ScrollbarWidth::None,
+            Some(thickness) if thickness <= Au(0) => ScrollbarWidth::None,
+            Some(thickness) if thickness <= self.thin_width => ScrollbarWidth::Thin,
+            _ => ScrollbarWidth::Auto,
+        }
+    }
+}
+
+/// The styles of the `::-webkit-scrollbar`, `::-webkit-scrollbar-thumb` and
+/// `::-webkit-scrollbar-track` pseudo-elements of a scroll container, for the
+/// ones that some rule applies to.
+#[derive(Default)]
+pub struct WebkitScrollbarStyles {
+    pub scrollbar: Option<Arc<ComputedValues>>,
+    pub thumb: Option<Arc<ComputedValues>>,
+    pub track: Option<Arc<ComputedValues>>,
+}
+
+/// The scrollbar-width and scrollbar-color of a scroll container, once its
+/// `::-webkit-scrollbar` pseudo-elements are mapped onto the ones that are
+/// `auto`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollbarStyle {
+    pub width: ScrollbarWidth,
+    /// The color of the thumbs, if not the one of the theme.
+    pub thumb_color: Option<RGBA>,
+    /// The color of the tracks, if not the one of the theme.
+    pub track_color: Option<RGBA>,
+}
+
+impl ScrollbarStyle {
+    pub fn new(
+        theme: &ScrollbarTheme,
+        style: &ComputedValues,
+        webkit_styles: &WebkitScrollbarStyles,
+    ) -> ScrollbarStyle {
+        let width = match (
+            style.get_ui().clone_scrollbar_width(),
+            &webkit_styles.scrollbar,
+        ) {
+            (ScrollbarWidth::Auto, &Some(ref scrollbar)) => {
+                // The width applies to vertical scrollbars and the height to
+                // horizontal ones, but both share one thickness here.
+                let position = scrollbar.get_position();
+                let thickness = [position.clone_width(), position.clone_height()]
+                    .iter()
+                    .filter_map(|size| match *size {
+                        Size::LengthPercentage(ref length) => length.0.to_length().map(Au::from),
+                        Size::Auto => None,
+                    })
+                    .min();
+                let displayed = !scrollbar.get_box().clone_display().is_none();
+                theme.webkit_scrollbar_width(displayed, thickness)
+            },
+            (width, _) => width,
+        };
+        let (thumb_color, track_color) = match style.get_inherited_ui().clone_scrollbar_color() {
+            ScrollbarColor::Colors { thumb, track } => (
+                Some(style.resolve_color(thumb)),
+                Some(style.resolve_color(track)),
+            ),
+            ScrollbarColor::Auto => (
+                webkit_styles.thumb.as_ref().and_then(background_color),
+                webkit_styles
+                    .track
+                    .as_ref()
+                    .and_then(background_color)
+                    .or_else(|| webkit_styles.scrollbar.as_ref().and_then(background_color)),
+            ),
+        };
+        ScrollbarStyle {
+            width,
+            thumb_color,
+            track_color,
+        }
+    }
+}
+
+/// The background color of a `::-webkit-scrollbar` pseudo-element, unless it
+/// is transparent, as it is when no rule sets it.
+fn background_color(style: &Arc<ComputedValues>) -> Option<RGBA> {
+    let color = style.resolve_color(style.get_background().clone_background_color());
+    if color.alpha == 0 {
+        None
+    } else {
+        Some(color)
+    }
+}
+
+/// The direction a scrollbar scrolls in.
+#[derive(Clone, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq)]
+pub enum ScrollbarAxis {
+    Horizontal,
+    Vertical,
+}
+
+/// What the scrollbars of a scroll container depend on.
+pub struct ScrollContainer {
+    /// The padding box of the scroll container, over which the scrollbars
+    /// are painted.
+    pub padding_box: Rect<Au>,
+    /// How far the content can be scrolled along each axis.
+    pub max_scroll: Size2D<Au>,
+    /// How far the content is scrolled along each axis.
+    pub scroll_position: Point2D<Au>,
+    pub overflow_x: Overflow,
+    pub overflow_y: Overflow,
+    /// The thickness of the scrollbars.
+    pub thickness: Au,
+    /// Whether a resizer is painted in the bottom-right corner.
+    pub resizable: bool,
+}
+
+/// A scrollbar, in the coordinates of the padding box of its scroll container.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scrollbar {
+    pub axis: ScrollbarAxis,
+    pub track: Rect<Au>,
+    pub thumb: Rect<Au>,
+    /// How far the content can be scrolled along the axis of the scrollbar.
+    pub max_scroll: Au,
+}
+
+impl Scrollbar {
+    fn new(
+        axis: ScrollbarAxis,
+        track: Rect<Au>,
+        visible_length: Au,
+        max_scroll: Au,
+        position: Au,
+        min_thumb_length: Au,
+    ) -> Scrollbar {
+        let track_length = along(axis, track.size);
+        let (offset, length) = if max_scroll <= Au(0) {
+            (Au(0), track_length)
+        } else {
+            let content_length = visible_length + max_scroll;
+            let length = track_length
+                .scale_by(visible_length.to_f32_px() / content_length.to_f32_px())
+                .max(min_thumb_length)
+                .min(track_length);
+            let position = position.max(Au(0)).min(max_scroll);
+            let offset =
+                (track_length - length).scale_by(position.to_f32_px() / max_scroll.to_f32_px());
+            (offset, length)
+        };
+        let thumb = match axis {
+            ScrollbarAxis::Horizontal => Rect::new(
+                Point2D::new(track.origin.x + offset, track.origin.y),
+                Size2D::new(length, track.size.height),
+            ),
+            ScrollbarAxis::Vertical => Rect::new(
+                Point2D::new(track.origin.x, track.origin.y + offset),
+                Size2D::new(track.size.width, length),
+            ),
+        };
+        Scrollbar {
+            axis,
+            track,
+            thumb,
+            max_scroll,
+        }
+    }
+
+    /// How far the thumb can move along the track.
+    pub fn thumb_travel(&self) -> Au {
+        (along(self.axis, self.track.size) - along(self.axis, self.thumb.size)).max(Au(0))
+    }
+
+    /// How far the content is scrolled per unit of thumb movement.
+    pub fn scroll_per_thumb_offset(&self) -> f32 {
+        let travel = self.thumb_travel();
+        if travel <= Au(0) {
+            return 0.;
+        }
+        self.max_scroll.to_f32_px() / travel.to_f32_px()
+    }
+
+    /// Whether `point` is on the track before the thumb, after it, or on
+    /// neither, as the sign of the direction a click there pages in.
+    pub fn page_direction(&self, point: Point2D<Au>) -> i32 {
+        if !self.track.contains(point) || self.thumb.contains(point) {
+            return 0;
+        }
+        let (point, thumb_start) = match self.axis {
+            ScrollbarAxis::Horizontal => (point.x, self.thumb.origin.x),
+            ScrollbarAxis::Vertical => (point.y, self.thumb.origin.y),
+        };
+        if point < thumb_start {
+            -1
+        } else {
+            1
+        }
+    }
+}
+
+/// The scrollbars and resizer of a scroll container.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollbarGeometry {
+    pub horizontal: Option<Scrollbar>,
+    pub vertical: Option<Scrollbar>,
+    /// The corner where the scrollbars meet, which holds the resizer if there
+    /// is one.
+    pub corner: Option<Rect<Au>>,
+}
+
+impl ScrollbarGeometry {
+    pub fn new(theme: &ScrollbarTheme, container: &ScrollContainer) -> ScrollbarGeometry {
+        let padding_box = container.padding_box;
+        let thickness = container.thickness;
+        let has_scrollbar = |overflow: Overflow, max_scroll: Au| {
+            thickness > Au(0) &&
+                (overflow == Overflow::Scroll ||
+                    overflow == Overflow::Auto && max_scroll > Au(0))
+        };
+        let horizontal = has_scrollbar(container.overflow_x, container.max_scroll.width);
+        let vertical = has_scrollbar(container.overflow_y, container.max_scroll.height);
+
+        let corner_size = if container.resizable {
+            thickness.max(theme.resizer_size)
+        } else {
+            thickness
+        };
+        let corner = if container.resizable || horizontal && vertical {
+            Some(Rect::new(
+                Point2D::new(
+                    padding_box.max_x() - corner_size,
+                    padding_box.max_y() - corner_size,
+                ),
+                Size2D::new(corner_size, corner_size),
+            ))
+        } else {
+            None
+        };
+        let reserved = corner.map_or(Au(0), |_| corner_size);
+
+        let horizontal = if horizontal {
+            Some(Scrollbar::new(
+                ScrollbarAxis::Horizontal,
+                Rect::new(
+                    Point2D::new(padding_box.origin.x, padding_box.max_y() - thickness),
+                    Size2D::new(padding_box.size.width - reserved, thickness),
+                ),
+                padding_box.size.width,
+                container.max_scroll.width,
+                container.scroll_position.x,
+                theme.min_thumb_length,
+            ))
+        } else {
+            None
+        };
+        let vertical = if vertical {
+            Some(Scrollbar::new(
+                ScrollbarAxis::Vertical,
+                Rect::new(
+                    Point2D::new(padding_box.max_x() - thickness, padding_box.origin.y),
+                    Size2D::new(thickness, padding_box.size.height - reserved),
+                ),
+                padding_box.size.height,
+                container.max_scroll.height,
+                container.scroll_position.y,
+                theme.min_thumb_length,
+            ))
+        } else {
+            None
+        };
+
+        ScrollbarGeometry {
+            horizontal,
+            vertical,
+            corner,
+        }
+    }
+
+    /// The scrollbar at `point`, if any.
+    pub fn scrollbar_at(&self, point: Point2D<Au>) -> Option<Scrollbar> {
+        self.horizontal
+            .into_iter()
+            .chain(self.vertical)
+            .find(|scrollbar| scrollbar.track.contains(point))
+    }
+}
+
+fn along(axis: ScrollbarAxis, size: Size2D<Au>) -> Au {
+    match axis {
+        ScrollbarAxis::Horizontal => size.width,
+        ScrollbarAxis::Vertical => size.height,
+    }
+}
@@ -4,6 +4,7 @@
 
 #![allow(unsafe_code)]
 
+use crate::scrollbar::{ScrollbarMode, ScrollbarStyle, ScrollbarTheme, WebkitScrollbarStyles};
 use crate::HTMLCanvasData;
 use crate::HTMLMediaData;
 use crate::LayoutNodeType;
@@ -16,14 +17,17 @@ use msg::constellation_msg::{BrowsingContextId, PipelineId};
 use net_traits::image::base::{Image, ImageMetadata};
 use range::Range;
 use servo_arc::Arc;
+use servo_config::pref;
 use servo_url::ServoUrl;
 use std::fmt::Debug;
 use std::sync::Arc as StdArc;
 use style::attr::AttrValue;
+use style::computed_values::overflow_x::T as Overflow;
 use style::context::SharedStyleContext;
 use style::data::ElementData;
 use style::dom::OpaqueNode;
 use style::dom::{LayoutIterator, NodeInfo, TElement, TNode};
+use style::element_state::ElementState;
 use style::font_metrics::ServoMetricsProvider;
 use style::properties::ComputedValues;
 use style::selector_parser::{PseudoElement, PseudoElementCascadeType, SelectorImpl};
@@ -191,6 +195,10 @@ pub trait ThreadSafeLayoutNode:
     /// the parent until all the children have been processed.
     fn parent_style(&self) -> Arc<ComputedValues>;
 
+    /// Returns the `::selection` style for a text node, which is the one of
+    /// its parent. The same caveats as for `parent_style` apply.
+    fn parent_selected_style(&self) -> Arc<ComputedValues>;
+
     fn get_before_pseudo(&self) -> Option<Self> {
         self.as_element()
             .and_then(|el| el.get_before_pseudo())
@@ -247,11 +255,16 @@ pub trait ThreadSafeLayoutNode:
             el.selected_style()
         } else {
             debug_assert!(self.is_text_node());
-            // TODO(stshine): What should the selected style be for text?
-            self.parent_style()
+            self.parent_selected_style()
         }
     }
 
+    /// Returns the style of the `::backdrop` of the node, which only exists
+    /// while it is a modal element in the top layer.
+    fn backdrop_style(&self, context: &SharedStyleContext) -> Option<Arc<ComputedValues>> {
+        self.as_element().and_then(|el| el.backdrop_style(context))
+    }
+
     fn is_ignorable_whitespace(&self, context: &SharedStyleContext) -> bool;
 
     /// Returns true if this node contributes content. This is used in the implementation of
@@ -466,6 +479,66 @@ pub trait ThreadSafeLayoutElement:
             .clone()
     }
 
+    #[inline]
+    fn backdrop_style(&self, context: &SharedStyleContext) -> Option<Arc<ComputedValues>> {
+        let element = unsafe { self.unsafe_get() };
+        if self.get_pseudo_element_type() != PseudoElementType::Normal ||
+            !element.state().contains(ElementState::IN_MODAL_STATE)
+        {
+            return None;
+        }
+        context.stylist.lazily_compute_pseudo_element_style(
+            &context.guards,
+            element,
+            &PseudoElement::Backdrop,
+            RuleInclusion::All,
+            self.style_data().styles.primary(),
+            /* is_probe = */ false,
+            &ServoMetricsProvider,
+            /* matching_func = */ None,
+        )
+    }
+
+    /// The style of the scrollbars of the element, with its
+    /// `::-webkit-scrollbar` pseudo-elements mapped onto it, if it is a scroll
+    /// container.
+    fn scrollbar_style(&self, context: &SharedStyleContext) -> Option<ScrollbarStyle> {
+        if !pref!(layout.scrollbars.enabled) ||
+            self.get_pseudo_element_type() != PseudoElementType::Normal
+        {
+            return None;
+        }
+        let data = self.style_data();
+        let style = data.styles.primary();
+        let box_ = style.get_box();
+        if box_.overflow_x == Overflow::Visible && box_.overflow_y == Overflow::Visible {
+            return None;
+        }
+        let element = unsafe { self.unsafe_get() };
+        let pseudo_style = |pseudo: &PseudoElement| {
+            context.stylist.lazily_compute_pseudo_element_style(
+                &context.guards,
+                element,
+                pseudo,
+                RuleInclusion::All,
+                style,
+                /* is_probe = */ true,
+                &ServoMetricsProvider,
+                /* matching_func = */ None,
+            )
+        };
+        let webkit_styles = WebkitScrollbarStyles {
+            scrollbar: pseudo_style(&PseudoElement::WebkitScrollbar),
+            thumb: pseudo_style(&PseudoElement::WebkitScrollbarThumb),
+            track: pseudo_style(&PseudoElement::WebkitScrollbarTrack),
+        };
+        Some(ScrollbarStyle::new(
+            &ScrollbarTheme::new(ScrollbarMode::from_prefs()),
+            style,
+            &webkit_styles,
+        ))
+    }
+
     /// Returns the already resolved style of the node.
     ///
     /// This differs from `style(ctx)` in that if the pseudo-element has not yet
@@ -45,21 +45,25 @@ use msg::constellation_msg::{
     BlobId, BrowsingContextId, HistoryStateId, MessagePortId, PipelineId,
 };
 use msg::constellation_msg::{PipelineNamespaceId, TopLevelBrowsingContextId};
+use net_traits::filemanager_thread::SelectedFile;
 use net_traits::image::base::Image;
 use net_traits::image_cache::ImageCache;
 use net_traits::request::Referrer;
 use net_traits::storage_thread::StorageType;
+use net_traits::user_agent_data::UserAgentOverride;
 use net_traits::{FetchResponseMsg, ReferrerPolicy, ResourceThreads};
 use pixels::PixelFormat;
 use profile_traits::mem;
 use profile_traits::time as profile_time;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use servo_atoms::Atom;
+use servo_config::prefs::PrefValue;
 use servo_url::ImmutableOrigin;
 use servo_url::ServoUrl;
 use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use style_traits::CSSPixel;
@@ -72,7 +76,7 @@ use webrender_api::{BuiltDisplayListDescriptor, HitTestFlags, HitTestResult, Res
 use webvr_traits::{WebVREvent, WebVRMsg};
 
 pub use crate::script_msg::{
-    DOMMessage, HistoryEntryReplacement, SWManagerMsg, SWManagerSenders, ScopeThings,
+    CrashReport, DOMMessage, HistoryEntryReplacement, SWManagerMsg, SWManagerSenders, ScopeThings,
     ServiceWorkerMsg,
 };
 pub use crate::script_msg::{
@@ -146,6 +150,53 @@ pub enum LoadOrigin {
     Script(ImmutableOrigin),
 }
 
+/// How a load came to happen, which navigation timing reports.
+/// <https://w3c.github.io/navigation-timing/#dom-navigationtype>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum NavigationType {
+    /// A load that none of the other types is for.
+    Navigate,
+    /// A reload of the document of the browsing context.
+    Reload,
+    /// A load of a document of the session history when traversing it.
+    BackForward,
+}
+
+/// Whether the scroll positions of a session history entry are restored when
+/// traversing to it.
+/// <https://html.spec.whatwg.org/multipage/#scroll-restoration-mode>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum ScrollRestorationMode {
+    /// The user agent restores the scroll positions.
+    Auto,
+    /// The page takes care of restoring the scroll positions.
+    Manual,
+}
+
+/// The state of a document that is persisted in a session history entry when
+/// the document leaves it, and restored when the entry is traversed to.
+/// <https://html.spec.whatwg.org/multipage/#she-other>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PersistedUserState {
+    /// The scroll restoration mode of the entry.
+    pub scroll_restoration_mode: ScrollRestorationMode,
+    /// The scroll position of the viewport.
+    pub viewport_scroll_position: Vector2D<f32, LayoutPixel>,
+    /// The scroll positions of the scrolled elements, which are identified by
+    /// the indices of the element and its ancestors among their siblings.
+    pub element_scroll_positions: Vec<(Vec<u32>, Vector2D<f32, LayoutPixel>)>,
+}
+
+impl Default for PersistedUserState {
+    fn default() -> PersistedUserState {
+        PersistedUserState {
+            scroll_restoration_mode: ScrollRestorationMode::Auto,
+            viewport_scroll_position: Vector2D::zero(),
+            element_scroll_positions: vec![],
+        }
+    }
+}
+
 /// can be passed to `LoadUrl` to load a page with GET/POST
 /// parameters or headers
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -179,6 +230,11 @@ pub struct LoadData {
 
     /// The source to use instead of a network response for a srcdoc document.
     pub srcdoc: String,
+    /// How the load came to happen.
+    pub navigation_type: NavigationType,
+    /// The persisted state of the session history entry that is loaded, when
+    /// reloading the discarded document of the entry.
+    pub persisted_user_state: Option<PersistedUserState>,
 }
 
 /// The result of evaluating a javascript scheme url.
@@ -211,6 +267,8 @@ impl LoadData {
             referrer: referrer,
             referrer_policy: referrer_policy,
             srcdoc: "".to_string(),
+            navigation_type: NavigationType::Navigate,
+            persisted_user_state: None,
         }
     }
 }
@@ -274,6 +332,32 @@ pub enum ProgressiveWebMetricType {
     TimeToInteractive,
 }
 
+/// How much the boxes of a page moved between two frames.
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift>
+#[derive(Clone, Debug)]
+pub struct LayoutShift {
+    /// When the frame was laid out, in nanoseconds.
+    pub time: u64,
+    /// The layout shift score of the frame.
+    pub score: f64,
+    /// The nodes that moved the most.
+    pub sources: Vec<LayoutShiftSource>,
+}
+
+/// A node that moved between two frames.
+/// <https://wicg.github.io/layout-instability/#sec-layout-shift-attribution>
+#[derive(Clone, Debug)]
+pub struct LayoutShiftSource {
+    /// The node that moved.
+    pub node: UntrustedNodeAddress,
+    /// The visible part of the border box of the node in the previous frame,
+    /// relative to the viewport, in CSS pixels.
+    pub previous_rect: euclid::default::Rect<f32>,
+    /// The visible part of the border box of the node in the current frame,
+    /// relative to the viewport, in CSS pixels.
+    pub current_rect: euclid::default::Rect<f32>,
+}
+
 /// The reason why the pipeline id of an iframe is being updated.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
 pub enum UpdatePipelineIdReason {
@@ -303,6 +387,9 @@ pub enum ConstellationControlMsg {
     ExitFullScreen(PipelineId),
     /// Notifies the script that the document associated with this pipeline should 'unload'.
     UnloadDocument(PipelineId),
+    /// Sets when the unload event of the previous document of the browsing context of a
+    /// pipeline started and ended, in nanoseconds, for the navigation timing of its document.
+    SetUnloadEventTiming(PipelineId, u64, u64),
     /// Notifies the script that a pipeline should be closed.
     ExitPipeline(PipelineId, DiscardBrowsingContext),
     /// Notifies the script that the whole thread should be closed.
@@ -358,8 +445,14 @@ pub enum ConstellationControlMsg {
         PipelineId,
         UpdatePipelineIdReason,
     ),
-    /// Updates the history state and url of a given pipeline.
-    UpdateHistoryState(PipelineId, Option<HistoryStateId>, ServoUrl),
+    /// Updates the history state and url of a given pipeline, and restores the
+    /// persisted state of the session history entry traversed to.
+    UpdateHistoryState(
+        PipelineId,
+        Option<HistoryStateId>,
+        ServoUrl,
+        PersistedUserState,
+    ),
     /// Removes inaccesible history states.
     RemoveHistoryStates(PipelineId, Vec<HistoryStateId>),
     /// Set an iframe to be focused. Used when an element in an iframe gains focus.
@@ -369,6 +462,9 @@ pub enum ConstellationControlMsg {
     WebDriverScriptCommand(PipelineId, WebDriverScriptCommand),
     /// Notifies script thread that all animations are done
     TickAllAnimations(PipelineId),
+    /// Notifies the script thread that the dedicated workers of the pipeline
+    /// that wait for an animation frame can run their callbacks.
+    TickWorkerAnimationFrames(PipelineId),
     /// Notifies the script thread of a transition end
     TransitionEnd(UntrustedNodeAddress, String, f64),
     /// Notifies the script thread that a new Web font has been loaded, and thus the page should be
@@ -403,6 +499,33 @@ pub enum ConstellationControlMsg {
     PaintMetric(PipelineId, ProgressiveWebMetricType, u64),
     /// Notifies the media session about a user requested media session action.
     MediaSessionAction(PipelineId, MediaSessionActionType),
+    /// Notifies the script thread that the embedder changed a preference.
+    SetPreference(String, PrefValue),
+    /// Notifies the script thread that the network connection changed.
+    SetNetworkStatus(NetworkStatus),
+    /// Notifies the script thread of a reading of the sensors of the device,
+    /// for the documents of the given browser.
+    DeviceSensorReading(TopLevelBrowsingContextId, DeviceSensorReading),
+    /// Notifies the script thread of the user agent that the webview of a
+    /// pipeline is overridden with, if any.
+    SetUserAgentOverride(PipelineId, Option<UserAgentOverride>),
+    /// Notifies the script thread of the device that the webview of a
+    /// pipeline emulates, if any.
+    SetDeviceEmulation(PipelineId, Option<DeviceEmulation>),
+    /// Notifies the script thread of how a pipeline is throttled because its
+    /// frame is far from the viewport, or that it isn't throttled anymore.
+    SetFrameThrottling(PipelineId, Option<FrameThrottlingPolicy>),
+    /// Gets the rectangle of an area of the document of a pipeline that a
+    /// screenshot is requested of, relative to its viewport, or `None` if
+    /// there is no such area.
+    GetScreenshotRect(
+        PipelineId,
+        ScreenshotArea,
+        IpcSender<Option<Rect<f32, CSSPixel>>>,
+    ),
+    /// Prefetches a document that the document of a pipeline links to, at the
+    /// request of the embedder.
+    Prefetch(PipelineId, ServoUrl),
 }
 
 impl fmt::Debug for ConstellationControlMsg {
@@ -415,6 +538,7 @@ impl fmt::Debug for ConstellationControlMsg {
             Resize(..) => "Resize",
             ResizeInactive(..) => "ResizeInactive",
             UnloadDocument(..) => "UnloadDocument",
+            SetUnloadEventTiming(..) => "SetUnloadEventTiming",
             ExitPipeline(..) => "ExitPipeline",
             ExitScriptThread => "ExitScriptThread",
             SendEvent(..) => "SendEvent",
@@ -432,6 +556,7 @@ impl fmt::Debug for ConstellationControlMsg {
             FocusIFrame(..) => "FocusIFrame",
             WebDriverScriptCommand(..) => "WebDriverScriptCommand",
             TickAllAnimations(..) => "TickAllAnimations",
+            TickWorkerAnimationFrames(..) => "TickWorkerAnimationFrames",
             TransitionEnd(..) => "TransitionEnd",
             WebFontLoaded(..) => "WebFontLoaded",
             DispatchIFrameLoadEvent { .. } => "DispatchIFrameLoadEvent",
@@ -442,6 +567,14 @@ impl fmt::Debug for ConstellationControlMsg {
             PaintMetric(..) => "PaintMetric",
             ExitFullScreen(..) => "ExitFullScreen",
             MediaSessionAction(..) => "MediaSessionAction",
+            SetPreference(..) => "SetPreference",
+            SetNetworkStatus(..) => "SetNetworkStatus",
+            DeviceSensorReading(..) => "DeviceSensorReading",
+            SetUserAgentOverride(..) => "SetUserAgentOverride",
+            SetDeviceEmulation(..) => "SetDeviceEmulation",
+            SetFrameThrottling(..) => "SetFrameThrottling",
+            GetScreenshotRect(..) => "GetScreenshotRect",
+            Prefetch(..) => "Prefetch",
         };
         write!(formatter, "ConstellationControlMsg::{}", variant)
     }
@@ -468,6 +601,10 @@ pub enum AnimationState {
     NoAnimationsPresent,
     /// No animations are active but callbacks are queued
     NoAnimationCallbacksPresent,
+    /// Dedicated workers of the pipeline have animation callbacks queued
+    WorkerAnimationCallbacksPresent,
+    /// No dedicated worker of the pipeline has animation callbacks queued
+    NoWorkerAnimationCallbacksPresent,
 }
 
 /// The type of input represented by a multi-touch event.
@@ -490,7 +627,7 @@ pub enum TouchEventType {
 pub struct TouchId(pub i32);
 
 /// The mouse button involved in the event.
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum MouseButton {
     /// The left mouse button.
     Left = 1,
@@ -557,6 +694,13 @@ pub enum CompositorEvent {
         // Bitmask of MouseButton values representing the currently pressed buttons
         u16,
     ),
+    /// The mouse was moved by a delta, in CSS pixels, while the pointer is
+    /// locked and the cursor stays in place.
+    PointerLockedMoveEvent(
+        Vector2D<f32, UnknownUnit>,
+        // Bitmask of MouseButton values representing the currently pressed buttons
+        u16,
+    ),
     /// A touch event was generated with a touch ID and location.
     TouchEvent(
         TouchEventType,
@@ -566,10 +710,54 @@ pub enum CompositorEvent {
     ),
     /// A wheel event was generated with a delta in the X, Y, and/or Z directions
     WheelEvent(WheelDelta, Point2D<f32>, Option<UntrustedNodeAddress>),
+    /// A point on the node was double-tapped, which zooms to the block it is in.
+    DoubleTapEvent(Option<UntrustedNodeAddress>),
     /// A key was pressed.
     KeyboardEvent(KeyboardEvent),
     /// An event from the IME is dispatched.
     CompositionEvent(CompositionEvent),
+    /// A drag from outside of the window moved over a point, or left, or was
+    /// dropped.
+    DragEvent(
+        DragEventType,
+        Point2D<f32>,
+        Option<UntrustedNodeAddress>,
+        DragData,
+    ),
+}
+
+/// The events of a drag that started outside of the window.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DragEventType {
+    /// The drag entered the window, or a document in it.
+    Enter,
+    /// The drag moved.
+    Over,
+    /// The drag left the window, or the document, or was cancelled.
+    Leave,
+    /// The drag was dropped.
+    Drop,
+}
+
+/// The data of a drag that started outside of the window.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DragData {
+    /// The dragged strings, and their types such as `text/plain`.
+    pub strings: Vec<(String, String)>,
+    pub files: Vec<DraggedFile>,
+}
+
+/// A file that is dragged into the window.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum DraggedFile {
+    /// A file on the disk, as the embedder gives it. Documents never see the
+    /// paths of the files.
+    Path(PathBuf),
+    /// The name of a file that documents can't read before it is dropped.
+    Hidden(String),
+    /// A file that was dropped on a document, and that the file manager keeps
+    /// for it.
+    Dropped(SelectedFile),
 }
 
 /// Requests a TimerEvent-Message be sent after the given duration.
@@ -770,6 +958,8 @@ pub enum AnimationTickType {
     Script,
     /// The layout thread.
     Layout,
+    /// The dedicated workers of the script thread.
+    Worker,
 }
 
 /// The scroll state of a stacking context.
@@ -781,6 +971,67 @@ pub struct ScrollState {
     pub scroll_offset: Vector2D<f32, LayoutPixel>,
 }
 
+/// How much of the visible area a click on a scrollbar track, or a key that
+/// pages, scrolls by.
+pub const SCROLLBAR_PAGE_RATIO: f32 = 0.875;
+
+/// How long overlay scrollbars stay visible after their scroll node scrolled,
+/// in seconds.
+pub const OVERLAY_SCROLLBAR_VISIBLE_DURATION: f64 = 1.0;
+
+/// How long overlay scrollbars take to fade out, in seconds.
+pub const OVERLAY_SCROLLBAR_FADE_DURATION: f64 = 0.5;
+
+/// The opacity of overlay scrollbars `elapsed` seconds after their scroll node
+/// last scrolled, or `None` if it never did.
+pub fn overlay_scrollbar_opacity(elapsed: Option<f64>) -> f32 {
+    match elapsed {
+        Some(elapsed) if elapsed < OVERLAY_SCROLLBAR_VISIBLE_DURATION => 1.,
+        Some(elapsed)
+            if elapsed < OVERLAY_SCROLLBAR_VISIBLE_DURATION + OVERLAY_SCROLLBAR_FADE_DURATION =>
+        {
+            (1. - (elapsed - OVERLAY_SCROLLBAR_VISIBLE_DURATION) / OVERLAY_SCROLLBAR_FADE_DURATION)
+                as f32
+        },
+        _ => 0.,
+    }
+}
+
+/// A scrollbar thumb that layout painted, which the compositor moves along its
+/// track as its scroll node scrolls so that the display list doesn't need to
+/// be rebuilt.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScrollbarThumb {
+    /// The scroll node that the thumb follows.
+    pub scroll_id: ExternalScrollId,
+    /// Whether the thumb moves vertically rather than horizontally.
+    pub vertical: bool,
+    /// The id of the property binding of the transform that moves the thumb.
+    pub transform_binding: u64,
+    /// The id of the property binding of the opacity of the thumb, if it is
+    /// an overlay scrollbar that fades out.
+    pub opacity_binding: Option<u64>,
+    /// How far the thumb moves per pixel that the scroll node scrolls.
+    pub thumb_offset_per_scroll: f32,
+    /// How far the thumb can move along its track.
+    pub max_thumb_offset: f32,
+}
+
+impl ScrollbarThumb {
+    /// How far the thumb is from the start of its track when its scroll node
+    /// has the given scroll offset.
+    pub fn offset(&self, scroll_offset: Vector2D<f32, LayoutPixel>) -> f32 {
+        let scrolled = if self.vertical {
+            -scroll_offset.y
+        } else {
+            -scroll_offset.x
+        };
+        (scrolled * self.thumb_offset_per_scroll)
+            .max(0.)
+            .min(self.max_thumb_offset)
+    }
+}
+
 /// Data about the window size.
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
 pub struct WindowSizeData {
@@ -792,6 +1043,96 @@ pub struct WindowSizeData {
     pub device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
 }
 
+/// The orientation of an emulated device.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum DeviceOrientation {
+    /// The viewport is at least as high as it is wide.
+    Portrait,
+    /// The viewport is wider than it is high.
+    Landscape,
+}
+
+/// The color scheme that the user of an emulated device prefers.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum ColorScheme {
+    /// Dark text on light backgrounds.
+    Light,
+    /// Light text on dark backgrounds.
+    Dark,
+}
+
+/// The device that a webview emulates for responsive testing, instead of the
+/// one its window is displayed on, without the window being resized. The
+/// missing values are the ones of the window.
+#[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct DeviceEmulation {
+    /// The size of the viewport.
+    pub viewport_size: Option<Size2D<f32, CSSPixel>>,
+    /// The device pixel ratio.
+    pub device_pixel_ratio: Option<Scale<f32, CSSPixel, DevicePixel>>,
+    /// The orientation, which swaps the width and the height of the viewport
+    /// when they don't match it.
+    pub orientation: Option<DeviceOrientation>,
+    /// The number of simultaneous touch points, which is zero for the devices
+    /// without touch support.
+    pub max_touch_points: u32,
+    /// The color scheme that the user prefers.
+    pub prefers_color_scheme: Option<ColorScheme>,
+}
+
+impl DeviceEmulation {
+    /// The size of a window as the webviews emulating this device see it.
+    pub fn window_size(&self, window_size: WindowSizeData) -> WindowSizeData {
+        let mut viewport = self.viewport_size.unwrap_or(window_size.initial_viewport);
+        let is_landscape = viewport.width > viewport.height;
+        let swap = match self.orientation {
+            Some(DeviceOrientation::Portrait) => is_landscape,
+            Some(DeviceOrientation::Landscape) => !is_landscape,
+            None => false,
+        };
+        if swap {
+            viewport = Size2D::new(viewport.height, viewport.width);
+        }
+        WindowSizeData {
+            initial_viewport: viewport,
+            device_pixel_ratio: self
+                .device_pixel_ratio
+                .unwrap_or(window_size.device_pixel_ratio),
+        }
+    }
+}
+
+/// How the frames of a webview that are far from the viewport of their parent
+/// are throttled, to save power on pages with many embedded documents.
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct FrameThrottlingPolicy {
+    /// How far from the viewport of its parent a frame has to be to get
+    /// throttled, in CSS pixels. The frames nested in a throttled one are
+    /// throttled too.
+    pub viewport_margin: f32,
+    /// The minimum delay of the timers of the throttled frames, in
+    /// milliseconds, if they are clamped.
+    pub timer_min_duration: Option<u64>,
+    /// Whether the animation frame callbacks of the throttled frames wait
+    /// until they are close to the viewport again.
+    pub suspend_animation_frames: bool,
+}
+
+/// The area of a webview that a screenshot is taken of.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ScreenshotArea {
+    /// What the viewport shows.
+    Viewport,
+    /// The whole document, including the parts that are scrolled out of the
+    /// viewport.
+    FullPage,
+    /// A rectangle, relative to the viewport.
+    Rect(Rect<f32, CSSPixel>),
+    /// The border box of the first element that matches a selector, clipped
+    /// to the viewport.
+    Element(String),
+}
+
 /// The type of window size change.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
 pub enum WindowSizeType {
@@ -862,6 +1203,8 @@ pub struct WorkerGlobalScopeInit {
     pub is_headless: bool,
     /// An optional string allowing the user agnet to be set for testing.
     pub user_agent: Cow<'static, str>,
+    /// The user agent that the webview of the worker is overridden with.
+    pub user_agent_override: Option<UserAgentOverride>,
 }
 
 /// Common entities representing a network load origin
@@ -1071,6 +1414,82 @@ pub enum MediaSessionActionType {
     SeekTo,
 }
 
+/// Whether the network can be reached, and how fast, as reported by the embedder.
+/// <https://wicg.github.io/netinfo/>
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct NetworkStatus {
+    /// Whether the user agent may be able to reach the network.
+    pub online: bool,
+    /// The type of connection that the throughput and latency of the network resemble.
+    pub effective_type: EffectiveConnectionType,
+    /// The effective bandwidth estimate, in megabits per second.
+    pub downlink: f64,
+    /// The effective round-trip time estimate, in milliseconds.
+    pub rtt: u64,
+    /// Whether the user asked for the data usage to be reduced.
+    pub save_data: bool,
+}
+
+impl Default for NetworkStatus {
+    fn default() -> NetworkStatus {
+        NetworkStatus {
+            online: true,
+            effective_type: EffectiveConnectionType::FourG,
+            downlink: 10.,
+            rtt: 50,
+            save_data: false,
+        }
+    }
+}
+
+/// The effective connection types.
+/// <https://wicg.github.io/netinfo/#effectiveconnectiontype-enum>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum EffectiveConnectionType {
+    Slow2G,
+    TwoG,
+    ThreeG,
+    FourG,
+}
+
+/// A reading of the motion sensors of the device, as reported by the embedder.
+/// <https://w3c.github.io/deviceorientation/>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DeviceSensorReading {
+    Orientation(OrientationReading),
+    Motion(MotionReading),
+}
+
+/// The orientation of the device, in degrees, or none for the angles that
+/// the device can't provide.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OrientationReading {
+    /// The rotation around the z axis, in [0, 360).
+    pub alpha: Option<f64>,
+    /// The rotation around the x axis, in [-180, 180).
+    pub beta: Option<f64>,
+    /// The rotation around the y axis, in [-90, 90).
+    pub gamma: Option<f64>,
+    /// Whether the orientation is relative to the Earth rather than to an
+    /// arbitrary frame.
+    pub absolute: bool,
+}
+
+/// The acceleration and rotation rate of the device.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct MotionReading {
+    /// The acceleration along the x, y and z axes without the effect of
+    /// gravity, in m/s².
+    pub acceleration: Option<[f64; 3]>,
+    /// The acceleration along the x, y and z axes including the effect of
+    /// gravity, in m/s².
+    pub acceleration_including_gravity: Option<[f64; 3]>,
+    /// The rotation rate around the z, x and y axes, in degrees per second.
+    pub rotation_rate: Option<[f64; 3]>,
+    /// The interval at which the sensors are read, in milliseconds.
+    pub interval: f64,
+}
+
 impl From<i32> for MediaSessionActionType {
     fn from(value: i32) -> MediaSessionActionType {
         match value {
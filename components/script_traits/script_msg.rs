@@ -10,7 +10,10 @@ use crate::IFrameLoadInfoWithData;
 use crate::LayoutControlMsg;
 use crate::LoadData;
 use crate::MessagePortMsg;
+use crate::PersistedUserState;
 use crate::PortMessageTask;
+use crate::ScrollState;
+use crate::ScrollbarThumb;
 use crate::StructuredSerializedData;
 use crate::WindowSizeType;
 use crate::WorkerGlobalScopeInit;
@@ -19,7 +22,7 @@ use canvas_traits::canvas::{CanvasId, CanvasMsg};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use embedder_traits::{EmbedderMsg, MediaSessionEvent};
 use euclid::default::Size2D as UntypedSize2D;
-use euclid::Size2D;
+use euclid::{Rect, Size2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use msg::constellation_msg::{
@@ -38,7 +41,8 @@ use std::fmt;
 use style_traits::viewport::ViewportConstraints;
 use style_traits::CSSPixel;
 use webgpu::{wgpu, WebGPUResponseResult};
-use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
+use webrender_api::units::{DeviceIntPoint, DeviceIntSize, LayoutPoint};
+use webrender_api::ExternalScrollId;
 
 /// A particular iframe's size, associated with a browsing context.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -70,6 +74,11 @@ pub enum LayoutMsg {
     PendingPaintMetric(PipelineId, Epoch),
     /// Notifies the constellation that the viewport has been constrained in some manner
     ViewportConstrained(PipelineId, ViewportConstraints),
+    /// The scrollbar thumbs that layout painted for the pipeline, which the
+    /// compositor moves and fades.
+    ScrollbarThumbs(PipelineId, Vec<ScrollbarThumb>),
+    /// Script scrolled a scroll node, which the compositor doesn't see by itself.
+    ScrolledFromScript(ScrollState),
 }
 
 impl fmt::Debug for LayoutMsg {
@@ -80,6 +89,8 @@ impl fmt::Debug for LayoutMsg {
             IFrameSizes(..) => "IFrameSizes",
             PendingPaintMetric(..) => "PendingPaintMetric",
             ViewportConstrained(..) => "ViewportConstrained",
+            ScrollbarThumbs(..) => "ScrollbarThumbs",
+            ScrolledFromScript(..) => "ScrolledFromScript",
         };
         write!(formatter, "LayoutMsg::{}", variant)
     }
@@ -107,6 +118,19 @@ pub enum LogEntry {
     Warn(String),
 }
 
+/// A panic that the constellation recovered from, as listed by about:crashes.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashReport {
+    /// The reason of the panic.
+    pub reason: String,
+    /// The backtrace of the panic, if any.
+    pub backtrace: Option<String>,
+    /// The URL of the page whose pipeline panicked, if known.
+    pub url: Option<ServoUrl>,
+    /// When the panic happened, in milliseconds since the Unix epoch.
+    pub time: u64,
+}
+
 /// https://html.spec.whatwg.org/multipage/#replacement-enabled
 #[derive(Debug, Deserialize, Serialize)]
 pub enum HistoryEntryReplacement {
@@ -230,6 +254,9 @@ pub enum ScriptMsg {
     PushHistoryState(HistoryStateId, ServoUrl),
     /// Inform the constellation of a replaced history state.
     ReplaceHistoryState(HistoryStateId, ServoUrl),
+    /// Inform the constellation of the state to persist in the current session
+    /// history entry of the document when it leaves the entry.
+    PersistUserState(PersistedUserState),
     /// Gets the length of the joint session history from the constellation.
     JointSessionHistoryLength(IpcSender<u32>),
     /// Notification that this iframe should be removed.
@@ -237,6 +264,9 @@ pub enum ScriptMsg {
     RemoveIFrame(BrowsingContextId, IpcSender<Vec<PipelineId>>),
     /// Notifies constellation that an iframe's visibility has been changed.
     VisibilityChangeComplete(bool),
+    /// Notifies constellation of how far from the viewport the iframes of the
+    /// document are, in CSS pixels, for the ones whose distance changed.
+    IFrameViewportDistances(Vec<(BrowsingContextId, f32)>),
     /// A load has been requested in an IFrame.
     ScriptLoadedURLInIFrame(IFrameLoadInfoWithData),
     /// A load of the initial `about:blank` has been completed in an IFrame.
@@ -248,16 +278,33 @@ pub enum ScriptMsg {
     ),
     /// Mark a new document as active
     ActivateDocument,
+    /// The document was unloaded, and its unload event started and ended at these times,
+    /// in nanoseconds.
+    DocumentUnloaded(u64, u64),
     /// Set the document state for a pipeline (used by screenshot / reftests)
     SetDocumentState(DocumentState),
     /// Update the pipeline Url, which can change after redirections.
     SetFinalUrl(ServoUrl),
     /// Script has handled a touch event, and either prevented or allowed default actions.
     TouchEventProcessed(EventResult),
+    /// Script has found the block that a double-tap zooms to, relative to the viewport.
+    DoubleTapZoomRect(Option<Rect<f32, CSSPixel>>),
+    /// Scrolls a scroll node smoothly to a position, which the compositor
+    /// animates.
+    ScrollSmoothly(ExternalScrollId, LayoutPoint),
+    /// Stops the smooth scroll of a scroll node, if there is one.
+    AbortSmoothScroll(ExternalScrollId),
+    /// The pointer was locked to an element of the document, or released, so
+    /// that mouse events are sent to the document while it is locked.
+    PointerLockChanged(bool),
     /// A log entry, with the top-level browsing context id and thread name
     LogEntry(Option<String>, LogEntry),
-    /// Discard the document.
-    DiscardDocument,
+    /// Get the panics that the constellation recovered from, oldest first.
+    GetCrashReports(IpcSender<Vec<CrashReport>>),
+    /// The document was unloaded, with whether it is salvageable, i.e. whether
+    /// it can be kept in the back-forward cache to be shown again. Documents
+    /// that aren't salvageable are discarded.
+    DocumentSalvageable(bool),
     /// Discard the browsing context.
     DiscardTopLevelBrowsingContext,
     /// Notifies the constellation that this pipeline has exited.
@@ -273,6 +320,8 @@ pub enum ScriptMsg {
     GetScreenSize(IpcSender<DeviceIntSize>),
     /// Get the available screen size (pixel)
     GetScreenAvailSize(IpcSender<DeviceIntSize>),
+    /// Get the angle the screen is rotated by from its natural orientation (degrees)
+    GetScreenOrientationAngle(IpcSender<u16>),
     /// Notifies the constellation about media session events
     /// (i.e. when there is metadata for the active media session, playback state changes...).
     MediaSessionEvent(PipelineId, MediaSessionEvent),
@@ -319,18 +368,26 @@ impl fmt::Debug for ScriptMsg {
             TraverseHistory(..) => "TraverseHistory",
             PushHistoryState(..) => "PushHistoryState",
             ReplaceHistoryState(..) => "ReplaceHistoryState",
+            PersistUserState(..) => "PersistUserState",
             JointSessionHistoryLength(..) => "JointSessionHistoryLength",
             RemoveIFrame(..) => "RemoveIFrame",
             VisibilityChangeComplete(..) => "VisibilityChangeComplete",
+            IFrameViewportDistances(..) => "IFrameViewportDistances",
             ScriptLoadedURLInIFrame(..) => "ScriptLoadedURLInIFrame",
             ScriptNewIFrame(..) => "ScriptNewIFrame",
             ScriptNewAuxiliary(..) => "ScriptNewAuxiliary",
             ActivateDocument => "ActivateDocument",
+            DocumentUnloaded(..) => "DocumentUnloaded",
             SetDocumentState(..) => "SetDocumentState",
             SetFinalUrl(..) => "SetFinalUrl",
             TouchEventProcessed(..) => "TouchEventProcessed",
+            DoubleTapZoomRect(..) => "DoubleTapZoomRect",
+            ScrollSmoothly(..) => "ScrollSmoothly",
+            AbortSmoothScroll(..) => "AbortSmoothScroll",
+            PointerLockChanged(..) => "PointerLockChanged",
             LogEntry(..) => "LogEntry",
-            DiscardDocument => "DiscardDocument",
+            GetCrashReports(..) => "GetCrashReports",
+            DocumentSalvageable(..) => "DocumentSalvageable",
             DiscardTopLevelBrowsingContext => "DiscardTopLevelBrowsingContext",
             PipelineExited => "PipelineExited",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
@@ -338,6 +395,7 @@ impl fmt::Debug for ScriptMsg {
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
+            GetScreenOrientationAngle(..) => "GetScreenOrientationAngle",
             MediaSessionEvent(..) => "MediaSessionEvent",
             RequestAdapter(..) => "RequestAdapter",
         };
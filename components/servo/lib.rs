@@ -107,6 +107,7 @@ use profile_traits::mem;
 use profile_traits::time;
 use script::JSEngineSetup;
 use script_traits::{SWManagerSenders, ScriptToConstellationChan, WindowSizeData};
+use servo_config::basedir::profile_dir;
 use servo_config::opts;
 use servo_config::{pref, prefs};
 use servo_media::player::context::GlContext;
@@ -508,12 +509,22 @@ where
 
         let pending_wr_frame = Arc::new(AtomicBool::new(false));
 
+        // The data of the instance is kept in the directory of its profile,
+        // if it has one.
+        let config_dir = match opts.user_profile {
+            Some(ref profile) => opts
+                .config_dir
+                .as_ref()
+                .and_then(|config_dir| profile_dir(config_dir, profile)),
+            None => opts.config_dir.clone(),
+        };
+
         // Create the constellation, which maintains the engine
         // pipelines, including the script and layout threads, as well
         // as the navigation context.
         let (constellation_chan, sw_senders) = create_constellation(
             opts.user_agent.clone(),
-            opts.config_dir.clone(),
+            config_dir,
             embedder_proxy.clone(),
             compositor_proxy.clone(),
             time_profiler_chan.clone(),
@@ -536,6 +547,20 @@ where
         // Send the constellation's swmanager sender to service worker manager thread
         script::init_service_workers(sw_senders);
 
+        // Preferences that the embedder changes at runtime have to be changed in the
+        // script threads of the running pipelines too, which don't share the preferences
+        // of this process when they run in content processes.
+        let prefs_constellation_chan = constellation_chan.clone();
+        prefs::pref_map().add_observer(move |name, value| {
+            let msg = ConstellationMsg::PreferenceChanged(name.to_owned(), value.clone());
+            if let Err(e) = prefs_constellation_chan.send(msg) {
+                warn!(
+                    "Sending preference change to constellation failed ({:?}).",
+                    e
+                );
+            }
+        });
+
         if cfg!(feature = "webdriver") {
             if let Some(port) = opts.webdriver_port {
                 webdriver(port, constellation_chan.clone());
@@ -604,6 +629,13 @@ where
                 }
             },
 
+            WindowEvent::Prefetch(top_level_browsing_context_id, url) => {
+                let msg = ConstellationMsg::Prefetch(top_level_browsing_context_id, url);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending prefetch to constellation failed ({:?}).", e);
+                }
+            },
+
             WindowEvent::MouseWindowEventClass(mouse_window_event) => {
                 self.compositor
                     .on_mouse_window_event_class(mouse_window_event);
@@ -613,6 +645,14 @@ where
                 self.compositor.on_mouse_window_move_event_class(cursor);
             },
 
+            WindowEvent::Drag(event_type, point, data) => {
+                self.compositor.on_drag_event(event_type, point, data);
+            },
+
+            WindowEvent::PointerLockedMouseMove(delta) => {
+                self.compositor.on_pointer_locked_mouse_move(delta);
+            },
+
             WindowEvent::Touch(event_type, identifier, location) => {
                 self.compositor
                     .on_touch_event(event_type, identifier, location);
@@ -691,8 +731,8 @@ where
                 self.compositor.capture_webrender();
             },
 
-            WindowEvent::NewBrowser(url, browser_id) => {
-                let msg = ConstellationMsg::NewBrowser(url, browser_id);
+            WindowEvent::NewBrowser(url, browser_id, profile) => {
+                let msg = ConstellationMsg::NewBrowser(url, browser_id, profile);
                 if let Err(e) = self.constellation_chan.send(msg) {
                     warn!(
                         "Sending NewBrowser message to constellation failed ({:?}).",
@@ -753,6 +793,108 @@ where
                     );
                 }
             },
+
+            WindowEvent::NetworkStatusChanged(status) => {
+                let msg = ConstellationMsg::NetworkStatusChanged(status);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending NetworkStatusChanged to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::DeviceSensorReading(reading) => {
+                let msg = ConstellationMsg::DeviceSensorReading(reading);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending DeviceSensorReading to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetContentBlocking(top_level_browsing_context_id, enabled) => {
+                let msg =
+                    ConstellationMsg::SetContentBlocking(top_level_browsing_context_id, enabled);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetContentBlocking to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetUserAgentOverride(top_level_browsing_context_id, ua_override) => {
+                let msg = ConstellationMsg::SetUserAgentOverride(
+                    top_level_browsing_context_id,
+                    ua_override,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetUserAgentOverride to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetDeviceEmulation(top_level_browsing_context_id, emulation) => {
+                let msg =
+                    ConstellationMsg::SetDeviceEmulation(top_level_browsing_context_id, emulation);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetDeviceEmulation to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetNetworkConditions(top_level_browsing_context_id, conditions) => {
+                let msg = ConstellationMsg::SetNetworkConditions(
+                    top_level_browsing_context_id,
+                    conditions,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetNetworkConditions to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::SetFrameThrottlingPolicy(top_level_browsing_context_id, policy) => {
+                let msg = ConstellationMsg::SetFrameThrottlingPolicy(
+                    top_level_browsing_context_id,
+                    policy,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending SetFrameThrottlingPolicy to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            WindowEvent::TakeScreenshot(top_level_browsing_context_id, id, area, format) => {
+                let msg = ConstellationMsg::TakeScreenshot(
+                    top_level_browsing_context_id,
+                    id,
+                    area,
+                    format,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending TakeScreenshot to constellation failed ({:?}).", e);
+                }
+            },
+
+            WindowEvent::StartFrameCapture(top_level_browsing_context_id, id, options) => {
+                self.compositor
+                    .start_frame_capture(top_level_browsing_context_id, id, options);
+            },
+
+            WindowEvent::StopFrameCapture(id) => {
+                self.compositor.stop_frame_capture(id);
+            },
         }
     }
 
@@ -770,8 +912,11 @@ where
                 (_, ShutdownState::ShuttingDown) => {},
 
                 (EmbedderMsg::Keyboard(key_event), ShutdownState::NotShuttingDown) => {
-                    let event = (top_level_browsing_context, EmbedderMsg::Keyboard(key_event));
-                    self.embedder_events.push(event);
+                    // Keys that scroll aren't handed on to the embedder.
+                    if !self.compositor.on_unhandled_key_event(&key_event) {
+                        let event = (top_level_browsing_context, EmbedderMsg::Keyboard(key_event));
+                        self.embedder_events.push(event);
+                    }
                 },
 
                 (msg, ShutdownState::NotShuttingDown) => {
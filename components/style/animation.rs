@@ -9,25 +9,30 @@
 // import.
 
 use crate::bezier::Bezier;
-use crate::context::SharedStyleContext;
+use crate::context::{QuirksMode, SharedStyleContext};
+use crate::custom_properties::{Name as CustomPropertyName, VariableValue};
 use crate::dom::{OpaqueNode, TElement};
 use crate::font_metrics::FontMetricsProvider;
+use crate::media_queries::Device;
 use crate::properties::animated_properties::AnimatedProperty;
 use crate::properties::longhands::animation_direction::computed_value::single_value::T as AnimationDirection;
 use crate::properties::longhands::animation_play_state::computed_value::single_value::T as AnimationPlayState;
 use crate::properties::{self, CascadeMode, ComputedValues, LonghandId};
+use crate::properties_and_values::value::ComputedValue as RegisteredComputedValue;
 use crate::stylesheets::keyframes_rule::{KeyframesAnimation, KeyframesStep, KeyframesStepValue};
 use crate::stylesheets::Origin;
 use crate::timer::Timer;
+use crate::values::animated::{Animate, Procedure};
 use crate::values::computed::box_::TransitionProperty;
 use crate::values::computed::Time;
-use crate::values::computed::TimingFunction;
+use crate::values::computed::{Context, TimingFunction};
 use crate::values::generics::box_::AnimationIterationCount;
 use crate::values::generics::easing::{StepPosition, TimingFunction as GenericTimingFunction};
 use crate::Atom;
 #[cfg(feature = "servo")]
 use crossbeam_channel::Sender;
 use servo_arc::Arc;
+use std::borrow::Cow;
 use std::fmt;
 #[cfg(feature = "gecko")]
 use std::sync::mpsc::Sender;
@@ -257,18 +262,112 @@ pub struct AnimationFrame {
     pub duration: f64,
 }
 
+/// An animation of a registered custom property.
+#[derive(Clone, Debug)]
+struct CustomPropertyAnimation {
+    name: CustomPropertyName,
+    from: Arc<VariableValue>,
+    to: Arc<VariableValue>,
+    /// The computed values to interpolate between, or `None` if the property
+    /// animates discretely.
+    interpolable: Option<(RegisteredComputedValue, RegisteredComputedValue)>,
+}
+
+impl CustomPropertyAnimation {
+    /// Creates an animation of the custom property `name` between two styles,
+    /// unless it isn't registered or has the same value in both.
+    fn new(
+        name: &CustomPropertyName,
+        device: &Device,
+        old_style: &ComputedValues,
+        new_style: &ComputedValues,
+    ) -> Option<Self> {
+        let registration = device.property_registry().get(name)?;
+        let value = |style: &ComputedValues| {
+            style
+                .custom_properties()
+                .and_then(|custom_properties| custom_properties.get(name))
+                .cloned()
+        };
+        let (from, to) = (value(old_style)?, value(new_style)?);
+        if from == to {
+            return None;
+        }
+        // The values are computed values, which only need a context to be
+        // parsed back.
+        let computed = |value: &VariableValue| {
+            Context::for_media_query_evaluation(device, QuirksMode::NoQuirks, |context| {
+                match registration.parse_value(value) {
+                    Some(Ok(specified)) => Some(specified.to_computed_value(context)),
+                    _ => None,
+                }
+            })
+        };
+        let interpolable = match (computed(&from), computed(&to)) {
+            (Some(from), Some(to)) => {
+                let procedure = Procedure::Interpolate { progress: 0.5 };
+                from.animate(&to, procedure).ok().map(|_| (from, to))
+            },
+            _ => None,
+        };
+        Some(CustomPropertyAnimation {
+            name: name.clone(),
+            from,
+            to,
+            interpolable,
+        })
+    }
+
+    fn update(&self, style: &mut ComputedValues, progress: f64) {
+        let interpolated = self.interpolable.as_ref().and_then(|&(ref from, ref to)| {
+            from.animate(to, Procedure::Interpolate { progress })
+                .ok()
+                .map(|value| value.to_variable_value())
+        });
+        let value = interpolated.unwrap_or_else(|| {
+            if progress < 0.5 {
+                self.from.clone()
+            } else {
+                self.to.clone()
+            }
+        });
+        style.set_custom_property(self.name.clone(), value);
+    }
+}
+
+/// The property a `PropertyAnimation` animates.
+#[derive(Clone, Debug)]
+enum AnimatingProperty {
+    /// A longhand property.
+    Longhand(AnimatedProperty),
+    /// A registered custom property.
+    Custom(CustomPropertyAnimation),
+}
+
+impl AnimatingProperty {
+    fn update(&self, style: &mut ComputedValues, progress: f64) {
+        match *self {
+            AnimatingProperty::Longhand(ref property) => property.update(style, progress),
+            AnimatingProperty::Custom(ref property) => property.update(style, progress),
+        }
+    }
+}
+
 /// Represents an animation for a given property.
 #[derive(Clone, Debug)]
 pub struct PropertyAnimation {
-    property: AnimatedProperty,
+    property: AnimatingProperty,
     timing_function: TimingFunction,
     duration: Time, // TODO: isn't this just repeated?
 }
 
 impl PropertyAnimation {
     /// Returns the given property name.
-    pub fn property_name(&self) -> &'static str {
-        self.property.name()
+    pub fn property_name(&self) -> Cow<'static, str> {
+        match self.property {
+            AnimatingProperty::Longhand(ref property) => Cow::Borrowed(property.name()),
+            AnimatingProperty::Custom(ref property) => Cow::Owned(format!("--{}", property.name)),
+        }
     }
 
     /// Creates a new property animation for the given transition index and old
@@ -277,6 +376,7 @@ impl PropertyAnimation {
     /// to arbitrarily many (for `all`).
     pub fn from_transition(
         transition_index: usize,
+        device: &Device,
         old_style: &ComputedValues,
         new_style: &mut ComputedValues,
     ) -> Vec<PropertyAnimation> {
@@ -287,7 +387,21 @@ impl PropertyAnimation {
         let duration = box_style.transition_duration_mod(transition_index);
 
         match transition_property {
-            TransitionProperty::Custom(..) | TransitionProperty::Unsupported(..) => result,
+            TransitionProperty::Unsupported(..) => result,
+            TransitionProperty::Custom(ref name) => {
+                // Only the registered properties that can be interpolated
+                // transition.
+                let animation = CustomPropertyAnimation::new(name, device, old_style, new_style)
+                    .filter(|animation| animation.interpolable.is_some())
+                    .map(|animation| PropertyAnimation {
+                        property: AnimatingProperty::Custom(animation),
+                        timing_function,
+                        duration,
+                    })
+                    .filter(|animation| animation.does_animate());
+                result.extend(animation);
+                result
+            },
             TransitionProperty::Shorthand(ref shorthand_id) => shorthand_id
                 .longhands()
                 .filter_map(|longhand| {
@@ -327,7 +441,7 @@ impl PropertyAnimation {
         let animated_property = AnimatedProperty::from_longhand(longhand, old_style, new_style)?;
 
         let property_animation = PropertyAnimation {
-            property: animated_property,
+            property: AnimatingProperty::Longhand(animated_property),
             timing_function: timing_function,
             duration: duration,
         };
@@ -339,6 +453,27 @@ impl PropertyAnimation {
         }
     }
 
+    fn from_custom_property(
+        name: &CustomPropertyName,
+        timing_function: TimingFunction,
+        duration: Time,
+        device: &Device,
+        old_style: &ComputedValues,
+        new_style: &ComputedValues,
+    ) -> Option<PropertyAnimation> {
+        let animation = CustomPropertyAnimation::new(name, device, old_style, new_style)?;
+        let property_animation = PropertyAnimation {
+            property: AnimatingProperty::Custom(animation),
+            timing_function,
+            duration,
+        };
+        if property_animation.does_animate() {
+            Some(property_animation)
+        } else {
+            None
+        }
+    }
+
     /// Update the given animation at a given point of progress.
     pub fn update(&self, style: &mut ComputedValues, time: f64) {
         let epsilon = 1. / (200. * (self.duration.seconds() as f64));
@@ -392,13 +527,25 @@ impl PropertyAnimation {
 
     #[inline]
     fn does_animate(&self) -> bool {
-        self.property.does_animate() && self.duration.seconds() != 0.0
+        let property_animates = match self.property {
+            AnimatingProperty::Longhand(ref property) => property.does_animate(),
+            AnimatingProperty::Custom(ref property) => property.from != property.to,
+        };
+        property_animates && self.duration.seconds() != 0.0
     }
 
     /// Whether this animation has the same end value as another one.
     #[inline]
     pub fn has_the_same_end_value_as(&self, other: &Self) -> bool {
-        self.property.has_the_same_end_value_as(&other.property)
+        match (&self.property, &other.property) {
+            (&AnimatingProperty::Longhand(ref this), &AnimatingProperty::Longhand(ref other)) => {
+                this.has_the_same_end_value_as(other)
+            },
+            (&AnimatingProperty::Custom(ref this), &AnimatingProperty::Custom(ref other)) => {
+                this.name == other.name && this.to == other.to
+            },
+            _ => false,
+        }
     }
 }
 
@@ -407,6 +554,7 @@ impl PropertyAnimation {
 /// Returns true if any animations were kicked off and false otherwise.
 pub fn start_transitions_if_applicable(
     new_animations_sender: &Sender<Animation>,
+    device: &Device,
     opaque_node: OpaqueNode,
     old_style: &ComputedValues,
     new_style: &mut Arc<ComputedValues>,
@@ -417,7 +565,7 @@ pub fn start_transitions_if_applicable(
     for i in 0..new_style.get_box().transition_property_count() {
         // Create any property animations, if applicable.
         let property_animations =
-            PropertyAnimation::from_transition(i, old_style, Arc::make_mut(new_style));
+            PropertyAnimation::from_transition(i, device, old_style, Arc::make_mut(new_style));
         for property_animation in property_animations {
             // Set the property to the initial value.
             //
@@ -490,7 +638,7 @@ where
                 // Also we filter our non-animatable properties.
                 guard
                     .normal_declaration_iter()
-                    .filter(|declaration| declaration.is_animatable())
+                    .filter(|declaration| declaration.is_animatable() || declaration.is_custom())
                     .map(|decl| (decl, Origin::Author))
             };
 
@@ -836,6 +984,20 @@ where
                 }
             }
 
+            for custom_property in animation.custom_properties_changed.iter() {
+                let animation = PropertyAnimation::from_custom_property(
+                    custom_property,
+                    timing_function,
+                    Time::from_seconds(relative_duration as f32),
+                    context.stylist.device(),
+                    &from_style,
+                    &target_style,
+                );
+                if let Some(property_animation) = animation {
+                    property_animation.update(Arc::make_mut(&mut new_style), relative_progress);
+                }
+            }
+
             debug!(
                 "update_style_for_animation: got style change in animation \"{}\"",
                 name
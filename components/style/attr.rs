@@ -25,6 +25,52 @@ use std::str::FromStr;
 // Duplicated from script::dom::values.
 const UNSIGNED_LONG_MAX: u32 = 2147483647;
 
+/// The maximum length of the strings that are stored as atoms, so that the
+/// many nodes repeating such a string share its storage. Longer strings are
+/// rarely repeated, and are not worth growing the atom table with.
+pub const MAX_ATOMIZED_VALUE_LENGTH: usize = 12;
+
+/// A string stored as an atom if it isn't longer than
+/// `MAX_ATOMIZED_VALUE_LENGTH`, and as a string otherwise.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(MallocSizeOf))]
+pub enum AtomOrString {
+    Atom(Atom),
+    String(String),
+}
+
+impl AtomOrString {
+    pub fn new(string: String) -> AtomOrString {
+        if string.len() <= MAX_ATOMIZED_VALUE_LENGTH {
+            AtomOrString::Atom(Atom::from(string))
+        } else {
+            AtomOrString::String(string)
+        }
+    }
+
+    pub fn push_str(&mut self, string: &str) {
+        match *self {
+            AtomOrString::String(ref mut value) => value.push_str(string),
+            AtomOrString::Atom(ref value) => {
+                let mut value = String::from(&**value);
+                value.push_str(string);
+                *self = AtomOrString::new(value);
+            },
+        }
+    }
+}
+
+impl ::std::ops::Deref for AtomOrString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match *self {
+            AtomOrString::Atom(ref value) => &value,
+            AtomOrString::String(ref value) => &value,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "servo", derive(MallocSizeOf))]
 pub enum LengthOrPercentageOrAuto {
@@ -37,7 +83,11 @@ pub enum LengthOrPercentageOrAuto {
 #[cfg_attr(feature = "servo", derive(MallocSizeOf))]
 pub enum AttrValue {
     String(String),
-    TokenList(String, Vec<Atom>),
+    /// The serialization of a token list, and its unique tokens.
+    ///
+    /// Short serializations are atoms because the same lists of tokens, like
+    /// class lists, tend to be repeated across many elements of a page.
+    TokenList(AtomOrString, Vec<Atom>),
     UInt(String, u32),
     Int(String, i32),
     Double(String, f64),
@@ -167,7 +217,7 @@ impl AttrValue {
                     }
                     acc
                 });
-        AttrValue::TokenList(tokens, atoms)
+        AttrValue::TokenList(AtomOrString::new(tokens), atoms)
     }
 
     pub fn from_comma_separated_tokenlist(tokens: String) -> AttrValue {
@@ -179,12 +229,12 @@ impl AttrValue {
                 }
                 acc
             });
-        AttrValue::TokenList(tokens, atoms)
+        AttrValue::TokenList(AtomOrString::new(tokens), atoms)
     }
 
     pub fn from_atomic_tokens(atoms: Vec<Atom>) -> AttrValue {
         // TODO(ajeffrey): effecient conversion of Vec<Atom> to String
-        let tokens = String::from(str_join(&atoms, "\x20"));
+        let tokens = AtomOrString::new(str_join(&atoms, "\x20"));
         AttrValue::TokenList(tokens, atoms)
     }
 
@@ -242,6 +292,16 @@ impl AttrValue {
         AttrValue::Atom(value)
     }
 
+    /// Stores a value without a parsed representation, as an atom if it is
+    /// short enough to be likely repeated across elements, like most `data-*`
+    /// attribute values.
+    pub fn from_string_or_atom(string: String) -> AttrValue {
+        match AtomOrString::new(string) {
+            AtomOrString::Atom(value) => AttrValue::Atom(value),
+            AtomOrString::String(value) => AttrValue::String(value),
+        }
+    }
+
     pub fn from_resolved_url(base: &ServoUrl, url: String) -> AttrValue {
         let joined = base.join(&url).ok();
         AttrValue::ResolvedUrl(url, joined)
@@ -368,7 +428,6 @@ impl ::std::ops::Deref for AttrValue {
     fn deref(&self) -> &str {
         match *self {
             AttrValue::String(ref value) |
-            AttrValue::TokenList(ref value, _) |
             AttrValue::UInt(ref value, _) |
             AttrValue::Double(ref value, _) |
             AttrValue::Length(ref value, _) |
@@ -378,6 +437,7 @@ impl ::std::ops::Deref for AttrValue {
             AttrValue::Declaration(ref value, _) |
             AttrValue::Dimension(ref value, _) => &value,
             AttrValue::Atom(ref value) => &value,
+            AttrValue::TokenList(ref value, _) => &value,
         }
     }
 }
@@ -9,6 +9,7 @@
 use crate::hash::map::Entry;
 use crate::media_queries::Device;
 use crate::properties::{CSSWideKeyword, CustomDeclaration, CustomDeclarationValue};
+use crate::properties_and_values::registry::PropertyRegistry;
 use crate::selector_map::{PrecomputedHashMap, PrecomputedHashSet, PrecomputedHasher};
 use crate::stylesheets::{Origin, PerOrigin};
 use crate::Atom;
@@ -234,6 +235,19 @@ impl VariableValue {
         )
     }
 
+    /// The serialization of the tokens of the value.
+    #[inline]
+    pub fn css(&self) -> &str {
+        &self.css
+    }
+
+    /// Whether the value has `var()` or `env()` references, which are
+    /// substituted at computed-value time.
+    #[inline]
+    pub fn has_references(&self) -> bool {
+        !self.references.is_empty() || self.references_environment
+    }
+
     /// Parse a custom property value.
     pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Arc<Self>, ParseError<'i>> {
         let mut references = VarOrEnvReferences::default();
@@ -523,6 +537,7 @@ pub struct CustomPropertiesBuilder<'a> {
     custom_properties: Option<CustomPropertiesMap>,
     inherited: Option<&'a Arc<CustomPropertiesMap>>,
     device: &'a Device,
+    registry: &'a PropertyRegistry,
 }
 
 impl<'a> CustomPropertiesBuilder<'a> {
@@ -535,6 +550,7 @@ impl<'a> CustomPropertiesBuilder<'a> {
             custom_properties: None,
             inherited,
             device,
+            registry: device.property_registry(),
         }
     }
 
@@ -598,8 +614,22 @@ impl<'a> CustomPropertiesBuilder<'a> {
                 CSSWideKeyword::Initial => {
                     map.remove(name);
                 },
-                // handled in value_may_affect_style
-                CSSWideKeyword::Unset | CSSWideKeyword::Inherit => unreachable!(),
+                CSSWideKeyword::Unset | CSSWideKeyword::Inherit => {
+                    // Only registered properties that don't inherit get
+                    // here, see value_may_affect_style.
+                    if keyword == CSSWideKeyword::Unset {
+                        map.remove(name);
+                        return;
+                    }
+                    match self.inherited.and_then(|inherited| inherited.get(name)) {
+                        Some(value) => {
+                            map.insert(name.clone(), value.clone());
+                        },
+                        None => {
+                            map.remove(name);
+                        },
+                    }
+                },
             },
         }
     }
@@ -608,10 +638,17 @@ impl<'a> CustomPropertiesBuilder<'a> {
         match *value {
             CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Unset) |
             CustomDeclarationValue::CSSWideKeyword(CSSWideKeyword::Inherit) => {
-                // Custom properties are inherited by default. So
-                // explicit 'inherit' or 'unset' means we can just use
-                // any existing value in the inherited CustomPropertiesMap.
-                return false;
+                // Custom properties are inherited by default, unless they're
+                // registered as not inherited. So explicit 'inherit' or
+                // 'unset' means we can just use any existing value in the
+                // inherited CustomPropertiesMap.
+                let inherits = self
+                    .registry
+                    .get(name)
+                    .map_or(true, |registration| registration.inherits);
+                if inherits {
+                    return false;
+                }
             },
             _ => {},
         }
@@ -650,13 +687,77 @@ impl<'a> CustomPropertiesBuilder<'a> {
     pub fn build(mut self) -> Option<Arc<CustomPropertiesMap>> {
         let mut map = match self.custom_properties.take() {
             Some(m) => m,
-            None => return self.inherited.cloned(),
+            None => {
+                if self.registry.is_empty() {
+                    return self.inherited.cloned();
+                }
+                let mut map = self
+                    .inherited
+                    .map_or_else(CustomPropertiesMap::default, |inherited| {
+                        (**inherited).clone()
+                    });
+                if !self.apply_registrations(&mut map) {
+                    return self.inherited.cloned();
+                }
+                return Some(Arc::new(map));
+            },
         };
         if self.may_have_cycles {
             substitute_all(&mut map, self.device);
         }
+        self.apply_registrations(&mut map);
         Some(Arc::new(map))
     }
+
+    /// Gives the registered properties that aren't declared and don't
+    /// inherit, or don't have a value, their initial value, and unsets the
+    /// declared values that don't match the syntax of their property.
+    ///
+    /// Returns whether the map changed.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#calculation-of-computed-values>
+    fn apply_registrations(&self, map: &mut CustomPropertiesMap) -> bool {
+        let mut changed = false;
+        for registration in self.registry.iter() {
+            let name = &registration.name;
+            let declared = self.seen.contains(name);
+            let value = match map.get(name) {
+                _ if !declared && !registration.inherits => registration.initial_value.as_ref(),
+                None => registration.initial_value.as_ref(),
+                Some(value) => {
+                    let is_valid = registration
+                        .parse_value(value)
+                        .map_or(true, |parsed| parsed.is_ok());
+                    if !declared || is_valid {
+                        continue;
+                    }
+                    if registration.inherits {
+                        self.inherited.and_then(|inherited| inherited.get(name))
+                    } else {
+                        registration.initial_value.as_ref()
+                    }
+                },
+            };
+            let unchanged = match (map.get(name), value) {
+                (Some(current), Some(value)) => Arc::ptr_eq(current, value),
+                (None, None) => true,
+                _ => false,
+            };
+            if unchanged {
+                continue;
+            }
+            match value {
+                Some(value) => {
+                    map.insert(name.clone(), value.clone());
+                },
+                None => {
+                    map.remove(name);
+                },
+            }
+            changed = true;
+        }
+        changed
+    }
 }
 
 /// Resolve all custom properties to either substituted or invalid.
@@ -137,6 +137,8 @@ bitflags! {
         const IN_AUTOFILL_STATE = 1 << 50;
         /// Non-standard & undocumented.
         const IN_AUTOFILL_PREVIEW_STATE = 1 << 51;
+        /// <https://html.spec.whatwg.org/multipage/#selector-modal>
+        const IN_MODAL_STATE = 1 << 52;
     }
 }
 
@@ -21,7 +21,7 @@ use crate::values::specified::font::SpecifiedFontStyle;
 use crate::values::specified::font::SpecifiedFontVariationSettings;
 use crate::values::specified::font::{AbsoluteFontWeight, FontStretch};
 use crate::values::specified::url::SpecifiedUrl;
-use crate::values::specified::Angle;
+use crate::values::specified::{Angle, Percentage};
 #[cfg(feature = "gecko")]
 use cssparser::UnicodeRange;
 use cssparser::{AtRuleParser, DeclarationListParser, DeclarationParser, Parser};
@@ -113,6 +113,61 @@ pub enum FontDisplay {
     Optional,
 }
 
+/// The value of the `size-adjust` descriptor of a @font-face rule, a
+/// multiplier for the glyph outlines and metrics of the font face.
+///
+/// <https://drafts.csswg.org/css-fonts-5/#size-adjust-desc>
+#[derive(Clone, Debug, PartialEq, ToCss, ToShmem)]
+pub struct SizeAdjust(pub Percentage);
+
+impl Parse for SizeAdjust {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        Ok(SizeAdjust(Percentage::parse_non_negative(context, input)?))
+    }
+}
+
+/// The value of the `ascent-override`, `descent-override` and
+/// `line-gap-override` descriptors of a @font-face rule.
+///
+/// <https://drafts.csswg.org/css-fonts-5/#font-metrics-override-desc>
+#[derive(Clone, Debug, PartialEq, ToCss, ToShmem)]
+pub enum MetricsOverride {
+    /// `normal`, which uses the metric from the font.
+    Normal,
+    /// A percentage of the used font size.
+    Percentage(Percentage),
+}
+
+impl MetricsOverride {
+    /// The overriding metric, as a fraction of the used font size, if any.
+    pub fn fraction(&self) -> Option<f32> {
+        match *self {
+            MetricsOverride::Normal => None,
+            MetricsOverride::Percentage(ref percentage) => Some(percentage.get()),
+        }
+    }
+}
+
+impl Parse for MetricsOverride {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        if input
+            .try(|input| input.expect_ident_matching("normal"))
+            .is_ok()
+        {
+            return Ok(MetricsOverride::Normal);
+        }
+        Ok(MetricsOverride::Percentage(Percentage::parse_non_negative(
+            context, input,
+        )?))
+    }
+}
+
 macro_rules! impl_range {
     ($range:ident, $component:ident) => {
         impl Parse for $range {
@@ -587,5 +642,16 @@ font_face_descriptors! {
         "src" sources / mSrc: Vec<Source>,
     ]
     optional descriptors = [
+        /// The multiplier for the glyph outlines and metrics of this font face.
+        "size-adjust" size_adjust / mSizeAdjust: SizeAdjust,
+
+        /// The ascent metric of this font face.
+        "ascent-override" ascent_override / mAscentOverride: MetricsOverride,
+
+        /// The descent metric of this font face.
+        "descent-override" descent_override / mDescentOverride: MetricsOverride,
+
+        /// The line gap metric of this font face.
+        "line-gap-override" line_gap_override / mLineGapOverride: MetricsOverride,
     ]
 }
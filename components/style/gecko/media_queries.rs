@@ -10,6 +10,7 @@ use crate::gecko_bindings::bindings;
 use crate::gecko_bindings::structs;
 use crate::media_queries::MediaType;
 use crate::properties::ComputedValues;
+use crate::properties_and_values::registry::PropertyRegistry;
 use crate::string_cache::Atom;
 use crate::values::specified::font::FONT_MEDIUM_PX;
 use crate::values::{CustomIdent, KeyframesName};
@@ -53,6 +54,8 @@ pub struct Device {
     /// The CssEnvironment object responsible of getting CSS environment
     /// variables.
     environment: CssEnvironment,
+    /// The custom properties registered in the document.
+    property_registry: Arc<PropertyRegistry>,
 }
 
 impl fmt::Debug for Device {
@@ -91,6 +94,7 @@ impl Device {
             used_root_font_size: AtomicBool::new(false),
             used_viewport_size: AtomicBool::new(false),
             environment: CssEnvironment,
+            property_registry: Arc::new(PropertyRegistry::default()),
         }
     }
 
@@ -100,6 +104,17 @@ impl Device {
         &self.environment
     }
 
+    /// The custom properties registered in the document.
+    #[inline]
+    pub fn property_registry(&self) -> &Arc<PropertyRegistry> {
+        &self.property_registry
+    }
+
+    /// Sets the custom properties registered in the document.
+    pub fn set_property_registry(&mut self, registry: Arc<PropertyRegistry>) {
+        self.property_registry = registry;
+    }
+
     /// Tells the device that a new viewport rule has been found, and stores the
     /// relevant viewport constraints.
     pub fn account_for_viewport_rule(&mut self, _constraints: &ViewportConstraints) {
@@ -464,6 +464,13 @@ impl StylesheetInvalidationSet {
                     // existing elements.
                 }
             },
+            Property(..) => {
+                debug!(
+                    " > Found @property rule, which may change the value of \
+                     any custom property, marking the whole tree invalid."
+                );
+                self.fully_invalid = true;
+            },
             CounterStyle(..) | Page(..) | Viewport(..) | FontFeatureValues(..) => {
                 debug!(
                     " > Found unsupported rule, marking the whole subtree \
@@ -466,6 +466,7 @@ trait PrivateMatchMethods: TElement {
         if let Some(ref values) = *old_values {
             animation::start_transitions_if_applicable(
                 new_animations_sender,
+                shared_context.stylist.device(),
                 this_opaque,
                 &values,
                 new_values,
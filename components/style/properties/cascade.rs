@@ -606,6 +606,7 @@ impl<'a, 'b: 'a> Cascade<'a, 'b> {
         if Phase::is_early() {
             self.fixup_font_stuff();
             self.compute_writing_mode();
+            self.compute_registered_custom_properties();
         } else {
             self.finished_applying_properties();
         }
@@ -621,6 +622,52 @@ impl<'a, 'b: 'a> Cascade<'a, 'b> {
         self.context.builder.writing_mode = writing_mode;
     }
 
+    /// Replaces the values of the registered custom properties that this
+    /// element doesn't inherit by their computed values, now that the font
+    /// properties they may depend on are known.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#calculation-of-computed-values>
+    fn compute_registered_custom_properties(&mut self) {
+        let device = self.context.builder.device;
+        let registry = device.property_registry();
+        if registry.is_empty() {
+            return;
+        }
+        let custom_properties = match self.context.builder.custom_properties {
+            Some(ref custom_properties) => custom_properties.clone(),
+            None => return,
+        };
+        let inherited_style = self.context.builder.inherited_style;
+        let inherited = inherited_style.custom_properties();
+        let mut computed_values = SmallVec::<[_; 4]>::new();
+        for (name, value) in custom_properties.iter() {
+            let registration = match registry.get(name) {
+                Some(registration) => registration,
+                None => continue,
+            };
+            let is_inherited_or_initial = inherited
+                .and_then(|inherited| inherited.get(name))
+                .into_iter()
+                .chain(registration.initial_value.as_ref())
+                .any(|computed| Arc::ptr_eq(computed, value));
+            if is_inherited_or_initial {
+                continue;
+            }
+            if let Some(Ok(specified)) = registration.parse_value(value) {
+                let computed = specified.to_computed_value(self.context);
+                computed_values.push((name.clone(), computed.to_variable_value()));
+            }
+        }
+        if computed_values.is_empty() {
+            return;
+        }
+        let mut custom_properties = (*custom_properties).clone();
+        for (name, value) in computed_values {
+            custom_properties.insert(name, value);
+        }
+        self.context.builder.custom_properties = Some(Arc::new(custom_properties));
+    }
+
     fn compute_visited_style_if_needed<E>(
         &mut self,
         element: Option<E>,
@@ -198,6 +198,19 @@ impl ComputedValuesInner {
         }
     }
 
+    /// Sets the computed value of a custom property, for animations.
+    pub fn set_custom_property(
+        &mut self,
+        name: crate::custom_properties::Name,
+        value: Arc<crate::custom_properties::ComputedValue>,
+    ) {
+        let mut custom_properties = self.custom_properties
+            .as_ref()
+            .map_or_else(Default::default, |map| (**map).clone());
+        custom_properties.insert(name, value);
+        self.custom_properties = Some(Arc::new(custom_properties));
+    }
+
     fn to_outer(
         self,
         pseudo: Option<<&PseudoElement>,
@@ -449,7 +449,7 @@ ${helpers.predefined_type(
 ${helpers.single_keyword(
     "scroll-behavior",
     "auto smooth",
-    engines="gecko",
+    engines="gecko servo-2013",
     spec="https://drafts.csswg.org/cssom-view/#propdef-scroll-behavior",
     animation_value_type="discrete",
     gecko_enum_prefix="StyleScrollBehavior",
@@ -537,7 +537,7 @@ ${helpers.predefined_type(
     "resize",
     "Resize",
     "computed::Resize::None",
-    engines="gecko",
+    engines="gecko servo-2013",
     animation_value_type="discrete",
     needs_context=False,
     gecko_ffi_name="mResize",
@@ -17,14 +17,15 @@ ${helpers.predefined_type(
 )}
 
 // NB: `pointer-events: auto` (and use of `pointer-events` in anything that isn't SVG, in fact)
-// is nonstandard, slated for CSS4-UI.
-// TODO(pcwalton): SVG-only values.
+// is nonstandard, slated for CSS4-UI. The SVG-only values behave like `auto` on CSS boxes.
 ${helpers.single_keyword(
     "pointer-events",
     "auto none",
     engines="gecko servo-2013 servo-2020",
     animation_value_type="discrete",
     extra_gecko_values="visiblepainted visiblefill visiblestroke visible painted fill stroke all",
+    extra_servo_2013_values="visiblepainted visiblefill visiblestroke visible painted fill stroke all",
+    extra_servo_2020_values="visiblepainted visiblefill visiblestroke visible painted fill stroke all",
     spec="https://www.w3.org/TR/SVG11/interact.html#PointerEventsProperty",
     gecko_enum_prefix="StylePointerEvents",
 )}
@@ -75,9 +76,10 @@ ${helpers.predefined_type(
     "scrollbar-color",
     "ui::ScrollbarColor",
     "Default::default()",
-    engines="gecko",
+    engines="gecko servo-2013",
     spec="https://drafts.csswg.org/css-scrollbars-1/#scrollbar-color",
     gecko_pref="layout.css.scrollbar-color.enabled",
+    servo_2013_pref="layout.scrollbars.enabled",
     # Surprisingly, yes the computed value of scrollbar-color has no effect on
     # Gecko scrollbar elements, since the value only matters on the scrollable
     # element itself.
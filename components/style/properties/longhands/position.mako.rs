@@ -132,7 +132,7 @@ ${helpers.single_keyword(
         "align-items",
         "stretch flex-start flex-end center baseline",
         engines="servo-2013 servo-2020",
-        servo_2020_pref="layout.2020.unimplemented",
+        servo_2020_pref="layout.grid.enabled",
         extra_prefixes="webkit",
         spec="https://drafts.csswg.org/css-flexbox/#align-items-property",
         animation_value_type="discrete",
@@ -208,7 +208,7 @@ ${helpers.predefined_type(
         "align-self",
         "auto stretch flex-start flex-end center baseline",
         engines="servo-2013 servo-2020",
-        servo_2020_pref="layout.2020.unimplemented",
+        servo_2020_pref="layout.grid.enabled",
         extra_prefixes="webkit",
         spec="https://drafts.csswg.org/css-flexbox/#propdef-align-self",
         animation_value_type="discrete",
@@ -347,7 +347,8 @@ ${helpers.predefined_type(
             "grid-%s-%s" % (kind, range),
             "GridLine",
             "Default::default()",
-            engines="gecko",
+            engines="gecko servo-2020",
+            servo_2020_pref="layout.grid.enabled",
             animation_value_type="discrete",
             spec="https://drafts.csswg.org/css-grid/#propdef-grid-%s-%s" % (kind, range),
         )}
@@ -357,7 +358,8 @@ ${helpers.predefined_type(
         "grid-auto-%ss" % kind,
         "ImplicitGridTracks",
         "Default::default()",
-        engines="gecko",
+        engines="gecko servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         animation_value_type="discrete",
         spec="https://drafts.csswg.org/css-grid/#propdef-grid-auto-%ss" % kind,
     )}
@@ -366,7 +368,8 @@ ${helpers.predefined_type(
         "grid-template-%ss" % kind,
         "GridTemplateComponent",
         "specified::GenericGridTemplateComponent::None",
-        engines="gecko",
+        engines="gecko servo-2020",
+        servo_2020_pref="layout.grid.enabled",
         spec="https://drafts.csswg.org/css-grid/#propdef-grid-template-%ss" % kind,
         animation_value_type="ComputedValue",
     )}
@@ -377,7 +380,8 @@ ${helpers.predefined_type(
     "grid-auto-flow",
     "GridAutoFlow",
     "computed::GridAutoFlow::row()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-grid/#propdef-grid-auto-flow",
 )}
@@ -386,7 +390,8 @@ ${helpers.predefined_type(
     "grid-template-areas",
     "GridTemplateAreas",
     "computed::GridTemplateAreas::none()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     animation_value_type="discrete",
     spec="https://drafts.csswg.org/css-grid/#propdef-grid-template-areas",
 )}
@@ -395,10 +400,11 @@ ${helpers.predefined_type(
     "column-gap",
     "length::NonNegativeLengthPercentageOrNormal",
     "computed::length::NonNegativeLengthPercentageOrNormal::normal()",
-    engines="gecko servo-2013",
-    alias="grid-column-gap" if engine == "gecko" else "",
+    engines="gecko servo-2013 servo-2020",
+    alias="grid-column-gap" if engine in ["gecko", "servo-2020"] else "",
     extra_prefixes="moz:layout.css.prefixes.columns",
     servo_2013_pref="layout.columns.enabled",
+    servo_2020_pref="layout.grid.enabled",
     spec="https://drafts.csswg.org/css-align-3/#propdef-column-gap",
     animation_value_type="NonNegativeLengthPercentageOrNormal",
     servo_restyle_damage="reflow",
@@ -409,7 +415,8 @@ ${helpers.predefined_type(
     "row-gap",
     "length::NonNegativeLengthPercentageOrNormal",
     "computed::length::NonNegativeLengthPercentageOrNormal::normal()",
-    engines="gecko",
+    engines="gecko servo-2020",
+    servo_2020_pref="layout.grid.enabled",
     alias="grid-row-gap",
     spec="https://drafts.csswg.org/css-align-3/#propdef-row-gap",
     animation_value_type="NonNegativeLengthPercentageOrNormal",
@@ -3101,6 +3101,19 @@ impl ComputedValuesInner {
         }
     % endfor
 
+    /// Sets the computed value of a custom property, for animations.
+    pub fn set_custom_property(
+        &mut self,
+        name: crate::custom_properties::Name,
+        value: Arc<crate::custom_properties::ComputedValue>,
+    ) {
+        let mut custom_properties = self.custom_properties
+            .as_ref()
+            .map_or_else(Default::default, |map| (**map).clone());
+        custom_properties.insert(name, value);
+        self.custom_properties = Some(Arc::new(custom_properties));
+    }
+
     /// Gets a reference to the rule node. Panic if no rule node exists.
     pub fn rules(&self) -> &StrongRuleNode {
         self.rules.as_ref().unwrap()
@@ -0,0 +1,27 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Support for [registered custom properties][spec], which have a syntax, may
+//! not inherit, and have an initial value.
+//!
+//! [spec]: https://drafts.css-houdini.org/css-properties-values-api/
+
+pub mod registry;
+pub mod syntax;
+pub mod value;
+
+/// Whether registering custom properties, with `@property` rules or with
+/// `CSS.registerProperty()`, is enabled.
+#[cfg(feature = "servo")]
+pub fn enabled() -> bool {
+    use servo_config::pref;
+    pref!(layout.css.properties_and_values.enabled)
+}
+
+/// Whether registering custom properties, with `@property` rules or with
+/// `CSS.registerProperty()`, is enabled.
+#[cfg(not(feature = "servo"))]
+pub fn enabled() -> bool {
+    false
+}
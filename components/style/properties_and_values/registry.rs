@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The registrations of custom properties, and the registry of a document.
+
+use super::syntax::Descriptor;
+use super::value::{self, SpecifiedValue};
+use crate::context::QuirksMode;
+use crate::custom_properties::{Name, VariableValue};
+use crate::media_queries::Device;
+use crate::selector_map::PrecomputedHashMap;
+use crate::stylesheets::UrlExtraData;
+use crate::values::computed::Context;
+use servo_arc::Arc;
+
+/// A custom property registration, from `CSS.registerProperty()` or from an
+/// `@property` rule.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#the-registeredpropertyset>
+#[derive(Clone, Debug)]
+pub struct PropertyRegistration {
+    /// The name of the property, without the `--` prefix.
+    pub name: Name,
+    /// The syntax that the values of the property must match.
+    pub syntax: Descriptor,
+    /// Whether the property inherits.
+    pub inherits: bool,
+    /// The initial value of the property. Once the registration is in a
+    /// registry, this is the computed initial value.
+    pub initial_value: Option<Arc<VariableValue>>,
+    /// The URL data the initial value and the values of the property are
+    /// resolved against.
+    pub url_data: UrlExtraData,
+}
+
+impl PropertyRegistration {
+    /// Checks that the registration has an initial value if the syntax
+    /// requires one, and that it matches the syntax and is computationally
+    /// independent.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#the-registerproperty-function>
+    pub fn validate(&self) -> Result<(), ()> {
+        let initial_value = match self.initial_value {
+            Some(ref initial_value) => initial_value,
+            None if self.syntax.is_universal() => return Ok(()),
+            None => return Err(()),
+        };
+        if initial_value.has_references() {
+            return Err(());
+        }
+        if self.syntax.is_universal() {
+            return Ok(());
+        }
+        SpecifiedValue::parse(&self.syntax, initial_value, &self.url_data)?;
+        if !value::is_computationally_independent(initial_value) {
+            return Err(());
+        }
+        Ok(())
+    }
+
+    /// Parses a value of the property against its syntax, returning `None`
+    /// for the universal syntax, whose values aren't typed.
+    pub fn parse_value(&self, value: &VariableValue) -> Option<Result<SpecifiedValue, ()>> {
+        if self.syntax.is_universal() {
+            return None;
+        }
+        Some(SpecifiedValue::parse(&self.syntax, value, &self.url_data))
+    }
+}
+
+/// The custom properties registered in a document, which the device the
+/// document's styles are computed for holds.
+#[derive(Debug, Default)]
+pub struct PropertyRegistry {
+    properties: PrecomputedHashMap<Name, Arc<PropertyRegistration>>,
+}
+
+impl PropertyRegistry {
+    /// Returns the registration of the custom property `name`, if any.
+    #[inline]
+    pub fn get(&self, name: &Name) -> Option<&Arc<PropertyRegistration>> {
+        self.properties.get(name)
+    }
+
+    /// Whether no custom property is registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Iterates over the registrations.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<PropertyRegistration>> {
+        self.properties.values()
+    }
+
+    /// Adds a valid registration, replacing any previous registration of the
+    /// same property, and computes its initial value for `device`.
+    pub fn insert(
+        &mut self,
+        registration: &PropertyRegistration,
+        device: &Device,
+        quirks_mode: QuirksMode,
+    ) {
+        debug_assert!(registration.validate().is_ok());
+        let mut registration = registration.clone();
+        let specified_initial_value = registration
+            .initial_value
+            .as_ref()
+            .and_then(|initial_value| registration.parse_value(initial_value))
+            .and_then(|value| value.ok());
+        if let Some(value) = specified_initial_value {
+            let computed = Context::for_media_query_evaluation(device, quirks_mode, |context| {
+                value.to_computed_value(context)
+            });
+            registration.initial_value = Some(computed.to_variable_value());
+        }
+        self.properties
+            .insert(registration.name.clone(), Arc::new(registration));
+    }
+}
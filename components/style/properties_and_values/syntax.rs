@@ -0,0 +1,252 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The syntax strings of registered custom properties.
+//!
+//! <https://drafts.css-houdini.org/css-properties-values-api/#syntax-strings>
+
+use crate::values::CustomIdent;
+use cssparser::{Parser, ParserInput, Token};
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// The data types that a syntax component may name.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#supported-names>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum DataType {
+    /// `<length>`
+    Length,
+    /// `<number>`
+    Number,
+    /// `<percentage>`
+    Percentage,
+    /// `<length-percentage>`
+    LengthPercentage,
+    /// `<color>`
+    Color,
+    /// `<image>`
+    Image,
+    /// `<url>`
+    Url,
+    /// `<integer>`
+    Integer,
+    /// `<angle>`
+    Angle,
+    /// `<time>`
+    Time,
+    /// `<resolution>`
+    Resolution,
+    /// `<transform-function>`
+    TransformFunction,
+    /// `<custom-ident>`
+    CustomIdent,
+    /// `<transform-list>`, which is a list by itself and thus can't have a
+    /// multiplier.
+    TransformList,
+}
+
+impl DataType {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "length" => DataType::Length,
+            "number" => DataType::Number,
+            "percentage" => DataType::Percentage,
+            "length-percentage" => DataType::LengthPercentage,
+            "color" => DataType::Color,
+            "image" => DataType::Image,
+            "url" => DataType::Url,
+            "integer" => DataType::Integer,
+            "angle" => DataType::Angle,
+            "time" => DataType::Time,
+            "resolution" => DataType::Resolution,
+            "transform-function" => DataType::TransformFunction,
+            "custom-ident" => DataType::CustomIdent,
+            "transform-list" => DataType::TransformList,
+            _ => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            DataType::Length => "length",
+            DataType::Number => "number",
+            DataType::Percentage => "percentage",
+            DataType::LengthPercentage => "length-percentage",
+            DataType::Color => "color",
+            DataType::Image => "image",
+            DataType::Url => "url",
+            DataType::Integer => "integer",
+            DataType::Angle => "angle",
+            DataType::Time => "time",
+            DataType::Resolution => "resolution",
+            DataType::TransformFunction => "transform-function",
+            DataType::CustomIdent => "custom-ident",
+            DataType::TransformList => "transform-list",
+        }
+    }
+}
+
+/// What a syntax component matches: a value of a data type, or an
+/// identifier literally.
+#[derive(Clone, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum ComponentName {
+    /// A data type name, like `<length>`.
+    DataType(DataType),
+    /// An identifier, which only matches itself.
+    Ident(CustomIdent),
+}
+
+/// How many values a syntax component matches.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#multipliers>
+#[derive(Clone, Copy, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub enum Multiplier {
+    /// `+`, a space-separated list of one or more values.
+    Space,
+    /// `#`, a comma-separated list of one or more values.
+    Comma,
+}
+
+/// A syntax component, like `<length>+`.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#syntax-component>
+#[derive(Clone, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub struct Component {
+    /// What the component matches.
+    pub name: ComponentName,
+    /// Whether the component matches a list of values.
+    pub multiplier: Option<Multiplier>,
+}
+
+impl ToCss for Component {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match self.name {
+            ComponentName::DataType(data_type) => {
+                dest.write_char('<')?;
+                dest.write_str(data_type.name())?;
+                dest.write_char('>')?;
+            },
+            ComponentName::Ident(ref ident) => ident.to_css(dest)?,
+        }
+        match self.multiplier {
+            Some(Multiplier::Space) => dest.write_char('+'),
+            Some(Multiplier::Comma) => dest.write_char('#'),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A syntax definition, that is, the alternative components that the values
+/// of a registered custom property must match, or the universal syntax `*`
+/// if there are none.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#syntax-definition>
+#[derive(Clone, Debug, Eq, MallocSizeOf, PartialEq, ToShmem)]
+pub struct Descriptor {
+    components: Box<[Component]>,
+}
+
+impl Descriptor {
+    /// The universal syntax definition, which matches any value.
+    pub fn universal() -> Self {
+        Descriptor {
+            components: Box::new([]),
+        }
+    }
+
+    /// Whether this is the universal syntax definition.
+    pub fn is_universal(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// The components of the syntax definition, in order.
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Parses a syntax string.
+    ///
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#consume-syntax-definition>
+    pub fn from_str(css: &str) -> Result<Self, ()> {
+        let mut input = ParserInput::new(css);
+        let mut input = Parser::new(&mut input);
+        input.parse_entirely(Self::parse).map_err(|_| ())
+    }
+
+    fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        if input.try(|input| input.expect_delim('*')).is_ok() {
+            return Ok(Self::universal());
+        }
+        let mut components = vec![];
+        loop {
+            components.push(parse_component(input)?);
+            input.skip_whitespace();
+            if input.is_exhausted() {
+                break;
+            }
+            input.expect_delim('|')?;
+        }
+        Ok(Descriptor {
+            components: components.into_boxed_slice(),
+        })
+    }
+}
+
+impl ToCss for Descriptor {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        if self.is_universal() {
+            return dest.write_char('*');
+        }
+        for (i, component) in self.components.iter().enumerate() {
+            if i != 0 {
+                dest.write_str(" | ")?;
+            }
+            component.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a syntax component, where the data type names and the multipliers
+/// can't be separated by whitespace.
+fn parse_component<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Component, ParseError<'i>> {
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    let name = if input.try(|input| input.expect_delim('<')).is_ok() {
+        let data_type = match *input.next_including_whitespace()? {
+            Token::Ident(ref name) => DataType::from_name(name),
+            _ => None,
+        };
+        let data_type = data_type
+            .ok_or_else(|| location.new_custom_error(StyleParseErrorKind::UnspecifiedError))?;
+        match *input.next_including_whitespace()? {
+            Token::Delim('>') => {},
+            _ => return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError)),
+        }
+        ComponentName::DataType(data_type)
+    } else {
+        let ident = input.expect_ident()?;
+        ComponentName::Ident(CustomIdent::from_ident(location, ident, &[])?)
+    };
+    let state = input.state();
+    let multiplier = match input.next_including_whitespace() {
+        Ok(&Token::Delim('+')) => Some(Multiplier::Space),
+        Ok(&Token::Delim('#')) => Some(Multiplier::Comma),
+        _ => {
+            input.reset(&state);
+            None
+        },
+    };
+    if multiplier.is_some() && name == ComponentName::DataType(DataType::TransformList) {
+        return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+    }
+    Ok(Component { name, multiplier })
+}
@@ -0,0 +1,454 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The typed values of registered custom properties.
+//!
+//! The custom properties map of a style keeps storing token streams, so that
+//! `var()` substitution doesn't need to know about registrations. For
+//! registered properties, those are the serializations of the computed
+//! values, which are parsed back when they need to be interpolated.
+
+use super::syntax::{ComponentName, DataType, Descriptor, Multiplier};
+use crate::context::QuirksMode;
+use crate::custom_properties::VariableValue;
+use crate::parser::{Parse, ParserContext};
+use crate::stylesheets::{CssRuleType, Origin, UrlExtraData};
+use crate::values::animated::{Animate, Procedure, ToAnimatedValue};
+use crate::values::computed::{self, Context, ToComputedValue};
+use crate::values::specified;
+use crate::values::CustomIdent;
+use cssparser::{Parser, ParserInput, Token};
+use servo_arc::Arc;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, ParsingMode, StyleParseErrorKind, ToCss};
+
+/// A specified value of a data type or an identifier.
+#[derive(Clone, Debug)]
+pub enum SpecifiedComponent {
+    /// A `<length>`.
+    Length(specified::Length),
+    /// A `<number>`.
+    Number(specified::Number),
+    /// A `<percentage>`.
+    Percentage(specified::Percentage),
+    /// A `<length-percentage>`.
+    LengthPercentage(specified::LengthPercentage),
+    /// A `<color>`.
+    Color(specified::Color),
+    /// An `<image>`, kept as its serialization.
+    Image(String),
+    /// A `<url>`, kept as its serialization.
+    Url(String),
+    /// An `<integer>`.
+    Integer(specified::Integer),
+    /// An `<angle>`.
+    Angle(specified::Angle),
+    /// A `<time>`.
+    Time(specified::Time),
+    /// A `<resolution>`.
+    Resolution(specified::Resolution),
+    /// A `<transform-function>`, or a `<transform-list>`.
+    Transform(specified::Transform),
+    /// A `<custom-ident>`, or an identifier of the syntax.
+    Ident(CustomIdent),
+}
+
+impl SpecifiedComponent {
+    fn parse<'i, 't>(
+        context: &ParserContext,
+        name: &ComponentName,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        let data_type = match *name {
+            ComponentName::DataType(data_type) => data_type,
+            ComponentName::Ident(ref expected) => {
+                let ident = input.expect_ident()?;
+                if **ident != *expected.0 {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                return Ok(SpecifiedComponent::Ident(expected.clone()));
+            },
+        };
+        Ok(match data_type {
+            DataType::Length => {
+                SpecifiedComponent::Length(specified::Length::parse(context, input)?)
+            },
+            DataType::Number => {
+                SpecifiedComponent::Number(specified::Number::parse(context, input)?)
+            },
+            DataType::Percentage => {
+                SpecifiedComponent::Percentage(specified::Percentage::parse(context, input)?)
+            },
+            DataType::LengthPercentage => SpecifiedComponent::LengthPercentage(
+                specified::LengthPercentage::parse(context, input)?,
+            ),
+            DataType::Color => SpecifiedComponent::Color(specified::Color::parse(context, input)?),
+            DataType::Image => {
+                SpecifiedComponent::Image(specified::Image::parse(context, input)?.to_css_string())
+            },
+            DataType::Url => SpecifiedComponent::Url(
+                specified::url::SpecifiedUrl::parse(context, input)?.to_css_string(),
+            ),
+            DataType::Integer => {
+                SpecifiedComponent::Integer(specified::Integer::parse(context, input)?)
+            },
+            DataType::Angle => SpecifiedComponent::Angle(specified::Angle::parse(context, input)?),
+            DataType::Time => SpecifiedComponent::Time(specified::Time::parse(context, input)?),
+            DataType::Resolution => {
+                SpecifiedComponent::Resolution(specified::Resolution::parse(context, input)?)
+            },
+            DataType::TransformFunction | DataType::TransformList => {
+                let transform = specified::Transform::parse(context, input)?;
+                let expected_single = data_type == DataType::TransformFunction;
+                if transform.0.is_empty() || (expected_single && transform.0.len() != 1) {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                SpecifiedComponent::Transform(transform)
+            },
+            DataType::CustomIdent => {
+                let ident = input.expect_ident()?;
+                SpecifiedComponent::Ident(CustomIdent::from_ident(location, ident, &[])?)
+            },
+        })
+    }
+
+    fn to_computed_value(&self, context: &Context) -> ComputedComponent {
+        match *self {
+            SpecifiedComponent::Length(ref value) => {
+                ComputedComponent::Length(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Number(ref value) => {
+                ComputedComponent::Number(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Percentage(ref value) => {
+                ComputedComponent::Percentage(value.to_computed_value(context))
+            },
+            SpecifiedComponent::LengthPercentage(ref value) => {
+                ComputedComponent::LengthPercentage(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Color(ref value) => {
+                ComputedComponent::Color(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Image(ref value) => ComputedComponent::Image(value.clone()),
+            SpecifiedComponent::Url(ref value) => ComputedComponent::Url(value.clone()),
+            SpecifiedComponent::Integer(ref value) => {
+                ComputedComponent::Integer(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Angle(ref value) => {
+                ComputedComponent::Angle(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Time(ref value) => {
+                ComputedComponent::Time(value.to_computed_value(context).seconds())
+            },
+            SpecifiedComponent::Resolution(ref value) => {
+                ComputedComponent::Resolution(value.to_computed_value(context).dppx())
+            },
+            SpecifiedComponent::Transform(ref value) => {
+                ComputedComponent::Transform(value.to_computed_value(context))
+            },
+            SpecifiedComponent::Ident(ref value) => ComputedComponent::Ident(value.clone()),
+        }
+    }
+}
+
+/// A computed value of a data type or an identifier.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComputedComponent {
+    /// A `<length>`.
+    Length(computed::Length),
+    /// A `<number>`.
+    Number(computed::Number),
+    /// A `<percentage>`.
+    Percentage(computed::Percentage),
+    /// A `<length-percentage>`.
+    LengthPercentage(computed::LengthPercentage),
+    /// A `<color>`.
+    Color(computed::Color),
+    /// An `<image>`, as its specified serialization.
+    Image(String),
+    /// A `<url>`, as its specified serialization.
+    Url(String),
+    /// An `<integer>`.
+    Integer(computed::Integer),
+    /// An `<angle>`.
+    Angle(computed::Angle),
+    /// A `<time>`, in seconds.
+    Time(f32),
+    /// A `<resolution>`, in dots per pixel.
+    Resolution(f32),
+    /// A `<transform-function>`, or a `<transform-list>`.
+    Transform(computed::Transform),
+    /// A `<custom-ident>`, or an identifier of the syntax.
+    Ident(CustomIdent),
+}
+
+impl ToCss for ComputedComponent {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            ComputedComponent::Length(ref value) => value.to_css(dest),
+            ComputedComponent::Number(ref value) => value.to_css(dest),
+            ComputedComponent::Percentage(ref value) => value.to_css(dest),
+            ComputedComponent::LengthPercentage(ref value) => value.to_css(dest),
+            ComputedComponent::Color(ref value) => value.to_css(dest),
+            ComputedComponent::Image(ref value) | ComputedComponent::Url(ref value) => {
+                dest.write_str(value)
+            },
+            ComputedComponent::Integer(ref value) => value.to_css(dest),
+            ComputedComponent::Angle(ref value) => value.to_css(dest),
+            ComputedComponent::Time(seconds) => computed::Time::from_seconds(seconds).to_css(dest),
+            ComputedComponent::Resolution(dppx) => {
+                computed::Resolution::from_dppx(dppx).to_css(dest)
+            },
+            ComputedComponent::Transform(ref value) => value.to_css(dest),
+            ComputedComponent::Ident(ref value) => value.to_css(dest),
+        }
+    }
+}
+
+/// Interpolates the components of the data types that can be interpolated,
+/// and fails for the others, which animate discretely.
+impl Animate for ComputedComponent {
+    fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()> {
+        Ok(match (self, other) {
+            (&ComputedComponent::Length(ref this), &ComputedComponent::Length(ref other)) => {
+                ComputedComponent::Length(this.animate(other, procedure)?)
+            },
+            (&ComputedComponent::Number(ref this), &ComputedComponent::Number(ref other)) => {
+                ComputedComponent::Number(this.animate(other, procedure)?)
+            },
+            (
+                &ComputedComponent::Percentage(ref this),
+                &ComputedComponent::Percentage(ref other),
+            ) => ComputedComponent::Percentage(this.animate(other, procedure)?),
+            (
+                &ComputedComponent::LengthPercentage(ref this),
+                &ComputedComponent::LengthPercentage(ref other),
+            ) => ComputedComponent::LengthPercentage(this.animate(other, procedure)?),
+            (&ComputedComponent::Color(ref this), &ComputedComponent::Color(ref other)) => {
+                let animated = this
+                    .to_animated_value()
+                    .animate(&other.to_animated_value(), procedure)?;
+                ComputedComponent::Color(ToAnimatedValue::from_animated_value(animated))
+            },
+            (&ComputedComponent::Integer(ref this), &ComputedComponent::Integer(ref other)) => {
+                ComputedComponent::Integer(this.animate(other, procedure)?)
+            },
+            (&ComputedComponent::Angle(ref this), &ComputedComponent::Angle(ref other)) => {
+                ComputedComponent::Angle(this.animate(other, procedure)?)
+            },
+            (&ComputedComponent::Time(ref this), &ComputedComponent::Time(ref other)) => {
+                ComputedComponent::Time(this.animate(other, procedure)?)
+            },
+            (
+                &ComputedComponent::Resolution(ref this),
+                &ComputedComponent::Resolution(ref other),
+            ) => ComputedComponent::Resolution(this.animate(other, procedure)?),
+            (&ComputedComponent::Transform(ref this), &ComputedComponent::Transform(ref other)) => {
+                ComputedComponent::Transform(this.animate(other, procedure)?)
+            },
+            _ => return Err(()),
+        })
+    }
+}
+
+/// A specified value of a registered custom property, that matched one of
+/// the components of its syntax.
+#[derive(Clone, Debug)]
+pub enum SpecifiedValue {
+    /// A value that matched a component without a multiplier.
+    Single(SpecifiedComponent),
+    /// The values that matched a component with a multiplier.
+    List(Vec<SpecifiedComponent>, Multiplier),
+}
+
+impl SpecifiedValue {
+    /// Parses the tokens of a custom property value as a value that matches
+    /// `syntax`, trying the components of the syntax in order. Fails for the
+    /// universal syntax, whose values aren't typed.
+    pub fn parse(
+        syntax: &Descriptor,
+        value: &VariableValue,
+        url_data: &UrlExtraData,
+    ) -> Result<Self, ()> {
+        let context = ParserContext::new(
+            Origin::Author,
+            url_data,
+            Some(CssRuleType::Style),
+            ParsingMode::DEFAULT,
+            QuirksMode::NoQuirks,
+            None,
+            None,
+        );
+        let mut input = ParserInput::new(value.css());
+        let mut input = Parser::new(&mut input);
+        for component in syntax.components() {
+            let result = input.try(|input| {
+                input.parse_entirely(|input| {
+                    let multiplier = match component.multiplier {
+                        Some(multiplier) => multiplier,
+                        None => {
+                            let value =
+                                SpecifiedComponent::parse(&context, &component.name, input)?;
+                            return Ok(SpecifiedValue::Single(value));
+                        },
+                    };
+                    let values = match multiplier {
+                        Multiplier::Comma => input.parse_comma_separated(|input| {
+                            SpecifiedComponent::parse(&context, &component.name, input)
+                        })?,
+                        Multiplier::Space => {
+                            let mut values = vec![];
+                            loop {
+                                values.push(SpecifiedComponent::parse(
+                                    &context,
+                                    &component.name,
+                                    input,
+                                )?);
+                                input.skip_whitespace();
+                                if input.is_exhausted() {
+                                    break;
+                                }
+                            }
+                            values
+                        },
+                    };
+                    Ok(SpecifiedValue::List(values, multiplier))
+                })
+            });
+            if let Ok(value) = result {
+                return Ok(value);
+            }
+        }
+        Err(())
+    }
+
+    /// Computes the value in `context`.
+    pub fn to_computed_value(&self, context: &Context) -> ComputedValue {
+        match *self {
+            SpecifiedValue::Single(ref value) => {
+                ComputedValue::Single(value.to_computed_value(context))
+            },
+            SpecifiedValue::List(ref values, multiplier) => ComputedValue::List(
+                values
+                    .iter()
+                    .map(|value| value.to_computed_value(context))
+                    .collect(),
+                multiplier,
+            ),
+        }
+    }
+}
+
+/// A computed value of a registered custom property.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComputedValue {
+    /// A value that matched a component without a multiplier.
+    Single(ComputedComponent),
+    /// The values that matched a component with a multiplier.
+    List(Vec<ComputedComponent>, Multiplier),
+}
+
+impl ComputedValue {
+    /// Converts the value to the token stream that the custom properties map
+    /// stores.
+    pub fn to_variable_value(&self) -> Arc<VariableValue> {
+        let css = self.to_css_string();
+        let mut input = ParserInput::new(&css);
+        let mut input = Parser::new(&mut input);
+        VariableValue::parse(&mut input).expect("Computed values should serialize to valid tokens")
+    }
+}
+
+impl ToCss for ComputedValue {
+    fn to_css<W>(&self, dest: &mut CssWriter<W>) -> fmt::Result
+    where
+        W: Write,
+    {
+        match *self {
+            ComputedValue::Single(ref value) => value.to_css(dest),
+            ComputedValue::List(ref values, multiplier) => {
+                let separator = match multiplier {
+                    Multiplier::Space => " ",
+                    Multiplier::Comma => ", ",
+                };
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        dest.write_str(separator)?;
+                    }
+                    value.to_css(dest)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Interpolates single values, and lists of the same length item by item.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#animation-behavior-of-custom-properties>
+impl Animate for ComputedValue {
+    fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()> {
+        match (self, other) {
+            (&ComputedValue::Single(ref this), &ComputedValue::Single(ref other)) => {
+                Ok(ComputedValue::Single(this.animate(other, procedure)?))
+            },
+            (
+                &ComputedValue::List(ref this, multiplier),
+                &ComputedValue::List(ref other, other_multiplier),
+            ) if multiplier == other_multiplier && this.len() == other.len() => {
+                let values = this
+                    .iter()
+                    .zip(other.iter())
+                    .map(|(this, other)| this.animate(other, procedure))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ComputedValue::List(values, multiplier))
+            },
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether a value doesn't depend on the style of the element or on the
+/// viewport, as the initial values of registered properties must.
+///
+/// <https://drafts.css-houdini.org/css-properties-values-api/#computationally-independent>
+pub fn is_computationally_independent(value: &VariableValue) -> bool {
+    fn is_independent<'i, 't>(input: &mut Parser<'i, 't>) -> bool {
+        while let Ok(token) = input.next() {
+            let nested = match *token {
+                Token::Dimension { ref unit, .. } => {
+                    const RELATIVE_UNITS: &[&str] =
+                        &["em", "ex", "ch", "rem", "vw", "vh", "vmin", "vmax"];
+                    if RELATIVE_UNITS
+                        .iter()
+                        .any(|relative| unit.eq_ignore_ascii_case(relative))
+                    {
+                        return false;
+                    }
+                    false
+                },
+                Token::Function(..) |
+                Token::ParenthesisBlock |
+                Token::SquareBracketBlock |
+                Token::CurlyBracketBlock => true,
+                _ => false,
+            };
+            if nested {
+                let result: Result<bool, ParseError> =
+                    input.parse_nested_block(|input| Ok(is_independent(input)));
+                if !result.unwrap_or(false) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    let mut input = ParserInput::new(value.css());
+    is_independent(&mut Parser::new(&mut input))
+}
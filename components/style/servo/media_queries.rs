@@ -10,6 +10,7 @@ use crate::media_queries::media_feature::{Evaluator, MediaFeatureDescription};
 use crate::media_queries::media_feature_expression::RangeOrOperator;
 use crate::media_queries::MediaType;
 use crate::properties::ComputedValues;
+use crate::properties_and_values::registry::PropertyRegistry;
 use crate::values::computed::CSSPixelLength;
 use crate::values::specified::font::FONT_MEDIUM_PX;
 use crate::values::KeyframesName;
@@ -17,6 +18,7 @@ use app_units::Au;
 use cssparser::RGBA;
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::{Scale, SideOffsets2D, Size2D};
+use servo_arc::Arc;
 use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
 use style_traits::viewport::ViewportConstraints;
 use style_traits::{CSSPixel, DevicePixel};
@@ -33,6 +35,8 @@ pub struct Device {
     viewport_size: Size2D<f32, CSSPixel>,
     /// The current device pixel ratio, from CSS pixels to device pixels.
     device_pixel_ratio: Scale<f32, CSSPixel, DevicePixel>,
+    /// The color scheme that the user prefers.
+    prefers_color_scheme: PrefersColorScheme,
 
     /// The font size of the root element
     /// This is set when computing the style of the root
@@ -54,6 +58,9 @@ pub struct Device {
     /// The CssEnvironment object responsible of getting CSS environment
     /// variables.
     environment: CssEnvironment,
+    /// The custom properties registered in the document.
+    #[ignore_malloc_size_of = "Arc"]
+    property_registry: Arc<PropertyRegistry>,
 }
 
 impl Device {
@@ -67,11 +74,13 @@ impl Device {
             media_type,
             viewport_size,
             device_pixel_ratio,
+            prefers_color_scheme: PrefersColorScheme::Light,
             // FIXME(bz): Seems dubious?
             root_font_size: AtomicIsize::new(Au::from_px(FONT_MEDIUM_PX).0 as isize),
             used_root_font_size: AtomicBool::new(false),
             used_viewport_units: AtomicBool::new(false),
             environment: CssEnvironment,
+            property_registry: Arc::new(PropertyRegistry::default()),
         }
     }
 
@@ -81,6 +90,17 @@ impl Device {
         &self.environment
     }
 
+    /// The custom properties registered in the document.
+    #[inline]
+    pub fn property_registry(&self) -> &Arc<PropertyRegistry> {
+        &self.property_registry
+    }
+
+    /// Sets the custom properties registered in the document.
+    pub fn set_property_registry(&mut self, registry: Arc<PropertyRegistry>) {
+        self.property_registry = registry;
+    }
+
     /// Return the default computed values for this device.
     pub fn default_computed_values(&self) -> &ComputedValues {
         // FIXME(bz): This isn't really right, but it's no more wrong
@@ -145,6 +165,16 @@ impl Device {
         self.device_pixel_ratio
     }
 
+    /// Returns the color scheme that the user prefers.
+    pub fn prefers_color_scheme(&self) -> PrefersColorScheme {
+        self.prefers_color_scheme
+    }
+
+    /// Sets the color scheme that the user prefers.
+    pub fn set_prefers_color_scheme(&mut self, prefers_color_scheme: PrefersColorScheme) {
+        self.prefers_color_scheme = prefers_color_scheme;
+    }
+
     /// Take into account a viewport rule taken from the stylesheets.
     pub fn account_for_viewport_rule(&mut self, constraints: &ViewportConstraints) {
         self.viewport_size = constraints.size;
@@ -189,6 +219,25 @@ fn eval_width(
     )
 }
 
+#[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
+#[repr(u8)]
+enum Orientation {
+    Landscape,
+    Portrait,
+}
+
+/// https://drafts.csswg.org/mediaqueries-4/#orientation
+fn eval_orientation(device: &Device, value: Option<Orientation>) -> bool {
+    let size = device.au_viewport_size();
+    // Square viewports are portrait, per spec.
+    let is_landscape = size.width > size.height;
+    match value {
+        Some(Orientation::Landscape) => is_landscape,
+        Some(Orientation::Portrait) => !is_landscape,
+        None => true,
+    }
+}
+
 #[derive(Clone, Copy, Debug, FromPrimitive, Parse, ToCss)]
 #[repr(u8)]
 enum Scan {
@@ -203,20 +252,61 @@ fn eval_scan(_: &Device, _: Option<Scan>) -> bool {
     false
 }
 
+/// Values for the prefers-color-scheme media feature.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    FromPrimitive,
+    MallocSizeOf,
+    Parse,
+    PartialEq,
+    Serialize,
+    ToCss,
+)]
+#[repr(u8)]
+#[allow(missing_docs)]
+pub enum PrefersColorScheme {
+    Light,
+    Dark,
+}
+
+/// https://drafts.csswg.org/mediaqueries-5/#prefers-color-scheme
+fn eval_prefers_color_scheme(device: &Device, value: Option<PrefersColorScheme>) -> bool {
+    match value {
+        Some(value) => device.prefers_color_scheme() == value,
+        None => true,
+    }
+}
+
 lazy_static! {
     /// A list with all the media features that Servo supports.
-    pub static ref MEDIA_FEATURES: [MediaFeatureDescription; 2] = [
+    pub static ref MEDIA_FEATURES: [MediaFeatureDescription; 4] = [
         feature!(
             atom!("width"),
             AllowsRanges::Yes,
             Evaluator::Length(eval_width),
             ParsingRequirements::empty(),
         ),
+        feature!(
+            atom!("orientation"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_orientation, Orientation),
+            ParsingRequirements::empty(),
+        ),
         feature!(
             atom!("scan"),
             AllowsRanges::No,
             keyword_evaluator!(eval_scan, Scan),
             ParsingRequirements::empty(),
         ),
+        feature!(
+            atom!("prefers-color-scheme"),
+            AllowsRanges::No,
+            keyword_evaluator!(eval_prefers_color_scheme, PrefersColorScheme),
+            ParsingRequirements::empty(),
+        ),
     ];
 }
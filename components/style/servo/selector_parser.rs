@@ -47,7 +47,11 @@ pub enum PseudoElement {
     // APPLIES_TO_PLACEHOLDER properties expect!
 
     // Non-eager pseudos.
+    Backdrop,
     DetailsSummary,
+    WebkitScrollbar,
+    WebkitScrollbarThumb,
+    WebkitScrollbarTrack,
     DetailsContent,
     ServoText,
     ServoInputText,
@@ -78,7 +82,11 @@ impl ToCss for PseudoElement {
             After => "::after",
             Before => "::before",
             Selection => "::selection",
+            Backdrop => "::backdrop",
             DetailsSummary => "::-servo-details-summary",
+            WebkitScrollbar => "::-webkit-scrollbar",
+            WebkitScrollbarThumb => "::-webkit-scrollbar-thumb",
+            WebkitScrollbarTrack => "::-webkit-scrollbar-track",
             DetailsContent => "::-servo-details-content",
             ServoText => "::-servo-text",
             ServoInputText => "::-servo-input-text",
@@ -220,7 +228,11 @@ impl PseudoElement {
             PseudoElement::After | PseudoElement::Before | PseudoElement::Selection => {
                 PseudoElementCascadeType::Eager
             },
-            PseudoElement::DetailsSummary => PseudoElementCascadeType::Lazy,
+            PseudoElement::Backdrop |
+            PseudoElement::DetailsSummary |
+            PseudoElement::WebkitScrollbar |
+            PseudoElement::WebkitScrollbarThumb |
+            PseudoElement::WebkitScrollbarTrack => PseudoElementCascadeType::Lazy,
             PseudoElement::DetailsContent |
             PseudoElement::ServoText |
             PseudoElement::ServoInputText |
@@ -287,11 +299,14 @@ pub enum NonTSPseudoClass {
     Indeterminate,
     Lang(Lang),
     Link,
+    Modal,
     PlaceholderShown,
     ReadWrite,
     ReadOnly,
     ServoNonZeroBorder,
     Target,
+    Valid,
+    Invalid,
     Visited,
 }
 
@@ -341,11 +356,14 @@ impl ToCss for NonTSPseudoClass {
             Hover => ":hover",
             Indeterminate => ":indeterminate",
             Link => ":link",
+            Modal => ":modal",
             PlaceholderShown => ":placeholder-shown",
             ReadWrite => ":read-write",
             ReadOnly => ":read-only",
             ServoNonZeroBorder => ":-servo-nonzero-border",
             Target => ":target",
+            Valid => ":valid",
+            Invalid => ":invalid",
             Visited => ":visited",
             Lang(_) => unreachable!(),
         })
@@ -378,9 +396,12 @@ impl NonTSPseudoClass {
             Disabled => ElementState::IN_DISABLED_STATE,
             Checked => ElementState::IN_CHECKED_STATE,
             Indeterminate => ElementState::IN_INDETERMINATE_STATE,
+            Modal => ElementState::IN_MODAL_STATE,
             ReadOnly | ReadWrite => ElementState::IN_READ_WRITE_STATE,
             PlaceholderShown => ElementState::IN_PLACEHOLDER_SHOWN_STATE,
             Target => ElementState::IN_TARGET_STATE,
+            Valid => ElementState::IN_VALID_STATE,
+            Invalid => ElementState::IN_INVALID_STATE,
 
             AnyLink | Lang(_) | Link | Visited | ServoNonZeroBorder => ElementState::empty(),
         }
@@ -447,10 +468,13 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "hover" => Hover,
             "indeterminate" => Indeterminate,
             "link" => Link,
+            "modal" => Modal,
             "placeholder-shown" => PlaceholderShown,
             "read-write" => ReadWrite,
             "read-only" => ReadOnly,
             "target" => Target,
+            "valid" => Valid,
+            "invalid" => Invalid,
             "visited" => Visited,
             "-servo-nonzero-border" => {
                 if !self.in_user_agent_stylesheet() {
@@ -492,6 +516,10 @@ impl<'a, 'i> ::selectors::Parser<'i> for SelectorParser<'a> {
             "before" => Before,
             "after" => After,
             "selection" => Selection,
+            "backdrop" => Backdrop,
+            "-webkit-scrollbar" => WebkitScrollbar,
+            "-webkit-scrollbar-thumb" => WebkitScrollbarThumb,
+            "-webkit-scrollbar-track" => WebkitScrollbarTrack,
             "-servo-details-summary" => {
                 if !self.in_user_agent_stylesheet() {
                     return Err(location.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone())))
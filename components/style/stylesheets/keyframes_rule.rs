@@ -4,6 +4,7 @@
 
 //! Keyframes: https://drafts.csswg.org/css-animations/#keyframes
 
+use crate::custom_properties::Name as CustomPropertyName;
 use crate::error_reporting::ContextualParseError;
 use crate::parser::ParserContext;
 use crate::properties::longhands::transition_timing_function::single_value::SpecifiedValue as SpecifiedTimingFunction;
@@ -353,16 +354,21 @@ pub struct KeyframesAnimation {
     pub steps: Vec<KeyframesStep>,
     /// The properties that change in this animation.
     pub properties_changed: LonghandIdSet,
+    /// The custom properties that change in this animation, which animate if
+    /// they're registered.
+    pub custom_properties_changed: Vec<CustomPropertyName>,
     /// Vendor prefix type the @keyframes has.
     pub vendor_prefix: Option<VendorPrefix>,
 }
 
-/// Get all the animated properties in a keyframes animation.
+/// Get all the animated properties in a keyframes animation, and the custom
+/// properties it sets.
 fn get_animated_properties(
     keyframes: &[Arc<Locked<Keyframe>>],
     guard: &SharedRwLockReadGuard,
-) -> LonghandIdSet {
+) -> (LonghandIdSet, Vec<CustomPropertyName>) {
     let mut ret = LonghandIdSet::new();
+    let mut custom_properties = vec![];
     // NB: declarations are already deduplicated, so we don't have to check for
     // it here.
     for keyframe in keyframes {
@@ -377,7 +383,12 @@ fn get_animated_properties(
         for declaration in block.normal_declaration_iter() {
             let longhand_id = match declaration.id() {
                 PropertyDeclarationId::Longhand(id) => id,
-                _ => continue,
+                PropertyDeclarationId::Custom(name) => {
+                    if !custom_properties.contains(name) {
+                        custom_properties.push(name.clone());
+                    }
+                    continue;
+                },
             };
 
             if longhand_id == LonghandId::Display {
@@ -392,7 +403,7 @@ fn get_animated_properties(
         }
     }
 
-    ret
+    (ret, custom_properties)
 }
 
 impl KeyframesAnimation {
@@ -412,6 +423,7 @@ impl KeyframesAnimation {
         let mut result = KeyframesAnimation {
             steps: vec![],
             properties_changed: LonghandIdSet::new(),
+            custom_properties_changed: vec![],
             vendor_prefix,
         };
 
@@ -419,8 +431,11 @@ impl KeyframesAnimation {
             return result;
         }
 
-        result.properties_changed = get_animated_properties(keyframes, guard);
-        if result.properties_changed.is_empty() {
+        let (properties_changed, custom_properties_changed) =
+            get_animated_properties(keyframes, guard);
+        result.properties_changed = properties_changed;
+        result.custom_properties_changed = custom_properties_changed;
+        if result.properties_changed.is_empty() && result.custom_properties_changed.is_empty() {
             return result;
         }
 
@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The [`@property`][property] at-rule.
+//!
+//! [property]: https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule
+
+use crate::custom_properties::{self, Name, VariableValue};
+use crate::error_reporting::ContextualParseError;
+use crate::parser::ParserContext;
+use crate::properties_and_values::registry::PropertyRegistration;
+use crate::properties_and_values::syntax::Descriptor;
+use crate::shared_lock::{SharedRwLockReadGuard, ToCssWithGuard};
+use crate::str::CssStringWriter;
+use crate::stylesheets::UrlExtraData;
+use cssparser::{serialize_string, Parser, SourceLocation};
+use cssparser::{AtRuleParser, CowRcStr, DeclarationListParser, DeclarationParser};
+use selectors::parser::SelectorParseErrorKind;
+use servo_arc::Arc;
+use std::fmt::{self, Write};
+use style_traits::{CssWriter, ParseError, StyleParseErrorKind, ToCss};
+
+/// An [`@property`][property] rule, that registers a custom property.
+///
+/// The rule is only kept if it has valid `syntax` and `inherits` descriptors,
+/// and an initial value that matches the syntax if it requires one.
+///
+/// [property]: https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule
+#[derive(Clone, Debug, ToShmem)]
+pub struct PropertyRule {
+    /// The name of the registered property, without the `--` prefix.
+    pub name: Name,
+    /// The `syntax` descriptor.
+    pub syntax: Descriptor,
+    /// The `inherits` descriptor.
+    pub inherits: bool,
+    /// The `initial-value` descriptor, if any.
+    pub initial_value: Option<Arc<VariableValue>>,
+    /// The URL data of the stylesheet the rule is in.
+    pub url_data: UrlExtraData,
+    /// The source position this rule was found at.
+    pub source_location: SourceLocation,
+}
+
+impl PropertyRule {
+    /// Parses the name of an `@property` rule, which must be a custom
+    /// property name.
+    pub fn parse_name<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Name, ParseError<'i>> {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        match custom_properties::parse_name(ident) {
+            Ok(name) => Ok(Name::from(name)),
+            Err(()) => {
+                Err(location
+                    .new_custom_error(SelectorParseErrorKind::UnexpectedIdent(ident.clone())))
+            },
+        }
+    }
+
+    /// Parses the descriptors of an `@property` rule.
+    pub fn parse<'i, 't>(
+        context: &ParserContext,
+        name: Name,
+        input: &mut Parser<'i, 't>,
+        source_location: SourceLocation,
+    ) -> Result<Self, ParseError<'i>> {
+        let mut descriptors = PropertyDescriptors::default();
+        {
+            let parser = PropertyRuleParser {
+                descriptors: &mut descriptors,
+            };
+            let mut iter = DeclarationListParser::new(input, parser);
+            while let Some(declaration) = iter.next() {
+                if let Err((error, slice)) = declaration {
+                    let location = error.location;
+                    let error = ContextualParseError::UnsupportedPropertyDescriptorDeclaration(
+                        slice, error,
+                    );
+                    context.log_css_error(location, error)
+                }
+            }
+        }
+
+        let invalid = || source_location.new_custom_error(StyleParseErrorKind::UnspecifiedError);
+        let rule = PropertyRule {
+            name,
+            syntax: descriptors.syntax.ok_or_else(invalid)?,
+            inherits: descriptors.inherits.ok_or_else(invalid)?,
+            initial_value: descriptors.initial_value,
+            url_data: context.url_data.clone(),
+            source_location,
+        };
+        if rule.to_registration().validate().is_err() {
+            return Err(invalid());
+        }
+        Ok(rule)
+    }
+
+    /// The registration of the property this rule registers.
+    pub fn to_registration(&self) -> PropertyRegistration {
+        PropertyRegistration {
+            name: self.name.clone(),
+            syntax: self.syntax.clone(),
+            inherits: self.inherits,
+            initial_value: self.initial_value.clone(),
+            url_data: self.url_data.clone(),
+        }
+    }
+}
+
+impl ToCssWithGuard for PropertyRule {
+    /// <https://drafts.css-houdini.org/css-properties-values-api/#serialize-a-csspropertyrule>
+    fn to_css(&self, _guard: &SharedRwLockReadGuard, dest: &mut CssStringWriter) -> fmt::Result {
+        dest.write_str("@property --")?;
+        dest.write_str(&self.name)?;
+        dest.write_str(" { syntax: ")?;
+        serialize_string(&self.syntax.to_css_string(), dest)?;
+        dest.write_str("; inherits: ")?;
+        dest.write_str(if self.inherits { "true" } else { "false" })?;
+        dest.write_str("; ")?;
+        if let Some(ref initial_value) = self.initial_value {
+            dest.write_str("initial-value: ")?;
+            initial_value.to_css(&mut CssWriter::new(dest))?;
+            dest.write_str("; ")?;
+        }
+        dest.write_str("}")
+    }
+}
+
+#[derive(Default)]
+struct PropertyDescriptors {
+    syntax: Option<Descriptor>,
+    inherits: Option<bool>,
+    initial_value: Option<Arc<VariableValue>>,
+}
+
+struct PropertyRuleParser<'a> {
+    descriptors: &'a mut PropertyDescriptors,
+}
+
+/// Default methods reject all at rules.
+impl<'a, 'i> AtRuleParser<'i> for PropertyRuleParser<'a> {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = ();
+    type Error = StyleParseErrorKind<'i>;
+}
+
+impl<'a, 'i> DeclarationParser<'i> for PropertyRuleParser<'a> {
+    type Declaration = ();
+    type Error = StyleParseErrorKind<'i>;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<(), ParseError<'i>> {
+        let location = input.current_source_location();
+        let invalid = || location.new_custom_error(StyleParseErrorKind::UnspecifiedError);
+        match_ignore_ascii_case! { &*name,
+            "syntax" => {
+                let syntax = input.expect_string()?.clone();
+                let syntax = Descriptor::from_str(&syntax).map_err(|()| invalid())?;
+                self.descriptors.syntax = Some(syntax);
+            },
+            "inherits" => {
+                let inherits = try_match_ident_ignore_ascii_case! { input,
+                    "true" => true,
+                    "false" => false,
+                };
+                self.descriptors.inherits = Some(inherits);
+            },
+            "initial-value" => {
+                self.descriptors.initial_value = Some(VariableValue::parse(input)?);
+            },
+            _ => return Err(input.new_custom_error(SelectorParseErrorKind::UnexpectedIdent(name.clone()))),
+        }
+        Ok(())
+    }
+}
@@ -5,11 +5,13 @@
 //! Parsing of the stylesheet contents.
 
 use crate::counter_style::{parse_counter_style_body, parse_counter_style_name_definition};
+use crate::custom_properties::Name;
 use crate::error_reporting::ContextualParseError;
 use crate::font_face::parse_font_face_block;
 use crate::media_queries::MediaList;
 use crate::parser::{Parse, ParserContext};
 use crate::properties::parse_property_declaration_list;
+use crate::properties_and_values;
 use crate::selector_parser::{SelectorImpl, SelectorParser};
 use crate::shared_lock::{Locked, SharedRwLock};
 use crate::str::starts_with_ignore_ascii_case;
@@ -19,9 +21,10 @@ use crate::stylesheets::keyframes_rule::parse_keyframe_list;
 use crate::stylesheets::stylesheet::Namespaces;
 use crate::stylesheets::supports_rule::SupportsCondition;
 use crate::stylesheets::viewport_rule;
+use crate::stylesheets::ViewportRule;
 use crate::stylesheets::{CorsMode, DocumentRule, FontFeatureValuesRule, KeyframesRule, MediaRule};
 use crate::stylesheets::{CssRule, CssRuleType, CssRules, RulesMutateError, StylesheetLoader};
-use crate::stylesheets::{NamespaceRule, PageRule, StyleRule, SupportsRule, ViewportRule};
+use crate::stylesheets::{NamespaceRule, PageRule, PropertyRule, StyleRule, SupportsRule};
 use crate::values::computed::font::FamilyName;
 use crate::values::{CssUrl, CustomIdent, KeyframesName};
 use crate::{Namespace, Prefix};
@@ -162,6 +165,8 @@ pub enum AtRuleBlockPrelude {
     Page,
     /// A @document rule, with its conditional.
     Document(DocumentCondition),
+    /// An @property rule prelude, with the name of the property.
+    Property(Name),
 }
 
 /// A rule prelude for at-rule without block.
@@ -446,6 +451,13 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                 let cond = DocumentCondition::parse(self.context, input)?;
                 Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Document(cond)))
             },
+            "property" => {
+                if !properties_and_values::enabled() {
+                    return Err(input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone())));
+                }
+                let name = PropertyRule::parse_name(input)?;
+                Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Property(name)))
+            },
             _ => Err(input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone())))
         }
     }
@@ -566,6 +578,17 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser<'a, 'b> {
                     },
                 ))))
             },
+            AtRuleBlockPrelude::Property(name) => {
+                let context = ParserContext::new_with_rule_type(
+                    self.context,
+                    CssRuleType::Property,
+                    self.namespaces,
+                );
+
+                Ok(CssRule::Property(Arc::new(self.shared_lock.wrap(
+                    PropertyRule::parse(&context, name, input, source_location)?,
+                ))))
+            },
         }
     }
 }
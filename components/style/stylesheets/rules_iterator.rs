@@ -89,6 +89,7 @@ where
                     CssRule::Viewport(_) |
                     CssRule::Keyframes(_) |
                     CssRule::Page(_) |
+                    CssRule::Property(_) |
                     CssRule::FontFeatureValues(_) => return Some(rule),
                     CssRule::Import(ref import_rule) => {
                         let import_rule = import_rule.read_with(self.guard);
@@ -16,6 +16,7 @@ use crate::invalidation::media_queries::{EffectiveMediaQueryResults, ToMediaList
 use crate::media_queries::Device;
 use crate::properties::{self, CascadeMode, ComputedValues};
 use crate::properties::{AnimationRules, PropertyDeclarationBlock};
+use crate::properties_and_values::registry::{PropertyRegistration, PropertyRegistry};
 use crate::rule_cache::{RuleCache, RuleCacheConditions};
 use crate::rule_collector::{containing_shadow_ignoring_svg_use, RuleCollector};
 use crate::rule_tree::{CascadeLevel, RuleTree, StrongRuleNode, StyleSource};
@@ -389,6 +390,11 @@ pub struct Stylist {
 
     /// The total number of times the stylist has been rebuilt.
     num_rebuilds: usize,
+
+    /// The custom properties registered with `CSS.registerProperty()`, in
+    /// order.
+    #[ignore_malloc_size_of = "Arc"]
+    script_property_registrations: Vec<PropertyRegistration>,
 }
 
 /// What cascade levels to include when styling elements.
@@ -427,6 +433,7 @@ impl Stylist {
             author_styles_enabled: AuthorStylesEnabled::Yes,
             rule_tree: RuleTree::new(),
             num_rebuilds: 0,
+            script_property_registrations: Vec::new(),
         }
     }
 
@@ -561,9 +568,35 @@ impl Stylist {
             .rebuild(&self.device, self.quirks_mode, flusher, guards)
             .unwrap_or_else(|_| warn!("OOM in Stylist::flush"));
 
+        self.update_property_registry();
+
         had_invalidations
     }
 
+    /// Registers a custom property from `CSS.registerProperty()`, which takes
+    /// precedence over the `@property` rules for the same property.
+    ///
+    /// The registration applies once the stylist is flushed.
+    pub fn register_custom_property(&mut self, registration: PropertyRegistration) {
+        self.script_property_registrations.push(registration);
+        self.force_stylesheet_origins_dirty(Origin::Author.into());
+    }
+
+    /// Rebuilds the registry of custom properties of the device, from the
+    /// `@property` rules in cascade order and then from the registrations
+    /// from script.
+    fn update_property_registry(&mut self) {
+        let mut registry = PropertyRegistry::default();
+        let rule_registrations = self
+            .cascade_data
+            .iter_origins_rev()
+            .flat_map(|(data, _)| data.property_registrations.iter());
+        for registration in rule_registrations.chain(self.script_property_registrations.iter()) {
+            registry.insert(registration, &self.device, self.quirks_mode);
+        }
+        self.device.set_property_registry(Arc::new(registry));
+    }
+
     /// Insert a given stylesheet before another stylesheet in the document.
     pub fn insert_stylesheet_before(
         &mut self,
@@ -1061,6 +1094,7 @@ impl Stylist {
             }
         }
 
+        device.set_property_registry(self.device.property_registry().clone());
         self.device = device;
         self.media_features_change_changed_style(guards, &self.device)
     }
@@ -1780,6 +1814,11 @@ pub struct CascadeData {
     /// by name.
     animations: PrecomputedHashMap<Atom, KeyframesAnimation>,
 
+    /// The custom properties registered by `@property` rules at this
+    /// `CascadeData`'s origin, in order.
+    #[ignore_malloc_size_of = "Arc"]
+    property_registrations: Vec<PropertyRegistration>,
+
     /// Effective media query results cached from the last rebuild.
     effective_media_query_results: EffectiveMediaQueryResults,
 
@@ -1812,6 +1851,7 @@ impl CascadeData {
             mapped_ids: PrecomputedHashSet::default(),
             selectors_for_cache_revalidation: SelectorMap::new(),
             animations: Default::default(),
+            property_registrations: Vec::new(),
             extra_data: ExtraStyleData::default(),
             effective_media_query_results: EffectiveMediaQueryResults::new(),
             rules_source_order: 0,
@@ -2116,6 +2156,10 @@ impl CascadeData {
                             .try_insert(keyframes_rule.name.as_atom().clone(), animation)?;
                     }
                 },
+                CssRule::Property(ref rule) => {
+                    let rule = rule.read_with(guard);
+                    self.property_registrations.push(rule.to_registration());
+                },
                 #[cfg(feature = "gecko")]
                 CssRule::FontFace(ref rule) => {
                     self.extra_data.add_font_face(rule);
@@ -2185,7 +2229,8 @@ impl CascadeData {
                 CssRule::Page(..) |
                 CssRule::Viewport(..) |
                 CssRule::Document(..) |
-                CssRule::FontFeatureValues(..) => {
+                CssRule::FontFeatureValues(..) |
+                CssRule::Property(..) => {
                     // Not affected by device changes.
                     continue;
                 },
@@ -2249,6 +2294,7 @@ impl CascadeData {
             host_rules.clear();
         }
         self.animations.clear();
+        self.property_registrations.clear();
         self.extra_data.clear();
         self.rules_source_order = 0;
         self.num_selectors = 0;
@@ -34,6 +34,14 @@ fn moz_box_display_values_enabled(context: &ParserContext) -> bool {
         static_prefs::pref!("layout.css.xul-box-display-values.content.enabled")
 }
 
+/// Whether `display: grid` and `display: inline-grid` are supported, which
+/// they only are in layout 2020 when the grid layout pref is set.
+#[cfg(feature = "servo-layout-2020")]
+fn grid_enabled() -> bool {
+    use servo_config::pref;
+    pref!(layout.grid.enabled)
+}
+
 /// Defines an element’s display type, which consists of
 /// the two basic qualities of how an element generates boxes
 /// <https://drafts.csswg.org/css-display/#propdef-display>
@@ -65,7 +73,7 @@ pub enum DisplayInside {
     FlowRoot,
     #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     Flex,
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     Grid,
     #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     Table,
@@ -107,6 +115,8 @@ pub enum DisplayInside {
     MozDeck,
     #[cfg(feature = "gecko")]
     MozPopup,
+    #[cfg(feature = "servo-layout-2020")]
+    Math,
 }
 
 #[allow(missing_docs)]
@@ -148,9 +158,9 @@ impl Display {
     pub const Flex: Self = Self::new(DisplayOutside::Block, DisplayInside::Flex);
     #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const InlineFlex: Self = Self::new(DisplayOutside::Inline, DisplayInside::Flex);
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     pub const Grid: Self = Self::new(DisplayOutside::Block, DisplayInside::Grid);
-    #[cfg(feature = "gecko")]
+    #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
     pub const InlineGrid: Self = Self::new(DisplayOutside::Inline, DisplayInside::Grid);
     #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
     pub const Table: Self = Self::new(DisplayOutside::Block, DisplayInside::Table);
@@ -316,6 +326,8 @@ impl Display {
             Display::InlineBlock => true,
             #[cfg(any(feature = "servo-layout-2013"))]
             Display::InlineFlex | Display::InlineTable => true,
+            #[cfg(feature = "servo-layout-2020")]
+            Display::InlineGrid => true,
             _ => false,
         }
     }
@@ -328,7 +340,7 @@ impl Display {
         match self.inside() {
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             DisplayInside::Flex => true,
-            #[cfg(feature = "gecko")]
+            #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
             DisplayInside::Grid => true,
             _ => false,
         }
@@ -426,7 +438,7 @@ impl ToCss for Display {
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             Display::TableCaption => dest.write_str("table-caption"),
             _ => match (outside, inside) {
-                #[cfg(feature = "gecko")]
+                #[cfg(any(feature = "servo-layout-2020", feature = "gecko"))]
                 (DisplayOutside::Inline, DisplayInside::Grid) => dest.write_str("inline-grid"),
                 #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
                 (DisplayOutside::Inline, DisplayInside::Flex) |
@@ -436,6 +448,8 @@ impl ToCss for Display {
                 },
                 #[cfg(feature = "gecko")]
                 (DisplayOutside::Block, DisplayInside::Ruby) => dest.write_str("block ruby"),
+                #[cfg(feature = "servo-layout-2020")]
+                (DisplayOutside::Block, DisplayInside::Math) => dest.write_str("block math"),
                 (_, inside) => {
                     if self.is_list_item() {
                         if outside != DisplayOutside::Block {
@@ -456,8 +470,9 @@ impl ToCss for Display {
     }
 }
 
-/// <display-inside> = flow | flow-root | table | flex | grid | ruby
+/// <display-inside> = flow | flow-root | table | flex | grid | ruby | math
 /// https://drafts.csswg.org/css-display/#typedef-display-inside
+/// https://mathml-refresh.github.io/mathml-core/#new-display-math-value
 fn parse_display_inside<'i, 't>(
     input: &mut Parser<'i, 't>,
 ) -> Result<DisplayInside, ParseError<'i>> {
@@ -471,8 +486,12 @@ fn parse_display_inside<'i, 't>(
         "flex" => DisplayInside::Flex,
         #[cfg(feature = "gecko")]
         "grid" => DisplayInside::Grid,
+        #[cfg(feature = "servo-layout-2020")]
+        "grid" if grid_enabled() => DisplayInside::Grid,
         #[cfg(feature = "gecko")]
         "ruby" => DisplayInside::Ruby,
+        #[cfg(feature = "servo-layout-2020")]
+        "math" => DisplayInside::Math,
     })
 }
 
@@ -558,6 +577,10 @@ impl Parse for Display {
                 // https://drafts.csswg.org/css-display/#inside-model
                 #[cfg(feature = "gecko")]
                 DisplayInside::Ruby => DisplayOutside::Inline,
+                // Likewise for math.
+                // https://mathml-refresh.github.io/mathml-core/#new-display-math-value
+                #[cfg(feature = "servo-layout-2020")]
+                DisplayInside::Math => DisplayOutside::Inline,
                 _ => DisplayOutside::Block,
             });
             return Ok(Display::from3(outside, inside, got_list_item));
@@ -577,6 +600,8 @@ impl Parse for Display {
             "inline-flex" | "-webkit-inline-flex" => Display::InlineFlex,
             #[cfg(feature = "gecko")]
             "inline-grid" => Display::InlineGrid,
+            #[cfg(feature = "servo-layout-2020")]
+            "inline-grid" if grid_enabled() => Display::InlineGrid,
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
             "table-caption" => Display::TableCaption,
             #[cfg(any(feature = "servo-layout-2013", feature = "gecko"))]
@@ -640,6 +665,8 @@ impl SpecifiedValueInfo for Display {
             "inline list-item",
             "inline flow-root list-item",
             "list-item",
+            "block math",
+            "math",
             "none",
             "block ruby",
             "ruby",
@@ -30,6 +30,8 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use keyboard_types::webdriver::send_keys;
 use msg::constellation_msg::{BrowsingContextId, TopLevelBrowsingContextId, TraversalDirection};
+use net_traits::user_agent_data::UserAgentOverride;
+use net_traits::NetworkConditions;
 use pixels::PixelFormat;
 use script_traits::webdriver_msg::{LoadStatus, WebDriverCookieError, WebDriverFrameId};
 use script_traits::webdriver_msg::{
@@ -90,6 +92,16 @@ fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
             "/session/{sessionId}/servo/prefs/reset",
             ServoExtensionRoute::ResetPrefs,
         ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/user_agent/set",
+            ServoExtensionRoute::SetUserAgentOverride,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/network_conditions/set",
+            ServoExtensionRoute::SetNetworkConditions,
+        ),
     ];
 }
 
@@ -196,6 +208,8 @@ enum ServoExtensionRoute {
     GetPrefs,
     SetPrefs,
     ResetPrefs,
+    SetUserAgentOverride,
+    SetNetworkConditions,
 }
 
 impl WebDriverExtensionRoute for ServoExtensionRoute {
@@ -219,6 +233,14 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
                 let parameters: GetPrefsParameters = serde_json::from_value(body_data.clone())?;
                 ServoExtensionCommand::ResetPrefs(parameters)
             },
+            ServoExtensionRoute::SetUserAgentOverride => {
+                let parameters: UserAgentOverride = serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::SetUserAgentOverride(parameters)
+            },
+            ServoExtensionRoute::SetNetworkConditions => {
+                let parameters: NetworkConditions = serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::SetNetworkConditions(parameters)
+            },
         };
         Ok(WebDriverCommand::Extension(command))
     }
@@ -229,6 +251,8 @@ enum ServoExtensionCommand {
     GetPrefs(GetPrefsParameters),
     SetPrefs(SetPrefsParameters),
     ResetPrefs(GetPrefsParameters),
+    SetUserAgentOverride(UserAgentOverride),
+    SetNetworkConditions(NetworkConditions),
 }
 
 impl WebDriverExtensionCommand for ServoExtensionCommand {
@@ -237,6 +261,8 @@ impl WebDriverExtensionCommand for ServoExtensionCommand {
             ServoExtensionCommand::GetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::SetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::ResetPrefs(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::SetUserAgentOverride(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::SetNetworkConditions(ref x) => serde_json::to_value(x).ok(),
         }
     }
 }
@@ -1681,6 +1707,32 @@ impl Handler {
             serde_json::to_value(prefs)?,
         )))
     }
+
+    /// Makes the browser of the session present itself as another user
+    /// agent, or as the default one again when nothing is overridden.
+    fn handle_set_user_agent_override(
+        &self,
+        ua_override: &UserAgentOverride,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        let ua_override = Some(ua_override.clone())
+            .filter(|ua_override| *ua_override != UserAgentOverride::default());
+        let msg =
+            ConstellationMsg::SetUserAgentOverride(top_level_browsing_context_id, ua_override);
+        self.constellation_chan.send(msg).unwrap();
+        Ok(WebDriverResponse::Void)
+    }
+
+    fn handle_set_network_conditions(
+        &self,
+        conditions: &NetworkConditions,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        let conditions = Some(*conditions).filter(|conditions| *conditions != Default::default());
+        let msg = ConstellationMsg::SetNetworkConditions(top_level_browsing_context_id, conditions);
+        self.constellation_chan.send(msg).unwrap();
+        Ok(WebDriverResponse::Void)
+    }
 }
 
 impl WebDriverHandler<ServoExtensionRoute> for Handler {
@@ -1770,6 +1822,12 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
                 ServoExtensionCommand::ResetPrefs(ref x) => self.handle_reset_prefs(x),
+                ServoExtensionCommand::SetUserAgentOverride(ref x) => {
+                    self.handle_set_user_agent_override(x)
+                },
+                ServoExtensionCommand::SetNetworkConditions(ref x) => {
+                    self.handle_set_network_conditions(x)
+                },
             },
             _ => Err(WebDriverError::new(
                 ErrorStatus::UnsupportedOperation,
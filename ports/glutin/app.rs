@@ -72,7 +72,7 @@ impl App {
 
         let mut servo = Servo::new(embedder, window.clone());
         let browser_id = BrowserId::new();
-        servo.handle_events(vec![WindowEvent::NewBrowser(get_default_url(), browser_id)]);
+        servo.handle_events(vec![WindowEvent::NewBrowser(get_default_url(), browser_id, None)]);
         servo.setup_logging();
 
         register_window(window);
@@ -105,6 +105,16 @@ impl App {
             glutin::Event::Awakened => {
                 self.event_queue.borrow_mut().push(WindowEvent::Idle);
             },
+            glutin::Event::DeviceEvent {
+                event: glutin::DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                WINDOWS.with(|windows| {
+                    for window in windows.borrow().values() {
+                        window.handle_mouse_motion(delta);
+                    }
+                });
+            },
             glutin::Event::DeviceEvent { .. } => {},
 
             // Window level events
@@ -3,22 +3,21 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::keyutils::{CMD_OR_ALT, CMD_OR_CONTROL};
-use crate::window_trait::{WindowPortsMethods, LINE_HEIGHT};
-use euclid::{Point2D, Vector2D};
+use crate::window_trait::WindowPortsMethods;
 use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
 use servo::compositing::windowing::{WebRenderDebugOption, WindowEvent};
 use servo::embedder_traits::{
-    EmbedderMsg, FilterPattern, PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
-    PermissionPrompt,
+    AuthenticatorError, EmbedderMsg, FilterPattern, PermissionRequest, PromptDefinition,
+    PromptOrigin, PromptResult, PermissionPrompt, ScreenshotFormat, SpeechError, SpeechEvent,
+    SpeechEventKind,
 };
 use servo::msg::constellation_msg::TopLevelBrowsingContextId as BrowserId;
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::net_traits::pub_domains::is_reg_domain;
-use servo::script_traits::TouchEventType;
+use servo::script_traits::ScreenshotArea;
 use servo::servo_config::opts;
 use servo::servo_config::pref;
 use servo::servo_url::ServoUrl;
-use servo::webrender_api::ScrollLocation;
 use clipboard::{ClipboardContext, ClipboardProvider};
 use std::env;
 use std::fs::File;
@@ -145,6 +144,16 @@ where
                     Duration::from_secs(duration),
                 ));
             })
+            .shortcut(Modifiers::CONTROL, Key::F8, || {
+                if let Some(id) = self.browser_id {
+                    self.event_queue.push(WindowEvent::TakeScreenshot(
+                        id,
+                        0,
+                        ScreenshotArea::FullPage,
+                        ScreenshotFormat::Png,
+                    ));
+                }
+            })
             .shortcut(Modifiers::CONTROL, Key::F9, || {
                 self.event_queue.push(WindowEvent::CaptureWebRender)
             })
@@ -223,58 +232,9 @@ where
             })
             .shortcut(CMD_OR_CONTROL, '0', || {
                 self.event_queue.push(WindowEvent::ResetZoom)
-            })
-            .shortcut(Modifiers::empty(), Key::PageDown, || {
-                let scroll_location = ScrollLocation::Delta(Vector2D::new(
-                    0.0,
-                    -self.window.page_height() + 2.0 * LINE_HEIGHT,
-                ));
-                self.scroll_window_from_key(scroll_location, TouchEventType::Move);
-            })
-            .shortcut(Modifiers::empty(), Key::PageUp, || {
-                let scroll_location = ScrollLocation::Delta(Vector2D::new(
-                    0.0,
-                    self.window.page_height() - 2.0 * LINE_HEIGHT,
-                ));
-                self.scroll_window_from_key(scroll_location, TouchEventType::Move);
-            })
-            .shortcut(Modifiers::empty(), Key::Home, || {
-                self.scroll_window_from_key(ScrollLocation::Start, TouchEventType::Move);
-            })
-            .shortcut(Modifiers::empty(), Key::End, || {
-                self.scroll_window_from_key(ScrollLocation::End, TouchEventType::Move);
-            })
-            .shortcut(Modifiers::empty(), Key::ArrowUp, || {
-                self.scroll_window_from_key(
-                    ScrollLocation::Delta(Vector2D::new(0.0, 3.0 * LINE_HEIGHT)),
-                    TouchEventType::Move,
-                );
-            })
-            .shortcut(Modifiers::empty(), Key::ArrowDown, || {
-                self.scroll_window_from_key(
-                    ScrollLocation::Delta(Vector2D::new(0.0, -3.0 * LINE_HEIGHT)),
-                    TouchEventType::Move,
-                );
-            })
-            .shortcut(Modifiers::empty(), Key::ArrowLeft, || {
-                self.scroll_window_from_key(
-                    ScrollLocation::Delta(Vector2D::new(LINE_HEIGHT, 0.0)),
-                    TouchEventType::Move,
-                );
-            })
-            .shortcut(Modifiers::empty(), Key::ArrowRight, || {
-                self.scroll_window_from_key(
-                    ScrollLocation::Delta(Vector2D::new(-LINE_HEIGHT, 0.0)),
-                    TouchEventType::Move,
-                );
             });
     }
 
-    fn scroll_window_from_key(&mut self, scroll_location: ScrollLocation, phase: TouchEventType) {
-        let event = WindowEvent::Scroll(scroll_location, Point2D::zero(), phase);
-        self.event_queue.push(event);
-    }
-
     pub fn handle_servo_events(&mut self, events: Vec<(Option<BrowserId>, EmbedderMsg)>) {
         for (browser_id, msg) in events {
             match msg {
@@ -494,6 +454,17 @@ where
                         self.event_queue.push(WindowEvent::SendError(None, reason));
                     };
                 },
+                EmbedderMsg::SelectDirectory(sender) => {
+                    let directory = if opts::get().headless {
+                        None
+                    } else {
+                        get_selected_directory()
+                    };
+                    if let Err(e) = sender.send(directory) {
+                        let reason = format!("Failed to send SelectDirectory response: {}", e);
+                        self.event_queue.push(WindowEvent::SendError(None, reason));
+                    };
+                },
                 EmbedderMsg::PromptPermission(prompt, sender) => {
                     let permission_state = prompt_user(prompt);
                     let _ = sender.send(permission_state);
@@ -504,6 +475,48 @@ where
                 EmbedderMsg::HideIME => {
                     debug!("HideIME received");
                 },
+                EmbedderMsg::ShowDateTimePicker(_kind, value, _min, _max, sender) => {
+                    let value = if opts::get().headless {
+                        None
+                    } else {
+                        tinyfiledialogs::input_box("", "Enter a value", &tiny_dialog_escape(&value))
+                    };
+                    let _ = sender.send(value);
+                },
+                EmbedderMsg::ShowColorPicker(value, sender) => {
+                    let value = if opts::get().headless {
+                        None
+                    } else {
+                        tinyfiledialogs::color_chooser_dialog(
+                            "Choose a color",
+                            tinyfiledialogs::DefaultColorValue::Hex(&value),
+                        )
+                        .map(|(hex, _)| hex.to_ascii_lowercase())
+                    };
+                    let _ = sender.send(value);
+                },
+                EmbedderMsg::ShowValidationMessage(message, _rect) => {
+                    if !opts::get().headless {
+                        let _ = thread::Builder::new()
+                            .name("display validation message".to_owned())
+                            .spawn(move || {
+                                tinyfiledialogs::message_box_ok(
+                                    "",
+                                    &tiny_dialog_escape(&message),
+                                    MessageBoxIcon::Warning,
+                                );
+                            });
+                    }
+                },
+                EmbedderMsg::ReportScriptError(error) => {
+                    debug!(
+                        "Script error at {}:{}:{}: {}",
+                        error.filename, error.line_number, error.column_number, error.message
+                    );
+                },
+                EmbedderMsg::ContentBlocked(url, count) => {
+                    debug!("Blocked {} ({} requests blocked)", url, count);
+                },
                 EmbedderMsg::ReportProfile(bytes) => {
                     let filename = env::var("PROFILE_OUTPUT").unwrap_or("samples.json".to_string());
                     let result = File::create(&filename).and_then(|mut f| f.write_all(&bytes));
@@ -515,6 +528,80 @@ where
                     debug!("MediaSessionEvent received");
                     // TODO(ferjm): MediaSession support for Glutin based browsers.
                 },
+                EmbedderMsg::RequestMidiAccess(_sysex, sender, _events) => {
+                    // TODO: expose the MIDI ports of the system.
+                    let _ = sender.send(Some(vec![]));
+                },
+                EmbedderMsg::SendMidiMessage(..) => {
+                    debug!("SendMidiMessage received");
+                },
+                EmbedderMsg::ShowDatalistSuggestions(suggestions, _rect, sender) => {
+                    // A modal list dialog would get in the way of the typing,
+                    // so the suggestions are only logged.
+                    debug!("Datalist suggestions: {:?}", suggestions);
+                    let _ = sender.send(None);
+                },
+                EmbedderMsg::HideDatalistSuggestions => {
+                    debug!("HideDatalistSuggestions received");
+                },
+                EmbedderMsg::LockScreenOrientation(_, sender) => {
+                    // Desktop windows can't be rotated.
+                    let _ = sender.send(false);
+                },
+                EmbedderMsg::UnlockScreenOrientation => {},
+                EmbedderMsg::LockPointer(sender) => {
+                    let _ = sender.send(self.window.set_pointer_lock(true));
+                },
+                EmbedderMsg::UnlockPointer => {
+                    self.window.set_pointer_lock(false);
+                },
+                EmbedderMsg::StartDrag(_) => {
+                    // TODO: start a drag with the data of the page in the OS.
+                    debug!("StartDrag received");
+                },
+                EmbedderMsg::EndDrag(_) | EmbedderMsg::SetDropEffect(_) => {},
+                EmbedderMsg::ScreenshotTaken(_, Ok(screenshot)) => {
+                    let filename =
+                        env::var("SCREENSHOT_OUTPUT").unwrap_or("screenshot.png".to_string());
+                    let result =
+                        File::create(&filename).and_then(|mut f| f.write_all(&screenshot.data));
+                    if let Err(e) = result {
+                        error!("Failed to store screenshot: {}", e);
+                    }
+                },
+                EmbedderMsg::ScreenshotTaken(_, Err(e)) => {
+                    warn!("Failed to take screenshot: {:?}", e);
+                },
+                EmbedderMsg::FrameCaptured(..) => {},
+                EmbedderMsg::MakeCredential(_, sender) => {
+                    // TODO: talk to the security keys plugged in the system.
+                    let _ = sender.send(Err(AuthenticatorError::NotAllowed));
+                },
+                EmbedderMsg::GetAssertion(_, sender) => {
+                    let _ = sender.send(Err(AuthenticatorError::NotAllowed));
+                },
+                EmbedderMsg::GetPosition(_, sender) |
+                EmbedderMsg::WatchPosition(_, _, sender) => {
+                    // TODO: ask the location services of the system.
+                    let _ = sender.send(None);
+                },
+                EmbedderMsg::ClearPositionWatch(_) => {},
+                EmbedderMsg::GetSpeechVoices(sender) => {
+                    // TODO: use the speech synthesizer of the system.
+                    let _ = sender.send(vec![]);
+                },
+                EmbedderMsg::Speak(_, _, sender) => {
+                    let _ = sender.send(SpeechEvent {
+                        kind: SpeechEventKind::Error(SpeechError::SynthesisUnavailable),
+                        char_index: 0,
+                        char_length: 0,
+                        elapsed_time: 0.,
+                    });
+                },
+                EmbedderMsg::PauseSpeech(_) |
+                EmbedderMsg::ResumeSpeech(_) |
+                EmbedderMsg::CancelSpeech(_) |
+                EmbedderMsg::FrameProximityChanged(_) => {},
             }
         }
     }
@@ -537,6 +624,10 @@ fn prompt_user(prompt: PermissionPrompt) -> PermissionRequest {
                 permission_name
             )
         },
+        PermissionPrompt::StorageAccess(site) => format!(
+            "Do you want to allow {} to use its cookies while embedded in this page?",
+            site
+        ),
     };
 
     match tinyfiledialogs::message_box_yes_no(
@@ -622,6 +713,15 @@ fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Opt
         .expect("Thread spawning failed")
 }
 
+fn get_selected_directory() -> Option<String> {
+    thread::Builder::new()
+        .name("Pick a directory".to_owned())
+        .spawn(move || tinyfiledialogs::select_folder_dialog("Pick a directory", ""))
+        .unwrap()
+        .join()
+        .expect("Thread spawning failed")
+}
+
 fn sanitize_url(request: &str) -> Option<ServoUrl> {
     let request = request.trim();
     ServoUrl::parse(&request)
@@ -29,6 +29,7 @@ use keyboard_types::{Key, KeyState, KeyboardEvent};
 use servo::compositing::windowing::{AnimationState, MouseWindowEvent, WindowEvent};
 use servo::compositing::windowing::{EmbedderCoordinates, WindowMethods};
 use servo::embedder_traits::Cursor;
+use servo::script_traits::{DragData, DragEventType, DraggedFile};
 use servo::script_traits::{TouchEventType, WheelMode, WheelDelta};
 use servo::servo_config::{opts, pref};
 use servo::servo_geometry::DeviceIndependentPixel;
@@ -38,6 +39,7 @@ use servo::webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 use servo_media::player::context::{GlApi, GlContext as PlayerGLContext, NativeDisplay};
 use std::cell::{Cell, RefCell};
 use std::mem;
+use std::path::PathBuf;
 use std::rc::Rc;
 #[cfg(target_os = "windows")]
 use winapi;
@@ -62,7 +64,6 @@ fn builder_with_platform_options(builder: glutin::WindowBuilder) -> glutin::Wind
 pub struct Window {
     gl_context: RefCell<GlContext>,
     events_loop: Rc<RefCell<EventsLoop>>,
-    screen_size: Size2D<u32, DeviceIndependentPixel>,
     inner_size: Cell<Size2D<u32, DeviceIndependentPixel>>,
     mouse_down_button: Cell<Option<glutin::MouseButton>>,
     mouse_down_point: Cell<Point2D<i32, DevicePixel>>,
@@ -72,6 +73,8 @@ pub struct Window {
     last_pressed: Cell<Option<KeyboardEvent>>,
     animation_state: Cell<AnimationState>,
     fullscreen: Cell<bool>,
+    pointer_locked: Cell<bool>,
+    hovered_files: RefCell<Vec<PathBuf>>,
     gl: Rc<dyn gl::Gl>,
     xr_rotation: Cell<Rotation3D<f32, UnknownUnit, UnknownUnit>>,
     xr_translation: Cell<Vector3D<f32, UnknownUnit>>,
@@ -162,11 +165,6 @@ impl Window {
 
         let primary_monitor = events_loop.borrow().as_winit().get_primary_monitor();
 
-        let PhysicalSize {
-            width: screen_width,
-            height: screen_height,
-        } = primary_monitor.get_dimensions();
-        let screen_size = Size2D::new(screen_width as u32, screen_height as u32);
         // TODO(ajeffrey): can this fail?
         let LogicalSize { width, height } = context
             .window()
@@ -202,13 +200,14 @@ impl Window {
             mouse_down_button: Cell::new(None),
             mouse_down_point: Cell::new(Point2D::new(0, 0)),
             mouse_pos: Cell::new(Point2D::new(0, 0)),
+            hovered_files: RefCell::new(vec![]),
             last_pressed: Cell::new(None),
             gl: gl.clone(),
             animation_state: Cell::new(AnimationState::Idle),
             fullscreen: Cell::new(false),
+            pointer_locked: Cell::new(false),
             inner_size: Cell::new(inner_size),
             primary_monitor,
-            screen_size,
             xr_rotation: Cell::new(Rotation3D::identity()),
             xr_translation: Cell::new(Vector3D::zero()),
             angle,
@@ -324,15 +323,19 @@ impl Window {
     ) {
         use servo::script_traits::MouseButton;
 
+        let servo_button = match button {
+            glutin::MouseButton::Middle => MouseButton::Middle,
+            _ => MouseButton::Left,
+        };
         let max_pixel_dist = 10.0 * self.servo_hidpi_factor().get();
         let event = match action {
             ElementState::Pressed => {
                 self.mouse_down_point.set(coords);
                 self.mouse_down_button.set(Some(button));
-                MouseWindowEvent::MouseDown(MouseButton::Left, coords.to_f32())
+                MouseWindowEvent::MouseDown(servo_button, coords.to_f32())
             },
             ElementState::Released => {
-                let mouse_up_event = MouseWindowEvent::MouseUp(MouseButton::Left, coords.to_f32());
+                let mouse_up_event = MouseWindowEvent::MouseUp(servo_button, coords.to_f32());
                 match self.mouse_down_button.get() {
                     None => mouse_up_event,
                     Some(but) if button == but => {
@@ -344,7 +347,7 @@ impl Window {
                             self.event_queue
                                 .borrow_mut()
                                 .push(WindowEvent::MouseWindowEventClass(mouse_up_event));
-                            MouseWindowEvent::Click(MouseButton::Left, coords.to_f32())
+                            MouseWindowEvent::Click(servo_button, coords.to_f32())
                         } else {
                             mouse_up_event
                         }
@@ -371,6 +374,22 @@ impl Window {
             },
         }
     }
+
+    fn handle_file_drag(&self, event_type: DragEventType) {
+        let files = self
+            .hovered_files
+            .borrow()
+            .iter()
+            .cloned()
+            .map(DraggedFile::Path)
+            .collect();
+        let data = DragData { strings: vec![], files };
+        self.event_queue.borrow_mut().push(WindowEvent::Drag(
+            event_type,
+            self.mouse_pos.get().to_f32(),
+            data,
+        ));
+    }
 }
 
 impl WindowPortsMethods for Window {
@@ -382,17 +401,6 @@ impl WindowPortsMethods for Window {
         !self.event_queue.borrow().is_empty()
     }
 
-    fn page_height(&self) -> f32 {
-        let dpr = self.servo_hidpi_factor();
-        let size = self
-            .gl_context
-            .borrow()
-            .window()
-            .get_inner_size()
-            .expect("Failed to get window inner size.");
-        size.height as f32 * dpr.get()
-    }
-
     fn set_title(&self, title: &str) {
         self.gl_context.borrow().window().set_title(title);
     }
@@ -421,6 +429,26 @@ impl WindowPortsMethods for Window {
         return self.fullscreen.get();
     }
 
+    fn set_pointer_lock(&self, locked: bool) -> bool {
+        let context = self.gl_context.borrow();
+        let window = context.window();
+        if let Err(e) = window.grab_cursor(locked) {
+            warn!("Failed to {} the cursor: {}", if locked { "grab" } else { "release" }, e);
+            return false;
+        }
+        window.hide_cursor(locked);
+        self.pointer_locked.set(locked);
+        true
+    }
+
+    fn handle_mouse_motion(&self, (dx, dy): (f64, f64)) {
+        if self.pointer_locked.get() {
+            self.event_queue
+                .borrow_mut()
+                .push(WindowEvent::PointerLockedMouseMove(Vector2D::new(dx as f32, dy as f32)));
+        }
+    }
+
     fn set_cursor(&self, cursor: Cursor) {
         use glutin::MouseCursor;
 
@@ -477,14 +505,23 @@ impl WindowPortsMethods for Window {
             glutin::WindowEvent::ReceivedCharacter(ch) => self.handle_received_character(ch),
             glutin::WindowEvent::KeyboardInput { input, .. } => self.handle_keyboard_input(input),
             glutin::WindowEvent::MouseInput { state, button, .. } => {
-                if button == MouseButton::Left || button == MouseButton::Right {
+                if button == MouseButton::Left ||
+                    button == MouseButton::Middle ||
+                    button == MouseButton::Right
+                {
                     self.handle_mouse(button, state, self.mouse_pos.get());
                 }
             },
+            glutin::WindowEvent::CursorMoved { .. } if self.pointer_locked.get() => {
+                // The motion of the mouse is sent instead.
+            },
             glutin::WindowEvent::CursorMoved { position, .. } => {
                 let pos = position.to_physical(self.device_hidpi_factor().get() as f64);
                 let (x, y): (i32, i32) = pos.into();
                 self.mouse_pos.set(Point2D::new(x, y));
+                if !self.hovered_files.borrow().is_empty() {
+                    return self.handle_file_drag(DragEventType::Over);
+                }
                 self.event_queue
                     .borrow_mut()
                     .push(WindowEvent::MouseWindowMoveEventClass(Point2D::new(
@@ -537,6 +574,26 @@ impl WindowPortsMethods for Window {
                     .borrow_mut()
                     .push(WindowEvent::Touch(phase, id, point));
             },
+            glutin::WindowEvent::HoveredFile(path) => {
+                // Every file of a drag is hovered on its own.
+                self.hovered_files.borrow_mut().push(path);
+                self.handle_file_drag(DragEventType::Over);
+            },
+            glutin::WindowEvent::HoveredFileCancelled => {
+                self.handle_file_drag(DragEventType::Leave);
+                self.hovered_files.borrow_mut().clear();
+            },
+            glutin::WindowEvent::DroppedFile(path) => {
+                // The files that were hovered are all dropped at once, the
+                // events for the other files of the drop are ignored.
+                if self.hovered_files.borrow().is_empty() {
+                    self.hovered_files.borrow_mut().push(path);
+                } else if !self.hovered_files.borrow().contains(&path) {
+                    return;
+                }
+                self.handle_file_drag(DragEventType::Drop);
+                self.hovered_files.borrow_mut().clear();
+            },
             glutin::WindowEvent::Refresh => {
                 self.event_queue.borrow_mut().push(WindowEvent::Refresh);
             },
@@ -630,7 +687,15 @@ impl WindowMethods for Window {
             .unwrap_or(LogicalPosition::new(0., 0.));
         let win_size = (Size2D::new(width as f32, height as f32) * dpr).to_i32();
         let win_origin = (Point2D::new(x as f32, y as f32) * dpr).to_i32();
-        let screen = (self.screen_size.to_f32() * dpr).to_i32();
+        // The screen is the one the window is currently on, which changes as
+        // the window is moved across monitors.
+        let PhysicalSize { width, height } = self
+            .gl_context
+            .borrow()
+            .window()
+            .get_current_monitor()
+            .get_dimensions();
+        let screen = Size2D::new(width as i32, height as i32);
 
         let LogicalSize { width, height } = self
             .gl_context
@@ -649,6 +714,7 @@ impl WindowMethods for Window {
             screen: screen,
             // FIXME: Glutin doesn't have API for available size. Fallback to screen size
             screen_avail: screen,
+            screen_orientation_angle: 0,
             hidpi_factor: self.servo_hidpi_factor(),
         }
     }
@@ -140,11 +140,6 @@ impl WindowPortsMethods for Window {
         unsafe { glutin::WindowId::dummy() }
     }
 
-    fn page_height(&self) -> f32 {
-        let dpr = self.servo_hidpi_factor();
-        self.context.height as f32 * dpr.get()
-    }
-
     fn set_fullscreen(&self, state: bool) {
         self.fullscreen.set(state);
     }
@@ -153,6 +148,11 @@ impl WindowPortsMethods for Window {
         return self.fullscreen.get();
     }
 
+    fn set_pointer_lock(&self, _locked: bool) -> bool {
+        // There is no cursor to hide.
+        true
+    }
+
     fn is_animating(&self) -> bool {
         self.animation_state.get() == AnimationState::Animating
     }
@@ -178,6 +178,7 @@ impl WindowMethods for Window {
             window: (size, Point2D::zero()),
             screen: size,
             screen_avail: size,
+            screen_orientation_angle: 0,
             hidpi_factor: dpr,
         }
     }
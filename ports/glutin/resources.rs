@@ -27,10 +27,15 @@ fn filename(file: Resource) -> &'static str {
         Resource::UserAgentCSS => "user-agent.css",
         Resource::ServoCSS => "servo.css",
         Resource::PresentationalHintsCSS => "presentational-hints.css",
+        Resource::MathMLCSS => "mathml.css",
         Resource::QuirksModeCSS => "quirks-mode.css",
         Resource::RippyPNG => "rippy.png",
         Resource::MediaControlsCSS => "media-controls.css",
         Resource::MediaControlsJS => "media-controls.js",
+        Resource::AboutConfigHTML => "about-config.html",
+        Resource::AboutMemoryHTML => "about-memory.html",
+        Resource::AboutCrashesHTML => "about-crashes.html",
+        Resource::ContentBlockingList => "content_blocking_list.txt",
     }
 }
 
@@ -17,7 +17,6 @@ pub trait WindowPortsMethods: WindowMethods + webxr::glwindow::GlWindow {
     fn get_events(&self) -> Vec<WindowEvent>;
     fn id(&self) -> glutin::WindowId;
     fn has_events(&self) -> bool;
-    fn page_height(&self) -> f32;
     fn get_fullscreen(&self) -> bool;
     fn winit_event_to_servo_event(&self, event: glutin::WindowEvent);
     fn is_animating(&self) -> bool;
@@ -26,4 +25,10 @@ pub trait WindowPortsMethods: WindowMethods + webxr::glwindow::GlWindow {
     fn set_position(&self, _point: DeviceIntPoint) {}
     fn set_fullscreen(&self, _state: bool) {}
     fn set_cursor(&self, _cursor: Cursor) {}
+    /// Hides the cursor and keeps it in place, or releases it, and returns
+    /// whether it could.
+    fn set_pointer_lock(&self, _locked: bool) -> bool { false }
+    /// Handles the raw motion of the mouse, which is only sent to content
+    /// while the pointer is locked.
+    fn handle_mouse_motion(&self, _delta: (f64, f64)) {}
 }
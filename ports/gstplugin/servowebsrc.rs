@@ -206,7 +206,7 @@ impl ServoThread {
         let gfx = window.gfx.clone();
         let mut servo = Servo::new(embedder, window);
         let id = TopLevelBrowsingContextId::new();
-        servo.handle_events(vec![WindowEvent::NewBrowser(url, id)]);
+        servo.handle_events(vec![WindowEvent::NewBrowser(url, id, None)]);
 
         Self {
             receiver,
@@ -442,6 +442,7 @@ impl WindowMethods for ServoWebSrcWindow {
             hidpi_factor: Scale::new(1.0),
             screen: size,
             screen_avail: size,
+            screen_orientation_angle: 0,
             window: (size, origin),
             framebuffer: size,
             viewport: Rect::new(origin, size),
@@ -8,9 +8,15 @@ extern crate log;
 pub mod gl_glue;
 
 pub use servo::embedder_traits::{
-    MediaSessionPlaybackState, PermissionPrompt, PermissionRequest, PromptResult,
+    FrameCaptureOptions, MediaSessionPlaybackState, PermissionPrompt, PermissionRequest,
+    PromptResult, Screenshot, ScreenshotError, ScreenshotFormat,
+};
+pub use servo::net_traits::user_agent_data::UserAgentOverride;
+pub use servo::net_traits::NetworkConditions;
+pub use servo::script_traits::{
+    ColorScheme, DeviceEmulation, DeviceOrientation, DeviceSensorReading, FrameThrottlingPolicy,
+    MediaSessionActionType, MotionReading, MouseButton, OrientationReading, ScreenshotArea,
 };
-pub use servo::script_traits::{MediaSessionActionType, MouseButton};
 
 use getopts::Options;
 use servo::compositing::windowing::{
@@ -18,12 +24,16 @@ use servo::compositing::windowing::{
     WindowMethods,
 };
 use servo::embedder_traits::resources::{self, Resource, ResourceReaderMethods};
-use servo::embedder_traits::{EmbedderMsg, MediaSessionEvent, PromptDefinition, PromptOrigin};
+use servo::embedder_traits::{
+    AuthenticatorError, EmbedderMsg, MediaSessionEvent, PromptDefinition, PromptOrigin,
+    SpeechError, SpeechEvent, SpeechEventKind,
+};
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::script_traits::{TouchEventType, TouchId};
 use servo::servo_config::opts;
+use servo::servo_config::prefs::{self, PrefValue};
 use servo::servo_config::{pref, set_pref};
 use servo::servo_url::ServoUrl;
 use servo::webrender_api::units::DevicePixel;
@@ -145,6 +155,10 @@ pub trait HostTrait {
     fn on_media_session_playback_state_change(&self, state: MediaSessionPlaybackState);
     /// Called when the media session position state is set.
     fn on_media_session_set_position_state(&self, duration: f64, position: f64, playback_rate: f64);
+    /// The screenshot requested with the given id was taken, or couldn't be.
+    fn on_screenshot_taken(&self, id: u32, result: Result<Screenshot, ScreenshotError>);
+    /// A frame of the browser was composited for the frame capture with the given id.
+    fn on_frame_captured(&self, id: u32, frame: Screenshot);
 }
 
 pub struct ServoGlue {
@@ -239,7 +253,7 @@ pub fn init(
             current_url: Some(url.clone()),
         };
         let browser_id = BrowserId::new();
-        let _ = servo_glue.process_event(WindowEvent::NewBrowser(url, browser_id));
+        let _ = servo_glue.process_event(WindowEvent::NewBrowser(url, browser_id, None));
         *s.borrow_mut() = Some(servo_glue);
     });
 
@@ -304,6 +318,18 @@ impl ServoGlue {
             })
     }
 
+    /// Fetch a same-origin link of the page into the HTTP cache, e.g. when
+    /// the user hovers it, so that following it doesn't wait on the network.
+    pub fn prefetch(&mut self, url: &str) -> Result<(), &'static str> {
+        info!("prefetch: {}", url);
+        ServoUrl::parse(url)
+            .map_err(|_| "Can't parse URL")
+            .and_then(|url| {
+                let browser_id = self.get_browser_id()?;
+                self.process_event(WindowEvent::Prefetch(browser_id, url))
+            })
+    }
+
     /// Reload the page.
     pub fn reload(&mut self) -> Result<(), &'static str> {
         info!("reload");
@@ -500,6 +526,118 @@ impl ServoGlue {
         }
     }
 
+    /// Turn the content blocker on or off for the requests of the browser.
+    pub fn set_content_blocking(&mut self, enabled: bool) -> Result<(), &'static str> {
+        info!("set_content_blocking {}", enabled);
+        let id = self.get_browser_id()?;
+        self.process_event(WindowEvent::SetContentBlocking(id, enabled))
+    }
+
+    /// Make the browser present itself as another user agent, or as the
+    /// default one again.
+    pub fn set_user_agent_override(
+        &mut self,
+        ua_override: Option<UserAgentOverride>,
+    ) -> Result<(), &'static str> {
+        info!("set_user_agent_override {:?}", ua_override);
+        let id = self.get_browser_id()?;
+        self.process_event(WindowEvent::SetUserAgentOverride(id, ua_override))
+    }
+
+    /// Make the browser emulate a device, like a phone, without resizing the
+    /// window, or stop emulating one.
+    pub fn set_device_emulation(
+        &mut self,
+        emulation: Option<DeviceEmulation>,
+    ) -> Result<(), &'static str> {
+        info!("set_device_emulation {:?}", emulation);
+        let id = self.get_browser_id()?;
+        self.process_event(WindowEvent::SetDeviceEmulation(id, emulation))
+    }
+
+    /// Make the requests of the browser fail as if it were offline, or slow
+    /// them down, or make them under the real network conditions again.
+    pub fn set_network_conditions(
+        &mut self,
+        conditions: Option<NetworkConditions>,
+    ) -> Result<(), &'static str> {
+        info!("set_network_conditions {:?}", conditions);
+        let id = self.get_browser_id()?;
+        self.process_event(WindowEvent::SetNetworkConditions(id, conditions))
+    }
+
+    /// Throttle the frames of the browser that are far from the viewport with
+    /// the given policy, or stop throttling them.
+    pub fn set_frame_throttling_policy(
+        &mut self,
+        policy: Option<FrameThrottlingPolicy>,
+    ) -> Result<(), &'static str> {
+        info!("set_frame_throttling_policy {:?}", policy);
+        let id = self.get_browser_id()?;
+        self.process_event(WindowEvent::SetFrameThrottlingPolicy(id, policy))
+    }
+
+    /// Feed a reading of the orientation or motion sensors of the device to
+    /// the active browser, which fires deviceorientation and devicemotion
+    /// events from it.
+    pub fn device_sensor_reading(
+        &mut self,
+        reading: DeviceSensorReading,
+    ) -> Result<(), &'static str> {
+        self.process_event(WindowEvent::DeviceSensorReading(reading))
+    }
+
+    /// Take a screenshot of an area of the browser once it is done loading
+    /// and painting. The host is called back with `on_screenshot_taken` and
+    /// the given id.
+    pub fn take_screenshot(
+        &mut self,
+        id: u32,
+        area: ScreenshotArea,
+        format: ScreenshotFormat,
+    ) -> Result<(), &'static str> {
+        info!("take_screenshot {} {:?} {:?}", id, area, format);
+        let browser_id = self.get_browser_id()?;
+        self.process_event(WindowEvent::TakeScreenshot(browser_id, id, area, format))
+    }
+
+    /// Start sending the frames of the browser to the host as they are
+    /// composited, with `on_frame_captured` and the given id.
+    pub fn start_frame_capture(
+        &mut self,
+        id: u32,
+        options: FrameCaptureOptions,
+    ) -> Result<(), &'static str> {
+        info!("start_frame_capture {} {:?}", id, options);
+        let browser_id = self.get_browser_id()?;
+        self.process_event(WindowEvent::StartFrameCapture(browser_id, id, options))
+    }
+
+    pub fn stop_frame_capture(&mut self, id: u32) -> Result<(), &'static str> {
+        info!("stop_frame_capture {}", id);
+        self.process_event(WindowEvent::StopFrameCapture(id))
+    }
+
+    pub fn get_pref(&self, key: &str) -> PrefValue {
+        prefs::pref_map().get(key)
+    }
+
+    /// Set a preference. Running pipelines see the new value too.
+    pub fn set_pref(&mut self, key: &str, value: PrefValue) -> Result<(), &'static str> {
+        info!("set_pref {} = {:?}", key, value);
+        prefs::pref_map()
+            .set(key, value)
+            .map_err(|_| "Failed to set preference")
+    }
+
+    pub fn reset_pref(&mut self, key: &str) -> Result<(), &'static str> {
+        info!("reset_pref {}", key);
+        prefs::pref_map()
+            .reset(key)
+            .map(|_| ())
+            .map_err(|_| "Failed to reset preference")
+    }
+
     fn process_event(&mut self, event: WindowEvent) -> Result<(), &'static str> {
         self.events.push(event);
         if !self.batch_mode {
@@ -556,6 +694,40 @@ impl ServoGlue {
                 EmbedderMsg::GetSelectedBluetoothDevice(_, sender) => {
                     let _ = sender.send(None);
                 },
+                EmbedderMsg::RequestMidiAccess(_, sender, _) => {
+                    let _ = sender.send(Some(vec![]));
+                },
+                EmbedderMsg::LockScreenOrientation(_, sender) => {
+                    let _ = sender.send(false);
+                },
+                EmbedderMsg::LockPointer(sender) => {
+                    let _ = sender.send(false);
+                },
+                EmbedderMsg::MakeCredential(_, sender) => {
+                    let _ = sender.send(Err(AuthenticatorError::NotAllowed));
+                },
+                EmbedderMsg::GetAssertion(_, sender) => {
+                    let _ = sender.send(Err(AuthenticatorError::NotAllowed));
+                },
+                EmbedderMsg::GetPosition(_, sender) | EmbedderMsg::WatchPosition(_, _, sender) => {
+                    let _ = sender.send(None);
+                },
+                EmbedderMsg::GetSpeechVoices(sender) => {
+                    let _ = sender.send(vec![]);
+                },
+                EmbedderMsg::Speak(_, _, sender) => {
+                    let _ = sender.send(SpeechEvent {
+                        kind: SpeechEventKind::Error(SpeechError::SynthesisUnavailable),
+                        char_index: 0,
+                        char_length: 0,
+                        elapsed_time: 0.,
+                    });
+                },
+                EmbedderMsg::ShowDateTimePicker(.., sender) |
+                EmbedderMsg::ShowColorPicker(_, sender) |
+                EmbedderMsg::ShowDatalistSuggestions(_, _, sender) => {
+                    let _ = sender.send(None);
+                },
                 EmbedderMsg::AllowUnload(sender) => {
                     let _ = sender.send(true);
                 },
@@ -629,6 +801,10 @@ impl ServoGlue {
                                 permission_name
                             )
                         },
+                        PermissionPrompt::StorageAccess(site) => format!(
+                            "Do you want to allow {} to use its cookies while embedded in this page?",
+                            site
+                        ),
                     };
 
                     let result = match self.callbacks.host_callbacks.prompt_yes_no(message, true) {
@@ -669,8 +845,21 @@ impl ServoGlue {
                             ),
                     };
                 },
+                EmbedderMsg::ScreenshotTaken(id, result) => {
+                    self.callbacks
+                        .host_callbacks
+                        .on_screenshot_taken(id, result);
+                },
+                EmbedderMsg::FrameCaptured(id, frame) => {
+                    self.callbacks.host_callbacks.on_frame_captured(id, frame);
+                },
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
+                EmbedderMsg::SelectDirectory(..) |
+                EmbedderMsg::ShowValidationMessage(..) |
+                EmbedderMsg::ReportScriptError(..) |
+                EmbedderMsg::ContentBlocked(..) |
+                EmbedderMsg::HideDatalistSuggestions |
                 EmbedderMsg::MoveTo(..) |
                 EmbedderMsg::ResizeTo(..) |
                 EmbedderMsg::Keyboard(..) |
@@ -679,7 +868,18 @@ impl ServoGlue {
                 EmbedderMsg::HeadParsed |
                 EmbedderMsg::SetFullscreenState(..) |
                 EmbedderMsg::Panic(..) |
-                EmbedderMsg::ReportProfile(..) => {},
+                EmbedderMsg::ReportProfile(..) |
+                EmbedderMsg::SendMidiMessage(..) |
+                EmbedderMsg::UnlockScreenOrientation |
+                EmbedderMsg::UnlockPointer |
+                EmbedderMsg::StartDrag(..) |
+                EmbedderMsg::EndDrag(..) |
+                EmbedderMsg::SetDropEffect(..) |
+                EmbedderMsg::ClearPositionWatch(..) |
+                EmbedderMsg::PauseSpeech(..) |
+                EmbedderMsg::ResumeSpeech(..) |
+                EmbedderMsg::CancelSpeech(..) |
+                EmbedderMsg::FrameProximityChanged(..) => {},
             }
         }
         Ok(())
@@ -778,6 +978,7 @@ impl WindowMethods for ServoWindowCallbacks {
             window: (coords.viewport.size, Point2D::new(0, 0)),
             screen: coords.viewport.size,
             screen_avail: coords.viewport.size,
+            screen_orientation_angle: 0,
             hidpi_factor: Scale::new(self.density),
         }
     }
@@ -824,6 +1025,7 @@ impl ResourceReaderMethods for ResourceReaderInstance {
             Resource::PresentationalHintsCSS => {
                 &include_bytes!("../../../../resources/presentational-hints.css")[..]
             },
+            Resource::MathMLCSS => &include_bytes!("../../../../resources/mathml.css")[..],
             Resource::QuirksModeCSS => &include_bytes!("../../../../resources/quirks-mode.css")[..],
             Resource::RippyPNG => &include_bytes!("../../../../resources/rippy.png")[..],
             Resource::DomainList => &include_bytes!("../../../../resources/public_domains.txt")[..],
@@ -836,6 +1038,18 @@ impl ResourceReaderMethods for ResourceReaderInstance {
             Resource::MediaControlsJS => {
                 &include_bytes!("../../../../resources/media-controls.js")[..]
             },
+            Resource::AboutConfigHTML => {
+                &include_bytes!("../../../../resources/about-config.html")[..]
+            },
+            Resource::AboutMemoryHTML => {
+                &include_bytes!("../../../../resources/about-memory.html")[..]
+            },
+            Resource::AboutCrashesHTML => {
+                &include_bytes!("../../../../resources/about-crashes.html")[..]
+            },
+            Resource::ContentBlockingList => {
+                &include_bytes!("../../../../resources/content_blocking_list.txt")[..]
+            },
         })
     }
 
@@ -17,14 +17,16 @@ use env_logger;
 use log::LevelFilter;
 use simpleservo::{self, gl_glue, ServoGlue, SERVO};
 use simpleservo::{
-    Coordinates, EventLoopWaker, HostTrait, InitOptions, MediaSessionActionType,
-    MediaSessionPlaybackState, MouseButton, PromptResult, VRInitOptions,
+    Coordinates, EventLoopWaker, FrameCaptureOptions, HostTrait, InitOptions,
+    MediaSessionActionType, MediaSessionPlaybackState, MouseButton, PromptResult, Screenshot,
+    ScreenshotArea, ScreenshotError, ScreenshotFormat, VRInitOptions,
 };
 use std::ffi::{CStr, CString};
 #[cfg(target_os = "windows")]
 use std::mem;
 use std::os::raw::{c_char, c_void};
 use std::panic::{self, UnwindSafe};
+use std::ptr;
 use std::slice;
 use std::str::FromStr;
 use std::sync::RwLock;
@@ -221,6 +223,10 @@ pub struct CHostCallbacks {
     pub on_media_session_playback_state_change: extern "C" fn(state: CMediaSessionPlaybackState),
     pub on_media_session_set_position_state:
         extern "C" fn(duration: f64, position: f64, playback_rate: f64),
+    pub on_screenshot_taken:
+        extern "C" fn(id: u32, width: u32, height: u32, data: *const u8, len: usize),
+    pub on_frame_captured:
+        extern "C" fn(id: u32, width: u32, height: u32, data: *const u8, len: usize),
     pub prompt_alert: extern "C" fn(message: *const c_char, trusted: bool),
     pub prompt_ok_cancel: extern "C" fn(message: *const c_char, trusted: bool) -> CPromptResult,
     pub prompt_yes_no: extern "C" fn(message: *const c_char, trusted: bool) -> CPromptResult,
@@ -325,6 +331,21 @@ impl CMediaSessionActionType {
     }
 }
 
+#[repr(C)]
+pub enum CScreenshotFormat {
+    Png,
+    Raw,
+}
+
+impl CScreenshotFormat {
+    pub fn convert(&self) -> ScreenshotFormat {
+        match self {
+            CScreenshotFormat::Png => ScreenshotFormat::Png,
+            CScreenshotFormat::Raw => ScreenshotFormat::Raw,
+        }
+    }
+}
+
 /// The returned string is not freed. This will leak.
 #[no_mangle]
 pub extern "C" fn servo_version() -> *const c_char {
@@ -686,6 +707,53 @@ pub extern "C" fn media_session_action(action: CMediaSessionActionType) {
     });
 }
 
+/// Take a screenshot of the element that matches the selector, or of the
+/// viewport or of the whole page if the selector is null. The result is sent
+/// to `on_screenshot_taken` with the given id.
+#[no_mangle]
+pub extern "C" fn take_screenshot(
+    id: u32,
+    selector: *const c_char,
+    full_page: bool,
+    format: CScreenshotFormat,
+) {
+    catch_any_panic(|| {
+        debug!("take_screenshot");
+        let area = if !selector.is_null() {
+            let selector = unsafe { CStr::from_ptr(selector) };
+            ScreenshotArea::Element(selector.to_string_lossy().into_owned())
+        } else if full_page {
+            ScreenshotArea::FullPage
+        } else {
+            ScreenshotArea::Viewport
+        };
+        call(|s| s.take_screenshot(id, area.clone(), format.convert()));
+    });
+}
+
+/// Start sending the frames of the browser to `on_frame_captured` with the
+/// given id as they are composited, at most `max_frame_rate` per second, or
+/// all of them if it is zero.
+#[no_mangle]
+pub extern "C" fn start_frame_capture(id: u32, format: CScreenshotFormat, max_frame_rate: u32) {
+    catch_any_panic(|| {
+        debug!("start_frame_capture");
+        let options = FrameCaptureOptions {
+            format: format.convert(),
+            max_frame_rate,
+        };
+        call(|s| s.start_frame_capture(id, options));
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn stop_frame_capture(id: u32) {
+    catch_any_panic(|| {
+        debug!("stop_frame_capture");
+        call(|s| s.stop_frame_capture(id));
+    });
+}
+
 #[no_mangle]
 pub extern "C" fn change_visibility(visible: bool) {
     catch_any_panic(|| {
@@ -824,6 +892,30 @@ impl HostTrait for HostCallbacks {
         (self.0.on_media_session_set_position_state)(duration, position, playback_rate);
     }
 
+    fn on_screenshot_taken(&self, id: u32, result: Result<Screenshot, ScreenshotError>) {
+        debug!("on_screenshot_taken ({:?} {:?})", id, result);
+        match result {
+            Ok(screenshot) => (self.0.on_screenshot_taken)(
+                id,
+                screenshot.width,
+                screenshot.height,
+                screenshot.data.as_ptr(),
+                screenshot.data.len(),
+            ),
+            Err(_) => (self.0.on_screenshot_taken)(id, 0, 0, ptr::null(), 0),
+        }
+    }
+
+    fn on_frame_captured(&self, id: u32, frame: Screenshot) {
+        (self.0.on_frame_captured)(
+            id,
+            frame.width,
+            frame.height,
+            frame.data.as_ptr(),
+            frame.data.len(),
+        );
+    }
+
     fn prompt_alert(&self, message: String, trusted: bool) {
         debug!("prompt_alert");
         let message = CString::new(message).expect("Can't create string");
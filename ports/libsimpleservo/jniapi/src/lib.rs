@@ -16,8 +16,9 @@ use libc::{dup2, pipe, read};
 use log::Level;
 use simpleservo::{self, gl_glue, ServoGlue, SERVO};
 use simpleservo::{
-    Coordinates, EventLoopWaker, HostTrait, InitOptions, MediaSessionPlaybackState, PromptResult,
-    VRInitOptions,
+    Coordinates, EventLoopWaker, FrameCaptureOptions, HostTrait, InitOptions,
+    MediaSessionPlaybackState, PromptResult, Screenshot, ScreenshotArea, ScreenshotError,
+    ScreenshotFormat, VRInitOptions,
 };
 use std::os::raw::{c_char, c_int, c_void};
 use std::ptr::{null, null_mut};
@@ -346,6 +347,45 @@ pub fn Java_org_mozilla_servoview_JNIServo_mediaSessionAction(
     call(&env, |s| s.media_session_action((action as i32).into()));
 }
 
+#[no_mangle]
+pub fn Java_org_mozilla_servoview_JNIServo_takeScreenshot(
+    env: JNIEnv,
+    _: JClass,
+    id: jint,
+    full_page: jboolean,
+) {
+    debug!("takeScreenshot");
+    let area = if full_page == JNI_TRUE {
+        ScreenshotArea::FullPage
+    } else {
+        ScreenshotArea::Viewport
+    };
+    call(&env, |s| {
+        s.take_screenshot(id as u32, area.clone(), ScreenshotFormat::Png)
+    });
+}
+
+#[no_mangle]
+pub fn Java_org_mozilla_servoview_JNIServo_startFrameCapture(
+    env: JNIEnv,
+    _: JClass,
+    id: jint,
+    max_frame_rate: jint,
+) {
+    debug!("startFrameCapture");
+    let options = FrameCaptureOptions {
+        format: ScreenshotFormat::Png,
+        max_frame_rate: max_frame_rate as u32,
+    };
+    call(&env, |s| s.start_frame_capture(id as u32, options));
+}
+
+#[no_mangle]
+pub fn Java_org_mozilla_servoview_JNIServo_stopFrameCapture(env: JNIEnv, _: JClass, id: jint) {
+    debug!("stopFrameCapture");
+    call(&env, |s| s.stop_frame_capture(id as u32));
+}
+
 pub struct WakeupCallback {
     callback: GlobalRef,
     jvm: Arc<JavaVM>,
@@ -603,6 +643,50 @@ impl HostTrait for HostCallbacks {
         )
         .unwrap();
     }
+
+    fn on_screenshot_taken(&self, id: u32, result: Result<Screenshot, ScreenshotError>) {
+        info!("on_screenshot_taken ({:?}, {:?})", id, result);
+        let env = self.jvm.get_env().unwrap();
+        let (width, height, data) = match result {
+            Ok(screenshot) => match env.byte_array_from_slice(&screenshot.data) {
+                Ok(data) => (screenshot.width, screenshot.height, JObject::from(data)),
+                Err(_) => (0, 0, JObject::null()),
+            },
+            Err(_) => (0, 0, JObject::null()),
+        };
+        env.call_method(
+            self.callbacks.as_obj(),
+            "onScreenshotTaken",
+            "(III[B)V",
+            &[
+                JValue::Int(id as jint),
+                JValue::Int(width as jint),
+                JValue::Int(height as jint),
+                JValue::Object(data),
+            ],
+        )
+        .unwrap();
+    }
+
+    fn on_frame_captured(&self, id: u32, frame: Screenshot) {
+        let env = self.jvm.get_env().unwrap();
+        let data = match env.byte_array_from_slice(&frame.data) {
+            Ok(data) => JObject::from(data),
+            Err(_) => return warn!("Failed to copy a captured frame"),
+        };
+        env.call_method(
+            self.callbacks.as_obj(),
+            "onFrameCaptured",
+            "(III[B)V",
+            &[
+                JValue::Int(id as jint),
+                JValue::Int(frame.width as jint),
+                JValue::Int(frame.height as jint),
+                JValue::Object(data),
+            ],
+        )
+        .unwrap();
+    }
 }
 
 fn initialize_android_glue(env: &JNIEnv, activity: JObject) {
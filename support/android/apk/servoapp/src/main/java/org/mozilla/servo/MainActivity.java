@@ -266,4 +266,14 @@ public class MainActivity extends Activity implements Servo.Client {
         mMediaSession.setPositionState(duration, position, playbackRate);
         return;
     }
+
+    @Override
+    public void onScreenshotTaken(int id, int width, int height, byte[] png) {
+        Log.d("onScreenshotTaken", id + " " + width + "x" + height);
+    }
+
+    @Override
+    public void onFrameCaptured(int id, int width, int height, byte[] png) {
+        Log.d("onFrameCaptured", id + " " + width + "x" + height);
+    }
 }
@@ -68,6 +68,12 @@ public class JNIServo {
 
     public native void mediaSessionAction(int action);
 
+    public native void takeScreenshot(int id, boolean fullPage);
+
+    public native void startFrameCapture(int id, int maxFrameRate);
+
+    public native void stopFrameCapture(int id);
+
     public static class ServoOptions {
       public String args;
       public String url;
@@ -117,6 +123,10 @@ public class JNIServo {
         void onMediaSessionPlaybackStateChange(int state);
 
         void onMediaSessionSetPositionState(float duration, float position, float playbackRate);
+
+        void onScreenshotTaken(int id, int width, int height, byte[] png);
+
+        void onFrameCaptured(int id, int width, int height, byte[] png);
     }
 }
 
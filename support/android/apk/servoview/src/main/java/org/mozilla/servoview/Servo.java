@@ -172,6 +172,18 @@ public class Servo {
         mRunCallback.inGLThread(() -> mJNI.mediaSessionAction(action));
     }
 
+    public void takeScreenshot(int id, boolean fullPage) {
+        mRunCallback.inGLThread(() -> mJNI.takeScreenshot(id, fullPage));
+    }
+
+    public void startFrameCapture(int id, int maxFrameRate) {
+        mRunCallback.inGLThread(() -> mJNI.startFrameCapture(id, maxFrameRate));
+    }
+
+    public void stopFrameCapture(int id) {
+        mRunCallback.inGLThread(() -> mJNI.stopFrameCapture(id));
+    }
+
     public interface Client {
         void onAlert(String message);
 
@@ -194,6 +206,10 @@ public class Servo {
         void onMediaSessionPlaybackStateChange(int state);
 
         void onMediaSessionSetPositionState(float duration, float position, float playbackRate);
+
+        void onScreenshotTaken(int id, int width, int height, byte[] png);
+
+        void onFrameCaptured(int id, int width, int height, byte[] png);
     }
 
     public interface RunCallback {
@@ -291,5 +307,13 @@ public class Servo {
         public void onMediaSessionSetPositionState(float duration, float position, float playbackRate) {
             mRunCallback.inUIThread(() -> mClient.onMediaSessionSetPositionState(duration, position, playbackRate));
         }
+
+        public void onScreenshotTaken(int id, int width, int height, byte[] png) {
+            mRunCallback.inUIThread(() -> mClient.onScreenshotTaken(id, width, height, png));
+        }
+
+        public void onFrameCaptured(int id, int width, int height, byte[] png) {
+            mRunCallback.inUIThread(() -> mClient.onFrameCaptured(id, width, height, png));
+        }
     }
 }
@@ -138,6 +138,18 @@ public class ServoView extends GLSurfaceView
         mServo.mediaSessionAction(action);
     }
 
+    public void takeScreenshot(int id, boolean fullPage) {
+        mServo.takeScreenshot(id, fullPage);
+    }
+
+    public void startFrameCapture(int id, int maxFrameRate) {
+        mServo.startFrameCapture(id, maxFrameRate);
+    }
+
+    public void stopFrameCapture(int id) {
+        mServo.stopFrameCapture(id);
+    }
+
     public void flushGLBuffers() {
         requestRender();
     }
@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use euclid::default::{Point2D, Rect, Size2D};
+use metrics::LayoutShiftMetrics;
+use script_traits::UntrustedNodeAddress;
+use std::collections::HashMap;
+
+fn node(id: usize) -> UntrustedNodeAddress {
+    UntrustedNodeAddress::from_id(id)
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect<f32> {
+    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+}
+
+fn viewport() -> Size2D<f32> {
+    Size2D::new(800., 600.)
+}
+
+#[test]
+fn test_first_frame_has_no_layout_shift() {
+    let metrics = LayoutShiftMetrics::new();
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 0., 800., 300.));
+    metrics.observe_frame(boxes, viewport());
+    assert!(metrics.take_layout_shifts().is_empty());
+}
+
+#[test]
+fn test_resized_and_inserted_nodes_do_not_shift() {
+    let metrics = LayoutShiftMetrics::new();
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 0., 800., 300.));
+    metrics.observe_frame(boxes, viewport());
+
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 0., 800., 400.));
+    boxes.insert(node(2), rect(0., 400., 800., 100.));
+    metrics.observe_frame(boxes, viewport());
+    assert!(metrics.take_layout_shifts().is_empty());
+}
+
+#[test]
+fn test_moved_node_shifts() {
+    let metrics = LayoutShiftMetrics::new();
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 0., 800., 300.));
+    boxes.insert(node(2), rect(0., 0., 400., 300.));
+    metrics.observe_frame(boxes, viewport());
+
+    // The node moves down by a quarter of the viewport height, so its
+    // previous and current boxes cover half of the viewport.
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 0., 800., 300.));
+    boxes.insert(node(2), rect(0., 150., 400., 300.));
+    metrics.observe_frame(boxes, viewport());
+
+    let shifts = metrics.take_layout_shifts();
+    assert_eq!(shifts.len(), 1);
+    let impact_fraction = (400. * 450.) / (800. * 600.);
+    let distance_fraction = 150. / 800.;
+    assert!((shifts[0].score - impact_fraction * distance_fraction).abs() < 1e-6);
+    assert_eq!(shifts[0].sources.len(), 1);
+    assert_eq!(shifts[0].sources[0].node, node(2));
+    assert_eq!(shifts[0].sources[0].previous_rect, rect(0., 0., 400., 300.));
+    assert_eq!(
+        shifts[0].sources[0].current_rect,
+        rect(0., 150., 400., 300.)
+    );
+    assert!(metrics.take_layout_shifts().is_empty());
+}
+
+#[test]
+fn test_scrolling_does_not_shift() {
+    let metrics = LayoutShiftMetrics::new();
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 700., 800., 300.));
+    metrics.observe_frame(boxes.clone(), viewport());
+
+    metrics.set_scroll_position(Point2D::new(0., 500.));
+    metrics.observe_frame(boxes, viewport());
+    assert!(metrics.take_layout_shifts().is_empty());
+}
+
+#[test]
+fn test_offscreen_node_does_not_shift() {
+    let metrics = LayoutShiftMetrics::new();
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 1000., 800., 300.));
+    metrics.observe_frame(boxes, viewport());
+
+    let mut boxes = HashMap::new();
+    boxes.insert(node(1), rect(0., 1200., 800., 300.));
+    metrics.observe_frame(boxes, viewport());
+    assert!(metrics.take_layout_shifts().is_empty());
+}
@@ -5,4 +5,5 @@
 #![cfg(test)]
 
 mod interactive_time;
+mod layout_shift;
 mod paint_time;
@@ -122,3 +122,57 @@ fn test_first_contentful_paint_setter() {
         "first paint is set"
     );
 }
+
+#[test]
+fn test_first_paint_is_kept_after_contentful_paint() {
+    let first_epoch = Epoch(0);
+    let paint_time_metrics = test_common(false, first_epoch);
+    let navigation_start = paint_time_metrics.get_navigation_start().unwrap();
+    paint_time_metrics.maybe_set_metric(first_epoch, navigation_start + 1);
+    assert_eq!(
+        paint_time_metrics.get_first_paint(),
+        Some(1),
+        "first paint is set"
+    );
+
+    let contentful_epoch = Epoch(1);
+    paint_time_metrics.maybe_observe_paint_time(
+        &DummyProfilerMetadataFactory {},
+        contentful_epoch,
+        true,
+    );
+    paint_time_metrics.maybe_set_metric(contentful_epoch, navigation_start + 2);
+    assert_eq!(
+        paint_time_metrics.get_first_paint(),
+        Some(1),
+        "first paint is unchanged"
+    );
+    assert_eq!(
+        paint_time_metrics.get_first_contentful_paint(),
+        Some(2),
+        "first contentful paint is set"
+    );
+}
+
+#[test]
+fn test_stale_paint_metrics_are_dropped() {
+    let stale_epoch = Epoch(0);
+    let paint_time_metrics = test_common(true, stale_epoch);
+    let navigation_start = paint_time_metrics.get_navigation_start().unwrap();
+
+    // A later frame is presented before the earlier one ever was.
+    let epoch = Epoch(1);
+    paint_time_metrics.maybe_observe_paint_time(&DummyProfilerMetadataFactory {}, epoch, false);
+    paint_time_metrics.maybe_set_metric(epoch, navigation_start + 1);
+    paint_time_metrics.maybe_set_metric(stale_epoch, navigation_start + 2);
+    assert_eq!(
+        paint_time_metrics.get_first_paint(),
+        Some(1),
+        "first paint is set"
+    );
+    assert_eq!(
+        paint_time_metrics.get_first_contentful_paint(),
+        None,
+        "first contentful paint is None"
+    );
+}
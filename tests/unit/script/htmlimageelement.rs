@@ -21,7 +21,28 @@ fn width_one_value() {
         descriptor: first_descriptor,
     };
     let sources = &[first_imagesource];
-    assert_eq!(parse_a_srcset_attribute("small-image.jpg, 320w"), sources);
+    assert_eq!(parse_a_srcset_attribute("small-image.jpg 320w"), sources);
+}
+
+//A comma right after the URL ends the candidate, whose descriptors are missing
+#[test]
+fn trailing_comma_value() {
+    let first_imagesource = ImageSource {
+        url: "small-image.jpg".to_string(),
+        descriptor: Descriptor {
+            wid: None,
+            den: None,
+        },
+    };
+    let second_imagesource = ImageSource {
+        url: "320w".to_string(),
+        descriptor: Descriptor {
+            wid: None,
+            den: None,
+        },
+    };
+    let sources = &[first_imagesource, second_imagesource];
+    assert_eq!(parse_a_srcset_attribute("small-image.jpg,, 320w"), sources);
 }
 
 #[test]
@@ -162,3 +183,44 @@ fn different_descriptor() {
         sources
     );
 }
+
+#[test]
+fn exponent_descriptor() {
+    let first_descriptor = Descriptor {
+        wid: None,
+        den: Some(1.0),
+    };
+    let first_imagesource = ImageSource {
+        url: "small-image.jpg".to_string(),
+        descriptor: first_descriptor,
+    };
+    let sources = &[first_imagesource];
+    assert_eq!(parse_a_srcset_attribute("small-image.jpg 1e0x"), sources);
+}
+
+#[test]
+fn non_ascii_value() {
+    let first_descriptor = Descriptor {
+        wid: None,
+        den: Some(2.0),
+    };
+    let first_imagesource = ImageSource {
+        url: "imágen-pequeña.jpg".to_string(),
+        descriptor: first_descriptor,
+    };
+    let sources = &[first_imagesource];
+    assert_eq!(
+        parse_a_srcset_attribute("\timágen-pequeña.jpg  2x, "),
+        sources
+    );
+}
+
+//Does not parse an ImageSource with a width of zero or an invalid descriptor
+#[test]
+fn invalid_descriptor() {
+    let empty_vec = Vec::new();
+    assert_eq!(
+        parse_a_srcset_attribute("small-image.jpg 0w, medium-image.jpg +1x, large-image.jpg 1y"),
+        empty_vec
+    );
+}
@@ -0,0 +1,45 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::htmlmediaelement::nearest_position;
+use script::test::timeranges::TimeRangesContainer;
+
+fn ranges(ranges: &[(f64, f64)]) -> TimeRangesContainer {
+    let mut container = TimeRangesContainer::new();
+    for &(start, end) in ranges {
+        container.add(start, end).unwrap();
+    }
+    container
+}
+
+#[test]
+fn seek_without_seekable_ranges() {
+    assert_eq!(nearest_position(&ranges(&[]), 5., 0.), None);
+}
+
+#[test]
+fn seek_inside_a_range() {
+    let seekable = ranges(&[(0., 10.)]);
+    assert_eq!(nearest_position(&seekable, 0., 5.), Some(0.));
+    assert_eq!(nearest_position(&seekable, 4.5, 0.), Some(4.5));
+    assert_eq!(nearest_position(&seekable, 10., 0.), Some(10.));
+}
+
+#[test]
+fn seek_outside_of_the_ranges() {
+    let seekable = ranges(&[(2., 4.), (8., 10.)]);
+    assert_eq!(nearest_position(&seekable, 0., 9.), Some(2.));
+    assert_eq!(nearest_position(&seekable, 12., 0.), Some(10.));
+    assert_eq!(nearest_position(&seekable, 5., 0.), Some(4.));
+    assert_eq!(nearest_position(&seekable, 7.5, 0.), Some(8.));
+}
+
+#[test]
+fn seek_between_two_ranges_as_near() {
+    // Both ends are one second away, so the one nearest to the current
+    // playback position wins.
+    let seekable = ranges(&[(2., 4.), (6., 10.)]);
+    assert_eq!(nearest_position(&seekable, 5., 1.), Some(4.));
+    assert_eq!(nearest_position(&seekable, 5., 9.), Some(6.));
+}
@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::import_map::ImportMap;
+use servo_url::ServoUrl;
+
+fn base_url() -> ServoUrl {
+    ServoUrl::parse("https://example.com/app/index.html").unwrap()
+}
+
+fn resolve(import_map: &ImportMap, specifier: &str, base: &str) -> Option<String> {
+    let base = ServoUrl::parse(base).unwrap();
+    import_map
+        .resolve_a_module_specifier(specifier, &base)
+        .ok()
+        .map(|url| url.into_string())
+}
+
+#[test]
+fn parse_errors() {
+    assert!(ImportMap::parse("{", &base_url()).is_err());
+    assert!(ImportMap::parse("[]", &base_url()).is_err());
+    assert!(ImportMap::parse(r#"{"imports": []}"#, &base_url()).is_err());
+    assert!(ImportMap::parse(r#"{"scopes": {"/": 1}}"#, &base_url()).is_err());
+    assert!(ImportMap::parse(r#"{"integrity": "sha384-abc"}"#, &base_url()).is_err());
+    assert!(ImportMap::parse(r#"{"unknown": 1}"#, &base_url()).is_ok());
+}
+
+#[test]
+fn bare_specifiers() {
+    let import_map = ImportMap::parse(
+        r#"{"imports": {
+            "moment": "/node_modules/moment/src/moment.js",
+            "lodash/": "./node_modules/lodash-es/",
+            "blocked": null,
+            "invalid/": "/not-a-package"
+        }}"#,
+        &base_url(),
+    )
+    .unwrap();
+    let from = "https://example.com/app/index.html";
+
+    assert_eq!(
+        resolve(&import_map, "moment", from),
+        Some("https://example.com/node_modules/moment/src/moment.js".to_owned())
+    );
+    assert_eq!(
+        resolve(&import_map, "lodash/map.js", from),
+        Some("https://example.com/app/node_modules/lodash-es/map.js".to_owned())
+    );
+    assert_eq!(resolve(&import_map, "blocked", from), None);
+    assert_eq!(resolve(&import_map, "invalid/foo.js", from), None);
+    assert_eq!(resolve(&import_map, "unmapped", from), None);
+    assert_eq!(
+        resolve(&import_map, "./local.js", from),
+        Some("https://example.com/app/local.js".to_owned())
+    );
+}
+
+#[test]
+fn url_like_specifiers_can_be_remapped() {
+    let import_map = ImportMap::parse(
+        r#"{"imports": {"https://cdn.example.com/vue.js": "/vendor/vue.js"}}"#,
+        &base_url(),
+    )
+    .unwrap();
+    assert_eq!(
+        resolve(
+            &import_map,
+            "https://cdn.example.com/vue.js",
+            "https://example.com/app/index.html"
+        ),
+        Some("https://example.com/vendor/vue.js".to_owned())
+    );
+}
+
+#[test]
+fn longest_prefix_wins() {
+    let import_map = ImportMap::parse(
+        r#"{"imports": {
+            "a/": "/short/",
+            "a/b/": "/long/"
+        }}"#,
+        &base_url(),
+    )
+    .unwrap();
+    let from = "https://example.com/app/index.html";
+    assert_eq!(
+        resolve(&import_map, "a/b/c.js", from),
+        Some("https://example.com/long/c.js".to_owned())
+    );
+    assert_eq!(
+        resolve(&import_map, "a/c.js", from),
+        Some("https://example.com/short/c.js".to_owned())
+    );
+}
+
+#[test]
+fn backtracking_is_blocked() {
+    let import_map = ImportMap::parse(r#"{"imports": {"pkg/": "/pkg/"}}"#, &base_url()).unwrap();
+    assert_eq!(
+        resolve(
+            &import_map,
+            "pkg/../secret.js",
+            "https://example.com/app/index.html"
+        ),
+        None
+    );
+}
+
+#[test]
+fn scopes() {
+    let import_map = ImportMap::parse(
+        r#"{
+            "imports": {"dep": "/dep-v1.js"},
+            "scopes": {
+                "/legacy/": {"dep": "/dep-v0.js"},
+                "/exact.js": {"dep": "/dep-exact.js"}
+            }
+        }"#,
+        &base_url(),
+    )
+    .unwrap();
+    assert_eq!(
+        resolve(&import_map, "dep", "https://example.com/app/main.js"),
+        Some("https://example.com/dep-v1.js".to_owned())
+    );
+    assert_eq!(
+        resolve(&import_map, "dep", "https://example.com/legacy/main.js"),
+        Some("https://example.com/dep-v0.js".to_owned())
+    );
+    assert_eq!(
+        resolve(&import_map, "dep", "https://example.com/exact.js"),
+        Some("https://example.com/dep-exact.js".to_owned())
+    );
+}
+
+#[test]
+fn integrity() {
+    let import_map = ImportMap::parse(
+        r#"{"integrity": {
+            "./module.js": "sha384-abc",
+            "/invalid.js": 42
+        }}"#,
+        &base_url(),
+    )
+    .unwrap();
+    assert_eq!(
+        import_map.resolve_a_module_integrity_metadata(
+            &ServoUrl::parse("https://example.com/app/module.js").unwrap()
+        ),
+        "sha384-abc"
+    );
+    assert_eq!(
+        import_map.resolve_a_module_integrity_metadata(
+            &ServoUrl::parse("https://example.com/invalid.js").unwrap()
+        ),
+        ""
+    );
+}
@@ -9,13 +9,25 @@ mod htmlareaelement;
 #[cfg(test)]
 mod htmlimageelement;
 #[cfg(test)]
+mod htmlmediaelement;
+#[cfg(test)]
+mod import_map;
+#[cfg(test)]
+mod midi;
+#[cfg(test)]
 mod origin;
+#[cfg(test)]
+mod scrollbar;
 #[cfg(all(test, target_pointer_width = "64"))]
 mod size_of;
 #[cfg(test)]
+mod speculation_rules;
+#[cfg(test)]
 mod textinput;
 #[cfg(test)]
 mod timeranges;
+#[cfg(test)]
+mod xpath;
 
 /**
 ```compile_fail,E0277
@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::midi::validate_midi_messages;
+
+#[test]
+fn accepts_complete_messages() {
+    assert_eq!(validate_midi_messages(&[]), Some(false));
+    // Note on, program change and timing clock.
+    assert_eq!(validate_midi_messages(&[0x90, 0x45, 0x7f]), Some(false));
+    assert_eq!(validate_midi_messages(&[0xc0, 0x01]), Some(false));
+    assert_eq!(validate_midi_messages(&[0xf8]), Some(false));
+    assert_eq!(
+        validate_midi_messages(&[0x90, 0x45, 0x7f, 0x80, 0x45, 0x00, 0xf8]),
+        Some(false)
+    );
+}
+
+#[test]
+fn reports_system_exclusive_messages() {
+    assert_eq!(validate_midi_messages(&[0xf0, 0x7e, 0x7f, 0xf7]), Some(true));
+    assert_eq!(validate_midi_messages(&[0xf0, 0xf7]), Some(true));
+    assert_eq!(
+        validate_midi_messages(&[0x90, 0x45, 0x7f, 0xf0, 0x01, 0xf7, 0xfe]),
+        Some(true)
+    );
+}
+
+#[test]
+fn rejects_incomplete_messages() {
+    assert_eq!(validate_midi_messages(&[0x90, 0x45]), None);
+    assert_eq!(validate_midi_messages(&[0xc0]), None);
+    // An unterminated system exclusive message.
+    assert_eq!(validate_midi_messages(&[0xf0, 0x01, 0x02]), None);
+}
+
+#[test]
+fn rejects_running_status_and_stray_bytes() {
+    // Running status omits the status byte of the second note on.
+    assert_eq!(validate_midi_messages(&[0x90, 0x45, 0x7f, 0x46, 0x7f]), None);
+    // A data byte above 0x7f, and undefined or stray status bytes.
+    assert_eq!(validate_midi_messages(&[0x90, 0x80, 0x7f]), None);
+    assert_eq!(validate_midi_messages(&[0xf4]), None);
+    assert_eq!(validate_midi_messages(&[0xf7]), None);
+    // A status byte inside a system exclusive message.
+    assert_eq!(validate_midi_messages(&[0xf0, 0x90, 0xf7]), None);
+}
@@ -0,0 +1,120 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use euclid::default::{Point2D, Rect, Size2D};
+use script_layout_interface::scrollbar::{ScrollContainer, ScrollbarAxis, ScrollbarGeometry};
+use script_layout_interface::scrollbar::{ScrollbarMode, ScrollbarTheme};
+use script_layout_interface::scrollbar::{
+    OVERLAY_SCROLLBAR_FADE_DURATION, OVERLAY_SCROLLBAR_VISIBLE_DURATION,
+};
+use style::computed_values::overflow_x::T as Overflow;
+use style::computed_values::scrollbar_width::T as ScrollbarWidth;
+
+fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect<Au> {
+    Rect::new(
+        Point2D::new(Au::from_px(x), Au::from_px(y)),
+        Size2D::new(Au::from_px(width), Au::from_px(height)),
+    )
+}
+
+/// A 100x100 scroll container showing a fifth of 500px tall content.
+fn container(scroll_top: i32) -> ScrollContainer {
+    ScrollContainer {
+        padding_box: rect(0, 0, 100, 100),
+        max_scroll: Size2D::new(Au(0), Au::from_px(400)),
+        scroll_position: Point2D::new(Au(0), Au::from_px(scroll_top)),
+        overflow_x: Overflow::Auto,
+        overflow_y: Overflow::Auto,
+        thickness: Au::from_px(10),
+        resizable: false,
+    }
+}
+
+#[test]
+fn auto_overflow_only_shows_scrollbars_that_scroll() {
+    let theme = ScrollbarTheme::new(ScrollbarMode::Classic);
+    let geometry = ScrollbarGeometry::new(&theme, &container(0));
+    assert!(geometry.horizontal.is_none());
+    assert!(geometry.corner.is_none());
+    let vertical = geometry.vertical.unwrap();
+    assert_eq!(vertical.axis, ScrollbarAxis::Vertical);
+    assert_eq!(vertical.track, rect(90, 0, 10, 100));
+    assert_eq!(vertical.thumb, rect(90, 0, 10, 20));
+}
+
+#[test]
+fn thumb_follows_scroll_position() {
+    let theme = ScrollbarTheme::new(ScrollbarMode::Classic);
+    let geometry = ScrollbarGeometry::new(&theme, &container(400));
+    let vertical = geometry.vertical.unwrap();
+    assert_eq!(vertical.thumb, rect(90, 80, 10, 20));
+    // The thumb moves 80px over 400px of content.
+    assert_eq!(vertical.thumb_travel(), Au::from_px(80));
+    assert_eq!(vertical.scroll_per_thumb_offset(), 5.);
+}
+
+#[test]
+fn clicks_on_the_track_page_towards_the_pointer() {
+    let theme = ScrollbarTheme::new(ScrollbarMode::Classic);
+    let geometry = ScrollbarGeometry::new(&theme, &container(200));
+    let point = |y| Point2D::new(Au::from_px(95), Au::from_px(y));
+    let vertical = geometry.scrollbar_at(point(10)).unwrap();
+    assert_eq!(vertical.page_direction(point(10)), -1);
+    assert_eq!(vertical.page_direction(point(50)), 0);
+    assert_eq!(vertical.page_direction(point(90)), 1);
+    assert!(geometry
+        .scrollbar_at(Point2D::new(Au::from_px(50), Au::from_px(50)))
+        .is_none());
+}
+
+#[test]
+fn resizable_containers_reserve_the_corner() {
+    let theme = ScrollbarTheme::new(ScrollbarMode::Classic);
+    let mut container = container(0);
+    container.resizable = true;
+    let geometry = ScrollbarGeometry::new(&theme, &container);
+    assert_eq!(geometry.corner, Some(rect(85, 85, 15, 15)));
+    assert_eq!(geometry.vertical.unwrap().track, rect(90, 0, 10, 85));
+}
+
+#[test]
+fn overlay_scrollbars_fade_out() {
+    let classic = ScrollbarTheme::new(ScrollbarMode::Classic);
+    assert_eq!(classic.opacity(None), 1.);
+
+    let overlay = ScrollbarTheme::new(ScrollbarMode::Overlay);
+    assert!(overlay.track_color.is_none());
+    assert_eq!(overlay.opacity(None), 0.);
+    assert_eq!(overlay.opacity(Some(0.)), 1.);
+    let halfway = OVERLAY_SCROLLBAR_VISIBLE_DURATION + OVERLAY_SCROLLBAR_FADE_DURATION / 2.;
+    assert!((overlay.opacity(Some(halfway)) - 0.5).abs() < 0.001);
+    let end = OVERLAY_SCROLLBAR_VISIBLE_DURATION + OVERLAY_SCROLLBAR_FADE_DURATION;
+    assert_eq!(overlay.opacity(Some(end)), 0.);
+}
+
+#[test]
+fn webkit_scrollbar_widths() {
+    let theme = ScrollbarTheme::new(ScrollbarMode::Classic);
+    assert_eq!(
+        theme.webkit_scrollbar_width(true, None),
+        ScrollbarWidth::Auto
+    );
+    assert_eq!(
+        theme.webkit_scrollbar_width(true, Some(Au::from_px(20))),
+        ScrollbarWidth::Auto
+    );
+    assert_eq!(
+        theme.webkit_scrollbar_width(true, Some(Au::from_px(4))),
+        ScrollbarWidth::Thin
+    );
+    assert_eq!(
+        theme.webkit_scrollbar_width(true, Some(Au(0))),
+        ScrollbarWidth::None
+    );
+    assert_eq!(
+        theme.webkit_scrollbar_width(false, None),
+        ScrollbarWidth::None
+    );
+}
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::speculation_rules::SpeculationRuleSet;
+use servo_url::ServoUrl;
+
+fn base_url() -> ServoUrl {
+    ServoUrl::parse("https://example.com/app/index.html").unwrap()
+}
+
+fn prefetched_urls(input: &str) -> Vec<String> {
+    SpeculationRuleSet::parse(input, &base_url())
+        .unwrap()
+        .prefetch_candidates()
+        .into_iter()
+        .map(|(url, _)| url.into_string())
+        .collect()
+}
+
+#[test]
+fn parse_errors() {
+    assert!(SpeculationRuleSet::parse("{", &base_url()).is_err());
+    assert!(SpeculationRuleSet::parse("[]", &base_url()).is_err());
+    assert!(SpeculationRuleSet::parse(r#"{"prefetch": {}}"#, &base_url()).is_err());
+    assert!(SpeculationRuleSet::parse(r#"{"prerender": "/next"}"#, &base_url()).is_err());
+    assert!(SpeculationRuleSet::parse(r#"{"unknown": 1}"#, &base_url()).is_ok());
+}
+
+#[test]
+fn list_rules() {
+    let urls = prefetched_urls(
+        r#"{
+            "prefetch": [{"source": "list", "urls": ["next.html", "/about", "data:,x"]}],
+            "prerender": [{"urls": ["/about", "https://example.com/contact"]}]
+        }"#,
+    );
+    assert_eq!(
+        urls,
+        vec![
+            "https://example.com/app/next.html",
+            "https://example.com/about",
+            "https://example.com/contact",
+        ]
+    );
+}
+
+#[test]
+fn invalid_rules_are_ignored() {
+    let urls = prefetched_urls(
+        r#"{"prefetch": [
+            "next.html",
+            {"source": "document", "where": {"href_matches": "/*"}},
+            {"source": "list", "urls": ["/a"], "unknown": true},
+            {"source": "list", "urls": ["/b"], "requires": ["unknown"]},
+            {"source": "list", "urls": ["/c"], "referrer_policy": "unknown"},
+            {"source": "list", "urls": [1]},
+            {"source": "list", "urls": ["/d"], "requires": ["anonymous-client-ip-when-cross-origin"]}
+        ]}"#,
+    );
+    assert_eq!(urls, vec!["https://example.com/d"]);
+}
+
+#[test]
+fn referrer_policy() {
+    let rule_set = SpeculationRuleSet::parse(
+        r#"{"prefetch": [
+            {"source": "list", "urls": ["/a"], "referrer_policy": "no-referrer"},
+            {"source": "list", "urls": ["/b"], "referrer_policy": ""},
+            {"source": "list", "urls": ["/c"]}
+        ]}"#,
+        &base_url(),
+    )
+    .unwrap();
+    let policies: Vec<bool> = rule_set
+        .prefetch_candidates()
+        .into_iter()
+        .map(|(_, policy)| policy.is_some())
+        .collect();
+    assert_eq!(policies, vec![true, false, false]);
+}
@@ -454,6 +454,30 @@ fn test_textinput_adjust_horizontal_by_word() {
     assert_eq!(textinput_3.edit_point().index, UTF8Bytes(6));
 }
 
+#[test]
+fn test_textinput_select_word_at_edit_point() {
+    let mut textinput = text_input(Lines::Single, "abc def, ghi");
+    textinput.set_edit_point_index(5);
+    textinput.select_word_at_edit_point();
+    assert_eq!(textinput.get_selection_text(), Some("def".to_owned()));
+
+    // Right after a word, the word is selected rather than the punctuation.
+    textinput.clear_selection();
+    textinput.set_edit_point_index(7);
+    textinput.select_word_at_edit_point();
+    assert_eq!(textinput.get_selection_text(), Some("def".to_owned()));
+
+    textinput.clear_selection();
+    textinput.set_edit_point_index(8);
+    textinput.select_word_at_edit_point();
+    assert_eq!(textinput.get_selection_text(), Some(" ".to_owned()));
+
+    textinput.clear_selection();
+    textinput.set_edit_point_index(12);
+    textinput.select_word_at_edit_point();
+    assert_eq!(textinput.get_selection_text(), Some("ghi".to_owned()));
+}
+
 #[test]
 fn test_textinput_adjust_horizontal_to_line_end() {
     // Test standard case of movement to end based on UAX#29 rules
@@ -0,0 +1,377 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script::test::xpath::{
+    compare, number_to_string, parse, string_to_number, tokenize, Axis, BinaryOperator, Error,
+    Expr, NodeTest, PathStart, QName, Step, Token, Value,
+};
+use std::f64;
+
+fn qname(prefix: Option<&str>, local: &str) -> QName {
+    QName {
+        prefix: prefix.map(str::to_owned),
+        local: local.to_owned(),
+    }
+}
+
+fn name(local: &str) -> NodeTest {
+    NodeTest::Name(qname(None, local))
+}
+
+fn step(axis: Axis, test: NodeTest, predicates: Vec<Expr>) -> Step {
+    Step {
+        axis,
+        test,
+        predicates,
+    }
+}
+
+fn child(local: &str) -> Expr {
+    Expr::Path(
+        PathStart::ContextNode,
+        vec![step(Axis::Child, name(local), vec![])],
+    )
+}
+
+fn number(value: f64) -> Box<Expr> {
+    Box::new(Expr::Number(value))
+}
+
+fn is_invalid_expression<T>(result: Result<T, Error>) -> bool {
+    match result {
+        Err(Error::InvalidExpression(_)) => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn tokenize_star_and_operator_names_by_preceding_token() {
+    assert_eq!(tokenize("*"), Ok(vec![Token::NameTest(NodeTest::Wildcard)]));
+    assert_eq!(
+        tokenize("2 * 3"),
+        Ok(vec![
+            Token::Number(2.),
+            Token::Operator(BinaryOperator::Multiply),
+            Token::Number(3.),
+        ])
+    );
+    assert_eq!(
+        tokenize("div div div"),
+        Ok(vec![
+            Token::NameTest(name("div")),
+            Token::Operator(BinaryOperator::Divide),
+            Token::NameTest(name("div")),
+        ])
+    );
+    assert_eq!(
+        tokenize("a-b - c"),
+        Ok(vec![
+            Token::NameTest(name("a-b")),
+            Token::Operator(BinaryOperator::Subtract),
+            Token::NameTest(name("c")),
+        ])
+    );
+    assert!(is_invalid_expression(tokenize("a b")));
+}
+
+#[test]
+fn tokenize_names() {
+    assert_eq!(
+        tokenize("child::x:y"),
+        Ok(vec![
+            Token::AxisName(Axis::Child),
+            Token::DoubleColon,
+            Token::NameTest(NodeTest::Name(qname(Some("x"), "y"))),
+        ])
+    );
+    assert_eq!(
+        tokenize("svg:*"),
+        Ok(vec![Token::NameTest(NodeTest::NamespaceWildcard(
+            "svg".to_owned()
+        ))])
+    );
+    assert_eq!(
+        tokenize("text()"),
+        Ok(vec![
+            Token::NodeType("text".to_owned()),
+            Token::LeftParen,
+            Token::RightParen,
+        ])
+    );
+    assert_eq!(
+        tokenize("count (p)"),
+        Ok(vec![
+            Token::FunctionName(qname(None, "count")),
+            Token::LeftParen,
+            Token::NameTest(name("p")),
+            Token::RightParen,
+        ])
+    );
+    assert_eq!(
+        tokenize("$x:y"),
+        Ok(vec![Token::Variable(qname(Some("x"), "y"))])
+    );
+    assert!(is_invalid_expression(tokenize("sideways::p")));
+}
+
+#[test]
+fn tokenize_numbers_literals_and_punctuation() {
+    assert_eq!(
+        tokenize(".5 + 1."),
+        Ok(vec![
+            Token::Number(0.5),
+            Token::Operator(BinaryOperator::Add),
+            Token::Number(1.),
+        ])
+    );
+    assert_eq!(
+        tokenize("'a\"b' != \"c'd\""),
+        Ok(vec![
+            Token::Literal("a\"b".to_owned()),
+            Token::Operator(BinaryOperator::NotEqual),
+            Token::Literal("c'd".to_owned()),
+        ])
+    );
+    assert_eq!(
+        tokenize("..//@id"),
+        Ok(vec![
+            Token::DotDot,
+            Token::DoubleSlash,
+            Token::At,
+            Token::NameTest(name("id")),
+        ])
+    );
+    assert_eq!(
+        tokenize("1<=2>3"),
+        Ok(vec![
+            Token::Number(1.),
+            Token::Operator(BinaryOperator::LessOrEqual),
+            Token::Number(2.),
+            Token::Operator(BinaryOperator::Greater),
+            Token::Number(3.),
+        ])
+    );
+    assert!(is_invalid_expression(tokenize("'abc")));
+    assert!(is_invalid_expression(tokenize("#")));
+}
+
+#[test]
+fn parse_operator_precedence_and_associativity() {
+    assert_eq!(
+        parse("1 + 2 * 3"),
+        Ok(Expr::Binary(
+            number(1.),
+            BinaryOperator::Add,
+            Box::new(Expr::Binary(
+                number(2.),
+                BinaryOperator::Multiply,
+                number(3.)
+            )),
+        ))
+    );
+    assert_eq!(
+        parse("1 - 2 - 3"),
+        Ok(Expr::Binary(
+            Box::new(Expr::Binary(
+                number(1.),
+                BinaryOperator::Subtract,
+                number(2.)
+            )),
+            BinaryOperator::Subtract,
+            number(3.),
+        ))
+    );
+    assert_eq!(
+        parse("a or b and c"),
+        Ok(Expr::Binary(
+            Box::new(child("a")),
+            BinaryOperator::Or,
+            Box::new(Expr::Binary(
+                Box::new(child("b")),
+                BinaryOperator::And,
+                Box::new(child("c")),
+            )),
+        ))
+    );
+    assert_eq!(
+        parse("-a | b"),
+        Ok(Expr::Negate(Box::new(Expr::Binary(
+            Box::new(child("a")),
+            BinaryOperator::Union,
+            Box::new(child("b")),
+        ))))
+    );
+    assert_eq!(
+        parse("--1"),
+        Ok(Expr::Negate(Box::new(Expr::Negate(number(1.)))))
+    );
+}
+
+#[test]
+fn parse_location_paths() {
+    assert_eq!(parse("/"), Ok(Expr::Path(PathStart::Root, vec![])));
+    assert_eq!(
+        parse("//p[@class='a'][2]"),
+        Ok(Expr::Path(
+            PathStart::Root,
+            vec![
+                step(Axis::DescendantOrSelf, NodeTest::Node, vec![]),
+                step(
+                    Axis::Child,
+                    name("p"),
+                    vec![
+                        Expr::Binary(
+                            Box::new(Expr::Path(
+                                PathStart::ContextNode,
+                                vec![step(Axis::Attribute, name("class"), vec![])],
+                            )),
+                            BinaryOperator::Equal,
+                            Box::new(Expr::Literal("a".to_owned())),
+                        ),
+                        Expr::Number(2.),
+                    ],
+                ),
+            ],
+        ))
+    );
+    assert_eq!(
+        parse("../preceding-sibling::processing-instruction('x')"),
+        Ok(Expr::Path(
+            PathStart::ContextNode,
+            vec![
+                step(Axis::Parent, NodeTest::Node, vec![]),
+                step(
+                    Axis::PrecedingSibling,
+                    NodeTest::ProcessingInstruction(Some("x".to_owned())),
+                    vec![],
+                ),
+            ],
+        ))
+    );
+}
+
+#[test]
+fn parse_filter_expressions_and_function_calls() {
+    assert_eq!(
+        parse("$v[1]/a"),
+        Ok(Expr::Path(
+            PathStart::Filter(Box::new(Expr::Filter(
+                Box::new(Expr::Variable(qname(None, "v"))),
+                vec![Expr::Number(1.)],
+            ))),
+            vec![step(Axis::Child, name("a"), vec![])],
+        ))
+    );
+    assert_eq!(
+        parse("concat('a', 'b')"),
+        Ok(Expr::Function(
+            qname(None, "concat"),
+            vec![Expr::Literal("a".to_owned()), Expr::Literal("b".to_owned()),],
+        ))
+    );
+    assert_eq!(parse("f()"), Ok(Expr::Function(qname(None, "f"), vec![])));
+}
+
+#[test]
+fn parse_rejects_invalid_expressions() {
+    assert!(is_invalid_expression(parse("")));
+    assert!(is_invalid_expression(parse("p[")));
+    assert!(is_invalid_expression(parse("f(1,)")));
+    assert!(is_invalid_expression(parse("1 2")));
+    assert!(is_invalid_expression(parse("child::")));
+}
+
+#[test]
+fn reverse_axes() {
+    assert!(Axis::Ancestor.is_reverse());
+    assert!(Axis::AncestorOrSelf.is_reverse());
+    assert!(Axis::Preceding.is_reverse());
+    assert!(Axis::PrecedingSibling.is_reverse());
+    assert!(!Axis::Child.is_reverse());
+    assert!(!Axis::Following.is_reverse());
+    assert!(!Axis::FollowingSibling.is_reverse());
+}
+
+#[test]
+fn numeric_coercion() {
+    assert_eq!(string_to_number(" \n12.5\t"), 12.5);
+    assert_eq!(string_to_number("-.5"), -0.5);
+    assert!(string_to_number("+1").is_nan());
+    assert!(string_to_number("1e3").is_nan());
+    assert!(string_to_number("1.2.3").is_nan());
+    assert!(string_to_number("").is_nan());
+    assert_eq!(Value::Boolean(true).number(), 1.);
+    assert_eq!(Value::Boolean(false).number(), 0.);
+    assert_eq!(Value::String("42".to_owned()).number(), 42.);
+}
+
+#[test]
+fn string_coercion() {
+    assert_eq!(number_to_string(1.), "1");
+    assert_eq!(number_to_string(-0.), "0");
+    assert_eq!(number_to_string(0.5), "0.5");
+    assert_eq!(number_to_string(1e21), "1000000000000000000000");
+    assert_eq!(number_to_string(f64::NAN), "NaN");
+    assert_eq!(number_to_string(f64::NEG_INFINITY), "-Infinity");
+    assert_eq!(Value::Boolean(false).string(), "false");
+    assert_eq!(Value::Number(-2.25).string(), "-2.25");
+    assert_eq!(Value::NodeSet(vec![]).string(), "");
+}
+
+#[test]
+fn boolean_coercion() {
+    assert!(!Value::Number(f64::NAN).boolean());
+    assert!(!Value::Number(-0.).boolean());
+    assert!(Value::Number(0.1).boolean());
+    assert!(Value::String("0".to_owned()).boolean());
+    assert!(!Value::String(String::new()).boolean());
+    assert!(!Value::NodeSet(vec![]).boolean());
+}
+
+#[test]
+fn comparisons() {
+    let string = |s: &str| Value::String(s.to_owned());
+    assert!(compare(
+        &Value::Number(1.),
+        BinaryOperator::Equal,
+        &string("1.0")
+    ));
+    assert!(compare(
+        &Value::Boolean(true),
+        BinaryOperator::Equal,
+        &string("false")
+    ));
+    assert!(!compare(
+        &string("abc"),
+        BinaryOperator::Equal,
+        &string("abc ")
+    ));
+    assert!(compare(&string("2"), BinaryOperator::Less, &string("10")));
+    assert!(compare(
+        &Value::Number(f64::NAN),
+        BinaryOperator::NotEqual,
+        &Value::Number(f64::NAN)
+    ));
+    assert!(!compare(
+        &Value::Number(f64::NAN),
+        BinaryOperator::GreaterOrEqual,
+        &Value::Number(f64::NAN)
+    ));
+    // An empty node-set equals false, but no number.
+    assert!(compare(
+        &Value::NodeSet(vec![]),
+        BinaryOperator::Equal,
+        &Value::Boolean(false)
+    ));
+    assert!(!compare(
+        &Value::NodeSet(vec![]),
+        BinaryOperator::Equal,
+        &Value::Number(0.)
+    ));
+    assert!(!compare(
+        &Value::NodeSet(vec![]),
+        BinaryOperator::NotEqual,
+        &Value::Number(0.)
+    ));
+}
@@ -3,8 +3,16 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use servo_atoms::Atom;
 use style::attr::{parse_length, AttrValue, LengthOrPercentageOrAuto};
 
+/// The heap memory used by `values`, as measured by the allocator.
+fn heap_size<T: MallocSizeOf>(values: &[T]) -> usize {
+    let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+    values.iter().map(|value| value.size_of(&mut ops)).sum()
+}
+
 #[test]
 fn test_parse_double() {
     let value = String::from("432.5e2");
@@ -57,6 +65,49 @@ fn test_from_limited_i32_should_keep_parsed_value_when_not_an_int() {
     }
 }
 
+#[test]
+fn test_from_string_or_atom_shares_short_values() {
+    let short: Vec<_> = (0..100)
+        .map(|_| AttrValue::from_string_or_atom(String::from("selected")))
+        .collect();
+    assert!(short.iter().all(|value| &**value == "selected"));
+    assert_eq!(heap_size(&short), 0);
+
+    let long_value = "a value that isn't repeated";
+    let long: Vec<_> = (0..100)
+        .map(|_| AttrValue::from_string_or_atom(String::from(long_value)))
+        .collect();
+    assert!(long.iter().all(|value| &**value == long_value));
+    assert!(heap_size(&long) >= 100 * long_value.len());
+}
+
+#[test]
+fn test_short_tokenlists_share_their_serialization() {
+    let tokens = || vec![Atom::from("row"), Atom::from("odd")];
+    let lists: Vec<_> = (0..100)
+        .map(|_| AttrValue::from_atomic_tokens(tokens()))
+        .collect();
+    assert!(lists.iter().all(|list| &**list == "row odd"));
+    // Only the lists of tokens take heap memory.
+    let token_vecs: Vec<_> = (0..100).map(|_| tokens()).collect();
+    assert_eq!(heap_size(&lists), heap_size(&token_vecs));
+
+    let first = AttrValue::from_serialized_tokenlist(String::from("row odd"));
+    let second = AttrValue::from_serialized_tokenlist(String::from("row odd"));
+    assert_eq!(first.as_ptr(), second.as_ptr());
+}
+
+#[test]
+fn test_long_tokenlists_own_their_serialization() {
+    let tokens = || vec![Atom::from("row"), Atom::from("odd"), Atom::from("selected")];
+    let lists: Vec<_> = (0..100)
+        .map(|_| AttrValue::from_atomic_tokens(tokens()))
+        .collect();
+    assert!(lists.iter().all(|list| &**list == "row odd selected"));
+    let token_vecs: Vec<_> = (0..100).map(|_| tokens()).collect();
+    assert!(heap_size(&lists) >= heap_size(&token_vecs) + 100 * "row odd selected".len());
+}
+
 #[test]
 pub fn test_parse_length() {
     fn check(input: &str, expected: LengthOrPercentageOrAuto) {
@@ -10,9 +10,11 @@ extern crate cssparser;
 extern crate euclid;
 #[macro_use]
 extern crate html5ever;
+extern crate malloc_size_of;
 extern crate rayon;
 extern crate selectors;
 extern crate serde_json;
+extern crate servo_allocator;
 extern crate servo_arc;
 extern crate servo_atoms;
 extern crate servo_config;
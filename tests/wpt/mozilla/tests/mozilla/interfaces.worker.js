@@ -46,10 +46,12 @@ test_interfaces([
   "PerformanceObserverEntryList",
   "PerformancePaintTiming",
   "PerformanceResourceTiming",
+  "PerformanceServerTiming",
   "ProgressEvent",
   "PromiseRejectionEvent",
   "Request",
   "Response",
+  "StorageManager",
   "TextDecoder",
   "TextEncoder",
   "URL",
@@ -0,0 +1,2 @@
+import "./preloaded_module_dependency.js";
+window.preloadedEvaluations.push("preloaded_module");
@@ -0,0 +1 @@
+window.preloadedEvaluations.push("preloaded_module_dependency");
@@ -0,0 +1,10 @@
+# This Source Code Form is subject to the terms of the Mozilla Public
+# License, v. 2.0. If a copy of the MPL was not distributed with this
+# file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+
+def main(request, response):
+    headers = [('Content-Type', 'text/plain')]
+    for value in request.GET.get_list('metrics'):
+        headers += [('Server-Timing', value)]
+    return 200, headers, 'ok'
@@ -0,0 +1,2 @@
+log.push("throwing module");
+throw new Error("throwing module failed");
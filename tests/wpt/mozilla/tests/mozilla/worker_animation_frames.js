@@ -0,0 +1,19 @@
+var canvas = new OffscreenCanvas(10, 10);
+var context = canvas.getContext("2d");
+var times = [];
+var cancelled = requestAnimationFrame(function() {
+  postMessage({ error: "cancelled callback ran" });
+});
+cancelAnimationFrame(cancelled);
+
+function frame(time) {
+  times.push(time);
+  context.fillStyle = times.length % 2 ? "red" : "blue";
+  context.fillRect(0, 0, 10, 10);
+  if (times.length < 5) {
+    requestAnimationFrame(frame);
+    return;
+  }
+  postMessage({ times: times });
+}
+requestAnimationFrame(frame);